@@ -0,0 +1,301 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Functions that run subcommands in-process and return their results,
+//! instead of reporting failures with an exit code, and the builder of the
+//! state that runs the programs of a project with the module resolution of
+//! the CLI.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::PermissionsContainer;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::args::CheckFlags;
+use crate::args::CliOptions;
+use crate::args::DenoSubcommand;
+use crate::args::Flags;
+use crate::args::TestFlags;
+use crate::module_loader::ModuleLoadPreparer;
+use crate::proc_state::ProcState;
+use crate::tools::check::unused::find_unused_exports;
+use crate::tools::test::format_test_error;
+use crate::tsc::Diagnostics;
+use crate::worker::create_main_worker;
+use crate::worker::CliMainWorker;
+
+pub use crate::tools::check::unused::UnusedExport;
+
+/// Builds the state of a project like the subcommands do, so that its
+/// programs run in-process with the full module resolution of the CLI
+/// (import maps, npm packages and the emit cache).
+///
+/// ```ignore
+/// let project = ProjectBuilder::from_flags(flags)?.build().await?;
+/// let exit_code = project
+///   .run(main_module, PermissionsContainer::allow_all())
+///   .await?;
+/// ```
+pub struct ProjectBuilder {
+  options: Arc<CliOptions>,
+  maybe_file_watcher_sender: Option<UnboundedSender<Vec<PathBuf>>>,
+}
+
+impl ProjectBuilder {
+  /// Resolves the options of the project from the flags, which discovers
+  /// its configuration file, import map and lockfile.
+  pub fn from_flags(flags: Flags) -> Result<Self, AnyError> {
+    let options = CliOptions::from_flags(flags)?;
+    Ok(Self::from_cli_options(Arc::new(options)))
+  }
+
+  pub(crate) fn from_cli_options(options: Arc<CliOptions>) -> Self {
+    Self {
+      options,
+      maybe_file_watcher_sender: None,
+    }
+  }
+
+  /// Sends the paths of the files the project loads, so that a file watcher
+  /// restarts when they change.
+  pub fn file_watcher_sender(
+    mut self,
+    sender: UnboundedSender<Vec<PathBuf>>,
+  ) -> Self {
+    self.maybe_file_watcher_sender = Some(sender);
+    self
+  }
+
+  /// Creates the caches, the npm resolution and the module graph of the
+  /// project.
+  pub async fn build(self) -> Result<Project, AnyError> {
+    let has_file_watcher = self.maybe_file_watcher_sender.is_some();
+    let ps = ProcState::build_with_sender(
+      self.options,
+      self.maybe_file_watcher_sender,
+    )
+    .await?;
+    if has_file_watcher {
+      ps.init_watcher();
+    }
+    Ok(Project(ps))
+  }
+}
+
+/// The state of a project built by a [`ProjectBuilder`], which is shared by
+/// the workers that run its programs.
+#[derive(Clone)]
+pub struct Project(ProcState);
+
+impl Project {
+  pub(crate) fn options(&self) -> &Arc<CliOptions> {
+    &self.0.options
+  }
+
+  /// Builds and type checks the module graph of the modules before they are
+  /// loaded, which is also done for the modules the programs import.
+  pub(crate) fn module_load_preparer(&self) -> &Arc<ModuleLoadPreparer> {
+    &self.0.module_load_preparer
+  }
+
+  /// Creates a main worker for the module, whose module loader resolves,
+  /// transpiles and caches the modules it loads like `deno run`.
+  pub(crate) async fn create_main_worker(
+    &self,
+    main_module: ModuleSpecifier,
+    permissions: PermissionsContainer,
+  ) -> Result<CliMainWorker, AnyError> {
+    create_main_worker(&self.0, main_module, permissions).await
+  }
+
+  /// Runs the module until its event loop is done and returns its exit code.
+  pub async fn run(
+    &self,
+    main_module: ModuleSpecifier,
+    permissions: PermissionsContainer,
+  ) -> Result<i32, AnyError> {
+    let mut worker = self.create_main_worker(main_module, permissions).await?;
+    worker.run().await
+  }
+
+  pub(crate) fn proc_state(&self) -> &ProcState {
+    &self.0
+  }
+
+  pub(crate) fn into_proc_state(self) -> ProcState {
+    self.0
+  }
+}
+
+/// The outcome of a test run, see [`run_test`].
+#[derive(Debug, Clone, Default)]
+pub struct TestReport {
+  pub passed: usize,
+  pub failed: usize,
+  pub ignored: usize,
+  pub passed_steps: usize,
+  pub failed_steps: usize,
+  pub ignored_steps: usize,
+  pub filtered_out: usize,
+  pub measured: usize,
+  pub failures: Vec<TestFailureReport>,
+  /// The errors thrown outside of the tests, with the module they were
+  /// thrown in.
+  pub uncaught_errors: Vec<(String, String)>,
+  /// If a test or test step used the "only" option.
+  pub used_only: bool,
+}
+
+/// A failed test or test step.
+#[derive(Debug, Clone)]
+pub struct TestFailureReport {
+  pub name: String,
+  /// The module of the test.
+  pub origin: String,
+  /// The failure, formatted like `deno test` prints it.
+  pub message: String,
+}
+
+impl TestReport {
+  pub fn into_result(self) -> Result<(), AnyError> {
+    if self.used_only {
+      return Err(generic_error(
+        "Test failed because the \"only\" option was used",
+      ));
+    }
+
+    if self.failed > 0 {
+      return Err(generic_error("Test failed"));
+    }
+
+    Ok(())
+  }
+}
+
+impl From<crate::tools::test::TestReport> for TestReport {
+  fn from(report: crate::tools::test::TestReport) -> Self {
+    let summary = report.summary;
+    Self {
+      passed: summary.passed,
+      failed: summary.failed,
+      ignored: summary.ignored,
+      passed_steps: summary.passed_steps,
+      failed_steps: summary.failed_steps,
+      ignored_steps: summary.ignored_steps,
+      filtered_out: summary.filtered_out,
+      measured: summary.measured,
+      failures: summary
+        .failures
+        .into_iter()
+        .map(|(description, failure)| TestFailureReport {
+          name: description.name,
+          origin: description.origin,
+          message: failure.to_string(),
+        })
+        .collect(),
+      uncaught_errors: summary
+        .uncaught_errors
+        .into_iter()
+        .map(|(origin, error)| (origin, format_test_error(&error)))
+        .collect(),
+      used_only: report.used_only,
+    }
+  }
+}
+
+/// The outcome of type checking modules, see [`run_check`].
+#[derive(Debug, Default)]
+pub struct CheckReport {
+  diagnostics: Diagnostics,
+  /// The exports of the local modules that no module imports, which are only
+  /// found with `--unused`.
+  pub unused_exports: Vec<UnusedExport>,
+}
+
+impl CheckReport {
+  /// If no type errors were found.
+  pub fn passed(&self) -> bool {
+    self.diagnostics.is_empty()
+  }
+
+  /// The type errors, formatted like `deno check` prints them.
+  pub fn type_errors(&self) -> String {
+    self.diagnostics.to_string()
+  }
+
+  pub fn into_result(self) -> Result<(), AnyError> {
+    if self.diagnostics.is_empty() {
+      Ok(())
+    } else {
+      Err(self.diagnostics.into())
+    }
+  }
+}
+
+/// Runs the tests like `deno test`, returning the summary of the run even
+/// when tests failed. The flags are the ones of a `deno test` command, e.g.
+/// from [`flags_from_vec`](crate::flags_from_vec).
+pub async fn run_test(flags: Flags) -> Result<TestReport, AnyError> {
+  let test_flags = match &flags.subcommand {
+    DenoSubcommand::Test(test_flags) => test_flags.clone(),
+    _ => {
+      return Err(generic_error(
+        "Expected the flags of a `deno test` command.",
+      ))
+    }
+  };
+  let report = test(flags, test_flags).await?;
+  Ok(report.into())
+}
+
+/// Type checks the files like `deno check`, returning the type errors
+/// instead of failing. The flags are the ones of a `deno check` command,
+/// e.g. from [`flags_from_vec`](crate::flags_from_vec).
+pub async fn run_check(flags: Flags) -> Result<CheckReport, AnyError> {
+  let check_flags = match &flags.subcommand {
+    DenoSubcommand::Check(check_flags) => check_flags.clone(),
+    _ => {
+      return Err(generic_error(
+        "Expected the flags of a `deno check` command.",
+      ))
+    }
+  };
+  check(flags, check_flags).await
+}
+
+pub(crate) async fn test(
+  flags: Flags,
+  test_flags: TestFlags,
+) -> Result<crate::tools::test::TestReport, AnyError> {
+  let cli_options = CliOptions::from_flags(flags)?;
+  let test_options = cli_options.resolve_test_options(test_flags)?;
+  crate::tools::test::run_tests(cli_options, test_options).await
+}
+
+pub(crate) async fn check(
+  flags: Flags,
+  check_flags: CheckFlags,
+) -> Result<CheckReport, AnyError> {
+  let ps = ProcState::from_flags(flags).await?;
+  let result = ps
+    .module_load_preparer
+    .load_and_type_check_files(&check_flags.files)
+    .await;
+  let diagnostics = match result {
+    Ok(()) => Diagnostics::default(),
+    Err(err) => err.downcast::<Diagnostics>()?,
+  };
+  // the graph is kept even when the type check failed
+  let unused_exports = if check_flags.unused {
+    find_unused_exports(&ps.graph_container.graph(), &ps.parsed_source_cache)
+  } else {
+    Vec::new()
+  };
+  Ok(CheckReport {
+    diagnostics,
+    unused_exports,
+  })
+}
@@ -3,6 +3,7 @@
 use crate::args::ConfigFlag;
 use crate::args::Flags;
 use crate::util::fs::canonicalize_path;
+use crate::util::glob::GlobPattern;
 use crate::util::path::specifier_parent;
 use crate::util::path::specifier_to_file_path;
 
@@ -17,7 +18,11 @@ use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::serde_json::Value;
 use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::ChildPermissionsArg;
+use deno_runtime::permissions::PermissionDenyRules;
+use deno_semver::Version;
 use indexmap::IndexMap;
+use regex::Regex;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -177,7 +182,7 @@ pub fn json_merge(a: &mut Value, b: &Value) {
   }
 }
 
-fn parse_compiler_options(
+pub fn parse_compiler_options(
   compiler_options: &HashMap<String, Value>,
   maybe_specifier: Option<ModuleSpecifier>,
 ) -> Result<(Value, Option<IgnoredCompilerOptions>), AnyError> {
@@ -279,6 +284,19 @@ impl Serialize for TsConfig {
   }
 }
 
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DocLintConfig {
+  /// Documentation lint rules that should not be reported.
+  pub exclude: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DocConfig {
+  pub lint: DocLintConfig,
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct LintRulesConfig {
@@ -346,12 +364,96 @@ impl FilesConfig {
   }
 }
 
+/// The severity of the diagnostics of a lint rule.
+#[derive(
+  Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum LintSeverity {
+  Off,
+  Warn,
+  Error,
+}
+
+/// The lowest severity of the diagnostics that make `deno lint` fail.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LintFailOn {
+  #[default]
+  Error,
+  Warn,
+  Never,
+}
+
+impl LintFailOn {
+  pub fn fails(&self, severity: LintSeverity) -> bool {
+    match self {
+      LintFailOn::Error => severity == LintSeverity::Error,
+      LintFailOn::Warn => severity != LintSeverity::Off,
+      LintFailOn::Never => false,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedLintOverride {
+  pub files: Vec<String>,
+  pub severity: BTreeMap<String, LintSeverity>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LintOverride {
+  /// The files and directories that the severities apply to.
+  pub files: Vec<PathBuf>,
+  pub severity: BTreeMap<String, LintSeverity>,
+}
+
+/// The severities of lint rules, which can differ between paths.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct LintSeverityConfig {
+  pub rules: BTreeMap<String, LintSeverity>,
+  /// The severities of paths, where later overrides take precedence.
+  pub overrides: Vec<LintOverride>,
+}
+
+impl LintSeverityConfig {
+  /// Gets the configured severity of a rule in a file, which is `None` when
+  /// the rule set decides whether it is reported.
+  pub fn resolve(&self, file_path: &Path, code: &str) -> Option<LintSeverity> {
+    self
+      .overrides
+      .iter()
+      .rev()
+      .filter(|o| o.files.iter().any(|path| file_path.starts_with(path)))
+      .find_map(|o| o.severity.get(code).copied())
+      .or_else(|| self.rules.get(code).copied())
+  }
+
+  /// The codes of the rules that are reported in some files because of
+  /// their severity.
+  pub fn enabled_codes(&self) -> HashSet<&str> {
+    self
+      .rules
+      .iter()
+      .chain(self.overrides.iter().flat_map(|o| o.severity.iter()))
+      .filter(|(_, severity)| **severity != LintSeverity::Off)
+      .map(|(code, _)| code.as_str())
+      .collect()
+  }
+}
+
 #[derive(Clone, Debug, Default, Deserialize)]
 #[serde(default, deny_unknown_fields)]
 struct SerializedLintConfig {
   pub rules: LintRulesConfig,
   pub files: SerializedFilesConfig,
   pub report: Option<String>,
+  pub plugins: Vec<String>,
+  pub severity: BTreeMap<String, LintSeverity>,
+  pub overrides: Vec<SerializedLintOverride>,
+  #[serde(rename = "failOn")]
+  pub fail_on: LintFailOn,
 }
 
 impl SerializedLintConfig {
@@ -359,10 +461,40 @@ impl SerializedLintConfig {
     self,
     config_file_specifier: &ModuleSpecifier,
   ) -> Result<LintConfig, AnyError> {
+    let config_dir = specifier_parent(config_file_specifier);
+    let overrides = self
+      .overrides
+      .into_iter()
+      .map(|o| {
+        let files = o
+          .files
+          .iter()
+          .map(|p| specifier_to_file_path(&config_dir.join(p)?))
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(LintOverride {
+          files,
+          severity: o.severity,
+        })
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
     Ok(LintConfig {
       rules: self.rules,
       files: self.files.into_resolved(config_file_specifier)?,
       report: self.report,
+      plugins: self
+        .plugins
+        .into_iter()
+        .map(|p| {
+          config_dir
+            .join(&p)
+            .with_context(|| format!("Invalid lint plugin specifier \"{p}\"."))
+        })
+        .collect::<Result<Vec<_>, _>>()?,
+      severity: LintSeverityConfig {
+        rules: self.severity,
+        overrides,
+      },
+      fail_on: self.fail_on,
     })
   }
 }
@@ -372,9 +504,13 @@ pub struct LintConfig {
   pub rules: LintRulesConfig,
   pub files: FilesConfig,
   pub report: Option<String>,
+  /// Modules providing custom lint rules.
+  pub plugins: Vec<ModuleSpecifier>,
+  pub severity: LintSeverityConfig,
+  pub fail_on: LintFailOn,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub enum ProseWrap {
   Always,
@@ -382,7 +518,7 @@ pub enum ProseWrap {
   Preserve,
 }
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default, deny_unknown_fields, rename_all = "camelCase")]
 pub struct FmtOptionsConfig {
   pub use_tabs: Option<bool>,
@@ -391,6 +527,44 @@ pub struct FmtOptionsConfig {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<ProseWrap>,
   pub semi_colons: Option<bool>,
+  pub embedded_code: Option<bool>,
+  /// Remove unused imports and group and sort the imports of JavaScript and
+  /// TypeScript files.
+  pub organize_imports: Option<bool>,
+}
+
+impl FmtOptionsConfig {
+  /// Replaces the options that are set in `other`.
+  pub fn merge(&mut self, other: &FmtOptionsConfig) {
+    self.use_tabs = other.use_tabs.or(self.use_tabs);
+    self.line_width = other.line_width.or(self.line_width);
+    self.indent_width = other.indent_width.or(self.indent_width);
+    self.single_quote = other.single_quote.or(self.single_quote);
+    self.prose_wrap = other.prose_wrap.or(self.prose_wrap);
+    self.semi_colons = other.semi_colons.or(self.semi_colons);
+    self.embedded_code = other.embedded_code.or(self.embedded_code);
+    self.organize_imports = other.organize_imports.or(self.organize_imports);
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedFmtOverride {
+  pub files: Vec<String>,
+  pub options: FmtOptionsConfig,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FmtOverride {
+  /// The files and directories that the options apply to.
+  pub files: Vec<PathBuf>,
+  pub options: FmtOptionsConfig,
+}
+
+impl FmtOverride {
+  pub fn matches(&self, file_path: &Path) -> bool {
+    self.files.iter().any(|path| file_path.starts_with(path))
+  }
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -398,6 +572,7 @@ pub struct FmtOptionsConfig {
 struct SerializedFmtConfig {
   pub options: FmtOptionsConfig,
   pub files: SerializedFilesConfig,
+  pub overrides: Vec<SerializedFmtOverride>,
 }
 
 impl SerializedFmtConfig {
@@ -405,9 +580,26 @@ impl SerializedFmtConfig {
     self,
     config_file_specifier: &ModuleSpecifier,
   ) -> Result<FmtConfig, AnyError> {
+    let config_dir = specifier_parent(config_file_specifier);
+    let overrides = self
+      .overrides
+      .into_iter()
+      .map(|o| {
+        let files = o
+          .files
+          .iter()
+          .map(|p| specifier_to_file_path(&config_dir.join(p)?))
+          .collect::<Result<Vec<_>, _>>()?;
+        Ok(FmtOverride {
+          files,
+          options: o.options,
+        })
+      })
+      .collect::<Result<Vec<_>, AnyError>>()?;
     Ok(FmtConfig {
       options: self.options,
       files: self.files.into_resolved(config_file_specifier)?,
+      overrides,
     })
   }
 }
@@ -416,12 +608,16 @@ impl SerializedFmtConfig {
 pub struct FmtConfig {
   pub options: FmtOptionsConfig,
   pub files: FilesConfig,
+  /// The options of paths, where later overrides take precedence.
+  pub overrides: Vec<FmtOverride>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
-#[serde(default, deny_unknown_fields)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
 struct SerializedTestConfig {
   pub files: SerializedFilesConfig,
+  pub global_setup: Option<String>,
+  pub global_teardown: Option<String>,
 }
 
 impl SerializedTestConfig {
@@ -429,15 +625,35 @@ impl SerializedTestConfig {
     self,
     config_file_specifier: &ModuleSpecifier,
   ) -> Result<TestConfig, AnyError> {
+    let config_dir = specifier_parent(config_file_specifier);
+    let resolve_module = |maybe_module: Option<String>| {
+      maybe_module
+        .map(|module| config_dir.join(&module))
+        .transpose()
+    };
     Ok(TestConfig {
       files: self.files.into_resolved(config_file_specifier)?,
+      global_setup: resolve_module(self.global_setup)?,
+      global_teardown: resolve_module(self.global_teardown)?,
     })
   }
 }
 
+/// The name and version of the package of the configuration file, which are
+/// published with it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackageMetadata {
+  pub name: String,
+  pub version: String,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct TestConfig {
   pub files: FilesConfig,
+  /// The module whose default export is run once before the test modules.
+  pub global_setup: Option<ModuleSpecifier>,
+  /// The module whose default export is run once after the test modules.
+  pub global_teardown: Option<ModuleSpecifier>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -462,6 +678,342 @@ pub struct BenchConfig {
   pub files: FilesConfig,
 }
 
+/// How the `vendor` directory generated by `deno vendor` next to the
+/// configuration file is used, from the `"vendor"` configuration.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum VendorMode {
+  /// Resolves with the import map of the vendor directory, downloading the
+  /// remote modules that are not vendored.
+  Prefer,
+  /// Resolves with the import map of the vendor directory and errors for
+  /// the remote modules that are not vendored.
+  Only,
+  /// Only uses the vendor directory when its import map is passed with
+  /// `--import-map`.
+  #[default]
+  Ignore,
+}
+
+/// The glob patterns of the paths that cause a restart in watch mode in
+/// addition to the ones of the module graph, and of the ones whose changes are
+/// ignored, from the `"watch"` configuration. They're relative to the
+/// configuration file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct WatchConfig {
+  pub include: Vec<String>,
+  pub exclude: Vec<String>,
+  /// The milliseconds to wait for more changes before restarting.
+  pub debounce: Option<u64>,
+  /// The task that runs before every restart, like one that generates code.
+  pub before_restart: Option<String>,
+}
+
+/// Limits for downloading remote modules, from the `"fetch"` configuration.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct FetchConfig {
+  /// The maximum number of requests made to an origin at the same time.
+  pub max_concurrent_requests_per_origin: Option<usize>,
+  /// The maximum number of requests started per second for an origin.
+  pub max_requests_per_second_per_origin: Option<f64>,
+  /// The maximum number of redirects followed for a remote module.
+  pub max_redirects: Option<u32>,
+  /// The origins whose stale modules are revalidated with `--reload=stale`,
+  /// which are all of them when not set.
+  pub revalidate_origins: Option<Vec<String>>,
+}
+
+/// The policy for the licenses of the dependencies checked by `deno license`,
+/// from the `"licenses"` configuration.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct LicensesConfig {
+  /// The SPDX license identifiers the dependencies may be licensed under,
+  /// which allows any license when not set.
+  pub allow: Option<Vec<String>>,
+}
+
+/// How an import cycle between local modules is reported.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum CyclePolicy {
+  #[default]
+  Ignore,
+  Warn,
+  Error,
+}
+
+/// The policies for the import cycles of the modules matching glob patterns,
+/// from the `"cycles"` configuration.
+#[derive(Clone, Debug, Default)]
+pub struct CyclesConfig(Vec<(String, GlobPattern, CyclePolicy)>);
+
+impl CyclesConfig {
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Gets the policy of the most specific, meaning the longest, pattern that
+  /// matches the module. The cycles of the modules that match none of the
+  /// patterns are ignored.
+  pub fn policy(&self, specifier: &ModuleSpecifier) -> CyclePolicy {
+    let path = match specifier_to_file_path(specifier) {
+      Ok(path) => path,
+      Err(_) => return CyclePolicy::Ignore,
+    };
+    self
+      .0
+      .iter()
+      .filter(|(_, pattern, _)| pattern.matches(&path))
+      .max_by_key(|(text, _, _)| text.len())
+      .map(|(_, _, policy)| *policy)
+      .unwrap_or_default()
+  }
+
+  /// Gets the strictest policy of the modules of an import cycle.
+  pub fn cycle_policy(&self, cycle: &[ModuleSpecifier]) -> CyclePolicy {
+    cycle
+      .iter()
+      .map(|specifier| self.policy(specifier))
+      .max()
+      .unwrap_or_default()
+  }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SerializedUrlRewriteRule {
+  pub prefix: Option<String>,
+  pub regex: Option<String>,
+  pub to: String,
+}
+
+impl SerializedUrlRewriteRule {
+  pub fn into_resolved(self) -> Result<UrlRewriteRule, AnyError> {
+    match (self.prefix, self.regex) {
+      (Some(prefix), None) => Ok(UrlRewriteRule::Prefix {
+        prefix,
+        to: self.to,
+      }),
+      (None, Some(regex)) => Ok(UrlRewriteRule::Regex {
+        regex: Regex::new(&regex)
+          .with_context(|| format!("Invalid URL rewrite regex: {regex}"))?,
+        to: self.to,
+      }),
+      _ => bail!(
+        "URL rewrite rules must specify exactly one of \"prefix\" or \"regex\""
+      ),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub enum UrlRewriteRule {
+  /// Replaces the matching start of a URL with `to`.
+  Prefix { prefix: String, to: String },
+  /// Replaces a URL matching the regex with `to`, which may reference
+  /// capture groups (ex. `$1`).
+  Regex { regex: Regex, to: String },
+}
+
+impl UrlRewriteRule {
+  fn rewrite(&self, url: &str) -> Option<String> {
+    match self {
+      UrlRewriteRule::Prefix { prefix, to } => url
+        .strip_prefix(prefix.as_str())
+        .map(|rest| format!("{to}{rest}")),
+      UrlRewriteRule::Regex { regex, to } => {
+        if regex.is_match(url) {
+          Some(regex.replace(url, to.as_str()).into_owned())
+        } else {
+          None
+        }
+      }
+    }
+  }
+}
+
+/// Rules from the `"urlRewrites"` configuration that redirect where remote
+/// modules are downloaded from (ex. an internal mirror). The module graph,
+/// cache and lockfile continue to use the original URL.
+#[derive(Clone, Debug, Default)]
+pub struct UrlRewriteRules(Vec<UrlRewriteRule>);
+
+impl UrlRewriteRules {
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  /// Gets the URL to download the provided specifier from based on the
+  /// first matching rule.
+  pub fn rewrite(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    for rule in &self.0 {
+      if let Some(url) = rule.rewrite(specifier.as_str()) {
+        let rewritten = ModuleSpecifier::parse(&url).with_context(|| {
+          format!("Invalid URL rewrite of \"{specifier}\" to \"{url}\"")
+        })?;
+        return Ok(Some(rewritten));
+      }
+    }
+    Ok(None)
+  }
+}
+
+/// A build profile from the `"profiles"` configuration, selected with
+/// `--profile=<NAME>`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct ProfileConfig {
+  /// Global identifiers or member expressions (ex. `process.env.NODE_ENV`)
+  /// that are replaced with the provided JavaScript expression.
+  pub define: BTreeMap<String, String>,
+  /// Minify the emitted JavaScript.
+  pub minify: bool,
+  /// Compiler options applied on top of the `"compilerOptions"`.
+  pub compiler_options: Option<HashMap<String, Value>>,
+  /// The name of the permission set of the `"permissions"` that is granted
+  /// with the profile, unless `--permission-set` selects another one.
+  pub permissions: Option<String>,
+}
+
+/// A permission of a permission set, which is either granted entirely
+/// (`true`) or only for the listed values.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PermissionSetValue {
+  All(bool),
+  List(Vec<String>),
+}
+
+/// A permission set from the `"permissions"` configuration, granted with
+/// `--permission-set=<NAME>` or by the build profile that names it. The paths
+/// of `read`, `write` and `ffi` are relative to the configuration file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PermissionSetConfig {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub read: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub write: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub net: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub env: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub sys: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub run: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ffi: Option<PermissionSetValue>,
+  #[serde(skip_serializing_if = "std::ops::Not::not")]
+  pub hrtime: bool,
+  /// The parts of the granted permissions that are denied.
+  #[serde(skip_serializing_if = "PermissionSetDenyConfig::is_empty")]
+  pub deny: PermissionSetDenyConfig,
+}
+
+/// The deny rules of a permission set, which deny the listed values even when
+/// the permission is granted (ex. a directory of secrets).
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PermissionSetDenyConfig {
+  pub read: Vec<String>,
+  pub write: Vec<String>,
+  pub net: Vec<String>,
+  pub env: Vec<String>,
+  pub sys: Vec<String>,
+  pub run: Vec<String>,
+  pub ffi: Vec<String>,
+}
+
+impl PermissionSetDenyConfig {
+  pub fn is_empty(&self) -> bool {
+    self.to_deny_rules().is_empty()
+  }
+
+  pub fn to_deny_rules(&self) -> PermissionDenyRules {
+    let to_paths =
+      |values: &Vec<String>| values.iter().map(PathBuf::from).collect();
+    PermissionDenyRules {
+      read: to_paths(&self.read),
+      write: to_paths(&self.write),
+      net: self.net.clone(),
+      env: self.env.clone(),
+      sys: self.sys.clone(),
+      run: self.run.clone(),
+      ffi: to_paths(&self.ffi),
+    }
+  }
+}
+
+impl PermissionSetConfig {
+  /// Grants the permissions in addition to the ones of the permission flags.
+  pub fn grant(&self, flags: &mut Flags) {
+    grant_permission(&mut flags.allow_read, &self.read, PathBuf::from);
+    grant_permission(&mut flags.allow_write, &self.write, PathBuf::from);
+    grant_permission(&mut flags.allow_net, &self.net, String::from);
+    grant_permission(&mut flags.allow_env, &self.env, String::from);
+    grant_permission(&mut flags.allow_sys, &self.sys, String::from);
+    grant_permission(&mut flags.allow_run, &self.run, String::from);
+    grant_permission(&mut flags.allow_ffi, &self.ffi, PathBuf::from);
+    flags.allow_hrtime |= self.hrtime;
+  }
+
+  fn resolve_paths(&mut self, config_dir: &Path) {
+    for value in [&mut self.read, &mut self.write, &mut self.ffi] {
+      if let Some(PermissionSetValue::List(paths)) = value {
+        for path in paths {
+          *path = config_dir.join(&*path).to_string_lossy().to_string();
+        }
+      }
+    }
+    for paths in [
+      &mut self.deny.read,
+      &mut self.deny.write,
+      &mut self.deny.ffi,
+    ] {
+      for path in paths {
+        *path = config_dir.join(&*path).to_string_lossy().to_string();
+      }
+    }
+  }
+}
+
+/// Adds the values of a permission to its allow list, where an empty list
+/// means that the permission is granted entirely.
+fn grant_permission<T: PartialEq>(
+  allow_list: &mut Option<Vec<T>>,
+  value: &Option<PermissionSetValue>,
+  to_item: impl Fn(&str) -> T,
+) {
+  match value {
+    None | Some(PermissionSetValue::All(false)) => {}
+    Some(PermissionSetValue::All(true)) => *allow_list = Some(Vec::new()),
+    Some(PermissionSetValue::List(values)) if values.is_empty() => {}
+    Some(PermissionSetValue::List(values)) => match allow_list {
+      Some(items) if items.is_empty() => {}
+      Some(items) => {
+        for value in values {
+          let item = to_item(value);
+          if !items.contains(&item) {
+            items.push(item);
+          }
+        }
+      }
+      None => {
+        *allow_list = Some(values.iter().map(|value| to_item(value)).collect())
+      }
+    },
+  }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(untagged)]
 pub enum LockConfig {
@@ -469,6 +1021,51 @@ pub enum LockConfig {
   PathBuf(PathBuf),
 }
 
+/// The TLS configuration of the requests to an origin, from the `"tls"`
+/// configuration.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OriginTlsConfig {
+  /// A PEM file of certificate authorities that are trusted for the origin,
+  /// in addition to the ones of the root certificate store.
+  pub ca_file: Option<PathBuf>,
+  /// The PEM files of the client certificate chain and its private key,
+  /// which are sent to servers that require mutual TLS.
+  pub client_cert: Option<(PathBuf, PathBuf)>,
+}
+
+/// The shared cache of the emits and type check results built by other
+/// machines, from the `"remoteCache"` configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteCacheConfig {
+  /// The URL the artifacts are stored under, which ends with a slash.
+  pub url: ModuleSpecifier,
+  /// If the artifacts built by this process are not uploaded.
+  pub read_only: bool,
+  /// The environment variable with the bearer token sent to the cache,
+  /// which is looked up in `DENO_AUTH_TOKENS` when not set.
+  pub auth_token_env: Option<String>,
+  /// The directory of the configuration file, which the local modules are
+  /// keyed relative to.
+  pub config_dir: ModuleSpecifier,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct SerializedRemoteCacheConfig {
+  url: String,
+  #[serde(default)]
+  read_only: bool,
+  auth_token_env: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+struct SerializedOriginTlsConfig {
+  ca_file: Option<String>,
+  cert_file: Option<String>,
+  key_file: Option<String>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigFileJson {
@@ -481,7 +1078,28 @@ pub struct ConfigFileJson {
   pub tasks: Option<Value>,
   pub test: Option<Value>,
   pub bench: Option<Value>,
+  pub doc: Option<Value>,
+  pub profiles: Option<Value>,
   pub lock: Option<Value>,
+  pub url_rewrites: Option<Value>,
+  pub fetch: Option<Value>,
+  pub tls: Option<Value>,
+  pub workspace: Option<Value>,
+  pub unstable: Option<Value>,
+  pub lib_overrides: Option<Value>,
+  pub check_units: Option<Value>,
+  pub references: Option<Value>,
+  pub worker_permissions: Option<Value>,
+  pub permissions: Option<Value>,
+  pub vendor: Option<Value>,
+  pub licenses: Option<Value>,
+  pub cycles: Option<Value>,
+  pub watch: Option<Value>,
+  pub type_acquisition: Option<Value>,
+  pub remote_cache: Option<Value>,
+  pub preload: Option<Value>,
+  pub name: Option<Value>,
+  pub version: Option<Value>,
 }
 
 #[derive(Clone, Debug)]
@@ -724,6 +1342,40 @@ impl ConfigFile {
     }
   }
 
+  pub fn to_package_metadata(
+    &self,
+  ) -> Result<Option<PackageMetadata>, AnyError> {
+    let name: Option<String> = match self.json.name.clone() {
+      Some(value) => Some(
+        serde_json::from_value(value)
+          .context("Failed to parse \"name\" configuration")?,
+      ),
+      None => None,
+    };
+    let version: Option<String> = match self.json.version.clone() {
+      Some(value) => Some(
+        serde_json::from_value(value)
+          .context("Failed to parse \"version\" configuration")?,
+      ),
+      None => None,
+    };
+    match (name, version) {
+      (Some(name), Some(version)) => {
+        if Version::parse_standard(&version).is_err() {
+          bail!(
+            "The \"version\" configuration must be a semantic version, but was \"{}\".",
+            version
+          );
+        }
+        Ok(Some(PackageMetadata { name, version }))
+      }
+      (None, None) => Ok(None),
+      _ => bail!(
+        "The \"name\" and \"version\" configuration must be set together."
+      ),
+    }
+  }
+
   pub fn to_bench_config(&self) -> Result<Option<BenchConfig>, AnyError> {
     if let Some(config) = self.json.bench.clone() {
       let bench_config: SerializedBenchConfig = serde_json::from_value(config)
@@ -734,6 +1386,16 @@ impl ConfigFile {
     }
   }
 
+  pub fn to_doc_config(&self) -> Result<Option<DocConfig>, AnyError> {
+    if let Some(config) = self.json.doc.clone() {
+      let doc_config: DocConfig = serde_json::from_value(config)
+        .context("Failed to parse \"doc\" configuration")?;
+      Ok(Some(doc_config))
+    } else {
+      Ok(None)
+    }
+  }
+
   /// Return any tasks that are defined in the configuration file as a sequence
   /// of JSON objects providing the name of the task and the arguments of the
   /// task in a detail field.
@@ -828,59 +1490,724 @@ impl ConfigFile {
     Ok(tasks_config)
   }
 
-  pub fn to_lock_config(&self) -> Result<Option<LockConfig>, AnyError> {
-    if let Some(config) = self.json.lock.clone() {
-      let lock_config: LockConfig = serde_json::from_value(config)
-        .context("Failed to parse \"lock\" configuration")?;
-      Ok(Some(lock_config))
-    } else {
-      Ok(None)
-    }
+  /// Gets the directories of the workspace members, which are resolved
+  /// relative to the configuration file.
+  pub fn to_workspace_members(&self) -> Result<Vec<PathBuf>, AnyError> {
+    let members: Vec<String> = match self.json.workspace.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"workspace\" configuration")?,
+      None => return Ok(Vec::new()),
+    };
+    let config_dir = specifier_parent(&self.specifier);
+    members
+      .iter()
+      .map(|member| {
+        let url = config_dir.join(member)?;
+        specifier_to_file_path(&url)
+      })
+      .collect()
   }
-}
 
-/// Represents the "default" type library that should be used when type
-/// checking the code in the module graph.  Note that a user provided config
-/// of `"lib"` would override this value.
-#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
-pub enum TsTypeLib {
-  DenoWindow,
-  DenoWorker,
-  UnstableDenoWindow,
-  UnstableDenoWorker,
-}
+  /// Gets the type libraries that override the `"lib"` compiler option for
+  /// the modules in the directory of a workspace member whose configuration
+  /// file sets it, or in the paths of the `"libOverrides"`.
+  pub fn to_lib_overrides(&self) -> Result<Vec<LibOverride>, AnyError> {
+    let mut lib_overrides = Vec::new();
+    for member_dir in self.to_workspace_members()? {
+      let maybe_config_path = ["deno.json", "deno.jsonc"]
+        .iter()
+        .map(|file_name| member_dir.join(file_name))
+        .find(|path| path.is_file());
+      let member_config_file = match maybe_config_path {
+        Some(config_path) => ConfigFile::read(&config_path)?,
+        None => continue,
+      };
+      let maybe_lib = member_config_file
+        .json
+        .compiler_options
+        .as_ref()
+        .and_then(|options| options.get("lib"));
+      if let Some(lib) = maybe_lib {
+        let lib: Vec<String> = serde_json::from_value(lib.clone())
+          .with_context(|| {
+            format!(
+              "Failed to parse \"compilerOptions.lib\" of '{}'",
+              member_config_file.specifier
+            )
+          })?;
+        lib_overrides.push(LibOverride {
+          specifier: specifier_parent(&member_config_file.specifier),
+          lib,
+        });
+      }
+    }
 
-impl Default for TsTypeLib {
-  fn default() -> Self {
-    Self::DenoWindow
+    let overrides: BTreeMap<String, Vec<String>> =
+      match self.json.lib_overrides.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"libOverrides\" configuration")?,
+        None => BTreeMap::new(),
+      };
+    let config_dir = specifier_parent(&self.specifier);
+    for (path, lib) in overrides {
+      let specifier = config_dir.join(&path).with_context(|| {
+        format!("Invalid path \"{path}\" in \"libOverrides\" configuration")
+      })?;
+      lib_overrides.push(LibOverride { specifier, lib });
+    }
+    Ok(lib_overrides)
   }
-}
 
-impl Serialize for TsTypeLib {
-  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-  where
-    S: Serializer,
-  {
-    let value = match self {
-      Self::DenoWindow => vec!["deno.window".to_string()],
-      Self::DenoWorker => vec!["deno.worker".to_string()],
-      Self::UnstableDenoWindow => {
-        vec!["deno.window".to_string(), "deno.unstable".to_string()]
+  /// Gets the permissions that the workers in the paths of the
+  /// `"workerPermissions"` are restricted to.
+  pub fn to_worker_permissions(
+    &self,
+  ) -> Result<Vec<WorkerPermissions>, AnyError> {
+    let serialized: BTreeMap<String, Value> =
+      match self.json.worker_permissions.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"workerPermissions\" configuration")?,
+        None => return Ok(Vec::new()),
+      };
+    let config_dir = specifier_parent(&self.specifier);
+    let mut worker_permissions = Vec::with_capacity(serialized.len());
+    for (path, permissions) in serialized {
+      let specifier = config_dir.join(&path).with_context(|| {
+        format!(
+          "Invalid path \"{path}\" in \"workerPermissions\" configuration"
+        )
+      })?;
+      let permissions = serde_json::from_value(permissions).with_context(|| {
+        format!(
+          "Failed to parse the permissions of \"{path}\" in \"workerPermissions\" configuration"
+        )
+      })?;
+      worker_permissions.push(WorkerPermissions {
+        specifier,
+        permissions,
+      });
+    }
+    Ok(worker_permissions)
+  }
+
+  /// Gets the `"checkUnits"` and the projects of the `"references"`, sorted
+  /// so that each unit comes after the units it depends on.
+  pub fn to_check_units(&self) -> Result<Vec<CheckUnit>, AnyError> {
+    let serialized_units: BTreeMap<String, SerializedCheckUnit> =
+      match self.json.check_units.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"checkUnits\" configuration")?,
+        None => BTreeMap::new(),
+      };
+    let config_dir = specifier_parent(&self.specifier);
+    let mut units = BTreeMap::new();
+    for (name, serialized_unit) in &serialized_units {
+      for dependency in &serialized_unit.dependencies {
+        if !serialized_units.contains_key(dependency) {
+          bail!(
+            "The check unit \"{}\" depends on the unknown unit \"{}\".",
+            name,
+            dependency
+          );
+        }
       }
-      Self::UnstableDenoWorker => {
-        vec!["deno.worker".to_string(), "deno.unstable".to_string()]
+      let include = serialized_unit
+        .include
+        .iter()
+        .map(|path| {
+          config_dir.join(path).with_context(|| {
+            format!("Invalid path \"{path}\" in the check unit \"{name}\"")
+          })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+      units.insert(
+        name.clone(),
+        CheckUnit {
+          name: name.clone(),
+          include,
+          dependencies: serialized_unit.dependencies.clone(),
+          maybe_project: None,
+        },
+      );
+    }
+    for unit in self.to_referenced_check_units()? {
+      if units.contains_key(&unit.name) {
+        bail!(
+          "The referenced project \"{}\" has the name of a check unit.",
+          unit.name
+        );
       }
-    };
-    Serialize::serialize(&value, serializer)
+      units.insert(unit.name.clone(), unit);
+    }
+    sort_check_units(units)
   }
-}
 
-/// An enum that represents the base tsc configuration to return.
-pub enum TsConfigType {
-  /// Return a configuration for bundling, using swc to emit the bundle. This is
-  /// independent of type checking.
-  Bundle,
-  /// Return a configuration to use tsc to type check. This
+  /// Reads the configuration files of the projects in `"references"`, which
+  /// are the paths of the configuration files or of the directories that
+  /// contain them.
+  fn read_references(&self) -> Result<Vec<ConfigFile>, AnyError> {
+    let references: Vec<SerializedReference> =
+      match self.json.references.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"references\" configuration")?,
+        None => return Ok(Vec::new()),
+      };
+    let config_dir = specifier_parent(&self.specifier);
+    references
+      .iter()
+      .map(|reference| {
+        let path = specifier_to_file_path(&config_dir.join(&reference.path)?)?;
+        if !path.is_dir() {
+          return ConfigFile::read(&path);
+        }
+        let config_path = ["deno.json", "deno.jsonc"]
+          .iter()
+          .map(|file_name| path.join(file_name))
+          .find(|path| path.is_file())
+          .ok_or_else(|| {
+            anyhow!(
+              "No configuration file found in the referenced project \"{}\" of '{}'.",
+              reference.path,
+              self.specifier
+            )
+          })?;
+        ConfigFile::read(&config_path)
+      })
+      .collect()
+  }
+
+  /// Gets a check unit for each project referenced by the configuration
+  /// file, directly or through other projects. The unit of a project includes
+  /// its directory and depends on the projects it references.
+  fn to_referenced_check_units(&self) -> Result<Vec<CheckUnit>, AnyError> {
+    let root_dir = specifier_parent(&self.specifier);
+    let project_name = |project_dir: &ModuleSpecifier| match root_dir
+      .make_relative(project_dir)
+    {
+      Some(name) if !name.is_empty() => name.trim_end_matches('/').to_string(),
+      _ => project_dir.to_string(),
+    };
+
+    // the specifiers of the configuration files are all canonicalized by
+    // `ConfigFile::read`, so the names of the projects match those of their
+    // dependents' dependencies
+    let mut units = BTreeMap::new();
+    let mut pending = self.read_references()?;
+    while let Some(config_file) = pending.pop() {
+      if config_file.specifier == self.specifier {
+        bail!(
+          "The referenced project '{}' references the configuration file '{}'.",
+          specifier_parent(&config_file.specifier),
+          self.specifier
+        );
+      }
+      if units.contains_key(&config_file.specifier) {
+        continue;
+      }
+      let references = config_file.read_references()?;
+      let dependencies = references
+        .iter()
+        .map(|reference| project_name(&specifier_parent(&reference.specifier)))
+        .collect();
+      let (compiler_options, _) = config_file.to_compiler_options()?;
+      let project_dir = specifier_parent(&config_file.specifier);
+      units.insert(
+        config_file.specifier.clone(),
+        CheckUnit {
+          name: project_name(&project_dir),
+          include: vec![project_dir],
+          dependencies,
+          maybe_project: Some(CheckUnitProject {
+            config_specifier: config_file.specifier.clone(),
+            compiler_options,
+          }),
+        },
+      );
+      pending.extend(references);
+    }
+    Ok(units.into_values().collect())
+  }
+
+  pub fn to_url_rewrite_rules(&self) -> Result<UrlRewriteRules, AnyError> {
+    if let Some(config) = self.json.url_rewrites.clone() {
+      let rules: Vec<SerializedUrlRewriteRule> = serde_json::from_value(config)
+        .context("Failed to parse \"urlRewrites\" configuration")?;
+      Ok(UrlRewriteRules(
+        rules
+          .into_iter()
+          .map(|rule| rule.into_resolved())
+          .collect::<Result<Vec<_>, _>>()?,
+      ))
+    } else {
+      Ok(Default::default())
+    }
+  }
+
+  pub fn to_fetch_config(&self) -> Result<FetchConfig, AnyError> {
+    let mut fetch_config: FetchConfig = match self.json.fetch.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"fetch\" configuration")?,
+      None => return Ok(Default::default()),
+    };
+    if fetch_config.max_concurrent_requests_per_origin == Some(0) {
+      bail!("\"fetch.maxConcurrentRequestsPerOrigin\" must be greater than 0.");
+    }
+    if let Some(max) = fetch_config.max_requests_per_second_per_origin {
+      if max <= 0.0 || !max.is_finite() {
+        bail!(
+          "\"fetch.maxRequestsPerSecondPerOrigin\" must be greater than 0."
+        );
+      }
+    }
+    if let Some(origins) = &mut fetch_config.revalidate_origins {
+      for origin in origins.iter_mut() {
+        let url = ModuleSpecifier::parse(origin).with_context(|| {
+          format!("Invalid origin \"{origin}\" in \"fetch.revalidateOrigins\"")
+        })?;
+        *origin = url.origin().ascii_serialization();
+      }
+    }
+    Ok(fetch_config)
+  }
+
+  /// Gets the TLS configurations of the `"tls"` configuration by the origin
+  /// they are used for, with their files resolved relative to this file.
+  pub fn to_origin_tls_configs(
+    &self,
+  ) -> Result<BTreeMap<String, OriginTlsConfig>, AnyError> {
+    let serialized: BTreeMap<String, SerializedOriginTlsConfig> =
+      match self.json.tls.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"tls\" configuration")?,
+        None => return Ok(BTreeMap::new()),
+      };
+    let config_dir = specifier_parent(&self.specifier);
+    let resolve_path = |path: &str| {
+      config_dir
+        .join(path)
+        .map_err(AnyError::from)
+        .and_then(|url| specifier_to_file_path(&url))
+        .with_context(|| {
+          format!("Invalid path \"{path}\" in \"tls\" configuration")
+        })
+    };
+    let mut configs = BTreeMap::new();
+    for (origin, config) in serialized {
+      let url = ModuleSpecifier::parse(&origin).with_context(|| {
+        format!("Invalid origin \"{origin}\" in \"tls\" configuration")
+      })?;
+      if url.scheme() != "https" {
+        bail!(
+          "The origin \"{}\" in \"tls\" configuration must be an https origin.",
+          origin
+        );
+      }
+      let client_cert = match (config.cert_file, config.key_file) {
+        (Some(cert_file), Some(key_file)) => {
+          Some((resolve_path(&cert_file)?, resolve_path(&key_file)?))
+        }
+        (None, None) => None,
+        _ => bail!(
+          "The \"certFile\" and \"keyFile\" of \"{}\" in \"tls\" configuration must be set together.",
+          origin
+        ),
+      };
+      configs.insert(
+        url.origin().ascii_serialization(),
+        OriginTlsConfig {
+          ca_file: config
+            .ca_file
+            .map(|path| resolve_path(&path))
+            .transpose()?,
+          client_cert,
+        },
+      );
+    }
+    Ok(configs)
+  }
+
+  pub fn to_vendor_mode(&self) -> Result<VendorMode, AnyError> {
+    match self.json.vendor.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"vendor\" configuration"),
+      None => Ok(Default::default()),
+    }
+  }
+
+  pub fn to_watch_config(&self) -> Result<WatchConfig, AnyError> {
+    match self.json.watch.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"watch\" configuration"),
+      None => Ok(Default::default()),
+    }
+  }
+
+  /// If the `@types/*` packages of npm packages that don't ship their own
+  /// types should be acquired for type checking, which is the default.
+  pub fn to_type_acquisition(&self) -> Result<bool, AnyError> {
+    match self.json.type_acquisition.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"typeAcquisition\" configuration"),
+      None => Ok(true),
+    }
+  }
+
+  pub fn to_remote_cache_config(
+    &self,
+  ) -> Result<Option<RemoteCacheConfig>, AnyError> {
+    let serialized: SerializedRemoteCacheConfig =
+      match self.json.remote_cache.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"remoteCache\" configuration")?,
+        None => return Ok(None),
+      };
+    let mut url =
+      ModuleSpecifier::parse(&serialized.url).with_context(|| {
+        format!(
+          "Invalid URL \"{}\" in \"remoteCache\" configuration",
+          serialized.url
+        )
+      })?;
+    if !matches!(url.scheme(), "http" | "https") {
+      bail!(
+        "The URL \"{}\" in \"remoteCache\" configuration must be an http or https URL.",
+        serialized.url
+      );
+    }
+    if !url.path().ends_with('/') {
+      url.set_path(&format!("{}/", url.path()));
+    }
+    Ok(Some(RemoteCacheConfig {
+      url,
+      read_only: serialized.read_only,
+      auth_token_env: serialized.auth_token_env,
+      config_dir: self.specifier.join("./").unwrap(),
+    }))
+  }
+
+  /// Gets the modules to evaluate before the main module, as written in the
+  /// `"preload"` configuration.
+  pub fn to_preload_imports(&self) -> Result<Vec<String>, AnyError> {
+    match self.json.preload.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"preload\" configuration"),
+      None => Ok(Vec::new()),
+    }
+  }
+
+  pub fn to_licenses_config(&self) -> Result<LicensesConfig, AnyError> {
+    match self.json.licenses.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"licenses\" configuration"),
+      None => Ok(Default::default()),
+    }
+  }
+
+  pub fn to_cycles_config(&self) -> Result<CyclesConfig, AnyError> {
+    let serialized: BTreeMap<String, CyclePolicy> =
+      match self.json.cycles.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"cycles\" configuration")?,
+        None => return Ok(Default::default()),
+      };
+    // only the local modules have import cycles
+    let config_dir =
+      match specifier_to_file_path(&specifier_parent(&self.specifier)) {
+        Ok(config_dir) => config_dir,
+        Err(_) => return Ok(Default::default()),
+      };
+    let mut patterns = Vec::with_capacity(serialized.len());
+    for (text, policy) in serialized {
+      let pattern =
+        GlobPattern::new(&config_dir, &text).with_context(|| {
+          format!("Invalid pattern \"{text}\" in \"cycles\" configuration")
+        })?;
+      patterns.push((text, pattern, policy));
+    }
+    Ok(CyclesConfig(patterns))
+  }
+
+  /// The `vendor/import_map.json` generated by `deno vendor` in the
+  /// directory of the configuration file, when it exists.
+  pub fn to_vendor_import_map_path(&self) -> Option<PathBuf> {
+    let config_path = specifier_to_file_path(&self.specifier).ok()?;
+    let import_map_path =
+      config_path.parent()?.join("vendor").join("import_map.json");
+    import_map_path.is_file().then_some(import_map_path)
+  }
+
+  /// The unstable features to enable, like with their `--unstable-<name>`
+  /// flags.
+  pub fn to_unstable_features(&self) -> Result<Vec<String>, AnyError> {
+    let features: Vec<String> = match self.json.unstable.clone() {
+      Some(config) => serde_json::from_value(config)
+        .context("Failed to parse \"unstable\" configuration")?,
+      None => return Ok(Vec::new()),
+    };
+    for feature in &features {
+      if !deno_runtime::UNSTABLE_GRANULAR_FLAGS
+        .iter()
+        .any(|(name, _)| name == feature)
+      {
+        bail!(
+          "Unknown unstable feature \"{}\" in the configuration file. Available features: {}",
+          feature,
+          deno_runtime::UNSTABLE_GRANULAR_FLAGS
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", "),
+        );
+      }
+    }
+    Ok(features)
+  }
+
+  pub fn to_profile_config(
+    &self,
+    name: &str,
+  ) -> Result<ProfileConfig, AnyError> {
+    let mut profiles: BTreeMap<String, ProfileConfig> =
+      match self.json.profiles.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"profiles\" configuration")?,
+        None => BTreeMap::new(),
+      };
+    match profiles.remove(name) {
+      Some(profile) => Ok(profile),
+      None if profiles.is_empty() => bail!(
+        "Profile \"{}\" was not found because the configuration file does not define any profiles.\n  Config: {}",
+        name,
+        self.specifier,
+      ),
+      None => bail!(
+        "Profile \"{}\" was not found in the configuration file. Available profiles: {}\n  Config: {}",
+        name,
+        profiles.keys().cloned().collect::<Vec<_>>().join(", "),
+        self.specifier,
+      ),
+    }
+  }
+
+  /// Gets a permission set of the `"permissions"`, with the paths resolved
+  /// relative to the configuration file.
+  pub fn to_permission_set(
+    &self,
+    name: &str,
+  ) -> Result<PermissionSetConfig, AnyError> {
+    let mut permission_sets: BTreeMap<String, PermissionSetConfig> =
+      match self.json.permissions.clone() {
+        Some(config) => serde_json::from_value(config)
+          .context("Failed to parse \"permissions\" configuration")?,
+        None => BTreeMap::new(),
+      };
+    let mut permission_set = match permission_sets.remove(name) {
+      Some(permission_set) => permission_set,
+      None if permission_sets.is_empty() => bail!(
+        "Permission set \"{}\" was not found because the configuration file does not define any permission sets.\n  Config: {}",
+        name,
+        self.specifier,
+      ),
+      None => bail!(
+        "Permission set \"{}\" was not found in the configuration file. Available permission sets: {}\n  Config: {}",
+        name,
+        permission_sets.keys().cloned().collect::<Vec<_>>().join(", "),
+        self.specifier,
+      ),
+    };
+    if let Ok(config_path) = specifier_to_file_path(&self.specifier) {
+      if let Some(config_dir) = config_path.parent() {
+        permission_set.resolve_paths(config_dir);
+      }
+    }
+    Ok(permission_set)
+  }
+
+  pub fn to_lock_config(&self) -> Result<Option<LockConfig>, AnyError> {
+    if let Some(config) = self.json.lock.clone() {
+      let lock_config: LockConfig = serde_json::from_value(config)
+        .context("Failed to parse \"lock\" configuration")?;
+      Ok(Some(lock_config))
+    } else {
+      Ok(None)
+    }
+  }
+}
+
+/// The type libraries of the modules in a directory, or of a single module,
+/// configured in the configuration file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LibOverride {
+  /// The module, or the directory when the specifier ends with a slash.
+  pub specifier: ModuleSpecifier,
+  pub lib: Vec<String>,
+}
+
+impl LibOverride {
+  pub fn matches(&self, specifier: &ModuleSpecifier) -> bool {
+    is_in_path(&self.specifier, specifier)
+  }
+}
+
+/// The permissions that the workers of a directory, or of a single module,
+/// are restricted to, configured in the configuration file.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WorkerPermissions {
+  /// The module, or the directory when the specifier ends with a slash.
+  pub specifier: ModuleSpecifier,
+  pub permissions: ChildPermissionsArg,
+}
+
+impl WorkerPermissions {
+  pub fn matches(&self, specifier: &ModuleSpecifier) -> bool {
+    is_in_path(&self.specifier, specifier)
+  }
+}
+
+/// Gets if a module is the `path` module, or in the `path` directory when it
+/// ends with a slash.
+fn is_in_path(path: &ModuleSpecifier, specifier: &ModuleSpecifier) -> bool {
+  if path.path().ends_with('/') {
+    specifier.as_str().starts_with(path.as_str())
+  } else {
+    specifier == path
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedCheckUnit {
+  include: Vec<String>,
+  dependencies: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SerializedReference {
+  path: String,
+}
+
+/// A part of the project that is type checked and cached separately, like a
+/// TypeScript project reference. Its modules may only import the modules of
+/// the units it declares as dependencies.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckUnit {
+  pub name: String,
+  /// The modules, or directories when ending with a slash, of the unit.
+  pub include: Vec<ModuleSpecifier>,
+  /// The names of the units the unit depends on.
+  pub dependencies: Vec<String>,
+  /// The project of the unit when it was referenced in `"references"`.
+  pub maybe_project: Option<CheckUnitProject>,
+}
+
+/// A project referenced by a configuration file, which is type checked with
+/// its own compiler options and build info.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckUnitProject {
+  pub config_specifier: ModuleSpecifier,
+  /// The compiler options of the project's configuration file, without the
+  /// ones that are ignored.
+  pub compiler_options: Value,
+}
+
+impl CheckUnit {
+  /// Gets the length of the most specific path of the unit that includes the
+  /// module, if any.
+  pub fn match_len(&self, specifier: &ModuleSpecifier) -> Option<usize> {
+    self
+      .include
+      .iter()
+      .filter(|path| is_in_path(path, specifier))
+      .map(|path| path.as_str().len())
+      .max()
+  }
+}
+
+/// Sorts the check units so that each unit comes after its dependencies.
+fn sort_check_units(
+  units: BTreeMap<String, CheckUnit>,
+) -> Result<Vec<CheckUnit>, AnyError> {
+  fn visit<'a>(
+    name: &'a str,
+    units: &'a BTreeMap<String, CheckUnit>,
+    visiting: &mut Vec<&'a str>,
+    sorted: &mut Vec<CheckUnit>,
+  ) -> Result<(), AnyError> {
+    if sorted.iter().any(|unit| unit.name == name) {
+      return Ok(());
+    }
+    if let Some(position) = visiting.iter().position(|n| *n == name) {
+      let mut cycle = visiting[position..].to_vec();
+      cycle.push(name);
+      bail!(
+        "The check units depend on each other in a cycle: {}",
+        cycle.join(" -> ")
+      );
+    }
+    let unit = &units[name];
+    visiting.push(name);
+    for dependency in &unit.dependencies {
+      visit(dependency, units, visiting, sorted)?;
+    }
+    visiting.pop();
+    sorted.push(unit.clone());
+    Ok(())
+  }
+
+  let mut sorted = Vec::with_capacity(units.len());
+  for name in units.keys() {
+    visit(name, &units, &mut Vec::new(), &mut sorted)?;
+  }
+  Ok(sorted)
+}
+
+/// Represents the "default" type library that should be used when type
+/// checking the code in the module graph.  Note that a user provided config
+/// of `"lib"` would override this value, except for a `Custom` library that
+/// was selected for the entry points being checked.
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+pub enum TsTypeLib {
+  DenoWindow,
+  DenoWorker,
+  UnstableDenoWindow,
+  UnstableDenoWorker,
+  /// The libraries of an entry point from a `LibOverride`.
+  Custom(Vec<String>),
+}
+
+impl Default for TsTypeLib {
+  fn default() -> Self {
+    Self::DenoWindow
+  }
+}
+
+impl Serialize for TsTypeLib {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    let value = match self {
+      Self::DenoWindow => vec!["deno.window".to_string()],
+      Self::DenoWorker => vec!["deno.worker".to_string()],
+      Self::UnstableDenoWindow => {
+        vec!["deno.window".to_string(), "deno.unstable".to_string()]
+      }
+      Self::UnstableDenoWorker => {
+        vec!["deno.worker".to_string(), "deno.unstable".to_string()]
+      }
+      Self::Custom(lib) => lib.clone(),
+    };
+    Serialize::serialize(&value, serializer)
+  }
+}
+
+/// An enum that represents the base tsc configuration to return.
+pub enum TsConfigType {
+  /// Return a configuration for bundling, using swc to emit the bundle. This is
+  /// independent of type checking.
+  Bundle,
+  /// Return a configuration to use tsc to type check. This
   /// is independent of either bundling or emitting via swc.
   Check { lib: TsTypeLib },
   /// Return a configuration to use swc to emit single module files.
@@ -1065,6 +2392,10 @@ mod tests {
           "proseWrap": "preserve"
         }
       },
+      "test": {
+        "globalSetup": "./test/setup.ts",
+        "globalTeardown": "https://deno.land/x/teardown.ts"
+      },
       "tasks": {
         "build": "deno run --allow-read --allow-write build.ts",
         "server": "deno run --allow-net --allow-read server.ts"
@@ -1120,6 +2451,17 @@ mod tests {
     assert_eq!(fmt_config.options.indent_width, Some(4));
     assert_eq!(fmt_config.options.single_quote, Some(true));
 
+    let test_config = config_file.to_test_config().unwrap().unwrap();
+    assert!(test_config.files.include.is_empty());
+    assert_eq!(
+      test_config.global_setup.unwrap().as_str(),
+      "file:///deno/test/setup.ts"
+    );
+    assert_eq!(
+      test_config.global_teardown.unwrap().as_str(),
+      "https://deno.land/x/teardown.ts"
+    );
+
     let tasks_config = config_file.to_tasks_config().unwrap().unwrap();
     assert_eq!(
       tasks_config["build"],
@@ -1277,6 +2619,920 @@ mod tests {
     );
   }
 
+  #[test]
+  fn unstable_features() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file =
+      ConfigFile::new(r#"{ "unstable": ["kv", "ffi"] }"#, &config_specifier)
+        .unwrap();
+    assert_eq!(
+      config_file.to_unstable_features().unwrap(),
+      vec!["kv", "ffi"]
+    );
+
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert!(config_file.to_unstable_features().unwrap().is_empty());
+
+    let config_file =
+      ConfigFile::new(r#"{ "unstable": ["gpu"] }"#, &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_unstable_features().err().unwrap().to_string(),
+      "Unknown unstable feature \"gpu\" in the configuration file. Available features: broadcast-channel, ffi, fs, http, kv, net, worker-options",
+    );
+  }
+
+  #[test]
+  fn fetch_config() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{ "fetch": { "maxConcurrentRequestsPerOrigin": 4, "maxRequestsPerSecondPerOrigin": 2.5, "maxRedirects": 3 } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_fetch_config().unwrap(),
+      FetchConfig {
+        max_concurrent_requests_per_origin: Some(4),
+        max_requests_per_second_per_origin: Some(2.5),
+        max_redirects: Some(3),
+        revalidate_origins: None,
+      }
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "fetch": { "revalidateOrigins": ["https://deno.land/x/", "https://esm.sh"] } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_fetch_config().unwrap().revalidate_origins,
+      Some(vec![
+        "https://deno.land".to_string(),
+        "https://esm.sh".to_string()
+      ]),
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "fetch": { "revalidateOrigins": ["deno.land"] } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_fetch_config().err().unwrap().to_string(),
+      "Invalid origin \"deno.land\" in \"fetch.revalidateOrigins\"",
+    );
+
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_fetch_config().unwrap(),
+      FetchConfig::default()
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "fetch": { "maxConcurrentRequestsPerOrigin": 0 } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_fetch_config().err().unwrap().to_string(),
+      "\"fetch.maxConcurrentRequestsPerOrigin\" must be greater than 0.",
+    );
+  }
+
+  #[test]
+  fn origin_tls_configs() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "tls": {
+          "https://registry.internal.example.com/": {
+            "caFile": "./certs/ca.pem",
+            "certFile": "certs/client.pem",
+            "keyFile": "certs/client.key"
+          },
+          "https://deno.internal.example.com": { "caFile": "/etc/ca.pem" }
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let configs = config_file.to_origin_tls_configs().unwrap();
+    assert_eq!(
+      configs.get("https://registry.internal.example.com"),
+      Some(&OriginTlsConfig {
+        ca_file: Some(PathBuf::from("/deno/certs/ca.pem")),
+        client_cert: Some((
+          PathBuf::from("/deno/certs/client.pem"),
+          PathBuf::from("/deno/certs/client.key")
+        )),
+      })
+    );
+    assert_eq!(
+      configs.get("https://deno.internal.example.com"),
+      Some(&OriginTlsConfig {
+        ca_file: Some(PathBuf::from("/etc/ca.pem")),
+        client_cert: None,
+      })
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "tls": { "https://deno.land": { "certFile": "client.pem" } } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_origin_tls_configs().err().unwrap().to_string(),
+      "The \"certFile\" and \"keyFile\" of \"https://deno.land\" in \"tls\" configuration must be set together.",
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "tls": { "http://deno.land": { "caFile": "ca.pem" } } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_origin_tls_configs().err().unwrap().to_string(),
+      "The origin \"http://deno.land\" in \"tls\" configuration must be an https origin.",
+    );
+  }
+
+  #[test]
+  fn workspace_members() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{ "workspace": ["./packages/a", "packages/b/", "../c"] }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_workspace_members().unwrap(),
+      vec![
+        PathBuf::from("/deno/packages/a"),
+        PathBuf::from("/deno/packages/b"),
+        PathBuf::from("/c"),
+      ]
+    );
+
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert!(config_file.to_workspace_members().unwrap().is_empty());
+  }
+
+  #[test]
+  fn lib_overrides() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "libOverrides": {
+          "./web/": ["dom", "esnext"],
+          "./workers/main.ts": ["deno.worker"]
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let lib_overrides = config_file.to_lib_overrides().unwrap();
+    assert_eq!(
+      lib_overrides,
+      vec![
+        LibOverride {
+          specifier: ModuleSpecifier::parse("file:///deno/web/").unwrap(),
+          lib: vec!["dom".to_string(), "esnext".to_string()],
+        },
+        LibOverride {
+          specifier: ModuleSpecifier::parse("file:///deno/workers/main.ts")
+            .unwrap(),
+          lib: vec!["deno.worker".to_string()],
+        },
+      ]
+    );
+    let matches = |index: usize, specifier: &str| {
+      lib_overrides[index].matches(&ModuleSpecifier::parse(specifier).unwrap())
+    };
+    assert!(matches(0, "file:///deno/web/app.tsx"));
+    assert!(matches(0, "file:///deno/web/components/button.tsx"));
+    assert!(!matches(0, "file:///deno/website/main.ts"));
+    assert!(matches(1, "file:///deno/workers/main.ts"));
+    assert!(!matches(1, "file:///deno/workers/other.ts"));
+
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert!(config_file.to_lib_overrides().unwrap().is_empty());
+  }
+
+  #[test]
+  fn vendor_mode() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file =
+      ConfigFile::new(r#"{ "vendor": "only" }"#, &config_specifier).unwrap();
+    assert_eq!(config_file.to_vendor_mode().unwrap(), VendorMode::Only);
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert_eq!(config_file.to_vendor_mode().unwrap(), VendorMode::Ignore);
+    let config_file =
+      ConfigFile::new(r#"{ "vendor": true }"#, &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_vendor_mode().err().unwrap().to_string(),
+      "Failed to parse \"vendor\" configuration",
+    );
+  }
+
+  #[test]
+  fn watch_config() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "watch": {
+          "include": ["templates/**/*.html"],
+          "exclude": [".git"],
+          "debounce": 500,
+          "beforeRestart": "codegen"
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_watch_config().unwrap(),
+      WatchConfig {
+        include: vec!["templates/**/*.html".to_string()],
+        exclude: vec![".git".to_string()],
+        debounce: Some(500),
+        before_restart: Some("codegen".to_string()),
+      }
+    );
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_watch_config().unwrap(),
+      WatchConfig::default()
+    );
+    let config_file = ConfigFile::new(
+      r#"{ "watch": { "paths": ["static"] } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_watch_config().err().unwrap().to_string(),
+      "Failed to parse \"watch\" configuration",
+    );
+  }
+
+  #[test]
+  fn type_acquisition() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert!(config_file.to_type_acquisition().unwrap());
+    let config_file =
+      ConfigFile::new(r#"{ "typeAcquisition": false }"#, &config_specifier)
+        .unwrap();
+    assert!(!config_file.to_type_acquisition().unwrap());
+    let config_file =
+      ConfigFile::new(r#"{ "typeAcquisition": "off" }"#, &config_specifier)
+        .unwrap();
+    assert_eq!(
+      config_file.to_type_acquisition().err().unwrap().to_string(),
+      "Failed to parse \"typeAcquisition\" configuration",
+    );
+  }
+
+  #[test]
+  fn remote_cache_config() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert_eq!(config_file.to_remote_cache_config().unwrap(), None);
+
+    let config_file = ConfigFile::new(
+      r#"{
+        "remoteCache": {
+          "url": "https://cache.example.com/deno",
+          "readOnly": true,
+          "authTokenEnv": "DENO_REMOTE_CACHE_TOKEN"
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_remote_cache_config().unwrap(),
+      Some(RemoteCacheConfig {
+        url: ModuleSpecifier::parse("https://cache.example.com/deno/").unwrap(),
+        read_only: true,
+        auth_token_env: Some("DENO_REMOTE_CACHE_TOKEN".to_string()),
+        config_dir: ModuleSpecifier::parse("file:///deno/").unwrap(),
+      })
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "remoteCache": { "url": "file:///cache/" } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_remote_cache_config().err().unwrap().to_string(),
+      "The URL \"file:///cache/\" in \"remoteCache\" configuration must be an http or https URL.",
+    );
+  }
+
+  #[test]
+  fn preload_imports() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert!(config_file.to_preload_imports().unwrap().is_empty());
+
+    let config_file = ConfigFile::new(
+      r#"{ "preload": ["./instrument.ts", "npm:dotenv/config"] }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_preload_imports().unwrap(),
+      vec![
+        "./instrument.ts".to_string(),
+        "npm:dotenv/config".to_string()
+      ],
+    );
+
+    let config_file =
+      ConfigFile::new(r#"{ "preload": "./instrument.ts" }"#, &config_specifier)
+        .unwrap();
+    assert_eq!(
+      config_file.to_preload_imports().err().unwrap().to_string(),
+      "Failed to parse \"preload\" configuration",
+    );
+  }
+
+  #[test]
+  fn licenses_config() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{ "licenses": { "allow": ["MIT", "Apache-2.0"] } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_licenses_config().unwrap(),
+      LicensesConfig {
+        allow: Some(vec!["MIT".to_string(), "Apache-2.0".to_string()]),
+      }
+    );
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert_eq!(config_file.to_licenses_config().unwrap().allow, None);
+    let config_file = ConfigFile::new(
+      r#"{ "licenses": { "deny": ["GPL-3.0"] } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_licenses_config().err().unwrap().to_string(),
+      "Failed to parse \"licenses\" configuration",
+    );
+  }
+
+  #[test]
+  fn cycles_config() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "cycles": {
+          "./": "warn",
+          "src/": "error",
+          "src/legacy/": "ignore",
+          "src/**/*.test.ts": "warn"
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let cycles_config = config_file.to_cycles_config().unwrap();
+    let policy =
+      |path: &str| cycles_config.policy(&ModuleSpecifier::parse(path).unwrap());
+    assert_eq!(policy("file:///deno/main.ts"), CyclePolicy::Warn);
+    assert_eq!(policy("file:///deno/src/a.ts"), CyclePolicy::Error);
+    assert_eq!(policy("file:///deno/src/legacy/a.ts"), CyclePolicy::Ignore);
+    assert_eq!(policy("file:///deno/src/a/b.test.ts"), CyclePolicy::Warn);
+    assert_eq!(policy("file:///deno/srcs/a.ts"), CyclePolicy::Warn);
+    assert_eq!(policy("file:///other/a.ts"), CyclePolicy::Ignore);
+    assert_eq!(
+      cycles_config.cycle_policy(&[
+        ModuleSpecifier::parse("file:///deno/src/legacy/a.ts").unwrap(),
+        ModuleSpecifier::parse("file:///deno/src/b.ts").unwrap(),
+      ]),
+      CyclePolicy::Error
+    );
+
+    let config_file = ConfigFile::new("{}", &config_specifier).unwrap();
+    assert!(config_file.to_cycles_config().unwrap().is_empty());
+    let config_file =
+      ConfigFile::new(r#"{ "cycles": { "src/": "fail" } }"#, &config_specifier)
+        .unwrap();
+    assert_eq!(
+      config_file.to_cycles_config().err().unwrap().to_string(),
+      "Failed to parse \"cycles\" configuration",
+    );
+  }
+
+  #[test]
+  fn fmt_overrides() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "fmt": {
+          "options": { "lineWidth": 100, "singleQuote": true },
+          "overrides": [
+            { "files": ["./gen/"], "options": { "lineWidth": 120 } },
+            { "files": ["./gen/api.ts"], "options": { "semiColons": false } }
+          ]
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let fmt_config = config_file.to_fmt_config().unwrap().unwrap();
+    assert_eq!(
+      fmt_config.overrides[0].files,
+      vec![PathBuf::from("/deno/gen/")]
+    );
+    assert!(fmt_config.overrides[1].matches(Path::new("/deno/gen/api.ts")));
+    assert!(!fmt_config.overrides[0].matches(Path::new("/deno/main.ts")));
+
+    let mut options = fmt_config.options.clone();
+    options.merge(&fmt_config.overrides[0].options);
+    options.merge(&fmt_config.overrides[1].options);
+    assert_eq!(
+      options,
+      FmtOptionsConfig {
+        line_width: Some(120),
+        single_quote: Some(true),
+        semi_colons: Some(false),
+        ..Default::default()
+      }
+    );
+  }
+
+  #[test]
+  fn lint_severity() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "lint": {
+          "severity": { "no-explicit-any": "warn", "no-console": "error" },
+          "overrides": [
+            { "files": ["./scripts/"], "severity": { "no-console": "off" } },
+            { "files": ["./scripts/ci.ts"], "severity": { "no-console": "warn" } }
+          ],
+          "failOn": "warn"
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let lint_config = config_file.to_lint_config().unwrap().unwrap();
+    assert_eq!(lint_config.fail_on, LintFailOn::Warn);
+    let severity = lint_config.severity;
+    assert_eq!(
+      severity.overrides[0].files,
+      vec![PathBuf::from("/deno/scripts/")]
+    );
+    let main = Path::new("/deno/main.ts");
+    let script = Path::new("/deno/scripts/build.ts");
+    let ci_script = Path::new("/deno/scripts/ci.ts");
+    assert_eq!(
+      severity.resolve(main, "no-console"),
+      Some(LintSeverity::Error)
+    );
+    assert_eq!(
+      severity.resolve(script, "no-console"),
+      Some(LintSeverity::Off)
+    );
+    assert_eq!(
+      severity.resolve(ci_script, "no-console"),
+      Some(LintSeverity::Warn)
+    );
+    assert_eq!(
+      severity.resolve(script, "no-explicit-any"),
+      Some(LintSeverity::Warn)
+    );
+    assert_eq!(severity.resolve(main, "no-var"), None);
+    assert_eq!(
+      severity.enabled_codes(),
+      HashSet::from(["no-console", "no-explicit-any"])
+    );
+    assert!(!LintFailOn::Error.fails(LintSeverity::Warn));
+    assert!(LintFailOn::Warn.fails(LintSeverity::Warn));
+    assert!(!LintFailOn::Never.fails(LintSeverity::Error));
+
+    let config_file = ConfigFile::new(
+      r#"{ "lint": { "severity": { "no-console": "info" } } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert!(config_file.to_lint_config().is_err());
+  }
+
+  #[test]
+  fn worker_permissions() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "workerPermissions": {
+          "./plugins/": "none",
+          "./workers/fetcher.ts": { "net": ["example.com"] }
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let worker_permissions = config_file.to_worker_permissions().unwrap();
+    assert_eq!(
+      worker_permissions,
+      vec![
+        WorkerPermissions {
+          specifier: ModuleSpecifier::parse("file:///deno/plugins/").unwrap(),
+          permissions: ChildPermissionsArg::none(),
+        },
+        WorkerPermissions {
+          specifier: ModuleSpecifier::parse("file:///deno/workers/fetcher.ts")
+            .unwrap(),
+          permissions: serde_json::from_value(
+            json!({ "net": ["example.com"] })
+          )
+          .unwrap(),
+        },
+      ]
+    );
+    assert!(worker_permissions[0].matches(
+      &ModuleSpecifier::parse("file:///deno/plugins/a/mod.ts").unwrap()
+    ));
+    assert!(!worker_permissions[1].matches(
+      &ModuleSpecifier::parse("file:///deno/workers/other.ts").unwrap()
+    ));
+
+    let config_file = ConfigFile::new(
+      r#"{ "workerPermissions": { "./plugins/": "some" } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_worker_permissions().err().unwrap().to_string(),
+      "Failed to parse the permissions of \"./plugins/\" in \"workerPermissions\" configuration",
+    );
+  }
+
+  #[test]
+  fn check_units() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{
+        "checkUnits": {
+          "app": { "include": ["./app/", "./main.ts"], "dependencies": ["core", "ui"] },
+          "core": { "include": ["./core/"] },
+          "ui": { "include": ["./ui/"], "dependencies": ["core"] }
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    let units = config_file.to_check_units().unwrap();
+    assert_eq!(
+      units
+        .iter()
+        .map(|unit| unit.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["core", "ui", "app"]
+    );
+    assert_eq!(
+      units[2].include,
+      vec![
+        ModuleSpecifier::parse("file:///deno/app/").unwrap(),
+        ModuleSpecifier::parse("file:///deno/main.ts").unwrap(),
+      ]
+    );
+    let match_len = |index: usize, specifier: &str| {
+      units[index].match_len(&ModuleSpecifier::parse(specifier).unwrap())
+    };
+    assert_eq!(match_len(0, "file:///deno/core/mod.ts"), Some(18));
+    assert_eq!(match_len(2, "file:///deno/main.ts"), Some(20));
+    assert_eq!(match_len(2, "file:///deno/core/mod.ts"), None);
+
+    let config_file = ConfigFile::new(
+      r#"{
+        "checkUnits": {
+          "a": { "dependencies": ["b"] },
+          "b": { "dependencies": ["a"] }
+        }
+      }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_check_units().err().unwrap().to_string(),
+      "The check units depend on each other in a cycle: a -> b -> a"
+    );
+
+    let config_file = ConfigFile::new(
+      r#"{ "checkUnits": { "a": { "dependencies": ["c"] } } }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file.to_check_units().err().unwrap().to_string(),
+      "The check unit \"a\" depends on the unknown unit \"c\"."
+    );
+  }
+
+  #[test]
+  fn referenced_check_units() {
+    let temp_dir = test_util::TempDir::new();
+    temp_dir.write(
+      "deno.json",
+      r#"{
+        "references": [{ "path": "./packages/app" }],
+        "checkUnits": { "scripts": { "include": ["./scripts/"] } }
+      }"#,
+    );
+    temp_dir.create_dir_all("packages/app");
+    temp_dir.create_dir_all("packages/core");
+    temp_dir.write(
+      "packages/app/deno.json",
+      r#"{
+        "compilerOptions": { "strict": false },
+        "references": [{ "path": "../core/deno.jsonc" }]
+      }"#,
+    );
+    temp_dir.write("packages/core/deno.jsonc", "{}");
+
+    let config_file =
+      ConfigFile::read(&temp_dir.path().join("deno.json")).unwrap();
+    let root_dir = specifier_parent(&config_file.specifier);
+    let units = config_file.to_check_units().unwrap();
+    assert_eq!(
+      units
+        .iter()
+        .map(|unit| unit.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["packages/core", "packages/app", "scripts"]
+    );
+    assert_eq!(
+      units[1].include,
+      vec![root_dir.join("packages/app/").unwrap()]
+    );
+    assert_eq!(units[1].dependencies, vec!["packages/core".to_string()]);
+    let project = units[1].maybe_project.as_ref().unwrap();
+    assert_eq!(
+      project.config_specifier,
+      root_dir.join("packages/app/deno.json").unwrap()
+    );
+    assert_eq!(project.compiler_options, json!({ "strict": false }));
+    assert!(units[2].maybe_project.is_none());
+
+    temp_dir.write(
+      "packages/core/deno.jsonc",
+      r#"{ "references": [{ "path": "../app" }] }"#,
+    );
+    assert_eq!(
+      config_file.to_check_units().err().unwrap().to_string(),
+      "The check units depend on each other in a cycle: packages/app -> packages/core -> packages/app"
+    );
+
+    temp_dir.write(
+      "deno.json",
+      r#"{ "references": [{ "path": "./packages/other" }] }"#,
+    );
+    let config_file =
+      ConfigFile::read(&temp_dir.path().join("deno.json")).unwrap();
+    assert!(config_file
+      .to_check_units()
+      .err()
+      .unwrap()
+      .to_string()
+      .starts_with("Could not find the config file"));
+  }
+
+  #[test]
+  fn url_rewrite_rules() {
+    let config_text = r#"{
+      "urlRewrites": [
+        { "prefix": "https://deno.land/", "to": "https://mirror.internal/deno/" },
+        { "regex": "^https://esm\\.sh/(.+)$", "to": "https://mirror.internal/esm/$1" }
+      ]
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let rules = config_file.to_url_rewrite_rules().unwrap();
+    let rewrite = |url: &str| {
+      rules
+        .rewrite(&ModuleSpecifier::parse(url).unwrap())
+        .unwrap()
+        .map(|s| s.to_string())
+    };
+    assert_eq!(
+      rewrite("https://deno.land/std/mod.ts").as_deref(),
+      Some("https://mirror.internal/deno/std/mod.ts"),
+    );
+    assert_eq!(
+      rewrite("https://esm.sh/preact@10").as_deref(),
+      Some("https://mirror.internal/esm/preact@10"),
+    );
+    assert_eq!(rewrite("https://example.com/mod.ts"), None);
+  }
+
+  #[test]
+  fn profile_config() {
+    let config_text = r#"{
+      "profiles": {
+        "dev": { "define": { "DEBUG": "true" }, "permissions": "local" },
+        "prod": {
+          "define": { "DEBUG": "false", "process.env.NODE_ENV": "\"production\"" },
+          "minify": true,
+          "compilerOptions": { "noUnusedLocals": true }
+        }
+      }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    assert_eq!(
+      config_file.to_profile_config("dev").unwrap(),
+      ProfileConfig {
+        define: BTreeMap::from([("DEBUG".to_string(), "true".to_string())]),
+        minify: false,
+        compiler_options: None,
+        permissions: Some("local".to_string()),
+      }
+    );
+    assert_eq!(
+      config_file.to_profile_config("prod").unwrap(),
+      ProfileConfig {
+        define: BTreeMap::from([
+          ("DEBUG".to_string(), "false".to_string()),
+          (
+            "process.env.NODE_ENV".to_string(),
+            "\"production\"".to_string()
+          ),
+        ]),
+        minify: true,
+        compiler_options: Some(HashMap::from([(
+          "noUnusedLocals".to_string(),
+          json!(true)
+        )])),
+        permissions: None,
+      }
+    );
+    assert_eq!(
+      config_file
+        .to_profile_config("staging")
+        .err()
+        .unwrap()
+        .to_string(),
+      concat!(
+        "Profile \"staging\" was not found in the configuration file. ",
+        "Available profiles: dev, prod\n  Config: file:///deno/deno.json",
+      ),
+    );
+  }
+
+  #[test]
+  fn permission_set() {
+    let config_text = r#"{
+      "permissions": {
+        "dev": {
+          "read": ["./data"],
+          "net": ["deno.land", "localhost:8000"],
+          "env": true,
+          "hrtime": true
+        },
+        "ci": { "run": ["git"], "deny": { "run": ["curl"] } },
+        "web-server": {
+          "net": true,
+          "read": true,
+          "deny": { "read": ["./secrets"], "net": ["localhost:5432"] }
+        }
+      }
+    }"#;
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, &config_specifier).unwrap();
+    let permission_set = config_file.to_permission_set("dev").unwrap();
+    assert_eq!(
+      permission_set.net,
+      Some(PermissionSetValue::List(vec![
+        "deno.land".to_string(),
+        "localhost:8000".to_string()
+      ]))
+    );
+    assert_eq!(permission_set.env, Some(PermissionSetValue::All(true)));
+    assert!(permission_set.hrtime);
+    assert_eq!(permission_set.write, None);
+
+    let mut flags = Flags {
+      allow_net: Some(vec!["example.com".to_string()]),
+      allow_write: Some(vec![]),
+      ..Default::default()
+    };
+    permission_set.grant(&mut flags);
+    assert_eq!(
+      flags.allow_net,
+      Some(vec![
+        "example.com".to_string(),
+        "deno.land".to_string(),
+        "localhost:8000".to_string()
+      ])
+    );
+    assert_eq!(flags.allow_env, Some(vec![]));
+    assert_eq!(flags.allow_write, Some(vec![]));
+    assert_eq!(flags.allow_run, None);
+    assert_eq!(flags.allow_read.unwrap().len(), 1);
+    assert!(flags.allow_hrtime);
+
+    assert_eq!(
+      config_file
+        .to_permission_set("prod")
+        .err()
+        .unwrap()
+        .to_string(),
+      concat!(
+        "Permission set \"prod\" was not found in the configuration file. ",
+        "Available permission sets: ci, dev, web-server\n  Config: file:///deno/deno.json",
+      ),
+    );
+
+    let permission_set = config_file.to_permission_set("web-server").unwrap();
+    let deny_rules = permission_set.deny.to_deny_rules();
+    assert_eq!(deny_rules.read.len(), 1);
+    assert!(deny_rules.read[0].ends_with("deno/secrets"));
+    assert_eq!(deny_rules.net, vec!["localhost:5432".to_string()]);
+    assert!(permission_set.deny.write.is_empty());
+  }
+
+  #[test]
+  fn url_rewrite_rules_invalid() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(
+      r#"{ "urlRewrites": [{ "prefix": "a", "regex": "b", "to": "c" }] }"#,
+      &config_specifier,
+    )
+    .unwrap();
+    assert_eq!(
+      config_file
+        .to_url_rewrite_rules()
+        .err()
+        .unwrap()
+        .to_string(),
+      "URL rewrite rules must specify exactly one of \"prefix\" or \"regex\"",
+    );
+  }
+
+  #[test]
+  fn package_metadata() {
+    let config_specifier =
+      ModuleSpecifier::parse("file:///deno/deno.json").unwrap();
+    let to_package_metadata = |config_text: &str| {
+      ConfigFile::new(config_text, &config_specifier)
+        .unwrap()
+        .to_package_metadata()
+    };
+    assert_eq!(
+      to_package_metadata(
+        r#"{ "name": "@scope/package", "version": "1.2.3" }"#
+      )
+      .unwrap(),
+      Some(PackageMetadata {
+        name: "@scope/package".to_string(),
+        version: "1.2.3".to_string(),
+      })
+    );
+    assert_eq!(to_package_metadata("{}").unwrap(), None);
+    assert_eq!(
+      to_package_metadata(r#"{ "name": "package" }"#)
+        .err()
+        .unwrap()
+        .to_string(),
+      "The \"name\" and \"version\" configuration must be set together.",
+    );
+    assert_eq!(
+      to_package_metadata(r#"{ "name": "package", "version": "latest" }"#)
+        .err()
+        .unwrap()
+        .to_string(),
+      "The \"version\" configuration must be a semantic version, but was \"latest\".",
+    );
+  }
+
   fn run_task_error_test(config_text: &str, expected_error: &str) {
     let config_dir = ModuleSpecifier::parse("file:///deno/").unwrap();
     let config_specifier = config_dir.join("tsconfig.json").unwrap();
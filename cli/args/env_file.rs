@@ -0,0 +1,69 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+
+/// Loads the variables of the `--env-file` files into the environment of the
+/// process, so that they are visible to the code that runs and the tasks and
+/// subprocesses it spawns. Variables that were already set are kept, and the
+/// variables of later files override the ones of earlier files.
+pub fn load_env_files(env_files: &[PathBuf]) -> Result<(), AnyError> {
+  if env_files.is_empty() {
+    return Ok(());
+  }
+  let existing_vars = std::env::vars_os()
+    .map(|(key, _)| key)
+    .collect::<HashSet<_>>();
+  for env_file in env_files {
+    let context = || format!("Failed loading env file {}", env_file.display());
+    // the values are expanded with the variables set by the previous files
+    for item in dotenv::from_path_iter(env_file).with_context(context)? {
+      let (key, value) = item.with_context(context)?;
+      if !existing_vars.contains(std::ffi::OsStr::new(&key)) {
+        std::env::set_var(key, value);
+      }
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  #[test]
+  fn loads_env_files_in_order() {
+    let temp_dir = TempDir::new();
+    temp_dir.write(
+      ".env",
+      "ENV_FILE_TEST_HOST=localhost\nENV_FILE_TEST_PORT=8000\n",
+    );
+    temp_dir.write(
+      ".env.local",
+      concat!(
+        "ENV_FILE_TEST_PORT=9000\n",
+        "ENV_FILE_TEST_URL=http://${ENV_FILE_TEST_HOST}:${ENV_FILE_TEST_PORT}\n",
+        "ENV_FILE_TEST_EXISTING=overridden\n",
+      ),
+    );
+    std::env::set_var("ENV_FILE_TEST_EXISTING", "kept");
+    load_env_files(&[
+      temp_dir.path().join(".env"),
+      temp_dir.path().join(".env.local"),
+    ])
+    .unwrap();
+    assert_eq!(std::env::var("ENV_FILE_TEST_HOST").unwrap(), "localhost");
+    assert_eq!(std::env::var("ENV_FILE_TEST_PORT").unwrap(), "9000");
+    assert_eq!(
+      std::env::var("ENV_FILE_TEST_URL").unwrap(),
+      "http://localhost:9000"
+    );
+    assert_eq!(std::env::var("ENV_FILE_TEST_EXISTING").unwrap(), "kept");
+
+    assert!(load_env_files(&[temp_dir.path().join("missing.env")]).is_err());
+  }
+}
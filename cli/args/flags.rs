@@ -12,7 +12,9 @@ use deno_core::url::Url;
 use deno_runtime::permissions::parse_sys_kind;
 use log::debug;
 use log::Level;
+use log::LevelFilter;
 use std::env;
+use std::fmt;
 use std::net::SocketAddr;
 use std::num::NonZeroU32;
 use std::num::NonZeroU8;
@@ -22,6 +24,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 use crate::util::fs::canonicalize_path;
+use crate::util::logger::LogFormat;
 
 use super::flags_allow_net;
 
@@ -31,12 +34,20 @@ pub struct FileFlags {
   pub include: Vec<PathBuf>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AddFlags {
+  pub packages: Vec<String>,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct BenchFlags {
   pub files: FileFlags,
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub save_baseline: Option<String>,
+  pub baseline: Option<String>,
+  pub regression_threshold: Option<u32>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -48,11 +59,31 @@ pub struct BundleFlags {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CacheFlags {
   pub files: Vec<String>,
+  pub infer_types: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CheckFlags {
   pub files: Vec<String>,
+  pub all_errors: bool,
+  pub declarations_out_dir: Option<PathBuf>,
+  /// Report the exports of the local modules that no module imports.
+  pub unused: bool,
+  pub json: bool,
+}
+
+/// The caches deleted by `deno clean`, which are all of them when none of
+/// the scopes is provided.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CleanFlags {
+  /// Delete the emitted code of modules.
+  pub emits: bool,
+  /// Delete the downloaded npm packages.
+  pub npm: bool,
+  /// Delete the remote modules and emits of these origins.
+  pub origins: Vec<String>,
+  /// Show what would be deleted and its size without deleting it.
+  pub dry_run: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -62,7 +93,51 @@ pub struct CompileFlags {
   pub args: Vec<String>,
   pub target: Option<String>,
   pub include: Vec<String>,
-}
+  pub snapshot: Vec<String>,
+  pub icon: Option<PathBuf>,
+  /// The `KEY=VALUE` pairs of `--metadata`, like the product name and
+  /// version of the executable.
+  pub metadata: Vec<(String, String)>,
+  /// The identity to sign the executable with, which is empty to let the
+  /// signing tool pick one.
+  pub sign: Option<String>,
+  /// Compile for every target in `COMPILE_TARGETS`, with `output` being the
+  /// directory of the executables.
+  pub all_targets: bool,
+  /// The directories to embed as a read-only file system.
+  pub include_dirs: Vec<PathBuf>,
+  pub self_update: Option<CompileSelfUpdateFlags>,
+}
+
+/// The options of `deno compile --update-url`, which lets the executable
+/// update itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompileSelfUpdateFlags {
+  /// The URL of the update manifest.
+  pub url: Url,
+  /// The base64 encoded Ed25519 public key the updates are signed with.
+  pub public_key: String,
+  /// The version of the executable.
+  pub version: String,
+}
+
+/// The targets `deno compile` can cross compile to.
+pub const COMPILE_TARGETS: [&str; 4] = [
+  "x86_64-unknown-linux-gnu",
+  "x86_64-pc-windows-msvc",
+  "x86_64-apple-darwin",
+  "aarch64-apple-darwin",
+];
+
+/// The keys of `deno compile --metadata`.
+pub const COMPILE_METADATA_KEYS: &[&str] = &[
+  "name",
+  "version",
+  "description",
+  "company",
+  "copyright",
+  "identifier",
+];
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct CompletionsFlags {
@@ -78,6 +153,11 @@ pub struct CoverageFlags {
   pub lcov: bool,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CronFlags {
+  pub script: String,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DocSourceFileFlag {
   Builtin,
@@ -96,6 +176,13 @@ pub struct DocFlags {
   pub json: bool,
   pub source_file: DocSourceFileFlag,
   pub filter: Option<String>,
+  pub html: Option<DocHtmlFlag>,
+  pub lint: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DocHtmlFlag {
+  pub output: PathBuf,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -114,6 +201,7 @@ pub struct FmtFlags {
   pub single_quote: Option<bool>,
   pub prose_wrap: Option<String>,
   pub no_semicolons: Option<bool>,
+  pub organize_imports: Option<bool>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -125,6 +213,24 @@ pub struct InitFlags {
 pub struct InfoFlags {
   pub json: bool,
   pub file: Option<String>,
+  pub cache_status: bool,
+  pub diff: Option<PathBuf>,
+  pub sbom: Option<SbomFormat>,
+  /// Show the import cycles between the local modules.
+  pub cycles: bool,
+}
+
+/// The format of the software bill of materials written by `deno info --sbom`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SbomFormat {
+  CycloneDx,
+  Spdx,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LicenseFlags {
+  pub files: Vec<String>,
+  pub json: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -145,6 +251,8 @@ pub struct UninstallFlags {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct LintFlags {
   pub files: FileFlags,
+  /// Apply the automatic fixes of the diagnostics to the files.
+  pub fix: bool,
   pub rules: bool,
   pub maybe_rules_tags: Option<Vec<String>>,
   pub maybe_rules_include: Option<Vec<String>>,
@@ -153,6 +261,50 @@ pub struct LintFlags {
   pub compact: bool,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockFlags {
+  pub subcommand: LockSubcommand,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LockSubcommand {
+  Diff(LockDiffFlags),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LockDiffFlags {
+  pub old: PathBuf,
+  pub new: PathBuf,
+  pub json: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutdatedFlags {
+  pub json: bool,
+  pub update: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackFlags {
+  pub entrypoint: String,
+  pub out_dir: PathBuf,
+  pub name: String,
+  pub version: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublishFlags {
+  pub entrypoint: String,
+  pub registry: String,
+  pub token: Option<String>,
+  pub dry_run: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoveFlags {
+  pub packages: Vec<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ReplFlags {
   pub eval_files: Option<Vec<String>>,
@@ -160,6 +312,15 @@ pub struct ReplFlags {
   pub is_default_command: bool,
 }
 
+/// `--suggest-permissions[=<NAME>]`, which prints the permission flags that
+/// the program used after it ran.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SuggestPermissionsFlags {
+  /// The permission set of the configuration file to save the permissions
+  /// to.
+  pub permission_set: Option<String>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RunFlags {
   pub script: String,
@@ -171,10 +332,25 @@ impl RunFlags {
   }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ServeFlags {
+  pub script: String,
+  pub host: String,
+  pub port: u16,
+  pub parallel: NonZeroUsize,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TaskFlags {
   pub cwd: Option<String>,
   pub task: Option<String>,
+  pub recursive: Option<TaskRecursiveFlags>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TaskRecursiveFlags {
+  pub filter: Option<String>,
+  pub changed: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -188,15 +364,69 @@ pub struct TestFlags {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: Option<NonZeroUsize>,
   pub trace_ops: bool,
+  pub isolate_pool: bool,
+  pub reporter: Option<String>,
+  pub reporter_output: Option<PathBuf>,
+  pub shard: Option<TestShard>,
+  pub update_snapshots: bool,
+  pub global_setup: Option<String>,
+  pub global_teardown: Option<String>,
+  pub import_map_overrides: Option<String>,
+}
+
+/// The part of the test modules run by `--shard=<INDEX>/<COUNT>`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TestShard {
+  /// Starts at 1.
+  pub index: usize,
+  pub count: usize,
+}
+
+impl fmt::Display for TestShard {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}/{}", self.index, self.count)
+  }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UpgradeFlags {
   pub dry_run: bool,
   pub force: bool,
-  pub canary: bool,
+  pub channel: UpgradeChannel,
   pub version: Option<String>,
   pub output: Option<PathBuf>,
+  /// The base64 encoded Ed25519 public key that the archive must be signed
+  /// with.
+  pub verify_key: Option<String>,
+}
+
+/// Where `deno upgrade` looks up and downloads releases.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum UpgradeChannel {
+  #[default]
+  Stable,
+  Canary,
+  /// A mirror with the latest version in `latest.txt` and the archives in
+  /// `<version>/deno-<target>.zip`, whose archives must be signed.
+  Custom(Url),
+}
+
+impl FromStr for UpgradeChannel {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "stable" => Ok(Self::Stable),
+      "canary" => Ok(Self::Canary),
+      _ => match Url::parse(s) {
+        Ok(url) if url.scheme() == "https" => Ok(Self::Custom(url)),
+        _ => Err(
+          "expected \"stable\", \"canary\" or the https URL of a channel"
+            .to_string(),
+        ),
+      },
+    }
+  }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -208,24 +438,35 @@ pub struct VendorFlags {
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DenoSubcommand {
+  Add(AddFlags),
   Bench(BenchFlags),
   Bundle(BundleFlags),
   Cache(CacheFlags),
   Check(CheckFlags),
+  Clean(CleanFlags),
   Compile(CompileFlags),
   Completions(CompletionsFlags),
   Coverage(CoverageFlags),
+  Cron(CronFlags),
   Doc(DocFlags),
   Eval(EvalFlags),
   Fmt(FmtFlags),
   Init(InitFlags),
   Info(InfoFlags),
   Install(InstallFlags),
+  InstallDeps,
   Uninstall(UninstallFlags),
   Lsp,
+  License(LicenseFlags),
   Lint(LintFlags),
+  Lock(LockFlags),
+  Outdated(OutdatedFlags),
+  Pack(PackFlags),
+  Publish(PublishFlags),
+  Remove(RemoveFlags),
   Repl(ReplFlags),
   Run(RunFlags),
+  Serve(ServeFlags),
   Task(TaskFlags),
   Test(TestFlags),
   Types,
@@ -261,6 +502,13 @@ impl Default for TypeCheckMode {
   }
 }
 
+/// Where to write the machine readable progress events of `--progress=json`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ProgressFlag {
+  JsonStderr,
+  JsonFile(PathBuf),
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ConfigFlag {
   Discover,
@@ -309,30 +557,82 @@ pub struct Flags {
   pub type_check_mode: TypeCheckMode,
   pub config_flag: ConfigFlag,
   pub node_modules_dir: Option<bool>,
+  pub allow_scripts: Vec<String>,
   pub coverage_dir: Option<String>,
+  pub cpu_prof: bool,
   pub enable_testing_features: bool,
+  /// The `.env` files to load environment variables from before running.
+  pub env_file: Vec<PathBuf>,
   pub ext: Option<String>,
+  pub heap_snapshot_on_exit: bool,
   pub ignore: Vec<PathBuf>,
   pub import_map_path: Option<String>,
   pub inspect_brk: Option<SocketAddr>,
   pub inspect_wait: Option<SocketAddr>,
   pub inspect: Option<SocketAddr>,
+  /// Inspect workers and child deno processes like the main module.
+  pub inspect_children: bool,
+  /// The inspector server that the inspectors are registered with, which is
+  /// the one of the process that spawned this one with `--inspect-children`.
+  pub inspect_parent: Option<SocketAddr>,
   pub location: Option<Url>,
   pub lock_write: bool,
   pub lock: Option<PathBuf>,
   pub log_level: Option<Level>,
+  /// The level overrides of targets of `--log-level`, like
+  /// `module_loader=debug`.
+  pub log_filters: Vec<(String, LevelFilter)>,
+  pub log_format: LogFormat,
+  pub log_timestamps: bool,
+  /// Append the allowed and denied network accesses to this file as newline
+  /// delimited JSON.
+  pub net_audit_log: Option<PathBuf>,
+  pub progress: Option<ProgressFlag>,
+  pub profile: Option<String>,
   pub no_remote: bool,
   pub no_lock: bool,
+  /// Fail instead of updating the lockfile or fetching modules that are not
+  /// in the cache.
+  pub frozen: bool,
   pub no_npm: bool,
+  pub canonicalize_symlinks: bool,
+  pub graph_memory_budget: Option<u64>,
   pub no_prompt: bool,
+  /// Resolve the hosts of `--allow-net` once at startup and connect only to
+  /// those addresses.
+  pub pin_dns: bool,
+  /// The permission set of the configuration file to grant, selected with
+  /// `--permission-set=<NAME>`.
+  pub permission_set: Option<String>,
+  /// The modules that workers use as their entrypoint to prepare before
+  /// running the program, with `--prepare-worker`.
+  pub prepare_workers: Vec<String>,
+  /// The modules to evaluate before the main module, with `--preload` or
+  /// `--import`.
+  pub preload_imports: Vec<String>,
+  /// The directory of `--cpu-prof` and `--heap-snapshot-on-exit`.
+  pub prof_dir: Option<PathBuf>,
   pub reload: bool,
+  /// Revalidate the cached remote modules that are stale according to their
+  /// HTTP caching headers, which is `--reload=stale`.
+  pub reload_stale: bool,
+  pub restrict_local_imports: bool,
+  pub suggest_permissions: Option<SuggestPermissionsFlags>,
+  /// Append every permission check to this file as newline delimited JSON,
+  /// with the stack of the code that made it.
+  pub trace_permissions: Option<PathBuf>,
   pub seed: Option<u64>,
   pub unstable: bool,
+  pub unstable_features: Vec<String>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub v8_flags: Vec<String>,
   pub version: bool,
   pub watch: Option<Vec<PathBuf>>,
   pub no_clear_screen: bool,
+  /// Glob patterns of additional paths that cause a restart in watch mode.
+  pub watch_include: Vec<String>,
+  /// Glob patterns of paths whose changes are ignored in watch mode.
+  pub watch_exclude: Vec<String>,
 }
 
 fn join_paths(allowlist: &[PathBuf], d: &str) -> String {
@@ -449,6 +749,18 @@ impl Flags {
       args.push("--allow-hrtime".to_string());
     }
 
+    if self.restrict_local_imports {
+      args.push("--restrict-local-imports".to_string());
+    }
+
+    if self.pin_dns {
+      args.push("--pin-dns".to_string());
+    }
+
+    if let Some(net_audit_log) = &self.net_audit_log {
+      args.push(format!("--net-audit-log={}", net_audit_log.display()));
+    }
+
     args
   }
 
@@ -463,7 +775,9 @@ impl Flags {
     match &self.subcommand {
       Fmt(FmtFlags { files, .. }) => Some(files.include.clone()),
       Lint(LintFlags { files, .. }) => Some(files.include.clone()),
-      Run(RunFlags { script }) => {
+      Run(RunFlags { script })
+      | Serve(ServeFlags { script, .. })
+      | Cron(CronFlags { script }) => {
         if let Ok(module_specifier) = resolve_url_or_path(script, current_dir) {
           if module_specifier.scheme() == "file"
             || module_specifier.scheme() == "npm"
@@ -505,7 +819,9 @@ impl Flags {
     use DenoSubcommand::*;
 
     match &self.subcommand {
-      Run(RunFlags { script }) => {
+      Run(RunFlags { script })
+      | Serve(ServeFlags { script, .. })
+      | Cron(CronFlags { script }) => {
         let module_specifier = resolve_url_or_path(script, current_dir).ok()?;
         if module_specifier.scheme() == "file" {
           let p = module_specifier
@@ -527,7 +843,8 @@ impl Flags {
           .ok()
       }
       Task(_) | Check(_) | Coverage(_) | Cache(_) | Info(_) | Eval(_)
-      | Test(_) | Bench(_) | Repl(_) => std::env::current_dir().ok(),
+      | Test(_) | Bench(_) | Repl(_) | InstallDeps | Pack(_) | Publish(_)
+      | License(_) => std::env::current_dir().ok(),
       _ => None,
     }
   }
@@ -542,6 +859,7 @@ impl Flags {
       || self.allow_run.is_some()
       || self.allow_sys.is_some()
       || self.allow_write.is_some()
+      || self.permission_set.is_some()
   }
 
   pub fn has_permission_in_argv(&self) -> bool {
@@ -567,6 +885,9 @@ static ENV_VARIABLES_HELP: &str = r#"ENVIRONMENT VARIABLES:
     DENO_TLS_CA_STORE    Comma-separated list of order dependent certificate
                          stores. Possible values: "system", "mozilla".
                          Defaults to "mozilla".
+    DENO_CACHE_BACKEND   Set to "sqlite" to keep remote modules and emits in a
+                         single database file in the cache directory instead
+                         of a file each (faster on network file systems)
     DENO_CERT            Load certificate authority from PEM encoded file
     DENO_DIR             Set the cache directory
     DENO_INSTALL_ROOT    Set deno install's output directory
@@ -579,6 +900,9 @@ static ENV_VARIABLES_HELP: &str = r#"ENVIRONMENT VARIABLES:
                          (alternative to passing --no-prompt on invocation)
     DENO_NO_UPDATE_CHECK Set to disable checking if a newer Deno version is
                          available
+    DENO_PROXY_PAC       Path to a proxy auto-configuration (PAC) file, whose
+                         FindProxyForURL function picks the proxy of module
+                         and npm package downloads
     DENO_V8_FLAGS        Set V8 command line options
     DENO_JOBS            Number of parallel workers used for the --parallel
                          flag with the test subcommand. Defaults to number
@@ -587,10 +911,18 @@ static ENV_VARIABLES_HELP: &str = r#"ENVIRONMENT VARIABLES:
                          (module downloads, fetch)
     HTTPS_PROXY          Proxy address for HTTPS requests
                          (module downloads, fetch)
+    ALL_PROXY            Proxy address for requests without a proxy for
+                         their scheme (module downloads)
     NPM_CONFIG_REGISTRY  URL to use for the npm registry.
     NO_COLOR             Set to disable color
-    NO_PROXY             Comma-separated list of hosts which do not use a proxy
-                         (module downloads, fetch)"#;
+    NO_PROXY             Comma-separated list of hosts which do not use a proxy,
+                         which may be domains with subdomains (.example.com),
+                         IP addresses or CIDR ranges (10.0.0.0/8)
+                         (module downloads, fetch)
+    OTEL_EXPORTER_OTLP_ENDPOINT
+                         OTLP/HTTP endpoint that spans of the CLI phases and
+                         cache counters are exported to as JSON, configured
+                         further with the standard OTEL_* variables"#;
 
 static DENO_HELP: &str = concat!(
   "A modern JavaScript and TypeScript runtime
@@ -625,42 +957,65 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
   if matches.get_flag("unstable") {
     flags.unstable = true;
   }
+  for (name, _) in deno_runtime::UNSTABLE_GRANULAR_FLAGS {
+    if matches.get_flag(&format!("unstable-{name}")) {
+      flags.unstable_features.push(name.to_string());
+    }
+  }
 
+  if let Some((log_level, log_filters)) =
+    matches.get_one::<LogLevelArg>("log-level").cloned()
+  {
+    flags.log_level = log_level;
+    flags.log_filters = log_filters;
+  }
   if matches.get_flag("quiet") {
     flags.log_level = Some(Level::Error);
-  } else if let Some(log_level) = matches.get_one::<String>("log-level") {
-    flags.log_level = match log_level.as_str() {
-      "debug" => Some(Level::Debug),
-      "info" => Some(Level::Info),
-      _ => unreachable!(),
+  }
+  if let Some(log_format) = matches.get_one::<String>("log-format") {
+    flags.log_format = match log_format.as_str() {
+      "json" => LogFormat::Json,
+      _ => LogFormat::Text,
     };
   }
+  flags.log_timestamps = matches.get_flag("log-timestamps");
 
   if let Some((subcommand, mut m)) = matches.remove_subcommand() {
     match subcommand.as_str() {
+      "add" => add_parse(&mut flags, &mut m),
       "bench" => bench_parse(&mut flags, &mut m),
       "bundle" => bundle_parse(&mut flags, &mut m),
       "cache" => cache_parse(&mut flags, &mut m),
       "check" => check_parse(&mut flags, &mut m),
+      "clean" => clean_parse(&mut flags, &mut m),
       "compile" => compile_parse(&mut flags, &mut m),
       "completions" => completions_parse(&mut flags, &mut m, app),
       "coverage" => coverage_parse(&mut flags, &mut m),
+      "cron" => cron_parse(&mut flags, &mut m),
       "doc" => doc_parse(&mut flags, &mut m),
       "eval" => eval_parse(&mut flags, &mut m),
       "fmt" => fmt_parse(&mut flags, &mut m),
       "init" => init_parse(&mut flags, &mut m),
       "info" => info_parse(&mut flags, &mut m),
       "install" => install_parse(&mut flags, &mut m),
+      "license" => license_parse(&mut flags, &mut m),
       "lint" => lint_parse(&mut flags, &mut m),
+      "lock" => lock_parse(&mut flags, &mut m),
+      "outdated" => outdated_parse(&mut flags, &mut m),
+      "pack" => pack_parse(&mut flags, &mut m),
+      "publish" => publish_parse(&mut flags, &mut m),
+      "remove" => remove_parse(&mut flags, &mut m),
       "lsp" => lsp_parse(&mut flags, &mut m),
       "repl" => repl_parse(&mut flags, &mut m),
       "run" => run_parse(&mut flags, &mut m),
+      "serve" => serve_parse(&mut flags, &mut m),
       "task" => task_parse(&mut flags, &mut m),
       "test" => test_parse(&mut flags, &mut m),
       "types" => types_parse(&mut flags, &mut m),
       "uninstall" => uninstall_parse(&mut flags, &mut m),
       "upgrade" => upgrade_parse(&mut flags, &mut m),
       "vendor" => vendor_parse(&mut flags, &mut m),
+      "x" => x_parse(&mut flags, &mut m),
       _ => unreachable!(),
     }
   } else {
@@ -677,6 +1032,19 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
   Ok(flags)
 }
 
+fn unstable_granular_args() -> Vec<Arg> {
+  deno_runtime::UNSTABLE_GRANULAR_FLAGS
+    .iter()
+    .map(|(name, help)| {
+      Arg::new(format!("unstable-{name}"))
+        .long(format!("unstable-{name}"))
+        .help(*help)
+        .action(ArgAction::SetTrue)
+        .global(true)
+    })
+    .collect()
+}
+
 fn handle_repl_flags(flags: &mut Flags, repl_flags: ReplFlags) {
   // If user runs just `deno` binary we enter REPL and allow all permissions.
   if repl_flags.is_default_command {
@@ -719,13 +1087,42 @@ fn clap_root() -> Command {
         .action(ArgAction::SetTrue)
         .global(true),
     )
+    .args(unstable_granular_args())
     .arg(
       Arg::new("log-level")
         .short('L')
         .long("log-level")
         .help("Set log level")
+        .long_help(
+          "Sets the log level, which is debug or info, and overrides the level \
+of the modules of targets with TARGET=LEVEL, separated by commas. A target is \
+a module of the CLI like module_loader or npm, or the module path of a crate \
+like deno_runtime::permissions, and its level is one of off, error, warn, \
+info, debug and trace:
+
+  deno run --log-level=module_loader=debug,npm=info main.ts",
+        )
         .hide(true)
-        .value_parser(["debug", "info"])
+        .value_parser(parse_log_level_arg)
+        .global(true),
+    )
+    .arg(
+      Arg::new("log-format")
+        .long("log-format")
+        .help("Set the format of the log output")
+        .long_help(
+          "Sets the format of the log output. The json format writes one \
+object per line with the timestamp, level, target, line and message of the \
+record.",
+        )
+        .value_parser(["text", "json"])
+        .global(true),
+    )
+    .arg(
+      Arg::new("log-timestamps")
+        .long("log-timestamps")
+        .help("Prefix the log output with timestamps")
+        .action(ArgAction::SetTrue)
         .global(true),
     )
     .arg(
@@ -736,13 +1133,16 @@ fn clap_root() -> Command {
         .action(ArgAction::SetTrue)
         .global(true),
     )
+    .subcommand(add_subcommand())
     .subcommand(bench_subcommand())
     .subcommand(bundle_subcommand())
     .subcommand(cache_subcommand())
     .subcommand(check_subcommand())
+    .subcommand(clean_subcommand())
     .subcommand(compile_subcommand())
     .subcommand(completions_subcommand())
     .subcommand(coverage_subcommand())
+    .subcommand(cron_subcommand())
     .subcommand(doc_subcommand())
     .subcommand(eval_subcommand())
     .subcommand(fmt_subcommand())
@@ -751,20 +1151,61 @@ fn clap_root() -> Command {
     .subcommand(install_subcommand())
     .subcommand(uninstall_subcommand())
     .subcommand(lsp_subcommand())
+    .subcommand(license_subcommand())
     .subcommand(lint_subcommand())
+    .subcommand(lock_subcommand())
+    .subcommand(outdated_subcommand())
+    .subcommand(pack_subcommand())
+    .subcommand(publish_subcommand())
+    .subcommand(remove_subcommand())
     .subcommand(repl_subcommand())
     .subcommand(run_subcommand())
+    .subcommand(serve_subcommand())
     .subcommand(task_subcommand())
     .subcommand(test_subcommand())
     .subcommand(types_subcommand())
     .subcommand(upgrade_subcommand())
     .subcommand(vendor_subcommand())
+    .subcommand(x_subcommand())
     .long_about(DENO_HELP)
     .after_help(ENV_VARIABLES_HELP)
 }
 
+fn add_subcommand() -> Command {
+  Command::new("add")
+    .about("Add dependencies to the configuration file")
+    .long_about(
+      "Add dependencies to the \"imports\" of the configuration file.
+
+The latest version of the package that satisfies the provided version
+requirement is resolved from its registry and the lockfile is updated:
+
+  deno add npm:express@^4
+  deno add npm:chalk
+  deno add std/http
+  deno add x/oak@v12.5.0
+
+A deno.json file is created in the current directory when none is found.",
+    )
+    .arg(
+      Arg::new("packages")
+        .num_args(1..)
+        .action(ArgAction::Append)
+        .required(true),
+    )
+    .arg(no_config_arg())
+    .arg(config_arg())
+    .arg(lock_arg())
+    .arg(no_lock_arg())
+    .arg(local_npm_arg())
+    .arg(allow_scripts_arg())
+    .arg(ca_file_arg())
+}
+
 fn bench_subcommand() -> Command {
   runtime_args(Command::new("bench"), true, false)
+    .arg(env_file_arg())
+    .arg(import_arg())
     .arg(check_arg(true))
     .arg(
       Arg::new("json")
@@ -800,8 +1241,42 @@ fn bench_subcommand() -> Command {
         .help("Cache bench modules, but don't run benchmarks")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("save-baseline")
+        .long("save-baseline")
+        .value_name("NAME")
+        .require_equals(true)
+        .help("Save the results as a baseline to compare later runs against")
+        .long_help(
+          "Save the results as a baseline to compare later runs against.
+Names ending with '.json' are saved to that file relative to the current
+directory, other names are saved in DENO_DIR.",
+        ),
+    )
+    .arg(
+      Arg::new("baseline")
+        .long("baseline")
+        .value_name("NAME")
+        .require_equals(true)
+        .help("Compare the results against a baseline saved with --save-baseline"),
+    )
+    .arg(
+      Arg::new("regression-threshold")
+        .long("regression-threshold")
+        .value_name("PERCENT")
+        .require_equals(true)
+        .requires("baseline")
+        .value_parser(value_parser!(u32))
+        .help("Fail when a bench is slower than the baseline by more than this percentage (defaults to 10)")
+        .long_help(
+          "Fail when a bench is significantly slower than the baseline by more
+than this percentage. Defaults to 10.",
+        ),
+    )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
     .arg(script_arg().last(true))
     .about("Run benchmarks")
     .long_about(
@@ -835,6 +1310,8 @@ fn bundle_subcommand() -> Command {
     )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
     .arg(executable_ext_arg())
     .about("Bundle module and dependencies into single file")
     .long_about(
@@ -851,6 +1328,12 @@ If no output file is given, the output is written to standard output:
 fn cache_subcommand() -> Command {
   compile_args(Command::new("cache"))
     .arg(check_arg(false))
+    .arg(
+      Arg::new("infer-types")
+        .long("infer-types")
+        .help("Infer and cache the declarations of remote JavaScript modules that don't provide types")
+        .action(ArgAction::SetTrue)
+    )
     .arg(
       Arg::new("file")
         .num_args(1..)
@@ -867,7 +1350,13 @@ them in the local cache, without running any code:
   deno cache https://deno.land/std/http/file_server.ts
 
 Future runs of this module will trigger no downloads or compilation unless
---reload is specified.",
+--reload is specified.
+
+Infer the declarations of the remote JavaScript modules that have no
+X-TypeScript-Types header, which type checking and the language server then
+use instead of the JavaScript:
+
+  deno cache --infer-types main.ts",
     )
 }
 
@@ -889,6 +1378,41 @@ fn check_subcommand() -> Command {
         .conflicts_with("no-remote")
         .hide(true)
       )
+    .arg(
+      Arg::new("all-errors")
+        .long("all-errors")
+        .help("Report all module resolution and loading errors instead of stopping at the first one")
+        .action(ArgAction::SetTrue)
+    )
+    .arg(
+      Arg::new("emit-declarations")
+        .long("emit-declarations")
+        .help("Write the declaration files and declaration maps of the local modules to the --out-dir directory")
+        .action(ArgAction::SetTrue)
+        .requires("out-dir")
+    )
+    .arg(
+      Arg::new("out-dir")
+        .long("out-dir")
+        .value_name("DIR")
+        .help("Directory to write the declaration files to")
+        .value_parser(value_parser!(PathBuf))
+        .requires("emit-declarations")
+        .value_hint(ValueHint::DirPath),
+    )
+    .arg(
+      Arg::new("unused")
+        .long("unused")
+        .help("Report the exports of the local modules that are not imported by any module")
+        .action(ArgAction::SetTrue)
+    )
+    .arg(
+      Arg::new("json")
+        .long("json")
+        .help("Output the unused exports in JSON format")
+        .action(ArgAction::SetTrue)
+        .requires("unused")
+    )
     .arg(
       Arg::new("file")
         .num_args(1..)
@@ -901,7 +1425,73 @@ fn check_subcommand() -> Command {
 
   deno check https://deno.land/std/http/file_server.ts
 
-Unless --reload is specified, this command will not re-download already cached dependencies.",
+Unless --reload is specified, this command will not re-download already cached dependencies.
+
+Write the declaration files of the local modules, with the imports of remote
+modules and npm packages rewritten, for publishing them:
+
+  deno check --emit-declarations --out-dir=types mod.ts
+
+Report the exports of the local modules that are not imported by any module
+of the graph. The exports of the provided files are their public API, so they
+are never reported:
+
+  deno check --unused mod.ts
+  deno check --unused --json mod.ts",
+    )
+}
+
+fn clean_subcommand() -> Command {
+  Command::new("clean")
+    .about("Delete the caches of the cache directory")
+    .long_about(
+      "Delete the caches of DENO_DIR, which are downloaded again or rebuilt
+when they're needed. All the caches are deleted when no scope is provided:
+
+  deno clean
+
+Only delete the emitted code of modules, or the npm packages:
+
+  deno clean --emits
+  deno clean --npm
+
+Only delete the remote modules of an origin and their emits:
+
+  deno clean --origin=esm.sh
+
+Show what would be deleted and its size, without deleting it:
+
+  deno clean --dry-run",
+    )
+    .arg(
+      Arg::new("emits")
+        .long("emits")
+        .help("Delete the emitted code of modules")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("npm")
+        .long("npm")
+        .help("Delete the downloaded npm packages")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("origin")
+        .long("origin")
+        .help("Delete the remote modules and emits of an origin")
+        .long_help(
+          "Delete the remote modules and emits of an origin, which is a host \
+(ex. esm.sh) for both http and https or a URL (ex. https://deno.land:8080). \
+Can be provided multiple times.",
+        )
+        .value_name("ORIGIN")
+        .action(ArgAction::Append),
+    )
+    .arg(
+      Arg::new("dry-run")
+        .long("dry-run")
+        .help("Show what would be deleted without deleting it")
+        .action(ArgAction::SetTrue),
     )
 }
 
@@ -922,6 +1512,38 @@ fn compile_subcommand() -> Command {
         .action(ArgAction::Append)
         .value_hint(ValueHint::FilePath),
     )
+    .arg(
+      Arg::new("snapshot")
+        .long("snapshot")
+        .help("UNSTABLE: Module to pre-execute into a startup snapshot")
+        .long_help(
+          "Executes a module when compiling and embeds the resulting V8 startup
+    snapshot in the executable, so its startup code doesn't run again. The
+    module runs before the runtime is bootstrapped, so it can't use the Deno
+    namespace, and it must not leave open resources or pending work. This flag
+    can be passed multiple times, to snapshot multiple modules.",
+        )
+        .action(ArgAction::Append)
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("include-dir")
+        .long("include-dir")
+        .value_name("DIR")
+        .help("Embed a directory as a read-only file system")
+        .long_help(
+          "Embeds the files of a directory in the executable, which reads them \
+instead of the disk at the path the directory had when compiling, with \
+Deno.readFile(), node:fs, require(), fetch() of file: URLs, dynamic imports of \
+JavaScript and JSON modules and the other file system APIs. Writing to the \
+directory fails. Reading it requires read permissions like any other file. The \
+links of the directory are only followed when they point inside of it. This \
+flag can be passed multiple times, to embed multiple directories.",
+        )
+        .action(ArgAction::Append)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::DirPath),
+    )
     .arg(
       Arg::new("output")
         .long("output")
@@ -934,12 +1556,115 @@ fn compile_subcommand() -> Command {
       Arg::new("target")
         .long("target")
         .help("Target OS architecture")
-        .value_parser([
-          "x86_64-unknown-linux-gnu",
-          "x86_64-pc-windows-msvc",
-          "x86_64-apple-darwin",
-          "aarch64-apple-darwin",
-        ]),
+        .value_parser(COMPILE_TARGETS),
+    )
+    .arg(
+      Arg::new("all-targets")
+        .long("all-targets")
+        .help("Compile an executable for every target")
+        .long_help(
+          "Compiles an executable for every supported target, named \
+<inferred-name>-<target>. The '--output' flag is the directory of the \
+executables in this mode.",
+        )
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["target", "snapshot", "icon", "metadata", "sign"]),
+    )
+    .arg(
+      Arg::new("icon")
+        .long("icon")
+        .value_name("FILE")
+        .value_parser(value_parser!(PathBuf))
+        .help("Icon of the executable, an .ico file for Windows or an .icns file for macOS")
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("metadata")
+        .long("metadata")
+        .value_name("KEY=VALUE")
+        .help("Metadata of the executable, like --metadata version=1.2.0")
+        .long_help(
+          "Sets the metadata of the executable, which is its version \
+information on Windows and the Info.plist of the app bundle on macOS. The keys \
+are name, version, description, company, copyright and identifier, the bundle \
+identifier on macOS. This flag can be passed multiple times.",
+        )
+        .action(ArgAction::Append)
+        .value_parser(|value: &str| -> Result<(String, String), String> {
+          match value.split_once('=') {
+            Some((key, value)) if COMPILE_METADATA_KEYS.contains(&key) => {
+              Ok((key.to_string(), value.to_string()))
+            }
+            _ => Err(format!(
+              "expected KEY=VALUE where KEY is one of {}",
+              COMPILE_METADATA_KEYS.join(", ")
+            )),
+          }
+        }),
+    )
+    .arg(
+      Arg::new("sign")
+        .long("sign")
+        .value_name("IDENTITY")
+        .num_args(0..=1)
+        .require_equals(true)
+        .default_missing_value("")
+        .help("Sign the executable with signtool on Windows or codesign on macOS")
+        .long_help(
+          "Signs the executable with signtool on Windows or codesign on macOS, \
+which must be run on the platform of the target. The identity is the subject \
+name of the certificate for signtool and the signing identity for codesign. \
+Without an identity, signtool picks the best certificate and codesign signs \
+ad hoc.",
+        ),
+    )
+    .arg(
+      Arg::new("update-url")
+        .long("update-url")
+        .value_name("URL")
+        .value_parser(|value: &str| Url::parse(value).map_err(|e| e.to_string()))
+        .requires_all(["update-key", "app-version"])
+        .help("URL of the update manifest that Deno.selfUpdate() checks")
+        .long_help(
+          "Lets the executable replace itself with newer versions with \
+Deno.selfUpdate(). The URL is a JSON manifest with the latest version and the \
+URL and signature of the executable of every target:
+
+  {
+    \"version\": \"1.3.0\",
+    \"targets\": {
+      \"x86_64-unknown-linux-gnu\": {
+        \"url\": \"app-1.3.0-linux\",
+        \"signature\": \"...\"
+      }
+    }
+  }
+
+The URLs of the executables may be relative to the manifest. The signature is \
+the base64 encoded Ed25519 signature of the version, the target and the hex \
+encoded SHA-256 digest of the executable, separated by newlines:
+
+  printf '1.3.0\\nx86_64-unknown-linux-gnu\\n%s' \\
+    \"$(sha256sum app-1.3.0-linux | cut -d' ' -f1)\" > message.txt
+  openssl pkeyutl -sign -inkey key.pem -rawin -in message.txt | base64 -w0
+
+Updates are only applied when their signature matches '--update-key' and their \
+version is newer than '--app-version'. Fetching them requires net permissions.",
+        ),
+    )
+    .arg(
+      Arg::new("update-key")
+        .long("update-key")
+        .value_name("PUBLIC_KEY")
+        .requires("update-url")
+        .help("Base64 encoded Ed25519 public key that updates are signed with"),
+    )
+    .arg(
+      Arg::new("app-version")
+        .long("app-version")
+        .value_name("VERSION")
+        .requires("update-url")
+        .help("Semver version of the executable, compared to the one of updates"),
     )
     .arg(executable_ext_arg())
     .about("UNSTABLE: Compile the script into a self contained executable")
@@ -961,8 +1686,26 @@ generic name. If the resulting name has an '@...' suffix, strip it.
 
 Cross-compiling to different target architectures is supported using the
 `--target` flag. On the first invocation with deno will download proper
-binary and cache it in $DENO_DIR. The aarch64-apple-darwin target is not
-supported in canary.
+binary and cache it in $DENO_DIR, and verify it against its published
+checksum. The aarch64-apple-darwin target is not supported in canary. With
+`--all-targets`, an executable is compiled for every target:
+
+  deno compile --all-targets --output dist/ main.ts
+
+The icon and metadata of Windows executables are set with rcedit, which must
+be in the PATH. For macOS, an app bundle with an Info.plist is created when
+any of '--icon', '--metadata' or '--sign' is passed, which keeps the executable
+signable:
+
+  deno compile --target=x86_64-pc-windows-msvc --icon=app.ico \\
+    --metadata=name=\"My App\" --metadata=version=1.2.0 --sign main.ts
+
+Executables compiled with '--update-url' can update themselves with
+Deno.selfUpdate(), which verifies the updates with '--update-key':
+
+  deno compile --allow-net=example.com --app-version=1.2.0 \\
+    --update-url=https://example.com/app/manifest.json \\
+    --update-key=MCowBQYDK2VwAyEA... main.ts
 ",
     )
 }
@@ -1080,12 +1823,58 @@ Generate html reports from lcov:
     )
 }
 
-fn doc_subcommand() -> Command {
-  Command::new("doc")
-    .about("Show documentation for a module")
-    .long_about(
-      "Show documentation for a module.
-
+fn cron_subcommand() -> Command {
+  runtime_args(Command::new("cron"), true, true)
+    .arg(env_file_arg())
+    .arg(check_arg(false))
+    .arg(
+      watch_arg(true)
+        .conflicts_with("inspect")
+        .conflicts_with("inspect-wait")
+        .conflicts_with("inspect-brk"),
+    )
+    .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
+    .arg(script_arg().required(true).trailing_var_arg(true))
+    .about("Run the scheduled tasks of a module")
+    .long_about(
+      "Run the scheduled tasks of a module.
+
+The module must default export an object with the schedules, which have a name,
+a cron expression and a handler:
+
+  export default {
+    schedules: [
+      {
+        name: \"cleanup\",
+        schedule: \"*/5 * * * *\",
+        // \"skip\" (default), \"allow\" or \"queue\" a run while the previous
+        // run of the schedule didn't complete
+        overlap: \"queue\",
+        // \"UTC\" (default) or an offset from UTC like \"+09:00\"
+        timezone: \"+09:00\",
+        async handler() {
+          await cleanup();
+        },
+      },
+    ],
+  };
+
+  deno cron --allow-net tasks.ts
+
+The last run of every schedule is kept in DENO_DIR, so a restart doesn't repeat
+a run, and a run that was due while deno cron wasn't running runs at the start.
+The handlers are reloaded when their modules change with --watch.",
+    )
+}
+
+fn doc_subcommand() -> Command {
+  Command::new("doc")
+    .about("Show documentation for a module")
+    .long_about(
+      "Show documentation for a module.
+
 Output documentation to standard output:
 
     deno doc ./path/to/module.ts
@@ -1098,6 +1887,15 @@ Output documentation in JSON format:
 
     deno doc --json ./path/to/module.ts
 
+Generate a static HTML site with search in the ./docs/ directory:
+
+    deno doc --html ./path/to/module.ts
+    deno doc --html --output=./site/ ./path/to/module.ts
+
+Check that exported symbols are documented, exiting with an error if not:
+
+    deno doc --lint ./path/to/module.ts
+
 Target a specific symbol:
 
     deno doc ./path/to/module.ts MyClass.someField
@@ -1109,6 +1907,8 @@ Show documentation for runtime built-ins:
     )
     .arg(import_map_arg())
     .arg(reload_arg())
+    .arg(config_arg())
+    .arg(no_config_arg())
     .arg(lock_arg())
     .arg(no_lock_arg())
     .arg(no_npm_arg())
@@ -1119,6 +1919,33 @@ Show documentation for runtime built-ins:
         .help("Output documentation in JSON format")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("html")
+        .long("html")
+        .help("Output documentation as a static HTML site")
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["json", "filter"]),
+    )
+    .arg(
+      Arg::new("output")
+        .long("output")
+        .value_name("DIR")
+        .help(
+          "Directory to output the HTML documentation to (defaults to ./docs/)",
+        )
+        .requires("html")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::DirPath),
+    )
+    .arg(
+      Arg::new("lint")
+        .long("lint")
+        .help(
+          "Check for missing or malformed documentation of exported symbols",
+        )
+        .action(ArgAction::SetTrue)
+        .conflicts_with_all(["json", "html", "filter"]),
+    )
     .arg(
       Arg::new("private")
         .long("private")
@@ -1140,7 +1967,8 @@ Show documentation for runtime built-ins:
 }
 
 fn eval_subcommand() -> Command {
-  runtime_args(Command::new("eval"), false, true)
+  profile_args(runtime_args(Command::new("eval"), false, true))
+    .arg(env_file_arg())
     .about("Eval script")
     .long_about(
       "Evaluate JavaScript from the command line.
@@ -1240,6 +2068,8 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
     )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
     .arg(
       Arg::new("use-tabs")
         .long("use-tabs")
@@ -1291,6 +2121,16 @@ Ignore formatting a file by adding an ignore comment at the top of the file:
         .require_equals(true)
         .help("Don't use semicolons except where necessary."),
     )
+    .arg(
+      Arg::new("organize-imports")
+        .long("organize-imports")
+        .alias("options-organize-imports")
+        .num_args(0..=1)
+        .value_parser(value_parser!(bool))
+        .default_missing_value("true")
+        .require_equals(true)
+        .help("Remove unused imports and group and sort the imports of JavaScript and TypeScript files."),
+    )
 }
 
 fn init_subcommand() -> Command {
@@ -1318,6 +2158,25 @@ type: JavaScript, TypeScript, or JSON.
 emit: Local path of compiled source code. (TypeScript only.)
 dependencies: Dependency tree of the source file.
 
+Show how long ago remote modules were cached, their HTTP headers and whether
+--reload would likely download a newer version:
+
+  deno info --cache-status https://deno.land/std/http/file_server.ts
+
+Show the dependencies that were added, removed or changed compared to a
+previous lock file, for example one from the main branch:
+
+  deno info --diff old.lock main.ts
+
+Write a software bill of materials of the remote modules and npm packages
+in the CycloneDX or SPDX JSON format:
+
+  deno info --sbom=cyclonedx main.ts
+
+Show the import cycles between the local modules:
+
+  deno info --cycles main.ts
+
 Without any additional arguments, 'deno info' shows:
 
 DENO_DIR: Directory containing Deno-managed files.
@@ -1341,28 +2200,65 @@ TypeScript compiler cache: Subdirectory containing TS compiler output.",
     .arg(config_arg())
     .arg(import_map_arg())
     .arg(local_npm_arg())
+    .arg(allow_scripts_arg())
     .arg(
       Arg::new("json")
         .long("json")
         .help("UNSTABLE: Outputs the information in JSON format")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("cache-status")
+        .long("cache-status")
+        .help("Show the cache age, headers and freshness of remote modules")
+        .requires("file")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("diff")
+        .long("diff")
+        .value_name("OLD_LOCKFILE")
+        .help("Show how the dependencies changed compared to a previous lock file")
+        .requires("file")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("sbom")
+        .long("sbom")
+        .value_name("FORMAT")
+        .require_equals(true)
+        .help("Output a software bill of materials in the given format")
+        .value_parser(["cyclonedx", "spdx"])
+        .requires("file")
+        .conflicts_with_all(["json", "cache-status", "diff"]),
+    )
+    .arg(
+      Arg::new("cycles")
+        .long("cycles")
+        .help("Show the import cycles between the local modules")
+        .requires("file")
+        .conflicts_with("sbom")
+        .action(ArgAction::SetTrue),
+    )
 }
 
 fn install_subcommand() -> Command {
   runtime_args(Command::new("install"), true, true)
-    .arg(Arg::new("cmd").required(true).num_args(1..).value_hint(ValueHint::FilePath))
+    .arg(Arg::new("cmd").num_args(1..).value_hint(ValueHint::FilePath))
     .arg(check_arg(true))
     .arg(
       Arg::new("name")
         .long("name")
         .short('n')
         .help("Executable file name")
+        .requires("cmd")
         .required(false))
     .arg(
       Arg::new("root")
         .long("root")
         .help("Installation root")
+        .requires("cmd")
         .value_parser(value_parser!(PathBuf))
         .value_hint(ValueHint::DirPath))
     .arg(
@@ -1370,8 +2266,9 @@ fn install_subcommand() -> Command {
         .long("force")
         .short('f')
         .help("Forcefully overwrite existing installation")
+        .requires("cmd")
         .action(ArgAction::SetTrue))
-    .about("Install script as an executable")
+    .about("Install script as an executable or the dependencies of the project")
     .long_about(
       "Installs a script as an executable in the installation root's bin directory.
 
@@ -1399,7 +2296,16 @@ The installation root is determined, in order of precedence:
   - DENO_INSTALL_ROOT environment variable
   - $HOME/.deno
 
-These must be added to the path manually if required.")
+These must be added to the path manually if required.
+
+When no script is provided, installs the dependencies of the project instead.
+The remote and npm modules mapped in the \"imports\" of the configuration
+file (or import map) and the dependencies of the package.json are downloaded
+to the cache, the local node_modules directory is set up when enabled and the
+lockfile is written:
+
+  deno install
+  deno install --node-modules-dir")
 }
 
 fn uninstall_subcommand() -> Command {
@@ -1445,6 +2351,44 @@ fn lsp_subcommand() -> Command {
     .long_about(LSP_HELP)
 }
 
+fn license_subcommand() -> Command {
+  compile_args_without_check_args(Command::new("license"))
+    .about("Show the licenses of the dependencies of modules")
+    .long_about(
+      "Show the licenses of the npm packages and remote modules that modules
+depend on:
+
+  deno license main.ts
+
+The license of an npm package is read from the \"license\" field of its
+package.json. The license of remote modules is detected from the LICENSE
+file at the root of the package they are part of (ex.
+https://deno.land/std@0.190.0/LICENSE), or at the root of their origin for
+unversioned URLs.
+
+When the configuration file has a \"licenses\" policy, the command fails
+if a dependency isn't licensed under one of the allowed licenses:
+
+  {
+    \"licenses\": {
+      \"allow\": [\"MIT\", \"Apache-2.0\", \"BSD-3-Clause\"]
+    }
+  }",
+    )
+    .arg(
+      Arg::new("json")
+        .long("json")
+        .help("Output the licenses of the dependencies in JSON format")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("files")
+        .num_args(1..)
+        .required(true)
+        .value_hint(ValueHint::FilePath),
+    )
+}
+
 fn lint_subcommand() -> Command {
   Command::new("lint")
     .about("Lint source files")
@@ -1467,6 +2411,10 @@ List available rules:
 
   deno lint --rules
 
+Fix the problems that can be fixed automatically:
+
+  deno lint --fix
+
 Ignore diagnostics on the next line by preceding it with an ignore comment and
 rule name:
 
@@ -1480,6 +2428,13 @@ Ignore linting a file by adding an ignore comment at the top of the file:
   // deno-lint-ignore-file
 ",
     )
+    .arg(
+      Arg::new("fix")
+        .long("fix")
+        .help("Fix the problems that can be fixed automatically")
+        .action(ArgAction::SetTrue)
+        .conflicts_with("rules"),
+    )
     .arg(
       Arg::new("rules")
         .long("rules")
@@ -1549,10 +2504,207 @@ Ignore linting a file by adding an ignore comment at the top of the file:
     )
     .arg(watch_arg(false))
     .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
+}
+
+fn lock_subcommand() -> Command {
+  Command::new("lock")
+    .about("Inspect lockfiles")
+    .subcommand_required(true)
+    .subcommand(
+      Command::new("diff")
+        .about("Show the dependency changes between two lockfiles")
+        .long_about(
+          "Show the remote modules and npm packages that were added, removed or
+changed between two lockfiles, including their version and integrity changes.
+
+  deno lock diff old.lock deno.lock
+
+A lockfile can also be read from a git commit with <commit>:<path>:
+
+  deno lock diff main:deno.lock deno.lock
+
+Output the changes in JSON format:
+
+  deno lock diff --json old.lock deno.lock",
+        )
+        .arg(
+          Arg::new("old")
+            .required(true)
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+          Arg::new("new")
+            .required(true)
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+        )
+        .arg(
+          Arg::new("json")
+            .long("json")
+            .help("Output the changes in JSON format")
+            .action(ArgAction::SetTrue),
+        ),
+    )
+}
+
+fn outdated_subcommand() -> Command {
+  Command::new("outdated")
+    .about("Show dependencies with newer versions")
+    .long_about(
+      "Show the npm packages and deno.land modules of the import map and the
+lockfile that have newer versions available in their registry.
+
+The wanted version is the latest one satisfying the version requirement of
+the dependency, while the latest version might not satisfy it:
+
+  deno outdated
+
+Update the entries of the import map to the wanted versions, or the latest
+version for pinned deno.land modules, and update the lockfile:
+
+  deno outdated --update",
+    )
+    .arg(
+      Arg::new("json")
+        .long("json")
+        .help("Output the outdated dependencies in JSON format")
+        .conflicts_with("update")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("update")
+        .long("update")
+        .help("Update the import map entries of the outdated dependencies")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(no_config_arg())
+    .arg(config_arg())
+    .arg(import_map_arg())
+    .arg(lock_arg())
+    .arg(no_lock_arg())
+    .arg(ca_file_arg())
+}
+
+fn pack_subcommand() -> Command {
+  compile_args_without_check_args(Command::new("pack"))
+    .about("Turn a module into an npm package")
+    .long_about(
+      "Turn a local module into a package directory that is ready to be
+published to npm.
+
+The module and the local modules it imports are type-checked and emitted as
+ES modules along with their declaration files. The imports of npm specifiers,
+whether direct or mapped by the import map, and of modules of npm CDNs like
+esm.sh become imports of the packages, which are added to the dependencies of
+the generated package.json:
+
+  deno pack --name=my-lib --version=1.0.0 mod.ts
+
+Other remote modules can't be imported by the module.
+
+The package is ESM only: its modules are not emitted as CommonJS, so CommonJS
+modules can't be packed and the package can only be imported, not required.",
+    )
+    .arg(
+      Arg::new("name")
+        .long("name")
+        .help("The name of the package")
+        .required(true),
+    )
+    .arg(
+      Arg::new("version")
+        .long("version")
+        .help("The version of the package")
+        .required(true),
+    )
+    .arg(
+      Arg::new("out-dir")
+        .long("out-dir")
+        .value_name("DIR")
+        .help("The directory to write the package to")
+        .default_value("npm")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::DirPath),
+    )
+    .arg(
+      Arg::new("entrypoint")
+        .required(true)
+        .value_hint(ValueHint::FilePath),
+    )
+}
+
+fn publish_subcommand() -> Command {
+  compile_args_without_check_args(Command::new("publish"))
+    .about("Publish a module to a registry")
+    .long_about(
+      "Type-check a module and publish it with the modules it imports to a
+registry, as the package with the \"name\" and \"version\" of the
+configuration file:
+
+  deno publish --registry=https://registry.example.com/ mod.ts
+
+The modules are bundled into an eszip archive, which is uploaded with a PUT
+request to <registry>/<name>/<version>. The JSON body of the request has the
+\"name\" and \"version\" of the package, its \"entrypoint\", the
+\"modules\" of the archive with the \"integrity\" of their source, the
+\"integrity\" and base64 encoded content of the \"eszip\" archive, and the
+\"provenance\" of the package. The registry responds with 409 when the
+version is already published.
+
+The request is authorized with the bearer token passed with --token, or the
+one configured for the host of the registry in DENO_AUTH_TOKENS.",
+    )
+    .arg(
+      Arg::new("registry")
+        .long("registry")
+        .value_name("URL")
+        .help("The URL of the registry to publish to")
+        .required(true)
+        .value_hint(ValueHint::Url),
+    )
+    .arg(
+      Arg::new("token")
+        .long("token")
+        .help("The token to authorize with the registry"),
+    )
+    .arg(
+      Arg::new("dry-run")
+        .long("dry-run")
+        .help("Print the metadata of the package without publishing it")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("entrypoint")
+        .required(true)
+        .value_hint(ValueHint::FilePath),
+    )
+}
+
+fn remove_subcommand() -> Command {
+  Command::new("remove")
+    .about("Remove dependencies from the configuration file")
+    .long_about(
+      "Remove dependencies from the \"imports\" of the configuration file.
+
+  deno remove express
+  deno remove std/http",
+    )
+    .arg(
+      Arg::new("packages")
+        .num_args(1..)
+        .action(ArgAction::Append)
+        .required(true),
+    )
+    .arg(no_config_arg())
+    .arg(config_arg())
 }
 
 fn repl_subcommand() -> Command {
   runtime_args(Command::new("repl"), true, true)
+    .arg(env_file_arg())
     .about("Read Eval Print Loop")
     .arg(check_arg(false))
     .arg(
@@ -1573,7 +2725,8 @@ fn repl_subcommand() -> Command {
 }
 
 fn run_subcommand() -> Command {
-  runtime_args(Command::new("run"), true, true)
+  profile_args(runtime_args(Command::new("run"), true, true))
+    .arg(env_file_arg())
     .arg(check_arg(false))
     .arg(
       watch_arg(true)
@@ -1582,7 +2735,12 @@ fn run_subcommand() -> Command {
         .conflicts_with("inspect-brk"),
     )
     .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
     .arg(executable_ext_arg())
+    .arg(prepare_worker_arg())
+    .arg(import_arg())
+    .arg(suggest_permissions_arg())
     .arg(
       script_arg()
         .required_unless_present("v8-flags")
@@ -1609,17 +2767,93 @@ Grant permission to read allow-listed files from disk:
 
   deno run --allow-read=/etc https://deno.land/std/http/file_server.ts
 
+Print the permission flags a program used, and save them as the permission
+set \"dev\" of the configuration file:
+
+  deno run -A --suggest-permissions=dev main.ts
+  deno run --permission-set=dev main.ts
+
 Specifying the filename '-' to read the file from stdin.
 
   curl https://deno.land/std/examples/welcome.ts | deno run -",
     )
 }
 
+fn serve_subcommand() -> Command {
+  runtime_args(Command::new("serve"), true, true)
+    .arg(env_file_arg())
+    .arg(import_arg())
+    .arg(check_arg(false))
+    .arg(
+      watch_arg(true)
+        .conflicts_with("inspect")
+        .conflicts_with("inspect-wait")
+        .conflicts_with("inspect-brk"),
+    )
+    .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
+    .arg(
+      Arg::new("port")
+        .long("port")
+        .value_name("PORT")
+        .help("The TCP port to listen on")
+        .default_value("8000")
+        .value_parser(value_parser!(u16)),
+    )
+    .arg(
+      Arg::new("host")
+        .long("host")
+        .value_name("HOSTNAME")
+        .help("The hostname to listen on")
+        .default_value("0.0.0.0"),
+    )
+    .arg(
+      Arg::new("parallel")
+        .long("parallel")
+        .value_name("WORKERS")
+        .help("The number of workers that serve requests")
+        .long_help(
+          "The number of workers that serve requests, each running the module \
+in an isolate of its own on a separate thread. The workers listen on the same \
+port, which is only supported on Linux.",
+        )
+        .default_value("1")
+        .value_parser(value_parser!(NonZeroUsize)),
+    )
+    .arg(script_arg().required(true).trailing_var_arg(true))
+    .about("Serve the fetch handler of a module over HTTP")
+    .long_about(
+      "Serve the fetch handler of a module over HTTP.
+
+The module must default export an object with a fetch method, which is called
+with the request and the connection info of every incoming request:
+
+  export default {
+    fetch(request) {
+      return new Response(\"Hello world\");
+    },
+  };
+
+  deno serve --allow-net server.ts
+
+Listen on another port, and serve requests with four workers:
+
+  deno serve --allow-net --port=3000 --parallel=4 server.ts
+
+The server is restarted when its modules change with --watch, once the requests
+it's handling complete. This command
+enables the unstable HTTP APIs, and the unstable net APIs when the workers
+share the port.",
+    )
+}
+
 fn task_subcommand() -> Command {
   Command::new("task")
     .allow_external_subcommands(true)
     .subcommand_value_name("TASK")
     .arg(config_arg())
+    .arg(env_file_arg())
     .arg(
       Arg::new("cwd")
         .long("cwd")
@@ -1627,16 +2861,50 @@ fn task_subcommand() -> Command {
         .help("Specify the directory to run the task in")
         .value_hint(ValueHint::DirPath),
     )
+    .arg(
+      Arg::new("recursive")
+        .long("recursive")
+        .short('r')
+        .help("Run the task in every member of the workspace")
+        .conflicts_with("cwd")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("filter")
+        .long("filter")
+        .value_name("PATTERN")
+        .help("Only run the task in the workspace members matching the pattern")
+        .requires("recursive"),
+    )
+    .arg(
+      Arg::new("changed")
+        .long("changed")
+        .value_name("REF")
+        .num_args(0..=1)
+        .require_equals(true)
+        .default_missing_value("HEAD")
+        .help("Only run the task in the workspace members with changes since the git ref (defaults to HEAD) and their dependents")
+        .requires("recursive"),
+    )
     .about("Run a task defined in the configuration file")
     .long_about(
       "Run a task defined in the configuration file
 
-  deno task build",
+  deno task build
+
+Run a task in every member of the \"workspace\" of the configuration file,
+with the members that others depend on running first:
+
+  deno task --recursive build
+  deno task -r --filter \"packages/*\" build
+  deno task -r --changed=main test",
     )
 }
 
 fn test_subcommand() -> Command {
   runtime_args(Command::new("test"), true, true)
+    .arg(env_file_arg())
+    .arg(import_arg())
     .arg(check_arg(true))
     .arg(
       Arg::new("ignore")
@@ -1660,6 +2928,46 @@ fn test_subcommand() -> Command {
         .help("Enable tracing of async ops. Useful when debugging leaking ops in test, but impacts test execution time.")
         .action(ArgAction::SetTrue),
     )
+    .arg(
+      Arg::new("isolate-pool")
+        .long("isolate-pool")
+        .help("Reuse isolates across test modules to reduce startup time")
+        .long_help(
+          "Reuse isolates across test modules to reduce startup time. \
+The globals, event listeners, timers, resources and permissions of a test \
+module are reset before the next one runs, while modules imported by several \
+test modules are only evaluated once per isolate. A fresh isolate is created \
+when the globals can't be restored. Ignored when collecting coverage or \
+debugging.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .require_equals(true)
+        .value_parser(["pretty", "json", "junit", "tap"])
+        .help("Select the reporter to use (defaults to pretty)")
+        .long_help(
+          "Select the reporter to use. \"json\" and \"junit\" write a report \
+of the whole run once it completes, while \"tap\" streams the results in the \
+Test Anything Protocol. Defaults to \"pretty\".",
+        ),
+    )
+    .arg(
+      Arg::new("reporter-output")
+        .long("reporter-output")
+        .value_name("PATH")
+        .require_equals(true)
+        .requires("reporter")
+        .value_parser(value_parser!(PathBuf))
+        .help("Write the report to a file instead of stdout")
+        .long_help(
+          "Write the report of --reporter to a file instead of stdout, while \
+the results are still printed to the console.",
+        )
+        .value_hint(ValueHint::FilePath),
+    )
     .arg(
       Arg::new("doc")
         .long("doc")
@@ -1698,8 +3006,89 @@ fn test_subcommand() -> Command {
         .value_parser(value_parser!(u64)),
     )
     .arg(
-      Arg::new("coverage")
-        .long("coverage")
+      Arg::new("update-snapshots")
+        .long("update-snapshots")
+        .help("Create or update the snapshots asserted by the tests")
+        .long_help(
+          "Create or update the snapshots asserted with t.assertSnapshot() \
+instead of comparing against them. The snapshots that are no longer asserted \
+are removed when every test of their module ran and passed.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("global-setup")
+        .long("global-setup")
+        .value_name("MODULE")
+        .require_equals(true)
+        .help("Run the default export of a module before the test modules")
+        .long_help(
+          "Run the default export of MODULE once before the test modules, \
+for example to start a database or a server they share. The JSON \
+serializable value it returns is available to the tests as \
+t.globalContext. Overrides \"test.globalSetup\" of the configuration file.",
+        )
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("global-teardown")
+        .long("global-teardown")
+        .value_name("MODULE")
+        .require_equals(true)
+        .help("Run the default export of a module after the test modules")
+        .long_help(
+          "Run the default export of MODULE once after the test modules, \
+even when they failed, with the value returned by --global-setup. \
+Overrides \"test.globalTeardown\" of the configuration file.",
+        )
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("import-map-overrides")
+        .long("import-map-overrides")
+        .value_name("FILE")
+        .require_equals(true)
+        .help("Substitute modules for the test run with an import map")
+        .long_help(
+          "Substitute modules for the test run, for example to replace \
+./db.ts with ./db.mock.ts. FILE is an import map whose \"imports\" and \
+\"scopes\" map the resolved URLs of modules to the modules used instead, \
+relative to FILE. A module can still import the module it substitutes.",
+        )
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("shard")
+        .long("shard")
+        .value_name("INDEX/COUNT")
+        .require_equals(true)
+        .help("Run only one of COUNT parts of the test modules")
+        .long_help(
+          "Split the test modules into COUNT parts and only run the part \
+INDEX (starting at 1), so that a large test suite can be spread over \
+multiple CI jobs. The modules are split after they are collected, in the \
+order given by --shuffle when used, so every job must use the same seed.",
+        )
+        .value_parser(|value: &str| -> Result<TestShard, String> {
+          let shard = value.split_once('/').and_then(|(index, count)| {
+            Some(TestShard {
+              index: index.parse().ok()?,
+              count: count.parse().ok()?,
+            })
+          });
+          match shard {
+            Some(shard) if shard.index >= 1 && shard.index <= shard.count => {
+              Ok(shard)
+            }
+            _ => Err(
+              "Expected <INDEX>/<COUNT> where 1 <= INDEX <= COUNT".to_string(),
+            ),
+          }
+        }),
+    )
+    .arg(
+      Arg::new("coverage")
+        .long("coverage")
         .require_equals(true)
         .value_name("DIR")
         .conflicts_with("inspect")
@@ -1737,6 +3126,8 @@ fn test_subcommand() -> Command {
         .conflicts_with("coverage"),
     )
     .arg(no_clear_screen_arg())
+    .arg(watch_include_arg())
+    .arg(watch_exclude_arg())
     .arg(script_arg().last(true))
     .about("Run tests")
     .long_about(
@@ -1780,7 +3171,23 @@ and is used to replace the current executable.
 If you want to not replace the current Deno executable but instead download an
 update to a different location, use the --output flag
 
-  deno upgrade --output $HOME/my_deno",
+  deno upgrade --output $HOME/my_deno
+
+Releases are looked up in the stable channel by default. The canary channel
+has a build of every commit, and a custom channel is the https URL of a mirror
+with the latest version in 'latest.txt' and the archives in
+'<version>/deno-<target>.zip', which must be signed:
+
+  deno upgrade --channel canary
+  deno upgrade --channel https://deno.example.com/releases/ --verify-key=...
+
+Archives are verified against their published '.sha256sum' checksum, and
+against their '.sig' Ed25519 signature when '--verify-key' is passed or, for
+the stable and canary channels, with the release key of the executable.
+Archives published without a checksum or a signature, like the ones of older
+releases, are installed with a warning, unless '--verify-key' is passed. Use
+--dry-run to see what would change, including the changelog, without
+replacing the executable.",
     )
     .arg(
       Arg::new("version")
@@ -1797,7 +3204,7 @@ update to a different location, use the --output flag
     .arg(
       Arg::new("dry-run")
         .long("dry-run")
-        .help("Perform all checks without replacing old exe")
+        .help("Perform all checks and report the changes without replacing old exe")
         .action(ArgAction::SetTrue),
     )
     .arg(
@@ -1811,7 +3218,21 @@ update to a different location, use the --output flag
       Arg::new("canary")
         .long("canary")
         .help("Upgrade to canary builds")
-        .action(ArgAction::SetTrue),
+        .action(ArgAction::SetTrue)
+        .conflicts_with("channel"),
+    )
+    .arg(
+      Arg::new("channel")
+        .long("channel")
+        .value_name("CHANNEL")
+        .help("The channel to upgrade from: stable, canary or the URL of a custom channel")
+        .value_parser(value_parser!(UpgradeChannel)),
+    )
+    .arg(
+      Arg::new("verify-key")
+        .long("verify-key")
+        .value_name("PUBLIC_KEY")
+        .help("Base64 encoded Ed25519 public key that the release archive must be signed with"),
     )
     .arg(ca_file_arg())
 }
@@ -1863,6 +3284,38 @@ Remote modules and multiple modules may also be specified:
     .arg(ca_file_arg())
 }
 
+fn x_subcommand() -> Command {
+  runtime_args(Command::new("x"), false, false)
+    .arg(check_arg(false))
+    .arg(
+      Arg::new("package_arg")
+        .num_args(1..)
+        .action(ArgAction::Append)
+        .required(true)
+        .trailing_var_arg(true)
+        .help("The npm package to run, optionally followed by the name of one of its binaries and the arguments to pass to it")
+        .value_name("PACKAGE")
+        .value_hint(ValueHint::Other),
+    )
+    .about("Run a binary of an npm package")
+    .long_about(
+      "Run a binary of an npm package, similar to npx.
+
+The package is downloaded to the npm cache and run with all permissions,
+without installing it or creating a shim for it:
+
+  deno x cowsay hello
+
+A binary other than the one named after the package is selected by its name:
+
+  deno x typescript/tsc --version
+
+The arguments after -- are passed to the binary as is:
+
+  deno x prettier@2 -- --check .",
+    )
+}
+
 fn compile_args(app: Command) -> Command {
   compile_args_without_check_args(app.arg(no_check_arg()))
 }
@@ -1873,12 +3326,18 @@ fn compile_args_without_check_args(app: Command) -> Command {
     .arg(no_remote_arg())
     .arg(no_npm_arg())
     .arg(local_npm_arg())
+    .arg(allow_scripts_arg())
+    .arg(canonicalize_symlinks_arg())
+    .arg(graph_memory_budget_arg())
+    .arg(progress_arg())
+    .arg(profile_arg())
     .arg(config_arg())
     .arg(no_config_arg())
     .arg(reload_arg())
     .arg(lock_arg())
     .arg(lock_write_arg())
     .arg(no_lock_arg())
+    .arg(frozen_arg())
     .arg(ca_file_arg())
 }
 
@@ -1988,6 +3447,78 @@ fn permission_args(app: Command) -> Command {
         .action(ArgAction::SetTrue)
         .help("Always throw if required permission wasn't passed"),
     )
+    .arg(
+      Arg::new("restrict-local-imports")
+        .long("restrict-local-imports")
+        .action(ArgAction::SetTrue)
+        .help("Require read permission to import local modules outside the project root")
+        .long_help(
+          "Require read permission to import local modules that are outside \
+the project root (the directory of the configuration file or the current \
+working directory). This prevents accidentally depending on files that are \
+not checked into version control.",
+        ),
+    )
+    .arg(
+      Arg::new("pin-dns")
+        .long("pin-dns")
+        .action(ArgAction::SetTrue)
+        .requires("allow-net")
+        .help("Resolve the hosts of --allow-net at startup and only connect to those addresses")
+        .long_help(
+          "Resolve the hosts listed in --allow-net once at startup and only \
+connect to the resolved addresses for fetch() and the downloads of remote \
+modules. This prevents a DNS record that changes while the program runs from \
+redirecting an allowed host to another address.",
+        ),
+    )
+    .arg(
+      Arg::new("net-audit-log")
+        .long("net-audit-log")
+        .num_args(1)
+        .value_name("FILE")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath)
+        .help("Append the allowed and denied network accesses to a file")
+        .long_help(
+          "Append every network access that was checked against the \
+permissions to a file as newline delimited JSON, with the API that made it, \
+the host, the port, the URL and whether it was allowed.
+
+  deno run --allow-net=deno.land --net-audit-log=net.ndjson main.ts",
+        ),
+    )
+    .arg(
+      Arg::new("trace-permissions")
+        .long("trace-permissions")
+        .num_args(1)
+        .value_name("FILE")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath)
+        .help("Append every permission check to a file with the stack of the code that made it")
+        .long_help(
+          "Append every permission check, granted or denied, to a file as \
+newline delimited JSON. Each entry has the permission, the checked value, the \
+API, whether it was granted, the flag that grants it and the source mapped \
+stack of the code that made the check, so the minimal set of permission \
+flags a program needs can be derived from it.
+
+  deno run -A --trace-permissions=permissions.ndjson main.ts",
+        ),
+    )
+    .arg(
+      Arg::new("permission-set")
+        .long("permission-set")
+        .value_name("NAME")
+        .require_equals(true)
+        .help("Grant the permissions of a permission set from the configuration file")
+        .long_help(
+          "Grant the permissions of a permission set from the \"permissions\" \
+of the configuration file, in addition to the ones of the permission flags. \
+It replaces the permission set named by the build profile of --profile. \
+A permission set can be generated with deno run --suggest-permissions=NAME.",
+        ),
+    )
 }
 
 fn runtime_args(
@@ -2011,6 +3542,7 @@ fn runtime_args(
     .arg(location_arg())
     .arg(v8_flags_arg())
     .arg(seed_arg())
+    .arg(disallow_code_generation_from_strings_arg())
     .arg(enable_testing_features_arg())
 }
 
@@ -2047,6 +3579,14 @@ fn inspect_args(app: Command) -> Command {
         .require_equals(true)
         .value_parser(value_parser!(SocketAddr)),
     )
+    .arg(
+      Arg::new("inspect-children")
+        .long("inspect-children")
+        .help(
+          "Inspect workers and child deno processes in the same way, listing them on the inspector of this process",
+        )
+        .action(ArgAction::SetTrue),
+    )
 }
 
 static IMPORT_MAP_HELP: &str = concat!(
@@ -2088,7 +3628,9 @@ fn reload_arg() -> Arg {
 --reload=npm:
   Reload all npm modules
 --reload=npm:chalk
-  Reload specific npm module",
+  Reload specific npm module
+--reload=stale
+  Revalidate remote modules that are stale according to their cache headers",
     )
     .value_hint(ValueHint::FilePath)
     .value_parser(reload_arg_validate)
@@ -2160,6 +3702,163 @@ fn v8_flags_arg() -> Arg {
     Any flags set with this flag are appended after the DENO_V8_FLAGS environmental variable")
 }
 
+fn prepare_worker_arg() -> Arg {
+  Arg::new("prepare-worker")
+    .long("prepare-worker")
+    .value_name("MODULE")
+    .help("UNSTABLE: Prepare a worker module before running the program")
+    .long_help(
+      "UNSTABLE: Prepares a module before running the program, so workers that \
+use it as their entrypoint don't need to prepare it again, like \
+Deno.preloadModule() does. This flag can be passed multiple times.
+
+This flag used to be named --preload, which now evaluates modules before the \
+main module instead (see --import).",
+    )
+    .action(ArgAction::Append)
+    .value_hint(ValueHint::FilePath)
+}
+
+fn import_arg() -> Arg {
+  Arg::new("import")
+    .long("import")
+    .visible_alias("preload")
+    .value_name("MODULE")
+    .help("Evaluate a module before the main module")
+    .long_help(
+      "Evaluates a module before the main module, like an instrumentation \
+agent or a polyfill, without importing it in the program. The module is \
+resolved like an import of the current directory, so relative paths start \
+with './' and the import map applies. It's also evaluated before the \
+entrypoint of workers. This flag can be passed multiple times, and the \
+modules are evaluated in order after the ones of the \"preload\" \
+configuration.
+
+--preload is an alias of this flag. To prepare worker modules without \
+evaluating them, which --preload used to do, use --prepare-worker.",
+    )
+    .action(ArgAction::Append)
+    .value_hint(ValueHint::FilePath)
+}
+
+fn suggest_permissions_arg() -> Arg {
+  Arg::new("suggest-permissions")
+    .long("suggest-permissions")
+    .value_name("NAME")
+    .num_args(0..=1)
+    .require_equals(true)
+    .conflicts_with("trace-permissions")
+    .conflicts_with("watch")
+    .help("Print the minimal permission flags the program used after it ran")
+    .long_help(
+      "Print the minimal permission flags, with the specific paths, hosts and \
+names, that the program used after it ran. Run the program with the \
+permissions it needs, for example with -A, so none of its permission checks \
+are denied. When a NAME is given, the permissions are also saved as that \
+permission set in the \"permissions\" of the configuration file, which can \
+be granted in future runs with --permission-set=NAME.",
+    )
+}
+
+/// The level and target level overrides of `--log-level`.
+type LogLevelArg = (Option<Level>, Vec<(String, LevelFilter)>);
+
+fn parse_log_level_arg(value: &str) -> Result<LogLevelArg, String> {
+  let mut level = None;
+  let mut filters = Vec::new();
+  for directive in value.split(',').map(str::trim) {
+    match directive.split_once('=') {
+      Some((target, target_level)) => {
+        let target_level = match LevelFilter::from_str(target_level) {
+          Ok(target_level) if !target.is_empty() => target_level,
+          _ => {
+            return Err(format!(
+              "invalid target level \"{directive}\", expected TARGET=LEVEL \
+where LEVEL is off, error, warn, info, debug or trace"
+            ))
+          }
+        };
+        filters.push((target.to_string(), target_level));
+      }
+      None => {
+        level = match directive {
+          "debug" => Some(Level::Debug),
+          "info" => Some(Level::Info),
+          _ => {
+            return Err(format!(
+              "invalid log level \"{directive}\", expected debug, info or \
+TARGET=LEVEL"
+            ))
+          }
+        };
+      }
+    }
+  }
+  Ok((level, filters))
+}
+
+fn profile_args(app: Command) -> Command {
+  app
+    .arg(
+      Arg::new("cpu-prof")
+        .long("cpu-prof")
+        .help("Write a CPU profile of the main thread to --prof-dir")
+        .long_help(
+          "Writes a CPU profile of the main thread to --prof-dir when the \
+program exits, and whenever the process receives SIGUSR2. The profiles can be \
+loaded in the Performance panel of Chrome DevTools.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("heap-snapshot-on-exit")
+        .long("heap-snapshot-on-exit")
+        .help("Write a heap snapshot of the main thread to --prof-dir")
+        .long_help(
+          "Writes a heap snapshot of the main thread to --prof-dir when the \
+program exits, and whenever the process receives SIGUSR2. The snapshots can be \
+loaded in the Memory panel of Chrome DevTools.",
+        )
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("prof-dir")
+        .long("prof-dir")
+        .value_name("DIR")
+        .require_equals(true)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::DirPath)
+        .help(
+          "Directory of the profiles of --cpu-prof and --heap-snapshot-on-exit (defaults to the current directory)",
+        ),
+    )
+}
+
+fn profile_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.cpu_prof = matches.get_flag("cpu-prof");
+  flags.heap_snapshot_on_exit = matches.get_flag("heap-snapshot-on-exit");
+  flags.prof_dir = matches.remove_one::<PathBuf>("prof-dir");
+}
+
+fn env_file_arg() -> Arg {
+  Arg::new("env-file")
+    .long("env-file")
+    .value_name("FILE")
+    .num_args(0..=1)
+    .require_equals(true)
+    .default_missing_value(".env")
+    .action(ArgAction::Append)
+    .value_parser(value_parser!(PathBuf))
+    .value_hint(ValueHint::FilePath)
+    .help("Load environment variables from a .env file")
+    .long_help(
+      "Loads environment variables from a .env file, or the given file, \
+before any code runs. Variables that are already set are not overridden, and \
+values can reference other variables with ${NAME}. This flag can be passed \
+multiple times, in which case later files override the earlier ones.",
+    )
+}
+
 fn seed_arg() -> Arg {
   Arg::new("seed")
     .long("seed")
@@ -2168,6 +3867,17 @@ fn seed_arg() -> Arg {
     .value_parser(value_parser!(u64))
 }
 
+fn disallow_code_generation_from_strings_arg() -> Arg {
+  Arg::new("disallow-code-generation-from-strings")
+    .long("disallow-code-generation-from-strings")
+    .help("Disallow eval and the Function constructor")
+    .long_help(
+      "Disallow compiling code from strings with eval, the Function \
+constructor and similar APIs. Attempts throw an EvalError.",
+    )
+    .action(ArgAction::SetTrue)
+}
+
 fn watch_arg(takes_files: bool) -> Arg {
   let arg = Arg::new("watch")
     .long("watch")
@@ -2194,6 +3904,41 @@ Additional paths might be watched by passing them as arguments to this flag.",
   }
 }
 
+fn watch_include_arg() -> Arg {
+  Arg::new("watch-include")
+    .long("watch-include")
+    .value_name("GLOBS")
+    .num_args(1..)
+    .use_value_delimiter(true)
+    .require_equals(true)
+    .requires("watch")
+    .help("Restart on changes of additional files matching the glob patterns")
+    .long_help(
+      "Restart on changes of additional files matching the glob patterns, like \
+static assets or templates that aren't imported by the modules. The patterns \
+are relative to the current directory, where `*` matches within a path \
+component and `**` matches any number of them.
+  --watch-include=templates/**/*.html,static",
+    )
+}
+
+fn watch_exclude_arg() -> Arg {
+  Arg::new("watch-exclude")
+    .long("watch-exclude")
+    .value_name("GLOBS")
+    .num_args(1..)
+    .use_value_delimiter(true)
+    .require_equals(true)
+    .requires("watch")
+    .help("Ignore changes of files matching the glob patterns")
+    .long_help(
+      "Ignore changes of files matching the glob patterns, including the \
+files inside of the matched directories. The patterns are relative to the \
+current directory.
+  --watch-exclude=node_modules,.git,dist",
+    )
+}
+
 fn no_clear_screen_arg() -> Arg {
   Arg::new("no-clear-screen")
     .requires("watch")
@@ -2288,6 +4033,20 @@ fn no_lock_arg() -> Arg {
     .conflicts_with("lock")
 }
 
+fn frozen_arg() -> Arg {
+  Arg::new("frozen")
+    .long("frozen")
+    .action(ArgAction::SetTrue)
+    .help("Error out if the lock file or the cache would need to be updated.")
+    .long_help(
+      "Error out if the lock file or the cache would need to be updated.
+Remote modules and npm packages must already be cached, and the lock file
+must already contain every module and npm package that is loaded. Useful
+to guarantee reproducible runs on CI.",
+    )
+    .conflicts_with_all(["lock-write", "no-lock", "reload"])
+}
+
 static CONFIG_HELP: &str = concat!(
   "The configuration file can be used to configure different aspects of
 deno including TypeScript, linting, and code formatting. Typically the
@@ -2330,6 +4089,76 @@ fn no_npm_arg() -> Arg {
     .help("Do not resolve npm modules")
 }
 
+fn profile_arg() -> Arg {
+  Arg::new("profile")
+    .long("profile")
+    .value_name("NAME")
+    .require_equals(true)
+    .help("Use a build profile from the configuration file")
+    .long_help(
+      "Use a build profile from the \"profiles\" of the configuration file, \
+which can replace global identifiers with constant expressions (\"define\"), \
+minify the emitted code (\"minify\") and adjust the compiler options used \
+for type checking and emitting (\"compilerOptions\").
+
+A profile can also grant a permission set from the \"permissions\" of the \
+configuration file by naming it in its \"permissions\", unless \
+--permission-set selects another permission set.",
+    )
+}
+
+fn progress_arg() -> Arg {
+  Arg::new("progress")
+    .long("progress")
+    .value_name("FORMAT")
+    .require_equals(true)
+    .help("Write machine readable progress events")
+    .long_help(
+      "Write progress events (downloads, emits and type checking) as \
+newline delimited JSON instead of displaying progress bars. Use \
+--progress=json to write the events to stderr or --progress=json:<PATH> to \
+write them to a file or named pipe.",
+    )
+    .value_parser(|value: &str| -> Result<ProgressFlag, String> {
+      match value.strip_prefix("json") {
+        Some("") => Ok(ProgressFlag::JsonStderr),
+        Some(path) if path.len() > 1 && path.starts_with(':') => {
+          Ok(ProgressFlag::JsonFile(PathBuf::from(&path[1..])))
+        }
+        _ => Err("Expected \"json\" or \"json:<PATH>\"".to_string()),
+      }
+    })
+}
+
+fn graph_memory_budget_arg() -> Arg {
+  Arg::new("graph-memory-budget")
+    .long("graph-memory-budget")
+    .value_name("MEGABYTES")
+    .require_equals(true)
+    .value_parser(value_parser!(u64).range(1..))
+    .help("Limit the memory used to retain parsed modules")
+    .long_help(
+      "Limit the memory used to retain the parsed modules that are needed \
+again after building the module graph, measured by the size of their source \
+code. Once over the budget, the modules that don't get transpiled are freed \
+first, followed by the ones that were parsed first, and they're parsed again \
+when needed. By default every one of them is retained.",
+    )
+}
+
+fn canonicalize_symlinks_arg() -> Arg {
+  Arg::new("canonicalize-symlinks")
+    .long("canonicalize-symlinks")
+    .action(ArgAction::SetTrue)
+    .help("Resolve symlinks in local module paths")
+    .long_help(
+      "Resolve symlinks in local module paths so that a module reached \
+through multiple symlinked paths (ex. pnpm or bazel style layouts) is only \
+instantiated once. By default the symlinked path is used as the module's \
+identity.",
+    )
+}
+
 fn local_npm_arg() -> Arg {
   Arg::new("node-modules-dir")
     .long("node-modules-dir")
@@ -2340,6 +4169,16 @@ fn local_npm_arg() -> Arg {
     .help("Creates a local node_modules folder")
 }
 
+fn allow_scripts_arg() -> Arg {
+  Arg::new("allow-scripts")
+    .long("allow-scripts")
+    .num_args(1..)
+    .use_value_delimiter(true)
+    .require_equals(true)
+    .value_name("PACKAGES")
+    .help("Run the install scripts of the listed npm packages without prompting when they are added to the local node_modules folder")
+}
+
 fn unsafely_ignore_certificate_errors_arg() -> Arg {
   Arg::new("unsafely-ignore-certificate-errors")
     .long("unsafely-ignore-certificate-errors")
@@ -2351,10 +4190,26 @@ fn unsafely_ignore_certificate_errors_arg() -> Arg {
     .value_parser(flags_allow_net::validator)
 }
 
+fn add_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  ca_file_arg_parse(flags, matches);
+  config_args_parse(flags, matches);
+  lock_arg_parse(flags, matches);
+  no_lock_arg_parse(flags, matches);
+  local_npm_args_parse(flags, matches);
+  // adding dependencies only downloads them
+  flags.type_check_mode = TypeCheckMode::None;
+
+  flags.subcommand = DenoSubcommand::Add(AddFlags {
+    packages: matches.remove_many::<String>("packages").unwrap().collect(),
+  });
+}
+
 fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.type_check_mode = TypeCheckMode::Local;
 
   runtime_args_parse(flags, matches, true, false);
+  env_file_arg_parse(flags, matches);
+  import_arg_parse(flags, matches);
 
   // NOTE: `deno bench` always uses `--no-prompt`, tests shouldn't ever do
   // interactive prompts, unless done by user code
@@ -2382,6 +4237,9 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   };
 
   let no_run = matches.get_flag("no-run");
+  let save_baseline = matches.remove_one::<String>("save-baseline");
+  let baseline = matches.remove_one::<String>("baseline");
+  let regression_threshold = matches.remove_one::<u32>("regression-threshold");
 
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Bench(BenchFlags {
@@ -2389,6 +4247,9 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     filter,
     json,
     no_run,
+    save_baseline,
+    baseline,
+    regression_threshold,
   });
 }
 
@@ -2419,7 +4280,8 @@ fn bundle_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 fn cache_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   compile_args_parse(flags, matches);
   let files = matches.remove_many::<String>("file").unwrap().collect();
-  flags.subcommand = DenoSubcommand::Cache(CacheFlags { files });
+  let infer_types = matches.get_flag("infer-types");
+  flags.subcommand = DenoSubcommand::Cache(CacheFlags { files, infer_types });
 }
 
 fn check_parse(flags: &mut Flags, matches: &mut ArgMatches) {
@@ -2429,7 +4291,33 @@ fn check_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if matches.get_flag("all") || matches.get_flag("remote") {
     flags.type_check_mode = TypeCheckMode::All;
   }
-  flags.subcommand = DenoSubcommand::Check(CheckFlags { files });
+  let all_errors = matches.get_flag("all-errors");
+  let declarations_out_dir = if matches.get_flag("emit-declarations") {
+    matches.remove_one::<PathBuf>("out-dir")
+  } else {
+    None
+  };
+  let unused = matches.get_flag("unused");
+  let json = matches.get_flag("json");
+  flags.subcommand = DenoSubcommand::Check(CheckFlags {
+    files,
+    all_errors,
+    declarations_out_dir,
+    unused,
+    json,
+  });
+}
+
+fn clean_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.subcommand = DenoSubcommand::Clean(CleanFlags {
+    emits: matches.get_flag("emits"),
+    npm: matches.get_flag("npm"),
+    origins: matches
+      .remove_many::<String>("origin")
+      .map(|origins| origins.collect())
+      .unwrap_or_default(),
+    dry_run: matches.get_flag("dry-run"),
+  });
 }
 
 fn compile_parse(flags: &mut Flags, matches: &mut ArgMatches) {
@@ -2445,6 +4333,29 @@ fn compile_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Some(f) => f.collect(),
     None => vec![],
   };
+  let snapshot = match matches.remove_many::<String>("snapshot") {
+    Some(f) => f.collect(),
+    None => vec![],
+  };
+  let icon = matches.remove_one::<PathBuf>("icon");
+  let metadata = match matches.remove_many::<(String, String)>("metadata") {
+    Some(f) => f.collect(),
+    None => vec![],
+  };
+  let sign = matches.remove_one::<String>("sign");
+  let all_targets = matches.get_flag("all-targets");
+  let include_dirs = match matches.remove_many::<PathBuf>("include-dir") {
+    Some(f) => f.collect(),
+    None => vec![],
+  };
+  let self_update =
+    matches
+      .remove_one::<Url>("update-url")
+      .map(|url| CompileSelfUpdateFlags {
+        url,
+        public_key: matches.remove_one::<String>("update-key").unwrap(),
+        version: matches.remove_one::<String>("app-version").unwrap(),
+      });
   ext_arg_parse(flags, matches);
 
   flags.subcommand = DenoSubcommand::Compile(CompileFlags {
@@ -2453,6 +4364,13 @@ fn compile_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     args,
     target,
     include,
+    snapshot,
+    icon,
+    metadata,
+    sign,
+    all_targets,
+    include_dirs,
+    self_update,
   });
 }
 
@@ -2516,9 +4434,22 @@ fn coverage_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   });
 }
 
+fn cron_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  runtime_args_parse(flags, matches, true, true);
+  env_file_arg_parse(flags, matches);
+
+  let mut script_arg = matches.remove_many::<String>("script_arg").unwrap();
+  let script = script_arg.next().unwrap();
+  flags.argv.extend(script_arg);
+
+  watch_arg_parse(flags, matches, true);
+  flags.subcommand = DenoSubcommand::Cron(CronFlags { script });
+}
+
 fn doc_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   import_map_arg_parse(flags, matches);
   reload_arg_parse(flags, matches);
+  config_args_parse(flags, matches);
   lock_arg_parse(flags, matches);
   no_lock_arg_parse(flags, matches);
   no_npm_arg_parse(flags, matches);
@@ -2537,16 +4468,29 @@ fn doc_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let private = matches.get_flag("private");
   let json = matches.get_flag("json");
   let filter = matches.remove_one::<String>("filter");
+  let lint = matches.get_flag("lint");
+  let html = if matches.get_flag("html") {
+    let output = matches
+      .remove_one::<PathBuf>("output")
+      .unwrap_or_else(|| PathBuf::from("./docs/"));
+    Some(DocHtmlFlag { output })
+  } else {
+    None
+  };
   flags.subcommand = DenoSubcommand::Doc(DocFlags {
     source_file,
     json,
     filter,
     private,
+    html,
+    lint,
   });
 }
 
 fn eval_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   runtime_args_parse(flags, matches, false, true);
+  profile_args_parse(flags, matches);
+  env_file_arg_parse(flags, matches);
   flags.allow_net = Some(vec![]);
   flags.allow_env = Some(vec![]);
   flags.allow_run = Some(vec![]);
@@ -2600,6 +4544,7 @@ fn fmt_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let single_quote = matches.remove_one::<bool>("single-quote");
   let prose_wrap = matches.remove_one::<String>("prose-wrap");
   let no_semicolons = matches.remove_one::<bool>("no-semicolons");
+  let organize_imports = matches.remove_one::<bool>("organize-imports");
 
   flags.subcommand = DenoSubcommand::Fmt(FmtFlags {
     check: matches.get_flag("check"),
@@ -2610,6 +4555,7 @@ fn fmt_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     single_quote,
     prose_wrap,
     no_semicolons,
+    organize_imports,
   });
 }
 
@@ -2631,9 +4577,20 @@ fn info_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   no_remote_arg_parse(flags, matches);
   no_npm_arg_parse(flags, matches);
   let json = matches.get_flag("json");
+  let cache_status = matches.get_flag("cache-status");
   flags.subcommand = DenoSubcommand::Info(InfoFlags {
     file: matches.remove_one::<String>("file"),
     json,
+    cache_status,
+    diff: matches.remove_one::<PathBuf>("diff"),
+    sbom: matches.remove_one::<String>("sbom").map(|format| {
+      match format.as_str() {
+        "cyclonedx" => SbomFormat::CycloneDx,
+        "spdx" => SbomFormat::Spdx,
+        _ => unreachable!(),
+      }
+    }),
+    cycles: matches.get_flag("cycles"),
   });
 }
 
@@ -2644,7 +4601,18 @@ fn install_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
   let force = matches.get_flag("force");
   let name = matches.remove_one::<String>("name");
-  let mut cmd_values = matches.remove_many::<String>("cmd").unwrap();
+  let mut cmd_values = match matches.remove_many::<String>("cmd") {
+    Some(cmd_values) => cmd_values,
+    None => {
+      // installing the dependencies only downloads them, unless type
+      // checking was requested
+      if !matches.contains_id("check") {
+        flags.type_check_mode = TypeCheckMode::None;
+      }
+      flags.subcommand = DenoSubcommand::InstallDeps;
+      return;
+    }
+  };
 
   let module_url = cmd_values.next().unwrap();
   let args = cmd_values.collect();
@@ -2669,6 +4637,15 @@ fn lsp_parse(flags: &mut Flags, _matches: &mut ArgMatches) {
   flags.subcommand = DenoSubcommand::Lsp;
 }
 
+fn license_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  compile_args_without_check_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::License(LicenseFlags {
+    files: matches.remove_many::<String>("files").unwrap().collect(),
+    json: matches.get_flag("json"),
+  });
+}
+
 fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   config_args_parse(flags, matches);
   watch_arg_parse(flags, matches, false);
@@ -2680,6 +4657,7 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Some(f) => f.collect(),
     None => vec![],
   };
+  let fix = matches.get_flag("fix");
   let rules = matches.get_flag("rules");
   let maybe_rules_tags = matches
     .remove_many::<String>("rules-tags")
@@ -2700,6 +4678,7 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
       include: files,
       ignore,
     },
+    fix,
     rules,
     maybe_rules_tags,
     maybe_rules_include,
@@ -2710,12 +4689,77 @@ fn lint_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   });
 }
 
-fn repl_parse(flags: &mut Flags, matches: &mut ArgMatches) {
-  runtime_args_parse(flags, matches, true, true);
-  unsafely_ignore_certificate_errors_parse(flags, matches);
+fn lock_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  let subcommand = match matches.remove_subcommand() {
+    Some((name, mut matches)) => match name.as_str() {
+      "diff" => LockSubcommand::Diff(LockDiffFlags {
+        old: matches.remove_one::<PathBuf>("old").unwrap(),
+        new: matches.remove_one::<PathBuf>("new").unwrap(),
+        json: matches.get_flag("json"),
+      }),
+      _ => unreachable!(),
+    },
+    // the subcommand is required
+    None => unreachable!(),
+  };
+  flags.subcommand = DenoSubcommand::Lock(LockFlags { subcommand });
+}
 
-  let eval_files = matches
-    .remove_many::<String>("eval-file")
+fn outdated_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  ca_file_arg_parse(flags, matches);
+  config_args_parse(flags, matches);
+  import_map_arg_parse(flags, matches);
+  lock_arg_parse(flags, matches);
+  no_lock_arg_parse(flags, matches);
+  // updating dependencies only downloads them
+  flags.type_check_mode = TypeCheckMode::None;
+
+  flags.subcommand = DenoSubcommand::Outdated(OutdatedFlags {
+    json: matches.get_flag("json"),
+    update: matches.get_flag("update"),
+  });
+}
+
+fn pack_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  // the declaration files are emitted by the type check
+  flags.type_check_mode = TypeCheckMode::Local;
+  compile_args_without_check_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Pack(PackFlags {
+    entrypoint: matches.remove_one::<String>("entrypoint").unwrap(),
+    out_dir: matches.remove_one::<PathBuf>("out-dir").unwrap(),
+    name: matches.remove_one::<String>("name").unwrap(),
+    version: matches.remove_one::<String>("version").unwrap(),
+  });
+}
+
+fn publish_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.type_check_mode = TypeCheckMode::Local;
+  compile_args_without_check_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Publish(PublishFlags {
+    entrypoint: matches.remove_one::<String>("entrypoint").unwrap(),
+    registry: matches.remove_one::<String>("registry").unwrap(),
+    token: matches.remove_one::<String>("token"),
+    dry_run: matches.get_flag("dry-run"),
+  });
+}
+
+fn remove_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  config_args_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Remove(RemoveFlags {
+    packages: matches.remove_many::<String>("packages").unwrap().collect(),
+  });
+}
+
+fn repl_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  runtime_args_parse(flags, matches, true, true);
+  env_file_arg_parse(flags, matches);
+  unsafely_ignore_certificate_errors_parse(flags, matches);
+
+  let eval_files = matches
+    .remove_many::<String>("eval-file")
     .map(|values| values.collect());
 
   handle_repl_flags(
@@ -2730,27 +4774,59 @@ fn repl_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
 fn run_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   runtime_args_parse(flags, matches, true, true);
+  profile_args_parse(flags, matches);
+  env_file_arg_parse(flags, matches);
 
   let mut script_arg = matches.remove_many::<String>("script_arg").unwrap();
 
   let script = script_arg.next().unwrap();
   flags.argv.extend(script_arg);
+  strip_npm_binary_double_hyphen(flags, &script);
 
   ext_arg_parse(flags, matches);
+  prepare_worker_arg_parse(flags, matches);
+  import_arg_parse(flags, matches);
+  suggest_permissions_arg_parse(flags, matches);
 
   watch_arg_parse(flags, matches, true);
   flags.subcommand = DenoSubcommand::Run(RunFlags { script });
 }
 
+fn serve_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  runtime_args_parse(flags, matches, true, true);
+  env_file_arg_parse(flags, matches);
+  import_arg_parse(flags, matches);
+
+  let mut script_arg = matches.remove_many::<String>("script_arg").unwrap();
+  let script = script_arg.next().unwrap();
+  flags.argv.extend(script_arg);
+
+  watch_arg_parse(flags, matches, true);
+  flags.subcommand = DenoSubcommand::Serve(ServeFlags {
+    script,
+    host: matches.remove_one::<String>("host").unwrap(),
+    port: matches.remove_one::<u16>("port").unwrap(),
+    parallel: matches.remove_one::<NonZeroUsize>("parallel").unwrap(),
+  });
+}
+
 fn task_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.config_flag = matches
     .remove_one::<String>("config")
     .map_or(ConfigFlag::Discover, ConfigFlag::Path);
+  env_file_arg_parse(flags, matches);
 
   let mut task_flags = TaskFlags {
     cwd: matches.remove_one::<String>("cwd"),
     task: None,
+    recursive: None,
   };
+  if matches.get_flag("recursive") {
+    task_flags.recursive = Some(TaskRecursiveFlags {
+      filter: matches.remove_one::<String>("filter"),
+      changed: matches.remove_one::<String>("changed"),
+    });
+  }
 
   if let Some((task, mut matches)) = matches.remove_subcommand() {
     task_flags.task = Some(task);
@@ -2770,6 +4846,8 @@ fn task_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.type_check_mode = TypeCheckMode::Local;
   runtime_args_parse(flags, matches, true, true);
+  env_file_arg_parse(flags, matches);
+  import_arg_parse(flags, matches);
   // NOTE: `deno test` always uses `--no-prompt`, tests shouldn't ever do
   // interactive prompts, unless done by user code
   flags.no_prompt = true;
@@ -2781,6 +4859,9 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
   let no_run = matches.get_flag("no-run");
   let trace_ops = matches.get_flag("trace-ops");
+  let isolate_pool = matches.get_flag("isolate-pool");
+  let reporter = matches.remove_one::<String>("reporter");
+  let reporter_output = matches.remove_one::<PathBuf>("reporter-output");
   let doc = matches.get_flag("doc");
   let allow_none = matches.get_flag("allow-none");
   let filter = matches.remove_one::<String>("filter");
@@ -2839,6 +4920,13 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Vec::new()
   };
 
+  let shard = matches.remove_one::<TestShard>("shard");
+  let update_snapshots = matches.get_flag("update-snapshots");
+  let global_setup = matches.remove_one::<String>("global-setup");
+  let global_teardown = matches.remove_one::<String>("global-teardown");
+  let import_map_overrides =
+    matches.remove_one::<String>("import-map-overrides");
+
   flags.coverage_dir = matches.remove_one::<String>("coverage");
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Test(TestFlags {
@@ -2851,6 +4939,14 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     allow_none,
     concurrent_jobs,
     trace_ops,
+    isolate_pool,
+    reporter,
+    reporter_output,
+    shard,
+    update_snapshots,
+    global_setup,
+    global_teardown,
+    import_map_overrides,
   });
 }
 
@@ -2863,15 +4959,23 @@ fn upgrade_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
   let dry_run = matches.get_flag("dry-run");
   let force = matches.get_flag("force");
-  let canary = matches.get_flag("canary");
+  let channel = if matches.get_flag("canary") {
+    UpgradeChannel::Canary
+  } else {
+    matches
+      .remove_one::<UpgradeChannel>("channel")
+      .unwrap_or_default()
+  };
   let version = matches.remove_one::<String>("version");
   let output = matches.remove_one::<PathBuf>("output");
+  let verify_key = matches.remove_one::<String>("verify-key");
   flags.subcommand = DenoSubcommand::Upgrade(UpgradeFlags {
     dry_run,
     force,
-    canary,
+    channel,
     version,
     output,
+    verify_key,
   });
 }
 
@@ -2892,6 +4996,42 @@ fn vendor_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   });
 }
 
+fn x_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  runtime_args_parse(flags, matches, false, false);
+  // like npx, the binaries are run with all permissions
+  flags.allow_all = true;
+  flags.allow_read = Some(vec![]);
+  flags.allow_env = Some(vec![]);
+  flags.allow_net = Some(vec![]);
+  flags.allow_run = Some(vec![]);
+  flags.allow_write = Some(vec![]);
+  flags.allow_sys = Some(vec![]);
+  flags.allow_ffi = Some(vec![]);
+  flags.allow_hrtime = true;
+
+  let mut package_arg = matches.remove_many::<String>("package_arg").unwrap();
+  let package = package_arg.next().unwrap();
+  let script = if package.starts_with("npm:") {
+    package
+  } else {
+    format!("npm:{package}")
+  };
+  flags.argv.extend(package_arg);
+  strip_npm_binary_double_hyphen(flags, &script);
+
+  flags.subcommand = DenoSubcommand::Run(RunFlags { script });
+}
+
+/// The binaries of npm packages are run like with npx, where a leading `--`
+/// only separates the arguments of the binary from the ones of Deno.
+fn strip_npm_binary_double_hyphen(flags: &mut Flags, script: &str) {
+  if script.starts_with("npm:")
+    && flags.argv.first().map(|arg| arg.as_str()) == Some("--")
+  {
+    flags.argv.remove(0);
+  }
+}
+
 fn compile_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   compile_args_without_check_parse(flags, matches);
   no_check_arg_parse(flags, matches);
@@ -2906,6 +5046,10 @@ fn compile_args_without_check_parse(
   no_remote_arg_parse(flags, matches);
   no_npm_arg_parse(flags, matches);
   local_npm_args_parse(flags, matches);
+  canonicalize_symlinks_arg_parse(flags, matches);
+  graph_memory_budget_arg_parse(flags, matches);
+  progress_arg_parse(flags, matches);
+  profile_arg_parse(flags, matches);
   config_args_parse(flags, matches);
   reload_arg_parse(flags, matches);
   lock_args_parse(flags, matches);
@@ -2964,6 +5108,15 @@ fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if matches.get_flag("no-prompt") {
     flags.no_prompt = true;
   }
+  if matches.get_flag("restrict-local-imports") {
+    flags.restrict_local_imports = true;
+  }
+  if matches.get_flag("pin-dns") {
+    flags.pin_dns = true;
+  }
+  flags.net_audit_log = matches.remove_one::<PathBuf>("net-audit-log");
+  flags.trace_permissions = matches.remove_one::<PathBuf>("trace-permissions");
+  flags.permission_set = matches.remove_one::<String>("permission-set");
 }
 fn unsafely_ignore_certificate_errors_parse(
   flags: &mut Flags,
@@ -2994,6 +5147,7 @@ fn runtime_args_parse(
   location_arg_parse(flags, matches);
   v8_flags_arg_parse(flags, matches);
   seed_arg_parse(flags, matches);
+  disallow_code_generation_from_strings_arg_parse(flags, matches);
   enable_testing_features_arg_parse(flags, matches);
 }
 
@@ -3026,6 +5180,7 @@ fn inspect_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   } else {
     None
   };
+  flags.inspect_children = matches.get_flag("inspect-children");
 }
 
 fn import_map_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
@@ -3034,7 +5189,14 @@ fn import_map_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
 
 fn reload_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if let Some(cache_bl) = matches.remove_many::<String>("reload") {
-    let raw_cache_blocklist: Vec<String> = cache_bl.collect();
+    let mut raw_cache_blocklist: Vec<String> = cache_bl.collect();
+    if let Some(index) = raw_cache_blocklist.iter().position(|s| s == "stale") {
+      raw_cache_blocklist.remove(index);
+      flags.reload_stale = true;
+      if raw_cache_blocklist.is_empty() {
+        return;
+      }
+    }
     if raw_cache_blocklist.is_empty() {
       flags.reload = true;
     } else {
@@ -3068,6 +5230,28 @@ fn ext_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.ext = matches.remove_one::<String>("ext");
 }
 
+fn suggest_permissions_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(mut permission_set) =
+    matches.remove_many::<String>("suggest-permissions")
+  {
+    flags.suggest_permissions = Some(SuggestPermissionsFlags {
+      permission_set: permission_set.next(),
+    });
+  }
+}
+
+fn import_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(imports) = matches.remove_many::<String>("import") {
+    flags.preload_imports = imports.collect();
+  }
+}
+
+fn prepare_worker_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(modules) = matches.remove_many::<String>("prepare-worker") {
+    flags.prepare_workers = modules.collect();
+  }
+}
+
 fn location_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.location = matches.remove_one::<Url>("location");
 }
@@ -3078,6 +5262,12 @@ fn v8_flags_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn env_file_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  if let Some(env_files) = matches.remove_many::<PathBuf>("env-file") {
+    flags.env_file = env_files.collect();
+  }
+}
+
 fn seed_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if let Some(seed) = matches.remove_one::<u64>("seed") {
     flags.seed = Some(seed);
@@ -3086,6 +5276,17 @@ fn seed_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn disallow_code_generation_from_strings_arg_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+) {
+  if matches.get_flag("disallow-code-generation-from-strings") {
+    flags
+      .v8_flags
+      .push("--disallow-code-generation-from-strings".to_string());
+  }
+}
+
 fn no_check_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if let Some(cache_type) = matches.get_one::<String>("no-check") {
     match cache_type.as_str() {
@@ -3120,6 +5321,9 @@ fn lock_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if matches.get_flag("lock-write") {
     flags.lock_write = true;
   }
+  if matches.get_flag("frozen") {
+    flags.frozen = true;
+  }
 }
 
 fn lock_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
@@ -3159,14 +5363,43 @@ fn no_npm_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn profile_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.profile = matches.remove_one::<String>("profile");
+}
+
+fn progress_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.progress = matches.remove_one::<ProgressFlag>("progress");
+}
+
+fn graph_memory_budget_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.graph_memory_budget = matches.remove_one::<u64>("graph-memory-budget");
+}
+
+fn canonicalize_symlinks_arg_parse(
+  flags: &mut Flags,
+  matches: &mut ArgMatches,
+) {
+  if matches.get_flag("canonicalize-symlinks") {
+    flags.canonicalize_symlinks = true;
+  }
+}
+
 fn local_npm_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.node_modules_dir = matches.remove_one::<bool>("node-modules-dir");
+  if let Some(packages) = matches.remove_many::<String>("allow-scripts") {
+    flags.allow_scripts = packages
+      .map(|name| name.strip_prefix("npm:").unwrap_or(&name).to_string())
+      .collect();
+  }
 }
 
 fn reload_arg_validate(urlstr: &str) -> Result<String, String> {
   if urlstr.is_empty() {
     return Err(String::from("Missing url. Check for extra commas."));
   }
+  if urlstr == "stale" {
+    return Ok(urlstr.to_string());
+  }
   match Url::from_str(urlstr) {
     Ok(_) => Ok(urlstr.to_string()),
     Err(e) => Err(e.to_string()),
@@ -3189,6 +5422,12 @@ fn watch_arg_parse(
   if matches.get_flag("no-clear-screen") {
     flags.no_clear_screen = true;
   }
+  if let Some(include) = matches.remove_many::<String>("watch-include") {
+    flags.watch_include = include.collect();
+  }
+  if let Some(exclude) = matches.remove_many::<String>("watch-exclude") {
+    flags.watch_exclude = exclude.collect();
+  }
 }
 
 // TODO(ry) move this to utility module and add test.
@@ -3243,6 +5482,27 @@ mod tests {
     assert_eq!(flags2, flags);
   }
 
+  #[test]
+  fn unstable_granular_flags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "--unstable-kv",
+      "run",
+      "--unstable-ffi",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        unstable_features: svec!["ffi", "kv"],
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn upgrade() {
     let r = flags_from_vec(svec!["deno", "upgrade", "--dry-run", "--force"]);
@@ -3253,13 +5513,76 @@ mod tests {
         subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
           force: true,
           dry_run: true,
-          canary: false,
+          channel: UpgradeChannel::Stable,
+          version: None,
+          output: None,
+          verify_key: None,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn upgrade_channel() {
+    let r = flags_from_vec(svec!["deno", "upgrade", "--canary"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
+          force: false,
+          dry_run: false,
+          channel: UpgradeChannel::Canary,
+          version: None,
+          output: None,
+          verify_key: None,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "upgrade",
+      "--channel",
+      "https://deno.example.com/releases/",
+      "--verify-key",
+      "MCowBQYDK2VwAyEA"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
+          force: false,
+          dry_run: false,
+          channel: UpgradeChannel::Custom(
+            Url::parse("https://deno.example.com/releases/").unwrap()
+          ),
           version: None,
           output: None,
+          verify_key: Some("MCowBQYDK2VwAyEA".to_string()),
         }),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "upgrade", "--channel", "nightly"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "upgrade",
+      "--channel",
+      "http://deno.example.com/releases/"
+    ]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "upgrade",
+      "--canary",
+      "--channel",
+      "stable"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -3292,6 +5615,40 @@ mod tests {
     );
   }
 
+  #[test]
+  fn run_reload_stale() {
+    let r = flags_from_vec(svec!["deno", "run", "--reload=stale", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        reload_stale: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--reload=stale,https://deno.land/std",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          infer_types: false,
+        }),
+        reload_stale: true,
+        cache_blocklist: svec!["https://deno.land/std"],
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn run_watch() {
     let r = flags_from_vec(svec!["deno", "run", "--watch", "script.ts"]);
@@ -3349,6 +5706,96 @@ mod tests {
     );
   }
 
+  #[test]
+  fn run_watch_with_include_and_exclude() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch",
+      "--watch-include=templates/**/*.html,static",
+      "--watch-exclude=node_modules",
+      "script.ts"
+    ]);
+
+    let flags = r.unwrap();
+    assert_eq!(
+      flags,
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        watch: Some(vec![]),
+        watch_include: svec!["templates/**/*.html", "static"],
+        watch_exclude: svec!["node_modules"],
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--watch-exclude=node_modules",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn cron() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "cron",
+      "--allow-net",
+      "--watch",
+      "tasks.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cron(CronFlags {
+          script: "tasks.ts".to_string(),
+        }),
+        allow_net: Some(vec![]),
+        watch: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn serve() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "serve",
+      "--allow-net",
+      "--port=3000",
+      "--parallel=4",
+      "--import=./setup.ts",
+      "--watch",
+      "server.ts",
+      "--verbose"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Serve(ServeFlags {
+          script: "server.ts".to_string(),
+          host: "0.0.0.0".to_string(),
+          port: 3000,
+          parallel: NonZeroUsize::new(4).unwrap(),
+        }),
+        argv: svec!["--verbose"],
+        allow_net: Some(vec![]),
+        preload_imports: svec!["./setup.ts"],
+        watch: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "serve", "--parallel=0", "server.ts"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_reload_allow_write() {
     let r =
@@ -3520,7 +5967,96 @@ mod tests {
   }
 
   #[test]
-  fn fmt() {
+  fn double_hyphen_npm_binary() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "-A",
+      "npm:prettier@2/prettier",
+      "--",
+      "--check",
+      "--",
+      "."
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "npm:prettier@2/prettier".to_string(),
+        }),
+        argv: svec!["--check", "--", "."],
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn x_subcommand() {
+    let r = flags_from_vec(svec!["deno", "x", "cowsay", "hello"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "npm:cowsay".to_string(),
+        }),
+        argv: svec!["hello"],
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "x",
+      "--node-modules-dir",
+      "npm:typescript@5/tsc",
+      "--",
+      "--version"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "npm:typescript@5/tsc".to_string(),
+        }),
+        argv: svec!["--version"],
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        node_modules_dir: Some(true),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "x"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn fmt() {
     let r = flags_from_vec(svec!["deno", "fmt", "script_1.ts", "script_2.ts"]);
     assert_eq!(
       r.unwrap(),
@@ -3540,6 +6076,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         ..Flags::default()
@@ -3562,6 +6099,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         ..Flags::default()
@@ -3584,6 +6122,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         ..Flags::default()
@@ -3606,6 +6145,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         watch: Some(vec![]),
@@ -3630,6 +6170,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         watch: Some(vec![]),
@@ -3661,6 +6202,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         watch: Some(vec![]),
@@ -3684,6 +6226,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         ext: Some("ts".to_string()),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
@@ -3714,6 +6257,7 @@ mod tests {
           single_quote: None,
           prose_wrap: None,
           no_semicolons: None,
+          organize_imports: None,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ext: Some("ts".to_string()),
@@ -3734,6 +6278,7 @@ mod tests {
       "--prose-wrap",
       "never",
       "--no-semicolons",
+      "--organize-imports",
     ]);
     assert_eq!(
       r.unwrap(),
@@ -3750,6 +6295,7 @@ mod tests {
           single_quote: Some(true),
           prose_wrap: Some("never".to_string()),
           no_semicolons: Some(true),
+          organize_imports: Some(true),
         }),
         ext: Some("ts".to_string()),
         ..Flags::default()
@@ -3763,6 +6309,7 @@ mod tests {
       "--use-tabs=false",
       "--single-quote=false",
       "--no-semicolons=false",
+      "--organize-imports=false",
     ]);
     assert_eq!(
       r.unwrap(),
@@ -3779,6 +6326,7 @@ mod tests {
           single_quote: Some(false),
           prose_wrap: None,
           no_semicolons: Some(false),
+          organize_imports: Some(false),
         }),
         ext: Some("ts".to_string()),
         ..Flags::default()
@@ -3800,6 +6348,7 @@ mod tests {
             ],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -3829,6 +6378,7 @@ mod tests {
             ],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -3860,6 +6410,7 @@ mod tests {
             ],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -3873,6 +6424,27 @@ mod tests {
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "lint", "--fix", "script_1.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lint(LintFlags {
+          files: FileFlags {
+            include: vec![PathBuf::from("script_1.ts")],
+            ignore: vec![],
+          },
+          fix: true,
+          rules: false,
+          maybe_rules_tags: None,
+          maybe_rules_include: None,
+          maybe_rules_exclude: None,
+          json: false,
+          compact: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
     let r =
       flags_from_vec(svec!["deno", "lint", "--ignore=script_1.ts,script_2.ts"]);
     assert_eq!(
@@ -3886,6 +6458,7 @@ mod tests {
               PathBuf::from("script_2.ts")
             ],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -3906,6 +6479,7 @@ mod tests {
             include: vec![],
             ignore: vec![],
           },
+          fix: false,
           rules: true,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -3932,6 +6506,7 @@ mod tests {
             include: vec![],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: Some(svec![""]),
           maybe_rules_include: Some(svec!["ban-untagged-todo", "no-undef"]),
@@ -3952,6 +6527,7 @@ mod tests {
             include: vec![PathBuf::from("script_1.ts")],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -3979,6 +6555,7 @@ mod tests {
             include: vec![PathBuf::from("script_1.ts")],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -4007,6 +6584,7 @@ mod tests {
             include: vec![PathBuf::from("script_1.ts")],
             ignore: vec![],
           },
+          fix: false,
           rules: false,
           maybe_rules_tags: None,
           maybe_rules_include: None,
@@ -4021,25 +6599,34 @@ mod tests {
   }
 
   #[test]
-  fn types() {
-    let r = flags_from_vec(svec!["deno", "types"]);
+  fn clean() {
+    let r = flags_from_vec(svec!["deno", "clean"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Types,
+        subcommand: DenoSubcommand::Clean(CleanFlags::default()),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn cache() {
-    let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "clean",
+      "--emits",
+      "--npm",
+      "--origin=esm.sh",
+      "--origin",
+      "https://deno.land",
+      "--dry-run"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts"],
+        subcommand: DenoSubcommand::Clean(CleanFlags {
+          emits: true,
+          npm: true,
+          origins: svec!["esm.sh", "https://deno.land"],
+          dry_run: true,
         }),
         ..Flags::default()
       }
@@ -4047,446 +6634,1439 @@ mod tests {
   }
 
   #[test]
-  fn check() {
-    let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
+  fn lock_diff() {
+    let r =
+      flags_from_vec(svec!["deno", "lock", "diff", "old.lock", "new.lock"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Check(CheckFlags {
-          files: svec!["script.ts"],
+        subcommand: DenoSubcommand::Lock(LockFlags {
+          subcommand: LockSubcommand::Diff(LockDiffFlags {
+            old: PathBuf::from("old.lock"),
+            new: PathBuf::from("new.lock"),
+            json: false,
+          }),
         }),
-        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    for all_flag in ["--remote", "--all"] {
-      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
-      assert_eq!(
-        r.unwrap(),
-        Flags {
-          subcommand: DenoSubcommand::Check(CheckFlags {
-            files: svec!["script.ts"],
+    let r = flags_from_vec(svec![
+      "deno", "lock", "diff", "--json", "old.lock", "new.lock"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Lock(LockFlags {
+          subcommand: LockSubcommand::Diff(LockDiffFlags {
+            old: PathBuf::from("old.lock"),
+            new: PathBuf::from("new.lock"),
+            json: true,
           }),
-          type_check_mode: TypeCheckMode::All,
-          ..Flags::default()
-        }
-      );
+        }),
+        ..Flags::default()
+      }
+    );
 
-      let r = flags_from_vec(svec![
-        "deno",
-        "check",
-        all_flag,
-        "--no-remote",
-        "script.ts"
-      ]);
-      assert_eq!(
-        r.unwrap_err().kind(),
-        clap::error::ErrorKind::ArgumentConflict
-      );
-    }
+    let r = flags_from_vec(svec!["deno", "lock", "diff", "old.lock"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn info() {
-    let r = flags_from_vec(svec!["deno", "info", "script.ts"]);
+  fn add() {
+    let r = flags_from_vec(svec!["deno", "add", "npm:express@^4", "std/http"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: Some("script.ts".to_string()),
+        subcommand: DenoSubcommand::Add(AddFlags {
+          packages: svec!["npm:express@^4", "std/http"],
         }),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "info", "--reload", "script.ts"]);
+    let r = flags_from_vec(svec!["deno", "add"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn outdated() {
+    let r = flags_from_vec(svec!["deno", "outdated"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
+        subcommand: DenoSubcommand::Outdated(OutdatedFlags {
           json: false,
-          file: Some("script.ts".to_string()),
+          update: false,
         }),
-        reload: true,
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "info", "--json", "script.ts"]);
+    let r = flags_from_vec(svec!["deno", "outdated", "--update"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: true,
-          file: Some("script.ts".to_string()),
+        subcommand: DenoSubcommand::Outdated(OutdatedFlags {
+          json: false,
+          update: true,
         }),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "info"]);
+    let r = flags_from_vec(svec!["deno", "outdated", "--json", "--update"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn pack() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "pack",
+      "--name=my-lib",
+      "--version=1.0.0",
+      "mod.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: None
+        subcommand: DenoSubcommand::Pack(PackFlags {
+          entrypoint: "mod.ts".to_string(),
+          out_dir: PathBuf::from("npm"),
+          name: "my-lib".to_string(),
+          version: "1.0.0".to_string(),
         }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "info", "--json"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "pack",
+      "--name=@scope/my-lib",
+      "--version=1.0.0",
+      "--out-dir=dist",
+      "--import-map=import_map.json",
+      "mod.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: true,
-          file: None
+        subcommand: DenoSubcommand::Pack(PackFlags {
+          entrypoint: "mod.ts".to_string(),
+          out_dir: PathBuf::from("dist"),
+          name: "@scope/my-lib".to_string(),
+          version: "1.0.0".to_string(),
         }),
+        type_check_mode: TypeCheckMode::Local,
+        import_map_path: Some("import_map.json".to_string()),
         ..Flags::default()
       }
     );
 
+    let r = flags_from_vec(svec!["deno", "pack", "--version=1.0.0", "mod.ts"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::MissingRequiredArgument
+    );
+  }
+
+  #[test]
+  fn publish() {
     let r = flags_from_vec(svec![
       "deno",
-      "info",
-      "--no-npm",
-      "--no-remote",
-      "--config",
-      "tsconfig.json"
+      "publish",
+      "--registry=https://registry.example.com/",
+      "mod.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: None
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          entrypoint: "mod.ts".to_string(),
+          registry: "https://registry.example.com/".to_string(),
+          token: None,
+          dry_run: false,
         }),
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        no_npm: true,
-        no_remote: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn tsconfig() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "-c", "tsconfig.json", "script.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "publish",
+      "--registry=https://registry.example.com/",
+      "--token=abc123",
+      "--dry-run",
+      "--config=deno.jsonc",
+      "mod.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Publish(PublishFlags {
+          entrypoint: "mod.ts".to_string(),
+          registry: "https://registry.example.com/".to_string(),
+          token: Some("abc123".to_string()),
+          dry_run: true,
         }),
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::Local,
+        config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "publish", "mod.ts"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::MissingRequiredArgument
+    );
   }
 
   #[test]
-  fn eval() {
-    let r = flags_from_vec(svec!["deno", "eval", "'console.log(\"hello\")'"]);
+  fn license() {
+    let r = flags_from_vec(svec!["deno", "license", "main.ts", "worker.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "'console.log(\"hello\")'".to_string(),
+        subcommand: DenoSubcommand::License(LicenseFlags {
+          files: svec!["main.ts", "worker.ts"],
+          json: false,
         }),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn eval_p() {
-    let r = flags_from_vec(svec!["deno", "eval", "-p", "1+2"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "license",
+      "--json",
+      "--lock=deno.lock",
+      "main.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: true,
-          code: "1+2".to_string(),
+        subcommand: DenoSubcommand::License(LicenseFlags {
+          files: svec!["main.ts"],
+          json: true,
         }),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
+        lock: Some(PathBuf::from("deno.lock")),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "license"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::MissingRequiredArgument
+    );
+  }
+
+  #[test]
+  fn remove() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "remove",
+      "--config",
+      "deno.jsonc",
+      "express"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Remove(RemoveFlags {
+          packages: svec!["express"],
+        }),
+        config_flag: ConfigFlag::Path("deno.jsonc".to_owned()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn types() {
+    let r = flags_from_vec(svec!["deno", "types"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Types,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn cache() {
+    let r = flags_from_vec(svec!["deno", "cache", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          infer_types: false,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn check() {
+    let r = flags_from_vec(svec!["deno", "check", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+          all_errors: false,
+          declarations_out_dir: None,
+          unused: false,
+          json: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    for all_flag in ["--remote", "--all"] {
+      let r = flags_from_vec(svec!["deno", "check", all_flag, "script.ts"]);
+      assert_eq!(
+        r.unwrap(),
+        Flags {
+          subcommand: DenoSubcommand::Check(CheckFlags {
+            files: svec!["script.ts"],
+            all_errors: false,
+            declarations_out_dir: None,
+            unused: false,
+            json: false,
+          }),
+          type_check_mode: TypeCheckMode::All,
+          ..Flags::default()
+        }
+      );
+
+      let r = flags_from_vec(svec![
+        "deno",
+        "check",
+        all_flag,
+        "--no-remote",
+        "script.ts"
+      ]);
+      assert_eq!(
+        r.unwrap_err().kind(),
+        clap::error::ErrorKind::ArgumentConflict
+      );
+    }
+
+    let r = flags_from_vec(svec!["deno", "check", "--all-errors", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["script.ts"],
+          all_errors: true,
+          declarations_out_dir: None,
+          unused: false,
+          json: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "check",
+      "--emit-declarations",
+      "--out-dir=types",
+      "mod.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["mod.ts"],
+          all_errors: false,
+          declarations_out_dir: Some(PathBuf::from("types")),
+          unused: false,
+          json: false,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "check", "--out-dir=types", "mod.ts"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::MissingRequiredArgument
+    );
+    let r =
+      flags_from_vec(svec!["deno", "check", "--emit-declarations", "mod.ts"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::MissingRequiredArgument
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "check", "--unused", "--json", "mod.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Check(CheckFlags {
+          files: svec!["mod.ts"],
+          all_errors: false,
+          declarations_out_dir: None,
+          unused: true,
+          json: true,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "check", "--json", "mod.ts"]);
+    assert_eq!(
+      r.unwrap_err().kind(),
+      clap::error::ErrorKind::MissingRequiredArgument
+    );
+  }
+
+  #[test]
+  fn info() {
+    let r = flags_from_vec(svec!["deno", "info", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info", "--reload", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        reload: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info", "--json", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: true,
+          file: Some("script.ts".to_string()),
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: None,
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info", "--json"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: true,
+          file: None,
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "info",
+      "--no-npm",
+      "--no-remote",
+      "--config",
+      "tsconfig.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: None,
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        no_npm: true,
+        no_remote: true,
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "info", "--cache-status", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          cache_status: true,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info", "--cache-status"]);
+    assert!(r.is_err());
+
+    let r =
+      flags_from_vec(svec!["deno", "info", "--diff", "old.lock", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          cache_status: false,
+          diff: Some(PathBuf::from("old.lock")),
+          sbom: None,
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info", "--diff", "old.lock"]);
+    assert!(r.is_err());
+
+    let r =
+      flags_from_vec(svec!["deno", "info", "--sbom=cyclonedx", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          cache_status: false,
+          diff: None,
+          sbom: Some(SbomFormat::CycloneDx),
+          cycles: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "info",
+      "--sbom=spdx",
+      "--json",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "info", "--sbom=swid", "script.ts"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec!["deno", "info", "--cycles", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("script.ts".to_string()),
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: true,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "info", "--cycles"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn tsconfig() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "-c", "tsconfig.json", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn eval() {
+    let r = flags_from_vec(svec!["deno", "eval", "'console.log(\"hello\")'"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "'console.log(\"hello\")'".to_string(),
+        }),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn eval_p() {
+    let r = flags_from_vec(svec!["deno", "eval", "-p", "1+2"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: true,
+          code: "1+2".to_string(),
+        }),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn eval_typescript() {
+    let r =
+      flags_from_vec(svec!["deno", "eval", "-T", "'console.log(\"hello\")'"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "'console.log(\"hello\")'".to_string(),
+        }),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ext: Some("ts".to_string()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn eval_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "eval", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "42"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "42".to_string(),
+        }),
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn eval_args() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "eval",
+      "console.log(Deno.args)",
+      "arg1",
+      "arg2"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "console.log(Deno.args)".to_string(),
+        }),
+        argv: svec!["arg1", "arg2"],
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn repl() {
+    let r = flags_from_vec(svec!["deno"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: None,
+          is_default_command: true,
+        }),
+        allow_net: Some(vec![]),
+        unsafely_ignore_certificate_errors: None,
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn repl_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "repl", "-A", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--unsafely-ignore-certificate-errors"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: None,
+          is_default_command: false,
+        }),
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        location: Some(Url::parse("https://foo/").unwrap()),
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn repl_with_eval_flag() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "repl", "--allow-write", "--eval", "console.log('hello');"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: Some("console.log('hello');".to_string()),
+          is_default_command: false,
+        }),
+        allow_write: Some(vec![]),
+        type_check_mode: TypeCheckMode::None,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn repl_with_eval_file_flag() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "repl", "--eval-file=./a.js,./b.ts,https://examples.deno.land/hello-world.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: Some(vec![
+            "./a.js".to_string(),
+            "./b.ts".to_string(),
+            "https://examples.deno.land/hello-world.ts".to_string()
+          ]),
+          eval: None,
+          is_default_command: false,
+        }),
+        type_check_mode: TypeCheckMode::None,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_read_allowlist() {
+    use test_util::TempDir;
+    let temp_dir_guard = TempDir::new();
+    let temp_dir = temp_dir_guard.path().to_path_buf();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      format!("--allow-read=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        allow_read: Some(vec![PathBuf::from("."), temp_dir]),
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_write_allowlist() {
+    use test_util::TempDir;
+    let temp_dir_guard = TempDir::new();
+    let temp_dir = temp_dir_guard.path().to_path_buf();
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      format!("--allow-write=.,{}", temp_dir.to_str().unwrap()),
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        allow_write: Some(vec![PathBuf::from("."), temp_dir]),
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_net_allowlist() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-net=127.0.0.1",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_net: Some(svec!["127.0.0.1"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_env_allowlist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_env: Some(svec!["HOME"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_env_allowlist_multiple() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-env=HOME,PATH",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_env: Some(svec!["HOME", "PATH"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_env_allowlist_validator() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
+    assert!(r.is_ok());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=H=ME", "script.ts"]);
+    assert!(r.is_err());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-env=H\0ME", "script.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn allow_sys() {
+    let r = flags_from_vec(svec!["deno", "run", "--allow-sys", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_sys: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_sys_allowlist() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_sys: Some(svec!["hostname"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_sys_allowlist_multiple() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-sys=hostname,osRelease",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        allow_sys: Some(svec!["hostname", "osRelease"]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn allow_sys_allowlist_validator() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
+    assert!(r.is_ok());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-sys=hostname,osRelease",
+      "script.ts"
+    ]);
+    assert!(r.is_ok());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--allow-sys=foo", "script.ts"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--allow-sys=hostname,foo",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn reload_validator() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/",
+      "script.ts"
+    ]);
+    assert!(r.is_ok(), "should accept valid urls");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/a,http://deno.land/b",
+      "script.ts"
+    ]);
+    assert!(r.is_ok(), "should accept accept multiple valid urls");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=./relativeurl/",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject relative urls that start with ./");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=relativeurl/",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject relative urls");
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--reload=/absolute", "script.ts"]);
+    assert!(r.is_err(), "Should reject absolute urls");
+
+    let r = flags_from_vec(svec!["deno", "run", "--reload=/", "script.ts"]);
+    assert!(r.is_err(), "Should reject absolute root url");
+
+    let r = flags_from_vec(svec!["deno", "run", "--reload=", "script.ts"]);
+    assert!(r.is_err(), "Should reject when nothing is provided");
+
+    let r = flags_from_vec(svec!["deno", "run", "--reload=,", "script.ts"]);
+    assert!(r.is_err(), "Should reject when a single comma is provided");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=,http://deno.land/a",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject a leading comma");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/a,",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject a trailing comma");
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--reload=http://deno.land/a,,http://deno.land/b",
+      "script.ts"
+    ]);
+    assert!(r.is_err(), "Should reject adjacent commas");
+  }
+
+  #[test]
+  fn bundle() {
+    let r = flags_from_vec(svec!["deno", "bundle", "source.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn bundle_with_config() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--no-remote",
+      "--config",
+      "tsconfig.json",
+      "source.ts",
+      "bundle.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: Some(PathBuf::from("bundle.js")),
+        }),
+        allow_write: Some(vec![]),
+        no_remote: true,
+        type_check_mode: TypeCheckMode::Local,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn bundle_with_output() {
+    let r = flags_from_vec(svec!["deno", "bundle", "source.ts", "bundle.js"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: Some(PathBuf::from("bundle.js")),
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        allow_write: Some(vec![]),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn bundle_with_lock() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--lock-write",
+      "--lock=lock.json",
+      "source.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
+        }),
+        type_check_mode: TypeCheckMode::Local,
+        lock_write: true,
+        lock: Some(PathBuf::from("lock.json")),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn eval_typescript() {
-    let r =
-      flags_from_vec(svec!["deno", "eval", "-T", "'console.log(\"hello\")'"]);
+  fn bundle_with_reload() {
+    let r = flags_from_vec(svec!["deno", "bundle", "--reload", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "'console.log(\"hello\")'".to_string(),
+        reload: true,
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
         }),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        ext: Some("ts".to_string()),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn eval_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "eval", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "42"]);
+  fn bundle_nocheck() {
+    let r = flags_from_vec(svec!["deno", "bundle", "--no-check", "script.ts"])
+      .unwrap();
     assert_eq!(
-      r.unwrap(),
+      r,
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "42".to_string(),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "script.ts".to_string(),
+          out_file: None,
         }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
         type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
-        lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn eval_args() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "eval",
-      "console.log(Deno.args)",
-      "arg1",
-      "arg2"
-    ]);
+  fn bundle_watch() {
+    let r = flags_from_vec(svec!["deno", "bundle", "--watch", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Eval(EvalFlags {
-          print: false,
-          code: "console.log(Deno.args)".to_string(),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
         }),
-        argv: svec!["arg1", "arg2"],
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
+        type_check_mode: TypeCheckMode::Local,
+        watch: Some(vec![]),
         ..Flags::default()
       }
-    );
+    )
   }
 
   #[test]
-  fn repl() {
-    let r = flags_from_vec(svec!["deno"]);
+  fn bundle_watch_with_no_clear_screen() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bundle",
+      "--watch",
+      "--no-clear-screen",
+      "source.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: None,
-          is_default_command: true,
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
         }),
-        allow_net: Some(vec![]),
-        unsafely_ignore_certificate_errors: None,
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
+        type_check_mode: TypeCheckMode::Local,
+        watch: Some(vec![]),
+        no_clear_screen: true,
         ..Flags::default()
       }
-    );
+    )
   }
 
   #[test]
-  fn repl_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "repl", "-A", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--location", "https:foo", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--unsafely-ignore-certificate-errors"]);
+  fn run_import_map() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--import-map=import_map.json",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: None,
-          is_default_command: false,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
-        lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        location: Some(Url::parse("https://foo/").unwrap()),
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
-        allow_all: true,
-        allow_net: Some(vec![]),
-        allow_env: Some(vec![]),
-        allow_run: Some(vec![]),
-        allow_read: Some(vec![]),
-        allow_sys: Some(vec![]),
-        allow_write: Some(vec![]),
-        allow_ffi: Some(vec![]),
-        allow_hrtime: true,
-        unsafely_ignore_certificate_errors: Some(vec![]),
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl_with_eval_flag() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "repl", "--allow-write", "--eval", "console.log('hello');"]);
+  fn info_import_map() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "info",
+      "--import-map=import_map.json",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: Some("console.log('hello');".to_string()),
-          is_default_command: false,
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          file: Some("script.ts".to_string()),
+          json: false,
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
         }),
-        allow_write: Some(vec![]),
-        type_check_mode: TypeCheckMode::None,
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl_with_eval_file_flag() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "repl", "--eval-file=./a.js,./b.ts,https://examples.deno.land/hello-world.ts"]);
+  fn cache_import_map() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "cache",
+      "--import-map=import_map.json",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: Some(vec![
-            "./a.js".to_string(),
-            "./b.ts".to_string(),
-            "https://examples.deno.land/hello-world.ts".to_string()
-          ]),
-          eval: None,
-          is_default_command: false,
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          infer_types: false,
         }),
-        type_check_mode: TypeCheckMode::None,
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_read_allowlist() {
-    use test_util::TempDir;
-    let temp_dir_guard = TempDir::new();
-    let temp_dir = temp_dir_guard.path().to_path_buf();
-
+  fn doc_import_map() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      format!("--allow-read=.,{}", temp_dir.to_str().unwrap()),
+      "doc",
+      "--import-map=import_map.json",
       "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        allow_read: Some(vec![PathBuf::from("."), temp_dir]),
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          source_file: DocSourceFileFlag::Path("script.ts".to_owned()),
+          private: false,
+          json: false,
+          filter: None,
+          html: None,
+          lint: false,
         }),
+        import_map_path: Some("import_map.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_write_allowlist() {
-    use test_util::TempDir;
-    let temp_dir_guard = TempDir::new();
-    let temp_dir = temp_dir_guard.path().to_path_buf();
+  fn cache_multiple() {
+    let r =
+      flags_from_vec(svec!["deno", "cache", "script.ts", "script_two.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts", "script_two.ts"],
+          infer_types: false,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      format!("--allow-write=.,{}", temp_dir.to_str().unwrap()),
-      "script.ts"
-    ]);
+  #[test]
+  fn cache_infer_types() {
+    let r =
+      flags_from_vec(svec!["deno", "cache", "--infer-types", "script.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          infer_types: true,
+        }),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn run_seed() {
+    let r = flags_from_vec(svec!["deno", "run", "--seed", "250", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        allow_write: Some(vec![PathBuf::from("."), temp_dir]),
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
+        seed: Some(250_u64),
+        v8_flags: svec!["--random-seed=250"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_net_allowlist() {
+  fn run_seed_with_v8_flags() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--allow-net=127.0.0.1",
+      "--seed",
+      "250",
+      "--v8-flags=--expose-gc",
       "script.ts"
     ]);
     assert_eq!(
@@ -4495,34 +8075,44 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        allow_net: Some(svec!["127.0.0.1"]),
+        seed: Some(250_u64),
+        v8_flags: svec!["--expose-gc", "--random-seed=250"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_env_allowlist() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
+  fn run_disallow_code_generation_from_strings() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--disallow-code-generation-from-strings",
+      "--v8-flags=--expose-gc",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        allow_env: Some(svec!["HOME"]),
+        v8_flags: svec![
+          "--expose-gc",
+          "--disallow-code-generation-from-strings"
+        ],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_env_allowlist_multiple() {
+  fn run_allow_scripts() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--allow-env=HOME,PATH",
+      "--node-modules-dir",
+      "--allow-scripts=npm:esbuild,@myorg/native",
       "script.ts"
     ]);
     assert_eq!(
@@ -4531,338 +8121,268 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        allow_env: Some(svec!["HOME", "PATH"]),
+        node_modules_dir: Some(true),
+        allow_scripts: svec!["esbuild", "@myorg/native"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn allow_env_allowlist_validator() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=HOME", "script.ts"]);
-    assert!(r.is_ok());
     let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=H=ME", "script.ts"]);
-    assert!(r.is_err());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-env=H\0ME", "script.ts"]);
+      flags_from_vec(svec!["deno", "run", "--allow-scripts", "script.ts"]);
     assert!(r.is_err());
   }
 
   #[test]
-  fn allow_sys() {
-    let r = flags_from_vec(svec!["deno", "run", "--allow-sys", "script.ts"]);
+  fn install() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "install",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          name: None,
+          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
+          args: vec![],
+          root: None,
+          force: false,
         }),
-        allow_sys: Some(vec![]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn allow_sys_allowlist() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
+  fn install_deps() {
+    let r = flags_from_vec(svec!["deno", "install"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-        }),
-        allow_sys: Some(svec!["hostname"]),
+        subcommand: DenoSubcommand::InstallDeps,
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn allow_sys_allowlist_multiple() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--allow-sys=hostname,osRelease",
-      "script.ts"
+      "install",
+      "--node-modules-dir",
+      "--lock-write"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
-        }),
-        allow_sys: Some(svec!["hostname", "osRelease"]),
+        subcommand: DenoSubcommand::InstallDeps,
+        type_check_mode: TypeCheckMode::None,
+        node_modules_dir: Some(true),
+        lock_write: true,
         ..Flags::default()
       }
     );
-  }
-
-  #[test]
-  fn allow_sys_allowlist_validator() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-sys=hostname", "script.ts"]);
-    assert!(r.is_ok());
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-sys=hostname,osRelease",
-      "script.ts"
-    ]);
-    assert!(r.is_ok());
-    let r =
-      flags_from_vec(svec!["deno", "run", "--allow-sys=foo", "script.ts"]);
-    assert!(r.is_err());
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--allow-sys=hostname,foo",
-      "script.ts"
-    ]);
-    assert!(r.is_err());
-  }
-
-  #[test]
-  fn reload_validator() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/",
-      "script.ts"
-    ]);
-    assert!(r.is_ok(), "should accept valid urls");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/a,http://deno.land/b",
-      "script.ts"
-    ]);
-    assert!(r.is_ok(), "should accept accept multiple valid urls");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=./relativeurl/",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject relative urls that start with ./");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=relativeurl/",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject relative urls");
-
-    let r =
-      flags_from_vec(svec!["deno", "run", "--reload=/absolute", "script.ts"]);
-    assert!(r.is_err(), "Should reject absolute urls");
-
-    let r = flags_from_vec(svec!["deno", "run", "--reload=/", "script.ts"]);
-    assert!(r.is_err(), "Should reject absolute root url");
-
-    let r = flags_from_vec(svec!["deno", "run", "--reload=", "script.ts"]);
-    assert!(r.is_err(), "Should reject when nothing is provided");
-
-    let r = flags_from_vec(svec!["deno", "run", "--reload=,", "script.ts"]);
-    assert!(r.is_err(), "Should reject when a single comma is provided");
-
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=,http://deno.land/a",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject a leading comma");
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/a,",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject a trailing comma");
+    // type checking can still be requested
+    let r = flags_from_vec(svec!["deno", "install", "--check"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::InstallDeps,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
 
-    let r = flags_from_vec(svec![
-      "deno",
-      "run",
-      "--reload=http://deno.land/a,,http://deno.land/b",
-      "script.ts"
-    ]);
-    assert!(r.is_err(), "Should reject adjacent commas");
+    // the executable options require a script
+    let r = flags_from_vec(svec!["deno", "install", "--name", "foo"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn bundle() {
-    let r = flags_from_vec(svec!["deno", "bundle", "source.ts"]);
+  fn install_with_flags() {
+    #[rustfmt::skip]
+    let r = flags_from_vec(svec!["deno", "install", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--name", "file_server", "--root", "/foo", "--force", "https://deno.land/std/http/file_server.ts", "foo", "bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
+        subcommand: DenoSubcommand::Install(InstallFlags {
+          name: Some("file_server".to_string()),
+          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
+          args: svec!["foo", "bar"],
+          root: Some(PathBuf::from("/foo")),
+          force: true,
         }),
-        type_check_mode: TypeCheckMode::Local,
+        import_map_path: Some("import_map.json".to_string()),
+        no_remote: true,
+        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
+        type_check_mode: TypeCheckMode::None,
+        reload: true,
+        lock: Some(PathBuf::from("lock.json")),
+        lock_write: true,
+        ca_data: Some(CaData::File("example.crt".to_string())),
+        cached_only: true,
+        v8_flags: svec!["--help", "--random-seed=1"],
+        seed: Some(1),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        allow_net: Some(vec![]),
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        allow_read: Some(vec![]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_with_config() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "bundle",
-      "--no-remote",
-      "--config",
-      "tsconfig.json",
-      "source.ts",
-      "bundle.js"
-    ]);
+  fn uninstall() {
+    let r = flags_from_vec(svec!["deno", "uninstall", "file_server"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: Some(PathBuf::from("bundle.js")),
+        subcommand: DenoSubcommand::Uninstall(UninstallFlags {
+          name: "file_server".to_string(),
+          root: None,
         }),
-        allow_write: Some(vec![]),
-        no_remote: true,
-        type_check_mode: TypeCheckMode::Local,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_with_output() {
-    let r = flags_from_vec(svec!["deno", "bundle", "source.ts", "bundle.js"]);
+  fn uninstall_with_help_flag() {
+    let r = flags_from_vec(svec!["deno", "uninstall", "--help"]);
+    assert_eq!(r.err().unwrap().kind(), clap::error::ErrorKind::DisplayHelp);
+  }
+
+  #[test]
+  fn log_level() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--log-level=debug", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: Some(PathBuf::from("bundle.js")),
-        }),
-        type_check_mode: TypeCheckMode::Local,
-        allow_write: Some(vec![]),
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        log_level: Some(Level::Debug),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_with_lock() {
+  fn log_level_with_targets() {
     let r = flags_from_vec(svec![
       "deno",
-      "bundle",
-      "--lock-write",
-      "--lock=lock.json",
-      "source.ts"
+      "run",
+      "--log-level=module_loader=debug,npm=info",
+      "--log-format=json",
+      "--log-timestamps",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        type_check_mode: TypeCheckMode::Local,
-        lock_write: true,
-        lock: Some(PathBuf::from("lock.json")),
+        log_filters: vec![
+          ("module_loader".to_string(), LevelFilter::Debug),
+          ("npm".to_string(), LevelFilter::Info),
+        ],
+        log_format: LogFormat::Json,
+        log_timestamps: true,
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn bundle_with_reload() {
-    let r = flags_from_vec(svec!["deno", "bundle", "--reload", "source.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--log-level=debug,deno_runtime::permissions=off",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        reload: true,
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        type_check_mode: TypeCheckMode::Local,
+        log_level: Some(Level::Debug),
+        log_filters: vec![(
+          "deno_runtime::permissions".to_string(),
+          LevelFilter::Off
+        )],
         ..Flags::default()
       }
     );
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--log-level=npm=loud", "script.ts"]);
+    assert!(r.is_err());
+    let r =
+      flags_from_vec(svec!["deno", "run", "--log-level=verbose", "script.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn bundle_nocheck() {
-    let r = flags_from_vec(svec!["deno", "bundle", "--no-check", "script.ts"])
-      .unwrap();
+  fn quiet() {
+    let r = flags_from_vec(svec!["deno", "run", "-q", "script.ts"]);
     assert_eq!(
-      r,
+      r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "script.ts".to_string(),
-          out_file: None,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        type_check_mode: TypeCheckMode::None,
+        log_level: Some(Level::Error),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn bundle_watch() {
-    let r = flags_from_vec(svec!["deno", "bundle", "--watch", "source.ts"]);
-    assert_eq!(
-      r.unwrap(),
-      Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
-        }),
-        type_check_mode: TypeCheckMode::Local,
-        watch: Some(vec![]),
-        ..Flags::default()
+  fn completions() {
+    let r = flags_from_vec(svec!["deno", "completions", "zsh"]).unwrap();
+
+    match r.subcommand {
+      DenoSubcommand::Completions(CompletionsFlags { buf }) => {
+        assert!(!buf.is_empty())
       }
-    )
+      _ => unreachable!(),
+    }
   }
 
   #[test]
-  fn bundle_watch_with_no_clear_screen() {
+  fn run_with_args() {
     let r = flags_from_vec(svec![
       "deno",
-      "bundle",
-      "--watch",
-      "--no-clear-screen",
-      "source.ts"
+      "run",
+      "script.ts",
+      "--allow-read",
+      "--allow-net"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Bundle(BundleFlags {
-          source_file: "source.ts".to_string(),
-          out_file: None,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        type_check_mode: TypeCheckMode::Local,
-        watch: Some(vec![]),
-        no_clear_screen: true,
+        argv: svec!["--allow-read", "--allow-net"],
         ..Flags::default()
       }
-    )
-  }
-
-  #[test]
-  fn run_import_map() {
+    );
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--import-map=import_map.json",
-      "script.ts"
+      "--location",
+      "https:foo",
+      "--allow-read",
+      "script.ts",
+      "--allow-net",
+      "-r",
+      "--help",
+      "--foo",
+      "bar"
     ]);
     assert_eq!(
       r.unwrap(),
@@ -4870,258 +8390,224 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        import_map_path: Some("import_map.json".to_owned()),
+        location: Some(Url::parse("https://foo/").unwrap()),
+        allow_read: Some(vec![]),
+        argv: svec!["--allow-net", "-r", "--help", "--foo", "bar"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn info_import_map() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "info",
-      "--import-map=import_map.json",
-      "script.ts"
-    ]);
+    let r = flags_from_vec(svec!["deno", "run", "script.ts", "foo", "bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          file: Some("script.ts".to_string()),
-          json: false,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        import_map_path: Some("import_map.json".to_owned()),
+        argv: svec!["foo", "bar"],
         ..Flags::default()
       }
     );
-  }
-
-  #[test]
-  fn cache_import_map() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "cache",
-      "--import-map=import_map.json",
-      "script.ts"
-    ]);
+    let r = flags_from_vec(svec!["deno", "run", "script.ts", "-"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts"],
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        import_map_path: Some("import_map.json".to_owned()),
+        argv: svec!["-"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn doc_import_map() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "doc",
-      "--import-map=import_map.json",
-      "script.ts"
-    ]);
+    let r =
+      flags_from_vec(svec!["deno", "run", "script.ts", "-", "foo", "bar"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          source_file: DocSourceFileFlag::Path("script.ts".to_owned()),
-          private: false,
-          json: false,
-          filter: None,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        import_map_path: Some("import_map.json".to_owned()),
+        argv: svec!["-", "foo", "bar"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn cache_multiple() {
-    let r =
-      flags_from_vec(svec!["deno", "cache", "script.ts", "script_two.ts"]);
+  fn no_check() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-check", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Cache(CacheFlags {
-          files: svec!["script.ts", "script_two.ts"],
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_seed() {
-    let r = flags_from_vec(svec!["deno", "run", "--seed", "250", "script.ts"]);
+  fn no_check_remote() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--no-check=remote", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        seed: Some(250_u64),
-        v8_flags: svec!["--random-seed=250"],
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_seed_with_v8_flags() {
+  fn repl_with_unsafely_ignore_certificate_errors() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--seed",
-      "250",
-      "--v8-flags=--expose-gc",
-      "script.ts"
+      "repl",
+      "--eval",
+      "console.log('hello');",
+      "--unsafely-ignore-certificate-errors"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: Some("console.log('hello');".to_string()),
+          is_default_command: false,
         }),
-        seed: Some(250_u64),
-        v8_flags: svec!["--expose-gc", "--random-seed=250"],
+        unsafely_ignore_certificate_errors: Some(vec![]),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn install() {
+  fn run_with_unsafely_ignore_certificate_errors() {
     let r = flags_from_vec(svec![
       "deno",
-      "install",
-      "https://deno.land/std/examples/colors.ts"
+      "run",
+      "--unsafely-ignore-certificate-errors",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install(InstallFlags {
-          name: None,
-          module_url: "https://deno.land/std/examples/colors.ts".to_string(),
-          args: vec![],
-          root: None,
-          force: false,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
+        unsafely_ignore_certificate_errors: Some(vec![]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn install_with_flags() {
-    #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "install", "--import-map", "import_map.json", "--no-remote", "--config", "tsconfig.json", "--no-check", "--unsafely-ignore-certificate-errors", "--reload", "--lock", "lock.json", "--lock-write", "--cert", "example.crt", "--cached-only", "--allow-read", "--allow-net", "--v8-flags=--help", "--seed", "1", "--inspect=127.0.0.1:9229", "--name", "file_server", "--root", "/foo", "--force", "https://deno.land/std/http/file_server.ts", "foo", "bar"]);
+  fn run_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Install(InstallFlags {
-          name: Some("file_server".to_string()),
-          module_url: "https://deno.land/std/http/file_server.ts".to_string(),
-          args: svec!["foo", "bar"],
-          root: Some(PathBuf::from("/foo")),
-          force: true,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        import_map_path: Some("import_map.json".to_string()),
-        no_remote: true,
-        config_flag: ConfigFlag::Path("tsconfig.json".to_owned()),
-        type_check_mode: TypeCheckMode::None,
-        reload: true,
-        lock: Some(PathBuf::from("lock.json")),
-        lock_write: true,
-        ca_data: Some(CaData::File("example.crt".to_string())),
-        cached_only: true,
-        v8_flags: svec!["--help", "--random-seed=1"],
-        seed: Some(1),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
-        allow_net: Some(vec![]),
-        unsafely_ignore_certificate_errors: Some(vec![]),
-        allow_read: Some(vec![]),
+        unsafely_ignore_certificate_errors: Some(svec![
+          "deno.land",
+          "localhost",
+          "::",
+          "127.0.0.1",
+          "[::1]",
+          "1.2.3.4"
+        ]),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn uninstall() {
-    let r = flags_from_vec(svec!["deno", "uninstall", "file_server"]);
+  fn repl_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "repl",
+      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Uninstall(UninstallFlags {
-          name: "file_server".to_string(),
-          root: None,
+        subcommand: DenoSubcommand::Repl(ReplFlags {
+          eval_files: None,
+          eval: None,
+          is_default_command: false,
         }),
+        unsafely_ignore_certificate_errors: Some(svec![
+          "deno.land",
+          "localhost",
+          "::",
+          "127.0.0.1",
+          "[::1]",
+          "1.2.3.4"
+        ]),
+        type_check_mode: TypeCheckMode::None,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn uninstall_with_help_flag() {
-    let r = flags_from_vec(svec!["deno", "uninstall", "--help"]);
-    assert_eq!(r.err().unwrap().kind(), clap::error::ErrorKind::DisplayHelp);
-  }
-
-  #[test]
-  fn log_level() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--log-level=debug", "script.ts"]);
+  fn no_remote() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-remote", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        log_level: Some(Level::Debug),
+        no_remote: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn quiet() {
-    let r = flags_from_vec(svec!["deno", "run", "-q", "script.ts"]);
+  fn restrict_local_imports() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--restrict-local-imports",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        log_level: Some(Level::Error),
+        restrict_local_imports: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn completions() {
-    let r = flags_from_vec(svec!["deno", "completions", "zsh"]).unwrap();
-
-    match r.subcommand {
-      DenoSubcommand::Completions(CompletionsFlags { buf }) => {
-        assert!(!buf.is_empty())
-      }
-      _ => unreachable!(),
-    }
-  }
-
-  #[test]
-  fn run_with_args() {
+  fn pin_dns_and_net_audit_log() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "script.ts",
-      "--allow-read",
-      "--allow-net"
+      "--allow-net=deno.land",
+      "--pin-dns",
+      "--net-audit-log=net.ndjson",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
@@ -5129,22 +8615,25 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        argv: svec!["--allow-read", "--allow-net"],
+        allow_net: Some(svec!["deno.land"]),
+        pin_dns: true,
+        net_audit_log: Some(PathBuf::from("net.ndjson")),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec!["deno", "run", "--pin-dns", "script.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn suggest_permissions() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--location",
-      "https:foo",
       "--allow-read",
-      "script.ts",
-      "--allow-net",
-      "-r",
-      "--help",
-      "--foo",
-      "bar"
+      "--suggest-permissions",
+      "script.ts"
     ]);
     assert_eq!(
       r.unwrap(),
@@ -5152,111 +8641,168 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        location: Some(Url::parse("https://foo/").unwrap()),
         allow_read: Some(vec![]),
-        argv: svec!["--allow-net", "-r", "--help", "--foo", "bar"],
+        suggest_permissions: Some(SuggestPermissionsFlags {
+          permission_set: None,
+        }),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "run", "script.ts", "foo", "bar"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--suggest-permissions=dev",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        argv: svec!["foo", "bar"],
+        suggest_permissions: Some(SuggestPermissionsFlags {
+          permission_set: Some("dev".to_string()),
+        }),
         ..Flags::default()
       }
     );
-    let r = flags_from_vec(svec!["deno", "run", "script.ts", "-"]);
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--permission-set=dev", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        argv: svec!["-"],
+        permission_set: Some("dev".to_string()),
         ..Flags::default()
       }
     );
+  }
 
-    let r =
-      flags_from_vec(svec!["deno", "run", "script.ts", "-", "foo", "bar"]);
+  #[test]
+  fn trace_permissions() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "-A",
+      "--trace-permissions=permissions.ndjson",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        argv: svec!["-", "foo", "bar"],
+        allow_all: true,
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        trace_permissions: Some(PathBuf::from("permissions.ndjson")),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn no_check() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-check", "script.ts"]);
+  fn run_prepare_worker() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--prepare-worker",
+      "worker.ts",
+      "--prepare-worker=other_worker.ts",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        type_check_mode: TypeCheckMode::None,
+        prepare_workers: svec!["worker.ts", "other_worker.ts"],
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn no_check_remote() {
-    let r =
-      flags_from_vec(svec!["deno", "run", "--no-check=remote", "script.ts"]);
+  fn run_import() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--import",
+      "npm:@opentelemetry/auto",
+      "--import=./polyfill.ts",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        type_check_mode: TypeCheckMode::Local,
+        preload_imports: svec!["npm:@opentelemetry/auto", "./polyfill.ts"],
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn repl_with_unsafely_ignore_certificate_errors() {
+    let r =
+      flags_from_vec(svec!["deno", "test", "--import=./setup.ts", "a_test.ts"]);
+    assert_eq!(r.unwrap().preload_imports, svec!["./setup.ts"]);
+
+    // --preload is the same flag
     let r = flags_from_vec(svec![
       "deno",
-      "repl",
-      "--eval",
-      "console.log('hello');",
-      "--unsafely-ignore-certificate-errors"
+      "run",
+      "--preload",
+      "./agent.ts",
+      "--import=./polyfill.ts",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap().preload_imports,
+      svec!["./agent.ts", "./polyfill.ts"]
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--import=./setup.ts",
+      "a_bench.ts"
     ]);
+    assert_eq!(r.unwrap().preload_imports, svec!["./setup.ts"]);
+  }
+
+  #[test]
+  fn no_npm() {
+    let r = flags_from_vec(svec!["deno", "run", "--no-npm", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: Some("console.log('hello');".to_string()),
-          is_default_command: false,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
         }),
-        unsafely_ignore_certificate_errors: Some(vec![]),
-        type_check_mode: TypeCheckMode::None,
+        no_npm: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn run_with_unsafely_ignore_certificate_errors() {
+  fn graph_memory_budget() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--unsafely-ignore-certificate-errors",
+      "--graph-memory-budget=512",
       "script.ts"
     ]);
     assert_eq!(
@@ -5265,18 +8811,26 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        unsafely_ignore_certificate_errors: Some(vec![]),
+        graph_memory_budget: Some(512),
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "check",
+      "--graph-memory-budget=0",
+      "script.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn run_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
+  fn canonicalize_symlinks() {
     let r = flags_from_vec(svec![
       "deno",
       "run",
-      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4",
+      "--canonicalize-symlinks",
       "script.ts"
     ]);
     assert_eq!(
@@ -5285,72 +8839,63 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        unsafely_ignore_certificate_errors: Some(svec![
-          "deno.land",
-          "localhost",
-          "::",
-          "127.0.0.1",
-          "[::1]",
-          "1.2.3.4"
-        ]),
+        canonicalize_symlinks: true,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn repl_with_unsafely_treat_insecure_origin_as_secure_with_ipv6_address() {
-    let r = flags_from_vec(svec![
-      "deno",
-      "repl",
-      "--unsafely-ignore-certificate-errors=deno.land,localhost,::,127.0.0.1,[::1],1.2.3.4"]);
+  fn progress() {
+    let r =
+      flags_from_vec(svec!["deno", "cache", "--progress=json", "script.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Repl(ReplFlags {
-          eval_files: None,
-          eval: None,
-          is_default_command: false,
+        subcommand: DenoSubcommand::Cache(CacheFlags {
+          files: svec!["script.ts"],
+          infer_types: false,
         }),
-        unsafely_ignore_certificate_errors: Some(svec![
-          "deno.land",
-          "localhost",
-          "::",
-          "127.0.0.1",
-          "[::1]",
-          "1.2.3.4"
-        ]),
-        type_check_mode: TypeCheckMode::None,
+        progress: Some(ProgressFlag::JsonStderr),
         ..Flags::default()
       }
     );
-  }
 
-  #[test]
-  fn no_remote() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-remote", "script.ts"]);
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--progress=json:/tmp/progress",
+      "script.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        no_remote: true,
+        progress: Some(ProgressFlag::JsonFile(PathBuf::from("/tmp/progress"))),
         ..Flags::default()
       }
     );
+
+    let r =
+      flags_from_vec(svec!["deno", "cache", "--progress=text", "script.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn no_npm() {
-    let r = flags_from_vec(svec!["deno", "run", "--no-npm", "script.ts"]);
+  fn profile() {
+    let r =
+      flags_from_vec(svec!["deno", "bundle", "--profile=prod", "source.ts"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "script.ts".to_string(),
+        subcommand: DenoSubcommand::Bundle(BundleFlags {
+          source_file: "source.ts".to_string(),
+          out_file: None,
         }),
-        no_npm: true,
+        profile: Some("prod".to_string()),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
@@ -5542,23 +9087,58 @@ mod tests {
         subcommand: DenoSubcommand::Run(RunFlags {
           script: "script.ts".to_string(),
         }),
-        lock_write: true,
+        lock_write: true,
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--lock", "--no-lock", "script.ts"]);
+    assert!(r.is_err(),);
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--lock-write",
+      "--no-lock",
+      "script.ts"
+    ]);
+    assert!(r.is_err(),);
+  }
+
+  #[test]
+  fn frozen() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--frozen",
+      "--lock=lock.json",
+      "script.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "script.ts".to_string(),
+        }),
+        frozen: true,
+        lock: Some(PathBuf::from("lock.json")),
         ..Flags::default()
       }
     );
 
-    let r =
-      flags_from_vec(svec!["deno", "run", "--lock", "--no-lock", "script.ts"]);
-    assert!(r.is_err(),);
-
     let r = flags_from_vec(svec![
       "deno",
       "run",
+      "--frozen",
       "--lock-write",
-      "--no-lock",
       "script.ts"
     ]);
-    assert!(r.is_err(),);
+    assert!(r.is_err());
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--frozen", "--reload", "script.ts"]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -5598,6 +9178,14 @@ mod tests {
           shuffle: None,
           concurrent_jobs: None,
           trace_ops: true,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         unstable: true,
         no_prompt: true,
@@ -5673,6 +9261,14 @@ mod tests {
           },
           concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5703,6 +9299,14 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5737,6 +9341,14 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5765,6 +9377,14 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         no_prompt: true,
         watch: None,
@@ -5774,6 +9394,111 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_isolate_pool() {
+    let r =
+      flags_from_vec(svec!["deno", "test", "--parallel", "--isolate-pool"]);
+    let flags = r.unwrap();
+    match flags.subcommand {
+      DenoSubcommand::Test(test_flags) => {
+        assert!(test_flags.isolate_pool);
+        assert!(test_flags.concurrent_jobs.is_some());
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn test_update_snapshots() {
+    let r = flags_from_vec(svec!["deno", "test", "--update-snapshots"]);
+    let flags = r.unwrap();
+    match flags.subcommand {
+      DenoSubcommand::Test(test_flags) => assert!(test_flags.update_snapshots),
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn test_global_setup() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--global-setup=setup.ts",
+      "--global-teardown=teardown.ts"
+    ]);
+    let flags = r.unwrap();
+    match flags.subcommand {
+      DenoSubcommand::Test(test_flags) => {
+        assert_eq!(test_flags.global_setup, Some("setup.ts".to_string()));
+        assert_eq!(test_flags.global_teardown, Some("teardown.ts".to_string()));
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn test_import_map_overrides() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--import-map-overrides=mocks.json"
+    ]);
+    let flags = r.unwrap();
+    match flags.subcommand {
+      DenoSubcommand::Test(test_flags) => {
+        assert_eq!(
+          test_flags.import_map_overrides,
+          Some("mocks.json".to_string())
+        );
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  #[test]
+  fn test_shard() {
+    let r = flags_from_vec(svec!["deno", "test", "--shard=2/5"]);
+    let flags = r.unwrap();
+    match flags.subcommand {
+      DenoSubcommand::Test(test_flags) => {
+        assert_eq!(test_flags.shard, Some(TestShard { index: 2, count: 5 }));
+      }
+      _ => unreachable!(),
+    }
+
+    for value in ["0/5", "6/5", "2", "a/5", "2/"] {
+      let r = flags_from_vec(svec!["deno", "test", format!("--shard={value}")]);
+      assert!(r.is_err(), "{value}");
+    }
+  }
+
+  #[test]
+  fn test_reporter() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=junit",
+      "--reporter-output=report.xml"
+    ]);
+    let flags = r.unwrap();
+    match flags.subcommand {
+      DenoSubcommand::Test(test_flags) => {
+        assert_eq!(test_flags.reporter, Some("junit".to_string()));
+        assert_eq!(
+          test_flags.reporter_output,
+          Some(PathBuf::from("report.xml"))
+        );
+      }
+      _ => unreachable!(),
+    }
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=xml"]);
+    assert!(r.is_err());
+    let r =
+      flags_from_vec(svec!["deno", "test", "--reporter-output=report.xml"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn test_watch() {
     let r = flags_from_vec(svec!["deno", "test", "--watch"]);
@@ -5793,6 +9518,14 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5820,6 +9553,14 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5849,6 +9590,14 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          isolate_pool: false,
+          reporter: None,
+          reporter_output: None,
+          shard: None,
+          update_snapshots: false,
+          global_setup: None,
+          global_teardown: None,
+          import_map_overrides: None,
         }),
         watch: Some(vec![]),
         type_check_mode: TypeCheckMode::Local,
@@ -5891,9 +9640,10 @@ mod tests {
         subcommand: DenoSubcommand::Upgrade(UpgradeFlags {
           force: false,
           dry_run: false,
-          canary: false,
+          channel: UpgradeChannel::Stable,
           version: None,
           output: None,
+          verify_key: None,
         }),
         ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
@@ -5916,6 +9666,7 @@ mod tests {
       Flags {
         subcommand: DenoSubcommand::Cache(CacheFlags {
           files: svec!["script.ts", "script_two.ts"],
+          infer_types: false,
         }),
         ca_data: Some(CaData::File("example.crt".to_owned())),
         ..Flags::default()
@@ -5935,171 +9686,533 @@ mod tests {
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Info(InfoFlags {
-          json: false,
-          file: Some("https://example.com".to_string()),
+        subcommand: DenoSubcommand::Info(InfoFlags {
+          json: false,
+          file: Some("https://example.com".to_string()),
+          cache_status: false,
+          diff: None,
+          sbom: None,
+          cycles: false,
+        }),
+        ca_data: Some(CaData::File("example.crt".to_owned())),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn doc() {
+    let r = flags_from_vec(svec!["deno", "doc", "--json", "path/to/module.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: true,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+          html: None,
+          lint: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "path/to/module.ts",
+      "SomeClass.someField"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: Some("SomeClass.someField".to_string()),
+          html: None,
+          lint: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: Default::default(),
+          filter: None,
+          html: None,
+          lint: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "doc", "--builtin", "Deno.Listener"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: DocSourceFileFlag::Builtin,
+          filter: Some("Deno.Listener".to_string()),
+          html: None,
+          lint: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "--no-npm",
+      "--no-remote",
+      "--private",
+      "path/to/module.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: true,
+          json: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.js".to_string()),
+          filter: None,
+          html: None,
+          lint: false,
+        }),
+        no_npm: true,
+        no_remote: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "--html",
+      "--output=./site",
+      "path/to/module.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+          html: Some(DocHtmlFlag {
+            output: PathBuf::from("./site"),
+          }),
+          lint: false,
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "doc", "--output=./site", "module.ts"]);
+    assert!(r.is_err());
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "doc",
+      "--lint",
+      "--config",
+      "deno.json",
+      "path/to/module.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Doc(DocFlags {
+          private: false,
+          json: false,
+          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
+          filter: None,
+          html: None,
+          lint: true,
+        }),
+        config_flag: ConfigFlag::Path("deno.json".to_owned()),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "doc", "--lint", "--json", "module.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn inspect_default_host() {
+    let r = flags_from_vec(svec!["deno", "run", "--inspect", "foo.js"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "foo.js".to_string(),
+        }),
+        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn inspect_wait() {
+    let r = flags_from_vec(svec!["deno", "run", "--inspect-wait", "foo.js"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "foo.js".to_string(),
+        }),
+        inspect_wait: Some("127.0.0.1:9229".parse().unwrap()),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--inspect-wait=127.0.0.1:3567",
+      "foo.js"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "foo.js".to_string(),
         }),
-        ca_data: Some(CaData::File("example.crt".to_owned())),
+        inspect_wait: Some("127.0.0.1:3567".parse().unwrap()),
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn doc() {
-    let r = flags_from_vec(svec!["deno", "doc", "--json", "path/to/module.ts"]);
+  fn inspect_children() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--inspect-wait",
+      "--inspect-children",
+      "foo.js"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: true,
-          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
-          filter: None,
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "foo.js".to_string(),
         }),
+        inspect_wait: Some("127.0.0.1:9229".parse().unwrap()),
+        inspect_children: true,
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn run_profile_flags() {
     let r = flags_from_vec(svec![
       "deno",
-      "doc",
-      "path/to/module.ts",
-      "SomeClass.someField"
+      "run",
+      "--cpu-prof",
+      "--heap-snapshot-on-exit",
+      "--prof-dir=profiles",
+      "foo.js"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          source_file: DocSourceFileFlag::Path("path/to/module.ts".to_string()),
-          filter: Some("SomeClass.someField".to_string()),
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "foo.js".to_string(),
         }),
+        cpu_prof: true,
+        heap_snapshot_on_exit: true,
+        prof_dir: Some(PathBuf::from("profiles")),
         ..Flags::default()
       }
     );
 
-    let r = flags_from_vec(svec!["deno", "doc"]);
+    let r = flags_from_vec(svec!["deno", "eval", "--cpu-prof", "42"]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          source_file: Default::default(),
-          filter: None,
+        subcommand: DenoSubcommand::Eval(EvalFlags {
+          print: false,
+          code: "42".to_string(),
         }),
+        allow_net: Some(vec![]),
+        allow_env: Some(vec![]),
+        allow_run: Some(vec![]),
+        allow_read: Some(vec![]),
+        allow_sys: Some(vec![]),
+        allow_write: Some(vec![]),
+        allow_ffi: Some(vec![]),
+        allow_hrtime: true,
+        cpu_prof: true,
         ..Flags::default()
       }
     );
+  }
 
-    let r = flags_from_vec(svec!["deno", "doc", "--builtin", "Deno.Listener"]);
+  #[test]
+  fn compile() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "https://deno.land/std/examples/colors.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: false,
-          json: false,
-          source_file: DocSourceFileFlag::Builtin,
-          filter: Some("Deno.Listener".to_string()),
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "https://deno.land/std/examples/colors.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          include: vec![],
+          snapshot: vec![],
+          icon: None,
+          metadata: vec![],
+          sign: None,
+          all_targets: false,
+          include_dirs: vec![],
+          self_update: None,
         }),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn compile_with_snapshot() {
     let r = flags_from_vec(svec![
       "deno",
-      "doc",
-      "--no-npm",
-      "--no-remote",
-      "--private",
-      "path/to/module.js"
+      "compile",
+      "--snapshot",
+      "startup.ts",
+      "--snapshot",
+      "data.ts",
+      "main.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Doc(DocFlags {
-          private: true,
-          json: false,
-          source_file: DocSourceFileFlag::Path("path/to/module.js".to_string()),
-          filter: None,
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          include: vec![],
+          snapshot: svec!["startup.ts", "data.ts"],
+          icon: None,
+          metadata: vec![],
+          sign: None,
+          all_targets: false,
+          include_dirs: vec![],
+          self_update: None,
         }),
-        no_npm: true,
-        no_remote: true,
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
   }
 
   #[test]
-  fn inspect_default_host() {
-    let r = flags_from_vec(svec!["deno", "run", "--inspect", "foo.js"]);
+  fn compile_with_icon_metadata_and_sign() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--icon=app.ico",
+      "--metadata",
+      "name=My App",
+      "--metadata=version=1.2.0",
+      "--sign",
+      "main.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "foo.js".to_string(),
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          include: vec![],
+          snapshot: vec![],
+          icon: Some(PathBuf::from("app.ico")),
+          metadata: vec![
+            ("name".to_string(), "My App".to_string()),
+            ("version".to_string(), "1.2.0".to_string()),
+          ],
+          sign: Some("".to_string()),
+          all_targets: false,
+          include_dirs: vec![],
+          self_update: None,
         }),
-        inspect: Some("127.0.0.1:9229".parse().unwrap()),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--sign=Developer ID Application: Deno",
+      "main.ts"
+    ]);
+    match r.unwrap().subcommand {
+      DenoSubcommand::Compile(compile_flags) => assert_eq!(
+        compile_flags.sign.as_deref(),
+        Some("Developer ID Application: Deno")
+      ),
+      _ => unreachable!(),
+    }
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--metadata=license=MIT",
+      "main.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn inspect_wait() {
-    let r = flags_from_vec(svec!["deno", "run", "--inspect-wait", "foo.js"]);
+  fn compile_with_include_dir() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--include-dir",
+      "static",
+      "--include-dir=templates",
+      "main.ts"
+    ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "foo.js".to_string(),
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: None,
+          args: vec![],
+          target: None,
+          include: vec![],
+          snapshot: vec![],
+          icon: None,
+          metadata: vec![],
+          sign: None,
+          all_targets: false,
+          include_dirs: vec![
+            PathBuf::from("static"),
+            PathBuf::from("templates")
+          ],
+          self_update: None,
         }),
-        inspect_wait: Some("127.0.0.1:9229".parse().unwrap()),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+  }
 
+  #[test]
+  fn compile_all_targets() {
     let r = flags_from_vec(svec![
       "deno",
-      "run",
-      "--inspect-wait=127.0.0.1:3567",
-      "foo.js"
+      "compile",
+      "--all-targets",
+      "--output",
+      "dist/",
+      "main.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
-        subcommand: DenoSubcommand::Run(RunFlags {
-          script: "foo.js".to_string(),
+        subcommand: DenoSubcommand::Compile(CompileFlags {
+          source_file: "main.ts".to_string(),
+          output: Some(PathBuf::from("dist/")),
+          args: vec![],
+          target: None,
+          include: vec![],
+          snapshot: vec![],
+          icon: None,
+          metadata: vec![],
+          sign: None,
+          all_targets: true,
+          include_dirs: vec![],
+          self_update: None,
         }),
-        inspect_wait: Some("127.0.0.1:3567".parse().unwrap()),
+        type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--all-targets",
+      "--target",
+      "x86_64-pc-windows-msvc",
+      "main.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
-  fn compile() {
+  fn compile_with_update_url() {
     let r = flags_from_vec(svec![
       "deno",
       "compile",
-      "https://deno.land/std/examples/colors.ts"
+      "--update-url=https://example.com/app/manifest.json",
+      "--update-key=MCowBQYDK2VwAyEA",
+      "--app-version=1.2.0",
+      "main.ts"
     ]);
     assert_eq!(
       r.unwrap(),
       Flags {
         subcommand: DenoSubcommand::Compile(CompileFlags {
-          source_file: "https://deno.land/std/examples/colors.ts".to_string(),
+          source_file: "main.ts".to_string(),
           output: None,
           args: vec![],
           target: None,
-          include: vec![]
+          include: vec![],
+          snapshot: vec![],
+          icon: None,
+          metadata: vec![],
+          sign: None,
+          all_targets: false,
+          include_dirs: vec![],
+          self_update: Some(CompileSelfUpdateFlags {
+            url: Url::parse("https://example.com/app/manifest.json").unwrap(),
+            public_key: "MCowBQYDK2VwAyEA".to_string(),
+            version: "1.2.0".to_string(),
+          }),
         }),
         type_check_mode: TypeCheckMode::Local,
         ..Flags::default()
       }
     );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "compile",
+      "--update-url=https://example.com/app/manifest.json",
+      "main.ts"
+    ]);
+    assert!(r.is_err());
   }
 
   #[test]
@@ -6114,7 +10227,14 @@ mod tests {
           output: Some(PathBuf::from("colors")),
           args: svec!["foo", "bar"],
           target: None,
-          include: vec![]
+          include: vec![],
+          snapshot: vec![],
+          icon: None,
+          metadata: vec![],
+          sign: None,
+          all_targets: false,
+          include_dirs: vec![],
+          self_update: None,
         }),
         import_map_path: Some("import_map.json".to_string()),
         no_remote: true,
@@ -6293,6 +10413,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         argv: svec!["hello", "world"],
         ..Flags::default()
@@ -6306,6 +10427,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         ..Flags::default()
       }
@@ -6318,6 +10440,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: Some("foo".to_string()),
           task: Some("build".to_string()),
+          recursive: None,
         }),
         ..Flags::default()
       }
@@ -6342,6 +10465,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         argv: svec!["--", "hello", "world"],
         config_flag: ConfigFlag::Path("deno.json".to_owned()),
@@ -6358,6 +10482,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: Some("foo".to_string()),
           task: Some("build".to_string()),
+          recursive: None,
         }),
         argv: svec!["--", "hello", "world"],
         ..Flags::default()
@@ -6375,6 +10500,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         argv: svec!["--"],
         ..Flags::default()
@@ -6391,6 +10517,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         argv: svec!["-1", "--test"],
         ..Flags::default()
@@ -6407,6 +10534,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         argv: svec!["--test"],
         ..Flags::default()
@@ -6425,6 +10553,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: Some("build".to_string()),
+          recursive: None,
         }),
         unstable: true,
         log_level: Some(log::Level::Error),
@@ -6433,6 +10562,90 @@ mod tests {
     );
   }
 
+  #[test]
+  fn task_subcommand_recursive() {
+    let r = flags_from_vec(svec!["deno", "task", "-r", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          recursive: Some(Default::default()),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "task",
+      "--recursive",
+      "--filter",
+      "packages/*",
+      "--changed",
+      "build"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          recursive: Some(TaskRecursiveFlags {
+            filter: Some("packages/*".to_string()),
+            changed: Some("HEAD".to_string()),
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "task", "-r", "--changed=main", "build"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          recursive: Some(TaskRecursiveFlags {
+            filter: None,
+            changed: Some("main".to_string()),
+          }),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    // the selection flags require --recursive
+    let r = flags_from_vec(svec!["deno", "task", "--filter", "a", "build"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn task_subcommand_env_file() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "task",
+      "--env-file",
+      "--env-file=.env.local",
+      "build"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Task(TaskFlags {
+          cwd: None,
+          task: Some("build".to_string()),
+          recursive: None,
+        }),
+        env_file: vec![PathBuf::from(".env"), PathBuf::from(".env.local")],
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn task_subcommand_empty() {
     let r = flags_from_vec(svec!["deno", "task"]);
@@ -6442,6 +10655,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: None,
+          recursive: None,
         }),
         ..Flags::default()
       }
@@ -6457,6 +10671,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: None,
+          recursive: None,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
@@ -6473,6 +10688,7 @@ mod tests {
         subcommand: DenoSubcommand::Task(TaskFlags {
           cwd: None,
           task: None,
+          recursive: None,
         }),
         config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
         ..Flags::default()
@@ -6521,6 +10737,9 @@ mod tests {
             include: vec![PathBuf::from("dir1/"), PathBuf::from("dir2/")],
             ignore: vec![],
           },
+          save_baseline: None,
+          baseline: None,
+          regression_threshold: None,
         }),
         unstable: true,
         no_npm: true,
@@ -6549,6 +10768,9 @@ mod tests {
             include: vec![],
             ignore: vec![],
           },
+          save_baseline: None,
+          baseline: None,
+          regression_threshold: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -6558,6 +10780,40 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bench_baseline() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--save-baseline=main",
+      "--baseline=baseline.json",
+      "--regression-threshold=5",
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Bench(BenchFlags {
+          filter: None,
+          json: false,
+          no_run: false,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          save_baseline: Some("main".to_string()),
+          baseline: Some("baseline.json".to_string()),
+          regression_threshold: Some(5),
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "bench", "--regression-threshold=5"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_with_check() {
     let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
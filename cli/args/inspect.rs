@@ -0,0 +1,127 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::net::SocketAddr;
+
+use super::DenoSubcommand;
+use super::Flags;
+
+/// The environment variable that passes the inspector configuration of
+/// `--inspect-children` on to child deno processes, as `<mode>@<host>` where
+/// the host is the inspector server of the root process.
+const INSPECT_PARENT_ENV_VAR: &str = "DENO_INSPECT_PARENT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InspectMode {
+  Inspect,
+  Wait,
+  Brk,
+}
+
+impl InspectMode {
+  fn from_flags(flags: &Flags) -> Self {
+    if flags.inspect_brk.is_some() {
+      InspectMode::Brk
+    } else if flags.inspect_wait.is_some() {
+      InspectMode::Wait
+    } else {
+      InspectMode::Inspect
+    }
+  }
+
+  fn as_str(&self) -> &'static str {
+    match self {
+      InspectMode::Inspect => "inspect",
+      InspectMode::Wait => "wait",
+      InspectMode::Brk => "brk",
+    }
+  }
+}
+
+fn parse_inspect_parent(value: &str) -> Option<(InspectMode, SocketAddr)> {
+  let (mode, host) = value.split_once('@')?;
+  let mode = match mode {
+    "inspect" => InspectMode::Inspect,
+    "wait" => InspectMode::Wait,
+    "brk" => InspectMode::Brk,
+    _ => return None,
+  };
+  Some((mode, host.parse().ok()?))
+}
+
+/// Makes a deno process that was spawned by a process with
+/// `--inspect-children` inspectable in the same way, on an available port,
+/// unless it has inspector flags of its own.
+pub fn inherit_inspect_flags(flags: &mut Flags) {
+  if flags.inspect.is_some()
+    || flags.inspect_brk.is_some()
+    || flags.inspect_wait.is_some()
+  {
+    return;
+  }
+  if !matches!(
+    flags.subcommand,
+    DenoSubcommand::Cron(_)
+      | DenoSubcommand::Eval(_)
+      | DenoSubcommand::Repl(_)
+      | DenoSubcommand::Run(_)
+      | DenoSubcommand::Serve(_)
+      | DenoSubcommand::Test(_)
+  ) {
+    return;
+  }
+  let (mode, parent) = match std::env::var(INSPECT_PARENT_ENV_VAR)
+    .ok()
+    .and_then(|value| parse_inspect_parent(&value))
+  {
+    Some(inspect_parent) => inspect_parent,
+    None => return,
+  };
+  let host = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 0);
+  match mode {
+    InspectMode::Inspect => flags.inspect = Some(host),
+    InspectMode::Wait => flags.inspect_wait = Some(host),
+    InspectMode::Brk => flags.inspect_brk = Some(host),
+  }
+  flags.inspect_children = true;
+  flags.inspect_parent = Some(parent);
+}
+
+/// Passes the inspector configuration on to the child processes, whose
+/// inspectors are registered with the server of the root process.
+pub fn set_inspect_parent_env_var(flags: &Flags, host: SocketAddr) {
+  let mut root = flags.inspect_parent.unwrap_or(host);
+  if root.ip().is_unspecified() {
+    root.set_ip(match root.ip() {
+      IpAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+      IpAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+    });
+  }
+  let mode = InspectMode::from_flags(flags);
+  std::env::set_var(
+    INSPECT_PARENT_ENV_VAR,
+    format!("{}@{}", mode.as_str(), root),
+  );
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_inspect_parent() {
+    assert_eq!(
+      parse_inspect_parent("wait@127.0.0.1:9229"),
+      Some((InspectMode::Wait, "127.0.0.1:9229".parse().unwrap()))
+    );
+    assert_eq!(
+      parse_inspect_parent("brk@[::1]:9230"),
+      Some((InspectMode::Brk, "[::1]:9230".parse().unwrap()))
+    );
+    assert_eq!(parse_inspect_parent("wait"), None);
+    assert_eq!(parse_inspect_parent("debug@127.0.0.1:9229"), None);
+    assert_eq!(parse_inspect_parent("inspect@localhost"), None);
+  }
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -10,6 +11,7 @@ use deno_core::error::AnyError;
 use deno_core::futures::stream::FuturesOrdered;
 use deno_core::futures::StreamExt;
 use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
 use deno_npm::registry::NpmRegistryApi;
 use deno_npm::resolution::SerializedNpmResolutionSnapshot;
 use deno_npm::resolution::SerializedNpmResolutionSnapshotPackage;
@@ -19,7 +21,10 @@ use deno_semver::npm::NpmPackageReq;
 
 use crate::args::config_file::LockConfig;
 use crate::args::ConfigFile;
+use crate::cache::FastInsecureHasher;
 use crate::npm::CliNpmRegistryApi;
+use crate::util::fs::atomic_write_file;
+use crate::util::fs::LaxSingleProcessFsFlag;
 use crate::Flags;
 
 use super::DenoSubcommand;
@@ -74,6 +79,68 @@ pub fn discover(
   Ok(Some(lockfile))
 }
 
+/// Writes the lockfile when its content changed, in a way that's safe when
+/// other processes write it at the same time.
+///
+/// The writes of the processes are serialized with a file lock, the entries
+/// that other processes wrote since the lockfile was read are kept, and the
+/// file is replaced atomically so it's never read partially written.
+///
+/// With `--frozen`, an error is returned instead of writing the lockfile.
+pub async fn write_lockfile(
+  lockfile: &Mutex<Lockfile>,
+  frozen: bool,
+) -> Result<(), AnyError> {
+  let filename = {
+    let lockfile = lockfile.lock();
+    if !lockfile.has_content_changed && !lockfile.overwrite {
+      return Ok(());
+    }
+    if frozen {
+      bail!(
+        "The lockfile is out of date and --frozen is specified: \"{}\". Run without --frozen to update it.",
+        lockfile.filename.display()
+      );
+    }
+    lockfile.filename.clone()
+  };
+  let _flag = LaxSingleProcessFsFlag::lock(
+    write_lock_path(&filename),
+    "Blocking waiting for another process to write the lockfile.",
+  )
+  .await;
+
+  let mut lockfile = lockfile.lock();
+  if !lockfile.overwrite {
+    if let Ok(on_disk) = Lockfile::new(filename.clone(), false) {
+      let content = &mut lockfile.content;
+      for (key, value) in on_disk.content.remote {
+        content.remote.entry(key).or_insert(value);
+      }
+      for (key, value) in on_disk.content.npm.specifiers {
+        content.npm.specifiers.entry(key).or_insert(value);
+      }
+      for (key, value) in on_disk.content.npm.packages {
+        content.npm.packages.entry(key).or_insert(value);
+      }
+    }
+  }
+  let mut text = serde_json::to_string_pretty(&lockfile.content)?;
+  text.push('\n');
+  atomic_write_file(&filename, text, 0o644).with_context(|| {
+    format!("Failed writing lockfile at {}", filename.display())
+  })?;
+  Ok(())
+}
+
+/// The file that's locked while writing the lockfile, which is kept out of
+/// the directory of the lockfile.
+fn write_lock_path(filename: &Path) -> PathBuf {
+  let mut hasher = FastInsecureHasher::new();
+  hasher.write_str(&filename.to_string_lossy());
+  std::env::temp_dir().join(format!("deno_lockfile_{}.lock", hasher.finish()))
+}
+
 pub async fn snapshot_from_lockfile(
   lockfile: Arc<Mutex<Lockfile>>,
   api: &CliNpmRegistryApi,
@@ -161,3 +228,33 @@ pub async fn snapshot_from_lockfile(
   .into_valid()
   .context("The lockfile is corrupt. You can recreate it with --lock-write")
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  #[tokio::test]
+  async fn write_lockfile_keeps_entries_of_other_processes() {
+    let temp_dir = TempDir::new();
+    let filename = temp_dir.path().join("deno.lock");
+    // both processes read the lockfile before either wrote it
+    let first = Mutex::new(Lockfile::new(filename.clone(), false).unwrap());
+    let second = Mutex::new(Lockfile::new(filename.clone(), false).unwrap());
+
+    assert!(first
+      .lock()
+      .check_or_insert_remote("https://deno.land/x/a.ts", "a"));
+    write_lockfile(&first, false).await.unwrap();
+    assert!(second
+      .lock()
+      .check_or_insert_remote("https://deno.land/x/b.ts", "b"));
+    write_lockfile(&second, false).await.unwrap();
+
+    let lockfile = Lockfile::new(filename, false).unwrap();
+    assert_eq!(
+      lockfile.content.remote.keys().collect::<Vec<_>>(),
+      vec!["https://deno.land/x/a.ts", "https://deno.land/x/b.ts"]
+    );
+  }
+}
@@ -1,12 +1,16 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 mod config_file;
+mod env_file;
 mod flags;
 mod flags_allow_net;
 mod import_map;
+mod inspect;
 mod lockfile;
 pub mod package_json;
 
+use self::config_file::LibOverride;
+use self::config_file::WorkerPermissions;
 pub use self::import_map::resolve_import_map_from_specifier;
 use self::lockfile::snapshot_from_lockfile;
 use self::package_json::PackageJsonDeps;
@@ -17,19 +21,43 @@ use deno_semver::npm::NpmPackageReqReference;
 use indexmap::IndexMap;
 
 pub use config_file::BenchConfig;
+pub use config_file::CheckUnit;
+pub use config_file::CheckUnitProject;
 pub use config_file::CompilerOptions;
 pub use config_file::ConfigFile;
+pub use config_file::CyclePolicy;
+pub use config_file::CyclesConfig;
+pub use config_file::DocConfig;
+pub use config_file::DocLintConfig;
 pub use config_file::EmitConfigOptions;
+pub use config_file::FetchConfig;
 pub use config_file::FilesConfig;
 pub use config_file::FmtOptionsConfig;
+pub use config_file::FmtOverride;
 pub use config_file::JsxImportSourceConfig;
+pub use config_file::LicensesConfig;
+pub use config_file::LintFailOn;
 pub use config_file::LintRulesConfig;
+pub use config_file::LintSeverity;
+pub use config_file::LintSeverityConfig;
+pub use config_file::OriginTlsConfig;
+pub use config_file::PermissionSetConfig;
+pub use config_file::PermissionSetDenyConfig;
+pub use config_file::PermissionSetValue;
+pub use config_file::ProfileConfig;
 pub use config_file::ProseWrap;
+pub use config_file::RemoteCacheConfig;
 pub use config_file::TsConfig;
 pub use config_file::TsConfigForEmit;
 pub use config_file::TsConfigType;
 pub use config_file::TsTypeLib;
+pub use config_file::UrlRewriteRules;
+pub use config_file::VendorMode;
+pub use config_file::WatchConfig;
+pub use env_file::load_env_files;
 pub use flags::*;
+pub use inspect::inherit_inspect_flags;
+pub use lockfile::write_lockfile;
 pub use lockfile::Lockfile;
 pub use lockfile::LockfileError;
 
@@ -41,6 +69,7 @@ use deno_core::error::AnyError;
 use deno_core::normalize_path;
 use deno_core::parking_lot::Mutex;
 use deno_core::serde_json;
+use deno_core::url::Host;
 use deno_core::url::Url;
 use deno_runtime::colors;
 use deno_runtime::deno_node::PackageJson;
@@ -50,23 +79,35 @@ use deno_runtime::deno_tls::rustls_native_certs::load_native_certs;
 use deno_runtime::deno_tls::rustls_pemfile;
 use deno_runtime::deno_tls::webpki_roots;
 use deno_runtime::inspector_server::InspectorServer;
+use deno_runtime::ops::worker_host::WorkerPermissionsPolicy;
+use deno_runtime::permissions::PermissionDenyRules;
 use deno_runtime::permissions::PermissionsOptions;
 use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::env;
 use std::io::BufReader;
 use std::io::Cursor;
+use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::net::ToSocketAddrs;
 use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::cache::DenoDir;
 use crate::file_fetcher::FileFetcher;
 use crate::npm::CliNpmRegistryApi;
 use crate::npm::NpmProcessState;
+use crate::npm::NpmRc;
+use crate::tools::profiler::ProfilerOptions;
+use crate::util::file_watcher::WatchFilter;
+use crate::util::fs::canonicalize_file_specifier;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
+use crate::util::glob::GlobPattern;
+use crate::util::path::specifier_to_file_path;
 use crate::version;
 
 use self::config_file::FmtConfig;
@@ -80,6 +121,9 @@ pub enum CacheSetting {
   /// Only the cached files should be used.  Any files not in the cache will
   /// error.  This is the equivalent of `--cached-only` in the CLI.
   Only,
+  /// Like `Only`, but for `--frozen`, which also forbids updating the
+  /// lockfile.
+  Frozen,
   /// No cached source files should be used, and all files should be reloaded.
   /// This is the equivalent of `--reload` in the CLI.
   ReloadAll,
@@ -91,16 +135,32 @@ pub enum CacheSetting {
   /// headers and other metadata associated with a cached response, reloading
   /// any cached "non-fresh" cached responses.
   RespectHeaders,
+  /// Cached responses that are stale according to their HTTP caching headers
+  /// are revalidated with a conditional request, while the others are used,
+  /// except for the ones in the list which are reloaded like with
+  /// `ReloadSome`. This is the equivalent of `--reload=stale` or
+  /// `--reload=stale,https://deno.land/std` in the CLI.
+  RevalidateStale(Vec<String>),
   /// The cached source files should be used for local modules.  This is the
   /// default behavior of the CLI.
   Use,
 }
 
 impl CacheSetting {
+  /// The flag that restricts loading to the cached files, if any, which the
+  /// errors for files not in the cache mention.
+  pub fn cache_only_flag(&self) -> Option<&'static str> {
+    match self {
+      CacheSetting::Only => Some("--cached-only"),
+      CacheSetting::Frozen => Some("--frozen"),
+      _ => None,
+    }
+  }
+
   pub fn should_use_for_npm_package(&self, package_name: &str) -> bool {
     match self {
       CacheSetting::ReloadAll => false,
-      CacheSetting::ReloadSome(list) => {
+      CacheSetting::ReloadSome(list) | CacheSetting::RevalidateStale(list) => {
         if list.iter().any(|i| i == "npm:") {
           return false;
         }
@@ -121,6 +181,9 @@ pub struct BenchOptions {
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub save_baseline: Option<String>,
+  pub baseline: Option<String>,
+  pub regression_threshold: u32,
 }
 
 impl BenchOptions {
@@ -137,6 +200,9 @@ impl BenchOptions {
       filter: bench_flags.filter,
       json: bench_flags.json,
       no_run: bench_flags.no_run,
+      save_baseline: bench_flags.save_baseline,
+      baseline: bench_flags.baseline,
+      regression_threshold: bench_flags.regression_threshold.unwrap_or(10),
     })
   }
 }
@@ -147,6 +213,8 @@ pub struct FmtOptions {
   pub check: bool,
   pub options: FmtOptionsConfig,
   pub files: FilesConfig,
+  /// The options of paths, which take precedence over `options` in order.
+  pub overrides: Vec<FmtOverride>,
 }
 
 impl FmtOptions {
@@ -165,8 +233,20 @@ impl FmtOptions {
     } else {
       false
     };
-    let (maybe_config_options, maybe_config_files) =
-      maybe_fmt_config.map(|c| (c.options, c.files)).unzip();
+    let (maybe_config_options, maybe_config_files, config_overrides) =
+      match maybe_fmt_config {
+        Some(c) => (Some(c.options), Some(c.files), c.overrides),
+        None => (None, None, Vec::new()),
+      };
+    // the flags take precedence over the options of the overrides
+    let flag_options = resolve_fmt_options(maybe_fmt_flags.as_ref(), None);
+    let overrides = config_overrides
+      .into_iter()
+      .map(|mut o| {
+        o.options.merge(&flag_options);
+        o
+      })
+      .collect();
 
     Ok(Self {
       is_stdin,
@@ -179,8 +259,18 @@ impl FmtOptions {
         maybe_config_files,
         maybe_fmt_flags.map(|f| f.files),
       ),
+      overrides,
     })
   }
+
+  /// Gets the options that a file is formatted with.
+  pub fn options_for_path(&self, file_path: &Path) -> FmtOptionsConfig {
+    let mut options = self.options.clone();
+    for o in self.overrides.iter().filter(|o| o.matches(file_path)) {
+      options.merge(&o.options);
+    }
+    options
+  }
 }
 
 fn resolve_fmt_options(
@@ -219,6 +309,10 @@ fn resolve_fmt_options(
     if let Some(no_semis) = &fmt_flags.no_semicolons {
       options.semi_colons = Some(!no_semis);
     }
+
+    if let Some(organize_imports) = fmt_flags.organize_imports {
+      options.organize_imports = Some(organize_imports);
+    }
   }
 
   options
@@ -235,20 +329,47 @@ pub struct TestOptions {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: NonZeroUsize,
   pub trace_ops: bool,
+  pub isolate_pool: bool,
+  pub reporter: TestReporterKind,
+  pub reporter_output: Option<PathBuf>,
+  pub shard: Option<TestShard>,
+  pub global_setup: Option<ModuleSpecifier>,
+  pub global_teardown: Option<ModuleSpecifier>,
 }
 
 impl TestOptions {
   pub fn resolve(
     maybe_test_config: Option<TestConfig>,
     maybe_test_flags: Option<TestFlags>,
+    initial_cwd: &Path,
   ) -> Result<Self, AnyError> {
     let test_flags = maybe_test_flags.unwrap_or_default();
+    let test_config = maybe_test_config.unwrap_or_default();
+    // the modules of the flags are relative to the cwd and take precedence
+    // over the ones of the configuration file
+    let global_setup = match &test_flags.global_setup {
+      Some(module) => Some(resolve_url_or_path(module, initial_cwd)?),
+      None => test_config.global_setup,
+    };
+    let global_teardown = match &test_flags.global_teardown {
+      Some(module) => Some(resolve_url_or_path(module, initial_cwd)?),
+      None => test_config.global_teardown,
+    };
+    let reporter = match test_flags.reporter.as_deref() {
+      None | Some("pretty") => TestReporterKind::Pretty,
+      Some("json") => TestReporterKind::Json,
+      Some("junit") => TestReporterKind::Junit,
+      Some("tap") => TestReporterKind::Tap,
+      Some(reporter) => bail!("Invalid test reporter \"{}\"", reporter),
+    };
+    if reporter == TestReporterKind::Pretty
+      && test_flags.reporter_output.is_some()
+    {
+      bail!("--reporter-output requires a --reporter other than \"pretty\"");
+    }
 
     Ok(Self {
-      files: resolve_files(
-        maybe_test_config.map(|c| c.files),
-        Some(test_flags.files),
-      ),
+      files: resolve_files(Some(test_config.files), Some(test_flags.files)),
       allow_none: test_flags.allow_none,
       concurrent_jobs: test_flags
         .concurrent_jobs
@@ -259,10 +380,25 @@ impl TestOptions {
       no_run: test_flags.no_run,
       shuffle: test_flags.shuffle,
       trace_ops: test_flags.trace_ops,
+      isolate_pool: test_flags.isolate_pool,
+      reporter,
+      reporter_output: test_flags.reporter_output,
+      shard: test_flags.shard,
+      global_setup,
+      global_teardown,
     })
   }
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TestReporterKind {
+  #[default]
+  Pretty,
+  Json,
+  Junit,
+  Tap,
+}
+
 #[derive(Clone, Default, Debug)]
 pub enum LintReporterKind {
   #[default]
@@ -277,11 +413,18 @@ pub struct LintOptions {
   pub files: FilesConfig,
   pub is_stdin: bool,
   pub reporter_kind: LintReporterKind,
+  pub plugins: Vec<ModuleSpecifier>,
+  /// Apply the automatic fixes of the diagnostics with `--fix`.
+  pub fix: bool,
+  /// The options that the fixed code is formatted with.
+  pub fmt_options: FmtOptions,
+  pub severity: LintSeverityConfig,
+  pub fail_on: LintFailOn,
 }
 
 impl LintOptions {
   pub fn resolve(
-    maybe_lint_config: Option<LintConfig>,
+    mut maybe_lint_config: Option<LintConfig>,
     mut maybe_lint_flags: Option<LintFlags>,
   ) -> Result<Self, AnyError> {
     let is_stdin = if let Some(lint_flags) = maybe_lint_flags.as_mut() {
@@ -322,6 +465,7 @@ impl LintOptions {
       }
     }
 
+    let fix = maybe_lint_flags.as_ref().map(|f| f.fix).unwrap_or(false);
     let (
       maybe_file_flags,
       maybe_rules_tags,
@@ -338,11 +482,24 @@ impl LintOptions {
       })
       .unwrap_or_default();
 
+    let plugins = maybe_lint_config
+      .as_mut()
+      .map(|c| std::mem::take(&mut c.plugins))
+      .unwrap_or_default();
+    let (severity, fail_on) = maybe_lint_config
+      .as_mut()
+      .map(|c| (std::mem::take(&mut c.severity), c.fail_on))
+      .unwrap_or_default();
     let (maybe_config_files, maybe_config_rules) =
       maybe_lint_config.map(|c| (c.files, c.rules)).unzip();
     Ok(Self {
       reporter_kind: maybe_reporter_kind.unwrap_or_default(),
       is_stdin,
+      plugins,
+      fix,
+      fmt_options: Default::default(),
+      severity,
+      fail_on,
       files: resolve_files(maybe_config_files, Some(maybe_file_flags)),
       rules: resolve_lint_rules_options(
         maybe_config_rules,
@@ -516,12 +673,19 @@ pub struct CliOptions {
   maybe_config_file: Option<ConfigFile>,
   maybe_package_json: Option<PackageJson>,
   maybe_lockfile: Option<Arc<Mutex<Lockfile>>>,
+  maybe_profile: Option<ProfileConfig>,
+  lib_overrides: Vec<LibOverride>,
+  check_units: Vec<CheckUnit>,
+  worker_permissions: Vec<WorkerPermissions>,
+  permission_deny_rules: PermissionDenyRules,
+  vendor_mode: VendorMode,
+  maybe_vendor_import_map: Option<PathBuf>,
   overrides: CliOptionOverrides,
 }
 
 impl CliOptions {
   pub fn new(
-    flags: Flags,
+    mut flags: Flags,
     initial_cwd: PathBuf,
     maybe_config_file: Option<ConfigFile>,
     maybe_lockfile: Option<Lockfile>,
@@ -541,6 +705,46 @@ impl CliOptions {
       eprintln!("{}", colors::yellow(msg));
     }
 
+    if let Some(config_file) = &maybe_config_file {
+      for feature in config_file.to_unstable_features()? {
+        if !flags.unstable_features.contains(&feature) {
+          flags.unstable_features.push(feature);
+        }
+      }
+    }
+
+    let maybe_profile = match &flags.profile {
+      Some(name) => match &maybe_config_file {
+        Some(config_file) => Some(config_file.to_profile_config(name)?),
+        None => bail!(
+          "--profile={} requires a configuration file that defines the profile.",
+          name
+        ),
+      },
+      None => None,
+    };
+
+    // --permission-set takes precedence over the permission set of the
+    // profile
+    let maybe_permission_set_name =
+      flags.permission_set.clone().or_else(|| {
+        maybe_profile
+          .as_ref()
+          .and_then(|profile| profile.permissions.clone())
+      });
+    let mut permission_deny_rules = PermissionDenyRules::default();
+    if let Some(name) = &maybe_permission_set_name {
+      let permission_set = match &maybe_config_file {
+        Some(config_file) => config_file.to_permission_set(name)?,
+        None => bail!(
+          "--permission-set={} requires a configuration file that defines the permission set.",
+          name
+        ),
+      };
+      permission_set.grant(&mut flags);
+      permission_deny_rules = permission_set.deny.to_deny_rules();
+    }
+
     let maybe_lockfile = maybe_lockfile.map(|l| Arc::new(Mutex::new(l)));
     let maybe_node_modules_folder = resolve_local_node_modules_folder(
       &initial_cwd,
@@ -549,6 +753,46 @@ impl CliOptions {
       maybe_package_json.as_ref(),
     )
     .with_context(|| "Resolving node_modules folder.")?;
+    if !flags.allow_scripts.is_empty() && maybe_node_modules_folder.is_none() {
+      log::warn!(
+        "{} --allow-scripts is ignored without a local node_modules directory. Use --node-modules-dir to create one.",
+        colors::yellow("Warning"),
+      );
+    }
+    let lib_overrides = match &maybe_config_file {
+      Some(config_file) => config_file.to_lib_overrides()?,
+      None => Vec::new(),
+    };
+    let check_units = match &maybe_config_file {
+      Some(config_file) => config_file.to_check_units()?,
+      None => Vec::new(),
+    };
+    let worker_permissions = match &maybe_config_file {
+      Some(config_file) => config_file.to_worker_permissions()?,
+      None => Vec::new(),
+    };
+    let vendor_mode = match &maybe_config_file {
+      // `deno vendor` downloads the remote modules to create the vendor
+      // directory, so it doesn't use an existing one
+      Some(_) if matches!(flags.subcommand, DenoSubcommand::Vendor(_)) => {
+        VendorMode::Ignore
+      }
+      Some(config_file) => config_file.to_vendor_mode()?,
+      None => VendorMode::Ignore,
+    };
+    let maybe_vendor_import_map = match &maybe_config_file {
+      Some(config_file) if vendor_mode != VendorMode::Ignore => {
+        let maybe_path = config_file.to_vendor_import_map_path();
+        if maybe_path.is_none() && vendor_mode == VendorMode::Only {
+          bail!(
+            "The configuration file \"{}\" sets \"vendor\" to \"only\", but no vendor directory was found next to it. Run `deno vendor` first.",
+            config_file.specifier
+          );
+        }
+        maybe_path
+      }
+      _ => None,
+    };
 
     Ok(Self {
       flags,
@@ -557,6 +801,13 @@ impl CliOptions {
       maybe_lockfile,
       maybe_package_json,
       maybe_node_modules_folder,
+      maybe_profile,
+      lib_overrides,
+      check_units,
+      worker_permissions,
+      permission_deny_rules,
+      vendor_mode,
+      maybe_vendor_import_map,
       overrides: Default::default(),
     })
   }
@@ -604,12 +855,51 @@ impl CliOptions {
     &self.initial_cwd
   }
 
+  /// The canonicalized directory that local imports are restricted to when
+  /// using `--restrict-local-imports`. This is the directory of the
+  /// configuration file or otherwise the current working directory.
+  pub fn maybe_local_import_root(&self) -> Option<PathBuf> {
+    if !self.flags.restrict_local_imports {
+      return None;
+    }
+    let maybe_config_dir = self
+      .maybe_config_file
+      .as_ref()
+      .and_then(|config_file| config_file.specifier.to_file_path().ok())
+      .and_then(|path| path.parent().map(|parent| parent.to_path_buf()));
+    let root = maybe_config_dir.unwrap_or_else(|| self.initial_cwd.clone());
+    Some(canonicalize_path_maybe_not_exists(&root).unwrap_or(root))
+  }
+
+  /// The worker modules to prepare before running the program with
+  /// `--prepare-worker`.
+  pub fn prepare_worker_modules(
+    &self,
+  ) -> Result<Vec<ModuleSpecifier>, AnyError> {
+    self
+      .flags
+      .prepare_workers
+      .iter()
+      .map(|specifier| {
+        resolve_url_or_path(specifier, &self.initial_cwd)
+          .map_err(|err| err.into())
+      })
+      .collect()
+  }
+
   pub fn maybe_config_file_specifier(&self) -> Option<ModuleSpecifier> {
     self.maybe_config_file.as_ref().map(|f| f.specifier.clone())
   }
 
+  /// Gets if any unstable APIs are enabled. The unstable type declarations
+  /// are shared by all the features, so they're included as soon as one of
+  /// them is enabled.
+  fn has_unstable_apis(&self) -> bool {
+    self.flags.unstable || !self.flags.unstable_features.is_empty()
+  }
+
   pub fn ts_type_lib_window(&self) -> TsTypeLib {
-    if self.flags.unstable {
+    if self.has_unstable_apis() {
       TsTypeLib::UnstableDenoWindow
     } else {
       TsTypeLib::DenoWindow
@@ -617,16 +907,93 @@ impl CliOptions {
   }
 
   pub fn ts_type_lib_worker(&self) -> TsTypeLib {
-    if self.flags.unstable {
+    if self.has_unstable_apis() {
       TsTypeLib::UnstableDenoWorker
     } else {
       TsTypeLib::DenoWorker
     }
   }
 
+  /// Resolves the type library to type check an entry point with, which is
+  /// the `default` one unless the configuration file overrides it for the
+  /// entry point or a directory that contains it.
+  pub fn resolve_ts_type_lib(
+    &self,
+    specifier: &ModuleSpecifier,
+    default: &TsTypeLib,
+  ) -> TsTypeLib {
+    let maybe_lib_override = self
+      .lib_overrides
+      .iter()
+      .filter(|lib_override| lib_override.matches(specifier))
+      .max_by_key(|lib_override| lib_override.specifier.as_str().len());
+    let mut lib = match maybe_lib_override {
+      Some(lib_override) => lib_override.lib.clone(),
+      None => return default.clone(),
+    };
+    let has_deno_lib = lib
+      .iter()
+      .any(|name| name == "deno.window" || name == "deno.worker");
+    if self.has_unstable_apis()
+      && has_deno_lib
+      && !lib.iter().any(|name| name == "deno.unstable")
+    {
+      lib.push("deno.unstable".to_string());
+    }
+    TsTypeLib::Custom(lib)
+  }
+
+  /// The policy that restricts the permissions of the workers whose main
+  /// module is in the paths of the `"workerPermissions"` configuration, using
+  /// the most specific path that matches.
+  pub fn worker_permissions_policy(&self) -> Option<WorkerPermissionsPolicy> {
+    if self.worker_permissions.is_empty() {
+      return None;
+    }
+    let worker_permissions = self.worker_permissions.clone();
+    Some(WorkerPermissionsPolicy(Arc::new(move |specifier| {
+      worker_permissions
+        .iter()
+        .filter(|worker_permissions| worker_permissions.matches(specifier))
+        .max_by_key(|worker_permissions| {
+          worker_permissions.specifier.as_str().len()
+        })
+        .map(|worker_permissions| worker_permissions.permissions.clone())
+    })))
+  }
+
+  /// Groups the entry points by the type library they're type checked with,
+  /// keeping the order of the entry points.
+  pub fn group_roots_by_ts_type_lib(
+    &self,
+    roots: &[ModuleSpecifier],
+    default: &TsTypeLib,
+  ) -> Vec<(TsTypeLib, Vec<ModuleSpecifier>)> {
+    let mut groups: Vec<(TsTypeLib, Vec<ModuleSpecifier>)> = Vec::new();
+    for root in roots {
+      let lib = self.resolve_ts_type_lib(root, default);
+      match groups.iter_mut().find(|(group_lib, _)| *group_lib == lib) {
+        Some((_, group_roots)) => group_roots.push(root.clone()),
+        None => groups.push((lib, vec![root.clone()])),
+      }
+    }
+    groups
+  }
+
+  /// The units of the project that are type checked separately, sorted so
+  /// that each unit comes after the units it depends on.
+  pub fn check_units(&self) -> &[CheckUnit] {
+    &self.check_units
+  }
+
   pub fn cache_setting(&self) -> CacheSetting {
-    if self.flags.cached_only {
+    if self.flags.frozen {
+      CacheSetting::Frozen
+    } else if self.flags.cached_only {
       CacheSetting::Only
+    } else if self.flags.reload_stale {
+      // the other values of `--reload=stale,<url>` are still reloaded
+      CacheSetting::RevalidateStale(self.flags.cache_blocklist.clone())
     } else if !self.flags.cache_blocklist.is_empty() {
       CacheSetting::ReloadSome(self.flags.cache_blocklist.clone())
     } else if self.flags.reload {
@@ -648,6 +1015,17 @@ impl CliOptions {
   ) -> Result<Option<ModuleSpecifier>, AnyError> {
     match self.overrides.import_map_specifier.clone() {
       Some(maybe_path) => Ok(maybe_path),
+      // the import map generated by `deno vendor` already includes the import
+      // map of the configuration file, so it takes its place
+      None
+        if self.flags.import_map_path.is_none()
+          && self.maybe_vendor_import_map.is_some() =>
+      {
+        let path = self.maybe_vendor_import_map.as_ref().unwrap();
+        Ok(Some(ModuleSpecifier::from_file_path(path).map_err(
+          |_| anyhow!("Invalid vendor import map path: {}", path.display()),
+        )?))
+      }
       None => resolve_import_map_specifier(
         self.flags.import_map_path.as_deref(),
         self.maybe_config_file.as_ref(),
@@ -676,7 +1054,38 @@ impl CliOptions {
     .map(Some)
   }
 
+  /// Resolves the import map of `deno test --import-map-overrides`, which
+  /// substitutes modules after they were resolved.
+  pub async fn resolve_import_map_overrides(
+    &self,
+    file_fetcher: &FileFetcher,
+  ) -> Result<Option<ImportMap>, AnyError> {
+    let path = match self.sub_command() {
+      DenoSubcommand::Test(TestFlags {
+        import_map_overrides: Some(path),
+        ..
+      }) => path,
+      _ => return Ok(None),
+    };
+    let specifier = resolve_url_or_path(path, self.initial_cwd())?;
+    resolve_import_map_from_specifier(&specifier, None, file_fetcher)
+      .await
+      .with_context(|| {
+        format!("Unable to load '{specifier}' import map overrides")
+      })
+      .map(Some)
+  }
+
   pub fn resolve_main_module(&self) -> Result<ModuleSpecifier, AnyError> {
+    let main_module = self.resolve_main_module_inner()?;
+    if self.flags.canonicalize_symlinks {
+      Ok(canonicalize_file_specifier(main_module))
+    } else {
+      Ok(main_module)
+    }
+  }
+
+  fn resolve_main_module_inner(&self) -> Result<ModuleSpecifier, AnyError> {
     match &self.flags.subcommand {
       DenoSubcommand::Bundle(bundle_flags) => {
         resolve_url_or_path(&bundle_flags.source_file, self.initial_cwd())
@@ -712,6 +1121,14 @@ impl CliOptions {
             .map_err(AnyError::from)
         }
       }
+      DenoSubcommand::Serve(serve_flags) => {
+        resolve_url_or_path(&serve_flags.script, self.initial_cwd())
+          .map_err(AnyError::from)
+      }
+      DenoSubcommand::Cron(cron_flags) => {
+        resolve_url_or_path(&cron_flags.script, self.initial_cwd())
+          .map_err(AnyError::from)
+      }
       _ => {
         bail!("No main module.")
       }
@@ -792,6 +1209,12 @@ impl CliOptions {
     self.maybe_node_modules_folder.clone()
   }
 
+  /// The npm packages whose install scripts may run when they are added to
+  /// the local node_modules directory.
+  pub fn allow_scripts(&self) -> &[String] {
+    &self.flags.allow_scripts
+  }
+
   pub fn node_modules_dir_specifier(&self) -> Option<ModuleSpecifier> {
     self
       .maybe_node_modules_folder
@@ -811,10 +1234,30 @@ impl CliOptions {
     &self,
     config_type: TsConfigType,
   ) -> Result<TsConfigForEmit, AnyError> {
-    config_file::get_ts_config_for_emit(
+    let mut ts_config_for_emit = config_file::get_ts_config_for_emit(
       config_type,
       self.maybe_config_file.as_ref(),
-    )
+    )?;
+    if let Some(compiler_options) = self
+      .maybe_profile
+      .as_ref()
+      .and_then(|profile| profile.compiler_options.as_ref())
+    {
+      let (value, maybe_ignored_options) = config_file::parse_compiler_options(
+        compiler_options,
+        self.maybe_config_file_specifier(),
+      )?;
+      ts_config_for_emit.ts_config.merge(&value);
+      if ts_config_for_emit.maybe_ignored_options.is_none() {
+        ts_config_for_emit.maybe_ignored_options = maybe_ignored_options;
+      }
+    }
+    Ok(ts_config_for_emit)
+  }
+
+  /// The build profile selected with `--profile`.
+  pub fn profile(&self) -> Option<&ProfileConfig> {
+    self.maybe_profile.as_ref()
   }
 
   /// Resolves the storage key to use based on the current flags, config, or main module.
@@ -847,8 +1290,78 @@ impl CliOptions {
       .inspect
       .or(self.flags.inspect_brk)
       .or(self.flags.inspect_wait);
-    maybe_inspect_host
-      .map(|host| InspectorServer::new(host, version::get_user_agent()))
+    // the child processes of a child process register with the root process
+    let server = InspectorServer::new_with_parent(
+      maybe_inspect_host?,
+      version::get_user_agent(),
+      self.flags.inspect_parent,
+      self.flags.inspect_children && self.flags.inspect_parent.is_none(),
+    );
+    if self.flags.inspect_children {
+      inspect::set_inspect_parent_env_var(&self.flags, server.host);
+    }
+    Some(server)
+  }
+
+  pub fn resolve_url_rewrite_rules(&self) -> Result<UrlRewriteRules, AnyError> {
+    if let Some(config_file) = &self.maybe_config_file {
+      config_file.to_url_rewrite_rules()
+    } else {
+      Ok(Default::default())
+    }
+  }
+
+  pub fn resolve_cycles_config(&self) -> Result<CyclesConfig, AnyError> {
+    if let Some(config_file) = &self.maybe_config_file {
+      config_file.to_cycles_config()
+    } else {
+      Ok(Default::default())
+    }
+  }
+
+  pub fn resolve_licenses_config(&self) -> Result<LicensesConfig, AnyError> {
+    if let Some(config_file) = &self.maybe_config_file {
+      config_file.to_licenses_config()
+    } else {
+      Ok(Default::default())
+    }
+  }
+
+  pub fn resolve_fetch_config(&self) -> Result<FetchConfig, AnyError> {
+    if let Some(config_file) = &self.maybe_config_file {
+      config_file.to_fetch_config()
+    } else {
+      Ok(Default::default())
+    }
+  }
+
+  pub fn resolve_origin_tls_configs(
+    &self,
+  ) -> Result<BTreeMap<String, OriginTlsConfig>, AnyError> {
+    if let Some(config_file) = &self.maybe_config_file {
+      config_file.to_origin_tls_configs()
+    } else {
+      Ok(Default::default())
+    }
+  }
+
+  /// Resolves the npm registries and credentials of the `.npmrc` files of
+  /// the user and the project, which is the directory of the package.json,
+  /// the config file or the current working directory.
+  pub fn resolve_npmrc(&self) -> Result<NpmRc, AnyError> {
+    let maybe_config_dir = self
+      .maybe_config_file
+      .as_ref()
+      .and_then(|config_file| config_file.specifier.to_file_path().ok())
+      .and_then(|path| path.parent().map(|dir| dir.to_path_buf()));
+    let project_dir = self
+      .maybe_package_json
+      .as_ref()
+      .and_then(|package_json| package_json.path.parent())
+      .map(|dir| dir.to_path_buf())
+      .or(maybe_config_dir)
+      .unwrap_or_else(|| self.initial_cwd.clone());
+    NpmRc::load(Some(&project_dir))
   }
 
   pub fn maybe_lock_file(&self) -> Option<Arc<Mutex<Lockfile>>> {
@@ -931,7 +1444,19 @@ impl CliOptions {
     } else {
       None
     };
-    LintOptions::resolve(maybe_lint_config, Some(lint_flags))
+    let mut lint_options =
+      LintOptions::resolve(maybe_lint_config, Some(lint_flags))?;
+    if lint_options.fix {
+      // the fixed code is formatted like `deno fmt` would format it
+      let maybe_fmt_config = if let Some(config_file) = &self.maybe_config_file
+      {
+        config_file.to_fmt_config()?
+      } else {
+        None
+      };
+      lint_options.fmt_options = FmtOptions::resolve(maybe_fmt_config, None)?;
+    }
+    Ok(lint_options)
   }
 
   pub fn resolve_test_options(
@@ -943,7 +1468,11 @@ impl CliOptions {
     } else {
       None
     };
-    TestOptions::resolve(maybe_test_config, Some(test_flags))
+    TestOptions::resolve(
+      maybe_test_config,
+      Some(test_flags),
+      self.initial_cwd(),
+    )
   }
 
   pub fn resolve_bench_options(
@@ -980,6 +1509,22 @@ impl CliOptions {
       .unwrap_or(false)
   }
 
+  /// The options of `--cpu-prof` and `--heap-snapshot-on-exit`, which are
+  /// `None` when neither is used.
+  pub fn profiler_options(&self) -> Option<ProfilerOptions> {
+    if !self.flags.cpu_prof && !self.flags.heap_snapshot_on_exit {
+      return None;
+    }
+    Some(ProfilerOptions {
+      dir: match &self.flags.prof_dir {
+        Some(dir) => self.initial_cwd.join(dir),
+        None => self.initial_cwd.clone(),
+      },
+      cpu_prof: self.flags.cpu_prof,
+      heap_snapshot: self.flags.heap_snapshot_on_exit,
+    })
+  }
+
   pub fn coverage_dir(&self) -> Option<String> {
     fn allow_coverage(sub_command: &DenoSubcommand) -> bool {
       match sub_command {
@@ -1024,6 +1569,11 @@ impl CliOptions {
     self.flags.inspect_wait
   }
 
+  /// If workers and child deno processes inherit the inspector configuration.
+  pub fn inspect_children(&self) -> bool {
+    self.flags.inspect_children
+  }
+
   pub fn log_level(&self) -> Option<log::Level> {
     self.flags.log_level
   }
@@ -1051,6 +1601,98 @@ impl CliOptions {
     self.flags.no_clear_screen
   }
 
+  /// The paths to watch in addition to the ones of the subcommand and the
+  /// ones to ignore in watch mode, from `--watch-include`, `--watch-exclude`
+  /// and the `"watch"` configuration.
+  pub fn watch_filter(&self) -> Result<WatchFilter, AnyError> {
+    // the changed paths are canonicalized, so the patterns must be as well
+    let canonicalize = |path: &Path| {
+      canonicalize_path_maybe_not_exists(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+    };
+    let mut filter = WatchFilter::default();
+    if let Some(config_file) = &self.maybe_config_file {
+      let watch_config = config_file.to_watch_config()?;
+      if let Some(config_dir) = specifier_to_file_path(&config_file.specifier)
+        .ok()
+        .and_then(|path| path.parent().map(canonicalize))
+      {
+        for pattern in &watch_config.include {
+          filter.include.push(GlobPattern::new(&config_dir, pattern)?);
+        }
+        for pattern in &watch_config.exclude {
+          filter.exclude.push(GlobPattern::new(&config_dir, pattern)?);
+        }
+      }
+    }
+    let cwd = canonicalize(&self.initial_cwd);
+    for pattern in &self.flags.watch_include {
+      filter.include.push(GlobPattern::new(&cwd, pattern)?);
+    }
+    for pattern in &self.flags.watch_exclude {
+      filter.exclude.push(GlobPattern::new(&cwd, pattern)?);
+    }
+    Ok(filter)
+  }
+
+  /// How long the watcher waits for more changes before restarting, from the
+  /// `"watch"` configuration.
+  pub fn watch_debounce(&self) -> Result<Option<Duration>, AnyError> {
+    Ok(match &self.maybe_config_file {
+      Some(config_file) => config_file
+        .to_watch_config()?
+        .debounce
+        .map(Duration::from_millis),
+      None => None,
+    })
+  }
+
+  /// If the `@types/*` packages of the npm packages that don't ship their
+  /// own types are acquired, from the `"typeAcquisition"` configuration.
+  pub fn type_acquisition(&self) -> Result<bool, AnyError> {
+    match &self.maybe_config_file {
+      Some(config_file) => config_file.to_type_acquisition(),
+      None => Ok(true),
+    }
+  }
+
+  pub fn remote_cache_config(
+    &self,
+  ) -> Result<Option<RemoteCacheConfig>, AnyError> {
+    match &self.maybe_config_file {
+      Some(config_file) => config_file.to_remote_cache_config(),
+      None => Ok(None),
+    }
+  }
+
+  /// The modules to evaluate before the main module, from the `"preload"`
+  /// configuration and then the `--import` flags, with the specifier
+  /// they're resolved from.
+  pub fn preload_imports(
+    &self,
+  ) -> Result<Vec<deno_graph::ReferrerImports>, AnyError> {
+    let mut preload_imports = Vec::new();
+    if let Some(config_file) = &self.maybe_config_file {
+      let imports = config_file.to_preload_imports()?;
+      if !imports.is_empty() {
+        preload_imports.push(deno_graph::ReferrerImports {
+          referrer: config_file.specifier.clone(),
+          imports,
+        });
+      }
+    }
+    if !self.flags.preload_imports.is_empty() {
+      // the flags are resolved like the imports of a module in the cwd
+      let referrer =
+        ModuleSpecifier::from_directory_path(&self.initial_cwd).unwrap();
+      preload_imports.push(deno_graph::ReferrerImports {
+        referrer,
+        imports: self.flags.preload_imports.clone(),
+      });
+    }
+    Ok(preload_imports)
+  }
+
   pub fn no_prompt(&self) -> bool {
     resolve_no_prompt(&self.flags)
   }
@@ -1059,10 +1701,28 @@ impl CliOptions {
     self.flags.no_remote
   }
 
+  /// Whether remote modules must come from the vendor directory, because the
+  /// configuration file sets `"vendor"` to `"only"`.
+  pub fn vendor_only(&self) -> bool {
+    self.vendor_mode == VendorMode::Only
+  }
+
   pub fn no_npm(&self) -> bool {
     self.flags.no_npm
   }
 
+  pub fn canonicalize_symlinks(&self) -> bool {
+    self.flags.canonicalize_symlinks
+  }
+
+  /// The memory budget for retaining parsed modules, in bytes.
+  pub fn graph_memory_budget(&self) -> Option<usize> {
+    self.flags.graph_memory_budget.map(|megabytes| {
+      usize::try_from(megabytes.saturating_mul(1024 * 1024))
+        .unwrap_or(usize::MAX)
+    })
+  }
+
   pub fn permissions_options(&self) -> PermissionsOptions {
     PermissionsOptions {
       allow_env: self.flags.allow_env.clone(),
@@ -1074,13 +1734,79 @@ impl CliOptions {
       allow_sys: self.flags.allow_sys.clone(),
       allow_write: self.flags.allow_write.clone(),
       prompt: !self.no_prompt(),
+      net_audit_log: self.net_audit_log(),
+      trace_permissions: self
+        .flags
+        .trace_permissions
+        .as_ref()
+        .map(|path| self.initial_cwd.join(path)),
+      deny: self.permission_deny_rules.clone(),
     }
   }
 
+  /// The file to append the network audit log to (`--net-audit-log`).
+  pub fn net_audit_log(&self) -> Option<PathBuf> {
+    self
+      .flags
+      .net_audit_log
+      .as_ref()
+      .map(|path| self.initial_cwd.join(path))
+  }
+
+  /// Resolves the hosts of `--allow-net` to the addresses that connections
+  /// are pinned to when `--pin-dns` is specified.
+  pub fn resolve_pinned_hosts(
+    &self,
+  ) -> Result<HashMap<String, Vec<IpAddr>>, AnyError> {
+    let mut pinned_hosts = HashMap::new();
+    if !self.flags.pin_dns {
+      return Ok(pinned_hosts);
+    }
+    let allow_net = match &self.flags.allow_net {
+      Some(allow_net) if !allow_net.is_empty() => allow_net,
+      _ => bail!(
+        "--pin-dns requires a list of hosts in --allow-net, for example \
+--allow-net=deno.land"
+      ),
+    };
+    for entry in allow_net {
+      let url = Url::parse(&format!("http://{entry}"))
+        .with_context(|| format!("Invalid --allow-net host \"{entry}\""))?;
+      // ip addresses don't need to be resolved
+      let domain = match url.host() {
+        Some(Host::Domain(domain)) => domain.to_string(),
+        _ => continue,
+      };
+      if pinned_hosts.contains_key(&domain) {
+        continue;
+      }
+      let mut addrs = (domain.as_str(), 0)
+        .to_socket_addrs()
+        .with_context(|| {
+          format!("Failed resolving \"{domain}\" for --pin-dns")
+        })?
+        .map(|addr| addr.ip())
+        .collect::<Vec<_>>();
+      addrs.sort();
+      addrs.dedup();
+      if addrs.is_empty() {
+        bail!("Failed resolving \"{}\" for --pin-dns", domain);
+      }
+      pinned_hosts.insert(domain, addrs);
+    }
+    Ok(pinned_hosts)
+  }
+
   pub fn reload_flag(&self) -> bool {
     self.flags.reload
   }
 
+  /// Whether `--frozen` was specified, in which case neither the lockfile nor
+  /// the cache may be updated.
+  pub fn frozen(&self) -> bool {
+    self.flags.frozen
+  }
+
   pub fn seed(&self) -> Option<u64> {
     self.flags.seed
   }
@@ -1096,6 +1822,30 @@ impl CliOptions {
     }
   }
 
+  /// Whether all module graph errors should be reported instead of
+  /// stopping at the first one.
+  pub fn all_graph_errors(&self) -> bool {
+    match self.sub_command() {
+      DenoSubcommand::Check(flags) => flags.all_errors,
+      _ => false,
+    }
+  }
+
+  /// The directory to write the declaration files of the checked modules to.
+  pub fn declarations_out_dir(&self) -> Option<PathBuf> {
+    match self.sub_command() {
+      DenoSubcommand::Check(flags) => flags
+        .declarations_out_dir
+        .as_ref()
+        .map(|dir| self.initial_cwd.join(dir)),
+      // the declaration files are written next to the emitted modules
+      DenoSubcommand::Pack(flags) => {
+        Some(self.initial_cwd.join(&flags.out_dir))
+      }
+      _ => None,
+    }
+  }
+
   pub fn shuffle_tests(&self) -> Option<u64> {
     match self.sub_command() {
       DenoSubcommand::Test(flags) => flags.shuffle,
@@ -1103,6 +1853,13 @@ impl CliOptions {
     }
   }
 
+  pub fn update_snapshots(&self) -> bool {
+    match self.sub_command() {
+      DenoSubcommand::Test(flags) => flags.update_snapshots,
+      _ => false,
+    }
+  }
+
   pub fn type_check_mode(&self) -> TypeCheckMode {
     self.flags.type_check_mode
   }
@@ -1115,6 +1872,12 @@ impl CliOptions {
     self.flags.unstable
   }
 
+  /// The unstable features enabled individually with `--unstable-<name>`
+  /// or the "unstable" setting of the configuration file.
+  pub fn unstable_features(&self) -> Vec<String> {
+    self.flags.unstable_features.clone()
+  }
+
   pub fn v8_flags(&self) -> &Vec<String> {
     &self.flags.v8_flags
   }
@@ -17,9 +17,9 @@ pub struct AuthToken {
   token: AuthTokenData,
 }
 
-impl fmt::Display for AuthToken {
+impl fmt::Display for AuthTokenData {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    match &self.token {
+    match self {
       AuthTokenData::Bearer(token) => write!(f, "Bearer {token}"),
       AuthTokenData::Basic { username, password } => {
         let credentials = format!("{username}:{password}");
@@ -29,6 +29,12 @@ impl fmt::Display for AuthToken {
   }
 }
 
+impl fmt::Display for AuthToken {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    self.token.fmt(f)
+  }
+}
+
 /// A structure which contains bearer tokens that can be used when sending
 /// requests to websites, intended to authorize access to private resources
 /// such as remote modules.
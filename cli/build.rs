@@ -244,6 +244,10 @@ mod ts {
       "cargo:rerun-if-changed={}",
       cwd.join("js").join("40_testing.js").display()
     );
+    println!(
+      "cargo:rerun-if-changed={}",
+      cwd.join("js").join("41_modules.js").display()
+    );
 
     // create a copy of the vector that includes any op crate libs to be passed
     // to the JavaScript compiler to build into the snapshot
@@ -311,7 +315,8 @@ deno_core::extension!(
   cli,
   esm = [
     dir "js",
-    "40_testing.js"
+    "40_testing.js",
+    "41_modules.js"
   ],
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
     ext.esm(vec![ExtensionFileSource {
@@ -340,6 +345,7 @@ fn create_cli_snapshot(snapshot_path: PathBuf) {
       "".to_owned(),
       None,
       None,
+      Default::default(),
     ),
     deno_webstorage::deno_webstorage::init_ops(None),
     deno_crypto::deno_crypto::init_ops(None),
@@ -349,8 +355,10 @@ fn create_cli_snapshot(snapshot_path: PathBuf) {
     ),
     deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(false),
     deno_net::deno_net::init_ops::<PermissionsContainer>(
-      None, false, // No --unstable.
       None,
+      false, // No --unstable.
+      None,
+      Default::default(),
     ),
     deno_tls::deno_tls::init_ops(),
     deno_kv::deno_kv::init_ops(
@@ -455,6 +463,11 @@ fn main() {
   }
   println!("cargo:rerun-if-env-changed=DENO_CANARY");
 
+  if let Ok(key) = env::var("DENO_RELEASE_VERIFY_KEY") {
+    println!("cargo:rustc-env=DENO_RELEASE_VERIFY_KEY={key}");
+  }
+  println!("cargo:rerun-if-env-changed=DENO_RELEASE_VERIFY_KEY");
+
   println!("cargo:rustc-env=GIT_COMMIT_HASH={}", git_commit_hash());
   println!("cargo:rerun-if-env-changed=GIT_COMMIT_HASH");
   println!(
@@ -331,7 +331,6 @@ impl CacheDB {
     }
   }
 
-  #[cfg(test)]
   pub fn ensure_connected(&self) -> Result<(), AnyError> {
     self.with_connection(|_| Ok(()))
   }
@@ -0,0 +1,417 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_runtime::deno_webstorage::rusqlite::params;
+use once_cell::sync::Lazy;
+use walkdir::WalkDir;
+
+use crate::colors;
+
+use super::cache_db::CacheDB;
+use super::cache_db::CacheDBConfiguration;
+use super::cache_db::CacheFailure;
+
+pub static CACHE_STORE_DB: CacheDBConfiguration = CacheDBConfiguration {
+  table_initializer: "CREATE TABLE IF NOT EXISTS cachefile (
+      key TEXT PRIMARY KEY,
+      data BLOB NOT NULL,
+      modified INTEGER NOT NULL
+    );",
+  // the entries don't depend on the version of the CLI (emits are keyed by
+  // a hash that includes it), so they're kept when upgrading
+  on_version_change: "",
+  preheat_queries: &[],
+  // the file layout is used instead when the store can't be opened
+  on_failure: CacheFailure::Error,
+};
+
+/// The file of the store, in the folder of the caches that use it.
+pub const CACHE_STORE_FILE_NAME: &str = "cache_store_v1.db";
+
+static SHOULD_USE_SQLITE_BACKEND: Lazy<bool> = Lazy::new(|| {
+  std::env::var("DENO_CACHE_BACKEND")
+    .map(|value| value.eq_ignore_ascii_case("sqlite"))
+    .unwrap_or(false)
+});
+
+/// The folders of the file layout whose files are kept in the store.
+const STORE_FOLDER_NAMES: &[&str] = &["deps", "gen"];
+
+/// The stores of the cache folders, which are `None` when they couldn't be
+/// opened.
+static OPEN_STORES: Lazy<Mutex<HashMap<PathBuf, Option<CacheStore>>>> =
+  Lazy::new(Default::default);
+
+/// A store that keeps the files of the remote modules, their metadata and
+/// the emits in a single SQLite database instead of a file each, which
+/// is much faster on network file systems and in containers.
+///
+/// Entries are keyed by their path in the file layout relative to the root
+/// of the store, which is also the path that `deno info` shows for them.
+/// The files of the file layout are migrated when the store is created, and
+/// the ones that are added later are migrated when they're first read. They
+/// are left in place so that switching back to the file backend keeps
+/// working.
+#[derive(Clone)]
+pub struct CacheStore {
+  root: PathBuf,
+  db: CacheDB,
+}
+
+impl fmt::Debug for CacheStore {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("CacheStore")
+      .field("root", &self.root)
+      .finish()
+  }
+}
+
+impl CacheStore {
+  pub fn new(db: CacheDB, root: &Path) -> Self {
+    Self {
+      root: root.to_path_buf(),
+      db,
+    }
+  }
+
+  /// Gets the store for a cache location when the SQLite backend is
+  /// selected with `DENO_CACHE_BACKEND=sqlite`. The store is shared by the
+  /// caches of the same folder, so that they use one connection.
+  ///
+  /// This is `None` when the store can't be opened, so that the file layout
+  /// is used instead.
+  pub fn for_cache_location(location: &Path) -> Option<Self> {
+    if !*SHOULD_USE_SQLITE_BACKEND {
+      return None;
+    }
+    let root = location.parent()?;
+    OPEN_STORES
+      .lock()
+      .entry(root.to_path_buf())
+      .or_insert_with(|| Self::open(root))
+      .clone()
+  }
+
+  fn open(root: &Path) -> Option<Self> {
+    let db_path = root.join(CACHE_STORE_FILE_NAME);
+    let is_new = !db_path.exists();
+    let db = CacheDB::from_path(
+      &CACHE_STORE_DB,
+      db_path.clone(),
+      crate::version::deno(),
+    );
+    if let Err(err) = db.ensure_connected() {
+      log::warn!(
+        "{} Could not open the cache store at '{}', so the cache files are used instead. {:#}",
+        colors::yellow("Warning"),
+        db_path.display(),
+        err
+      );
+      return None;
+    }
+    let store = Self::new(db, root);
+    if is_new {
+      let folders = STORE_FOLDER_NAMES
+        .iter()
+        .map(|name| root.join(name))
+        .collect::<Vec<_>>();
+      match store.migrate_folders(&folders) {
+        Ok(count) => {
+          log::debug!("Migrated {} cache files to the store.", count)
+        }
+        // the files are still migrated when they're first read
+        Err(err) => log::debug!("Failed migrating the cache files: {:#}", err),
+      }
+    }
+    Some(store)
+  }
+
+  /// Copies the files in the folders of the file layout that aren't in the
+  /// store yet to it, in a single transaction, and returns how many were
+  /// copied.
+  pub fn migrate_folders(
+    &self,
+    folders: &[PathBuf],
+  ) -> Result<usize, AnyError> {
+    self.db.with_connection(|conn| {
+      let transaction = conn.unchecked_transaction()?;
+      let mut count = 0;
+      {
+        let mut stmt = transaction.prepare_cached(
+          "INSERT OR IGNORE INTO cachefile (key, data, modified) VALUES (?1, ?2, ?3)",
+        )?;
+        let entries = folders
+          .iter()
+          .flat_map(|folder| WalkDir::new(folder).into_iter())
+          .filter_map(|entry| entry.ok())
+          .filter(|entry| entry.file_type().is_file());
+        for entry in entries {
+          let key = self.key(entry.path())?;
+          let data = match fs::read(entry.path()) {
+            Ok(data) => data,
+            Err(err) => {
+              log::debug!("Failed migrating {}: {:#}", key, err);
+              continue;
+            }
+          };
+          let modified = entry
+            .metadata()
+            .ok()
+            .and_then(|metadata| metadata.modified().ok())
+            .unwrap_or_else(SystemTime::now);
+          count += stmt.execute(params![key, data, to_millis(modified)])?;
+        }
+      }
+      transaction.commit()?;
+      Ok(count)
+    })
+  }
+
+  /// Reads the file at the path, which is migrated from the file layout
+  /// when it's not in the store.
+  pub fn get(&self, path: &Path) -> io::Result<Vec<u8>> {
+    let key = self.key(path)?;
+    let maybe_data = self
+      .db
+      .query_row(
+        "SELECT data FROM cachefile WHERE key=?1 LIMIT 1",
+        params![key],
+        |row| {
+          let data: Vec<u8> = row.get(0)?;
+          Ok(data)
+        },
+      )
+      .map_err(to_io_error)?;
+    if let Some(data) = maybe_data {
+      return Ok(data);
+    }
+
+    let data = fs::read(path)?;
+    let modified = fs::metadata(path)
+      .and_then(|metadata| metadata.modified())
+      .unwrap_or_else(|_| SystemTime::now());
+    if let Err(err) = self.insert(&key, &data, modified) {
+      log::debug!("Failed migrating {} to the store: {:#}", key, err);
+    }
+    Ok(data)
+  }
+
+  /// Gets if the file at the path is in the store or in the file layout.
+  pub fn contains(&self, path: &Path) -> bool {
+    self.modified(path).is_some()
+  }
+
+  /// Gets when the file at the path was last written.
+  pub fn modified(&self, path: &Path) -> Option<SystemTime> {
+    let key = self.key(path).ok()?;
+    let maybe_millis = self
+      .db
+      .query_row(
+        "SELECT modified FROM cachefile WHERE key=?1 LIMIT 1",
+        params![key],
+        |row| {
+          let millis: i64 = row.get(0)?;
+          Ok(millis)
+        },
+      )
+      .ok()?;
+    match maybe_millis {
+      Some(millis) => Some(
+        SystemTime::UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64),
+      ),
+      None => fs::metadata(path).and_then(|m| m.modified()).ok(),
+    }
+  }
+
+  /// Writes the file at the path.
+  pub fn set(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+    let key = self.key(path)?;
+    self
+      .insert(&key, data, SystemTime::now())
+      .map_err(to_io_error)
+  }
+
+  /// Gets the total size of the files in the folder at the path.
+  pub fn folder_size(&self, path: &Path) -> io::Result<u64> {
+    let prefix = self.folder_key_prefix(path)?;
+    let size = self
+      .db
+      .query_row(
+        "SELECT COALESCE(SUM(LENGTH(data)), 0) FROM cachefile WHERE substr(key, 1, ?1)=?2",
+        params![prefix.len(), prefix],
+        |row| {
+          let size: i64 = row.get(0)?;
+          Ok(size)
+        },
+      )
+      .map_err(to_io_error)?;
+    Ok(size.unwrap_or(0).max(0) as u64)
+  }
+
+  /// Removes the files in the folder at the path, and then frees the space
+  /// they used.
+  pub fn remove_folder(&self, path: &Path) -> io::Result<()> {
+    let prefix = self.folder_key_prefix(path)?;
+    let removed_count = self
+      .db
+      .execute(
+        "DELETE FROM cachefile WHERE substr(key, 1, ?1)=?2",
+        params![prefix.len(), prefix],
+      )
+      .map_err(to_io_error)?;
+    if removed_count > 0 {
+      self.db.execute("VACUUM", []).map_err(to_io_error)?;
+    }
+    Ok(())
+  }
+
+  fn folder_key_prefix(&self, path: &Path) -> io::Result<String> {
+    let key = self.key(path)?;
+    // the root of the store contains all the keys
+    if key.is_empty() {
+      Ok(key)
+    } else {
+      Ok(format!("{key}/"))
+    }
+  }
+
+  fn insert(
+    &self,
+    key: &str,
+    data: &[u8],
+    modified: SystemTime,
+  ) -> Result<(), AnyError> {
+    self.db.execute(
+      "INSERT OR REPLACE INTO cachefile (key, data, modified) VALUES (?1, ?2, ?3)",
+      params![key, data, to_millis(modified)],
+    )?;
+    Ok(())
+  }
+
+  fn key(&self, path: &Path) -> io::Result<String> {
+    let relative = path.strip_prefix(&self.root).map_err(|_| {
+      io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+          "'{}' is not in the cache store at '{}'",
+          path.display(),
+          self.root.display()
+        ),
+      )
+    })?;
+    // use the same keys on every platform
+    let components = relative
+      .components()
+      .map(|c| c.as_os_str().to_string_lossy())
+      .collect::<Vec<_>>();
+    Ok(components.join("/"))
+  }
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+  time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .map(|d| d.as_millis() as i64)
+    .unwrap_or(0)
+}
+
+fn to_io_error(err: AnyError) -> io::Error {
+  io::Error::new(io::ErrorKind::Other, format!("{err:#}"))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  #[test]
+  fn test_cache_store() {
+    let temp_dir = TempDir::new();
+    let root = temp_dir.path().to_path_buf();
+    let store =
+      CacheStore::new(CacheDB::in_memory(&CACHE_STORE_DB, "1.0.0"), &root);
+    let path = root.join("deps").join("https").join("deno.land").join("a");
+    let err = store.get(&path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert_eq!(store.modified(&path), None);
+
+    store.set(&path, b"hello").unwrap();
+    assert_eq!(store.get(&path).unwrap(), b"hello");
+    assert!(store.modified(&path).is_some());
+    // nothing is written in the file layout
+    assert!(!path.exists());
+
+    let other_path = root.join("deps").join("https").join("esm.sh").join("b");
+    store.set(&other_path, b"hi").unwrap();
+    let deno_land = root.join("deps").join("https").join("deno.land");
+    assert_eq!(store.folder_size(&deno_land).unwrap(), 5);
+    assert_eq!(store.folder_size(&root).unwrap(), 7);
+    store.remove_folder(&deno_land).unwrap();
+    assert_eq!(store.folder_size(&deno_land).unwrap(), 0);
+    assert_eq!(store.get(&other_path).unwrap(), b"hi");
+
+    // paths outside of the root are rejected
+    let outside = temp_dir.path().parent().unwrap().join("a");
+    let err = store.set(&outside, b"").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn test_cache_store_migrates_files() {
+    let temp_dir = TempDir::new();
+    let root = temp_dir.path().to_path_buf();
+    let store =
+      CacheStore::new(CacheDB::in_memory(&CACHE_STORE_DB, "1.0.0"), &root);
+    let path = root.join("gen").join("file").join("mod.ts.js");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "console.log(1);").unwrap();
+
+    assert_eq!(store.get(&path).unwrap(), b"console.log(1);");
+    fs::remove_file(&path).unwrap();
+    // it's now read from the store
+    assert_eq!(store.get(&path).unwrap(), b"console.log(1);");
+    assert!(store.modified(&path).is_some());
+  }
+
+  #[test]
+  fn test_cache_store_migrates_folders() {
+    let temp_dir = TempDir::new();
+    let root = temp_dir.path().to_path_buf();
+    let store =
+      CacheStore::new(CacheDB::in_memory(&CACHE_STORE_DB, "1.0.0"), &root);
+    let deps_path = root.join("deps").join("https").join("deno.land");
+    let gen_path = root.join("gen").join("file");
+    fs::create_dir_all(&deps_path).unwrap();
+    fs::create_dir_all(&gen_path).unwrap();
+    fs::write(deps_path.join("a"), "a").unwrap();
+    fs::write(deps_path.join("a.metadata.json"), "{}").unwrap();
+    fs::write(gen_path.join("b.js"), "b").unwrap();
+    // the entries of the store are kept
+    store.set(&deps_path.join("a"), b"new a").unwrap();
+
+    let count = store
+      .migrate_folders(&[root.join("deps"), root.join("gen")])
+      .unwrap();
+    assert_eq!(count, 2);
+    fs::remove_dir_all(root.join("deps")).unwrap();
+    fs::remove_dir_all(root.join("gen")).unwrap();
+    assert_eq!(store.get(&deps_path.join("a")).unwrap(), b"new a");
+    assert_eq!(
+      store.get(&deps_path.join("a.metadata.json")).unwrap(),
+      b"{}"
+    );
+    assert_eq!(store.get(&gen_path.join("b.js")).unwrap(), b"b");
+    assert!(store.contains(&gen_path.join("b.js")));
+    assert!(!store.contains(&gen_path.join("c.js")));
+  }
+}
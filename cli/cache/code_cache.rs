@@ -0,0 +1,145 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::PathBuf;
+
+use deno_ast::ModuleSpecifier;
+
+use super::DiskCache;
+use super::FastInsecureHasher;
+
+/// The cache that stores the V8 code caches of emitted modules, which let
+/// V8 skip parsing and compiling them on repeat runs.
+///
+/// Each entry starts with the hash of the code it was created for, which
+/// includes the V8 and CLI versions and the V8 flags because V8 rejects code
+/// caches created by other versions or with other flags.
+#[derive(Clone)]
+pub struct CodeCache {
+  disk_cache: DiskCache,
+  cli_version: &'static str,
+  v8_version: &'static str,
+  v8_flags: String,
+}
+
+impl CodeCache {
+  pub fn new(disk_cache: DiskCache, v8_flags: &[String]) -> Self {
+    Self {
+      disk_cache,
+      cli_version: crate::version::deno(),
+      v8_version: deno_core::v8_version(),
+      v8_flags: v8_flags.join(" "),
+    }
+  }
+
+  /// Computes the hash used to verify that a code cache is for the code.
+  pub fn compute_code_hash(&self, code: &[u8]) -> u64 {
+    FastInsecureHasher::new()
+      .write(code)
+      .write_str(self.v8_version)
+      .write_str(self.cli_version)
+      .write_str(&self.v8_flags)
+      .finish()
+  }
+
+  /// Gets the code cache for the module, unless it was created for other
+  /// code, by other V8 or CLI versions or with other V8 flags.
+  pub fn get_code_cache(
+    &self,
+    specifier: &ModuleSpecifier,
+    expected_code_hash: u64,
+  ) -> Option<Vec<u8>> {
+    let filename = self.get_code_cache_filename(specifier)?;
+    let mut bytes = self.disk_cache.get(&filename).ok()?;
+    if bytes.len() < 8 {
+      return None;
+    }
+    let code_hash = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+    if code_hash != expected_code_hash {
+      return None;
+    }
+    bytes.drain(..8);
+    Some(bytes)
+  }
+
+  /// Sets the code cache of the module.
+  pub fn set_code_cache(
+    &self,
+    specifier: &ModuleSpecifier,
+    code_hash: u64,
+    data: &[u8],
+  ) {
+    let filename = match self.get_code_cache_filename(specifier) {
+      Some(filename) => filename,
+      None => return,
+    };
+    let mut bytes = Vec::with_capacity(8 + data.len());
+    bytes.extend_from_slice(&code_hash.to_le_bytes());
+    bytes.extend_from_slice(data);
+    if let Err(err) = self.disk_cache.set(&filename, &bytes) {
+      // failing to save a code cache only makes the next run slower
+      log::debug!("Error saving code cache ({}): {}", specifier, err);
+    }
+  }
+
+  fn get_code_cache_filename(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<PathBuf> {
+    self
+      .disk_cache
+      .get_cache_filename_with_extension(specifier, "v8cache")
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[test]
+  pub fn code_cache_general_use() {
+    let temp_dir = TempDir::new();
+    let disk_cache = DiskCache::new(temp_dir.path());
+    let cache = CodeCache {
+      disk_cache: disk_cache.clone(),
+      cli_version: "1.0.0",
+      v8_version: "11.0.0",
+      v8_flags: String::new(),
+    };
+
+    let specifier =
+      ModuleSpecifier::from_file_path(temp_dir.path().join("file1.ts"))
+        .unwrap();
+    let code_hash = cache.compute_code_hash(b"console.log(1);");
+    assert_eq!(cache.get_code_cache(&specifier, code_hash), None);
+    cache.set_code_cache(&specifier, code_hash, &[1, 2, 3]);
+    assert_eq!(
+      cache.get_code_cache(&specifier, code_hash),
+      Some(vec![1, 2, 3])
+    );
+    // the code changed
+    let other_code_hash = cache.compute_code_hash(b"console.log(2);");
+    assert_eq!(cache.get_code_cache(&specifier, other_code_hash), None);
+
+    // the code caches created with other V8 flags are not used
+    let cache = CodeCache {
+      disk_cache: disk_cache.clone(),
+      cli_version: "1.0.0",
+      v8_version: "11.0.0",
+      v8_flags: "--jitless".to_string(),
+    };
+    let code_hash = cache.compute_code_hash(b"console.log(1);");
+    assert_eq!(cache.get_code_cache(&specifier, code_hash), None);
+
+    // the code caches of other V8 versions are not used
+    let cache = CodeCache {
+      disk_cache,
+      cli_version: "1.0.0",
+      v8_version: "11.1.0",
+      v8_flags: String::new(),
+    };
+    let code_hash = cache.compute_code_hash(b"console.log(1);");
+    assert_eq!(cache.get_code_cache(&specifier, code_hash), None);
+  }
+}
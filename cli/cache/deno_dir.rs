@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use super::cache_store::CacheStore;
+use super::cache_store::CACHE_STORE_FILE_NAME;
 use super::DiskCache;
 
 use std::env;
@@ -39,13 +41,13 @@ impl DenoDir {
     assert!(root.is_absolute());
     let gen_path = root.join("gen");
 
-    let deno_dir = Self {
-      root,
-      gen_cache: DiskCache::new(&gen_path),
-    };
-    deno_dir.gen_cache.ensure_dir_exists(&gen_path)?;
+    let gen_cache = DiskCache::new(&gen_path);
+    gen_cache.ensure_dir_exists(&gen_path)?;
+    // the store is opened once its folder exists
+    let gen_cache =
+      gen_cache.with_store(CacheStore::for_cache_location(&gen_path));
 
-    Ok(deno_dir)
+    Ok(Self { root, gen_cache })
   }
 
   /// The root directory of the DENO_DIR for display purposes only.
@@ -83,6 +85,18 @@ impl DenoDir {
     self.root.join("check_cache_v1")
   }
 
+  /// Path for the database of the SQLite backend of the caches
+  /// (`DENO_CACHE_BACKEND=sqlite`).
+  pub fn cache_store_db_file_path(&self) -> PathBuf {
+    self.root.join(CACHE_STORE_FILE_NAME)
+  }
+
+  /// Folder used for the declaration files inferred for untyped remote
+  /// modules.
+  pub fn inferred_types_folder_path(&self) -> PathBuf {
+    self.root.join("inferred_types")
+  }
+
   /// Path to the registries cache, used for the lps.
   pub fn registries_folder_path(&self) -> PathBuf {
     self.root.join("registries")
@@ -123,6 +137,16 @@ impl DenoDir {
     }
   }
 
+  /// Folder used for the results saved with `deno bench --save-baseline`.
+  pub fn bench_baselines_folder_path(&self) -> PathBuf {
+    self.root.join("bench_baselines")
+  }
+
+  /// Folder used for the last runs of the schedules of `deno cron`.
+  pub fn cron_state_folder_path(&self) -> PathBuf {
+    self.root.join("cron")
+  }
+
   /// Folder path used for downloading new versions of deno.
   pub fn dl_folder_path(&self) -> PathBuf {
     self.root.join("dl")
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use super::cache_store::CacheStore;
 use super::http_cache::url_to_filename;
 use super::CACHE_PERM;
 use crate::util::fs::atomic_write_file;
@@ -15,9 +16,11 @@ use std::path::PathBuf;
 use std::path::Prefix;
 use std::str;
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct DiskCache {
   pub location: PathBuf,
+  /// Keeps the files in a single file store instead of the location.
+  maybe_store: Option<CacheStore>,
 }
 
 fn with_io_context<T: AsRef<str>>(
@@ -33,9 +36,16 @@ impl DiskCache {
     assert!(location.is_absolute());
     Self {
       location: location.to_owned(),
+      maybe_store: None,
     }
   }
 
+  /// Keeps the files of the cache in the store, when provided.
+  pub fn with_store(mut self, maybe_store: Option<CacheStore>) -> Self {
+    self.maybe_store = maybe_store;
+    self
+  }
+
   /// Ensures the location of the cache.
   pub fn ensure_dir_exists(&self, path: &Path) -> io::Result<()> {
     if path.is_dir() {
@@ -142,13 +152,30 @@ impl DiskCache {
     }
   }
 
+  /// Gets if the file is cached, in the store or in the location.
+  pub fn contains(&self, filename: &Path) -> bool {
+    let path = self.location.join(filename);
+    match &self.maybe_store {
+      Some(store) => store.contains(&path),
+      None => path.is_file(),
+    }
+  }
+
   pub fn get(&self, filename: &Path) -> std::io::Result<Vec<u8>> {
     let path = self.location.join(filename);
-    fs::read(path)
+    match &self.maybe_store {
+      Some(store) => store.get(&path),
+      None => fs::read(path),
+    }
   }
 
   pub fn set(&self, filename: &Path, data: &[u8]) -> std::io::Result<()> {
     let path = self.location.join(filename);
+    if let Some(store) = &self.maybe_store {
+      return store
+        .set(&path, data)
+        .map_err(|e| with_io_context(&e, format!("{:#?}", &path)));
+    }
     match path.parent() {
       Some(parent) => self.ensure_dir_exists(parent),
       None => Ok(()),
@@ -67,17 +67,18 @@ impl EmitCache {
     Some(emit_text)
   }
 
-  /// Gets the filepath which stores the emit.
+  /// Gets the filepath which stores the emit, when it's cached. It's the key
+  /// of the emit when the cache keeps its files in the store.
   pub fn get_emit_filepath(
     &self,
     specifier: &ModuleSpecifier,
   ) -> Option<PathBuf> {
-    Some(
-      self
-        .disk_cache
-        .location
-        .join(self.get_emit_filename(specifier)?),
-    )
+    let emit_filename = self.get_emit_filename(specifier)?;
+    if self.disk_cache.contains(&emit_filename) {
+      Some(self.disk_cache.location.join(emit_filename))
+    } else {
+      None
+    }
   }
 
   /// Sets the emit code in the cache.
@@ -3,6 +3,7 @@
 //! as defined in RFC 7234 (<https://tools.ietf.org/html/rfc7234>).
 //! Currently it's a very simplified version to fulfill Deno needs
 //! at hand.
+use crate::http_util::CacheSemantics;
 use crate::http_util::HeadersMap;
 use crate::util;
 use deno_core::error::generic_error;
@@ -12,19 +13,20 @@ use deno_core::serde::Serialize;
 use deno_core::serde_json;
 use deno_core::url::Url;
 use std::fs;
-use std::fs::File;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use std::time::SystemTime;
 
+use super::cache_store::CacheStore;
 use super::CACHE_PERM;
 
 /// Turn base of url (scheme, hostname, port) into a valid filename.
 /// This method replaces port part with a special string token (because
 /// ":" cannot be used in filename on some platforms).
 /// Ex: $DENO_DIR/deps/https/deno.land/
-fn base_url_to_filename(url: &Url) -> Option<PathBuf> {
+pub fn base_url_to_filename(url: &Url) -> Option<PathBuf> {
   let mut out = PathBuf::new();
 
   let scheme = url.scheme();
@@ -100,21 +102,54 @@ impl CachedUrlMetadata {
   pub fn filename(cache_filename: &Path) -> PathBuf {
     cache_filename.with_extension("metadata.json")
   }
+
+  /// Gets how fresh the cached response is at the provided time according
+  /// to its HTTP caching headers.
+  pub fn freshness(&self, now: SystemTime) -> CacheFreshness {
+    CacheSemantics::new(self.headers.clone(), self.now, now).freshness()
+  }
+}
+
+/// The freshness of a cached response, as described in
+/// <https://www.rfc-editor.org/rfc/rfc9111#section-4.2>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheFreshness {
+  /// How long ago the response was generated.
+  pub age: Duration,
+  /// How long the response stays fresh, if the headers say so.
+  pub lifetime: Option<Duration>,
+  /// If the server marked the response as never changing.
+  pub immutable: bool,
+}
+
+impl CacheFreshness {
+  /// Gets if the response is stale, or `None` when the headers don't
+  /// provide any caching information.
+  pub fn is_stale(&self) -> Option<bool> {
+    if self.immutable {
+      return Some(false);
+    }
+    self.lifetime.map(|lifetime| self.age > lifetime)
+  }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct HttpCache {
   pub location: PathBuf,
+  /// Keeps the responses in a single file store instead of the location.
+  maybe_store: Option<CacheStore>,
 }
 
 impl HttpCache {
-  /// Returns a new instance.
+  /// Returns a new instance, which uses the store of the location when the
+  /// SQLite cache backend is selected.
   ///
   /// `location` must be an absolute path.
   pub fn new(location: &Path) -> Self {
     assert!(location.is_absolute());
     Self {
       location: location.to_owned(),
+      maybe_store: CacheStore::for_cache_location(location),
     }
   }
 
@@ -137,22 +172,43 @@ impl HttpCache {
     Some(self.location.join(url_to_filename(url)?))
   }
 
+  fn get_cache_filename_or_err(&self, url: &Url) -> Result<PathBuf, AnyError> {
+    self
+      .get_cache_filename(url)
+      .ok_or_else(|| generic_error("Can't convert url to filename."))
+  }
+
   // TODO(bartlomieju): this method should check headers file
   // and validate against ETAG/Last-modified-as headers.
   // ETAG check is currently done in `cli/file_fetcher.rs`.
   pub fn get(
     &self,
     url: &Url,
-  ) -> Result<(File, HeadersMap, SystemTime), AnyError> {
-    let cache_filename = self.location.join(
-      url_to_filename(url)
-        .ok_or_else(|| generic_error("Can't convert url to filename."))?,
-    );
-    let metadata_filename = CachedUrlMetadata::filename(&cache_filename);
-    let file = File::open(cache_filename)?;
-    let metadata = fs::read_to_string(metadata_filename)?;
-    let metadata: CachedUrlMetadata = serde_json::from_str(&metadata)?;
-    Ok((file, metadata.headers, metadata.now))
+  ) -> Result<(Vec<u8>, HeadersMap, SystemTime), AnyError> {
+    let cache_filename = self.get_cache_filename_or_err(url)?;
+    let content = self.read_file(&cache_filename)?;
+    let metadata = self.read_metadata(&cache_filename)?;
+    Ok((content, metadata.headers, metadata.now))
+  }
+
+  /// Gets the metadata of a cached response, without reading its content.
+  pub fn get_metadata(&self, url: &Url) -> Result<CachedUrlMetadata, AnyError> {
+    let cache_filename = self.get_cache_filename_or_err(url)?;
+    self.read_metadata(&cache_filename)
+  }
+
+  /// Gets when the response was last written to the cache, if it's cached.
+  pub fn get_modified_time(&self, url: &Url) -> Option<SystemTime> {
+    let cache_filename = self.get_cache_filename(url)?;
+    match &self.maybe_store {
+      Some(store) => store.modified(&cache_filename),
+      None => fs::metadata(cache_filename).ok()?.modified().ok(),
+    }
+  }
+
+  /// Gets if a response is cached for the url.
+  pub fn contains(&self, url: &Url) -> bool {
+    self.get_modified_time(url).is_some()
   }
 
   pub fn set(
@@ -161,32 +217,98 @@ impl HttpCache {
     headers_map: HeadersMap,
     content: &[u8],
   ) -> Result<(), AnyError> {
-    let cache_filename = self.location.join(
-      url_to_filename(url)
-        .ok_or_else(|| generic_error("Can't convert url to filename."))?,
-    );
-    // Create parent directory
-    let parent_filename = cache_filename
-      .parent()
-      .expect("Cache filename should have a parent dir");
-    self.ensure_dir_exists(parent_filename)?;
+    let cache_filename = self.get_cache_filename_or_err(url)?;
     // Cache content
-    util::fs::atomic_write_file(&cache_filename, content, CACHE_PERM)?;
+    self.write_file(&cache_filename, content)?;
 
     let metadata = CachedUrlMetadata {
       now: SystemTime::now(),
       url: url.to_string(),
       headers: headers_map,
     };
-    metadata.write(&cache_filename)
+    self.write_metadata(&cache_filename, &metadata)
+  }
+
+  /// Updates the headers and time of a cached response that the server
+  /// responded to a conditional request for with `304 Not Modified`, so
+  /// that it's fresh again.
+  pub fn refresh(
+    &self,
+    url: &Url,
+    headers_map: HeadersMap,
+  ) -> Result<(), AnyError> {
+    let cache_filename = self.get_cache_filename_or_err(url)?;
+    let mut metadata = self.read_metadata(&cache_filename)?;
+    // the headers of the 304 response replace the stored ones
+    // https://www.rfc-editor.org/rfc/rfc9111#section-4.3.4
+    metadata.headers.extend(
+      headers_map
+        .into_iter()
+        .filter(|(key, _)| key != "content-length"),
+    );
+    metadata.now = SystemTime::now();
+    self.write_metadata(&cache_filename, &metadata)
+  }
+
+  fn read_metadata(
+    &self,
+    cache_filename: &Path,
+  ) -> Result<CachedUrlMetadata, AnyError> {
+    match &self.maybe_store {
+      Some(store) => {
+        let bytes = store.get(&CachedUrlMetadata::filename(cache_filename))?;
+        Ok(serde_json::from_slice(&bytes)?)
+      }
+      None => CachedUrlMetadata::read(cache_filename),
+    }
+  }
+
+  fn write_metadata(
+    &self,
+    cache_filename: &Path,
+    metadata: &CachedUrlMetadata,
+  ) -> Result<(), AnyError> {
+    match &self.maybe_store {
+      Some(store) => {
+        let json = serde_json::to_string_pretty(metadata)?;
+        store.set(
+          &CachedUrlMetadata::filename(cache_filename),
+          json.as_bytes(),
+        )?;
+        Ok(())
+      }
+      None => metadata.write(cache_filename),
+    }
+  }
+
+  fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+    match &self.maybe_store {
+      Some(store) => store.get(path),
+      None => fs::read(path),
+    }
+  }
+
+  fn write_file(&self, path: &Path, content: &[u8]) -> Result<(), AnyError> {
+    if let Some(store) = &self.maybe_store {
+      store.set(path, content)?;
+      return Ok(());
+    }
+    // Create parent directory
+    let parent_filename = path
+      .parent()
+      .expect("Cache filename should have a parent dir");
+    self.ensure_dir_exists(parent_filename)?;
+    util::fs::atomic_write_file(path, content, CACHE_PERM)?;
+    Ok(())
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::cache::cache_db::CacheDB;
+  use crate::cache::cache_store::CACHE_STORE_DB;
   use std::collections::HashMap;
-  use std::io::Read;
   use test_util::TempDir;
 
   #[test]
@@ -233,10 +355,8 @@ mod tests {
     assert!(r.is_ok());
     let r = cache.get(&url);
     assert!(r.is_ok());
-    let (mut file, headers, _) = r.unwrap();
-    let mut content = String::new();
-    file.read_to_string(&mut content).unwrap();
-    assert_eq!(content, "Hello world");
+    let (content, headers, _) = r.unwrap();
+    assert_eq!(content, b"Hello world");
     assert_eq!(
       headers.get("content-type").unwrap(),
       "application/javascript"
@@ -245,6 +365,138 @@ mod tests {
     assert_eq!(headers.get("foobar"), None);
   }
 
+  #[test]
+  fn test_refresh() {
+    let dir = TempDir::new();
+    let cache = HttpCache::new(dir.path());
+    let url = Url::parse("https://deno.land/x/welcome.ts").unwrap();
+    let mut headers = HashMap::new();
+    headers.insert("cache-control".to_string(), "max-age=60".to_string());
+    headers.insert("etag".to_string(), "as5625rqdsfb".to_string());
+    cache.set(&url, headers, b"Hello world").unwrap();
+    let (_, _, cached_time) = cache.get(&url).unwrap();
+
+    let mut headers = HashMap::new();
+    headers.insert("cache-control".to_string(), "max-age=3600".to_string());
+    cache.refresh(&url, headers).unwrap();
+    let (content, headers, refreshed_time) = cache.get(&url).unwrap();
+    assert_eq!(content, b"Hello world");
+    assert_eq!(headers.get("cache-control").unwrap(), "max-age=3600");
+    assert_eq!(headers.get("etag").unwrap(), "as5625rqdsfb");
+    assert!(refreshed_time >= cached_time);
+  }
+
+  #[test]
+  fn test_get_set_store() {
+    let dir = TempDir::new();
+    let location = dir.path().join("deps");
+    let cache = HttpCache {
+      location: location.clone(),
+      maybe_store: Some(CacheStore::new(
+        CacheDB::in_memory(&CACHE_STORE_DB, "1.0.0"),
+        dir.path(),
+      )),
+    };
+    let url = Url::parse("https://deno.land/x/welcome.ts").unwrap();
+    assert!(!cache.contains(&url));
+    let mut headers = HashMap::new();
+    headers.insert("etag".to_string(), "as5625rqdsfb".to_string());
+    cache.set(&url, headers, b"Hello world").unwrap();
+    assert!(cache.contains(&url));
+    // nothing is written in the file layout
+    assert!(!location.exists());
+
+    let mut headers = HashMap::new();
+    headers.insert("cache-control".to_string(), "max-age=3600".to_string());
+    cache.refresh(&url, headers).unwrap();
+    let (content, headers, _) = cache.get(&url).unwrap();
+    assert_eq!(content, b"Hello world");
+    assert_eq!(headers.get("etag").unwrap(), "as5625rqdsfb");
+    let metadata = cache.get_metadata(&url).unwrap();
+    assert_eq!(metadata.url, "https://deno.land/x/welcome.ts");
+    assert_eq!(
+      metadata.headers.get("cache-control").unwrap(),
+      "max-age=3600"
+    );
+  }
+
+  #[test]
+  fn test_store_migrates_file_layout() {
+    let dir = TempDir::new();
+    let location = dir.path().join("deps");
+    let url = Url::parse("https://deno.land/x/welcome.ts").unwrap();
+    HttpCache {
+      location: location.clone(),
+      maybe_store: None,
+    }
+    .set(&url, HashMap::new(), b"Hello world")
+    .unwrap();
+
+    let cache = HttpCache {
+      location,
+      maybe_store: Some(CacheStore::new(
+        CacheDB::in_memory(&CACHE_STORE_DB, "1.0.0"),
+        dir.path(),
+      )),
+    };
+    let cache_filename = cache.get_cache_filename(&url).unwrap();
+    let (content, _, _) = cache.get(&url).unwrap();
+    assert_eq!(content, b"Hello world");
+    fs::remove_file(&cache_filename).unwrap();
+    fs::remove_file(CachedUrlMetadata::filename(&cache_filename)).unwrap();
+    let (content, _, _) = cache.get(&url).unwrap();
+    assert_eq!(content, b"Hello world");
+  }
+
+  #[test]
+  fn test_freshness() {
+    fn freshness(headers: &[(&str, &str)], age_secs: u64) -> CacheFreshness {
+      let cached_time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+      let metadata = CachedUrlMetadata {
+        headers: headers
+          .iter()
+          .map(|(k, v)| (k.to_string(), v.to_string()))
+          .collect(),
+        url: "https://deno.land/x/mod.ts".to_string(),
+        now: cached_time,
+      };
+      metadata.freshness(cached_time + Duration::from_secs(age_secs))
+    }
+
+    let result = freshness(&[("cache-control", "public, max-age=60")], 30);
+    assert_eq!(result.lifetime, Some(Duration::from_secs(60)));
+    assert_eq!(result.is_stale(), Some(false));
+    let result = freshness(&[("cache-control", "public, max-age=60")], 90);
+    assert_eq!(result.age, Duration::from_secs(90));
+    assert_eq!(result.is_stale(), Some(true));
+    let result = freshness(&[("cache-control", "no-cache")], 1);
+    assert_eq!(result.is_stale(), Some(true));
+    let result = freshness(&[("cache-control", "max-age=0, immutable")], 1_000);
+    assert!(result.immutable);
+    assert_eq!(result.is_stale(), Some(false));
+    let result = freshness(
+      &[
+        ("date", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        ("expires", "Wed, 21 Oct 2015 08:28:00 GMT"),
+      ],
+      0,
+    );
+    assert_eq!(result.lifetime, Some(Duration::from_secs(3600)));
+    let result = freshness(&[("expires", "0")], 0);
+    assert_eq!(result.lifetime, Some(Duration::ZERO));
+    let result = freshness(
+      &[
+        ("date", "Wed, 21 Oct 2015 07:28:00 GMT"),
+        ("last-modified", "Wed, 11 Oct 2015 07:28:00 GMT"),
+      ],
+      0,
+    );
+    assert_eq!(result.lifetime, Some(Duration::from_secs(86_400)));
+    let result = freshness(&[("etag", "abc")], 0);
+    assert_eq!(result.lifetime, None);
+    assert_eq!(result.is_stale(), None);
+  }
+
   #[test]
   fn test_url_to_filename() {
     let test_cases = [
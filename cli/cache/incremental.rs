@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -17,12 +18,23 @@ use super::cache_db::CacheFailure;
 use super::common::FastInsecureHasher;
 
 pub static INCREMENTAL_CACHE_DB: CacheDBConfiguration = CacheDBConfiguration {
-  table_initializer: "CREATE TABLE IF NOT EXISTS incrementalcache (
+  table_initializer: concat!(
+    "CREATE TABLE IF NOT EXISTS incrementalcache (
       file_path TEXT PRIMARY KEY,
       state_hash TEXT NOT NULL,
       source_hash TEXT NOT NULL
     );",
-  on_version_change: "DELETE FROM incrementalcache;",
+    "CREATE TABLE IF NOT EXISTS incrementalcontentcache (
+      file_path TEXT NOT NULL,
+      state_hash TEXT NOT NULL,
+      source_hash TEXT NOT NULL,
+      PRIMARY KEY (file_path, state_hash, source_hash)
+    );",
+  ),
+  on_version_change: concat!(
+    "DELETE FROM incrementalcache;",
+    "DELETE FROM incrementalcontentcache;",
+  ),
   preheat_queries: &[],
   // If the cache fails, just ignore all caching attempts
   on_failure: CacheFailure::Blackhole,
@@ -30,6 +42,10 @@ pub static INCREMENTAL_CACHE_DB: CacheDBConfiguration = CacheDBConfiguration {
 
 /// Cache used to skip formatting/linting a file again when we
 /// know it is already formatted or has no lint diagnostics.
+///
+/// Files are looked up by the hash of their last content, but also by the
+/// hashes of all the contents that previously passed at the same path with
+/// the same configuration (ex. after switching back to another branch).
 pub struct IncrementalCache(IncrementalCacheInner);
 
 impl IncrementalCache {
@@ -54,6 +70,10 @@ impl IncrementalCache {
   }
 }
 
+/// The maximum number of contents that are remembered as passed. When
+/// exceeded, the least recently passed ones are removed.
+const MAX_PASSED_SOURCE_HASHES: usize = 10_000;
+
 enum ReceiverMessage {
   Update(PathBuf, u64),
   Exit,
@@ -61,6 +81,7 @@ enum ReceiverMessage {
 
 struct IncrementalCacheInner {
   previous_hashes: HashMap<PathBuf, u64>,
+  passed_source_hashes: HashSet<(PathBuf, u64)>,
   sender: tokio::sync::mpsc::UnboundedSender<ReceiverMessage>,
   handle: Mutex<Option<JoinHandle<()>>>,
 }
@@ -88,6 +109,8 @@ impl IncrementalCacheInner {
         previous_hashes.insert(path.to_path_buf(), hash);
       }
     }
+    let _ = cache.prune_passed_source_hashes(MAX_PASSED_SOURCE_HASHES);
+    let passed_source_hashes = cache.get_passed_source_hashes();
 
     let (sender, mut receiver) =
       tokio::sync::mpsc::unbounded_channel::<ReceiverMessage>();
@@ -98,6 +121,7 @@ impl IncrementalCacheInner {
         match message {
           ReceiverMessage::Update(path, hash) => {
             let _ = cache.set_source_hash(&path, hash);
+            let _ = cache.add_passed_source_hash(&path, hash);
           }
           ReceiverMessage::Exit => break,
         }
@@ -106,17 +130,19 @@ impl IncrementalCacheInner {
 
     IncrementalCacheInner {
       previous_hashes,
+      passed_source_hashes,
       sender,
       handle: Mutex::new(Some(handle)),
     }
   }
 
   pub fn is_file_same(&self, file_path: &Path, file_text: &str) -> bool {
+    let hash = FastInsecureHasher::new().write_str(file_text).finish();
     match self.previous_hashes.get(file_path) {
-      Some(hash) => {
-        *hash == FastInsecureHasher::new().write_str(file_text).finish()
-      }
-      None => false,
+      Some(previous_hash) if *previous_hash == hash => true,
+      _ => self
+        .passed_source_hashes
+        .contains(&(file_path.to_path_buf(), hash)),
     }
   }
 
@@ -214,6 +240,87 @@ impl SqlIncrementalCache {
     )?;
     Ok(())
   }
+
+  /// Gets the paths and hashes of all the file contents that previously
+  /// passed with the current state.
+  pub fn get_passed_source_hashes(&self) -> HashSet<(PathBuf, u64)> {
+    match self.get_passed_source_hashes_result() {
+      Ok(hashes) => hashes,
+      Err(err) => {
+        if cfg!(debug_assertions) {
+          panic!("Error retrieving hashes: {err}");
+        } else {
+          // fail silently when not debugging
+          HashSet::new()
+        }
+      }
+    }
+  }
+
+  fn get_passed_source_hashes_result(
+    &self,
+  ) -> Result<HashSet<(PathBuf, u64)>, AnyError> {
+    let query = "
+      SELECT
+        file_path,
+        source_hash
+      FROM
+        incrementalcontentcache
+      WHERE
+        state_hash=?1";
+    self.conn.with_connection(|conn| {
+      let mut stmt = conn.prepare_cached(query)?;
+      let mut rows = stmt.query(params![self.state_hash.to_string()])?;
+      let mut hashes = HashSet::new();
+      while let Some(row) = rows.next()? {
+        let path: String = row.get(0)?;
+        let hash: String = row.get(1)?;
+        hashes.insert((PathBuf::from(path), hash.parse::<u64>()?));
+      }
+      Ok(hashes)
+    })
+  }
+
+  pub fn add_passed_source_hash(
+    &self,
+    path: &Path,
+    source_hash: u64,
+  ) -> Result<(), AnyError> {
+    // replace instead of ignore so the row becomes the most recent one
+    let sql = "
+      INSERT OR REPLACE INTO
+        incrementalcontentcache (file_path, state_hash, source_hash)
+      VALUES
+        (?1, ?2, ?3)";
+    self.conn.execute(
+      sql,
+      params![
+        path.to_string_lossy(),
+        &self.state_hash.to_string(),
+        &source_hash.to_string()
+      ],
+    )?;
+    Ok(())
+  }
+
+  /// Removes the least recently passed contents so that at most
+  /// `max_entries` remain.
+  pub fn prune_passed_source_hashes(
+    &self,
+    max_entries: usize,
+  ) -> Result<(), AnyError> {
+    let sql = "
+      DELETE FROM
+        incrementalcontentcache
+      WHERE
+        rowid NOT IN (
+          SELECT rowid FROM incrementalcontentcache
+          ORDER BY rowid DESC
+          LIMIT ?1
+        )";
+    self.conn.execute(sql, params![max_entries as i64])?;
+    Ok(())
+  }
 }
 
 #[cfg(test)]
@@ -263,6 +370,65 @@ mod test {
     assert_eq!(cache.get_source_hash(&path2), Some(5));
   }
 
+  #[test]
+  pub fn sql_cache_passed_source_hashes() {
+    let conn = CacheDB::in_memory(&INCREMENTAL_CACHE_DB, "1.0.0");
+    let mut cache = SqlIncrementalCache::new(conn, 1);
+    let path = PathBuf::from("/mod.ts");
+    let path2 = PathBuf::from("/mod2.ts");
+    assert!(cache.get_passed_source_hashes().is_empty());
+    cache.add_passed_source_hash(&path, 2).unwrap();
+    cache.add_passed_source_hash(&path, 3).unwrap();
+    cache.add_passed_source_hash(&path, 3).unwrap();
+    cache.add_passed_source_hash(&path2, 3).unwrap();
+    assert_eq!(
+      cache.get_passed_source_hashes(),
+      HashSet::from([(path.clone(), 2), (path.clone(), 3), (path2.clone(), 3)])
+    );
+
+    // only the most recently passed contents are kept when pruning
+    cache.add_passed_source_hash(&path, 2).unwrap();
+    cache.prune_passed_source_hashes(2).unwrap();
+    assert_eq!(
+      cache.get_passed_source_hashes(),
+      HashSet::from([(path.clone(), 2), (path2, 3)])
+    );
+
+    // the hashes are specific to the state
+    cache.state_hash = 2;
+    assert!(cache.get_passed_source_hashes().is_empty());
+    cache.state_hash = 1;
+
+    // and cleared when the cli version changes
+    let conn = cache.conn.recreate_with_version("2.0.0");
+    let cache = SqlIncrementalCache::new(conn, 1);
+    assert!(cache.get_passed_source_hashes().is_empty());
+  }
+
+  #[tokio::test]
+  pub async fn incremental_cache_content_hash() {
+    let conn = CacheDB::in_memory(&INCREMENTAL_CACHE_DB, "1.0.0");
+    let sql_cache = SqlIncrementalCache::new(conn, 1);
+    let file_path = PathBuf::from("/mod.ts");
+    let file_hash = FastInsecureHasher::new().write_str("test").finish();
+    sql_cache
+      .add_passed_source_hash(&file_path, file_hash)
+      .unwrap();
+    let other_hash = FastInsecureHasher::new().write_str("other").finish();
+    sql_cache.set_source_hash(&file_path, other_hash).unwrap();
+    let cache = IncrementalCacheInner::from_sql_incremental_cache(
+      sql_cache,
+      &[file_path.clone()],
+    );
+
+    // content that previously passed at the same path is the same
+    assert!(cache.is_file_same(&file_path, "test"));
+    assert!(cache.is_file_same(&file_path, "other"));
+    assert!(!cache.is_file_same(&file_path, "changed"));
+    // but not at another path
+    assert!(!cache.is_file_same(&PathBuf::from("/copy.ts"), "test"));
+  }
+
   #[tokio::test]
   pub async fn incremental_cache_general_use() {
     let conn = CacheDB::in_memory(&INCREMENTAL_CACHE_DB, "1.0.0");
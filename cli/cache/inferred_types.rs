@@ -0,0 +1,110 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::anyhow;
+use deno_core::error::AnyError;
+
+use super::DiskCache;
+
+/// The cache that stores the declaration files inferred for remote JavaScript
+/// modules that don't provide types (`deno cache --infer-types`).
+#[derive(Debug, Clone)]
+pub struct InferredTypesCache {
+  disk_cache: DiskCache,
+}
+
+impl InferredTypesCache {
+  pub fn new(location: &Path) -> Self {
+    Self {
+      disk_cache: DiskCache::new(location),
+    }
+  }
+
+  /// Gets the specifier of the declaration file inferred for the module,
+  /// when one was cached.
+  pub fn get_types_specifier(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<ModuleSpecifier> {
+    let path = self.get_types_filepath(specifier)?;
+    if path.is_file() {
+      ModuleSpecifier::from_file_path(path).ok()
+    } else {
+      None
+    }
+  }
+
+  /// Caches the declaration file inferred for the module.
+  pub fn set_types(
+    &self,
+    specifier: &ModuleSpecifier,
+    text: &str,
+  ) -> Result<(), AnyError> {
+    let filename = self
+      .get_types_filename(specifier)
+      .ok_or_else(|| anyhow!("Could not get cache filename for {specifier}"))?;
+    self.disk_cache.set(&filename, text.as_bytes())?;
+    Ok(())
+  }
+
+  /// Gets if the specifier is a declaration file of this cache.
+  pub fn contains(&self, specifier: &ModuleSpecifier) -> bool {
+    specifier
+      .to_file_path()
+      .map(|path| path.starts_with(&self.disk_cache.location))
+      .unwrap_or(false)
+  }
+
+  fn get_types_filepath(&self, specifier: &ModuleSpecifier) -> Option<PathBuf> {
+    Some(
+      self
+        .disk_cache
+        .location
+        .join(self.get_types_filename(specifier)?),
+    )
+  }
+
+  fn get_types_filename(&self, specifier: &ModuleSpecifier) -> Option<PathBuf> {
+    match specifier.scheme() {
+      "http" | "https" => self
+        .disk_cache
+        .get_cache_filename_with_extension(specifier, "d.ts"),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  #[test]
+  fn test_inferred_types_cache() {
+    let temp_dir = TempDir::new();
+    let cache = InferredTypesCache::new(&temp_dir.path().join("inferred"));
+    let specifier =
+      ModuleSpecifier::parse("https://cdn.example.com/lib/mod.js").unwrap();
+    assert_eq!(cache.get_types_specifier(&specifier), None);
+
+    cache
+      .set_types(&specifier, "export declare const a: number;\n")
+      .unwrap();
+    let types_specifier = cache.get_types_specifier(&specifier).unwrap();
+    assert!(types_specifier.path().ends_with(".d.ts"));
+    assert!(cache.contains(&types_specifier));
+    assert!(!cache.contains(&specifier));
+    assert_eq!(
+      std::fs::read_to_string(types_specifier.to_file_path().unwrap()).unwrap(),
+      "export declare const a: number;\n"
+    );
+
+    // only the declarations of remote modules are cached
+    let local = ModuleSpecifier::parse("file:///project/mod.js").unwrap();
+    assert!(cache.set_types(&local, "").is_err());
+    assert_eq!(cache.get_types_specifier(&local), None);
+  }
+}
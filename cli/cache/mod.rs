@@ -1,7 +1,9 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::crash_report;
 use crate::errors::get_error_class_name;
 use crate::file_fetcher::FileFetcher;
+use crate::util::define::apply_defines;
 
 use deno_core::futures;
 use deno_core::futures::FutureExt;
@@ -11,32 +13,45 @@ use deno_graph::source::LoadFuture;
 use deno_graph::source::LoadResponse;
 use deno_graph::source::Loader;
 use deno_runtime::permissions::PermissionsContainer;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::Arc;
 
 mod cache_db;
+mod cache_store;
 mod caches;
 mod check;
+mod code_cache;
 mod common;
 mod deno_dir;
 mod disk_cache;
 mod emit;
 mod http_cache;
 mod incremental;
+mod inferred_types;
 mod node;
 mod parsed_source;
+mod remote;
 
+pub use cache_store::CacheStore;
 pub use caches::Caches;
 pub use check::TypeCheckCache;
+pub use code_cache::CodeCache;
 pub use common::FastInsecureHasher;
 pub use deno_dir::DenoDir;
 pub use disk_cache::DiskCache;
 pub use emit::EmitCache;
+pub use http_cache::base_url_to_filename;
+pub use http_cache::CacheFreshness;
 pub use http_cache::CachedUrlMetadata;
 pub use http_cache::HttpCache;
 pub use incremental::IncrementalCache;
+pub use inferred_types::InferredTypesCache;
 pub use node::NodeAnalysisCache;
 pub use parsed_source::ParsedSourceCache;
+pub use remote::RemoteArtifactHasher;
+pub use remote::RemoteArtifactKind;
+pub use remote::RemoteBuildCache;
 
 /// Permissions used to save a file in the disk caches.
 pub const CACHE_PERM: u32 = 0o644;
@@ -51,6 +66,7 @@ pub struct FetchCacher {
   root_permissions: PermissionsContainer,
   cache_info_enabled: bool,
   maybe_local_node_modules_url: Option<ModuleSpecifier>,
+  maybe_defines: Option<Arc<BTreeMap<String, String>>>,
 }
 
 impl FetchCacher {
@@ -61,6 +77,7 @@ impl FetchCacher {
     root_permissions: PermissionsContainer,
     dynamic_permissions: PermissionsContainer,
     maybe_local_node_modules_url: Option<ModuleSpecifier>,
+    maybe_defines: Option<Arc<BTreeMap<String, String>>>,
   ) -> Self {
     Self {
       emit_cache,
@@ -70,6 +87,7 @@ impl FetchCacher {
       root_permissions,
       cache_info_enabled: false,
       maybe_local_node_modules_url,
+      maybe_defines,
     }
   }
 
@@ -87,19 +105,12 @@ impl Loader for FetchCacher {
     }
 
     let local = self.file_fetcher.get_local_path(specifier)?;
-    if local.is_file() {
-      let emit = self
-        .emit_cache
-        .get_emit_filepath(specifier)
-        .filter(|p| p.is_file());
-      Some(CacheInfo {
-        local: Some(local),
-        emit,
-        map: None,
-      })
-    } else {
-      None
-    }
+    let emit = self.emit_cache.get_emit_filepath(specifier);
+    Some(CacheInfo {
+      local: Some(local),
+      emit,
+      map: None,
+    })
   }
 
   fn load(
@@ -107,6 +118,7 @@ impl Loader for FetchCacher {
     specifier: &ModuleSpecifier,
     is_dynamic: bool,
   ) -> LoadFuture {
+    crash_report::set_last_loaded_module(specifier);
     if let Some(node_modules_url) = self.maybe_local_node_modules_url.as_ref() {
       // The specifier might be in a completely different symlinked tree than
       // what the resolved node_modules_url is in (ex. `/my-project-1/node_modules`
@@ -131,6 +143,7 @@ impl Loader for FetchCacher {
     };
     let file_fetcher = self.file_fetcher.clone();
     let file_header_overrides = self.file_header_overrides.clone();
+    let maybe_defines = self.maybe_defines.clone();
     let specifier = specifier.clone();
 
     async move {
@@ -147,10 +160,21 @@ impl Loader for FetchCacher {
               (None, Some(overrides)) => Some(overrides.clone()),
               (None, None) => None,
             };
+          let content = maybe_defines
+            .and_then(|defines| {
+              apply_defines(
+                &file.specifier,
+                file.media_type,
+                &file.source,
+                &defines,
+              )
+            })
+            .map(|source| source.into())
+            .unwrap_or(file.source);
           Ok(Some(LoadResponse::Module {
             specifier: file.specifier,
             maybe_headers,
-            content: file.source,
+            content,
           }))
         })
         .unwrap_or_else(|err| {
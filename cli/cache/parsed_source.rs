@@ -1,6 +1,5 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use deno_ast::MediaType;
@@ -14,6 +13,7 @@ use deno_graph::DefaultModuleAnalyzer;
 use deno_graph::ModuleInfo;
 use deno_graph::ModuleParser;
 use deno_runtime::deno_webstorage::rusqlite::params;
+use indexmap::IndexMap;
 
 use super::cache_db::CacheDB;
 use super::cache_db::CacheDBConfiguration;
@@ -44,10 +44,76 @@ pub static PARSED_SOURCE_CACHE_DB: CacheDBConfiguration =
     on_failure: CacheFailure::InMemory,
   };
 
+#[derive(Default)]
+struct RetainedParsedSources {
+  /// The parsed sources of the modules that get transpiled, in the order
+  /// they were stored so the oldest are freed first.
+  transpiled: IndexMap<ModuleSpecifier, ParsedSource>,
+  /// The parsed sources of the other modules, which are only needed again
+  /// in rare cases, in the order they were stored.
+  others: IndexMap<ModuleSpecifier, ParsedSource>,
+  /// The size of the source text of the retained parsed sources.
+  size: usize,
+}
+
+impl RetainedParsedSources {
+  fn get(&self, specifier: &ModuleSpecifier) -> Option<&ParsedSource> {
+    self
+      .transpiled
+      .get(specifier)
+      .or_else(|| self.others.get(specifier))
+  }
+
+  fn insert(
+    &mut self,
+    specifier: ModuleSpecifier,
+    parsed_source: ParsedSource,
+  ) {
+    self.size += source_size(&parsed_source);
+    if is_transpiled(parsed_source.media_type()) {
+      self.transpiled.insert(specifier, parsed_source);
+    } else {
+      self.others.insert(specifier, parsed_source);
+    }
+  }
+
+  fn remove(&mut self, specifier: &ModuleSpecifier) -> Option<ParsedSource> {
+    let parsed_source = self
+      .transpiled
+      .shift_remove(specifier)
+      .or_else(|| self.others.shift_remove(specifier))?;
+    self.size -= source_size(&parsed_source);
+    Some(parsed_source)
+  }
+
+  /// Frees the oldest parsed source of a module that's not transpiled, or
+  /// the oldest one of a transpiled module when allowed and there's none.
+  fn free_oldest(&mut self, allow_transpiled: bool) -> bool {
+    let mut maybe_oldest = self.others.shift_remove_index(0);
+    if maybe_oldest.is_none() && allow_transpiled {
+      maybe_oldest = self.transpiled.shift_remove_index(0);
+    }
+    match maybe_oldest {
+      Some((_, oldest)) => {
+        self.size -= source_size(&oldest);
+        true
+      }
+      None => false,
+    }
+  }
+
+  fn clear(&mut self) {
+    self.transpiled.clear();
+    self.others.clear();
+    self.size = 0;
+  }
+}
+
 #[derive(Clone, Default)]
-struct ParsedSourceCacheSources(
-  Arc<Mutex<HashMap<ModuleSpecifier, ParsedSource>>>,
-);
+struct ParsedSourceCacheSources {
+  retained: Arc<Mutex<RetainedParsedSources>>,
+  memory_budget: Option<usize>,
+}
 
 /// It's ok that this is racy since in non-LSP situations
 /// this will only ever store one form of a parsed source
@@ -60,17 +126,53 @@ impl deno_graph::ParsedSourceStore for ParsedSourceCacheSources {
     specifier: deno_graph::ModuleSpecifier,
     parsed_source: ParsedSource,
   ) -> Option<ParsedSource> {
-    self.0.lock().insert(specifier, parsed_source)
+    let mut retained = self.retained.lock();
+    let previous = retained.remove(&specifier);
+    let size = source_size(&parsed_source);
+    if let Some(memory_budget) = self.memory_budget {
+      // Only the modules that get transpiled need their parsed source again
+      // after their dependencies are analyzed, so the parsed sources of the
+      // other modules are freed first and never free the ones of transpiled
+      // modules. They are parsed again in the rare cases they're needed.
+      let allow_transpiled = is_transpiled(parsed_source.media_type());
+      while retained.size + size > memory_budget {
+        if !retained.free_oldest(allow_transpiled) {
+          break;
+        }
+      }
+      if retained.size + size > memory_budget {
+        return previous;
+      }
+    }
+    retained.insert(specifier, parsed_source);
+    previous
   }
 
   fn get_parsed_source(
     &self,
     specifier: &deno_graph::ModuleSpecifier,
   ) -> Option<ParsedSource> {
-    self.0.lock().get(specifier).cloned()
+    self.retained.lock().get(specifier).cloned()
   }
 }
 
+fn is_transpiled(media_type: MediaType) -> bool {
+  matches!(
+    media_type,
+    MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Jsx
+      | MediaType::Tsx
+  )
+}
+
+/// The size of the source text, which the memory used by the parsed source
+/// is proportional to.
+fn source_size(parsed_source: &ParsedSource) -> usize {
+  parsed_source.text_info().text_str().len()
+}
+
 /// A cache of `ParsedSource`s, which may be used with `deno_graph`
 /// for cached dependency analysis.
 pub struct ParsedSourceCache {
@@ -87,15 +189,21 @@ impl ParsedSourceCache {
     }
   }
 
-  pub fn new(db: CacheDB) -> Self {
+  /// The parsed sources that are retained for emitting are freed, oldest
+  /// first, once the size of their source text exceeds the memory budget
+  /// (in bytes).
+  pub fn new(db: CacheDB, memory_budget: Option<usize>) -> Self {
     Self {
       db,
-      sources: Default::default(),
+      sources: ParsedSourceCacheSources {
+        retained: Default::default(),
+        memory_budget,
+      },
     }
   }
 
   pub fn clear(&self) {
-    self.sources.0.lock().clear();
+    self.sources.retained.lock().clear();
   }
 
   pub fn get_parsed_source_from_esm_module(
@@ -124,7 +232,7 @@ impl ParsedSourceCache {
 
   /// Frees the parsed source from memory.
   pub fn free(&self, specifier: &ModuleSpecifier) {
-    self.sources.0.lock().remove(specifier);
+    self.sources.retained.lock().remove(specifier);
   }
 
   pub fn as_analyzer(&self) -> Box<dyn deno_graph::ModuleAnalyzer> {
@@ -271,6 +379,7 @@ fn compute_source_hash(bytes: &[u8]) -> String {
 
 #[cfg(test)]
 mod test {
+  use deno_graph::ParsedSourceStore;
   use deno_graph::PositionRange;
   use deno_graph::SpecifierWithRange;
 
@@ -359,4 +468,63 @@ mod test {
       None,
     );
   }
+
+  fn parse(specifier: &ModuleSpecifier, source: &str) -> ParsedSource {
+    deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: specifier.to_string(),
+      text_info: deno_ast::SourceTextInfo::from_string(source.to_string()),
+      media_type: MediaType::from_specifier(specifier),
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap()
+  }
+
+  #[test]
+  fn parsed_source_store_memory_budget() {
+    let sources = ParsedSourceCacheSources {
+      retained: Default::default(),
+      memory_budget: Some(40),
+    };
+    let a = ModuleSpecifier::parse("file:///a.ts").unwrap();
+    let b = ModuleSpecifier::parse("file:///b.ts").unwrap();
+    let c = ModuleSpecifier::parse("file:///c.js").unwrap();
+    let d = ModuleSpecifier::parse("file:///d.ts").unwrap();
+    // 20 bytes each
+    sources.set_parsed_source(a.clone(), parse(&a, "const a: number = 1;"));
+    sources.set_parsed_source(b.clone(), parse(&b, "const b: number = 2;"));
+    assert!(sources.get_parsed_source(&a).is_some());
+    assert!(sources.get_parsed_source(&b).is_some());
+
+    // not transpiled, so it doesn't free the ones of transpiled modules
+    sources.set_parsed_source(c.clone(), parse(&c, "const c = 3;"));
+    assert!(sources.get_parsed_source(&c).is_none());
+    assert!(sources.get_parsed_source(&a).is_some());
+
+    // the oldest is freed to stay within the budget
+    sources.set_parsed_source(d.clone(), parse(&d, "const d: number = 4;"));
+    assert!(sources.get_parsed_source(&a).is_none());
+    assert!(sources.get_parsed_source(&b).is_some());
+    assert!(sources.get_parsed_source(&d).is_some());
+    assert_eq!(sources.retained.lock().size, 40);
+
+    // the ones of modules that aren't transpiled are retained within the
+    // budget and freed first
+    sources.retained.lock().remove(&b);
+    sources.set_parsed_source(c.clone(), parse(&c, "const c = 3;"));
+    assert!(sources.get_parsed_source(&c).is_some());
+    assert_eq!(sources.retained.lock().size, 32);
+    sources.set_parsed_source(b.clone(), parse(&b, "const b: number = 2;"));
+    assert!(sources.get_parsed_source(&c).is_none());
+    assert!(sources.get_parsed_source(&b).is_some());
+    assert!(sources.get_parsed_source(&d).is_some());
+    assert_eq!(sources.retained.lock().size, 40);
+
+    // larger than the whole budget
+    let large = format!("const a: string = \"{}\";", "a".repeat(40));
+    sources.set_parsed_source(a.clone(), parse(&a, &large));
+    assert!(sources.get_parsed_source(&a).is_none());
+    assert_eq!(sources.retained.lock().size, 0);
+  }
 }
@@ -0,0 +1,493 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::time::Duration;
+
+use deno_core::error::AnyError;
+use deno_core::futures::stream;
+use deno_core::futures::StreamExt;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
+use deno_runtime::deno_fetch::reqwest::RequestBuilder;
+use ring::digest;
+use ring::hmac;
+
+use crate::args::RemoteCacheConfig;
+use crate::auth_tokens::AuthTokens;
+use crate::colors;
+use crate::http_util::BadResponseError;
+use crate::http_util::HttpClient;
+use crate::util::sync::AtomicFlag;
+use crate::util::telemetry;
+
+/// The length of the HMAC-SHA256 signature that the artifacts start with.
+const SIGNATURE_LEN: usize = 32;
+
+/// What the directory of the configuration file is replaced with in the
+/// artifacts, so that they don't have the paths of the machine they were
+/// built on.
+const CONFIG_DIR_PLACEHOLDER: &str = "deno-remote-cache:///";
+
+/// How long a request to the cache can take, including reading the artifact,
+/// before the artifact is built locally instead.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The requests that are sent to the cache at the same time at most.
+const MAX_CONCURRENT_REQUESTS: usize = 16;
+
+/// The kinds of artifacts that are shared with the remote cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteArtifactKind {
+  /// The emitted code of a module.
+  Emit,
+  /// The `.tsbuildinfo` of a successful type check, which also tells that
+  /// the check doesn't need to run again.
+  Check,
+}
+
+impl RemoteArtifactKind {
+  fn as_str(&self) -> &'static str {
+    match self {
+      RemoteArtifactKind::Emit => "emit",
+      RemoteArtifactKind::Check => "check",
+    }
+  }
+}
+
+/// Hashes the inputs of an artifact into its key in the remote cache. This
+/// is SHA-256, so that an artifact can't be made to have the key of another.
+pub struct RemoteArtifactHasher(digest::Context);
+
+impl RemoteArtifactHasher {
+  pub fn new() -> Self {
+    Self(digest::Context::new(&digest::SHA256))
+  }
+
+  /// Writes the bytes along with their length, so that the bytes of an input
+  /// can't be moved to the next one.
+  pub fn write(&mut self, bytes: &[u8]) -> &mut Self {
+    self.0.update(&(bytes.len() as u64).to_le_bytes());
+    self.0.update(bytes);
+    self
+  }
+
+  pub fn write_str(&mut self, text: &str) -> &mut Self {
+    self.write(text.as_bytes())
+  }
+
+  pub fn write_u64(&mut self, value: u64) -> &mut Self {
+    self.write(&value.to_le_bytes())
+  }
+
+  /// Gets the key, as a lowercase hex string.
+  pub fn finish(&self) -> String {
+    self
+      .0
+      .clone()
+      .finish()
+      .as_ref()
+      .iter()
+      .map(|byte| format!("{byte:02x}"))
+      .collect()
+  }
+}
+
+impl Default for RemoteArtifactHasher {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// A shared HTTP cache of build artifacts, configured with `"remoteCache"`
+/// in the configuration file, that's read through before building an
+/// artifact locally and written through after.
+///
+/// The artifacts are keyed by a SHA-256 hash of their inputs and the CLI
+/// version, so that they're only reused for the same inputs. They're read
+/// with `GET` requests and uploaded with `PUT` requests to
+/// `<url>/<kind>/<key>`. A cache that can't be reached only makes the
+/// artifacts built locally, and it's not used for the rest of the process
+/// after the first request that fails to connect or times out.
+///
+/// The artifacts start with an HMAC-SHA256 signature of their kind, key and
+/// content that's keyed by the auth token of the cache, so that the ones that
+/// weren't uploaded by a holder of the token are ignored.
+pub struct RemoteBuildCache {
+  url: Url,
+  config_dir: Url,
+  read_only: bool,
+  authorization: String,
+  signing_key: hmac::Key,
+  http_client: HttpClient,
+  is_unreachable: AtomicFlag,
+}
+
+impl RemoteBuildCache {
+  /// Creates the cache, or returns `None` with a warning when there's no
+  /// auth token for it, because its artifacts can't be verified without one.
+  pub fn new(
+    config: &RemoteCacheConfig,
+    http_client: HttpClient,
+  ) -> Option<Self> {
+    let maybe_authorization = match &config.auth_token_env {
+      Some(env_var) => std::env::var(env_var)
+        .ok()
+        .map(|token| format!("Bearer {token}")),
+      None => AuthTokens::new(std::env::var("DENO_AUTH_TOKENS").ok())
+        .get(&config.url)
+        .map(|token| token.to_string()),
+    };
+    match maybe_authorization {
+      Some(authorization) => {
+        Some(Self::with_authorization(config, authorization, http_client))
+      }
+      None => {
+        log::warn!(
+          "{} The remote cache at {} is not used, because there's no auth token to verify its artifacts with.",
+          colors::yellow("Warning"),
+          config.url
+        );
+        None
+      }
+    }
+  }
+
+  fn with_authorization(
+    config: &RemoteCacheConfig,
+    authorization: String,
+    http_client: HttpClient,
+  ) -> Self {
+    let signing_key =
+      hmac::Key::new(hmac::HMAC_SHA256, authorization.as_bytes());
+    Self {
+      url: config.url.clone(),
+      config_dir: config.config_dir.clone(),
+      read_only: config.read_only,
+      authorization,
+      signing_key,
+      http_client,
+      is_unreachable: Default::default(),
+    }
+  }
+
+  /// Gets the specifier of a module in the inputs of the artifacts. The local
+  /// modules are relative to the directory of the configuration file, so
+  /// that the checkouts of a project in other directories share them.
+  pub fn key_specifier(&self, specifier: &ModuleSpecifier) -> String {
+    if specifier.scheme() == "file" {
+      if let Some(relative) = self.config_dir.make_relative(specifier) {
+        return relative;
+      }
+    }
+    specifier.to_string()
+  }
+
+  /// Replaces the directory of the configuration file in an artifact that's
+  /// uploaded, like in the paths of a `.tsbuildinfo`.
+  pub fn to_portable_text(&self, text: &str) -> String {
+    text.replace(self.config_dir.as_str(), CONFIG_DIR_PLACEHOLDER)
+  }
+
+  /// Puts the directory of the configuration file back in an artifact that
+  /// was read from the cache.
+  pub fn to_local_text(&self, text: &str) -> String {
+    text.replace(CONFIG_DIR_PLACEHOLDER, self.config_dir.as_str())
+  }
+
+  /// Gets the artifacts that are in the cache, in the order of the keys.
+  pub async fn get_many(
+    &self,
+    kind: RemoteArtifactKind,
+    keys: &[String],
+  ) -> Vec<Option<Vec<u8>>> {
+    stream::iter(keys.iter().map(|key| self.get(kind, key)))
+      .buffered(MAX_CONCURRENT_REQUESTS)
+      .collect()
+      .await
+  }
+
+  /// Gets an artifact, or `None` when it's not in the cache or its signature
+  /// is invalid.
+  pub async fn get(
+    &self,
+    kind: RemoteArtifactKind,
+    key: &str,
+  ) -> Option<Vec<u8>> {
+    if self.is_unreachable.is_raised() {
+      return None;
+    }
+    let url = self.artifact_url(kind, key);
+    let maybe_bytes = match self.get_result(&url).await {
+      Ok(maybe_signed_bytes) => maybe_signed_bytes.and_then(|signed_bytes| {
+        let maybe_bytes = self.verify(kind, key, signed_bytes);
+        if maybe_bytes.is_none() {
+          log::warn!(
+            "{} Ignored {} from the remote cache, because its signature is invalid.",
+            colors::yellow("Warning"),
+            url
+          );
+        }
+        maybe_bytes
+      }),
+      Err(err) => {
+        if !self.handle_unreachable(&err) {
+          log::warn!("Failed reading {} from the remote cache: {:#}", url, err);
+        }
+        return None;
+      }
+    };
+    telemetry::record_cache(
+      &format!("remote_{}", kind.as_str()),
+      maybe_bytes.is_some(),
+    );
+    maybe_bytes
+  }
+
+  /// Uploads the artifacts, unless the cache is read only.
+  pub async fn put_many(
+    &self,
+    kind: RemoteArtifactKind,
+    artifacts: Vec<(String, Vec<u8>)>,
+  ) {
+    stream::iter(
+      artifacts
+        .iter()
+        .map(|(key, bytes)| self.put(kind, key, bytes)),
+    )
+    .buffer_unordered(MAX_CONCURRENT_REQUESTS)
+    .collect::<Vec<_>>()
+    .await;
+  }
+
+  /// Uploads an artifact, unless the cache is read only.
+  pub async fn put(&self, kind: RemoteArtifactKind, key: &str, bytes: &[u8]) {
+    if self.read_only || self.is_unreachable.is_raised() {
+      return;
+    }
+    let url = self.artifact_url(kind, key);
+    if let Err(err) = self.put_result(&url, self.sign(kind, key, bytes)).await {
+      if !self.handle_unreachable(&err) {
+        log::warn!("Failed uploading {} to the remote cache: {:#}", url, err);
+      }
+    }
+  }
+
+  /// Stops using the cache when the error is a failure to connect to it or a
+  /// timeout, so that the next artifacts don't wait for it too, and warns
+  /// about it once. Returns if the error was handled.
+  fn handle_unreachable(&self, err: &AnyError) -> bool {
+    let is_unreachable = err
+      .downcast_ref::<reqwest::Error>()
+      .map(|err| err.is_connect() || err.is_timeout())
+      .unwrap_or(false);
+    if !is_unreachable {
+      return false;
+    }
+    if self.is_unreachable.raise() {
+      log::warn!(
+        "{} The remote cache at {} is not used for the rest of this run, because it can't be reached: {:#}",
+        colors::yellow("Warning"),
+        self.url,
+        err
+      );
+    }
+    true
+  }
+
+  /// Prepends the signature of the artifact to it.
+  fn sign(&self, kind: RemoteArtifactKind, key: &str, bytes: &[u8]) -> Vec<u8> {
+    let signature =
+      hmac::sign(&self.signing_key, &signed_message(kind, key, bytes));
+    let mut signed_bytes = signature.as_ref().to_vec();
+    signed_bytes.extend_from_slice(bytes);
+    signed_bytes
+  }
+
+  /// Gets the artifact without its signature, if it's valid.
+  fn verify(
+    &self,
+    kind: RemoteArtifactKind,
+    key: &str,
+    mut signed_bytes: Vec<u8>,
+  ) -> Option<Vec<u8>> {
+    if signed_bytes.len() < SIGNATURE_LEN {
+      return None;
+    }
+    let bytes = signed_bytes.split_off(SIGNATURE_LEN);
+    hmac::verify(
+      &self.signing_key,
+      &signed_message(kind, key, &bytes),
+      &signed_bytes,
+    )
+    .ok()?;
+    Some(bytes)
+  }
+
+  async fn get_result(&self, url: &Url) -> Result<Option<Vec<u8>>, AnyError> {
+    let request = self.http_client.get_no_redirect(url.clone());
+    let response = self.with_authorization(request).send().await?;
+    if response.status() == 404 {
+      return Ok(None);
+    }
+    if !response.status().is_success() {
+      return Err(
+        BadResponseError {
+          status: response.status(),
+          maybe_response_text: response.text().await.ok(),
+        }
+        .into(),
+      );
+    }
+    Ok(Some(response.bytes().await?.to_vec()))
+  }
+
+  async fn put_result(
+    &self,
+    url: &Url,
+    bytes: Vec<u8>,
+  ) -> Result<(), AnyError> {
+    let request = self.http_client.put(url.clone()).body(bytes);
+    let response = self.with_authorization(request).send().await?;
+    if !response.status().is_success() {
+      return Err(
+        BadResponseError {
+          status: response.status(),
+          maybe_response_text: response.text().await.ok(),
+        }
+        .into(),
+      );
+    }
+    Ok(())
+  }
+
+  fn with_authorization(&self, request: RequestBuilder) -> RequestBuilder {
+    request
+      .header(AUTHORIZATION, &self.authorization)
+      .timeout(REQUEST_TIMEOUT)
+  }
+
+  fn artifact_url(&self, kind: RemoteArtifactKind, key: &str) -> Url {
+    // the configured URL always ends with a slash
+    self
+      .url
+      .join(&format!("{}/{}", kind.as_str(), key))
+      .unwrap()
+  }
+}
+
+/// The message that the signature of an artifact is computed over, which
+/// includes its kind and key so that it can't be stored under another one.
+fn signed_message(
+  kind: RemoteArtifactKind,
+  key: &str,
+  bytes: &[u8],
+) -> Vec<u8> {
+  let mut message = format!("{}/{}\n", kind.as_str(), key).into_bytes();
+  message.extend_from_slice(bytes);
+  message
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn create_cache(authorization: &str) -> RemoteBuildCache {
+    RemoteBuildCache::with_authorization(
+      &RemoteCacheConfig {
+        url: Url::parse("https://cache.example.com/deno/").unwrap(),
+        read_only: false,
+        auth_token_env: None,
+        config_dir: Url::parse("file:///project/").unwrap(),
+      },
+      authorization.to_string(),
+      HttpClient::new(None, None).unwrap(),
+    )
+  }
+
+  #[test]
+  fn test_artifact_url() {
+    let cache = create_cache("Bearer token");
+    let key = RemoteArtifactHasher::new().write_str("mod.ts").finish();
+    assert_eq!(key.len(), 64);
+    assert_eq!(
+      cache
+        .artifact_url(RemoteArtifactKind::Emit, &key)
+        .to_string(),
+      format!("https://cache.example.com/deno/emit/{key}")
+    );
+    assert_eq!(
+      cache
+        .artifact_url(RemoteArtifactKind::Check, "abc")
+        .to_string(),
+      "https://cache.example.com/deno/check/abc"
+    );
+  }
+
+  #[test]
+  fn test_key_specifier() {
+    let cache = create_cache("Bearer token");
+    let key_specifier = |specifier: &str| {
+      cache.key_specifier(&ModuleSpecifier::parse(specifier).unwrap())
+    };
+    assert_eq!(key_specifier("file:///project/src/mod.ts"), "src/mod.ts");
+    assert_eq!(key_specifier("file:///shared/mod.ts"), "../shared/mod.ts");
+    assert_eq!(
+      key_specifier("https://deno.land/x/mod.ts"),
+      "https://deno.land/x/mod.ts"
+    );
+
+    let text = r#"{"root":["file:///project/main.ts"]}"#;
+    let portable_text = cache.to_portable_text(text);
+    assert_eq!(
+      portable_text,
+      r#"{"root":["deno-remote-cache:///main.ts"]}"#
+    );
+    assert_eq!(cache.to_local_text(&portable_text), text);
+  }
+
+  #[test]
+  fn test_artifact_hasher() {
+    // the inputs can't be shifted into each other
+    assert_ne!(
+      RemoteArtifactHasher::new()
+        .write_str("a")
+        .write_str("bc")
+        .finish(),
+      RemoteArtifactHasher::new()
+        .write_str("ab")
+        .write_str("c")
+        .finish(),
+    );
+    assert_eq!(
+      RemoteArtifactHasher::new().write_str("a").finish(),
+      RemoteArtifactHasher::new().write_str("a").finish(),
+    );
+  }
+
+  #[test]
+  fn test_signatures() {
+    let cache = create_cache("Bearer token");
+    let kind = RemoteArtifactKind::Emit;
+    let signed_bytes = cache.sign(kind, "a", b"console.log(1);");
+    assert_eq!(signed_bytes.len(), SIGNATURE_LEN + 15);
+    assert_eq!(
+      cache.verify(kind, "a", signed_bytes.clone()).unwrap(),
+      b"console.log(1);"
+    );
+    // it's not valid for another key, kind or token
+    assert!(cache.verify(kind, "b", signed_bytes.clone()).is_none());
+    assert!(cache
+      .verify(RemoteArtifactKind::Check, "a", signed_bytes.clone())
+      .is_none());
+    let other_cache = create_cache("Bearer other");
+    assert!(other_cache
+      .verify(kind, "a", signed_bytes.clone())
+      .is_none());
+    // nor when it's changed
+    let mut changed_bytes = signed_bytes;
+    *changed_bytes.last_mut().unwrap() = b'!';
+    assert!(cache.verify(kind, "a", changed_bytes).is_none());
+    assert!(cache.verify(kind, "a", vec![1, 2, 3]).is_none());
+  }
+}
@@ -0,0 +1,196 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::panic::PanicInfo;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use deno_core::ModuleSpecifier;
+
+use crate::version;
+
+thread_local! {
+  // The panic hook runs on the panicking thread, so a per thread record
+  // avoids contending on a lock for every loaded module.
+  static LAST_LOADED_MODULE: RefCell<Option<String>> = RefCell::new(None);
+}
+
+const REDACTED: &str = "<redacted>";
+
+/// Records the origin of the module the loaders of this thread are processing,
+/// so that a crash report tells where a crash of the module graph or the
+/// loader comes from.
+pub fn set_last_loaded_module(specifier: &ModuleSpecifier) {
+  let origin = redact_specifier(specifier);
+  LAST_LOADED_MODULE.with(|last| *last.borrow_mut() = Some(origin));
+}
+
+/// Reduces a specifier to its origin, since paths and query strings may
+/// contain user names or tokens.
+fn redact_specifier(specifier: &ModuleSpecifier) -> String {
+  match specifier.scheme() {
+    "http" | "https" => {
+      format!("{}/{REDACTED}", specifier.origin().ascii_serialization())
+    }
+    "file" => format!("file:///{REDACTED}"),
+    scheme => format!("{scheme}:{REDACTED}"),
+  }
+}
+
+/// Writes a crash report for the panic to the temp directory and returns its
+/// path.
+pub fn write(panic_info: &PanicInfo) -> std::io::Result<PathBuf> {
+  let report = create_report(panic_info, env_args());
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis())
+    .unwrap_or_default();
+  let path = std::env::temp_dir().join(format!(
+    "deno-crash-{}-{}.txt",
+    timestamp,
+    std::process::id()
+  ));
+  std::fs::write(&path, report)?;
+  Ok(path)
+}
+
+fn env_args() -> Vec<String> {
+  std::env::args_os()
+    .map(|arg| arg.to_string_lossy().into_owned())
+    .collect()
+}
+
+fn create_report(panic_info: &PanicInfo, args: Vec<String>) -> String {
+  let (subcommand, args) = redact_args(args);
+  let mut report = String::new();
+  writeln!(report, "Deno crash report").unwrap();
+  writeln!(report).unwrap();
+  writeln!(report, "Version: {}", version::deno()).unwrap();
+  writeln!(
+    report,
+    "Platform: {} {}",
+    std::env::consts::OS,
+    std::env::consts::ARCH
+  )
+  .unwrap();
+  writeln!(
+    report,
+    "Subcommand: {}",
+    subcommand.as_deref().unwrap_or("-")
+  )
+  .unwrap();
+  writeln!(report, "Args: {}", args.join(" ")).unwrap();
+  let last_loaded_module = LAST_LOADED_MODULE
+    .try_with(|last| match last.try_borrow() {
+      Ok(last) => last.clone().unwrap_or_else(|| "-".to_string()),
+      // the panic happened while the module was being recorded
+      Err(_) => "unknown".to_string(),
+    })
+    .unwrap_or_else(|_| "unknown".to_string());
+  writeln!(report, "Last loaded module: {last_loaded_module}").unwrap();
+  writeln!(report).unwrap();
+  writeln!(report, "{panic_info}").unwrap();
+  writeln!(report).unwrap();
+  writeln!(report, "Backtrace:").unwrap();
+  writeln!(report, "{}", Backtrace::force_capture()).unwrap();
+  report
+}
+
+/// Returns the subcommand and the args with the values of the flags and the
+/// other positional args redacted, since they may contain paths, URLs or
+/// tokens that shouldn't end up in a bug report.
+fn redact_args(args: Vec<String>) -> (Option<String>, Vec<String>) {
+  let mut subcommand = None;
+  let mut redacted = Vec::with_capacity(args.len());
+  let mut args = args.into_iter();
+  if let Some(bin) = args.next() {
+    redacted.push(
+      PathBuf::from(bin)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default(),
+    );
+  }
+  let mut after_double_dash = false;
+  for arg in args {
+    if arg == "--" {
+      after_double_dash = true;
+      redacted.push(arg);
+    } else if !after_double_dash && arg.starts_with('-') {
+      match arg.split_once('=') {
+        Some((flag, _)) => redacted.push(format!("{flag}={REDACTED}")),
+        None => redacted.push(arg),
+      }
+    } else if subcommand.is_none() && !after_double_dash {
+      subcommand = Some(arg.clone());
+      redacted.push(arg);
+    } else {
+      redacted.push(REDACTED.to_string());
+    }
+  }
+  (subcommand, redacted)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_redact_args() {
+    let (subcommand, args) = redact_args(
+      [
+        "/usr/bin/deno",
+        "run",
+        "--allow-read=/home/user",
+        "--reload",
+        "--config",
+        "deno.json",
+        "main.ts",
+        "--",
+        "--token=secret",
+      ]
+      .into_iter()
+      .map(String::from)
+      .collect(),
+    );
+    assert_eq!(subcommand.as_deref(), Some("run"));
+    assert_eq!(
+      args,
+      vec![
+        "deno",
+        "run",
+        "--allow-read=<redacted>",
+        "--reload",
+        "--config",
+        "<redacted>",
+        "<redacted>",
+        "--",
+        "<redacted>",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_redact_specifier() {
+    let cases = [
+      (
+        "https://deno.land/x/mod.ts?token=secret",
+        "https://deno.land/<redacted>",
+      ),
+      (
+        "http://localhost:4545/mod.ts",
+        "http://localhost:4545/<redacted>",
+      ),
+      ("file:///home/user/main.ts", "file:///<redacted>"),
+      ("npm:chalk@5", "npm:<redacted>"),
+      ("data:application/javascript,1", "data:<redacted>"),
+    ];
+    for (specifier, expected) in cases {
+      let specifier = ModuleSpecifier::parse(specifier).unwrap();
+      assert_eq!(redact_specifier(&specifier), expected);
+    }
+  }
+}
@@ -1,21 +1,40 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::Lockfile;
 use crate::cache::EmitCache;
 use crate::cache::FastInsecureHasher;
 use crate::cache::ParsedSourceCache;
+use crate::cache::RemoteArtifactHasher;
+use crate::cache::RemoteArtifactKind;
+use crate::cache::RemoteBuildCache;
+use crate::util::progress_bar;
+use crate::util::telemetry;
+use crate::version;
 
+use deno_ast::swc::codegen;
+use deno_ast::swc::codegen::text_writer::JsWriter;
+use deno_ast::swc::common::FileName;
+use deno_ast::swc::common::SourceMap;
 use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
 use deno_core::ModuleCode;
 use deno_core::ModuleSpecifier;
 use deno_graph::MediaType;
 use deno_graph::Module;
 use deno_graph::ModuleGraph;
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
 
 pub struct Emitter {
   emit_cache: EmitCache,
+  /// Emits done or loaded in this process keyed by specifier and source hash,
+  /// which are shared across workers so that modules imported by many of them
+  /// (ex. shared dependencies of `deno test` modules) are only read once.
+  memory_emit_cache: Mutex<HashMap<ModuleSpecifier, (u64, Arc<str>)>>,
   parsed_source_cache: Arc<ParsedSourceCache>,
   emit_options: deno_ast::EmitOptions,
+  minify: bool,
   // cached hash of the emit options
   emit_options_hash: u64,
 }
@@ -25,33 +44,35 @@ impl Emitter {
     emit_cache: EmitCache,
     parsed_source_cache: Arc<ParsedSourceCache>,
     emit_options: deno_ast::EmitOptions,
+    minify: bool,
   ) -> Self {
     let emit_options_hash = FastInsecureHasher::new()
       .write_hashable(&emit_options)
+      .write_hashable(&minify)
       .finish();
     Self {
       emit_cache,
+      memory_emit_cache: Default::default(),
       parsed_source_cache,
       emit_options,
+      minify,
       emit_options_hash,
     }
   }
 
+  /// If the emitted code is minified, in which case JavaScript modules
+  /// should also go through the emitter.
+  pub fn is_minifying(&self) -> bool {
+    self.minify
+  }
+
   pub fn cache_module_emits(
     &self,
     graph: &ModuleGraph,
   ) -> Result<(), AnyError> {
     for module in graph.modules() {
       if let Module::Esm(module) = module {
-        let is_emittable = matches!(
-          module.media_type,
-          MediaType::TypeScript
-            | MediaType::Mts
-            | MediaType::Cts
-            | MediaType::Jsx
-            | MediaType::Tsx
-        );
-        if is_emittable {
+        if is_emittable(module.media_type) {
           self.emit_parsed_source(
             &module.specifier,
             module.media_type,
@@ -63,6 +84,103 @@ impl Emitter {
     Ok(())
   }
 
+  /// Reads the emits of the modules of the graph that aren't cached locally
+  /// from the remote cache, then emits the ones it doesn't have and uploads
+  /// them.
+  ///
+  /// The modules whose integrity is checked with the lockfile are always
+  /// emitted locally, because a remote emit could bypass that check.
+  pub async fn sync_remote_emits(
+    &self,
+    graph: &ModuleGraph,
+    remote_cache: &RemoteBuildCache,
+    maybe_lockfile: Option<&Mutex<Lockfile>>,
+  ) -> Result<(), AnyError> {
+    let mut missing = Vec::new();
+    {
+      let maybe_lockfile = maybe_lockfile.map(|lockfile| lockfile.lock());
+      for module in graph.modules() {
+        if let Module::Esm(module) = module {
+          if !is_emittable(module.media_type) {
+            continue;
+          }
+          let source_hash = self.get_source_hash(&module.source);
+          if self
+            .emit_cache
+            .get_emit_code(&module.specifier, source_hash)
+            .is_none()
+          {
+            let is_locked = maybe_lockfile
+              .as_ref()
+              .map(|lockfile| {
+                lockfile
+                  .content
+                  .remote
+                  .contains_key(module.specifier.as_str())
+              })
+              .unwrap_or(false);
+            let remote_key = RemoteArtifactHasher::new()
+              .write_str(&remote_cache.key_specifier(&module.specifier))
+              .write_str(&module.source)
+              .write_u64(self.emit_options_hash)
+              .write_str(version::deno())
+              .finish();
+            missing.push((module, source_hash, remote_key, is_locked));
+          }
+        }
+      }
+    }
+    if missing.is_empty() {
+      return Ok(());
+    }
+
+    let readable_keys = missing
+      .iter()
+      .filter(|(_, _, _, is_locked)| !is_locked)
+      .map(|(_, _, remote_key, _)| remote_key.clone())
+      .collect::<Vec<_>>();
+    let mut remote_emits = remote_cache
+      .get_many(RemoteArtifactKind::Emit, &readable_keys)
+      .await
+      .into_iter();
+    let mut uploads = Vec::new();
+    for (module, source_hash, remote_key, is_locked) in missing {
+      let maybe_bytes = if is_locked {
+        None
+      } else {
+        remote_emits.next().flatten()
+      };
+      match maybe_bytes.and_then(|bytes| String::from_utf8(bytes).ok()) {
+        Some(emit_code) => {
+          let emit_code = map_inline_source_map(&emit_code, |source_map| {
+            remote_cache.to_local_text(source_map)
+          });
+          self.emit_cache.set_emit_code(
+            &module.specifier,
+            source_hash,
+            &emit_code,
+          );
+          self.set_memory_emit(&module.specifier, source_hash, &emit_code);
+        }
+        None => {
+          let emit_code = self.emit_parsed_source(
+            &module.specifier,
+            module.media_type,
+            &module.source,
+          )?;
+          let emit_code = map_inline_source_map(&emit_code, |source_map| {
+            remote_cache.to_portable_text(source_map)
+          });
+          uploads.push((remote_key, emit_code.into_bytes()));
+        }
+      }
+    }
+    remote_cache
+      .put_many(RemoteArtifactKind::Emit, uploads)
+      .await;
+    Ok(())
+  }
+
   /// Gets a cached emit if the source matches the hash found in the cache.
   pub fn maybed_cached_emit(
     &self,
@@ -81,11 +199,25 @@ impl Emitter {
   ) -> Result<ModuleCode, AnyError> {
     let source_hash = self.get_source_hash(source);
 
+    if let Some((cached_hash, emit_code)) =
+      self.memory_emit_cache.lock().get(specifier)
+    {
+      if *cached_hash == source_hash {
+        return Ok(emit_code.to_string().into());
+      }
+    }
+
     if let Some(emit_code) =
       self.emit_cache.get_emit_code(specifier, source_hash)
     {
+      telemetry::record_cache("emit", true);
+      self.set_memory_emit(specifier, source_hash, &emit_code);
       Ok(emit_code.into())
     } else {
+      telemetry::record_cache("emit", false);
+      let _emit_phase = progress_bar::report_phase("emit", specifier.as_str());
+      let mut span = telemetry::span("emit");
+      span.set_attribute("module", specifier);
       // this will use a cached version if it exists
       let parsed_source = self.parsed_source_cache.get_or_parse_module(
         specifier,
@@ -94,15 +226,36 @@ impl Emitter {
       )?;
       let transpiled_source = parsed_source.transpile(&self.emit_options)?;
       debug_assert!(transpiled_source.source_map.is_none());
-      self.emit_cache.set_emit_code(
-        specifier,
-        source_hash,
-        &transpiled_source.text,
-      );
-      Ok(transpiled_source.text.into())
+      let emit_code = if self.minify {
+        minify_js(specifier, transpiled_source.text)?
+      } else {
+        transpiled_source.text
+      };
+      self
+        .emit_cache
+        .set_emit_code(specifier, source_hash, &emit_code);
+      self.set_memory_emit(specifier, source_hash, &emit_code);
+      Ok(emit_code.into())
     }
   }
 
+  /// Removes the in-memory emit of a module.
+  pub fn free(&self, specifier: &ModuleSpecifier) {
+    self.memory_emit_cache.lock().remove(specifier);
+  }
+
+  fn set_memory_emit(
+    &self,
+    specifier: &ModuleSpecifier,
+    source_hash: u64,
+    emit_code: &str,
+  ) {
+    self
+      .memory_emit_cache
+      .lock()
+      .insert(specifier.clone(), (source_hash, emit_code.into()));
+  }
+
   /// A hashing function that takes the source code and uses the global emit
   /// options then generates a string hash which can be stored to
   /// determine if the cached emit is valid or not.
@@ -113,3 +266,72 @@ impl Emitter {
       .finish()
   }
 }
+
+/// Changes the inline source map at the end of an emit, which is kept as it
+/// is when it can't be decoded.
+fn map_inline_source_map(code: &str, f: impl FnOnce(&str) -> String) -> String {
+  const PREFIX: &str = "//# sourceMappingURL=data:application/json;base64,";
+  let start = match code.rfind(PREFIX) {
+    Some(index) => index + PREFIX.len(),
+    None => return code.to_string(),
+  };
+  let encoded = code[start..].trim_end();
+  let source_map = match base64::decode(encoded)
+    .ok()
+    .and_then(|bytes| String::from_utf8(bytes).ok())
+  {
+    Some(source_map) => source_map,
+    None => return code.to_string(),
+  };
+  format!(
+    "{}{}{}",
+    &code[..start],
+    base64::encode(f(&source_map)),
+    &code[start + encoded.len()..]
+  )
+}
+
+fn is_emittable(media_type: MediaType) -> bool {
+  matches!(
+    media_type,
+    MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Jsx
+      | MediaType::Tsx
+  )
+}
+
+/// Re-prints the provided JavaScript without comments or unnecessary
+/// whitespace. Note that this drops any inline source map.
+pub fn minify_js(
+  specifier: &ModuleSpecifier,
+  code: String,
+) -> Result<String, AnyError> {
+  let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+    specifier: specifier.to_string(),
+    text_info: deno_ast::SourceTextInfo::from_string(code),
+    media_type: MediaType::JavaScript,
+    capture_tokens: false,
+    scope_analysis: false,
+    maybe_syntax: None,
+  })?;
+  let source_map = Rc::new(SourceMap::default());
+  source_map.new_source_file(
+    FileName::Url(specifier.clone()),
+    parsed_source.text_info().text_str().to_string(),
+  );
+  let mut buf = Vec::new();
+  {
+    let writer =
+      Box::new(JsWriter::new(source_map.clone(), "\n", &mut buf, None));
+    let mut emitter = codegen::Emitter {
+      cfg: codegen::Config::default().with_minify(true),
+      comments: None,
+      cm: source_map,
+      wr: writer,
+    };
+    emitter.emit_module(parsed_source.module())?;
+  }
+  Ok(String::from_utf8(buf)?)
+}
@@ -16,6 +16,9 @@ use deno_graph::ModuleGraphError;
 use deno_graph::ResolutionError;
 use import_map::ImportMapError;
 
+use crate::file_fetcher::RedirectError;
+use crate::module_loader::LoadPreparedModuleError;
+
 fn get_import_map_error_class(_: &ImportMapError) -> &'static str {
   "URIError"
 }
@@ -40,6 +43,15 @@ fn get_module_graph_error_class(err: &ModuleGraphError) -> &'static str {
   }
 }
 
+fn get_load_prepared_module_error_class(
+  err: &LoadPreparedModuleError,
+) -> &'static str {
+  match err {
+    LoadPreparedModuleError::NodeBuiltIn { .. } => "Error",
+    LoadPreparedModuleError::UnexpectedMediaType { .. } => "TypeError",
+  }
+}
+
 fn get_resolution_error_class(err: &ResolutionError) -> &'static str {
   match err {
     ResolutionError::ResolverError { error, .. } => {
@@ -64,6 +76,11 @@ pub fn get_error_class_name(e: &AnyError) -> &'static str {
       e.downcast_ref::<ResolutionError>()
         .map(get_resolution_error_class)
     })
+    .or_else(|| {
+      e.downcast_ref::<LoadPreparedModuleError>()
+        .map(get_load_prepared_module_error_class)
+    })
+    .or_else(|| e.downcast_ref::<RedirectError>().map(|_| "Http"))
     .unwrap_or_else(|| {
       if cfg!(debug) {
         log::warn!(
@@ -0,0 +1,69 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Runs, type checks and tests a module in-process with the public API of
+//! the `deno` crate.
+
+use std::path::Path;
+
+use deno::api::run_check;
+use deno::api::run_test;
+use deno::flags_from_vec;
+use deno::Flags;
+use deno::ProjectBuilder;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::tokio_util::run_local;
+use tokio::sync::mpsc::unbounded_channel;
+
+fn flags(args: &[&str]) -> Result<Flags, AnyError> {
+  let args = std::iter::once("deno")
+    .chain(args.iter().copied())
+    .map(String::from)
+    .collect();
+  Ok(flags_from_vec(args)?)
+}
+
+fn main() -> Result<(), AnyError> {
+  let module_path =
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/in_process.ts");
+  let module = module_path.to_str().unwrap();
+  let main_module = ModuleSpecifier::from_file_path(&module_path).unwrap();
+
+  run_local(async move {
+    // run the module like `deno run`
+    let (sender, mut receiver) = unbounded_channel();
+    let project = ProjectBuilder::from_flags(flags(&["run", module])?)?
+      .file_watcher_sender(sender)
+      .build()
+      .await?;
+    let exit_code = project
+      .run(main_module, PermissionsContainer::allow_all())
+      .await?;
+    println!("exited with code {exit_code}");
+    while let Ok(paths) = receiver.try_recv() {
+      println!("loaded {paths:?}");
+    }
+
+    // type check it like `deno check --unused`
+    let report = run_check(flags(&["check", "--unused", module])?).await?;
+    if !report.passed() {
+      eprintln!("{}", report.type_errors());
+    }
+    for unused in &report.unused_exports {
+      println!(
+        "unused export {} at {}:{}:{}",
+        unused.name, unused.specifier, unused.line, unused.column
+      );
+    }
+    report.into_result()?;
+
+    // and test it like `deno test`
+    let report = run_test(flags(&["test", module])?).await?;
+    println!("{} passed, {} failed", report.passed, report.failed);
+    for failure in &report.failures {
+      eprintln!("{} ({}): {}", failure.name, failure.origin, failure.message);
+    }
+    report.into_result()
+  })
+}
@@ -0,0 +1,14 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+Deno.test("add", () => {
+  if (add(1, 2) !== 3) {
+    throw new Error("1 + 2 should be 3");
+  }
+});
+
+if (import.meta.main) {
+  console.log(`1 + 2 = ${add(1, 2)}`);
+}
@@ -1,6 +1,8 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CacheSetting;
+use crate::args::FetchConfig;
+use crate::args::UrlRewriteRules;
 use crate::auth_tokens::AuthToken;
 use crate::auth_tokens::AuthTokens;
 use crate::cache::HttpCache;
@@ -10,12 +12,16 @@ use crate::http_util::resolve_redirect_from_response;
 use crate::http_util::CacheSemantics;
 use crate::http_util::HeadersMap;
 use crate::http_util::HttpClient;
+use crate::util::checksum;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::UpdateGuard;
+use crate::util::rate_limit::KeyedLimiter;
+use crate::util::telemetry;
 use crate::util::text_encoding;
 
 use data_url::DataUrl;
 use deno_ast::MediaType;
+use deno_core::anyhow::Context;
 use deno_core::error::custom_error;
 use deno_core::error::generic_error;
 use deno_core::error::uri_error;
@@ -25,12 +31,16 @@ use deno_core::futures::future::FutureExt;
 use deno_core::parking_lot::Mutex;
 use deno_core::url::Url;
 use deno_core::ModuleSpecifier;
+use deno_lockfile::Lockfile;
 use deno_runtime::deno_fetch::reqwest::header::HeaderValue;
 use deno_runtime::deno_fetch::reqwest::header::ACCEPT;
 use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
+use deno_runtime::deno_fetch::reqwest::header::IF_MODIFIED_SINCE;
 use deno_runtime::deno_fetch::reqwest::header::IF_NONE_MATCH;
 use deno_runtime::deno_fetch::reqwest::StatusCode;
 use deno_runtime::deno_web::BlobStore;
+use deno_runtime::permissions::NetAuditEntry;
+use deno_runtime::permissions::NetAuditLog;
 use deno_runtime::permissions::PermissionsContainer;
 use log::debug;
 use std::borrow::Borrow;
@@ -38,15 +48,92 @@ use std::collections::HashMap;
 use std::env;
 use std::fs;
 use std::future::Future;
-use std::io::Read;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::SystemTime;
+use thiserror::Error;
 
 pub const SUPPORTED_SCHEMES: [&str; 5] =
   ["data", "blob", "file", "http", "https"];
 
+/// The response header with which a server advertises an eszip pre-bundle of
+/// the module graph of a remote module, as a URL relative to the module.
+pub const ESZIP_PREBUNDLE_HEADER: &str = "x-deno-eszip";
+
+/// The number of redirects followed for a remote module when the `"fetch"`
+/// configuration doesn't set `"maxRedirects"`.
+pub const DEFAULT_MAX_REDIRECTS: u32 = 10;
+
+/// An error following the redirects of a remote module, which includes the
+/// specifiers that were followed.
+#[derive(Debug, Error)]
+pub enum RedirectError {
+  #[error("Redirect loop detected:{}", format_redirect_chain(.0))]
+  Loop(Vec<ModuleSpecifier>),
+  #[error(
+    "Too many redirects (the limit is {limit}):{}",
+    format_redirect_chain(.chain)
+  )]
+  TooMany {
+    limit: i64,
+    chain: Vec<ModuleSpecifier>,
+  },
+}
+
+fn format_redirect_chain(chain: &[ModuleSpecifier]) -> String {
+  chain
+    .iter()
+    .enumerate()
+    .map(|(i, specifier)| {
+      if i == 0 {
+        format!("\n    {specifier}")
+      } else {
+        format!("\n -> {specifier}")
+      }
+    })
+    .collect()
+}
+
+/// The specifiers visited while following the redirects of a remote module.
+#[derive(Debug, Clone)]
+struct RedirectChain {
+  limit: i64,
+  specifiers: Vec<ModuleSpecifier>,
+}
+
+impl RedirectChain {
+  fn new(specifier: &ModuleSpecifier, limit: i64) -> Self {
+    Self {
+      limit,
+      specifiers: vec![specifier.clone()],
+    }
+  }
+
+  /// Gets the chain after following a redirect to the specifier, erroring
+  /// when it loops or exceeds the limit.
+  fn follow(&self, specifier: &ModuleSpecifier) -> Result<Self, AnyError> {
+    let mut specifiers = self.specifiers.clone();
+    specifiers.push(specifier.clone());
+    if self.specifiers.contains(specifier) {
+      return Err(RedirectError::Loop(specifiers).into());
+    }
+    if self.specifiers.len() as i64 > self.limit {
+      return Err(
+        RedirectError::TooMany {
+          limit: self.limit,
+          chain: specifiers,
+        }
+        .into(),
+      );
+    }
+    Ok(Self {
+      limit: self.limit,
+      specifiers,
+    })
+  }
+}
+
 /// A structure representing a source file.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct File {
@@ -83,6 +170,11 @@ impl FileCache {
     let mut cache = self.0.lock();
     cache.insert(specifier, file)
   }
+
+  pub fn remove(&self, specifier: &ModuleSpecifier) -> Option<File> {
+    let mut cache = self.0.lock();
+    cache.remove(specifier)
+  }
 }
 
 /// Fetch a source file from the local file system.
@@ -182,6 +274,12 @@ pub struct FileFetcher {
   blob_store: BlobStore,
   download_log_level: log::Level,
   progress_bar: Option<ProgressBar>,
+  url_rewrite_rules: Arc<UrlRewriteRules>,
+  origin_limiter: Arc<KeyedLimiter>,
+  max_redirects: i64,
+  revalidate_origins: Option<Arc<Vec<String>>>,
+  vendor_only: bool,
+  net_audit_log: Option<NetAuditLog>,
 }
 
 impl FileFetcher {
@@ -203,6 +301,12 @@ impl FileFetcher {
       blob_store,
       download_log_level: log::Level::Info,
       progress_bar,
+      url_rewrite_rules: Default::default(),
+      origin_limiter: Default::default(),
+      max_redirects: DEFAULT_MAX_REDIRECTS as i64,
+      revalidate_origins: None,
+      vendor_only: false,
+      net_audit_log: None,
     }
   }
 
@@ -211,6 +315,51 @@ impl FileFetcher {
     self.download_log_level = level;
   }
 
+  /// Errors for remote modules, which must come from the vendor directory
+  /// instead.
+  pub fn set_vendor_only(&mut self) {
+    self.vendor_only = true;
+  }
+
+  /// Sets the rules used to rewrite the URL that remote modules are
+  /// downloaded from. The original specifier is still used for caching.
+  pub fn set_url_rewrite_rules(&mut self, rules: UrlRewriteRules) {
+    self.url_rewrite_rules = Arc::new(rules);
+  }
+
+  /// Limits the number of concurrent requests and the request rate per
+  /// origin when downloading remote modules, the number of redirects
+  /// followed for them and the origins revalidated with `--reload=stale`.
+  pub fn set_fetch_config(&mut self, config: &FetchConfig) {
+    self.origin_limiter = Arc::new(KeyedLimiter::new(
+      config.max_concurrent_requests_per_origin,
+      config.max_requests_per_second_per_origin,
+    ));
+    self.max_redirects =
+      config.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS) as i64;
+    self.revalidate_origins = config.revalidate_origins.clone().map(Arc::new);
+  }
+
+  /// Records the downloads of remote modules and the ones denied by the
+  /// permissions to the network audit log.
+  pub fn set_net_audit_log(&mut self, net_audit_log: NetAuditLog) {
+    self.net_audit_log = Some(net_audit_log);
+  }
+
+  fn record_import(&self, url: &Url, allowed: bool) {
+    if let Some(net_audit_log) = &self.net_audit_log {
+      if matches!(url.scheme(), "http" | "https") {
+        net_audit_log.record(&NetAuditEntry {
+          api: "import",
+          host: url.host_str().unwrap_or_default(),
+          port: url.port_or_known_default(),
+          url: Some(url.as_str()),
+          allowed,
+        });
+      }
+    }
+  }
+
   /// Creates a `File` structure for a remote file.
   fn build_remote_file(
     &self,
@@ -254,13 +403,21 @@ impl FileFetcher {
     &self,
     specifier: &ModuleSpecifier,
     redirect_limit: i64,
+  ) -> Result<Option<File>, AnyError> {
+    self.fetch_cached_with_chain(
+      specifier,
+      RedirectChain::new(specifier, redirect_limit),
+    )
+  }
+
+  fn fetch_cached_with_chain(
+    &self,
+    specifier: &ModuleSpecifier,
+    redirect_chain: RedirectChain,
   ) -> Result<Option<File>, AnyError> {
     debug!("FileFetcher::fetch_cached - specifier: {}", specifier);
-    if redirect_limit < 0 {
-      return Err(custom_error("Http", "Too many redirects."));
-    }
 
-    let (mut source_file, headers, _) = match self.http_cache.get(specifier) {
+    let (bytes, headers, _) = match self.http_cache.get(specifier) {
       Err(err) => {
         if let Some(err) = err.downcast_ref::<std::io::Error>() {
           if err.kind() == std::io::ErrorKind::NotFound {
@@ -274,10 +431,9 @@ impl FileFetcher {
     if let Some(redirect_to) = headers.get("location") {
       let redirect =
         deno_core::resolve_import(redirect_to, specifier.as_str())?;
-      return self.fetch_cached(&redirect, redirect_limit - 1);
+      let redirect_chain = redirect_chain.follow(&redirect)?;
+      return self.fetch_cached_with_chain(&redirect, redirect_chain);
     }
-    let mut bytes = Vec::new();
-    source_file.read_to_end(&mut bytes)?;
     let file = self.build_remote_file(specifier, bytes, &headers)?;
 
     Ok(Some(file))
@@ -296,11 +452,11 @@ impl FileFetcher {
       Err(err) => return Err(err),
     }
 
-    if self.cache_setting == CacheSetting::Only {
+    if let Some(flag) = self.cache_setting.cache_only_flag() {
       return Err(custom_error(
         "NotCached",
         format!(
-          "Specifier not found in cache: \"{specifier}\", --cached-only is specified."
+          "Specifier not found in cache: \"{specifier}\", {flag} is specified."
         ),
       ));
     }
@@ -343,11 +499,11 @@ impl FileFetcher {
       Err(err) => return Err(err),
     }
 
-    if self.cache_setting == CacheSetting::Only {
+    if let Some(flag) = self.cache_setting.cache_only_flag() {
       return Err(custom_error(
         "NotCached",
         format!(
-          "Specifier not found in cache: \"{specifier}\", --cached-only is specified."
+          "Specifier not found in cache: \"{specifier}\", {flag} is specified."
         ),
       ));
     }
@@ -406,19 +562,31 @@ impl FileFetcher {
     redirect_limit: i64,
     maybe_accept: Option<String>,
   ) -> Pin<Box<dyn Future<Output = Result<File, AnyError>> + Send>> {
-    debug!("FileFetcher::fetch_remote() - specifier: {}", specifier);
-    if redirect_limit < 0 {
-      return futures::future::err(custom_error("Http", "Too many redirects."))
-        .boxed();
-    }
+    self.fetch_remote_with_chain(
+      specifier,
+      permissions,
+      RedirectChain::new(specifier, redirect_limit),
+      maybe_accept,
+    )
+  }
 
+  fn fetch_remote_with_chain(
+    &self,
+    specifier: &ModuleSpecifier,
+    permissions: PermissionsContainer,
+    redirect_chain: RedirectChain,
+    maybe_accept: Option<String>,
+  ) -> Pin<Box<dyn Future<Output = Result<File, AnyError>> + Send>> {
+    debug!("FileFetcher::fetch_remote() - specifier: {}", specifier);
     if let Err(err) = permissions.check_specifier(specifier) {
+      self.record_import(specifier, false);
       return futures::future::err(err).boxed();
     }
 
     if self.should_use_cache(specifier) {
-      match self.fetch_cached(specifier, redirect_limit) {
+      match self.fetch_cached_with_chain(specifier, redirect_chain.clone()) {
         Ok(Some(file)) => {
+          telemetry::record_cache("http", true);
           return futures::future::ok(file).boxed();
         }
         Ok(None) => {}
@@ -427,17 +595,28 @@ impl FileFetcher {
         }
       }
     }
+    telemetry::record_cache("http", false);
 
-    if self.cache_setting == CacheSetting::Only {
+    if let Some(flag) = self.cache_setting.cache_only_flag() {
       return futures::future::err(custom_error(
         "NotCached",
         format!(
-          "Specifier not found in cache: \"{specifier}\", --cached-only is specified."
+          "Specifier not found in cache: \"{specifier}\", {flag} is specified."
         ),
       ))
       .boxed();
     }
 
+    let download_url = match self.url_rewrite_rules.rewrite(specifier) {
+      Ok(Some(url)) => {
+        debug!("Rewrote download of {} to {}", specifier, url);
+        url
+      }
+      Ok(None) => specifier.clone(),
+      Err(err) => return futures::future::err(err).boxed(),
+    };
+    self.record_import(&download_url, true);
+
     let mut maybe_progress_guard = None;
     if let Some(pb) = self.progress_bar.as_ref() {
       maybe_progress_guard = Some(pb.update(specifier.as_str()));
@@ -450,39 +629,62 @@ impl FileFetcher {
       );
     }
 
-    let maybe_etag = match self.http_cache.get(specifier) {
-      Ok((_, headers, _)) => headers.get("etag").cloned(),
-      _ => None,
+    // the validators of the cached response, so that it's only downloaded
+    // again when it changed
+    let (maybe_etag, maybe_last_modified) = match self.http_cache.get(specifier)
+    {
+      Ok((_, headers, _)) => (
+        headers.get("etag").cloned(),
+        headers.get("last-modified").cloned(),
+      ),
+      _ => (None, None),
     };
-    let maybe_auth_token = self.auth_tokens.get(specifier);
+    let maybe_auth_token = self.auth_tokens.get(&download_url);
     let specifier = specifier.clone();
     let client = self.http_client.clone();
     let file_fetcher = self.clone();
     // A single pass of fetch either yields code or yields a redirect.
     async move {
-      let result = match fetch_once(
+      let origin = download_url.origin().ascii_serialization();
+      let limiter_permit = file_fetcher.origin_limiter.acquire(&origin).await;
+      let fetch_result = fetch_once(
         &client,
         FetchOnceArgs {
-          url: specifier.clone(),
+          url: download_url.clone(),
           maybe_accept: maybe_accept.clone(),
           maybe_etag,
+          maybe_last_modified,
           maybe_auth_token,
           maybe_progress_guard: maybe_progress_guard.as_ref(),
         },
       )
-      .await?
-      {
-        FetchOnceResult::NotModified => {
-          let file = file_fetcher.fetch_cached(&specifier, 10)?.unwrap();
+      .await;
+      // release the permit before following a redirect, which might be
+      // to the same origin
+      drop(limiter_permit);
+      let result = match fetch_result? {
+        FetchOnceResult::NotModified(headers) => {
+          file_fetcher.http_cache.refresh(&specifier, headers)?;
+          let file = file_fetcher
+            .fetch_cached(&specifier, file_fetcher.max_redirects)?
+            .unwrap();
           Ok(file)
         }
         FetchOnceResult::Redirect(redirect_url, headers) => {
-          file_fetcher.http_cache.set(&specifier, headers, &[])?;
+          // the redirect is relative to the mirror, so it's stored under the
+          // mirror url and the original specifier redirects to the mirror
+          file_fetcher.http_cache.set(&download_url, headers, &[])?;
+          if download_url != specifier {
+            let mut headers = HashMap::new();
+            headers.insert("location".to_string(), download_url.to_string());
+            file_fetcher.http_cache.set(&specifier, headers, &[])?;
+          }
+          let redirect_chain = redirect_chain.follow(&redirect_url)?;
           file_fetcher
-            .fetch_remote(
+            .fetch_remote_with_chain(
               &redirect_url,
               permissions,
-              redirect_limit - 1,
+              redirect_chain,
               maybe_accept,
             )
             .await
@@ -506,34 +708,44 @@ impl FileFetcher {
   fn should_use_cache(&self, specifier: &ModuleSpecifier) -> bool {
     match &self.cache_setting {
       CacheSetting::ReloadAll => false,
-      CacheSetting::Use | CacheSetting::Only => true,
+      CacheSetting::Use | CacheSetting::Only | CacheSetting::Frozen => true,
       CacheSetting::RespectHeaders => {
-        if let Ok((_, headers, cache_time)) = self.http_cache.get(specifier) {
-          let cache_semantics =
-            CacheSemantics::new(headers, cache_time, SystemTime::now());
+        if let Ok(metadata) = self.http_cache.get_metadata(specifier) {
+          let cache_semantics = CacheSemantics::new(
+            metadata.headers,
+            metadata.now,
+            SystemTime::now(),
+          );
           cache_semantics.should_use()
         } else {
           false
         }
       }
-      CacheSetting::ReloadSome(list) => {
-        let mut url = specifier.clone();
-        url.set_fragment(None);
-        if list.iter().any(|x| x == url.as_str()) {
+      CacheSetting::RevalidateStale(list) => {
+        if is_in_reload_list(list, specifier) {
           return false;
         }
-        url.set_query(None);
-        let mut path = PathBuf::from(url.as_str());
-        loop {
-          if list.contains(&path.to_str().unwrap().to_string()) {
-            return false;
+        if let Some(origins) = &self.revalidate_origins {
+          let origin = specifier.origin().ascii_serialization();
+          if !origins.contains(&origin) {
+            return true;
           }
-          if !path.pop() {
-            break;
+        }
+        match self.http_cache.get_metadata(specifier) {
+          // responses without caching information are used, so that they
+          // aren't downloaded again on every run
+          Ok(metadata) => {
+            let cache_semantics = CacheSemantics::new(
+              metadata.headers,
+              metadata.now,
+              SystemTime::now(),
+            );
+            cache_semantics.freshness().is_stale() != Some(true)
           }
+          Err(_) => false,
         }
-        true
       }
+      CacheSetting::ReloadSome(list) => !is_in_reload_list(list, specifier),
     }
   }
 
@@ -554,7 +766,10 @@ impl FileFetcher {
     maybe_accept: Option<&str>,
   ) -> Result<File, AnyError> {
     let scheme = get_validated_scheme(specifier)?;
-    permissions.check_specifier(specifier)?;
+    if let Err(err) = permissions.check_specifier(specifier) {
+      self.record_import(specifier, false);
+      return Err(err);
+    }
     if let Some(file) = self.cache.get(specifier) {
       Ok(file)
     } else if scheme == "file" {
@@ -573,6 +788,11 @@ impl FileFetcher {
         self.cache.insert(specifier.clone(), file.clone());
       }
       result
+    } else if self.vendor_only {
+      Err(custom_error(
+        "NoRemote",
+        format!("A remote specifier was requested: \"{specifier}\", but it is not vendored and \"vendor\" is set to \"only\" in the configuration file."),
+      ))
     } else if !self.allow_remote {
       Err(custom_error(
         "NoRemote",
@@ -583,7 +803,7 @@ impl FileFetcher {
         .fetch_remote(
           specifier,
           permissions,
-          10,
+          self.max_redirects,
           maybe_accept.map(String::from),
         )
         .await;
@@ -594,15 +814,145 @@ impl FileFetcher {
     }
   }
 
+  /// Fetches the eszip pre-bundle that the server advertised for a remote
+  /// entry point and adds its modules to the caches, so that the module graph
+  /// of the entry point is populated from a single request instead of one
+  /// per module. The modules that don't match their checksum in the lockfile
+  /// are skipped, so they're fetched like usual, as are the modules of
+  /// another origin than the pre-bundle and the ones that aren't permitted,
+  /// while the modules that are already cached are kept.
+  ///
+  /// Returns the number of modules that were added.
+  pub async fn fetch_eszip_prebundle(
+    &self,
+    specifier: &ModuleSpecifier,
+    permissions: PermissionsContainer,
+    maybe_lockfile: Option<&Mutex<Lockfile>>,
+  ) -> Result<usize, AnyError> {
+    if !matches!(specifier.scheme(), "http" | "https")
+      || !self.allow_remote
+      || self.cache_setting.cache_only_flag().is_some()
+    {
+      return Ok(0);
+    }
+    let file = self.fetch(specifier, permissions.clone()).await?;
+    let maybe_eszip_url = file
+      .maybe_headers
+      .as_ref()
+      .and_then(|headers| headers.get(ESZIP_PREBUNDLE_HEADER));
+    let eszip_url = match maybe_eszip_url {
+      Some(url) => file.specifier.join(url)?,
+      None => return Ok(0),
+    };
+    permissions.check_specifier(&eszip_url)?;
+
+    let maybe_bytes = match self.progress_bar.as_ref() {
+      Some(pb) => {
+        let guard = pb.update(eszip_url.as_str());
+        self
+          .http_client
+          .download_with_progress(eszip_url.clone(), &guard)
+          .await?
+      }
+      None => {
+        log::log!(
+          self.download_log_level,
+          "{} {}",
+          colors::green("Download"),
+          eszip_url
+        );
+        Some(self.http_client.download(eszip_url.clone()).await?)
+      }
+    };
+    let bytes = maybe_bytes.ok_or_else(|| {
+      custom_error("NotFound", format!("Pre-bundle not found: {eszip_url}"))
+    })?;
+    let reader = futures::io::BufReader::new(futures::io::Cursor::new(bytes));
+    let (eszip, loader) = eszip::EszipV2::parse(reader)
+      .await
+      .context("Failed to parse eszip header")?;
+    loader.await.context("Failed to parse eszip archive")?;
+
+    // the pre-bundle may only provide the modules of its own origin
+    let eszip_origin = eszip_url.origin();
+    let is_allowed_entry = |specifier: &ModuleSpecifier| {
+      matches!(specifier.scheme(), "http" | "https")
+        && specifier.origin() == eszip_origin
+        && permissions.check_specifier(specifier).is_ok()
+    };
+    let mut added_count = 0;
+    for eszip_specifier in eszip.specifiers() {
+      let module_specifier = match ModuleSpecifier::parse(&eszip_specifier) {
+        Ok(specifier) if is_allowed_entry(&specifier) => specifier,
+        _ => continue,
+      };
+      // the modules that are already cached are kept as they are
+      if self.cache.get(&module_specifier).is_some()
+        || self.http_cache.contains(&module_specifier)
+      {
+        continue;
+      }
+      let module = match eszip.get_module(&eszip_specifier) {
+        Some(module) => module,
+        None => continue,
+      };
+      if module.specifier != eszip_specifier {
+        // the specifier redirects to the one of the module
+        match ModuleSpecifier::parse(&module.specifier) {
+          Ok(specifier) if is_allowed_entry(&specifier) => {}
+          _ => continue,
+        }
+        let mut headers = HashMap::new();
+        headers.insert("location".to_string(), module.specifier.clone());
+        self.http_cache.set(&module_specifier, headers, &[])?;
+        continue;
+      }
+      let bytes = match module.source().await {
+        Some(source) => source.to_vec(),
+        None => continue,
+      };
+      if let Some(lockfile) = maybe_lockfile {
+        let lockfile = lockfile.lock();
+        let maybe_checksum =
+          lockfile.content.remote.get(module_specifier.as_str());
+        if let Some(lockfile_checksum) = maybe_checksum {
+          if *lockfile_checksum != checksum::gen(&[&bytes]) {
+            debug!(
+              "Skipping {} of the pre-bundle, which does not match the lockfile.",
+              module_specifier
+            );
+            continue;
+          }
+        }
+      }
+      let mut headers = HashMap::new();
+      if matches!(module.kind, eszip::ModuleKind::Json) {
+        headers
+          .insert("content-type".to_string(), "application/json".to_string());
+      }
+      self
+        .http_cache
+        .set(&module_specifier, headers.clone(), &bytes)?;
+      let file = self.build_remote_file(&module_specifier, bytes, &headers)?;
+      self.cache.insert(module_specifier, file);
+      added_count += 1;
+    }
+    Ok(added_count)
+  }
+
+  /// Gets the path of a module that is on disk or cached, which is the key of
+  /// its entry when the cache keeps its files in the store.
   pub fn get_local_path(&self, specifier: &ModuleSpecifier) -> Option<PathBuf> {
     // TODO(@kitsonk) fix when deno_graph does not query cache for synthetic
     // modules
     if specifier.scheme() == "flags" {
       None
     } else if specifier.scheme() == "file" {
-      specifier.to_file_path().ok()
-    } else {
+      specifier.to_file_path().ok().filter(|path| path.is_file())
+    } else if self.http_cache.contains(specifier) {
       self.http_cache.get_cache_filename(specifier)
+    } else {
+      None
     }
   }
 
@@ -633,12 +983,18 @@ impl FileFetcher {
   pub fn insert_cached(&self, file: File) -> Option<File> {
     self.cache.insert(file.specifier.clone(), file)
   }
+
+  /// Removes a file from the in-process cache so that it's read again the
+  /// next time it's fetched.
+  pub fn remove_cached(&self, specifier: &ModuleSpecifier) -> Option<File> {
+    self.cache.remove(specifier)
+  }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 enum FetchOnceResult {
   Code(Vec<u8>, HeadersMap),
-  NotModified,
+  NotModified(HeadersMap),
   Redirect(Url, HeadersMap),
 }
 
@@ -647,6 +1003,7 @@ struct FetchOnceArgs<'a> {
   pub url: Url,
   pub maybe_accept: Option<String>,
   pub maybe_etag: Option<String>,
+  pub maybe_last_modified: Option<String>,
   pub maybe_auth_token: Option<AuthToken>,
   pub maybe_progress_guard: Option<&'a UpdateGuard>,
 }
@@ -666,6 +1023,10 @@ async fn fetch_once<'a>(
     let if_none_match_val = HeaderValue::from_str(&etag)?;
     request = request.header(IF_NONE_MATCH, if_none_match_val);
   }
+  if let Some(last_modified) = args.maybe_last_modified {
+    let if_modified_since_val = HeaderValue::from_str(&last_modified)?;
+    request = request.header(IF_MODIFIED_SINCE, if_modified_since_val);
+  }
   if let Some(auth_token) = args.maybe_auth_token {
     let authorization_val = HeaderValue::from_str(&auth_token.to_string())?;
     request = request.header(AUTHORIZATION, authorization_val);
@@ -676,10 +1037,6 @@ async fn fetch_once<'a>(
   }
   let response = request.send().await?;
 
-  if response.status() == StatusCode::NOT_MODIFIED {
-    return Ok(FetchOnceResult::NotModified);
-  }
-
   let mut result_headers = HashMap::new();
   let response_headers = response.headers();
 
@@ -702,6 +1059,10 @@ async fn fetch_once<'a>(
     result_headers.insert(key_str, values_str);
   }
 
+  if response.status() == StatusCode::NOT_MODIFIED {
+    return Ok(FetchOnceResult::NotModified(result_headers));
+  }
+
   if response.status().is_redirection() {
     let new_url = resolve_redirect_from_response(&args.url, &response)?;
     return Ok(FetchOnceResult::Redirect(new_url, result_headers));
@@ -714,6 +1075,18 @@ async fn fetch_once<'a>(
         "NotFound",
         format!("Import '{}' failed, not found.", args.url),
       )
+    } else if matches!(
+      response.status(),
+      StatusCode::BAD_GATEWAY
+        | StatusCode::SERVICE_UNAVAILABLE
+        | StatusCode::GATEWAY_TIMEOUT
+    ) {
+      // these are usually temporary, so they're http errors that can be
+      // retried instead of generic ones
+      custom_error(
+        "Http",
+        format!("Import '{}' failed: {}", args.url, response.status()),
+      )
     } else {
       generic_error(format!(
         "Import '{}' failed: {}",
@@ -733,6 +1106,27 @@ async fn fetch_once<'a>(
   Ok(FetchOnceResult::Code(body, result_headers))
 }
 
+/// Gets if the specifier, or a parent path of it, is in the list of
+/// specifiers to reload of `--reload=<urls>`.
+fn is_in_reload_list(list: &[String], specifier: &ModuleSpecifier) -> bool {
+  let mut url = specifier.clone();
+  url.set_fragment(None);
+  if list.iter().any(|x| x == url.as_str()) {
+    return true;
+  }
+  url.set_query(None);
+  let mut path = PathBuf::from(url.as_str());
+  loop {
+    if list.contains(&path.to_str().unwrap().to_string()) {
+      return true;
+    }
+    if !path.pop() {
+      break;
+    }
+  }
+  false
+}
+
 #[cfg(test)]
 mod tests {
   use crate::cache::CachedUrlMetadata;
@@ -1469,6 +1863,39 @@ mod tests {
     assert!(result.is_err());
   }
 
+  #[test]
+  fn test_fetch_cached_redirect_loop() {
+    let (file_fetcher, _) = setup(CacheSetting::Use, None);
+    let specifier_a = resolve_url("https://example.com/a.ts").unwrap();
+    let specifier_b = resolve_url("https://example.com/b.ts").unwrap();
+    for (specifier, location) in
+      [(&specifier_a, "./b.ts"), (&specifier_b, "./a.ts")]
+    {
+      let mut headers = HashMap::new();
+      headers.insert("location".to_string(), location.to_string());
+      file_fetcher
+        .http_cache
+        .set(specifier, headers, &[])
+        .unwrap();
+    }
+
+    let err = file_fetcher.fetch_cached(&specifier_a, 10).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Redirect loop detected:
+    https://example.com/a.ts
+ -> https://example.com/b.ts
+ -> https://example.com/a.ts"
+    );
+    let err = file_fetcher.fetch_cached(&specifier_a, 0).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Too many redirects (the limit is 0):
+    https://example.com/a.ts
+ -> https://example.com/b.ts"
+    );
+  }
+
   #[tokio::test]
   async fn test_fetch_same_host_redirect() {
     let _http_server_guard = test_util::http_server();
@@ -1660,6 +2087,55 @@ mod tests {
     assert_eq!(first, second);
   }
 
+  #[tokio::test]
+  async fn test_revalidate_stale() {
+    let _g = test_util::http_server();
+    let temp_dir = TempDir::new();
+    let fetch_source = |specifier: &'static str,
+                        reload_list: Vec<String>,
+                        fetch_config: FetchConfig| {
+      let temp_dir = temp_dir.clone();
+      async move {
+        let (mut file_fetcher, _) =
+          setup(CacheSetting::RevalidateStale(reload_list), Some(temp_dir));
+        file_fetcher.set_fetch_config(&fetch_config);
+        let specifier = ModuleSpecifier::parse(specifier).unwrap();
+        file_fetcher
+          .fetch(&specifier, PermissionsContainer::allow_all())
+          .await
+          .unwrap()
+          .source
+      }
+    };
+
+    // "no-cache" is always stale
+    let specifier = "http://localhost:4545/dynamic";
+    let first = fetch_source(specifier, vec![], Default::default()).await;
+    let second = fetch_source(specifier, vec![], Default::default()).await;
+    assert_ne!(first, second);
+    // unless the origin isn't revalidated
+    let third = fetch_source(
+      specifier,
+      vec![],
+      FetchConfig {
+        revalidate_origins: Some(vec!["https://deno.land".to_string()]),
+        ..Default::default()
+      },
+    )
+    .await;
+    assert_eq!(second, third);
+
+    let specifier = "http://localhost:4545/dynamic_cache";
+    let first = fetch_source(specifier, vec![], Default::default()).await;
+    let second = fetch_source(specifier, vec![], Default::default()).await;
+    assert_eq!(first, second);
+    // unless it's reloaded with `--reload=stale,<url>`
+    let third =
+      fetch_source(specifier, vec![specifier.to_string()], Default::default())
+        .await;
+    assert_ne!(second, third);
+  }
+
   #[tokio::test]
   async fn test_fetch_local_utf_16be() {
     let expected = String::from_utf8(
@@ -1763,6 +2239,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -1791,6 +2268,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -1820,6 +2298,7 @@ mod tests {
         url: url.clone(),
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -1843,12 +2322,13 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: Some("33a64df551425fcc55e".to_string()),
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
     )
     .await;
-    assert_eq!(res.unwrap(), FetchOnceResult::NotModified);
+    assert!(matches!(res.unwrap(), FetchOnceResult::NotModified(_)));
   }
 
   #[tokio::test]
@@ -1864,6 +2344,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -1895,6 +2376,7 @@ mod tests {
         url,
         maybe_accept: Some("application/json".to_string()),
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -1922,6 +2404,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -1963,6 +2446,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -2001,6 +2485,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -2037,6 +2522,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -2079,6 +2565,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -2124,6 +2611,7 @@ mod tests {
         url: url.clone(),
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -2148,12 +2636,13 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: Some("33a64df551425fcc55e".to_string()),
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
     )
     .await;
-    assert_eq!(res.unwrap(), FetchOnceResult::NotModified);
+    assert!(matches!(res.unwrap(), FetchOnceResult::NotModified(_)));
   }
 
   #[tokio::test]
@@ -2186,6 +2675,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
@@ -2205,6 +2695,26 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn test_fetch_eszip_prebundle_not_advertised() {
+    let _http_server_guard = test_util::http_server();
+    let (file_fetcher, _) = setup(CacheSetting::Use, None);
+    let specifier =
+      resolve_url("http://localhost:4545/subdir/mod2.ts").unwrap();
+
+    let added_count = file_fetcher
+      .fetch_eszip_prebundle(
+        &specifier,
+        PermissionsContainer::allow_all(),
+        None,
+      )
+      .await
+      .unwrap();
+    assert_eq!(added_count, 0);
+    // the entry point is still fetched and cached
+    assert!(file_fetcher.get_source(&specifier).is_some());
+  }
+
   #[tokio::test]
   async fn bad_redirect() {
     let _g = test_util::http_server();
@@ -2217,6 +2727,7 @@ mod tests {
         url,
         maybe_accept: None,
         maybe_etag: None,
+        maybe_last_modified: None,
         maybe_auth_token: None,
         maybe_progress_guard: None,
       },
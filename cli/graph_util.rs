@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CliOptions;
+use crate::args::CyclePolicy;
 use crate::args::Lockfile;
 use crate::args::TsTypeLib;
 use crate::args::TypeCheckMode;
@@ -27,12 +28,18 @@ use deno_graph::Module;
 use deno_graph::ModuleError;
 use deno_graph::ModuleGraph;
 use deno_graph::ModuleGraphError;
+use deno_graph::Resolution;
 use deno_graph::ResolutionError;
 use deno_graph::SpecifierError;
 use deno_runtime::permissions::PermissionsContainer;
 use import_map::ImportMapError;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 #[derive(Clone, Copy)]
@@ -40,6 +47,8 @@ pub struct GraphValidOptions {
   pub check_js: bool,
   pub follow_type_only: bool,
   pub is_vendoring: bool,
+  /// Report every error found in the graph instead of only the first one.
+  pub all_errors: bool,
 }
 
 /// Check if `roots` and their deps are available. Returns `Ok(())` if
@@ -57,13 +66,317 @@ pub fn graph_valid_with_cli_options(
       is_vendoring: false,
       follow_type_only: options.type_check_mode() != TypeCheckMode::None,
       check_js: options.check_js(),
+      all_errors: options.all_graph_errors(),
     },
-  )
+  )?;
+  if cfg!(any(windows, target_os = "macos")) {
+    for mismatch in find_specifier_casing_mismatches(graph) {
+      log::warn!(
+        "{} Import casing does not match the file on disk. This will fail on case-sensitive file systems.\n    import: {}\n    actual: {}\n    at {}:{}:{}",
+        colors::yellow("Warning"),
+        mismatch.specifier,
+        mismatch.actual_path.display(),
+        colors::cyan(mismatch.range.specifier.as_str()),
+        colors::yellow(&(mismatch.range.start.line + 1).to_string()),
+        colors::yellow(&(mismatch.range.start.character + 1).to_string()),
+      );
+    }
+  }
+  Ok(())
+}
+
+/// Warns about or fails for the import cycles of the graph based on the
+/// `"cycles"` configuration. This is done once for a top level graph rather
+/// than whenever the graph is validated, so the cycles whose modules are all
+/// in `checked`, the modules that were in the graph before, are skipped.
+pub fn check_import_cycles(
+  graph: &ModuleGraph,
+  options: &CliOptions,
+  checked: &HashSet<ModuleSpecifier>,
+) -> Result<(), AnyError> {
+  let cycles_config = options.resolve_cycles_config()?;
+  if cycles_config.is_empty() {
+    return Ok(());
+  }
+  let mut errors = Vec::new();
+  for cycle in find_import_cycles(graph) {
+    if cycle.iter().all(|specifier| checked.contains(specifier)) {
+      continue;
+    }
+    match cycles_config.cycle_policy(&cycle) {
+      CyclePolicy::Ignore => {}
+      CyclePolicy::Warn => log::warn!(
+        "{} Import cycle: {}",
+        colors::yellow("Warning"),
+        import_cycle_to_string(&cycle)
+      ),
+      CyclePolicy::Error => errors.push(import_cycle_to_string(&cycle)),
+    }
+  }
+  if !errors.is_empty() {
+    bail!(
+      "Import cycles are not allowed by the \"cycles\" configuration:\n  {}",
+      errors.join("\n  ")
+    );
+  }
+  Ok(())
+}
+
+/// Finds the import cycles between the local modules of the graph. One cycle
+/// is returned for each group of modules that import each other, starting
+/// with the module of the group that sorts first. Dynamic imports are not
+/// followed, since they don't make the modules depend on each other while
+/// they're evaluated.
+pub fn find_import_cycles(graph: &ModuleGraph) -> Vec<Vec<ModuleSpecifier>> {
+  let mut specifiers = graph
+    .modules()
+    .filter_map(|module| match module {
+      Module::Esm(module) if module.specifier.scheme() == "file" => {
+        Some(module.specifier.clone())
+      }
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  specifiers.sort();
+  let indexes = specifiers
+    .iter()
+    .enumerate()
+    .map(|(index, specifier)| (specifier.clone(), index))
+    .collect::<HashMap<_, _>>();
+  let edges = specifiers
+    .iter()
+    .map(|specifier| {
+      let module = match graph.get(specifier) {
+        Some(Module::Esm(module)) => module,
+        _ => return Vec::new(),
+      };
+      let mut edges = module
+        .dependencies
+        .values()
+        .filter(|dep| !dep.is_dynamic)
+        .flat_map(|dep| [&dep.maybe_code, &dep.maybe_type])
+        .filter_map(|resolution| resolution.maybe_specifier())
+        .filter_map(|specifier| indexes.get(&graph.resolve(specifier)))
+        .copied()
+        .collect::<Vec<_>>();
+      edges.sort_unstable();
+      edges.dedup();
+      edges
+    })
+    .collect::<Vec<_>>();
+
+  let mut cycles = strongly_connected_components(&edges)
+    .into_iter()
+    .filter_map(|mut component| {
+      component.sort_unstable();
+      let start = component[0];
+      if component.len() == 1 && !edges[start].contains(&start) {
+        return None;
+      }
+      let cycle = shortest_cycle(&edges, &component, start);
+      Some(cycle.into_iter().map(|i| specifiers[i].clone()).collect())
+    })
+    .collect::<Vec<Vec<_>>>();
+  cycles.sort();
+  cycles
+}
+
+/// Formats an import cycle like `a.ts -> b.ts -> a.ts`.
+pub fn import_cycle_to_string(cycle: &[ModuleSpecifier]) -> String {
+  let mut text = cycle
+    .iter()
+    .map(|specifier| specifier.as_str())
+    .collect::<Vec<_>>()
+    .join(" -> ");
+  if let Some(first) = cycle.first() {
+    text.push_str(" -> ");
+    text.push_str(first.as_str());
+  }
+  text
+}
+
+/// Tarjan's algorithm, without recursion so that long import chains don't
+/// overflow the stack.
+fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+  let mut indexes = vec![usize::MAX; edges.len()];
+  let mut lowlinks = vec![0; edges.len()];
+  let mut on_stack = vec![false; edges.len()];
+  let mut stack = Vec::new();
+  let mut next_index = 0;
+  let mut components = Vec::new();
+  for start in 0..edges.len() {
+    if indexes[start] != usize::MAX {
+      continue;
+    }
+    indexes[start] = next_index;
+    lowlinks[start] = next_index;
+    next_index += 1;
+    stack.push(start);
+    on_stack[start] = true;
+    let mut call_stack = vec![(start, 0)];
+    while let Some(&(node, edge_index)) = call_stack.last() {
+      if let Some(&next) = edges[node].get(edge_index) {
+        call_stack.last_mut().unwrap().1 += 1;
+        if indexes[next] == usize::MAX {
+          indexes[next] = next_index;
+          lowlinks[next] = next_index;
+          next_index += 1;
+          stack.push(next);
+          on_stack[next] = true;
+          call_stack.push((next, 0));
+        } else if on_stack[next] {
+          lowlinks[node] = lowlinks[node].min(indexes[next]);
+        }
+        continue;
+      }
+      call_stack.pop();
+      if let Some(&(parent, _)) = call_stack.last() {
+        lowlinks[parent] = lowlinks[parent].min(lowlinks[node]);
+      }
+      if lowlinks[node] == indexes[node] {
+        let mut component = Vec::new();
+        loop {
+          let member = stack.pop().unwrap();
+          on_stack[member] = false;
+          component.push(member);
+          if member == node {
+            break;
+          }
+        }
+        components.push(component);
+      }
+    }
+  }
+  components
+}
+
+/// Finds the shortest path from `start` back to itself within the component.
+fn shortest_cycle(
+  edges: &[Vec<usize>],
+  component: &[usize],
+  start: usize,
+) -> Vec<usize> {
+  let mut parents = HashMap::new();
+  let mut queue = VecDeque::from([start]);
+  while let Some(node) = queue.pop_front() {
+    for &next in &edges[node] {
+      if next == start {
+        let mut cycle = vec![node];
+        let mut current = node;
+        while let Some(&parent) = parents.get(&current) {
+          cycle.push(parent);
+          current = parent;
+        }
+        cycle.reverse();
+        return cycle;
+      }
+      if component.binary_search(&next).is_ok()
+        && !parents.contains_key(&next)
+        && next != start
+      {
+        parents.insert(next, node);
+        queue.push_back(next);
+      }
+    }
+  }
+  vec![start]
+}
+
+pub struct SpecifierCasingMismatch {
+  pub specifier: ModuleSpecifier,
+  pub actual_path: PathBuf,
+  pub range: deno_graph::Range,
+}
+
+/// Finds local imports whose casing differs from the name of the file or
+/// directories on disk. These resolve fine on case-insensitive file systems
+/// (macOS and Windows), but fail on case-sensitive ones.
+pub fn find_specifier_casing_mismatches(
+  graph: &ModuleGraph,
+) -> Vec<SpecifierCasingMismatch> {
+  let mut dir_entries_cache: HashMap<PathBuf, Option<Vec<OsString>>> =
+    HashMap::new();
+  let mut checked = HashMap::new();
+  let mut mismatches = Vec::new();
+  for module in graph.modules() {
+    let module = match module {
+      Module::Esm(module) => module,
+      _ => continue,
+    };
+    for dep in module.dependencies.values() {
+      for resolution in [&dep.maybe_code, &dep.maybe_type] {
+        let resolved = match resolution {
+          Resolution::Ok(resolved) if resolved.specifier.scheme() == "file" => {
+            resolved
+          }
+          _ => continue,
+        };
+        let maybe_actual_path = checked
+          .entry(resolved.specifier.clone())
+          .or_insert_with(|| {
+            let path = resolved.specifier.to_file_path().ok()?;
+            actual_path_casing(&path, &mut dir_entries_cache)
+              .filter(|actual_path| actual_path != &path)
+          })
+          .clone();
+        if let Some(actual_path) = maybe_actual_path {
+          mismatches.push(SpecifierCasingMismatch {
+            specifier: resolved.specifier.clone(),
+            actual_path,
+            range: resolved.range.clone(),
+          });
+        }
+      }
+    }
+  }
+  mismatches
+}
+
+/// Gets the path with the casing of each component as found on disk, or
+/// `None` when the path couldn't be found.
+fn actual_path_casing(
+  path: &Path,
+  dir_entries_cache: &mut HashMap<PathBuf, Option<Vec<OsString>>>,
+) -> Option<PathBuf> {
+  let mut actual_path = PathBuf::new();
+  for component in path.components() {
+    let name = match component {
+      Component::Normal(name) => name,
+      other => {
+        actual_path.push(other);
+        continue;
+      }
+    };
+    // the entries are read from the path found so far, so that a directory
+    // with a different casing is still found on case-sensitive file systems
+    let entries = dir_entries_cache
+      .entry(actual_path.clone())
+      .or_insert_with(|| {
+        std::fs::read_dir(&actual_path).ok().map(|entries| {
+          entries
+            .filter_map(|entry| entry.ok().map(|e| e.file_name()))
+            .collect()
+        })
+      })
+      .as_ref()?;
+    let actual_name = if entries.iter().any(|entry| entry == name) {
+      name.to_os_string()
+    } else {
+      let lowercase_name = name.to_string_lossy().to_lowercase();
+      entries
+        .iter()
+        .find(|entry| entry.to_string_lossy().to_lowercase() == lowercase_name)?
+        .clone()
+    };
+    actual_path.push(actual_name);
+  }
+  Some(actual_path)
 }
 
 /// Check if `roots` and their deps are available. Returns `Ok(())` if
 /// so. Returns `Err(_)` if there is a known module graph or resolution
-/// error statically reachable from `roots`.
+/// error statically reachable from `roots`. When `all_errors` is set, the
+/// returned error describes every error found instead of only the first.
 ///
 /// It is preferable to use this over using deno_graph's API directly
 /// because it will have enhanced error message information specifically
@@ -129,12 +442,34 @@ pub fn graph_valid(
         }
       }
 
-      Some(custom_error(get_error_class_name(&error.into()), message))
+      Some((get_error_class_name(&error.into()), message))
     });
-  if let Some(error) = errors.next() {
-    Err(error)
-  } else {
-    Ok(())
+  if !options.all_errors {
+    return match errors.next() {
+      Some((class, message)) => Err(custom_error(class, message)),
+      None => Ok(()),
+    };
+  }
+
+  let errors = errors.collect::<Vec<_>>();
+  match errors.len() {
+    0 => Ok(()),
+    1 => {
+      let (class, message) = errors.into_iter().next().unwrap();
+      Err(custom_error(class, message))
+    }
+    len => {
+      let class = errors[0].0;
+      let message = errors
+        .into_iter()
+        .map(|(_, message)| message)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+      Err(custom_error(
+        class,
+        format!("{message}\n\nFound {len} module graph errors."),
+      ))
+    }
   }
 }
 
@@ -267,22 +602,35 @@ impl ModuleGraphBuilder {
 
     let graph = Arc::new(graph);
     graph_valid_with_cli_options(&graph, &graph.roots, &self.options)?;
+    check_import_cycles(&graph, &self.options, &Default::default())?;
     if let Some(lockfile) = &self.lockfile {
       graph_lock_or_exit(&graph, &mut lockfile.lock());
     }
 
     if self.options.type_check_mode() != TypeCheckMode::None {
-      self
-        .type_checker
-        .check(
-          graph.clone(),
-          check::CheckOptions {
-            lib: self.options.ts_type_lib_window(),
-            log_ignored_options: true,
-            reload: self.options.reload_flag(),
-          },
-        )
-        .await?;
+      let groups = self.options.group_roots_by_ts_type_lib(
+        &graph.roots,
+        &self.options.ts_type_lib_window(),
+      );
+      let has_single_group = groups.len() == 1;
+      for (lib, roots) in groups {
+        let graph = if has_single_group {
+          graph.clone()
+        } else {
+          Arc::new(graph.segment(&roots))
+        };
+        self
+          .type_checker
+          .check(
+            graph,
+            check::CheckOptions {
+              lib,
+              log_ignored_options: true,
+              reload: self.options.reload_flag(),
+            },
+          )
+          .await?;
+      }
     }
 
     Ok(graph)
@@ -308,6 +656,18 @@ impl ModuleGraphBuilder {
     // that were inserted by building the graph
     self.npm_resolver.resolve_pending().await?;
 
+    // acquire the types of the npm packages that don't ship their own
+    if !graph.npm_packages.is_empty()
+      && self.options.type_check_mode() != TypeCheckMode::None
+      && self.options.type_acquisition()?
+    {
+      let types_reqs = self
+        .npm_resolver
+        .resolve_types_package_reqs(&graph.npm_packages)
+        .await;
+      self.npm_resolver.add_package_reqs(types_reqs).await?;
+    }
+
     Ok(())
   }
 
@@ -331,6 +691,11 @@ impl ModuleGraphBuilder {
       root_permissions,
       dynamic_permissions,
       self.options.node_modules_dir_specifier(),
+      self
+        .options
+        .profile()
+        .filter(|profile| !profile.define.is_empty())
+        .map(|profile| Arc::new(profile.define.clone())),
     )
   }
 
@@ -407,6 +772,8 @@ fn get_resolution_error_bare_specifier(
 struct GraphData {
   graph: Arc<ModuleGraph>,
   checked_libs: HashMap<TsTypeLib, HashSet<ModuleSpecifier>>,
+  /// The roots that were prepared without errors.
+  prepared_roots: HashSet<ModuleSpecifier>,
 }
 
 /// Holds the `ModuleGraph` and what parts of it are type checked.
@@ -421,7 +788,9 @@ pub struct ModuleGraphContainer {
 
 impl ModuleGraphContainer {
   pub fn clear(&self) {
-    self.graph_data.write().graph = Default::default();
+    let mut data = self.graph_data.write();
+    data.graph = Default::default();
+    data.prepared_roots.clear();
   }
 
   /// Acquires a permit to modify the module graph without other code
@@ -440,6 +809,78 @@ impl ModuleGraphContainer {
     self.graph_data.read().graph.clone()
   }
 
+  /// Removes the provided modules and every module that depends on them
+  /// (directly or transitively) from the graph, so that they're loaded again
+  /// the next time they're prepared. Returns the removed specifiers.
+  pub async fn invalidate(
+    &self,
+    specifiers: &[ModuleSpecifier],
+  ) -> HashSet<ModuleSpecifier> {
+    let mut permit = self.acquire_update_permit().await;
+    let graph = permit.graph_mut();
+    let mut invalidated = specifiers
+      .iter()
+      .filter_map(|specifier| graph.get(specifier))
+      .map(|module| module.specifier().clone())
+      .collect::<HashSet<_>>();
+    if invalidated.is_empty() {
+      return invalidated;
+    }
+
+    // keep going until no more dependents are found
+    loop {
+      let dependents = graph
+        .modules()
+        .filter_map(|module| match module {
+          Module::Esm(module) => Some(module),
+          _ => None,
+        })
+        .filter(|module| !invalidated.contains(&module.specifier))
+        .filter(|module| {
+          let mut dependencies = module
+            .dependencies
+            .values()
+            .flat_map(|dep| [&dep.maybe_code, &dep.maybe_type])
+            .chain(
+              module
+                .maybe_types_dependency
+                .as_ref()
+                .map(|dep| &dep.dependency),
+            );
+          dependencies.any(|resolution| match resolution.maybe_specifier() {
+            Some(specifier) => invalidated.contains(&graph.resolve(specifier)),
+            None => false,
+          })
+        })
+        .map(|module| module.specifier.clone())
+        .collect::<Vec<_>>();
+      if dependents.is_empty() {
+        break;
+      }
+      invalidated.extend(dependents);
+    }
+
+    // none of the remaining roots depend on an invalidated module, so the
+    // segment won't include any of them
+    let roots = graph
+      .roots
+      .iter()
+      .filter(|root| !invalidated.contains(&graph.resolve(root)))
+      .cloned()
+      .collect::<Vec<_>>();
+    *graph = graph.segment(&roots);
+    permit.commit();
+
+    let mut data = self.graph_data.write();
+    for checked_lib_set in data.checked_libs.values_mut() {
+      checked_lib_set.retain(|specifier| !invalidated.contains(specifier));
+    }
+    data
+      .prepared_roots
+      .retain(|specifier| !invalidated.contains(specifier));
+    invalidated
+  }
+
   /// Mark `roots` and all of their dependencies as type checked under `lib`.
   /// Assumes that all of those modules are known.
   pub fn set_type_checked(&self, roots: &[ModuleSpecifier], lib: TsTypeLib) {
@@ -464,14 +905,33 @@ impl ModuleGraphContainer {
     }
   }
 
+  /// Marks `roots` as prepared, meaning their modules are in the graph and
+  /// were validated.
+  pub fn set_prepared(&self, roots: &[ModuleSpecifier]) {
+    let mut data = self.graph_data.write();
+    let roots = roots
+      .iter()
+      .map(|root| data.graph.resolve(root))
+      .collect::<Vec<_>>();
+    data.prepared_roots.extend(roots);
+  }
+
+  /// Check if `roots` are all marked as prepared.
+  pub fn is_prepared(&self, roots: &[ModuleSpecifier]) -> bool {
+    let data = self.graph_data.read();
+    roots
+      .iter()
+      .all(|root| data.prepared_roots.contains(&data.graph.resolve(root)))
+  }
+
   /// Check if `roots` are all marked as type checked under `lib`.
   pub fn is_type_checked(
     &self,
     roots: &[ModuleSpecifier],
-    lib: TsTypeLib,
+    lib: &TsTypeLib,
   ) -> bool {
     let data = self.graph_data.read();
-    match data.checked_libs.get(&lib) {
+    match data.checked_libs.get(lib) {
       Some(checked_lib_set) => roots.iter().all(|r| {
         let found = data.graph.resolve(r);
         checked_lib_set.contains(&found)
@@ -508,6 +968,7 @@ impl<'a> ModuleGraphUpdatePermit<'a> {
 
 #[cfg(test)]
 mod test {
+  use std::collections::HashMap;
   use std::sync::Arc;
 
   use deno_ast::ModuleSpecifier;
@@ -556,4 +1017,79 @@ mod test {
       assert_eq!(get_resolution_error_bare_node_specifier(&err), output,);
     }
   }
+
+  #[test]
+  fn actual_path_casing() {
+    let temp_dir = test_util::TempDir::new();
+    temp_dir.create_dir_all("Dir");
+    temp_dir.write("Dir/File.ts", "");
+    let root = temp_dir.path().canonicalize().unwrap();
+    let mut cache = HashMap::new();
+    assert_eq!(
+      super::actual_path_casing(&root.join("Dir/File.ts"), &mut cache),
+      Some(root.join("Dir/File.ts"))
+    );
+    assert_eq!(
+      super::actual_path_casing(&root.join("dir/file.ts"), &mut cache),
+      Some(root.join("Dir/File.ts"))
+    );
+    assert_eq!(
+      super::actual_path_casing(&root.join("Dir/Other.ts"), &mut cache),
+      None
+    );
+  }
+
+  #[tokio::test]
+  async fn find_import_cycles() {
+    use deno_graph::source::MemoryLoader;
+    use deno_graph::source::Source;
+    use deno_graph::ModuleGraph;
+
+    let sources = [
+      ("file:///mod.ts", "import './a.ts';\nimport './self.ts';"),
+      ("file:///a.ts", "import './b.ts';"),
+      ("file:///b.ts", "import './c.ts';\nimport './a.ts';"),
+      ("file:///c.ts", "import './a.ts';"),
+      (
+        "file:///self.ts",
+        "import './self.ts';\nawait import('./mod.ts');",
+      ),
+    ];
+    let mut loader = MemoryLoader::new(
+      sources
+        .iter()
+        .map(|(specifier, content)| {
+          (
+            specifier.to_string(),
+            Source::Module {
+              specifier: specifier.to_string(),
+              content: content.to_string(),
+              maybe_headers: None,
+            },
+          )
+        })
+        .collect(),
+      Vec::new(),
+    );
+    let mut graph = ModuleGraph::default();
+    graph
+      .build(
+        vec![ModuleSpecifier::parse("file:///mod.ts").unwrap()],
+        &mut loader,
+        Default::default(),
+      )
+      .await;
+    let cycles = super::find_import_cycles(&graph)
+      .iter()
+      .map(|cycle| super::import_cycle_to_string(cycle))
+      .collect::<Vec<_>>();
+    // the dynamic import of mod.ts doesn't create a cycle
+    assert_eq!(
+      cycles,
+      vec![
+        "file:///a.ts -> file:///b.ts -> file:///a.ts",
+        "file:///self.ts -> file:///self.ts",
+      ]
+    );
+  }
 }
@@ -1,22 +1,32 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::args::OriginTlsConfig;
+use crate::cache::CacheFreshness;
+use crate::proxy::env_proxy_resolver;
 use crate::util::progress_bar::UpdateGuard;
 use crate::version::get_user_agent;
 
 use cache_control::Cachability;
 use cache_control::CacheControl;
 use chrono::DateTime;
-use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::custom_error;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::futures::StreamExt;
 use deno_core::url::Url;
-use deno_runtime::deno_fetch::create_http_client;
+use deno_runtime::deno_fetch::create_http_client_builder;
+use deno_runtime::deno_fetch::pin_hosts;
 use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
 use deno_runtime::deno_fetch::reqwest::header::LOCATION;
 use deno_runtime::deno_fetch::reqwest::Response;
+use deno_runtime::deno_fetch::reqwest::StatusCode;
 use deno_runtime::deno_tls::rustls::RootCertStore;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::SystemTime;
 
@@ -99,6 +109,16 @@ impl CacheSemantics {
     }
   }
 
+  /// Gets how fresh the response is, which decides if it's revalidated with
+  /// `--reload=stale`.
+  pub fn freshness(&self) -> CacheFreshness {
+    CacheFreshness {
+      age: self.age(),
+      lifetime: self.freshness_lifetime(),
+      immutable: self.cache_control.immutable,
+    }
+  }
+
   fn age(&self) -> Duration {
     let mut age = self.age_header_value();
 
@@ -124,16 +144,24 @@ impl CacheSemantics {
   }
 
   fn max_age(&self) -> Duration {
-    if self.cache_control.cachability == Some(Cachability::NoCache) {
-      return Duration::from_secs(0);
+    self.freshness_lifetime().unwrap_or_default()
+  }
+
+  /// How long the response stays fresh, or `None` when its headers don't
+  /// provide any caching information.
+  fn freshness_lifetime(&self) -> Option<Duration> {
+    if self.cache_control.cachability == Some(Cachability::NoCache)
+      || self.cache_control.no_store
+    {
+      return Some(Duration::from_secs(0));
     }
 
     if self.headers.get("vary").map(|s| s.trim()) == Some("*") {
-      return Duration::from_secs(0);
+      return Some(Duration::from_secs(0));
     }
 
     if let Some(max_age) = self.cache_control.max_age {
-      return max_age;
+      return Some(max_age);
     }
 
     let default_min_ttl = Duration::from_secs(0);
@@ -141,12 +169,14 @@ impl CacheSemantics {
     let server_date = self.raw_server_date();
     if let Some(expires) = self.headers.get("expires") {
       return match DateTime::parse_from_rfc2822(expires) {
-        Err(_) => Duration::from_secs(0),
+        Err(_) => Some(Duration::from_secs(0)),
         Ok(expires) => {
           let expires = SystemTime::UNIX_EPOCH
             + Duration::from_secs(expires.timestamp().max(0) as _);
-          return default_min_ttl
-            .max(expires.duration_since(server_date).unwrap_or_default());
+          return Some(
+            default_min_ttl
+              .max(expires.duration_since(server_date).unwrap_or_default()),
+          );
         }
       };
     }
@@ -157,12 +187,14 @@ impl CacheSemantics {
           + Duration::from_secs(last_modified.timestamp().max(0) as _);
         if let Ok(diff) = server_date.duration_since(last_modified) {
           let secs_left = diff.as_secs() as f64 * 0.1;
-          return default_min_ttl.max(Duration::from_secs(secs_left as _));
+          return Some(
+            default_min_ttl.max(Duration::from_secs(secs_left as _)),
+          );
         }
       }
     }
 
-    default_min_ttl
+    None
   }
 
   fn raw_server_date(&self) -> SystemTime {
@@ -217,34 +249,135 @@ impl CacheSemantics {
   }
 }
 
+/// A response with a status other than a success or not found.
+#[derive(Debug)]
+pub struct BadResponseError {
+  pub status: StatusCode,
+  pub maybe_response_text: Option<String>,
+}
+
+impl std::fmt::Display for BadResponseError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Bad response: {:?}", self.status)?;
+    if let Some(text) = &self.maybe_response_text {
+      write!(f, "\n\n{text}")?;
+    }
+    Ok(())
+  }
+}
+
+impl std::error::Error for BadResponseError {}
+
 #[derive(Debug, Clone)]
-pub struct HttpClient(reqwest::Client);
+pub struct HttpClient {
+  client: reqwest::Client,
+  /// The clients for the origins with a TLS configuration of their own.
+  origin_clients: Arc<HashMap<String, reqwest::Client>>,
+}
 
 impl HttpClient {
   pub fn new(
     root_cert_store: Option<RootCertStore>,
     unsafely_ignore_certificate_errors: Option<Vec<String>>,
   ) -> Result<Self, AnyError> {
-    Ok(HttpClient::from_client(create_http_client(
-      get_user_agent(),
+    Self::with_origin_tls_configs(
       root_cert_store,
-      vec![],
-      None,
       unsafely_ignore_certificate_errors,
-      None,
-    )?))
+      &BTreeMap::new(),
+      &HashMap::new(),
+    )
+  }
+
+  /// Creates a client that trusts the certificate authorities and sends the
+  /// client certificates of the TLS configurations for the requests to
+  /// their origins, and connects to the pinned addresses of the hosts.
+  pub fn with_origin_tls_configs(
+    root_cert_store: Option<RootCertStore>,
+    unsafely_ignore_certificate_errors: Option<Vec<String>>,
+    origin_tls_configs: &BTreeMap<String, OriginTlsConfig>,
+    pinned_hosts: &HashMap<String, Vec<IpAddr>>,
+  ) -> Result<Self, AnyError> {
+    let maybe_proxy_resolver = env_proxy_resolver()?;
+    let create_client =
+      |ca_certs: Vec<Vec<u8>>,
+       client_cert_chain_and_key: Option<(String, String)>|
+       -> Result<reqwest::Client, AnyError> {
+        let mut builder = create_http_client_builder(
+          get_user_agent(),
+          root_cert_store.clone(),
+          ca_certs,
+          None,
+          unsafely_ignore_certificate_errors.clone(),
+          client_cert_chain_and_key,
+        )?;
+        if let Some(proxy_resolver) = &maybe_proxy_resolver {
+          builder = builder.proxy(proxy_resolver.to_reqwest_proxy());
+        }
+        builder = pin_hosts(builder, pinned_hosts);
+        Ok(builder.build()?)
+      };
+
+    let mut origin_clients = HashMap::new();
+    for (origin, config) in origin_tls_configs {
+      let read_file = |path: &PathBuf| {
+        std::fs::read(path).with_context(|| {
+          format!(
+            "Failed reading {} of the TLS configuration of {}",
+            path.display(),
+            origin
+          )
+        })
+      };
+      let ca_certs = match &config.ca_file {
+        Some(ca_file) => vec![read_file(ca_file)?],
+        None => vec![],
+      };
+      let client_cert_chain_and_key = match &config.client_cert {
+        Some((cert_file, key_file)) => Some((
+          String::from_utf8(read_file(cert_file)?)?,
+          String::from_utf8(read_file(key_file)?)?,
+        )),
+        None => None,
+      };
+      let client = create_client(ca_certs, client_cert_chain_and_key)
+        .with_context(|| format!("Invalid TLS configuration of {origin}"))?;
+      origin_clients.insert(origin.clone(), client);
+    }
+
+    Ok(Self {
+      client: create_client(vec![], None)?,
+      origin_clients: Arc::new(origin_clients),
+    })
   }
 
   pub fn from_client(client: reqwest::Client) -> Self {
-    Self(client)
+    Self {
+      client,
+      origin_clients: Default::default(),
+    }
+  }
+
+  /// The client for the requests to the origin of the URL.
+  fn client_for(&self, url: &Url) -> &reqwest::Client {
+    self
+      .origin_clients
+      .get(&url.origin().ascii_serialization())
+      .unwrap_or(&self.client)
   }
 
   /// Do a GET request without following redirects.
-  pub fn get_no_redirect<U: reqwest::IntoUrl>(
-    &self,
-    url: U,
-  ) -> reqwest::RequestBuilder {
-    self.0.get(url)
+  pub fn get_no_redirect(&self, url: Url) -> reqwest::RequestBuilder {
+    self.client_for(&url).get(url)
+  }
+
+  /// Do a PUT request.
+  pub fn put(&self, url: Url) -> reqwest::RequestBuilder {
+    self.client_for(&url).put(url)
+  }
+
+  /// Do a POST request.
+  pub fn post(&self, url: Url) -> reqwest::RequestBuilder {
+    self.client_for(&url).post(url)
   }
 
   pub async fn download_text<U: reqwest::IntoUrl>(
@@ -259,7 +392,7 @@ impl HttpClient {
     &self,
     url: U,
   ) -> Result<Vec<u8>, AnyError> {
-    let maybe_bytes = self.inner_download(url, None).await?;
+    let maybe_bytes = self.inner_download(url, None, None).await?;
     match maybe_bytes {
       Some(bytes) => Ok(bytes),
       None => Err(custom_error("Http", "Not found.")),
@@ -271,28 +404,43 @@ impl HttpClient {
     url: U,
     progress_guard: &UpdateGuard,
   ) -> Result<Option<Vec<u8>>, AnyError> {
-    self.inner_download(url, Some(progress_guard)).await
+    self.inner_download(url, None, Some(progress_guard)).await
+  }
+
+  /// Downloads with the provided value of the authorization header, which
+  /// is only sent to the origin of the URL when following redirects.
+  pub async fn download_with_auth_and_progress<U: reqwest::IntoUrl>(
+    &self,
+    url: U,
+    maybe_authorization: Option<&str>,
+    progress_guard: &UpdateGuard,
+  ) -> Result<Option<Vec<u8>>, AnyError> {
+    self
+      .inner_download(url, maybe_authorization, Some(progress_guard))
+      .await
   }
 
   async fn inner_download<U: reqwest::IntoUrl>(
     &self,
     url: U,
+    maybe_authorization: Option<&str>,
     progress_guard: Option<&UpdateGuard>,
   ) -> Result<Option<Vec<u8>>, AnyError> {
-    let response = self.get_redirected_response(url).await?;
+    let response = self
+      .get_redirected_response_with_auth(url, maybe_authorization)
+      .await?;
 
     if response.status() == 404 {
       return Ok(None);
     } else if !response.status().is_success() {
       let status = response.status();
       let maybe_response_text = response.text().await.ok();
-      bail!(
-        "Bad response: {:?}{}",
-        status,
-        match maybe_response_text {
-          Some(text) => format!("\n\n{text}"),
-          None => String::new(),
+      return Err(
+        BadResponseError {
+          status,
+          maybe_response_text,
         }
+        .into(),
       );
     }
 
@@ -304,14 +452,33 @@ impl HttpClient {
   pub async fn get_redirected_response<U: reqwest::IntoUrl>(
     &self,
     url: U,
+  ) -> Result<Response, AnyError> {
+    self.get_redirected_response_with_auth(url, None).await
+  }
+
+  async fn get_redirected_response_with_auth<U: reqwest::IntoUrl>(
+    &self,
+    url: U,
+    maybe_authorization: Option<&str>,
   ) -> Result<Response, AnyError> {
     let mut url = url.into_url()?;
-    let mut response = self.get_no_redirect(url.clone()).send().await?;
+    let origin = url.origin();
+    let get = |url: &Url| {
+      let request = self.get_no_redirect(url.clone());
+      match maybe_authorization {
+        // don't leak the credentials to other origins
+        Some(authorization) if url.origin() == origin => {
+          request.header(AUTHORIZATION, authorization)
+        }
+        _ => request,
+      }
+    };
+    let mut response = get(&url).send().await?;
     let status = response.status();
     if status.is_redirection() {
       for _ in 0..5 {
         let new_url = resolve_redirect_from_response(&url, &response)?;
-        let new_response = self.get_no_redirect(new_url.clone()).send().await?;
+        let new_response = get(&new_url).send().await?;
         let status = new_response.status();
         if status.is_redirection() {
           response = new_response;
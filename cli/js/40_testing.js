@@ -3,7 +3,7 @@
 const core = globalThis.Deno.core;
 const ops = core.ops;
 import { setExitHandler } from "ext:runtime/30_os.js";
-import { Console } from "ext:deno_console/02_console.js";
+import { Console, inspect } from "ext:deno_console/02_console.js";
 import { serializePermissions } from "ext:runtime/10_permissions.js";
 import { assert } from "ext:deno_web/00_infra.js";
 const primordials = globalThis.__bootstrap.primordials;
@@ -20,6 +20,8 @@ const {
   MapPrototypeHas,
   MapPrototypeSet,
   MathCeil,
+  MathSqrt,
+  NumberPOSITIVE_INFINITY,
   ObjectKeys,
   ObjectPrototypeHasOwnProperty,
   ObjectPrototypeIsPrototypeOf,
@@ -788,6 +790,14 @@ function compareMeasurements(a, b) {
 }
 
 function benchStats(n, highPrecision, avg, min, max, all) {
+  // sample standard deviation, used to tell whether the difference with a
+  // stored baseline is significant
+  const mean = avg / n;
+  let squares = 0;
+  for (let i = 0; i < n; i++) {
+    squares += (all[i] - mean) * (all[i] - mean);
+  }
+  const sd = n > 1 ? MathSqrt(squares / (n - 1)) : 0;
   return {
     n,
     min,
@@ -797,6 +807,7 @@ function benchStats(n, highPrecision, avg, min, max, all) {
     p995: all[MathCeil(n * (99.5 / 100)) - 1],
     p999: all[MathCeil(n * (99.9 / 100)) - 1],
     avg: !highPrecision ? (avg / n) : MathCeil(avg / n),
+    sd,
   };
 }
 
@@ -975,12 +986,34 @@ function stepReportResult(desc, result, elapsed) {
   });
 }
 
+/**
+ * Serializes the value of a snapshot so that it doesn't depend on the order
+ * in which the properties were added. Strings are stored as is.
+ * @param value {unknown}
+ * @returns {string}
+ */
+function serializeSnapshot(value) {
+  if (typeof value === "string") {
+    return value;
+  }
+  return inspect(value, {
+    depth: NumberPOSITIVE_INFINITY,
+    sorted: true,
+    trailingComma: true,
+    compact: false,
+    iterableLimit: NumberPOSITIVE_INFINITY,
+    strAbbreviateSize: NumberPOSITIVE_INFINITY,
+    colors: false,
+  });
+}
+
 /** @param desc {TestDescription | TestStepDescription} */
 function createTestContext(desc) {
   let parent;
   let level;
   let rootId;
   let rootName;
+  let snapshotCount = 0;
   if ("parent" in desc) {
     parent = MapPrototypeGet(testStates, desc.parent.id).context;
     level = desc.level;
@@ -1006,6 +1039,22 @@ function createTestContext(desc) {
      * File Uri of the test code.
      */
     origin: desc.origin,
+    /**
+     * The value returned by the global setup module of the test run, copied
+     * for each access.
+     */
+    get globalContext() {
+      return ops.op_test_global_context();
+    },
+    /**
+     * @param actual {unknown}
+     * @param options {{ name?: string } | undefined}
+     */
+    assertSnapshot(actual, options = {}) {
+      snapshotCount++;
+      const name = `${options.name ?? getFullName(desc)} ${snapshotCount}`;
+      ops.op_assert_snapshot(name, serializeSnapshot(actual));
+    },
     /**
      * @param nameOrFnOrOptions {string | TestStepDefinition | ((t: TestContext) => void | Promise<void>)}
      * @param maybeFn {((t: TestContext) => void | Promise<void>) | undefined}
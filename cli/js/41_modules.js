@@ -0,0 +1,79 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+const core = globalThis.Deno.core;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ArrayPrototypeMap,
+  MathMax,
+  NumberIsInteger,
+  ObjectPrototypeIsPrototypeOf,
+  RangeError,
+  String,
+} = primordials;
+import { URLPrototype } from "ext:deno_url/00_url.js";
+import { denoNsUnstable } from "ext:runtime/90_deno_ns.js";
+
+function refreshModule(specifier) {
+  if (ObjectPrototypeIsPrototypeOf(URLPrototype, specifier)) {
+    specifier = specifier.href;
+  }
+  return core.opAsync("op_refresh_module", String(specifier));
+}
+
+function toPathOrUrl(pathOrUrl) {
+  if (ObjectPrototypeIsPrototypeOf(URLPrototype, pathOrUrl)) {
+    return pathOrUrl.href;
+  }
+  return String(pathOrUrl);
+}
+
+function fingerprintAssets(assets, options) {
+  return core.opAsync(
+    "op_fingerprint_assets",
+    ArrayPrototypeMap(assets, toPathOrUrl),
+    toPathOrUrl(options.outDir),
+    options.baseDir === undefined ? null : toPathOrUrl(options.baseDir),
+  );
+}
+
+function preloadModule(specifier) {
+  if (ObjectPrototypeIsPrototypeOf(URLPrototype, specifier)) {
+    specifier = specifier.href;
+  }
+  return core.opAsync("op_preload_module", String(specifier));
+}
+
+function checkForSelfUpdate() {
+  return core.opAsync("op_self_update", false);
+}
+
+function selfUpdate() {
+  return core.opAsync("op_self_update", true);
+}
+
+function setDynamicImportRetryPolicy(policy) {
+  if (policy === null || policy === undefined) {
+    core.ops.op_set_dynamic_import_retry_policy(null);
+    return;
+  }
+  const maxAttempts = policy.maxAttempts ?? 3;
+  if (!NumberIsInteger(maxAttempts) || maxAttempts < 1) {
+    throw new RangeError(
+      `"maxAttempts" must be a positive integer, received ${maxAttempts}`,
+    );
+  }
+  const initialDelay = MathMax(0, policy.initialDelay ?? 100);
+  const maxDelay = MathMax(initialDelay, policy.maxDelay ?? 10_000);
+  core.ops.op_set_dynamic_import_retry_policy({
+    maxAttempts,
+    initialDelay,
+    maxDelay,
+  });
+}
+
+denoNsUnstable.checkForSelfUpdate = checkForSelfUpdate;
+denoNsUnstable.fingerprintAssets = fingerprintAssets;
+denoNsUnstable.preloadModule = preloadModule;
+denoNsUnstable.refreshModule = refreshModule;
+denoNsUnstable.selfUpdate = selfUpdate;
+denoNsUnstable.setDynamicImportRetryPolicy = setDynamicImportRetryPolicy;
@@ -0,0 +1,378 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! The Deno CLI, which is also a library so that other crates can build the
+//! state of a project and run its programs in-process with the module
+//! resolution of the CLI, see [`ProjectBuilder`]. The `in_process` example
+//! uses each of the functions of the library.
+
+pub mod api;
+mod args;
+mod auth_tokens;
+mod cache;
+mod crash_report;
+mod deno_std;
+mod emit;
+mod errors;
+mod file_fetcher;
+mod graph_util;
+mod http_util;
+mod js;
+mod lsp;
+mod module_loader;
+mod node;
+mod npm;
+mod ops;
+mod proc_state;
+mod proxy;
+mod resolver;
+mod standalone;
+mod tools;
+mod tsc;
+mod util;
+mod version;
+mod worker;
+
+use crate::args::DenoSubcommand;
+use crate::resolver::CliGraphResolver;
+use crate::util::display;
+use crate::util::v8::get_v8_flags_from_env;
+use crate::util::v8::init_v8_flags;
+
+use args::CliOptions;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_runtime::colors;
+use deno_runtime::fmt_errors::format_js_error;
+use deno_runtime::tokio_util::run_local;
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+pub use crate::api::Project;
+pub use crate::api::ProjectBuilder;
+pub use crate::args::flags_from_vec;
+pub use crate::args::Flags;
+
+async fn run_subcommand(flags: Flags) -> Result<i32, AnyError> {
+  match flags.subcommand.clone() {
+    DenoSubcommand::Add(add_flags) => {
+      tools::deps::add(flags, add_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Bench(bench_flags) => {
+      let cli_options = CliOptions::from_flags(flags)?;
+      let bench_options = cli_options.resolve_bench_options(bench_flags)?;
+      if cli_options.watch_paths().is_some() {
+        tools::bench::run_benchmarks_with_watch(cli_options, bench_options)
+          .await?;
+      } else {
+        tools::bench::run_benchmarks(cli_options, bench_options).await?;
+      }
+      Ok(0)
+    }
+    DenoSubcommand::Bundle(bundle_flags) => {
+      tools::bundle::bundle(flags, bundle_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Cron(_) => tools::cron::run_schedules(flags).await,
+    DenoSubcommand::Doc(doc_flags) => {
+      tools::doc::print_docs(flags, doc_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Eval(eval_flags) => {
+      tools::run::eval_command(flags, eval_flags).await
+    }
+    DenoSubcommand::Cache(cache_flags) => {
+      let project = api::ProjectBuilder::from_flags(flags)?.build().await?;
+      project
+        .module_load_preparer()
+        .load_and_type_check_files(&cache_flags.files)
+        .await?;
+      let ps = project.proc_state();
+      ps.emitter.cache_module_emits(&ps.graph_container.graph())?;
+      if cache_flags.infer_types {
+        tools::check::inferred_types::infer_remote_types(ps)?;
+      }
+      Ok(0)
+    }
+    DenoSubcommand::Check(check_flags) => {
+      let (unused, json) = (check_flags.unused, check_flags.json);
+      let report = api::check(flags, check_flags).await?;
+      let unused_result = if unused {
+        tools::check::unused::report_unused_exports(
+          &report.unused_exports,
+          json,
+        )
+      } else {
+        Ok(())
+      };
+      report.into_result()?;
+      unused_result?;
+      Ok(0)
+    }
+    DenoSubcommand::Clean(clean_flags) => {
+      tools::clean::clean(flags, clean_flags)?;
+      Ok(0)
+    }
+    DenoSubcommand::Compile(compile_flags) => {
+      tools::standalone::compile(flags, compile_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Coverage(coverage_flags) => {
+      tools::coverage::cover_files(flags, coverage_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Fmt(fmt_flags) => {
+      let cli_options = Arc::new(CliOptions::from_flags(flags.clone())?);
+      let fmt_options = cli_options.resolve_fmt_options(fmt_flags)?;
+      tools::fmt::format(cli_options, fmt_options).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Init(init_flags) => {
+      tools::init::init_project(init_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Info(info_flags) => {
+      tools::info::info(flags, info_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Install(install_flags) => {
+      tools::installer::install_command(flags, install_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::InstallDeps => {
+      tools::installer::install_deps(flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Uninstall(uninstall_flags) => {
+      tools::installer::uninstall(uninstall_flags.name, uninstall_flags.root)?;
+      Ok(0)
+    }
+    DenoSubcommand::Lock(lock_flags) => {
+      tools::lock::lock_command(lock_flags)?;
+      Ok(0)
+    }
+    DenoSubcommand::Lsp => {
+      lsp::start().await?;
+      Ok(0)
+    }
+    DenoSubcommand::Lint(lint_flags) => {
+      if lint_flags.rules {
+        tools::lint::print_rules_list(lint_flags.json);
+      } else {
+        let cli_options = Arc::new(CliOptions::from_flags(flags)?);
+        let lint_options = cli_options.resolve_lint_options(lint_flags)?;
+        tools::lint::lint(cli_options, lint_options).await?;
+      }
+      Ok(0)
+    }
+    DenoSubcommand::License(license_flags) => {
+      tools::license::license(flags, license_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Outdated(outdated_flags) => {
+      tools::deps::outdated(flags, outdated_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Pack(pack_flags) => {
+      tools::pack::pack(flags, pack_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Publish(publish_flags) => {
+      tools::publish::publish(flags, publish_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Remove(remove_flags) => {
+      tools::deps::remove(flags, remove_flags)?;
+      Ok(0)
+    }
+    DenoSubcommand::Repl(repl_flags) => {
+      tools::repl::run(flags, repl_flags).await
+    }
+    DenoSubcommand::Run(run_flags) => {
+      if run_flags.is_stdin() {
+        tools::run::run_from_stdin(flags).await
+      } else if let Some(suggest_flags) = flags.suggest_permissions.clone() {
+        let flags = Flags {
+          suggest_permissions: None,
+          ..flags
+        };
+        tools::suggest_permissions::run_and_suggest_permissions(
+          flags,
+          suggest_flags,
+        )
+        .await
+      } else {
+        tools::run::run_script(flags).await
+      }
+    }
+    DenoSubcommand::Serve(serve_flags) => {
+      tools::serve::serve(flags, serve_flags).await
+    }
+    DenoSubcommand::Task(task_flags) => {
+      tools::task::execute_script(flags, task_flags).await
+    }
+    DenoSubcommand::Test(test_flags) => {
+      if let Some(ref coverage_dir) = flags.coverage_dir {
+        std::fs::create_dir_all(coverage_dir)
+          .with_context(|| format!("Failed creating: {coverage_dir}"))?;
+        // this is set in order to ensure spawned processes use the same
+        // coverage directory
+        env::set_var(
+          "DENO_UNSTABLE_COVERAGE_DIR",
+          PathBuf::from(coverage_dir).canonicalize()?,
+        );
+      }
+      if flags.watch.is_some() {
+        let cli_options = CliOptions::from_flags(flags)?;
+        let test_options = cli_options.resolve_test_options(test_flags)?;
+        tools::test::run_tests_with_watch(cli_options, test_options).await?;
+      } else {
+        api::test(flags, test_flags).await?.into_result()?;
+      }
+
+      Ok(0)
+    }
+    DenoSubcommand::Completions(completions_flags) => {
+      display::write_to_stdout_ignore_sigpipe(&completions_flags.buf)?;
+      Ok(0)
+    }
+    DenoSubcommand::Types => {
+      let types = tsc::get_types_declaration_file_text(flags.unstable);
+      display::write_to_stdout_ignore_sigpipe(types.as_bytes())?;
+      Ok(0)
+    }
+    DenoSubcommand::Upgrade(upgrade_flags) => {
+      tools::upgrade::upgrade(flags, upgrade_flags).await?;
+      Ok(0)
+    }
+    DenoSubcommand::Vendor(vendor_flags) => {
+      tools::vendor::vendor(flags, vendor_flags).await?;
+      Ok(0)
+    }
+  }
+}
+
+fn setup_panic_hook() {
+  // This function does three things inside of the panic hook:
+  // - Tokio does not exit the process when a task panics, so we define a custom
+  //   panic hook to implement this behaviour.
+  // - We print a message to stderr to indicate that this is a bug in Deno, and
+  //   should be reported to us.
+  // - We write a crash report with the context of the panic that can be
+  //   attached to the bug report.
+  let orig_hook = std::panic::take_hook();
+  std::panic::set_hook(Box::new(move |panic_info| {
+    eprintln!("\n============================================================");
+    eprintln!("Deno has panicked. This is a bug in Deno. Please report this");
+    eprintln!("at https://github.com/denoland/deno/issues/new.");
+    eprintln!("If you can reliably reproduce this panic, include the");
+    eprintln!("reproduction steps and re-run with the RUST_BACKTRACE=1 env");
+    eprintln!("var set and include the backtrace in your report.");
+    eprintln!();
+    eprintln!("Platform: {} {}", env::consts::OS, env::consts::ARCH);
+    eprintln!("Version: {}", version::deno());
+    eprintln!("Args: {:?}", env::args().collect::<Vec<_>>());
+    eprintln!();
+    orig_hook(panic_info);
+    eprintln!();
+    match crash_report::write(panic_info) {
+      Ok(path) => {
+        eprintln!("A crash report was written to {}.", path.display());
+        eprintln!("Please attach it to your bug report.");
+      }
+      Err(err) => eprintln!("Failed writing the crash report: {err}"),
+    }
+    std::process::exit(1);
+  }));
+}
+
+fn unwrap_or_exit<T>(result: Result<T, AnyError>) -> T {
+  match result {
+    Ok(value) => value,
+    Err(error) => {
+      let mut error_string = format!("{error:?}");
+      let mut error_code = 1;
+
+      if let Some(e) = error.downcast_ref::<JsError>() {
+        error_string = format_js_error(e);
+      } else if let Some(e) = error.downcast_ref::<args::LockfileError>() {
+        error_string = e.to_string();
+        error_code = 10;
+      }
+
+      eprintln!(
+        "{}: {}",
+        colors::red_bold("error"),
+        error_string.trim_start_matches("error: ")
+      );
+      std::process::exit(error_code);
+    }
+  }
+}
+
+pub fn main() {
+  setup_panic_hook();
+
+  util::unix::raise_fd_limit();
+  util::windows::ensure_stdio_open();
+  #[cfg(windows)]
+  colors::enable_ansi(); // For Windows 10
+  deno_runtime::permissions::set_prompt_callbacks(
+    Box::new(util::draw_thread::DrawThread::hide),
+    Box::new(util::draw_thread::DrawThread::show),
+  );
+
+  let args: Vec<String> = env::args().collect();
+  let root_span_name = util::telemetry::root_span_name(&args);
+
+  let future = async move {
+    let standalone_res =
+      match standalone::extract_standalone(args.clone()).await {
+        Ok(Some((metadata, eszip, maybe_startup_snapshot, maybe_vfs))) => {
+          standalone::run(eszip, metadata, maybe_startup_snapshot, maybe_vfs)
+            .await
+        }
+        Ok(None) => Ok(()),
+        Err(err) => Err(err),
+      };
+    // TODO(bartlomieju): doesn't handle exit code set by the runtime properly
+    unwrap_or_exit(standalone_res);
+
+    let mut flags = match flags_from_vec(args) {
+      Ok(flags) => flags,
+      Err(err @ clap::Error { .. })
+        if err.kind() == clap::error::ErrorKind::DisplayHelp
+          || err.kind() == clap::error::ErrorKind::DisplayVersion =>
+      {
+        err.print().unwrap();
+        std::process::exit(0);
+      }
+      Err(err) => unwrap_or_exit(Err(AnyError::from(err))),
+    };
+
+    args::load_env_files(&flags.env_file)?;
+    args::inherit_inspect_flags(&mut flags);
+    init_v8_flags(&flags.v8_flags, get_v8_flags_from_env());
+
+    util::logger::init(util::logger::LoggerOptions {
+      level: flags.log_level,
+      filters: flags.log_filters.clone(),
+      format: flags.log_format,
+      timestamps: flags.log_timestamps,
+    });
+    if let Some(progress) = &flags.progress {
+      util::progress_bar::json::init(progress)?;
+    }
+    util::telemetry::init(&root_span_name)?;
+
+    let result = run_subcommand(flags).await;
+    util::telemetry::flush(!matches!(result, Ok(0)));
+    result
+  };
+
+  let exit_code = unwrap_or_exit(run_local(future));
+
+  std::process::exit(exit_code);
+}
@@ -5,7 +5,8 @@ use super::documents::Documents;
 use super::language_server;
 use super::tsc;
 
-use crate::tools::lint::create_linter;
+use crate::tools::lint::lint_parsed_source;
+use crate::tools::lint::LintRuleSet;
 
 use deno_ast::SourceRange;
 use deno_ast::SourceRangedForSpanned;
@@ -16,11 +17,12 @@ use deno_core::error::AnyError;
 use deno_core::serde::Deserialize;
 use deno_core::serde_json::json;
 use deno_core::ModuleSpecifier;
-use deno_lint::rules::LintRule;
+use import_map::ImportMap;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::path::Path;
 use tower_lsp::lsp_types as lsp;
 use tower_lsp::lsp_types::Position;
 use tower_lsp::lsp_types::Range;
@@ -119,11 +121,12 @@ fn as_lsp_range(range: &deno_lint::diagnostic::Range) -> Range {
 }
 
 pub fn get_lint_references(
+  file_path: &Path,
   parsed_source: &deno_ast::ParsedSource,
-  lint_rules: Vec<&'static dyn LintRule>,
+  rule_set: &LintRuleSet,
 ) -> Result<Vec<Reference>, AnyError> {
-  let linter = create_linter(parsed_source.media_type(), lint_rules);
-  let lint_diagnostics = linter.lint_with_ast(parsed_source);
+  let lint_diagnostics =
+    lint_parsed_source(file_path, parsed_source, rule_set)?;
 
   Ok(
     lint_diagnostics
@@ -149,20 +152,65 @@ fn code_as_string(code: &Option<lsp::NumberOrString>) -> String {
 }
 
 /// Iterate over the supported extensions, concatenating the extension on the
-/// specifier, returning the first specifier that is resolve-able, otherwise
-/// None if none match.
+/// specifier, returning the first specifier that is resolve-able. If the
+/// resolved module is mapped in the import map, the bare specifier from the
+/// import map is returned instead. Otherwise None if none match.
 fn check_specifier(
   specifier: &str,
   referrer: &ModuleSpecifier,
   documents: &Documents,
+  maybe_import_map: Option<&ImportMap>,
 ) -> Option<String> {
-  for ext in SUPPORTED_EXTENSIONS {
-    let specifier_with_ext = format!("{specifier}{ext}");
-    if documents.contains_import(&specifier_with_ext, referrer) {
-      return Some(specifier_with_ext);
+  let maybe_specifier_with_ext = SUPPORTED_EXTENSIONS
+    .iter()
+    .map(|ext| format!("{specifier}{ext}"))
+    .find(|specifier| documents.contains_import(specifier, referrer));
+  if let Some(import_map) = maybe_import_map {
+    let maybe_mapped = referrer
+      .join(maybe_specifier_with_ext.as_deref().unwrap_or(specifier))
+      .ok()
+      .and_then(|resolved| to_import_map_specifier(import_map, &resolved));
+    if maybe_mapped.is_some() {
+      return maybe_mapped;
     }
   }
-  None
+  maybe_specifier_with_ext
+}
+
+/// Finds the shortest specifier from the top level imports of the import map
+/// which resolves to the provided specifier. This is used so that auto-imports
+/// use mapped specifiers (ex. `"std/path/mod.ts"` instead of the full URL).
+pub fn to_import_map_specifier(
+  import_map: &ImportMap,
+  specifier: &ModuleSpecifier,
+) -> Option<String> {
+  let mut maybe_result: Option<String> = None;
+  for entry in import_map.imports().entries() {
+    let value = match entry.value {
+      Some(value) => value,
+      None => continue,
+    };
+    let maybe_candidate = if entry.raw_key.ends_with('/') {
+      specifier
+        .as_str()
+        .strip_prefix(value.as_str())
+        .map(|rest| format!("{}{}", entry.raw_key, rest))
+    } else if value == specifier {
+      Some(entry.raw_key.to_string())
+    } else {
+      None
+    };
+    if let Some(candidate) = maybe_candidate {
+      let is_shorter = match &maybe_result {
+        Some(result) => candidate.len() < result.len(),
+        None => true,
+      };
+      if is_shorter {
+        maybe_result = Some(candidate);
+      }
+    }
+  }
+  maybe_result
 }
 
 /// For a set of tsc changes, can them for any that contain something that looks
@@ -171,6 +219,7 @@ pub fn fix_ts_import_changes(
   referrer: &ModuleSpecifier,
   changes: &[tsc::FileTextChanges],
   documents: &Documents,
+  maybe_import_map: Option<&ImportMap>,
 ) -> Result<Vec<tsc::FileTextChanges>, AnyError> {
   let mut r = Vec::new();
   for change in changes {
@@ -184,7 +233,7 @@ pub fn fix_ts_import_changes(
           if let Some(captures) = IMPORT_SPECIFIER_RE.captures(line) {
             let specifier = captures.get(1).unwrap().as_str();
             if let Some(new_specifier) =
-              check_specifier(specifier, referrer, documents)
+              check_specifier(specifier, referrer, documents, maybe_import_map)
             {
               line.replace(specifier, &new_specifier)
             } else {
@@ -216,6 +265,7 @@ fn fix_ts_import_action(
   referrer: &ModuleSpecifier,
   action: &tsc::CodeFixAction,
   documents: &Documents,
+  maybe_import_map: Option<&ImportMap>,
 ) -> Result<tsc::CodeFixAction, AnyError> {
   if action.fix_name == "import" {
     let change = action
@@ -233,7 +283,7 @@ fn fix_ts_import_action(
         .ok_or_else(|| anyhow!("Missing capture."))?
         .as_str();
       if let Some(new_specifier) =
-        check_specifier(specifier, referrer, documents)
+        check_specifier(specifier, referrer, documents, maybe_import_map)
       {
         let description = action.description.replace(specifier, &new_specifier);
         let changes = action
@@ -554,8 +604,12 @@ impl CodeActionCollection {
         "The action returned from TypeScript is unsupported.",
       ));
     }
-    let action =
-      fix_ts_import_action(specifier, action, &language_server.documents)?;
+    let action = fix_ts_import_action(
+      specifier,
+      action,
+      &language_server.documents,
+      language_server.maybe_import_map.as_deref(),
+    )?;
     let edit = ts_changes_to_edit(&action.changes, language_server)?;
     let code_action = lsp::CodeAction {
       title: action.description.clone(),
@@ -824,4 +878,40 @@ mod tests {
       }
     );
   }
+
+  #[test]
+  fn test_to_import_map_specifier() {
+    let base = ModuleSpecifier::parse("file:///a/import_map.json").unwrap();
+    let import_map = import_map::parse_from_json(
+      &base,
+      r#"{
+        "imports": {
+          "std/": "https://deno.land/std@0.180.0/",
+          "std_path": "https://deno.land/std@0.180.0/path/mod.ts",
+          "@app/": "./packages/app/"
+        }
+      }"#,
+    )
+    .unwrap()
+    .import_map;
+    let fixtures = [
+      (
+        "https://deno.land/std@0.180.0/path/mod.ts",
+        Some("std_path"),
+      ),
+      (
+        "https://deno.land/std@0.180.0/fs/mod.ts",
+        Some("std/fs/mod.ts"),
+      ),
+      ("file:///a/packages/app/mod.ts", Some("@app/mod.ts")),
+      ("file:///a/other.ts", None),
+    ];
+    for (specifier, expected) in fixtures {
+      let specifier = ModuleSpecifier::parse(specifier).unwrap();
+      assert_eq!(
+        to_import_map_specifier(&import_map, &specifier).as_deref(),
+        expected,
+      );
+    }
+  }
 }
@@ -1,6 +1,5 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
-use crate::cache::CachedUrlMetadata;
 use crate::cache::HttpCache;
 
 use deno_core::parking_lot::Mutex;
@@ -14,14 +13,24 @@ use std::time::SystemTime;
 /// Calculate a version for for a given path.
 pub fn calculate_fs_version(path: &Path) -> Option<String> {
   let metadata = fs::metadata(path).ok()?;
-  if let Ok(modified) = metadata.modified() {
-    if let Ok(n) = modified.duration_since(SystemTime::UNIX_EPOCH) {
-      Some(n.as_millis().to_string())
-    } else {
-      Some("1".to_string())
-    }
-  } else {
-    Some("1".to_string())
+  Some(version_from_modified_time(metadata.modified().ok()))
+}
+
+/// Calculate a version for a remote module in the cache.
+pub fn calculate_cache_version(
+  cache: &HttpCache,
+  specifier: &ModuleSpecifier,
+) -> Option<String> {
+  let modified = cache.get_modified_time(specifier)?;
+  Some(version_from_modified_time(Some(modified)))
+}
+
+fn version_from_modified_time(maybe_modified: Option<SystemTime>) -> String {
+  match maybe_modified
+    .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+  {
+    Some(n) => n.as_millis().to_string(),
+    None => "1".to_string(),
   }
 }
 
@@ -71,10 +80,7 @@ impl CacheMetadata {
     if matches!(specifier.scheme(), "file" | "npm" | "node") {
       return None;
     }
-    let version = self
-      .cache
-      .get_cache_filename(specifier)
-      .and_then(|ref path| calculate_fs_version(path));
+    let version = calculate_cache_version(&self.cache, specifier);
     let metadata = self.metadata.lock().get(specifier).cloned();
     if metadata.as_ref().and_then(|m| m.version.clone()) != version {
       self.refresh(specifier).map(|m| m.values)
@@ -87,10 +93,9 @@ impl CacheMetadata {
     if matches!(specifier.scheme(), "file" | "npm" | "node") {
       return None;
     }
-    let cache_filename = self.cache.get_cache_filename(specifier)?;
-    let specifier_metadata = CachedUrlMetadata::read(&cache_filename).ok()?;
+    let specifier_metadata = self.cache.get_metadata(specifier).ok()?;
     let values = Arc::new(parse_metadata(&specifier_metadata.headers));
-    let version = calculate_fs_version(&cache_filename);
+    let version = calculate_cache_version(&self.cache, specifier);
     let mut metadata_map = self.metadata.lock();
     let metadata = Metadata { values, version };
     metadata_map.insert(specifier.clone(), metadata.clone());
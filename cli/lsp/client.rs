@@ -156,6 +156,11 @@ impl OutsideLockClient {
   ) {
     self.0.publish_diagnostics(uri, diags, version).await;
   }
+
+  /// Asks the client to pull diagnostics again for all documents.
+  pub async fn refresh_diagnostics(&self) -> Result<(), AnyError> {
+    self.0.refresh_diagnostics().await
+  }
 }
 
 #[async_trait]
@@ -167,6 +172,7 @@ trait ClientTrait: Send + Sync {
     diagnostics: Vec<lsp::Diagnostic>,
     version: Option<i32>,
   );
+  async fn refresh_diagnostics(&self) -> Result<(), AnyError>;
   async fn send_registry_state_notification(
     &self,
     params: lsp_custom::RegistryStateNotificationParams,
@@ -202,6 +208,14 @@ impl ClientTrait for TowerClient {
     self.0.publish_diagnostics(uri, diagnostics, version).await
   }
 
+  async fn refresh_diagnostics(&self) -> Result<(), AnyError> {
+    self
+      .0
+      .send_request::<lsp_custom::DiagnosticRefreshRequest>(())
+      .await
+      .map_err(|err| anyhow!("{}", err))
+  }
+
   async fn send_registry_state_notification(
     &self,
     params: lsp_custom::RegistryStateNotificationParams,
@@ -324,6 +338,10 @@ impl ClientTrait for ReplClient {
   ) {
   }
 
+  async fn refresh_diagnostics(&self) -> Result<(), AnyError> {
+    Ok(())
+  }
+
   async fn send_registry_state_notification(
     &self,
     _params: lsp_custom::RegistryStateNotificationParams,
@@ -20,6 +20,11 @@ pub const SETTINGS_SECTION: &str = "deno";
 pub struct ClientCapabilities {
   pub code_action_disabled_support: bool,
   pub line_folding_only: bool,
+  /// The client provides the `textDocument.diagnostic` capability and will
+  /// request diagnostics via `textDocument/diagnostic` instead of the server
+  /// publishing them. lsp_types 0.93 does not have the capability, so it's
+  /// set from the raw `initialize` request (see `PullDiagnosticsService`).
+  pub pull_diagnostics: bool,
   pub snippet_support: bool,
   pub status_notification: bool,
   /// The client provides the `experimental.testingApi` capability, which is
@@ -17,7 +17,8 @@ use crate::args::LintOptions;
 use crate::graph_util;
 use crate::graph_util::enhanced_resolution_error_message;
 use crate::node;
-use crate::tools::lint::get_configured_rules;
+use crate::tools::lint::LintRuleSet;
+use crate::util::path::specifier_to_file_path;
 
 use deno_ast::MediaType;
 use deno_core::anyhow::anyhow;
@@ -30,11 +31,12 @@ use deno_core::ModuleSpecifier;
 use deno_graph::Resolution;
 use deno_graph::ResolutionError;
 use deno_graph::SpecifierError;
-use deno_lint::rules::LintRule;
 use deno_runtime::tokio_util::create_basic_runtime;
 use deno_semver::npm::NpmPackageReqReference;
 use log::error;
 use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread;
 use tokio::sync::mpsc;
@@ -53,11 +55,19 @@ type DiagnosticMap =
 type TsDiagnosticsMap = HashMap<String, Vec<crate::tsc::Diagnostic>>;
 type DiagnosticsByVersionMap = HashMap<Option<i32>, Vec<lsp::Diagnostic>>;
 
-#[derive(Clone)]
+#[derive(Debug, Default)]
+struct PublishedDiagnostics {
+  /// Changes whenever diagnostics are added for the document, so that the
+  /// client pulling diagnostics can be told they are unchanged.
+  result_id: usize,
+  by_version: DiagnosticsByVersionMap,
+}
+
+#[derive(Clone, Debug)]
 struct DiagnosticsPublisher {
   client: Client,
-  all_diagnostics:
-    Arc<Mutex<HashMap<ModuleSpecifier, DiagnosticsByVersionMap>>>,
+  all_diagnostics: Arc<Mutex<HashMap<ModuleSpecifier, PublishedDiagnostics>>>,
+  next_result_id: Arc<AtomicUsize>,
 }
 
 impl DiagnosticsPublisher {
@@ -65,15 +75,20 @@ impl DiagnosticsPublisher {
     Self {
       client,
       all_diagnostics: Default::default(),
+      next_result_id: Default::default(),
     }
   }
 
+  /// Stores the provided diagnostics and sends them to the client. When the
+  /// client pulls diagnostics, it is only told to refresh instead.
   pub async fn publish(
     &self,
     diagnostics: DiagnosticVec,
     token: &CancellationToken,
+    pull: bool,
   ) {
     let mut all_diagnostics = self.all_diagnostics.lock().await;
+    let has_diagnostics = !diagnostics.is_empty();
     for (specifier, version, diagnostics) in diagnostics {
       if token.is_cancelled() {
         return;
@@ -81,20 +96,47 @@ impl DiagnosticsPublisher {
 
       // the versions of all the published diagnostics should be the same, but just
       // in case they're not keep track of that
-      let diagnostics_by_version =
-        all_diagnostics.entry(specifier.clone()).or_default();
+      let published = all_diagnostics.entry(specifier.clone()).or_default();
+      published.result_id = self.next_result_id.fetch_add(1, Ordering::SeqCst);
       let version_diagnostics =
-        diagnostics_by_version.entry(version).or_default();
+        published.by_version.entry(version).or_default();
       version_diagnostics.extend(diagnostics);
 
-      self
+      if !pull {
+        self
+          .client
+          .when_outside_lsp_lock()
+          .publish_diagnostics(specifier, version_diagnostics.clone(), version)
+          .await;
+      }
+    }
+    drop(all_diagnostics);
+
+    if pull && has_diagnostics && !token.is_cancelled() {
+      if let Err(err) = self
         .client
         .when_outside_lsp_lock()
-        .publish_diagnostics(specifier, version_diagnostics.clone(), version)
-        .await;
+        .refresh_diagnostics()
+        .await
+      {
+        error!("Error requesting a diagnostics refresh: {}", err);
+      }
     }
   }
 
+  /// Gets the result id and the diagnostics of a document version, if any
+  /// were published for it.
+  pub async fn get(
+    &self,
+    specifier: &ModuleSpecifier,
+    version: Option<i32>,
+  ) -> Option<(String, Vec<lsp::Diagnostic>)> {
+    let all_diagnostics = self.all_diagnostics.lock().await;
+    let published = all_diagnostics.get(specifier)?;
+    let diagnostics = published.by_version.get(&version)?;
+    Some((published.result_id.to_string(), diagnostics.clone()))
+  }
+
   pub async fn clear(&self) {
     let mut all_diagnostics = self.all_diagnostics.lock().await;
     all_diagnostics.clear();
@@ -148,7 +190,7 @@ impl TsDiagnosticsStore {
 pub struct DiagnosticsServer {
   channel: Option<mpsc::UnboundedSender<SnapshotForDiagnostics>>,
   ts_diagnostics: TsDiagnosticsStore,
-  client: Client,
+  diagnostics_publisher: DiagnosticsPublisher,
   performance: Arc<Performance>,
   ts_server: Arc<TsServer>,
 }
@@ -162,7 +204,7 @@ impl DiagnosticsServer {
     DiagnosticsServer {
       channel: Default::default(),
       ts_diagnostics: Default::default(),
-      client,
+      diagnostics_publisher: DiagnosticsPublisher::new(client),
       performance,
       ts_server,
     }
@@ -176,6 +218,20 @@ impl DiagnosticsServer {
     self.ts_diagnostics.get(specifier, document_version)
   }
 
+  /// Gets the result id and all the diagnostics currently known for a
+  /// document version. This is used to respond to clients that pull
+  /// diagnostics.
+  pub async fn get_diagnostics(
+    &self,
+    specifier: &ModuleSpecifier,
+    document_version: Option<i32>,
+  ) -> Option<(String, Vec<lsp::Diagnostic>)> {
+    self
+      .diagnostics_publisher
+      .get(specifier, document_version)
+      .await
+  }
+
   pub fn invalidate(&self, specifiers: &[ModuleSpecifier]) {
     self.ts_diagnostics.invalidate(specifiers);
   }
@@ -188,7 +244,7 @@ impl DiagnosticsServer {
   pub fn start(&mut self) {
     let (tx, mut rx) = mpsc::unbounded_channel::<SnapshotForDiagnostics>();
     self.channel = Some(tx);
-    let client = self.client.clone();
+    let diagnostics_publisher = self.diagnostics_publisher.clone();
     let performance = self.performance.clone();
     let ts_diagnostics_store = self.ts_diagnostics.clone();
     let ts_server = self.ts_server.clone();
@@ -201,7 +257,6 @@ impl DiagnosticsServer {
         let mut ts_handle: Option<tokio::task::JoinHandle<()>> = None;
         let mut lint_handle: Option<tokio::task::JoinHandle<()>> = None;
         let mut deps_handle: Option<tokio::task::JoinHandle<()>> = None;
-        let diagnostics_publisher = DiagnosticsPublisher::new(client.clone());
 
         loop {
           match rx.recv().await {
@@ -256,7 +311,13 @@ impl DiagnosticsServer {
 
                   if !token.is_cancelled() {
                     ts_diagnostics_store.update(&diagnostics);
-                    diagnostics_publisher.publish(diagnostics, &token).await;
+                    diagnostics_publisher
+                      .publish(
+                        diagnostics,
+                        &token,
+                        config.client_capabilities.pull_diagnostics,
+                      )
+                      .await;
 
                     if !token.is_cancelled() {
                       performance.measure(mark);
@@ -285,7 +346,13 @@ impl DiagnosticsServer {
                   )
                   .await;
 
-                  diagnostics_publisher.publish(diagnostics, &token).await;
+                  diagnostics_publisher
+                    .publish(
+                      diagnostics,
+                      &token,
+                      config.client_capabilities.pull_diagnostics,
+                    )
+                    .await;
 
                   if !token.is_cancelled() {
                     performance.measure(mark);
@@ -314,7 +381,13 @@ impl DiagnosticsServer {
                   )
                   .await;
 
-                  diagnostics_publisher.publish(diagnostics, &token).await;
+                  diagnostics_publisher
+                    .publish(
+                      diagnostics,
+                      &token,
+                      config.client_capabilities.pull_diagnostics,
+                    )
+                    .await;
 
                   if !token.is_cancelled() {
                     performance.measure(mark);
@@ -459,7 +532,8 @@ async fn generate_lint_diagnostics(
     .documents
     .documents(DocumentsFilter::OpenDiagnosable);
   let workspace_settings = config.settings.workspace.clone();
-  let lint_rules = get_configured_rules(lint_options.rules.clone());
+  let rule_set =
+    LintRuleSet::new(lint_options.rules.clone(), lint_options.severity.clone());
   let mut diagnostics_vec = Vec::new();
   if workspace_settings.lint {
     for document in documents {
@@ -482,7 +556,7 @@ async fn generate_lint_diagnostics(
         generate_document_lint_diagnostics(
           config,
           lint_options,
-          lint_rules.clone(),
+          &rule_set,
           &document,
         ),
       ));
@@ -494,7 +568,7 @@ async fn generate_lint_diagnostics(
 fn generate_document_lint_diagnostics(
   config: &ConfigSnapshot,
   lint_options: &LintOptions,
-  lint_rules: Vec<&'static dyn LintRule>,
+  rule_set: &LintRuleSet,
   document: &Document,
 ) -> Vec<lsp::Diagnostic> {
   if !config.specifier_enabled(document.specifier()) {
@@ -505,8 +579,11 @@ fn generate_document_lint_diagnostics(
   }
   match document.maybe_parsed_source() {
     Some(Ok(parsed_source)) => {
+      // overrides of the severity don't match documents that aren't files
+      let file_path =
+        specifier_to_file_path(document.specifier()).unwrap_or_default();
       if let Ok(references) =
-        analysis::get_lint_references(&parsed_source, lint_rules)
+        analysis::get_lint_references(&file_path, &parsed_source, rule_set)
       {
         references
           .into_iter()
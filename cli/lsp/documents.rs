@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use super::cache::calculate_cache_version;
 use super::cache::calculate_fs_version;
 use super::client::LspClientKind;
 use super::text::LineIndex;
@@ -10,9 +11,9 @@ use crate::args::package_json;
 use crate::args::package_json::PackageJsonDeps;
 use crate::args::ConfigFile;
 use crate::args::JsxImportSourceConfig;
-use crate::cache::CachedUrlMetadata;
 use crate::cache::FastInsecureHasher;
 use crate::cache::HttpCache;
+use crate::cache::InferredTypesCache;
 use crate::file_fetcher::get_source_from_bytes;
 use crate::file_fetcher::map_content_type;
 use crate::file_fetcher::SUPPORTED_SCHEMES;
@@ -695,11 +696,9 @@ impl SpecifierResolver {
     specifier: &ModuleSpecifier,
     redirect_limit: usize,
   ) -> Option<ModuleSpecifier> {
-    let cache_filename = self.cache.get_cache_filename(specifier)?;
-    if redirect_limit > 0 && cache_filename.is_file() {
-      let headers = CachedUrlMetadata::read(&cache_filename)
-        .ok()
-        .map(|m| m.headers)?;
+    if redirect_limit > 0 {
+      let headers =
+        self.cache.get_metadata(specifier).ok().map(|m| m.headers)?;
       if let Some(location) = headers.get("location") {
         let redirect =
           deno_core::resolve_import(location, specifier.as_str()).ok()?;
@@ -726,8 +725,7 @@ impl FileSystemDocuments {
     resolver: &dyn deno_graph::source::Resolver,
     specifier: &ModuleSpecifier,
   ) -> Option<Document> {
-    let fs_version = get_document_path(cache, specifier)
-      .and_then(|path| calculate_fs_version(&path));
+    let fs_version = calculate_document_version(cache, specifier);
     let file_system_doc = self.docs.get(specifier);
     if file_system_doc.map(|d| d.fs_version().to_string()) != fs_version {
       // attempt to update the file on the file system
@@ -745,10 +743,10 @@ impl FileSystemDocuments {
     resolver: &dyn deno_graph::source::Resolver,
     specifier: &ModuleSpecifier,
   ) -> Option<Document> {
-    let path = get_document_path(cache, specifier)?;
-    let fs_version = calculate_fs_version(&path)?;
-    let bytes = fs::read(path).ok()?;
+    let fs_version = calculate_document_version(cache, specifier)?;
     let doc = if specifier.scheme() == "file" {
+      let path = specifier_to_file_path(specifier).ok()?;
+      let bytes = fs::read(path).ok()?;
       let maybe_charset =
         Some(text_encoding::detect_charset(&bytes).to_string());
       let content = get_source_from_bytes(bytes, maybe_charset).ok()?;
@@ -760,11 +758,10 @@ impl FileSystemDocuments {
         resolver,
       )
     } else {
-      let cache_filename = cache.get_cache_filename(specifier)?;
-      let specifier_metadata = CachedUrlMetadata::read(&cache_filename).ok()?;
-      let maybe_content_type = specifier_metadata.headers.get("content-type");
+      let (bytes, headers, _) = cache.get(specifier).ok()?;
+      let maybe_content_type = headers.get("content-type");
       let (_, maybe_charset) = map_content_type(specifier, maybe_content_type);
-      let maybe_headers = Some(specifier_metadata.headers);
+      let maybe_headers = Some(headers);
       let content = get_source_from_bytes(bytes, maybe_charset).ok()?;
       Document::new(
         specifier.clone(),
@@ -780,14 +777,14 @@ impl FileSystemDocuments {
   }
 }
 
-fn get_document_path(
+fn calculate_document_version(
   cache: &HttpCache,
   specifier: &ModuleSpecifier,
-) -> Option<PathBuf> {
+) -> Option<String> {
   match specifier.scheme() {
     "npm" | "node" => None,
-    "file" => specifier_to_file_path(specifier).ok(),
-    _ => cache.get_cache_filename(specifier),
+    "file" => calculate_fs_version(&specifier_to_file_path(specifier).ok()?),
+    _ => calculate_cache_version(cache, specifier),
   }
 }
 
@@ -960,9 +957,13 @@ impl Documents {
       if self.open_docs.contains_key(&specifier) {
         return true;
       }
-      if let Some(path) = get_document_path(&self.cache, &specifier) {
-        return path.is_file();
-      }
+      return match specifier.scheme() {
+        "npm" | "node" => false,
+        "file" => specifier_to_file_path(&specifier)
+          .map(|path| path.is_file())
+          .unwrap_or(false),
+        _ => self.cache.contains(&specifier),
+      };
     }
     false
   }
@@ -1165,6 +1166,7 @@ impl Documents {
     maybe_import_map: Option<Arc<import_map::ImportMap>>,
     maybe_config_file: Option<&ConfigFile>,
     maybe_package_json: Option<&PackageJson>,
+    maybe_inferred_types_cache: Option<InferredTypesCache>,
     npm_registry_api: Arc<CliNpmRegistryApi>,
     npm_resolution: Arc<NpmResolution>,
   ) {
@@ -1225,6 +1227,10 @@ impl Documents {
     self.resolver = Arc::new(CliGraphResolver::new(
       maybe_jsx_config,
       maybe_import_map,
+      None,
+      maybe_inferred_types_cache,
+      false,
+      false,
       false,
       npm_registry_api,
       npm_resolution,
@@ -1899,6 +1905,7 @@ console.log(b, "hello deno");
         Some(Arc::new(import_map)),
         None,
         None,
+        None,
         npm_registry_api.clone(),
         npm_resolution.clone(),
       );
@@ -1939,6 +1946,7 @@ console.log(b, "hello deno");
         Some(Arc::new(import_map)),
         None,
         None,
+        None,
         npm_registry_api,
         npm_resolution,
       );
@@ -11,6 +11,7 @@ use deno_core::serde_json::Value;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_node::PackageJson;
 use deno_runtime::deno_web::BlobStore;
+use deno_runtime::permissions::PermissionsContainer;
 use import_map::ImportMap;
 use log::error;
 use serde_json::from_value;
@@ -72,25 +73,33 @@ use crate::args::Flags;
 use crate::args::FmtOptions;
 use crate::args::LintOptions;
 use crate::args::TsConfig;
+use crate::cache::Caches;
 use crate::cache::DenoDir;
 use crate::cache::HttpCache;
+use crate::cache::InferredTypesCache;
+use crate::cache::NodeAnalysisCache;
 use crate::file_fetcher::FileFetcher;
 use crate::graph_util;
 use crate::http_util::HttpClient;
 use crate::lsp::urls::LspUrlKind;
 use crate::node::CliNodeResolver;
+use crate::node::NodeCodeTranslator;
+use crate::node::NodeResolution;
 use crate::npm::create_npm_fs_resolver;
 use crate::npm::CliNpmRegistryApi;
 use crate::npm::NpmCache;
 use crate::npm::NpmPackageResolver;
+use crate::npm::NpmRc;
 use crate::npm::NpmResolution;
 use crate::proc_state::ProcState;
 use crate::tools::fmt::format_file;
 use crate::tools::fmt::format_parsed_source;
+use crate::tools::organize_imports::organize_imports;
 use crate::util::fs::remove_dir_all_if_exists;
 use crate::util::path::specifier_to_file_path;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
+use crate::util::sync::AtomicFlag;
 
 #[derive(Debug, Clone)]
 pub struct LanguageServer(Arc<tokio::sync::RwLock<Inner>>);
@@ -104,6 +113,55 @@ pub struct StateSnapshot {
   pub maybe_import_map: Option<Arc<ImportMap>>,
   pub maybe_node_resolver: Option<Arc<CliNodeResolver>>,
   pub maybe_npm_resolver: Option<Arc<NpmPackageResolver>>,
+  pub maybe_node_code_translator: Option<Arc<NodeCodeTranslator>>,
+}
+
+impl StateSnapshot {
+  /// Gets the ESM translation of a CommonJS module in an npm package, which is
+  /// how the module is loaded at runtime. tsc indexes the translation instead
+  /// of the source, so that references and renames see the same exports.
+  pub fn translate_npm_cjs_module(
+    &self,
+    specifier: &ModuleSpecifier,
+    asset_or_doc: &AssetOrDocument,
+  ) -> Option<String> {
+    let npm_resolver = self.maybe_npm_resolver.as_ref()?;
+    let node_resolver = self.maybe_node_resolver.as_ref()?;
+    if !is_npm_cjs_module(npm_resolver, node_resolver, specifier) {
+      return None;
+    }
+    self
+      .maybe_node_code_translator
+      .as_ref()?
+      .translate_cjs_to_esm(
+        specifier,
+        asset_or_doc.text().to_string(),
+        MediaType::Cjs,
+        &mut PermissionsContainer::allow_all(),
+      )
+      .map_err(|err| {
+        lsp_warn!("Could not translate \"{}\" to ESM: {:#}", specifier, err)
+      })
+      .ok()
+  }
+}
+
+/// Gets if a module is a CommonJS module in an npm package, which tsc sees as
+/// its ESM translation, so the positions that tsc reports in it don't map to
+/// its source.
+fn is_npm_cjs_module(
+  npm_resolver: &NpmPackageResolver,
+  node_resolver: &CliNodeResolver,
+  specifier: &ModuleSpecifier,
+) -> bool {
+  matches!(
+    MediaType::from_specifier(specifier),
+    MediaType::JavaScript | MediaType::Cjs
+  ) && npm_resolver.in_npm_package(specifier)
+    && matches!(
+      node_resolver.url_to_node_resolution(specifier.clone()),
+      Ok(NodeResolution::CommonJs(_))
+    )
 }
 
 #[derive(Debug)]
@@ -116,6 +174,9 @@ pub struct Inner {
   cache_metadata: cache::CacheMetadata,
   /// The LSP client that this LSP server is connected to.
   pub client: Client,
+  /// Raised when the client initializes with the `textDocument.diagnostic`
+  /// capability (see `PullDiagnosticsService`).
+  client_pulls_diagnostics: Arc<AtomicFlag>,
   /// Configuration information.
   pub config: Config,
   deps_http_cache: HttpCache,
@@ -124,6 +185,9 @@ pub struct Inner {
   /// on disk or "open" within the client.
   pub documents: Documents,
   http_client: HttpClient,
+  /// The declarations inferred for untyped remote modules with
+  /// `deno cache --infer-types`.
+  inferred_types_cache: InferredTypesCache,
   /// Handles module registries, which allow discovery of modules
   module_registries: ModuleRegistry,
   /// The path to the module registries cache
@@ -134,8 +198,11 @@ pub struct Inner {
   /// An optional configuration file which has been specified in the client
   /// options.
   maybe_config_file: Option<ConfigFile>,
+  /// The configuration file that a diagnostic of an invalid configuration was
+  /// published for, so that it's cleared once the configuration is fixed.
+  maybe_config_file_error_uri: Option<Url>,
   /// An optional import map which is used to resolve modules.
-  maybe_import_map: Option<Arc<ImportMap>>,
+  pub maybe_import_map: Option<Arc<ImportMap>>,
   /// The URL for the import map which is used to determine relative imports.
   maybe_import_map_uri: Option<Url>,
   /// An optional package.json configuration file.
@@ -154,6 +221,8 @@ pub struct Inner {
   npm_resolution: Arc<NpmResolution>,
   /// Resolver for npm packages.
   npm_resolver: Arc<NpmPackageResolver>,
+  /// Translates the CommonJS modules of npm packages to ESM for tsc.
+  node_code_translator: Arc<NodeCodeTranslator>,
   /// A collection of measurements which instrument that performance of the LSP.
   performance: Arc<Performance>,
   /// A memoized version of fixable diagnostic codes retrieved from TypeScript.
@@ -165,8 +234,14 @@ pub struct Inner {
 }
 
 impl LanguageServer {
-  pub fn new(client: Client) -> Self {
-    Self(Arc::new(tokio::sync::RwLock::new(Inner::new(client))))
+  pub fn new(
+    client: Client,
+    client_pulls_diagnostics: Arc<AtomicFlag>,
+  ) -> Self {
+    Self(Arc::new(tokio::sync::RwLock::new(Inner::new(
+      client,
+      client_pulls_diagnostics,
+    ))))
   }
 
   /// Similar to `deno cache` on the command line, where modules will be cached
@@ -202,6 +277,7 @@ impl LanguageServer {
           is_vendoring: false,
           follow_type_only: true,
           check_js: false,
+          all_errors: ps.options.all_graph_errors(),
         },
       )?;
       Ok(())
@@ -306,6 +382,13 @@ impl LanguageServer {
     self.0.read().await.inlay_hint(params).await
   }
 
+  pub async fn document_diagnostic(
+    &self,
+    params: lsp_custom::DocumentDiagnosticParams,
+  ) -> LspResult<lsp_custom::DocumentDiagnosticReport> {
+    self.0.read().await.document_diagnostic(params).await
+  }
+
   pub async fn virtual_text_document(
     &self,
     params: Option<Value>,
@@ -429,6 +512,11 @@ fn create_lsp_structs(
 ) {
   let registry_url = CliNpmRegistryApi::default_url();
   let progress_bar = ProgressBar::new(ProgressBarStyle::TextOnly);
+  // only the user's .npmrc is known before the workspace is initialized
+  let npmrc = Arc::new(NpmRc::load(None).unwrap_or_else(|err| {
+    lsp_warn!("Error loading .npmrc: {:#}", err);
+    Default::default()
+  }));
   let npm_cache = Arc::new(NpmCache::from_deno_dir(
     dir,
     // Use an "only" cache setting in order to make the
@@ -436,11 +524,13 @@ fn create_lsp_structs(
     // the cache from being filled with lots of packages while
     // the user is typing.
     CacheSetting::Only,
+    npmrc.clone(),
     http_client.clone(),
     progress_bar.clone(),
   ));
   let api = Arc::new(CliNpmRegistryApi::new(
     registry_url.clone(),
+    npmrc,
     npm_cache.clone(),
     http_client,
     progress_bar.clone(),
@@ -453,6 +543,7 @@ fn create_lsp_structs(
     registry_url.clone(),
     resolution.clone(),
     None,
+    Vec::new(),
   );
   (
     api,
@@ -466,8 +557,30 @@ fn create_lsp_structs(
   )
 }
 
+/// Creates the translator of the CommonJS modules in npm packages, which only
+/// reads the files of the packages from the npm cache.
+fn create_node_code_translator(
+  dir: &DenoDir,
+  http_client: HttpClient,
+  npm_resolver: Arc<NpmPackageResolver>,
+) -> Arc<NodeCodeTranslator> {
+  let file_fetcher = FileFetcher::new(
+    HttpCache::new(&dir.deps_folder_path()),
+    CacheSetting::Only,
+    true,
+    http_client,
+    BlobStore::default(),
+    None,
+  );
+  Arc::new(NodeCodeTranslator::new(
+    NodeAnalysisCache::new(Caches::default().node_analysis_db(dir)),
+    Arc::new(file_fetcher),
+    npm_resolver,
+  ))
+}
+
 impl Inner {
-  fn new(client: Client) -> Self {
+  fn new(client: Client, client_pulls_diagnostics: Arc<AtomicFlag>) -> Self {
     let maybe_custom_root = env::var("DENO_DIR").map(String::into).ok();
     let dir =
       DenoDir::new(maybe_custom_root).expect("could not access DENO_DIR");
@@ -478,6 +591,8 @@ impl Inner {
         .unwrap();
     let location = dir.deps_folder_path();
     let documents = Documents::new(&location, client.kind());
+    let inferred_types_cache =
+      InferredTypesCache::new(&dir.inferred_types_folder_path());
     let deps_http_cache = HttpCache::new(&location);
     let cache_metadata = cache::CacheMetadata::new(deps_http_cache.clone());
     let performance = Arc::new(Performance::default());
@@ -491,18 +606,26 @@ impl Inner {
     let assets = Assets::new(ts_server.clone());
     let (npm_api, npm_cache, npm_resolver, npm_resolution) =
       create_lsp_structs(&dir, http_client.clone());
+    let node_code_translator = create_node_code_translator(
+      &dir,
+      http_client.clone(),
+      npm_resolver.clone(),
+    );
 
     Self {
       assets,
       cache_metadata,
       client,
+      client_pulls_diagnostics,
       config,
       deps_http_cache,
       diagnostics_server,
       documents,
       http_client,
+      inferred_types_cache,
       maybe_cache_path: None,
       maybe_config_file: None,
+      maybe_config_file_error_uri: None,
       maybe_import_map: None,
       maybe_import_map_uri: None,
       maybe_package_json: None,
@@ -515,6 +638,7 @@ impl Inner {
       npm_cache,
       npm_resolution,
       npm_resolver,
+      node_code_translator,
       performance,
       ts_fixable_diagnostics: Default::default(),
       ts_server,
@@ -550,6 +674,31 @@ impl Inner {
     }
   }
 
+  /// Gets the line index of a file a reference points to. References may
+  /// point into npm packages which are not loaded as documents, so in that
+  /// case the line index is built from the file on disk.
+  fn get_reference_line_index(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<Arc<text::LineIndex>> {
+    if let Some(asset_or_doc) = self.get_maybe_asset_or_document(specifier) {
+      return Some(asset_or_doc.line_index());
+    }
+    let path = specifier_to_file_path(specifier).ok()?;
+    let text = std::fs::read_to_string(path).ok()?;
+    Some(Arc::new(text::LineIndex::new(&text)))
+  }
+
+  /// Gets if tsc sees the module as the ESM translation of a CommonJS module
+  /// in an npm package (see `StateSnapshot::translate_npm_cjs_module`).
+  pub fn is_npm_cjs_module(&self, specifier: &ModuleSpecifier) -> bool {
+    let node_resolver = CliNodeResolver::new(
+      self.npm_resolution.clone(),
+      self.npm_resolver.clone(),
+    );
+    is_npm_cjs_module(&self.npm_resolver, &node_resolver, specifier)
+  }
+
   pub async fn get_navigation_tree(
     &self,
     specifier: &ModuleSpecifier,
@@ -711,6 +860,7 @@ impl Inner {
         self.npm_api.base_url().clone(),
         npm_resolution.clone(),
         None,
+        Vec::new(),
       ),
       None,
     ));
@@ -723,6 +873,7 @@ impl Inner {
       maybe_import_map: self.maybe_import_map.clone(),
       maybe_node_resolver: Some(node_resolver),
       maybe_npm_resolver: Some(npm_resolver),
+      maybe_node_code_translator: Some(self.node_code_translator.clone()),
     })
   }
 
@@ -797,10 +948,17 @@ impl Inner {
       self.npm_resolver,
       self.npm_resolution,
     ) = create_lsp_structs(&dir, self.http_client.clone());
+    self.node_code_translator = create_node_code_translator(
+      &dir,
+      self.http_client.clone(),
+      self.npm_resolver.clone(),
+    );
     // update the cache path
     let location = dir.deps_folder_path();
     self.documents.set_location(&location);
     self.cache_metadata.set_location(&location);
+    self.inferred_types_cache =
+      InferredTypesCache::new(&dir.inferred_types_folder_path());
     self.maybe_cache_path = new_cache_path;
     Ok(())
   }
@@ -1103,6 +1261,8 @@ impl Inner {
           .collect()
       });
       self.config.update_capabilities(&params.capabilities);
+      self.config.client_capabilities.pull_diagnostics =
+        self.client_pulls_diagnostics.is_raised();
     }
 
     self.update_debug_flag();
@@ -1157,6 +1317,7 @@ impl Inner {
       self.maybe_import_map.clone(),
       self.maybe_config_file.as_ref(),
       self.maybe_package_json.as_ref(),
+      Some(self.inferred_types_cache.clone()),
       self.npm_api.clone(),
       self.npm_resolution.clone(),
     );
@@ -1225,16 +1386,62 @@ impl Inner {
   }
 
   async fn refresh_npm_specifiers(&mut self) {
-    let package_reqs = self.documents.npm_package_reqs();
-    if let Err(err) = self
-      .npm_resolver
-      .set_package_reqs((*package_reqs).clone())
-      .await
-    {
+    let mut package_reqs = (*self.documents.npm_package_reqs()).clone();
+    let (type_acquisition, maybe_error) = match &self.maybe_config_file {
+      Some(config_file) => match config_file.to_type_acquisition() {
+        Ok(type_acquisition) => (type_acquisition, None),
+        // the types are acquired like without the invalid configuration
+        Err(err) => (true, Some((config_file.specifier.clone(), err))),
+      },
+      None => (true, None),
+    };
+    self.publish_config_file_error(maybe_error).await;
+    if type_acquisition {
+      // the packages resolved by the previous refresh are used, because
+      // the ones of new requirements aren't cached yet
+      let types_reqs = self
+        .npm_resolver
+        .resolve_types_package_reqs(&package_reqs)
+        .await;
+      package_reqs.extend(types_reqs);
+    }
+    if let Err(err) = self.npm_resolver.set_package_reqs(package_reqs).await {
       lsp_warn!("Could not set npm package requirements. {:#}", err);
     }
   }
 
+  /// Publishes an error of the configuration file as a diagnostic of it, or
+  /// clears the diagnostic that was published before when there's none.
+  async fn publish_config_file_error(
+    &mut self,
+    maybe_error: Option<(ModuleSpecifier, AnyError)>,
+  ) {
+    match maybe_error {
+      Some((uri, err)) => {
+        self
+          .client
+          .publish_diagnostics(
+            uri.clone(),
+            vec![Diagnostic {
+              range: Range::default(),
+              severity: Some(DiagnosticSeverity::ERROR),
+              source: Some("deno".to_string()),
+              message: format!("{err:#}"),
+              ..Default::default()
+            }],
+            None,
+          )
+          .await;
+        self.maybe_config_file_error_uri = Some(uri);
+      }
+      None => {
+        if let Some(uri) = self.maybe_config_file_error_uri.take() {
+          self.client.publish_diagnostics(uri, Vec::new(), None).await;
+        }
+      }
+    }
+  }
+
   async fn did_close(&mut self, params: DidCloseTextDocumentParams) {
     let mark = self.performance.mark("did_close", Some(&params));
     if params.text_document.uri.scheme() == "deno" {
@@ -1456,9 +1663,10 @@ impl Inner {
       return Ok(None);
     }
 
+    let fmt_options = self.fmt_options.options_for_path(&file_path);
     let format_result = match document.maybe_parsed_source() {
       Some(Ok(parsed_source)) => {
-        format_parsed_source(&parsed_source, &self.fmt_options.options)
+        format_parsed_source(&parsed_source, &fmt_options)
       }
       Some(Err(err)) => Err(anyhow!("{}", err)),
       None => {
@@ -1471,7 +1679,7 @@ impl Inner {
           .map(|ext| file_path.with_extension(ext))
           .unwrap_or(file_path);
         // it's not a js/ts file, so attempt to format its contents
-        format_file(&file_path, &document.content(), &self.fmt_options.options)
+        format_file(&file_path, &document.content(), &fmt_options)
       }
     };
 
@@ -1733,6 +1941,43 @@ impl Inner {
         .map(CodeActionOrCommand::CodeAction),
     );
 
+    // Organize imports
+    let wants_organize_imports = params
+      .context
+      .only
+      .as_ref()
+      .map(|only| {
+        only.iter().any(|kind| {
+          CodeActionKind::SOURCE_ORGANIZE_IMPORTS
+            .as_str()
+            .starts_with(kind.as_str())
+        })
+      })
+      .unwrap_or(false);
+    if wants_organize_imports {
+      if let Some(Ok(parsed_source)) = asset_or_doc.maybe_parsed_source() {
+        if let Some(new_text) = organize_imports(&parsed_source) {
+          let text_edits = text::get_edits(
+            parsed_source.text_info().text_str(),
+            &new_text,
+            line_index.as_ref(),
+          );
+          all_actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Organize imports".to_string(),
+            kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+            edit: Some(WorkspaceEdit {
+              changes: Some(HashMap::from([(
+                params.text_document.uri.clone(),
+                text_edits,
+              )])),
+              ..Default::default()
+            }),
+            ..Default::default()
+          }));
+        }
+      }
+    }
+
     let code_action_disabled_support =
       self.config.client_capabilities.code_action_disabled_support;
     let actions: Vec<CodeActionOrCommand> = all_actions.into_iter().filter(|ca| {
@@ -1791,6 +2036,7 @@ impl Inner {
           &code_action_data.specifier,
           &combined_code_actions.changes,
           &self.documents,
+          self.maybe_import_map.as_deref(),
         )
         .map_err(|err| {
           error!("Unable to remap changes: {}", err);
@@ -1986,12 +2232,17 @@ impl Inner {
         }
         let reference_specifier =
           resolve_url(&reference.entry.document_span.file_name).unwrap();
+        // the exports of a translated CommonJS module are not in its source
+        if self.is_npm_cjs_module(&reference_specifier) {
+          continue;
+        }
         let reference_line_index = if reference_specifier == specifier {
           line_index.clone()
         } else {
-          let asset_or_doc =
-            self.get_asset_or_document(&reference_specifier)?;
-          asset_or_doc.line_index()
+          match self.get_reference_line_index(&reference_specifier) {
+            Some(line_index) => line_index,
+            None => continue,
+          }
         };
         results.push(
           reference
@@ -2532,24 +2783,36 @@ impl Inner {
     let mark = self.performance.mark("rename", Some(&params));
     let asset_or_doc = self.get_asset_or_document(&specifier)?;
     let line_index = asset_or_doc.line_index();
+    let position =
+      line_index.offset_tsc(params.text_document_position.position)?;
 
-    let req = tsc::RequestMethod::FindRenameLocations {
-      specifier,
-      position: line_index
-        .offset_tsc(params.text_document_position.position)?,
-      find_in_strings: false,
-      find_in_comments: false,
-      provide_prefix_and_suffix_text_for_rename: false,
+    let mut maybe_locations = self
+      .find_rename_locations(&specifier, position, false)
+      .await?;
+    // the modules of npm packages can't be edited, so a symbol that one of
+    // them exports is only renamed in the workspace, where it's imported
+    // with an alias instead
+    let in_npm_package = |location: &tsc::RenameLocation| {
+      location
+        .specifier()
+        .map(|specifier| self.npm_resolver.in_npm_package(&specifier))
+        .unwrap_or(false)
     };
-
-    let maybe_locations: Option<Vec<tsc::RenameLocation>> = self
-      .ts_server
-      .request(self.snapshot(), req)
-      .await
-      .map_err(|err| {
-        error!("Failed to request to tsserver {}", err);
-        LspError::invalid_request()
-      })?;
+    if maybe_locations
+      .as_ref()
+      .map(|locations| locations.iter().any(in_npm_package))
+      .unwrap_or(false)
+    {
+      maybe_locations = self
+        .find_rename_locations(&specifier, position, true)
+        .await?
+        .map(|locations| {
+          locations
+            .into_iter()
+            .filter(|location| !in_npm_package(location))
+            .collect()
+        });
+    }
 
     if let Some(locations) = maybe_locations {
       let rename_locations = tsc::RenameLocations { locations };
@@ -2568,6 +2831,29 @@ impl Inner {
     }
   }
 
+  async fn find_rename_locations(
+    &self,
+    specifier: &ModuleSpecifier,
+    position: u32,
+    provide_prefix_and_suffix_text_for_rename: bool,
+  ) -> LspResult<Option<Vec<tsc::RenameLocation>>> {
+    let req = tsc::RequestMethod::FindRenameLocations {
+      specifier: specifier.clone(),
+      position,
+      find_in_strings: false,
+      find_in_comments: false,
+      provide_prefix_and_suffix_text_for_rename,
+    };
+    self
+      .ts_server
+      .request(self.snapshot(), req)
+      .await
+      .map_err(|err| {
+        error!("Failed to request to tsserver {}", err);
+        LspError::invalid_request()
+      })
+  }
+
   async fn selection_range(
     &self,
     params: SelectionRangeParams,
@@ -3288,6 +3574,51 @@ impl Inner {
     )
   }
 
+  async fn document_diagnostic(
+    &self,
+    params: lsp_custom::DocumentDiagnosticParams,
+  ) -> LspResult<lsp_custom::DocumentDiagnosticReport> {
+    let specifier = self
+      .url_map
+      .normalize_url(&params.text_document.uri, LspUrlKind::File);
+    if !self.is_diagnosable(&specifier)
+      || !self.config.specifier_enabled(&specifier)
+    {
+      return Ok(lsp_custom::DocumentDiagnosticReport::Full {
+        result_id: None,
+        items: Vec::new(),
+      });
+    }
+
+    let mark = self.performance.mark("document_diagnostic", Some(&params));
+    let document_version = self
+      .documents
+      .get(&specifier)
+      .and_then(|doc| doc.maybe_lsp_version());
+    let maybe_diagnostics = self
+      .diagnostics_server
+      .get_diagnostics(&specifier, document_version)
+      .await;
+    self.performance.measure(mark);
+    Ok(match maybe_diagnostics {
+      Some((result_id, _))
+        if params.previous_result_id.as_ref() == Some(&result_id) =>
+      {
+        lsp_custom::DocumentDiagnosticReport::Unchanged { result_id }
+      }
+      Some((result_id, items)) => lsp_custom::DocumentDiagnosticReport::Full {
+        result_id: Some(result_id),
+        items,
+      },
+      // the diagnostics of this version are still being generated, and the
+      // client is asked to refresh once they are
+      None => lsp_custom::DocumentDiagnosticReport::Full {
+        result_id: None,
+        items: Vec::new(),
+      },
+    })
+  }
+
   async fn inlay_hint(
     &self,
     params: InlayHintParams,
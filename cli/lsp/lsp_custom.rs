@@ -14,6 +14,11 @@ pub const VIRTUAL_TEXT_DOCUMENT: &str = "deno/virtualTextDocument";
 // While lsp_types supports inlay hints currently, tower_lsp does not.
 pub const INLAY_HINT: &str = "textDocument/inlayHint";
 
+// Pull diagnostics are not supported by tower_lsp or lsp_types 0.93, so the
+// request and the refresh request are handled as custom methods and the
+// capabilities are negotiated by `PullDiagnosticsService`.
+pub const DOCUMENT_DIAGNOSTIC: &str = "textDocument/diagnostic";
+
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CacheParams {
@@ -25,6 +30,48 @@ pub struct CacheParams {
   pub uris: Vec<lsp::TextDocumentIdentifier>,
 }
 
+/// The `diagnosticProvider` server capability.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticOptions {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub identifier: Option<String>,
+  /// Whether a change in one document can change the diagnostics of others.
+  pub inter_file_dependencies: bool,
+  pub workspace_diagnostics: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+  pub text_document: lsp::TextDocumentIdentifier,
+  /// The result id of a previous response if provided.
+  pub previous_result_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DocumentDiagnosticReport {
+  Full {
+    #[serde(rename = "resultId", skip_serializing_if = "Option::is_none")]
+    result_id: Option<String>,
+    items: Vec<lsp::Diagnostic>,
+  },
+  Unchanged {
+    #[serde(rename = "resultId")]
+    result_id: String,
+  },
+}
+
+pub enum DiagnosticRefreshRequest {}
+
+impl lsp::request::Request for DiagnosticRefreshRequest {
+  type Params = ();
+  type Result = ();
+
+  const METHOD: &'static str = "workspace/diagnostic/refresh";
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct RegistryStateNotificationParams {
   pub origin: String,
@@ -1,10 +1,14 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::sync::Arc;
+
 use deno_core::error::AnyError;
 use tower_lsp::LspService;
 use tower_lsp::Server;
 
 use crate::lsp::language_server::LanguageServer;
+use crate::lsp::pull_diagnostics::PullDiagnosticsService;
+use crate::util::sync::AtomicFlag;
 pub use repl::ReplCompletionItem;
 pub use repl::ReplLanguageServer;
 
@@ -23,6 +27,7 @@ mod lsp_custom;
 mod parent_process_checker;
 mod path_to_regex;
 mod performance;
+mod pull_diagnostics;
 mod refactor;
 mod registries;
 mod repl;
@@ -36,8 +41,12 @@ pub async fn start() -> Result<(), AnyError> {
   let stdin = tokio::io::stdin();
   let stdout = tokio::io::stdout();
 
+  let client_pulls_diagnostics = Arc::new(AtomicFlag::default());
   let (service, socket) = LspService::build(|client| {
-    language_server::LanguageServer::new(client::Client::from_tower(client))
+    language_server::LanguageServer::new(
+      client::Client::from_tower(client),
+      client_pulls_diagnostics.clone(),
+    )
   })
   .custom_method(lsp_custom::CACHE_REQUEST, LanguageServer::cache_request)
   .custom_method(
@@ -59,8 +68,13 @@ pub async fn start() -> Result<(), AnyError> {
     LanguageServer::virtual_text_document,
   )
   .custom_method(lsp_custom::INLAY_HINT, LanguageServer::inlay_hint)
+  .custom_method(
+    lsp_custom::DOCUMENT_DIAGNOSTIC,
+    LanguageServer::document_diagnostic,
+  )
   .finish();
 
+  let service = PullDiagnosticsService::new(service, client_pulls_diagnostics);
   Server::new(stdin, stdout, socket).serve(service).await;
 
   Ok(())
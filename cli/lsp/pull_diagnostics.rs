@@ -0,0 +1,148 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use deno_core::futures::future::BoxFuture;
+use deno_core::futures::FutureExt;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
+use tower::Service;
+use tower_lsp::jsonrpc::Request;
+use tower_lsp::jsonrpc::Response;
+
+use super::lsp_custom;
+use crate::util::sync::AtomicFlag;
+
+/// Negotiates pull diagnostics, which lsp_types 0.93 does not have in the
+/// client and server capabilities. The `textDocument.diagnostic` client
+/// capability is read from the raw `initialize` request and, when the client
+/// has it, the `diagnosticProvider` is added to the server capabilities of
+/// the response.
+pub struct PullDiagnosticsService<S> {
+  inner: S,
+  client_pulls_diagnostics: Arc<AtomicFlag>,
+}
+
+impl<S> PullDiagnosticsService<S> {
+  /// Wraps the service, raising `client_pulls_diagnostics` before the
+  /// language server handles the `initialize` request of a client that
+  /// pulls diagnostics.
+  pub fn new(inner: S, client_pulls_diagnostics: Arc<AtomicFlag>) -> Self {
+    Self {
+      inner,
+      client_pulls_diagnostics,
+    }
+  }
+}
+
+impl<S> Service<Request> for PullDiagnosticsService<S>
+where
+  S: Service<Request, Response = Option<Response>>,
+  S::Future: Send + 'static,
+{
+  type Response = S::Response;
+  type Error = S::Error;
+  type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+  fn poll_ready(
+    &mut self,
+    cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx)
+  }
+
+  fn call(&mut self, request: Request) -> Self::Future {
+    let is_initialize = request.method() == "initialize";
+    let pulls_diagnostics =
+      is_initialize && client_pulls_diagnostics(request.params());
+    if pulls_diagnostics {
+      self.client_pulls_diagnostics.raise();
+    }
+    let future = self.inner.call(request);
+    async move {
+      let maybe_response = future.await?;
+      if !pulls_diagnostics {
+        return Ok(maybe_response);
+      }
+      Ok(maybe_response.map(|response| {
+        let (id, result) = response.into_parts();
+        Response::from_parts(id, result.map(add_diagnostic_provider))
+      }))
+    }
+    .boxed()
+  }
+}
+
+fn client_pulls_diagnostics(maybe_params: Option<&Value>) -> bool {
+  maybe_params
+    .and_then(|params| params.pointer("/capabilities/textDocument/diagnostic"))
+    .map(|diagnostic| diagnostic.is_object())
+    .unwrap_or(false)
+}
+
+fn add_diagnostic_provider(mut result: Value) -> Value {
+  if let Some(capabilities) = result
+    .get_mut("capabilities")
+    .and_then(|capabilities| capabilities.as_object_mut())
+  {
+    let options = lsp_custom::DiagnosticOptions {
+      identifier: Some("deno".to_string()),
+      // the diagnostics of a module depend on the modules it imports
+      inter_file_dependencies: true,
+      workspace_diagnostics: false,
+    };
+    capabilities.insert(
+      "diagnosticProvider".to_string(),
+      serde_json::to_value(options).unwrap(),
+    );
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::serde_json::json;
+
+  #[test]
+  fn test_client_pulls_diagnostics() {
+    assert!(client_pulls_diagnostics(Some(&json!({
+      "capabilities": {
+        "textDocument": {
+          "diagnostic": { "dynamicRegistration": false },
+        },
+      },
+    }))));
+    assert!(!client_pulls_diagnostics(Some(&json!({
+      "capabilities": {
+        "textDocument": {},
+        "experimental": { "pullDiagnostics": true },
+      },
+    }))));
+    assert!(!client_pulls_diagnostics(None));
+  }
+
+  #[test]
+  fn test_add_diagnostic_provider() {
+    let result = add_diagnostic_provider(json!({
+      "capabilities": { "hoverProvider": true },
+      "serverInfo": { "name": "deno-language-server" },
+    }));
+    assert_eq!(
+      result,
+      json!({
+        "capabilities": {
+          "hoverProvider": true,
+          "diagnosticProvider": {
+            "identifier": "deno",
+            "interFileDependencies": true,
+            "workspaceDiagnostics": false,
+          },
+        },
+        "serverInfo": { "name": "deno-language-server" },
+      })
+    );
+  }
+}
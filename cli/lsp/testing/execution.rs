@@ -295,6 +295,7 @@ impl TestRun {
             sender.clone(),
             fail_fast_tracker,
             filter,
+            Default::default(),
           ))
         };
         if let Err(error) = file_result {
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use super::analysis::to_import_map_specifier;
 use super::code_lens;
 use super::config;
 use super::documents::AssetOrDocument;
@@ -44,6 +45,7 @@ use deno_core::ModuleSpecifier;
 use deno_core::OpState;
 use deno_core::RuntimeOptions;
 use deno_runtime::tokio_util::create_basic_runtime;
+use import_map::ImportMap;
 use lazy_regex::lazy_regex;
 use once_cell::sync::Lazy;
 use regex::Captures;
@@ -837,7 +839,11 @@ impl DocumentSpan {
       .normalize_specifier(&target_specifier)
       .ok()?;
     let (target_range, target_selection_range) =
-      if let Some(context_span) = &self.context_span {
+      if language_server.is_npm_cjs_module(&target_specifier) {
+        // the positions in the translation of a CommonJS module are not in
+        // its source, so link to the start of the module
+        (lsp::Range::default(), lsp::Range::default())
+      } else if let Some(context_span) = &self.context_span {
         (
           context_span.to_range(target_line_index.clone()),
           self.text_span.to_range(target_line_index),
@@ -1205,8 +1211,14 @@ pub struct RenameLocation {
   #[serde(flatten)]
   document_span: DocumentSpan,
   // RenameLocation props
-  // prefix_text: Option<String>,
-  // suffix_text: Option<String>,
+  prefix_text: Option<String>,
+  suffix_text: Option<String>,
+}
+
+impl RenameLocation {
+  pub fn specifier(&self) -> Option<ModuleSpecifier> {
+    normalize_specifier(&self.document_span.file_name).ok()
+  }
 }
 
 pub struct RenameLocations {
@@ -1250,7 +1262,12 @@ impl RenameLocations {
           .document_span
           .text_span
           .to_range(asset_or_doc.line_index()),
-        new_text: new_name.to_string(),
+        new_text: format!(
+          "{}{}{}",
+          location.prefix_text.as_deref().unwrap_or_default(),
+          new_name,
+          location.suffix_text.as_deref().unwrap_or_default()
+        ),
       }));
     }
 
@@ -1948,6 +1965,7 @@ fn parse_code_actions(
             update_import_statement(
               tc.as_text_edit(asset_or_doc.line_index()),
               data,
+              language_server.maybe_import_map.as_deref(),
             )
           }));
         } else {
@@ -2143,6 +2161,7 @@ struct CompletionEntryDataImport {
 fn update_import_statement(
   mut text_edit: lsp::TextEdit,
   item_data: &CompletionItemData,
+  maybe_import_map: Option<&ImportMap>,
 ) -> lsp::TextEdit {
   if let Some(data) = &item_data.data {
     if let Ok(import_data) =
@@ -2150,9 +2169,14 @@ fn update_import_statement(
     {
       if let Ok(import_specifier) = normalize_specifier(&import_data.file_name)
       {
-        if let Some(new_module_specifier) =
-          relative_specifier(&item_data.specifier, &import_specifier)
-        {
+        let maybe_new_module_specifier = maybe_import_map
+          .and_then(|import_map| {
+            to_import_map_specifier(import_map, &import_specifier)
+          })
+          .or_else(|| {
+            relative_specifier(&item_data.specifier, &import_specifier)
+          });
+        if let Some(new_module_specifier) = maybe_new_module_specifier {
           text_edit.new_text = text_edit
             .new_text
             .replace(&import_data.module_specifier, &new_module_specifier);
@@ -2737,11 +2761,16 @@ fn op_load(
   let mark = state.performance.mark("op_load", Some(&args));
   let specifier = state.normalize_specifier(args.specifier)?;
   let asset_or_document = state.get_asset_or_document(&specifier);
+  let maybe_translated = asset_or_document.as_ref().and_then(|doc| {
+    state
+      .state_snapshot
+      .translate_npm_cjs_module(&specifier, doc)
+  });
   state.performance.measure(mark);
   Ok(match asset_or_document {
     Some(doc) => {
       json!({
-        "data": doc.text(),
+        "data": maybe_translated.map(Into::into).unwrap_or_else(|| doc.text()),
         "scriptKind": crate::tsc::as_ts_script_kind(doc.media_type()),
         "version": state.script_version(&specifier),
       })
@@ -4338,6 +4367,7 @@ mod tests {
           new_text: orig_text.to_string(),
         },
         &item_data,
+        None,
       );
       assert_eq!(
         actual,
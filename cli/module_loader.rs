@@ -1,11 +1,18 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::args::write_lockfile;
 use crate::args::CliOptions;
 use crate::args::DenoSubcommand;
 use crate::args::TsTypeLib;
 use crate::args::TypeCheckMode;
+use crate::cache::CodeCache;
 use crate::cache::ParsedSourceCache;
+use crate::cache::RemoteBuildCache;
+use crate::crash_report;
 use crate::emit::Emitter;
+use crate::errors::get_error_class_name;
+use crate::file_fetcher::FileFetcher;
+use crate::graph_util::check_import_cycles;
 use crate::graph_util::graph_lock_or_exit;
 use crate::graph_util::graph_valid_with_cli_options;
 use crate::graph_util::ModuleGraphBuilder;
@@ -20,7 +27,10 @@ use crate::proc_state::ProcState;
 use crate::resolver::CliGraphResolver;
 use crate::tools::check;
 use crate::tools::check::TypeChecker;
+use crate::util::fs::canonicalize_path_maybe_not_exists;
+use crate::util::fs::to_extended_length_path;
 use crate::util::progress_bar::ProgressBar;
+use crate::util::telemetry;
 use crate::util::text_encoding::code_without_source_map;
 use crate::util::text_encoding::source_map_from_code;
 
@@ -36,6 +46,7 @@ use deno_core::parking_lot::Mutex;
 use deno_core::resolve_url;
 use deno_core::resolve_url_or_path;
 use deno_core::ModuleCode;
+use deno_core::ModuleCodeCache;
 use deno_core::ModuleLoader;
 use deno_core::ModuleSource;
 use deno_core::ModuleSpecifier;
@@ -43,25 +54,63 @@ use deno_core::ModuleType;
 use deno_core::OpState;
 use deno_core::ResolutionKind;
 use deno_core::SourceMapGetter;
+use deno_graph::source::LoadFuture;
+use deno_graph::source::LoadResponse;
+use deno_graph::source::Loader;
 use deno_graph::source::Resolver;
 use deno_graph::EsmModule;
 use deno_graph::JsonModule;
 use deno_graph::Module;
+use deno_graph::ModuleGraph;
 use deno_graph::Resolution;
 use deno_lockfile::Lockfile;
 use deno_runtime::deno_node::NodeResolutionMode;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_semver::npm::NpmPackageReqReference;
+use serde::Deserialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::str;
 use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Loads the remote modules from the disk cache only, leaving out the other
+/// modules, which tells if the remote modules of a graph are all cached.
+struct CachedRemoteModuleLoader<'a>(&'a FileFetcher);
+
+impl Loader for CachedRemoteModuleLoader<'_> {
+  fn load(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    _is_dynamic: bool,
+  ) -> LoadFuture {
+    let result = if matches!(specifier.scheme(), "http" | "https") {
+      self.0.fetch_cached(specifier, 10).map(|maybe_file| {
+        maybe_file.map(|file| LoadResponse::Module {
+          specifier: file.specifier,
+          maybe_headers: file.maybe_headers,
+          content: file.source,
+        })
+      })
+    } else {
+      Ok(Some(LoadResponse::External {
+        specifier: specifier.clone(),
+      }))
+    };
+    Box::pin(deno_core::futures::future::ready(result))
+  }
+}
 
 pub struct ModuleLoadPreparer {
   options: Arc<CliOptions>,
+  emitter: Arc<Emitter>,
+  file_fetcher: Arc<FileFetcher>,
   graph_container: Arc<ModuleGraphContainer>,
   lockfile: Option<Arc<Mutex<Lockfile>>>,
   maybe_file_watcher_reporter: Option<FileWatcherReporter>,
@@ -70,12 +119,15 @@ pub struct ModuleLoadPreparer {
   progress_bar: ProgressBar,
   resolver: Arc<CliGraphResolver>,
   type_checker: Arc<TypeChecker>,
+  maybe_remote_build_cache: Option<Arc<RemoteBuildCache>>,
 }
 
 impl ModuleLoadPreparer {
   #[allow(clippy::too_many_arguments)]
   pub fn new(
     options: Arc<CliOptions>,
+    emitter: Arc<Emitter>,
+    file_fetcher: Arc<FileFetcher>,
     graph_container: Arc<ModuleGraphContainer>,
     lockfile: Option<Arc<Mutex<Lockfile>>>,
     maybe_file_watcher_reporter: Option<FileWatcherReporter>,
@@ -84,9 +136,12 @@ impl ModuleLoadPreparer {
     progress_bar: ProgressBar,
     resolver: Arc<CliGraphResolver>,
     type_checker: Arc<TypeChecker>,
+    maybe_remote_build_cache: Option<Arc<RemoteBuildCache>>,
   ) -> Self {
     Self {
       options,
+      emitter,
+      file_fetcher,
       graph_container,
       lockfile,
       maybe_file_watcher_reporter,
@@ -95,6 +150,7 @@ impl ModuleLoadPreparer {
       progress_bar,
       resolver,
       type_checker,
+      maybe_remote_build_cache,
     }
   }
 
@@ -113,6 +169,15 @@ impl ModuleLoadPreparer {
   ) -> Result<(), AnyError> {
     log::debug!("Preparing module load.");
     let _pb_clear_guard = self.progress_bar.clear_guard();
+    let mut span = telemetry::span("prepare_module_load");
+    span.set_attribute("modules.roots", roots.len());
+    span.set_attribute("modules.dynamic", is_dynamic);
+
+    if !is_dynamic {
+      self
+        .fetch_eszip_prebundles(&roots, root_permissions.clone())
+        .await;
+    }
 
     let mut cache = self
       .module_graph_builder
@@ -157,14 +222,25 @@ impl ModuleLoadPreparer {
       .await?;
 
     graph_valid_with_cli_options(graph, &roots, &self.options)?;
+    // the dynamically imported modules are part of the top level graph that
+    // was already checked for cycles
+    if !is_dynamic {
+      check_import_cycles(graph, &self.options, &reload_exclusions)?;
+    }
 
     // If there is a lockfile...
     if let Some(lockfile) = &self.lockfile {
-      let mut lockfile = lockfile.lock();
-      // validate the integrity of all the modules
-      graph_lock_or_exit(graph, &mut lockfile);
+      {
+        let mut lockfile = lockfile.lock();
+        // validate the integrity of all the modules
+        graph_lock_or_exit(graph, &mut lockfile);
+      }
       // update it with anything new
-      lockfile.write()?;
+      write_lockfile(lockfile, self.options.frozen()).await?;
+    } else if self.options.frozen() && !graph.npm_packages.is_empty() {
+      return Err(generic_error(
+        "npm packages can't be resolved without a lockfile when --frozen is specified.",
+      ));
     }
 
     // save the graph and get a reference to the new graph
@@ -172,31 +248,167 @@ impl ModuleLoadPreparer {
 
     drop(_pb_clear_guard);
 
-    // type check if necessary
-    if self.options.type_check_mode() != TypeCheckMode::None
-      && !self.graph_container.is_type_checked(&roots, lib)
-    {
-      let graph = Arc::new(graph.segment(&roots));
+    // type check if necessary, separately for the entry points whose type
+    // library is overridden in the configuration file
+    if self.options.type_check_mode() != TypeCheckMode::None {
+      for (lib, roots) in self.options.group_roots_by_ts_type_lib(&roots, &lib)
+      {
+        if self.graph_container.is_type_checked(&roots, &lib) {
+          continue;
+        }
+        let graph = Arc::new(graph.segment(&roots));
+        self
+          .type_checker
+          .check(
+            graph,
+            check::CheckOptions {
+              lib: lib.clone(),
+              log_ignored_options: false,
+              reload: self.options.reload_flag()
+                && !roots.iter().all(|r| reload_exclusions.contains(r)),
+            },
+          )
+          .await?;
+        self.graph_container.set_type_checked(&roots, lib);
+      }
+    }
+
+    // the emits are shared with the remote cache ahead of loading the modules
+    if let Some(remote_build_cache) = &self.maybe_remote_build_cache {
       self
-        .type_checker
-        .check(
-          graph,
-          check::CheckOptions {
-            lib,
-            log_ignored_options: false,
-            reload: self.options.reload_flag()
-              && !roots.iter().all(|r| reload_exclusions.contains(r)),
-          },
-        )
+        .emitter
+        .sync_remote_emits(&graph, remote_build_cache, self.lockfile.as_deref())
         .await?;
-      self.graph_container.set_type_checked(&roots, lib);
     }
 
+    self.graph_container.set_prepared(&roots);
+
     log::debug!("Prepared module load.");
 
     Ok(())
   }
 
+  /// Gets if the roots were already prepared and type checked under `lib`,
+  /// in which case preparing them again would not load or check anything.
+  pub fn is_prepared(
+    &self,
+    roots: &[ModuleSpecifier],
+    lib: &TsTypeLib,
+  ) -> bool {
+    if !self.graph_container.is_prepared(roots) {
+      return false;
+    }
+    self.options.type_check_mode() == TypeCheckMode::None
+      || self
+        .options
+        .group_roots_by_ts_type_lib(roots, lib)
+        .iter()
+        .all(|(lib, roots)| self.graph_container.is_type_checked(roots, lib))
+  }
+
+  /// Prepares modules that web workers use as their entrypoint, so that the
+  /// workers spawned afterwards don't need to prepare them again.
+  pub async fn prepare_worker_modules(
+    &self,
+    roots: Vec<ModuleSpecifier>,
+    permissions: PermissionsContainer,
+  ) -> Result<(), AnyError> {
+    let lib = self.options.ts_type_lib_worker();
+    self
+      .prepare_module_load(roots, false, lib, permissions.clone(), permissions)
+      .await
+  }
+
+  /// Populates the caches with the modules of the pre-bundles advertised for
+  /// the remote roots that are not in the module graph or the disk cache yet,
+  /// falling back to fetching the modules one by one when a pre-bundle can't
+  /// be used.
+  async fn fetch_eszip_prebundles(
+    &self,
+    roots: &[ModuleSpecifier],
+    permissions: PermissionsContainer,
+  ) {
+    let graph = self.graph_container.graph();
+    for root in roots {
+      if !matches!(root.scheme(), "http" | "https")
+        || graph.get(root).is_some()
+        || self.is_remote_graph_cached(root).await
+      {
+        continue;
+      }
+      let result = self
+        .file_fetcher
+        .fetch_eszip_prebundle(
+          root,
+          permissions.clone(),
+          self.lockfile.as_deref(),
+        )
+        .await;
+      match result {
+        Ok(0) => {}
+        Ok(count) => {
+          log::debug!("Loaded {} modules from the pre-bundle of {}", count, root)
+        }
+        Err(err) => log::debug!(
+          "Failed to load the pre-bundle of {}, fetching its modules instead: {:#}",
+          root,
+          err
+        ),
+      }
+    }
+  }
+
+  /// Gets if the remote modules of the graph of a root are all in the disk
+  /// cache, so that its pre-bundle doesn't need to be fetched.
+  async fn is_remote_graph_cached(&self, root: &ModuleSpecifier) -> bool {
+    let mut loader = CachedRemoteModuleLoader(&self.file_fetcher);
+    let analyzer = self.parsed_source_cache.as_analyzer();
+    let mut graph = ModuleGraph::default();
+    graph
+      .build(
+        vec![root.clone()],
+        &mut loader,
+        deno_graph::BuildOptions {
+          is_dynamic: false,
+          imports: Vec::new(),
+          resolver: Some(self.resolver.as_graph_resolver()),
+          npm_resolver: None,
+          module_analyzer: Some(&*analyzer),
+          reporter: None,
+        },
+      )
+      .await;
+    let mut errors = graph
+      .walk(
+        &[root.clone()],
+        deno_graph::WalkOptions {
+          check_js: true,
+          follow_type_only: true,
+          follow_dynamic: false,
+        },
+      )
+      .errors();
+    errors.next().is_none()
+  }
+
+  /// Removes the provided modules and their dependents from the module graph
+  /// and the in-memory caches, so that the next time they're prepared their
+  /// current source is used. Returns the removed specifiers.
+  pub async fn invalidate_modules(
+    &self,
+    specifiers: &[ModuleSpecifier],
+  ) -> Vec<ModuleSpecifier> {
+    let invalidated = self.graph_container.invalidate(specifiers).await;
+    let mut invalidated = invalidated.into_iter().collect::<Vec<_>>();
+    invalidated.sort();
+    for specifier in specifiers.iter().chain(invalidated.iter()) {
+      self.file_fetcher.remove_cached(specifier);
+      self.parsed_source_cache.free(specifier);
+      self.emitter.free(specifier);
+    }
+    invalidated
+  }
+
   /// Helper around prepare_module_load that loads and type checks
   /// the provided files.
   pub async fn load_and_type_check_files(
@@ -236,15 +448,35 @@ pub struct CliModuleLoader {
   /// Permissions used to resolve dynamic imports, these get passed as
   /// "root permissions" for Web Worker.
   dynamic_permissions: PermissionsContainer,
+  /// When set, local modules outside this directory require read permission.
+  maybe_local_import_root: Option<PathBuf>,
+  /// Skips preparing the root module when it was already prepared, which is
+  /// the case for web workers whose entrypoint was preloaded or spawned
+  /// before.
+  skip_prepared_roots: bool,
   cli_options: Arc<CliOptions>,
   cjs_resolutions: Arc<CjsResolutionStore>,
+  code_cache: CodeCache,
+  /// The code hashes of the modules that requested a V8 code cache or whose
+  /// code cache may be rejected by V8, which are used to store the new code
+  /// cache once it's created.
+  code_cache_hashes: RefCell<HashMap<ModuleSpecifier, u64>>,
   emitter: Arc<Emitter>,
+  file_fetcher: Arc<FileFetcher>,
   graph_container: Arc<ModuleGraphContainer>,
   module_load_preparer: Arc<ModuleLoadPreparer>,
   node_code_translator: Arc<NodeCodeTranslator>,
   node_resolver: Arc<CliNodeResolver>,
   parsed_source_cache: Arc<ParsedSourceCache>,
   resolver: Arc<CliGraphResolver>,
+  /// `data:` and `blob:` modules that were loaded without being in the
+  /// module graph (ex. blob URLs created at runtime by frameworks).
+  runtime_modules: Rc<RefCell<HashMap<ModuleSpecifier, RuntimeModule>>>,
+}
+
+struct RuntimeModule {
+  source: Arc<str>,
+  media_type: MediaType,
 }
 
 impl CliModuleLoader {
@@ -253,20 +485,13 @@ impl CliModuleLoader {
     root_permissions: PermissionsContainer,
     dynamic_permissions: PermissionsContainer,
   ) -> Rc<Self> {
-    Rc::new(CliModuleLoader {
-      lib: ps.options.ts_type_lib_window(),
+    Rc::new(Self::from_proc_state(
+      ps.options.ts_type_lib_window(),
+      false,
+      ps,
       root_permissions,
       dynamic_permissions,
-      cli_options: ps.options.clone(),
-      cjs_resolutions: ps.cjs_resolutions.clone(),
-      emitter: ps.emitter.clone(),
-      graph_container: ps.graph_container.clone(),
-      module_load_preparer: ps.module_load_preparer.clone(),
-      node_code_translator: ps.node_code_translator.clone(),
-      node_resolver: ps.node_resolver.clone(),
-      parsed_source_cache: ps.parsed_source_cache.clone(),
-      resolver: ps.resolver.clone(),
-    })
+    ))
   }
 
   pub fn new_for_worker(
@@ -274,20 +499,42 @@ impl CliModuleLoader {
     root_permissions: PermissionsContainer,
     dynamic_permissions: PermissionsContainer,
   ) -> Rc<Self> {
-    Rc::new(CliModuleLoader {
-      lib: ps.options.ts_type_lib_worker(),
+    Rc::new(Self::from_proc_state(
+      ps.options.ts_type_lib_worker(),
+      true,
+      ps,
+      root_permissions,
+      dynamic_permissions,
+    ))
+  }
+
+  fn from_proc_state(
+    lib: TsTypeLib,
+    skip_prepared_roots: bool,
+    ps: ProcState,
+    root_permissions: PermissionsContainer,
+    dynamic_permissions: PermissionsContainer,
+  ) -> Self {
+    CliModuleLoader {
+      lib,
       root_permissions,
       dynamic_permissions,
+      maybe_local_import_root: ps.options.maybe_local_import_root(),
+      skip_prepared_roots,
       cli_options: ps.options.clone(),
       cjs_resolutions: ps.cjs_resolutions.clone(),
+      code_cache: ps.code_cache.clone(),
+      code_cache_hashes: Default::default(),
       emitter: ps.emitter.clone(),
+      file_fetcher: ps.file_fetcher.clone(),
       graph_container: ps.graph_container.clone(),
       module_load_preparer: ps.module_load_preparer.clone(),
       node_code_translator: ps.node_code_translator.clone(),
       node_resolver: ps.node_resolver.clone(),
       parsed_source_cache: ps.parsed_source_cache.clone(),
       resolver: ps.resolver.clone(),
-    })
+      runtime_modules: Default::default(),
+    }
   }
 
   fn load_prepared_module(
@@ -295,11 +542,19 @@ impl CliModuleLoader {
     specifier: &ModuleSpecifier,
     maybe_referrer: Option<&ModuleSpecifier>,
   ) -> Result<ModuleCodeSource, AnyError> {
+    let graph = self.graph_container.graph();
+    let referrer_chain = || get_referrer_chain(&graph, maybe_referrer);
     if specifier.scheme() == "node" {
-      unreachable!(); // Node built-in modules should be handled internally.
+      // Node built-in modules should be handled internally.
+      return Err(
+        LoadPreparedModuleError::NodeBuiltIn {
+          specifier: specifier.clone(),
+          referrer_chain: referrer_chain(),
+        }
+        .into(),
+      );
     }
 
-    let graph = self.graph_container.graph();
     match graph.get(specifier) {
       Some(deno_graph::Module::Json(JsonModule {
         source,
@@ -317,29 +572,13 @@ impl CliModuleLoader {
         specifier,
         ..
       })) => {
-        let code: ModuleCode = match media_type {
-          MediaType::JavaScript
-          | MediaType::Unknown
-          | MediaType::Cjs
-          | MediaType::Mjs
-          | MediaType::Json => source.clone().into(),
-          MediaType::Dts | MediaType::Dcts | MediaType::Dmts => {
-            Default::default()
-          }
-          MediaType::TypeScript
-          | MediaType::Mts
-          | MediaType::Cts
-          | MediaType::Jsx
-          | MediaType::Tsx => {
-            // get emit text
-            self
-              .emitter
-              .emit_parsed_source(specifier, *media_type, source)?
-          }
-          MediaType::TsBuildInfo | MediaType::Wasm | MediaType::SourceMap => {
-            panic!("Unexpected media type {media_type} for {specifier}")
-          }
-        };
+        let code = esm_module_code(
+          &self.emitter,
+          specifier,
+          *media_type,
+          source,
+          referrer_chain,
+        )?;
 
         // at this point, we no longer need the parsed source in memory, so free it
         self.parsed_source_cache.free(specifier);
@@ -351,6 +590,19 @@ impl CliModuleLoader {
         })
       }
       _ => {
+        if let Some(module) = self.runtime_modules.borrow().get(specifier) {
+          return Ok(ModuleCodeSource {
+            code: esm_module_code(
+              &self.emitter,
+              specifier,
+              module.media_type,
+              &module.source,
+              referrer_chain,
+            )?,
+            found_url: specifier.clone(),
+            media_type: module.media_type,
+          });
+        }
         let mut msg = format!("Loading unprepared module: {specifier}");
         if let Some(referrer) = maybe_referrer {
           msg = format!("{}, imported from: {}", msg, referrer.as_str());
@@ -360,15 +612,74 @@ impl CliModuleLoader {
     }
   }
 
+  /// Ensures the worker has read permission for local modules outside of
+  /// the project root when running with `--restrict-local-imports`.
+  fn check_local_import_root(
+    &self,
+    specifier: &ModuleSpecifier,
+    maybe_referrer: Option<&ModuleSpecifier>,
+  ) -> Result<(), AnyError> {
+    let root = match &self.maybe_local_import_root {
+      Some(root) => root,
+      None => return Ok(()),
+    };
+    if specifier.scheme() != "file"
+      || self.node_resolver.in_npm_package(specifier)
+    {
+      return Ok(());
+    }
+    let path = match specifier.to_file_path() {
+      Ok(path) => path,
+      Err(_) => return Ok(()),
+    };
+    // compare the real paths, so that a symlink in the root can't point
+    // outside of it
+    let path = canonicalize_path_maybe_not_exists(&path).unwrap_or(path);
+    if path.starts_with(root) {
+      return Ok(());
+    }
+    // the root permissions are "allow all" for the main worker, so check
+    // against the permissions the worker was actually granted
+    self
+      .dynamic_permissions
+      .clone()
+      .check_read(&path, "import")
+      .with_context(|| {
+        let mut msg = format!(
+          "Import of \"{}\" escapes the project root \"{}\"",
+          path.display(),
+          root.display(),
+        );
+        if let Some(referrer) = maybe_referrer {
+          msg.push_str(&format!(" (imported from {referrer})"));
+        }
+        msg
+      })
+  }
+
   fn load_sync(
     &self,
     specifier: &ModuleSpecifier,
     maybe_referrer: Option<&ModuleSpecifier>,
     is_dynamic: bool,
   ) -> Result<ModuleSource, AnyError> {
-    let code_source = if self.node_resolver.in_npm_package(specifier) {
+    self.check_local_import_root(specifier, maybe_referrer)?;
+    let code_source = if self.node_resolver.in_npm_package(specifier)
+      && node::is_native_addon_specifier(specifier)
+    {
+      // Node-API addons are loaded with `require` when the module is evaluated
+      ModuleCodeSource {
+        code: self
+          .node_code_translator
+          .translate_native_addon_to_esm(specifier)
+          .into(),
+        found_url: specifier.clone(),
+        media_type: MediaType::Cjs,
+      }
+    } else if self.node_resolver.in_npm_package(specifier) {
       let file_path = specifier.to_file_path().unwrap();
-      let code = std::fs::read_to_string(&file_path).with_context(|| {
+      let read_path = to_extended_length_path(&file_path);
+      let code = std::fs::read_to_string(&read_path).with_context(|| {
         let mut msg = "Unable to load ".to_string();
         msg.push_str(&file_path.to_string_lossy());
         if let Some(referrer) = &maybe_referrer {
@@ -405,24 +716,84 @@ impl CliModuleLoader {
     } else {
       self.load_prepared_module(specifier, maybe_referrer)?
     };
-    let code = if self.cli_options.is_inspecting() {
-      // we need the code with the source map in order for
-      // it to work with --inspect or --inspect-brk
-      code_source.code
-    } else {
-      // reduce memory and throw away the source map
-      // because we don't need it
-      code_without_source_map(code_source.code)
-    };
-    Ok(ModuleSource::new_with_redirect(
-      match code_source.media_type {
-        MediaType::Json => ModuleType::Json,
-        _ => ModuleType::JavaScript,
-      },
-      code,
+    let found_url = code_source.found_url.clone();
+    let module_source = into_module_source(
+      code_source,
       specifier,
-      &code_source.found_url,
-    ))
+      self.cli_options.is_inspecting(),
+    );
+    Ok(self.with_code_cache(module_source, &found_url))
+  }
+
+  /// Attaches the V8 code cache of an emitted module to its source, or
+  /// requests one to be created when there's none for the current code.
+  fn with_code_cache(
+    &self,
+    module_source: ModuleSource,
+    specifier: &ModuleSpecifier,
+  ) -> ModuleSource {
+    if module_source.module_type != ModuleType::JavaScript
+      || !matches!(
+        self.graph_container.graph().get(specifier),
+        Some(Module::Esm(_))
+      )
+    {
+      return module_source;
+    }
+    let code_hash = self
+      .code_cache
+      .compute_code_hash(module_source.code.as_bytes());
+    // a new code cache is also created when V8 rejects the cached one, which
+    // then overwrites it
+    self
+      .code_cache_hashes
+      .borrow_mut()
+      .insert(specifier.clone(), code_hash);
+    match self.code_cache.get_code_cache(specifier, code_hash) {
+      Some(data) => module_source.with_code_cache(ModuleCodeCache::Data(data)),
+      None => module_source.with_code_cache(ModuleCodeCache::Requested),
+    }
+  }
+
+  /// Fetches a `data:` or `blob:` module that's not in the module graph,
+  /// which happens when it was created after the graph was built.
+  fn load_runtime_module(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Pin<Box<deno_core::ModuleSourceFuture>> {
+    let specifier = specifier.clone();
+    let file_fetcher = self.file_fetcher.clone();
+    let emitter = self.emitter.clone();
+    let runtime_modules = self.runtime_modules.clone();
+    let permissions = self.dynamic_permissions.clone();
+    let is_inspecting = self.cli_options.is_inspecting();
+    async move {
+      let file = file_fetcher.fetch(&specifier, permissions).await?;
+      let code = esm_module_code(
+        &emitter,
+        &specifier,
+        file.media_type,
+        &file.source,
+        Vec::new,
+      )?;
+      runtime_modules.borrow_mut().insert(
+        specifier.clone(),
+        RuntimeModule {
+          source: file.source,
+          media_type: file.media_type,
+        },
+      );
+      Ok(into_module_source(
+        ModuleCodeSource {
+          code,
+          found_url: specifier.clone(),
+          media_type: file.media_type,
+        },
+        &specifier,
+        is_inspecting,
+      ))
+    }
+    .boxed_local()
   }
 
   fn handle_node_resolve_result(
@@ -532,7 +903,7 @@ impl ModuleLoader for CliModuleLoader {
     let is_repl =
       matches!(self.cli_options.sub_command(), DenoSubcommand::Repl(_));
     let referrer = if referrer.is_empty() && is_repl {
-      deno_core::resolve_path("./$deno$repl.ts", &cwd)?
+      crate::tools::repl::resolve_repl_referrer(&cwd)?
     } else {
       referrer_result?
     };
@@ -573,6 +944,14 @@ impl ModuleLoader for CliModuleLoader {
     maybe_referrer: Option<&ModuleSpecifier>,
     is_dynamic: bool,
   ) -> Pin<Box<deno_core::ModuleSourceFuture>> {
+    crash_report::set_last_loaded_module(specifier);
+    if matches!(specifier.scheme(), "data" | "blob")
+      && self.graph_container.graph().get(specifier).is_none()
+      && !self.runtime_modules.borrow().contains_key(specifier)
+    {
+      return self.load_runtime_module(specifier);
+    }
+
     // NOTE: this block is async only because of `deno_core` interface
     // requirements; module was already loaded when constructing module graph
     // during call to `prepare_load` so we can load it synchronously.
@@ -585,7 +964,7 @@ impl ModuleLoader for CliModuleLoader {
 
   fn prepare_load(
     &self,
-    _op_state: Rc<RefCell<OpState>>,
+    op_state: Rc<RefCell<OpState>>,
     specifier: &ModuleSpecifier,
     _maybe_referrer: Option<String>,
     is_dynamic: bool,
@@ -594,6 +973,15 @@ impl ModuleLoader for CliModuleLoader {
       // nothing to prepare
       return Box::pin(deno_core::futures::future::ready(Ok(())));
     }
+    if self.skip_prepared_roots
+      && !is_dynamic
+      && self
+        .module_load_preparer
+        .is_prepared(std::slice::from_ref(specifier), &self.lib)
+    {
+      // the module graph already has the whole subgraph of this module
+      return Box::pin(deno_core::futures::future::ready(Ok(())));
+    }
 
     let specifier = specifier.clone();
     let module_load_preparer = self.module_load_preparer.clone();
@@ -604,21 +992,241 @@ impl ModuleLoader for CliModuleLoader {
     } else {
       self.root_permissions.clone()
     };
-    let lib = self.lib;
+    let lib = self.lib.clone();
+    let maybe_retry_policy = if is_dynamic {
+      op_state
+        .borrow()
+        .try_borrow::<DynamicImportRetryPolicy>()
+        .cloned()
+    } else {
+      None
+    };
 
     async move {
-      module_load_preparer
-        .prepare_module_load(
-          vec![specifier],
-          is_dynamic,
-          lib,
-          root_permissions,
-          dynamic_permissions,
-        )
-        .await
+      let mut attempt = 1;
+      loop {
+        let result = module_load_preparer
+          .prepare_module_load(
+            vec![specifier.clone()],
+            is_dynamic,
+            lib.clone(),
+            root_permissions.clone(),
+            dynamic_permissions.clone(),
+          )
+          .await;
+        match (result, &maybe_retry_policy) {
+          (Err(err), Some(policy))
+            if attempt < policy.max_attempts && is_transient_error(&err) =>
+          {
+            let delay = policy.delay_for_attempt(attempt);
+            log::debug!(
+              "Retrying dynamic import of {} in {}ms after error: {}",
+              specifier,
+              delay.as_millis(),
+              err
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+          }
+          (result, _) => return result,
+        }
+      }
     }
     .boxed_local()
   }
+
+  fn code_cache_ready(&self, specifier: ModuleSpecifier, code_cache: Vec<u8>) {
+    let code_hash = match self.code_cache_hashes.borrow_mut().remove(&specifier)
+    {
+      Some(code_hash) => code_hash,
+      None => return,
+    };
+    // write it in the background so it doesn't delay the program
+    let cache = self.code_cache.clone();
+    tokio::task::spawn_blocking(move || {
+      cache.set_code_cache(&specifier, code_hash, &code_cache);
+    });
+  }
+}
+
+/// How dynamic imports that failed because of a network error are retried,
+/// which is set with the unstable `Deno.setDynamicImportRetryPolicy()`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicImportRetryPolicy {
+  /// The total number of attempts, including the first one.
+  pub max_attempts: u32,
+  /// The delay before the first retry in milliseconds, which doubles
+  /// for every following retry.
+  pub initial_delay: u64,
+  /// The maximum delay between retries in milliseconds.
+  pub max_delay: u64,
+}
+
+impl DynamicImportRetryPolicy {
+  fn delay_for_attempt(&self, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(
+      self
+        .initial_delay
+        .saturating_mul(factor)
+        .min(self.max_delay),
+    )
+  }
+}
+
+/// Gets if the error was caused by a network failure that might not
+/// happen again, as opposed to a missing or invalid module.
+fn is_transient_error(err: &AnyError) -> bool {
+  matches!(
+    get_error_class_name(err),
+    "Http"
+      | "TimedOut"
+      | "ConnectionRefused"
+      | "ConnectionReset"
+      | "ConnectionAborted"
+      | "BrokenPipe"
+      | "Interrupted"
+  )
+}
+
+/// An error loading a module from the module graph, which happens when the
+/// graph is malformed, for example when it was built by an embedder.
+#[derive(Debug, Error)]
+pub enum LoadPreparedModuleError {
+  #[error(
+    "Node built-in module \"{specifier}\" was not handled internally{}",
+    format_referrer_chain(.referrer_chain)
+  )]
+  NodeBuiltIn {
+    specifier: ModuleSpecifier,
+    referrer_chain: Vec<ModuleSpecifier>,
+  },
+  #[error(
+    "Unexpected media type {media_type} for \"{specifier}\"{}",
+    format_referrer_chain(.referrer_chain)
+  )]
+  UnexpectedMediaType {
+    specifier: ModuleSpecifier,
+    media_type: MediaType,
+    referrer_chain: Vec<ModuleSpecifier>,
+  },
+}
+
+fn format_referrer_chain(referrer_chain: &[ModuleSpecifier]) -> String {
+  referrer_chain
+    .iter()
+    .map(|referrer| format!("\n    imported from \"{referrer}\""))
+    .collect()
+}
+
+/// Gets the referrer followed by the modules that transitively import it,
+/// walking up the module graph until a root or a cycle is reached.
+fn get_referrer_chain(
+  graph: &ModuleGraph,
+  maybe_referrer: Option<&ModuleSpecifier>,
+) -> Vec<ModuleSpecifier> {
+  let referrer = match maybe_referrer {
+    Some(referrer) => referrer,
+    None => return Vec::new(),
+  };
+  // the first module importing each module, collected in a single pass over
+  // the graph instead of searching it for each module of the chain
+  let mut importers: HashMap<ModuleSpecifier, &ModuleSpecifier> =
+    HashMap::new();
+  for module in graph.modules() {
+    if let Module::Esm(module) = module {
+      for dep in module.dependencies.values() {
+        let dep_specifiers = [
+          dep.maybe_code.maybe_specifier(),
+          dep.maybe_type.maybe_specifier(),
+        ];
+        for dep_specifier in dep_specifiers.into_iter().flatten() {
+          importers
+            .entry(graph.resolve(dep_specifier).clone())
+            .or_insert(&module.specifier);
+        }
+      }
+    }
+  }
+
+  let mut chain = Vec::new();
+  let mut seen = HashSet::new();
+  let mut maybe_current = Some(referrer);
+  while let Some(current) = maybe_current.take() {
+    if !seen.insert(current) {
+      break;
+    }
+    maybe_current = importers.get(current).copied();
+    chain.push(current.clone());
+  }
+  chain
+}
+
+/// Gets the code to execute for an ES module, which is emitted when
+/// necessary.
+fn esm_module_code(
+  emitter: &Emitter,
+  specifier: &ModuleSpecifier,
+  media_type: MediaType,
+  source: &Arc<str>,
+  referrer_chain: impl FnOnce() -> Vec<ModuleSpecifier>,
+) -> Result<ModuleCode, AnyError> {
+  let code = match media_type {
+    MediaType::JavaScript | MediaType::Mjs if emitter.is_minifying() => {
+      emitter.emit_parsed_source(specifier, media_type, source)?
+    }
+    MediaType::JavaScript
+    | MediaType::Unknown
+    | MediaType::Cjs
+    | MediaType::Mjs
+    | MediaType::Json => source.clone().into(),
+    MediaType::Dts | MediaType::Dcts | MediaType::Dmts => Default::default(),
+    MediaType::TypeScript
+    | MediaType::Mts
+    | MediaType::Cts
+    | MediaType::Jsx
+    | MediaType::Tsx => {
+      // get emit text
+      emitter.emit_parsed_source(specifier, media_type, source)?
+    }
+    MediaType::TsBuildInfo | MediaType::Wasm | MediaType::SourceMap => {
+      return Err(
+        LoadPreparedModuleError::UnexpectedMediaType {
+          specifier: specifier.clone(),
+          media_type,
+          referrer_chain: referrer_chain(),
+        }
+        .into(),
+      );
+    }
+  };
+  Ok(code)
+}
+
+fn into_module_source(
+  code_source: ModuleCodeSource,
+  specifier: &ModuleSpecifier,
+  is_inspecting: bool,
+) -> ModuleSource {
+  let code = if is_inspecting {
+    // we need the code with the source map in order for
+    // it to work with --inspect or --inspect-brk
+    code_source.code
+  } else {
+    // reduce memory and throw away the source map
+    // because we don't need it
+    code_without_source_map(code_source.code)
+  };
+  ModuleSource::new_with_redirect(
+    match code_source.media_type {
+      MediaType::Json => ModuleType::Json,
+      _ => ModuleType::JavaScript,
+    },
+    code,
+    specifier,
+    &code_source.found_url,
+  )
 }
 
 impl SourceMapGetter for CliModuleLoader {
@@ -639,11 +1247,17 @@ impl SourceMapGetter for CliModuleLoader {
     file_name: &str,
     line_number: usize,
   ) -> Option<String> {
+    let specifier = resolve_url(file_name).ok()?;
     let graph = self.graph_container.graph();
-    let code = match graph.get(&resolve_url(file_name).ok()?) {
-      Some(deno_graph::Module::Esm(module)) => &module.source,
-      Some(deno_graph::Module::Json(module)) => &module.source,
-      _ => return None,
+    let code = match graph.get(&specifier) {
+      Some(deno_graph::Module::Esm(module)) => module.source.clone(),
+      Some(deno_graph::Module::Json(module)) => module.source.clone(),
+      _ => self
+        .runtime_modules
+        .borrow()
+        .get(&specifier)?
+        .source
+        .clone(),
     };
     // Do NOT use .lines(): it skips the terminating empty line.
     // (due to internally using_terminator() instead of .split())
@@ -658,3 +1272,60 @@ impl SourceMapGetter for CliModuleLoader {
     }
   }
 }
+
+#[cfg(test)]
+mod test {
+  use deno_graph::source::MemoryLoader;
+  use deno_graph::source::Source;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn referrer_chain() {
+    let sources = [
+      ("file:///mod.ts", "import './a.ts';"),
+      ("file:///a.ts", "import './b.ts';"),
+      ("file:///b.ts", "import './c.ts';\nimport './a.ts';"),
+      ("file:///c.ts", "export {};"),
+    ];
+    let mut loader = MemoryLoader::new(
+      sources
+        .iter()
+        .map(|(specifier, content)| {
+          (
+            specifier.to_string(),
+            Source::Module {
+              specifier: specifier.to_string(),
+              content: content.to_string(),
+              maybe_headers: None,
+            },
+          )
+        })
+        .collect(),
+      Vec::new(),
+    );
+    let mut graph = ModuleGraph::default();
+    graph
+      .build(
+        vec![ModuleSpecifier::parse("file:///mod.ts").unwrap()],
+        &mut loader,
+        Default::default(),
+      )
+      .await;
+    let referrer_chain = |specifier: &str| {
+      let specifier = ModuleSpecifier::parse(specifier).unwrap();
+      get_referrer_chain(&graph, Some(&specifier))
+        .iter()
+        .map(|specifier| specifier.to_string())
+        .collect::<Vec<_>>()
+    };
+
+    // the cycle between a.ts and b.ts ends the chain
+    assert_eq!(
+      referrer_chain("file:///c.ts"),
+      vec!["file:///c.ts", "file:///b.ts", "file:///a.ts"]
+    );
+    assert_eq!(referrer_chain("file:///mod.ts"), vec!["file:///mod.ts"]);
+    assert!(get_referrer_chain(&graph, None).is_empty());
+  }
+}
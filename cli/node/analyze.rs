@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::fmt::Write;
@@ -17,6 +18,7 @@ use deno_ast::ParsedSource;
 use deno_ast::SourceRanged;
 use deno_core::anyhow::anyhow;
 use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
 use deno_runtime::deno_node::package_exports_resolve;
 use deno_runtime::deno_node::NodeModuleKind;
 use deno_runtime::deno_node::NodePermissions;
@@ -32,6 +34,8 @@ use crate::cache::NodeAnalysisCache;
 use crate::file_fetcher::FileFetcher;
 use crate::npm::NpmPackageResolver;
 
+use super::is_native_addon_specifier;
+
 static NODE_GLOBALS: &[&str] = &[
   "Buffer",
   "clearImmediate",
@@ -49,6 +53,21 @@ pub struct NodeCodeTranslator {
   analysis_cache: NodeAnalysisCache,
   file_fetcher: Arc<FileFetcher>,
   npm_resolver: Arc<NpmPackageResolver>,
+  /// Translations done in this process keyed by specifier and source hash.
+  /// This prevents every worker (ex. each `deno test` module) from translating
+  /// the same shared npm dependencies again.
+  translated_cache: Mutex<HashMap<ModuleSpecifier, (String, Arc<str>)>>,
+}
+
+impl std::fmt::Debug for NodeCodeTranslator {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("NodeCodeTranslator")
+      .field("analysis_cache", &"<omitted>")
+      .field("file_fetcher", &self.file_fetcher)
+      .field("npm_resolver", &self.npm_resolver)
+      .field("translated_cache", &"<omitted>")
+      .finish()
+  }
 }
 
 impl NodeCodeTranslator {
@@ -61,6 +80,7 @@ impl NodeCodeTranslator {
       analysis_cache,
       file_fetcher,
       npm_resolver,
+      translated_cache: Default::default(),
     }
   }
 
@@ -84,15 +104,49 @@ impl NodeCodeTranslator {
     code: String,
     media_type: MediaType,
     permissions: &mut dyn NodePermissions,
+  ) -> Result<String, AnyError> {
+    // check the permissions before the cache is used, since a translation
+    // cached by another worker might have been done with other permissions
+    if let Ok(path) = specifier.to_file_path() {
+      self
+        .npm_resolver
+        .as_require_npm_resolver()
+        .ensure_read_permission(permissions, &path)?;
+    }
+
+    let source_hash = NodeAnalysisCache::compute_source_hash(&code);
+    if let Some((cached_hash, translated)) =
+      self.translated_cache.lock().get(specifier)
+    {
+      if *cached_hash == source_hash {
+        return Ok(translated.to_string());
+      }
+    }
+
+    let translated_source = self.translate_cjs_to_esm_uncached(
+      specifier,
+      code,
+      media_type,
+      permissions,
+    )?;
+    self.translated_cache.lock().insert(
+      specifier.clone(),
+      (source_hash, translated_source.as_str().into()),
+    );
+    Ok(translated_source)
+  }
+
+  fn translate_cjs_to_esm_uncached(
+    &self,
+    specifier: &ModuleSpecifier,
+    code: String,
+    media_type: MediaType,
+    permissions: &mut dyn NodePermissions,
   ) -> Result<String, AnyError> {
     let mut temp_var_count = 0;
     let mut handled_reexports: HashSet<String> = HashSet::default();
 
-    let mut source = vec![
-      r#"import {createRequire as __internalCreateRequire} from "node:module";
-      const require = __internalCreateRequire(import.meta.url);"#
-        .to_string(),
-    ];
+    let mut source = vec![CJS_REQUIRE_PRELUDE.to_string()];
 
     let analysis =
       self.perform_cjs_analysis(specifier.as_str(), media_type, code)?;
@@ -128,6 +182,10 @@ impl NodeCodeTranslator {
       )?;
       let reexport_specifier =
         ModuleSpecifier::from_file_path(resolved_reexport).unwrap();
+      if is_native_addon_specifier(&reexport_specifier) {
+        // the exports of a Node-API addon are only known once it's loaded
+        continue;
+      }
       // Second, read the source code from disk
       let reexport_file = self
         .file_fetcher
@@ -161,17 +219,7 @@ impl NodeCodeTranslator {
       }
     }
 
-    source.push(format!(
-      "const mod = require(\"{}\");",
-      specifier
-        .to_file_path()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .replace('\\', "\\\\")
-        .replace('\'', "\\\'")
-        .replace('\"', "\\\"")
-    ));
+    source.push(require_mod_statement(specifier));
 
     for export in &all_exports {
       if export.as_str() != "default" {
@@ -190,6 +238,15 @@ impl NodeCodeTranslator {
     Ok(translated_source)
   }
 
+  /// Creates an ES module that loads a Node-API addon with `require`, which
+  /// checks for the ffi permission, and default exports its exports.
+  pub fn translate_native_addon_to_esm(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> String {
+    native_addon_esm_code(specifier)
+  }
+
   fn perform_cjs_analysis(
     &self,
     specifier: &str,
@@ -315,6 +372,32 @@ impl NodeCodeTranslator {
   }
 }
 
+const CJS_REQUIRE_PRELUDE: &str = r#"import {createRequire as __internalCreateRequire} from "node:module";
+      const require = __internalCreateRequire(import.meta.url);"#;
+
+fn require_mod_statement(specifier: &ModuleSpecifier) -> String {
+  format!(
+    "const mod = require(\"{}\");",
+    specifier
+      .to_file_path()
+      .unwrap()
+      .to_str()
+      .unwrap()
+      .replace('\\', "\\\\")
+      .replace('\'', "\\\'")
+      .replace('\"', "\\\"")
+  )
+}
+
+fn native_addon_esm_code(specifier: &ModuleSpecifier) -> String {
+  [
+    CJS_REQUIRE_PRELUDE.to_string(),
+    require_mod_statement(specifier),
+    "export default mod;".to_string(),
+  ]
+  .join("\n")
+}
+
 fn esm_code_with_node_globals(
   analysis_cache: &NodeAnalysisCache,
   specifier: &ModuleSpecifier,
@@ -632,6 +715,19 @@ mod tests {
     assert!(r.contains("export const x = 1;"));
   }
 
+  #[test]
+  fn test_native_addon_esm_code() {
+    let path = std::env::current_dir().unwrap().join("build/addon.node");
+    let r =
+      native_addon_esm_code(&ModuleSpecifier::from_file_path(&path).unwrap());
+    assert!(r.starts_with(CJS_REQUIRE_PRELUDE));
+    assert!(r.contains(&format!(
+      "const mod = require(\"{}\");",
+      path.to_str().unwrap().replace('\\', "\\\\")
+    )));
+    assert!(r.ends_with("\nexport default mod;"));
+  }
+
   #[test]
   fn test_esm_code_with_node_globals_with_shebang() {
     let r = esm_code_with_node_globals(
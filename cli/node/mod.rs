@@ -304,7 +304,13 @@ impl CliNodeResolver {
     })?;
     let resolved_path = match maybe_resolved_path {
       Some(resolved_path) => resolved_path,
-      None => return Ok(None),
+      None => {
+        return self.resolve_npm_reference_acquired_types(
+          reference,
+          mode,
+          permissions,
+        )
+      }
     };
     let resolved_path = match mode {
       NodeResolutionMode::Execution => resolved_path,
@@ -314,7 +320,13 @@ impl CliNodeResolver {
           node_module_kind,
         ) {
           Some(path) => path,
-          None => return Ok(None),
+          None => {
+            return self.resolve_npm_reference_acquired_types(
+              reference,
+              mode,
+              permissions,
+            )
+          }
         }
       }
     };
@@ -325,6 +337,33 @@ impl CliNodeResolver {
     Ok(Some(resolve_response))
   }
 
+  /// Resolves the types of a package that doesn't ship them from the
+  /// `@types/*` package that was acquired for it, if any.
+  fn resolve_npm_reference_acquired_types(
+    &self,
+    reference: &NpmPackageNvReference,
+    mode: NodeResolutionMode,
+    permissions: &mut dyn NodePermissions,
+  ) -> Result<Option<NodeResolution>, AnyError> {
+    if !matches!(mode, NodeResolutionMode::Types) {
+      return Ok(None);
+    }
+    match self
+      .npm_resolver
+      .resolve_types_package_nv(&reference.nv.name)
+    {
+      Some(nv) => self.resolve_npm_reference(
+        &NpmPackageNvReference {
+          nv,
+          sub_path: reference.sub_path.clone(),
+        },
+        mode,
+        permissions,
+      ),
+      None => Ok(None),
+    }
+  }
+
   pub fn resolve_binary_commands(
     &self,
     pkg_nv: &NpmPackageNv,
@@ -340,7 +379,7 @@ impl CliNodeResolver {
     )?;
 
     Ok(match package_json.bin {
-      Some(Value::String(_)) => vec![pkg_nv.name.to_string()],
+      Some(Value::String(_)) => vec![default_bin_name(pkg_nv).to_string()],
       Some(Value::Object(o)) => {
         o.into_iter().map(|(key, _)| key).collect::<Vec<_>>()
       }
@@ -403,6 +442,9 @@ impl CliNodeResolver {
       }
     } else if url_str.ends_with(".mjs") || url_str.ends_with(".d.mts") {
       Ok(NodeResolution::Esm(url))
+    } else if is_native_addon_specifier(&url) {
+      // Node-API addons can only be loaded with `require`
+      Ok(NodeResolution::CommonJs(url))
     } else if url_str.ends_with(".ts") {
       Err(generic_error(format!(
         "TypeScript files are not supported in npm packages: {url}"
@@ -413,6 +455,12 @@ impl CliNodeResolver {
   }
 }
 
+/// Gets if the specifier points to a Node-API addon (`.node` file), which
+/// is loaded as a native library rather than evaluated as source code.
+pub fn is_native_addon_specifier(specifier: &ModuleSpecifier) -> bool {
+  specifier.path().to_lowercase().ends_with(".node")
+}
+
 /// Resolves a specifier that is pointing into a node_modules folder.
 ///
 /// Note: This should be called whenever getting the specifier from
@@ -432,6 +480,15 @@ pub fn resolve_specifier_into_node_modules(
     .unwrap_or_else(|| specifier.clone())
 }
 
+/// The name of the binary of a package with a single `"bin"` path, which
+/// npm names after the package without its scope.
+fn default_bin_name(pkg_nv: &NpmPackageNv) -> &str {
+  match pkg_nv.name.split_once('/') {
+    Some((scope, name)) if scope.starts_with('@') => name,
+    _ => &pkg_nv.name,
+  }
+}
+
 fn resolve_bin_entry_value<'a>(
   pkg_nv: &NpmPackageNv,
   bin_name: Option<&str>,
@@ -439,10 +496,9 @@ fn resolve_bin_entry_value<'a>(
 ) -> Result<&'a str, AnyError> {
   let bin_entry = match bin {
     Value::String(_) => {
-      if bin_name.is_some() && bin_name.unwrap() != pkg_nv.name {
-        None
-      } else {
-        Some(bin)
+      match bin_name {
+        Some(bin_name) if bin_name != pkg_nv.name && bin_name != default_bin_name(pkg_nv) => None,
+        _ => Some(bin),
       }
     }
     Value::Object(o) => {
@@ -451,7 +507,7 @@ fn resolve_bin_entry_value<'a>(
       } else if o.len() == 1 || o.len() > 1 && o.values().all(|v| v == o.values().next().unwrap()) {
         o.values().next()
       } else {
-        o.get(&pkg_nv.name)
+        o.get(&pkg_nv.name).or_else(|| o.get(default_bin_name(pkg_nv)))
       }
     },
     _ => bail!("package '{}' did not have a bin property with a string or object value in its package.json", pkg_nv),
@@ -467,16 +523,24 @@ fn resolve_bin_entry_value<'a>(
             .collect::<Vec<_>>()
         })
         .unwrap_or_default();
-      bail!(
-        "package '{}' did not have a bin entry for '{}' in its package.json{}",
-        pkg_nv,
-        bin_name.unwrap_or(&pkg_nv.name),
-        if keys.is_empty() {
-          "".to_string()
-        } else {
-          format!("\n\nPossibilities:\n{}", keys.join("\n"))
-        }
-      )
+      let possibilities = if keys.is_empty() {
+        "".to_string()
+      } else {
+        format!("\n\nPossibilities:\n{}", keys.join("\n"))
+      };
+      match bin_name {
+        Some(bin_name) => bail!(
+          "package '{}' did not have a bin entry for '{}' in its package.json{}",
+          pkg_nv,
+          bin_name,
+          possibilities,
+        ),
+        None => bail!(
+          "package '{}' has multiple bin entries in its package.json and none is named after the package. Specify the one to run.{}",
+          pkg_nv,
+          possibilities,
+        ),
+      }
     }
   };
   match bin_entry {
@@ -634,6 +698,20 @@ mod tests {
 
   use super::*;
 
+  #[test]
+  fn test_is_native_addon_specifier() {
+    let is_native_addon = |specifier: &str| {
+      is_native_addon_specifier(&ModuleSpecifier::parse(specifier).unwrap())
+    };
+    assert!(is_native_addon(
+      "file:///node_modules/sqlite3/build/Release/node_sqlite3.node"
+    ));
+    assert!(is_native_addon("file:///node_modules/addon/ADDON.NODE"));
+    assert!(!is_native_addon("file:///node_modules/addon/index.js"));
+    assert!(!is_native_addon("file:///node_modules/node/index.js"));
+    assert!(!is_native_addon("node:fs"));
+  }
+
   #[test]
   fn test_resolve_bin_entry_value() {
     // should resolve the specified value
@@ -694,7 +772,7 @@ mod tests {
       .unwrap()
       .to_string(),
       concat!(
-        "package 'asdf@1.2.3' did not have a bin entry for 'asdf' in its package.json\n",
+        "package 'asdf@1.2.3' has multiple bin entries in its package.json and none is named after the package. Specify the one to run.\n",
         "\n",
         "Possibilities:\n",
         " * npm:asdf@1.2.3/bin1\n",
@@ -718,6 +796,31 @@ mod tests {
       "./value"
     );
 
+    // should resolve the bin named after a scoped package without its scope
+    let value = json!({
+      "other": "./other",
+      "cli": "./cli",
+    });
+    assert_eq!(
+      resolve_bin_entry_value(
+        &NpmPackageNv::from_str("@scope/cli@1.0.0").unwrap(),
+        None,
+        &value
+      )
+      .unwrap(),
+      "./cli"
+    );
+    let value = json!("./cli");
+    assert_eq!(
+      resolve_bin_entry_value(
+        &NpmPackageNv::from_str("@scope/cli@1.0.0").unwrap(),
+        Some("cli"),
+        &value
+      )
+      .unwrap(),
+      "./cli"
+    );
+
     // should not resolve when specified and is a string
     let value = json!("./value");
     assert_eq!(
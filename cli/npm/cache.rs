@@ -4,6 +4,7 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use deno_ast::ModuleSpecifier;
 use deno_core::anyhow::bail;
@@ -14,6 +15,7 @@ use deno_core::parking_lot::Mutex;
 use deno_core::url::Url;
 use deno_npm::registry::NpmPackageVersionDistInfo;
 use deno_npm::NpmPackageCacheFolderId;
+use deno_runtime::deno_crypto::rand;
 use deno_semver::npm::NpmPackageNv;
 use deno_semver::Version;
 use once_cell::sync::Lazy;
@@ -25,7 +27,9 @@ use crate::util::fs::canonicalize_path;
 use crate::util::fs::hard_link_dir_recursive;
 use crate::util::path::root_url_to_safe_local_dirname;
 use crate::util::progress_bar::ProgressBar;
+use crate::util::telemetry;
 
+use super::npmrc::NpmRc;
 use super::tarball::verify_and_extract_tarball;
 
 static SHOULD_SYNC_DOWNLOAD: Lazy<bool> =
@@ -39,79 +43,74 @@ pub fn should_sync_download() -> bool {
   *SHOULD_SYNC_DOWNLOAD
 }
 
+/// A file that package folders created by previous versions of Deno
+/// contained while they were being set up, so a folder that still has it
+/// was never completely set up.
 const NPM_PACKAGE_SYNC_LOCK_FILENAME: &str = ".deno_sync_lock";
 
-pub fn with_folder_sync_lock(
+/// Sets up a package folder by running the action on a temporary folder next
+/// to it, which is then renamed into place.
+///
+/// This way other processes never see a partially set up package folder, and
+/// when several processes set up the same package at the same time (ex. CI
+/// jobs sharing a `DENO_DIR`), the first rename wins and the others discard
+/// their copy.
+pub fn atomic_create_package_folder(
   package: &NpmPackageNv,
   output_folder: &Path,
-  action: impl FnOnce() -> Result<(), AnyError>,
+  action: impl FnOnce(&Path) -> Result<(), AnyError>,
 ) -> Result<(), AnyError> {
-  fn inner(
-    output_folder: &Path,
-    action: impl FnOnce() -> Result<(), AnyError>,
-  ) -> Result<(), AnyError> {
-    fs::create_dir_all(output_folder).with_context(|| {
-      format!("Error creating '{}'.", output_folder.display())
-    })?;
-
-    // This sync lock file is a way to ensure that partially created
-    // npm package directories aren't considered valid. This could maybe
-    // be a bit smarter in the future to not bother extracting here
-    // if another process has taken the lock in the past X seconds and
-    // wait for the other process to finish (it could try to create the
-    // file with `create_new(true)` then if it exists, check the metadata
-    // then wait until the other process finishes with a timeout), but
-    // for now this is good enough.
-    let sync_lock_path = output_folder.join(NPM_PACKAGE_SYNC_LOCK_FILENAME);
-    match fs::OpenOptions::new()
-      .write(true)
-      .create(true)
-      .open(&sync_lock_path)
-    {
-      Ok(_) => {
-        action()?;
-        // extraction succeeded, so only now delete this file
-        let _ignore = std::fs::remove_file(&sync_lock_path);
-        Ok(())
-      }
-      Err(err) => {
-        bail!(
-          concat!(
-            "Error creating package sync lock file at '{}'. ",
-            "Maybe try manually deleting this folder.\n\n{:#}",
-          ),
-          output_folder.display(),
-          err
-        );
-      }
-    }
+  let temp_folder = temp_sibling_path(output_folder);
+  let result = fs::create_dir_all(&temp_folder)
+    .with_context(|| format!("Error creating '{}'.", temp_folder.display()))
+    .and_then(|_| action(&temp_folder));
+  if let Err(err) = result {
+    let _ignore = fs::remove_dir_all(&temp_folder);
+    return Err(err.context(format!(
+      "Failed setting up package cache directory for {package}"
+    )));
   }
 
-  match inner(output_folder, action) {
+  let is_complete = |folder: &Path| {
+    folder.is_dir() && !folder.join(NPM_PACKAGE_SYNC_LOCK_FILENAME).exists()
+  };
+  if output_folder.exists() && !is_complete(output_folder) {
+    // left over by a previous version that failed setting it up
+    let _ignore = fs::remove_dir_all(output_folder);
+  }
+  match fs::rename(&temp_folder, output_folder) {
     Ok(()) => Ok(()),
     Err(err) => {
-      if let Err(remove_err) = fs::remove_dir_all(output_folder) {
-        if remove_err.kind() != std::io::ErrorKind::NotFound {
-          bail!(
-            concat!(
-              "Failed setting up package cache directory for {}, then ",
-              "failed cleaning it up.\n\nOriginal error:\n\n{}\n\n",
-              "Remove error:\n\n{}\n\nPlease manually ",
-              "delete this folder or you will run into issues using this ",
-              "package in the future:\n\n{}"
-            ),
+      let _ignore = fs::remove_dir_all(&temp_folder);
+      if is_complete(output_folder) {
+        // another process set up the package first
+        Ok(())
+      } else {
+        Err(err).with_context(|| {
+          format!(
+            "Failed moving the package cache directory for {} to '{}'.",
             package,
-            err,
-            remove_err,
-            output_folder.display(),
-          );
-        }
+            output_folder.display()
+          )
+        })
       }
-      Err(err)
     }
   }
 }
 
+/// Gets a unique path in the same directory, so that renaming it to the
+/// provided path is atomic.
+fn temp_sibling_path(path: &Path) -> PathBuf {
+  let rand: String = (0..4)
+    .map(|_| format!("{:02x}", rand::random::<u8>()))
+    .collect();
+  let file_name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default();
+  path.with_file_name(format!(".{file_name}.{rand}.tmp"))
+}
+
 #[derive(Clone, Debug)]
 pub struct ReadonlyNpmCache {
   root_dir: PathBuf,
@@ -299,6 +298,7 @@ impl ReadonlyNpmCache {
 pub struct NpmCache {
   readonly: ReadonlyNpmCache,
   cache_setting: CacheSetting,
+  npmrc: Arc<NpmRc>,
   http_client: HttpClient,
   progress_bar: ProgressBar,
   /// ensures a package is only downloaded once per run
@@ -309,12 +309,14 @@ impl NpmCache {
   pub fn from_deno_dir(
     dir: &DenoDir,
     cache_setting: CacheSetting,
+    npmrc: Arc<NpmRc>,
     http_client: HttpClient,
     progress_bar: ProgressBar,
   ) -> Self {
     Self {
       readonly: ReadonlyNpmCache::from_deno_dir(dir),
       cache_setting,
+      npmrc,
       http_client,
       progress_bar,
       previously_reloaded_packages: Default::default(),
@@ -376,22 +378,25 @@ impl NpmCache {
       // the first time, or another process is currently extracting the zip file
       && !package_folder.join(NPM_PACKAGE_SYNC_LOCK_FILENAME).exists()
     {
+      telemetry::record_cache("npm", true);
       return Ok(());
-    } else if self.cache_setting == CacheSetting::Only {
+    }
+    telemetry::record_cache("npm", false);
+    if let Some(flag) = self.cache_setting.cache_only_flag() {
       return Err(custom_error(
         "NotCached",
         format!(
-          "An npm specifier not found in cache: \"{}\", --cached-only is specified.",
+          "An npm specifier not found in cache: \"{}\", {flag} is specified.",
           &package.name
-        )
-      )
-      );
+        ),
+      ));
     }
 
     let guard = self.progress_bar.update(&dist.tarball);
+    let tarball_url = Url::parse(&dist.tarball)?;
     let maybe_bytes = self
-      .http_client
-      .download_with_progress(&dist.tarball, &guard)
+      .npmrc
+      .download_with_progress(&self.http_client, &tarball_url, &guard)
       .await?;
     match maybe_bytes {
       Some(bytes) => {
@@ -428,8 +433,8 @@ impl NpmCache {
     let original_package_folder = self
       .readonly
       .package_folder_for_name_and_version(&folder_id.nv, registry_url);
-    with_folder_sync_lock(&folder_id.nv, &package_folder, || {
-      hard_link_dir_recursive(&original_package_folder, &package_folder)
+    atomic_create_package_folder(&folder_id.nv, &package_folder, |folder| {
+      hard_link_dir_recursive(&original_package_folder, folder)
     })?;
     Ok(())
   }
@@ -488,11 +493,15 @@ pub fn mixed_case_package_name_decode(name: &str) -> Option<String> {
 
 #[cfg(test)]
 mod test {
+  use deno_core::anyhow::bail;
   use deno_core::url::Url;
   use deno_semver::npm::NpmPackageNv;
   use deno_semver::Version;
+  use test_util::TempDir;
 
+  use super::atomic_create_package_folder;
   use super::ReadonlyNpmCache;
+  use super::NPM_PACKAGE_SYNC_LOCK_FILENAME;
   use crate::npm::cache::NpmPackageCacheFolderId;
 
   #[test]
@@ -570,4 +579,66 @@ mod test {
         .join("2.1.5"),
     );
   }
+
+  #[test]
+  fn should_create_package_folder_atomically() {
+    let temp_dir = TempDir::new();
+    let package = NpmPackageNv {
+      name: "package".to_string(),
+      version: Version::parse_from_npm("1.0.0").unwrap(),
+    };
+    let output_folder = temp_dir.path().join("package").join("1.0.0");
+    let list_package_dir = || {
+      let mut names = std::fs::read_dir(temp_dir.path().join("package"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+      names.sort();
+      names
+    };
+
+    // a failed setup leaves nothing behind
+    let err = atomic_create_package_folder(&package, &output_folder, |_| {
+      bail!("extraction failed")
+    })
+    .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Failed setting up package cache directory for package@1.0.0"
+    );
+    assert!(list_package_dir().is_empty());
+
+    atomic_create_package_folder(&package, &output_folder, |folder| {
+      std::fs::write(folder.join("index.js"), "first")?;
+      Ok(())
+    })
+    .unwrap();
+    assert_eq!(list_package_dir(), vec!["1.0.0".to_string()]);
+
+    // the folder of the process that finished first is kept
+    atomic_create_package_folder(&package, &output_folder, |folder| {
+      std::fs::write(folder.join("index.js"), "second")?;
+      Ok(())
+    })
+    .unwrap();
+    assert_eq!(list_package_dir(), vec!["1.0.0".to_string()]);
+    assert_eq!(
+      std::fs::read_to_string(output_folder.join("index.js")).unwrap(),
+      "first"
+    );
+
+    // but a partially set up folder of a previous version is replaced
+    std::fs::write(output_folder.join(NPM_PACKAGE_SYNC_LOCK_FILENAME), "")
+      .unwrap();
+    atomic_create_package_folder(&package, &output_folder, |folder| {
+      std::fs::write(folder.join("index.js"), "third")?;
+      Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+      std::fs::read_to_string(output_folder.join("index.js")).unwrap(),
+      "third"
+    );
+    assert!(!output_folder.join(NPM_PACKAGE_SYNC_LOCK_FILENAME).exists());
+  }
 }
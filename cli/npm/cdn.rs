@@ -0,0 +1,87 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::ModuleSpecifier;
+use deno_semver::npm::NpmPackageReqReference;
+
+/// The hosts of CDNs that serve npm packages as ES modules, with the path
+/// prefix the package is specified after.
+const NPM_CDNS: &[(&str, &str)] = &[
+  ("esm.sh", "/"),
+  ("cdn.skypack.dev", "/"),
+  ("unpkg.com", "/"),
+  ("cdn.jsdelivr.net", "/npm/"),
+];
+
+/// Gets the npm package a URL of a CDN serving npm packages refers to (ex.
+/// `https://esm.sh/preact@10/hooks` refers to `npm:preact@10/hooks`), so that
+/// the package can be depended on when the module is used outside of Deno.
+pub fn npm_package_ref_from_cdn_url(
+  url: &ModuleSpecifier,
+) -> Option<NpmPackageReqReference> {
+  if url.scheme() != "https" {
+    return None;
+  }
+  let host = url.host_str()?;
+  let (_, prefix) = NPM_CDNS.iter().find(|(cdn_host, _)| *cdn_host == host)?;
+  let mut path = url.path().strip_prefix(prefix)?;
+  if host == "esm.sh" {
+    // the build version of esm.sh (ex. `/v135/` or `/stable/`)
+    if let Some((first, rest)) = path.split_once('/') {
+      let is_build_version = first == "stable"
+        || first.len() > 1
+          && first.starts_with('v')
+          && first[1..].chars().all(|c| c.is_ascii_digit());
+      if is_build_version {
+        path = rest;
+      }
+    }
+  }
+  // the ES module build of jsDelivr
+  let path = path.strip_suffix("/+esm").unwrap_or(path);
+  let path = path.trim_end_matches('/');
+  if path.is_empty() {
+    return None;
+  }
+  NpmPackageReqReference::from_str(&format!("npm:{path}")).ok()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn npm_ref(url: &str) -> Option<String> {
+    let url = ModuleSpecifier::parse(url).unwrap();
+    npm_package_ref_from_cdn_url(&url).map(|r| r.to_string())
+  }
+
+  #[test]
+  fn test_npm_package_ref_from_cdn_url() {
+    assert_eq!(
+      npm_ref("https://esm.sh/chalk@5.2.0"),
+      Some("npm:chalk@5.2.0".to_string())
+    );
+    assert_eq!(
+      npm_ref("https://esm.sh/v135/preact@10/hooks?target=es2022"),
+      Some("npm:preact@10/hooks".to_string())
+    );
+    assert_eq!(
+      npm_ref("https://esm.sh/stable/@scope/package@1"),
+      Some("npm:@scope/package@1".to_string())
+    );
+    assert_eq!(
+      npm_ref("https://cdn.jsdelivr.net/npm/lodash-es@4/+esm"),
+      Some("npm:lodash-es@4".to_string())
+    );
+    assert_eq!(
+      npm_ref("https://unpkg.com/preact"),
+      Some("npm:preact".to_string())
+    );
+    assert_eq!(npm_ref("https://esm.sh/"), None);
+    assert_eq!(
+      npm_ref("https://cdn.jsdelivr.net/gh/user/repo/mod.js"),
+      None
+    );
+    assert_eq!(npm_ref("https://deno.land/std@0.190.0/path/mod.ts"), None);
+    assert_eq!(npm_ref("http://esm.sh/chalk@5"), None);
+  }
+}
@@ -1,7 +1,9 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 mod cache;
+mod cdn;
 mod installer;
+mod npmrc;
 mod registry;
 mod resolution;
 mod resolvers;
@@ -9,7 +11,9 @@ mod tarball;
 
 pub use cache::should_sync_download;
 pub use cache::NpmCache;
+pub use cdn::npm_package_ref_from_cdn_url;
 pub use installer::PackageJsonDepsInstaller;
+pub use npmrc::NpmRc;
 pub use registry::CliNpmRegistryApi;
 pub use resolution::NpmResolution;
 pub use resolvers::create_npm_fs_resolver;
@@ -0,0 +1,431 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_runtime::deno_fetch::reqwest::StatusCode;
+
+use crate::auth_tokens::AuthTokenData;
+use crate::http_util::BadResponseError;
+use crate::http_util::HttpClient;
+use crate::util::progress_bar::UpdateGuard;
+
+/// The credentials of a registry configured in an `.npmrc` file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct RegistryCredentials {
+  auth_token: Option<String>,
+  /// Base64 encoded `username:password`.
+  auth: Option<String>,
+  username: Option<String>,
+  /// Base64 encoded password.
+  password: Option<String>,
+}
+
+impl RegistryCredentials {
+  /// Gets the credentials, expanding the environment variables referenced
+  /// by the settings.
+  fn to_auth_token_data(
+    &self,
+    expand_env_vars: impl Fn(&str) -> Result<String, AnyError>,
+  ) -> Result<Option<AuthTokenData>, AnyError> {
+    if let Some(token) = &self.auth_token {
+      return Ok(Some(AuthTokenData::Bearer(expand_env_vars(token)?)));
+    }
+    if let Some(auth) = &self.auth {
+      let credentials = decode_base64(&expand_env_vars(auth)?, "_auth")?;
+      return match credentials.split_once(':') {
+        Some((username, password)) => Ok(Some(AuthTokenData::Basic {
+          username: username.to_string(),
+          password: password.to_string(),
+        })),
+        None => {
+          bail!("The \"_auth\" setting must encode \"username:password\".")
+        }
+      };
+    }
+    match (&self.username, &self.password) {
+      (Some(username), Some(password)) => Ok(Some(AuthTokenData::Basic {
+        username: expand_env_vars(username)?,
+        password: decode_base64(&expand_env_vars(password)?, "_password")?,
+      })),
+      _ => Ok(None),
+    }
+  }
+}
+
+/// The scoped registries and their credentials configured in the `.npmrc`
+/// files of the user and the project.
+///
+/// The default registry is still configured with the `NPM_CONFIG_REGISTRY`
+/// environment variable.
+///
+/// The environment variables referenced by the settings are only expanded
+/// when the registry is used, so that a variable that isn't set only errors
+/// for the registry that needs it.
+#[derive(Debug, Default, Clone)]
+pub struct NpmRc {
+  /// The registry of each scope (ex. `@myorg` to
+  /// `https://npm.pkg.github.com`).
+  scope_registries: HashMap<String, String>,
+  /// The credentials keyed by the registry URL without its scheme
+  /// (ex. `//npm.pkg.github.com/`).
+  credentials: HashMap<String, RegistryCredentials>,
+  /// The values of the environment variables referenced by the settings.
+  env_vars: HashMap<String, String>,
+}
+
+impl NpmRc {
+  /// Loads the `.npmrc` file of the user's home directory followed by the
+  /// one of the project directory, whose settings take precedence.
+  pub fn load(maybe_project_dir: Option<&Path>) -> Result<Self, AnyError> {
+    let home_env_var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    let mut paths = Vec::with_capacity(2);
+    if let Some(home_dir) = std::env::var_os(home_env_var) {
+      paths.push(PathBuf::from(home_dir).join(".npmrc"));
+    }
+    if let Some(project_dir) = maybe_project_dir {
+      paths.push(project_dir.join(".npmrc"));
+    }
+
+    let mut npmrc = Self::default();
+    for path in paths {
+      let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+        Err(err) => {
+          return Err(err)
+            .with_context(|| format!("Error reading '{}'.", path.display()))
+        }
+      };
+      npmrc
+        .parse_text(&text, |name| std::env::var(name).ok())
+        .with_context(|| format!("Error parsing '{}'.", path.display()))?;
+    }
+    Ok(npmrc)
+  }
+
+  fn parse_text(
+    &mut self,
+    text: &str,
+    get_env_var: impl Fn(&str) -> Option<String>,
+  ) -> Result<(), AnyError> {
+    for line in text.lines() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+        continue;
+      }
+      let (key, value) = match line.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => continue,
+      };
+      let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+      for name in env_var_names(value) {
+        if let Some(env_value) = get_env_var(name) {
+          self.env_vars.insert(name.to_string(), env_value);
+        }
+      }
+      let value = value.to_string();
+
+      if let Some(scope) = key.strip_suffix(":registry") {
+        if scope.starts_with('@') {
+          self.scope_registries.insert(scope.to_string(), value);
+        }
+      } else if key.starts_with("//") {
+        let (registry, setting) = match key.rsplit_once(':') {
+          Some(parts) => parts,
+          None => continue,
+        };
+        let registry = format!("{}/", registry.trim_end_matches('/'));
+        let credentials = self.credentials.entry(registry).or_default();
+        match setting {
+          "_authToken" => credentials.auth_token = Some(value),
+          "_auth" => credentials.auth = Some(value),
+          "username" => credentials.username = Some(value),
+          "_password" => credentials.password = Some(value),
+          _ => {}
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Gets the registry configured for the scope of the package, if any.
+  pub fn registry_url_for_package(
+    &self,
+    name: &str,
+  ) -> Result<Option<Url>, AnyError> {
+    let scope = match name.split_once('/') {
+      Some((scope, _)) => scope,
+      None => return Ok(None),
+    };
+    let value = match self.scope_registries.get(scope) {
+      Some(value) => value,
+      None => return Ok(None),
+    };
+    let value = self
+      .expand_env_vars(value)
+      .with_context(|| format!("Invalid registry URL for {scope}."))?;
+    let url = Url::parse(&format!("{}/", value.trim_end_matches('/')))
+      .with_context(|| format!("Invalid registry URL for {scope}."))?;
+    Ok(Some(url))
+  }
+
+  fn expand_env_vars(&self, value: &str) -> Result<String, AnyError> {
+    expand_env_vars(value, |name| self.env_vars.get(name).cloned())
+  }
+
+  /// Gets the value of the authorization header for a request to a registry,
+  /// matching the URL to the most specific registry with credentials.
+  pub fn authorization_for_url(
+    &self,
+    url: &Url,
+  ) -> Result<Option<String>, AnyError> {
+    let host = match url.host_str() {
+      Some(host) => host,
+      None => return Ok(None),
+    };
+    let url_key = match url.port() {
+      Some(port) => format!("//{}:{}{}", host, port, url.path()),
+      None => format!("//{}{}", host, url.path()),
+    };
+    let maybe_credentials = self
+      .credentials
+      .iter()
+      .filter(|(registry, _)| url_key.starts_with(registry.as_str()))
+      .max_by_key(|(registry, _)| registry.len());
+    match maybe_credentials {
+      Some((registry, credentials)) => {
+        let maybe_data = credentials
+          .to_auth_token_data(|value| self.expand_env_vars(value))
+          .with_context(|| format!("Invalid credentials for {registry}"))?;
+        Ok(maybe_data.map(|data| data.to_string()))
+      }
+      None => Ok(None),
+    }
+  }
+
+  /// Downloads from a registry with the configured credentials, surfacing
+  /// authentication and authorization failures separately from missing
+  /// packages, which resolve to `None`.
+  pub async fn download_with_progress(
+    &self,
+    http_client: &HttpClient,
+    url: &Url,
+    progress_guard: &UpdateGuard,
+  ) -> Result<Option<Vec<u8>>, AnyError> {
+    let maybe_authorization = self.authorization_for_url(url)?;
+    let result = http_client
+      .download_with_auth_and_progress(
+        url.clone(),
+        maybe_authorization.as_deref(),
+        progress_guard,
+      )
+      .await;
+    let err = match result {
+      Ok(maybe_bytes) => return Ok(maybe_bytes),
+      Err(err) => err,
+    };
+    let status = match err.downcast_ref::<BadResponseError>() {
+      Some(err) => err.status,
+      None => return Err(err),
+    };
+    let has_credentials = maybe_authorization.is_some();
+    let message = match status {
+      StatusCode::UNAUTHORIZED if has_credentials => format!(
+        "Unauthorized (401) at {url}. The credentials configured in .npmrc for this registry were rejected."
+      ),
+      StatusCode::UNAUTHORIZED => format!(
+        "Unauthorized (401) at {url}. Configure an auth token for this registry in an .npmrc file (ex. \"//{}/:_authToken=${{NPM_TOKEN}}\").",
+        url.host_str().unwrap_or_default(),
+      ),
+      StatusCode::FORBIDDEN if has_credentials => format!(
+        "Forbidden (403) at {url}. The credentials configured in .npmrc for this registry do not have access to it."
+      ),
+      StatusCode::FORBIDDEN => format!(
+        "Forbidden (403) at {url}. The registry may require credentials configured in an .npmrc file."
+      ),
+      _ => return Err(err),
+    };
+    Err(custom_error("Http", message))
+  }
+}
+
+fn decode_base64(value: &str, setting: &str) -> Result<String, AnyError> {
+  base64::decode(value)
+    .ok()
+    .and_then(|bytes| String::from_utf8(bytes).ok())
+    .with_context(|| {
+      format!("The \"{setting}\" setting must be base64 encoded.")
+    })
+}
+
+/// Gets the names of the `${NAME}` environment variable references in a
+/// value.
+fn env_var_names(value: &str) -> Vec<&str> {
+  let mut names = Vec::new();
+  let mut rest = value;
+  while let Some(start) = rest.find("${") {
+    let end = match rest[start..].find('}') {
+      Some(end) => start + end,
+      None => break,
+    };
+    names.push(&rest[start + 2..end]);
+    rest = &rest[end + 1..];
+  }
+  names
+}
+
+/// Replaces the `${NAME}` environment variable references in a value.
+fn expand_env_vars(
+  value: &str,
+  get_env_var: impl Fn(&str) -> Option<String>,
+) -> Result<String, AnyError> {
+  let mut result = String::with_capacity(value.len());
+  let mut rest = value;
+  while let Some(start) = rest.find("${") {
+    let end = match rest[start..].find('}') {
+      Some(end) => start + end,
+      None => break,
+    };
+    let name = &rest[start + 2..end];
+    match get_env_var(name) {
+      Some(env_value) => {
+        result.push_str(&rest[..start]);
+        result.push_str(&env_value);
+      }
+      None => bail!("The environment variable {} is not set.", name),
+    }
+    rest = &rest[end + 1..];
+  }
+  result.push_str(rest);
+  Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  fn parse(text: &str) -> NpmRc {
+    let mut npmrc = NpmRc::default();
+    npmrc
+      .parse_text(text, |name| match name {
+        "NPM_TOKEN" => Some("secret".to_string()),
+        _ => None,
+      })
+      .unwrap();
+    npmrc
+  }
+
+  #[test]
+  fn resolves_scope_registries() {
+    let npmrc = parse(
+      r#"# comment
+@myorg:registry=https://npm.pkg.github.com
+@other:registry="https://registry.example.com/npm/"
+registry=https://registry.npmjs.org/
+"#,
+    );
+    let registry_url = |name: &str| {
+      npmrc
+        .registry_url_for_package(name)
+        .unwrap()
+        .map(|url| url.to_string())
+    };
+    assert_eq!(
+      registry_url("@myorg/package").as_deref(),
+      Some("https://npm.pkg.github.com/")
+    );
+    assert_eq!(
+      registry_url("@other/package").as_deref(),
+      Some("https://registry.example.com/npm/")
+    );
+    assert_eq!(registry_url("@unknown/package"), None);
+    assert_eq!(registry_url("chalk"), None);
+  }
+
+  #[test]
+  fn resolves_authorization() {
+    let npmrc = parse(
+      r#"//npm.pkg.github.com/:_authToken=${NPM_TOKEN}
+//registry.example.com/npm/:username=user
+//registry.example.com/npm/:_password=cGFzcw==
+//registry.example.com/npm/private/:_auth=YWRtaW46c2VjcmV0
+//localhost:4873/:_authToken=local
+"#,
+    );
+    let authorization = |url: &str| {
+      npmrc
+        .authorization_for_url(&Url::parse(url).unwrap())
+        .unwrap()
+    };
+    assert_eq!(
+      authorization("https://npm.pkg.github.com/@myorg%2fpackage").as_deref(),
+      Some("Bearer secret")
+    );
+    assert_eq!(
+      authorization("https://registry.example.com/npm/@other/package")
+        .as_deref(),
+      Some("Basic dXNlcjpwYXNz")
+    );
+    assert_eq!(
+      authorization("https://registry.example.com/npm/private/package")
+        .as_deref(),
+      Some("Basic YWRtaW46c2VjcmV0")
+    );
+    assert_eq!(
+      authorization("http://localhost:4873/package").as_deref(),
+      Some("Bearer local")
+    );
+    assert_eq!(authorization("https://registry.example.com/other"), None);
+    assert_eq!(authorization("https://registry.npmjs.org/chalk"), None);
+  }
+
+  #[test]
+  fn errors_for_missing_env_var_when_used() {
+    let npmrc = parse(
+      r#"@myorg:registry=${MISSING_REGISTRY}
+//npm.pkg.github.com/:_authToken=${MISSING}
+//localhost:4873/:_authToken=${NPM_TOKEN}
+"#,
+    );
+    // the registries not referencing the variables still work
+    assert_eq!(
+      npmrc
+        .authorization_for_url(&Url::parse("http://localhost:4873/a").unwrap())
+        .unwrap()
+        .as_deref(),
+      Some("Bearer secret")
+    );
+    assert_eq!(
+      npmrc.registry_url_for_package("@other/package").unwrap(),
+      None
+    );
+
+    let err = npmrc
+      .authorization_for_url(
+        &Url::parse("https://npm.pkg.github.com/package").unwrap(),
+      )
+      .unwrap_err();
+    assert_eq!(
+      format!("{err:#}"),
+      "Invalid credentials for //npm.pkg.github.com/: The environment variable MISSING is not set."
+    );
+    let err = npmrc
+      .registry_url_for_package("@myorg/package")
+      .unwrap_err();
+    assert_eq!(
+      format!("{err:#}"),
+      "Invalid registry URL for @myorg.: The environment variable MISSING_REGISTRY is not set."
+    );
+  }
+}
@@ -17,12 +17,16 @@ use deno_core::futures::future::Shared;
 use deno_core::futures::FutureExt;
 use deno_core::parking_lot::Mutex;
 use deno_core::serde_json;
+use deno_core::serde_json::Value;
 use deno_core::url::Url;
 use deno_core::TaskQueue;
 use deno_npm::registry::NpmPackageInfo;
 use deno_npm::registry::NpmRegistryApi;
 use deno_npm::registry::NpmRegistryPackageInfoLoadError;
+use deno_semver::npm::NpmPackageNv;
 use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::args::CacheSetting;
 use crate::cache::CACHE_PERM;
@@ -33,6 +37,7 @@ use crate::util::sync::AtomicFlag;
 
 use super::cache::should_sync_download;
 use super::cache::NpmCache;
+use super::npmrc::NpmRc;
 
 static NPM_REGISTRY_DEFAULT_URL: Lazy<Url> = Lazy::new(|| {
   let env_var_name = "NPM_CONFIG_REGISTRY";
@@ -62,15 +67,18 @@ impl CliNpmRegistryApi {
 
   pub fn new(
     base_url: Url,
+    npmrc: Arc<NpmRc>,
     cache: Arc<NpmCache>,
     http_client: HttpClient,
     progress_bar: ProgressBar,
   ) -> Self {
     Self(Some(Arc::new(CliNpmRegistryApiInner {
       base_url,
+      npmrc,
       cache,
       force_reload_flag: Default::default(),
       mem_cache: Default::default(),
+      typed_versions: Default::default(),
       previously_reloaded_packages: Default::default(),
       http_client,
       progress_bar,
@@ -95,6 +103,15 @@ impl CliNpmRegistryApi {
     self.inner().get_cached_package_info(name)
   }
 
+  /// Gets if the registry information of the package version declares its
+  /// own types, or `None` when the information isn't loaded or was cached
+  /// before this was kept.
+  ///
+  /// A package may still ship types in an `index.d.ts` that isn't declared.
+  pub fn version_declares_types(&self, nv: &NpmPackageNv) -> Option<bool> {
+    self.inner().version_declares_types(nv)
+  }
+
   pub fn base_url(&self) -> &Url {
     &self.inner().base_url
   }
@@ -108,7 +125,7 @@ impl CliNpmRegistryApi {
     // is disabled or if we're already reloading
     if matches!(
       self.inner().cache.cache_setting(),
-      CacheSetting::Only | CacheSetting::ReloadAll
+      CacheSetting::Only | CacheSetting::Frozen | CacheSetting::ReloadAll
     ) {
       return false;
     }
@@ -165,12 +182,74 @@ enum CacheItem {
   Resolved(Option<Arc<NpmPackageInfo>>),
 }
 
+/// The package information in the file cache, along with the versions that
+/// declare their own types, which `NpmPackageInfo` doesn't keep.
+#[derive(Serialize, Deserialize)]
+struct FileCachedPackageInfo {
+  #[serde(flatten)]
+  info: NpmPackageInfo,
+  /// This is `None` in the files cached before it was kept.
+  #[serde(
+    default,
+    rename = "typedVersions",
+    skip_serializing_if = "Option::is_none"
+  )]
+  typed_versions: Option<HashSet<String>>,
+}
+
+/// The fields of the versions in the registry that declare their types.
+#[derive(Deserialize)]
+struct RegistryPackageTypesInfo {
+  #[serde(default)]
+  versions: HashMap<String, RegistryVersionTypesInfo>,
+}
+
+#[derive(Deserialize)]
+struct RegistryVersionTypesInfo {
+  types: Option<Value>,
+  typings: Option<Value>,
+  exports: Option<Value>,
+}
+
+impl RegistryPackageTypesInfo {
+  fn typed_versions(&self) -> HashSet<String> {
+    self
+      .versions
+      .iter()
+      .filter(|(_, info)| {
+        info.types.is_some()
+          || info.typings.is_some()
+          || info
+            .exports
+            .as_ref()
+            .map(exports_have_types_condition)
+            .unwrap_or(false)
+      })
+      .map(|(version, _)| version.clone())
+      .collect()
+  }
+}
+
+/// Gets if the `"exports"` of a package.json have a `"types"` condition.
+pub fn exports_have_types_condition(exports: &Value) -> bool {
+  match exports {
+    Value::Object(map) => map.iter().any(|(key, value)| {
+      key == "types" || exports_have_types_condition(value)
+    }),
+    Value::Array(values) => values.iter().any(exports_have_types_condition),
+    _ => false,
+  }
+}
+
 #[derive(Debug)]
 struct CliNpmRegistryApiInner {
   base_url: Url,
+  npmrc: Arc<NpmRc>,
   cache: Arc<NpmCache>,
   force_reload_flag: AtomicFlag,
   mem_cache: Mutex<HashMap<String, CacheItem>>,
+  /// The versions of the loaded packages that declare their own types.
+  typed_versions: Mutex<HashMap<String, Arc<HashSet<String>>>>,
   previously_reloaded_packages: Mutex<HashSet<String>>,
   http_client: HttpClient,
   progress_bar: ProgressBar,
@@ -274,8 +353,16 @@ impl CliNpmRegistryApiInner {
       Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
       Err(err) => return Err(err.into()),
     };
-    match serde_json::from_str(&file_text) {
-      Ok(package_info) => Ok(Some(package_info)),
+    match serde_json::from_str::<FileCachedPackageInfo>(&file_text) {
+      Ok(cached) => {
+        if let Some(typed_versions) = cached.typed_versions {
+          self
+            .typed_versions
+            .lock()
+            .insert(name.to_string(), Arc::new(typed_versions));
+        }
+        Ok(Some(cached.info))
+      }
       Err(err) => {
         // This scenario might mean we need to load more data from the
         // npm registry than before. So, just debug log while in debug
@@ -293,21 +380,22 @@ impl CliNpmRegistryApiInner {
   fn save_package_info_to_file_cache(
     &self,
     name: &str,
-    package_info: &NpmPackageInfo,
-  ) {
+    package_info: FileCachedPackageInfo,
+  ) -> NpmPackageInfo {
     if let Err(err) =
-      self.save_package_info_to_file_cache_result(name, package_info)
+      self.save_package_info_to_file_cache_result(name, &package_info)
     {
       if cfg!(debug_assertions) {
         panic!("error saving cached npm package info for {name}: {err:#}");
       }
     }
+    package_info.info
   }
 
   fn save_package_info_to_file_cache_result(
     &self,
     name: &str,
-    package_info: &NpmPackageInfo,
+    package_info: &FileCachedPackageInfo,
   ) -> Result<(), AnyError> {
     let file_cache_path = self.get_package_file_cache_path(name);
     let file_text = serde_json::to_string(&package_info)?;
@@ -323,12 +411,11 @@ impl CliNpmRegistryApiInner {
     self
       .load_package_info_from_registry_inner(name)
       .await
-      .with_context(|| {
-        format!(
-          "Error getting response at {} for package \"{}\"",
-          self.get_package_url(name),
-          name
-        )
+      .with_context(|| match self.get_package_url(name) {
+        Ok(package_url) => format!(
+          "Error getting response at {package_url} for package \"{name}\""
+        ),
+        Err(_) => format!("Error getting response for package \"{name}\""),
       })
   }
 
@@ -336,34 +423,52 @@ impl CliNpmRegistryApiInner {
     &self,
     name: &str,
   ) -> Result<Option<NpmPackageInfo>, AnyError> {
-    if *self.cache.cache_setting() == CacheSetting::Only {
+    if let Some(flag) = self.cache.cache_setting().cache_only_flag() {
       return Err(custom_error(
         "NotCached",
         format!(
-          "An npm specifier not found in cache: \"{name}\", --cached-only is specified."
+          "An npm specifier not found in cache: \"{name}\", {flag} is specified."
         )
       ));
     }
 
-    let package_url = self.get_package_url(name);
+    let package_url = self.get_package_url(name)?;
     let guard = self.progress_bar.update(package_url.as_str());
 
     let maybe_bytes = self
-      .http_client
-      .download_with_progress(package_url, &guard)
+      .npmrc
+      .download_with_progress(&self.http_client, &package_url, &guard)
       .await?;
     match maybe_bytes {
       Some(bytes) => {
-        let package_info = serde_json::from_slice(&bytes)?;
-        self.save_package_info_to_file_cache(name, &package_info);
-        Ok(Some(package_info))
+        let info = serde_json::from_slice(&bytes)?;
+        let typed_versions =
+          serde_json::from_slice::<RegistryPackageTypesInfo>(&bytes)?
+            .typed_versions();
+        self
+          .typed_versions
+          .lock()
+          .insert(name.to_string(), Arc::new(typed_versions.clone()));
+        Ok(Some(self.save_package_info_to_file_cache(
+          name,
+          FileCachedPackageInfo {
+            info,
+            typed_versions: Some(typed_versions),
+          },
+        )))
       }
       None => Ok(None),
     }
   }
 
-  fn get_package_url(&self, name: &str) -> Url {
-    self.base_url.join(name).unwrap()
+  fn get_package_url(&self, name: &str) -> Result<Url, AnyError> {
+    // packages of scopes with their own registry are still cached in the
+    // folder of the default registry because their names can't conflict
+    let registry_url = self
+      .npmrc
+      .registry_url_for_package(name)?
+      .unwrap_or_else(|| self.base_url.clone());
+    Ok(registry_url.join(name).unwrap())
   }
 
   fn get_package_file_cache_path(&self, name: &str) -> PathBuf {
@@ -373,6 +478,13 @@ impl CliNpmRegistryApiInner {
 
   pub fn clear_memory_cache(&self) {
     self.mem_cache.lock().clear();
+    self.typed_versions.lock().clear();
+  }
+
+  fn version_declares_types(&self, nv: &NpmPackageNv) -> Option<bool> {
+    let typed_versions = self.typed_versions.lock();
+    let versions = typed_versions.get(&nv.name)?;
+    Some(versions.contains(&nv.version.to_string()))
   }
 
   pub fn get_cached_package_info(
@@ -387,3 +499,48 @@ impl CliNpmRegistryApiInner {
     }
   }
 }
+
+#[cfg(test)]
+mod test {
+  use deno_core::serde_json;
+
+  use super::FileCachedPackageInfo;
+  use super::RegistryPackageTypesInfo;
+
+  #[test]
+  fn typed_versions() {
+    let registry_text = r#"{
+      "name": "package",
+      "dist-tags": { "latest": "4.0.0" },
+      "versions": {
+        "1.0.0": { "version": "1.0.0", "dist": { "tarball": "https://localhost/a.tgz" } },
+        "2.0.0": { "version": "2.0.0", "types": "mod.d.ts", "dist": { "tarball": "https://localhost/b.tgz" } },
+        "3.0.0": { "version": "3.0.0", "typings": "mod.d.ts", "dist": { "tarball": "https://localhost/c.tgz" } },
+        "4.0.0": { "version": "4.0.0", "exports": { ".": { "types": "./mod.d.ts" } }, "dist": { "tarball": "https://localhost/d.tgz" } }
+      }
+    }"#;
+    let typed_versions =
+      serde_json::from_str::<RegistryPackageTypesInfo>(registry_text)
+        .unwrap()
+        .typed_versions();
+    let mut typed_versions = typed_versions.into_iter().collect::<Vec<_>>();
+    typed_versions.sort();
+    assert_eq!(typed_versions, vec!["2.0.0", "3.0.0", "4.0.0"]);
+
+    // the files cached before the typed versions were kept still load
+    let cached =
+      serde_json::from_str::<FileCachedPackageInfo>(registry_text).unwrap();
+    assert_eq!(cached.info.versions.len(), 4);
+    assert!(cached.typed_versions.is_none());
+    let cached = FileCachedPackageInfo {
+      typed_versions: Some(["2.0.0".to_string()].into_iter().collect()),
+      ..cached
+    };
+    let cached = serde_json::from_str::<FileCachedPackageInfo>(
+      &serde_json::to_string(&cached).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(cached.info.versions.len(), 4);
+    assert_eq!(cached.typed_versions.unwrap().len(), 1);
+  }
+}
@@ -10,6 +10,8 @@ use deno_core::TaskQueue;
 use deno_lockfile::NpmPackageDependencyLockfileInfo;
 use deno_lockfile::NpmPackageLockfileInfo;
 use deno_npm::registry::NpmPackageInfo;
+use deno_npm::registry::NpmRegistryApi;
+use deno_npm::registry::NpmRegistryPackageInfoLoadError;
 use deno_npm::resolution::NpmPackageVersionResolutionError;
 use deno_npm::resolution::NpmPackagesPartitioned;
 use deno_npm::resolution::NpmResolutionError;
@@ -27,9 +29,11 @@ use deno_semver::npm::NpmPackageNv;
 use deno_semver::npm::NpmPackageNvReference;
 use deno_semver::npm::NpmPackageReq;
 use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::Version;
 use deno_semver::VersionReq;
 
 use crate::args::Lockfile;
+use crate::util::telemetry;
 
 use super::registry::CliNpmRegistryApi;
 
@@ -211,6 +215,71 @@ impl NpmResolution {
       .map(|pkg| pkg.pkg_id.clone())
   }
 
+  /// Gets the package of the top level requirement with the provided package
+  /// name, picking the highest version when there are several.
+  pub fn resolve_pkg_nv_from_pkg_name(
+    &self,
+    name: &str,
+  ) -> Option<NpmPackageNv> {
+    self
+      .snapshot
+      .read()
+      .package_reqs()
+      .iter()
+      .filter(|(req, _)| req.name == name)
+      .map(|(_, nv)| nv)
+      .max_by(|a, b| a.version.cmp(&b.version))
+      .cloned()
+  }
+
+  /// Gets if the registry information of the package version declares its
+  /// own types, or `None` when it's unknown.
+  pub fn version_declares_types(&self, nv: &NpmPackageNv) -> Option<bool> {
+    self.api.version_declares_types(nv)
+  }
+
+  /// Gets the requirement of the `@types/*` package with the provided name
+  /// that provides the types of the package, or `None` when there's no such
+  /// package.
+  ///
+  /// An already resolved requirement is reused, so that the version in the
+  /// lockfile is respected. Otherwise, the latest version with the same major
+  /// version as the package is preferred.
+  pub async fn resolve_types_package_req(
+    &self,
+    types_name: &str,
+    nv: &NpmPackageNv,
+  ) -> Result<Option<NpmPackageReq>, AnyError> {
+    let maybe_req = self
+      .snapshot
+      .read()
+      .package_reqs()
+      .keys()
+      .find(|req| req.name == types_name)
+      .cloned();
+    if let Some(req) = maybe_req {
+      return Ok(Some(req));
+    }
+    let info = match self.api.package_info(types_name).await {
+      Ok(info) => info,
+      Err(NpmRegistryPackageInfoLoadError::PackageNotExists { .. }) => {
+        return Ok(None)
+      }
+      Err(err) => return Err(err.into()),
+    };
+    let has_same_major = info
+      .versions
+      .keys()
+      .filter_map(|version| Version::parse_from_npm(version).ok())
+      .any(|version| version.major == nv.version.major);
+    let req_text = if has_same_major {
+      format!("{}@{}", types_name, nv.version.major)
+    } else {
+      types_name.to_string()
+    };
+    Ok(Some(NpmPackageReq::from_str(&req_text)?))
+  }
+
   /// Resolves a package requirement for deno graph. This should only be
   /// called by deno_graph's NpmResolver or for resolving packages in
   /// a package.json
@@ -277,6 +346,8 @@ async fn add_package_reqs_to_snapshot(
     return Ok(snapshot); // already up to date
   }
 
+  let mut span = telemetry::span("npm_resolution");
+  span.set_attribute("npm.package_reqs", package_reqs.len());
   let result = snapshot.resolve_pending(package_reqs.clone()).await;
   api.clear_memory_cache();
   let snapshot = match result {
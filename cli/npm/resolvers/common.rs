@@ -9,6 +9,8 @@ use async_trait::async_trait;
 use deno_ast::ModuleSpecifier;
 use deno_core::error::AnyError;
 use deno_core::futures;
+use deno_core::serde_json;
+use deno_core::serde_json::Value;
 use deno_core::url::Url;
 use deno_npm::NpmPackageId;
 use deno_npm::NpmResolutionPackage;
@@ -16,7 +18,9 @@ use deno_runtime::deno_node::NodePermissions;
 use deno_runtime::deno_node::NodeResolutionMode;
 
 use crate::npm::cache::should_sync_download;
+use crate::npm::registry::exports_have_types_condition;
 use crate::npm::NpmCache;
+use crate::util::fs::canonicalize_path;
 
 /// Part of the resolution that interacts with the file system.
 #[async_trait]
@@ -101,8 +105,10 @@ pub fn ensure_registry_read_permission(
       .all(|c| !matches!(c, std::path::Component::ParentDir))
   {
     // todo(dsherret): cache this?
-    if let Ok(registry_path) = std::fs::canonicalize(registry_path) {
-      match std::fs::canonicalize(path) {
+    // use the same canonicalization for both paths so that UNC prefixes
+    // (ex. for network drives) are handled consistently
+    if let Ok(registry_path) = canonicalize_path(registry_path) {
+      match canonicalize_path(path) {
         Ok(path) if path.starts_with(registry_path) => {
           return Ok(());
         }
@@ -122,11 +128,42 @@ pub fn types_package_name(package_name: &str) -> String {
   debug_assert!(!package_name.starts_with("@types/"));
   // Scoped packages will get two underscores for each slash
   // https://github.com/DefinitelyTyped/DefinitelyTyped/tree/15f1ece08f7b498f4b9a2147c2a46e94416ca777#what-about-scoped-packages
-  format!("@types/{}", package_name.replace('/', "__"))
+  format!(
+    "@types/{}",
+    package_name.trim_start_matches('@').replace('/', "__")
+  )
+}
+
+/// Gets if the package in the provided folder ships its own declaration
+/// files, or `None` when the package isn't cached.
+pub fn package_ships_types(package_folder: &Path) -> Option<bool> {
+  let text =
+    std::fs::read_to_string(package_folder.join("package.json")).ok()?;
+  let package_json: Value = serde_json::from_str(&text).ok()?;
+  if package_json.get("types").is_some()
+    || package_json.get("typings").is_some()
+    || package_json
+      .get("exports")
+      .map(exports_have_types_condition)
+      .unwrap_or(false)
+  {
+    return Some(true);
+  }
+  let main = package_json
+    .get("main")
+    .and_then(|main| main.as_str())
+    .unwrap_or("index.js");
+  Some(
+    package_folder.join("index.d.ts").is_file()
+      || package_folder.join(main).with_extension("d.ts").is_file(),
+  )
 }
 
 #[cfg(test)]
 mod test {
+  use test_util::TempDir;
+
+  use super::package_ships_types;
   use super::types_package_name;
 
   #[test]
@@ -134,7 +171,28 @@ mod test {
     assert_eq!(types_package_name("name"), "@types/name");
     assert_eq!(
       types_package_name("@scoped/package"),
-      "@types/@scoped__package"
+      "@types/scoped__package"
+    );
+  }
+
+  #[test]
+  fn test_package_ships_types() {
+    let temp_dir = TempDir::new();
+    let package_folder = temp_dir.path().join("package");
+    assert_eq!(package_ships_types(&package_folder), None);
+
+    std::fs::create_dir_all(package_folder.join("lib")).unwrap();
+    temp_dir.write("package/package.json", r#"{ "main": "lib/main.js" }"#);
+    assert_eq!(package_ships_types(&package_folder), Some(false));
+    temp_dir.write("package/lib/main.d.ts", "");
+    assert_eq!(package_ships_types(&package_folder), Some(true));
+
+    temp_dir.write(
+      "package/package.json",
+      r#"{ "exports": { ".": { "types": "./types.d.ts", "default": "./mod.js" } } }"#,
     );
+    assert_eq!(package_ships_types(&package_folder), Some(true));
+    temp_dir.write("package/package.json", r#"{ "typings": "mod.d.ts" }"#);
+    assert_eq!(package_ships_types(&package_folder), Some(true));
   }
 }
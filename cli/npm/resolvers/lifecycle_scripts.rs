@@ -0,0 +1,290 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Code for running the install scripts of npm packages in a local
+//! node_modules directory.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::serde_json;
+use deno_runtime::colors;
+use deno_runtime::deno_node::PackageJson;
+use deno_runtime::permissions::permission_prompt;
+use deno_runtime::permissions::PromptResponse;
+use deno_runtime::tokio_util::run_local;
+use deno_semver::npm::NpmPackageNv;
+use deno_task_shell::ExecuteResult;
+use deno_task_shell::ShellCommand;
+use deno_task_shell::ShellCommandContext;
+
+use crate::util::telemetry;
+
+/// The scripts npm runs when installing a package, in the order they run.
+const LIFECYCLE_SCRIPT_NAMES: [&str; 3] =
+  ["preinstall", "install", "postinstall"];
+
+/// The environment variables of the process that are passed on to the
+/// scripts. Others, like the registry credentials, are not exposed.
+const INHERITED_ENV_VAR_NAMES: [&str; 12] = [
+  "PATH",
+  "PATHEXT",
+  "HOME",
+  "USERPROFILE",
+  "APPDATA",
+  "LOCALAPPDATA",
+  "SYSTEMROOT",
+  "COMSPEC",
+  "TMPDIR",
+  "TEMP",
+  "TMP",
+  "LANG",
+];
+
+/// Runs the script passed to the `node` shim as a CommonJS module, the way
+/// node runs it.
+const NODE_SHIM_SCRIPT: &str = r#"import { createRequire } from "node:module";
+import process from "node:process";
+const scriptPath = SCRIPT_PATH;
+process.argv = [process.argv[0], scriptPath, ...process.argv.slice(2)];
+createRequire(scriptPath)(scriptPath);
+"#;
+
+/// A package in the node_modules directory whose install scripts may run.
+pub struct LifecycleScriptsPackage {
+  pub nv: NpmPackageNv,
+  /// The `node_modules/.deno/<package_folder>` folder of the package.
+  pub folder_path: PathBuf,
+  /// The folder of the package within its `node_modules` sub folder.
+  pub package_path: PathBuf,
+  /// If the package folder was created while syncing the resolution.
+  pub is_new: bool,
+}
+
+/// Runs the install scripts of the packages allowed with `--allow-scripts`
+/// in a task shell, writing a file to the package folder afterwards so they
+/// only run once per package version. The packages are expected in
+/// dependency order, so the scripts of a package run after the ones of its
+/// dependencies.
+///
+/// The scripts of the newly added packages that were not allowed only run
+/// when granted at a permission prompt, and a warning is shown for the ones
+/// that were denied.
+pub async fn run_lifecycle_scripts(
+  packages: Vec<LifecycleScriptsPackage>,
+  allow_scripts: &[String],
+  root_node_modules_dir_path: &Path,
+) -> Result<(), AnyError> {
+  let mut skipped_packages = Vec::new();
+  let mut is_all_allowed = false;
+  for package in packages {
+    let is_allowed = allow_scripts.iter().any(|name| *name == package.nv.name);
+    let scripts_ran_file = package.folder_path.join(".scripts-ran");
+    if (is_allowed && scripts_ran_file.exists())
+      || (!is_allowed && !package.is_new)
+    {
+      continue;
+    }
+    let scripts = read_lifecycle_scripts(&package.package_path)?;
+    if scripts.is_empty() {
+      continue;
+    }
+    if !is_allowed && !is_all_allowed {
+      let msg = format!("run access to the install scripts of {}", package.nv);
+      match permission_prompt(&msg, "scripts", None, true) {
+        PromptResponse::Allow => {}
+        PromptResponse::AllowAll => is_all_allowed = true,
+        PromptResponse::Deny => {
+          skipped_packages.push(package.nv);
+          continue;
+        }
+      }
+    }
+
+    for (script_name, script) in scripts {
+      log::info!(
+        "{} {} {} {}",
+        colors::green("Script"),
+        colors::cyan(&package.nv.to_string()),
+        script_name,
+        script,
+      );
+      let env_vars = collect_env_vars(
+        &package.nv,
+        &script_name,
+        &script,
+        root_node_modules_dir_path,
+      );
+      let cwd = package.package_path.clone();
+      let node_shim = NodeShimCommand {
+        package_path: package.package_path.clone(),
+        root_node_modules_dir_path: root_node_modules_dir_path.to_path_buf(),
+      };
+      let mut span = telemetry::span("subprocess");
+      span.set_attribute("npm.package", &package.nv);
+      span.set_attribute("npm.script", &script_name);
+      let exit_code = tokio::task::spawn_blocking(move || {
+        let seq_list = deno_task_shell::parser::parse(&script)
+          .with_context(|| format!("Error parsing script '{script_name}'."))?;
+        let mut custom_commands: HashMap<String, Rc<dyn ShellCommand>> =
+          HashMap::new();
+        custom_commands.insert("node".to_string(), Rc::new(node_shim));
+        Ok::<_, AnyError>(run_local(deno_task_shell::execute(
+          seq_list,
+          env_vars,
+          &cwd,
+          custom_commands,
+        )))
+      })
+      .await??;
+      span.set_exit_code(exit_code);
+      drop(span);
+      if exit_code != 0 {
+        bail!(
+          "The install scripts of {} failed with exit code {}.",
+          package.nv,
+          exit_code,
+        );
+      }
+    }
+    fs::write(&scripts_ran_file, "")
+      .with_context(|| format!("Creating '{}'", scripts_ran_file.display()))?;
+  }
+
+  if !skipped_packages.is_empty() {
+    let mut names = skipped_packages
+      .iter()
+      .map(|nv| nv.name.as_str())
+      .collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    log::warn!(
+      "{} The install scripts of the following packages were not run: {}\nRun them with --allow-scripts={}",
+      colors::yellow("Warning"),
+      skipped_packages
+        .iter()
+        .map(|nv| nv.to_string())
+        .collect::<Vec<_>>()
+        .join(", "),
+      names.join(","),
+    );
+  }
+
+  Ok(())
+}
+
+/// The `node` command of the install scripts, which runs the script with
+/// Deno. It may read the node_modules directory and write to the package
+/// folder, while any other access is prompted for.
+struct NodeShimCommand {
+  package_path: PathBuf,
+  root_node_modules_dir_path: PathBuf,
+}
+
+impl ShellCommand for NodeShimCommand {
+  fn execute(
+    &self,
+    mut context: ShellCommandContext,
+  ) -> LocalBoxFuture<'static, ExecuteResult> {
+    let (script, script_args) = match context.args.split_first() {
+      Some((script, _)) if script.starts_with('-') => {
+        let _ = context
+          .stderr
+          .write_line(&format!("node: unsupported option '{script}'"));
+        return Box::pin(futures::future::ready(
+          ExecuteResult::from_exit_code(1),
+        ));
+      }
+      Some((script, script_args)) => (script, script_args),
+      None => {
+        let _ = context.stderr.write_line("node: missing script");
+        return Box::pin(futures::future::ready(
+          ExecuteResult::from_exit_code(1),
+        ));
+      }
+    };
+    let current_exe = match std::env::current_exe() {
+      Ok(current_exe) => current_exe,
+      Err(err) => {
+        let _ = context.stderr.write_line(&format!("node: {err}"));
+        return Box::pin(futures::future::ready(
+          ExecuteResult::from_exit_code(1),
+        ));
+      }
+    };
+    let script_path = context.state.cwd().join(script);
+    let code = NODE_SHIM_SCRIPT.replace(
+      "SCRIPT_PATH",
+      &serde_json::to_string(&script_path.to_string_lossy()).unwrap(),
+    );
+    let mut args = vec![
+      "run".to_string(),
+      "--no-config".to_string(),
+      format!(
+        "--allow-read={}",
+        self.root_node_modules_dir_path.to_string_lossy()
+      ),
+      format!("--allow-write={}", self.package_path.to_string_lossy()),
+      format!(
+        "data:application/javascript;base64,{}",
+        base64::encode(code)
+      ),
+    ];
+    args.extend(script_args.iter().cloned());
+    let executable_command = deno_task_shell::ExecutableCommand::new(
+      current_exe.to_string_lossy().to_string(),
+    );
+    executable_command.execute(ShellCommandContext { args, ..context })
+  }
+}
+
+fn read_lifecycle_scripts(
+  package_path: &Path,
+) -> Result<Vec<(String, String)>, AnyError> {
+  let package_json = PackageJson::load_skip_read_permission::<
+    deno_runtime::deno_node::RealFs,
+  >(package_path.join("package.json"))?;
+  let scripts = match package_json.scripts {
+    Some(scripts) => scripts,
+    None => return Ok(Vec::new()),
+  };
+  Ok(
+    LIFECYCLE_SCRIPT_NAMES
+      .iter()
+      .filter_map(|name| {
+        let script = scripts.get(*name)?;
+        Some((name.to_string(), script.clone()))
+      })
+      .collect(),
+  )
+}
+
+fn collect_env_vars(
+  nv: &NpmPackageNv,
+  script_name: &str,
+  script: &str,
+  root_node_modules_dir_path: &Path,
+) -> HashMap<String, String> {
+  let mut env_vars = INHERITED_ENV_VAR_NAMES
+    .iter()
+    .filter_map(|name| Some((name.to_string(), std::env::var(name).ok()?)))
+    .collect::<HashMap<_, _>>();
+  env_vars.insert("npm_package_name".to_string(), nv.name.clone());
+  env_vars.insert("npm_package_version".to_string(), nv.version.to_string());
+  env_vars.insert("npm_lifecycle_event".to_string(), script_name.to_string());
+  env_vars.insert("npm_lifecycle_script".to_string(), script.to_string());
+  if let Some(project_dir) = root_node_modules_dir_path.parent() {
+    env_vars.insert(
+      "INIT_CWD".to_string(),
+      project_dir.to_string_lossy().to_string(),
+    );
+  }
+  env_vars
+}
@@ -23,6 +23,7 @@ use deno_core::url::Url;
 use deno_npm::resolution::NpmResolutionSnapshot;
 use deno_npm::NpmPackageCacheFolderId;
 use deno_npm::NpmPackageId;
+use deno_npm::NpmResolutionPackage;
 use deno_runtime::deno_core::futures;
 use deno_runtime::deno_node::NodePermissions;
 use deno_runtime::deno_node::NodeResolutionMode;
@@ -39,6 +40,8 @@ use crate::util::fs::hard_link_dir_recursive;
 use super::common::ensure_registry_read_permission;
 use super::common::types_package_name;
 use super::common::NpmPackageFsResolver;
+use super::lifecycle_scripts::run_lifecycle_scripts;
+use super::lifecycle_scripts::LifecycleScriptsPackage;
 
 /// Resolver that creates a local node_modules directory
 /// and resolves packages from it.
@@ -50,6 +53,7 @@ pub struct LocalNpmPackageResolver {
   registry_url: Url,
   root_node_modules_path: PathBuf,
   root_node_modules_url: Url,
+  allow_scripts: Vec<String>,
 }
 
 impl LocalNpmPackageResolver {
@@ -59,6 +63,7 @@ impl LocalNpmPackageResolver {
     registry_url: Url,
     node_modules_folder: PathBuf,
     resolution: Arc<NpmResolution>,
+    allow_scripts: Vec<String>,
   ) -> Self {
     Self {
       cache,
@@ -68,6 +73,7 @@ impl LocalNpmPackageResolver {
       root_node_modules_url: Url::from_directory_path(&node_modules_folder)
         .unwrap(),
       root_node_modules_path: node_modules_folder,
+      allow_scripts,
     }
   }
 
@@ -196,6 +202,7 @@ impl NpmPackageFsResolver for LocalNpmPackageResolver {
       &self.progress_bar,
       &self.registry_url,
       &self.root_node_modules_path,
+      &self.allow_scripts,
     )
     .await
   }
@@ -220,6 +227,7 @@ async fn sync_resolution_with_fs(
   progress_bar: &ProgressBar,
   registry_url: &Url,
   root_node_modules_dir_path: &Path,
+  allow_scripts: &[String],
 ) -> Result<(), AnyError> {
   if snapshot.is_empty() {
     return Ok(()); // don't create the directory
@@ -254,6 +262,7 @@ async fn sync_resolution_with_fs(
   }
   let mut handles: Vec<JoinHandle<Result<(), AnyError>>> =
     Vec::with_capacity(package_partitions.packages.len());
+  let mut new_folder_names = HashSet::new();
   for package in &package_partitions.packages {
     let folder_name =
       get_package_folder_id_folder_name(&package.get_package_cache_folder_id());
@@ -264,6 +273,7 @@ async fn sync_resolution_with_fs(
       .should_use_for_npm_package(&package.pkg_id.nv.name)
       || !initialized_file.exists()
     {
+      new_folder_names.insert(folder_name);
       let pb = progress_bar.clone();
       let cache = cache.clone();
       let registry_url = registry_url.clone();
@@ -309,10 +319,12 @@ async fn sync_resolution_with_fs(
   // 2. Create any "copy" packages, which are used for peer dependencies
   for package in &package_partitions.copy_packages {
     let package_cache_folder_id = package.get_package_cache_folder_id();
-    let destination_path = deno_local_registry_dir
-      .join(get_package_folder_id_folder_name(&package_cache_folder_id));
+    let folder_name =
+      get_package_folder_id_folder_name(&package_cache_folder_id);
+    let destination_path = deno_local_registry_dir.join(&folder_name);
     let initialized_file = destination_path.join(".initialized");
     if !initialized_file.exists() {
+      new_folder_names.insert(folder_name);
       let sub_node_modules = destination_path.join("node_modules");
       let package_path =
         join_package_name(&sub_node_modules, &package.pkg_id.nv.name);
@@ -399,12 +411,77 @@ async fn sync_resolution_with_fs(
     }
   }
 
-  drop(single_process_lock);
+  // 5. Run the install scripts of the packages allowed with `--allow-scripts`
+  // once their dependencies are available in the node_modules folder, with
+  // the scripts of the dependencies running first.
+  let mut lifecycle_scripts_packages = Vec::with_capacity(all_packages.len());
+  for package in packages_in_dependency_order(snapshot, &all_packages) {
+    let folder_name =
+      get_package_folder_id_folder_name(&package.get_package_cache_folder_id());
+    let folder_path = deno_local_registry_dir.join(&folder_name);
+    lifecycle_scripts_packages.push(LifecycleScriptsPackage {
+      nv: package.pkg_id.nv.clone(),
+      package_path: join_package_name(
+        &folder_path.join("node_modules"),
+        &package.pkg_id.nv.name,
+      ),
+      folder_path,
+      is_new: new_folder_names.contains(&folder_name),
+    });
+  }
   drop(pb_clear_guard);
+  run_lifecycle_scripts(
+    lifecycle_scripts_packages,
+    allow_scripts,
+    root_node_modules_dir_path,
+  )
+  .await?;
+
+  drop(single_process_lock);
 
   Ok(())
 }
 
+/// Orders the packages so that each package comes after its dependencies,
+/// except for the dependency cycles.
+fn packages_in_dependency_order<'a>(
+  snapshot: &'a NpmResolutionSnapshot,
+  packages: &'a [NpmResolutionPackage],
+) -> Vec<&'a NpmResolutionPackage> {
+  fn folder_name(package: &NpmResolutionPackage) -> String {
+    get_package_folder_id_folder_name(&package.get_package_cache_folder_id())
+  }
+
+  let mut roots = packages.iter().collect::<Vec<_>>();
+  roots.sort_by_cached_key(|package| folder_name(package));
+  let mut ordered = Vec::with_capacity(packages.len());
+  let mut visited = HashSet::with_capacity(packages.len());
+  for root in roots {
+    // the packages with a flag of whether their dependencies were pushed
+    let mut pending = vec![(root, false)];
+    while let Some((package, dependencies_pushed)) = pending.pop() {
+      if dependencies_pushed {
+        ordered.push(package);
+        continue;
+      }
+      if !visited.insert(folder_name(package)) {
+        continue;
+      }
+      pending.push((package, true));
+      let mut dependencies = package
+        .dependencies
+        .values()
+        .filter_map(|id| snapshot.package_from_id(id))
+        .collect::<Vec<_>>();
+      // reversed so that they're popped in order
+      dependencies
+        .sort_by_cached_key(|package| std::cmp::Reverse(folder_name(package)));
+      pending.extend(dependencies.into_iter().map(|dep| (dep, false)));
+    }
+  }
+  ordered
+}
+
 fn get_package_folder_id_folder_name(
   folder_id: &NpmPackageCacheFolderId,
 ) -> String {
@@ -2,6 +2,7 @@
 
 mod common;
 mod global;
+mod lifecycle_scripts;
 mod local;
 
 use std::path::Path;
@@ -32,6 +33,8 @@ use crate::args::Lockfile;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
 use crate::util::progress_bar::ProgressBar;
 
+use self::common::package_ships_types;
+use self::common::types_package_name;
 use self::common::NpmPackageFsResolver;
 use self::local::LocalNpmPackageResolver;
 use super::resolution::NpmResolution;
@@ -223,6 +226,68 @@ impl NpmPackageResolver {
     Ok(())
   }
 
+  /// Gets the requirements of the `@types/*` packages for the packages of the
+  /// provided requirements that don't ship their own types.
+  ///
+  /// This is best effort, so packages that aren't resolved yet or that have no
+  /// `@types/*` package are skipped. The packages that aren't cached yet are
+  /// checked with the information of the registry.
+  pub async fn resolve_types_package_reqs(
+    &self,
+    reqs: &[NpmPackageReq],
+  ) -> Vec<NpmPackageReq> {
+    let mut types_reqs: Vec<NpmPackageReq> = Vec::new();
+    for req in reqs {
+      if req.name.starts_with("@types/") {
+        continue;
+      }
+      let pkg_id = match self.resolution.resolve_pkg_id_from_pkg_req(req) {
+        Ok(pkg_id) => pkg_id,
+        Err(_) => continue,
+      };
+      let ships_types = self
+        .fs_resolver
+        .package_folder(&pkg_id)
+        .ok()
+        .and_then(|folder| package_ships_types(&folder))
+        .or_else(|| self.resolution.version_declares_types(&pkg_id.nv));
+      if ships_types != Some(false) {
+        continue;
+      }
+      let types_name = types_package_name(&req.name);
+      match self
+        .resolution
+        .resolve_types_package_req(&types_name, &pkg_id.nv)
+        .await
+      {
+        Ok(Some(types_req)) => {
+          if !types_reqs.contains(&types_req) {
+            types_reqs.push(types_req);
+          }
+        }
+        Ok(None) => {}
+        Err(err) => {
+          log::debug!("Failed resolving {} for {}. {:#}", types_name, req, err)
+        }
+      }
+    }
+    types_reqs
+  }
+
+  /// Gets the `@types/*` package that was acquired for the types of the
+  /// package with the provided name.
+  pub fn resolve_types_package_nv(
+    &self,
+    package_name: &str,
+  ) -> Option<NpmPackageNv> {
+    if package_name.starts_with("@types/") {
+      return None;
+    }
+    self
+      .resolution
+      .resolve_pkg_nv_from_pkg_name(&types_package_name(package_name))
+  }
+
   pub async fn resolve_pending(&self) -> Result<(), AnyError> {
     self.resolution.resolve_pending().await?;
     self.fs_resolver.cache_packages().await?;
@@ -287,6 +352,7 @@ pub fn create_npm_fs_resolver(
   registry_url: Url,
   resolution: Arc<NpmResolution>,
   maybe_node_modules_path: Option<PathBuf>,
+  allow_scripts: Vec<String>,
 ) -> Arc<dyn NpmPackageFsResolver> {
   match maybe_node_modules_path {
     Some(node_modules_folder) => Arc::new(LocalNpmPackageResolver::new(
@@ -295,6 +361,7 @@ pub fn create_npm_fs_resolver(
       registry_url,
       node_modules_folder,
       resolution,
+      allow_scripts,
     )),
     None => Arc::new(GlobalNpmPackageResolver::new(
       cache,
@@ -13,7 +13,7 @@ use flate2::read::GzDecoder;
 use tar::Archive;
 use tar::EntryType;
 
-use super::cache::with_folder_sync_lock;
+use super::cache::atomic_create_package_folder;
 
 pub fn verify_and_extract_tarball(
   package: &NpmPackageNv,
@@ -23,8 +23,8 @@ pub fn verify_and_extract_tarball(
 ) -> Result<(), AnyError> {
   verify_tarball_integrity(package, data, &dist_info.integrity())?;
 
-  with_folder_sync_lock(package, output_folder, || {
-    extract_tarball(data, output_folder)
+  atomic_create_package_folder(package, output_folder, |folder| {
+    extract_tarball(data, folder)
   })
 }
 
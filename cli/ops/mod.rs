@@ -1,27 +1,73 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::args::CliOptions;
+use crate::module_loader::DynamicImportRetryPolicy;
+use crate::module_loader::ModuleLoadPreparer;
 use crate::npm::NpmPackageResolver;
+use crate::standalone::self_update::SelfUpdateInfo;
+use crate::standalone::self_update::SelfUpdater;
+use crate::util::fingerprint::fingerprint_assets;
+use crate::util::path::specifier_to_file_path;
+use deno_core::error::custom_error;
 use deno_core::error::AnyError;
 use deno_core::op;
+use deno_core::resolve_url_or_path;
 use deno_core::Extension;
 use deno_core::OpState;
+use deno_runtime::ops::worker_host::WorkerPermissionsPolicy;
+use deno_runtime::permissions::PermissionsContainer;
 
 pub mod bench;
 pub mod testing;
 
-pub fn cli_exts(npm_resolver: Arc<NpmPackageResolver>) -> Vec<Extension> {
-  vec![deno_cli::init_ops(npm_resolver)]
+pub fn cli_exts(
+  cli_options: Arc<CliOptions>,
+  npm_resolver: Arc<NpmPackageResolver>,
+  module_load_preparer: Arc<ModuleLoadPreparer>,
+  worker_permissions_policy: Option<WorkerPermissionsPolicy>,
+  self_updater: Option<SelfUpdater>,
+) -> Vec<Extension> {
+  vec![deno_cli::init_ops(
+    cli_options,
+    npm_resolver,
+    module_load_preparer,
+    worker_permissions_policy,
+    self_updater,
+  )]
 }
 
 deno_core::extension!(deno_cli,
-  ops = [op_npm_process_state],
+  ops = [
+    op_fingerprint_assets,
+    op_npm_process_state,
+    op_preload_module,
+    op_refresh_module,
+    op_self_update,
+    op_set_dynamic_import_retry_policy,
+  ],
   options = {
+    cli_options: Arc<CliOptions>,
     npm_resolver: Arc<NpmPackageResolver>,
+    module_load_preparer: Arc<ModuleLoadPreparer>,
+    worker_permissions_policy: Option<WorkerPermissionsPolicy>,
+    self_updater: Option<SelfUpdater>,
   },
   state = |state, options| {
+    state.put(options.cli_options);
     state.put(options.npm_resolver);
+    state.put(options.module_load_preparer);
+    if let Some(worker_permissions_policy) = options.worker_permissions_policy {
+      state.put(worker_permissions_policy);
+    }
+    if let Some(self_updater) = options.self_updater {
+      state.put(self_updater);
+    }
   },
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
     ext.force_op_registration();
@@ -33,3 +79,136 @@ fn op_npm_process_state(state: &mut OpState) -> Result<String, AnyError> {
   let npm_resolver = state.borrow_mut::<Arc<NpmPackageResolver>>();
   Ok(npm_resolver.get_npm_process_state())
 }
+
+/// Copies local assets to the output directory under content hashed file
+/// names and writes their manifest.
+#[op]
+async fn op_fingerprint_assets(
+  state: Rc<RefCell<OpState>>,
+  assets: Vec<String>,
+  out_dir: String,
+  base_dir: Option<String>,
+) -> Result<BTreeMap<String, String>, AnyError> {
+  const API_NAME: &str = "Deno.fingerprintAssets()";
+  let cwd = state
+    .borrow()
+    .borrow::<Arc<CliOptions>>()
+    .initial_cwd()
+    .to_path_buf();
+  let to_path = |specifier: &str| -> Result<PathBuf, AnyError> {
+    specifier_to_file_path(&resolve_url_or_path(specifier, &cwd)?)
+  };
+  let assets = assets
+    .iter()
+    .map(|asset| to_path(asset))
+    .collect::<Result<Vec<_>, _>>()?;
+  let out_dir = to_path(&out_dir)?;
+  let base_dir = match base_dir {
+    Some(base_dir) => to_path(&base_dir)?,
+    None => cwd.clone(),
+  };
+  {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    for asset in &assets {
+      permissions.check_read(asset, API_NAME)?;
+    }
+    permissions.check_write(&out_dir, API_NAME)?;
+  }
+  tokio::task::spawn_blocking(move || {
+    fingerprint_assets(&assets, &base_dir, &out_dir)
+  })
+  .await?
+}
+
+/// Prepares a module that web workers use as their entrypoint, so that
+/// spawning them doesn't prepare it again.
+#[op]
+async fn op_preload_module(
+  state: Rc<RefCell<OpState>>,
+  specifier: String,
+) -> Result<(), AnyError> {
+  let (module_load_preparer, permissions, specifier) = {
+    let state = state.borrow();
+    let cwd = state.borrow::<Arc<CliOptions>>().initial_cwd();
+    (
+      state.borrow::<Arc<ModuleLoadPreparer>>().clone(),
+      state.borrow::<PermissionsContainer>().clone(),
+      resolve_url_or_path(&specifier, cwd)?,
+    )
+  };
+  module_load_preparer
+    .prepare_worker_modules(vec![specifier], permissions)
+    .await
+}
+
+/// Removes a module and its dependents from the module graph and in-memory
+/// caches, returning the removed specifiers.
+#[op]
+async fn op_refresh_module(
+  state: Rc<RefCell<OpState>>,
+  specifier: String,
+) -> Result<Vec<String>, AnyError> {
+  let (module_load_preparer, specifier) = {
+    let state = state.borrow();
+    let cwd = state.borrow::<Arc<CliOptions>>().initial_cwd();
+    (
+      state.borrow::<Arc<ModuleLoadPreparer>>().clone(),
+      resolve_url_or_path(&specifier, cwd)?,
+    )
+  };
+  let invalidated = module_load_preparer.invalidate_modules(&[specifier]).await;
+  Ok(invalidated.into_iter().map(|s| s.to_string()).collect())
+}
+
+/// Checks the update manifest of an executable compiled with `--update-url`
+/// for a newer version, which replaces the executable when `apply` is set.
+#[op]
+async fn op_self_update(
+  state: Rc<RefCell<OpState>>,
+  apply: bool,
+) -> Result<SelfUpdateInfo, AnyError> {
+  const API_NAME: &str = "Deno.selfUpdate()";
+  let self_updater = {
+    let mut state = state.borrow_mut();
+    let self_updater =
+      state.try_borrow::<SelfUpdater>().cloned().ok_or_else(|| {
+        custom_error(
+          "NotSupported",
+          "The executable was not compiled with --update-url.",
+        )
+      })?;
+    state
+      .borrow_mut::<PermissionsContainer>()
+      .check_net_url(&self_updater.config().url, API_NAME)?;
+    self_updater
+  };
+  let manifest = self_updater.fetch_manifest().await?;
+  let mut info = self_updater.update_info(&manifest);
+  if !apply || !info.available {
+    return Ok(info);
+  }
+  let (url, target) = self_updater.resolve_target(&manifest)?;
+  state
+    .borrow_mut()
+    .borrow_mut::<PermissionsContainer>()
+    .check_net_url(&url, API_NAME)?;
+  self_updater.apply(url, &manifest.version, target).await?;
+  info.updated = true;
+  Ok(info)
+}
+
+/// Sets or clears how failed dynamic imports of the current worker are
+/// retried.
+#[op]
+fn op_set_dynamic_import_retry_policy(
+  state: &mut OpState,
+  policy: Option<DynamicImportRetryPolicy>,
+) {
+  match policy {
+    Some(policy) => state.put(policy),
+    None => {
+      state.try_take::<DynamicImportRetryPolicy>();
+    }
+  }
+}
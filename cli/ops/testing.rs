@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::tools::test::SnapshotStore;
 use crate::tools::test::TestDescription;
 use crate::tools::test::TestEvent;
 use crate::tools::test::TestEventSender;
@@ -9,6 +10,7 @@ use crate::tools::test::TestStepDescription;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::op;
+use deno_core::serde_json;
 use deno_core::serde_v8;
 use deno_core::v8;
 use deno_core::ModuleSpecifier;
@@ -21,6 +23,7 @@ use serde::Deserializer;
 use serde::Serialize;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Default)]
@@ -35,13 +38,19 @@ deno_core::extension!(deno_test,
     op_register_test,
     op_register_test_step,
     op_dispatch_test_event,
+    op_assert_snapshot,
+    op_test_global_context,
   ],
   options = {
     sender: TestEventSender,
+    update_snapshots: bool,
+    global_context: Arc<serde_json::Value>,
   },
   state = |state, options| {
     state.put(options.sender);
     state.put(TestContainer::default());
+    state.put(UpdateSnapshots(options.update_snapshots));
+    state.put(GlobalContext(options.global_context));
   },
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
     ext.force_op_registration();
@@ -200,3 +209,29 @@ fn op_dispatch_test_event(
   sender.send(event).ok();
   Ok(())
 }
+
+struct UpdateSnapshots(bool);
+
+#[op]
+fn op_assert_snapshot(
+  state: &mut OpState,
+  name: String,
+  actual: String,
+) -> Result<(), AnyError> {
+  // the snapshots are loaded when the first one of the test module is
+  // asserted and written by the test runner after all its tests ran
+  if state.try_borrow::<SnapshotStore>().is_none() {
+    let specifier = state.borrow::<ModuleSpecifier>().clone();
+    let update = state.borrow::<UpdateSnapshots>().0;
+    state.put(SnapshotStore::load(&specifier, update)?);
+  }
+  state.borrow_mut::<SnapshotStore>().assert(&name, &actual)
+}
+
+/// The value returned by the global setup module of the test run.
+struct GlobalContext(Arc<serde_json::Value>);
+
+#[op]
+fn op_test_global_context(state: &mut OpState) -> serde_json::Value {
+  state.borrow::<GlobalContext>().0.as_ref().clone()
+}
@@ -1,16 +1,21 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::api::ProjectBuilder;
 use crate::args::CliOptions;
 use crate::args::DenoSubcommand;
 use crate::args::Flags;
 use crate::args::Lockfile;
 use crate::args::TsConfigType;
+use crate::args::TypeCheckMode;
 use crate::cache::Caches;
+use crate::cache::CodeCache;
 use crate::cache::DenoDir;
 use crate::cache::EmitCache;
 use crate::cache::HttpCache;
+use crate::cache::InferredTypesCache;
 use crate::cache::NodeAnalysisCache;
 use crate::cache::ParsedSourceCache;
+use crate::cache::RemoteBuildCache;
 use crate::emit::Emitter;
 use crate::file_fetcher::FileFetcher;
 use crate::graph_util::ModuleGraphBuilder;
@@ -40,9 +45,12 @@ use deno_runtime::deno_broadcast_channel::InMemoryBroadcastChannel;
 use deno_runtime::deno_tls::rustls::RootCertStore;
 use deno_runtime::deno_web::BlobStore;
 use deno_runtime::inspector_server::InspectorServer;
+use deno_runtime::permissions::NetAuditLog;
 use import_map::ImportMap;
 use log::warn;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::net::IpAddr;
 use std::ops::Deref;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -60,12 +68,16 @@ pub struct Inner {
   pub http_client: HttpClient,
   pub options: Arc<CliOptions>,
   pub emit_cache: EmitCache,
+  pub code_cache: CodeCache,
   pub emitter: Arc<Emitter>,
   pub graph_container: Arc<ModuleGraphContainer>,
   pub lockfile: Option<Arc<Mutex<Lockfile>>>,
   pub maybe_import_map: Option<Arc<ImportMap>>,
   pub maybe_inspector_server: Option<Arc<InspectorServer>>,
   pub root_cert_store: RootCertStore,
+  /// The addresses of the hosts that connections are pinned to
+  /// (`--pin-dns`).
+  pub pinned_hosts: HashMap<String, Vec<IpAddr>>,
   pub blob_store: BlobStore,
   pub broadcast_channel: InMemoryBroadcastChannel,
   pub shared_array_buffer_store: SharedArrayBufferStore,
@@ -97,11 +109,13 @@ impl ProcState {
   pub async fn from_cli_options(
     options: Arc<CliOptions>,
   ) -> Result<Self, AnyError> {
-    Self::build_with_sender(options, None).await
+    let project = ProjectBuilder::from_cli_options(options).build().await?;
+    Ok(project.into_proc_state())
   }
 
   pub async fn from_flags(flags: Flags) -> Result<Self, AnyError> {
-    Self::from_cli_options(Arc::new(CliOptions::from_flags(flags)?)).await
+    let project = ProjectBuilder::from_flags(flags)?.build().await?;
+    Ok(project.into_proc_state())
   }
 
   pub async fn from_flags_for_file_watcher(
@@ -109,12 +123,11 @@ impl ProcState {
     files_to_watch_sender: tokio::sync::mpsc::UnboundedSender<Vec<PathBuf>>,
   ) -> Result<Self, AnyError> {
     // resolve the config each time
-    let cli_options = Arc::new(CliOptions::from_flags(flags)?);
-    let ps =
-      Self::build_with_sender(cli_options, Some(files_to_watch_sender.clone()))
-        .await?;
-    ps.init_watcher();
-    Ok(ps)
+    let project = ProjectBuilder::from_flags(flags)?
+      .file_watcher_sender(files_to_watch_sender)
+      .build()
+      .await?;
+    Ok(project.into_proc_state())
   }
 
   /// Reset all runtime state to its default. This should be used on file
@@ -129,6 +142,7 @@ impl ProcState {
       caches: self.caches.clone(),
       options: self.options.clone(),
       emit_cache: self.emit_cache.clone(),
+      code_cache: self.code_cache.clone(),
       emitter: self.emitter.clone(),
       file_fetcher: self.file_fetcher.clone(),
       http_client: self.http_client.clone(),
@@ -137,6 +151,7 @@ impl ProcState {
       maybe_import_map: self.maybe_import_map.clone(),
       maybe_inspector_server: self.maybe_inspector_server.clone(),
       root_cert_store: self.root_cert_store.clone(),
+      pinned_hosts: self.pinned_hosts.clone(),
       blob_store: self.blob_store.clone(),
       broadcast_channel: Default::default(),
       shared_array_buffer_store: Default::default(),
@@ -161,7 +176,7 @@ impl ProcState {
 
   // Add invariant files like the import map and explicit watch flag list to
   // the watcher. Dedup for build_for_file_watcher and reset_for_file_watcher.
-  fn init_watcher(&self) {
+  pub(crate) fn init_watcher(&self) {
     let files_to_watch_sender = match &self.0.maybe_file_watcher_reporter {
       Some(reporter) => &reporter.sender,
       None => return,
@@ -178,7 +193,9 @@ impl ProcState {
     }
   }
 
-  async fn build_with_sender(
+  /// Creates the state of the program. Use a
+  /// [`ProjectBuilder`](crate::api::ProjectBuilder) instead.
+  pub(crate) async fn build_with_sender(
     cli_options: Arc<CliOptions>,
     maybe_sender: Option<tokio::sync::mpsc::UnboundedSender<Vec<PathBuf>>>,
   ) -> Result<Self, AnyError> {
@@ -186,7 +203,7 @@ impl ProcState {
     let caches = Arc::new(Caches::default());
     // Warm up the caches we know we'll likely need based on the CLI mode
     match cli_options.sub_command() {
-      DenoSubcommand::Run(_) => {
+      DenoSubcommand::Run(_) | DenoSubcommand::Serve(_) => {
         _ = caches.dep_analysis_db(&dir);
         _ = caches.node_analysis_db(&dir);
       }
@@ -204,13 +221,20 @@ impl ProcState {
     let deps_cache_location = dir.deps_folder_path();
     let http_cache = HttpCache::new(&deps_cache_location);
     let root_cert_store = cli_options.resolve_root_cert_store()?;
+    let pinned_hosts = cli_options.resolve_pinned_hosts()?;
     let cache_usage = cli_options.cache_setting();
     let progress_bar = ProgressBar::new(ProgressBarStyle::TextOnly);
-    let http_client = HttpClient::new(
+    let http_client = HttpClient::with_origin_tls_configs(
       Some(root_cert_store.clone()),
       cli_options.unsafely_ignore_certificate_errors().clone(),
+      &cli_options.resolve_origin_tls_configs()?,
+      &pinned_hosts,
     )?;
-    let file_fetcher = FileFetcher::new(
+    crate::util::telemetry::set_http_client_options(
+      Some(root_cert_store.clone()),
+      cli_options.unsafely_ignore_certificate_errors().clone(),
+    );
+    let mut file_fetcher = FileFetcher::new(
       http_cache,
       cache_usage,
       !cli_options.no_remote(),
@@ -218,18 +242,30 @@ impl ProcState {
       blob_store.clone(),
       Some(progress_bar.clone()),
     );
+    file_fetcher
+      .set_url_rewrite_rules(cli_options.resolve_url_rewrite_rules()?);
+    file_fetcher.set_fetch_config(&cli_options.resolve_fetch_config()?);
+    if let Some(net_audit_log) = cli_options.net_audit_log() {
+      file_fetcher.set_net_audit_log(NetAuditLog::open(&net_audit_log)?);
+    }
+    if cli_options.vendor_only() {
+      file_fetcher.set_vendor_only();
+    }
 
     let lockfile = cli_options.maybe_lock_file();
 
     let npm_registry_url = CliNpmRegistryApi::default_url().to_owned();
+    let npmrc = Arc::new(cli_options.resolve_npmrc()?);
     let npm_cache = Arc::new(NpmCache::from_deno_dir(
       &dir,
       cli_options.cache_setting(),
+      npmrc.clone(),
       http_client.clone(),
       progress_bar.clone(),
     ));
     let npm_api = Arc::new(CliNpmRegistryApi::new(
       npm_registry_url.clone(),
+      npmrc.clone(),
       npm_cache.clone(),
       http_client.clone(),
       progress_bar.clone(),
@@ -248,6 +284,7 @@ impl ProcState {
       npm_registry_url,
       npm_resolution.clone(),
       cli_options.node_modules_dir_path(),
+      cli_options.allow_scripts().to_vec(),
     );
     let npm_resolver = Arc::new(NpmPackageResolver::new(
       npm_resolution.clone(),
@@ -263,13 +300,29 @@ impl ProcState {
       .resolve_import_map(&file_fetcher)
       .await?
       .map(Arc::new);
+    let maybe_import_map_overrides = cli_options
+      .resolve_import_map_overrides(&file_fetcher)
+      .await?
+      .map(Arc::new);
     let maybe_inspector_server =
       cli_options.resolve_inspector_server().map(Arc::new);
 
+    // the declarations inferred for untyped remote modules are only used for
+    // type checking, so that running a module never loads them
+    let maybe_inferred_types_cache =
+      if cli_options.type_check_mode() == TypeCheckMode::None {
+        None
+      } else {
+        Some(InferredTypesCache::new(&dir.inferred_types_folder_path()))
+      };
     let resolver = Arc::new(CliGraphResolver::new(
       cli_options.to_maybe_jsx_import_source_config(),
       maybe_import_map.clone(),
+      maybe_import_map_overrides,
+      maybe_inferred_types_cache,
       cli_options.no_npm(),
+      cli_options.canonicalize_symlinks(),
+      cli_options.vendor_only(),
       npm_api.clone(),
       npm_resolution.clone(),
       package_json_deps_installer.clone(),
@@ -287,17 +340,23 @@ impl ProcState {
       warn!("{}", ignored_options);
     }
     let emit_cache = EmitCache::new(dir.gen_cache.clone());
-    let parsed_source_cache =
-      Arc::new(ParsedSourceCache::new(caches.dep_analysis_db(&dir)));
+    let code_cache =
+      CodeCache::new(dir.gen_cache.clone(), cli_options.v8_flags());
+    let parsed_source_cache = Arc::new(ParsedSourceCache::new(
+      caches.dep_analysis_db(&dir),
+      cli_options.graph_memory_budget(),
+    ));
     let emit_options: deno_ast::EmitOptions = ts_config_result.ts_config.into();
     let emitter = Arc::new(Emitter::new(
       emit_cache.clone(),
       parsed_source_cache.clone(),
       emit_options,
+      cli_options.profile().map(|p| p.minify).unwrap_or(false),
     ));
     let npm_cache = Arc::new(NpmCache::from_deno_dir(
       &dir,
       cli_options.cache_setting(),
+      npmrc,
       http_client.clone(),
       progress_bar.clone(),
     ));
@@ -313,12 +372,17 @@ impl ProcState {
       npm_resolution.clone(),
       npm_resolver.clone(),
     ));
+    let maybe_remote_build_cache =
+      cli_options.remote_cache_config()?.and_then(|config| {
+        RemoteBuildCache::new(&config, http_client.clone()).map(Arc::new)
+      });
     let type_checker = Arc::new(TypeChecker::new(
       dir.clone(),
       caches.clone(),
       cli_options.clone(),
       node_resolver.clone(),
       npm_resolver.clone(),
+      maybe_remote_build_cache.clone(),
     ));
     let module_graph_builder = Arc::new(ModuleGraphBuilder::new(
       cli_options.clone(),
@@ -333,6 +397,8 @@ impl ProcState {
     let graph_container: Arc<ModuleGraphContainer> = Default::default();
     let module_load_preparer = Arc::new(ModuleLoadPreparer::new(
       cli_options.clone(),
+      emitter.clone(),
+      file_fetcher.clone(),
       graph_container.clone(),
       lockfile.clone(),
       maybe_file_watcher_reporter.clone(),
@@ -341,6 +407,7 @@ impl ProcState {
       progress_bar.clone(),
       resolver.clone(),
       type_checker,
+      maybe_remote_build_cache,
     ));
 
     Ok(ProcState(Arc::new(Inner {
@@ -348,6 +415,7 @@ impl ProcState {
       caches,
       options: cli_options,
       emit_cache,
+      code_cache,
       emitter,
       file_fetcher,
       http_client,
@@ -356,6 +424,7 @@ impl ProcState {
       maybe_import_map,
       maybe_inspector_server,
       root_cert_store,
+      pinned_hosts,
       blob_store,
       broadcast_channel,
       shared_array_buffer_store,
@@ -0,0 +1,667 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::ToSocketAddrs;
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::url::Host;
+use deno_core::url::Url;
+use deno_core::v8;
+use deno_core::JsRuntime;
+use deno_core::RuntimeOptions;
+use deno_runtime::colors;
+use deno_runtime::deno_fetch::reqwest;
+use once_cell::sync::Lazy;
+
+static PAC_UTILS: &str = include_str!("./pac_utils.js");
+
+/// How long the proxy auto-configuration file may run when it's loaded or
+/// resolves the proxy of a request, so that a request doesn't hang on it.
+const PAC_TIMEOUT: Duration = Duration::from_secs(5);
+
+static ENV_PROXY_RESOLVER: Lazy<Result<Option<Arc<ProxyResolver>>, String>> =
+  Lazy::new(|| {
+    ProxyResolver::from_env()
+      .map(|maybe_resolver| maybe_resolver.map(Arc::new))
+      .map_err(|err| format!("{err:#}"))
+  });
+
+/// Gets the proxy resolver configured by the environment, which is only
+/// created once for the process since a proxy auto-configuration file is
+/// evaluated in a thread of its own.
+pub fn env_proxy_resolver() -> Result<Option<Arc<ProxyResolver>>, AnyError> {
+  ENV_PROXY_RESOLVER.clone().map_err(|err| anyhow!(err))
+}
+
+/// Resolves the proxy that a request is sent through, from the proxies of
+/// the `HTTP_PROXY`, `HTTPS_PROXY` and `ALL_PROXY` environment variables for
+/// the scheme of the request, or from the `FindProxyForURL` function of the
+/// proxy auto-configuration (PAC) file of `DENO_PROXY_PAC`.
+///
+/// The hosts of `NO_PROXY` are always connected to directly.
+pub struct ProxyResolver {
+  http: Option<Url>,
+  https: Option<Url>,
+  all: Option<Url>,
+  no_proxy: NoProxy,
+  maybe_pac: Option<PacResolver>,
+}
+
+impl ProxyResolver {
+  /// Creates the resolver of the environment, which is `None` when no proxy
+  /// is configured.
+  pub fn from_env() -> Result<Option<Self>, AnyError> {
+    Self::from_vars(|name| std::env::var(name).ok())
+  }
+
+  fn from_vars(
+    get_var: impl Fn(&str) -> Option<String>,
+  ) -> Result<Option<Self>, AnyError> {
+    // the lowercase variables take precedence, like with curl
+    let var = |name: &str| {
+      get_var(&name.to_lowercase())
+        .or_else(|| get_var(name))
+        .filter(|value| !value.trim().is_empty())
+    };
+    let proxy_url = |name: &str| -> Result<Option<Url>, AnyError> {
+      match var(name) {
+        Some(value) => parse_proxy_url(&value)
+          .with_context(|| format!("Invalid proxy in {name}: \"{value}\""))
+          .map(Some),
+        None => Ok(None),
+      }
+    };
+    let http = proxy_url("HTTP_PROXY")?;
+    let https = proxy_url("HTTPS_PROXY")?;
+    let all = proxy_url("ALL_PROXY")?;
+    let maybe_pac = match get_var("DENO_PROXY_PAC") {
+      Some(value) => {
+        let path = pac_file_path(&value)?;
+        let source = std::fs::read_to_string(&path).with_context(|| {
+          format!(
+            "Failed reading the proxy auto-configuration file {}",
+            path.display()
+          )
+        })?;
+        Some(PacResolver::new(source, PAC_TIMEOUT)?)
+      }
+      None => None,
+    };
+    if http.is_none() && https.is_none() && all.is_none() && maybe_pac.is_none()
+    {
+      return Ok(None);
+    }
+    Ok(Some(Self {
+      http,
+      https,
+      all,
+      no_proxy: NoProxy::parse(&var("NO_PROXY").unwrap_or_default()),
+      maybe_pac,
+    }))
+  }
+
+  /// Gets the proxy to send a request for the URL through, or `None` when
+  /// it's sent directly.
+  pub fn resolve(&self, url: &Url) -> Option<Url> {
+    if self.no_proxy.matches(url) {
+      return None;
+    }
+    if let Some(pac) = &self.maybe_pac {
+      return pac.resolve(url);
+    }
+    let maybe_proxy = match url.scheme() {
+      "http" => self.http.as_ref(),
+      "https" => self.https.as_ref(),
+      _ => None,
+    };
+    maybe_proxy.or(self.all.as_ref()).cloned()
+  }
+
+  /// Gets the proxy of a reqwest client, which is resolved for each request.
+  pub fn to_reqwest_proxy(self: &Arc<Self>) -> reqwest::Proxy {
+    let resolver = self.clone();
+    reqwest::Proxy::custom(move |url| resolver.resolve(url))
+  }
+}
+
+/// Parses the URL of a proxy, which is an HTTP proxy when there's no scheme
+/// (ex. `proxy.example.com:8080`).
+fn parse_proxy_url(value: &str) -> Result<Url, AnyError> {
+  let value = value.trim();
+  let url = if value.contains("://") {
+    Url::parse(value)?
+  } else {
+    Url::parse(&format!("http://{value}"))?
+  };
+  match url.scheme() {
+    "http" | "https" | "socks5" | "socks5h" => Ok(url),
+    scheme => bail!("Unsupported proxy scheme \"{}\".", scheme),
+  }
+}
+
+/// The path of the proxy auto-configuration file, which must be a local file
+/// since it's needed before any request is sent.
+fn pac_file_path(value: &str) -> Result<PathBuf, AnyError> {
+  match Url::parse(value) {
+    Ok(url) if url.scheme() == "file" => url
+      .to_file_path()
+      .map_err(|_| anyhow!("Invalid file URL in DENO_PROXY_PAC: \"{value}\"")),
+    Ok(url) if matches!(url.scheme(), "http" | "https") => bail!(
+      "The proxy auto-configuration file of DENO_PROXY_PAC must be a local file, but was \"{}\".",
+      value
+    ),
+    _ => Ok(PathBuf::from(value)),
+  }
+}
+
+/// The hosts of the `NO_PROXY` environment variable, which is a comma
+/// separated list of domains, IP addresses and ranges of IP addresses in
+/// CIDR notation (ex. `localhost,.example.com,10.0.0.0/8`).
+#[derive(Debug, Default, PartialEq, Eq)]
+struct NoProxy(Vec<NoProxyEntry>);
+
+#[derive(Debug, PartialEq, Eq)]
+enum NoProxyEntry {
+  /// `*`, which matches every host.
+  All,
+  /// A domain and its subdomains, which may be written with a leading `.` or
+  /// `*.` (ex. `example.com`, `.example.com` or `*.example.com`).
+  Domain(String),
+  /// An IP address, or a range of them with a prefix length less than the
+  /// length of the address.
+  Ip { addr: IpAddr, prefix_len: u8 },
+}
+
+impl NoProxy {
+  fn parse(value: &str) -> Self {
+    let mut entries = Vec::new();
+    for entry in value.split(',') {
+      let entry = entry.trim();
+      if entry.is_empty() {
+        continue;
+      }
+      match parse_no_proxy_entry(entry) {
+        Some(entry) => entries.push(entry),
+        None => log::warn!(
+          "{} Ignoring invalid NO_PROXY entry \"{}\".",
+          colors::yellow("Warning"),
+          entry
+        ),
+      }
+    }
+    Self(entries)
+  }
+
+  /// If the host of the URL is connected to directly. A range of IP
+  /// addresses only matches URLs with an IP address, since the host isn't
+  /// resolved.
+  fn matches(&self, url: &Url) -> bool {
+    let host = match url.host() {
+      Some(host) => host,
+      None => return false,
+    };
+    self.0.iter().any(|entry| match (entry, &host) {
+      (NoProxyEntry::All, _) => true,
+      (NoProxyEntry::Domain(domain), Host::Domain(host)) => {
+        let host = host.to_lowercase();
+        host == *domain
+          || host
+            .strip_suffix(domain.as_str())
+            .map(|subdomain| subdomain.ends_with('.'))
+            .unwrap_or(false)
+      }
+      (NoProxyEntry::Ip { addr, prefix_len }, Host::Ipv4(ip)) => {
+        ip_in_range(IpAddr::V4(*ip), *addr, *prefix_len)
+      }
+      (NoProxyEntry::Ip { addr, prefix_len }, Host::Ipv6(ip)) => {
+        ip_in_range(IpAddr::V6(*ip), *addr, *prefix_len)
+      }
+      _ => false,
+    })
+  }
+}
+
+fn parse_no_proxy_entry(entry: &str) -> Option<NoProxyEntry> {
+  if entry == "*" {
+    return Some(NoProxyEntry::All);
+  }
+  let parse_ip = |value: &str| {
+    value
+      .trim_start_matches('[')
+      .trim_end_matches(']')
+      .parse::<IpAddr>()
+      .ok()
+  };
+  if let Some((addr, prefix_len)) = entry.split_once('/') {
+    let addr = parse_ip(addr)?;
+    let prefix_len = prefix_len.parse::<u8>().ok()?;
+    let max_prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+    if prefix_len > max_prefix_len {
+      return None;
+    }
+    return Some(NoProxyEntry::Ip { addr, prefix_len });
+  }
+  if let Some(addr) = parse_ip(entry) {
+    let prefix_len = if addr.is_ipv4() { 32 } else { 128 };
+    return Some(NoProxyEntry::Ip { addr, prefix_len });
+  }
+  let domain = entry.trim_start_matches("*.").trim_start_matches('.');
+  // the port of the entry is ignored
+  let domain = match domain.split_once(':') {
+    Some((domain, _)) => domain,
+    None => domain,
+  };
+  if domain.is_empty() || domain.contains('*') {
+    return None;
+  }
+  Some(NoProxyEntry::Domain(domain.to_lowercase()))
+}
+
+fn ip_in_range(ip: IpAddr, addr: IpAddr, prefix_len: u8) -> bool {
+  match (ip, addr) {
+    (IpAddr::V4(ip), IpAddr::V4(addr)) => {
+      let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+      u32::from(ip) & mask == u32::from(addr) & mask
+    }
+    (IpAddr::V6(ip), IpAddr::V6(addr)) => {
+      let mask = u128::MAX.checked_shl(128 - prefix_len as u32).unwrap_or(0);
+      u128::from(ip) & mask == u128::from(addr) & mask
+    }
+    _ => false,
+  }
+}
+
+struct PacRequest {
+  url: String,
+  host: String,
+  response_sender: mpsc::Sender<Result<String, AnyError>>,
+}
+
+/// Evaluates the `FindProxyForURL` function of a proxy auto-configuration
+/// file in a JS runtime of its own thread, since the proxy of a request is
+/// resolved synchronously by the HTTP client.
+struct PacResolver {
+  sender: Mutex<mpsc::Sender<PacRequest>>,
+  /// The proxies for each origin, which the result is assumed to only depend
+  /// on, like with browsers that strip the path of the URL they pass.
+  cache: Mutex<HashMap<String, Option<Url>>>,
+}
+
+impl PacResolver {
+  fn new(source: String, timeout: Duration) -> Result<Self, AnyError> {
+    let (sender, receiver) = mpsc::channel::<PacRequest>();
+    let (ready_sender, ready_receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+      let mut runtime = match create_pac_runtime(source, timeout) {
+        Ok(runtime) => {
+          let _ = ready_sender.send(Ok(()));
+          runtime
+        }
+        Err(err) => {
+          let _ = ready_sender.send(Err(err));
+          return;
+        }
+      };
+      // stops when the resolver is dropped
+      for request in receiver {
+        let result = run_with_timeout(&mut runtime, timeout, |runtime| {
+          find_proxy_for_url(runtime, &request.url, &request.host)
+        });
+        let _ = request.response_sender.send(result);
+      }
+    });
+    ready_receiver
+      .recv()
+      .context("The proxy auto-configuration runtime stopped.")??;
+    Ok(Self {
+      sender: Mutex::new(sender),
+      cache: Default::default(),
+    })
+  }
+
+  fn resolve(&self, url: &Url) -> Option<Url> {
+    let origin = url.origin().ascii_serialization();
+    if let Some(maybe_proxy) = self.cache.lock().get(&origin) {
+      return maybe_proxy.clone();
+    }
+    let result = self
+      .evaluate(&format!("{origin}/"), url.host_str().unwrap_or_default())
+      .and_then(|result| parse_pac_result(&result));
+    let maybe_proxy = match result {
+      Ok(maybe_proxy) => maybe_proxy,
+      Err(err) => {
+        log::warn!(
+          "{} Failed resolving the proxy for {} with DENO_PROXY_PAC, so it's connected to directly: {:#}",
+          colors::yellow("Warning"),
+          origin,
+          err
+        );
+        None
+      }
+    };
+    self.cache.lock().insert(origin, maybe_proxy.clone());
+    maybe_proxy
+  }
+
+  fn evaluate(&self, url: &str, host: &str) -> Result<String, AnyError> {
+    let (response_sender, response_receiver) = mpsc::channel();
+    let request = PacRequest {
+      url: url.to_string(),
+      host: host.to_string(),
+      response_sender,
+    };
+    self
+      .sender
+      .lock()
+      .send(request)
+      .map_err(|_| anyhow!("The proxy auto-configuration runtime stopped."))?;
+    response_receiver
+      .recv()
+      .context("The proxy auto-configuration runtime stopped.")?
+  }
+}
+
+deno_core::extension!(
+  deno_proxy_pac,
+  ops = [op_pac_dns_resolve, op_pac_my_ip_address],
+);
+
+fn create_pac_runtime(
+  source: String,
+  timeout: Duration,
+) -> Result<JsRuntime, AnyError> {
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![deno_proxy_pac::init_ops()],
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static("ext:deno_proxy_pac/pac_utils.js", PAC_UTILS)?;
+  run_with_timeout(&mut runtime, timeout, |runtime| {
+    runtime.execute_script("proxy.pac", source.into())
+  })
+  .context("Failed loading the proxy auto-configuration file")?;
+  Ok(runtime)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PacCallState {
+  Running,
+  Done,
+  TimedOut,
+}
+
+/// Runs the JS of the proxy auto-configuration file, terminating its
+/// execution when it doesn't complete within the timeout. The runtime can
+/// still be used afterwards.
+fn run_with_timeout<T>(
+  runtime: &mut JsRuntime,
+  timeout: Duration,
+  f: impl FnOnce(&mut JsRuntime) -> Result<T, AnyError>,
+) -> Result<T, AnyError> {
+  let state = Arc::new(Mutex::new(PacCallState::Running));
+  let (done_sender, done_receiver) = mpsc::channel::<()>();
+  std::thread::spawn({
+    let state = state.clone();
+    let isolate_handle = runtime.v8_isolate().thread_safe_handle();
+    move || {
+      if let Err(mpsc::RecvTimeoutError::Timeout) =
+        done_receiver.recv_timeout(timeout)
+      {
+        // the lock ensures the execution is only terminated while the call
+        // is still running
+        let mut state = state.lock();
+        if *state == PacCallState::Running {
+          *state = PacCallState::TimedOut;
+          isolate_handle.terminate_execution();
+        }
+      }
+    }
+  });
+  let result = f(runtime);
+  let timed_out = {
+    let mut state = state.lock();
+    let timed_out = *state == PacCallState::TimedOut;
+    *state = PacCallState::Done;
+    timed_out
+  };
+  drop(done_sender);
+  if timed_out {
+    runtime.v8_isolate().cancel_terminate_execution();
+    bail!(
+      "The proxy auto-configuration file didn't complete within {} seconds.",
+      timeout.as_secs_f64()
+    );
+  }
+  result
+}
+
+fn find_proxy_for_url(
+  runtime: &mut JsRuntime,
+  url: &str,
+  host: &str,
+) -> Result<String, AnyError> {
+  let source = format!(
+    "FindProxyForURL({}, {})",
+    serde_json::to_string(url)?,
+    serde_json::to_string(host)?
+  );
+  let value = runtime.execute_script("proxy.pac", source.into())?;
+  let scope = &mut runtime.handle_scope();
+  let value = v8::Local::new(scope, value);
+  serde_v8::from_v8(scope, value)
+    .context("FindProxyForURL must return a string.")
+}
+
+/// Gets the proxy of the first supported entry of the result of
+/// `FindProxyForURL` (ex. `PROXY proxy.example.com:8080; DIRECT`).
+fn parse_pac_result(result: &str) -> Result<Option<Url>, AnyError> {
+  for entry in result.split(';') {
+    let mut parts = entry.split_whitespace();
+    let kind = match parts.next() {
+      Some(kind) => kind.to_uppercase(),
+      None => continue,
+    };
+    let scheme = match kind.as_str() {
+      "DIRECT" => return Ok(None),
+      "PROXY" | "HTTP" => "http",
+      "HTTPS" => "https",
+      "SOCKS" | "SOCKS5" => "socks5",
+      _ => continue,
+    };
+    if let Some(address) = parts.next() {
+      return Ok(Some(Url::parse(&format!("{scheme}://{address}"))?));
+    }
+  }
+  bail!("No supported proxy in \"{}\".", result)
+}
+
+/// Resolves the IPv4 address of a host for `dnsResolve()`.
+#[op]
+fn op_pac_dns_resolve(host: String) -> Option<String> {
+  (host.as_str(), 0)
+    .to_socket_addrs()
+    .ok()?
+    .find(|addr| addr.is_ipv4())
+    .map(|addr| addr.ip().to_string())
+}
+
+/// Gets the IPv4 address of the machine for `myIpAddress()`, which is the
+/// one of the interface used to connect to a public address.
+#[op]
+fn op_pac_my_ip_address() -> String {
+  // connecting a UDP socket doesn't send anything
+  UdpSocket::bind("0.0.0.0:0")
+    .and_then(|socket| {
+      socket.connect("8.8.8.8:80")?;
+      socket.local_addr()
+    })
+    .map(|addr| addr.ip().to_string())
+    .unwrap_or_else(|_| "127.0.0.1".to_string())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn url(url: &str) -> Url {
+    Url::parse(url).unwrap()
+  }
+
+  #[test]
+  fn test_no_proxy() {
+    let no_proxy = NoProxy::parse(
+      "localhost, .example.com,*.deno.land,internal:8080,10.0.0.0/8,192.168.1.1,[::1],fd00::/8",
+    );
+    assert!(no_proxy.matches(&url("http://localhost:4545/mod.ts")));
+    assert!(no_proxy.matches(&url("https://example.com/mod.ts")));
+    assert!(no_proxy.matches(&url("https://cdn.example.com/mod.ts")));
+    assert!(!no_proxy.matches(&url("https://notexample.com/mod.ts")));
+    assert!(no_proxy.matches(&url("https://deno.land/std/mod.ts")));
+    assert!(no_proxy.matches(&url("http://internal/mod.ts")));
+    assert!(no_proxy.matches(&url("http://10.1.2.3/mod.ts")));
+    assert!(!no_proxy.matches(&url("http://11.1.2.3/mod.ts")));
+    assert!(no_proxy.matches(&url("http://192.168.1.1/mod.ts")));
+    assert!(!no_proxy.matches(&url("http://192.168.1.2/mod.ts")));
+    assert!(no_proxy.matches(&url("http://[::1]:8000/mod.ts")));
+    assert!(no_proxy.matches(&url("http://[fd12::1]/mod.ts")));
+    assert!(!no_proxy.matches(&url("http://[fe80::1]/mod.ts")));
+
+    assert!(NoProxy::parse("*").matches(&url("https://deno.land")));
+    assert!(NoProxy::parse("0.0.0.0/0").matches(&url("http://1.2.3.4")));
+    assert_eq!(NoProxy::parse("10.0.0.0/33,,"), NoProxy(vec![]));
+  }
+
+  #[test]
+  fn test_proxy_for_scheme() {
+    let vars = HashMap::from([
+      ("HTTP_PROXY", "proxy.example.com:3128"),
+      ("https_proxy", "https://secure-proxy.example.com"),
+      ("HTTPS_PROXY", "http://ignored.example.com"),
+      ("ALL_PROXY", "socks5://socks.example.com:1080"),
+      ("NO_PROXY", "deno.land"),
+    ]);
+    let resolver =
+      ProxyResolver::from_vars(|name| vars.get(name).map(|v| v.to_string()))
+        .unwrap()
+        .unwrap();
+    let resolve = |url: &str| resolver.resolve(&Url::parse(url).unwrap());
+    assert_eq!(
+      resolve("http://esm.sh/mod.ts"),
+      Some(url("http://proxy.example.com:3128"))
+    );
+    assert_eq!(
+      resolve("https://esm.sh/mod.ts"),
+      Some(url("https://secure-proxy.example.com"))
+    );
+    assert_eq!(
+      resolve("ws://esm.sh/"),
+      Some(url("socks5://socks.example.com:1080"))
+    );
+    assert_eq!(resolve("https://deno.land/std/mod.ts"), None);
+
+    assert!(ProxyResolver::from_vars(|_| None).unwrap().is_none());
+    let err = ProxyResolver::from_vars(|name| {
+      (name == "HTTP_PROXY").then(|| "ftp://proxy.example.com".to_string())
+    })
+    .err()
+    .unwrap();
+    assert_eq!(
+      format!("{err:#}"),
+      "Invalid proxy in HTTP_PROXY: \"ftp://proxy.example.com\": Unsupported proxy scheme \"ftp\"."
+    );
+  }
+
+  #[test]
+  fn test_parse_pac_result() {
+    assert_eq!(
+      parse_pac_result("PROXY proxy.example.com:8080; DIRECT").unwrap(),
+      Some(url("http://proxy.example.com:8080"))
+    );
+    assert_eq!(
+      parse_pac_result("SOCKS4 old.example.com:1080; SOCKS5 socks:1080")
+        .unwrap(),
+      Some(url("socks5://socks:1080"))
+    );
+    assert_eq!(parse_pac_result("DIRECT").unwrap(), None);
+    assert!(parse_pac_result("").is_err());
+  }
+
+  #[test]
+  fn test_pac_resolver() {
+    let resolver = PacResolver::new(
+      r#"
+function FindProxyForURL(url, host) {
+  if (isPlainHostName(host) || shExpMatch(host, "*.internal")) {
+    return "DIRECT";
+  }
+  if (isInNet(host, "10.0.0.0", "255.0.0.0")) {
+    return "PROXY intranet-proxy:8080";
+  }
+  return url.startsWith("https:") ? "HTTPS secure-proxy:443" : "PROXY proxy:3128";
+}
+"#
+      .to_string(),
+      PAC_TIMEOUT,
+    )
+    .unwrap();
+    assert_eq!(resolver.resolve(&url("http://intranet/mod.ts")), None);
+    assert_eq!(resolver.resolve(&url("https://git.internal/mod.ts")), None);
+    assert_eq!(
+      resolver.resolve(&url("http://10.1.2.3/mod.ts")),
+      Some(url("http://intranet-proxy:8080"))
+    );
+    assert_eq!(
+      resolver.resolve(&url("https://deno.land/std/mod.ts")),
+      Some(url("https://secure-proxy:443"))
+    );
+    assert_eq!(
+      resolver.resolve(&url("http://deno.land/std/mod.ts")),
+      Some(url("http://proxy:3128"))
+    );
+
+    assert!(PacResolver::new(
+      "function FindProxyForURL(".to_string(),
+      PAC_TIMEOUT
+    )
+    .is_err());
+  }
+
+  #[test]
+  fn test_pac_resolver_timeout() {
+    let timeout = Duration::from_millis(100);
+    let resolver = PacResolver::new(
+      r#"
+function FindProxyForURL(url, host) {
+  while (host === "hang.example.com") {}
+  return "PROXY proxy:3128";
+}
+"#
+      .to_string(),
+      timeout,
+    )
+    .unwrap();
+    assert_eq!(resolver.resolve(&url("https://hang.example.com/")), None);
+    // the runtime is still usable after the execution was terminated
+    assert_eq!(
+      resolver.resolve(&url("https://deno.land/")),
+      Some(url("http://proxy:3128"))
+    );
+
+    let result = PacResolver::new("while (true) {}".to_string(), timeout);
+    assert!(format!("{:#}", result.err().unwrap())
+      .contains("didn't complete within 0.1 seconds"));
+  }
+}
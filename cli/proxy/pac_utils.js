@@ -0,0 +1,176 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+// The functions that proxy auto-configuration files can call, see
+// https://developer.mozilla.org/en-US/docs/Web/HTTP/Proxy_servers_and_tunneling/Proxy_Auto-Configuration_PAC_file
+
+((window) => {
+  const { ops } = window.Deno.core;
+
+  const WEEKDAYS = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+  const MONTHS = [
+    "JAN",
+    "FEB",
+    "MAR",
+    "APR",
+    "MAY",
+    "JUN",
+    "JUL",
+    "AUG",
+    "SEP",
+    "OCT",
+    "NOV",
+    "DEC",
+  ];
+
+  function dnsResolve(host) {
+    return ops.op_pac_dns_resolve(host);
+  }
+
+  function myIpAddress() {
+    return ops.op_pac_my_ip_address();
+  }
+
+  function isPlainHostName(host) {
+    return !host.includes(".");
+  }
+
+  function dnsDomainIs(host, domain) {
+    return host.length >= domain.length && host.endsWith(domain);
+  }
+
+  function localHostOrDomainIs(host, hostdom) {
+    return host === hostdom || hostdom.startsWith(host + ".");
+  }
+
+  function isResolvable(host) {
+    return dnsResolve(host) !== null;
+  }
+
+  function convertAddr(ipaddr) {
+    const bytes = ipaddr.split(".");
+    return (((bytes[0] & 0xff) << 24) | ((bytes[1] & 0xff) << 16) |
+      ((bytes[2] & 0xff) << 8) | (bytes[3] & 0xff)) >>> 0;
+  }
+
+  function isInNet(ipaddr, pattern, mask) {
+    if (!/^\d+\.\d+\.\d+\.\d+$/.test(ipaddr)) {
+      ipaddr = dnsResolve(ipaddr);
+      if (ipaddr === null) {
+        return false;
+      }
+    }
+    const maskBits = convertAddr(mask);
+    return ((convertAddr(ipaddr) & maskBits) >>> 0) ===
+      ((convertAddr(pattern) & maskBits) >>> 0);
+  }
+
+  function dnsDomainLevels(host) {
+    return host.split(".").length - 1;
+  }
+
+  function shExpMatch(str, shexp) {
+    const pattern = shexp
+      .replace(/[.+^${}()|[\]\\]/g, "\\$&")
+      .replace(/\*/g, ".*")
+      .replace(/\?/g, ".");
+    return new RegExp(`^${pattern}$`).test(str);
+  }
+
+  // removes the trailing "GMT" argument of the time based functions
+  function takeGmt(args) {
+    const gmt = args[args.length - 1] === "GMT";
+    if (gmt) {
+      args.pop();
+    }
+    return gmt;
+  }
+
+  // if the value is in the inclusive range, which wraps around when the
+  // start comes after the end (ex. from friday to monday)
+  function inRange(value, start, end) {
+    return start <= end
+      ? start <= value && value <= end
+      : value >= start || value <= end;
+  }
+
+  function weekdayRange(...args) {
+    const gmt = takeGmt(args);
+    const now = new Date();
+    const day = gmt ? now.getUTCDay() : now.getDay();
+    const start = WEEKDAYS.indexOf(args[0]);
+    const end = args.length > 1 ? WEEKDAYS.indexOf(args[1]) : start;
+    if (start === -1 || end === -1) {
+      return false;
+    }
+    return inRange(day, start, end);
+  }
+
+  function dateRange(...args) {
+    const gmt = takeGmt(args);
+    if (args.length === 0 || (args.length > 1 && args.length % 2 !== 0)) {
+      return false;
+    }
+    const now = new Date();
+    const current = {
+      year: gmt ? now.getUTCFullYear() : now.getFullYear(),
+      month: gmt ? now.getUTCMonth() : now.getMonth(),
+      day: gmt ? now.getUTCDate() : now.getDate(),
+    };
+    // each argument is a day of the month, the name of a month or a year
+    const parts = args.map((arg) => {
+      if (typeof arg === "string") {
+        return { month: MONTHS.indexOf(arg) };
+      }
+      return arg > 31 ? { year: arg } : { day: arg };
+    });
+    const half = parts.length === 1 ? 1 : parts.length / 2;
+    const start = Object.assign({}, ...parts.slice(0, half));
+    const end = parts.length === 1
+      ? start
+      : Object.assign({}, ...parts.slice(half));
+    // compares the date by the components of the range
+    const value = (date) =>
+      ((("year" in start) ? date.year : 0) * 12 +
+        (("month" in start) ? date.month : 0)) * 32 +
+      (("day" in start) ? date.day : 0);
+    return inRange(value(current), value(start), value(end));
+  }
+
+  function timeRange(...args) {
+    const gmt = takeGmt(args);
+    if (![1, 2, 4, 6].includes(args.length)) {
+      return false;
+    }
+    const now = new Date();
+    const current = gmt
+      ? [now.getUTCHours(), now.getUTCMinutes(), now.getUTCSeconds()]
+      : [now.getHours(), now.getMinutes(), now.getSeconds()];
+    if (args.length === 1) {
+      return current[0] === args[0];
+    }
+    // compares the time with the precision of the range
+    const precision = args.length / 2;
+    const value = (time) =>
+      time.slice(0, precision).reduce((value, unit) => value * 60 + unit, 0);
+    return inRange(
+      value(current),
+      value(args.slice(0, precision)),
+      value(args.slice(precision)),
+    );
+  }
+
+  Object.assign(window, {
+    dateRange,
+    dnsDomainIs,
+    dnsDomainLevels,
+    dnsResolve,
+    isInNet,
+    isPlainHostName,
+    isResolvable,
+    localHostOrDomainIs,
+    myIpAddress,
+    shExpMatch,
+    timeRange,
+    weekdayRange,
+  });
+})(globalThis);
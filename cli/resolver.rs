@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use deno_core::anyhow::anyhow;
+use deno_core::error::custom_error;
 use deno_core::error::AnyError;
 use deno_core::futures::future;
 use deno_core::futures::future::LocalBoxFuture;
@@ -12,6 +13,7 @@ use deno_graph::source::NpmResolver;
 use deno_graph::source::Resolver;
 use deno_graph::source::UnknownBuiltInNodeModuleError;
 use deno_graph::source::DEFAULT_JSX_IMPORT_SOURCE_MODULE;
+use deno_graph::Range;
 use deno_npm::registry::NpmRegistryApi;
 use deno_runtime::deno_node::is_builtin_node_module;
 use deno_semver::npm::NpmPackageReq;
@@ -20,9 +22,11 @@ use std::sync::Arc;
 
 use crate::args::package_json::PackageJsonDeps;
 use crate::args::JsxImportSourceConfig;
+use crate::cache::InferredTypesCache;
 use crate::npm::CliNpmRegistryApi;
 use crate::npm::NpmResolution;
 use crate::npm::PackageJsonDepsInstaller;
+use crate::util::fs::canonicalize_file_specifier;
 use crate::util::sync::AtomicFlag;
 
 /// A resolver that takes care of resolution, taking into account loaded
@@ -30,9 +34,15 @@ use crate::util::sync::AtomicFlag;
 #[derive(Debug)]
 pub struct CliGraphResolver {
   maybe_import_map: Option<Arc<ImportMap>>,
+  /// Substitutes the resolved modules, like the mocks of a test run.
+  maybe_import_map_overrides: Option<Arc<ImportMap>>,
+  /// Provides the declarations inferred for untyped remote modules.
+  maybe_inferred_types_cache: Option<InferredTypesCache>,
   maybe_default_jsx_import_source: Option<String>,
   maybe_jsx_import_source_module: Option<String>,
   no_npm: bool,
+  canonicalize_symlinks: bool,
+  vendor_only: bool,
   npm_registry_api: Arc<CliNpmRegistryApi>,
   npm_resolution: Arc<NpmResolution>,
   package_json_deps_installer: Arc<PackageJsonDepsInstaller>,
@@ -52,9 +62,13 @@ impl Default for CliGraphResolver {
     ));
     Self {
       maybe_import_map: Default::default(),
+      maybe_import_map_overrides: Default::default(),
+      maybe_inferred_types_cache: Default::default(),
       maybe_default_jsx_import_source: Default::default(),
       maybe_jsx_import_source_module: Default::default(),
       no_npm: false,
+      canonicalize_symlinks: false,
+      vendor_only: false,
       npm_registry_api,
       npm_resolution,
       package_json_deps_installer: Default::default(),
@@ -65,22 +79,31 @@ impl Default for CliGraphResolver {
 }
 
 impl CliGraphResolver {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     maybe_jsx_import_source_config: Option<JsxImportSourceConfig>,
     maybe_import_map: Option<Arc<ImportMap>>,
+    maybe_import_map_overrides: Option<Arc<ImportMap>>,
+    maybe_inferred_types_cache: Option<InferredTypesCache>,
     no_npm: bool,
+    canonicalize_symlinks: bool,
+    vendor_only: bool,
     npm_registry_api: Arc<CliNpmRegistryApi>,
     npm_resolution: Arc<NpmResolution>,
     package_json_deps_installer: Arc<PackageJsonDepsInstaller>,
   ) -> Self {
     Self {
       maybe_import_map,
+      maybe_import_map_overrides,
+      maybe_inferred_types_cache,
       maybe_default_jsx_import_source: maybe_jsx_import_source_config
         .as_ref()
         .and_then(|c| c.default_specifier.clone()),
       maybe_jsx_import_source_module: maybe_jsx_import_source_config
         .map(|c| c.module),
       no_npm,
+      canonicalize_symlinks,
+      vendor_only,
       npm_registry_api,
       npm_resolution,
       package_json_deps_installer,
@@ -134,6 +157,45 @@ impl Resolver for CliGraphResolver {
     &self,
     specifier: &str,
     referrer: &ModuleSpecifier,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    let specifier = self.resolve_inner(specifier, referrer)?;
+    let specifier = self.resolve_override(specifier, referrer);
+    // with `"vendor": "only"` the import map of the vendor directory maps
+    // every vendored remote module to a local file
+    if self.vendor_only && matches!(specifier.scheme(), "http" | "https") {
+      return Err(custom_error(
+        "NoRemote",
+        format!("Module not found in the vendor directory: \"{specifier}\" (imported from \"{referrer}\"), and \"vendor\" is set to \"only\" in the configuration file."),
+      ));
+    }
+    if self.canonicalize_symlinks {
+      Ok(canonicalize_file_specifier(specifier))
+    } else {
+      Ok(specifier)
+    }
+  }
+
+  fn resolve_types(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<Option<(ModuleSpecifier, Option<Range>)>, AnyError> {
+    // this is only called for JavaScript modules that don't have types from
+    // an `X-TypeScript-Types` header or a types directive
+    Ok(
+      self
+        .maybe_inferred_types_cache
+        .as_ref()
+        .and_then(|cache| cache.get_types_specifier(specifier))
+        .map(|types_specifier| (types_specifier, None)),
+    )
+  }
+}
+
+impl CliGraphResolver {
+  fn resolve_inner(
+    &self,
+    specifier: &str,
+    referrer: &ModuleSpecifier,
   ) -> Result<ModuleSpecifier, AnyError> {
     // attempt to resolve with the import map first
     let maybe_import_map_err = match self
@@ -162,6 +224,24 @@ impl Resolver for CliGraphResolver {
       deno_graph::resolve_import(specifier, referrer).map_err(|err| err.into())
     }
   }
+
+  /// Maps the resolved module with the import map overrides. The module that
+  /// substitutes another one can still import it, so that a mock can re-export
+  /// parts of the original module.
+  fn resolve_override(
+    &self,
+    specifier: ModuleSpecifier,
+    referrer: &ModuleSpecifier,
+  ) -> ModuleSpecifier {
+    let import_map_overrides = match &self.maybe_import_map_overrides {
+      Some(import_map_overrides) => import_map_overrides,
+      None => return specifier,
+    };
+    match import_map_overrides.resolve(specifier.as_str(), referrer) {
+      Ok(substitute) if substitute != *referrer => substitute,
+      _ => specifier,
+    }
+  }
 }
 
 fn resolve_package_json_dep(
@@ -325,4 +405,50 @@ mod test {
     // non-existent bare specifier
     assert_eq!(resolve("non-existent", &deps).unwrap(), None);
   }
+
+  #[test]
+  fn test_resolve_import_map_overrides() {
+    let base = ModuleSpecifier::parse("file:///project/mocks.json").unwrap();
+    let import_map_overrides = import_map::parse_from_json(
+      &base,
+      r#"{
+        "imports": { "./db.ts": "./db.mock.ts" },
+        "scopes": { "./other/": { "./db.ts": "./other/db.mock.ts" } }
+      }"#,
+    )
+    .unwrap()
+    .import_map;
+    let resolver = CliGraphResolver {
+      maybe_import_map_overrides: Some(Arc::new(import_map_overrides)),
+      ..Default::default()
+    };
+    let resolve = |specifier: &str, referrer: &str| {
+      resolver
+        .resolve(specifier, &ModuleSpecifier::parse(referrer).unwrap())
+        .unwrap()
+        .to_string()
+    };
+
+    assert_eq!(
+      resolve("./db.ts", "file:///project/main_test.ts"),
+      "file:///project/db.mock.ts"
+    );
+    assert_eq!(
+      resolve("../db.ts", "file:///project/src/mod.ts"),
+      "file:///project/db.mock.ts"
+    );
+    assert_eq!(
+      resolve("../db.ts", "file:///project/other/mod.ts"),
+      "file:///project/other/db.mock.ts"
+    );
+    // the mock can import the module it substitutes
+    assert_eq!(
+      resolve("./db.ts", "file:///project/db.mock.ts"),
+      "file:///project/db.ts"
+    );
+    assert_eq!(
+      resolve("./util.ts", "file:///project/main_test.ts"),
+      "file:///project/util.ts"
+    );
+  }
 }
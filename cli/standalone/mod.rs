@@ -0,0 +1,810 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::args::CaData;
+use crate::args::Flags;
+use crate::colors;
+use crate::file_fetcher::get_source_from_data_url;
+use crate::ops;
+use crate::proc_state::ProcState;
+use crate::util::text_encoding::source_map_from_code;
+use crate::util::v8::construct_v8_flags;
+use crate::version;
+use crate::CliGraphResolver;
+use deno_ast::MediaType;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures;
+use deno_core::futures::io::AllowStdIo;
+use deno_core::futures::task::LocalFutureObj;
+use deno_core::futures::AsyncReadExt;
+use deno_core::futures::AsyncSeekExt;
+use deno_core::futures::FutureExt;
+use deno_core::located_script_name;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use deno_core::sourcemap::SourceMap;
+use deno_core::url::Url;
+use deno_core::v8_set_flags;
+use deno_core::ModuleCode;
+use deno_core::ModuleLoader;
+use deno_core::ModuleSpecifier;
+use deno_core::ModuleType;
+use deno_core::ResolutionKind;
+use deno_core::Snapshot;
+use deno_core::SourceMapGetter;
+use deno_graph::source::Resolver;
+use deno_runtime::deno_fs::FsOverlay;
+use deno_runtime::fmt_errors::format_js_error;
+use deno_runtime::ops::worker_host::CreateWebWorkerCb;
+use deno_runtime::ops::worker_host::WorkerEventCb;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::permissions::PermissionsOptions;
+use deno_runtime::web_worker::WebWorker;
+use deno_runtime::web_worker::WebWorkerOptions;
+use deno_runtime::worker::MainWorker;
+use deno_runtime::worker::WorkerOptions;
+use deno_runtime::BootstrapOptions;
+use import_map::parse_from_json;
+use log::Level;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env::current_exe;
+use std::ffi::OsStr;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::Poll;
+
+pub mod self_update;
+pub mod virtual_fs;
+
+use self_update::SelfUpdateConfig;
+use self_update::SelfUpdater;
+use virtual_fs::embedded_data_len;
+use virtual_fs::EmbeddedDir;
+use virtual_fs::VirtualFs;
+
+#[derive(Deserialize, Serialize)]
+pub struct Metadata {
+  pub argv: Vec<String>,
+  pub unstable: bool,
+  #[serde(default)]
+  pub unstable_features: Vec<String>,
+  pub seed: Option<u64>,
+  pub permissions: PermissionsOptions,
+  pub location: Option<Url>,
+  pub v8_flags: Vec<String>,
+  pub log_level: Option<Level>,
+  pub ca_stores: Option<Vec<String>>,
+  pub ca_data: Option<Vec<u8>>,
+  pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  pub maybe_import_map: Option<(Url, String)>,
+  pub entrypoint: ModuleSpecifier,
+  /// The length of the V8 startup snapshot of the startup modules, which is
+  /// stored right before the metadata.
+  #[serde(default)]
+  pub startup_snapshot_len: Option<u64>,
+  /// The directories of `--include-dir`, whose files are stored right before
+  /// the startup snapshot.
+  #[serde(default)]
+  pub embedded_dirs: Vec<EmbeddedDir>,
+  /// The configuration of `--update-url`.
+  #[serde(default)]
+  pub self_update: Option<SelfUpdateConfig>,
+}
+
+pub const MAGIC_TRAILER: &[u8; 8] = b"d3n0l4nd";
+
+/// The file in the resources of a macOS app bundle created by `deno compile`
+/// with the bundle, snapshot and metadata, which are stored outside of the
+/// executable so that it can be signed.
+pub const APP_BUNDLE_PAYLOAD_FILE_NAME: &str = "standalone.bin";
+
+/// This function will try to run this binary as a standalone binary
+/// produced by `deno compile`. It determines if this is a standalone
+/// binary by checking for the magic trailer string `d3n0l4nd` at EOF-24.
+/// The magic trailer is followed by:
+/// - a u64 pointer to the JS bundle embedded in the binary
+/// - a u64 pointer to JSON metadata (serialized flags) embedded in the binary
+/// These are dereferenced, and the bundle is executed under the configuration
+/// specified by the metadata. A V8 startup snapshot, which is stored between
+/// the bundle and the metadata, is also returned when the metadata has one,
+/// along with the file system of the embedded directories before it.
+/// If no magic trailer is present, this function exits with `Ok(None)`.
+#[allow(clippy::type_complexity)]
+pub async fn extract_standalone(
+  args: Vec<String>,
+) -> Result<
+  Option<(Metadata, eszip::EszipV2, Option<Vec<u8>>, Option<VirtualFs>)>,
+  AnyError,
+> {
+  let current_exe_path = current_exe()?;
+  let payload_path = payload_path(&current_exe_path);
+
+  let mut file = std::fs::File::open(&payload_path)?;
+  let trailer_pos = match find_trailer_pos(&mut file)? {
+    Some(trailer_pos) => trailer_pos,
+    None => return Ok(None),
+  };
+
+  let mut bufreader =
+    deno_core::futures::io::BufReader::new(AllowStdIo::new(file));
+
+  bufreader.seek(SeekFrom::Start(trailer_pos)).await?;
+  let mut trailer = [0; 24];
+  bufreader.read_exact(&mut trailer).await?;
+  let (_, rest) = trailer.split_at(8);
+
+  let (eszip_archive_pos, rest) = rest.split_at(8);
+  let metadata_pos = rest;
+  let eszip_archive_pos = u64_from_bytes(eszip_archive_pos)?;
+  let metadata_pos = u64_from_bytes(metadata_pos)?;
+  let metadata_len = trailer_pos - metadata_pos;
+
+  bufreader.seek(SeekFrom::Start(eszip_archive_pos)).await?;
+
+  let (eszip, loader) = eszip::EszipV2::parse(bufreader)
+    .await
+    .context("Failed to parse eszip header")?;
+
+  let mut bufreader = loader.await.context("Failed to parse eszip archive")?;
+
+  bufreader.seek(SeekFrom::Start(metadata_pos)).await?;
+
+  let mut metadata = String::new();
+
+  bufreader
+    .take(metadata_len)
+    .read_to_string(&mut metadata)
+    .await
+    .context("Failed to read metadata from the current executable")?;
+
+  let mut metadata: Metadata = serde_json::from_str(&metadata).unwrap();
+  metadata.argv.append(&mut args[1..].to_vec());
+
+  let maybe_startup_snapshot = match metadata.startup_snapshot_len {
+    Some(len) => {
+      bufreader.seek(SeekFrom::Start(metadata_pos - len)).await?;
+      let mut startup_snapshot = vec![0; len as usize];
+      bufreader
+        .read_exact(&mut startup_snapshot)
+        .await
+        .context("Failed to read the startup snapshot")?;
+      Some(startup_snapshot)
+    }
+    None => None,
+  };
+
+  let maybe_vfs = if metadata.embedded_dirs.is_empty() {
+    None
+  } else {
+    let data_pos = metadata_pos
+      - metadata.startup_snapshot_len.unwrap_or(0)
+      - embedded_data_len(&metadata.embedded_dirs);
+    Some(VirtualFs::new(
+      payload_path,
+      data_pos,
+      &metadata.embedded_dirs,
+    ))
+  };
+
+  Ok(Some((metadata, eszip, maybe_startup_snapshot, maybe_vfs)))
+}
+
+/// The file with the bundle of the executable, which is the executable itself
+/// unless it is in a macOS app bundle created by `deno compile`.
+fn payload_path(current_exe_path: &Path) -> PathBuf {
+  if cfg!(target_os = "macos") {
+    let maybe_contents_dir = current_exe_path
+      .parent()
+      .filter(|dir| dir.file_name() == Some(OsStr::new("MacOS")))
+      .and_then(|dir| dir.parent());
+    if let Some(contents_dir) = maybe_contents_dir {
+      let payload_path = contents_dir
+        .join("Resources")
+        .join(APP_BUNDLE_PAYLOAD_FILE_NAME);
+      if payload_path.exists() {
+        return payload_path;
+      }
+    }
+  }
+  current_exe_path.to_path_buf()
+}
+
+/// Finds the position of the magic trailer, which is at the end of the file
+/// unless a Windows Authenticode signature was appended after it.
+pub fn find_trailer_pos(
+  file: &mut std::fs::File,
+) -> Result<Option<u64>, std::io::Error> {
+  let file_len = file.seek(SeekFrom::End(0))?;
+  let mut end_positions = vec![file_len];
+  let mut header = Vec::new();
+  file.seek(SeekFrom::Start(0))?;
+  file.by_ref().take(1024).read_to_end(&mut header)?;
+  if let Some(signature_pos) = authenticode_signature_pos(&header, file_len) {
+    // the signature is aligned to eight bytes with zero padding
+    end_positions
+      .extend((0..8).filter_map(|padding| signature_pos.checked_sub(padding)));
+  }
+  for end_pos in end_positions {
+    if end_pos < 24 {
+      continue;
+    }
+    let mut magic_trailer = [0; 8];
+    file.seek(SeekFrom::Start(end_pos - 24))?;
+    file.read_exact(&mut magic_trailer)?;
+    if &magic_trailer == MAGIC_TRAILER {
+      return Ok(Some(end_pos - 24));
+    }
+  }
+  Ok(None)
+}
+
+/// The position of the certificate table of a signed Windows executable when
+/// it is at the end of the file, read from the security data directory of the
+/// PE header.
+fn authenticode_signature_pos(header: &[u8], file_len: u64) -> Option<u64> {
+  let read_u16 = |pos: usize| {
+    let bytes = header.get(pos..pos + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+  };
+  let read_u32 = |pos: usize| {
+    let bytes = header.get(pos..pos + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+  };
+  if header.get(0..2)? != b"MZ" {
+    return None;
+  }
+  let pe_pos = read_u32(0x3c)? as usize;
+  if header.get(pe_pos..pe_pos + 4)? != b"PE\0\0" {
+    return None;
+  }
+  let optional_header_pos = pe_pos + 24;
+  let data_directories_pos = match read_u16(optional_header_pos)? {
+    0x10b => optional_header_pos + 96,
+    0x20b => optional_header_pos + 112,
+    _ => return None,
+  };
+  // the security directory is the fifth one, and its address is a position in
+  // the file instead of a virtual address
+  let security_directory_pos = data_directories_pos + 4 * 8;
+  let signature_pos = read_u32(security_directory_pos)? as u64;
+  let signature_len = read_u32(security_directory_pos + 4)? as u64;
+  if signature_len > 0 && signature_pos + signature_len == file_len {
+    Some(signature_pos)
+  } else {
+    None
+  }
+}
+
+fn u64_from_bytes(arr: &[u8]) -> Result<u64, AnyError> {
+  let fixed_arr: &[u8; 8] = arr
+    .try_into()
+    .context("Failed to convert the buffer into a fixed-size array")?;
+  Ok(u64::from_be_bytes(*fixed_arr))
+}
+
+#[derive(Clone)]
+struct EmbeddedModuleLoader {
+  eszip: Arc<eszip::EszipV2>,
+  maybe_import_map_resolver: Option<Arc<CliGraphResolver>>,
+  /// The embedded directories, which modules missing from the bundle are
+  /// loaded from.
+  fs_overlay: Option<Arc<dyn FsOverlay>>,
+  /// The source maps of the loaded modules, which are shared by the workers.
+  source_maps: Arc<Mutex<HashMap<ModuleSpecifier, Vec<u8>>>>,
+}
+
+impl ModuleLoader for EmbeddedModuleLoader {
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &str,
+    _kind: ResolutionKind,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    // Try to follow redirects when resolving.
+    let referrer = match self.eszip.get_module(referrer) {
+      Some(eszip::Module { ref specifier, .. }) => {
+        ModuleSpecifier::parse(specifier)?
+      }
+      None => {
+        let cwd = std::env::current_dir().context("Unable to get CWD")?;
+        deno_core::resolve_url_or_path(referrer, &cwd)?
+      }
+    };
+
+    self
+      .maybe_import_map_resolver
+      .as_ref()
+      .map(|r| r.resolve(specifier, &referrer))
+      .unwrap_or_else(|| {
+        deno_core::resolve_import(specifier, referrer.as_str())
+          .map_err(|err| err.into())
+      })
+  }
+
+  fn load(
+    &self,
+    module_specifier: &ModuleSpecifier,
+    _maybe_referrer: Option<&ModuleSpecifier>,
+    _is_dynamic: bool,
+  ) -> Pin<Box<deno_core::ModuleSourceFuture>> {
+    let is_data_uri = get_source_from_data_url(module_specifier).ok();
+    let maybe_module = self.eszip.get_module(module_specifier.as_str());
+    if maybe_module.is_none() && is_data_uri.is_none() {
+      if let Some(result) = self.load_embedded_file(module_specifier) {
+        return futures::future::ready(result).boxed_local();
+      }
+    }
+    let module = maybe_module.ok_or_else(|| type_error("Module not found"));
+    // TODO(mmastrac): This clone can probably be removed in the future if ModuleSpecifier is no longer a full-fledged URL
+    let module_specifier = module_specifier.clone();
+    let source_maps = self.source_maps.clone();
+
+    async move {
+      if let Some((source, _)) = is_data_uri {
+        return Ok(deno_core::ModuleSource::new(
+          deno_core::ModuleType::JavaScript,
+          source.into(),
+          &module_specifier,
+        ));
+      }
+
+      let module = module?;
+      let code = module.source().await.unwrap_or_default();
+      let code: ModuleCode = std::str::from_utf8(&code)
+        .map_err(|_| type_error("Module source is not utf-8"))?
+        .to_owned()
+        .into();
+      // the emitted modules have an inline source map, which maps the
+      // stack traces of errors to the original sources
+      if let Some(source_map) = source_map_from_code(&code) {
+        source_maps
+          .lock()
+          .insert(module_specifier.clone(), source_map);
+      }
+
+      Ok(deno_core::ModuleSource::new(
+        match module.kind {
+          eszip::ModuleKind::JavaScript => ModuleType::JavaScript,
+          eszip::ModuleKind::Json => ModuleType::Json,
+        },
+        code,
+        &module_specifier,
+      ))
+    }
+    .boxed_local()
+  }
+}
+
+impl SourceMapGetter for EmbeddedModuleLoader {
+  fn get_source_map(&self, file_name: &str) -> Option<Vec<u8>> {
+    let specifier = ModuleSpecifier::parse(file_name).ok()?;
+    self.source_maps.lock().get(&specifier).cloned()
+  }
+
+  fn get_source_line(
+    &self,
+    file_name: &str,
+    line_number: usize,
+  ) -> Option<String> {
+    // the original sources aren't in the bundle, only in the source maps
+    let source_map =
+      SourceMap::from_slice(&self.get_source_map(file_name)?).ok()?;
+    let source = source_map.get_source_contents(0)?;
+    // Do NOT use .lines(): it skips the terminating empty line.
+    source
+      .split('\n')
+      .nth(line_number)
+      .map(|line| line.to_string())
+  }
+}
+
+impl EmbeddedModuleLoader {
+  /// Loads a JavaScript or JSON module from the embedded directories, which
+  /// is `None` when the specifier isn't in them.
+  fn load_embedded_file(
+    &self,
+    module_specifier: &ModuleSpecifier,
+  ) -> Option<Result<deno_core::ModuleSource, AnyError>> {
+    let fs_overlay = self.fs_overlay.as_ref()?;
+    let path = module_specifier.to_file_path().ok()?;
+    if !fs_overlay.contains(&path) {
+      return None;
+    }
+    let module_type = match MediaType::from_specifier(module_specifier) {
+      MediaType::JavaScript | MediaType::Mjs => ModuleType::JavaScript,
+      MediaType::Json => ModuleType::Json,
+      media_type => {
+        return Some(Err(type_error(format!(
+          "Embedded {media_type} modules are not supported, include them with --include instead: {module_specifier}"
+        ))))
+      }
+    };
+    let result = fs_overlay
+      .read_file(&path)
+      .map_err(AnyError::from)
+      .and_then(|code| {
+        String::from_utf8(code)
+          .map_err(|_| type_error("Module source is not utf-8"))
+      })
+      .map(|code| {
+        deno_core::ModuleSource::new(module_type, code.into(), module_specifier)
+      });
+    Some(result)
+  }
+}
+
+fn metadata_to_flags(metadata: &Metadata) -> Flags {
+  let permissions = metadata.permissions.clone();
+  Flags {
+    argv: metadata.argv.clone(),
+    unstable: metadata.unstable,
+    unstable_features: metadata.unstable_features.clone(),
+    seed: metadata.seed,
+    location: metadata.location.clone(),
+    allow_env: permissions.allow_env,
+    allow_hrtime: permissions.allow_hrtime,
+    allow_net: permissions.allow_net,
+    allow_ffi: permissions.allow_ffi,
+    allow_read: permissions.allow_read,
+    allow_run: permissions.allow_run,
+    allow_write: permissions.allow_write,
+    v8_flags: metadata.v8_flags.clone(),
+    log_level: metadata.log_level,
+    ca_stores: metadata.ca_stores.clone(),
+    ca_data: metadata.ca_data.clone().map(CaData::Bytes),
+    ..Default::default()
+  }
+}
+
+fn web_worker_callback() -> Arc<WorkerEventCb> {
+  Arc::new(|worker| {
+    let fut = async move { Ok(worker) };
+    LocalFutureObj::new(Box::new(fut))
+  })
+}
+
+fn create_web_worker_callback(
+  ps: &ProcState,
+  module_loader: &Rc<EmbeddedModuleLoader>,
+) -> Arc<CreateWebWorkerCb> {
+  let ps = ps.clone();
+  let module_loader = module_loader.as_ref().clone();
+  Arc::new(move |args| {
+    let module_loader = Rc::new(module_loader.clone());
+
+    let create_web_worker_cb = create_web_worker_callback(&ps, &module_loader);
+    let web_worker_cb = web_worker_callback();
+
+    let options = WebWorkerOptions {
+      bootstrap: BootstrapOptions {
+        args: ps.options.argv().clone(),
+        cpu_count: std::thread::available_parallelism()
+          .map(|p| p.get())
+          .unwrap_or(1),
+        debug_flag: ps.options.log_level().map_or(false, |l| l == Level::Debug),
+        enable_testing_features: false,
+        locale: deno_core::v8::icu::get_language_tag(),
+        location: Some(args.main_module.clone()),
+        no_color: !colors::use_color(),
+        is_tty: colors::is_tty(),
+        runtime_version: version::deno().to_string(),
+        ts_version: version::TYPESCRIPT.to_string(),
+        unstable: ps.options.unstable(),
+        unstable_features: ps.options.unstable_features(),
+        user_agent: version::get_user_agent().to_string(),
+        inspect: ps.options.is_inspecting(),
+      },
+      extensions: ops::cli_exts(
+        ps.options.clone(),
+        ps.npm_resolver.clone(),
+        ps.module_load_preparer.clone(),
+        ps.options.worker_permissions_policy(),
+        None,
+      ),
+      startup_snapshot: Some(crate::js::deno_isolate_init()),
+      unsafely_ignore_certificate_errors: ps
+        .options
+        .unsafely_ignore_certificate_errors()
+        .clone(),
+      root_cert_store: Some(ps.root_cert_store.clone()),
+      pinned_hosts: ps.pinned_hosts.clone(),
+      fs_overlay: module_loader.fs_overlay.clone(),
+      seed: ps.options.seed(),
+      source_map_getter: Some(Box::new(module_loader.clone())),
+      module_loader,
+      npm_resolver: None, // not currently supported
+      create_web_worker_cb,
+      preload_module_cb: web_worker_cb.clone(),
+      pre_execute_module_cb: web_worker_cb,
+      format_js_error_fn: Some(Arc::new(format_js_error)),
+      worker_type: args.worker_type,
+      maybe_inspector_server: None,
+      should_break_on_first_statement: false,
+      should_wait_for_inspector_session: false,
+      get_error_class_fn: Some(&get_error_class_name),
+      blob_store: ps.blob_store.clone(),
+      broadcast_channel: ps.broadcast_channel.clone(),
+      shared_array_buffer_store: Some(ps.shared_array_buffer_store.clone()),
+      compiled_wasm_module_store: Some(ps.compiled_wasm_module_store.clone()),
+      cache_storage_dir: None,
+      stdio: Default::default(),
+    };
+
+    WebWorker::bootstrap_from_options(
+      args.name,
+      args.permissions,
+      args.main_module,
+      args.worker_id,
+      options,
+    )
+  })
+}
+
+fn create_module_loader(
+  ps: &ProcState,
+  eszip: eszip::EszipV2,
+  metadata: &Metadata,
+  fs_overlay: Option<Arc<dyn FsOverlay>>,
+) -> Rc<EmbeddedModuleLoader> {
+  Rc::new(EmbeddedModuleLoader {
+    eszip: Arc::new(eszip),
+    maybe_import_map_resolver: metadata.maybe_import_map.as_ref().map(
+      |(base, source)| {
+        Arc::new(CliGraphResolver::new(
+          None,
+          Some(Arc::new(parse_from_json(base, source).unwrap().import_map)),
+          None,
+          None,
+          false,
+          false,
+          false,
+          ps.npm_api.clone(),
+          ps.npm_resolution.clone(),
+          ps.package_json_deps_installer.clone(),
+        ))
+      },
+    ),
+    fs_overlay,
+    source_maps: Default::default(),
+  })
+}
+
+fn create_worker_options(
+  ps: &ProcState,
+  metadata: &Metadata,
+  module_loader: Rc<EmbeddedModuleLoader>,
+  startup_snapshot: Snapshot,
+  will_snapshot: bool,
+) -> WorkerOptions {
+  let create_web_worker_cb = create_web_worker_callback(ps, &module_loader);
+  let web_worker_cb = web_worker_callback();
+
+  WorkerOptions {
+    bootstrap: BootstrapOptions {
+      args: metadata.argv.clone(),
+      cpu_count: std::thread::available_parallelism()
+        .map(|p| p.get())
+        .unwrap_or(1),
+      debug_flag: metadata
+        .log_level
+        .map(|l| l == Level::Debug)
+        .unwrap_or(false),
+      enable_testing_features: false,
+      locale: deno_core::v8::icu::get_language_tag(),
+      location: metadata.location.clone(),
+      no_color: !colors::use_color(),
+      is_tty: colors::is_tty(),
+      runtime_version: version::deno().to_string(),
+      ts_version: version::TYPESCRIPT.to_string(),
+      unstable: metadata.unstable,
+      unstable_features: metadata.unstable_features.clone(),
+      user_agent: version::get_user_agent().to_string(),
+      inspect: ps.options.is_inspecting(),
+    },
+    extensions: ops::cli_exts(
+      ps.options.clone(),
+      ps.npm_resolver.clone(),
+      ps.module_load_preparer.clone(),
+      ps.options.worker_permissions_policy(),
+      metadata
+        .self_update
+        .clone()
+        .map(|config| SelfUpdater::new(config, ps.http_client.clone())),
+    ),
+    startup_snapshot: Some(startup_snapshot),
+    will_snapshot,
+    unsafely_ignore_certificate_errors: metadata
+      .unsafely_ignore_certificate_errors
+      .clone(),
+    root_cert_store: Some(ps.root_cert_store.clone()),
+    pinned_hosts: ps.pinned_hosts.clone(),
+    fs_overlay: module_loader.fs_overlay.clone(),
+    seed: metadata.seed,
+    source_map_getter: Some(Box::new(module_loader.clone())),
+    format_js_error_fn: Some(Arc::new(format_js_error)),
+    create_web_worker_cb,
+    web_worker_preload_module_cb: web_worker_cb.clone(),
+    web_worker_pre_execute_module_cb: web_worker_cb,
+    maybe_inspector_server: None,
+    should_break_on_first_statement: false,
+    should_wait_for_inspector_session: false,
+    module_loader,
+    npm_resolver: None, // not currently supported
+    get_error_class_fn: Some(&get_error_class_name),
+    cache_storage_dir: None,
+    origin_storage_dir: None,
+    blob_store: ps.blob_store.clone(),
+    broadcast_channel: ps.broadcast_channel.clone(),
+    shared_array_buffer_store: Some(ps.shared_array_buffer_store.clone()),
+    compiled_wasm_module_store: Some(ps.compiled_wasm_module_store.clone()),
+    stdio: Default::default(),
+  }
+}
+
+/// Evaluates the startup modules of `deno compile --snapshot` in a worker
+/// that is not bootstrapped and returns a V8 startup snapshot of it, which
+/// the standalone binary then starts from.
+pub async fn create_startup_snapshot(
+  ps: &ProcState,
+  eszip: eszip::EszipV2,
+  metadata: &Metadata,
+  startup_modules: &[ModuleSpecifier],
+) -> Result<Vec<u8>, AnyError> {
+  // the included directories are still on the disk when compiling
+  let module_loader = create_module_loader(ps, eszip, metadata, None);
+  let options = create_worker_options(
+    ps,
+    metadata,
+    module_loader,
+    crate::js::deno_isolate_init(),
+    true,
+  );
+  // startup modules run at compile time, so they don't get any permissions
+  let permissions =
+    PermissionsContainer::new(Permissions::from_options(&Default::default())?);
+  let mut worker =
+    MainWorker::from_options(metadata.entrypoint.clone(), permissions, options);
+  let initial_rids = worker
+    .js_runtime
+    .op_state()
+    .borrow()
+    .resource_table
+    .names()
+    .map(|(rid, _)| rid)
+    .collect::<HashSet<_>>();
+
+  for specifier in startup_modules {
+    log::info!("{} {}", colors::green("Snapshot"), specifier);
+    let id = worker.preload_side_module(specifier).await?;
+    worker.evaluate_module(id).await.with_context(|| {
+      format!(
+        "Failed evaluating the startup module \"{specifier}\". Startup modules are evaluated before the runtime is bootstrapped, so they can't use the Deno namespace or any permissions."
+      )
+    })?;
+  }
+
+  let poll_result = futures::future::poll_fn(|cx| {
+    Poll::Ready(worker.js_runtime.poll_event_loop(cx, false))
+  })
+  .await;
+  match poll_result {
+    Poll::Ready(result) => result?,
+    Poll::Pending => bail!(
+      "The startup modules left pending work (ex. timers or unresolved ops), which can't be included in a snapshot."
+    ),
+  }
+
+  let open_resources = worker
+    .js_runtime
+    .op_state()
+    .borrow()
+    .resource_table
+    .names()
+    .filter(|(rid, _)| !initial_rids.contains(rid))
+    .map(|(rid, name)| format!("{name} (rid {rid})"))
+    .collect::<Vec<_>>();
+  if !open_resources.is_empty() {
+    bail!(
+      "The startup modules left resources open, which can't be included in a snapshot: {}",
+      open_resources.join(", ")
+    );
+  }
+
+  Ok(worker.snapshot().to_vec())
+}
+
+pub async fn run(
+  eszip: eszip::EszipV2,
+  metadata: Metadata,
+  maybe_startup_snapshot: Option<Vec<u8>>,
+  maybe_vfs: Option<VirtualFs>,
+) -> Result<(), AnyError> {
+  let flags = metadata_to_flags(&metadata);
+  let main_module = &metadata.entrypoint;
+  let ps = ProcState::from_flags(flags).await?;
+  let permissions = PermissionsContainer::new(Permissions::from_options(
+    &metadata.permissions,
+  )?);
+  let fs_overlay = maybe_vfs.map(|vfs| Arc::new(vfs) as Arc<dyn FsOverlay>);
+  let module_loader = create_module_loader(&ps, eszip, &metadata, fs_overlay);
+
+  v8_set_flags(construct_v8_flags(&metadata.v8_flags, vec![]));
+
+  let startup_snapshot = match maybe_startup_snapshot {
+    Some(startup_snapshot) => {
+      Snapshot::Boxed(startup_snapshot.into_boxed_slice())
+    }
+    None => crate::js::deno_isolate_init(),
+  };
+  let options = create_worker_options(
+    &ps,
+    &metadata,
+    module_loader,
+    startup_snapshot,
+    false,
+  );
+  let mut worker = MainWorker::bootstrap_from_options(
+    main_module.clone(),
+    permissions,
+    options,
+  );
+  worker.execute_main_module(main_module).await?;
+  worker.dispatch_load_event(located_script_name!())?;
+
+  loop {
+    worker.run_event_loop(false).await?;
+    if !worker.dispatch_beforeunload_event(located_script_name!())? {
+      break;
+    }
+  }
+
+  worker.dispatch_unload_event(located_script_name!())?;
+  std::process::exit(0);
+}
+
+fn get_error_class_name(e: &AnyError) -> &'static str {
+  deno_runtime::errors::get_error_class_name(e).unwrap_or_else(|| {
+    panic!(
+      "Error '{}' contains boxed error of unsupported type:{}",
+      e,
+      e.chain().map(|e| format!("\n  {e:?}")).collect::<String>()
+    );
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn finds_authenticode_signature() {
+    let mut header = vec![0; 1024];
+    header[0..2].copy_from_slice(b"MZ");
+    header[0x3c..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+    header[0x80..0x84].copy_from_slice(b"PE\0\0");
+    // a PE32+ optional header, whose data directories start after 112 bytes
+    header[0x98..0x9a].copy_from_slice(&0x20bu16.to_le_bytes());
+    let security_directory_pos = 0x98 + 112 + 4 * 8;
+    header[security_directory_pos..security_directory_pos + 4]
+      .copy_from_slice(&5000u32.to_le_bytes());
+    header[security_directory_pos + 4..security_directory_pos + 8]
+      .copy_from_slice(&1000u32.to_le_bytes());
+
+    assert_eq!(authenticode_signature_pos(&header, 6000), Some(5000));
+    // the signature isn't at the end of the file
+    assert_eq!(authenticode_signature_pos(&header, 7000), None);
+    assert_eq!(authenticode_signature_pos(b"\x7fELF", 6000), None);
+  }
+}
@@ -0,0 +1,327 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_semver::Version;
+
+use crate::args::CompileSelfUpdateFlags;
+use crate::http_util::HttpClient;
+use crate::tools::upgrade::check_exe_writable;
+use crate::tools::upgrade::replace_exe;
+use crate::util::checksum;
+use crate::util::signature::parse_ed25519_public_key;
+use crate::util::signature::verify_ed25519;
+
+use super::find_trailer_pos;
+use super::payload_path;
+
+/// The configuration of `deno compile --update-url`, which lets the
+/// executable replace itself with newer versions with `Deno.selfUpdate()`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SelfUpdateConfig {
+  /// The URL of the update manifest.
+  pub url: Url,
+  /// The raw Ed25519 public key that the executables are signed with.
+  pub public_key: Vec<u8>,
+  /// The version of the executable.
+  pub version: String,
+}
+
+impl SelfUpdateConfig {
+  pub fn from_flags(flags: &CompileSelfUpdateFlags) -> Result<Self, AnyError> {
+    if Version::parse_standard(&flags.version).is_err() {
+      bail!(
+        "The --app-version flag must be a semver version, got \"{}\".",
+        flags.version
+      );
+    }
+    Ok(Self {
+      url: flags.url.clone(),
+      public_key: parse_ed25519_public_key(&flags.public_key)
+        .context("Invalid --update-key flag.")?,
+      version: flags.version.clone(),
+    })
+  }
+}
+
+/// The JSON manifest at the update URL, which lists the latest executable of
+/// every target.
+#[derive(Debug, Deserialize)]
+pub struct UpdateManifest {
+  pub version: String,
+  #[serde(default)]
+  pub targets: HashMap<String, UpdateTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTarget {
+  /// The URL of the executable, which may be relative to the manifest.
+  pub url: String,
+  /// The base64 encoded Ed25519 signature of the `signed_message()` of the
+  /// executable.
+  pub signature: String,
+}
+
+/// The message that the signature of an update is of, which binds the
+/// executable to its version and target, so that an older executable can't
+/// be served as a newer version or one of another target.
+pub fn signed_message(version: &str, target: &str, data: &[u8]) -> String {
+  format!("{version}\n{target}\n{}", checksum::gen(&[data]))
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfUpdateInfo {
+  pub current_version: String,
+  pub latest_version: String,
+  pub available: bool,
+  pub updated: bool,
+}
+
+#[derive(Clone)]
+pub struct SelfUpdater {
+  config: SelfUpdateConfig,
+  http_client: HttpClient,
+}
+
+impl SelfUpdater {
+  pub fn new(config: SelfUpdateConfig, http_client: HttpClient) -> Self {
+    Self {
+      config,
+      http_client,
+    }
+  }
+
+  pub fn config(&self) -> &SelfUpdateConfig {
+    &self.config
+  }
+
+  pub async fn fetch_manifest(&self) -> Result<UpdateManifest, AnyError> {
+    let text = self
+      .http_client
+      .download_text(self.config.url.clone())
+      .await
+      .with_context(|| {
+        format!("Failed downloading the update manifest {}", self.config.url)
+      })?;
+    serde_json::from_str(&text).with_context(|| {
+      format!("Failed parsing the update manifest {}", self.config.url)
+    })
+  }
+
+  /// Compares the version of the manifest to the one of the executable.
+  pub fn update_info(&self, manifest: &UpdateManifest) -> SelfUpdateInfo {
+    let available = match (
+      Version::parse_standard(&self.config.version),
+      Version::parse_standard(&manifest.version),
+    ) {
+      (Ok(current), Ok(latest)) => latest > current,
+      _ => false,
+    };
+    SelfUpdateInfo {
+      current_version: self.config.version.clone(),
+      latest_version: manifest.version.clone(),
+      available,
+      updated: false,
+    }
+  }
+
+  /// Resolves the URL of the executable of the current target.
+  pub fn resolve_target<'a>(
+    &self,
+    manifest: &'a UpdateManifest,
+  ) -> Result<(Url, &'a UpdateTarget), AnyError> {
+    let target = manifest.targets.get(env!("TARGET")).with_context(|| {
+      format!(
+        "The update manifest {} has no executable for {}.",
+        self.config.url,
+        env!("TARGET")
+      )
+    })?;
+    let url = self.config.url.join(&target.url)?;
+    Ok((url, target))
+  }
+
+  /// Downloads the executable of the version, verifies its signature and
+  /// replaces the current executable with it, which takes effect on the next
+  /// start.
+  pub async fn apply(
+    &self,
+    url: Url,
+    version: &str,
+    target: &UpdateTarget,
+  ) -> Result<(), AnyError> {
+    let current_exe_path = std::env::current_exe()?;
+    if payload_path(&current_exe_path) != current_exe_path {
+      bail!("Self updating is not supported in macOS app bundles.");
+    }
+    let permissions = check_exe_writable(
+      &current_exe_path,
+      "Reinstall the executable in a directory you own to update it.",
+    )?;
+    let data = self
+      .http_client
+      .download(url.clone())
+      .await
+      .with_context(|| format!("Failed downloading {url}"))?;
+    self.verify_update(version, &data, &target.signature, url.as_str())?;
+    tokio::task::spawn_blocking(move || {
+      write_and_replace_exe(&current_exe_path, &data, permissions)
+    })
+    .await?
+  }
+
+  /// Verifies that the executable is of a newer version than the current
+  /// one, and that its signature is of that version and the current target.
+  fn verify_update(
+    &self,
+    version: &str,
+    data: &[u8],
+    signature: &str,
+    name: &str,
+  ) -> Result<(), AnyError> {
+    let is_newer = match (
+      Version::parse_standard(&self.config.version),
+      Version::parse_standard(version),
+    ) {
+      (Ok(current), Ok(version)) => version > current,
+      _ => false,
+    };
+    if !is_newer {
+      bail!(
+        "The update {} is not newer than the version {} of the executable.",
+        version,
+        self.config.version
+      );
+    }
+    verify_ed25519(
+      &self.config.public_key,
+      signed_message(version, env!("TARGET"), data).as_bytes(),
+      signature,
+      name,
+    )
+  }
+}
+
+/// Writes the new executable next to the current one, so that it is renamed
+/// over it on the same file system.
+fn write_and_replace_exe(
+  current_exe_path: &Path,
+  data: &[u8],
+  permissions: fs::Permissions,
+) -> Result<(), AnyError> {
+  let file_name = current_exe_path.file_name().unwrap().to_string_lossy();
+  let new_exe_path =
+    current_exe_path.with_file_name(format!(".{file_name}.update"));
+  let result = fs::write(&new_exe_path, data)
+    .map_err(AnyError::from)
+    .and_then(|_| {
+      let mut file = fs::File::open(&new_exe_path)?;
+      if find_trailer_pos(&mut file)?.is_none() {
+        bail!("The update is not an executable compiled with deno compile.");
+      }
+      Ok(())
+    })
+    .and_then(|_| {
+      fs::set_permissions(&new_exe_path, permissions)?;
+      replace_exe(&new_exe_path, current_exe_path).with_context(|| {
+        format!("Failed replacing {}", current_exe_path.display())
+      })
+    });
+  if result.is_err() {
+    let _ = fs::remove_file(&new_exe_path);
+  }
+  result
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use ring::rand::SystemRandom;
+  use ring::signature::Ed25519KeyPair;
+  use ring::signature::KeyPair;
+
+  fn updater(version: &str) -> SelfUpdater {
+    updater_with_key(version, vec![0; 32])
+  }
+
+  fn updater_with_key(version: &str, public_key: Vec<u8>) -> SelfUpdater {
+    SelfUpdater::new(
+      SelfUpdateConfig {
+        url: Url::parse("https://example.com/app/manifest.json").unwrap(),
+        public_key,
+        version: version.to_string(),
+      },
+      HttpClient::new(None, None).unwrap(),
+    )
+  }
+
+  #[test]
+  fn compares_manifest_versions() {
+    let manifest: UpdateManifest = serde_json::from_str(
+      r#"{
+        "version": "1.2.0",
+        "targets": {
+          "x86_64-unknown-linux-gnu": {
+            "url": "app-1.2.0-linux",
+            "signature": "c2lnbmF0dXJl"
+          }
+        }
+      }"#,
+    )
+    .unwrap();
+    assert!(updater("1.1.9").update_info(&manifest).available);
+    assert!(!updater("1.2.0").update_info(&manifest).available);
+    assert!(!updater("2.0.0").update_info(&manifest).available);
+    if env!("TARGET") == "x86_64-unknown-linux-gnu" {
+      let (url, _) = updater("1.1.9").resolve_target(&manifest).unwrap();
+      assert_eq!(url.as_str(), "https://example.com/app/app-1.2.0-linux");
+    }
+  }
+
+  #[test]
+  fn verifies_signed_versions() {
+    let rng = SystemRandom::new();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let updater =
+      updater_with_key("1.1.0", key_pair.public_key().as_ref().to_vec());
+    let sign = |version: &str, target: &str| {
+      let message = signed_message(version, target, b"exe");
+      base64::encode(key_pair.sign(message.as_bytes()))
+    };
+
+    let signature = sign("1.2.0", env!("TARGET"));
+    assert!(updater
+      .verify_update("1.2.0", b"exe", &signature, "exe")
+      .is_ok());
+    assert!(updater
+      .verify_update("1.2.0", b"tampered", &signature, "exe")
+      .is_err());
+    // an executable signed for an older version or another target isn't
+    // accepted with a newer version in the manifest
+    assert!(updater
+      .verify_update("1.3.0", b"exe", &signature, "exe")
+      .is_err());
+    let signature = sign("1.2.0", "another-target");
+    assert!(updater
+      .verify_update("1.2.0", b"exe", &signature, "exe")
+      .is_err());
+    let signature = sign("1.0.0", env!("TARGET"));
+    let err = updater
+      .verify_update("1.0.0", b"exe", &signature, "exe")
+      .unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("is not newer than the version 1.1.0"));
+  }
+}
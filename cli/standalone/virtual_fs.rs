@@ -0,0 +1,341 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::normalize_path;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use deno_runtime::colors;
+use deno_runtime::deno_fs::FsDirEntry;
+use deno_runtime::deno_fs::FsOverlay;
+use deno_runtime::deno_fs::FsStat;
+
+/// A directory embedded in the executable with `deno compile --include-dir`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EmbeddedDir {
+  /// The absolute path of the directory when it was compiled, which is where
+  /// the executable finds it.
+  pub path: PathBuf,
+  pub entries: Vec<EmbeddedEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EmbeddedEntry {
+  /// The path relative to the embedded directory, with `/` separators.
+  pub path: String,
+  /// The position and length of the contents of a file in the embedded data,
+  /// which is `None` for a directory.
+  pub contents: Option<(u64, u64)>,
+}
+
+/// Collects the entries of the directories, returning them along with the
+/// contents of their files concatenated.
+pub fn collect_embedded_dirs(
+  dirs: &[PathBuf],
+) -> Result<(Vec<EmbeddedDir>, Vec<u8>), AnyError> {
+  let mut data = Vec::new();
+  let mut embedded_dirs = Vec::with_capacity(dirs.len());
+  for dir in dirs {
+    // the path isn't canonicalized, so that it stays the one of the modules
+    let dir = normalize_path(dir);
+    if !dir.is_dir() {
+      bail!("The included path {} is not a directory.", dir.display());
+    }
+    let canonical_dir = dir.canonicalize()?;
+    let mut entries = Vec::new();
+    // the links are followed inside of the directory only, so that files
+    // outside of it, like ones of the home directory, aren't embedded
+    let walker = walkdir::WalkDir::new(&dir)
+      .follow_links(true)
+      .sort_by_file_name()
+      .min_depth(1)
+      .into_iter()
+      .filter_entry(|entry| {
+        if !entry.path_is_symlink() {
+          return true;
+        }
+        match entry.path().canonicalize() {
+          Ok(target) if target.starts_with(&canonical_dir) => true,
+          _ => {
+            log::warn!(
+              "{} Skipped {}, which links outside of {}.",
+              colors::yellow("Warning"),
+              entry.path().display(),
+              dir.display(),
+            );
+            false
+          }
+        }
+      });
+    for entry in walker {
+      let entry = entry?;
+      let path = entry
+        .path()
+        .strip_prefix(&dir)?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+      let contents = if entry.file_type().is_dir() {
+        None
+      } else {
+        let bytes = std::fs::read(entry.path()).with_context(|| {
+          format!("Failed reading {}", entry.path().display())
+        })?;
+        let pos = data.len() as u64;
+        data.extend_from_slice(&bytes);
+        Some((pos, bytes.len() as u64))
+      };
+      entries.push(EmbeddedEntry { path, contents });
+    }
+    embedded_dirs.push(EmbeddedDir { path: dir, entries });
+  }
+  Ok((embedded_dirs, data))
+}
+
+/// The length of the embedded data of the directories.
+pub fn embedded_data_len(dirs: &[EmbeddedDir]) -> u64 {
+  dirs
+    .iter()
+    .flat_map(|dir| &dir.entries)
+    .filter_map(|entry| entry.contents.map(|(_, len)| len))
+    .sum()
+}
+
+enum VfsEntry {
+  Dir(Vec<String>),
+  File { pos: u64, len: u64 },
+}
+
+/// The read-only file system of the embedded directories, which reads the
+/// contents of the files from the executable when they are accessed.
+pub struct VirtualFs {
+  payload_path: PathBuf,
+  data_pos: u64,
+  roots: Vec<PathBuf>,
+  entries: HashMap<PathBuf, VfsEntry>,
+}
+
+impl VirtualFs {
+  pub fn new(
+    payload_path: PathBuf,
+    data_pos: u64,
+    dirs: &[EmbeddedDir],
+  ) -> Self {
+    let mut roots = Vec::with_capacity(dirs.len());
+    let mut entries = HashMap::new();
+    for dir in dirs {
+      roots.push(dir.path.clone());
+      entries.insert(dir.path.clone(), VfsEntry::Dir(Vec::new()));
+      for entry in &dir.entries {
+        let path = entry
+          .path
+          .split('/')
+          .fold(dir.path.clone(), |path, component| path.join(component));
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+          if let Some(VfsEntry::Dir(children)) = entries.get_mut(parent) {
+            children.push(name.to_string_lossy().to_string());
+          }
+        }
+        let vfs_entry = match entry.contents {
+          Some((pos, len)) => VfsEntry::File { pos, len },
+          None => VfsEntry::Dir(Vec::new()),
+        };
+        entries.insert(path, vfs_entry);
+      }
+    }
+    Self {
+      payload_path,
+      data_pos,
+      roots,
+      entries,
+    }
+  }
+
+  fn entry(&self, path: &Path) -> io::Result<&VfsEntry> {
+    let path = normalize_path(path);
+    self.entries.get(&path).ok_or_else(|| {
+      io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No such embedded file or directory: {}", path.display()),
+      )
+    })
+  }
+}
+
+impl FsOverlay for VirtualFs {
+  fn contains(&self, path: &Path) -> bool {
+    let path = normalize_path(path);
+    self.roots.iter().any(|root| path.starts_with(root))
+  }
+
+  fn open(&self, path: &Path) -> io::Result<std::fs::File> {
+    let data = self.read_file(path)?;
+    let mut file = tempfile::tempfile()?;
+    file.write_all(&data)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+  }
+
+  fn read_file(&self, path: &Path) -> io::Result<Vec<u8>> {
+    match self.entry(path)? {
+      VfsEntry::File { pos, len } => {
+        let mut file = std::fs::File::open(&self.payload_path)?;
+        file.seek(SeekFrom::Start(self.data_pos + pos))?;
+        let mut data = vec![0; *len as usize];
+        file.read_exact(&mut data)?;
+        Ok(data)
+      }
+      VfsEntry::Dir(_) => Err(io::Error::new(
+        io::ErrorKind::Other,
+        format!("Is a directory: {}", path.display()),
+      )),
+    }
+  }
+
+  fn stat(&self, path: &Path) -> io::Result<FsStat> {
+    let (is_file, size, mode) = match self.entry(path)? {
+      VfsEntry::File { len, .. } => (true, *len, 0o444),
+      VfsEntry::Dir(_) => (false, 0, 0o555),
+    };
+    Ok(FsStat {
+      is_file,
+      is_directory: !is_file,
+      is_symlink: false,
+      size,
+      mtime: None,
+      atime: None,
+      birthtime: None,
+      dev: 0,
+      ino: 0,
+      mode,
+      nlink: 1,
+      uid: 0,
+      gid: 0,
+      rdev: 0,
+      blksize: 0,
+      blocks: 0,
+    })
+  }
+
+  fn read_dir(&self, path: &Path) -> io::Result<Vec<FsDirEntry>> {
+    let path = normalize_path(path);
+    match self.entry(&path)? {
+      VfsEntry::Dir(children) => Ok(
+        children
+          .iter()
+          .map(|name| {
+            let is_file = matches!(
+              self.entries.get(&path.join(name)),
+              Some(VfsEntry::File { .. })
+            );
+            FsDirEntry {
+              name: name.clone(),
+              is_file,
+              is_directory: !is_file,
+              is_symlink: false,
+            }
+          })
+          .collect(),
+      ),
+      VfsEntry::File { .. } => Err(io::Error::new(
+        io::ErrorKind::Other,
+        format!("Not a directory: {}", path.display()),
+      )),
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  #[test]
+  fn reads_embedded_dirs() {
+    let temp_dir = TempDir::new();
+    temp_dir.create_dir_all("static/css");
+    temp_dir.write("static/index.html", "<h1>Hi</h1>");
+    temp_dir.write("static/css/main.css", "h1 {}");
+    let (dirs, data) =
+      collect_embedded_dirs(&[temp_dir.path().join("static")]).unwrap();
+    assert_eq!(embedded_data_len(&dirs), data.len() as u64);
+
+    // the payload is the data after a prefix, like in an executable
+    let payload = format!("prefix{}", String::from_utf8(data).unwrap());
+    temp_dir.write("payload.bin", payload);
+    let vfs = VirtualFs::new(temp_dir.path().join("payload.bin"), 6, &dirs);
+
+    let static_dir = dirs[0].path.clone();
+    assert!(vfs.contains(&static_dir.join("css/../index.html")));
+    assert!(!vfs.contains(&static_dir.with_file_name("payload.bin")));
+    assert_eq!(
+      vfs.read_file(&static_dir.join("index.html")).unwrap(),
+      b"<h1>Hi</h1>"
+    );
+    assert_eq!(
+      vfs.read_file(&static_dir.join("css/main.css")).unwrap(),
+      b"h1 {}"
+    );
+    assert_eq!(
+      vfs
+        .read_file(&static_dir.join("missing.txt"))
+        .unwrap_err()
+        .kind(),
+      io::ErrorKind::NotFound
+    );
+    let stat = vfs.stat(&static_dir.join("css")).unwrap();
+    assert!(stat.is_directory);
+    let names = vfs
+      .read_dir(&static_dir)
+      .unwrap()
+      .into_iter()
+      .map(|entry| (entry.name, entry.is_file))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      names,
+      vec![("css".to_string(), false), ("index.html".to_string(), true)]
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn skips_links_outside_of_embedded_dirs() {
+    let temp_dir = TempDir::new();
+    temp_dir.create_dir_all("static/css");
+    temp_dir.create_dir_all("secrets");
+    temp_dir.write("static/css/main.css", "h1 {}");
+    temp_dir.write("secrets/key.txt", "secret");
+    let static_dir = temp_dir.path().join("static");
+    let symlink = std::os::unix::fs::symlink;
+    symlink(static_dir.join("css"), static_dir.join("styles")).unwrap();
+    symlink(temp_dir.path().join("secrets"), static_dir.join("secrets"))
+      .unwrap();
+    symlink(
+      temp_dir.path().join("secrets/key.txt"),
+      static_dir.join("key.txt"),
+    )
+    .unwrap();
+    let (dirs, data) = collect_embedded_dirs(&[static_dir]).unwrap();
+    let paths = dirs[0]
+      .entries
+      .iter()
+      .map(|entry| entry.path.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      paths,
+      vec!["css", "css/main.css", "styles", "styles/main.css"]
+    );
+    assert_eq!(data, b"h1 {}h1 {}");
+  }
+}
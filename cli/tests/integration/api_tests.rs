@@ -0,0 +1,75 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno::flags_from_vec;
+use deno::ProjectBuilder;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::tokio_util::run_local;
+use test_util::TempDir;
+
+#[test]
+fn project_builder_runs_module_in_process() {
+  let temp_dir = TempDir::new();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "imports": { "message": "./message.ts" } }"#,
+  );
+  temp_dir.write("message.ts", "export const message: string = 'hello';\n");
+  temp_dir.write(
+    "main.ts",
+    "import { message } from 'message';\nDeno.writeTextFileSync(new URL('./out.txt', import.meta.url), message);\n",
+  );
+  let config_path = temp_dir.path().join("deno.json");
+  let main_path = temp_dir.path().join("main.ts");
+
+  let mut flags = flags_from_vec(vec![
+    "deno".to_string(),
+    "run".to_string(),
+    format!("--config={}", config_path.display()),
+    main_path.display().to_string(),
+  ])
+  .unwrap();
+  flags.cache_path = Some(temp_dir.path().join("deno_dir"));
+  let main_module = ModuleSpecifier::from_file_path(&main_path).unwrap();
+  let exit_code = run_local(async move {
+    let project = ProjectBuilder::from_flags(flags)?.build().await?;
+    project
+      .run(main_module, PermissionsContainer::allow_all())
+      .await
+  })
+  .unwrap();
+
+  assert_eq!(exit_code, 0);
+  assert_eq!(temp_dir.read_to_string("out.txt"), "hello");
+}
+
+#[test]
+fn run_check_reports_type_errors() {
+  let temp_dir = TempDir::new();
+  temp_dir.write("main.ts", "const a: string = 1;\nconsole.log(a);\n");
+  let main_path = temp_dir.path().join("main.ts");
+
+  let mut flags = flags_from_vec(vec![
+    "deno".to_string(),
+    "check".to_string(),
+    main_path.display().to_string(),
+  ])
+  .unwrap();
+  flags.cache_path = Some(temp_dir.path().join("deno_dir"));
+  let report = run_local(deno::api::run_check(flags)).unwrap();
+
+  assert!(!report.passed());
+  assert!(report.type_errors().contains("TS2322"));
+  assert!(report.into_result().is_err());
+}
+
+#[test]
+fn run_test_requires_test_flags() {
+  let flags =
+    flags_from_vec(vec!["deno".to_string(), "info".to_string()]).unwrap();
+  let err = run_local(deno::api::run_test(flags)).unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "Expected the flags of a `deno test` command."
+  );
+}
@@ -5,6 +5,7 @@ use test_util as util;
 use util::assert_contains;
 use util::env_vars_for_npm_tests;
 use util::TestContext;
+use util::TestContextBuilder;
 
 itest!(overloads {
   args: "bench bench/overloads.ts",
@@ -221,6 +222,68 @@ fn file_protocol() {
     .assert_matches_file("bench/file_protocol.out");
 }
 
+#[test]
+fn baseline() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "noop_bench.ts",
+    "Deno.bench(\"noop\", () => {\n  for (let i = 0; i < 100; i++);\n});\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("bench --save-baseline=baseline.json")
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let baseline = temp_dir.read_to_string("baseline.json");
+  assert_contains!(baseline, r#""origin": "./noop_bench.ts""#);
+  assert_contains!(baseline, r#""name": "noop""#);
+
+  let output = context
+    .new_command()
+    .args("bench --baseline=baseline.json --regression-threshold=1000000")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "baseline: baseline.json");
+
+  temp_dir.write(
+    "fast.json",
+    r#"{
+  "runtime": "",
+  "cpu": "",
+  "benches": [
+    {
+      "origin": "./noop_bench.ts",
+      "group": null,
+      "name": "noop",
+      "stats": {
+        "n": 1000,
+        "min": 0.001,
+        "max": 0.001,
+        "avg": 0.001,
+        "p75": 0.001,
+        "p99": 0.001,
+        "p995": 0.001,
+        "p999": 0.001,
+        "sd": 0
+      }
+    }
+  ]
+}"#,
+  );
+  let output = context
+    .new_command()
+    .args("bench --baseline=fast.json")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Bench failed because 1 bench regressed more than 10% from the baseline"
+  );
+}
+
 itest!(package_json_basic {
   args: "bench",
   output: "package_json/basic/lib.bench.out",
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use test_util::env_vars_for_npm_tests;
 use test_util::TestContextBuilder;
 
@@ -181,3 +182,86 @@ fn cache_put_overwrite() {
   output.assert_matches_text("res1\n");
   output.assert_exit_code(0);
 }
+
+#[test]
+fn cache_progress_json() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let output = context
+    .new_command()
+    .args(
+      "cache --reload --progress=json http://localhost:4545/run/002_hello.ts",
+    )
+    .run();
+  output.assert_exit_code(0);
+  let events = output
+    .combined_output()
+    .lines()
+    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+    .collect::<Vec<_>>();
+  let start = events
+    .iter()
+    .find(|event| event["event"] == "start" && event["kind"] == "download")
+    .unwrap();
+  assert_eq!(start["message"], "http://localhost:4545/run/002_hello.ts");
+  assert!(events
+    .iter()
+    .any(|event| event["event"] == "finish" && event["id"] == start["id"]));
+}
+
+#[test]
+fn cache_infer_types() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .use_separate_deno_dir()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    "import { add, sub } from 'http://localhost:4545/cache/infer_types/mod.js';\n\nconst result: number = sub(add(1, 2), 3);\nconsole.log(result);\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("cache --infer-types main.ts")
+    .run();
+  output.assert_matches_text(concat!(
+    "[WILDCARD]",
+    "Infer http://localhost:4545/cache/infer_types/mod.js\n",
+    "Infer http://localhost:4545/cache/infer_types/sub.js\n",
+  ));
+  output.assert_exit_code(0);
+
+  let inferred_dir = context
+    .deno_dir()
+    .path()
+    .join("inferred_types/http/localhost_PORT4545");
+  let declarations = std::fs::read_dir(inferred_dir)
+    .unwrap()
+    .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+    .collect::<Vec<_>>();
+  assert_eq!(declarations.len(), 2);
+  // the relative imports are resolved, because the declarations are cached
+  // apart from the modules
+  assert!(declarations.iter().any(|text| text.contains(
+    "export { sub } from \"http://localhost:4545/cache/infer_types/sub.js\";"
+  )));
+  assert!(declarations
+    .iter()
+    .any(|text| text
+      .contains("export function add(a: number, b: number): number;")));
+
+  // the declarations are used for type checking and inferred only once
+  let output = context.new_command().args("check main.ts").run();
+  output.assert_matches_text("Check [WILDCARD]main.ts\n");
+  output.assert_exit_code(0);
+  let output = context
+    .new_command()
+    .args("cache --infer-types main.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert!(!output.combined_output().contains("Infer"));
+}
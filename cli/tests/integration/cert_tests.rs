@@ -13,6 +13,7 @@ use test_util as util;
 use test_util::TempDir;
 use tokio::task::LocalSet;
 use util::TestContext;
+use util::TestContextBuilder;
 
 itest_flaky!(cafile_url_imports {
   args: "run --quiet --reload --cert tls/RootCA.pem cert/cafile_url_imports.ts",
@@ -329,3 +330,47 @@ async fn listen_tls_alpn_fail() {
     })
     .await;
 }
+
+#[test]
+fn origin_tls_config_client_auth() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  let tls_file = |name: &str| {
+    Url::from_file_path(context.testdata_path().join("tls").join(name)).unwrap()
+  };
+  let module_url = "https://localhost:5552/subdir/mod2.ts";
+
+  // the server requires a client certificate
+  temp_dir.write(
+    "deno.json",
+    format!(
+      r#"{{ "tls": {{ "https://localhost:5552": {{ "caFile": "{}" }} }} }}"#,
+      tls_file("RootCA.pem"),
+    ),
+  );
+  let output = context
+    .new_command()
+    .args(format!("cache {module_url}"))
+    .run();
+  output.assert_exit_code(1);
+  output.skip_output_check();
+
+  temp_dir.write(
+    "deno.json",
+    format!(
+      r#"{{ "tls": {{ "https://localhost:5552": {{ "caFile": "{}", "certFile": "{}", "keyFile": "{}" }} }} }}"#,
+      tls_file("RootCA.pem"),
+      tls_file("localhost.crt"),
+      tls_file("localhost.key"),
+    ),
+  );
+  context
+    .new_command()
+    .args(format!("cache {module_url}"))
+    .run()
+    .assert_exit_code(0)
+    .skip_output_check();
+}
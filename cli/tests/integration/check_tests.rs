@@ -1,6 +1,8 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use test_util as util;
+use util::assert_contains;
 use util::env_vars_for_npm_tests;
 use util::env_vars_for_npm_tests_no_sync_download;
 use util::TestContext;
@@ -37,6 +39,12 @@ itest!(check_all_local {
   http_server: true,
 });
 
+itest!(check_all_errors {
+  args: "check --quiet --all-errors check/all_errors/main.ts",
+  output: "check/all_errors/main.out",
+  exit_code: 1,
+});
+
 itest!(module_detection_force {
   args: "check --quiet check/module_detection_force/main.ts",
   output_str: Some(""),
@@ -303,3 +311,444 @@ fn check_error_in_dep_then_fix() {
   output.assert_matches_text("Check [WILDCARD]main.ts\nerror: TS234[WILDCARD]");
   output.assert_exit_code(1);
 }
+
+#[test]
+fn check_lib_overrides() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "libOverrides": { "./web/": ["dom", "esnext"] } }"#,
+  );
+  temp_dir.create_dir_all("web");
+  temp_dir.write("web/main.ts", "document.body.append('Hello');\n");
+  temp_dir.write("main.ts", "console.log(Deno.cwd());\n");
+
+  let output = test_context
+    .new_command()
+    .args_vec(["check", "web/main.ts", "main.ts"])
+    .run();
+  output.assert_matches_text(
+    "Check [WILDCARD]web/main.ts\nCheck [WILDCARD]main.ts\n",
+  );
+  output.assert_exit_code(0);
+
+  // the dom types are not available without the override
+  let output = test_context
+    .new_command()
+    .args_vec(["check", "--no-config", "web/main.ts"])
+    .run();
+  output.assert_matches_text(
+    "Check [WILDCARD]web/main.ts\nerror: TS2584[WILDCARD]",
+  );
+  output.assert_exit_code(1);
+}
+
+#[test]
+fn check_units() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "checkUnits": {
+    "core": { "include": ["./core/"] },
+    "app": { "include": ["./app/"], "dependencies": ["core"] }
+  }
+}"#,
+  );
+  temp_dir.create_dir_all("core");
+  temp_dir.create_dir_all("app");
+  temp_dir.write(
+    "core/mod.ts",
+    "export function add(a: number, b: number) {\n  return a + b;\n}\n",
+  );
+  temp_dir.write(
+    "app/main.ts",
+    "import { add } from '../core/mod.ts';\n\nconsole.log(add(1, 2));\n",
+  );
+
+  let check_command = test_context
+    .new_command()
+    .args_vec(["check", "app/main.ts"]);
+  let output = check_command.run();
+  output.assert_matches_text("Check unit core\nCheck unit app\n");
+  output.assert_exit_code(0);
+
+  // only the dependents of the changed unit are checked again
+  temp_dir.write(
+    "app/main.ts",
+    "import { add } from '../core/mod.ts';\n\nconsole.log(add(2, 3));\n",
+  );
+  let output = check_command.run();
+  output.assert_matches_text("Check unit app\n");
+  output.assert_exit_code(0);
+
+  // the units may only import the units they depend on
+  temp_dir.write(
+    "core/mod.ts",
+    "import '../app/main.ts';\n\nexport function add(a: number, b: number) {\n  return a + b;\n}\n",
+  );
+  let output = check_command.run();
+  output.assert_matches_text(
+    "error: Module \"[WILDCARD]/core/mod.ts\" of the check unit \"core\" imports \"[WILDCARD]/app/main.ts\" of the check unit \"app\", which is not one of its dependencies.\n  Add \"app\" to the \"dependencies\" of the unit in the configuration file.\n",
+  );
+  output.assert_exit_code(1);
+}
+
+#[test]
+fn project_references() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+      "compilerOptions": { "noImplicitAny": true },
+      "references": [{ "path": "./app" }]
+    }"#,
+  );
+  temp_dir.create_dir_all("core");
+  temp_dir.create_dir_all("app");
+  temp_dir.write(
+    "core/deno.json",
+    r#"{ "compilerOptions": { "noImplicitAny": false } }"#,
+  );
+  temp_dir.write(
+    "app/deno.json",
+    r#"{ "references": [{ "path": "../core" }] }"#,
+  );
+  // only allowed by the compiler options of the core project, which override
+  // the ones of the root configuration file
+  temp_dir.write(
+    "core/mod.ts",
+    "export function add(a, b): number {\n  return a + b;\n}\n",
+  );
+  temp_dir.write(
+    "app/main.ts",
+    "import { add } from '../core/mod.ts';\n\nconsole.log(add(1, 2));\n",
+  );
+
+  let check_command = test_context
+    .new_command()
+    .args_vec(["check", "app/main.ts"]);
+  let output = check_command.run();
+  output.assert_matches_text("Check unit core\nCheck unit app\n");
+  output.assert_exit_code(0);
+
+  // only the projects that depend on the changed project are checked again
+  temp_dir.write(
+    "app/main.ts",
+    "import { add } from '../core/mod.ts';\n\nconsole.log(add(2, 3));\n",
+  );
+  let output = check_command.run();
+  output.assert_matches_text("Check unit app\n");
+  output.assert_exit_code(0);
+
+  temp_dir.write(
+    "app/main.ts",
+    "import { add } from '../core/mod.ts';\n\nexport function double(a) {\n  return add(a, a);\n}\n",
+  );
+  let output = check_command.run();
+  output.assert_matches_text(
+    "Check unit app\nerror: TS7006 [ERROR]: Parameter 'a' implicitly has an 'any' type.\n[WILDCARD]",
+  );
+  output.assert_exit_code(1);
+}
+
+#[test]
+fn emit_declarations() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.create_dir_all("src/util");
+  temp_dir.write(
+    "src/util/math.ts",
+    "export function add(a: number, b: number): number {\n  return a + b;\n}\n",
+  );
+  temp_dir.write(
+    "src/mod.ts",
+    "import { add } from './util/math.ts';\n\nexport { add };\nexport const answer = add(40, 2);\n",
+  );
+
+  let output = test_context
+    .new_command()
+    .args_vec([
+      "check",
+      "--emit-declarations",
+      "--out-dir=types",
+      "src/mod.ts",
+    ])
+    .run();
+  output.assert_matches_text("Check [WILDCARD]mod.ts\nEmit [WILDCARD]types\n");
+  output.assert_exit_code(0);
+
+  // the layout is relative to the common directory of the modules and the
+  // imports use the extension of the emitted JavaScript
+  let mod_declaration = temp_dir.read_to_string("types/mod.d.ts");
+  assert_contains!(mod_declaration, "import { add } from \"./util/math.js\";");
+  assert_contains!(mod_declaration, "export declare const answer: number;");
+  assert_contains!(mod_declaration, "//# sourceMappingURL=mod.d.ts.map");
+  let math_declaration = temp_dir.read_to_string("types/util/math.d.ts");
+  assert_contains!(
+    math_declaration,
+    "export declare function add(a: number, b: number): number;"
+  );
+  let math_map = temp_dir.read_to_string("types/util/math.d.ts.map");
+  assert_contains!(math_map, r#""file":"math.d.ts""#);
+  assert_contains!(math_map, r#""sources":["../../src/util/math.ts"]"#);
+}
+
+#[test]
+fn check_unused() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.write(
+    "util.ts",
+    "export const used = 1;\nexport function unused() {}\n",
+  );
+  temp_dir.write(
+    "mod.ts",
+    "import { used } from './util.ts';\n\nexport const value = used;\n",
+  );
+
+  let output = test_context
+    .new_command()
+    .args("check --unused mod.ts")
+    .run();
+  output.assert_matches_text(
+    "Check [WILDCARD]mod.ts
+unused export unused is not imported by any module
+    at file:///[WILDCARD]util.ts:2:1
+error: Found 1 unused export.
+",
+  );
+  output.assert_exit_code(1);
+
+  let output = test_context
+    .new_command()
+    .args("check --unused --json mod.ts")
+    .split_output()
+    .run();
+  output.assert_exit_code(1);
+  output.skip_output_check();
+  let value: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  let unused_exports = value["unusedExports"].as_array().unwrap();
+  assert_eq!(unused_exports.len(), 1);
+  assert_eq!(unused_exports[0]["name"], "unused");
+  assert_eq!(unused_exports[0]["line"], 2);
+  assert_eq!(unused_exports[0]["column"], 1);
+
+  // the exports are used once they are imported
+  temp_dir.write(
+    "mod.ts",
+    "import { unused, used } from './util.ts';\n\nexport const value = used;\nunused();\n",
+  );
+  test_context
+    .new_command()
+    .args("check --unused mod.ts")
+    .run()
+    .assert_matches_text("Check [WILDCARD]mod.ts\n")
+    .assert_exit_code(0);
+}
+
+#[test]
+fn check_import_cycles() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.create_dir_all("src/legacy");
+  temp_dir.write(
+    "main.ts",
+    "import './src/a.ts';\nimport './src/legacy/c.ts';\n",
+  );
+  temp_dir.write("src/a.ts", "import './b.ts';\nexport const a = 1;\n");
+  temp_dir.write("src/b.ts", "import './a.ts';\nexport const b = 1;\n");
+  temp_dir.write("src/legacy/c.ts", "import './d.ts';\nexport const c = 1;\n");
+  temp_dir.write("src/legacy/d.ts", "import './c.ts';\nexport const d = 1;\n");
+
+  // the cycles are ignored without configuration
+  temp_dir.write("deno.json", "{}");
+  test_context
+    .new_command()
+    .args("check main.ts")
+    .run()
+    .assert_matches_text("Check [WILDCARD]main.ts\n")
+    .assert_exit_code(0);
+
+  temp_dir.write(
+    "deno.json",
+    r#"{ "cycles": { "./": "warn", "src/legacy/": "ignore" } }"#,
+  );
+  test_context
+    .new_command()
+    .args("check main.ts")
+    .run()
+    .assert_matches_text(
+      "Warning Import cycle: file:///[WILDCARD]src/a.ts -> file:///[WILDCARD]src/b.ts -> file:///[WILDCARD]src/a.ts
+Check [WILDCARD]main.ts
+",
+    )
+    .assert_exit_code(0);
+
+  temp_dir.write(
+    "deno.json",
+    r#"{ "cycles": { "src/": "error", "src/legacy/": "ignore" } }"#,
+  );
+  test_context
+    .new_command()
+    .args("check main.ts")
+    .run()
+    .assert_matches_text(
+      "error: Import cycles are not allowed by the \"cycles\" configuration:
+  file:///[WILDCARD]src/a.ts -> file:///[WILDCARD]src/b.ts -> file:///[WILDCARD]src/a.ts
+",
+    )
+    .assert_exit_code(1);
+
+  // the cycles are reported once for the top level graph, rather than again
+  // when the graph is prepared for a dynamic import
+  temp_dir.write("deno.json", r#"{ "cycles": { "src/*.ts": "warn" } }"#);
+  temp_dir.write(
+    "run.ts",
+    "import './src/a.ts';
+await import('./src/legacy/c.ts');
+console.log('done');
+",
+  );
+  let output = test_context.new_command().args("run --check run.ts").run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_eq!(output_text.matches("Import cycle").count(), 1);
+  assert_contains!(output_text, "done");
+}
+
+#[test]
+fn emit_declarations_reports_diagnostics() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  // only an error when emitting the declarations
+  temp_dir.write(
+    "mod.ts",
+    "export const Counter = class {\n  private count = 0;\n};\n",
+  );
+
+  let output = test_context
+    .new_command()
+    .args_vec(["check", "--emit-declarations", "--out-dir=types", "mod.ts"])
+    .run();
+  output.assert_matches_text(
+    "Check [WILDCARD]mod.ts\nerror: TS4094 [ERROR]: Property 'count' of exported class expression may not be private or protected.\n[WILDCARD]",
+  );
+  output.assert_exit_code(1);
+}
+
+#[test]
+fn check_unused_with_check_options() {
+  let test_context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = test_context.temp_dir();
+  temp_dir.write(
+    "util.ts",
+    "export const used = 1;\nexport function unused() {}\n",
+  );
+  temp_dir.write(
+    "mod.ts",
+    "import { used } from './util.ts';\n\nexport const value = used;\n",
+  );
+
+  // the declarations are emitted like without --unused
+  let output = test_context
+    .new_command()
+    .args("check --unused --emit-declarations --out-dir=types mod.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "unused export unused is not imported"
+  );
+  assert!(temp_dir.path().join("types/util.d.ts").exists());
+
+  // and the type errors are reported along with the unused exports
+  temp_dir.write(
+    "mod.ts",
+    "import { used } from './util.ts';\n\nexport const value: string = used;\n",
+  );
+  let output = test_context
+    .new_command()
+    .args("check --unused mod.ts")
+    .run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "unused export unused is not imported");
+  assert_contains!(output_text, "TS2322 [ERROR]");
+}
+
+#[test]
+fn check_remote_cache() {
+  let test_context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .use_separate_deno_dir()
+    .env("REMOTE_CACHE_TOKEN", "remote-cache-token")
+    .build();
+  let temp_dir = test_context.temp_dir();
+  // the server keeps the artifacts of previous runs while it's up
+  let run_id = std::time::SystemTime::now()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap()
+    .as_nanos();
+  temp_dir.write(
+    "deno.json",
+    format!(
+      r#"{{
+  "remoteCache": {{
+    "url": "http://localhost:4545/remote_cache/{run_id}/",
+    "authTokenEnv": "REMOTE_CACHE_TOKEN"
+  }}
+}}"#
+    ),
+  );
+  temp_dir.write("main.ts", "const value: number = 1;\nconsole.log(value);\n");
+
+  // the check is written through to the remote cache
+  let output = test_context.new_command().args("check main.ts").run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Check");
+
+  // and read through from it with an empty DENO_DIR
+  let other_deno_dir = temp_dir.path().join("other_deno_dir");
+  let output = test_context
+    .new_command()
+    .env("DENO_DIR", other_deno_dir.to_string_lossy())
+    .args("check main.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert!(!output.combined_output().contains("Check"));
+  let output = test_context
+    .new_command()
+    .env("DENO_DIR", other_deno_dir.to_string_lossy())
+    .args("run main.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_eq!(output.combined_output(), "1\n");
+
+  // a cache that can't be reached is only warned about once
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "remoteCache": {
+    "url": "http://localhost:1/remote_cache/",
+    "authTokenEnv": "REMOTE_CACHE_TOKEN"
+  }
+}"#,
+  );
+  temp_dir.write("main.ts", "const value: number = 2;\nconsole.log(value);\n");
+  let third_deno_dir = temp_dir.path().join("third_deno_dir");
+  let output = test_context
+    .new_command()
+    .env("DENO_DIR", third_deno_dir.to_string_lossy())
+    .args("run --check main.ts")
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_eq!(output_text.matches("can't be reached").count(), 1);
+  assert_contains!(output_text, "Check");
+  assert!(output_text.ends_with("2\n"));
+}
@@ -0,0 +1,75 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use test_util::TestContext;
+use test_util::TestContextBuilder;
+
+fn write_caches(context: &TestContext) {
+  let deno_dir = context.deno_dir();
+  deno_dir.create_dir_all("deps/https/esm.sh");
+  deno_dir.write("deps/https/esm.sh/a", "export const a = 1;");
+  deno_dir.create_dir_all("deps/https/deno.land");
+  deno_dir.write("deps/https/deno.land/b", "export const b = 2;");
+  deno_dir.create_dir_all("gen/https/esm.sh");
+  deno_dir.write("gen/https/esm.sh/a.js", "export const a = 1;");
+  deno_dir.create_dir_all("npm/registry.npmjs.org/chalk");
+  deno_dir.write("npm/registry.npmjs.org/chalk/registry.json", "{}");
+  deno_dir.create_dir_all("location_data");
+  deno_dir.write("location_data/data", "data");
+}
+
+#[test]
+fn clean_origin() {
+  let context = TestContextBuilder::new().use_separate_deno_dir().build();
+  write_caches(&context);
+  let deno_dir = context.deno_dir().path();
+
+  let output = context
+    .new_command()
+    .args("clean --origin esm.sh --dry-run")
+    .run();
+  output.assert_matches_text(concat!(
+    "Would remove [WILDCARD]esm.sh (19B)\n",
+    "Would remove [WILDCARD]esm.sh (19B)\n",
+    "Would remove 38B in total\n",
+  ));
+  output.assert_exit_code(0);
+  assert!(deno_dir.join("deps/https/esm.sh/a").exists());
+
+  let output = context.new_command().args("clean --origin esm.sh").run();
+  output.assert_matches_text(concat!(
+    "Removed [WILDCARD]esm.sh (19B)\n",
+    "Removed [WILDCARD]esm.sh (19B)\n",
+    "Removed 38B in total\n",
+  ));
+  output.assert_exit_code(0);
+  assert!(!deno_dir.join("deps/https/esm.sh").exists());
+  assert!(!deno_dir.join("gen/https/esm.sh").exists());
+  assert!(deno_dir.join("deps/https/deno.land/b").exists());
+  assert!(deno_dir.join("npm/registry.npmjs.org").exists());
+}
+
+#[test]
+fn clean_scopes() {
+  let context = TestContextBuilder::new().use_separate_deno_dir().build();
+  write_caches(&context);
+  let deno_dir = context.deno_dir().path();
+
+  let output = context.new_command().args("clean --npm").run();
+  output
+    .assert_matches_text("Removed [WILDCARD]npm (2B)\nRemoved 2B in total\n");
+  output.assert_exit_code(0);
+  assert!(!deno_dir.join("npm").exists());
+  assert!(deno_dir.join("deps/https/esm.sh/a").exists());
+
+  // everything but the data that isn't a cache
+  let output = context.new_command().args("clean").run();
+  output.assert_matches_text("[WILDCARD]Removed 57B in total\n");
+  output.assert_exit_code(0);
+  assert!(!deno_dir.join("deps").exists());
+  assert!(!deno_dir.join("gen").exists());
+  assert!(deno_dir.join("location_data/data").exists());
+
+  let output = context.new_command().args("clean").run();
+  output.assert_matches_text("Nothing to clean in [WILDCARD]\n");
+  output.assert_exit_code(0);
+}
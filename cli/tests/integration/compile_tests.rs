@@ -787,3 +787,179 @@ fn dynamic_import_unanalyzable() {
   .unwrap();
   assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
 }
+
+#[test]
+fn compile_with_snapshot() {
+  let dir = TempDir::new();
+  let exe = if cfg!(windows) {
+    dir.path().join("snapshot.exe")
+  } else {
+    dir.path().join("snapshot")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .arg("compile")
+    .arg("--snapshot")
+    .arg("./compile/snapshot/startup.ts")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./compile/snapshot/main.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  // the startup module is not evaluated again when the main module imports it
+  assert_eq!(output.stdout, b"1\n0,1,4,9,16\n");
+}
+
+#[test]
+fn compile_with_snapshot_pending_work() {
+  let dir = TempDir::new();
+  let exe = if cfg!(windows) {
+    dir.path().join("snapshot_pending.exe")
+  } else {
+    dir.path().join("snapshot_pending")
+  };
+  let output = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .arg("compile")
+    .arg("--snapshot")
+    .arg("./compile/snapshot/pending_timer.ts")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./compile/snapshot/main.ts")
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(!output.status.success());
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  assert!(stderr.contains("The startup modules left pending work"));
+}
+
+#[test]
+fn compile_with_include_dir() {
+  let dir = TempDir::new();
+  dir.create_dir_all("static/nested");
+  dir.write("static/hello.txt", "Hello");
+  dir.write("static/nested/data.json", r#"{ "value": 42 }"#);
+  dir.write(
+    "main.ts",
+    r#"import { readFileSync } from "node:fs";
+import { createRequire } from "node:module";
+const hello = new URL("./static/hello.txt", import.meta.url);
+console.log(await Deno.readTextFile(hello));
+console.log(await (await fetch(hello)).text());
+console.log(readFileSync(hello, "utf8"));
+const require = createRequire(import.meta.url);
+console.log(require("./static/nested/data.json").value);
+// not analyzable, so it isn't in the bundle
+const dataPath = "./static/nested/" + "data.json";
+const data = await import(dataPath, { assert: { type: "json" } });
+console.log(data.default.value);
+const names = [];
+for (const entry of Deno.readDirSync(new URL("./static", import.meta.url))) {
+  names.push(`${entry.name}:${entry.isFile}`);
+}
+console.log(names.join(","));
+try {
+  Deno.writeTextFileSync(hello, "Bye");
+} catch (err) {
+  console.log(err.constructor.name);
+}
+"#,
+  );
+  let exe = if cfg!(windows) {
+    dir.path().join("include_dir.exe")
+  } else {
+    dir.path().join("include_dir")
+  };
+  let output = util::deno_cmd()
+    .current_dir(dir.path())
+    .arg("compile")
+    .arg("--allow-read")
+    .arg("--allow-write")
+    .arg("--include-dir")
+    .arg("static")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./main.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  // the executable reads the embedded files instead of the disk
+  std::fs::remove_dir_all(dir.path().join("static")).unwrap();
+  let output = Command::new(exe)
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  assert_eq!(
+    String::from_utf8(output.stdout).unwrap(),
+    "Hello\nHello\nHello\n42\n42\nhello.txt:true,nested:false\nPermissionDenied\n"
+  );
+}
+
+#[test]
+fn compile_stack_trace_source_map() {
+  let dir = TempDir::new();
+  dir.write(
+    "main.ts",
+    r#"interface Options {
+  fail: boolean;
+}
+
+function run(options: Options): void {
+  if (options.fail) {
+    throw new Error("boom");
+  }
+}
+
+run({ fail: true });
+"#,
+  );
+  let exe = if cfg!(windows) {
+    dir.path().join("source_map.exe")
+  } else {
+    dir.path().join("source_map")
+  };
+  let output = util::deno_cmd()
+    .current_dir(dir.path())
+    .arg("compile")
+    .arg("--output")
+    .arg(&exe)
+    .arg("./main.ts")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(output.status.success());
+  let output = Command::new(exe)
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  assert!(!output.status.success());
+  let stderr = String::from_utf8(output.stderr).unwrap();
+  // the frames and the source line are the ones of the TypeScript module
+  assert!(stderr.contains("throw new Error(\"boom\");"));
+  assert!(stderr.contains("main.ts:7:11"));
+  assert!(stderr.contains("main.ts:11:1"));
+}
@@ -0,0 +1,47 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use test_util::assert_contains;
+use test_util::TestContextBuilder;
+
+#[test]
+fn cron_invalid_schedule() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  context.temp_dir().write(
+    "tasks.ts",
+    r#"export default {
+  schedules: [
+    { name: "cleanup", schedule: "*/5 * * *", handler() {} },
+  ],
+};
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args("cron tasks.ts")
+    .run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "Failed registering the schedule \"cleanup\"");
+  assert_contains!(output_text, "Invalid cron schedule \"*/5 * * *\"");
+}
+
+#[test]
+fn cron_requires_schedules() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  context
+    .temp_dir()
+    .write("tasks.ts", "export default { cleanup() {} };\n");
+
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args("cron tasks.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "The default export of the module must be an object with a schedules array."
+  );
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use test_util as util;
 use util::assert_contains;
 use util::TestContext;
@@ -26,11 +27,44 @@ fn deno_doc() {
   }
 }
 
+#[test]
+fn deno_doc_json_reference_targets() {
+  let context = TestContext::default();
+  let output = context
+    .new_command()
+    .args("doc --json doc/references/mod.ts")
+    .split_output()
+    .run();
+
+  output.assert_exit_code(0);
+  let doc_nodes: serde_json::Value =
+    serde_json::from_str(output.stdout()).unwrap();
+  let function = doc_nodes
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|node| node["name"] == "run")
+    .unwrap();
+  let target =
+    &function["functionDef"]["params"][0]["tsType"]["typeRef"]["target"];
+  assert_eq!(target["name"], "Options");
+  assert!(target["specifier"]
+    .as_str()
+    .unwrap()
+    .ends_with("/doc/references/types.ts"));
+}
+
 itest!(deno_doc_import_map {
   args: "doc --unstable --import-map=doc/import_map.json doc/use_import_map.js",
   output: "doc/use_import_map.out",
 });
 
+itest!(deno_doc_lint {
+  args: "doc --lint doc/lint/main.ts",
+  output: "doc/lint/main.out",
+  exit_code: 1,
+});
+
 itest!(deno_doc_types_hint {
   args: "doc doc/types_hint.ts",
   output: "doc/types_hint.out",
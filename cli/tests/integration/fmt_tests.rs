@@ -3,6 +3,7 @@
 use test_util as util;
 use test_util::TempDir;
 use util::TestContext;
+use util::TestContextBuilder;
 
 #[test]
 fn fmt_test() {
@@ -164,6 +165,102 @@ fn fmt_auto_ignore_git_and_node_modules() {
   assert_eq!(output.combined_output(), "error: No target files found.\n");
 }
 
+#[test]
+fn fmt_overrides() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "fmt": {
+    "options": { "singleQuote": true },
+    "overrides": [{
+      "files": ["./generated/"],
+      "options": { "singleQuote": false, "semiColons": false }
+    }]
+  }
+}"#,
+  );
+  temp_dir.write("main.ts", "const a = \"x\";\n");
+  temp_dir.create_dir_all("generated");
+  temp_dir.write("generated/mod.ts", "const b = 'y';\n");
+
+  let output = context.new_command().args("fmt main.ts generated").run();
+  output.assert_exit_code(0);
+  assert_eq!(temp_dir.read_to_string("main.ts"), "const a = 'x';\n");
+  assert_eq!(
+    temp_dir.read_to_string("generated/mod.ts"),
+    "const b = \"y\"\n"
+  );
+
+  let output = context
+    .new_command()
+    .args("fmt --check main.ts generated")
+    .run();
+  output.assert_exit_code(0);
+
+  // the flags take precedence over the options of the overrides
+  let output = context
+    .new_command()
+    .args("fmt --check --single-quote main.ts generated")
+    .run();
+  output.assert_exit_code(1);
+  util::assert_contains!(
+    output.combined_output(),
+    "Found 1 not formatted file in 2 files"
+  );
+}
+
+#[test]
+fn fmt_organize_imports() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"import { z, a } from "./util.ts";
+import { unused } from "./util.ts";
+import chalk from "npm:chalk@5";
+import { join } from "https://deno.land/std@0.190.0/path/mod.ts";
+
+console.log(z, a, chalk, join);
+"#,
+  );
+  temp_dir.write("util.ts", "export const a = 1;\nexport const z = 2;\n");
+
+  let output = context.new_command().args("fmt --check main.ts").run();
+  output.assert_exit_code(0);
+  let output = context
+    .new_command()
+    .args("fmt --check --organize-imports main.ts")
+    .run();
+  output.assert_exit_code(1);
+
+  let output = context
+    .new_command()
+    .args("fmt --organize-imports main.ts util.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_eq!(
+    temp_dir.read_to_string("main.ts"),
+    r#"import { join } from "https://deno.land/std@0.190.0/path/mod.ts";
+
+import chalk from "npm:chalk@5";
+
+import { a, z } from "./util.ts";
+
+console.log(z, a, chalk, join);
+"#
+  );
+
+  // the option of the config file organizes them too
+  temp_dir.write(
+    "deno.json",
+    r#"{ "fmt": { "options": { "organizeImports": true } } }"#,
+  );
+  let output = context.new_command().args("fmt --check main.ts").run();
+  output.assert_exit_code(0);
+}
+
 itest!(fmt_quiet_check_fmt_dir {
   args: "fmt --check --quiet fmt/regular/",
   output_str: Some(""),
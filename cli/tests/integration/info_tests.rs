@@ -1,8 +1,11 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use test_util as util;
 use test_util::TempDir;
+use util::assert_contains;
 use util::env_vars_for_npm_tests_no_sync_download;
+use util::TestContextBuilder;
 
 #[test]
 fn info_with_compiled_source() {
@@ -153,3 +156,209 @@ itest!(package_json_basic {
   copy_temp_dir: Some("package_json/basic"),
   exit_code: 0,
 });
+
+#[test]
+fn info_cache_status() {
+  let context = TestContextBuilder::new().use_http_server().build();
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args("info --cache-status http://localhost:4545/run/002_hello.ts")
+    .run();
+  output.assert_exit_code(0);
+  let text = output.combined_output();
+  assert_contains!(text, "remote module cache:");
+  assert_contains!(text, "http://localhost:4545/run/002_hello.ts\n  cached: ");
+  assert_contains!(text, "  reload: ");
+  assert_contains!(text, "    content-type: application/typescript");
+}
+
+#[test]
+fn info_sqlite_cache_backend() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .env("DENO_CACHE_BACKEND", "sqlite")
+    .build();
+  let module_url = "http://localhost:4545/run/048_media_types_jsx.ts";
+  let output = context.new_command().args_vec(["cache", module_url]).run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  assert!(context.deno_dir().path().join("cache_store_v1.db").exists());
+
+  // the modules and emits in the store are reported
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args_vec(["info", module_url])
+    .run();
+  output.assert_exit_code(0);
+  let text = output.combined_output();
+  assert_contains!(text, "local: ");
+  assert_contains!(text, "emit: ");
+}
+
+#[test]
+fn info_diff() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "old.lock",
+    r#"{
+  "version": "2",
+  "remote": {
+    "http://localhost:4545/run/removed.ts": "0000",
+    "http://localhost:4545/subdir/print_hello.ts": "0000"
+  }
+}"#,
+  );
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args(
+      "info --diff old.lock http://localhost:4545/run/003_relative_import.ts",
+    )
+    .run();
+  output.assert_exit_code(0);
+  let text = output.combined_output();
+  assert_contains!(text, "old.lock:\n");
+  assert_contains!(
+    text,
+    "  + http://localhost:4545/run/003_relative_import.ts (+"
+  );
+  assert_contains!(text, "  - http://localhost:4545/run/removed.ts\n");
+  assert_contains!(
+    text,
+    "  ~ http://localhost:4545/subdir/print_hello.ts (integrity changed)\n"
+  );
+  assert_contains!(text, "(some sizes are unknown)");
+
+  // the changes are the ones of `deno lock diff`
+  let output = context
+    .new_command()
+    .args("info --json --diff old.lock http://localhost:4545/run/003_relative_import.ts")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  let json: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  assert_eq!(
+    json["dependencyDiff"]["remote"][2],
+    serde_json::json!({
+      "kind": "changed",
+      "name": "http://localhost:4545/subdir/print_hello.ts",
+      "oldVersion": null,
+      "newVersion": null,
+      "oldIntegrity": "0000",
+      "newIntegrity": json["dependencyDiff"]["remote"][2]["newIntegrity"],
+      "sizeDelta": null,
+    })
+  );
+
+  let output = context
+    .new_command()
+    .args("info --diff missing.lock http://localhost:4545/run/003_relative_import.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Could not find lock file");
+}
+
+#[test]
+fn info_cycles() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.ts", "import './a.ts';\n");
+  temp_dir.write("a.ts", "import './b.ts';\nexport const a = 1;\n");
+  temp_dir.write("b.ts", "import './a.ts';\nexport const b = 1;\n");
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args("info --cycles main.ts")
+    .run();
+  output.assert_exit_code(0);
+  let text = output.combined_output();
+  assert_contains!(text, "import cycles:\nfile:///");
+  assert_contains!(text, "a.ts -> file:///");
+  assert_contains!(text, "b.ts -> file:///");
+
+  let output = context
+    .new_command()
+    .split_output()
+    .args("info --cycles --json main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let json: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  let cycles = json["cycles"].as_array().unwrap();
+  assert_eq!(cycles.len(), 1);
+  assert_eq!(cycles[0].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn info_sbom_cyclonedx() {
+  let context = TestContextBuilder::new().use_http_server().build();
+  let output = context
+    .new_command()
+    .split_output()
+    .args(
+      "info --sbom=cyclonedx http://127.0.0.1:4545/run/003_relative_import.ts",
+    )
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let sbom: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  assert_eq!(sbom["bomFormat"], "CycloneDX");
+  assert_eq!(sbom["specVersion"], "1.5");
+  let components = sbom["components"].as_array().unwrap();
+  assert_eq!(components.len(), 2);
+  assert_eq!(
+    components[0]["name"],
+    "http://127.0.0.1:4545/run/003_relative_import.ts"
+  );
+  assert_eq!(
+    components[0]["hashes"][0]["content"],
+    "a1572e8fd2c2712b33f04aed2561505b5feb2c8696f1f2cded3de7127931b97e"
+  );
+  assert_eq!(
+    components[1]["name"],
+    "http://127.0.0.1:4545/subdir/print_hello.ts"
+  );
+  assert_eq!(
+    components[1]["hashes"][0]["content"],
+    "fa6692c8f9ff3fb107e773c3ece5274e9d08be282867a1e3ded1d9c00fcaa63c"
+  );
+  assert_eq!(
+    sbom["dependencies"][1]["dependsOn"],
+    serde_json::json!(["http://127.0.0.1:4545/subdir/print_hello.ts"])
+  );
+}
+
+#[test]
+fn info_sbom_spdx_npm() {
+  let context = TestContextBuilder::for_npm()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let output = context
+    .new_command()
+    .split_output()
+    .args("info --sbom=spdx npm:chalk@5.0.1")
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let sbom: serde_json::Value = serde_json::from_str(output.stdout()).unwrap();
+  assert_eq!(sbom["spdxVersion"], "SPDX-2.3");
+  let chalk = sbom["packages"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|package| package["name"] == "chalk")
+    .unwrap();
+  assert_eq!(chalk["versionInfo"], "5.0.1");
+  assert_eq!(chalk["checksums"][0]["algorithm"], "SHA512");
+  assert_eq!(
+    chalk["externalRefs"][0]["referenceLocator"],
+    "pkg:npm/chalk@5.0.1"
+  );
+}
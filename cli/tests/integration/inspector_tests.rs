@@ -1382,3 +1382,84 @@ async fn inspector_wait() {
   assert!(temp_dir.path().join("hello.txt").exists());
   tester.child.kill().unwrap();
 }
+
+#[tokio::test]
+async fn inspector_children() {
+  let script = util::testdata_path().join("inspector/inspect_children.js");
+  let mut child = util::deno_cmd()
+    .arg("run")
+    .arg("--allow-run")
+    .arg(inspect_flag_with_unique_port("--inspect"))
+    .arg("--inspect-children")
+    .arg(script)
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+
+  let stderr = child.stderr.as_mut().unwrap();
+  let mut stderr_lines =
+    std::io::BufReader::new(stderr).lines().map(|r| r.unwrap());
+  let ws_url = extract_ws_url_from_stderr(&mut stderr_lines);
+  // the child process registers its inspector with the server of the parent
+  let child_line = stderr_lines
+    .find(|line| line.starts_with("Debugger of a child process listening on "))
+    .unwrap();
+  let child_ws_url =
+    url::Url::parse(child_line.rsplit(' ').next().unwrap()).unwrap();
+  assert_eq!(child_ws_url.port(), ws_url.port());
+
+  let mut url = ws_url.clone();
+  let _ = url.set_scheme("http");
+  url.set_path("/json/list");
+  let resp = reqwest::get(url).await.unwrap();
+  assert_eq!(resp.status(), reqwest::StatusCode::OK);
+  let endpoint_list: Vec<deno_core::serde_json::Value> =
+    serde_json::from_str(&resp.text().await.unwrap()).unwrap();
+  assert_eq!(endpoint_list.len(), 2);
+  assert!(endpoint_list
+    .iter()
+    .any(|e| e["webSocketDebuggerUrl"] == child_ws_url.as_str()));
+
+  // the connection to the child is proxied by the parent
+  let (_socket, response) = tokio_tungstenite::connect_async(child_ws_url)
+    .await
+    .unwrap();
+  assert_eq!("101 Switching Protocols", response.status().to_string());
+  child.kill().unwrap();
+  child.wait().unwrap();
+}
+
+#[tokio::test]
+async fn inspector_rejects_children_without_flag() {
+  let script = util::testdata_path().join("inspector/inspector1.js");
+  let mut child = util::deno_cmd()
+    .arg("run")
+    .arg(inspect_flag_with_unique_port("--inspect"))
+    .arg(script)
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+
+  let stderr = child.stderr.as_mut().unwrap();
+  let mut stderr_lines =
+    std::io::BufReader::new(stderr).lines().map(|r| r.unwrap());
+  let ws_url = extract_ws_url_from_stderr(&mut stderr_lines);
+  let mut url = ws_url.clone();
+  let _ = url.set_scheme("http");
+  url.set_path("/json/children");
+  let body = json!({
+    "id": "2e1a5a5b-6d2c-4f1e-9b5a-3c1d2e3f4a5b",
+    "webSocketDebuggerUrl":
+      "ws://127.0.0.1:9999/ws/2e1a5a5b-6d2c-4f1e-9b5a-3c1d2e3f4a5b",
+  });
+  // the server only registers child processes with --inspect-children
+  let resp = reqwest::Client::new()
+    .put(url)
+    .body(body.to_string())
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), reqwest::StatusCode::NOT_FOUND);
+  child.kill().unwrap();
+  child.wait().unwrap();
+}
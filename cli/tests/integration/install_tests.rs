@@ -6,6 +6,7 @@ use test_util as util;
 use test_util::assert_contains;
 use test_util::assert_ends_with;
 use test_util::TempDir;
+use test_util::TestContextBuilder;
 
 #[test]
 fn install_basic() {
@@ -241,3 +242,76 @@ fn check_local_by_default2() {
     .unwrap();
   assert!(status.success());
 }
+
+#[test]
+fn install_deps_from_config() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "imports": {
+    "subdir/": "http://localhost:4545/subdir/",
+    "mod1": "http://localhost:4545/subdir/mod1.ts"
+  }
+}"#,
+  );
+  // the modules of prefix mappings are found from the local modules
+  temp_dir.write("main.ts", "import \"subdir/print_hello.ts\";\n");
+
+  let output = context.new_command().args("install").run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Successfully installed 2 import map entries"
+  );
+  let lockfile = temp_dir.read_to_string("deno.lock");
+  assert_contains!(lockfile, "http://localhost:4545/subdir/mod1.ts");
+  assert_contains!(lockfile, "http://localhost:4545/subdir/subdir2/mod2.ts");
+  assert_contains!(lockfile, "http://localhost:4545/subdir/print_hello.ts");
+}
+
+#[test]
+fn install_deps_frozen() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "imports": { "mod1": "http://localhost:4545/subdir/mod1.ts" } }"#,
+  );
+  context
+    .new_command()
+    .args("install")
+    .run()
+    .assert_exit_code(0);
+
+  // the lockfile is missing the remote modules
+  temp_dir.write("deno.lock", r#"{ "version": "2", "remote": {} }"#);
+  let output = context.new_command().args("install --frozen").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "The lockfile is out of date and --frozen is specified"
+  );
+  assert_eq!(
+    temp_dir.read_to_string("deno.lock"),
+    r#"{ "version": "2", "remote": {} }"#
+  );
+}
+
+#[test]
+fn install_deps_without_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let output = context.new_command().args("install").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "No deno.json or package.json found"
+  );
+}
@@ -0,0 +1,72 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use test_util::assert_contains;
+use test_util::TestContextBuilder;
+
+#[test]
+fn license_npm_and_remote() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"import chalk from "npm:chalk@5.0.1";
+import { value } from "http://localhost:4545/license/pkg@1.0.0/mod.ts";
+console.log(chalk, value);
+"#,
+  );
+  temp_dir.write(
+    "deno.json",
+    r#"{ "licenses": { "allow": ["MIT", "Apache-2.0"] } }"#,
+  );
+
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .split_output()
+    .args("license main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  assert_eq!(
+    output.stdout(),
+    concat!(
+      "MIT (2)\n",
+      "  npm:chalk@5.0.1\n",
+      "  http://localhost:4545/license/pkg@1.0.0/\n",
+    )
+  );
+}
+
+#[test]
+fn license_policy_violation() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"import chalk from "npm:chalk@5.0.1";
+import { value } from "http://localhost:4545/license/pkg@1.0.0/mod.ts";
+console.log(chalk, value);
+"#,
+  );
+  temp_dir.write("deno.json", r#"{ "licenses": { "allow": ["ISC"] } }"#);
+
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args("license main.ts")
+    .run();
+  output.assert_exit_code(1);
+  let text = output.combined_output();
+  assert_contains!(
+    text,
+    "error: 2 dependencies are not licensed under a license allowed by \"licenses.allow\" in file:///"
+  );
+  assert_contains!(
+    text,
+    concat!(
+      "deno.json:\n",
+      "  npm:chalk@5.0.1 (MIT)\n",
+      "  http://localhost:4545/license/pkg@1.0.0/ (MIT)\n",
+    )
+  );
+}
@@ -1,5 +1,9 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use test_util::assert_contains;
+use test_util::assert_not_contains;
+use test_util::TestContextBuilder;
+
 itest!(ignore_unexplicit_files {
   args: "lint --unstable --ignore=./",
   output_str: Some("error: No target files found.\n"),
@@ -114,3 +118,133 @@ itest!(lint_with_malformed_config2 {
   output: "lint/with_malformed_config2.out",
   exit_code: 1,
 });
+
+#[test]
+fn lint_with_plugin() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", r#"{ "lint": { "plugins": ["./plugin.ts"] } }"#);
+  temp_dir.write(
+    "plugin.ts",
+    r#"export default {
+  name: "my-plugin",
+  rules: {
+    "no-foo": {
+      create(context) {
+        return {
+          Ident(node) {
+            if (node.value === "foo") {
+              context.report({ span: node.span, message: "Don't use foo" });
+            }
+          },
+        };
+      },
+    },
+  },
+};
+"#,
+  );
+  temp_dir.write("main.ts", "export const foo = 1;\n");
+
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "my-plugin/no-foo");
+  assert_contains!(output_text, "Don't use foo");
+}
+
+#[test]
+fn lint_fix() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"import { join, resolve } from "./path.ts";
+
+let base = "/tmp";
+async function local(name: string) {
+  let unused = 1;
+  return join(base, name);
+}
+export async function dir(name: string) {
+  return await local(name);
+}
+"#,
+  );
+  temp_dir.write(
+    "path.ts",
+    "export const join = (a: string, b: string) => `${a}/${b}`;\nexport const resolve = join;\n",
+  );
+
+  let output = context.new_command().args("lint --fix main.ts").run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Fixed 4 problems in 1 file");
+  assert_eq!(
+    temp_dir.read_to_string("main.ts"),
+    r#"import { join } from "./path.ts";
+
+const base = "/tmp";
+function local(name: string) {
+  return join(base, name);
+}
+export async function dir(name: string) {
+  return await local(name);
+}
+"#
+  );
+
+  // nothing is left to fix
+  let output = context.new_command().args("lint --fix main.ts").run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Checked 1 file");
+}
+
+#[test]
+fn lint_severity_and_ignore_reasons() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "lint": {
+    "severity": { "no-var": "warn", "require-ignore-reason": "error" },
+    "overrides": [{ "files": ["./scripts/"], "severity": { "no-var": "off" } }]
+  }
+}"#,
+  );
+  temp_dir.write("main.ts", "var a = 1;\nconsole.log(a);\n");
+  temp_dir.create_dir_all("scripts");
+  temp_dir.write("scripts/build.ts", "var b = 2;\nconsole.log(b);\n");
+  temp_dir.write(
+    "ignore.ts",
+    r#"// deno-lint-ignore no-explicit-any -- the input is untyped
+export const x: any = 1;
+// deno-lint-ignore no-explicit-any
+export const y: any = 2;
+"#,
+  );
+
+  // warnings don't fail and the rule is off in the scripts
+  let output = context.new_command().args("lint main.ts scripts").run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "(no-var)");
+  assert_contains!(output_text, "Found 1 problem (1 warning)");
+  assert_contains!(output_text, "Checked 2 files");
+
+  // the directive without a reason is reported, but still ignores the line
+  let output = context.new_command().args("lint ignore.ts").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "(require-ignore-reason)");
+  assert_contains!(output_text, "Found 1 problem");
+  assert_not_contains!(output_text, "(no-explicit-any)");
+
+  temp_dir.write(
+    "deno.json",
+    r#"{ "lint": { "severity": { "no-var": "warn" }, "failOn": "warn" } }"#,
+  );
+  let output = context.new_command().args("lint main.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Found 1 problem (1 warning)");
+}
@@ -0,0 +1,16 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+itest!(lock_diff {
+  args: "lock diff lockfile/diff/old.lock lockfile/diff/new.lock",
+  output: "lockfile/diff/diff.out",
+});
+
+itest!(lock_diff_json {
+  args: "lock diff --json lockfile/diff/old.lock lockfile/diff/new.lock",
+  output: "lockfile/diff/diff_json.out",
+});
+
+itest!(lock_diff_no_changes {
+  args: "lock diff lockfile/diff/new.lock lockfile/diff/new.lock",
+  output: "lockfile/diff/same.out",
+});
@@ -5037,6 +5037,133 @@ fn lsp_completions_npm() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_npm_rename_imported_symbol() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { getValue } from \"npm:@denotest/esm-basic\";\n\nconsole.log(getValue());\n",
+    }
+  }));
+  client.write_request(
+    "deno/cache",
+    json!({
+      "referrer": { "uri": "file:///a/file.ts" },
+      "uris": [{ "uri": "npm:@denotest/esm-basic" }],
+    }),
+  );
+  client.read_diagnostics();
+
+  // the npm package is not edited, but the import is aliased instead
+  let res = client.write_request(
+    "textDocument/rename",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "position": { "line": 2, "character": 14 },
+      "newName": "get"
+    }),
+  );
+  assert_eq!(
+    res,
+    json!({
+      "documentChanges": [{
+        "textDocument": {
+          "uri": "file:///a/file.ts",
+          "version": 1
+        },
+        "edits": [{
+          "range": {
+            "start": { "line": 0, "character": 9 },
+            "end": { "line": 0, "character": 17 }
+          },
+          "newText": "getValue as get"
+        }, {
+          "range": {
+            "start": { "line": 2, "character": 12 },
+            "end": { "line": 2, "character": 20 }
+          },
+          "newText": "get"
+        }]
+      }]
+    })
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_npm_cjs_module_translated() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { hello } from \"npm:@denotest/cjs-reexport-collision\";\n\nhello.sayHello();\n",
+    }
+  }));
+  client.write_request(
+    "deno/cache",
+    json!({
+      "referrer": { "uri": "file:///a/file.ts" },
+      "uris": [{ "uri": "npm:@denotest/cjs-reexport-collision" }],
+    }),
+  );
+  client.read_diagnostics();
+
+  // `hello` is only exported by the translation of the module, since it's
+  // re-exported with `__exportStar`, and the positions in the translation
+  // are not in the source of the module
+  let res = client.write_request(
+    "textDocument/definition",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "position": { "line": 2, "character": 2 }
+    }),
+  );
+  let links = res.as_array().unwrap();
+  assert_eq!(links.len(), 1);
+  assert!(links[0]["targetUri"]
+    .as_str()
+    .unwrap()
+    .contains("/@denotest/cjs-reexport-collision/1.0.0/"));
+  assert_eq!(
+    links[0]["targetRange"],
+    json!({
+      "start": { "line": 0, "character": 0 },
+      "end": { "line": 0, "character": 0 }
+    })
+  );
+
+  // the references are only the ones in the workspace
+  let res = client.write_request(
+    "textDocument/references",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "position": { "line": 2, "character": 2 },
+      "context": { "includeDeclaration": true }
+    }),
+  );
+  let locations = res.as_array().unwrap();
+  assert_eq!(locations.len(), 2);
+  assert!(locations
+    .iter()
+    .all(|location| location["uri"] == "file:///a/file.ts"));
+  client.shutdown();
+}
+
 #[test]
 fn lsp_npm_specifier_unopened_file() {
   let context = TestContextBuilder::new()
@@ -6006,6 +6133,106 @@ fn lsp_diagnostics_refresh_dependents() {
   assert_eq!(client.queue_len(), 0);
 }
 
+#[test]
+fn lsp_diagnostics_pushed_without_pull_capability() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  let diagnostics = client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "const a: string = 1;\nconsole.log(a);\n",
+    },
+  }));
+  let diagnostics =
+    diagnostics.with_file_and_source("file:///a/file.ts", "deno-ts");
+  assert_eq!(diagnostics.diagnostics.len(), 1);
+  assert_eq!(
+    diagnostics.diagnostics[0].code,
+    Some(lsp::NumberOrString::Number(2322))
+  );
+  client.shutdown();
+}
+
+#[test]
+fn lsp_diagnostics_pull() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize(|builder| {
+    builder.enable_pull_diagnostics();
+  });
+  client.did_open_raw(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "const a: string = 1;\nconsole.log(a);\n",
+    },
+  }));
+  client.handle_configuration_request(json!([{ "enable": true }]));
+  // the client is asked to pull the diagnostics instead of being sent them
+  client.read_diagnostics_refresh();
+  client.assert_no_notification("textDocument/publishDiagnostics");
+
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+    }),
+  );
+  assert_eq!(res["kind"], "full");
+  let items = res["items"].as_array().unwrap();
+  assert_eq!(items.len(), 1);
+  assert_eq!(items[0]["code"], 2322);
+  let result_id = res["resultId"].as_str().unwrap().to_string();
+
+  // nothing changed since the previous result
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "previousResultId": result_id,
+    }),
+  );
+  assert_eq!(res, json!({ "kind": "unchanged", "resultId": result_id }));
+
+  // fix the code causing the diagnostic
+  client.write_notification(
+    "textDocument/didChange",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts",
+        "version": 2
+      },
+      "contentChanges": [
+        {
+          "range": {
+            "start": { "line": 0, "character": 18 },
+            "end": { "line": 0, "character": 19 }
+          },
+          "text": "\"a\""
+        }
+      ]
+    }),
+  );
+  client.read_diagnostics_refresh();
+  let res = client.write_request(
+    "textDocument/diagnostic",
+    json!({
+      "textDocument": { "uri": "file:///a/file.ts" },
+      "previousResultId": result_id,
+    }),
+  );
+  assert_eq!(res["kind"], "full");
+  assert_eq!(res["items"], json!([]));
+  assert_ne!(res["resultId"], json!(result_id));
+
+  client.shutdown();
+  assert_eq!(client.queue_len(), 0);
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PerformanceAverage {
@@ -6895,6 +7122,70 @@ fn lsp_code_actions_ignore_lint() {
   client.shutdown();
 }
 
+#[test]
+fn lsp_code_actions_organize_imports() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let mut client = context.new_lsp_command().build();
+  client.initialize_default();
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/file.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { b, a } from \"./b.ts\";\nimport { unused } from \"./c.ts\";\n\nconsole.log(a, b);\n"
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/file.ts"
+      },
+      "range": {
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 0, "character": 0 }
+      },
+      "context": {
+        "diagnostics": [],
+        "only": ["source.organizeImports"]
+      }
+    }),
+  );
+  let actions = res.as_array().unwrap();
+  assert_eq!(actions.len(), 1);
+  assert_eq!(actions[0]["title"], "Organize imports");
+  assert_eq!(actions[0]["kind"], "source.organizeImports");
+  assert!(actions[0]["edit"]["changes"]["file:///a/file.ts"].is_array());
+
+  // nothing is offered when the imports are organized
+  client.did_open(json!({
+    "textDocument": {
+      "uri": "file:///a/organized.ts",
+      "languageId": "typescript",
+      "version": 1,
+      "text": "import { a, b } from \"./b.ts\";\n\nconsole.log(a, b);\n"
+    }
+  }));
+  let res = client.write_request(
+    "textDocument/codeAction",
+    json!({
+      "textDocument": {
+        "uri": "file:///a/organized.ts"
+      },
+      "range": {
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 0, "character": 0 }
+      },
+      "context": {
+        "diagnostics": [],
+        "only": ["source.organizeImports"]
+      }
+    }),
+  );
+  assert_eq!(res, json!(null));
+  client.shutdown();
+}
+
 /// This test exercises updating an existing deno-lint-ignore-file comment.
 #[test]
 fn lsp_code_actions_update_ignore_lint() {
@@ -89,6 +89,8 @@ macro_rules! command_step(
 // the test (ex. `lint_tests.rs`) and which is the implementation (ex. `lint.rs`)
 // when both are open, especially for two tabs in VS Code
 
+#[path = "api_tests.rs"]
+mod api;
 #[path = "bench_tests.rs"]
 mod bench;
 #[path = "bundle_tests.rs"]
@@ -99,10 +101,14 @@ mod cache;
 mod cert;
 #[path = "check_tests.rs"]
 mod check;
+#[path = "clean_tests.rs"]
+mod clean;
 #[path = "compile_tests.rs"]
 mod compile;
 #[path = "coverage_tests.rs"]
 mod coverage;
+#[path = "cron_tests.rs"]
+mod cron;
 #[path = "doc_tests.rs"]
 mod doc;
 #[path = "eval_tests.rs"]
@@ -121,8 +127,12 @@ mod inspector;
 mod install;
 #[path = "js_unit_tests.rs"]
 mod js_unit_tests;
+#[path = "license_tests.rs"]
+mod license;
 #[path = "lint_tests.rs"]
 mod lint;
+#[path = "lock_tests.rs"]
+mod lock;
 #[path = "lsp_tests.rs"]
 mod lsp;
 #[path = "node_compat_tests.rs"]
@@ -131,10 +141,14 @@ mod node_compat_tests;
 mod node_unit_tests;
 #[path = "npm_tests.rs"]
 mod npm;
+#[path = "publish_tests.rs"]
+mod publish;
 #[path = "repl_tests.rs"]
 mod repl;
 #[path = "run_tests.rs"]
 mod run;
+#[path = "serve_tests.rs"]
+mod serve;
 #[path = "shared_library_tests.rs"]
 mod shared_library_tests;
 #[path = "task_tests.rs"]
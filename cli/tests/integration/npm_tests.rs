@@ -6,6 +6,7 @@ use pretty_assertions::assert_eq;
 use std::process::Stdio;
 use test_util as util;
 use util::assert_contains;
+use util::assert_not_contains;
 use util::env_vars_for_npm_tests;
 use util::env_vars_for_npm_tests_no_sync_download;
 use util::http_server;
@@ -713,6 +714,21 @@ itest!(deno_run_bin_cjs {
   http_server: true,
 });
 
+itest!(deno_x_bin_double_hyphen {
+  args: "x --quiet @denotest/bin/cli-esm -- this is a test",
+  output: "npm/deno_run_esm.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+});
+
+itest!(deno_x_bin_multiple_bins {
+  args: "x --quiet @denotest/bin this is a test",
+  output: "npm/deno_x_multiple_bins.out",
+  envs: env_vars_for_npm_tests(),
+  http_server: true,
+  exit_code: 1,
+});
+
 itest!(deno_run_non_existent {
   args: "run npm:mkdirp@0.5.125",
   output: "npm/deno_run_non_existent.out",
@@ -1797,3 +1813,443 @@ fn reload_info_not_found_cache_but_exists_remote() {
     output.assert_exit_code(0);
   }
 }
+
+#[test]
+fn add_and_remove_npm_dependency() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", "{}\n");
+
+  let output = context
+    .new_command()
+    .args("add npm:@denotest/esm-basic")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(
+    output.combined_output(),
+    "Add npm:@denotest/esm-basic@1.0.0"
+  );
+  assert_eq!(
+    temp_dir.read_to_string("deno.json"),
+    r#"{ "imports": { "@denotest/esm-basic": "npm:@denotest/esm-basic@^1.0.0" } }
+"#
+  );
+  assert_contains!(
+    temp_dir.read_to_string("deno.lock"),
+    "\"@denotest/esm-basic@1.0.0\""
+  );
+
+  // an unknown package leaves the config file untouched
+  let output = context
+    .new_command()
+    .args("add npm:@denotest/not-existing")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    temp_dir.read_to_string("deno.json"),
+    "\"@denotest/esm-basic\""
+  );
+
+  let output = context
+    .new_command()
+    .args("remove @denotest/esm-basic")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Remove @denotest/esm-basic");
+  assert_eq!(
+    temp_dir.read_to_string("deno.json"),
+    "{ \"imports\": {} }\n"
+  );
+}
+
+#[test]
+fn outdated_npm_dependencies() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "imports": {
+    "@denotest/esm-basic": "npm:@denotest/esm-basic@^1.0.0",
+    "@denotest/peer-dep-test-peer": "npm:@denotest/peer-dep-test-peer@^1.0.0"
+  }
+}
+"#,
+  );
+  context
+    .new_command()
+    .args("install")
+    .run()
+    .assert_exit_code(0)
+    .skip_output_check();
+
+  let output = context.new_command().args("outdated").run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "Dependency");
+  assert_contains!(output_text, "npm:@denotest/peer-dep-test-peer");
+  assert_not_contains!(output_text, "npm:@denotest/esm-basic");
+
+  let output = context
+    .new_command()
+    .args("outdated --json")
+    .split_output()
+    .run();
+  output.assert_exit_code(0);
+  output.assert_stdout_matches_text(
+    r#"[
+  {
+    "name": "npm:@denotest/peer-dep-test-peer",
+    "current": "1.0.0",
+    "wanted": "1.0.0",
+    "latest": "2.0.0"
+  }
+]
+"#,
+  );
+}
+
+#[test]
+fn npmrc_scope_registry_with_auth_token() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.js",
+    "import { getValue, setValue } from 'npm:@denotest/esm-basic';\nsetValue(5);\nconsole.log(getValue());\n",
+  );
+  temp_dir.write(
+    ".npmrc",
+    "@denotest:registry=http://localhost:4545/npm/private_registry/\n",
+  );
+
+  let output = context.new_command().args("run main.js").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Unauthorized (401) at http://localhost:4545/npm/private_registry/@denotest/esm-basic"
+  );
+
+  temp_dir.write(
+    ".npmrc",
+    r#"@denotest:registry=http://localhost:4545/npm/private_registry/
+//localhost:4545/npm/private_registry/:_authToken=${NPM_TOKEN}
+"#,
+  );
+  let output = context
+    .new_command()
+    .args("run main.js")
+    .env("NPM_TOKEN", "abcdef123456789")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("[WILDCARD]5\n");
+}
+
+#[test]
+fn allow_scripts_runs_install_scripts_once() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.js",
+    "import { value } from 'npm:@denotest/install-script';\nconsole.log(value);\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("run --node-modules-dir main.js")
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(
+    output_text,
+    "The install scripts of the following packages were not run: @denotest/install-script@1.0.0"
+  );
+  assert_contains!(output_text, "--allow-scripts=@denotest/install-script");
+  assert_not_contains!(output_text, "postinstall ran");
+
+  // the package folder already exists, but the scripts never ran for it
+  let output = context
+    .new_command()
+    .args("run --node-modules-dir --allow-scripts=npm:@denotest/install-script main.js")
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "preinstall ran");
+  assert_contains!(output_text, "postinstall ran");
+  assert_contains!(output_text, "5");
+  assert!(
+    output_text.find("preinstall ran") < output_text.find("postinstall ran")
+  );
+
+  let output = context
+    .new_command()
+    .args(
+      "run --node-modules-dir --allow-scripts=@denotest/install-script main.js",
+    )
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_not_contains!(output_text, "postinstall ran");
+  assert_not_contains!(output_text, "were not run");
+}
+
+#[test]
+fn pack_npm_package() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "imports": { "esm-basic": "npm:@denotest/esm-basic@^1.0.0" } }"#,
+  );
+  temp_dir.create_dir_all("src");
+  temp_dir.write(
+    "src/mod.ts",
+    "import { getValue, setValue } from 'esm-basic';\nexport { double } from './double.ts';\n\nexport function increment(): number {\n  setValue(getValue() + 1);\n  return getValue();\n}\n",
+  );
+  temp_dir.write(
+    "src/double.ts",
+    "export function double(value: number): number {\n  return value * 2;\n}\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("pack --name=my-lib --version=1.0.0 src/mod.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text(
+    "[WILDCARD]Check [WILDCARD]mod.ts\nEmit [WILDCARD]npm\nPack my-lib@1.0.0 to [WILDCARD]npm\n",
+  );
+
+  // the imports of the npm package and the local modules are rewritten for
+  // node
+  let mod_js = temp_dir.read_to_string("npm/mod.js");
+  assert_contains!(mod_js, "from \"esm-basic\"");
+  assert_contains!(mod_js, "from \"./double.js\"");
+  assert_not_contains!(mod_js, "sourceMappingURL");
+  let mod_declaration = temp_dir.read_to_string("npm/mod.d.ts");
+  assert_contains!(mod_declaration, "from \"./double.js\"");
+  assert_contains!(
+    mod_declaration,
+    "export declare function increment(): number;"
+  );
+  assert_contains!(
+    temp_dir.read_to_string("npm/double.d.ts"),
+    "export declare function double(value: number): number;"
+  );
+  assert_eq!(
+    temp_dir.read_to_string("npm/package.json"),
+    r#"{
+  "name": "my-lib",
+  "version": "1.0.0",
+  "type": "module",
+  "main": "./mod.js",
+  "types": "./mod.d.ts",
+  "exports": {
+    ".": {
+      "types": "./mod.d.ts",
+      "import": "./mod.js"
+    }
+  },
+  "dependencies": {
+    "@denotest/esm-basic": "^1.0.0"
+  }
+}
+"#
+  );
+}
+
+#[test]
+fn pack_remote_module_error() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "mod.ts",
+    "export { printHello } from 'http://localhost:4545/subdir/mod2.ts';\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("pack --name=my-lib --version=1.0.0 mod.ts")
+    .run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(concat!(
+    "[WILDCARD]error: Failed packing file:///[WILDCARD]mod.ts\n\n",
+    "Caused by:\n",
+    "    Remote module \"http://localhost:4545/subdir/mod2.ts\" can't be packed.\n",
+    "[WILDCARD]",
+  ));
+}
+
+#[test]
+fn type_acquisition() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    concat!(
+      "import { add } from 'npm:@denotest/untyped@1';\n",
+      "const value: string = add(1, 2);\n",
+      "console.log(value);\n",
+    ),
+  );
+  temp_dir.write("deno.json", "{}\n");
+
+  // the types come from the @types package with the same major version
+  let output = context.new_command().args("check main.ts").run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(concat!(
+    "[WILDCARD]error: TS2322 [ERROR]: Type 'number' is not assignable to type 'string'.\n",
+    "[WILDCARD]",
+  ));
+  let lockfile = temp_dir.read_to_string("deno.lock");
+  assert_contains!(lockfile, "\"@types/denotest__untyped@1\"");
+  assert_contains!(lockfile, "\"@types/denotest__untyped@1.0.0\"");
+
+  temp_dir.write("deno.json", r#"{ "typeAcquisition": false }"#);
+  let output = context.new_command().args("check main.ts").run();
+  output.assert_exit_code(0);
+}
+
+#[test]
+fn allow_scripts_runs_dependency_scripts_first_with_node_shim() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.js",
+    "import { built } from 'npm:@denotest/install-script-node';\nconsole.log(built);\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("run --node-modules-dir --allow-read --allow-scripts=@denotest/install-script,@denotest/install-script-node main.js")
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "postinstall ran");
+  assert_contains!(output_text, "node install script ran");
+  assert!(
+    output_text.find("postinstall ran")
+      < output_text.find("node install script ran")
+  );
+  assert!(output_text.ends_with("built\n"));
+}
+
+#[test]
+fn pack_commonjs_module_error() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("mod.ts", "import './legacy.cjs';\n");
+  temp_dir.write("legacy.cjs", "module.exports = {};\n");
+
+  let output = context
+    .new_command()
+    .args("pack --name=my-lib --version=1.0.0 mod.ts")
+    .run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(concat!(
+    "[WILDCARD]error: CommonJS module \"file:///[WILDCARD]legacy.cjs\" can't be packed.\n",
+    "  The package only contains ES modules.\n",
+  ));
+}
+
+/// Installs, caches and runs an npm package from a project in the directory,
+/// with the deno dir in it as well.
+#[cfg(windows)]
+fn assert_npm_package_in_dir(
+  context: &util::TestContext,
+  dir: &std::path::Path,
+) {
+  std::fs::create_dir_all(dir).unwrap();
+  std::fs::write(
+    dir.join("deno.json"),
+    r#"{ "imports": { "@denotest/esm-basic": "npm:@denotest/esm-basic@^1.0.0" } }"#,
+  )
+  .unwrap();
+  std::fs::write(
+    dir.join("main.ts"),
+    "import { getValue, setValue } from \"@denotest/esm-basic\";\nsetValue(5);\nconsole.log(getValue());\n",
+  )
+  .unwrap();
+  let deno_dir = dir.join("deno_dir");
+  let config = dir.join("deno.json").to_string_lossy().to_string();
+  let main = dir.join("main.ts").to_string_lossy().to_string();
+
+  let output = context
+    .new_command()
+    .env("DENO_DIR", deno_dir.to_string_lossy())
+    .args_vec(["install", "--node-modules-dir", "--config", config.as_str()])
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  assert!(dir
+    .join("node_modules")
+    .join("@denotest")
+    .join("esm-basic")
+    .join("package.json")
+    .exists());
+
+  // the global npm cache of the deno dir
+  let output = context
+    .new_command()
+    .env("DENO_DIR", deno_dir.to_string_lossy())
+    .args_vec(["cache", "--config", config.as_str(), main.as_str()])
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let output = context
+    .new_command()
+    .env("DENO_DIR", deno_dir.to_string_lossy())
+    .args_vec(["run", "--quiet", "--config", config.as_str(), main.as_str()])
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("5\n");
+
+  // the local node_modules directory
+  let output = context
+    .new_command()
+    .env("DENO_DIR", deno_dir.to_string_lossy())
+    .args_vec([
+      "run",
+      "--quiet",
+      "--node-modules-dir",
+      "--config",
+      config.as_str(),
+      main.as_str(),
+    ])
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("5\n");
+}
+
+#[cfg(windows)]
+#[test]
+fn npm_package_in_path_longer_than_max_path() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  let dir = context
+    .temp_dir()
+    .path()
+    .join("a".repeat(80))
+    .join("b".repeat(80))
+    .join("c".repeat(80));
+  // MAX_PATH, which the paths of the packages exceed without the
+  // extended-length prefix
+  assert!(dir.as_os_str().len() > 260);
+  assert_npm_package_in_dir(&context, &dir);
+}
+
+#[cfg(windows)]
+#[test]
+fn npm_package_in_unc_share() {
+  let context = TestContextBuilder::for_npm().use_temp_cwd().build();
+  // the temp directory on the administrative share of its drive, like
+  // \\?\UNC\localhost\C$\Users\...
+  let temp_path = context.temp_dir().path().to_string_lossy().to_string();
+  let temp_path = temp_path.strip_prefix(r"\\?\").unwrap_or(&temp_path);
+  let (drive, rest) = temp_path.split_once(r":\").unwrap();
+  let dir = std::path::PathBuf::from(format!(
+    r"\\?\UNC\localhost\{drive}$\{rest}\project"
+  ));
+  assert_npm_package_in_dir(&context, &dir);
+}
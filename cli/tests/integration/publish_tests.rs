@@ -0,0 +1,62 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use test_util::TestContextBuilder;
+
+#[test]
+fn publish_dry_run() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "name": "@scope/lib", "version": "1.0.0" }"#,
+  );
+  temp_dir.write("mod.ts", "export * from './util.ts';\n");
+  temp_dir.write("util.ts", "export const value = 1;\n");
+
+  let output = context
+    .new_command()
+    .args("publish --registry=https://registry.example.com/ --dry-run mod.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text(
+    r#"[WILDCARD]{
+  "modules": [
+    {
+      "specifier": "./mod.ts",
+      "integrity": "sha256-[WILDCARD]"
+    },
+    {
+      "specifier": "./util.ts",
+      "integrity": "sha256-[WILDCARD]"
+    }
+  ],
+  "provenance": {
+[WILDCARD]
+  },
+  "name": "@scope/lib",
+  "version": "1.0.0",
+  "entrypoint": "./mod.ts",
+  "eszip": {
+    "integrity": "sha256-[WILDCARD]"
+  }
+}
+"#,
+  );
+}
+
+#[test]
+fn publish_without_package_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", r#"{ "name": "@scope/lib" }"#);
+  temp_dir.write("mod.ts", "export const value = 1;\n");
+
+  let output = context
+    .new_command()
+    .args("publish --registry=https://registry.example.com/ --dry-run mod.ts")
+    .run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(
+    "error: The \"name\" and \"version\" configuration must be set together.\n",
+  );
+}
@@ -971,3 +971,30 @@ fn closed_file_pre_load_does_not_occur() {
       );
     });
 }
+
+#[test]
+fn dot_load_and_save_commands() {
+  let context = TestContextBuilder::default().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "add.ts",
+    "function add(a: number, b: number): number { return a + b; }",
+  );
+  context
+    .new_command()
+    .args_vec(["repl", "-A"])
+    .with_pty(|mut console| {
+      console.write_line(".load ./add.ts");
+      console.expect("undefined");
+      console.write_line("add(1, 2)");
+      console.expect("3");
+      console.write_line(".save ./session.ts");
+      console.expect("Session saved to:");
+      console.write_line(".load");
+      console.expect("Missing file argument for .load");
+    });
+  assert_eq!(
+    temp_dir.read_to_string("session.ts"),
+    ".load ./add.ts\nadd(1, 2)\n",
+  );
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::serde_json;
 use deno_core::url;
 use deno_runtime::deno_fetch::reqwest;
 use std::io::Read;
@@ -894,6 +895,61 @@ itest!(lock_v2_check_err2 {
   http_server: true,
 });
 
+#[test]
+fn lock_frozen() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"import { printHello } from "http://localhost:4545/subdir/print_hello.ts";
+printHello();
+"#,
+  );
+
+  // nothing is cached yet
+  context
+    .new_command()
+    .args("run --frozen --lock=deno.lock main.ts")
+    .run()
+    .assert_matches_text(
+      "error: Specifier not found in cache: \"http://localhost:4545/subdir/print_hello.ts\", --frozen is specified.\n[WILDCARD]",
+    )
+    .assert_exit_code(1);
+  assert!(!temp_dir.path().join("deno.lock").exists());
+
+  context
+    .new_command()
+    .args("cache --lock=deno.lock main.ts")
+    .run()
+    .skip_output_check();
+  assert!(temp_dir.path().join("deno.lock").exists());
+
+  context
+    .new_command()
+    .args("run --frozen --lock=deno.lock main.ts")
+    .run()
+    .assert_matches_text("Hello\n")
+    .assert_exit_code(0);
+
+  // the lockfile is missing the remote module
+  temp_dir.write("deno.lock", r#"{ "version": "2", "remote": {} }"#);
+  context
+    .new_command()
+    .args("run --frozen --lock=deno.lock main.ts")
+    .run()
+    .assert_matches_text(
+      "error: The lockfile is out of date and --frozen is specified: \"[WILDCARD]deno.lock\". Run without --frozen to update it.\n",
+    )
+    .assert_exit_code(1);
+  assert_eq!(
+    temp_dir.read_to_string("deno.lock"),
+    r#"{ "version": "2", "remote": {} }"#
+  );
+}
+
 itest!(lock_only_http_and_https {
   args: "run --lock=run/lock_only_http_and_https/deno.lock run/lock_only_http_and_https/main.ts",
   output: "run/lock_only_http_and_https/main.out",
@@ -1256,6 +1312,22 @@ itest!(seed_random {
   output: "run/seed_random.js.out",
 });
 
+itest!(disallow_code_generation_from_strings {
+  args: "run --disallow-code-generation-from-strings run/disallow_code_generation_from_strings.js",
+  output: "run/disallow_code_generation_from_strings.js.out",
+  exit_code: 1,
+});
+
+itest!(unstable_granular_flags {
+  args: "run --quiet --unstable-kv --unstable-broadcast-channel run/unstable_granular_flags.js",
+  output: "run/unstable_granular_flags.js.out",
+});
+
+itest!(unstable_granular_flags_all {
+  args: "run --quiet --unstable run/unstable_granular_flags.js",
+  output: "run/unstable_granular_flags_all.js.out",
+});
+
 itest!(type_definitions {
   args: "run --reload run/type_definitions.ts",
   output: "run/type_definitions.ts.out",
@@ -1721,12 +1793,29 @@ itest!(import_dynamic_data_url {
   output: "run/import_dynamic_data_url.ts.out",
 });
 
+itest!(dynamic_import_retry_policy {
+  args: "run --quiet --reload --unstable --allow-read run/dynamic_import_retry_policy.ts",
+  output: "run/dynamic_import_retry_policy.ts.out",
+});
+
+itest!(dynamic_import_retry_policy_flaky {
+  args: "run --quiet --reload --unstable --allow-net=localhost:4545 run/dynamic_import_retry_policy_flaky.ts",
+  output: "run/dynamic_import_retry_policy_flaky.ts.out",
+  http_server: true,
+});
+
 itest!(import_blob_url_error_stack {
   args: "run --quiet --reload run/import_blob_url_error_stack.ts",
   output: "run/import_blob_url_error_stack.ts.out",
   exit_code: 1,
 });
 
+itest!(import_blob_url_invalid_source_map {
+  args: "run --quiet --reload run/import_blob_url_invalid_source_map.ts",
+  output: "run/import_blob_url_invalid_source_map.ts.out",
+  exit_code: 1,
+});
+
 itest!(import_blob_url_import_relative {
   args: "run --quiet --reload run/import_blob_url_import_relative.ts",
   output: "run/import_blob_url_import_relative.ts.out",
@@ -4369,3 +4458,727 @@ itest!(extension_dynamic_import {
   output: "run/extension_dynamic_import.ts.out",
   exit_code: 1,
 });
+
+#[test]
+fn restrict_local_imports_outside_project_root() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.create_dir_all("project");
+  temp_dir.write("project/deno.json", "{}");
+  temp_dir.write(
+    "project/main.ts",
+    "import { value } from '../outside.ts';\nconsole.log(value);\n",
+  );
+  temp_dir.write("outside.ts", "export const value = 'outside';\n");
+
+  let output = context
+    .new_command()
+    .args(
+      "run --restrict-local-imports --config project/deno.json project/main.ts",
+    )
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "escapes the project root");
+
+  let output = context
+    .new_command()
+    .args("run --restrict-local-imports --allow-read --config project/deno.json project/main.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "outside");
+}
+
+#[cfg(unix)]
+#[test]
+fn restrict_local_imports_symlink_outside_project_root() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.create_dir_all("project");
+  temp_dir.write("project/deno.json", "{}");
+  temp_dir.write(
+    "project/main.ts",
+    "import { value } from './link.ts';\nconsole.log(value);\n",
+  );
+  temp_dir.write("outside.ts", "export const value = 'outside';\n");
+  std::os::unix::fs::symlink(
+    temp_dir.path().join("outside.ts"),
+    temp_dir.path().join("project/link.ts"),
+  )
+  .unwrap();
+
+  // the symlink is in the project root, but the module it points to isn't
+  let output = context
+    .new_command()
+    .args(
+      "run --restrict-local-imports --config project/deno.json project/main.ts",
+    )
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "escapes the project root");
+
+  let output = context
+    .new_command()
+    .args("run --restrict-local-imports --allow-read --config project/deno.json project/main.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "outside");
+}
+
+#[test]
+fn net_audit_log_with_pinned_dns() {
+  let context = TestContextBuilder::new()
+    .use_http_server()
+    .use_temp_cwd()
+    .build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    r#"import { printHello } from "http://localhost:4545/subdir/print_hello.ts";
+printHello();
+const res = await fetch("http://localhost:4545/subdir/mod1.ts");
+await res.body?.cancel();
+try {
+  await fetch("http://127.0.0.1:4546/");
+} catch (err) {
+  console.log(err.name);
+}
+const conn = await Deno.connect({ hostname: "localhost", port: 4545 });
+console.log("connected", conn.remoteAddr.hostname);
+conn.close();
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --allow-net=localhost:4545 --pin-dns --net-audit-log=net.ndjson main.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Hello");
+  assert_contains!(output.combined_output(), "PermissionDenied");
+  // Deno.connect() connects to a pinned address as well
+  assert_contains!(output.combined_output(), "connected 127.0.0.1");
+
+  let entries = temp_dir
+    .read_to_string("net.ndjson")
+    .lines()
+    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+    .collect::<Vec<_>>();
+  let import = entries
+    .iter()
+    .find(|entry| entry["api"] == "import")
+    .unwrap();
+  assert_eq!(import["url"], "http://localhost:4545/subdir/print_hello.ts");
+  assert_eq!(import["allowed"], true);
+  let fetches = entries
+    .iter()
+    .filter(|entry| entry["api"] == "fetch()")
+    .collect::<Vec<_>>();
+  assert_eq!(fetches.len(), 2);
+  assert_eq!(fetches[0]["host"], "localhost");
+  assert_eq!(fetches[0]["port"], 4545);
+  assert_eq!(fetches[0]["allowed"], true);
+  assert_eq!(fetches[1]["host"], "127.0.0.1");
+  assert_eq!(fetches[1]["allowed"], false);
+
+  // the hosts to pin must be listed
+  let output = context
+    .new_command()
+    .args("run --allow-net --pin-dns main.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "--pin-dns requires a list of hosts in --allow-net"
+  );
+}
+
+#[test]
+fn trace_permissions() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("data.txt", "data");
+  temp_dir.write(
+    "main.ts",
+    r#"interface Options {
+  path: string;
+}
+
+const options: Options = { path: "data.txt" };
+console.log(Deno.readTextFileSync(options.path));
+
+function readEnv(): string | undefined {
+  try {
+    return Deno.env.get("TRACE_VAR");
+  } catch (err) {
+    return err.name;
+  }
+}
+console.log(readEnv());
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --allow-read --trace-permissions=trace.ndjson main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("data\nPermissionDenied\n");
+
+  let entries = temp_dir
+    .read_to_string("trace.ndjson")
+    .lines()
+    .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+    .collect::<Vec<_>>();
+  let read = entries
+    .iter()
+    .find(|entry| entry["api"] == "Deno.readFileSync()")
+    .unwrap();
+  assert_eq!(read["permission"], "read");
+  assert_eq!(read["value"], "data.txt");
+  assert_eq!(read["flag"], "--allow-read=data.txt");
+  assert_eq!(read["granted"], true);
+  // the stack is mapped to the lines of the typescript source
+  assert_contains!(read["stack"][0].as_str().unwrap(), "main.ts:6:");
+
+  let env = entries
+    .iter()
+    .find(|entry| entry["permission"] == "env")
+    .unwrap();
+  assert_eq!(env["value"], "TRACE_VAR");
+  assert_eq!(env["flag"], "--allow-env=TRACE_VAR");
+  assert_eq!(env["granted"], false);
+  let frame = env["stack"][0].as_str().unwrap();
+  assert!(frame.starts_with("readEnv ("), "{frame}");
+  assert_contains!(frame, "main.ts:10:");
+}
+
+#[test]
+fn suggest_permissions() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", "{}");
+  temp_dir.write("data.txt", "data");
+  temp_dir.write(
+    "main.ts",
+    "console.log(Deno.readTextFileSync('data.txt'));\nconsole.log(Deno.env.get('SUGGEST_VAR'));\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("run -A --suggest-permissions=dev main.ts")
+    .env("SUGGEST_VAR", "value")
+    .run();
+  output.assert_exit_code(0);
+  let combined_output = output.combined_output();
+  assert_contains!(combined_output, "data\nvalue\n");
+  assert_contains!(combined_output, "--allow-read=data.txt\n");
+  assert_contains!(combined_output, "--allow-env=SUGGEST_VAR\n");
+  assert_contains!(combined_output, "--permission-set=dev");
+
+  let config = serde_json::from_str::<serde_json::Value>(
+    &temp_dir.read_to_string("deno.json"),
+  )
+  .unwrap();
+  assert_eq!(
+    config["permissions"]["dev"],
+    serde_json::json!({
+      "read": ["./data.txt"],
+      "env": ["SUGGEST_VAR"],
+    })
+  );
+
+  // the saved permission set grants the permissions
+  let output = context
+    .new_command()
+    .args("run --permission-set=dev main.ts")
+    .env("SUGGEST_VAR", "value")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("data\nvalue\n");
+}
+
+#[test]
+fn run_with_permission_set_deny_rules() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "profiles": {
+    "web-server": { "permissions": "web-server" },
+    "prod": {}
+  },
+  "permissions": {
+    "web-server": {
+      "read": true,
+      "env": ["APP_PORT"],
+      "deny": { "read": ["./secrets"] }
+    }
+  }
+}"#,
+  );
+  temp_dir.create_dir_all("secrets");
+  temp_dir.write("secrets/key.txt", "secret");
+  temp_dir.write("public.txt", "public");
+  temp_dir.write(
+    "main.ts",
+    r#"function read(path: string): string {
+  try {
+    return Deno.readTextFileSync(path);
+  } catch (err) {
+    return err.name;
+  }
+}
+console.log(read("public.txt"));
+console.log(read("secrets/key.txt"));
+console.log(Deno.env.get("APP_PORT"));
+const status = await Deno.permissions.query({
+  name: "read",
+  path: "secrets/key.txt",
+});
+console.log(status.state);
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --permission-set=web-server main.ts")
+    .env("APP_PORT", "8000")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("public\nPermissionDenied\n8000\ndenied\n");
+
+  // the build profile grants the permission set it names
+  let output = context
+    .new_command()
+    .args("run --profile=web-server main.ts")
+    .env("APP_PORT", "8000")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("public\nPermissionDenied\n8000\ndenied\n");
+
+  // a build profile without permissions grants none
+  let output = context
+    .new_command()
+    .args("run --profile=prod main.ts")
+    .run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(
+    "PermissionDenied\nPermissionDenied\nerror: Uncaught PermissionDenied: Requires env access to \"APP_PORT\"[WILDCARD]",
+  );
+}
+
+#[test]
+fn run_with_profile() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{
+  "profiles": {
+    "dev": { "define": { "DEBUG": "true" } },
+    "prod": { "define": { "DEBUG": "false" }, "minify": true }
+  }
+}"#,
+  );
+  temp_dir.write(
+    "main.ts",
+    "declare const DEBUG: boolean;\nconsole.log(DEBUG ? 'debug' : 'release');\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("run --profile=dev main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("debug\n");
+
+  let output = context
+    .new_command()
+    .args("run --profile=prod main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("release\n");
+
+  let output = context
+    .new_command()
+    .args("run --profile=staging main.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Available profiles: dev, prod");
+}
+
+#[test]
+fn run_refresh_module() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("value.ts", "export const value = 'one';\n");
+  temp_dir.write(
+    "plugin.ts",
+    "import { value } from './value.ts';\nself.postMessage(value);\n",
+  );
+  temp_dir.write(
+    "main.ts",
+    r#"function runPlugin(): Promise<string> {
+  const worker = new Worker(new URL("./plugin.ts", import.meta.url), {
+    type: "module",
+  });
+  return new Promise((resolve) => {
+    worker.onmessage = (e) => {
+      worker.terminate();
+      resolve(e.data);
+    };
+  });
+}
+
+console.log(await runPlugin());
+Deno.writeTextFileSync("value.ts", "export const value = 'two';\n");
+console.log(await runPlugin());
+const removed = await Deno.refreshModule(new URL("./value.ts", import.meta.url));
+console.log(removed.map((s) => s.slice(s.lastIndexOf("/") + 1)));
+console.log(await runPlugin());
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --unstable --quiet --allow-read --allow-write main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output
+    .assert_matches_text("one\none\n[ \"plugin.ts\", \"value.ts\" ]\ntwo\n");
+}
+
+#[test]
+fn run_eszip_prebundle_cached() {
+  let context = TestContextBuilder::new().use_http_server().build();
+  let module_url = "http://localhost:4545/run/eszip_prebundle/mod.ts";
+  let prebundle_download =
+    "Download http://localhost:4545/run/eszip_prebundle/prebundle.eszip";
+
+  // the pre-bundle can't be parsed, so the modules are fetched one by one
+  let output = context.new_command().args_vec(["run", module_url]).run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, prebundle_download);
+  assert_contains!(output_text, "dep\n");
+
+  // and it's not fetched again once they're cached
+  let output = context.new_command().args_vec(["run", module_url]).run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert!(!output_text.contains("Download"), "{output_text}");
+  assert_eq!(output_text, "dep\n");
+}
+
+#[test]
+fn run_prepare_worker_module() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("worker.ts", "self.postMessage('ready');\n");
+  temp_dir.write("bad_worker.ts", "const value: number = 'text';\n");
+  temp_dir.write(
+    "main.ts",
+    r#"const url = new URL("./worker.ts", import.meta.url);
+await Deno.preloadModule(url);
+const worker = new Worker(url, { type: "module" });
+worker.onmessage = (e) => {
+  console.log(e.data);
+  worker.terminate();
+};
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --unstable --quiet --check main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("ready\n");
+
+  // the prepared modules are type checked before the program runs
+  let output = context
+    .new_command()
+    .args(
+      "run --unstable --quiet --check --prepare-worker=bad_worker.ts main.ts",
+    )
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "TS2322");
+}
+
+#[test]
+fn run_preload_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "imports": { "setup/": "./setup/" }, "preload": ["./polyfill.ts", "setup/env.ts"] }"#,
+  );
+  temp_dir.write(
+    "polyfill.ts",
+    "console.log('polyfill');\n(globalThis as any).greeting = 'hello';\n",
+  );
+  temp_dir.create_dir_all("setup");
+  temp_dir.write("setup/env.ts", "console.log('env');\n");
+  temp_dir.write(
+    "worker.ts",
+    "self.postMessage((globalThis as any).greeting);\n",
+  );
+  temp_dir.write(
+    "main.ts",
+    r#"console.log((globalThis as any).greeting);
+const worker = new Worker(new URL("./worker.ts", import.meta.url), {
+  type: "module",
+});
+worker.onmessage = (e) => {
+  console.log(e.data);
+  worker.terminate();
+};
+"#,
+  );
+
+  // the preloaded modules are evaluated in order before the main module
+  // and the entrypoints of workers
+  let output = context.new_command().args("run --quiet main.ts").run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("polyfill\nenv\nhello\npolyfill\nenv\nhello\n");
+
+  temp_dir.write(
+    "main_test.ts",
+    "Deno.test('greeting', () => {\n  if ((globalThis as any).greeting !== 'hello') throw new Error();\n});\n",
+  );
+  let output = context
+    .new_command()
+    .args("test --quiet main_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "ok | 1 passed");
+}
+
+#[test]
+fn run_import_flag() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "imports": { "agent": "./agent.ts" }, "preload": ["./polyfill.ts"] }"#,
+  );
+  temp_dir.write("polyfill.ts", "console.log('polyfill');\n");
+  temp_dir.write("agent.ts", "console.log('agent');\n");
+  temp_dir.write("env.ts", "console.log('env');\n");
+  temp_dir.write("main.ts", "console.log('main');\n");
+
+  // the flags are resolved with the import map and evaluated in order after
+  // the modules of the configuration
+  let output = context
+    .new_command()
+    .args("run --quiet --import agent --import=./env.ts main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("polyfill\nagent\nenv\nmain\n");
+
+  // --preload is the same as --import
+  let output = context
+    .new_command()
+    .args("run --quiet --preload agent --import=./env.ts main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("polyfill\nagent\nenv\nmain\n");
+
+  temp_dir.write("main_bench.ts", "Deno.bench('noop', () => {});\n");
+  let output = context
+    .new_command()
+    .args("bench --quiet --import=./env.ts main_bench.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "env\n");
+
+  let output = context
+    .new_command()
+    .args("run --quiet --import=./missing.ts main.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "missing.ts");
+}
+
+#[test]
+fn run_worker_permissions_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "deno.json",
+    r#"{ "workerPermissions": { "./plugins/": "none" } }"#,
+  );
+  temp_dir.write("data.txt", "data");
+  temp_dir.create_dir_all("plugins");
+  let worker_source = r#"try {
+  self.postMessage(Deno.readTextFileSync("data.txt"));
+} catch (err) {
+  self.postMessage(err.name);
+}
+"#;
+  temp_dir.write("plugins/plugin.ts", worker_source);
+  temp_dir.write("trusted.ts", worker_source);
+  temp_dir.write(
+    "main.ts",
+    r#"function run(path: string): Promise<string> {
+  const worker = new Worker(new URL(path, import.meta.url), {
+    type: "module",
+  });
+  return new Promise((resolve) => {
+    worker.onmessage = (e) => {
+      worker.terminate();
+      resolve(e.data);
+    };
+  });
+}
+
+console.log(await run("./trusted.ts"));
+console.log(await run("./plugins/plugin.ts"));
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("run --quiet --allow-read main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("data\nPermissionDenied\n");
+}
+
+#[test]
+fn run_vendor_only_config() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("deno.json", r#"{ "vendor": "only" }"#);
+  temp_dir.write(
+    "main.ts",
+    "import { value } from \"http://localhost:4545/mod.ts\";\nconsole.log(value);\n",
+  );
+
+  let output = context.new_command().args("run --quiet main.ts").run();
+  output.assert_exit_code(1);
+  output.assert_matches_text("[WILDCARD]\"vendor\" to \"only\", but no vendor directory was found next to it. Run `deno vendor` first.\n");
+
+  temp_dir.create_dir_all("vendor/localhost_4545");
+  temp_dir.write(
+    "vendor/import_map.json",
+    r#"{ "imports": { "http://localhost:4545/": "./localhost_4545/" } }"#,
+  );
+  temp_dir.write("vendor/localhost_4545/mod.ts", "export const value = 5;\n");
+  let output = context.new_command().args("run --quiet main.ts").run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("5\n");
+
+  temp_dir.write(
+    "main.ts",
+    "import { value } from \"http://localhost:4546/mod.ts\";\nconsole.log(value);\n",
+  );
+  let output = context.new_command().args("run --quiet main.ts").run();
+  output.assert_exit_code(1);
+  output.assert_matches_text("[WILDCARD]Module not found in the vendor directory: \"http://localhost:4546/mod.ts\"[WILDCARD]");
+}
+
+#[test]
+fn run_env_file() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(".env", "GREETING=Hello\nNAME=env\n");
+  temp_dir.write(
+    ".env.local",
+    "NAME=local\nMESSAGE=\"${GREETING} ${NAME}\"\n",
+  );
+  temp_dir.write(
+    "main.ts",
+    "console.log(Deno.env.get(\"MESSAGE\") ?? Deno.env.get(\"GREETING\"));\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("run --quiet --allow-env --env-file main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("Hello\n");
+
+  let output = context
+    .new_command()
+    .args("run --quiet --allow-env --env-file --env-file=.env.local main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("Hello local\n");
+
+  let output = context
+    .new_command()
+    .args("run --quiet --allow-env --env-file=missing.env main.ts")
+    .run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(
+    "error: Failed loading env file missing.env[WILDCARD]",
+  );
+}
+
+#[test]
+fn run_cpu_prof_and_heap_snapshot() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "main.ts",
+    "function fib(n: number): number {\n  return n < 2 ? n : fib(n - 1) + fib(n - 2);\n}\nconsole.log(fib(30));\n",
+  );
+
+  let output = context
+    .new_command()
+    .args("run --cpu-prof --heap-snapshot-on-exit --prof-dir=profiles main.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text(
+    "832040\nCPU profile written to [WILDCARD]\nHeap snapshot written to [WILDCARD]\n",
+  );
+
+  let entries = std::fs::read_dir(temp_dir.path().join("profiles"))
+    .unwrap()
+    .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+    .collect::<Vec<_>>();
+  let cpu_profile = entries
+    .iter()
+    .find(|name| name.ends_with(".cpuprofile"))
+    .unwrap();
+  assert!(entries.iter().any(|name| name.ends_with(".heapsnapshot")));
+  let profile: serde_json::Value = serde_json::from_str(
+    &temp_dir.read_to_string(format!("profiles/{cpu_profile}")),
+  )
+  .unwrap();
+  // the call frames are mapped to the TypeScript source
+  let fib = profile["nodes"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .find(|node| node["callFrame"]["functionName"] == "fib")
+    .expect("the profile has no frame of fib");
+  assert_eq!(fib["callFrame"]["lineNumber"], 0);
+}
+
+#[test]
+fn run_cpu_prof_on_error() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("main.ts", "throw new Error(\"boom\");\n");
+
+  let output = context
+    .new_command()
+    .args("run --cpu-prof --prof-dir=profiles main.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "CPU profile written to");
+  let has_cpu_profile = std::fs::read_dir(temp_dir.path().join("profiles"))
+    .unwrap()
+    .any(|entry| {
+      entry
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .ends_with(".cpuprofile")
+    });
+  assert!(has_cpu_profile);
+}
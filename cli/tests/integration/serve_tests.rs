@@ -0,0 +1,154 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::io::BufRead;
+use std::io::BufReader;
+
+use deno_runtime::deno_fetch::reqwest;
+use test_util as util;
+use test_util::assert_contains;
+use test_util::TempDir;
+use test_util::TestContextBuilder;
+
+#[tokio::test]
+async fn serve_fetch_handler() {
+  let t = TempDir::new();
+  t.write(
+    "server.ts",
+    r#"export default {
+  fetch(request: Request) {
+    return new Response(`Hello from ${new URL(request.url).pathname}`);
+  },
+};
+"#,
+  );
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("serve")
+    .arg("--allow-net")
+    .arg("--host=127.0.0.1")
+    .arg("--port=4621")
+    .arg("server.ts")
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let mut stdout = BufReader::new(child.stdout.take().unwrap());
+  let mut line = String::new();
+  stdout.read_line(&mut line).unwrap();
+  assert_eq!(line.trim(), "Listening on http://127.0.0.1:4621/");
+
+  let res = reqwest::get("http://127.0.0.1:4621/hello").await.unwrap();
+  assert_eq!(res.status(), 200);
+  assert_eq!(res.text().await.unwrap(), "Hello from /hello");
+
+  child.kill().unwrap();
+  child.wait().unwrap();
+}
+
+#[test]
+fn serve_requires_fetch_handler() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  context
+    .temp_dir()
+    .write("server.ts", "export const handler = () => {};\n");
+
+  let output = context
+    .new_command()
+    .env("NO_COLOR", "1")
+    .args("serve --allow-net --port=4622 server.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "The default export of the module must be an object with a fetch method."
+  );
+}
+
+#[tokio::test]
+async fn serve_evaluates_imports() {
+  let t = TempDir::new();
+  t.write("setup.ts", "globalThis.greeting = \"Hello\";\n");
+  t.write(
+    "server.ts",
+    r#"export default {
+  fetch() {
+    return new Response(`${globalThis.greeting} from the server`);
+  },
+};
+"#,
+  );
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("serve")
+    .arg("--allow-net")
+    .arg("--host=127.0.0.1")
+    .arg("--port=4623")
+    .arg("--import=./setup.ts")
+    .arg("server.ts")
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let mut stdout = BufReader::new(child.stdout.take().unwrap());
+  let mut line = String::new();
+  stdout.read_line(&mut line).unwrap();
+  assert_eq!(line.trim(), "Listening on http://127.0.0.1:4623/");
+
+  let res = reqwest::get("http://127.0.0.1:4623/").await.unwrap();
+  assert_eq!(res.text().await.unwrap(), "Hello from the server");
+
+  child.kill().unwrap();
+  child.wait().unwrap();
+}
+
+#[tokio::test]
+async fn serve_watch_drains_requests() {
+  let t = TempDir::new();
+  let server_source = |text: &str| {
+    format!(
+      r#"export default {{
+  async fetch() {{
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    return new Response("{text}");
+  }},
+}};
+"#
+    )
+  };
+  t.write("server.ts", server_source("before"));
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("serve")
+    .arg("--watch")
+    .arg("--allow-net")
+    .arg("--host=127.0.0.1")
+    .arg("--port=4624")
+    .arg("server.ts")
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::null())
+    .spawn()
+    .unwrap();
+  let mut stdout = BufReader::new(child.stdout.take().unwrap());
+  let mut line = String::new();
+  stdout.read_line(&mut line).unwrap();
+  assert_eq!(line.trim(), "Listening on http://127.0.0.1:4624/");
+
+  // the request is handled by the server that was running when it was made,
+  // even though the server restarts before it completes
+  let request = tokio::spawn(reqwest::get("http://127.0.0.1:4624/"));
+  tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+  t.write("server.ts", server_source("after"));
+  let res = request.await.unwrap().unwrap();
+  assert_eq!(res.status(), 200);
+  assert_eq!(res.text().await.unwrap(), "before");
+
+  line.clear();
+  stdout.read_line(&mut line).unwrap();
+  assert_eq!(line.trim(), "Listening on http://127.0.0.1:4624/");
+  let res = reqwest::get("http://127.0.0.1:4624/").await.unwrap();
+  assert_eq!(res.text().await.unwrap(), "after");
+
+  child.kill().unwrap();
+  child.wait().unwrap();
+}
@@ -3,8 +3,11 @@
 // Most of the tests for this are in deno_task_shell.
 // These tests are intended to only test integration.
 
+use test_util::copy_dir_recursive;
 use test_util::env_vars_for_npm_tests;
+use test_util::testdata_path;
 use test_util::TestContext;
+use test_util::TestContextBuilder;
 
 itest!(task_no_args {
   args: "task -q --config task/deno_json/deno.json",
@@ -44,6 +47,80 @@ itest!(task_cwd_resolves_config_from_specified_dir {
   exit_code: 1,
 });
 
+itest!(task_recursive {
+  args: "task -q --config task/workspace/deno.json --recursive build",
+  output: "task/workspace/task_recursive.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+  exit_code: 0,
+});
+
+itest!(task_recursive_filter {
+  args: "task -q --config task/workspace/deno.json -r --filter app build",
+  output: "task/workspace/task_recursive_filter.out",
+  envs: vec![("NO_COLOR".to_string(), "1".to_string())],
+  exit_code: 0,
+});
+
+#[test]
+fn task_recursive_changed() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  copy_dir_recursive(&testdata_path().join("task/workspace"), temp_dir.path())
+    .unwrap();
+  for args in [
+    vec!["init", "--quiet"],
+    vec!["add", "."],
+    vec![
+      "-c",
+      "user.name=deno",
+      "-c",
+      "user.email=deno@example.com",
+      "commit",
+      "--quiet",
+      "-m",
+      "initial",
+    ],
+  ] {
+    let status = std::process::Command::new("git")
+      .args(args)
+      .current_dir(temp_dir.path())
+      .status()
+      .unwrap();
+    assert!(status.success());
+  }
+
+  // nothing changed since the commit
+  let output = context
+    .new_command()
+    .args("task -q --recursive --changed HEAD build")
+    .env("NO_COLOR", "1")
+    .run();
+  output.assert_exit_code(1);
+  output.assert_matches_text(
+    "Task not found in the selected workspace members: build\n",
+  );
+
+  // a changed member runs along with the members depending on it
+  temp_dir.write("lib/mod.ts", "export {};\n");
+  let output = context
+    .new_command()
+    .args("task -q --recursive --changed HEAD build")
+    .env("NO_COLOR", "1")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("[lib] lib\n[lib] done\n[app] app\n");
+
+  temp_dir.write("app/main.ts", "export {};\n");
+  std::fs::remove_file(temp_dir.path().join("lib/mod.ts")).unwrap();
+  let output = context
+    .new_command()
+    .args("task -q --recursive --changed HEAD build")
+    .env("NO_COLOR", "1")
+    .run();
+  output.assert_exit_code(0);
+  output.assert_matches_text("[app] app\n");
+}
+
 itest!(task_non_existent {
   args: "task --config task/deno_json/deno.json non_existent",
   output: "task/deno_json/task_non_existent.out",
@@ -3,9 +3,11 @@
 use deno_core::url::Url;
 use test_util as util;
 use util::assert_contains;
+use util::assert_not_contains;
 use util::env_vars_for_npm_tests;
 use util::wildcard_match;
 use util::TestContext;
+use util::TestContextBuilder;
 
 #[test]
 fn no_color() {
@@ -89,6 +91,12 @@ itest!(parallel_flag {
   output: "test/short-pass.out",
 });
 
+itest!(isolate_pool {
+  args: "test --isolate-pool --allow-read --allow-env test/isolate_pool",
+  exit_code: 0,
+  output: "test/isolate_pool.out",
+});
+
 itest!(parallel_flag_with_env_variable {
   args: "test test/short-pass.ts --parallel",
   envs: vec![("DENO_JOBS".to_owned(), "2".to_owned())],
@@ -413,6 +421,304 @@ fn file_protocol() {
     .assert_matches_file("test/file_protocol.out");
 }
 
+#[test]
+fn reporters() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "reporter_test.ts",
+    r#"Deno.test("passes", () => {});
+Deno.test("fails <1>", () => {
+  throw new Error("boom");
+});
+Deno.test("steps", async (t) => {
+  await t.step("step # 1", () => {});
+});
+"#,
+  );
+
+  let output = context.new_command().args("test --reporter=tap").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "TAP version 13");
+  assert_contains!(output_text, "ok 1 - passes");
+  assert_contains!(output_text, "not ok 2 - fails <1>");
+  assert_contains!(output_text, "  message: \"boom\"");
+  assert_contains!(output_text, "ok 3 - steps ... step \\# 1");
+  assert_contains!(output_text, "ok 4 - steps");
+  assert_contains!(output_text, "1..4");
+
+  let output = context.new_command().args("test --reporter=json").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, r#""name": "fails <1>""#);
+  assert_contains!(output_text, r#""status": "failed""#);
+  assert_contains!(output_text, r#""message": "boom""#);
+  assert_contains!(output_text, r#""passedSteps": 1"#);
+
+  let output = context
+    .new_command()
+    .args("test --reporter=junit --reporter-output=report.xml")
+    .run();
+  output.assert_exit_code(1);
+  // the pretty reporter still prints to the console
+  assert_contains!(output.combined_output(), "2 passed (1 step) | 1 failed");
+  let report = temp_dir.read_to_string("report.xml");
+  assert_contains!(
+    report,
+    r#"<testsuite name="./reporter_test.ts" tests="4" failures="1" errors="0" skipped="0""#
+  );
+  assert_contains!(report, r#"<testcase name="fails &lt;1&gt;""#);
+  assert_contains!(report, r#"<failure message="boom">"#);
+  assert_contains!(report, r#"<testcase name="steps ... step # 1""#);
+}
+
+#[test]
+fn shard() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  let files = [
+    "a_test.ts",
+    "b_test.ts",
+    "c_test.ts",
+    "d_test.ts",
+    "e_test.ts",
+  ];
+  for file in files {
+    temp_dir.write(file, format!("Deno.test(\"{file}\", () => {{}});\n"));
+  }
+
+  let mut ran = Vec::new();
+  for index in [1, 2] {
+    let output = context
+      .new_command()
+      .args(format!("test --shuffle=42 --shard={index}/2"))
+      .run();
+    output.assert_exit_code(0);
+    let output_text = output.combined_output();
+    assert_contains!(output_text, &format!("running shard {index}/2 with"));
+    for file in files {
+      if output_text.contains(&format!("from ./{file}")) {
+        ran.push(file);
+      }
+    }
+  }
+  // every module ran in exactly one of the shards
+  ran.sort();
+  assert_eq!(ran, files);
+
+  let output = context
+    .new_command()
+    .args("test --shard=1/2 --reporter=json")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), r#""index": 1,"#);
+  assert_contains!(output.combined_output(), r#""count": 2,"#);
+}
+
+#[test]
+fn snapshots() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "snapshot_test.ts",
+    r#"Deno.test("object", (t) => {
+  t.assertSnapshot({ b: [1, 2], a: "text" });
+  t.assertSnapshot("multiple\nlines");
+});
+"#,
+  );
+
+  let output = context.new_command().args("test").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Missing snapshot \"object 1\". Run with --update-snapshots to create it."
+  );
+
+  // no permissions are needed to write the snapshots
+  let output = context.new_command().args("test --update-snapshots").run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let snapshots =
+    temp_dir.read_to_string("__snapshots__/snapshot_test.ts.snap");
+  assert_contains!(snapshots, r#""object 2": "multiple\nlines""#);
+  let output = context.new_command().args("test").run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  temp_dir.write(
+    "snapshot_test.ts",
+    r#"Deno.test("object", (t) => {
+  t.assertSnapshot({ a: "other text", b: [1, 2] });
+});
+"#,
+  );
+  let output = context.new_command().args("test").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "Snapshot \"object 1\" does not match");
+  assert_contains!(output_text, "other text");
+  // only reported once every test of the module passes
+  assert_not_contains!(output_text, "obsolete snapshot");
+
+  temp_dir.write(
+    "snapshot_test.ts",
+    r#"Deno.test("object", (t) => {
+  t.assertSnapshot({ a: "text", b: [1, 2] });
+});
+"#,
+  );
+  let output = context.new_command().args("test").run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "1 obsolete snapshot in");
+  assert_contains!(output_text, "Run with --update-snapshots to remove it.");
+
+  // the obsolete snapshot is removed
+  let output = context.new_command().args("test --update-snapshots").run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+  let snapshots =
+    temp_dir.read_to_string("__snapshots__/snapshot_test.ts.snap");
+  assert_contains!(snapshots, "object 1");
+  assert_not_contains!(snapshots, "object 2");
+}
+
+#[test]
+fn global_setup() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "setup.ts",
+    r#"export let listener: Deno.Listener;
+
+export default function () {
+  listener = Deno.listen({ port: 0 });
+  (async () => {
+    for await (const conn of listener) {
+      await conn.write(new TextEncoder().encode("hello"));
+      conn.close();
+    }
+  })();
+  console.log("global setup");
+  return { port: (listener.addr as Deno.NetAddr).port };
+}
+"#,
+  );
+  temp_dir.write(
+    "teardown.ts",
+    r#"import { listener } from "./setup.ts";
+
+export default function (context: { port: number }) {
+  // shares the module instances of the setup
+  listener.close();
+  console.log(`global teardown ${typeof context.port}`);
+}
+"#,
+  );
+  let test_text = r#"Deno.test("server", async (t) => {
+  const { port } = t.globalContext as { port: number };
+  const conn = await Deno.connect({ port });
+  const buf = new Uint8Array(5);
+  await conn.read(buf);
+  conn.close();
+  if (new TextDecoder().decode(buf) !== "hello") {
+    throw new Error("unexpected response");
+  }
+});
+"#;
+  temp_dir.write("a_test.ts", test_text);
+  temp_dir.write("b_test.ts", test_text);
+
+  let output = context
+    .new_command()
+    .args(
+      "test --allow-net --global-setup=setup.ts --global-teardown=teardown.ts",
+    )
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  // run once, not for each test module
+  assert_eq!(output_text.matches("global setup").count(), 1);
+  assert_eq!(output_text.matches("global teardown number").count(), 1);
+  assert_contains!(output_text, "ok | 2 passed | 0 failed");
+
+  // from the configuration file
+  temp_dir.write(
+    "deno.json",
+    r#"{ "test": { "globalSetup": "./setup.ts", "globalTeardown": "./teardown.ts" } }"#,
+  );
+  let output = context.new_command().args("test --allow-net").run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "global setup");
+  assert_contains!(output_text, "global teardown number");
+
+  // the tests don't run when the setup fails
+  temp_dir.write(
+    "setup.ts",
+    r#"export default function () {
+  throw new Error("no database");
+}
+"#,
+  );
+  let output = context.new_command().args("test --allow-net").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "Failed running global setup");
+  assert_contains!(output_text, "no database");
+  assert_not_contains!(output_text, "passed");
+}
+
+#[test]
+fn import_map_overrides() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "db.ts",
+    r#"export const name = "db";
+export function query(): string {
+  throw new Error("no database");
+}
+"#,
+  );
+  temp_dir.write(
+    "db.mock.ts",
+    r#"export { name } from "./db.ts";
+export function query(): string {
+  return "mocked";
+}
+"#,
+  );
+  temp_dir.write("repo.ts", "export { name, query } from \"./db.ts\";\n");
+  temp_dir.write(
+    "repo_test.ts",
+    r#"import { name, query } from "./repo.ts";
+
+Deno.test("query", () => {
+  console.log(`${name} ${query()}`);
+});
+"#,
+  );
+  temp_dir.write(
+    "mocks.json",
+    r#"{ "imports": { "./db.ts": "./db.mock.ts" } }"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("test --import-map-overrides=mocks.json")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "db mocked");
+
+  // the original module is used without the overrides
+  let output = context.new_command().args("test").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "no database");
+}
+
 itest!(uncaught_errors {
   args: "test --quiet test/uncaught_errors_1.ts test/uncaught_errors_2.ts test/uncaught_errors_3.ts",
   output: "test/uncaught_errors.out",
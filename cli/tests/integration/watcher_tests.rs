@@ -781,6 +781,82 @@ async fn run_watch_external_watch_files() {
   check_alive_then_kill(child);
 }
 
+#[tokio::test]
+async fn run_watch_include_globs() {
+  let t = TempDir::new();
+  let file_to_watch = t.path().join("file_to_watch.js");
+  write(&file_to_watch, "console.log('Hello world');").unwrap();
+  let templates_dir = t.path().join("templates");
+  std::fs::create_dir_all(templates_dir.join("cache")).unwrap();
+  write(templates_dir.join("index.html"), "<h1>Hello</h1>").unwrap();
+
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("run")
+    .arg("--watch")
+    .arg("--watch-include=templates/**/*.html")
+    .arg("--watch-exclude=templates/cache")
+    .arg("-L")
+    .arg("debug")
+    .arg(&file_to_watch)
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (mut stdout_lines, mut stderr_lines) = child_lines(&mut child);
+  wait_contains("Process started", &mut stderr_lines).await;
+  wait_contains("Hello world", &mut stdout_lines).await;
+  wait_for_watcher("templates", &mut stderr_lines).await;
+
+  // the template isn't imported, but matches the include pattern
+  write(templates_dir.join("index.html"), "<h1>Hello world</h1>").unwrap();
+  wait_contains("Restarting", &mut stderr_lines).await;
+  wait_contains("Process finished", &mut stderr_lines).await;
+
+  check_alive_then_kill(child);
+}
+
+#[tokio::test]
+async fn run_watch_before_restart_task() {
+  let t = TempDir::new();
+  let file_to_watch = t.path().join("file_to_watch.js");
+  write(&file_to_watch, "console.log('Hello world');").unwrap();
+  write(
+    t.path().join("deno.json"),
+    r#"{
+      "tasks": { "codegen": "echo generating" },
+      "watch": { "debounce": 100, "beforeRestart": "codegen" }
+    }"#,
+  )
+  .unwrap();
+
+  let mut child = util::deno_cmd()
+    .current_dir(t.path())
+    .arg("run")
+    .arg("--watch")
+    .arg("-L")
+    .arg("debug")
+    .arg(&file_to_watch)
+    .env("NO_COLOR", "1")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap();
+  let (mut stdout_lines, mut stderr_lines) = child_lines(&mut child);
+  wait_contains("Process started", &mut stderr_lines).await;
+  wait_contains("Hello world", &mut stdout_lines).await;
+  wait_for_watcher("file_to_watch.js", &mut stderr_lines).await;
+
+  write(&file_to_watch, "console.log('Hello world2');").unwrap();
+  wait_contains("Task codegen echo generating", &mut stderr_lines).await;
+  wait_contains("generating", &mut stdout_lines).await;
+  wait_contains("Restarting!", &mut stderr_lines).await;
+  wait_contains("Hello world2", &mut stdout_lines).await;
+
+  check_alive_then_kill(child);
+}
+
 #[tokio::test]
 async fn run_watch_load_unload_events() {
   let t = TempDir::new();
@@ -0,0 +1,10 @@
+/**
+ * @param {number} a
+ * @param {number} b
+ * @returns {number}
+ */
+export function add(a, b) {
+  return a + b;
+}
+
+export { sub } from "./sub.js";
@@ -0,0 +1,7 @@
+/**
+ * @param {number} a
+ * @param {number} b
+ */
+export function sub(a, b) {
+  return a - b;
+}
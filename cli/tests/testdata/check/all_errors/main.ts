@@ -0,0 +1,4 @@
+import { a } from "./missing_a.ts";
+import { b } from "./missing_b.ts";
+
+console.log(a, b);
@@ -0,0 +1,5 @@
+import { table } from "./startup.ts";
+
+// deno-lint-ignore no-explicit-any
+console.log((globalThis as any).startupEvaluations);
+console.log(table.join(","));
@@ -0,0 +1 @@
+setTimeout(() => {}, 100_000);
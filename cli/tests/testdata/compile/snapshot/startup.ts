@@ -0,0 +1,6 @@
+// Evaluated once at compile time and restored from the startup snapshot.
+// deno-lint-ignore no-explicit-any
+const global = globalThis as any;
+global.startupEvaluations = (global.startupEvaluations ?? 0) + 1;
+
+export const table = Array.from({ length: 5 }, (_, i) => i * i);
@@ -0,0 +1,26 @@
+/** Adds two numbers. */
+export function add(a: number, b: number): number {
+  return a + b;
+}
+
+/**
+ * Subtracts two numbers.
+ *
+ * @param a the number to subtract from
+ * @param c the number to subtract
+ */
+export function subtract(a: number, b: number): number {
+  return a - b;
+}
+
+export class Calculator {}
+
+/**
+ * Multiplies two numbers.
+ *
+ * @param a the first number
+ * @param b the second number
+ */
+export function multiply(a: number, b: number): number {
+  return a * b;
+}
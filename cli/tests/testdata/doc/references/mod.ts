@@ -0,0 +1,6 @@
+import type { Options } from "./types.ts";
+
+/** Runs with the provided options. */
+export function run(options: Options): void {
+  console.log(options.name);
+}
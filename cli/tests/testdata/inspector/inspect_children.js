@@ -0,0 +1,4 @@
+const child = new Deno.Command(Deno.execPath(), {
+  args: ["eval", "setTimeout(() => {}, 5000)"],
+}).spawn();
+await child.status;
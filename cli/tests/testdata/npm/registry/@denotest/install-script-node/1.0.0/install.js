@@ -0,0 +1,5 @@
+const fs = require("fs");
+const path = require("path");
+
+fs.writeFileSync(path.join(__dirname, "built.txt"), process.argv[2]);
+console.log("node install script ran");
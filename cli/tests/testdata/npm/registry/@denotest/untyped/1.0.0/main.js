@@ -0,0 +1,3 @@
+module.exports.add = function (a, b) {
+  return a + b;
+};
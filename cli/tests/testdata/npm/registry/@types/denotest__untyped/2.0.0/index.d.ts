@@ -0,0 +1 @@
+export declare function add(a: string, b: string): string;
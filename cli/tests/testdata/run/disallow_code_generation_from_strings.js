@@ -0,0 +1,6 @@
+try {
+  new Function("return 1")();
+} catch (err) {
+  console.log(err instanceof EvalError);
+}
+eval("1 + 1");
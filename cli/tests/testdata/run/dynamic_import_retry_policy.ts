@@ -0,0 +1,15 @@
+try {
+  Deno.setDynamicImportRetryPolicy({ maxAttempts: 0 });
+} catch (err) {
+  console.log(err instanceof RangeError, err.message);
+}
+
+Deno.setDynamicImportRetryPolicy({ maxAttempts: 5, initialDelay: 10_000 });
+// missing modules are not retried, so this rejects right away
+const start = Date.now();
+try {
+  await import("./does_not_exist.ts");
+} catch (err) {
+  console.log(err instanceof TypeError, Date.now() - start < 10_000);
+}
+Deno.setDynamicImportRetryPolicy(null);
@@ -0,0 +1,10 @@
+// the server fails the first two requests for each of these modules
+try {
+  await import("http://localhost:4545/flaky/dynamic_import_no_retry.js");
+} catch (err) {
+  console.log(err.message);
+}
+
+Deno.setDynamicImportRetryPolicy({ maxAttempts: 3, initialDelay: 10 });
+const mod = await import("http://localhost:4545/flaky/dynamic_import_retry.js");
+console.log(mod.default);
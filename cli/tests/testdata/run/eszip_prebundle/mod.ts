@@ -0,0 +1,3 @@
+import { value } from "./dep.ts";
+
+console.log(value);
@@ -0,0 +1,8 @@
+const blob = new Blob(
+  [
+    "export function a() {\n  throw new Error('Hello');\n}\n//# sourceMappingURL=data:application/json;base64,%%%",
+  ],
+  { type: "application/javascript" },
+);
+const { a } = await import(URL.createObjectURL(blob));
+a();
@@ -0,0 +1,5 @@
+console.log("Deno.openKv:", typeof Deno.openKv);
+console.log("BroadcastChannel:", typeof globalThis.BroadcastChannel);
+console.log("Deno.dlopen:", typeof Deno.dlopen);
+console.log("Deno.umask:", typeof Deno.umask);
+console.log("WebSocketStream:", typeof globalThis.WebSocketStream);
@@ -0,0 +1,21 @@
+import { state } from "./shared.ts";
+
+// deno-lint-ignore no-explicit-any
+const global = globalThis as any;
+global.leaked = true;
+global.fetch = () => {
+  throw new Error("Stubbed.");
+};
+addEventListener("load", () => {
+  if (global.isolatePoolB) {
+    throw new Error("The load listener of a_test.ts was kept.");
+  }
+});
+state.importers.push("a_test.ts");
+state.rid = Deno.openSync(new URL("./shared.ts", import.meta.url)).rid;
+Deno.unrefTimer(setTimeout(() => {
+  global.timerFired = true;
+}, 100));
+await Deno.permissions.revoke({ name: "env" });
+
+Deno.test("a", () => {});
@@ -0,0 +1,30 @@
+import { state } from "./shared.ts";
+
+// deno-lint-ignore no-explicit-any
+const global = globalThis as any;
+global.isolatePoolB = true;
+state.importers.push("b_test.ts");
+
+Deno.test("b", async () => {
+  // the isolate of a_test.ts was reused
+  if (state.importers.join() !== "a_test.ts,b_test.ts") {
+    throw new Error("The isolate of a_test.ts wasn't reused.");
+  }
+  if ("leaked" in global) {
+    throw new Error("The global of a_test.ts was kept.");
+  }
+  if (global.fetch.name !== "fetch") {
+    throw new Error("The global replaced by a_test.ts was kept.");
+  }
+  if (String(state.rid) in Deno.resources()) {
+    throw new Error("The resource of a_test.ts was kept.");
+  }
+  const status = await Deno.permissions.query({ name: "env" });
+  if (status.state !== "granted") {
+    throw new Error("The permission revoked by a_test.ts was kept.");
+  }
+  await new Promise((resolve) => setTimeout(resolve, 200));
+  if ("timerFired" in global) {
+    throw new Error("The timer of a_test.ts was kept.");
+  }
+});
@@ -0,0 +1,5 @@
+// evaluated once per isolate, so the test modules of the pool share it
+export const state = {
+  importers: [] as string[],
+  rid: undefined as number | undefined,
+};
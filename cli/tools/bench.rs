@@ -14,9 +14,12 @@ use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
 use crate::util::fs::collect_specifiers;
 use crate::util::path::is_supported_ext;
+use crate::util::path::relative_specifier;
 use crate::version::get_user_agent;
 use crate::worker::create_custom_worker;
 
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
@@ -25,6 +28,7 @@ use deno_core::futures::stream;
 use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::located_script_name;
+use deno_core::serde_json;
 use deno_core::serde_v8;
 use deno_core::v8;
 use deno_core::ModuleSpecifier;
@@ -105,6 +109,7 @@ pub struct BenchStats {
   pub p99: f64,
   pub p995: f64,
   pub p999: f64,
+  pub sd: f64,
 }
 
 impl BenchReport {
@@ -522,7 +527,7 @@ async fn bench_specifiers(
   permissions: &Permissions,
   specifiers: Vec<ModuleSpecifier>,
   options: BenchSpecifierOptions,
-) -> Result<(), AnyError> {
+) -> Result<BenchReport, AnyError> {
   let log_level = ps.options.log_level();
 
   let (sender, mut receiver) = unbounded_channel::<BenchEvent>();
@@ -607,7 +612,7 @@ async fn bench_specifiers(
         return Err(generic_error("Bench failed"));
       }
 
-      Ok(())
+      Ok(report)
     })
   };
 
@@ -618,7 +623,213 @@ async fn bench_specifiers(
     join_result??;
   }
 
-  result??;
+  result?
+}
+
+/// The results of a bench run saved with `--save-baseline`, which later runs
+/// compare against with `--baseline`.
+#[derive(Debug, Serialize, Deserialize)]
+struct BenchBaseline {
+  runtime: String,
+  cpu: String,
+  benches: Vec<BenchBaselineEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BenchBaselineEntry {
+  /// The bench module relative to the current directory when possible, so
+  /// that baselines can be shared between checkouts.
+  origin: String,
+  group: Option<String>,
+  name: String,
+  stats: BenchStats,
+}
+
+impl BenchBaseline {
+  fn from_report(report: &BenchReport, cwd: &ModuleSpecifier) -> Self {
+    Self {
+      runtime: format!("{} {}", get_user_agent(), env!("TARGET")),
+      cpu: mitata::cpu::name(),
+      benches: report
+        .measurements
+        .iter()
+        .map(|(desc, stats)| BenchBaselineEntry {
+          origin: baseline_origin(&desc.origin, cwd),
+          group: desc.group.clone(),
+          name: desc.name.clone(),
+          stats: stats.clone(),
+        })
+        .collect(),
+    }
+  }
+
+  fn get(
+    &self,
+    desc: &BenchDescription,
+    cwd: &ModuleSpecifier,
+  ) -> Option<&BenchStats> {
+    let origin = baseline_origin(&desc.origin, cwd);
+    self
+      .benches
+      .iter()
+      .find(|entry| {
+        entry.origin == origin
+          && entry.group == desc.group
+          && entry.name == desc.name
+      })
+      .map(|entry| &entry.stats)
+  }
+}
+
+fn baseline_origin(origin: &str, cwd: &ModuleSpecifier) -> String {
+  ModuleSpecifier::parse(origin)
+    .ok()
+    .filter(|specifier| specifier.scheme() == "file")
+    .and_then(|specifier| relative_specifier(cwd, &specifier))
+    .unwrap_or_else(|| origin.to_string())
+}
+
+/// Names ending with `.json` are files relative to the current directory,
+/// other names are stored in DENO_DIR.
+fn resolve_baseline_path(
+  ps: &ProcState,
+  name: &str,
+) -> Result<PathBuf, AnyError> {
+  if name.ends_with(".json") {
+    return Ok(ps.options.initial_cwd().join(name));
+  }
+  if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+    bail!("Invalid bench baseline name \"{name}\". Use a name ending with \".json\" to store the baseline in a file.");
+  }
+  Ok(
+    ps.dir
+      .bench_baselines_folder_path()
+      .join(format!("{name}.json")),
+  )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BenchChange {
+  Regressed,
+  Improved,
+  Unchanged,
+}
+
+/// Compares the average time of a bench with its baseline, returning the
+/// change in percent. A change only counts when it exceeds the threshold and
+/// is statistically significant.
+fn compare_stats(
+  baseline: &BenchStats,
+  current: &BenchStats,
+  threshold: u32,
+) -> (f64, BenchChange) {
+  let change = (current.avg - baseline.avg) / baseline.avg * 100.0;
+  if !change.is_finite()
+    || change.abs() <= threshold as f64
+    || !is_significant_difference(baseline, current)
+  {
+    (change, BenchChange::Unchanged)
+  } else if change > 0.0 {
+    (change, BenchChange::Regressed)
+  } else {
+    (change, BenchChange::Improved)
+  }
+}
+
+/// Welch's t-test on the averages at a 95% confidence level. The t
+/// distribution is approximated with the normal distribution, which is close
+/// enough for the number of samples a bench collects.
+fn is_significant_difference(a: &BenchStats, b: &BenchStats) -> bool {
+  const CRITICAL_VALUE: f64 = 1.96;
+  let standard_error =
+    (a.sd.powi(2) / a.n as f64 + b.sd.powi(2) / b.n as f64).sqrt();
+  if standard_error == 0.0 {
+    return a.avg != b.avg;
+  }
+  ((a.avg - b.avg) / standard_error).abs() > CRITICAL_VALUE
+}
+
+/// Compares the results with the `--baseline` and saves them with
+/// `--save-baseline`, erroring when a bench regressed.
+fn apply_baselines(
+  ps: &ProcState,
+  bench_options: &BenchOptions,
+  report: &BenchReport,
+) -> Result<(), AnyError> {
+  let cwd = ModuleSpecifier::from_directory_path(ps.options.initial_cwd())
+    .map_err(|_| generic_error("Invalid current directory."))?;
+
+  let mut regressions = 0;
+  if let Some(name) = &bench_options.baseline {
+    let path = resolve_baseline_path(ps, name)?;
+    let text = std::fs::read_to_string(&path).with_context(|| {
+      format!(
+        "Failed reading bench baseline \"{name}\" at {}",
+        path.display()
+      )
+    })?;
+    let baseline: BenchBaseline = serde_json::from_str(&text)
+      .with_context(|| format!("Failed parsing bench baseline \"{name}\""))?;
+
+    if !bench_options.json {
+      println!("\n{}", colors::gray(format!("baseline: {name}")));
+    }
+    for (desc, stats) in &report.measurements {
+      let baseline_stats = match baseline.get(desc, &cwd) {
+        Some(stats) => stats,
+        None => continue,
+      };
+      let (change, kind) = compare_stats(
+        baseline_stats,
+        stats,
+        bench_options.regression_threshold,
+      );
+      if kind == BenchChange::Regressed {
+        regressions += 1;
+      }
+      if bench_options.json {
+        continue;
+      }
+      let change = format!("{change:+.1}%");
+      let kind = match kind {
+        BenchChange::Regressed => colors::red_bold("regressed").to_string(),
+        BenchChange::Improved => colors::green("improved").to_string(),
+        BenchChange::Unchanged => colors::gray("unchanged").to_string(),
+      };
+      println!(
+        "{} {} -> {} ({}) {}",
+        desc.name,
+        mitata::fmt_duration(baseline_stats.avg),
+        mitata::fmt_duration(stats.avg),
+        change,
+        kind,
+      );
+    }
+  }
+
+  if let Some(name) = &bench_options.save_baseline {
+    let path = resolve_baseline_path(ps, name)?;
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let baseline = BenchBaseline::from_report(report, &cwd);
+    std::fs::write(&path, serde_json::to_string_pretty(&baseline)?)
+      .with_context(|| {
+        format!(
+          "Failed saving bench baseline \"{name}\" at {}",
+          path.display()
+        )
+      })?;
+  }
+
+  if regressions > 0 {
+    return Err(generic_error(format!(
+      "Bench failed because {} bench{} regressed more than {}% from the baseline",
+      regressions,
+      if regressions == 1 { "" } else { "es" },
+      bench_options.regression_threshold,
+    )));
+  }
 
   Ok(())
 }
@@ -660,7 +871,7 @@ pub async fn run_benchmarks(
     return Ok(());
   }
 
-  bench_specifiers(
+  let report = bench_specifiers(
     &ps,
     &permissions,
     specifiers,
@@ -671,6 +882,8 @@ pub async fn run_benchmarks(
   )
   .await?;
 
+  apply_baselines(&ps, &bench_options, &report)?;
+
   Ok(())
 }
 
@@ -819,7 +1032,7 @@ pub async fn run_benchmarks_with_watch(
         return Ok(());
       }
 
-      bench_specifiers(
+      let report = bench_specifiers(
         &ps,
         permissions,
         specifiers,
@@ -830,17 +1043,26 @@ pub async fn run_benchmarks_with_watch(
       )
       .await?;
 
+      apply_baselines(&ps, bench_options, &report)?;
+
       Ok(())
     }
   };
 
   let clear_screen = !ps.borrow().options.no_clear_screen();
+  let filter = ps.borrow().options.watch_filter()?;
+  let debounce = ps.borrow().options.watch_debounce()?;
+  let before_restart =
+    crate::tools::task::before_restart_hook(&ps.borrow().options)?;
   file_watcher::watch_func(
     resolver,
     operation,
     file_watcher::PrintConfig {
       job_name: "Bench".to_string(),
       clear_screen,
+      filter,
+      debounce,
+      before_restart,
     },
   )
   .await?;
@@ -860,7 +1082,7 @@ mod mitata {
   use crate::colors;
   use std::str::FromStr;
 
-  fn fmt_duration(time: f64) -> String {
+  pub fn fmt_duration(time: f64) -> String {
     // SAFETY: this is safe since its just reformatting numbers
     unsafe {
       if time < 1e0 {
@@ -13,6 +13,7 @@ use crate::args::CliOptions;
 use crate::args::Flags;
 use crate::args::TsConfigType;
 use crate::args::TypeCheckMode;
+use crate::emit::minify_js;
 use crate::graph_util::error_for_any_npm_specifier;
 use crate::proc_state::ProcState;
 use crate::util;
@@ -131,6 +132,9 @@ pub async fn bundle(
       util::file_watcher::PrintConfig {
         job_name: "Bundle".to_string(),
         clear_screen: !cli_options.no_clear_screen(),
+        filter: cli_options.watch_filter()?,
+        debounce: cli_options.watch_debounce()?,
+        before_restart: crate::tools::task::before_restart_hook(&cli_options)?,
       },
     )
     .await?;
@@ -162,12 +166,18 @@ fn bundle_module_graph(
     }
   }
 
-  deno_emit::bundle_graph(
+  let mut bundle_emit = deno_emit::bundle_graph(
     graph,
     deno_emit::BundleOptions {
       bundle_type: deno_emit::BundleType::Module,
       emit_options: ts_config_result.ts_config.into(),
       emit_ignore_directives: true,
     },
-  )
+  )?;
+  if ps.options.profile().map(|p| p.minify).unwrap_or(false) {
+    bundle_emit.code = minify_js(&graph.roots[0], bundle_emit.code)?;
+    // the source map no longer lines up with the minified code
+    bundle_emit.maybe_map = None;
+  }
+  Ok(bundle_emit)
 }
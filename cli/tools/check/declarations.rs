@@ -0,0 +1,364 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::SourceRanged;
+use deno_ast::SourceRangedForSpanned;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_import;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_graph::ModuleGraph;
+use deno_semver::npm::NpmPackageReqReference;
+
+use crate::npm::npm_package_ref_from_cdn_url;
+use crate::tsc::EmittedDeclaration;
+use crate::util::fs::atomic_write_file;
+use crate::util::path::relative_specifier;
+use crate::util::path::specifier_to_file_path;
+
+const SOURCE_MAPPING_URL_PREFIX: &str = "//# sourceMappingURL=";
+
+/// Writes the declaration files and declaration maps emitted for the local
+/// modules of the graph to the output directory, keeping the layout of the
+/// modules relative to their common directory.
+///
+/// The imports of the declaration files are rewritten, so that they can be
+/// resolved without Deno: npm specifiers become bare package specifiers,
+/// imports of emitted modules use the extension of the emitted JavaScript,
+/// modules of npm CDNs become bare package specifiers as well and imports of
+/// redirected remote modules use the final URL.
+pub fn write_declarations(
+  graph: &ModuleGraph,
+  declarations: &[EmittedDeclaration],
+  out_dir: &Path,
+) -> Result<(), AnyError> {
+  let out_files = DeclarationOutFiles::new(graph, declarations, out_dir)?;
+  for declaration in declarations {
+    let out_file = out_files.get(&declaration.specifier).unwrap();
+    if let Some(parent) = out_file.path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    let mut text = rewrite_imports(graph, &out_files, declaration)?;
+    // the declaration map is referenced by the internal name of the module
+    // in tsc, so the reference is replaced with the written one
+    if let Some(index) = text.rfind(SOURCE_MAPPING_URL_PREFIX) {
+      text.truncate(index);
+    }
+    if let Some(map) = &declaration.maybe_map {
+      let map_file = map_file_path(&out_file.path);
+      let map = rewrite_map(map, &out_file.path, &declaration.specifier)?;
+      atomic_write_file(&map_file, map, 0o644)?;
+      if !text.ends_with('\n') {
+        text.push('\n');
+      }
+      text.push_str(SOURCE_MAPPING_URL_PREFIX);
+      text.push_str(&file_name(&map_file));
+      text.push('\n');
+    }
+    atomic_write_file(&out_file.path, text, 0o644)
+      .with_context(|| format!("Failed writing {}", out_file.path.display()))?;
+  }
+  Ok(())
+}
+
+struct DeclarationOutFile {
+  specifier: ModuleSpecifier,
+  path: PathBuf,
+  /// The extension of the JavaScript emitted for the module, which imports of
+  /// the module use.
+  js_extension: &'static str,
+}
+
+struct DeclarationOutFiles(Vec<DeclarationOutFile>);
+
+impl DeclarationOutFiles {
+  fn new(
+    graph: &ModuleGraph,
+    declarations: &[EmittedDeclaration],
+    out_dir: &Path,
+  ) -> Result<Self, AnyError> {
+    let mut sources = Vec::with_capacity(declarations.len());
+    for declaration in declarations {
+      let path = specifier_to_file_path(&declaration.specifier)?;
+      let media_type = graph
+        .get(&declaration.specifier)
+        .and_then(|m| m.esm())
+        .map(|m| m.media_type)
+        .unwrap_or_else(|| MediaType::from_specifier(&declaration.specifier));
+      sources.push((declaration.specifier.clone(), path, media_type));
+    }
+    let common_dir = common_dir(sources.iter().map(|(_, path, _)| path));
+
+    let out_files = sources
+      .into_iter()
+      .map(|(specifier, path, media_type)| {
+        let dts_extension = match media_type {
+          MediaType::Mts | MediaType::Mjs => ".d.mts",
+          MediaType::Cts | MediaType::Cjs => ".d.cts",
+          _ => ".d.ts",
+        };
+        let relative_path = path.strip_prefix(&common_dir).unwrap_or(&path);
+        let mut file_name = file_name(&relative_path.with_extension(""));
+        file_name.push_str(dts_extension);
+        let path = out_dir.join(relative_path).with_file_name(file_name);
+        DeclarationOutFile {
+          specifier,
+          path,
+          js_extension: js_extension(media_type),
+        }
+      })
+      .collect();
+    Ok(Self(out_files))
+  }
+
+  fn get(&self, specifier: &ModuleSpecifier) -> Option<&DeclarationOutFile> {
+    self.0.iter().find(|file| &file.specifier == specifier)
+  }
+}
+
+/// Rewrites the specifiers of the imports and exports of the declaration.
+fn rewrite_imports(
+  graph: &ModuleGraph,
+  out_files: &DeclarationOutFiles,
+  declaration: &EmittedDeclaration,
+) -> Result<String, AnyError> {
+  let out_file = out_files.get(&declaration.specifier).unwrap();
+  rewrite_specifiers(
+    &declaration.specifier,
+    &declaration.text,
+    MediaType::Dts,
+    |specifier| {
+      Ok(rewrite_specifier(
+        graph,
+        out_files,
+        out_file,
+        &declaration.specifier,
+        specifier,
+      ))
+    },
+  )
+  .with_context(|| {
+    format!(
+      "Failed rewriting the declaration of {}",
+      declaration.specifier
+    )
+  })
+}
+
+fn rewrite_specifier(
+  graph: &ModuleGraph,
+  out_files: &DeclarationOutFiles,
+  out_file: &DeclarationOutFile,
+  referrer: &ModuleSpecifier,
+  specifier: &str,
+) -> Option<String> {
+  let resolved = resolve_dependency(graph, referrer, specifier)?;
+  if let Some(reference) = npm_package_ref_from_cdn_url(&resolved) {
+    return Some(npm_package_import(&reference));
+  }
+  let resolved = graph.resolve(&resolved);
+
+  if let Ok(reference) = NpmPackageReqReference::from_specifier(&resolved) {
+    return Some(npm_package_import(&reference));
+  }
+  if let Some(dependency_file) = out_files.get(&resolved) {
+    let from = ModuleSpecifier::from_file_path(&out_file.path).ok()?;
+    let mut to = ModuleSpecifier::from_file_path(&dependency_file.path).ok()?;
+    let path = to.path().to_string();
+    let path = path
+      .strip_suffix(".d.mts")
+      .or_else(|| path.strip_suffix(".d.cts"))
+      .or_else(|| path.strip_suffix(".d.ts"))
+      .unwrap_or(&path);
+    to.set_path(&format!("{}{}", path, dependency_file.js_extension));
+    return relative_specifier(&from, &to);
+  }
+  match resolved.scheme() {
+    "http" | "https" if resolved.as_str() != specifier => {
+      Some(resolved.to_string())
+    }
+    _ => None,
+  }
+}
+
+/// Replaces the specifiers of the imports and exports of the module with the
+/// ones returned by `rewrite`, keeping the others.
+pub fn rewrite_specifiers(
+  specifier: &ModuleSpecifier,
+  text: &str,
+  media_type: MediaType,
+  mut rewrite: impl FnMut(&str) -> Result<Option<String>, AnyError>,
+) -> Result<String, AnyError> {
+  let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+    specifier: specifier.to_string(),
+    text_info: deno_ast::SourceTextInfo::new(text.into()),
+    media_type,
+    capture_tokens: false,
+    scope_analysis: false,
+    maybe_syntax: None,
+  })?;
+  let text_info = parsed_source.text_info();
+
+  let mut text = text.to_string();
+  let mut dependencies = parsed_source.analyze_dependencies();
+  // replace from the end, so that the ranges of the remaining dependencies
+  // stay valid
+  dependencies.sort_by_key(|d| std::cmp::Reverse(d.specifier_span.lo));
+  for dependency in dependencies {
+    let new_specifier = match rewrite(&dependency.specifier)? {
+      Some(specifier) => specifier,
+      None => continue,
+    };
+    // the range includes the quotes of the specifier
+    let range = dependency.specifier_span.range();
+    let start = range.start.as_byte_index(text_info.range().start);
+    let end = range.end.as_byte_index(text_info.range().start);
+    text.replace_range(start..end, &json!(new_specifier).to_string());
+  }
+  Ok(text)
+}
+
+/// Gets the specifier a module imports with the specifier, before redirects.
+///
+/// The npm package of a dependency is preferred over the types of it, so that
+/// the package is imported by its own name even when its types come from
+/// elsewhere.
+pub fn resolve_dependency(
+  graph: &ModuleGraph,
+  referrer: &ModuleSpecifier,
+  specifier: &str,
+) -> Option<ModuleSpecifier> {
+  let maybe_dependency = graph
+    .get(referrer)
+    .and_then(|m| m.esm())
+    .and_then(|m| m.dependencies.get(specifier));
+  match maybe_dependency {
+    Some(dependency) => dependency
+      .get_code()
+      .filter(|s| s.scheme() == "npm")
+      .or_else(|| dependency.get_type())
+      .or_else(|| dependency.get_code())
+      .cloned(),
+    None => resolve_import(specifier, referrer.as_str()).ok(),
+  }
+}
+
+/// The bare specifier a package outside of Deno imports the npm package with.
+pub fn npm_package_import(reference: &NpmPackageReqReference) -> String {
+  match &reference.sub_path {
+    Some(sub_path) => format!("{}/{}", reference.req.name, sub_path),
+    None => reference.req.name.clone(),
+  }
+}
+
+/// The extension of the JavaScript emitted for a module of the media type.
+pub fn js_extension(media_type: MediaType) -> &'static str {
+  match media_type {
+    MediaType::Mts | MediaType::Mjs => ".mjs",
+    MediaType::Cts | MediaType::Cjs => ".cjs",
+    _ => ".js",
+  }
+}
+
+/// Gets the deepest directory that contains all the files.
+pub fn common_dir<'a>(mut paths: impl Iterator<Item = &'a PathBuf>) -> PathBuf {
+  let mut common_dir = match paths.next().and_then(|path| path.parent()) {
+    Some(dir) => dir.to_path_buf(),
+    None => return PathBuf::new(),
+  };
+  for path in paths {
+    while !path.starts_with(&common_dir) {
+      if !common_dir.pop() {
+        return PathBuf::new();
+      }
+    }
+  }
+  common_dir
+}
+
+/// Points the declaration map at the written declaration file and the
+/// source of the module.
+fn rewrite_map(
+  map: &str,
+  out_file: &Path,
+  specifier: &ModuleSpecifier,
+) -> Result<String, AnyError> {
+  let mut map: serde_json::Value = serde_json::from_str(map)?;
+  let out_file_specifier = ModuleSpecifier::from_file_path(out_file).unwrap();
+  let source = relative_specifier(&out_file_specifier, specifier)
+    .unwrap_or_else(|| specifier.to_string());
+  if let Some(map) = map.as_object_mut() {
+    map.insert("file".to_string(), json!(file_name(out_file)));
+    map.insert("sourceRoot".to_string(), json!(""));
+    map.insert("sources".to_string(), json!([source]));
+  }
+  Ok(serde_json::to_string(&map)?)
+}
+
+fn map_file_path(out_file: &Path) -> PathBuf {
+  let mut file_name = file_name(out_file);
+  file_name.push_str(".map");
+  out_file.with_file_name(file_name)
+}
+
+fn file_name(path: &Path) -> String {
+  path
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  fn specifier(path: &Path) -> ModuleSpecifier {
+    ModuleSpecifier::from_file_path(path).unwrap()
+  }
+
+  #[test]
+  fn test_common_dir() {
+    let paths = [
+      PathBuf::from("/project/src/mod.ts"),
+      PathBuf::from("/project/src/util/strings.ts"),
+      PathBuf::from("/project/src/types.ts"),
+    ];
+    assert_eq!(common_dir(paths.iter()), PathBuf::from("/project/src"));
+    let paths = [
+      PathBuf::from("/project/src/mod.ts"),
+      PathBuf::from("/project/lib/mod.ts"),
+    ];
+    assert_eq!(common_dir(paths.iter()), PathBuf::from("/project"));
+  }
+
+  #[test]
+  fn test_rewrite_map() {
+    let temp_dir = TempDir::new();
+    let out_file = temp_dir.path().join("types/mod.d.ts");
+    let source = specifier(&temp_dir.path().join("src/mod.ts"));
+    let map = rewrite_map(
+      r#"{"version":3,"file":"abc.d.ts","sourceRoot":"","sources":["file:///abc.ts"],"names":[],"mappings":"AAAA"}"#,
+      &out_file,
+      &source,
+    )
+    .unwrap();
+    let map: serde_json::Value = serde_json::from_str(&map).unwrap();
+    assert_eq!(
+      map,
+      json!({
+        "version": 3,
+        "file": "mod.d.ts",
+        "sourceRoot": "",
+        "sources": ["../src/mod.ts"],
+        "names": [],
+        "mappings": "AAAA",
+      })
+    );
+  }
+}
@@ -0,0 +1,199 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Infers the declarations of the remote JavaScript modules of a graph that
+//! don't provide types, for `deno cache --infer-types`.
+
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::SourceRanged;
+use deno_ast::SourceRangedForSpanned;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_import;
+use deno_core::serde_json::json;
+use deno_graph::ModuleGraph;
+use deno_runtime::colors;
+
+use crate::args::TsConfigType;
+use crate::args::TypeCheckMode;
+use crate::cache::FastInsecureHasher;
+use crate::cache::InferredTypesCache;
+use crate::proc_state::ProcState;
+use crate::tsc;
+use crate::version;
+
+/// Infers the declarations of the untyped remote JavaScript modules of the
+/// graph of the process and caches them, so that type checking and the
+/// language server use them instead of analyzing the JavaScript.
+///
+/// Modules that get their types from an `X-TypeScript-Types` header or a
+/// types directive are skipped, and so are the ones inferred before unless
+/// `--reload` is specified.
+pub fn infer_remote_types(ps: &ProcState) -> Result<(), AnyError> {
+  let graph = ps.graph_container.graph();
+  let cache = InferredTypesCache::new(&ps.dir.inferred_types_folder_path());
+  let root_names =
+    find_untyped_remote_modules(&graph, &cache, ps.options.reload_flag());
+  if root_names.is_empty() {
+    return Ok(());
+  }
+  for (specifier, _) in &root_names {
+    log::info!("{} {}", colors::green("Infer"), specifier);
+  }
+
+  let mut ts_config = ps
+    .options
+    .resolve_ts_config_for_emit(TsConfigType::Check {
+      lib: ps.options.ts_type_lib_window(),
+    })?
+    .ts_config;
+  ts_config.merge(&json!({
+    "checkJs": false,
+    "declaration": true,
+    "emitDeclarationOnly": true,
+    "noEmit": false,
+  }));
+  let hash_data = {
+    let mut hasher = FastInsecureHasher::new();
+    hasher.write(&ts_config.as_bytes());
+    hasher.write_str(version::deno());
+    hasher.finish()
+  };
+  let response = tsc::exec(tsc::Request {
+    config: ts_config,
+    debug: ps.options.log_level() == Some(log::Level::Debug),
+    graph: graph.clone(),
+    hash_data,
+    maybe_node_resolver: Some(ps.node_resolver.clone()),
+    maybe_tsbuildinfo: None,
+    // all the roots are remote, so none of them is checked
+    check_mode: TypeCheckMode::Local,
+    root_names,
+    emit_declarations: false,
+    infer_remote_declarations: true,
+  })?;
+  log::debug!("{}", response.stats);
+
+  for declaration in response.emitted_declarations {
+    let text =
+      rewrite_imports(&graph, &declaration.specifier, &declaration.text)?;
+    cache.set_types(&declaration.specifier, &text)?;
+  }
+  Ok(())
+}
+
+/// Gets the remote JavaScript modules of the graph that have no types.
+fn find_untyped_remote_modules(
+  graph: &ModuleGraph,
+  cache: &InferredTypesCache,
+  reload: bool,
+) -> Vec<(ModuleSpecifier, MediaType)> {
+  graph
+    .modules()
+    .filter_map(|module| module.esm())
+    .filter(|module| {
+      matches!(module.specifier.scheme(), "http" | "https")
+        && matches!(
+          module.media_type,
+          MediaType::JavaScript
+            | MediaType::Jsx
+            | MediaType::Mjs
+            | MediaType::Cjs
+        )
+    })
+    .filter(|module| match &module.maybe_types_dependency {
+      Some(types_dependency) => {
+        reload
+          && types_dependency
+            .dependency
+            .maybe_specifier()
+            .map(|specifier| cache.contains(specifier))
+            .unwrap_or(false)
+      }
+      None => true,
+    })
+    .map(|module| (module.specifier.clone(), module.media_type))
+    .collect()
+}
+
+/// Rewrites the specifiers of the imports and exports of the declaration to
+/// the modules they resolve to, because the declaration is cached apart from
+/// the module it was inferred for.
+fn rewrite_imports(
+  graph: &ModuleGraph,
+  specifier: &ModuleSpecifier,
+  text: &str,
+) -> Result<String, AnyError> {
+  let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+    specifier: specifier.to_string(),
+    text_info: deno_ast::SourceTextInfo::new(text.into()),
+    media_type: MediaType::Dts,
+    capture_tokens: false,
+    scope_analysis: false,
+    maybe_syntax: None,
+  })
+  .with_context(|| {
+    format!("Failed parsing the inferred declaration of {specifier}")
+  })?;
+  let text_info = parsed_source.text_info();
+  let maybe_module = graph.get(specifier).and_then(|m| m.esm());
+
+  let mut text = text.to_string();
+  let mut dependencies = parsed_source.analyze_dependencies();
+  // replace from the end, so that the ranges of the remaining dependencies
+  // stay valid
+  dependencies.sort_by_key(|d| std::cmp::Reverse(d.specifier_span.lo));
+  for dependency in dependencies {
+    let dependency_specifier: &str = &dependency.specifier;
+    let resolved = match maybe_module
+      .and_then(|m| m.dependencies.get(dependency_specifier))
+    {
+      Some(dependency) => dependency
+        .get_type()
+        .or_else(|| dependency.get_code())
+        .cloned(),
+      None => resolve_import(dependency_specifier, specifier.as_str()).ok(),
+    };
+    let resolved = match resolved {
+      Some(resolved) => graph.resolve(&resolved),
+      None => continue,
+    };
+    if resolved.as_str() == dependency_specifier {
+      continue;
+    }
+    // the range includes the quotes of the specifier
+    let range = dependency.specifier_span.range();
+    let start = range.start.as_byte_index(text_info.range().start);
+    let end = range.end.as_byte_index(text_info.range().start);
+    text.replace_range(start..end, &json!(resolved.as_str()).to_string());
+  }
+  Ok(text)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_rewrite_imports() {
+    let specifier =
+      ModuleSpecifier::parse("https://cdn.example.com/lib/mod.js").unwrap();
+    let text = concat!(
+      "export { sub } from \"./sub.js\";\n",
+      "export declare function add(a: number, b: number): number;\n",
+      "export declare const util: typeof import(\"../util.js\");\n",
+      "export * from \"https://cdn.example.com/other.js\";\n",
+    );
+    let actual =
+      rewrite_imports(&ModuleGraph::default(), &specifier, text).unwrap();
+    assert_eq!(
+      actual,
+      concat!(
+        "export { sub } from \"https://cdn.example.com/lib/sub.js\";\n",
+        "export declare function add(a: number, b: number): number;\n",
+        "export declare const util: typeof import(\"https://cdn.example.com/util.js\");\n",
+        "export * from \"https://cdn.example.com/other.js\";\n",
+      )
+    );
+  }
+}
@@ -1,17 +1,24 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+pub mod declarations;
+pub mod inferred_types;
+pub mod unused;
+
 use std::collections::HashSet;
 use std::sync::Arc;
 
 use deno_ast::MediaType;
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
+use deno_core::serde_json::json;
 use deno_graph::Module;
 use deno_graph::ModuleGraph;
 use deno_runtime::colors;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::args::CheckUnit;
 use crate::args::CliOptions;
 use crate::args::TsConfig;
 use crate::args::TsConfigType;
@@ -20,10 +27,16 @@ use crate::args::TypeCheckMode;
 use crate::cache::Caches;
 use crate::cache::DenoDir;
 use crate::cache::FastInsecureHasher;
+use crate::cache::InferredTypesCache;
+use crate::cache::RemoteArtifactHasher;
+use crate::cache::RemoteArtifactKind;
+use crate::cache::RemoteBuildCache;
 use crate::cache::TypeCheckCache;
 use crate::node::CliNodeResolver;
 use crate::npm::NpmPackageResolver;
 use crate::tsc;
+use crate::util::progress_bar;
+use crate::util::telemetry;
 use crate::version;
 
 /// Options for performing a check of a module graph. Note that the decision to
@@ -44,6 +57,7 @@ pub struct TypeChecker {
   cli_options: Arc<CliOptions>,
   node_resolver: Arc<CliNodeResolver>,
   npm_resolver: Arc<NpmPackageResolver>,
+  maybe_remote_build_cache: Option<Arc<RemoteBuildCache>>,
 }
 
 impl TypeChecker {
@@ -53,6 +67,7 @@ impl TypeChecker {
     cli_options: Arc<CliOptions>,
     node_resolver: Arc<CliNodeResolver>,
     npm_resolver: Arc<NpmPackageResolver>,
+    maybe_remote_build_cache: Option<Arc<RemoteBuildCache>>,
   ) -> Self {
     Self {
       deno_dir,
@@ -60,6 +75,7 @@ impl TypeChecker {
       cli_options,
       node_resolver,
       npm_resolver,
+      maybe_remote_build_cache,
     }
   }
 
@@ -70,7 +86,7 @@ impl TypeChecker {
   pub async fn check(
     &self,
     graph: Arc<ModuleGraph>,
-    options: CheckOptions,
+    mut options: CheckOptions,
   ) -> Result<(), AnyError> {
     // node built-in specifiers use the @types/node package to determine
     // types, so inject that now (the caller should do this after the lockfile
@@ -82,45 +98,165 @@ impl TypeChecker {
         .await?;
     }
 
+    let check_units = self.cli_options.check_units();
+    let declarations = if check_units.is_empty() {
+      self.check_graph(graph.clone(), &options, None).await?
+    } else {
+      // check the units after the units they depend on, so that the check of
+      // an unchanged unit is cached and the errors of a unit are reported
+      // before the ones of its dependents
+      let mut declarations = Vec::new();
+      for (maybe_unit, roots) in group_roots_by_check_unit(&graph, check_units)?
+      {
+        let unit_graph = Arc::new(graph.segment(&roots));
+        // the modules of the units a unit depends on are emitted again with it
+        for declaration in
+          self.check_graph(unit_graph, &options, maybe_unit).await?
+        {
+          if !declarations.iter().any(|d: &tsc::EmittedDeclaration| {
+            d.specifier == declaration.specifier
+          }) {
+            declarations.push(declaration);
+          }
+        }
+        options.log_ignored_options = false;
+      }
+      declarations
+    };
+
+    if let Some(out_dir) = self.cli_options.declarations_out_dir() {
+      log::info!("{} {}", colors::green("Emit"), out_dir.display());
+      declarations::write_declarations(&graph, &declarations, &out_dir)?;
+    }
+    Ok(())
+  }
+
+  async fn check_graph(
+    &self,
+    graph: Arc<ModuleGraph>,
+    options: &CheckOptions,
+    maybe_unit: Option<&CheckUnit>,
+  ) -> Result<Vec<tsc::EmittedDeclaration>, AnyError> {
     log::debug!("Type checking.");
-    let ts_config_result = self
-      .cli_options
-      .resolve_ts_config_for_emit(TsConfigType::Check { lib: options.lib })?;
+    let ts_config_result =
+      self
+        .cli_options
+        .resolve_ts_config_for_emit(TsConfigType::Check {
+          lib: options.lib.clone(),
+        })?;
     if options.log_ignored_options {
       if let Some(ignored_options) = ts_config_result.maybe_ignored_options {
         log::warn!("{}", ignored_options);
       }
     }
 
-    let ts_config = ts_config_result.ts_config;
+    let mut ts_config = ts_config_result.ts_config;
+    // the libraries selected for the entry points take precedence over the
+    // "lib" compiler option
+    if let TsTypeLib::Custom(lib) = &options.lib {
+      ts_config.merge(&json!({ "lib": lib }));
+    }
+    // a referenced project is checked with its own compiler options, which
+    // override everything the root configuration file and its "libOverrides"
+    // set
+    let maybe_project = maybe_unit.and_then(|unit| unit.maybe_project.as_ref());
+    if let Some(project) = maybe_project {
+      ts_config.merge(&project.compiler_options);
+    }
+    let emit_declarations = self.cli_options.declarations_out_dir().is_some();
+    if emit_declarations {
+      ts_config.merge(&json!({
+        "declaration": true,
+        "declarationMap": true,
+        "emitDeclarationOnly": true,
+        "noEmit": false,
+      }));
+    }
     let type_check_mode = self.cli_options.type_check_mode();
     let debug = self.cli_options.log_level() == Some(log::Level::Debug);
     let cache =
       TypeCheckCache::new(self.caches.type_checking_cache_db(&self.deno_dir));
     let check_js = ts_config.get_check_js();
-    let check_hash = match get_check_hash(&graph, type_check_mode, &ts_config) {
-      CheckHashResult::NoFiles => return Ok(()),
-      CheckHashResult::Hash(hash) => hash,
+    let (check_hash, maybe_remote_check_key) = match get_check_hash(
+      &graph,
+      type_check_mode,
+      &ts_config,
+      self.maybe_remote_build_cache.as_deref(),
+    ) {
+      CheckHashResult::NoFiles => return Ok(Vec::new()),
+      CheckHashResult::Hash(hash, maybe_remote_key) => (hash, maybe_remote_key),
     };
 
-    // do not type check if we know this is type checked
-    if !options.reload && cache.has_check_hash(check_hash) {
-      return Ok(());
+    // while there might be multiple roots, we can't "merge" the build info, so we
+    // try to retrieve the build info for first root, which is the most common use
+    // case. A referenced project has the build info of its configuration file,
+    // which doesn't depend on which of its modules are checked.
+    let tsbuildinfo_specifier = match maybe_project {
+      Some(project) => &project.config_specifier,
+      None => &graph.roots[0],
+    };
+    let maybe_remote = self
+      .maybe_remote_build_cache
+      .as_ref()
+      .zip(maybe_remote_check_key);
+
+    // do not type check if we know this is type checked, unless the
+    // declarations need to be emitted
+    if !options.reload && !emit_declarations {
+      if cache.has_check_hash(check_hash) {
+        telemetry::record_cache("check", true);
+        return Ok(Vec::new());
+      }
+      if let Some((remote_build_cache, remote_check_key)) = &maybe_remote {
+        if let Some(tsbuildinfo) = remote_build_cache
+          .get(RemoteArtifactKind::Check, remote_check_key)
+          .await
+        {
+          // keep the build info, so that a later check of changes is faster
+          if let Ok(tsbuildinfo) = String::from_utf8(tsbuildinfo) {
+            if !tsbuildinfo.is_empty() {
+              cache.set_tsbuildinfo(
+                tsbuildinfo_specifier,
+                &remote_build_cache.to_local_text(&tsbuildinfo),
+              );
+            }
+          }
+          cache.add_check_hash(check_hash);
+          telemetry::record_cache("check", true);
+          return Ok(Vec::new());
+        }
+      }
     }
-
-    for root in &graph.roots {
-      let root_str = root.as_str();
-      log::info!("{} {}", colors::green("Check"), root_str);
+    telemetry::record_cache("check", false);
+    let mut span = telemetry::span("type_check");
+    if let Some(unit) = maybe_unit {
+      span.set_attribute("check.unit", &unit.name);
     }
 
+    // the "check" progress event replaces the log messages when enabled
+    let _check_phase = if progress_bar::json::is_enabled() {
+      let roots = graph.roots.iter().map(|r| r.as_str()).collect::<Vec<_>>();
+      let message = match maybe_unit {
+        Some(unit) => format!("unit {}", unit.name),
+        None => roots.join(", "),
+      };
+      Some(progress_bar::report_phase("check", &message))
+    } else if let Some(unit) = maybe_unit {
+      log::info!("{} unit {}", colors::green("Check"), unit.name);
+      None
+    } else {
+      for root in &graph.roots {
+        let root_str = root.as_str();
+        log::info!("{} {}", colors::green("Check"), root_str);
+      }
+      None
+    };
+
     let root_names = get_tsc_roots(&graph, check_js);
-    // while there might be multiple roots, we can't "merge" the build info, so we
-    // try to retrieve the build info for first root, which is the most common use
-    // case.
     let maybe_tsbuildinfo = if options.reload {
       None
     } else {
-      cache.get_tsbuildinfo(&graph.roots[0])
+      cache.get_tsbuildinfo(tsbuildinfo_specifier)
     };
     // to make tsc build info work, we need to consistently hash modules, so that
     // tsc can better determine if an emit is still valid or not, so we provide
@@ -141,14 +277,22 @@ impl TypeChecker {
       maybe_tsbuildinfo,
       root_names,
       check_mode: type_check_mode,
+      emit_declarations,
+      infer_remote_declarations: false,
     })?;
 
     let diagnostics = if type_check_mode == TypeCheckMode::Local {
+      // the declarations inferred for remote modules are not local modules
+      let inferred_types_cache =
+        InferredTypesCache::new(&self.deno_dir.inferred_types_folder_path());
       response.diagnostics.filter(|d| {
         if let Some(file_name) = &d.file_name {
           if !file_name.starts_with("http") {
             if ModuleSpecifier::parse(file_name)
-              .map(|specifier| !self.node_resolver.in_npm_package(&specifier))
+              .map(|specifier| {
+                !self.node_resolver.in_npm_package(&specifier)
+                  && !inferred_types_cache.contains(&specifier)
+              })
               .unwrap_or(true)
             {
               Some(d.clone())
@@ -166,26 +310,128 @@ impl TypeChecker {
       response.diagnostics
     };
 
-    if let Some(tsbuildinfo) = response.maybe_tsbuildinfo {
-      cache.set_tsbuildinfo(&graph.roots[0], &tsbuildinfo);
+    if let Some(tsbuildinfo) = &response.maybe_tsbuildinfo {
+      cache.set_tsbuildinfo(tsbuildinfo_specifier, tsbuildinfo);
     }
 
     if diagnostics.is_empty() {
       cache.add_check_hash(check_hash);
+      if let Some((remote_build_cache, remote_check_key)) = &maybe_remote {
+        let tsbuildinfo = response.maybe_tsbuildinfo.unwrap_or_default();
+        remote_build_cache
+          .put(
+            RemoteArtifactKind::Check,
+            remote_check_key,
+            remote_build_cache.to_portable_text(&tsbuildinfo).as_bytes(),
+          )
+          .await;
+      }
     }
 
     log::debug!("{}", response.stats);
 
     if diagnostics.is_empty() {
-      Ok(())
+      // only the declarations of the local modules are written
+      let declarations = response
+        .emitted_declarations
+        .into_iter()
+        .filter(|d| {
+          d.specifier.scheme() == "file"
+            && !self.node_resolver.in_npm_package(&d.specifier)
+        })
+        .collect();
+      Ok(declarations)
     } else {
       Err(diagnostics.into())
     }
   }
 }
 
+/// Groups the modules of the graph by the check unit that includes them, in
+/// the order of the units, followed by the roots of the graph that are not in
+/// a unit.
+///
+/// Errors when a module imports a module of a unit that its unit does not
+/// depend on.
+fn group_roots_by_check_unit<'a>(
+  graph: &ModuleGraph,
+  check_units: &'a [CheckUnit],
+) -> Result<Vec<(Option<&'a CheckUnit>, Vec<ModuleSpecifier>)>, AnyError> {
+  let find_unit_index = |specifier: &ModuleSpecifier| {
+    check_units
+      .iter()
+      .enumerate()
+      .filter_map(|(index, unit)| Some((index, unit.match_len(specifier)?)))
+      .max_by_key(|(_, match_len)| *match_len)
+      .map(|(index, _)| index)
+  };
+
+  let mut unit_roots = vec![Vec::new(); check_units.len()];
+  for module in graph.modules() {
+    let module = match module {
+      Module::Esm(module) => module,
+      Module::Json(_)
+      | Module::External(_)
+      | Module::Node(_)
+      | Module::Npm(_) => continue,
+    };
+    let unit_index = match find_unit_index(&module.specifier) {
+      Some(index) => index,
+      None => continue,
+    };
+    let unit = &check_units[unit_index];
+    for dependency in module.dependencies.values() {
+      let specifiers = dependency
+        .get_code()
+        .into_iter()
+        .chain(dependency.get_type());
+      for specifier in specifiers {
+        let specifier = graph.resolve(specifier);
+        let dependency_unit = match find_unit_index(&specifier) {
+          Some(index) if index != unit_index => &check_units[index],
+          _ => continue,
+        };
+        if !unit.dependencies.contains(&dependency_unit.name) {
+          bail!(
+            concat!(
+              "Module \"{}\" of the check unit \"{}\" imports \"{}\" of the check unit \"{}\", ",
+              "which is not one of its dependencies.\n",
+              "  Add \"{}\" to the \"dependencies\" of the unit in the configuration file.",
+            ),
+            module.specifier,
+            unit.name,
+            specifier,
+            dependency_unit.name,
+            dependency_unit.name,
+          );
+        }
+      }
+    }
+    unit_roots[unit_index].push(module.specifier.clone());
+  }
+
+  let mut groups = check_units
+    .iter()
+    .zip(unit_roots)
+    .filter(|(_, roots)| !roots.is_empty())
+    .map(|(unit, roots)| (Some(unit), roots))
+    .collect::<Vec<_>>();
+  let other_roots = graph
+    .roots
+    .iter()
+    .filter(|root| find_unit_index(&graph.resolve(root)).is_none())
+    .cloned()
+    .collect::<Vec<_>>();
+  if !other_roots.is_empty() {
+    groups.push((None, other_roots));
+  }
+  Ok(groups)
+}
+
 enum CheckHashResult {
-  Hash(u64),
+  /// The hash, along with the key of the check in the remote cache when
+  /// there's one.
+  Hash(u64, Option<String>),
   NoFiles,
 }
 
@@ -195,14 +441,27 @@ fn get_check_hash(
   graph: &ModuleGraph,
   type_check_mode: TypeCheckMode,
   ts_config: &TsConfig,
+  maybe_remote_build_cache: Option<&RemoteBuildCache>,
 ) -> CheckHashResult {
   let mut hasher = FastInsecureHasher::new();
-  hasher.write_u8(match type_check_mode {
+  // the key in the remote cache has the same inputs, but it's a hash that
+  // can't be made to collide
+  let mut maybe_remote_hasher =
+    maybe_remote_build_cache.map(|remote_build_cache| {
+      (remote_build_cache, RemoteArtifactHasher::new())
+    });
+  let type_check_mode = match type_check_mode {
     TypeCheckMode::All => 0,
     TypeCheckMode::Local => 1,
     TypeCheckMode::None => 2,
-  });
+  };
+  hasher.write_u8(type_check_mode);
   hasher.write(&ts_config.as_bytes());
+  if let Some((_, remote_hasher)) = &mut maybe_remote_hasher {
+    remote_hasher
+      .write(&[type_check_mode])
+      .write(&ts_config.as_bytes());
+  }
 
   let check_js = ts_config.get_check_js();
   let mut sorted_modules = graph.modules().collect::<Vec<_>>();
@@ -246,6 +505,13 @@ fn get_check_hash(
 
         hasher.write_str(module.specifier.as_str());
         hasher.write_str(&module.source);
+        if let Some((remote_build_cache, remote_hasher)) =
+          &mut maybe_remote_hasher
+        {
+          remote_hasher
+            .write_str(&remote_build_cache.key_specifier(&module.specifier))
+            .write_str(&module.source);
+        }
       }
       Module::Json(_)
       | Module::External(_)
@@ -260,7 +526,12 @@ fn get_check_hash(
     // no files to type check
     CheckHashResult::NoFiles
   } else {
-    CheckHashResult::Hash(hasher.finish())
+    // the check results shared with the remote cache only apply to the same
+    // CLI version, unlike the local ones that are cleared when upgrading
+    let maybe_remote_key = maybe_remote_hasher.map(|(_, mut remote_hasher)| {
+      remote_hasher.write_str(version::deno()).finish()
+    });
+    CheckHashResult::Hash(hasher.finish(), maybe_remote_key)
   }
 }
 
@@ -0,0 +1,500 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Finds the exports of the local modules of a graph that no module of the
+//! graph imports, for `deno check --unused`.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use deno_ast::swc::ast::ExportSpecifier;
+use deno_ast::swc::ast::ImportSpecifier;
+use deno_ast::swc::ast::ModuleDecl;
+use deno_ast::swc::ast::ModuleItem;
+use deno_ast::swc::ast::TsEntityName;
+use deno_ast::swc::ast::TsImportType;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::ModuleSpecifier;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_ast::SourceRangedForSpanned;
+use deno_core::error::AnyError;
+use deno_core::serde_json::json;
+use deno_graph::EsmModule;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_runtime::colors;
+use serde::Serialize;
+
+use crate::cache::ParsedSourceCache;
+use crate::tools::doc::references::decl_names;
+use crate::tools::doc::references::export_name;
+use crate::util::display::write_json_to_stdout;
+
+/// An export of a local module that is not imported by any module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnusedExport {
+  pub specifier: ModuleSpecifier,
+  pub name: String,
+  /// The one-based line of the export.
+  pub line: usize,
+  /// The one-based column of the export.
+  pub column: usize,
+}
+
+#[derive(Debug, Default)]
+struct ModuleExports {
+  /// The names the module exports, with the position of their export.
+  exports: Vec<(String, SourcePos)>,
+  /// The modules that are re-exported with `export * from "..."`.
+  star_exports: Vec<ModuleSpecifier>,
+}
+
+impl ModuleExports {
+  fn add(&mut self, name: String, pos: SourcePos) {
+    // declarations can be merged, like interfaces and namespaces
+    if !self.exports.iter().any(|(export, _)| *export == name) {
+      self.exports.push((name, pos));
+    }
+  }
+}
+
+/// The names of the exports of each module that are imported.
+#[derive(Debug, Default)]
+struct UsedExports {
+  names: HashMap<ModuleSpecifier, HashSet<String>>,
+  /// The modules that all the exports are used of, like the roots and the
+  /// modules imported as namespaces or dynamically.
+  all: HashSet<ModuleSpecifier>,
+}
+
+impl UsedExports {
+  fn add_name(&mut self, specifier: &ModuleSpecifier, name: String) -> bool {
+    if self.all.contains(specifier) {
+      return false;
+    }
+    self
+      .names
+      .entry(specifier.clone())
+      .or_default()
+      .insert(name)
+  }
+
+  fn add_all(&mut self, specifier: &ModuleSpecifier) -> bool {
+    self.all.insert(specifier.clone())
+  }
+
+  fn is_used(&self, specifier: &ModuleSpecifier, name: &str) -> bool {
+    self.all.contains(specifier)
+      || self
+        .names
+        .get(specifier)
+        .map(|names| names.contains(name))
+        .unwrap_or(false)
+  }
+}
+
+/// Finds the exports of the local modules of the graph that are not imported
+/// by any module of the graph. The exports of the roots are their public API,
+/// so they are considered used.
+///
+/// Namespace imports, dynamic imports and `export * as ns from "..."` use all
+/// the exports of the imported module, because the names they access can't
+/// be determined statically.
+pub fn find_unused_exports(
+  graph: &ModuleGraph,
+  parsed_source_cache: &ParsedSourceCache,
+) -> Vec<UnusedExport> {
+  let mut modules = HashMap::new();
+  let mut used = UsedExports::default();
+  for root in &graph.roots {
+    used.add_all(&graph.resolve(root));
+  }
+  for module in graph.modules() {
+    let module = match module {
+      Module::Esm(module) => module,
+      _ => continue,
+    };
+    let parsed_source =
+      match parsed_source_cache.get_parsed_source_from_esm_module(module) {
+        Ok(parsed_source) => parsed_source,
+        Err(_) => continue,
+      };
+    let exports = analyze_module(graph, module, &parsed_source, &mut used);
+    if module.specifier.scheme() == "file" {
+      modules.insert(module.specifier.clone(), (exports, parsed_source));
+    }
+  }
+
+  // the names that are used of a module, but it doesn't export itself, are
+  // used of the modules it re-exports all the exports of
+  loop {
+    let mut changed = false;
+    for (specifier, (exports, _)) in &modules {
+      for star_export in &exports.star_exports {
+        if used.all.contains(specifier) {
+          changed |= used.add_all(star_export);
+          continue;
+        }
+        let names = used
+          .names
+          .get(specifier)
+          .into_iter()
+          .flatten()
+          .filter(|name| {
+            *name != "default"
+              && !exports.exports.iter().any(|(export, _)| export == *name)
+          })
+          .cloned()
+          .collect::<Vec<_>>();
+        for name in names {
+          changed |= used.add_name(star_export, name);
+        }
+      }
+    }
+    if !changed {
+      break;
+    }
+  }
+
+  let mut unused_exports = Vec::new();
+  for (specifier, (exports, parsed_source)) in &modules {
+    for (name, pos) in &exports.exports {
+      if used.is_used(specifier, name) {
+        continue;
+      }
+      let display = parsed_source.text_info().line_and_column_display(*pos);
+      unused_exports.push(UnusedExport {
+        specifier: specifier.clone(),
+        name: name.clone(),
+        line: display.line_number,
+        column: display.column_number,
+      });
+    }
+  }
+  unused_exports.sort_by(|a, b| {
+    (&a.specifier, a.line, a.column).cmp(&(&b.specifier, b.line, b.column))
+  });
+  unused_exports
+}
+
+/// Prints the unused exports and fails when there are any.
+pub fn report_unused_exports(
+  unused_exports: &[UnusedExport],
+  json: bool,
+) -> Result<(), AnyError> {
+  if json {
+    write_json_to_stdout(&json!({ "unusedExports": unused_exports }))?;
+  } else {
+    for unused_export in unused_exports {
+      log::error!(
+        "{} {} is not imported by any module\n    at {}",
+        colors::red_bold("unused export"),
+        colors::bold(&unused_export.name),
+        colors::cyan(format!(
+          "{}:{}:{}",
+          unused_export.specifier, unused_export.line, unused_export.column
+        )),
+      );
+    }
+  }
+  match unused_exports.len() {
+    0 => Ok(()),
+    1 => Err(deno_core::anyhow::anyhow!("Found 1 unused export.")),
+    len => Err(deno_core::anyhow::anyhow!("Found {len} unused exports.")),
+  }
+}
+
+/// Collects the exports of the module and adds the exports it uses of the
+/// modules it imports.
+fn analyze_module(
+  graph: &ModuleGraph,
+  module: &EsmModule,
+  parsed_source: &ParsedSource,
+  used: &mut UsedExports,
+) -> ModuleExports {
+  // the exports are used of both the module and the module providing its
+  // types, like with `@deno-types`
+  let resolve_dependency = |src: &str| {
+    let mut specifiers = Vec::new();
+    if let Some(dependency) = module.dependencies.get(src) {
+      for resolution in [&dependency.maybe_code, &dependency.maybe_type] {
+        if let Some(module) = resolution
+          .maybe_specifier()
+          .and_then(|specifier| graph.get(specifier))
+        {
+          specifiers.push(module.specifier().clone());
+        }
+      }
+    }
+    specifiers
+  };
+
+  for dependency in module.dependencies.values() {
+    if !dependency.is_dynamic {
+      continue;
+    }
+    if let Some(specifier) = dependency.maybe_code.maybe_specifier() {
+      used.add_all(&graph.resolve(specifier));
+    }
+  }
+
+  let mut exports = ModuleExports::default();
+  for item in &parsed_source.module().body {
+    let module_decl = match item {
+      ModuleItem::ModuleDecl(module_decl) => module_decl,
+      ModuleItem::Stmt(_) => continue,
+    };
+    match module_decl {
+      ModuleDecl::ExportDecl(export_decl) => {
+        for name in decl_names(&export_decl.decl) {
+          exports.add(name, export_decl.range().start);
+        }
+      }
+      ModuleDecl::ExportDefaultDecl(export_default_decl) => {
+        exports.add("default".to_string(), export_default_decl.range().start);
+      }
+      ModuleDecl::ExportDefaultExpr(export_default_expr) => {
+        exports.add("default".to_string(), export_default_expr.range().start);
+      }
+      ModuleDecl::Import(import_decl) => {
+        let specifiers = resolve_dependency(&import_decl.src.value);
+        for import_specifier in &import_decl.specifiers {
+          for specifier in &specifiers {
+            match import_specifier {
+              ImportSpecifier::Named(named) => {
+                let name = named
+                  .imported
+                  .as_ref()
+                  .map(export_name)
+                  .unwrap_or_else(|| named.local.sym.to_string());
+                used.add_name(specifier, name);
+              }
+              ImportSpecifier::Default(_) => {
+                used.add_name(specifier, "default".to_string());
+              }
+              ImportSpecifier::Namespace(_) => {
+                used.add_all(specifier);
+              }
+            }
+          }
+        }
+      }
+      ModuleDecl::ExportNamed(named_export) => {
+        let specifiers = named_export
+          .src
+          .as_ref()
+          .map(|src| resolve_dependency(&src.value))
+          .unwrap_or_default();
+        for export_specifier in &named_export.specifiers {
+          let (exported, start) = match export_specifier {
+            ExportSpecifier::Named(named) => {
+              for specifier in &specifiers {
+                used.add_name(specifier, export_name(&named.orig));
+              }
+              (
+                export_name(named.exported.as_ref().unwrap_or(&named.orig)),
+                named.range().start,
+              )
+            }
+            ExportSpecifier::Namespace(namespace) => {
+              for specifier in &specifiers {
+                used.add_all(specifier);
+              }
+              (export_name(&namespace.name), namespace.range().start)
+            }
+            ExportSpecifier::Default(default) => {
+              for specifier in &specifiers {
+                used.add_name(specifier, "default".to_string());
+              }
+              (
+                default.exported.sym.to_string(),
+                default.exported.range().start,
+              )
+            }
+          };
+          exports.add(exported, start);
+        }
+      }
+      ModuleDecl::ExportAll(export_all) => {
+        exports
+          .star_exports
+          .extend(resolve_dependency(&export_all.src.value));
+      }
+      _ => {}
+    }
+  }
+
+  // the exports referenced by types like `import("./mod.ts").Foo`, which
+  // use all the exports when there's no qualifier like in
+  // `typeof import("./mod.ts")`
+  let mut import_types = ImportTypeCollector::default();
+  parsed_source.module().visit_with(&mut import_types);
+  for (src, maybe_name) in import_types.0 {
+    for specifier in resolve_dependency(&src) {
+      match &maybe_name {
+        Some(name) => used.add_name(&specifier, name.clone()),
+        None => used.add_all(&specifier),
+      };
+    }
+  }
+  exports
+}
+
+/// Collects the specifiers of the import types of a module with the name of
+/// the export they reference, if any.
+#[derive(Default)]
+struct ImportTypeCollector(Vec<(String, Option<String>)>);
+
+impl Visit for ImportTypeCollector {
+  fn visit_ts_import_type(&mut self, node: &TsImportType) {
+    let maybe_name = node.qualifier.as_ref().map(|qualifier| {
+      let mut entity_name = qualifier;
+      loop {
+        match entity_name {
+          TsEntityName::TsQualifiedName(qualified_name) => {
+            entity_name = &qualified_name.left;
+          }
+          TsEntityName::Ident(ident) => break ident.sym.to_string(),
+        }
+      }
+    });
+    self.0.push((node.arg.value.to_string(), maybe_name));
+    node.visit_children_with(self);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use deno_graph::source::MemoryLoader;
+  use deno_graph::source::Source;
+
+  async fn unused_exports(sources: Vec<(&str, &str)>) -> Vec<(String, String)> {
+    let mut loader = MemoryLoader::new(
+      sources
+        .into_iter()
+        .map(|(specifier, content)| {
+          (
+            specifier.to_string(),
+            Source::Module {
+              specifier: specifier.to_string(),
+              content: content.to_string(),
+              maybe_headers: None,
+            },
+          )
+        })
+        .collect(),
+      Vec::new(),
+    );
+    let mut graph = ModuleGraph::default();
+    graph
+      .build(
+        vec![ModuleSpecifier::parse("file:///mod.ts").unwrap()],
+        &mut loader,
+        Default::default(),
+      )
+      .await;
+    let parsed_source_cache = ParsedSourceCache::new_in_memory();
+    find_unused_exports(&graph, &parsed_source_cache)
+      .into_iter()
+      .map(|unused| (unused.specifier.to_string(), unused.name))
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn finds_unused_exports() {
+    let unused = unused_exports(vec![
+      (
+        "file:///mod.ts",
+        r#"import { used, Renamed as Local } from "./a.ts";
+import value from "./b.ts";
+import * as ns from "./c.ts";
+export { reexported } from "./d.ts";
+export const rootExport = used + value;
+"#,
+      ),
+      (
+        "file:///a.ts",
+        r#"export const used = 1;
+export const unused = 2;
+export function unusedFn() {}
+export interface Renamed {}
+"#,
+      ),
+      ("file:///b.ts", "export default 1;\nexport type Unused = 1;"),
+      ("file:///c.ts", "export const viaNamespace = 1;"),
+      (
+        "file:///d.ts",
+        "export const reexported = 1;\nexport const other = 1;",
+      ),
+    ])
+    .await;
+    assert_eq!(
+      unused,
+      vec![
+        ("file:///a.ts".to_string(), "unused".to_string()),
+        ("file:///a.ts".to_string(), "unusedFn".to_string()),
+        ("file:///b.ts".to_string(), "Unused".to_string()),
+        ("file:///d.ts".to_string(), "other".to_string()),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn follows_star_exports() {
+    let unused = unused_exports(vec![
+      (
+        "file:///mod.ts",
+        r#"import { a } from "./barrel.ts";
+await import("./dynamic.ts");
+console.log(a);
+"#,
+      ),
+      (
+        "file:///barrel.ts",
+        r#"export * from "./a.ts";
+export * from "./root_only.ts";
+"#,
+      ),
+      ("file:///a.ts", "export const a = 1;\nexport const b = 2;"),
+      ("file:///root_only.ts", "export const c = 1;"),
+      ("file:///dynamic.ts", "export const d = 1;"),
+    ])
+    .await;
+    assert_eq!(
+      unused,
+      vec![
+        ("file:///a.ts".to_string(), "b".to_string()),
+        ("file:///root_only.ts".to_string(), "c".to_string()),
+      ]
+    );
+  }
+
+  #[tokio::test]
+  async fn counts_import_types() {
+    let unused = unused_exports(vec![
+      (
+        "file:///mod.ts",
+        r#"export type A = import("./a.ts").Options;
+export type B = import("./b.ts").ns.Value;
+export type C = typeof import("./c.ts");
+"#,
+      ),
+      (
+        "file:///a.ts",
+        "export interface Options {}\nexport interface Other {}",
+      ),
+      (
+        "file:///b.ts",
+        "export namespace ns { export type Value = 1; }",
+      ),
+      ("file:///c.ts", "export const c = 1;"),
+    ])
+    .await;
+    assert_eq!(
+      unused,
+      vec![("file:///a.ts".to_string(), "Other".to_string())]
+    );
+  }
+}
@@ -0,0 +1,221 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_runtime::colors;
+
+use crate::args::CleanFlags;
+use crate::args::CliOptions;
+use crate::args::Flags;
+use crate::cache::base_url_to_filename;
+use crate::cache::CacheStore;
+use crate::cache::DenoDir;
+use crate::util::display;
+use crate::util::fs::dir_size;
+
+/// Deletes the caches of `DENO_DIR` selected by the flags, or all of them
+/// when no scope is provided. The data that isn't a cache, like the origin
+/// storage, the REPL history and the bench baselines, is always kept.
+pub fn clean(flags: Flags, clean_flags: CleanFlags) -> Result<(), AnyError> {
+  let cli_options = CliOptions::from_flags(flags)?;
+  let deno_dir = cli_options.resolve_deno_dir()?;
+  let maybe_store =
+    CacheStore::for_cache_location(&deno_dir.gen_cache.location);
+  let targets =
+    resolve_targets(&deno_dir, &clean_flags, maybe_store.is_some())?;
+
+  let mut total_size = 0;
+  let mut removed_count = 0;
+  for path in targets {
+    let size = path_size(&path, maybe_store.as_ref())
+      .with_context(|| format!("Failed reading '{}'", path.display()))?;
+    // empty folders and files are left in place
+    if size == 0 {
+      continue;
+    }
+    if !clean_flags.dry_run {
+      remove_path(&path, maybe_store.as_ref())
+        .with_context(|| format!("Failed removing '{}'", path.display()))?;
+    }
+    log::info!(
+      "{} {} ({})",
+      action_label(clean_flags.dry_run),
+      path.display(),
+      display::human_size(size as f64),
+    );
+    total_size += size;
+    removed_count += 1;
+  }
+
+  if removed_count == 0 {
+    log::info!("Nothing to clean in {}", deno_dir.root_path_for_display());
+  } else {
+    log::info!(
+      "{} {} in total",
+      action_label(clean_flags.dry_run),
+      display::human_size(total_size as f64),
+    );
+  }
+  Ok(())
+}
+
+fn action_label(dry_run: bool) -> String {
+  if dry_run {
+    colors::yellow("Would remove").to_string()
+  } else {
+    colors::green("Removed").to_string()
+  }
+}
+
+/// Gets the paths to delete, leaving out the ones inside of other paths.
+fn resolve_targets(
+  deno_dir: &DenoDir,
+  clean_flags: &CleanFlags,
+  uses_store: bool,
+) -> Result<Vec<PathBuf>, AnyError> {
+  let mut paths = Vec::new();
+  let is_scoped =
+    clean_flags.emits || clean_flags.npm || !clean_flags.origins.is_empty();
+  if !is_scoped {
+    paths.extend([
+      deno_dir.deps_folder_path(),
+      deno_dir.gen_cache.location.clone(),
+      deno_dir.npm_folder_path(),
+      deno_dir.registries_folder_path(),
+      deno_dir.inferred_types_folder_path(),
+      deno_dir.dl_folder_path(),
+    ]);
+    let mut db_paths = vec![
+      deno_dir.fmt_incremental_cache_db_file_path(),
+      deno_dir.lint_incremental_cache_db_file_path(),
+      deno_dir.dep_analysis_db_file_path(),
+      deno_dir.node_analysis_db_file_path(),
+      deno_dir.type_checking_cache_db_file_path(),
+    ];
+    // the database of the store is in use, so its entries are removed
+    // instead of the file
+    if !uses_store {
+      db_paths.push(deno_dir.cache_store_db_file_path());
+    }
+    for db_path in db_paths {
+      paths.push(journal_file_path(&db_path));
+      paths.push(db_path);
+    }
+  }
+  if clean_flags.emits {
+    paths.push(deno_dir.gen_cache.location.clone());
+  }
+  if clean_flags.npm {
+    paths.push(deno_dir.npm_folder_path());
+  }
+  for origin in &clean_flags.origins {
+    for folder in origin_folders(origin)? {
+      paths.extend([
+        deno_dir.deps_folder_path().join(&folder),
+        deno_dir.gen_cache.location.join(&folder),
+        deno_dir.inferred_types_folder_path().join(&folder),
+      ]);
+    }
+  }
+
+  let mut targets: Vec<PathBuf> = Vec::with_capacity(paths.len());
+  for path in paths {
+    if !targets.iter().any(|target| path.starts_with(target)) {
+      targets.push(path);
+    }
+  }
+  Ok(targets)
+}
+
+fn journal_file_path(db_path: &Path) -> PathBuf {
+  let mut file_name = db_path.file_name().unwrap().to_os_string();
+  file_name.push("-journal");
+  db_path.with_file_name(file_name)
+}
+
+/// Gets the folders of the caches for an origin, relative to their root.
+/// Both `http` and `https` are used when the origin has no scheme.
+fn origin_folders(origin: &str) -> Result<Vec<PathBuf>, AnyError> {
+  let urls = if origin.contains("://") {
+    vec![origin.to_string()]
+  } else {
+    vec![format!("https://{origin}"), format!("http://{origin}")]
+  };
+  let mut folders = Vec::with_capacity(urls.len());
+  for url in urls {
+    let url =
+      Url::parse(&url).with_context(|| format!("Invalid origin '{origin}'"))?;
+    if !matches!(url.scheme(), "http" | "https")
+      || url.path() != "/"
+      || url.query().is_some()
+    {
+      bail!(
+        "Invalid origin '{}', expected a host like 'esm.sh' or an origin like 'https://esm.sh'",
+        origin
+      );
+    }
+    folders.extend(base_url_to_filename(&url));
+  }
+  Ok(folders)
+}
+
+fn path_size(
+  path: &Path,
+  maybe_store: Option<&CacheStore>,
+) -> std::io::Result<u64> {
+  let mut size = match fs::metadata(path) {
+    Ok(metadata) if metadata.is_dir() => dir_size(path)?,
+    Ok(metadata) => metadata.len(),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+    Err(err) => return Err(err),
+  };
+  if let Some(store) = maybe_store {
+    size += store.folder_size(path)?;
+  }
+  Ok(size)
+}
+
+fn remove_path(
+  path: &Path,
+  maybe_store: Option<&CacheStore>,
+) -> std::io::Result<()> {
+  match fs::metadata(path) {
+    Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path)?,
+    Ok(_) => fs::remove_file(path)?,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+    Err(err) => return Err(err),
+  }
+  if let Some(store) = maybe_store {
+    store.remove_folder(path)?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_origin_folders() {
+    assert_eq!(
+      origin_folders("esm.sh").unwrap(),
+      vec![PathBuf::from("https/esm.sh"), PathBuf::from("http/esm.sh")]
+    );
+    assert_eq!(
+      origin_folders("http://localhost:4545").unwrap(),
+      vec![PathBuf::from("http/localhost_PORT4545")]
+    );
+    assert_eq!(
+      origin_folders("https://deno.land/").unwrap(),
+      vec![PathBuf::from("https/deno.land")]
+    );
+    assert!(origin_folders("https://deno.land/std").is_err());
+    assert!(origin_folders("file:///tmp").is_err());
+  }
+}
@@ -0,0 +1,697 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::FixedOffset;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Timelike;
+use chrono::Utc;
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::located_script_name;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_runtime::colors;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::worker::MainWorker;
+
+use crate::args::Flags;
+use crate::proc_state::ProcState;
+use crate::util;
+use crate::util::checksum;
+use crate::util::time::utc_now;
+use crate::worker::create_main_worker;
+
+/// Validates the schedules of the module namespace passed to it, returning
+/// them along with a function that runs the handler of a schedule. The
+/// promise of a run resolves to whether the handler succeeded.
+const REGISTER_SCHEDULES_SOURCE: &str = r#"(namespace) => {
+  const schedules = namespace.default?.schedules;
+  if (!Array.isArray(schedules)) {
+    throw new TypeError(
+      "The default export of the module must be an object with a schedules array.",
+    );
+  }
+  const handlers = new Map();
+  for (const schedule of schedules) {
+    if (
+      typeof schedule?.name !== "string" ||
+      typeof schedule.schedule !== "string" ||
+      typeof schedule.handler !== "function"
+    ) {
+      throw new TypeError(
+        "A schedule must have a name, a cron schedule and a handler function.",
+      );
+    }
+    if (handlers.has(schedule.name)) {
+      throw new TypeError(
+        `The schedule "${schedule.name}" is registered more than once.`,
+      );
+    }
+    handlers.set(schedule.name, schedule.handler);
+  }
+  return {
+    schedules: schedules.map(({ name, schedule, overlap, timezone }) => ({
+      name,
+      schedule,
+      overlap: overlap ?? "skip",
+      timezone: timezone ?? "UTC",
+    })),
+    async run(name) {
+      try {
+        await handlers.get(name)();
+        return true;
+      } catch (error) {
+        console.error(`Cron "${name}" failed:`, error);
+        return false;
+      }
+    },
+  };
+}"#;
+
+/// How often the scheduler checks whether the runs completed while the event
+/// loop is busy.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+pub async fn run_schedules(flags: Flags) -> Result<i32, AnyError> {
+  if flags.watch.is_some() {
+    return run_schedules_with_watch(flags).await;
+  }
+
+  let ps = ProcState::from_flags(flags).await?;
+  let main_module = ps.options.resolve_main_module()?;
+  run_scheduler(&ps, &main_module).await
+}
+
+async fn run_schedules_with_watch(flags: Flags) -> Result<i32, AnyError> {
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  let mut ps =
+    ProcState::from_flags_for_file_watcher(flags, sender.clone()).await?;
+  let clear_screen = !ps.options.no_clear_screen();
+  let filter = ps.options.watch_filter()?;
+  let debounce = ps.options.watch_debounce()?;
+  let before_restart = crate::tools::task::before_restart_hook(&ps.options)?;
+  let main_module = ps.options.resolve_main_module()?;
+
+  let operation = |main_module: ModuleSpecifier| {
+    ps.reset_for_file_watcher();
+    let ps = ps.clone();
+    Ok(async move {
+      run_scheduler(&ps, &main_module).await?;
+      Ok(())
+    })
+  };
+
+  util::file_watcher::watch_func2(
+    receiver,
+    operation,
+    main_module,
+    util::file_watcher::PrintConfig {
+      job_name: "Scheduler".to_string(),
+      clear_screen,
+      filter,
+      debounce,
+      before_restart,
+    },
+  )
+  .await?;
+
+  Ok(0)
+}
+
+/// What happens when a schedule is due while its previous run didn't
+/// complete yet.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum OverlapPolicy {
+  Skip,
+  Allow,
+  Queue,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleEntry {
+  name: String,
+  schedule: String,
+  overlap: OverlapPolicy,
+  timezone: String,
+}
+
+struct ScheduledJob {
+  name: String,
+  schedule: CronSchedule,
+  overlap: OverlapPolicy,
+  next_run: Option<DateTime<Utc>>,
+  runs: Vec<v8::Global<v8::Promise>>,
+  queued: bool,
+}
+
+/// Runs the handlers of the schedules of the module when they are due, until
+/// none of them has another run.
+async fn run_scheduler(
+  ps: &ProcState,
+  main_module: &ModuleSpecifier,
+) -> Result<i32, AnyError> {
+  let permissions = PermissionsContainer::new(Permissions::from_options(
+    &ps.options.permissions_options(),
+  )?);
+  let mut worker =
+    create_main_worker(ps, main_module.clone(), permissions).await?;
+  let namespace = worker.execute_main_module_for_namespace().await?;
+  let mut worker = worker.into_main_worker();
+  let (entries, run_fn) = register_schedules(&mut worker, namespace)?;
+  if entries.is_empty() {
+    bail!("The module didn't register any schedules.");
+  }
+
+  let state_path = ps
+    .dir
+    .cron_state_folder_path()
+    .join(format!("{}.json", checksum::gen(&[main_module.as_str()])));
+  let mut state = CronState::load(&state_path);
+  let now = utc_now();
+  let mut jobs = Vec::with_capacity(entries.len());
+  for entry in entries {
+    let schedule = parse_timezone(&entry.timezone)
+      .and_then(|offset| {
+        Ok(CronSchedule::parse(&entry.schedule)?.with_offset(offset))
+      })
+      .with_context(|| {
+        format!("Failed registering the schedule \"{}\"", entry.name)
+      })?;
+    // the next run follows the last run, so a restart neither repeats a run
+    // nor skips the one that was due while the scheduler wasn't running
+    let next_run =
+      schedule.next_after(state.last_run(&entry.name).unwrap_or(now));
+    match next_run {
+      Some(next_run) if next_run <= now => log::info!(
+        "{} {} ({}) missed a run at {}, which runs now",
+        colors::green("Cron"),
+        entry.name,
+        entry.schedule,
+        next_run.to_rfc3339(),
+      ),
+      Some(next_run) => log::info!(
+        "{} {} ({}) next runs at {}",
+        colors::green("Cron"),
+        entry.name,
+        entry.schedule,
+        next_run.to_rfc3339(),
+      ),
+      None => log::warn!(
+        "{} The schedule \"{}\" ({}) never runs.",
+        colors::yellow("Warning"),
+        entry.name,
+        entry.schedule,
+      ),
+    }
+    jobs.push(ScheduledJob {
+      name: entry.name,
+      schedule,
+      overlap: entry.overlap,
+      next_run,
+      runs: Vec::new(),
+      queued: false,
+    });
+  }
+  worker.dispatch_load_event(located_script_name!())?;
+
+  let mut event_loop_idle = false;
+  loop {
+    let now = utc_now();
+    for job in &mut jobs {
+      for succeeded in take_finished_runs(&mut worker, &mut job.runs) {
+        state.record_result(&job.name, succeeded);
+        state.save(&state_path)?;
+      }
+      let is_due = job.next_run.map(|next| next <= now).unwrap_or(false);
+      if is_due {
+        job.next_run = job.schedule.next_after(now);
+      }
+      let should_run = if job.queued && job.runs.is_empty() {
+        job.queued = false;
+        true
+      } else if is_due && !job.runs.is_empty() {
+        match job.overlap {
+          OverlapPolicy::Skip => {
+            log::warn!(
+              "{} Skipped a run of \"{}\" because its previous run didn't complete.",
+              colors::yellow("Warning"),
+              job.name,
+            );
+            false
+          }
+          OverlapPolicy::Queue => {
+            job.queued = true;
+            false
+          }
+          OverlapPolicy::Allow => true,
+        }
+      } else {
+        is_due
+      };
+      if should_run {
+        log::info!("{} {}", colors::green("Cron"), job.name);
+        job.runs.push(start_run(&mut worker, &run_fn, &job.name)?);
+        state.record_start(&job.name, now);
+        state.save(&state_path)?;
+        event_loop_idle = false;
+      }
+    }
+
+    let next_run = jobs.iter().filter_map(|job| job.next_run).min();
+    let has_runs = jobs.iter().any(|job| !job.runs.is_empty() || job.queued);
+    if next_run.is_none() && !has_runs && event_loop_idle {
+      break;
+    }
+    let sleep_duration = match next_run {
+      Some(next_run) => (next_run - utc_now())
+        .to_std()
+        .unwrap_or_default()
+        .min(POLL_INTERVAL),
+      None => POLL_INTERVAL,
+    };
+    tokio::select! {
+      _ = tokio::time::sleep(sleep_duration) => {},
+      result = worker.run_event_loop(false), if !event_loop_idle => {
+        result?;
+        event_loop_idle = true;
+      }
+    }
+  }
+
+  worker.dispatch_unload_event(located_script_name!())?;
+  Ok(worker.exit_code())
+}
+
+fn register_schedules(
+  worker: &mut MainWorker,
+  namespace: v8::Global<v8::Object>,
+) -> Result<(Vec<ScheduleEntry>, v8::Global<v8::Function>), AnyError> {
+  let register = worker.js_runtime.execute_script(
+    located_script_name!(),
+    REGISTER_SCHEDULES_SOURCE.to_string().into(),
+  )?;
+  let scope = &mut worker.js_runtime.handle_scope();
+  let namespace = v8::Local::new(scope, namespace);
+  let register = v8::Local::new(scope, register);
+  let register = v8::Local::<v8::Function>::try_from(register)?;
+  let tc_scope = &mut v8::TryCatch::new(scope);
+  let recv = v8::undefined(tc_scope).into();
+  let registration = match register.call(tc_scope, recv, &[namespace.into()]) {
+    Some(value) => v8::Local::<v8::Object>::try_from(value)?,
+    None => {
+      let exception = tc_scope.exception().unwrap();
+      return Err(JsError::from_v8_exception(tc_scope, exception).into());
+    }
+  };
+  let key = v8::String::new(tc_scope, "schedules").unwrap();
+  let schedules = registration.get(tc_scope, key.into()).unwrap();
+  let schedules = serde_v8::from_v8(tc_scope, schedules).context(
+    "Invalid schedules. The overlap must be \"skip\", \"allow\" or \"queue\", and the timezone a string.",
+  )?;
+  let key = v8::String::new(tc_scope, "run").unwrap();
+  let run_fn = registration.get(tc_scope, key.into()).unwrap();
+  let run_fn = v8::Local::<v8::Function>::try_from(run_fn)?;
+  Ok((schedules, v8::Global::new(tc_scope, run_fn)))
+}
+
+fn start_run(
+  worker: &mut MainWorker,
+  run_fn: &v8::Global<v8::Function>,
+  name: &str,
+) -> Result<v8::Global<v8::Promise>, AnyError> {
+  let scope = &mut worker.js_runtime.handle_scope();
+  let run_fn = v8::Local::new(scope, run_fn);
+  let name = v8::String::new(scope, name).unwrap();
+  let tc_scope = &mut v8::TryCatch::new(scope);
+  let recv = v8::undefined(tc_scope).into();
+  match run_fn.call(tc_scope, recv, &[name.into()]) {
+    Some(value) => {
+      let promise = v8::Local::<v8::Promise>::try_from(value)?;
+      Ok(v8::Global::new(tc_scope, promise))
+    }
+    None => {
+      let exception = tc_scope.exception().unwrap();
+      Err(JsError::from_v8_exception(tc_scope, exception).into())
+    }
+  }
+}
+
+/// Removes the runs that completed, returning whether each of them
+/// succeeded.
+fn take_finished_runs(
+  worker: &mut MainWorker,
+  runs: &mut Vec<v8::Global<v8::Promise>>,
+) -> Vec<bool> {
+  let scope = &mut worker.js_runtime.handle_scope();
+  let mut finished = Vec::new();
+  runs.retain(|run| {
+    let promise = v8::Local::new(scope, run);
+    match promise.state() {
+      v8::PromiseState::Pending => true,
+      _ => {
+        finished.push(promise.result(scope).is_true());
+        false
+      }
+    }
+  });
+  finished
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CronRunState {
+  last_run: String,
+  /// Whether the last run succeeded, which is missing while it runs.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  succeeded: Option<bool>,
+}
+
+/// The last runs of the schedules of a module, which are kept in DENO_DIR so
+/// that a restart doesn't repeat a run.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CronState(BTreeMap<String, CronRunState>);
+
+impl CronState {
+  fn load(path: &Path) -> Self {
+    std::fs::read_to_string(path)
+      .ok()
+      .and_then(|text| serde_json::from_str(&text).ok())
+      .unwrap_or_default()
+  }
+
+  fn save(&self, path: &Path) -> Result<(), AnyError> {
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    util::fs::atomic_write_file(
+      path,
+      serde_json::to_string_pretty(self)?,
+      0o644,
+    )
+    .with_context(|| format!("Failed saving {}", path.display()))
+  }
+
+  fn last_run(&self, name: &str) -> Option<DateTime<Utc>> {
+    let run = self.0.get(name)?;
+    DateTime::parse_from_rfc3339(&run.last_run)
+      .ok()
+      .map(|time| time.with_timezone(&Utc))
+  }
+
+  fn record_start(&mut self, name: &str, time: DateTime<Utc>) {
+    self.0.insert(
+      name.to_string(),
+      CronRunState {
+        last_run: time.to_rfc3339(),
+        succeeded: None,
+      },
+    );
+  }
+
+  fn record_result(&mut self, name: &str, succeeded: bool) {
+    if let Some(run) = self.0.get_mut(name) {
+      run.succeeded = Some(succeeded);
+    }
+  }
+}
+
+/// A cron expression with the five fields minute, hour, day of the month,
+/// month and day of the week, which is matched in UTC or at a fixed offset
+/// from UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+  minutes: u64,
+  hours: u64,
+  days_of_month: u64,
+  months: u64,
+  days_of_week: u64,
+  /// Whether both day fields are restricted, in which case a day matches
+  /// when either of them matches, like in cron.
+  restricts_both_days: bool,
+  offset: FixedOffset,
+}
+
+impl CronSchedule {
+  pub fn parse(text: &str) -> Result<Self, AnyError> {
+    let fields = text.split_whitespace().collect::<Vec<_>>();
+    if fields.len() != 5 {
+      bail!("Invalid cron schedule \"{text}\". Expected the five fields minute, hour, day of the month, month and day of the week.");
+    }
+    let parse = |index: usize, min: u32, max: u32| {
+      parse_field(fields[index], min, max)
+        .with_context(|| format!("Invalid cron schedule \"{text}\""))
+    };
+    let days_of_week = parse(4, 0, 7)?;
+    Ok(Self {
+      minutes: parse(0, 0, 59)?,
+      hours: parse(1, 0, 23)?,
+      days_of_month: parse(2, 1, 31)?,
+      months: parse(3, 1, 12)?,
+      // both 0 and 7 are Sunday
+      days_of_week: (days_of_week | (days_of_week >> 7)) & 0x7f,
+      restricts_both_days: !fields[2].starts_with('*')
+        && !fields[4].starts_with('*'),
+      offset: FixedOffset::east_opt(0).unwrap(),
+    })
+  }
+
+  /// Matches the schedule at the offset from UTC rather than in UTC.
+  pub fn with_offset(self, offset: FixedOffset) -> Self {
+    Self { offset, ..self }
+  }
+
+  /// The first minute after the time that matches the schedule.
+  pub fn next_after(&self, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let time = time.with_timezone(&self.offset).naive_local();
+    let mut next = time.date().and_hms_opt(time.hour(), time.minute(), 0)?
+      + Duration::minutes(1);
+    // a schedule like `0 0 30 2 *` never matches, so the search stops after
+    // a leap year cycle of the days of the week
+    let limit = next + Duration::days(366 * 28);
+    while next < limit {
+      if !has_bit(self.months, next.month()) {
+        let (year, month) = if next.month() == 12 {
+          (next.year() + 1, 1)
+        } else {
+          (next.year(), next.month() + 1)
+        };
+        next = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+      } else if !self.matches_day(next.date()) {
+        next = (next.date() + Duration::days(1)).and_hms_opt(0, 0, 0)?;
+      } else if !has_bit(self.hours, next.hour()) {
+        next = next.date().and_hms_opt(next.hour(), 0, 0)? + Duration::hours(1);
+      } else if !has_bit(self.minutes, next.minute()) {
+        next += Duration::minutes(1);
+      } else {
+        let next = self.offset.from_local_datetime(&next).single()?;
+        return Some(next.with_timezone(&Utc));
+      }
+    }
+    None
+  }
+
+  fn matches_day(&self, date: NaiveDate) -> bool {
+    let day_of_month = has_bit(self.days_of_month, date.day());
+    let day_of_week =
+      has_bit(self.days_of_week, date.weekday().num_days_from_sunday());
+    if self.restricts_both_days {
+      day_of_month || day_of_week
+    } else {
+      day_of_month && day_of_week
+    }
+  }
+}
+
+/// Parses the timezone of a schedule, which is `UTC` or an offset from UTC
+/// like `+09:00` or `-05:30`.
+fn parse_timezone(text: &str) -> Result<FixedOffset, AnyError> {
+  let invalid = || {
+    format!("Invalid timezone \"{text}\". Expected \"UTC\" or an offset from UTC like \"+09:00\".")
+  };
+  if text == "UTC" || text == "Z" {
+    return Ok(FixedOffset::east_opt(0).unwrap());
+  }
+  let (sign, offset) = if let Some(offset) = text.strip_prefix('+') {
+    (1, offset)
+  } else if let Some(offset) = text.strip_prefix('-') {
+    (-1, offset)
+  } else {
+    bail!(invalid());
+  };
+  let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+  if !hours
+    .chars()
+    .chain(minutes.chars())
+    .all(|c| c.is_ascii_digit())
+  {
+    bail!(invalid());
+  }
+  let seconds = match (hours.parse::<i32>(), minutes.parse::<i32>()) {
+    (Ok(hours), Ok(minutes)) if hours <= 23 && minutes <= 59 => {
+      (hours * 60 + minutes) * 60
+    }
+    _ => bail!(invalid()),
+  };
+  FixedOffset::east_opt(sign * seconds).with_context(invalid)
+}
+
+fn has_bit(bits: u64, value: u32) -> bool {
+  bits & (1u64 << value) != 0
+}
+
+/// Parses a field of comma separated numbers, ranges like `1-5` and `*`,
+/// which may be followed by a step like `*/15`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, AnyError> {
+  let mut bits = 0u64;
+  for part in field.split(',') {
+    let (range, step) = match part.split_once('/') {
+      Some((range, step)) => {
+        let step = step
+          .parse::<usize>()
+          .ok()
+          .filter(|step| *step > 0)
+          .with_context(|| format!("Invalid step \"{step}\"."))?;
+        (range, step)
+      }
+      None => (part, 1),
+    };
+    let (start, end) = if range == "*" {
+      (min, max)
+    } else if let Some((start, end)) = range.split_once('-') {
+      (parse_value(start, min, max)?, parse_value(end, min, max)?)
+    } else {
+      let value = parse_value(range, min, max)?;
+      // `5/15` is every 15 starting at 5
+      (value, if step > 1 { max } else { value })
+    };
+    if start > end {
+      bail!("Invalid range \"{range}\".");
+    }
+    for value in (start..=end).step_by(step) {
+      bits |= 1u64 << value;
+    }
+  }
+  Ok(bits)
+}
+
+fn parse_value(text: &str, min: u32, max: u32) -> Result<u32, AnyError> {
+  match text.parse::<u32>() {
+    Ok(value) if (min..=max).contains(&value) => Ok(value),
+    _ => {
+      bail!("Invalid value \"{text}\". Expected a number from {min} to {max}.")
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn time(text: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(text)
+      .unwrap()
+      .with_timezone(&Utc)
+  }
+
+  fn next_after(schedule: &str, after: &str) -> String {
+    CronSchedule::parse(schedule)
+      .unwrap()
+      .next_after(time(after))
+      .unwrap()
+      .to_rfc3339()
+  }
+
+  #[test]
+  fn next_runs() {
+    assert_eq!(
+      next_after("* * * * *", "2023-05-10T12:30:15Z"),
+      "2023-05-10T12:31:00+00:00"
+    );
+    assert_eq!(
+      next_after("*/15 * * * *", "2023-05-10T12:30:00Z"),
+      "2023-05-10T12:45:00+00:00"
+    );
+    assert_eq!(
+      next_after("0 0 * * *", "2023-12-31T12:00:00Z"),
+      "2024-01-01T00:00:00+00:00"
+    );
+    assert_eq!(
+      next_after("30 9 * * 1-5", "2023-05-12T10:00:00Z"),
+      "2023-05-15T09:30:00+00:00"
+    );
+    assert_eq!(
+      next_after("0 12 29 2 *", "2023-01-01T00:00:00Z"),
+      "2024-02-29T12:00:00+00:00"
+    );
+    // either day field matches when both are restricted
+    assert_eq!(
+      next_after("0 0 1 * 7", "2023-05-10T00:00:00Z"),
+      "2023-05-14T00:00:00+00:00"
+    );
+    assert_eq!(
+      next_after("5/20 8,18 * 6 *", "2023-05-10T00:00:00Z"),
+      "2023-06-01T08:05:00+00:00"
+    );
+    assert!(CronSchedule::parse("0 0 30 2 *")
+      .unwrap()
+      .next_after(time("2023-01-01T00:00:00Z"))
+      .is_none());
+  }
+
+  #[test]
+  fn next_runs_at_offset() {
+    let schedule = CronSchedule::parse("0 9 * * 1-5")
+      .unwrap()
+      .with_offset(parse_timezone("+09:00").unwrap());
+    assert_eq!(
+      schedule
+        .next_after(time("2023-05-12T01:00:00Z"))
+        .unwrap()
+        .to_rfc3339(),
+      // 09:00 on Monday in +09:00
+      "2023-05-15T00:00:00+00:00"
+    );
+    let schedule = CronSchedule::parse("30 23 * * *")
+      .unwrap()
+      .with_offset(parse_timezone("-05:30").unwrap());
+    assert_eq!(
+      schedule
+        .next_after(time("2023-05-10T00:00:00Z"))
+        .unwrap()
+        .to_rfc3339(),
+      "2023-05-10T05:00:00+00:00"
+    );
+  }
+
+  #[test]
+  fn timezones() {
+    assert_eq!(parse_timezone("UTC").unwrap().local_minus_utc(), 0);
+    assert_eq!(parse_timezone("+09:00").unwrap().local_minus_utc(), 32400);
+    assert_eq!(parse_timezone("-05:30").unwrap().local_minus_utc(), -19800);
+    assert_eq!(parse_timezone("+2").unwrap().local_minus_utc(), 7200);
+    for timezone in ["", "Asia/Seoul", "+24:00", "09:00", "+09:60"] {
+      assert!(parse_timezone(timezone).is_err(), "{timezone}");
+    }
+  }
+
+  #[test]
+  fn invalid_schedules() {
+    for schedule in ["* * * *", "60 * * * *", "*/0 * * * *", "5-1 * * * *"] {
+      assert!(CronSchedule::parse(schedule).is_err(), "{schedule}");
+    }
+  }
+}
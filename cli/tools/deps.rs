@@ -0,0 +1,991 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_npm::registry::NpmRegistryApi;
+use deno_npm::NpmPackageId;
+use deno_runtime::colors;
+use deno_semver::npm::NpmPackageReq;
+use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::Version;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::AddFlags;
+use crate::args::CliOptions;
+use crate::args::Flags;
+use crate::args::FmtOptionsConfig;
+use crate::args::OutdatedFlags;
+use crate::args::RemoveFlags;
+use crate::proc_state::ProcState;
+use crate::tools::fmt::format_json;
+use crate::tools::installer::install_project_deps;
+use crate::util::display;
+use crate::util::path::specifier_to_file_path;
+
+/// A dependency resolved from its registry.
+struct ResolvedDep {
+  /// The key of the entry in the "imports".
+  key: String,
+  /// The value of the entry in the "imports".
+  value: String,
+  /// The dependency and its resolved version.
+  display: String,
+}
+
+/// The versions of a module published to deno.land.
+#[derive(Deserialize)]
+struct DenoLandVersions {
+  latest: String,
+  versions: Vec<String>,
+}
+
+/// Matches the name and version of a module published to deno.land
+/// (ex. `https://deno.land/x/oak@v12.5.0/mod.ts`).
+static DENO_LAND_MODULE_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"^https://deno\.land/(std|x/[^/@]+)@([^/]+)/").unwrap()
+});
+
+/// A dependency of the project found in the import map or the lockfile.
+enum ProjectDep {
+  Npm {
+    /// The entry of the "imports" mapping to the dependency, if any.
+    maybe_entry: Option<(String, String)>,
+    reference: NpmPackageReqReference,
+    /// The version the requirement resolves to in the lockfile, if any.
+    current: Option<String>,
+  },
+  DenoLand {
+    maybe_entry: Option<(String, String)>,
+    /// The name of the module (ex. `std` or `x/oak`).
+    name: String,
+    version: String,
+  },
+}
+
+/// A dependency with a newer version available.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OutdatedDep {
+  name: String,
+  current: Option<String>,
+  wanted: String,
+  latest: String,
+  /// The updated entry of the "imports", when the dependency is mapped
+  /// by the import map.
+  #[serde(skip)]
+  maybe_updated_entry: Option<(String, String)>,
+}
+
+pub async fn add(flags: Flags, add_flags: AddFlags) -> Result<(), AnyError> {
+  let cli_options = CliOptions::from_flags(flags.clone())?;
+  let config_path = resolve_config_path(&cli_options)?;
+  let maybe_config_text = match std::fs::read_to_string(&config_path) {
+    Ok(text) => Some(text),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+    Err(err) => {
+      return Err(err).with_context(|| {
+        format!("Unable to read config file: {}", config_path.display())
+      })
+    }
+  };
+  let fmt_options = cli_options
+    .maybe_config_file()
+    .as_ref()
+    .and_then(|config| config.to_fmt_config().ok())
+    .unwrap_or_default()
+    .unwrap_or_default()
+    .options;
+
+  let ps = ProcState::from_cli_options(Arc::new(cli_options)).await?;
+  let mut deps = Vec::with_capacity(add_flags.packages.len());
+  for package in &add_flags.packages {
+    let dep = resolve_dep(&ps, package)
+      .await
+      .with_context(|| format!("Failed resolving '{package}'"))?;
+    deps.push(dep);
+  }
+  let maybe_lockfile_path = ps
+    .lockfile
+    .as_ref()
+    .map(|lockfile| lockfile.lock().filename.clone());
+  drop(ps);
+
+  let mut text = maybe_config_text
+    .clone()
+    .unwrap_or_else(|| "{}".to_string());
+  for dep in &deps {
+    text = set_import_in_config_text(&text, &dep.key, &dep.value)?;
+  }
+  write_imports_and_install(
+    flags,
+    &config_path,
+    maybe_config_text.as_deref(),
+    format_config_text(text, &fmt_options),
+    maybe_lockfile_path,
+  )
+  .await?;
+
+  for dep in deps {
+    log::info!("{} {}", colors::green("Add"), dep.display);
+  }
+  Ok(())
+}
+
+pub fn remove(flags: Flags, remove_flags: RemoveFlags) -> Result<(), AnyError> {
+  let cli_options = CliOptions::from_flags(flags)?;
+  let config_specifier = match cli_options.maybe_config_file_specifier() {
+    Some(specifier) => specifier,
+    None => bail!("No config file found in the current directory."),
+  };
+  let config_path = specifier_to_file_path(&config_specifier)?;
+  let fmt_options = cli_options
+    .maybe_config_file()
+    .as_ref()
+    .and_then(|config| config.to_fmt_config().ok())
+    .unwrap_or_default()
+    .unwrap_or_default()
+    .options;
+
+  let mut text = std::fs::read_to_string(&config_path)?;
+  let mut removed = Vec::with_capacity(remove_flags.packages.len());
+  for package in &remove_flags.packages {
+    let name = package.strip_prefix("npm:").unwrap_or(package);
+    match remove_import_from_config_text(&text, name)? {
+      Some((key, new_text)) => {
+        text = new_text;
+        removed.push(key);
+      }
+      None => bail!("Dependency not found in the \"imports\": {}", package),
+    }
+  }
+  let text = format_config_text(text, &fmt_options);
+  std::fs::write(&config_path, text)?;
+
+  for key in removed {
+    log::info!("{} {}", colors::green("Remove"), key);
+  }
+  Ok(())
+}
+
+pub async fn outdated(
+  flags: Flags,
+  outdated_flags: OutdatedFlags,
+) -> Result<(), AnyError> {
+  let ps = ProcState::from_flags(flags.clone()).await?;
+  let maybe_lockfile_path = ps
+    .lockfile
+    .as_ref()
+    .map(|lockfile| lockfile.lock().filename.clone());
+
+  let mut outdated_deps = Vec::new();
+  for dep in collect_project_deps(&ps) {
+    let maybe_outdated = match &dep {
+      ProjectDep::Npm {
+        maybe_entry,
+        reference,
+        current,
+      } => {
+        outdated_npm_dep(&ps, maybe_entry.as_ref(), reference, current.clone())
+          .await
+      }
+      ProjectDep::DenoLand {
+        maybe_entry,
+        name,
+        version,
+      } => {
+        outdated_deno_land_dep(&ps, maybe_entry.as_ref(), name, version).await
+      }
+    }?;
+    outdated_deps.extend(maybe_outdated);
+  }
+
+  if !outdated_flags.update {
+    if outdated_flags.json {
+      display::write_json_to_stdout(&outdated_deps)?;
+    } else if outdated_deps.is_empty() {
+      log::info!("All dependencies are up to date.");
+    } else {
+      log::info!("{}", format_outdated_table(&outdated_deps));
+    }
+    return Ok(());
+  }
+
+  let updated_deps = outdated_deps
+    .iter()
+    .filter_map(|dep| {
+      dep
+        .maybe_updated_entry
+        .as_ref()
+        .map(|(key, value)| (dep, key, value))
+    })
+    .collect::<Vec<_>>();
+  if updated_deps.is_empty() {
+    log::info!("No import map entries to update.");
+    return Ok(());
+  }
+
+  let import_map_specifier = match ps.options.resolve_import_map_specifier()? {
+    Some(specifier) => specifier,
+    None => bail!("No import map found to update."),
+  };
+  let import_map_path = specifier_to_file_path(&import_map_specifier)
+    .with_context(|| {
+      format!("Unable to update a remote import map: {import_map_specifier}")
+    })?;
+  let fmt_options = if ps.options.maybe_config_file_specifier().as_ref()
+    == Some(&import_map_specifier)
+  {
+    ps.options
+      .maybe_config_file()
+      .as_ref()
+      .and_then(|config| config.to_fmt_config().ok())
+      .unwrap_or_default()
+      .unwrap_or_default()
+      .options
+  } else {
+    Default::default()
+  };
+  drop(ps);
+
+  let original_text =
+    std::fs::read_to_string(&import_map_path).with_context(|| {
+      format!("Unable to read import map: {}", import_map_path.display())
+    })?;
+  let mut text = original_text.clone();
+  for (_, key, value) in &updated_deps {
+    text = set_import_in_config_text(&text, key, value)?;
+  }
+  write_imports_and_install(
+    flags,
+    &import_map_path,
+    Some(&original_text),
+    format_config_text(text, &fmt_options),
+    maybe_lockfile_path,
+  )
+  .await?;
+
+  for (dep, _, value) in updated_deps {
+    log::info!(
+      "{} {} {}",
+      colors::green("Update"),
+      dep.name,
+      value.trim_end_matches('/')
+    );
+  }
+  Ok(())
+}
+
+/// Collects the npm and deno.land dependencies mapped by the import map,
+/// followed by the ones only found in the lockfile.
+fn collect_project_deps(ps: &ProcState) -> Vec<ProjectDep> {
+  let lockfile = ps.lockfile.as_ref().map(|lockfile| lockfile.lock());
+  // the versions the npm package requirements resolve to in the lockfile
+  let locked_npm_versions = lockfile
+    .iter()
+    .flat_map(|lockfile| &lockfile.content.npm.specifiers)
+    .filter_map(|(req, id)| {
+      let req = NpmPackageReq::from_str(req).ok()?;
+      let id = NpmPackageId::from_serialized(id).ok()?;
+      Some((req, id.nv.version.to_string()))
+    })
+    .collect::<Vec<_>>();
+  let locked_npm_version = |req: &NpmPackageReq| {
+    locked_npm_versions
+      .iter()
+      .find(|(locked_req, _)| locked_req == req)
+      .map(|(_, version)| version.clone())
+  };
+
+  let mut deps = Vec::new();
+  if let Some(import_map) = &ps.maybe_import_map {
+    for entry in import_map.imports().entries() {
+      let (value, raw_value) = match (entry.value, entry.raw_value) {
+        (Some(value), Some(raw_value)) => (value, raw_value),
+        _ => continue,
+      };
+      let maybe_entry =
+        Some((entry.raw_key.to_string(), raw_value.to_string()));
+      if let Ok(reference) = NpmPackageReqReference::from_specifier(value) {
+        deps.push(ProjectDep::Npm {
+          maybe_entry,
+          current: locked_npm_version(&reference.req),
+          reference,
+        });
+      } else if let Some(captures) =
+        DENO_LAND_MODULE_RE.captures(value.as_str())
+      {
+        deps.push(ProjectDep::DenoLand {
+          maybe_entry,
+          name: captures[1].to_string(),
+          version: captures[2].to_string(),
+        });
+      }
+    }
+  }
+
+  for (req, version) in &locked_npm_versions {
+    let is_mapped = deps.iter().any(|dep| {
+      matches!(dep, ProjectDep::Npm { reference, .. } if &reference.req == req)
+    });
+    if !is_mapped {
+      deps.push(ProjectDep::Npm {
+        maybe_entry: None,
+        reference: NpmPackageReqReference {
+          req: req.clone(),
+          sub_path: None,
+        },
+        current: Some(version.clone()),
+      });
+    }
+  }
+  let remote_specifiers = lockfile
+    .iter()
+    .flat_map(|lockfile| lockfile.content.remote.keys());
+  for captures in remote_specifiers
+    .filter_map(|specifier| DENO_LAND_MODULE_RE.captures(specifier))
+  {
+    let is_found = deps.iter().any(|dep| {
+      matches!(
+        dep,
+        ProjectDep::DenoLand { name, version, .. }
+          if name == &captures[1] && version == &captures[2]
+      )
+    });
+    if !is_found {
+      deps.push(ProjectDep::DenoLand {
+        maybe_entry: None,
+        name: captures[1].to_string(),
+        version: captures[2].to_string(),
+      });
+    }
+  }
+  deps
+}
+
+async fn outdated_npm_dep(
+  ps: &ProcState,
+  maybe_entry: Option<&(String, String)>,
+  reference: &NpmPackageReqReference,
+  current: Option<String>,
+) -> Result<Option<OutdatedDep>, AnyError> {
+  let req = &reference.req;
+  let package_info = ps
+    .npm_api
+    .package_info(&req.name)
+    .await
+    .with_context(|| format!("Failed resolving 'npm:{}'", req.name))?;
+  let latest = match package_info.dist_tags.get("latest") {
+    Some(latest) => latest,
+    None => return Ok(None),
+  };
+  let wanted = match &req.version_req {
+    Some(version_req) => {
+      let versions = package_info
+        .versions
+        .keys()
+        .filter_map(|version| Version::parse_from_npm(version).ok());
+      match select_wanted_version(versions, |v| version_req.matches(v)) {
+        Some(version) => version.to_string(),
+        None => return Ok(None),
+      }
+    }
+    None => latest.clone(),
+  };
+  if current.as_ref().unwrap_or(&wanted) == latest {
+    return Ok(None);
+  }
+
+  // only ranges are updated to keep the versions pinned by the user
+  let maybe_updated_entry = maybe_entry.and_then(|(key, raw_value)| {
+    let operator = npm_range_operator(raw_value, &req.name)?;
+    if current.as_ref() == Some(&wanted) {
+      return None;
+    }
+    let sub_path = reference
+      .sub_path
+      .as_ref()
+      .map(|sub_path| format!("/{sub_path}"))
+      .unwrap_or_default();
+    Some((
+      key.clone(),
+      format!("npm:{}@{operator}{wanted}{sub_path}", req.name),
+    ))
+  });
+  Ok(Some(OutdatedDep {
+    name: format!("npm:{}", req.name),
+    current,
+    wanted,
+    latest: latest.clone(),
+    maybe_updated_entry,
+  }))
+}
+
+/// Selects the highest of the versions satisfying the requirement.
+fn select_wanted_version(
+  versions: impl Iterator<Item = Version>,
+  matches: impl Fn(&Version) -> bool,
+) -> Option<Version> {
+  versions.filter(|version| matches(version)).max()
+}
+
+/// Gets the operator of the version range in the value of an "imports"
+/// entry (ex. `^` for `npm:chalk@^5.2.0`).
+fn npm_range_operator(raw_value: &str, name: &str) -> Option<char> {
+  let version_text = raw_value
+    .strip_prefix("npm:")?
+    .trim_start_matches('/')
+    .strip_prefix(name)?
+    .strip_prefix('@')?;
+  match version_text.chars().next()? {
+    operator @ ('^' | '~') => Some(operator),
+    _ => None,
+  }
+}
+
+/// Checks a module published to deno.land against its latest version. The
+/// URLs of these modules are pinned, so the wanted version is the latest one.
+async fn outdated_deno_land_dep(
+  ps: &ProcState,
+  maybe_entry: Option<&(String, String)>,
+  name: &str,
+  version: &str,
+) -> Result<Option<OutdatedDep>, AnyError> {
+  let module_name = name.strip_prefix("x/").unwrap_or(name);
+  let versions_url =
+    format!("https://cdn.deno.land/{module_name}/meta/versions.json");
+  let versions_text = ps
+    .http_client
+    .download_text(versions_url)
+    .await
+    .with_context(|| format!("Unable to find the module '{name}'"))?;
+  let versions: DenoLandVersions = serde_json::from_str(&versions_text)?;
+  if versions.latest == version {
+    return Ok(None);
+  }
+
+  let maybe_updated_entry = maybe_entry.map(|(key, raw_value)| {
+    (
+      key.clone(),
+      update_deno_land_url(raw_value, name, version, &versions.latest),
+    )
+  });
+  Ok(Some(OutdatedDep {
+    name: format!("deno.land/{name}"),
+    current: Some(version.to_string()),
+    wanted: versions.latest.clone(),
+    latest: versions.latest,
+    maybe_updated_entry,
+  }))
+}
+
+/// Replaces the version of a deno.land module in its URL.
+fn update_deno_land_url(
+  url: &str,
+  name: &str,
+  version: &str,
+  new_version: &str,
+) -> String {
+  url.replacen(
+    &format!("/{name}@{version}/"),
+    &format!("/{name}@{new_version}/"),
+    1,
+  )
+}
+
+fn format_outdated_table(deps: &[OutdatedDep]) -> String {
+  let rows = deps
+    .iter()
+    .map(|dep| {
+      [
+        dep.name.as_str(),
+        dep.current.as_deref().unwrap_or("-"),
+        dep.wanted.as_str(),
+        dep.latest.as_str(),
+      ]
+    })
+    .collect::<Vec<_>>();
+  let header = ["Dependency", "Current", "Wanted", "Latest"];
+  let mut widths = header.map(|column| column.len());
+  for row in &rows {
+    for (width, column) in widths.iter_mut().zip(row) {
+      *width = (*width).max(column.chars().count());
+    }
+  }
+
+  let format_row = |row: [&str; 4]| {
+    row
+      .iter()
+      .zip(widths)
+      .map(|(column, width)| format!("{column:width$}"))
+      .collect::<Vec<_>>()
+      .join("  ")
+      .trim_end()
+      .to_string()
+  };
+  let mut lines = vec![colors::bold(format_row(header)).to_string()];
+  lines.extend(rows.into_iter().map(format_row));
+  lines.join("\n")
+}
+
+/// Writes the new text of the file containing the "imports", then caches the
+/// dependencies and updates the lockfile, restoring the previous files when
+/// that fails.
+async fn write_imports_and_install(
+  flags: Flags,
+  path: &Path,
+  maybe_original_text: Option<&str>,
+  text: String,
+  maybe_lockfile_path: Option<PathBuf>,
+) -> Result<(), AnyError> {
+  let maybe_lockfile = maybe_lockfile_path.map(|path| {
+    let maybe_text = std::fs::read_to_string(&path).ok();
+    (path, maybe_text)
+  });
+  std::fs::write(path, text)?;
+
+  let result = async {
+    let ps = ProcState::from_flags(flags).await?;
+    install_project_deps(&ps).await
+  }
+  .await;
+  if let Err(err) = result {
+    match maybe_original_text {
+      Some(text) => std::fs::write(path, text)?,
+      None => std::fs::remove_file(path)?,
+    }
+    if let Some((lockfile_path, maybe_text)) = &maybe_lockfile {
+      match maybe_text {
+        Some(text) => std::fs::write(lockfile_path, text)?,
+        None => {
+          let _ = std::fs::remove_file(lockfile_path);
+        }
+      }
+    }
+    return Err(err);
+  }
+  Ok(())
+}
+
+/// Gets the path of the config file to update, which is a new deno.json file
+/// in the current directory when there isn't one.
+fn resolve_config_path(cli_options: &CliOptions) -> Result<PathBuf, AnyError> {
+  match cli_options.maybe_config_file_specifier() {
+    Some(specifier) => specifier_to_file_path(&specifier),
+    None => Ok(cli_options.initial_cwd().join("deno.json")),
+  }
+}
+
+async fn resolve_dep(
+  ps: &ProcState,
+  package: &str,
+) -> Result<ResolvedDep, AnyError> {
+  if let Some(package) = package.strip_prefix("npm:") {
+    resolve_npm_dep(ps, package).await
+  } else if package == "std"
+    || package.starts_with("std/")
+    || package.starts_with("std@")
+  {
+    resolve_deno_land_dep(ps, package, "https://deno.land/").await
+  } else if let Some(package) = package.strip_prefix("x/") {
+    resolve_deno_land_dep(ps, package, "https://deno.land/x/").await
+  } else {
+    bail!(
+      "Unsupported package. Use an npm specifier (ex. npm:express) or a deno.land module (ex. std/http or x/oak)."
+    )
+  }
+}
+
+async fn resolve_npm_dep(
+  ps: &ProcState,
+  package: &str,
+) -> Result<ResolvedDep, AnyError> {
+  let req = NpmPackageReq::from_str(package)?;
+  let package_info = ps.npm_api.package_info(&req.name).await?;
+  // resolve the version the same way npm specifiers are resolved
+  let nv = ps
+    .npm_resolution
+    .resolve_package_req_as_pending_with_info(&req, &package_info)?;
+  let value = match &req.version_req {
+    Some(_) => format!("npm:{req}"),
+    None => format!("npm:{}@^{}", req.name, nv.version),
+  };
+  Ok(ResolvedDep {
+    key: req.name.clone(),
+    value,
+    display: format!("npm:{nv}"),
+  })
+}
+
+/// Resolves a module published to deno.land (ex. `std/http`,
+/// `oak@v12.5.0/middleware`) to a prefix mapping of its latest version or
+/// the provided one.
+async fn resolve_deno_land_dep(
+  ps: &ProcState,
+  package: &str,
+  base_url: &str,
+) -> Result<ResolvedDep, AnyError> {
+  let (module, path) = match package.split_once('/') {
+    Some((module, path)) => (module, path.trim_matches('/')),
+    None => (package, ""),
+  };
+  // the version can be provided after the name or the path
+  // (ex. `std@0.190.0/http` or `std/http@0.190.0`)
+  let (name, path, maybe_version) =
+    match (module.split_once('@'), path.split_once('@')) {
+      (Some((name, version)), _) => (name, path, Some(version)),
+      (None, Some((path, version))) => (module, path, Some(version)),
+      (None, None) => (module, path, None),
+    };
+  if name.is_empty() {
+    bail!("Missing module name.");
+  }
+
+  let versions_url = format!("https://cdn.deno.land/{name}/meta/versions.json");
+  let versions_text = ps
+    .http_client
+    .download_text(versions_url)
+    .await
+    .with_context(|| format!("Unable to find the module '{name}'"))?;
+  let versions: DenoLandVersions = serde_json::from_str(&versions_text)?;
+  let version = match maybe_version {
+    Some(version) if versions.versions.iter().any(|v| v == version) => {
+      version.to_string()
+    }
+    Some(version) => bail!("Version {} of '{}' was not found.", version, name),
+    None => versions.latest,
+  };
+
+  let (key, url) = if path.is_empty() {
+    (format!("{name}/"), format!("{base_url}{name}@{version}/"))
+  } else {
+    (
+      format!("{name}/{path}/"),
+      format!("{base_url}{name}@{version}/{path}/"),
+    )
+  };
+  Ok(ResolvedDep {
+    key,
+    display: url.trim_end_matches('/').to_string(),
+    value: url,
+  })
+}
+
+pub fn format_config_text(
+  text: String,
+  fmt_options: &FmtOptionsConfig,
+) -> String {
+  match format_json(&text, fmt_options) {
+    Ok(Some(formatted_text)) => formatted_text,
+    _ => text,
+  }
+}
+
+/// Adds or replaces an entry of the "imports" in the text of a config file.
+fn set_import_in_config_text(
+  text: &str,
+  key: &str,
+  value: &str,
+) -> Result<String, AnyError> {
+  set_entry_in_config_text(text, "imports", key, &serde_json::to_string(value)?)
+}
+
+/// Adds or replaces an entry of an object property (ex. "imports") in the
+/// text of a config file, where the value is JSON text.
+pub fn set_entry_in_config_text(
+  text: &str,
+  object_name: &str,
+  key: &str,
+  value_text: &str,
+) -> Result<String, AnyError> {
+  use jsonc_parser::ast::ObjectProp;
+  use jsonc_parser::ast::Value;
+  let ast =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())?;
+  let obj = match ast.value {
+    Some(Value::Object(obj)) => obj,
+    _ => bail!("The config file must contain an object."),
+  };
+  let key_text = serde_json::to_string(key)?;
+  let value_text = value_text.to_string();
+
+  let (range, new_text) = match obj.get(object_name) {
+    Some(ObjectProp {
+      value: Value::Object(object),
+      ..
+    }) => match object.get(key) {
+      Some(ObjectProp {
+        value: Value::StringLit(lit),
+        ..
+      }) => ((lit.range.start, lit.range.end), value_text),
+      Some(prop) => (
+        (prop.range.start, prop.range.end),
+        format!("{key_text}: {value_text}"),
+      ),
+      None => {
+        // insert it crudely and format after to make it look nice
+        let insert_position = object.range.end - 1;
+        let needs_comma = match object.properties.last() {
+          Some(prop) => !text[prop.range.end..insert_position]
+            .trim_start()
+            .starts_with(','),
+          None => false,
+        };
+        (
+          (insert_position, insert_position),
+          format!(
+            "{}{key_text}: {value_text}",
+            if needs_comma { "," } else { "" }
+          ),
+        )
+      }
+    },
+    Some(_) => bail!(
+      "The \"{}\" of the config file must be an object.",
+      object_name
+    ),
+    None => {
+      let insert_position = obj.range.end - 1;
+      let needs_comma = match obj.properties.last() {
+        Some(prop) => !text[prop.range.end..insert_position]
+          .trim_start()
+          .starts_with(','),
+        None => false,
+      };
+      (
+        (insert_position, insert_position),
+        format!(
+          "{}\"{object_name}\": {{ {key_text}: {value_text} }}",
+          if needs_comma { "," } else { "" }
+        ),
+      )
+    }
+  };
+  Ok(format!(
+    "{}{}{}",
+    &text[..range.0],
+    new_text,
+    &text[range.1..]
+  ))
+}
+
+/// Removes the entry of the "imports" for the provided name or prefix
+/// (ex. `std/http` for `std/http/`) from the text of a config file,
+/// returning the removed key and the new text.
+fn remove_import_from_config_text(
+  text: &str,
+  name: &str,
+) -> Result<Option<(String, String)>, AnyError> {
+  use jsonc_parser::ast::ObjectProp;
+  use jsonc_parser::ast::Value;
+  let ast =
+    jsonc_parser::parse_to_ast(text, &Default::default(), &Default::default())?;
+  let obj = match &ast.value {
+    Some(Value::Object(obj)) => obj,
+    _ => return Ok(None),
+  };
+  let imports = match obj.get("imports") {
+    Some(ObjectProp {
+      value: Value::Object(imports),
+      ..
+    }) => imports,
+    _ => return Ok(None),
+  };
+  let prefix = format!("{}/", name.trim_end_matches('/'));
+  let index = match imports.properties.iter().position(|prop| {
+    let key = prop.name.as_str();
+    key == name || key == prefix
+  }) {
+    Some(index) => index,
+    None => return Ok(None),
+  };
+
+  let prop = &imports.properties[index];
+  // remove the comma separating the entry from the next or previous one
+  let (start, end) = match imports.properties.get(index + 1) {
+    Some(next) => (prop.range.start, next.range.start),
+    None => match index.checked_sub(1) {
+      Some(previous_index) => {
+        (imports.properties[previous_index].range.end, prop.range.end)
+      }
+      None => (prop.range.start, prop.range.end),
+    },
+  };
+  Ok(Some((
+    prop.name.as_str().to_string(),
+    format!("{}{}", &text[..start], &text[end..]),
+  )))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  fn set_import(text: &str, key: &str, value: &str) -> String {
+    format_config_text(
+      set_import_in_config_text(text, key, value).unwrap(),
+      &Default::default(),
+    )
+  }
+
+  fn remove_import(text: &str, name: &str) -> Option<String> {
+    remove_import_from_config_text(text, name)
+      .unwrap()
+      .map(|(_, text)| format_config_text(text, &Default::default()))
+  }
+
+  #[test]
+  fn sets_imports() {
+    assert_eq!(
+      set_import("{}", "chalk", "npm:chalk@^5.2.0"),
+      r#"{ "imports": { "chalk": "npm:chalk@^5.2.0" } }
+"#
+    );
+    assert_eq!(
+      set_import(
+        r#"{
+  // comment
+  "tasks": {}
+}"#,
+        "std/http/",
+        "https://deno.land/std@0.190.0/http/"
+      ),
+      r#"{
+  // comment
+  "tasks": {},
+  "imports": { "std/http/": "https://deno.land/std@0.190.0/http/" }
+}
+"#
+    );
+    assert_eq!(
+      set_import(
+        r#"{
+  "imports": {
+    "chalk": "npm:chalk@^4.0.0"
+  }
+}"#,
+        "chalk",
+        "npm:chalk@^5.2.0"
+      ),
+      r#"{
+  "imports": {
+    "chalk": "npm:chalk@^5.2.0"
+  }
+}
+"#
+    );
+    assert_eq!(
+      set_import(
+        r#"{
+  "imports": {
+    "chalk": "npm:chalk@^5.2.0",
+  }
+}"#,
+        "express",
+        "npm:express@^4"
+      ),
+      r#"{
+  "imports": {
+    "chalk": "npm:chalk@^5.2.0",
+    "express": "npm:express@^4"
+  }
+}
+"#
+    );
+  }
+
+  #[test]
+  fn selects_wanted_version() {
+    let versions = ["4.1.2", "5.0.0", "5.2.0", "5.3.0-beta.1", "6.0.0"]
+      .iter()
+      .map(|version| Version::parse_from_npm(version).unwrap())
+      .collect::<Vec<_>>();
+    let select = |text: &str| {
+      let req = NpmPackageReq::from_str(&format!("chalk@{text}")).unwrap();
+      let version_req = req.version_req.unwrap();
+      select_wanted_version(versions.clone().into_iter(), |v| {
+        version_req.matches(v)
+      })
+      .map(|v| v.to_string())
+    };
+    assert_eq!(select("^5.0.0").as_deref(), Some("5.2.0"));
+    assert_eq!(select("~4.1.0").as_deref(), Some("4.1.2"));
+    assert_eq!(select("5.0.0").as_deref(), Some("5.0.0"));
+    assert_eq!(select("^7"), None);
+  }
+
+  #[test]
+  fn gets_npm_range_operator() {
+    assert_eq!(npm_range_operator("npm:chalk@^5.2.0", "chalk"), Some('^'));
+    assert_eq!(
+      npm_range_operator("npm:@scope/pkg@~1.0.0/sub", "@scope/pkg"),
+      Some('~')
+    );
+    assert_eq!(npm_range_operator("npm:chalk@5.2.0", "chalk"), None);
+    assert_eq!(npm_range_operator("npm:chalk", "chalk"), None);
+  }
+
+  #[test]
+  fn updates_deno_land_url() {
+    assert_eq!(
+      update_deno_land_url(
+        "https://deno.land/std@0.190.0/http/",
+        "std",
+        "0.190.0",
+        "0.192.0"
+      ),
+      "https://deno.land/std@0.192.0/http/"
+    );
+    assert_eq!(
+      update_deno_land_url(
+        "https://deno.land/x/oak@v12.4.0/mod.ts",
+        "x/oak",
+        "v12.4.0",
+        "v12.5.0"
+      ),
+      "https://deno.land/x/oak@v12.5.0/mod.ts"
+    );
+  }
+
+  #[test]
+  fn removes_imports() {
+    let text = r#"{
+  "imports": {
+    "chalk": "npm:chalk@^5.2.0",
+    "std/http/": "https://deno.land/std@0.190.0/http/",
+    "express": "npm:express@^4"
+  }
+}"#;
+    assert_eq!(
+      remove_import(text, "std/http").unwrap(),
+      r#"{
+  "imports": {
+    "chalk": "npm:chalk@^5.2.0",
+    "express": "npm:express@^4"
+  }
+}
+"#
+    );
+    assert_eq!(
+      remove_import(text, "express").unwrap(),
+      r#"{
+  "imports": {
+    "chalk": "npm:chalk@^5.2.0",
+    "std/http/": "https://deno.land/std@0.190.0/http/"
+  }
+}
+"#
+    );
+    assert_eq!(remove_import(text, "oak"), None);
+    assert_eq!(remove_import("{}", "chalk"), None);
+  }
+}
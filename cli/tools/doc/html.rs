@@ -0,0 +1,256 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Generates a static HTML site from the doc nodes of a module, with a page
+//! per symbol, links between symbols and a client side search index.
+
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_doc as doc;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Captures;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+
+const STYLES: &str = r#"body {
+  font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif;
+  margin: 0 auto;
+  max-width: 960px;
+  padding: 1rem 2rem;
+  color: #1f2328;
+}
+a { color: #0969da; text-decoration: none; }
+a:hover { text-decoration: underline; }
+pre { background: #f6f8fa; padding: 1rem; overflow-x: auto; border-radius: 6px; }
+.kind { color: #57606a; font-size: 0.8em; margin-right: 0.5em; text-transform: uppercase; }
+.location { color: #57606a; font-size: 0.9em; }
+#search { width: 100%; padding: 0.5rem; font-size: 1rem; box-sizing: border-box; }
+ul.symbols { list-style: none; padding: 0; }
+ul.symbols li { padding: 0.25rem 0; }
+"#;
+
+const SEARCH_SCRIPT: &str = r#"(() => {
+  const input = document.getElementById("search");
+  const list = document.getElementById("symbols");
+  function render(query) {
+    const q = query.trim().toLowerCase();
+    list.innerHTML = "";
+    for (const item of SEARCH_INDEX) {
+      if (q && !item.name.toLowerCase().includes(q) && !item.doc.toLowerCase().includes(q)) {
+        continue;
+      }
+      const li = document.createElement("li");
+      const kind = document.createElement("span");
+      kind.className = "kind";
+      kind.textContent = item.kind;
+      const link = document.createElement("a");
+      link.href = item.href;
+      link.textContent = item.name;
+      li.append(kind, link);
+      if (item.doc) {
+        li.append(" — " + item.doc);
+      }
+      list.append(li);
+    }
+  }
+  input.addEventListener("input", () => render(input.value));
+  render(input.value);
+})();
+"#;
+
+/// Writes the HTML documentation for the provided nodes to `output_dir`.
+pub fn write_html_docs(
+  title: &str,
+  doc_nodes: &[doc::DocNode],
+  private: bool,
+  output_dir: &Path,
+) -> Result<(), AnyError> {
+  // group overloads and declaration merged symbols onto the same page
+  let mut symbols: IndexMap<(String, String), Vec<doc::DocNode>> =
+    IndexMap::new();
+  for node in doc_nodes {
+    if node.kind == doc::DocNodeKind::Import {
+      continue;
+    }
+    symbols
+      .entry((kind_name(&node.kind), node.name.clone()))
+      .or_default()
+      .push(node.clone());
+  }
+  symbols.sort_by(|(a_kind, a_name), _, (b_kind, b_name), _| {
+    a_name.cmp(b_name).then_with(|| a_kind.cmp(b_kind))
+  });
+
+  let mut hrefs = HashMap::new();
+  for (kind, name) in symbols.keys() {
+    hrefs
+      .entry(name.clone())
+      .or_insert_with(|| symbol_href(kind, name));
+  }
+
+  std::fs::create_dir_all(output_dir)?;
+  std::fs::write(output_dir.join("styles.css"), STYLES)?;
+  std::fs::write(output_dir.join("search.js"), SEARCH_SCRIPT)?;
+
+  let mut search_index = Vec::with_capacity(symbols.len());
+  for ((kind, name), nodes) in &symbols {
+    let href = symbol_href(kind, name);
+    let signature = format!("{}", doc::DocPrinter::new(nodes, false, private));
+    let locations = nodes
+      .iter()
+      .map(|node| {
+        format!(
+          r#"<div class="location">Defined in {}:{}:{}</div>"#,
+          escape_html(&node.location.filename),
+          node.location.line,
+          node.location.col,
+        )
+      })
+      .collect::<Vec<_>>()
+      .join("\n");
+    let body = format!(
+      "<p><a href=\"index.html\">&larr; {title}</a></p>\n<h1><span class=\"kind\">{kind}</span>{name}</h1>\n{locations}\n<pre>{signature}</pre>",
+      title = escape_html(title),
+      kind = escape_html(kind),
+      name = escape_html(name),
+      signature = link_symbols(&escape_html(signature.trim_end()), &hrefs),
+    );
+    std::fs::write(
+      output_dir.join(&href),
+      render_page(&format!("{name} - {title}"), &body, false),
+    )?;
+
+    let doc = nodes
+      .iter()
+      .find_map(|node| node.js_doc.doc.as_deref())
+      .and_then(|doc| doc.lines().next())
+      .unwrap_or("");
+    search_index.push(json!({
+      "name": name,
+      "kind": kind,
+      "href": href,
+      "doc": doc,
+    }));
+  }
+
+  std::fs::write(
+    output_dir.join("search_index.js"),
+    format!(
+      "const SEARCH_INDEX = {};\n",
+      serde_json::to_string(&search_index)?
+    ),
+  )?;
+  let body = format!(
+    "<h1>{}</h1>\n<input id=\"search\" type=\"search\" placeholder=\"Search symbols\" autofocus>\n<ul id=\"symbols\" class=\"symbols\"></ul>",
+    escape_html(title),
+  );
+  std::fs::write(
+    output_dir.join("index.html"),
+    render_page(title, &body, true),
+  )?;
+
+  Ok(())
+}
+
+fn render_page(title: &str, body: &str, include_search: bool) -> String {
+  let scripts = if include_search {
+    "\n<script src=\"search_index.js\"></script>\n<script src=\"search.js\"></script>"
+  } else {
+    ""
+  };
+  format!(
+    "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<link rel=\"stylesheet\" href=\"styles.css\">\n</head>\n<body>\n{}{}\n</body>\n</html>\n",
+    escape_html(title),
+    body,
+    scripts,
+  )
+}
+
+fn kind_name(kind: &doc::DocNodeKind) -> String {
+  match serde_json::to_value(kind) {
+    Ok(serde_json::Value::String(name)) => name,
+    _ => format!("{kind:?}").to_lowercase(),
+  }
+}
+
+fn symbol_href(kind: &str, name: &str) -> String {
+  let name = name
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect::<String>();
+  format!("{kind}.{name}.html")
+}
+
+/// Links the identifiers in the (already escaped) text that refer to other
+/// documented symbols.
+fn link_symbols(text: &str, hrefs: &HashMap<String, String>) -> String {
+  static IDENTIFIER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[A-Za-z_$][\w$]*").unwrap());
+
+  IDENTIFIER_RE
+    .replace_all(text, |captures: &Captures| {
+      let ident = &captures[0];
+      match hrefs.get(ident) {
+        Some(href) => format!(r#"<a href="{href}">{ident}</a>"#),
+        None => ident.to_string(),
+      }
+    })
+    .to_string()
+}
+
+fn escape_html(text: &str) -> String {
+  let mut output = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => output.push_str("&amp;"),
+      '<' => output.push_str("&lt;"),
+      '>' => output.push_str("&gt;"),
+      '"' => output.push_str("&quot;"),
+      '\'' => output.push_str("&#39;"),
+      c => output.push(c),
+    }
+  }
+  output
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn link_symbols_in_text() {
+    let hrefs = HashMap::from([
+      ("Foo".to_string(), "class.Foo.html".to_string()),
+      ("bar".to_string(), "function.bar.html".to_string()),
+    ]);
+    assert_eq!(
+      link_symbols("function bar(foo: Foo): FooBar", &hrefs),
+      concat!(
+        r#"function <a href="function.bar.html">bar</a>(foo: "#,
+        r#"<a href="class.Foo.html">Foo</a>): FooBar"#,
+      ),
+    );
+  }
+
+  #[test]
+  fn escapes_html() {
+    assert_eq!(
+      escape_html(r#"<T extends "a" & 'b'>"#),
+      "&lt;T extends &quot;a&quot; &amp; &#39;b&#39;&gt;"
+    );
+  }
+
+  #[test]
+  fn symbol_hrefs() {
+    assert_eq!(symbol_href("class", "Foo"), "class.Foo.html");
+    assert_eq!(symbol_href("namespace", "a/b"), "namespace.a_b.html");
+  }
+}
@@ -0,0 +1,189 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Checks the documentation of exported symbols for `deno doc --lint`.
+
+use crate::args::DocLintConfig;
+use crate::colors;
+
+use deno_doc as doc;
+use std::collections::HashSet;
+
+const MISSING_JSDOC: &str = "missing-jsdoc";
+const MISSING_PARAM_DOC: &str = "missing-param-doc";
+const INVALID_PARAM_NAME: &str = "invalid-param-name";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocDiagnostic {
+  pub code: &'static str,
+  pub message: String,
+  pub location: doc::Location,
+}
+
+impl DocDiagnostic {
+  pub fn display(&self) -> String {
+    format!(
+      "({}) {}\n    at {}:{}:{}",
+      colors::red(self.code),
+      self.message,
+      colors::cyan(&self.location.filename),
+      colors::yellow(&self.location.line.to_string()),
+      colors::yellow(&(self.location.col + 1).to_string()),
+    )
+  }
+}
+
+/// Lints the documentation of the provided doc nodes, skipping any rules
+/// excluded in the configuration file.
+pub fn lint_doc_nodes(
+  doc_nodes: &[doc::DocNode],
+  config: &DocLintConfig,
+) -> Vec<DocDiagnostic> {
+  let mut linter = DocLinter {
+    excluded: config.exclude.iter().map(|s| s.as_str()).collect(),
+    diagnostics: Vec::new(),
+  };
+  for node in doc_nodes {
+    linter.lint_node(node, "");
+  }
+  let mut diagnostics = linter.diagnostics;
+  diagnostics.sort_by(|a, b| {
+    (&a.location.filename, a.location.line, a.location.col).cmp(&(
+      &b.location.filename,
+      b.location.line,
+      b.location.col,
+    ))
+  });
+  diagnostics
+}
+
+struct DocLinter<'a> {
+  excluded: HashSet<&'a str>,
+  diagnostics: Vec<DocDiagnostic>,
+}
+
+impl<'a> DocLinter<'a> {
+  fn add(
+    &mut self,
+    code: &'static str,
+    message: String,
+    location: &doc::Location,
+  ) {
+    if !self.excluded.contains(code) {
+      self.diagnostics.push(DocDiagnostic {
+        code,
+        message,
+        location: location.clone(),
+      });
+    }
+  }
+
+  fn lint_node(&mut self, node: &doc::DocNode, namespace: &str) {
+    if matches!(
+      node.kind,
+      doc::DocNodeKind::Import | doc::DocNodeKind::ModuleDoc
+    ) {
+      return;
+    }
+    let name = format!("{}{}", namespace, node.name);
+    if node.js_doc.doc.is_none() {
+      self.add(
+        MISSING_JSDOC,
+        format!("Exported symbol \"{name}\" is missing a JSDoc description."),
+        &node.location,
+      );
+    }
+    if let Some(function_def) = &node.function_def {
+      self.lint_params(&name, &node.js_doc, function_def, &node.location);
+    }
+    if let Some(class_def) = &node.class_def {
+      for method in &class_def.methods {
+        if method.js_doc.doc.is_none() && method.js_doc.tags.is_empty() {
+          continue;
+        }
+        self.lint_params(
+          &format!("{}.{}", name, method.name),
+          &method.js_doc,
+          &method.function_def,
+          &method.location,
+        );
+      }
+    }
+    if let Some(namespace_def) = &node.namespace_def {
+      let namespace = format!("{name}.");
+      for element in &namespace_def.elements {
+        self.lint_node(element, &namespace);
+      }
+    }
+  }
+
+  fn lint_params(
+    &mut self,
+    name: &str,
+    js_doc: &doc::js_doc::JsDoc,
+    function_def: &doc::function::FunctionDef,
+    location: &doc::Location,
+  ) {
+    let param_tags = js_doc
+      .tags
+      .iter()
+      .filter_map(|tag| match tag {
+        doc::js_doc::JsDocTag::Param { name, .. } => Some(name.as_str()),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    // there's nothing to check when the function isn't documented at all
+    // because that's already reported as missing JSDoc
+    if js_doc.doc.is_none() && param_tags.is_empty() {
+      return;
+    }
+
+    let mut param_names = Vec::with_capacity(function_def.params.len());
+    let mut has_destructured_param = false;
+    for param in &function_def.params {
+      match param_name(param) {
+        Some(param_name) => param_names.push(param_name),
+        None => has_destructured_param = true,
+      }
+    }
+
+    for param_name in &param_names {
+      let is_documented = param_tags.iter().any(|tag| {
+        tag == param_name
+          || tag
+            .strip_prefix(param_name)
+            .map(|rest| rest.starts_with('.'))
+            .unwrap_or(false)
+      });
+      if !is_documented {
+        self.add(
+          MISSING_PARAM_DOC,
+          format!("Parameter \"{param_name}\" of \"{name}\" is not documented with a @param tag."),
+          location,
+        );
+      }
+    }
+
+    // destructured parameters may be documented with any name
+    if !has_destructured_param {
+      for tag in &param_tags {
+        let root_name = tag.split('.').next().unwrap();
+        if !param_names.contains(&root_name) {
+          self.add(
+            INVALID_PARAM_NAME,
+            format!("@param \"{tag}\" of \"{name}\" does not match any parameter name."),
+            location,
+          );
+        }
+      }
+    }
+  }
+}
+
+fn param_name(param: &doc::ParamDef) -> Option<&str> {
+  match param {
+    doc::ParamDef::Identifier { name, .. } => Some(name),
+    doc::ParamDef::Assign { left, .. } => param_name(left),
+    doc::ParamDef::Rest { arg, .. } => param_name(arg),
+    doc::ParamDef::Array { .. } | doc::ParamDef::Object { .. } => None,
+  }
+}
@@ -15,16 +15,27 @@ use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::resolve_path;
 use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
 use deno_doc as doc;
 use deno_graph::ModuleSpecifier;
 use std::path::PathBuf;
 
+mod html;
+mod lint;
+pub mod references;
+
 pub async fn print_docs(
   flags: Flags,
   doc_flags: DocFlags,
 ) -> Result<(), AnyError> {
   let ps = ProcState::from_flags(flags).await?;
 
+  let title = match &doc_flags.source_file {
+    DocSourceFileFlag::Builtin => "Deno".to_string(),
+    DocSourceFileFlag::Path(source_file) => source_file.clone(),
+  };
+  // resolves the targets of the type references for the JSON output
+  let mut maybe_symbol_resolver = None;
   let mut doc_nodes = match doc_flags.source_file {
     DocSourceFileFlag::Builtin => {
       let source_file_specifier =
@@ -89,6 +100,13 @@ pub async fn print_docs(
         graph_lock_or_exit(&graph, &mut lockfile.lock());
       }
 
+      if doc_flags.json {
+        maybe_symbol_resolver = Some(references::SymbolResolver::new(
+          &graph,
+          &ps.parsed_source_cache,
+        ));
+      }
+
       let doc_parser = doc::DocParser::new(
         graph,
         doc_flags.private,
@@ -98,8 +116,38 @@ pub async fn print_docs(
     }
   };
 
-  if doc_flags.json {
-    write_json_to_stdout(&doc_nodes)
+  if doc_flags.lint {
+    let doc_config = match ps.options.maybe_config_file() {
+      Some(config_file) => config_file.to_doc_config()?.unwrap_or_default(),
+      None => Default::default(),
+    };
+    let diagnostics = lint::lint_doc_nodes(&doc_nodes, &doc_config.lint);
+    for diagnostic in &diagnostics {
+      log::error!("{}\n", diagnostic.display());
+    }
+    match diagnostics.len() {
+      0 => Ok(()),
+      1 => bail!("Found 1 documentation problem"),
+      len => bail!("Found {} documentation problems", len),
+    }
+  } else if let Some(html_flag) = doc_flags.html {
+    let output_dir = ps.options.initial_cwd().join(html_flag.output);
+    html::write_html_docs(&title, &doc_nodes, doc_flags.private, &output_dir)?;
+    log::info!(
+      "{} {}",
+      colors::green("Written"),
+      output_dir.join("index.html").display()
+    );
+    Ok(())
+  } else if doc_flags.json {
+    match maybe_symbol_resolver {
+      Some(symbol_resolver) => {
+        let mut value = serde_json::to_value(&doc_nodes)?;
+        references::add_reference_targets(&mut value, &symbol_resolver, None);
+        write_json_to_stdout(&value)
+      }
+      None => write_json_to_stdout(&doc_nodes),
+    }
   } else {
     doc_nodes.retain(|doc_node| doc_node.kind != doc::DocNodeKind::Import);
     let details = if let Some(filter) = doc_flags.filter {
@@ -0,0 +1,430 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Resolves the symbols that the type references of the doc nodes point to
+//! across the modules of the graph, so `deno doc --json` can include them
+//! and docs sites don't need to re-implement the module resolution.
+
+use deno_ast::swc::ast::Decl;
+use deno_ast::swc::ast::DefaultDecl;
+use deno_ast::swc::ast::ExportSpecifier;
+use deno_ast::swc::ast::ImportSpecifier;
+use deno_ast::swc::ast::ModuleDecl;
+use deno_ast::swc::ast::ModuleExportName;
+use deno_ast::swc::ast::ModuleItem;
+use deno_ast::swc::ast::Pat;
+use deno_ast::swc::ast::Stmt;
+use deno_ast::swc::ast::TsModuleName;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_graph::EsmModule;
+use deno_graph::ModuleGraph;
+use deno_graph::ModuleSpecifier;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::cache::ParsedSourceCache;
+
+/// The name used for the namespace object of a module.
+const NAMESPACE_NAME: &str = "*";
+
+#[derive(Debug, Default)]
+struct ModuleSymbols {
+  /// The names declared at the top level of the module.
+  declarations: HashSet<String>,
+  /// The local names of the imports, with the module and the name they
+  /// import.
+  imports: HashMap<String, (ModuleSpecifier, String)>,
+  /// The names exported with an `export { ... }` clause, with the module and
+  /// the name they refer to. Local exports refer to the module itself.
+  exports: HashMap<String, (ModuleSpecifier, String)>,
+  /// The modules that are re-exported with `export * from "..."`.
+  star_exports: Vec<ModuleSpecifier>,
+}
+
+/// The symbols declared, imported and exported by each module of a graph.
+#[derive(Debug, Default)]
+pub struct SymbolResolver {
+  modules: HashMap<ModuleSpecifier, ModuleSymbols>,
+}
+
+impl SymbolResolver {
+  pub fn new(
+    graph: &ModuleGraph,
+    parsed_source_cache: &ParsedSourceCache,
+  ) -> Self {
+    let mut modules = HashMap::new();
+    for module in graph.modules() {
+      let module = match module {
+        deno_graph::Module::Esm(module) => module,
+        _ => continue,
+      };
+      let parsed_source =
+        match parsed_source_cache.get_parsed_source_from_esm_module(module) {
+          Ok(parsed_source) => parsed_source,
+          Err(_) => continue,
+        };
+      modules.insert(
+        module.specifier.clone(),
+        analyze_module(graph, module, &parsed_source.module().body),
+      );
+    }
+    Self { modules }
+  }
+
+  /// Resolves the module and name of the symbol a type reference in the
+  /// provided module points to (ex. `Foo` or `ns.Foo`).
+  pub fn resolve_reference(
+    &self,
+    specifier: &ModuleSpecifier,
+    type_name: &str,
+  ) -> Option<(ModuleSpecifier, String)> {
+    let mut parts = type_name.split('.');
+    let mut visited = HashSet::new();
+    let (mut specifier, mut name) =
+      self.resolve_local(specifier, parts.next()?, &mut visited)?;
+    for part in parts {
+      if name == NAMESPACE_NAME {
+        (specifier, name) =
+          self.resolve_export(&specifier, part, &mut visited)?;
+      } else {
+        name.push('.');
+        name.push_str(part);
+      }
+    }
+    if name == NAMESPACE_NAME {
+      None
+    } else {
+      Some((specifier, name))
+    }
+  }
+
+  fn resolve_local(
+    &self,
+    specifier: &ModuleSpecifier,
+    name: &str,
+    visited: &mut HashSet<(ModuleSpecifier, String)>,
+  ) -> Option<(ModuleSpecifier, String)> {
+    let symbols = self.modules.get(specifier)?;
+    if symbols.declarations.contains(name) {
+      return Some((specifier.clone(), name.to_string()));
+    }
+    let (import_specifier, imported_name) = symbols.imports.get(name)?;
+    if imported_name == NAMESPACE_NAME {
+      Some((import_specifier.clone(), imported_name.clone()))
+    } else {
+      self.resolve_export(import_specifier, imported_name, visited)
+    }
+  }
+
+  fn resolve_export(
+    &self,
+    specifier: &ModuleSpecifier,
+    name: &str,
+    visited: &mut HashSet<(ModuleSpecifier, String)>,
+  ) -> Option<(ModuleSpecifier, String)> {
+    if !visited.insert((specifier.clone(), name.to_string())) {
+      return None; // circular re-exports
+    }
+    let symbols = self.modules.get(specifier)?;
+    if let Some((export_specifier, export_name)) = symbols.exports.get(name) {
+      return if export_specifier == specifier {
+        self.resolve_local(specifier, export_name, visited)
+      } else if export_name == NAMESPACE_NAME {
+        Some((export_specifier.clone(), export_name.clone()))
+      } else {
+        self.resolve_export(export_specifier, export_name, visited)
+      };
+    }
+    if symbols.declarations.contains(name) {
+      return Some((specifier.clone(), name.to_string()));
+    }
+    symbols.star_exports.iter().find_map(|star_specifier| {
+      self.resolve_export(star_specifier, name, visited)
+    })
+  }
+}
+
+/// Adds the resolved `target` of each type reference in the JSON of the doc
+/// nodes, based on the module in the location of the enclosing doc node.
+pub fn add_reference_targets(
+  value: &mut Value,
+  resolver: &SymbolResolver,
+  maybe_specifier: Option<&ModuleSpecifier>,
+) {
+  match value {
+    Value::Array(items) => {
+      for item in items {
+        add_reference_targets(item, resolver, maybe_specifier);
+      }
+    }
+    Value::Object(map) => {
+      let location_specifier = map
+        .get("location")
+        .and_then(|location| location.get("filename"))
+        .and_then(|filename| filename.as_str())
+        .and_then(|filename| ModuleSpecifier::parse(filename).ok());
+      let maybe_specifier = location_specifier.as_ref().or(maybe_specifier);
+      if let Some(specifier) = maybe_specifier {
+        if map.get("kind").and_then(|kind| kind.as_str()) == Some("typeRef") {
+          if let Some(Value::Object(type_ref)) = map.get_mut("typeRef") {
+            let maybe_target = type_ref
+              .get("typeName")
+              .and_then(|type_name| type_name.as_str())
+              .and_then(|type_name| {
+                resolver.resolve_reference(specifier, type_name)
+              });
+            if let Some((target_specifier, target_name)) = maybe_target {
+              type_ref.insert(
+                "target".to_string(),
+                json!({
+                  "specifier": target_specifier,
+                  "name": target_name,
+                }),
+              );
+            }
+          }
+        }
+      }
+      for value in map.values_mut() {
+        add_reference_targets(value, resolver, maybe_specifier);
+      }
+    }
+    _ => {}
+  }
+}
+
+fn analyze_module(
+  graph: &ModuleGraph,
+  module: &EsmModule,
+  body: &[ModuleItem],
+) -> ModuleSymbols {
+  let resolve_dependency = |src: &str| {
+    let dependency = module.dependencies.get(src)?;
+    let specifier = dependency
+      .maybe_type
+      .maybe_specifier()
+      .or_else(|| dependency.maybe_code.maybe_specifier())?;
+    graph
+      .get(specifier)
+      .map(|module| module.specifier().clone())
+  };
+
+  let mut symbols = ModuleSymbols::default();
+  for item in body {
+    match item {
+      ModuleItem::Stmt(Stmt::Decl(decl)) => {
+        symbols.declarations.extend(decl_names(decl));
+      }
+      ModuleItem::Stmt(_) => {}
+      ModuleItem::ModuleDecl(module_decl) => match module_decl {
+        ModuleDecl::ExportDecl(export_decl) => {
+          symbols.declarations.extend(decl_names(&export_decl.decl));
+        }
+        ModuleDecl::ExportDefaultDecl(export_default_decl) => {
+          let maybe_ident = match &export_default_decl.decl {
+            DefaultDecl::Class(class_expr) => class_expr.ident.as_ref(),
+            DefaultDecl::Fn(fn_expr) => fn_expr.ident.as_ref(),
+            DefaultDecl::TsInterfaceDecl(interface_decl) => {
+              Some(&interface_decl.id)
+            }
+          };
+          if let Some(ident) = maybe_ident {
+            symbols.declarations.insert(ident.sym.to_string());
+          }
+        }
+        ModuleDecl::Import(import_decl) => {
+          let import_specifier =
+            match resolve_dependency(&import_decl.src.value) {
+              Some(specifier) => specifier,
+              None => continue,
+            };
+          for specifier in &import_decl.specifiers {
+            let (local, imported) = match specifier {
+              ImportSpecifier::Named(named) => (
+                named.local.sym.to_string(),
+                named
+                  .imported
+                  .as_ref()
+                  .map(export_name)
+                  .unwrap_or_else(|| named.local.sym.to_string()),
+              ),
+              ImportSpecifier::Default(default) => {
+                (default.local.sym.to_string(), "default".to_string())
+              }
+              ImportSpecifier::Namespace(namespace) => {
+                (namespace.local.sym.to_string(), NAMESPACE_NAME.to_string())
+              }
+            };
+            symbols
+              .imports
+              .insert(local, (import_specifier.clone(), imported));
+          }
+        }
+        ModuleDecl::ExportNamed(named_export) => {
+          let export_specifier = match &named_export.src {
+            Some(src) => match resolve_dependency(&src.value) {
+              Some(specifier) => specifier,
+              None => continue,
+            },
+            None => module.specifier.clone(),
+          };
+          for specifier in &named_export.specifiers {
+            let (exported, orig) = match specifier {
+              ExportSpecifier::Named(named) => (
+                export_name(named.exported.as_ref().unwrap_or(&named.orig)),
+                export_name(&named.orig),
+              ),
+              ExportSpecifier::Namespace(namespace) => {
+                (export_name(&namespace.name), NAMESPACE_NAME.to_string())
+              }
+              ExportSpecifier::Default(default) => {
+                (default.exported.sym.to_string(), "default".to_string())
+              }
+            };
+            symbols
+              .exports
+              .insert(exported, (export_specifier.clone(), orig));
+          }
+        }
+        ModuleDecl::ExportAll(export_all) => {
+          if let Some(specifier) = resolve_dependency(&export_all.src.value) {
+            symbols.star_exports.push(specifier);
+          }
+        }
+        _ => {}
+      },
+    }
+  }
+  symbols
+}
+
+pub fn decl_names(decl: &Decl) -> Vec<String> {
+  match decl {
+    Decl::Class(class_decl) => vec![class_decl.ident.sym.to_string()],
+    Decl::Fn(fn_decl) => vec![fn_decl.ident.sym.to_string()],
+    Decl::Var(var_decl) => var_decl
+      .decls
+      .iter()
+      .filter_map(|declarator| match &declarator.name {
+        Pat::Ident(ident) => Some(ident.id.sym.to_string()),
+        _ => None,
+      })
+      .collect(),
+    Decl::TsInterface(interface_decl) => {
+      vec![interface_decl.id.sym.to_string()]
+    }
+    Decl::TsTypeAlias(type_alias_decl) => {
+      vec![type_alias_decl.id.sym.to_string()]
+    }
+    Decl::TsEnum(enum_decl) => vec![enum_decl.id.sym.to_string()],
+    Decl::TsModule(module_decl) => match &module_decl.id {
+      TsModuleName::Ident(ident) => vec![ident.sym.to_string()],
+      TsModuleName::Str(_) => Vec::new(),
+    },
+    _ => Vec::new(),
+  }
+}
+
+pub fn export_name(name: &ModuleExportName) -> String {
+  match name {
+    ModuleExportName::Ident(ident) => ident.sym.to_string(),
+    ModuleExportName::Str(str) => str.value.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use deno_graph::source::MemoryLoader;
+  use deno_graph::source::Source;
+
+  async fn create_resolver(
+    sources: Vec<(&str, &str)>,
+  ) -> (ModuleGraph, SymbolResolver) {
+    let mut loader = MemoryLoader::new(
+      sources
+        .into_iter()
+        .map(|(specifier, content)| {
+          (
+            specifier.to_string(),
+            Source::Module {
+              specifier: specifier.to_string(),
+              content: content.to_string(),
+              maybe_headers: None,
+            },
+          )
+        })
+        .collect(),
+      Vec::new(),
+    );
+    let mut graph = ModuleGraph::default();
+    graph
+      .build(
+        vec![ModuleSpecifier::parse("file:///mod.ts").unwrap()],
+        &mut loader,
+        Default::default(),
+      )
+      .await;
+    let parsed_source_cache = ParsedSourceCache::new_in_memory();
+    let resolver = SymbolResolver::new(&graph, &parsed_source_cache);
+    (graph, resolver)
+  }
+
+  #[tokio::test]
+  async fn resolves_references_across_modules() {
+    let (_graph, resolver) = create_resolver(vec![
+      (
+        "file:///mod.ts",
+        r#"import { Bar as Baz } from "./reexports.ts";
+import * as types from "./types.ts";
+export * from "./reexports.ts";
+interface Local {}
+"#,
+      ),
+      (
+        "file:///reexports.ts",
+        r#"export { Bar } from "./types.ts";
+export * from "./all.ts";
+"#,
+      ),
+      (
+        "file:///types.ts",
+        "export interface Bar {}\nexport type Qux = 1;",
+      ),
+      ("file:///all.ts", "export class Quux {}"),
+    ])
+    .await;
+    let mod_specifier = ModuleSpecifier::parse("file:///mod.ts").unwrap();
+    let types_specifier = ModuleSpecifier::parse("file:///types.ts").unwrap();
+    let resolve = |name: &str| resolver.resolve_reference(&mod_specifier, name);
+
+    assert_eq!(
+      resolve("Local"),
+      Some((mod_specifier.clone(), "Local".to_string()))
+    );
+    assert_eq!(
+      resolve("Baz"),
+      Some((types_specifier.clone(), "Bar".to_string()))
+    );
+    assert_eq!(
+      resolve("types.Qux"),
+      Some((types_specifier, "Qux".to_string()))
+    );
+    assert_eq!(resolve("Promise"), None);
+    assert_eq!(resolve("types"), None);
+
+    let mut value = json!([{
+      "location": { "filename": "file:///mod.ts", "line": 1, "col": 0 },
+      "tsType": {
+        "repr": "Baz",
+        "kind": "typeRef",
+        "typeRef": { "typeParams": null, "typeName": "Baz" },
+      },
+    }]);
+    add_reference_targets(&mut value, &resolver, None);
+    assert_eq!(
+      value[0]["tsType"]["typeRef"]["target"],
+      json!({ "specifier": "file:///types.ts", "name": "Bar" })
+    );
+  }
+}
@@ -13,6 +13,7 @@ use crate::args::FmtOptions;
 use crate::args::FmtOptionsConfig;
 use crate::args::ProseWrap;
 use crate::cache::Caches;
+use crate::cache::DenoDir;
 use crate::colors;
 use crate::util::diff::diff;
 use crate::util::file_watcher;
@@ -31,6 +32,9 @@ use deno_core::parking_lot::Mutex;
 use log::debug;
 use log::info;
 use log::warn;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
 use std::fs;
 use std::io::stdin;
 use std::io::stdout;
@@ -43,12 +47,20 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use crate::cache::IncrementalCache;
+use crate::cache::ParsedSourceCache;
+use crate::tools::organize_imports::organize_imports_in_file;
 
 /// Format JavaScript/TypeScript files.
 pub async fn format(
-  cli_options: CliOptions,
+  cli_options: Arc<CliOptions>,
   fmt_options: FmtOptions,
 ) -> Result<(), AnyError> {
+  let deno_dir = &cli_options.resolve_deno_dir()?;
+  let caches = Caches::default();
+  let parsed_source_cache = Arc::new(ParsedSourceCache::new(
+    caches.dep_analysis_db(deno_dir),
+    None,
+  ));
   if fmt_options.is_stdin {
     return format_stdin(
       fmt_options,
@@ -57,12 +69,12 @@ pub async fn format(
         .as_ref()
         .map(|s| s.as_str())
         .unwrap_or("ts"),
+      &parsed_source_cache,
     );
   }
 
-  let files = fmt_options.files;
+  let files = fmt_options.files.clone();
   let check = fmt_options.check;
-  let fmt_config_options = fmt_options.options;
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
@@ -84,7 +96,7 @@ pub async fn format(
       } else {
         files
       };
-      (refmt_files, fmt_config_options.clone())
+      (refmt_files, fmt_options.clone())
     });
 
     let paths_to_watch = files.include.clone();
@@ -101,21 +113,20 @@ pub async fn format(
       }
     }
   };
-  let deno_dir = &cli_options.resolve_deno_dir()?;
-  let caches = Caches::default();
-  let operation = |(paths, fmt_options): (Vec<PathBuf>, FmtOptionsConfig)| async {
-    let incremental_cache = Arc::new(IncrementalCache::new(
-      caches.fmt_incremental_cache_db(deno_dir),
-      &fmt_options,
+  let operation = |(paths, fmt_options): (Vec<PathBuf>, FmtOptions)| async {
+    let scopes = Arc::new(FmtScopes::new(
+      &caches,
+      deno_dir,
+      parsed_source_cache.clone(),
       &paths,
+      &fmt_options,
     ));
     if check {
-      check_source_files(paths, fmt_options, incremental_cache.clone()).await?;
+      check_source_files(paths, scopes.clone()).await?;
     } else {
-      format_source_files(paths, fmt_options, incremental_cache.clone())
-        .await?;
+      format_source_files(paths, scopes.clone()).await?;
     }
-    incremental_cache.wait_completion().await;
+    scopes.wait_completion().await;
     Ok(())
   };
 
@@ -126,6 +137,9 @@ pub async fn format(
       file_watcher::PrintConfig {
         job_name: "Fmt".to_string(),
         clear_screen: !cli_options.no_clear_screen(),
+        filter: cli_options.watch_filter()?,
+        debounce: cli_options.watch_debounce()?,
+        before_restart: crate::tools::task::before_restart_hook(&cli_options)?,
       },
     )
     .await?;
@@ -137,12 +151,77 @@ pub async fn format(
         Ok(files)
       }
     })?;
-    operation((files, fmt_config_options)).await?;
+    operation((files, fmt_options)).await?;
   }
 
   Ok(())
 }
 
+/// The options of the files that are formatted alike, like the ones in a
+/// directory with an override, along with an incremental cache keyed by them.
+struct FmtScope {
+  options: FmtOptionsConfig,
+  incremental_cache: Arc<IncrementalCache>,
+}
+
+struct FmtScopes {
+  scopes: Vec<FmtScope>,
+  file_scopes: HashMap<PathBuf, usize>,
+  /// Parses the files whose imports are organized.
+  parsed_source_cache: Arc<ParsedSourceCache>,
+}
+
+impl FmtScopes {
+  fn new(
+    caches: &Caches,
+    deno_dir: &DenoDir,
+    parsed_source_cache: Arc<ParsedSourceCache>,
+    paths: &[PathBuf],
+    fmt_options: &FmtOptions,
+  ) -> Self {
+    let mut scope_paths: Vec<(FmtOptionsConfig, Vec<PathBuf>)> = Vec::new();
+    for path in paths {
+      let options = fmt_options.options_for_path(path);
+      match scope_paths.iter_mut().find(|(o, _)| *o == options) {
+        Some((_, paths)) => paths.push(path.clone()),
+        None => scope_paths.push((options, vec![path.clone()])),
+      }
+    }
+    let mut file_scopes = HashMap::new();
+    let scopes = scope_paths
+      .into_iter()
+      .enumerate()
+      .map(|(index, (options, paths))| {
+        let incremental_cache = Arc::new(IncrementalCache::new(
+          caches.fmt_incremental_cache_db(deno_dir),
+          &options,
+          &paths,
+        ));
+        file_scopes.extend(paths.into_iter().map(|path| (path, index)));
+        FmtScope {
+          options,
+          incremental_cache,
+        }
+      })
+      .collect();
+    Self {
+      scopes,
+      file_scopes,
+      parsed_source_cache,
+    }
+  }
+
+  fn get(&self, file_path: &Path) -> &FmtScope {
+    &self.scopes[self.file_scopes[file_path]]
+  }
+
+  async fn wait_completion(&self) {
+    for scope in &self.scopes {
+      scope.incremental_cache.wait_completion().await;
+    }
+  }
+}
+
 fn collect_fmt_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
   FileCollector::new(is_supported_ext_fmt)
     .ignore_git_folder()
@@ -158,50 +237,13 @@ fn format_markdown(
   fmt_options: &FmtOptionsConfig,
 ) -> Result<Option<String>, AnyError> {
   let markdown_config = get_resolved_markdown_config(fmt_options);
+  let format_code_blocks = fmt_options.embedded_code.unwrap_or(true);
   dprint_plugin_markdown::format_text(
     file_text,
     &markdown_config,
     move |tag, text, line_width| {
-      let tag = tag.to_lowercase();
-      if matches!(
-        tag.as_str(),
-        "ts"
-          | "tsx"
-          | "js"
-          | "jsx"
-          | "cjs"
-          | "cts"
-          | "mjs"
-          | "mts"
-          | "javascript"
-          | "typescript"
-          | "json"
-          | "jsonc"
-      ) {
-        // It's important to tell dprint proper file extension, otherwise
-        // it might parse the file twice.
-        let extension = match tag.as_str() {
-          "javascript" => "js",
-          "typescript" => "ts",
-          rest => rest,
-        };
-
-        if matches!(extension, "json" | "jsonc") {
-          let mut json_config = get_resolved_json_config(fmt_options);
-          json_config.line_width = line_width;
-          dprint_plugin_json::format_text(text, &json_config)
-        } else {
-          let fake_filename =
-            PathBuf::from(format!("deno_fmt_stdin.{extension}"));
-          let mut codeblock_config =
-            get_resolved_typescript_config(fmt_options);
-          codeblock_config.line_width = line_width;
-          dprint_plugin_typescript::format_text(
-            &fake_filename,
-            text,
-            &codeblock_config,
-          )
-        }
+      if format_code_blocks {
+        format_embedded_code(tag, text, line_width, fmt_options)
       } else {
         Ok(None)
       }
@@ -209,6 +251,222 @@ fn format_markdown(
   )
 }
 
+/// Formats a block of code embedded in another document based on its
+/// language tag (ts/tsx, js/jsx, json/jsonc). Returns `Ok(None)` for
+/// unknown languages.
+fn format_embedded_code(
+  tag: &str,
+  text: &str,
+  line_width: u32,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  let tag = tag.to_lowercase();
+  if matches!(
+    tag.as_str(),
+    "ts"
+      | "tsx"
+      | "js"
+      | "jsx"
+      | "cjs"
+      | "cts"
+      | "mjs"
+      | "mts"
+      | "javascript"
+      | "typescript"
+      | "json"
+      | "jsonc"
+  ) {
+    // It's important to tell dprint proper file extension, otherwise
+    // it might parse the file twice.
+    let extension = match tag.as_str() {
+      "javascript" => "js",
+      "typescript" => "ts",
+      rest => rest,
+    };
+
+    if matches!(extension, "json" | "jsonc") {
+      let mut json_config = get_resolved_json_config(fmt_options);
+      json_config.line_width = line_width;
+      dprint_plugin_json::format_text(text, &json_config)
+    } else {
+      let fake_filename = PathBuf::from(format!("deno_fmt_stdin.{extension}"));
+      let mut codeblock_config = get_resolved_typescript_config(fmt_options);
+      codeblock_config.line_width = line_width;
+      dprint_plugin_typescript::format_text(
+        &fake_filename,
+        text,
+        &codeblock_config,
+      )
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+/// Formats the `<script>` blocks of HTML, Vue and Svelte files. The rest of
+/// the document is left untouched and the indentation of each block is
+/// preserved.
+fn format_script_blocks(
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<Option<String>, AnyError> {
+  if !fmt_options.embedded_code.unwrap_or(true) {
+    return Ok(None);
+  }
+
+  // lowercasing ascii characters keeps the byte offsets the same
+  let lowercase_text = file_text.to_ascii_lowercase();
+  let line_width = fmt_options.line_width.unwrap_or(80);
+  let mut output = String::with_capacity(file_text.len());
+  let mut last_end = 0;
+  let mut search_start = 0;
+  while let Some(open_start) = lowercase_text[search_start..]
+    .find("<script")
+    .map(|i| i + search_start)
+  {
+    let open_end = match lowercase_text[open_start..].find('>') {
+      Some(i) => open_start + i + 1,
+      None => break,
+    };
+    let close_start = match lowercase_text[open_end..].find("</script") {
+      Some(i) => open_end + i,
+      None => break,
+    };
+    search_start = close_start;
+
+    let content = &file_text[open_end..close_start];
+    // leave inline and empty scripts alone
+    if !content.contains('\n') || content.trim().is_empty() {
+      continue;
+    }
+    let tag = match script_block_language(&file_text[open_start..open_end]) {
+      Some(tag) => tag,
+      None => continue,
+    };
+
+    let indent = common_indent(content);
+    let closing_indent = match content.rfind('\n') {
+      Some(index) if content[index + 1..].trim().is_empty() => {
+        &content[index + 1..]
+      }
+      _ => "",
+    };
+    let code = dedent(content, indent.len());
+    let block_line_width = line_width
+      .saturating_sub(indent.chars().count() as u32)
+      .max(1);
+    let formatted =
+      format_embedded_code(tag, &code, block_line_width, fmt_options)
+        .with_context(|| format!("Error formatting <script> block: {tag}"))?
+        .unwrap_or(code);
+
+    let mut new_content = String::from("\n");
+    for line in formatted.trim_end().lines() {
+      if !line.is_empty() {
+        new_content.push_str(indent);
+        new_content.push_str(line);
+      }
+      new_content.push('\n');
+    }
+    new_content.push_str(closing_indent);
+
+    if new_content != content {
+      output.push_str(&file_text[last_end..open_end]);
+      output.push_str(&new_content);
+      last_end = close_start;
+    }
+  }
+
+  if last_end == 0 {
+    Ok(None)
+  } else {
+    output.push_str(&file_text[last_end..]);
+    Ok(Some(output))
+  }
+}
+
+/// Gets the language of a `<script>` block from its `lang` or `type`
+/// attribute, or `None` when the block shouldn't be formatted.
+fn script_block_language(open_tag: &str) -> Option<&'static str> {
+  static ATTRIBUTE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+      r#"(?i)\s([a-z-]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+)))?"#,
+    )
+    .unwrap()
+  });
+
+  let mut lang = None;
+  let mut kind = None;
+  for captures in ATTRIBUTE_RE.captures_iter(open_tag) {
+    let name = captures.get(1).unwrap().as_str().to_ascii_lowercase();
+    let value = captures
+      .get(2)
+      .or_else(|| captures.get(3))
+      .or_else(|| captures.get(4))
+      .map(|m| m.as_str().to_ascii_lowercase());
+    match name.as_str() {
+      // external scripts have no content to format
+      "src" => return None,
+      "lang" => lang = value,
+      "type" => kind = value,
+      _ => {}
+    }
+  }
+
+  if let Some(lang) = lang {
+    return match lang.as_str() {
+      "ts" | "typescript" => Some("ts"),
+      "tsx" => Some("tsx"),
+      "js" | "javascript" => Some("js"),
+      "jsx" => Some("jsx"),
+      _ => None,
+    };
+  }
+  match kind.as_deref() {
+    None | Some("module" | "text/javascript" | "application/javascript") => {
+      Some("js")
+    }
+    Some("text/typescript" | "application/typescript") => Some("ts"),
+    Some("application/json" | "application/ld+json" | "importmap") => {
+      Some("json")
+    }
+    _ => None,
+  }
+}
+
+/// Gets the leading whitespace shared by all the non-blank lines.
+fn common_indent(text: &str) -> &str {
+  let mut indent: Option<&str> = None;
+  for line in text.lines().filter(|l| !l.trim().is_empty()) {
+    let line_indent = &line[..line.len() - line.trim_start().len()];
+    indent = Some(match indent {
+      Some(indent) => {
+        let len = indent
+          .bytes()
+          .zip(line_indent.bytes())
+          .take_while(|(a, b)| a == b)
+          .count();
+        &indent[..len]
+      }
+      None => line_indent,
+    });
+  }
+  indent.unwrap_or("")
+}
+
+fn dedent(text: &str, indent_len: usize) -> String {
+  let mut output = String::with_capacity(text.len());
+  for line in text.trim_matches(|c| c == '\n' || c == '\r').lines() {
+    if line.len() >= indent_len {
+      output.push_str(&line[indent_len..]);
+    } else {
+      output.push_str(line.trim_start());
+    }
+    output.push('\n');
+  }
+  output
+}
+
 /// Formats JSON and JSONC using the rules provided by .deno()
 /// of configuration builder of <https://github.com/dprint/dprint-plugin-json>.
 /// See <https://github.com/dprint/dprint-plugin-json/blob/cfa1052dbfa0b54eb3d814318034cdc514c813d7/src/configuration/builder.rs#L87> for configuration.
@@ -220,7 +478,8 @@ pub fn format_json(
   dprint_plugin_json::format_text(file_text, &config)
 }
 
-/// Formats a single TS, TSX, JS, JSX, JSONC, JSON, or MD file.
+/// Formats a single TS, TSX, JS, JSX, JSONC, JSON, MD, HTML, Vue, or Svelte
+/// file.
 pub fn format_file(
   file_path: &Path,
   file_text: &str,
@@ -232,6 +491,8 @@ pub fn format_file(
     "md" | "mkd" | "mkdn" | "mdwn" | "mdown" | "markdown"
   ) {
     format_markdown(file_text, fmt_options)
+  } else if matches!(ext.as_str(), "html" | "htm" | "vue" | "svelte") {
+    format_script_blocks(file_text, fmt_options)
   } else if matches!(ext.as_str(), "json" | "jsonc") {
     format_json(file_text, fmt_options)
   } else {
@@ -240,6 +501,25 @@ pub fn format_file(
   }
 }
 
+/// Formats the file after organizing its imports, when that is enabled.
+fn format_file_with_imports(
+  file_path: &Path,
+  file_text: &str,
+  fmt_options: &FmtOptionsConfig,
+  parsed_source_cache: &ParsedSourceCache,
+) -> Result<Option<String>, AnyError> {
+  if fmt_options.organize_imports != Some(true) {
+    return format_file(file_path, file_text, fmt_options);
+  }
+  match organize_imports_in_file(file_path, file_text, parsed_source_cache)? {
+    Some(organized_text) => Ok(Some(
+      format_file(file_path, &organized_text, fmt_options)?
+        .unwrap_or(organized_text),
+    )),
+    None => format_file(file_path, file_text, fmt_options),
+  }
+}
+
 pub fn format_parsed_source(
   parsed_source: &ParsedSource,
   fmt_options: &FmtOptionsConfig,
@@ -252,8 +532,7 @@ pub fn format_parsed_source(
 
 async fn check_source_files(
   paths: Vec<PathBuf>,
-  fmt_options: FmtOptionsConfig,
-  incremental_cache: Arc<IncrementalCache>,
+  scopes: Arc<FmtScopes>,
 ) -> Result<(), AnyError> {
   let not_formatted_files_count = Arc::new(AtomicUsize::new(0));
   let checked_files_count = Arc::new(AtomicUsize::new(0));
@@ -267,13 +546,22 @@ async fn check_source_files(
     move |file_path| {
       checked_files_count.fetch_add(1, Ordering::Relaxed);
       let file_text = read_file_contents(&file_path)?.text;
+      let FmtScope {
+        options: fmt_options,
+        incremental_cache,
+      } = scopes.get(&file_path);
 
       // skip checking the file if we know it's formatted
       if incremental_cache.is_file_same(&file_path, &file_text) {
         return Ok(());
       }
 
-      match format_file(&file_path, &file_text, &fmt_options) {
+      match format_file_with_imports(
+        &file_path,
+        &file_text,
+        fmt_options,
+        &scopes.parsed_source_cache,
+      ) {
         Ok(Some(formatted_text)) => {
           not_formatted_files_count.fetch_add(1, Ordering::Relaxed);
           let _g = output_lock.lock();
@@ -333,8 +621,7 @@ async fn check_source_files(
 
 async fn format_source_files(
   paths: Vec<PathBuf>,
-  fmt_options: FmtOptionsConfig,
-  incremental_cache: Arc<IncrementalCache>,
+  scopes: Arc<FmtScopes>,
 ) -> Result<(), AnyError> {
   let formatted_files_count = Arc::new(AtomicUsize::new(0));
   let checked_files_count = Arc::new(AtomicUsize::new(0));
@@ -346,6 +633,10 @@ async fn format_source_files(
     move |file_path| {
       checked_files_count.fetch_add(1, Ordering::Relaxed);
       let file_contents = read_file_contents(&file_path)?;
+      let FmtScope {
+        options: fmt_options,
+        incremental_cache,
+      } = scopes.get(&file_path);
 
       // skip formatting the file if we know it's formatted
       if incremental_cache.is_file_same(&file_path, &file_contents.text) {
@@ -355,8 +646,15 @@ async fn format_source_files(
       match format_ensure_stable(
         &file_path,
         &file_contents.text,
-        &fmt_options,
-        format_file,
+        fmt_options,
+        |file_path, file_text, fmt_options| {
+          format_file_with_imports(
+            file_path,
+            file_text,
+            fmt_options,
+            &scopes.parsed_source_cache,
+          )
+        },
       ) {
         Ok(Some(formatted_text)) => {
           incremental_cache.update_file(&file_path, &formatted_text);
@@ -467,13 +765,22 @@ fn format_ensure_stable(
 /// Format stdin and write result to stdout.
 /// Treats input as set by `--ext` flag.
 /// Compatible with `--check` flag.
-fn format_stdin(fmt_options: FmtOptions, ext: &str) -> Result<(), AnyError> {
+fn format_stdin(
+  fmt_options: FmtOptions,
+  ext: &str,
+  parsed_source_cache: &ParsedSourceCache,
+) -> Result<(), AnyError> {
   let mut source = String::new();
   if stdin().read_to_string(&mut source).is_err() {
     bail!("Failed to read from stdin");
   }
   let file_path = PathBuf::from(format!("_stdin.{ext}"));
-  let formatted_text = format_file(&file_path, &source, &fmt_options.options)?;
+  let formatted_text = format_file_with_imports(
+    &file_path,
+    &source,
+    &fmt_options.options,
+    parsed_source_cache,
+  )?;
   if fmt_options.check {
     if formatted_text.is_some() {
       println!("Not formatted stdin");
@@ -683,6 +990,10 @@ fn is_supported_ext_fmt(path: &Path) -> bool {
         | "mdwn"
         | "mdown"
         | "markdown"
+        | "html"
+        | "htm"
+        | "vue"
+        | "svelte"
     )
   } else {
     false
@@ -718,6 +1029,64 @@ mod test {
     assert!(is_supported_ext_fmt(Path::new("foo.JSONC")));
     assert!(is_supported_ext_fmt(Path::new("foo.json")));
     assert!(is_supported_ext_fmt(Path::new("foo.JsON")));
+    assert!(is_supported_ext_fmt(Path::new("index.html")));
+    assert!(is_supported_ext_fmt(Path::new("App.vue")));
+    assert!(is_supported_ext_fmt(Path::new("App.svelte")));
+  }
+
+  #[test]
+  fn test_format_script_blocks() {
+    let input = r#"<html>
+  <body>
+    <script src="./external.js"></script>
+    <script type="module">
+      const a = {b:1}
+    </script>
+    <script type="text/template">
+      {{ not code }}
+    </script>
+  </body>
+</html>
+"#;
+    let expected = r#"<html>
+  <body>
+    <script src="./external.js"></script>
+    <script type="module">
+      const a = { b: 1 };
+    </script>
+    <script type="text/template">
+      {{ not code }}
+    </script>
+  </body>
+</html>
+"#;
+    let result = format_script_blocks(input, &Default::default()).unwrap();
+    assert_eq!(result.as_deref(), Some(expected));
+    // already formatted
+    let result = format_script_blocks(expected, &Default::default()).unwrap();
+    assert_eq!(result, None);
+    // disabled by config
+    let options = FmtOptionsConfig {
+      embedded_code: Some(false),
+      ..Default::default()
+    };
+    let result = format_script_blocks(input, &options).unwrap();
+    assert_eq!(result, None);
+  }
+
+  #[test]
+  fn test_script_block_language() {
+    assert_eq!(script_block_language("<script>"), Some("js"));
+    assert_eq!(
+      script_block_language(r#"<script setup lang="ts">"#),
+      Some("ts")
+    );
+    assert_eq!(
+      script_block_language(r#"<script type="importmap">"#),
+      Some("json")
+    );
+    assert_eq!(script_block_language(r#"<script src="a.js">"#), None);
+    assert_eq!(script_block_language(r#"<script lang="coffee">"#), None);
   }
 
   #[test]
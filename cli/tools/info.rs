@@ -4,8 +4,12 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::resolve_url_or_path;
 use deno_core::serde_json;
@@ -23,14 +27,30 @@ use deno_runtime::colors;
 use deno_semver::npm::NpmPackageNv;
 use deno_semver::npm::NpmPackageNvReference;
 use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::Version;
+use serde::Serialize;
 
 use crate::args::Flags;
 use crate::args::InfoFlags;
+use crate::cache::CacheFreshness;
+use crate::cache::HttpCache;
 use crate::display;
+use crate::graph_util::find_import_cycles;
 use crate::graph_util::graph_lock_or_exit;
+use crate::graph_util::import_cycle_to_string;
+use crate::npm::CliNpmRegistryApi;
+use crate::npm::NpmCache;
 use crate::npm::NpmPackageResolver;
 use crate::proc_state::ProcState;
+use crate::tools::lock::diff::split_npm_package_id;
+use crate::tools::lock::diff::ChangeKind;
+use crate::tools::lock::diff::DependencyChange;
+use crate::tools::lock::diff::LockDiff;
+use crate::tools::lock::diff::LockedDeps;
+use crate::tools::lock::read_locked_deps;
+use crate::tools::sbom;
 use crate::util::checksum;
+use crate::util::fs::dir_size;
 
 pub async fn info(flags: Flags, info_flags: InfoFlags) -> Result<(), AnyError> {
   let ps = ProcState::from_flags(flags).await?;
@@ -47,13 +67,66 @@ pub async fn info(flags: Flags, info_flags: InfoFlags) -> Result<(), AnyError> {
       graph_lock_or_exit(&graph, &mut lockfile.lock());
     }
 
+    if let Some(format) = info_flags.sbom {
+      let lockfile = ps.lockfile.as_ref().map(|lockfile| lockfile.lock());
+      let sbom = sbom::create_sbom(
+        format,
+        &graph,
+        &ps.npm_resolver.snapshot(),
+        &ps.npm_cache,
+        lockfile.as_deref(),
+      );
+      return display::write_json_to_stdout(&sbom);
+    }
+
+    let maybe_cache_statuses = if info_flags.cache_status {
+      let http_cache =
+        HttpCache::new(&ps.file_fetcher.get_http_cache_location());
+      Some(get_remote_cache_statuses(&graph, &http_cache))
+    } else {
+      None
+    };
+
+    let maybe_dependency_diff = match &info_flags.diff {
+      Some(old_lockfile_path) => Some(diff_dependencies(
+        &graph,
+        &ps,
+        &ps.options.initial_cwd().join(old_lockfile_path),
+      )?),
+      None => None,
+    };
+
+    let maybe_cycles = if info_flags.cycles {
+      Some(find_import_cycles(&graph))
+    } else {
+      None
+    };
+
     if info_flags.json {
       let mut json_graph = json!(graph);
       add_npm_packages_to_json(&mut json_graph, &ps.npm_resolver);
+      if let Some(statuses) = &maybe_cache_statuses {
+        json_graph["remoteCacheStatus"] = cache_statuses_to_json(statuses);
+      }
+      if let Some(diff) = &maybe_dependency_diff {
+        json_graph["dependencyDiff"] = diff.to_json();
+      }
+      if let Some(cycles) = &maybe_cycles {
+        json_graph["cycles"] = json!(cycles);
+      }
       display::write_json_to_stdout(&json_graph)?;
     } else {
       let mut output = String::new();
       GraphDisplayContext::write(&graph, &ps.npm_resolver, &mut output)?;
+      if let Some(statuses) = &maybe_cache_statuses {
+        write_cache_statuses(statuses, &mut output)?;
+      }
+      if let Some(diff) = &maybe_dependency_diff {
+        diff.write(&mut output)?;
+      }
+      if let Some(cycles) = &maybe_cycles {
+        write_import_cycles(cycles, &mut output)?;
+      }
       display::write_to_stdout_ignore_sigpipe(output.as_bytes())?;
     }
   } else {
@@ -237,6 +310,442 @@ fn add_npm_packages_to_json(
   json.insert("npmPackages".to_string(), json_packages.into());
 }
 
+/// Information from the HTTP cache about a remote module in the graph.
+struct RemoteCacheStatus {
+  specifier: ModuleSpecifier,
+  headers: Vec<(String, String)>,
+  freshness: CacheFreshness,
+}
+
+impl RemoteCacheStatus {
+  fn reload_hint(&self) -> &'static str {
+    match self.freshness.is_stale() {
+      Some(false) => "the cached response is still fresh, so --reload is unlikely to change it",
+      Some(true) => "the cached response is stale, so --reload may download a newer version",
+      None => "the server sent no caching headers, so --reload may download a newer version",
+    }
+  }
+}
+
+fn get_remote_cache_statuses(
+  graph: &ModuleGraph,
+  http_cache: &HttpCache,
+) -> Vec<RemoteCacheStatus> {
+  let now = SystemTime::now();
+  let mut statuses = graph
+    .modules()
+    .filter_map(|module| {
+      let specifier = module.specifier();
+      if !matches!(specifier.scheme(), "http" | "https") {
+        return None;
+      }
+      let metadata = http_cache.get_metadata(specifier).ok()?;
+      let mut headers = metadata
+        .headers
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect::<Vec<_>>();
+      headers.sort();
+      Some(RemoteCacheStatus {
+        specifier: specifier.clone(),
+        headers,
+        freshness: metadata.freshness(now),
+      })
+    })
+    .collect::<Vec<_>>();
+  statuses.sort_by(|a, b| a.specifier.cmp(&b.specifier));
+  statuses
+}
+
+fn write_cache_statuses<TWrite: Write>(
+  statuses: &[RemoteCacheStatus],
+  writer: &mut TWrite,
+) -> fmt::Result {
+  writeln!(writer)?;
+  writeln!(writer, "{}", colors::bold("remote module cache:"))?;
+  if statuses.is_empty() {
+    return writeln!(writer, "{}", colors::gray("(no remote modules)"));
+  }
+  for status in statuses {
+    let freshness = &status.freshness;
+    writeln!(writer, "{}", status.specifier)?;
+    writeln!(
+      writer,
+      "  {} {} ago",
+      colors::bold("cached:"),
+      human_duration(freshness.age)
+    )?;
+    let lifetime = if freshness.immutable {
+      "immutable".to_string()
+    } else {
+      match freshness.lifetime {
+        Some(lifetime) => human_duration(lifetime),
+        None => "unknown".to_string(),
+      }
+    };
+    writeln!(writer, "  {} {}", colors::bold("fresh for:"), lifetime)?;
+    writeln!(
+      writer,
+      "  {} {}",
+      colors::bold("reload:"),
+      status.reload_hint()
+    )?;
+    if !status.headers.is_empty() {
+      writeln!(writer, "  {}", colors::bold("headers:"))?;
+      for (name, value) in &status.headers {
+        writeln!(writer, "    {}: {}", colors::gray(name), value)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Formats a duration with its two largest units (ex. `3d 4h`).
+fn human_duration(duration: Duration) -> String {
+  let seconds = duration.as_secs();
+  let (days, hours, minutes) =
+    (seconds / 86_400, seconds / 3_600 % 24, seconds / 60 % 60);
+  if days > 0 {
+    format!("{days}d {hours}h")
+  } else if hours > 0 {
+    format!("{hours}h {minutes}m")
+  } else if minutes > 0 {
+    format!("{minutes}m {}s", seconds % 60)
+  } else {
+    format!("{seconds}s")
+  }
+}
+
+fn write_import_cycles<TWrite: Write>(
+  cycles: &[Vec<ModuleSpecifier>],
+  writer: &mut TWrite,
+) -> fmt::Result {
+  writeln!(writer)?;
+  writeln!(writer, "{}", colors::bold("import cycles:"))?;
+  if cycles.is_empty() {
+    return writeln!(writer, "{}", colors::gray("(no import cycles)"));
+  }
+  for cycle in cycles {
+    writeln!(writer, "{}", import_cycle_to_string(cycle))?;
+  }
+  Ok(())
+}
+
+fn cache_statuses_to_json(statuses: &[RemoteCacheStatus]) -> serde_json::Value {
+  statuses
+    .iter()
+    .map(|status| {
+      json!({
+        "specifier": status.specifier,
+        "ageMs": status.freshness.age.as_millis() as u64,
+        "freshnessLifetimeMs": status
+          .freshness
+          .lifetime
+          .map(|lifetime| lifetime.as_millis() as u64),
+        "immutable": status.freshness.immutable,
+        "stale": status.freshness.is_stale(),
+        "headers": status
+          .headers
+          .iter()
+          .cloned()
+          .collect::<serde_json::Map<_, _>>(),
+      })
+    })
+    .collect()
+}
+
+/// A change of a dependency since the previous lockfile, along with the
+/// change in size in bytes when the size of both sides is known.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SizedDependencyChange {
+  #[serde(flatten)]
+  change: DependencyChange,
+  size_delta: Option<i64>,
+}
+
+/// An origin that no remote module was loaded from before.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NewOrigin {
+  origin: String,
+  /// The module that first imports from the origin, or `None` for a root.
+  imported_by: Option<ModuleSpecifier>,
+}
+
+/// The changes of the dependencies of a module graph compared to a previous
+/// lockfile, which are the ones of `deno lock diff` along with their sizes
+/// and the new origins.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DependencyDiff {
+  #[serde(skip)]
+  old_lockfile_path: String,
+  remote: Vec<SizedDependencyChange>,
+  npm: Vec<SizedDependencyChange>,
+  new_origins: Vec<NewOrigin>,
+}
+
+impl DependencyDiff {
+  fn is_empty(&self) -> bool {
+    self.remote.is_empty() && self.npm.is_empty() && self.new_origins.is_empty()
+  }
+
+  fn total_size_delta(&self) -> (i64, bool) {
+    let changes = self.remote.iter().chain(self.npm.iter());
+    let mut total = 0;
+    let mut has_unknown = false;
+    for change in changes {
+      match change.size_delta {
+        Some(delta) => total += delta,
+        None => has_unknown = true,
+      }
+    }
+    (total, has_unknown)
+  }
+
+  fn write<TWrite: Write>(&self, writer: &mut TWrite) -> fmt::Result {
+    writeln!(writer)?;
+    if self.is_empty() {
+      return writeln!(
+        writer,
+        "{}",
+        colors::bold(format!(
+          "no dependency changes since {}",
+          self.old_lockfile_path
+        ))
+      );
+    }
+    writeln!(
+      writer,
+      "{}",
+      colors::bold(format!(
+        "dependency changes since {}:",
+        self.old_lockfile_path
+      ))
+    )?;
+    for (name_prefix, changes) in [("", &self.remote), ("npm:", &self.npm)] {
+      for change in changes {
+        writeln!(
+          writer,
+          "  {}{}",
+          change.change.format(name_prefix),
+          size_delta_to_text(change.size_delta)
+        )?;
+      }
+    }
+    if !self.new_origins.is_empty() {
+      writeln!(writer, "{}", colors::bold("new origins:"))?;
+      for new_origin in &self.new_origins {
+        match &new_origin.imported_by {
+          Some(referrer) => writeln!(
+            writer,
+            "  {} {}",
+            new_origin.origin,
+            colors::gray(format!("(imported by {referrer})"))
+          )?,
+          None => writeln!(writer, "  {}", new_origin.origin)?,
+        }
+      }
+    }
+    let (total, has_unknown) = self.total_size_delta();
+    writeln!(
+      writer,
+      "{} {}{}",
+      colors::bold("total size change:"),
+      signed_size(total),
+      if has_unknown {
+        colors::gray(" (some sizes are unknown)").to_string()
+      } else {
+        String::new()
+      }
+    )
+  }
+
+  fn to_json(&self) -> serde_json::Value {
+    let (total, _) = self.total_size_delta();
+    let mut value = json!(self);
+    value["totalSizeDelta"] = json!(total);
+    value
+  }
+}
+
+/// Compares the remote modules of the graph and the resolved npm packages
+/// with the ones in the lockfile at `old_lockfile_path`, like
+/// `deno lock diff`, and adds the sizes of the changes and the new origins.
+fn diff_dependencies(
+  graph: &ModuleGraph,
+  ps: &ProcState,
+  old_lockfile_path: &Path,
+) -> Result<DependencyDiff, AnyError> {
+  if !old_lockfile_path.is_file() {
+    bail!(
+      "Could not find lock file '{}'.",
+      old_lockfile_path.display()
+    );
+  }
+  let old = read_locked_deps(old_lockfile_path)?;
+  let mut new = LockedDeps::default();
+  let mut new_remote_sizes = HashMap::new();
+  for module in graph.modules() {
+    let (source, size) = match module {
+      Module::Esm(module) => (&module.source, module.size()),
+      Module::Json(module) => (&module.source, module.size()),
+      _ => continue,
+    };
+    let specifier = module.specifier().as_str();
+    if matches!(module.specifier().scheme(), "http" | "https") {
+      new.insert_remote(specifier, checksum::gen(&[source.as_bytes()]));
+      new_remote_sizes.insert(specifier.to_string(), size as i64);
+    }
+  }
+  for package in ps.npm_resolver.snapshot().all_packages() {
+    let id = package.pkg_id.as_serialized();
+    let (name, version) = split_npm_package_id(&id);
+    new.insert_npm(name, version, &package.dist.integrity().to_string());
+  }
+  let diff = LockDiff::new(&old, &new);
+
+  // the cache only holds the current source of a remote module, so the size
+  // of a changed module is only known when its specifier changed
+  let http_cache = HttpCache::new(&ps.file_fetcher.get_http_cache_location());
+  let old_remote_size = |change: &DependencyChange| {
+    let version = change.old_version.clone().unwrap_or_default();
+    let specifier =
+      old.remote_specifiers.get(&(change.name.clone(), version))?;
+    let url = ModuleSpecifier::parse(specifier).ok()?;
+    let (content, _, _) = http_cache.get(&url).ok()?;
+    Some((specifier, content.len() as i64))
+  };
+  let new_remote_size = |change: &DependencyChange| {
+    let version = change.new_version.clone().unwrap_or_default();
+    let specifier =
+      new.remote_specifiers.get(&(change.name.clone(), version))?;
+    Some((specifier, *new_remote_sizes.get(specifier)?))
+  };
+  let remote = diff
+    .remote
+    .into_iter()
+    .map(|change| {
+      let size_delta = match change.kind {
+        ChangeKind::Added => new_remote_size(&change).map(|(_, size)| size),
+        ChangeKind::Removed => old_remote_size(&change).map(|(_, size)| -size),
+        ChangeKind::Changed => old_remote_size(&change)
+          .zip(new_remote_size(&change))
+          .filter(|((old_specifier, _), (new_specifier, _))| {
+            old_specifier != new_specifier
+          })
+          .map(|((_, old_size), (_, new_size))| new_size - old_size),
+      };
+      SizedDependencyChange { change, size_delta }
+    })
+    .collect();
+
+  // the cache holds a single copy of each version of an npm package
+  let package_size = |name: &str, version: &Option<String>| match version {
+    Some(version) => package_version_size(&ps.npm_cache, name, version),
+    None => Some(0),
+  };
+  let npm = diff
+    .npm
+    .into_iter()
+    .map(|change| {
+      let size_delta = if change.old_version == change.new_version {
+        None
+      } else {
+        package_size(&change.name, &change.old_version)
+          .zip(package_size(&change.name, &change.new_version))
+          .map(|(old, new)| new as i64 - old as i64)
+      };
+      SizedDependencyChange { change, size_delta }
+    })
+    .collect();
+
+  // origins that are new, along with the module that imports from them
+  let old_origins = old
+    .remote_specifiers
+    .values()
+    .filter_map(|specifier| ModuleSpecifier::parse(specifier).ok())
+    .map(|url| url.origin().ascii_serialization())
+    .collect::<HashSet<_>>();
+  let mut new_origins: Vec<NewOrigin> = Vec::new();
+  let mut add_origin =
+    |specifier: &ModuleSpecifier, maybe_referrer: Option<&ModuleSpecifier>| {
+      if !matches!(specifier.scheme(), "http" | "https") {
+        return;
+      }
+      let origin = specifier.origin().ascii_serialization();
+      if !old_origins.contains(&origin)
+        && !new_origins
+          .iter()
+          .any(|new_origin| new_origin.origin == origin)
+      {
+        new_origins.push(NewOrigin {
+          origin,
+          imported_by: maybe_referrer.cloned(),
+        });
+      }
+    };
+  for root in &graph.roots {
+    add_origin(&graph.resolve(root), None);
+  }
+  for module in graph.modules() {
+    if let Module::Esm(esm) = module {
+      for dep in esm.dependencies.values() {
+        if let Some(specifier) = dep.get_code() {
+          add_origin(&graph.resolve(specifier), Some(module.specifier()));
+        }
+      }
+    }
+  }
+  new_origins.sort_by(|a, b| a.origin.cmp(&b.origin));
+
+  Ok(DependencyDiff {
+    old_lockfile_path: old_lockfile_path.display().to_string(),
+    remote,
+    npm,
+    new_origins,
+  })
+}
+
+/// Gets the size of the cached copy of a version of an npm package, whose
+/// peer dependencies are left out (ex. `5.2.0_preact@10.5.0`).
+fn package_version_size(
+  npm_cache: &NpmCache,
+  name: &str,
+  version: &str,
+) -> Option<u64> {
+  let version = version.split('_').next().unwrap_or(version);
+  let nv = NpmPackageNv {
+    name: name.to_string(),
+    version: Version::parse_from_npm(version).ok()?,
+  };
+  let folder = npm_cache
+    .package_folder_for_name_and_version(&nv, CliNpmRegistryApi::default_url());
+  dir_size(&folder).ok()
+}
+
+fn signed_size(size: i64) -> String {
+  let text = display::human_size(size as f64);
+  if size > 0 {
+    format!("+{text}")
+  } else {
+    text
+  }
+}
+
+fn size_delta_to_text(maybe_size_delta: Option<i64>) -> String {
+  match maybe_size_delta {
+    Some(size_delta) => {
+      format!(
+        " {}",
+        colors::gray(format!("({})", signed_size(size_delta)))
+      )
+    }
+    None => String::new(),
+  }
+}
+
 struct TreeNode {
   text: String,
   children: Vec<TreeNode>,
@@ -1,20 +1,26 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::resolve_no_prompt;
+use crate::args::write_lockfile;
 use crate::args::CaData;
 use crate::args::ConfigFlag;
+use crate::args::FilesConfig;
 use crate::args::Flags;
 use crate::args::InstallFlags;
 use crate::args::TypeCheckMode;
 use crate::http_util::HttpClient;
 use crate::proc_state::ProcState;
 use crate::util::fs::canonicalize_path_maybe_not_exists;
+use crate::util::fs::collect_specifiers;
+use crate::util::path::is_supported_ext;
 
 use deno_core::anyhow::Context;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::resolve_url_or_path;
 use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::PermissionsContainer;
 use deno_semver::npm::NpmPackageReqReference;
 use log::Level;
 use once_cell::sync::Lazy;
@@ -243,6 +249,134 @@ pub async fn install_command(
   create_install_shim(flags, install_flags).await
 }
 
+/// Downloads the remote and npm dependencies of the project in the current
+/// directory, sets up the local node_modules directory when enabled and
+/// writes the lockfile.
+pub async fn install_deps(flags: Flags) -> Result<(), AnyError> {
+  let ps = ProcState::from_flags(flags).await?;
+  if ps.options.maybe_config_file().is_none()
+    && ps.options.maybe_package_json().is_none()
+  {
+    return Err(generic_error(
+      "No deno.json or package.json found in the current directory. Provide a script to install as an executable instead.",
+    ));
+  }
+
+  let roots_count = install_project_deps(&ps).await?;
+  let package_json_deps_count = ps
+    .options
+    .maybe_package_json_deps()
+    .map(|deps| deps.len())
+    .unwrap_or(0);
+  log::info!(
+    "✅ Successfully installed {} import map {} and {} package.json {}",
+    roots_count,
+    if roots_count == 1 { "entry" } else { "entries" },
+    package_json_deps_count,
+    if package_json_deps_count == 1 {
+      "dependency"
+    } else {
+      "dependencies"
+    },
+  );
+  Ok(())
+}
+
+/// Caches the remote and npm modules mapped by the import map along with the
+/// package.json dependencies and updates the lockfile. Returns the number of
+/// import map entries that were installed.
+pub async fn install_project_deps(ps: &ProcState) -> Result<usize, AnyError> {
+  // the remote and npm modules mapped by the import map
+  let mut roots = Vec::new();
+  let mut remote_prefixes = Vec::new();
+  let mut entries_count = 0;
+  if let Some(import_map) = &ps.maybe_import_map {
+    for entry in import_map.imports().entries() {
+      let specifier = match entry.value {
+        Some(specifier) => specifier,
+        None => continue,
+      };
+      let is_prefix = entry.raw_key.ends_with('/');
+      match specifier.scheme() {
+        // the package of a prefix mapping (ex. `npm:preact@10/`)
+        "npm" if is_prefix => roots.push(ModuleSpecifier::parse(
+          specifier.as_str().trim_end_matches('/'),
+        )?),
+        "npm" | "http" | "https" if !is_prefix => roots.push(specifier.clone()),
+        "http" | "https" => remote_prefixes.push(specifier.clone()),
+        _ => continue,
+      }
+      entries_count += 1;
+    }
+  }
+  roots.extend(resolve_remote_prefix_imports(ps, &remote_prefixes).await?);
+
+  ps.package_json_deps_installer
+    .ensure_top_level_install()
+    .await?;
+  ps.npm_resolver.resolve_pending().await?;
+  if !roots.is_empty() {
+    ps.module_load_preparer
+      .prepare_module_load(
+        roots.clone(),
+        false,
+        ps.options.ts_type_lib_window(),
+        PermissionsContainer::allow_all(),
+        PermissionsContainer::allow_all(),
+      )
+      .await?;
+  }
+  if let Some(lockfile) = &ps.lockfile {
+    ps.npm_resolver.lock(&mut lockfile.lock())?;
+    write_lockfile(lockfile, ps.options.frozen()).await?;
+  }
+  Ok(entries_count)
+}
+
+/// Gets the remote modules that the local modules of the project import
+/// through the prefix mappings, since only the targets of the prefixes
+/// are known from the import map.
+async fn resolve_remote_prefix_imports(
+  ps: &ProcState,
+  remote_prefixes: &[ModuleSpecifier],
+) -> Result<Vec<ModuleSpecifier>, AnyError> {
+  if remote_prefixes.is_empty() {
+    return Ok(Vec::new());
+  }
+  let root_dir = ps
+    .options
+    .maybe_config_file_specifier()
+    .and_then(|specifier| specifier.to_file_path().ok())
+    .and_then(|path| path.parent().map(|dir| dir.to_path_buf()))
+    .unwrap_or_else(|| ps.options.initial_cwd().to_path_buf());
+  let local_specifiers = collect_specifiers(
+    &FilesConfig {
+      include: vec![root_dir],
+      exclude: Vec::new(),
+    },
+    is_supported_ext,
+  )?;
+  if local_specifiers.is_empty() {
+    return Ok(Vec::new());
+  }
+  let graph = ps
+    .module_graph_builder
+    .create_graph(local_specifiers)
+    .await?;
+  Ok(
+    graph
+      .specifiers()
+      .map(|(specifier, _)| specifier)
+      .filter(|specifier| {
+        remote_prefixes
+          .iter()
+          .any(|prefix| specifier.as_str().starts_with(prefix.as_str()))
+      })
+      .cloned()
+      .collect(),
+  )
+}
+
 async fn create_install_shim(
   flags: Flags,
   install_flags: InstallFlags,
@@ -344,11 +478,17 @@ async fn resolve_shim_data(
     executable_args.push("--cert".to_string());
     executable_args.push(ca_file.to_owned())
   }
+  let mut log_directives = flags
+    .log_filters
+    .iter()
+    .map(|(target, level)| {
+      format!("{}={}", target, level.to_string().to_lowercase())
+    })
+    .collect::<Vec<_>>();
   if let Some(log_level) = flags.log_level {
     if log_level == Level::Error {
       executable_args.push("--quiet".to_string());
     } else {
-      executable_args.push("--log-level".to_string());
       let log_level = match log_level {
         Level::Debug => "debug",
         Level::Info => "info",
@@ -356,9 +496,13 @@ async fn resolve_shim_data(
           return Err(generic_error(format!("invalid log level {log_level}")))
         }
       };
-      executable_args.push(log_level.to_string());
+      log_directives.insert(0, log_level.to_string());
     }
   }
+  if !log_directives.is_empty() {
+    executable_args.push("--log-level".to_string());
+    executable_args.push(log_directives.join(","));
+  }
 
   // we should avoid a default branch here to ensure we continue to cover any
   // changes to this flag.
@@ -372,6 +516,10 @@ async fn resolve_shim_data(
     executable_args.push("--unstable".to_string());
   }
 
+  for feature in &flags.unstable_features {
+    executable_args.push(format!("--unstable-{feature}"));
+  }
+
   if flags.no_remote {
     executable_args.push("--no-remote".to_string());
   }
@@ -409,6 +557,10 @@ async fn resolve_shim_data(
     executable_args.push(format!("--inspect-brk={inspect_brk}"));
   }
 
+  if flags.inspect_children {
+    executable_args.push("--inspect-children".to_string());
+  }
+
   if let Some(import_map_path) = &flags.import_map_path {
     let import_map_url = resolve_url_or_path(import_map_path, &cwd)?;
     executable_args.push("--import-map".to_string());
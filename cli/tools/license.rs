@@ -0,0 +1,545 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::ModuleSpecifier;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_npm::resolution::NpmResolutionSnapshot;
+use deno_npm::NpmResolutionPackage;
+use deno_runtime::colors;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_semver::npm::NpmPackageNv;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::args::Flags;
+use crate::args::LicenseFlags;
+use crate::graph_util::graph_lock_or_exit;
+use crate::graph_util::graph_valid_with_cli_options;
+use crate::npm::CliNpmRegistryApi;
+use crate::npm::NpmCache;
+use crate::proc_state::ProcState;
+use crate::util::display;
+
+/// The names of the license files looked for at the root of the package of
+/// remote modules.
+const LICENSE_FILE_NAMES: [&str; 3] = ["LICENSE", "LICENSE.md", "LICENSE.txt"];
+
+/// Matches the root of a versioned package in the url of a remote module, like
+/// `https://deno.land/std@0.190.0/` or `https://esm.sh/@scope/name@1.0.0/`.
+static VERSIONED_PACKAGE_ROOT_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"^https?://[^/]+/(?:[^/]+/)*?[^/@]+@[^/]+/").unwrap()
+});
+
+/// Matches the root of a repository in `https://raw.githubusercontent.com/`
+/// urls, which are `/<owner>/<repo>/<ref>/`.
+static GITHUB_REPO_ROOT_RE: Lazy<Regex> = Lazy::new(|| {
+  Regex::new(r"^https://raw\.githubusercontent\.com/[^/]+/[^/]+/[^/]+/")
+    .unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DependencyKind {
+  Npm,
+  Remote,
+}
+
+/// An npm package, or the remote modules of a package, and its license.
+struct LicensedDependency {
+  kind: DependencyKind,
+  /// `npm:<name>@<version>` for an npm package and the url of the root of the
+  /// package for remote modules.
+  name: String,
+  /// The SPDX license expression, if it could be determined.
+  license: Option<String>,
+  /// The LICENSE file the license of remote modules was detected from.
+  maybe_license_file: Option<ModuleSpecifier>,
+  /// The number of remote modules of the package.
+  module_count: usize,
+}
+
+pub async fn license(
+  flags: Flags,
+  license_flags: LicenseFlags,
+) -> Result<(), AnyError> {
+  let ps = ProcState::from_flags(flags).await?;
+  let licenses_config = ps.options.resolve_licenses_config()?;
+  let roots = license_flags
+    .files
+    .iter()
+    .map(|file| resolve_url_or_path(file, ps.options.initial_cwd()))
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let graph = ps.module_graph_builder.create_graph(roots.clone()).await?;
+  graph_valid_with_cli_options(&graph, &roots, &ps.options)?;
+  if let Some(lockfile) = &ps.lockfile {
+    graph_lock_or_exit(&graph, &mut lockfile.lock());
+  }
+  // the package.json files are read from the npm cache
+  ps.npm_resolver.resolve_pending().await?;
+
+  let mut dependencies = Vec::new();
+  let npm_snapshot = ps.npm_resolver.snapshot();
+  for package in graph_npm_packages(&graph, &npm_snapshot) {
+    dependencies.push(LicensedDependency {
+      kind: DependencyKind::Npm,
+      name: format!("npm:{}", package.pkg_id.nv),
+      license: npm_package_license(&ps.npm_cache, &package.pkg_id.nv),
+      maybe_license_file: None,
+      module_count: 0,
+    });
+  }
+  for (root, module_count) in remote_package_roots(&graph) {
+    let mut license = None;
+    let mut maybe_license_file = None;
+    for file_name in LICENSE_FILE_NAMES {
+      let specifier = root.join(file_name)?;
+      if let Ok(file) = ps
+        .file_fetcher
+        .fetch(&specifier, PermissionsContainer::allow_all())
+        .await
+      {
+        license = detect_license(&file.source).map(|id| id.to_string());
+        maybe_license_file = Some(specifier);
+        break;
+      }
+    }
+    dependencies.push(LicensedDependency {
+      kind: DependencyKind::Remote,
+      name: root.to_string(),
+      license,
+      maybe_license_file,
+      module_count,
+    });
+  }
+
+  let is_allowed = |dependency: &LicensedDependency| match (
+    &licenses_config.allow,
+    &dependency.license,
+  ) {
+    (Some(allow), Some(license)) => is_license_allowed(license, allow),
+    (Some(_), None) => false,
+    (None, _) => true,
+  };
+
+  if license_flags.json {
+    let json_dependencies = dependencies
+      .iter()
+      .map(|dependency| {
+        let mut json = json!({
+          "kind": match dependency.kind {
+            DependencyKind::Npm => "npm",
+            DependencyKind::Remote => "remote",
+          },
+          "name": dependency.name,
+          "license": dependency.license,
+        });
+        if dependency.kind == DependencyKind::Remote {
+          json["licenseFile"] = json!(dependency.maybe_license_file);
+          json["moduleCount"] = json!(dependency.module_count);
+        }
+        if licenses_config.allow.is_some() {
+          json["allowed"] = json!(is_allowed(dependency));
+        }
+        json
+      })
+      .collect::<Vec<_>>();
+    display::write_json_to_stdout(&json!({
+      "dependencies": json_dependencies,
+    }))?;
+  } else if dependencies.is_empty() {
+    println!("{}", colors::gray("No npm packages or remote modules."));
+  } else {
+    let mut by_license = BTreeMap::<&str, Vec<&LicensedDependency>>::new();
+    for dependency in &dependencies {
+      by_license
+        .entry(dependency.license.as_deref().unwrap_or("unknown"))
+        .or_default()
+        .push(dependency);
+    }
+    for (license, dependencies) in by_license {
+      println!(
+        "{} {}",
+        colors::bold(license),
+        colors::gray(format!("({})", dependencies.len()))
+      );
+      for dependency in dependencies {
+        println!("  {}", dependency.name);
+      }
+    }
+  }
+
+  let violations = dependencies
+    .iter()
+    .filter(|dependency| !is_allowed(dependency))
+    .map(|dependency| {
+      format!(
+        "  {} ({})",
+        dependency.name,
+        dependency.license.as_deref().unwrap_or("unknown")
+      )
+    })
+    .collect::<Vec<_>>();
+  if !violations.is_empty() {
+    let config_specifier = ps
+      .options
+      .maybe_config_file_specifier()
+      .map(|specifier| specifier.to_string())
+      .unwrap_or_default();
+    return Err(generic_error(format!(
+      "{} dependencies are not licensed under a license allowed by \"licenses.allow\" in {}:\n{}",
+      violations.len(),
+      config_specifier,
+      violations.join("\n"),
+    )));
+  }
+  Ok(())
+}
+
+/// Gets the npm packages the graph depends on, directly or through other npm
+/// packages, sorted by their id.
+pub fn graph_npm_packages(
+  graph: &ModuleGraph,
+  npm_snapshot: &NpmResolutionSnapshot,
+) -> Vec<NpmResolutionPackage> {
+  fn add_package(
+    package: &NpmResolutionPackage,
+    npm_snapshot: &NpmResolutionSnapshot,
+    packages: &mut BTreeMap<String, NpmResolutionPackage>,
+  ) {
+    let id = package.pkg_id.as_serialized();
+    if packages.contains_key(&id) {
+      return;
+    }
+    packages.insert(id, package.clone());
+    for dep_id in package.dependencies.values() {
+      if let Some(dep) = npm_snapshot.package_from_id(dep_id) {
+        add_package(dep, npm_snapshot, packages);
+      }
+    }
+  }
+
+  let mut packages = BTreeMap::new();
+  for module in graph.modules() {
+    if let Module::Npm(module) = module {
+      if let Ok(package) =
+        npm_snapshot.resolve_package_from_deno_module(&module.nv_reference.nv)
+      {
+        add_package(package, npm_snapshot, &mut packages);
+      }
+    }
+  }
+  packages.into_values().collect()
+}
+
+/// Gets the license of a cached npm package from its package.json.
+pub fn npm_package_license(
+  npm_cache: &NpmCache,
+  nv: &NpmPackageNv,
+) -> Option<String> {
+  let package_folder = npm_cache
+    .package_folder_for_name_and_version(nv, CliNpmRegistryApi::default_url());
+  let text =
+    std::fs::read_to_string(package_folder.join("package.json")).ok()?;
+  license_from_package_json(&serde_json::from_str(&text).ok()?)
+}
+
+/// Gets the license from the `license` field of a package.json or from the
+/// deprecated `licenses` array, joining several ones into an SPDX expression.
+fn license_from_package_json(
+  package_json: &serde_json::Value,
+) -> Option<String> {
+  let license_name = |value: &serde_json::Value| match value {
+    serde_json::Value::String(name) => Some(name.clone()),
+    serde_json::Value::Object(obj) => obj
+      .get("type")
+      .and_then(|t| t.as_str())
+      .map(|t| t.to_string()),
+    _ => None,
+  };
+  if let Some(license) = package_json.get("license").and_then(license_name) {
+    return Some(license);
+  }
+  let licenses = package_json
+    .get("licenses")?
+    .as_array()?
+    .iter()
+    .filter_map(license_name)
+    .collect::<Vec<_>>();
+  match licenses.len() {
+    0 => None,
+    1 => licenses.into_iter().next(),
+    _ => Some(format!("({})", licenses.join(" OR "))),
+  }
+}
+
+/// Groups the remote modules of the graph by the root of the package they are
+/// part of, along with the number of modules of each.
+fn remote_package_roots(
+  graph: &ModuleGraph,
+) -> BTreeMap<ModuleSpecifier, usize> {
+  let mut roots = BTreeMap::new();
+  for module in graph.modules() {
+    let specifier = module.specifier();
+    if matches!(module, Module::Esm(_) | Module::Json(_))
+      && matches!(specifier.scheme(), "http" | "https")
+    {
+      *roots.entry(remote_package_root(specifier)).or_default() += 1;
+    }
+  }
+  roots
+}
+
+/// Gets the root of the package of a remote module, which is the directory
+/// with the version in its name for versioned urls and the origin otherwise.
+fn remote_package_root(specifier: &ModuleSpecifier) -> ModuleSpecifier {
+  let url = specifier.as_str();
+  let maybe_root = VERSIONED_PACKAGE_ROOT_RE
+    .find(url)
+    .or_else(|| GITHUB_REPO_ROOT_RE.find(url))
+    .and_then(|root| ModuleSpecifier::parse(root.as_str()).ok());
+  match maybe_root {
+    Some(root) => root,
+    None => {
+      let mut root = specifier.clone();
+      root.set_path("/");
+      root.set_query(None);
+      root.set_fragment(None);
+      root
+    }
+  }
+}
+
+/// Detects the SPDX identifier of the common licenses from the text of a
+/// license file.
+fn detect_license(text: &str) -> Option<&'static str> {
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  let text = text.to_lowercase();
+  let has = |phrase: &str| text.contains(phrase);
+  let license = if has("apache license") && has("version 2.0") {
+    "Apache-2.0"
+  } else if has("mozilla public license") && has("2.0") {
+    "MPL-2.0"
+  } else if has("gnu affero general public license") {
+    "AGPL-3.0"
+  } else if has("gnu lesser general public license") {
+    if has("version 2.1") {
+      "LGPL-2.1"
+    } else {
+      "LGPL-3.0"
+    }
+  } else if has("gnu general public license") {
+    if has("version 2") && !has("version 3") {
+      "GPL-2.0"
+    } else {
+      "GPL-3.0"
+    }
+  } else if has("this is free and unencumbered software released into the public domain")
+  {
+    "Unlicense"
+  } else if has("permission is hereby granted, free of charge") {
+    "MIT"
+  } else if has("permission to use, copy, modify, and/or distribute this software for any purpose") {
+    if has("provided that the above copyright notice") {
+      "ISC"
+    } else {
+      "0BSD"
+    }
+  } else if has("redistribution and use in source and binary forms") {
+    if has("neither the name") || has("names of its contributors") {
+      "BSD-3-Clause"
+    } else {
+      "BSD-2-Clause"
+    }
+  } else {
+    return None;
+  };
+  Some(license)
+}
+
+/// Gets if an SPDX license expression, like `MIT` or
+/// `(MIT OR Apache-2.0) AND BSD-3-Clause`, is satisfied by the allowed
+/// licenses. Invalid expressions are never allowed.
+fn is_license_allowed(expression: &str, allow: &[String]) -> bool {
+  let expression = expression.replace('(', " ( ").replace(')', " ) ");
+  let tokens = expression.split_whitespace().collect::<Vec<_>>();
+  let mut parser = LicenseExpressionParser {
+    tokens: &tokens,
+    pos: 0,
+    allow,
+  };
+  parser.parse_or() == Some(true) && parser.pos == tokens.len()
+}
+
+struct LicenseExpressionParser<'a> {
+  tokens: &'a [&'a str],
+  pos: usize,
+  allow: &'a [String],
+}
+
+impl<'a> LicenseExpressionParser<'a> {
+  fn next_is(&self, operator: &str) -> bool {
+    self
+      .tokens
+      .get(self.pos)
+      .map(|token| token.eq_ignore_ascii_case(operator))
+      .unwrap_or(false)
+  }
+
+  fn parse_or(&mut self) -> Option<bool> {
+    let mut allowed = self.parse_and()?;
+    while self.next_is("OR") {
+      self.pos += 1;
+      allowed |= self.parse_and()?;
+    }
+    Some(allowed)
+  }
+
+  fn parse_and(&mut self) -> Option<bool> {
+    let mut allowed = self.parse_license()?;
+    while self.next_is("AND") {
+      self.pos += 1;
+      allowed &= self.parse_license()?;
+    }
+    Some(allowed)
+  }
+
+  fn parse_license(&mut self) -> Option<bool> {
+    let token = *self.tokens.get(self.pos)?;
+    self.pos += 1;
+    if token == "(" {
+      let allowed = self.parse_or()?;
+      if !self.next_is(")") {
+        return None;
+      }
+      self.pos += 1;
+      return Some(allowed);
+    }
+    if token == ")"
+      || ["AND", "OR", "WITH"]
+        .iter()
+        .any(|o| token.eq_ignore_ascii_case(o))
+    {
+      return None;
+    }
+    // an exception only loosens the license, so the license decides
+    if self.next_is("WITH") {
+      self.tokens.get(self.pos + 1)?;
+      self.pos += 2;
+    }
+    Some(
+      self
+        .allow
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(token)),
+    )
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_license_from_package_json() {
+    assert_eq!(
+      license_from_package_json(&json!({ "license": "MIT" })),
+      Some("MIT".to_string())
+    );
+    assert_eq!(
+      license_from_package_json(&json!({ "license": { "type": "ISC" } })),
+      Some("ISC".to_string())
+    );
+    assert_eq!(
+      license_from_package_json(&json!({
+        "licenses": [{ "type": "MIT" }, { "type": "Apache-2.0" }],
+      })),
+      Some("(MIT OR Apache-2.0)".to_string())
+    );
+    assert_eq!(license_from_package_json(&json!({})), None);
+  }
+
+  #[test]
+  fn test_remote_package_root() {
+    let root = |url: &str| {
+      remote_package_root(&ModuleSpecifier::parse(url).unwrap()).to_string()
+    };
+    assert_eq!(
+      root("https://deno.land/std@0.190.0/path/mod.ts"),
+      "https://deno.land/std@0.190.0/"
+    );
+    assert_eq!(
+      root("https://deno.land/x/oak@v12.5.0/mod.ts"),
+      "https://deno.land/x/oak@v12.5.0/"
+    );
+    assert_eq!(
+      root("https://esm.sh/@preact/signals@1.1.3/dist/signals.js"),
+      "https://esm.sh/@preact/signals@1.1.3/"
+    );
+    assert_eq!(
+      root("https://raw.githubusercontent.com/owner/repo/main/src/mod.ts"),
+      "https://raw.githubusercontent.com/owner/repo/main/"
+    );
+    assert_eq!(
+      root("https://example.com/lib/mod.ts?target=deno"),
+      "https://example.com/"
+    );
+  }
+
+  #[test]
+  fn test_detect_license() {
+    assert_eq!(
+      detect_license(
+        "MIT License\n\nPermission is hereby granted, free of charge, to any\nperson obtaining a copy"
+      ),
+      Some("MIT")
+    );
+    assert_eq!(
+      detect_license("Apache License\n  Version 2.0, January 2004"),
+      Some("Apache-2.0")
+    );
+    assert_eq!(
+      detect_license(
+        "Redistribution and use in source and binary forms, with or without modification, are permitted. Neither the name of the copyright holder"
+      ),
+      Some("BSD-3-Clause")
+    );
+    assert_eq!(
+      detect_license(
+        "Permission to use, copy, modify, and/or distribute this software for any purpose with or without fee is hereby granted, provided that the above copyright notice"
+      ),
+      Some("ISC")
+    );
+    assert_eq!(
+      detect_license(
+        "GNU LESSER GENERAL PUBLIC LICENSE Version 3, 29 June 2007 ... GNU General Public License"
+      ),
+      Some("LGPL-3.0")
+    );
+    assert_eq!(detect_license("All rights reserved."), None);
+  }
+
+  #[test]
+  fn test_is_license_allowed() {
+    let allow = vec!["MIT".to_string(), "Apache-2.0".to_string()];
+    assert!(is_license_allowed("MIT", &allow));
+    assert!(is_license_allowed("mit", &allow));
+    assert!(!is_license_allowed("GPL-3.0", &allow));
+    assert!(is_license_allowed("(MIT OR GPL-3.0)", &allow));
+    assert!(!is_license_allowed("MIT AND GPL-3.0", &allow));
+    assert!(is_license_allowed(
+      "(GPL-3.0 OR MIT) AND Apache-2.0",
+      &allow
+    ));
+    assert!(is_license_allowed("Apache-2.0 WITH LLVM-exception", &allow));
+    assert!(!is_license_allowed("(MIT", &allow));
+    assert!(!is_license_allowed("MIT OR", &allow));
+    assert!(!is_license_allowed("", &allow));
+  }
+}
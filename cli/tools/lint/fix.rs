@@ -0,0 +1,965 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Automatic fixes of lint diagnostics for `deno lint --fix`.
+//!
+//! The rules of `deno_lint` only report diagnostics, so the fixers of the
+//! supported rules create the fixes from the diagnostics and the AST of the
+//! file. A fix is a set of text edits that is applied as a whole or not at
+//! all. Fixes with edits that overlap the ones of another fix are left for the
+//! next pass, which lints the fixed text again.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use deno_ast::swc::ast::ArrowExpr;
+use deno_ast::swc::ast::AssignPatProp;
+use deno_ast::swc::ast::BindingIdent;
+use deno_ast::swc::ast::BlockStmt;
+use deno_ast::swc::ast::ClassMethod;
+use deno_ast::swc::ast::ClassProp;
+use deno_ast::swc::ast::Decl;
+use deno_ast::swc::ast::DefaultDecl;
+use deno_ast::swc::ast::ExportDecl;
+use deno_ast::swc::ast::ExportDefaultDecl;
+use deno_ast::swc::ast::ExportDefaultExpr;
+use deno_ast::swc::ast::ExportSpecifier;
+use deno_ast::swc::ast::Expr;
+use deno_ast::swc::ast::FnDecl;
+use deno_ast::swc::ast::ForInStmt;
+use deno_ast::swc::ast::ForOfStmt;
+use deno_ast::swc::ast::Function;
+use deno_ast::swc::ast::ImportDecl;
+use deno_ast::swc::ast::ImportSpecifier;
+use deno_ast::swc::ast::MethodProp;
+use deno_ast::swc::ast::Module;
+use deno_ast::swc::ast::ModuleExportName;
+use deno_ast::swc::ast::ModuleItem;
+use deno_ast::swc::ast::NamedExport;
+use deno_ast::swc::ast::Pat;
+use deno_ast::swc::ast::PrivateMethod;
+use deno_ast::swc::ast::Prop;
+use deno_ast::swc::ast::PropName;
+use deno_ast::swc::ast::PropOrSpread;
+use deno_ast::swc::ast::Script;
+use deno_ast::swc::ast::Stmt;
+use deno_ast::swc::ast::UnaryOp;
+use deno_ast::swc::ast::VarDecl;
+use deno_ast::swc::ast::VarDeclKind;
+use deno_ast::swc::ast::VarDeclarator;
+use deno_ast::swc::common::Span;
+use deno_ast::swc::common::Spanned;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_core::error::AnyError;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::rules::LintRule;
+use dissimilar::Chunk;
+
+use super::create_linter;
+use super::ignore_reasons::IgnoreReasons;
+use crate::args::FmtOptionsConfig;
+use crate::tools::fmt::format_file;
+
+/// The number of passes after which the remaining fixes are given up on, in
+/// case the fixes keep creating new problems.
+const MAX_PASSES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+  /// The byte range of the replaced text.
+  pub range: Range<usize>,
+  pub new_text: String,
+}
+
+/// The fix of a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFix {
+  pub edits: Vec<TextEdit>,
+}
+
+impl LintFix {
+  fn remove(range: Range<usize>) -> Self {
+    Self {
+      edits: vec![TextEdit {
+        range,
+        new_text: String::new(),
+      }],
+    }
+  }
+
+  fn replace(range: Range<usize>, new_text: String) -> Self {
+    Self {
+      edits: vec![TextEdit { range, new_text }],
+    }
+  }
+}
+
+/// Creates the fixes of the diagnostics of a lint rule.
+trait LintRuleFixer: Sync {
+  /// The code of the rule.
+  fn code(&self) -> &'static str;
+
+  fn fixes(
+    &self,
+    parsed_source: &ParsedSource,
+    diagnostics: &[&LintDiagnostic],
+  ) -> Vec<LintFix>;
+}
+
+static FIXERS: &[&dyn LintRuleFixer] =
+  &[&NoUnusedVarsFixer, &PreferConstFixer, &RequireAwaitFixer];
+
+pub struct FixedSource {
+  pub text: String,
+  /// The number of fixed diagnostics.
+  pub fix_count: usize,
+}
+
+/// Lints the source and applies the fixes of its diagnostics until none are
+/// left, after which the lines changed by the fixes are formatted. Only the
+/// diagnostics of the codes that `is_reported` accepts are fixed.
+pub fn fix_source(
+  file_path: &Path,
+  source_code: &str,
+  lint_rules: &[&'static dyn LintRule],
+  is_reported: impl Fn(&str) -> bool,
+  fmt_options: &FmtOptionsConfig,
+) -> Result<FixedSource, AnyError> {
+  let file_name = file_path.to_string_lossy().to_string();
+  let media_type = MediaType::from_path(file_path);
+  let mut text = source_code.to_string();
+  let mut fix_count = 0;
+  for _ in 0..MAX_PASSES {
+    // the reasons of ignore directives are blanked without moving the rest
+    // of the text, so the fixes apply to the text with the reasons
+    let reasons = IgnoreReasons::parse(&text);
+    let linter = create_linter(media_type, lint_rules.to_vec());
+    let (parsed_source, mut diagnostics) =
+      linter.lint(file_name.clone(), reasons.text)?;
+    diagnostics.retain(|d| is_reported(&d.code));
+    let (fixed_text, applied_count) =
+      apply_fixes(&text, create_fixes(&parsed_source, &diagnostics));
+    if applied_count == 0 {
+      break;
+    }
+    fix_count += applied_count;
+    text = fixed_text;
+  }
+
+  if fix_count > 0 {
+    let formatted =
+      format_fixed_lines(file_path, source_code, &text, fmt_options);
+    // mixing formatted and unformatted code is only done when it stays valid
+    let is_valid = deno_ast::parse_program(deno_ast::ParseParams {
+      specifier: file_name,
+      text_info: deno_ast::SourceTextInfo::from_string(formatted.clone()),
+      media_type,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .is_ok();
+    if is_valid {
+      text = formatted;
+    }
+  }
+
+  Ok(FixedSource { text, fix_count })
+}
+
+fn create_fixes(
+  parsed_source: &ParsedSource,
+  diagnostics: &[LintDiagnostic],
+) -> Vec<LintFix> {
+  let mut fixes = Vec::new();
+  for fixer in FIXERS {
+    let rule_diagnostics = diagnostics
+      .iter()
+      .filter(|d| d.code == fixer.code())
+      .collect::<Vec<_>>();
+    if !rule_diagnostics.is_empty() {
+      fixes.extend(fixer.fixes(parsed_source, &rule_diagnostics));
+    }
+  }
+  fixes
+}
+
+/// Applies the fixes whose edits don't conflict with the ones of a fix before
+/// them, returning the fixed text and the number of applied fixes.
+fn apply_fixes(text: &str, mut fixes: Vec<LintFix>) -> (String, usize) {
+  fixes.sort_by_key(|fix| fix.edits.iter().map(|e| e.range.start).min());
+  let mut edits: Vec<TextEdit> = Vec::new();
+  let mut applied_count = 0;
+  for fix in fixes {
+    let has_conflict = fix.edits.iter().any(|edit| {
+      edits
+        .iter()
+        .any(|other| ranges_conflict(&edit.range, &other.range))
+    });
+    if !has_conflict {
+      edits.extend(fix.edits);
+      applied_count += 1;
+    }
+  }
+
+  edits.sort_by_key(|edit| edit.range.start);
+  let mut output = String::with_capacity(text.len());
+  let mut last_end = 0;
+  for edit in edits {
+    output.push_str(&text[last_end..edit.range.start]);
+    output.push_str(&edit.new_text);
+    last_end = edit.range.end;
+  }
+  output.push_str(&text[last_end..]);
+  (output, applied_count)
+}
+
+fn ranges_conflict(a: &Range<usize>, b: &Range<usize>) -> bool {
+  // edits at the same position conflict because their order is ambiguous
+  a.start == b.start || (a.start < b.end && b.start < a.end)
+}
+
+/// Formats the lines that the fixes changed, leaving the formatting of the
+/// rest of the file as it is.
+fn format_fixed_lines(
+  file_path: &Path,
+  original_text: &str,
+  fixed_text: &str,
+  fmt_options: &FmtOptionsConfig,
+) -> String {
+  let formatted_text = match format_file(file_path, fixed_text, fmt_options) {
+    Ok(Some(formatted_text)) => formatted_text,
+    _ => return fixed_text.to_string(),
+  };
+
+  let line_starts = std::iter::once(0)
+    .chain(fixed_text.match_indices('\n').map(|(i, _)| i + 1))
+    .collect::<Vec<_>>();
+  let line_index = |pos: usize| match line_starts.binary_search(&pos) {
+    Ok(index) => index,
+    Err(index) => index - 1,
+  };
+  let mut fixed_lines = HashSet::new();
+  let mut pos = 0;
+  for chunk in dissimilar::diff(original_text, fixed_text) {
+    match chunk {
+      Chunk::Equal(s) => pos += s.len(),
+      Chunk::Delete(_) => {
+        // removed lines can leave blank lines before them
+        let line = line_index(pos);
+        fixed_lines.insert(line);
+        fixed_lines.insert(line.saturating_sub(1));
+      }
+      Chunk::Insert(s) => {
+        fixed_lines.extend(line_index(pos)..=line_index(pos + s.len()));
+        pos += s.len();
+      }
+    }
+  }
+
+  let mut output = String::with_capacity(formatted_text.len());
+  let mut pos = 0;
+  let mut deleted_len = 0;
+  let mut inserted = String::new();
+  let chunks = dissimilar::diff(fixed_text, &formatted_text);
+  for chunk in chunks.into_iter().chain(std::iter::once(Chunk::Equal(""))) {
+    match chunk {
+      Chunk::Delete(s) => deleted_len += s.len(),
+      Chunk::Insert(s) => inserted.push_str(s),
+      Chunk::Equal(s) => {
+        if deleted_len > 0 || !inserted.is_empty() {
+          let range = pos..pos + deleted_len;
+          let is_fixed = (line_index(range.start)..=line_index(range.end))
+            .any(|line| fixed_lines.contains(&line));
+          if is_fixed {
+            output.push_str(&inserted);
+          } else {
+            output.push_str(&fixed_text[range.clone()]);
+          }
+          pos = range.end;
+          deleted_len = 0;
+          inserted.clear();
+        }
+        output.push_str(s);
+        pos += s.len();
+      }
+    }
+  }
+  output
+}
+
+fn byte_range(parsed_source: &ParsedSource, span: Span) -> Range<usize> {
+  let start_pos = parsed_source.text_info().range().start;
+  let start = SourcePos::unsafely_from_byte_pos(span.lo) - start_pos;
+  let end = SourcePos::unsafely_from_byte_pos(span.hi) - start_pos;
+  start..end
+}
+
+fn reported_positions(diagnostics: &[&LintDiagnostic]) -> HashSet<usize> {
+  diagnostics
+    .iter()
+    .map(|d| d.range.start.byte_index)
+    .collect()
+}
+
+/// Extends the range of removed code over its lines when nothing else is on
+/// them.
+fn removal_range(text: &str, mut range: Range<usize>) -> Range<usize> {
+  if text[range.end..].starts_with(';') {
+    range.end += 1;
+  }
+  let line_start = text[..range.start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+  let line_end = text[range.end..]
+    .find('\n')
+    .map(|i| range.end + i + 1)
+    .unwrap_or(text.len());
+  if text[line_start..range.start].trim().is_empty()
+    && text[range.end..line_end].trim().is_empty()
+  {
+    line_start..line_end
+  } else {
+    range
+  }
+}
+
+/// Extends the range of a removed import specifier over the comma after it,
+/// or the one before it when it is the last specifier.
+fn specifier_removal_range(text: &str, range: Range<usize>) -> Range<usize> {
+  let after = &text[range.end..];
+  let trimmed_after = after.trim_start();
+  if trimmed_after.starts_with(',') {
+    let comma_end = range.end + (after.len() - trimmed_after.len()) + 1;
+    let rest = &text[comma_end..];
+    return range.start..comma_end + (rest.len() - rest.trim_start().len());
+  }
+  let trimmed_before = text[..range.start].trim_end();
+  if trimmed_before.ends_with(',') {
+    trimmed_before.len() - 1..range.end
+  } else {
+    range
+  }
+}
+
+/// Removes unused imports, and the declarations of unused variables whose
+/// initializers have no side effects and of unused functions. An import
+/// whose specifiers are all unused is kept as `import "mod";`, because
+/// evaluating the module may have side effects, unless it only imports types.
+struct NoUnusedVarsFixer;
+
+impl LintRuleFixer for NoUnusedVarsFixer {
+  fn code(&self) -> &'static str {
+    "no-unused-vars"
+  }
+
+  fn fixes(
+    &self,
+    parsed_source: &ParsedSource,
+    diagnostics: &[&LintDiagnostic],
+  ) -> Vec<LintFix> {
+    let mut collector = RemovableDeclCollector::default();
+    parsed_source.program().as_ref().visit_with(&mut collector);
+    let text = parsed_source.text_info().text_str();
+    let reported = reported_positions(diagnostics);
+    let is_reported =
+      |span: &Span| reported.contains(&byte_range(parsed_source, *span).start);
+
+    let mut fixes = Vec::new();
+    for import in collector.imports {
+      let unused = import
+        .specifiers
+        .iter()
+        .filter(|(local, _)| is_reported(local))
+        .collect::<Vec<_>>();
+      if unused.is_empty() {
+        continue;
+      }
+      if unused.len() == import.specifiers.len() && import.type_only {
+        fixes.push(LintFix::remove(removal_range(
+          text,
+          byte_range(parsed_source, import.span),
+        )));
+      } else if unused.len() == import.specifiers.len() {
+        let range = byte_range(parsed_source, import.span);
+        // the source keeps its quotes and the import assertions after it
+        let src_start = byte_range(parsed_source, import.src).start;
+        fixes.push(LintFix::replace(
+          range.clone(),
+          format!("import {}", &text[src_start..range.end]),
+        ));
+      } else {
+        for (_, specifier) in unused {
+          fixes.push(LintFix::remove(specifier_removal_range(
+            text,
+            byte_range(parsed_source, *specifier),
+          )));
+        }
+      }
+    }
+    for (binding, stmt) in collector.stmts {
+      if is_reported(&binding) {
+        fixes.push(LintFix::remove(removal_range(
+          text,
+          byte_range(parsed_source, stmt),
+        )));
+      }
+    }
+    fixes
+  }
+}
+
+struct ImportSpans {
+  span: Span,
+  src: Span,
+  /// Whether the import is `import type`, which has no side effects.
+  type_only: bool,
+  /// The spans of the local names and of the specifiers.
+  specifiers: Vec<(Span, Span)>,
+}
+
+#[derive(Default)]
+struct RemovableDeclCollector {
+  imports: Vec<ImportSpans>,
+  /// The spans of the names and of the statements that declare them.
+  stmts: Vec<(Span, Span)>,
+}
+
+impl RemovableDeclCollector {
+  fn add_stmts<'a>(&mut self, stmts: impl Iterator<Item = &'a Stmt>) {
+    for stmt in stmts {
+      match stmt {
+        Stmt::Decl(Decl::Var(var_decl))
+          if !var_decl.declare && var_decl.decls.len() == 1 =>
+        {
+          let declarator = &var_decl.decls[0];
+          if let Pat::Ident(binding) = &declarator.name {
+            if declarator.init.as_deref().map(is_pure).unwrap_or(true) {
+              self.stmts.push((binding.id.span, stmt.span()));
+            }
+          }
+        }
+        Stmt::Decl(Decl::Fn(fn_decl)) if !fn_decl.declare => {
+          self.stmts.push((fn_decl.ident.span, stmt.span()));
+        }
+        _ => {}
+      }
+    }
+  }
+}
+
+impl Visit for RemovableDeclCollector {
+  fn visit_import_decl(&mut self, import_decl: &ImportDecl) {
+    let specifiers = import_decl
+      .specifiers
+      .iter()
+      .map(|specifier| match specifier {
+        ImportSpecifier::Named(named) => (named.local.span, named.span),
+        ImportSpecifier::Default(default) => (default.local.span, default.span),
+        ImportSpecifier::Namespace(namespace) => {
+          (namespace.local.span, namespace.span)
+        }
+      })
+      .collect::<Vec<_>>();
+    if !specifiers.is_empty() {
+      self.imports.push(ImportSpans {
+        span: import_decl.span,
+        src: import_decl.src.span,
+        type_only: import_decl.type_only,
+        specifiers,
+      });
+    }
+  }
+
+  // only statements directly in a body are removed, because removing the
+  // body of an `if` or a loop would change the meaning of the code
+  fn visit_module(&mut self, module: &Module) {
+    self.add_stmts(module.body.iter().filter_map(|item| match item {
+      ModuleItem::Stmt(stmt) => Some(stmt),
+      ModuleItem::ModuleDecl(_) => None,
+    }));
+    module.visit_children_with(self);
+  }
+
+  fn visit_script(&mut self, script: &Script) {
+    self.add_stmts(script.body.iter());
+    script.visit_children_with(self);
+  }
+
+  fn visit_block_stmt(&mut self, block: &BlockStmt) {
+    self.add_stmts(block.stmts.iter());
+    block.visit_children_with(self);
+  }
+}
+
+/// Whether evaluating the expression has no side effects.
+fn is_pure(expr: &Expr) -> bool {
+  match expr {
+    Expr::Lit(_) | Expr::Ident(_) | Expr::Arrow(_) | Expr::Fn(_) => true,
+    Expr::Tpl(tpl) => tpl.exprs.is_empty(),
+    Expr::Paren(paren) => is_pure(&paren.expr),
+    Expr::Unary(unary) => unary.op != UnaryOp::Delete && is_pure(&unary.arg),
+    Expr::Array(array) => array
+      .elems
+      .iter()
+      .flatten()
+      .all(|elem| elem.spread.is_none() && is_pure(&elem.expr)),
+    Expr::Object(object) => object.props.iter().all(|prop| match prop {
+      PropOrSpread::Prop(prop) => match prop.as_ref() {
+        Prop::Shorthand(_) => true,
+        Prop::KeyValue(key_value) => {
+          !matches!(key_value.key, PropName::Computed(_))
+            && is_pure(&key_value.value)
+        }
+        _ => false,
+      },
+      PropOrSpread::Spread(_) => false,
+    }),
+    Expr::TsAs(ts_as) => is_pure(&ts_as.expr),
+    Expr::TsConstAssertion(assertion) => is_pure(&assertion.expr),
+    _ => false,
+  }
+}
+
+/// Replaces `let` with `const` in declarations whose bindings are all
+/// reported.
+struct PreferConstFixer;
+
+impl LintRuleFixer for PreferConstFixer {
+  fn code(&self) -> &'static str {
+    "prefer-const"
+  }
+
+  fn fixes(
+    &self,
+    parsed_source: &ParsedSource,
+    diagnostics: &[&LintDiagnostic],
+  ) -> Vec<LintFix> {
+    let mut collector = LetDeclCollector::default();
+    parsed_source.program().as_ref().visit_with(&mut collector);
+    let text = parsed_source.text_info().text_str();
+    let reported = reported_positions(diagnostics);
+
+    collector
+      .decls
+      .iter()
+      .filter(|decl| {
+        // `const` needs an initializer, unless it's the head of a for in/of
+        (decl.has_inits || collector.for_heads.contains(&decl.span))
+          && !decl.bindings.is_empty()
+          && decl.bindings.iter().all(|binding| {
+            reported.contains(&byte_range(parsed_source, *binding).start)
+          })
+      })
+      .filter_map(|decl| {
+        let start = byte_range(parsed_source, decl.span).start;
+        let range = start..start + "let".len();
+        (text.get(range.clone()) == Some("let")).then(|| LintFix {
+          edits: vec![TextEdit {
+            range,
+            new_text: "const".to_string(),
+          }],
+        })
+      })
+      .collect()
+  }
+}
+
+struct LetDecl {
+  span: Span,
+  /// The spans of the names of the declared bindings.
+  bindings: Vec<Span>,
+  has_inits: bool,
+}
+
+#[derive(Default)]
+struct LetDeclCollector {
+  decls: Vec<LetDecl>,
+  for_heads: Vec<Span>,
+}
+
+impl Visit for LetDeclCollector {
+  fn visit_for_in_stmt(&mut self, for_in: &ForInStmt) {
+    self.for_heads.push(for_in.left.span());
+    for_in.visit_children_with(self);
+  }
+
+  fn visit_for_of_stmt(&mut self, for_of: &ForOfStmt) {
+    self.for_heads.push(for_of.left.span());
+    for_of.visit_children_with(self);
+  }
+
+  fn visit_var_decl(&mut self, var_decl: &VarDecl) {
+    if var_decl.kind == VarDeclKind::Let && !var_decl.declare {
+      let mut collector = BindingCollector::default();
+      for declarator in &var_decl.decls {
+        declarator.name.visit_with(&mut collector);
+      }
+      self.decls.push(LetDecl {
+        span: var_decl.span,
+        bindings: collector.bindings,
+        has_inits: var_decl.decls.iter().all(|d| d.init.is_some()),
+      });
+    }
+    var_decl.visit_children_with(self);
+  }
+}
+
+#[derive(Default)]
+struct BindingCollector {
+  bindings: Vec<Span>,
+}
+
+impl Visit for BindingCollector {
+  fn visit_binding_ident(&mut self, binding: &BindingIdent) {
+    self.bindings.push(binding.id.span);
+  }
+
+  // the shorthand of an object pattern, like `a` in `let { a } = obj`
+  fn visit_assign_pat_prop(&mut self, prop: &AssignPatProp) {
+    self.bindings.push(prop.key.span());
+    prop.visit_children_with(self);
+  }
+}
+
+/// Removes the `async` keyword of functions that don't use `await`, unless
+/// their return type is annotated as a promise. The functions that other
+/// modules may call, which are the exported ones and the methods, are left
+/// as they are, because their callers may use the promise, like with
+/// `.then()`.
+struct RequireAwaitFixer;
+
+impl LintRuleFixer for RequireAwaitFixer {
+  fn code(&self) -> &'static str {
+    "require-await"
+  }
+
+  fn fixes(
+    &self,
+    parsed_source: &ParsedSource,
+    diagnostics: &[&LintDiagnostic],
+  ) -> Vec<LintFix> {
+    let mut exported_names = ExportedNamesCollector::default();
+    parsed_source
+      .program()
+      .as_ref()
+      .visit_with(&mut exported_names);
+    let mut collector = AsyncFnCollector {
+      exported_names: exported_names.names,
+      heads: Vec::new(),
+    };
+    parsed_source.program().as_ref().visit_with(&mut collector);
+    let text = parsed_source.text_info().text_str();
+
+    let mut fixes = Vec::new();
+    for (head, keyword_search_start) in collector.heads {
+      let head = byte_range(parsed_source, head);
+      let is_reported = diagnostics
+        .iter()
+        .any(|d| head.contains(&d.range.start.byte_index));
+      if !is_reported {
+        continue;
+      }
+      let search_start = byte_range(parsed_source, keyword_search_start)
+        .start
+        .max(head.start);
+      if let Some(range) = find_async_keyword(text, search_start..head.end) {
+        fixes.push(LintFix::remove(range));
+      }
+    }
+    fixes
+  }
+}
+
+/// Finds the `async` keyword in the head of a function, returning its range
+/// along with the whitespace after it.
+fn find_async_keyword(text: &str, head: Range<usize>) -> Option<Range<usize>> {
+  let head_text = &text[head.clone()];
+  let mut offset = 0;
+  while let Some(index) = head_text[offset..].find("async") {
+    let start = offset + index;
+    let end = start + "async".len();
+    let is_word_start = head_text[..start]
+      .chars()
+      .next_back()
+      .map(|c| !(c.is_alphanumeric() || c == '_' || c == '$'))
+      .unwrap_or(true);
+    let next_char = head_text[end..].chars().next();
+    if is_word_start
+      && matches!(next_char, Some(c) if c.is_whitespace() || c == '(')
+    {
+      let rest = &head_text[end..];
+      let whitespace_len = rest.len() - rest.trim_start().len();
+      return Some(head.start + start..head.start + end + whitespace_len);
+    }
+    offset = end;
+  }
+  None
+}
+
+/// Collects the local names exported with `export { name }` and
+/// `export default name`.
+#[derive(Default)]
+struct ExportedNamesCollector {
+  names: HashSet<String>,
+}
+
+impl Visit for ExportedNamesCollector {
+  fn visit_named_export(&mut self, named_export: &NamedExport) {
+    // a re-export doesn't export a local name
+    if named_export.src.is_some() {
+      return;
+    }
+    for specifier in &named_export.specifiers {
+      if let ExportSpecifier::Named(named) = specifier {
+        if let ModuleExportName::Ident(ident) = &named.orig {
+          self.names.insert(ident.sym.to_string());
+        }
+      }
+    }
+  }
+
+  fn visit_export_default_expr(&mut self, export: &ExportDefaultExpr) {
+    if let Expr::Ident(ident) = &*export.expr {
+      self.names.insert(ident.sym.to_string());
+    }
+  }
+}
+
+struct AsyncFnCollector {
+  exported_names: HashSet<String>,
+  /// The spans from the start of the functions to their bodies, along with
+  /// the span that the search for the keyword starts at, which is after the
+  /// decorators.
+  heads: Vec<(Span, Span)>,
+}
+
+impl AsyncFnCollector {
+  /// Visits the functions in an expression whose own function isn't fixed,
+  /// like the one of an exported variable.
+  fn visit_unfixed_fn_expr(&mut self, expr: &Expr) {
+    match expr {
+      Expr::Arrow(arrow) => arrow.visit_children_with(self),
+      Expr::Fn(fn_expr) => fn_expr.function.visit_children_with(self),
+      Expr::Paren(paren) => self.visit_unfixed_fn_expr(&paren.expr),
+      _ => expr.visit_with(self),
+    }
+  }
+}
+
+impl Visit for AsyncFnCollector {
+  fn visit_export_decl(&mut self, export: &ExportDecl) {
+    match &export.decl {
+      Decl::Fn(fn_decl) => fn_decl.function.visit_children_with(self),
+      Decl::Var(var_decl) => {
+        for declarator in &var_decl.decls {
+          declarator.name.visit_with(self);
+          if let Some(init) = &declarator.init {
+            self.visit_unfixed_fn_expr(init);
+          }
+        }
+      }
+      decl => decl.visit_with(self),
+    }
+  }
+
+  fn visit_export_default_decl(&mut self, export: &ExportDefaultDecl) {
+    match &export.decl {
+      DefaultDecl::Fn(fn_expr) => fn_expr.function.visit_children_with(self),
+      decl => decl.visit_with(self),
+    }
+  }
+
+  fn visit_export_default_expr(&mut self, export: &ExportDefaultExpr) {
+    self.visit_unfixed_fn_expr(&export.expr);
+  }
+
+  fn visit_fn_decl(&mut self, fn_decl: &FnDecl) {
+    if self.exported_names.contains(&*fn_decl.ident.sym) {
+      fn_decl.function.visit_children_with(self);
+    } else {
+      fn_decl.visit_children_with(self);
+    }
+  }
+
+  fn visit_var_declarator(&mut self, declarator: &VarDeclarator) {
+    let is_exported = match &declarator.name {
+      Pat::Ident(binding) => self.exported_names.contains(&*binding.id.sym),
+      _ => false,
+    };
+    match &declarator.init {
+      Some(init) if is_exported => {
+        declarator.name.visit_with(self);
+        self.visit_unfixed_fn_expr(init);
+      }
+      _ => declarator.visit_children_with(self),
+    }
+  }
+
+  fn visit_class_method(&mut self, method: &ClassMethod) {
+    method.key.visit_with(self);
+    method.function.visit_children_with(self);
+  }
+
+  fn visit_private_method(&mut self, method: &PrivateMethod) {
+    method.function.visit_children_with(self);
+  }
+
+  fn visit_method_prop(&mut self, method: &MethodProp) {
+    method.key.visit_with(self);
+    method.function.visit_children_with(self);
+  }
+
+  fn visit_class_prop(&mut self, prop: &ClassProp) {
+    prop.key.visit_with(self);
+    if let Some(value) = &prop.value {
+      self.visit_unfixed_fn_expr(value);
+    }
+  }
+
+  fn visit_function(&mut self, function: &Function) {
+    if function.is_async
+      && !function.is_generator
+      && function.return_type.is_none()
+    {
+      if let Some(body) = &function.body {
+        let head =
+          Span::new(function.span.lo, body.span.lo, Default::default());
+        let search_start = function
+          .decorators
+          .last()
+          .map(|decorator| decorator.span.shrink_to_hi())
+          .unwrap_or(head);
+        self.heads.push((head, search_start));
+      }
+    }
+    function.visit_children_with(self);
+  }
+
+  fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+    if arrow.is_async && !arrow.is_generator && arrow.return_type.is_none() {
+      let head =
+        Span::new(arrow.span.lo, arrow.body.span().lo, Default::default());
+      self.heads.push((head, head));
+    }
+    arrow.visit_children_with(self);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use deno_lint::rules::get_recommended_rules;
+
+  fn edit(range: Range<usize>, new_text: &str) -> LintFix {
+    LintFix {
+      edits: vec![TextEdit {
+        range,
+        new_text: new_text.to_string(),
+      }],
+    }
+  }
+
+  #[test]
+  fn applies_fixes_without_conflicts() {
+    let text = "let a = 1, b = 2;";
+    let (fixed, applied_count) = apply_fixes(
+      text,
+      vec![
+        edit(16..16, " // c"),
+        edit(0..3, "const"),
+        // conflicts with the fix of `let`
+        edit(0..10, "let"),
+        LintFix {
+          edits: vec![
+            TextEdit {
+              range: 4..5,
+              new_text: "x".to_string(),
+            },
+            TextEdit {
+              range: 11..12,
+              new_text: "y".to_string(),
+            },
+          ],
+        },
+      ],
+    );
+    assert_eq!(fixed, "const x = 1, y = 2 // c;");
+    assert_eq!(applied_count, 3);
+  }
+
+  #[test]
+  fn removal_ranges() {
+    let text = "a();\nconst x = 1;\nb(); const y = 2; c();\n";
+    assert_eq!(&text[removal_range(text, 5..16)], "const x = 1;\n");
+    assert_eq!(&text[removal_range(text, 23..34)], "const y = 2;");
+
+    let text = "import a, { b, c } from \"./mod.ts\";";
+    assert_eq!(&text[specifier_removal_range(text, 7..8)], "a, ");
+    assert_eq!(&text[specifier_removal_range(text, 12..13)], "b, ");
+    assert_eq!(&text[specifier_removal_range(text, 15..16)], ", c");
+  }
+
+  #[test]
+  fn finds_async_keyword() {
+    let text = "async function f() {}";
+    assert_eq!(find_async_keyword(text, 0..19), Some(0..6));
+    let text = "const f = async(x) => x;";
+    assert_eq!(find_async_keyword(text, 10..22), Some(10..15));
+    let text = "class A { asyncMethod() {} }";
+    assert_eq!(find_async_keyword(text, 10..24), None);
+  }
+
+  #[test]
+  fn fixes_source() {
+    let source = r#"import { a, b } from "./a.ts";
+import { c } from "./c.ts";
+import type { D } from "./d.ts";
+
+let x = 1;
+let y = 2;
+y++;
+
+async function g() {
+  return x + y + a;
+}
+
+export async function f() {
+  return g();
+}
+
+export const h = async () => 1;
+"#;
+    let fixed = fix_source(
+      Path::new("/mod.ts"),
+      source,
+      &get_recommended_rules(),
+      |_| true,
+      &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(
+      fixed.text,
+      r#"import { a } from "./a.ts";
+import "./c.ts";
+
+const x = 1;
+let y = 2;
+y++;
+
+function g() {
+  return x + y + a;
+}
+
+export async function f() {
+  return g();
+}
+
+export const h = async () => 1;
+"#
+    );
+    assert_eq!(fixed.fix_count, 5);
+  }
+}
@@ -0,0 +1,133 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Reasons of ignore directives, like
+//! `// deno-lint-ignore no-explicit-any -- the response is untyped`.
+//!
+//! `deno_lint` would take the words of a reason for rule codes, so the
+//! reasons are replaced with spaces in the text that is linted, which keeps
+//! the positions of the diagnostics.
+
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::diagnostic::Position;
+use deno_lint::diagnostic::Range;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// The code of the diagnostics of ignore directives without a reason, which
+/// are only reported when the rule is given a severity.
+pub const MISSING_REASON_CODE: &str = "require-ignore-reason";
+
+static IGNORE_DIRECTIVE_RE: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"//\s*deno-lint-ignore(?:-file)?(?:\s|$)").unwrap());
+static REASON_SEPARATOR_RE: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"(?:^|\s)--(?:\s|$)").unwrap());
+
+pub struct IgnoreReasons {
+  /// The source with the reasons of the directives replaced with spaces.
+  pub text: String,
+  pub has_reasons: bool,
+  /// The ranges of the directives without a reason.
+  pub missing: Vec<Range>,
+}
+
+impl IgnoreReasons {
+  pub fn parse(source: &str) -> Self {
+    let mut bytes = source.as_bytes().to_vec();
+    let mut has_reasons = false;
+    let mut missing = Vec::new();
+    let mut line_start = 0;
+    for (line_index, line) in source.split('\n').enumerate() {
+      let line_text = line.strip_suffix('\r').unwrap_or(line);
+      if let Some(directive) = IGNORE_DIRECTIVE_RE.find(line_text) {
+        let separator = REASON_SEPARATOR_RE.find(&line_text[directive.end()..]);
+        if let Some(separator) = &separator {
+          // the bytes are replaced, which keeps the text valid UTF-8 because
+          // whole characters up to the end of the line are replaced
+          let start = line_start + directive.end() + separator.start();
+          let end = line_start + line_text.len();
+          bytes[start..end].fill(b' ');
+        }
+        let has_reason = separator
+          .map(|separator| {
+            !line_text[directive.end() + separator.end()..]
+              .trim()
+              .is_empty()
+          })
+          .unwrap_or(false);
+        if has_reason {
+          has_reasons = true;
+        } else {
+          let position = |byte_offset: usize| Position {
+            line_index,
+            column_index: line_text[..byte_offset].chars().count(),
+            byte_index: line_start + byte_offset,
+          };
+          missing.push(Range {
+            start: position(directive.start()),
+            end: position(line_text.len()),
+          });
+        }
+      }
+      line_start += line.len() + 1;
+    }
+    Self {
+      text: String::from_utf8(bytes).unwrap(),
+      has_reasons,
+      missing,
+    }
+  }
+
+  /// The diagnostics of the directives without a reason.
+  pub fn missing_reason_diagnostics(
+    &self,
+    file_name: &str,
+  ) -> Vec<LintDiagnostic> {
+    self
+      .missing
+      .iter()
+      .map(|range| LintDiagnostic {
+        range: range.clone(),
+        filename: file_name.to_string(),
+        message: "Ignore directive is missing a reason".to_string(),
+        code: MISSING_REASON_CODE.to_string(),
+        hint: Some(
+          "Add the reason after \"--\", like `// deno-lint-ignore no-explicit-any -- the API is untyped`"
+            .to_string(),
+        ),
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parses_ignore_reasons() {
+    let source = "// deno-lint-ignore-file no-console -- a script\nconst a = \"ä\"; // deno-lint-ignore no-explicit-any -- ä\r\n// deno-lint-ignore no-var\n// deno-lint-ignore no-var --\n";
+    let reasons = IgnoreReasons::parse(source);
+    assert_eq!(reasons.text.len(), source.len());
+    assert_eq!(
+      reasons.text,
+      "// deno-lint-ignore-file no-console            \nconst a = \"ä\"; // deno-lint-ignore no-explicit-any      \r\n// deno-lint-ignore no-var\n// deno-lint-ignore no-var   \n",
+    );
+    assert!(reasons.has_reasons);
+    let missing = reasons
+      .missing
+      .iter()
+      .map(|range| {
+        (
+          range.start.line_index,
+          range.start.column_index,
+          range.end.column_index,
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(missing, vec![(2, 0, 26), (3, 0, 29)]);
+    assert_eq!(
+      reasons.missing_reason_diagnostics("main.ts")[0].code,
+      MISSING_REASON_CODE
+    );
+  }
+}
@@ -8,18 +8,26 @@
 //! the same functions as ops available in JS runtime.
 use crate::args::CliOptions;
 use crate::args::FilesConfig;
+use crate::args::FmtOptions;
+use crate::args::LintFailOn;
 use crate::args::LintOptions;
 use crate::args::LintReporterKind;
 use crate::args::LintRulesConfig;
+use crate::args::LintSeverity;
+use crate::args::LintSeverityConfig;
 use crate::cache::Caches;
 use crate::colors;
+use crate::proc_state::ProcState;
 use crate::tools::fmt::run_parallelized;
 use crate::util::file_watcher;
 use crate::util::file_watcher::ResolutionResult;
+use crate::util::fs::atomic_write_file;
 use crate::util::fs::FileCollector;
 use crate::util::path::is_supported_ext;
 use deno_ast::MediaType;
+use deno_ast::ParsedSource;
 use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsStackFrame;
@@ -33,18 +41,28 @@ use deno_runtime::fmt_errors::format_location;
 use log::debug;
 use log::info;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fs;
 use std::io::stdin;
 use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::Mutex;
 
 use crate::cache::IncrementalCache;
 
+mod fix;
+mod ignore_reasons;
+mod plugins;
+
+use ignore_reasons::IgnoreReasons;
+use plugins::LintPluginRunner;
+
 static STDIN_FILE_NAME: &str = "_stdin.ts";
 
 fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
@@ -56,18 +74,33 @@ fn create_reporter(kind: LintReporterKind) -> Box<dyn LintReporter + Send> {
 }
 
 pub async fn lint(
-  cli_options: CliOptions,
+  cli_options: Arc<CliOptions>,
   lint_options: LintOptions,
 ) -> Result<(), AnyError> {
   // Try to get lint rules. If none were set use recommended rules.
-  let lint_rules = get_configured_rules(lint_options.rules);
+  let rule_set =
+    Arc::new(LintRuleSet::new(lint_options.rules, lint_options.severity));
 
-  if lint_rules.is_empty() {
+  if rule_set.rules.is_empty() {
     bail!("No rules have been configured")
   }
 
   let files = lint_options.files;
   let reporter_kind = lint_options.reporter_kind;
+  let plugins = lint_options.plugins;
+  let fail_on = lint_options.fail_on;
+  let maybe_fix_fmt_options =
+    lint_options.fix.then_some(lint_options.fmt_options);
+  if lint_options.is_stdin && maybe_fix_fmt_options.is_some() {
+    return Err(generic_error(
+      "Lint fix on standard input is not supported.",
+    ));
+  }
+  let maybe_ps = if plugins.is_empty() {
+    None
+  } else {
+    Some(ProcState::from_cli_options(cli_options.clone()).await?)
+  };
 
   let resolver = |changed: Option<Vec<PathBuf>>| {
     let files_changed = changed.is_some();
@@ -103,24 +136,58 @@ pub async fn lint(
   let operation = |paths: Vec<PathBuf>| async {
     let incremental_cache = Arc::new(IncrementalCache::new(
       caches.lint_incremental_cache_db(&deno_dir),
-      // use a hash of the rule names in order to bust the cache
-      &{
-        // ensure this is stable by sorting it
-        let mut names = lint_rules.iter().map(|r| r.code()).collect::<Vec<_>>();
-        names.sort_unstable();
-        names
-      },
+      // use a hash of the rule names, plugins and severities in order to
+      // bust the cache
+      &(
+        {
+          // ensure this is stable by sorting it
+          let mut names =
+            rule_set.rules.iter().map(|r| r.code()).collect::<Vec<_>>();
+          names.sort_unstable();
+          names.extend(plugins.iter().map(|p| p.as_str()));
+          names
+        },
+        &rule_set.severity,
+      ),
       &paths,
     ));
+    if let Some(fmt_options) = &maybe_fix_fmt_options {
+      fix_files(
+        paths.clone(),
+        rule_set.clone(),
+        fmt_options.clone(),
+        incremental_cache.clone(),
+      )
+      .await?;
+    }
+    // plugins run in a single worker, so they're executed up front on this
+    // thread and their diagnostics are merged into the parallel pass below
+    let plugin_diagnostics = Arc::new(Mutex::new(match &maybe_ps {
+      Some(ps) => {
+        let mut runner = LintPluginRunner::create(ps, &plugins).await?;
+        let mut diagnostics = HashMap::new();
+        for file_path in &paths {
+          let file_text = fs::read_to_string(file_path)?;
+          if incremental_cache.is_file_same(file_path, &file_text) {
+            continue;
+          }
+          diagnostics
+            .insert(file_path.clone(), runner.lint_file(file_path, file_text));
+        }
+        diagnostics
+      }
+      None => HashMap::new(),
+    }));
     let target_files_len = paths.len();
     let reporter_lock =
       Arc::new(Mutex::new(create_reporter(reporter_kind.clone())));
 
     run_parallelized(paths, {
       let has_error = has_error.clone();
-      let lint_rules = lint_rules.clone();
+      let rule_set = rule_set.clone();
       let reporter_lock = reporter_lock.clone();
       let incremental_cache = incremental_cache.clone();
+      let plugin_diagnostics = plugin_diagnostics.clone();
       move |file_path| {
         let file_text = fs::read_to_string(&file_path)?;
 
@@ -129,7 +196,21 @@ pub async fn lint(
           return Ok(());
         }
 
-        let r = lint_file(&file_path, file_text, lint_rules);
+        let mut r = lint_file(&file_path, file_text, &rule_set.rules);
+        let maybe_plugin_result =
+          plugin_diagnostics.lock().unwrap().remove(&file_path);
+        if let Some(plugin_result) = maybe_plugin_result {
+          r = r.and_then(|(mut file_diagnostics, file_text)| {
+            file_diagnostics.extend(plugin_result?);
+            Ok((file_diagnostics, file_text))
+          });
+        }
+        let r = r.map(|(file_diagnostics, file_text)| {
+          (
+            rule_set.apply_severity(&file_path, file_diagnostics),
+            file_text,
+          )
+        });
         if let Ok((file_diagnostics, file_text)) = &r {
           if file_diagnostics.is_empty() {
             // update the incremental cache if there were no diagnostics
@@ -142,6 +223,7 @@ pub async fn lint(
           r,
           reporter_lock.clone(),
           has_error,
+          fail_on,
         );
 
         Ok(())
@@ -165,18 +247,27 @@ pub async fn lint(
       file_watcher::PrintConfig {
         job_name: "Lint".to_string(),
         clear_screen: !cli_options.no_clear_screen(),
+        filter: cli_options.watch_filter()?,
+        debounce: cli_options.watch_debounce()?,
+        before_restart: crate::tools::task::before_restart_hook(&cli_options)?,
       },
     )
     .await?;
   } else {
     if lint_options.is_stdin {
       let reporter_lock = Arc::new(Mutex::new(create_reporter(reporter_kind)));
-      let r = lint_stdin(lint_rules);
+      let r = lint_stdin(&rule_set.rules).map(|(file_diagnostics, source)| {
+        (
+          rule_set.apply_severity(Path::new(STDIN_FILE_NAME), file_diagnostics),
+          source,
+        )
+      });
       handle_lint_result(
         STDIN_FILE_NAME,
         r,
         reporter_lock.clone(),
         has_error.clone(),
+        fail_on,
       );
       reporter_lock.lock().unwrap().close(1);
     } else {
@@ -199,6 +290,80 @@ pub async fn lint(
   Ok(())
 }
 
+/// Applies the automatic fixes to the files, which are linted afterwards to
+/// report the problems that are left.
+async fn fix_files(
+  paths: Vec<PathBuf>,
+  rule_set: Arc<LintRuleSet>,
+  fmt_options: FmtOptions,
+  incremental_cache: Arc<IncrementalCache>,
+) -> Result<(), AnyError> {
+  let fix_count = Arc::new(AtomicUsize::new(0));
+  let fixed_files_count = Arc::new(AtomicUsize::new(0));
+  run_parallelized(paths, {
+    let fix_count = fix_count.clone();
+    let fixed_files_count = fixed_files_count.clone();
+    move |file_path| {
+      let file_text = fs::read_to_string(&file_path)?;
+      if incremental_cache.is_file_same(&file_path, &file_text) {
+        return Ok(());
+      }
+      // the errors of files that can't be linted are reported by the lint
+      let fixed = match fix::fix_source(
+        &file_path,
+        &file_text,
+        &rule_set.rules,
+        |code| rule_set.severity(&file_path, code) != LintSeverity::Off,
+        &fmt_options.options_for_path(&file_path),
+      ) {
+        Ok(fixed) => fixed,
+        Err(_) => return Ok(()),
+      };
+      if fixed.fix_count > 0 && fixed.text != file_text {
+        write_fixed_file(&file_path, &fixed.text)?;
+        fix_count.fetch_add(fixed.fix_count, Ordering::Relaxed);
+        fixed_files_count.fetch_add(1, Ordering::Relaxed);
+      }
+      Ok(())
+    }
+  })
+  .await?;
+
+  let fix_count = fix_count.load(Ordering::Relaxed);
+  let fixed_files_count = fixed_files_count.load(Ordering::Relaxed);
+  if fix_count > 0 {
+    info!(
+      "Fixed {} {} in {} {}",
+      fix_count,
+      if fix_count == 1 {
+        "problem"
+      } else {
+        "problems"
+      },
+      fixed_files_count,
+      if fixed_files_count == 1 {
+        "file"
+      } else {
+        "files"
+      },
+    );
+  }
+  Ok(())
+}
+
+/// Replaces the file with the fixed text at once, keeping its permissions.
+fn write_fixed_file(file_path: &Path, text: &str) -> Result<(), AnyError> {
+  #[cfg(unix)]
+  let mode = {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(file_path)?.permissions().mode()
+  };
+  #[cfg(not(unix))]
+  let mode = 0o644;
+  atomic_write_file(file_path, text, mode)
+    .with_context(|| format!("Failed writing {}", file_path.display()))
+}
+
 fn collect_lint_files(files: &FilesConfig) -> Result<Vec<PathBuf>, AnyError> {
   FileCollector::new(is_supported_ext)
     .ignore_git_folder()
@@ -247,17 +412,124 @@ pub fn create_linter(
     .build()
 }
 
+/// The rules that files are linted with and the severities of their
+/// diagnostics.
+pub struct LintRuleSet {
+  pub rules: Vec<&'static dyn LintRule>,
+  /// The codes of the rules that are reported when they have no severity.
+  default_codes: HashSet<&'static str>,
+  pub severity: LintSeverityConfig,
+}
+
+impl LintRuleSet {
+  pub fn new(
+    rules_config: LintRulesConfig,
+    severity: LintSeverityConfig,
+  ) -> Self {
+    let mut rules = get_configured_rules(rules_config);
+    let default_codes = rules.iter().map(|r| r.code()).collect::<HashSet<_>>();
+    // rules that aren't configured are still run when they have a severity
+    let enabled_codes = severity.enabled_codes();
+    rules.extend(rules::get_all_rules().into_iter().filter(|r| {
+      !default_codes.contains(r.code()) && enabled_codes.contains(r.code())
+    }));
+    Self {
+      rules,
+      default_codes,
+      severity,
+    }
+  }
+
+  /// Gets the severity of the diagnostics of a rule in a file.
+  pub fn severity(&self, file_path: &Path, code: &str) -> LintSeverity {
+    self.severity.resolve(file_path, code).unwrap_or_else(|| {
+      // the codes of plugin rules are prefixed with the name of the plugin
+      if self.default_codes.contains(code) || code.contains('/') {
+        LintSeverity::Error
+      } else {
+        LintSeverity::Off
+      }
+    })
+  }
+
+  /// Resolves the severities of the diagnostics of a file, leaving out the
+  /// ones that are turned off.
+  pub fn apply_severity(
+    &self,
+    file_path: &Path,
+    diagnostics: Vec<LintDiagnostic>,
+  ) -> Vec<(LintDiagnostic, LintSeverity)> {
+    diagnostics
+      .into_iter()
+      .filter_map(|d| {
+        let severity = self.severity(file_path, &d.code);
+        (severity != LintSeverity::Off).then_some((d, severity))
+      })
+      .collect()
+  }
+}
+
+/// Lints the source with the reasons of its ignore directives removed, adding
+/// the diagnostics of the directives without a reason.
+fn lint_source(
+  file_name: String,
+  media_type: MediaType,
+  source_code: &str,
+  lint_rules: &[&'static dyn LintRule],
+) -> Result<Vec<LintDiagnostic>, AnyError> {
+  let reasons = IgnoreReasons::parse(source_code);
+  let missing_reason_diagnostics =
+    reasons.missing_reason_diagnostics(&file_name);
+  let linter = create_linter(media_type, lint_rules.to_vec());
+  let (_, mut file_diagnostics) = linter.lint(file_name, reasons.text)?;
+  file_diagnostics.extend(missing_reason_diagnostics);
+  Ok(file_diagnostics)
+}
+
+/// Lints a parsed document of the language server, whose diagnostics that are
+/// turned off are left out.
+pub fn lint_parsed_source(
+  file_path: &Path,
+  parsed_source: &ParsedSource,
+  rule_set: &LintRuleSet,
+) -> Result<Vec<LintDiagnostic>, AnyError> {
+  let source_code = parsed_source.text_info().text_str();
+  let reasons = IgnoreReasons::parse(source_code);
+  let file_diagnostics = if reasons.has_reasons {
+    // the reasons need to be removed from the text, so it's parsed again
+    lint_source(
+      parsed_source.specifier().to_string(),
+      parsed_source.media_type(),
+      source_code,
+      &rule_set.rules,
+    )?
+  } else {
+    let linter =
+      create_linter(parsed_source.media_type(), rule_set.rules.clone());
+    let mut file_diagnostics = linter.lint_with_ast(parsed_source);
+    file_diagnostics
+      .extend(reasons.missing_reason_diagnostics(parsed_source.specifier()));
+    file_diagnostics
+  };
+  Ok(
+    rule_set
+      .apply_severity(file_path, file_diagnostics)
+      .into_iter()
+      .map(|(d, _)| d)
+      .collect(),
+  )
+}
+
 fn lint_file(
   file_path: &Path,
   source_code: String,
-  lint_rules: Vec<&'static dyn LintRule>,
+  lint_rules: &[&'static dyn LintRule],
 ) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
   let file_name = file_path.to_string_lossy().to_string();
   let media_type = MediaType::from_path(file_path);
 
-  let linter = create_linter(media_type, lint_rules);
-
-  let (_, file_diagnostics) = linter.lint(file_name, source_code.clone())?;
+  let file_diagnostics =
+    lint_source(file_name, media_type, &source_code, lint_rules)?;
 
   Ok((file_diagnostics, source_code))
 }
@@ -266,35 +538,40 @@ fn lint_file(
 /// Treats input as TypeScript.
 /// Compatible with `--json` flag.
 fn lint_stdin(
-  lint_rules: Vec<&'static dyn LintRule>,
+  lint_rules: &[&'static dyn LintRule],
 ) -> Result<(Vec<LintDiagnostic>, String), AnyError> {
   let mut source_code = String::new();
   if stdin().read_to_string(&mut source_code).is_err() {
     return Err(generic_error("Failed to read from stdin"));
   }
 
-  let linter = create_linter(MediaType::TypeScript, lint_rules);
-
-  let (_, file_diagnostics) =
-    linter.lint(STDIN_FILE_NAME.to_string(), source_code.clone())?;
+  let file_diagnostics = lint_source(
+    STDIN_FILE_NAME.to_string(),
+    MediaType::TypeScript,
+    &source_code,
+    lint_rules,
+  )?;
 
   Ok((file_diagnostics, source_code))
 }
 
 fn handle_lint_result(
   file_path: &str,
-  result: Result<(Vec<LintDiagnostic>, String), AnyError>,
+  result: Result<(Vec<(LintDiagnostic, LintSeverity)>, String), AnyError>,
   reporter_lock: Arc<Mutex<Box<dyn LintReporter + Send>>>,
   has_error: Arc<AtomicBool>,
+  fail_on: LintFailOn,
 ) {
   let mut reporter = reporter_lock.lock().unwrap();
 
   match result {
     Ok((mut file_diagnostics, source)) => {
-      sort_diagnostics(&mut file_diagnostics);
-      for d in file_diagnostics.iter() {
-        has_error.store(true, Ordering::Relaxed);
-        reporter.visit_diagnostic(d, source.split('\n').collect());
+      file_diagnostics.sort_by(|(a, _), (b, _)| compare_diagnostics(a, b));
+      for (d, severity) in file_diagnostics.iter() {
+        if fail_on.fails(*severity) {
+          has_error.store(true, Ordering::Relaxed);
+        }
+        reporter.visit_diagnostic(d, *severity, source.split('\n').collect());
       }
     }
     Err(err) => {
@@ -305,7 +582,12 @@ fn handle_lint_result(
 }
 
 trait LintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, source_lines: Vec<&str>);
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintSeverity,
+    source_lines: Vec<&str>,
+  );
   fn visit_error(&mut self, file_path: &str, err: &AnyError);
   fn close(&mut self, check_count: usize);
 }
@@ -318,19 +600,34 @@ struct LintError {
 
 struct PrettyLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl PrettyLintReporter {
   fn new() -> PrettyLintReporter {
-    PrettyLintReporter { lint_count: 0 }
+    PrettyLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for PrettyLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, source_lines: Vec<&str>) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintSeverity,
+    source_lines: Vec<&str>,
+  ) {
     self.lint_count += 1;
 
-    let pretty_message = format!("({}) {}", colors::red(&d.code), &d.message);
+    let code = if severity == LintSeverity::Warn {
+      self.warning_count += 1;
+      colors::yellow(&d.code)
+    } else {
+      colors::red(&d.code)
+    };
+    let pretty_message = format!("({}) {}", code, &d.message);
 
     let message = format_diagnostic(
       &d.code,
@@ -357,39 +654,45 @@ impl LintReporter for PrettyLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
-    }
-
-    match check_count {
-      n if n <= 1 => info!("Checked {} file", n),
-      n if n > 1 => info!("Checked {} files", n),
-      _ => unreachable!(),
-    }
+    log_summary(self.lint_count, self.warning_count, check_count);
   }
 }
 
 struct CompactLintReporter {
   lint_count: u32,
+  warning_count: u32,
 }
 
 impl CompactLintReporter {
   fn new() -> CompactLintReporter {
-    CompactLintReporter { lint_count: 0 }
+    CompactLintReporter {
+      lint_count: 0,
+      warning_count: 0,
+    }
   }
 }
 
 impl LintReporter for CompactLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintSeverity,
+    _source_lines: Vec<&str>,
+  ) {
     self.lint_count += 1;
 
+    let prefix = if severity == LintSeverity::Warn {
+      self.warning_count += 1;
+      "warning: "
+    } else {
+      ""
+    };
     eprintln!(
-      "{}: line {}, col {} - {} ({})",
+      "{}: line {}, col {} - {}{} ({})",
       d.filename,
       d.range.start.line_index + 1,
       d.range.start.column_index + 1,
+      prefix,
       d.message,
       d.code
     )
@@ -401,17 +704,26 @@ impl LintReporter for CompactLintReporter {
   }
 
   fn close(&mut self, check_count: usize) {
-    match self.lint_count {
-      1 => info!("Found 1 problem"),
-      n if n > 1 => info!("Found {} problems", self.lint_count),
-      _ => (),
-    }
+    log_summary(self.lint_count, self.warning_count, check_count);
+  }
+}
 
-    match check_count {
-      n if n <= 1 => info!("Checked {} file", n),
-      n if n > 1 => info!("Checked {} files", n),
-      _ => unreachable!(),
-    }
+fn log_summary(lint_count: u32, warning_count: u32, check_count: usize) {
+  let warnings = match warning_count {
+    0 => String::new(),
+    1 => " (1 warning)".to_string(),
+    n => format!(" ({n} warnings)"),
+  };
+  match lint_count {
+    1 => info!("Found 1 problem{}", warnings),
+    n if n > 1 => info!("Found {} problems{}", n, warnings),
+    _ => (),
+  }
+
+  match check_count {
+    n if n <= 1 => info!("Checked {} file", n),
+    n if n > 1 => info!("Checked {} files", n),
+    _ => unreachable!(),
   }
 }
 
@@ -478,9 +790,16 @@ pub fn format_diagnostic(
   )
 }
 
+#[derive(Serialize)]
+struct JsonLintDiagnostic {
+  #[serde(flatten)]
+  diagnostic: LintDiagnostic,
+  severity: LintSeverity,
+}
+
 #[derive(Serialize)]
 struct JsonLintReporter {
-  diagnostics: Vec<LintDiagnostic>,
+  diagnostics: Vec<JsonLintDiagnostic>,
   errors: Vec<LintError>,
 }
 
@@ -494,8 +813,16 @@ impl JsonLintReporter {
 }
 
 impl LintReporter for JsonLintReporter {
-  fn visit_diagnostic(&mut self, d: &LintDiagnostic, _source_lines: Vec<&str>) {
-    self.diagnostics.push(d.clone());
+  fn visit_diagnostic(
+    &mut self,
+    d: &LintDiagnostic,
+    severity: LintSeverity,
+    _source_lines: Vec<&str>,
+  ) {
+    self.diagnostics.push(JsonLintDiagnostic {
+      diagnostic: d.clone(),
+      severity,
+    });
   }
 
   fn visit_error(&mut self, file_path: &str, err: &AnyError) {
@@ -506,31 +833,33 @@ impl LintReporter for JsonLintReporter {
   }
 
   fn close(&mut self, _check_count: usize) {
-    sort_diagnostics(&mut self.diagnostics);
+    self
+      .diagnostics
+      .sort_by(|a, b| compare_diagnostics(&a.diagnostic, &b.diagnostic));
     let json = serde_json::to_string_pretty(&self);
     println!("{}", json.unwrap());
   }
 }
 
-fn sort_diagnostics(diagnostics: &mut [LintDiagnostic]) {
-  // Sort so that we guarantee a deterministic output which is useful for tests
-  diagnostics.sort_by(|a, b| {
-    use std::cmp::Ordering;
-    let file_order = a.filename.cmp(&b.filename);
-    match file_order {
-      Ordering::Equal => {
-        let line_order =
-          a.range.start.line_index.cmp(&b.range.start.line_index);
-        match line_order {
-          Ordering::Equal => {
-            a.range.start.column_index.cmp(&b.range.start.column_index)
-          }
-          _ => line_order,
+// Sort so that we guarantee a deterministic output which is useful for tests
+fn compare_diagnostics(
+  a: &LintDiagnostic,
+  b: &LintDiagnostic,
+) -> std::cmp::Ordering {
+  use std::cmp::Ordering;
+  let file_order = a.filename.cmp(&b.filename);
+  match file_order {
+    Ordering::Equal => {
+      let line_order = a.range.start.line_index.cmp(&b.range.start.line_index);
+      match line_order {
+        Ordering::Equal => {
+          a.range.start.column_index.cmp(&b.range.start.column_index)
         }
+        _ => line_order,
       }
-      _ => file_order,
     }
-  });
+    _ => file_order,
+  }
 }
 
 pub fn get_configured_rules(
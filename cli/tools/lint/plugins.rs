@@ -0,0 +1,310 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Support for custom lint rules provided by JavaScript or TypeScript
+//! modules listed in the `lint.plugins` field of the configuration file.
+//!
+//! A plugin module default exports an object in the shape of:
+//!
+//! ```ts
+//! export default {
+//!   name: "my-plugin",
+//!   rules: {
+//!     "no-foo": {
+//!       create(context) {
+//!         return {
+//!           Ident(node) {
+//!             if (node.value === "foo") {
+//!               context.report({ span: node.span, message: "Don't use foo" });
+//!             }
+//!           },
+//!         };
+//!       },
+//!     },
+//!   },
+//! };
+//! ```
+//!
+//! The plugins are executed in a worker without any permissions and are
+//! handed the SWC AST of each file serialized as JSON.
+
+use crate::proc_state::ProcState;
+use crate::worker::create_custom_worker;
+
+use deno_ast::swc::common::BytePos;
+use deno_ast::MediaType;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::located_script_name;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::ModuleSpecifier;
+use deno_lint::diagnostic::LintDiagnostic;
+use deno_lint::diagnostic::Position;
+use deno_lint::diagnostic::Range;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::worker::MainWorker;
+use serde::Deserialize;
+use std::path::Path;
+
+static PLUGIN_HOST_SCRIPT: &str = r#"(() => {
+  const plugins = [];
+
+  function registerPlugin(specifier, namespace) {
+    const plugin = namespace.default;
+    if (
+      typeof plugin !== "object" || plugin === null ||
+      typeof plugin.name !== "string" ||
+      typeof plugin.rules !== "object" || plugin.rules === null
+    ) {
+      throw new TypeError(
+        `Lint plugin "${specifier}" must default export an object with a "name" and "rules" property.`,
+      );
+    }
+    plugins.push(plugin);
+  }
+
+  function visit(node, visitors) {
+    if (Array.isArray(node)) {
+      for (const child of node) {
+        visit(child, visitors);
+      }
+      return;
+    }
+    if (node === null || typeof node !== "object") {
+      return;
+    }
+    if (typeof node.type === "string") {
+      for (const visitor of visitors) {
+        visitor[node.type]?.(node);
+      }
+    }
+    for (const key in node) {
+      if (key !== "span") {
+        visit(node[key], visitors);
+      }
+    }
+  }
+
+  function lintFile(fileName, source, astJson) {
+    const ast = JSON.parse(astJson);
+    const diagnostics = [];
+    const visitors = [];
+    for (const plugin of plugins) {
+      for (const [ruleName, rule] of Object.entries(plugin.rules)) {
+        const code = `${plugin.name}/${ruleName}`;
+        const context = {
+          fileName,
+          source,
+          report({ span, message, hint }) {
+            diagnostics.push({
+              code,
+              message: String(message),
+              hint: hint === undefined ? null : String(hint),
+              start: span.start,
+              end: span.end,
+            });
+          },
+        };
+        visitors.push(rule.create(context) ?? {});
+      }
+    }
+    visit(ast, visitors);
+    return diagnostics;
+  }
+
+  return { registerPlugin, lintFile };
+})()"#;
+
+#[derive(Deserialize)]
+struct PluginDiagnostic {
+  code: String,
+  message: String,
+  hint: Option<String>,
+  start: u32,
+  end: u32,
+}
+
+/// Hosts the configured lint plugins in a sandboxed worker.
+pub struct LintPluginRunner {
+  worker: MainWorker,
+  lint_file_fn: v8::Global<v8::Function>,
+}
+
+impl LintPluginRunner {
+  pub async fn create(
+    ps: &ProcState,
+    plugins: &[ModuleSpecifier],
+  ) -> Result<Self, AnyError> {
+    // the plugins only need to inspect the AST, so they get no permissions
+    let permissions = PermissionsContainer::new(Permissions::default());
+    let worker = create_custom_worker(
+      ps,
+      plugins[0].clone(),
+      permissions,
+      vec![],
+      Default::default(),
+    )
+    .await?;
+    let mut worker = worker.into_main_worker();
+
+    let host = worker
+      .js_runtime
+      .execute_script_static(located_script_name!(), PLUGIN_HOST_SCRIPT)?;
+    let (register_plugin_fn, lint_file_fn) = {
+      let scope = &mut worker.js_runtime.handle_scope();
+      let host = v8::Local::new(scope, host);
+      let host = v8::Local::<v8::Object>::try_from(host)?;
+      let get_fn = |scope: &mut v8::HandleScope, name: &str| {
+        let key = v8::String::new(scope, name).unwrap();
+        let value = host.get(scope, key.into()).unwrap();
+        let func = v8::Local::<v8::Function>::try_from(value).unwrap();
+        v8::Global::new(scope, func)
+      };
+      (get_fn(scope, "registerPlugin"), get_fn(scope, "lintFile"))
+    };
+
+    for specifier in plugins {
+      let id = worker.preload_side_module(specifier).await?;
+      worker.evaluate_module(id).await?;
+      let namespace = worker.js_runtime.get_module_namespace(id)?;
+      let scope = &mut worker.js_runtime.handle_scope();
+      let specifier = v8::String::new(scope, specifier.as_str()).unwrap();
+      let namespace = v8::Local::new(scope, namespace);
+      call_fn(
+        scope,
+        &register_plugin_fn,
+        &[specifier.into(), namespace.into()],
+      )?;
+    }
+
+    Ok(Self {
+      worker,
+      lint_file_fn,
+    })
+  }
+
+  /// Runs the plugin rules against the provided file.
+  pub fn lint_file(
+    &mut self,
+    file_path: &Path,
+    source_code: String,
+  ) -> Result<Vec<LintDiagnostic>, AnyError> {
+    let file_name = file_path.to_string_lossy().to_string();
+    let specifier = ModuleSpecifier::from_file_path(file_path).unwrap();
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: specifier.to_string(),
+      text_info: deno_ast::SourceTextInfo::from_string(source_code.clone()),
+      media_type: MediaType::from_path(file_path),
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })?;
+    let ast_json = serde_json::to_string(parsed_source.program().as_ref())?;
+
+    let scope = &mut self.worker.js_runtime.handle_scope();
+    let args = [
+      v8::String::new(scope, &file_name).unwrap().into(),
+      v8::String::new(scope, &source_code).unwrap().into(),
+      v8::String::new(scope, &ast_json).unwrap().into(),
+    ];
+    let result = call_fn(scope, &self.lint_file_fn, &args)?;
+    let diagnostics =
+      serde_v8::from_v8::<Vec<PluginDiagnostic>>(scope, result)?;
+
+    diagnostics
+      .into_iter()
+      .map(|d| {
+        Ok(LintDiagnostic {
+          range: Range {
+            start: to_position(&parsed_source, d.start)?,
+            end: to_position(&parsed_source, d.end)?,
+          },
+          filename: file_name.clone(),
+          message: d.message,
+          code: d.code,
+          hint: d.hint,
+        })
+      })
+      .collect()
+  }
+}
+
+fn call_fn<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  func: &v8::Global<v8::Function>,
+  args: &[v8::Local<v8::Value>],
+) -> Result<v8::Local<'s, v8::Value>, AnyError> {
+  let tc_scope = &mut v8::TryCatch::new(scope);
+  let func = v8::Local::new(tc_scope, func);
+  let recv = v8::undefined(tc_scope).into();
+  match func.call(tc_scope, recv, args) {
+    Some(value) => Ok(value),
+    None => {
+      let exception = tc_scope.exception().unwrap();
+      Err(JsError::from_v8_exception(tc_scope, exception).into())
+    }
+  }
+}
+
+/// Converts a position reported by a plugin to a position in the file,
+/// erroring when it's not a valid position instead of panicking.
+fn to_position(
+  parsed_source: &ParsedSource,
+  byte_pos: u32,
+) -> Result<Position, AnyError> {
+  let text_info = parsed_source.text_info();
+  let range = text_info.range();
+  let pos = SourcePos::unsafely_from_byte_pos(BytePos(byte_pos));
+  if pos < range.start
+    || pos > range.end
+    || !text_info.text_str().is_char_boundary(pos - range.start)
+  {
+    bail!(
+      "Lint plugin reported an invalid position {} in {}",
+      byte_pos,
+      parsed_source.specifier()
+    );
+  }
+  let line_and_column = text_info.line_and_column_index(pos);
+  Ok(Position {
+    line_index: line_and_column.line_index,
+    column_index: line_and_column.column_index,
+    byte_index: pos - range.start,
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_to_position() {
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: "file:///mod.ts".to_string(),
+      text_info: deno_ast::SourceTextInfo::from_string(
+        "const a = \"é\";\nconst b = 1;".to_string(),
+      ),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let start = parsed_source.text_info().range().start.as_byte_pos().0;
+
+    let position = to_position(&parsed_source, start + 17).unwrap();
+    assert_eq!(position.line_index, 1);
+    assert_eq!(position.column_index, 1);
+    assert_eq!(position.byte_index, 17);
+
+    // in the middle of a multi-byte character
+    assert!(to_position(&parsed_source, start + 12).is_err());
+    // out of range
+    assert!(to_position(&parsed_source, start + 100).is_err());
+  }
+}
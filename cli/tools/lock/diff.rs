@@ -0,0 +1,413 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Compares the dependencies of lockfiles, which is shared by `deno lock diff`
+//! and `deno info --diff`.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_runtime::colors;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+/// Matches the version of a remote specifier's path (ex. `std@0.190.0`),
+/// skipping the `@` of scoped package names (ex. `/@preact/signals@1.0.0`).
+static REMOTE_VERSION_RE: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"([^/])@([^/]+)").unwrap());
+
+/// Dependency name to its locked versions and their integrity.
+pub type LockedVersions = BTreeMap<String, BTreeMap<String, String>>;
+
+/// The remote modules and npm packages of a lockfile, grouped by name so
+/// that different versions of a dependency are compared with each other.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LockedDeps {
+  pub remote: LockedVersions,
+  pub npm: LockedVersions,
+  /// The specifiers of the remote modules, by name and version.
+  pub remote_specifiers: BTreeMap<(String, String), String>,
+}
+
+impl LockedDeps {
+  pub fn parse(value: serde_json::Value) -> Result<Self, AnyError> {
+    let mut value = match value {
+      serde_json::Value::Object(value) => value,
+      _ => bail!("Expected an object."),
+    };
+    let mut deps = Self::default();
+    let version = value.get("version").cloned();
+    let remote = match version {
+      // version 1 lockfiles only contain the remote modules
+      None => value,
+      Some(serde_json::Value::String(version)) if version == "2" => {
+        if let Some(serde_json::Value::Object(npm)) = value.remove("npm") {
+          if let Some(serde_json::Value::Object(packages)) = npm.get("packages")
+          {
+            for (id, package) in packages {
+              let (name, version) = split_npm_package_id(id);
+              let integrity = package
+                .get("integrity")
+                .and_then(|integrity| integrity.as_str())
+                .unwrap_or_default();
+              deps.insert_npm(name, version, integrity);
+            }
+          }
+        }
+        match value.remove("remote") {
+          Some(serde_json::Value::Object(remote)) => remote,
+          _ => Default::default(),
+        }
+      }
+      Some(version) => bail!("Unsupported lockfile version: {}", version),
+    };
+    for (specifier, checksum) in remote {
+      let checksum = match checksum {
+        serde_json::Value::String(checksum) => checksum,
+        _ => bail!("Expected a checksum for remote module: {}", specifier),
+      };
+      deps.insert_remote(&specifier, checksum);
+    }
+    Ok(deps)
+  }
+
+  /// Adds a remote module, under its specifier without the version.
+  pub fn insert_remote(&mut self, specifier: &str, checksum: String) {
+    let (name, version) = split_remote_specifier(specifier);
+    let version = version.unwrap_or_default();
+    self
+      .remote_specifiers
+      .insert((name.clone(), version.clone()), specifier.to_string());
+    self
+      .remote
+      .entry(name)
+      .or_default()
+      .insert(version, checksum);
+  }
+
+  pub fn insert_npm(&mut self, name: &str, version: &str, integrity: &str) {
+    self
+      .npm
+      .entry(name.to_string())
+      .or_default()
+      .insert(version.to_string(), integrity.to_string());
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+  Added,
+  Removed,
+  Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyChange {
+  pub kind: ChangeKind,
+  pub name: String,
+  pub old_version: Option<String>,
+  pub new_version: Option<String>,
+  pub old_integrity: Option<String>,
+  pub new_integrity: Option<String>,
+}
+
+impl DependencyChange {
+  /// Formats the change like `~ name 1.0.0 -> 2.0.0`, with the name after
+  /// the prefix (ex. `npm:`).
+  pub fn format(&self, name_prefix: &str) -> String {
+    match self.kind {
+      ChangeKind::Added => colors::green(format!(
+        "+ {}{}{}",
+        name_prefix,
+        self.name,
+        format_version(&self.new_version)
+      ))
+      .to_string(),
+      ChangeKind::Removed => colors::red(format!(
+        "- {}{}{}",
+        name_prefix,
+        self.name,
+        format_version(&self.old_version)
+      ))
+      .to_string(),
+      ChangeKind::Changed if self.old_version != self.new_version => {
+        colors::yellow(format!(
+          "~ {}{}{} ->{}",
+          name_prefix,
+          self.name,
+          format_version(&self.old_version),
+          format_version(&self.new_version)
+        ))
+        .to_string()
+      }
+      ChangeKind::Changed => colors::yellow(format!(
+        "~ {}{}{} (integrity changed)",
+        name_prefix,
+        self.name,
+        format_version(&self.new_version)
+      ))
+      .to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LockDiff {
+  pub remote: Vec<DependencyChange>,
+  pub npm: Vec<DependencyChange>,
+}
+
+impl LockDiff {
+  pub fn new(old: &LockedDeps, new: &LockedDeps) -> Self {
+    Self {
+      remote: diff_versions(&old.remote, &new.remote),
+      npm: diff_versions(&old.npm, &new.npm),
+    }
+  }
+}
+
+/// Splits an npm package id (ex. `@scope/name@1.0.0`) into its name and
+/// version, which includes the peer dependencies of the package.
+pub fn split_npm_package_id(id: &str) -> (&str, &str) {
+  // skip the `@` of scoped package names
+  match id.char_indices().skip(1).find(|(_, c)| *c == '@') {
+    Some((index, _)) => (&id[..index], &id[index + 1..]),
+    None => (id, ""),
+  }
+}
+
+/// Splits the version out of the path of a remote specifier, so that
+/// different versions of the same module are compared with each other.
+fn split_remote_specifier(specifier: &str) -> (String, Option<String>) {
+  let path_start = specifier
+    .find("://")
+    .and_then(|scheme_end| {
+      specifier[scheme_end + 3..]
+        .find('/')
+        .map(|index| scheme_end + 3 + index)
+    })
+    .unwrap_or(specifier.len());
+  let (origin, path) = specifier.split_at(path_start);
+  match REMOTE_VERSION_RE.captures(path) {
+    Some(captures) => {
+      let version = captures.get(2).unwrap();
+      // remove the `@` along with the version
+      let name = format!(
+        "{}{}{}",
+        origin,
+        &path[..version.start() - 1],
+        &path[version.end()..]
+      );
+      (name, Some(version.as_str().to_string()))
+    }
+    None => (specifier.to_string(), None),
+  }
+}
+
+fn diff_versions(
+  old: &LockedVersions,
+  new: &LockedVersions,
+) -> Vec<DependencyChange> {
+  let empty = BTreeMap::new();
+  let names = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+  let mut changes = Vec::new();
+  for name in names {
+    let old_versions = old.get(name).unwrap_or(&empty);
+    let new_versions = new.get(name).unwrap_or(&empty);
+    let removed = old_versions
+      .iter()
+      .filter(|(version, _)| !new_versions.contains_key(*version))
+      .collect::<Vec<_>>();
+    let added = new_versions
+      .iter()
+      .filter(|(version, _)| !old_versions.contains_key(*version))
+      .collect::<Vec<_>>();
+    let change = |kind,
+                  old: Option<(&String, &String)>,
+                  new: Option<(&String, &String)>| {
+      let version = |entry: Option<(&String, &String)>| {
+        entry
+          .map(|(version, _)| version.clone())
+          .filter(|version| !version.is_empty())
+      };
+      let integrity =
+        |entry: Option<(&String, &String)>| entry.map(|(_, i)| i.clone());
+      DependencyChange {
+        kind,
+        name: name.clone(),
+        old_version: version(old),
+        new_version: version(new),
+        old_integrity: integrity(old),
+        new_integrity: integrity(new),
+      }
+    };
+
+    for (version, old_integrity) in old_versions {
+      if let Some(new_integrity) = new_versions.get(version) {
+        if old_integrity != new_integrity {
+          changes.push(change(
+            ChangeKind::Changed,
+            Some((version, old_integrity)),
+            Some((version, new_integrity)),
+          ));
+        }
+      }
+    }
+    if removed.len() == 1 && added.len() == 1 {
+      changes.push(change(
+        ChangeKind::Changed,
+        Some(removed[0]),
+        Some(added[0]),
+      ));
+    } else {
+      for entry in removed {
+        changes.push(change(ChangeKind::Removed, Some(entry), None));
+      }
+      for entry in added {
+        changes.push(change(ChangeKind::Added, None, Some(entry)));
+      }
+    }
+  }
+  changes
+}
+
+fn format_version(version: &Option<String>) -> String {
+  match version {
+    Some(version) => format!(" {version}"),
+    None => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use deno_core::serde_json::json;
+
+  #[test]
+  fn splits_remote_specifiers() {
+    assert_eq!(
+      split_remote_specifier("https://deno.land/std@0.190.0/path/mod.ts"),
+      (
+        "https://deno.land/std/path/mod.ts".to_string(),
+        Some("0.190.0".to_string())
+      )
+    );
+    assert_eq!(
+      split_remote_specifier("https://esm.sh/@preact/signals@1.0.0/x.js"),
+      (
+        "https://esm.sh/@preact/signals/x.js".to_string(),
+        Some("1.0.0".to_string())
+      )
+    );
+    assert_eq!(
+      split_remote_specifier("https://user@example.com/mod.ts"),
+      ("https://user@example.com/mod.ts".to_string(), None)
+    );
+  }
+
+  #[test]
+  fn splits_npm_package_ids() {
+    assert_eq!(split_npm_package_id("chalk@5.0.1"), ("chalk", "5.0.1"));
+    assert_eq!(
+      split_npm_package_id("@types/node@18.0.0"),
+      ("@types/node", "18.0.0")
+    );
+    assert_eq!(
+      split_npm_package_id("preact-render-to-string@5.2.0_preact@10.5.0"),
+      ("preact-render-to-string", "5.2.0_preact@10.5.0")
+    );
+  }
+
+  #[test]
+  fn diffs_lockfiles() {
+    let old = LockedDeps::parse(json!({
+      "version": "2",
+      "remote": {
+        "https://deno.land/std@0.189.0/path/mod.ts": "a",
+        "https://example.com/mod.ts": "b",
+        "https://example.com/removed.ts": "c",
+      },
+      "npm": {
+        "specifiers": {},
+        "packages": {
+          "chalk@5.0.0": { "integrity": "d", "dependencies": {} },
+          "ansi@1.0.0": { "integrity": "e", "dependencies": {} },
+        }
+      }
+    }))
+    .unwrap();
+    let new = LockedDeps::parse(json!({
+      "version": "2",
+      "remote": {
+        "https://deno.land/std@0.190.0/path/mod.ts": "f",
+        "https://example.com/mod.ts": "g",
+      },
+      "npm": {
+        "specifiers": {},
+        "packages": {
+          "chalk@5.0.0": { "integrity": "d", "dependencies": {} },
+          "ansi@1.0.0": { "integrity": "e", "dependencies": {} },
+          "ansi@2.0.0": { "integrity": "h", "dependencies": {} },
+        }
+      }
+    }))
+    .unwrap();
+
+    let change = |kind, name: &str, versions: [Option<&str>; 4]| {
+      let [old_version, new_version, old_integrity, new_integrity] =
+        versions.map(|v| v.map(|v| v.to_string()));
+      DependencyChange {
+        kind,
+        name: name.to_string(),
+        old_version,
+        new_version,
+        old_integrity,
+        new_integrity,
+      }
+    };
+    assert_eq!(
+      diff_versions(&old.remote, &new.remote),
+      vec![
+        change(
+          ChangeKind::Changed,
+          "https://deno.land/std/path/mod.ts",
+          [Some("0.189.0"), Some("0.190.0"), Some("a"), Some("f")]
+        ),
+        change(
+          ChangeKind::Changed,
+          "https://example.com/mod.ts",
+          [None, None, Some("b"), Some("g")]
+        ),
+        change(
+          ChangeKind::Removed,
+          "https://example.com/removed.ts",
+          [None, None, Some("c"), None]
+        ),
+      ]
+    );
+    assert_eq!(
+      diff_versions(&old.npm, &new.npm),
+      vec![change(
+        ChangeKind::Added,
+        "ansi",
+        [None, Some("2.0.0"), None, Some("h")]
+      )]
+    );
+  }
+
+  #[test]
+  fn parses_version_1_lockfiles() {
+    let deps = LockedDeps::parse(json!({
+      "https://deno.land/std@0.190.0/path/mod.ts": "a",
+    }))
+    .unwrap();
+    assert_eq!(deps.remote.len(), 1);
+    assert!(deps.npm.is_empty());
+    assert!(LockedDeps::parse(json!({ "version": "3" })).is_err());
+  }
+}
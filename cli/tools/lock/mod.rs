@@ -0,0 +1,149 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_runtime::colors;
+
+use crate::args::LockDiffFlags;
+use crate::args::LockFlags;
+use crate::args::LockSubcommand;
+use crate::util::display;
+
+pub mod diff;
+
+use diff::ChangeKind;
+use diff::LockDiff;
+use diff::LockedDeps;
+
+pub fn lock_command(lock_flags: LockFlags) -> Result<(), AnyError> {
+  match lock_flags.subcommand {
+    LockSubcommand::Diff(diff_flags) => diff(diff_flags),
+  }
+}
+
+fn diff(diff_flags: LockDiffFlags) -> Result<(), AnyError> {
+  let old = read_locked_deps(&diff_flags.old)?;
+  let new = read_locked_deps(&diff_flags.new)?;
+  let diff = LockDiff::new(&old, &new);
+  if diff_flags.json {
+    display::write_json_to_stdout(&diff)
+  } else {
+    display::write_to_stdout_ignore_sigpipe(format_diff(&diff).as_bytes())
+      .map_err(AnyError::from)
+  }
+}
+
+/// Reads the dependencies of a lockfile from the file system or, when
+/// provided in the form `<commit>:<path>` (ex. `main:deno.lock`), from a
+/// commit with git.
+pub fn read_locked_deps(path: &Path) -> Result<LockedDeps, AnyError> {
+  let text = read_lockfile_text(path)
+    .with_context(|| format!("Unable to read lockfile: {}", path.display()))?;
+  let value: serde_json::Value = serde_json::from_str(&text)
+    .with_context(|| format!("Unable to parse lockfile: {}", path.display()))?;
+  LockedDeps::parse(value)
+    .with_context(|| format!("Invalid lockfile: {}", path.display()))
+}
+
+/// Reads a lockfile from the file system or, when provided in the form
+/// `<commit>:<path>` (ex. `main:deno.lock`), from a commit with git.
+fn read_lockfile_text(path: &Path) -> Result<String, AnyError> {
+  if path.exists() {
+    return Ok(std::fs::read_to_string(path)?);
+  }
+  let (commit, file_path) = match parse_commit_specifier(path) {
+    Some(commit_specifier) => commit_specifier,
+    None => return Ok(std::fs::read_to_string(path)?),
+  };
+  let output = std::process::Command::new("git")
+    .arg("show")
+    .arg(format!("{commit}:./{file_path}"))
+    .output()
+    .context("Failed running git to read the lockfile of the commit")?;
+  if !output.status.success() {
+    bail!(
+      "Failed reading the lockfile from git: {}",
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+  }
+  Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Splits a `<commit>:<path>` specifier into its commit and path, ignoring
+/// Windows paths with a drive letter (ex. `C:\deno.lock`).
+fn parse_commit_specifier(path: &Path) -> Option<(&str, &str)> {
+  let (commit, file_path) = path.to_str()?.split_once(':')?;
+  if commit.is_empty()
+    || file_path.is_empty()
+    || (cfg!(windows) && commit.len() == 1)
+  {
+    return None;
+  }
+  Some((commit, file_path))
+}
+
+fn format_diff(diff: &LockDiff) -> String {
+  let mut output = String::new();
+  for (title, changes) in [
+    ("Remote modules", &diff.remote),
+    ("npm packages", &diff.npm),
+  ] {
+    if changes.is_empty() {
+      continue;
+    }
+    writeln!(output, "{}", colors::bold(title)).unwrap();
+    for change in changes {
+      writeln!(output, "  {}", change.format("")).unwrap();
+    }
+    writeln!(output).unwrap();
+  }
+
+  let count = |kind| {
+    diff
+      .remote
+      .iter()
+      .chain(diff.npm.iter())
+      .filter(|change| change.kind == kind)
+      .count()
+  };
+  let (added, removed, changed) = (
+    count(ChangeKind::Added),
+    count(ChangeKind::Removed),
+    count(ChangeKind::Changed),
+  );
+  if added + removed + changed == 0 {
+    writeln!(output, "No dependency changes.").unwrap();
+  } else {
+    writeln!(
+      output,
+      "{added} added, {removed} removed, {changed} changed"
+    )
+    .unwrap();
+  }
+  output
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_commit_specifier() {
+    assert_eq!(
+      parse_commit_specifier(Path::new("main:deno.lock")),
+      Some(("main", "deno.lock"))
+    );
+    assert_eq!(
+      parse_commit_specifier(Path::new("HEAD~1:sub/deno.lock")),
+      Some(("HEAD~1", "sub/deno.lock"))
+    );
+    assert_eq!(parse_commit_specifier(Path::new("deno.lock")), None);
+    assert_eq!(parse_commit_specifier(Path::new(":deno.lock")), None);
+    assert_eq!(parse_commit_specifier(Path::new("main:")), None);
+  }
+}
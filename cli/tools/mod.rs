@@ -3,16 +3,28 @@
 pub mod bench;
 pub mod bundle;
 pub mod check;
+pub mod clean;
 pub mod coverage;
+pub mod cron;
+pub mod deps;
 pub mod doc;
 pub mod fmt;
 pub mod info;
 pub mod init;
 pub mod installer;
+pub mod license;
 pub mod lint;
+pub mod lock;
+pub mod organize_imports;
+pub mod pack;
+pub mod profiler;
+pub mod publish;
 pub mod repl;
 pub mod run;
+pub mod sbom;
+pub mod serve;
 pub mod standalone;
+pub mod suggest_permissions;
 pub mod task;
 pub mod test;
 pub mod upgrade;
@@ -0,0 +1,438 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Organizes the imports at the top of a module for
+//! `deno fmt --organize-imports` and the "Organize imports" code action of the
+//! language server.
+//!
+//! The unused imports are removed and the rest are grouped into standard
+//! library, remote, npm and local imports, which are sorted by specifier.
+//! Side effect imports keep their position, because the order they run in
+//! matters, so only the imports between them are sorted.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use deno_ast::swc::ast::ImportDecl;
+use deno_ast::swc::ast::ImportSpecifier;
+use deno_ast::swc::ast::JSXElement;
+use deno_ast::swc::ast::JSXFragment;
+use deno_ast::swc::ast::ModuleDecl;
+use deno_ast::swc::ast::ModuleExportName;
+use deno_ast::swc::ast::ModuleItem;
+use deno_ast::swc::ast::Program;
+use deno_ast::swc::common::Span;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::ParsedSource;
+use deno_ast::SourcePos;
+use deno_core::error::AnyError;
+
+use crate::cache::ParsedSourceCache;
+
+/// Organizes the imports of a JavaScript or TypeScript file, which is parsed
+/// with the cache. Returns `None` when they're already organized.
+pub fn organize_imports_in_file(
+  file_path: &Path,
+  file_text: &str,
+  parsed_source_cache: &ParsedSourceCache,
+) -> Result<Option<String>, AnyError> {
+  let media_type = MediaType::from_path(file_path);
+  if !matches!(
+    media_type,
+    MediaType::JavaScript
+      | MediaType::Jsx
+      | MediaType::Mjs
+      | MediaType::Cjs
+      | MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Dts
+      | MediaType::Dmts
+      | MediaType::Dcts
+      | MediaType::Tsx
+  ) {
+    return Ok(None);
+  }
+  let specifier = ModuleSpecifier::from_file_path(file_path)
+    .unwrap_or_else(|_| ModuleSpecifier::parse("file:///mod.ts").unwrap());
+  let result = parsed_source_cache.get_or_parse_module(
+    &specifier,
+    file_text.into(),
+    media_type,
+  );
+  // the parsed source is only needed once
+  parsed_source_cache.free(&specifier);
+  Ok(organize_imports(&result?))
+}
+
+/// Organizes the imports of a module, returning `None` when they're already
+/// organized.
+pub fn organize_imports(parsed_source: &ParsedSource) -> Option<String> {
+  let module = match parsed_source.program_ref() {
+    Program::Module(module) => module,
+    Program::Script(_) => return None,
+  };
+  let decls = module
+    .body
+    .iter()
+    .map_while(|item| match item {
+      ModuleItem::ModuleDecl(ModuleDecl::Import(decl)) => Some(decl),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  if decls.is_empty() {
+    return None;
+  }
+
+  let mut usage = UsageCollector::default();
+  for item in &module.body[decls.len()..] {
+    item.visit_with(&mut usage);
+  }
+  // the JSX factory, like `React`, is used implicitly, so nothing is removed
+  let remove_unused = !usage.has_jsx;
+
+  let text = parsed_source.text_info().text_str();
+  let ranges = decls
+    .iter()
+    .map(|decl| byte_range(parsed_source, decl.span))
+    .collect::<Vec<_>>();
+  let mut groups: Vec<Vec<(ImportKind, String, String)>> = Vec::new();
+  let mut segment: Vec<(ImportKind, String, String)> = Vec::new();
+  let mut chunk_start = ranges[0].start;
+  for (index, decl) in decls.iter().enumerate() {
+    let range = &ranges[index];
+    // comments on the lines before an import and after it on its line move
+    // along with it
+    let chunk_end = match ranges.get(index + 1) {
+      Some(next) if text[range.end..next.start].contains('\n') => {
+        range.end + text[range.end..].find('\n').unwrap()
+      }
+      Some(_) => range.end,
+      None => range.end + text[range.end..].find('\n').unwrap_or(text.len()),
+    };
+    let leading = text[chunk_start..range.start].trim_start();
+    let trailing = text[range.end..chunk_end].trim_end();
+    chunk_start = chunk_end;
+
+    let specifier = decl.src.value.to_string();
+    if decl.specifiers.is_empty() {
+      flush_segment(&mut groups, &mut segment);
+      groups.push(vec![(
+        ImportKind::from_specifier(&specifier),
+        specifier,
+        format!("{}{}{}", leading, &text[range.clone()], trailing),
+      )]);
+      continue;
+    }
+    let import_text =
+      match organize_import(parsed_source, text, decl, &usage, remove_unused) {
+        Some(import_text) => import_text,
+        // all of its specifiers are unused
+        None => continue,
+      };
+    segment.push((
+      ImportKind::from_specifier(&specifier),
+      specifier,
+      format!("{}{}{}", leading, import_text, trailing),
+    ));
+  }
+  flush_segment(&mut groups, &mut segment);
+
+  let block = ranges[0].start..chunk_start;
+  let organized = groups
+    .iter()
+    .map(|group| {
+      group
+        .iter()
+        .map(|(_, _, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n");
+  let new_text = if organized.is_empty() {
+    // every import was removed, along with the blank lines after them
+    let rest = text[block.end..].trim_start_matches(['\r', '\n']);
+    format!("{}{}", &text[..block.start], rest)
+  } else {
+    format!(
+      "{}{}{}",
+      &text[..block.start],
+      organized,
+      &text[block.end..]
+    )
+  };
+  (new_text != text).then_some(new_text)
+}
+
+/// The groups of imports, in the order they're written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ImportKind {
+  Std,
+  Remote,
+  Npm,
+  Local,
+}
+
+impl ImportKind {
+  fn from_specifier(specifier: &str) -> Self {
+    if specifier.starts_with("https://deno.land/std@")
+      || specifier.starts_with("https://deno.land/std/")
+      || specifier.starts_with("node:")
+    {
+      ImportKind::Std
+    } else if specifier.starts_with("npm:") {
+      ImportKind::Npm
+    } else if specifier.starts_with("./")
+      || specifier.starts_with("../")
+      || specifier.starts_with('/')
+      || specifier.starts_with("file:")
+    {
+      ImportKind::Local
+    } else {
+      // remote modules and bare specifiers of an import map
+      ImportKind::Remote
+    }
+  }
+}
+
+/// Sorts the imports between side effect imports into their groups.
+fn flush_segment(
+  groups: &mut Vec<Vec<(ImportKind, String, String)>>,
+  segment: &mut Vec<(ImportKind, String, String)>,
+) {
+  segment.sort_by(|(a_kind, a, _), (b_kind, b, _)| {
+    a_kind
+      .cmp(b_kind)
+      .then_with(|| a.to_lowercase().cmp(&b.to_lowercase()))
+      .then_with(|| a.cmp(b))
+  });
+  let mut imports = std::mem::take(segment).into_iter().peekable();
+  while let Some(first) = imports.next() {
+    let kind = first.0;
+    let mut group = vec![first];
+    while let Some(next) =
+      imports.next_if(|(next_kind, _, _)| *next_kind == kind)
+    {
+      group.push(next);
+    }
+    groups.push(group);
+  }
+}
+
+/// Creates the text of an import without its unused specifiers and with its
+/// named specifiers sorted, which is `None` when none are left.
+fn organize_import(
+  parsed_source: &ParsedSource,
+  text: &str,
+  decl: &ImportDecl,
+  usage: &UsageCollector,
+  remove_unused: bool,
+) -> Option<String> {
+  let range = byte_range(parsed_source, decl.span);
+  let src_range = byte_range(parsed_source, decl.src.span);
+  let kept = decl
+    .specifiers
+    .iter()
+    .filter(|specifier| {
+      !remove_unused || usage.names.contains(&*local_name(specifier))
+    })
+    .collect::<Vec<_>>();
+  if kept.is_empty() {
+    return None;
+  }
+  let mut named = kept
+    .iter()
+    .filter_map(|specifier| match specifier {
+      ImportSpecifier::Named(named) => Some(named),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  let is_sorted = named.windows(2).all(|pair| {
+    imported_name(pair[0]).to_lowercase()
+      <= imported_name(pair[1]).to_lowercase()
+  });
+  // comments within the import would be lost
+  let has_comments = text[range.start..src_range.start].contains("//")
+    || text[range.start..src_range.start].contains("/*");
+  if (kept.len() == decl.specifiers.len() && is_sorted) || has_comments {
+    return Some(text[range].to_string());
+  }
+
+  named.sort_by_key(|named| imported_name(named).to_lowercase());
+  let mut parts = kept
+    .iter()
+    .filter(|specifier| !matches!(specifier, ImportSpecifier::Named(_)))
+    .map(|specifier| {
+      text[byte_range(parsed_source, specifier_span(specifier))].to_string()
+    })
+    .collect::<Vec<_>>();
+  if !named.is_empty() {
+    parts.push(format!(
+      "{{ {} }}",
+      named
+        .iter()
+        .map(|named| &text[byte_range(parsed_source, named.span)])
+        .collect::<Vec<_>>()
+        .join(", ")
+    ));
+  }
+  Some(format!(
+    "import {}{} from {}",
+    if decl.type_only { "type " } else { "" },
+    parts.join(", "),
+    &text[src_range.start..range.end]
+  ))
+}
+
+fn local_name(specifier: &ImportSpecifier) -> String {
+  match specifier {
+    ImportSpecifier::Named(named) => named.local.sym.to_string(),
+    ImportSpecifier::Default(default) => default.local.sym.to_string(),
+    ImportSpecifier::Namespace(namespace) => namespace.local.sym.to_string(),
+  }
+}
+
+fn imported_name(named: &deno_ast::swc::ast::ImportNamedSpecifier) -> String {
+  match &named.imported {
+    Some(ModuleExportName::Ident(ident)) => ident.sym.to_string(),
+    Some(ModuleExportName::Str(str)) => str.value.to_string(),
+    None => named.local.sym.to_string(),
+  }
+}
+
+fn specifier_span(specifier: &ImportSpecifier) -> Span {
+  match specifier {
+    ImportSpecifier::Named(named) => named.span,
+    ImportSpecifier::Default(default) => default.span,
+    ImportSpecifier::Namespace(namespace) => namespace.span,
+  }
+}
+
+fn byte_range(parsed_source: &ParsedSource, span: Span) -> Range<usize> {
+  let start_pos = parsed_source.text_info().range().start;
+  let start = SourcePos::unsafely_from_byte_pos(span.lo) - start_pos;
+  let end = SourcePos::unsafely_from_byte_pos(span.hi) - start_pos;
+  start..end
+}
+
+/// Collects the identifiers of the code after the imports. Property names
+/// and shadowing bindings are included too, which only keeps more imports.
+#[derive(Default)]
+struct UsageCollector {
+  names: HashSet<String>,
+  has_jsx: bool,
+}
+
+impl Visit for UsageCollector {
+  fn visit_ident(&mut self, ident: &deno_ast::swc::ast::Ident) {
+    self.names.insert(ident.sym.to_string());
+  }
+
+  fn visit_jsx_element(&mut self, element: &JSXElement) {
+    self.has_jsx = true;
+    element.visit_children_with(self);
+  }
+
+  fn visit_jsx_fragment(&mut self, fragment: &JSXFragment) {
+    self.has_jsx = true;
+    fragment.visit_children_with(self);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn organize(text: &str) -> Option<String> {
+    organize_with_media_type(text, MediaType::TypeScript)
+  }
+
+  fn organize_with_media_type(
+    text: &str,
+    media_type: MediaType,
+  ) -> Option<String> {
+    let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+      specifier: "file:///mod.ts".to_string(),
+      text_info: deno_ast::SourceTextInfo::from_string(text.to_string()),
+      media_type,
+      capture_tokens: true,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    organize_imports(&parsed_source)
+  }
+
+  #[test]
+  fn groups_and_sorts_imports() {
+    let text = r#"// Copyright header
+import { z, a } from "./util.ts";
+import chalk from "npm:chalk@5";
+// the path utilities
+import { join } from "https://deno.land/std@0.190.0/path/mod.ts";
+import { serve } from "https://deno.land/x/serve/mod.ts"; // the server
+import { readFile } from "node:fs/promises";
+
+console.log(z, a, chalk, join, serve, readFile);
+"#;
+    assert_eq!(
+      organize(text).unwrap(),
+      r#"// Copyright header
+// the path utilities
+import { join } from "https://deno.land/std@0.190.0/path/mod.ts";
+import { readFile } from "node:fs/promises";
+
+import { serve } from "https://deno.land/x/serve/mod.ts"; // the server
+
+import chalk from "npm:chalk@5";
+
+import { a, z } from "./util.ts";
+
+console.log(z, a, chalk, join, serve, readFile);
+"#
+    );
+    // organizing is stable
+    assert_eq!(organize(&organize(text).unwrap()), None);
+  }
+
+  #[test]
+  fn removes_unused_imports() {
+    let text = r#"import Default, { a, b as c, type T } from "./a.ts";
+import * as unused from "./b.ts";
+import data from "./data.json" assert { type: "json" };
+import "./polyfill.ts";
+import { y, x } from "./c.ts";
+
+export const value: T = c(data, x, y);
+"#;
+    assert_eq!(
+      organize(text).unwrap(),
+      r#"import { b as c, type T } from "./a.ts";
+import data from "./data.json" assert { type: "json" };
+
+import "./polyfill.ts";
+
+import { x, y } from "./c.ts";
+
+export const value: T = c(data, x, y);
+"#
+    );
+
+    let text = "import { a } from \"./a.ts\";\n\nconsole.log(1);\n";
+    assert_eq!(organize(text).unwrap(), "console.log(1);\n");
+  }
+
+  #[test]
+  fn keeps_imports_of_jsx() {
+    let text = r#"import { Fragment } from "npm:preact";
+import React from "npm:react";
+
+export const App = () => <div />;
+"#;
+    assert_eq!(organize_with_media_type(text, MediaType::Tsx), None);
+  }
+}
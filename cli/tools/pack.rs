@@ -0,0 +1,281 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_runtime::colors;
+use deno_semver::npm::NpmPackageReq;
+use deno_semver::npm::NpmPackageReqReference;
+
+use crate::args::Flags;
+use crate::args::PackFlags;
+use crate::npm::npm_package_ref_from_cdn_url;
+use crate::proc_state::ProcState;
+use crate::tools::check::declarations::common_dir;
+use crate::tools::check::declarations::js_extension;
+use crate::tools::check::declarations::npm_package_import;
+use crate::tools::check::declarations::resolve_dependency;
+use crate::tools::check::declarations::rewrite_specifiers;
+use crate::util::fs::atomic_write_file;
+use crate::util::path::relative_specifier;
+use crate::util::path::specifier_to_file_path;
+use crate::util::text_encoding::code_without_source_map;
+
+/// A local module that is emitted into the package.
+struct PackedModule {
+  specifier: ModuleSpecifier,
+  media_type: MediaType,
+  source: Arc<str>,
+  out_path: PathBuf,
+}
+
+pub async fn pack(flags: Flags, pack_flags: PackFlags) -> Result<(), AnyError> {
+  let ps = ProcState::from_flags(flags).await?;
+  let initial_cwd = ps.options.initial_cwd();
+  let entrypoint = resolve_url_or_path(&pack_flags.entrypoint, initial_cwd)?;
+  if entrypoint.scheme() != "file" {
+    bail!("The entrypoint must be a local module: {}", entrypoint);
+  }
+  let out_dir = initial_cwd.join(&pack_flags.out_dir);
+
+  // the type check writes the declaration files to the output directory
+  let graph = ps
+    .module_graph_builder
+    .create_graph_and_maybe_check(vec![entrypoint.clone()])
+    .await?;
+
+  let modules = collect_packed_modules(&ps, &graph, &out_dir)?;
+  let mut dependencies = BTreeMap::new();
+  for module in &modules {
+    let code = match module.media_type {
+      MediaType::JavaScript | MediaType::Mjs | MediaType::Json => {
+        module.source.to_string()
+      }
+      // the inline source map points at the local files
+      _ => code_without_source_map(ps.emitter.emit_parsed_source(
+        &module.specifier,
+        module.media_type,
+        &module.source,
+      )?)
+      .as_str()
+      .to_owned(),
+    };
+    let code = if module.media_type == MediaType::Json {
+      code
+    } else {
+      rewrite_specifiers(
+        &module.specifier,
+        &code,
+        MediaType::JavaScript,
+        |specifier| {
+          rewrite_specifier(
+            &ps,
+            &graph,
+            &modules,
+            module,
+            specifier,
+            &mut dependencies,
+          )
+        },
+      )
+      .with_context(|| format!("Failed packing {}", module.specifier))?
+    };
+    if let Some(parent) = module.out_path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    atomic_write_file(&module.out_path, code, 0o644)?;
+  }
+
+  let entrypoint = graph.resolve(&entrypoint);
+  let entrypoint_module = match modules
+    .iter()
+    .find(|m| m.specifier == entrypoint)
+  {
+    Some(module) => module,
+    None => bail!("The entrypoint can't be a declaration file: {}", entrypoint),
+  };
+  let main = package_path(&out_dir, &entrypoint_module.out_path);
+  let types = package_path(
+    &out_dir,
+    &declaration_path(
+      &entrypoint_module.out_path,
+      entrypoint_module.media_type,
+    ),
+  );
+  let package_json = json!({
+    "name": pack_flags.name,
+    "version": pack_flags.version,
+    "type": "module",
+    "main": main,
+    "types": types,
+    "exports": {
+      ".": {
+        "types": types,
+        "import": main,
+      },
+    },
+    "dependencies": dependencies,
+  });
+  atomic_write_file(
+    &out_dir.join("package.json"),
+    format!("{}\n", serde_json::to_string_pretty(&package_json)?),
+    0o644,
+  )?;
+
+  log::info!(
+    "{} {}@{} to {}",
+    colors::green("Pack"),
+    pack_flags.name,
+    pack_flags.version,
+    out_dir.display()
+  );
+  Ok(())
+}
+
+/// Collects the local modules of the graph, which are written to the output
+/// directory with the layout they have relative to their common directory.
+/// CommonJS modules are rejected, as the package is ESM only.
+fn collect_packed_modules(
+  ps: &ProcState,
+  graph: &ModuleGraph,
+  out_dir: &Path,
+) -> Result<Vec<PackedModule>, AnyError> {
+  let mut modules = Vec::new();
+  for module in graph.modules() {
+    let (specifier, media_type, source) = match module {
+      Module::Esm(module) => {
+        (&module.specifier, module.media_type, &module.source)
+      }
+      Module::Json(module) => {
+        (&module.specifier, module.media_type, &module.source)
+      }
+      Module::External(_) | Module::Node(_) | Module::Npm(_) => continue,
+    };
+    let is_packed = specifier.scheme() == "file"
+      && !ps.node_resolver.in_npm_package(specifier)
+      && !matches!(
+        media_type,
+        MediaType::Dts | MediaType::Dmts | MediaType::Dcts
+      );
+    if is_packed {
+      // the package is ESM only, since its modules are not transformed to
+      // CommonJS
+      if matches!(media_type, MediaType::Cjs | MediaType::Cts) {
+        bail!(
+          concat!(
+            "CommonJS module \"{}\" can't be packed.\n",
+            "  The package only contains ES modules."
+          ),
+          specifier
+        );
+      }
+      let path = specifier_to_file_path(specifier)?;
+      modules.push((specifier.clone(), media_type, source.clone(), path));
+    }
+  }
+
+  let common_dir = common_dir(modules.iter().map(|(_, _, _, path)| path));
+  let modules = modules
+    .into_iter()
+    .map(|(specifier, media_type, source, path)| {
+      let relative_path = path.strip_prefix(&common_dir).unwrap_or(&path);
+      let out_path = out_dir.join(relative_path);
+      let out_path = if media_type == MediaType::Json {
+        out_path
+      } else {
+        out_path.with_extension(&js_extension(media_type)[1..])
+      };
+      PackedModule {
+        specifier,
+        media_type,
+        source,
+        out_path,
+      }
+    })
+    .collect();
+  Ok(modules)
+}
+
+/// Rewrites the specifier an emitted module imports with, adding the npm
+/// packages it imports to the dependencies of the package.
+fn rewrite_specifier(
+  ps: &ProcState,
+  graph: &ModuleGraph,
+  modules: &[PackedModule],
+  module: &PackedModule,
+  specifier: &str,
+  dependencies: &mut BTreeMap<String, String>,
+) -> Result<Option<String>, AnyError> {
+  let resolved = match resolve_dependency(graph, &module.specifier, specifier) {
+    Some(resolved) => resolved,
+    None => return Ok(None),
+  };
+  let maybe_npm_reference =
+    npm_package_ref_from_cdn_url(&resolved).or_else(|| {
+      NpmPackageReqReference::from_specifier(&graph.resolve(&resolved)).ok()
+    });
+  if let Some(reference) = maybe_npm_reference {
+    dependencies
+      .entry(reference.req.name.clone())
+      .or_insert_with(|| dependency_version(ps, &reference.req));
+    return Ok(Some(npm_package_import(&reference)));
+  }
+
+  let resolved = graph.resolve(&resolved);
+  if let Some(dependency) = modules.iter().find(|m| m.specifier == resolved) {
+    let from = ModuleSpecifier::from_file_path(&module.out_path).unwrap();
+    let to = ModuleSpecifier::from_file_path(&dependency.out_path).unwrap();
+    return Ok(relative_specifier(&from, &to));
+  }
+  match resolved.scheme() {
+    "http" | "https" => bail!(
+      concat!(
+        "Remote module \"{}\" can't be packed.\n",
+        "  Import the npm package it is published as with an \"npm:\" specifier instead."
+      ),
+      resolved
+    ),
+    _ => Ok(None),
+  }
+}
+
+/// The version requirement of a dependency of the package, which is the
+/// requirement it was imported with or the compatible versions of the one it
+/// resolved to.
+fn dependency_version(ps: &ProcState, req: &NpmPackageReq) -> String {
+  match &req.version_req {
+    Some(version_req) => version_req.to_string(),
+    None => match ps.npm_resolution.resolve_pkg_id_from_pkg_req(req) {
+      Ok(id) => format!("^{}", id.nv.version),
+      Err(_) => "*".to_string(),
+    },
+  }
+}
+
+/// The path of the declaration file the type check emitted for the module.
+fn declaration_path(out_path: &Path, media_type: MediaType) -> PathBuf {
+  let extension = match media_type {
+    MediaType::Mts | MediaType::Mjs => "d.mts",
+    _ => "d.ts",
+  };
+  out_path.with_extension(extension)
+}
+
+/// The path of a file in the package as referenced by the package.json.
+fn package_path(out_dir: &Path, path: &Path) -> String {
+  let relative_path = path.strip_prefix(out_dir).unwrap_or(path);
+  let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+  format!("./{relative_path}")
+}
@@ -0,0 +1,306 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Captures CPU profiles and heap snapshots of the main worker for
+//! `--cpu-prof` and `--heap-snapshot-on-exit`, which are written when the
+//! program exits and whenever the process receives SIGUSR2.
+
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc::UnboundedReceiver;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_core::sourcemap::SourceMap;
+use deno_core::url::Url;
+use deno_core::LocalInspectorSession;
+use deno_core::SourceMapGetter;
+
+use crate::util::time;
+
+#[derive(Debug, Clone)]
+pub struct ProfilerOptions {
+  pub dir: PathBuf,
+  pub cpu_prof: bool,
+  pub heap_snapshot: bool,
+}
+
+pub struct Profiler {
+  options: ProfilerOptions,
+  session: LocalInspectorSession,
+  notification_rx: UnboundedReceiver<Value>,
+  source_map_getter: Rc<dyn SourceMapGetter>,
+  sequence: usize,
+  #[cfg(unix)]
+  signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Profiler {
+  pub fn new(
+    options: ProfilerOptions,
+    mut session: LocalInspectorSession,
+    source_map_getter: Rc<dyn SourceMapGetter>,
+  ) -> Self {
+    let notification_rx = session.take_notification_rx();
+    Self {
+      options,
+      session,
+      notification_rx,
+      source_map_getter,
+      sequence: 0,
+      #[cfg(unix)]
+      signal: tokio::signal::unix::signal(
+        tokio::signal::unix::SignalKind::user_defined2(),
+      )
+      .ok(),
+    }
+  }
+
+  pub async fn start(&mut self) -> Result<(), AnyError> {
+    if self.options.cpu_prof {
+      self
+        .session
+        .post_message::<()>("Profiler.enable", None)
+        .await?;
+      self
+        .session
+        .post_message::<()>("Profiler.start", None)
+        .await?;
+    }
+    if self.options.heap_snapshot {
+      self
+        .session
+        .post_message::<()>("HeapProfiler.enable", None)
+        .await?;
+    }
+    Ok(())
+  }
+
+  /// Resolves when the process receives SIGUSR2, which never happens on
+  /// platforms without the signal.
+  pub async fn signal(&mut self) {
+    #[cfg(unix)]
+    if let Some(signal) = &mut self.signal {
+      signal.recv().await;
+      return;
+    }
+    std::future::pending::<()>().await
+  }
+
+  /// Writes the profiles of the program up to now, after which the CPU
+  /// profile continues with a new one.
+  pub async fn capture(&mut self) -> Result<(), AnyError> {
+    self.write_profiles().await?;
+    if self.options.cpu_prof {
+      self
+        .session
+        .post_message::<()>("Profiler.start", None)
+        .await?;
+    }
+    Ok(())
+  }
+
+  /// Writes the profiles when the program exits.
+  pub async fn stop(&mut self) -> Result<(), AnyError> {
+    self.write_profiles().await?;
+    if self.options.cpu_prof {
+      self
+        .session
+        .post_message::<()>("Profiler.disable", None)
+        .await?;
+    }
+    if self.options.heap_snapshot {
+      self
+        .session
+        .post_message::<()>("HeapProfiler.disable", None)
+        .await?;
+    }
+    Ok(())
+  }
+
+  async fn write_profiles(&mut self) -> Result<(), AnyError> {
+    fs::create_dir_all(&self.options.dir).with_context(|| {
+      format!("Failed creating {}", self.options.dir.display())
+    })?;
+    self.sequence += 1;
+    if self.options.cpu_prof {
+      let mut result = self
+        .session
+        .post_message::<()>("Profiler.stop", None)
+        .await?;
+      let mut profile = result["profile"].take();
+      apply_source_maps(&mut profile, self.source_map_getter.as_ref());
+      let path = self.profile_path("CPU", "cpuprofile");
+      fs::write(&path, serde_json::to_string(&profile)?)
+        .with_context(|| format!("Failed writing {}", path.display()))?;
+      log::info!("CPU profile written to {}", path.display());
+    }
+    if self.options.heap_snapshot {
+      let path = self.profile_path("Heap", "heapsnapshot");
+      let mut file = BufWriter::new(
+        File::create(&path)
+          .with_context(|| format!("Failed creating {}", path.display()))?,
+      );
+      self
+        .session
+        .post_message(
+          "HeapProfiler.takeHeapSnapshot",
+          Some(json!({ "reportProgress": false })),
+        )
+        .await?;
+      // the chunks are sent before the response of the command
+      while let Ok(Some(notification)) = self.notification_rx.try_next() {
+        if notification["method"] == "HeapProfiler.addHeapSnapshotChunk" {
+          if let Some(chunk) = notification["params"]["chunk"].as_str() {
+            file.write_all(chunk.as_bytes())?;
+          }
+        }
+      }
+      file.flush()?;
+      log::info!("Heap snapshot written to {}", path.display());
+    }
+    Ok(())
+  }
+
+  /// The path of a profile, named like the ones of Node.js, e.g.
+  /// `CPU.20230501.093012.1234.1.cpuprofile`.
+  fn profile_path(&self, prefix: &str, extension: &str) -> PathBuf {
+    let now = time::utc_now();
+    self.options.dir.join(format!(
+      "{}.{}.{}.{}.{}",
+      prefix,
+      now.format("%Y%m%d.%H%M%S"),
+      std::process::id(),
+      self.sequence,
+      extension
+    ))
+  }
+}
+
+/// Maps the positions of the call frames of a CPU profile in emitted code,
+/// like the one of TypeScript modules, to the original source.
+fn apply_source_maps(
+  profile: &mut Value,
+  source_map_getter: &dyn SourceMapGetter,
+) {
+  let mut source_maps: HashMap<String, Option<SourceMap>> = HashMap::new();
+  let nodes = match profile["nodes"].as_array_mut() {
+    Some(nodes) => nodes,
+    None => return,
+  };
+  for node in nodes {
+    let url = match node["callFrame"]["url"].as_str() {
+      Some(url) if !url.is_empty() => url.to_string(),
+      _ => continue,
+    };
+    let source_map = source_maps.entry(url.clone()).or_insert_with(|| {
+      source_map_getter
+        .get_source_map(&url)
+        .and_then(|source_map| SourceMap::from_slice(&source_map).ok())
+    });
+    let source_map = match source_map {
+      Some(source_map) => source_map,
+      None => continue,
+    };
+    let call_frame = &mut node["callFrame"];
+    let line = call_frame["lineNumber"].as_i64().unwrap_or(-1);
+    let column = call_frame["columnNumber"].as_i64().unwrap_or(-1);
+    if line >= 0 && column >= 0 {
+      if let Some(token) = source_map.lookup_token(line as u32, column as u32) {
+        call_frame["lineNumber"] = json!(token.get_src_line());
+        call_frame["columnNumber"] = json!(token.get_src_col());
+        if let Some(source) = token.get_source() {
+          if let Ok(source_url) = Url::parse(&url).and_then(|u| u.join(source))
+          {
+            call_frame["url"] = json!(source_url.as_str());
+          }
+        }
+      }
+    }
+    // the lines of the ticks are 1-based
+    if let Some(position_ticks) = node["positionTicks"].as_array_mut() {
+      for position_tick in position_ticks {
+        let line = position_tick["line"].as_u64().unwrap_or(0);
+        if line == 0 {
+          continue;
+        }
+        if let Some(token) = source_map.lookup_token(line as u32 - 1, 0) {
+          position_tick["line"] = json!(token.get_src_line() + 1);
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  struct TestSourceMapGetter;
+
+  impl SourceMapGetter for TestSourceMapGetter {
+    fn get_source_map(&self, file_name: &str) -> Option<Vec<u8>> {
+      if file_name != "file:///main.ts" {
+        return None;
+      }
+      // maps the first line of the emit to the third line of "main.ts"
+      let source_map = json!({
+        "version": 3,
+        "sources": ["main.ts"],
+        "names": [],
+        "mappings": "AAEA",
+      });
+      Some(source_map.to_string().into_bytes())
+    }
+
+    fn get_source_line(&self, _: &str, _: usize) -> Option<String> {
+      None
+    }
+  }
+
+  #[test]
+  fn maps_call_frames() {
+    let mut profile = json!({
+      "nodes": [{
+        "id": 1,
+        "callFrame": {
+          "functionName": "main",
+          "url": "file:///main.ts",
+          "lineNumber": 0,
+          "columnNumber": 4,
+        },
+        "positionTicks": [{ "line": 1, "ticks": 3 }],
+      }, {
+        "id": 2,
+        "callFrame": {
+          "functionName": "(program)",
+          "url": "",
+          "lineNumber": -1,
+          "columnNumber": -1,
+        },
+      }],
+    });
+    apply_source_maps(&mut profile, &TestSourceMapGetter);
+    assert_eq!(
+      profile["nodes"][0]["callFrame"],
+      json!({
+        "functionName": "main",
+        "url": "file:///main.ts",
+        "lineNumber": 2,
+        "columnNumber": 0,
+      })
+    );
+    assert_eq!(
+      profile["nodes"][0]["positionTicks"],
+      json!([{ "line": 3, "ticks": 3 }])
+    );
+    assert_eq!(profile["nodes"][1]["callFrame"]["lineNumber"], json!(-1));
+  }
+}
@@ -0,0 +1,246 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::env;
+use std::sync::Arc;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_runtime::colors;
+use deno_runtime::deno_fetch::reqwest::header::AUTHORIZATION;
+use deno_runtime::deno_fetch::reqwest::header::CONTENT_TYPE;
+use deno_runtime::deno_fetch::reqwest::StatusCode;
+use ring::digest::digest;
+use ring::digest::SHA256;
+
+use crate::args::Flags;
+use crate::args::PublishFlags;
+use crate::auth_tokens::AuthTokens;
+use crate::graph_util::error_for_any_npm_specifier;
+use crate::http_util::BadResponseError;
+use crate::proc_state::ProcState;
+use crate::util::display;
+use crate::util::path::relative_specifier;
+use crate::util::path::specifier_parent;
+use crate::version;
+
+/// Publishes the module graph of the entrypoint to the registry, as the
+/// package with the name and version of the configuration file.
+///
+/// The package is uploaded with a `PUT` request to `<registry>/<name>/<version>`
+/// whose JSON body is the metadata of the package with the base64 encoded
+/// eszip archive of the modules in `"eszip.content"`.
+/// A registry responds with a success status when the package was published,
+/// or with `409 Conflict` when the version was already published.
+pub async fn publish(
+  flags: Flags,
+  publish_flags: PublishFlags,
+) -> Result<(), AnyError> {
+  let ps = ProcState::from_flags(flags).await?;
+  let config_file = match ps.options.maybe_config_file() {
+    Some(config_file) => config_file,
+    None => bail!("A configuration file with the \"name\" and \"version\" of the package is required to publish it."),
+  };
+  let package = match config_file.to_package_metadata()? {
+    Some(package) => package,
+    None => bail!(
+      "The \"name\" and \"version\" of the package must be set in {}.",
+      config_file.specifier
+    ),
+  };
+  let config_dir = specifier_parent(&config_file.specifier);
+  let registry_url = registry_url(&publish_flags.registry)?;
+  let entrypoint =
+    resolve_url_or_path(&publish_flags.entrypoint, ps.options.initial_cwd())?;
+
+  let graph = Arc::try_unwrap(
+    ps.module_graph_builder
+      .create_graph_and_maybe_check(vec![entrypoint.clone()])
+      .await?,
+  )
+  .unwrap();
+  // the eszip archive can't contain npm packages
+  error_for_any_npm_specifier(&graph)?;
+
+  let display_specifier = |specifier: &ModuleSpecifier| {
+    if specifier.scheme() == "file" {
+      relative_specifier(&config_dir, specifier)
+        .unwrap_or_else(|| specifier.to_string())
+    } else {
+      specifier.to_string()
+    }
+  };
+  let mut metadata = package_metadata(&graph, &display_specifier);
+  metadata["name"] = json!(package.name);
+  metadata["version"] = json!(package.version);
+  metadata["entrypoint"] =
+    json!(display_specifier(&graph.resolve(&entrypoint)));
+
+  let parser = ps.parsed_source_cache.as_capturing_parser();
+  let eszip = eszip::EszipV2::from_graph(graph, &parser, Default::default())?;
+  let eszip = eszip.into_bytes();
+  metadata["eszip"] = json!({ "integrity": integrity(&eszip) });
+
+  if publish_flags.dry_run {
+    display::write_json_to_stdout(&metadata)?;
+    return Ok(());
+  }
+
+  let authorization = match &publish_flags.token {
+    Some(token) => format!("Bearer {token}"),
+    None => match AuthTokens::new(env::var("DENO_AUTH_TOKENS").ok())
+      .get(&registry_url)
+    {
+      Some(token) => token.to_string(),
+      None => bail!(
+        "No token to publish to {}. Pass it with --token or set it for the host in DENO_AUTH_TOKENS.",
+        registry_url
+      ),
+    },
+  };
+  let package_url = registry_url
+    .join(&format!("{}/{}", package.name, package.version))
+    .with_context(|| format!("Invalid package name \"{}\"", package.name))?;
+  metadata["eszip"]["content"] = json!(base64::encode(&eszip));
+  let response = ps
+    .http_client
+    .put(package_url)
+    .header(AUTHORIZATION, authorization)
+    .header(CONTENT_TYPE, "application/json")
+    .body(serde_json::to_vec(&metadata)?)
+    .send()
+    .await?;
+  let status = response.status();
+  if status == StatusCode::CONFLICT {
+    bail!(
+      "Version {} of {} is already published to {}.",
+      package.version,
+      package.name,
+      registry_url
+    );
+  } else if !status.is_success() {
+    let maybe_response_text = response.text().await.ok();
+    return Err(
+      BadResponseError {
+        status,
+        maybe_response_text,
+      }
+      .into(),
+    );
+  }
+
+  log::info!(
+    "{} {}@{} to {}",
+    colors::green("Publish"),
+    package.name,
+    package.version,
+    registry_url
+  );
+  Ok(())
+}
+
+/// The URL of the registry, which package paths are joined to.
+fn registry_url(registry: &str) -> Result<Url, AnyError> {
+  let mut url = Url::parse(registry)
+    .with_context(|| format!("Invalid registry URL \"{registry}\""))?;
+  if !url.path().ends_with('/') {
+    let path = format!("{}/", url.path());
+    url.set_path(&path);
+  }
+  Ok(url)
+}
+
+/// Gets the metadata of the modules of the package, which are identified by
+/// the integrity of their source, along with the provenance of the package.
+fn package_metadata(
+  graph: &ModuleGraph,
+  display_specifier: impl Fn(&ModuleSpecifier) -> String,
+) -> Value {
+  let mut modules = graph
+    .modules()
+    .filter_map(|module| {
+      let (specifier, source) = match module {
+        Module::Esm(module) => (&module.specifier, &module.source),
+        Module::Json(module) => (&module.specifier, &module.source),
+        Module::External(_) | Module::Node(_) | Module::Npm(_) => return None,
+      };
+      Some(json!({
+        "specifier": display_specifier(specifier),
+        "integrity": integrity(source.as_bytes()),
+      }))
+    })
+    .collect::<Vec<_>>();
+  // make it deterministic
+  modules
+    .sort_by_key(|module| module["specifier"].as_str().unwrap().to_string());
+  json!({
+    "modules": modules,
+    "provenance": provenance(),
+  })
+}
+
+/// Where and how the package was published, which includes the repository,
+/// commit and run of the workflow when published from GitHub Actions.
+fn provenance() -> Value {
+  let mut provenance = json!({ "denoVersion": version::deno() });
+  let github_env = (
+    env::var("GITHUB_SERVER_URL"),
+    env::var("GITHUB_REPOSITORY"),
+    env::var("GITHUB_SHA"),
+    env::var("GITHUB_RUN_ID"),
+  );
+  if let (Ok(server_url), Ok(repository), Ok(sha), Ok(run_id)) = github_env {
+    provenance["repository"] = json!(format!("{server_url}/{repository}"));
+    provenance["commit"] = json!(sha);
+    provenance["buildUrl"] =
+      json!(format!("{server_url}/{repository}/actions/runs/{run_id}"));
+  }
+  provenance
+}
+
+/// The subresource integrity of the bytes.
+fn integrity(bytes: &[u8]) -> String {
+  format!("sha256-{}", base64::encode(digest(&SHA256, bytes).as_ref()))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_registry_url() {
+    assert_eq!(
+      registry_url("https://registry.example.com")
+        .unwrap()
+        .join("@scope/package/1.0.0")
+        .unwrap()
+        .as_str(),
+      "https://registry.example.com/@scope/package/1.0.0"
+    );
+    assert_eq!(
+      registry_url("https://example.com/deno")
+        .unwrap()
+        .join("package/1.0.0")
+        .unwrap()
+        .as_str(),
+      "https://example.com/deno/package/1.0.0"
+    );
+    assert!(registry_url("registry").is_err());
+  }
+
+  #[test]
+  fn test_integrity() {
+    assert_eq!(
+      integrity(b"hello world"),
+      "sha256-uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek="
+    );
+  }
+}
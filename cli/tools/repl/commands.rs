@@ -0,0 +1,130 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::process::Command;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+
+pub const HELP_TEXT: &str = "\
+.editor      Enter editor mode using $VISUAL or $EDITOR
+.help        Print this help message
+.load <file> Load a file (or URL) into the REPL session
+.save <file> Save all evaluated commands in this session to a file";
+
+/// A Node-style dot command entered at the REPL prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplCommand {
+  Editor,
+  Help,
+  Load(String),
+  Save(String),
+}
+
+impl ReplCommand {
+  /// Parses a line entered in the REPL as a dot command.
+  ///
+  /// Returns `None` when the line is not a known dot command so that it
+  /// can be evaluated as code (ex. `.5 + 1`).
+  pub fn parse(line: &str) -> Option<Result<Self, AnyError>> {
+    let line = line.trim();
+    let rest = line.strip_prefix('.')?;
+    let (name, arg) = match rest.split_once(char::is_whitespace) {
+      Some((name, arg)) => (name, arg.trim()),
+      None => (rest, ""),
+    };
+    let command = match name {
+      "editor" => Ok(ReplCommand::Editor),
+      "help" => Ok(ReplCommand::Help),
+      "load" | "save" if arg.is_empty() => Err(deno_core::anyhow::anyhow!(
+        "Missing file argument for .{name}"
+      )),
+      "load" => Ok(ReplCommand::Load(arg.to_string())),
+      "save" => Ok(ReplCommand::Save(arg.to_string())),
+      _ => return None,
+    };
+    Some(command)
+  }
+}
+
+/// Opens the user's editor on a temporary file and returns the text
+/// that was written to it once the editor exits.
+pub fn read_from_external_editor() -> Result<String, AnyError> {
+  let editor =
+    get_editor_command().context("Neither $VISUAL nor $EDITOR is set.")?;
+  let temp_dir = tempfile::tempdir()?;
+  let file_path = temp_dir.path().join("$deno$repl_editor.ts");
+  std::fs::write(&file_path, "")?;
+  run_editor(&editor, &file_path)?;
+  let text = std::fs::read_to_string(&file_path)?;
+  Ok(text)
+}
+
+/// Writes the evaluated lines of a session to the provided file.
+pub fn save_transcript(
+  file_path: &Path,
+  transcript: &[String],
+) -> Result<(), AnyError> {
+  let mut text = transcript.join("\n");
+  text.push('\n');
+  std::fs::write(file_path, text).with_context(|| {
+    format!("Failed to save session to {}", file_path.display())
+  })
+}
+
+fn get_editor_command() -> Option<String> {
+  ["VISUAL", "EDITOR"]
+    .iter()
+    .filter_map(|name| std::env::var(name).ok())
+    .find(|value| !value.trim().is_empty())
+}
+
+fn run_editor(editor: &str, file_path: &Path) -> Result<(), AnyError> {
+  // the editor variable may contain arguments (ex. `code --wait`)
+  let mut parts = editor.split_whitespace();
+  let program = parts.next().unwrap();
+  let status = Command::new(program)
+    .args(parts)
+    .arg(file_path)
+    .status()
+    .with_context(|| format!("Failed to launch editor '{editor}'"))?;
+  if !status.success() {
+    bail!("Editor '{}' exited with {}", editor, status);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn parse_commands() {
+    assert_eq!(
+      ReplCommand::parse(".editor").unwrap().unwrap(),
+      ReplCommand::Editor
+    );
+    assert_eq!(
+      ReplCommand::parse("  .help  ").unwrap().unwrap(),
+      ReplCommand::Help
+    );
+    assert_eq!(
+      ReplCommand::parse(".load ./mod.ts").unwrap().unwrap(),
+      ReplCommand::Load("./mod.ts".to_string())
+    );
+    assert_eq!(
+      ReplCommand::parse(".save   out.ts ").unwrap().unwrap(),
+      ReplCommand::Save("out.ts".to_string())
+    );
+    assert!(ReplCommand::parse(".load").unwrap().is_err());
+  }
+
+  #[test]
+  fn parse_non_commands() {
+    assert!(ReplCommand::parse(".5 + 1").is_none());
+    assert!(ReplCommand::parse("1 + 1").is_none());
+    assert!(ReplCommand::parse(".unknown").is_none());
+    assert!(ReplCommand::parse("").is_none());
+  }
+}
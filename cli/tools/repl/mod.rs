@@ -6,12 +6,15 @@ use crate::colors;
 use crate::proc_state::ProcState;
 use crate::worker::create_main_worker;
 use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
 use deno_runtime::permissions::Permissions;
 use deno_runtime::permissions::PermissionsContainer;
 use rustyline::error::ReadlineError;
+use std::path::Path;
 
 mod cdp;
 mod channel;
+mod commands;
 mod editor;
 mod session;
 
@@ -19,8 +22,10 @@ use channel::rustyline_channel;
 use channel::RustylineSyncMessage;
 use channel::RustylineSyncMessageHandler;
 use channel::RustylineSyncResponse;
+use commands::ReplCommand;
 use editor::EditorHelper;
 use editor::ReplEditor;
+use session::result_to_evaluation_output;
 use session::EvaluationOutput;
 use session::ReplSession;
 
@@ -64,6 +69,12 @@ async fn read_line_and_poll(
   }
 }
 
+/// Resolves the synthetic module that code evaluated in the REPL is treated
+/// as being part of when resolving its imports.
+pub fn resolve_repl_referrer(cwd: &Path) -> Result<ModuleSpecifier, AnyError> {
+  Ok(deno_core::resolve_path("./$deno$repl.ts", cwd)?)
+}
+
 async fn read_eval_file(
   ps: &ProcState,
   eval_file: &str,
@@ -79,17 +90,81 @@ async fn read_eval_file(
   Ok((*file.source).to_string())
 }
 
+/// Loads a file into the session. The file is first added to the module
+/// graph so that its dependencies are downloaded and type checked the same
+/// way a dynamic import would be, using the permissions of the REPL.
+async fn load_file(
+  ps: &ProcState,
+  repl_session: &mut ReplSession,
+  permissions: &PermissionsContainer,
+  file: &str,
+) -> Result<EvaluationOutput, AnyError> {
+  let specifier =
+    deno_core::resolve_url_or_path(file, ps.options.initial_cwd())?;
+  ps.module_load_preparer
+    .prepare_module_load(
+      vec![specifier.clone()],
+      true,
+      ps.options.ts_type_lib_window(),
+      permissions.clone(),
+      permissions.clone(),
+    )
+    .await?;
+  let file = ps
+    .file_fetcher
+    .fetch(&specifier, permissions.clone())
+    .await?;
+  Ok(
+    repl_session
+      .evaluate_file_and_get_output(&specifier, &file.source)
+      .await,
+  )
+}
+
+async fn run_command(
+  ps: &ProcState,
+  repl_session: &mut ReplSession,
+  permissions: &PermissionsContainer,
+  transcript: &mut Vec<String>,
+  command: ReplCommand,
+) -> Result<Option<EvaluationOutput>, AnyError> {
+  match command {
+    ReplCommand::Editor => {
+      println!("// Entering editor mode, close the editor to evaluate");
+      let source = commands::read_from_external_editor()?;
+      if source.trim().is_empty() {
+        return Ok(None);
+      }
+      let output = repl_session.evaluate_line_and_get_output(&source).await;
+      transcript.push(source);
+      Ok(Some(output))
+    }
+    ReplCommand::Help => {
+      println!("{}", commands::HELP_TEXT);
+      Ok(None)
+    }
+    ReplCommand::Load(file) => {
+      let output = load_file(ps, repl_session, permissions, &file).await?;
+      transcript.push(format!(".load {file}"));
+      Ok(Some(output))
+    }
+    ReplCommand::Save(file) => {
+      let file_path = ps.options.initial_cwd().join(file);
+      commands::save_transcript(&file_path, transcript)?;
+      println!("Session saved to: {}", file_path.display());
+      Ok(None)
+    }
+  }
+}
+
 pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
   let ps = ProcState::from_flags(flags).await?;
   let main_module = ps.options.resolve_main_module()?;
-  let mut worker = create_main_worker(
-    &ps,
-    main_module,
-    PermissionsContainer::new(Permissions::from_options(
-      &ps.options.permissions_options(),
-    )?),
-  )
-  .await?;
+  let permissions = PermissionsContainer::new(Permissions::from_options(
+    &ps.options.permissions_options(),
+  )?);
+  let mut worker =
+    create_main_worker(&ps, main_module, permissions.clone()).await?;
   worker.setup_repl().await?;
   let worker = worker.into_main_worker();
   let mut repl_session = ReplSession::initialize(ps.clone(), worker).await?;
@@ -144,6 +219,7 @@ pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
     }
   }
 
+  let mut transcript = Vec::new();
   loop {
     let line = read_line_and_poll(
       &mut repl_session,
@@ -155,7 +231,33 @@ pub async fn run(flags: Flags, repl_flags: ReplFlags) -> Result<i32, AnyError> {
       Ok(line) => {
         editor.set_should_exit_on_interrupt(false);
         editor.update_history(line.clone());
-        let output = repl_session.evaluate_line_and_get_output(&line).await;
+        let output = match ReplCommand::parse(&line) {
+          Some(command) => {
+            let result = match command {
+              Ok(command) => {
+                run_command(
+                  &ps,
+                  &mut repl_session,
+                  &permissions,
+                  &mut transcript,
+                  command,
+                )
+                .await
+              }
+              Err(err) => Err(err),
+            };
+            match result {
+              Ok(Some(output)) => output,
+              Ok(None) => continue,
+              Err(err) => result_to_evaluation_output(Err(err)),
+            }
+          }
+          None => {
+            let output = repl_session.evaluate_line_and_get_output(&line).await;
+            transcript.push(line);
+            output
+          }
+        };
 
         // We check for close and break here instead of making it a loop condition to get
         // consistent behavior in when the user evaluates a call to close().
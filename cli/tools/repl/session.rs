@@ -171,11 +171,8 @@ impl ReplSession {
     }
     assert_ne!(context_id, 0);
 
-    let referrer = deno_core::resolve_path(
-      "./$deno$repl.ts",
-      proc_state.options.initial_cwd(),
-    )
-    .unwrap();
+    let referrer =
+      super::resolve_repl_referrer(proc_state.options.initial_cwd()).unwrap();
 
     let mut repl_session = ReplSession {
       proc_state,
@@ -297,6 +294,19 @@ impl ReplSession {
     result_to_evaluation_output(result)
   }
 
+  /// Evaluates the source of a file loaded with `.load`, checking its
+  /// npm and node imports relative to the file instead of the REPL.
+  pub async fn evaluate_file_and_get_output(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    source: &str,
+  ) -> EvaluationOutput {
+    let referrer = std::mem::replace(&mut self.referrer, specifier.clone());
+    let output = self.evaluate_line_and_get_output(source).await;
+    self.referrer = referrer;
+    output
+  }
+
   async fn evaluate_line_with_object_wrapping(
     &mut self,
     line: &str,
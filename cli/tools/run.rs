@@ -8,6 +8,7 @@ use deno_core::error::AnyError;
 use deno_runtime::permissions::Permissions;
 use deno_runtime::permissions::PermissionsContainer;
 
+use crate::api::ProjectBuilder;
 use crate::args::EvalFlags;
 use crate::args::Flags;
 use crate::file_fetcher::File;
@@ -34,24 +35,22 @@ To grant permissions, set them before the script argument. For example:
   // TODO(bartlomieju): actually I think it will also fail if there's an import
   // map specified and bare specifier is used on the command line - this should
   // probably call `ProcState::resolve` instead
-  let ps = ProcState::from_flags(flags).await?;
+  let project = ProjectBuilder::from_flags(flags)?.build().await?;
 
   // Run a background task that checks for available upgrades. If an earlier
   // run of this background task found a new version of Deno.
+  let ps = project.proc_state();
   super::upgrade::check_for_upgrades(
     ps.http_client.clone(),
     ps.dir.upgrade_check_file_path(),
   );
 
-  let main_module = ps.options.resolve_main_module()?;
+  let main_module = project.options().resolve_main_module()?;
 
   let permissions = PermissionsContainer::new(Permissions::from_options(
-    &ps.options.permissions_options(),
+    &project.options().permissions_options(),
   )?);
-  let mut worker = create_main_worker(&ps, main_module, permissions).await?;
-
-  let exit_code = worker.run().await?;
-  Ok(exit_code)
+  project.run(main_module, permissions).await
 }
 
 pub async fn run_from_stdin(flags: Flags) -> Result<i32, AnyError> {
@@ -93,6 +92,9 @@ async fn run_with_watch(flags: Flags) -> Result<i32, AnyError> {
   let mut ps =
     ProcState::from_flags_for_file_watcher(flags, sender.clone()).await?;
   let clear_screen = !ps.options.no_clear_screen();
+  let filter = ps.options.watch_filter()?;
+  let debounce = ps.options.watch_debounce()?;
+  let before_restart = crate::tools::task::before_restart_hook(&ps.options)?;
   let main_module = ps.options.resolve_main_module()?;
 
   let operation = |main_module: ModuleSpecifier| {
@@ -116,6 +118,9 @@ async fn run_with_watch(flags: Flags) -> Result<i32, AnyError> {
     util::file_watcher::PrintConfig {
       job_name: "Process".to_string(),
       clear_screen,
+      filter,
+      debounce,
+      before_restart,
     },
   )
   .await?;
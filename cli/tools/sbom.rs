@@ -0,0 +1,733 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use deno_ast::ModuleSpecifier;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_npm::resolution::NpmResolutionSnapshot;
+use deno_semver::npm::NpmPackageNvReference;
+use deno_semver::npm::NpmPackageReqReference;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use uuid::Uuid;
+
+use crate::args::Lockfile;
+use crate::args::SbomFormat;
+use crate::npm::NpmCache;
+use crate::tools::license::graph_npm_packages;
+use crate::tools::license::npm_package_license;
+use crate::util::checksum;
+use crate::util::time::utc_now;
+
+/// Matches the version in urls like `https://deno.land/std@0.180.0/` or
+/// `https://esm.sh/preact@10.13.1`.
+static URL_VERSION_RE: Lazy<Regex> =
+  Lazy::new(|| Regex::new(r"@v?(\d+\.\d+\.\d+[^/]*)(?:/|$)").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ComponentKind {
+  RemoteModule,
+  NpmPackage,
+}
+
+/// A remote module or an npm package in the bill of materials.
+#[derive(Debug)]
+struct Component {
+  /// The specifier of a remote module or the serialized npm package id,
+  /// which uniquely identifies the component in the document.
+  id: String,
+  kind: ComponentKind,
+  name: String,
+  version: Option<String>,
+  purl: Option<String>,
+  download_url: Option<String>,
+  /// Hashes as CycloneDX algorithm names (ex. `SHA-256`) and hex values.
+  hashes: Vec<(&'static str, String)>,
+  license: Option<String>,
+  depends_on: Vec<String>,
+}
+
+/// The root module, which the local modules are considered part of, along
+/// with the remote modules and npm packages it depends on.
+#[derive(Debug)]
+struct Components {
+  root_name: String,
+  root_depends_on: Vec<String>,
+  components: Vec<Component>,
+}
+
+/// Creates a software bill of materials (SBOM) of the remote modules and npm
+/// packages of the graph, in the CycloneDX 1.5 or SPDX 2.3 JSON format.
+pub fn create_sbom(
+  format: SbomFormat,
+  graph: &ModuleGraph,
+  npm_snapshot: &NpmResolutionSnapshot,
+  npm_cache: &NpmCache,
+  maybe_lockfile: Option<&Lockfile>,
+) -> serde_json::Value {
+  let components =
+    collect_components(graph, npm_snapshot, npm_cache, maybe_lockfile);
+  match format {
+    SbomFormat::CycloneDx => to_cyclonedx(&components),
+    SbomFormat::Spdx => to_spdx(&components),
+  }
+}
+
+fn collect_components(
+  graph: &ModuleGraph,
+  npm_snapshot: &NpmResolutionSnapshot,
+  npm_cache: &NpmCache,
+  maybe_lockfile: Option<&Lockfile>,
+) -> Components {
+  let resolve_npm_package = |specifier: &ModuleSpecifier| {
+    if let Ok(reference) = NpmPackageNvReference::from_specifier(specifier) {
+      npm_snapshot
+        .resolve_package_from_deno_module(&reference.nv)
+        .ok()
+    } else if let Ok(reference) =
+      NpmPackageReqReference::from_specifier(specifier)
+    {
+      npm_snapshot.resolve_pkg_from_pkg_req(&reference.req).ok()
+    } else {
+      None
+    }
+  };
+  // the id of the component a dependency resolves to, if any
+  let dependency_id = |specifier: &ModuleSpecifier| {
+    let specifier = graph.resolve(specifier);
+    if is_remote(&specifier) {
+      Some(specifier.to_string())
+    } else {
+      resolve_npm_package(&specifier).map(|pkg| pkg.pkg_id.as_serialized())
+    }
+  };
+
+  let mut components = Vec::new();
+  let mut root_depends_on = BTreeSet::new();
+  for module in graph.modules() {
+    let specifier = module.specifier();
+    let mut depends_on = BTreeSet::new();
+    if let Module::Esm(module) = module {
+      for dep in module.dependencies.values() {
+        for dep_specifier in
+          [dep.get_code(), dep.get_type()].into_iter().flatten()
+        {
+          depends_on.extend(dependency_id(dep_specifier));
+        }
+      }
+    }
+    let source = match module {
+      Module::Esm(module) => &module.source,
+      Module::Json(module) => &module.source,
+      Module::Npm(_) | Module::Node(_) | Module::External(_) => continue,
+    };
+    if !is_remote(specifier) {
+      // local modules are considered part of the root
+      root_depends_on.extend(depends_on);
+      continue;
+    }
+    let checksum = maybe_lockfile
+      .and_then(|lockfile| lockfile.content.remote.get(specifier.as_str()))
+      .cloned()
+      .unwrap_or_else(|| checksum::gen(&[source.as_bytes()]));
+    components.push(Component {
+      id: specifier.to_string(),
+      kind: ComponentKind::RemoteModule,
+      name: specifier.to_string(),
+      version: URL_VERSION_RE
+        .captures(specifier.as_str())
+        .map(|captures| captures[1].to_string()),
+      purl: None,
+      download_url: Some(specifier.to_string()),
+      hashes: vec![("SHA-256", checksum)],
+      license: None,
+      depends_on: depends_on.into_iter().collect(),
+    });
+  }
+
+  for pkg in graph_npm_packages(graph, npm_snapshot) {
+    let id = pkg.pkg_id.as_serialized();
+    let nv = &pkg.pkg_id.nv;
+    let integrity = maybe_lockfile
+      .and_then(|lockfile| lockfile.content.npm.packages.get(&id))
+      .map(|info| info.integrity.clone())
+      .unwrap_or_else(|| pkg.dist.integrity().to_string());
+    let mut depends_on = pkg
+      .dependencies
+      .values()
+      .map(|id| id.as_serialized())
+      .collect::<Vec<_>>();
+    depends_on.sort();
+    components.push(Component {
+      id,
+      kind: ComponentKind::NpmPackage,
+      name: nv.name.clone(),
+      version: Some(nv.version.to_string()),
+      purl: Some(npm_purl(&nv.name, &nv.version.to_string())),
+      download_url: Some(pkg.dist.tarball.clone()),
+      hashes: integrity_to_hash(&integrity).into_iter().collect(),
+      license: npm_package_license(npm_cache, nv),
+      depends_on,
+    });
+  }
+
+  // remote and npm roots are dependencies of the root as well
+  for root in &graph.roots {
+    root_depends_on.extend(dependency_id(root));
+  }
+  Components {
+    root_name: graph
+      .roots
+      .first()
+      .map(|root| root.to_string())
+      .unwrap_or_default(),
+    root_depends_on: root_depends_on.into_iter().collect(),
+    components,
+  }
+}
+
+fn is_remote(specifier: &ModuleSpecifier) -> bool {
+  matches!(specifier.scheme(), "http" | "https")
+}
+
+/// Gets the package url (https://github.com/package-url/purl-spec) of an npm
+/// package, in which the `@` of a scope is percent encoded.
+fn npm_purl(name: &str, version: &str) -> String {
+  match name.strip_prefix('@') {
+    Some(scoped_name) => format!("pkg:npm/%40{scoped_name}@{version}"),
+    None => format!("pkg:npm/{name}@{version}"),
+  }
+}
+
+/// Converts a subresource integrity value (ex. `sha512-<base64>`) or a legacy
+/// hex encoded sha1 shasum to a CycloneDX hash algorithm and hex value.
+fn integrity_to_hash(integrity: &str) -> Option<(&'static str, String)> {
+  if let Some((algorithm, base64_hash)) = integrity.split_once('-') {
+    let algorithm = match algorithm {
+      "sha1" => "SHA-1",
+      "sha256" => "SHA-256",
+      "sha384" => "SHA-384",
+      "sha512" => "SHA-512",
+      _ => return None,
+    };
+    let bytes = base64::decode(base64_hash).ok()?;
+    let hex = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    Some((algorithm, hex))
+  } else if integrity.len() == 40
+    && integrity.chars().all(|c| c.is_ascii_hexdigit())
+  {
+    Some(("SHA-1", integrity.to_lowercase()))
+  } else {
+    None
+  }
+}
+
+fn to_cyclonedx(components: &Components) -> serde_json::Value {
+  // a remote root module is also a component, so it can't be the root's ref
+  const ROOT_REF: &str = "root";
+  let json_components = components
+    .components
+    .iter()
+    .map(|component| {
+      let mut json = serde_json::Map::new();
+      json.insert(
+        "type".to_string(),
+        match component.kind {
+          ComponentKind::RemoteModule => "file",
+          ComponentKind::NpmPackage => "library",
+        }
+        .into(),
+      );
+      json.insert("bom-ref".to_string(), component.id.clone().into());
+      json.insert("name".to_string(), component.name.clone().into());
+      if let Some(version) = &component.version {
+        json.insert("version".to_string(), version.clone().into());
+      }
+      if let Some(purl) = &component.purl {
+        json.insert("purl".to_string(), purl.clone().into());
+      }
+      json.insert(
+        "hashes".to_string(),
+        component
+          .hashes
+          .iter()
+          .map(|(alg, content)| json!({ "alg": alg, "content": content }))
+          .collect(),
+      );
+      if let Some(license) = &component.license {
+        let license = if is_spdx_expression(license) {
+          json!({ "expression": license })
+        } else {
+          json!({ "license": { "name": license } })
+        };
+        json.insert("licenses".to_string(), json!([license]));
+      }
+      if let Some(url) = &component.download_url {
+        json.insert(
+          "externalReferences".to_string(),
+          json!([{ "type": "distribution", "url": url }]),
+        );
+      }
+      serde_json::Value::Object(json)
+    })
+    .collect::<Vec<_>>();
+  let mut dependencies = vec![json!({
+    "ref": ROOT_REF,
+    "dependsOn": components.root_depends_on,
+  })];
+  dependencies.extend(components.components.iter().map(|component| {
+    json!({
+      "ref": component.id,
+      "dependsOn": component.depends_on,
+    })
+  }));
+  json!({
+    "bomFormat": "CycloneDX",
+    "specVersion": "1.5",
+    "serialNumber": format!("urn:uuid:{}", Uuid::new_v4()),
+    "version": 1,
+    "metadata": {
+      "timestamp": utc_now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+      "tools": [{
+        "vendor": "Deno",
+        "name": "deno",
+        "version": crate::version::deno(),
+      }],
+      "component": {
+        "type": "application",
+        "bom-ref": ROOT_REF,
+        "name": components.root_name,
+      },
+    },
+    "components": json_components,
+    "dependencies": dependencies,
+  })
+}
+
+fn to_spdx(components: &Components) -> serde_json::Value {
+  const ROOT_ID: &str = "SPDXRef-Root";
+  // SPDX identifiers may only contain letters, numbers, `.` and `-`
+  let spdx_ids = components
+    .components
+    .iter()
+    .enumerate()
+    .map(|(i, component)| {
+      let prefix = match component.kind {
+        ComponentKind::RemoteModule => "SPDXRef-Module",
+        ComponentKind::NpmPackage => "SPDXRef-Npm",
+      };
+      (component.id.as_str(), format!("{prefix}-{}", i + 1))
+    })
+    .collect::<BTreeMap<_, _>>();
+  let mut packages = vec![json!({
+    "SPDXID": ROOT_ID,
+    "name": components.root_name,
+    "downloadLocation": "NOASSERTION",
+    "filesAnalyzed": false,
+    "licenseConcluded": "NOASSERTION",
+    "licenseDeclared": "NOASSERTION",
+    "copyrightText": "NOASSERTION",
+  })];
+  // the licenses that aren't SPDX expressions, by their reference
+  let mut extracted_licenses = BTreeMap::new();
+  packages.extend(components.components.iter().map(|component| {
+    let mut json = serde_json::Map::new();
+    json.insert(
+      "SPDXID".to_string(),
+      spdx_ids[component.id.as_str()].clone().into(),
+    );
+    json.insert("name".to_string(), component.name.clone().into());
+    if let Some(version) = &component.version {
+      json.insert("versionInfo".to_string(), version.clone().into());
+    }
+    json.insert(
+      "downloadLocation".to_string(),
+      component
+        .download_url
+        .clone()
+        .unwrap_or_else(|| "NOASSERTION".to_string())
+        .into(),
+    );
+    json.insert("filesAnalyzed".to_string(), false.into());
+    json.insert(
+      "checksums".to_string(),
+      component
+        .hashes
+        .iter()
+        .map(|(alg, content)| {
+          json!({
+            "algorithm": alg.replace('-', ""),
+            "checksumValue": content,
+          })
+        })
+        .collect(),
+    );
+    json.insert("licenseConcluded".to_string(), "NOASSERTION".into());
+    let license_declared = match &component.license {
+      Some(license) if is_spdx_expression(license) => license.clone(),
+      Some(license) => match license_ref(license) {
+        Some(license_ref) => {
+          extracted_licenses.insert(license_ref.clone(), license.clone());
+          license_ref
+        }
+        None => "NOASSERTION".to_string(),
+      },
+      None => "NOASSERTION".to_string(),
+    };
+    json.insert("licenseDeclared".to_string(), license_declared.into());
+    json.insert("copyrightText".to_string(), "NOASSERTION".into());
+    if let Some(purl) = &component.purl {
+      json.insert(
+        "externalRefs".to_string(),
+        json!([{
+          "referenceCategory": "PACKAGE-MANAGER",
+          "referenceType": "purl",
+          "referenceLocator": purl,
+        }]),
+      );
+    }
+    serde_json::Value::Object(json)
+  }));
+
+  let mut relationships = vec![json!({
+    "spdxElementId": "SPDXRef-DOCUMENT",
+    "relationshipType": "DESCRIBES",
+    "relatedSpdxElement": ROOT_ID,
+  })];
+  let dependency_relationships =
+    std::iter::once((ROOT_ID, &components.root_depends_on)).chain(
+      components.components.iter().map(|component| {
+        (
+          spdx_ids[component.id.as_str()].as_str(),
+          &component.depends_on,
+        )
+      }),
+    );
+  for (spdx_id, depends_on) in dependency_relationships {
+    for dep_id in depends_on {
+      if let Some(dep_spdx_id) = spdx_ids.get(dep_id.as_str()) {
+        relationships.push(json!({
+          "spdxElementId": spdx_id,
+          "relationshipType": "DEPENDS_ON",
+          "relatedSpdxElement": dep_spdx_id,
+        }));
+      }
+    }
+  }
+
+  let document_id = Uuid::new_v4();
+  json!({
+    "spdxVersion": "SPDX-2.3",
+    "dataLicense": "CC0-1.0",
+    "SPDXID": "SPDXRef-DOCUMENT",
+    "name": components.root_name,
+    "documentNamespace": format!("https://deno.land/spdx/{document_id}"),
+    "creationInfo": {
+      "created": utc_now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+      "creators": [format!("Tool: deno-{}", crate::version::deno())],
+    },
+    "packages": packages,
+    "relationships": relationships,
+    "hasExtractedLicensingInfos": extracted_licenses
+      .into_iter()
+      .map(|(license_ref, license)| {
+        json!({
+          "licenseId": license_ref,
+          "name": license,
+          "extractedText": license,
+        })
+      })
+      .collect::<Vec<_>>(),
+  })
+}
+
+/// Gets if the license is a valid SPDX license expression, like `MIT` or
+/// `(MIT OR Apache-2.0)`, unlike the free text licenses of some npm packages
+/// (ex. `SEE LICENSE IN LICENSE.md` or `UNLICENSED`).
+fn is_spdx_expression(license: &str) -> bool {
+  fn is_id(token: &str) -> bool {
+    let id = token.strip_suffix('+').unwrap_or(token);
+    !id.is_empty()
+      && !matches!(id, "AND" | "OR" | "WITH" | "UNLICENSED")
+      && id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+  }
+
+  /// Parses `term ((AND | OR) term)*`, returning the index after it.
+  fn parse_expression(tokens: &[&str], index: usize) -> Option<usize> {
+    let mut index = parse_term(tokens, index)?;
+    while matches!(tokens.get(index), Some(&"AND") | Some(&"OR")) {
+      index = parse_term(tokens, index + 1)?;
+    }
+    Some(index)
+  }
+
+  /// Parses `( expression )` or `id [WITH id]`.
+  fn parse_term(tokens: &[&str], index: usize) -> Option<usize> {
+    match *tokens.get(index)? {
+      "(" => {
+        let index = parse_expression(tokens, index + 1)?;
+        (tokens.get(index) == Some(&")")).then_some(index + 1)
+      }
+      token if is_id(token) => {
+        if tokens.get(index + 1) == Some(&"WITH") {
+          is_id(tokens.get(index + 2)?).then_some(index + 3)
+        } else {
+          Some(index + 1)
+        }
+      }
+      _ => None,
+    }
+  }
+
+  let license = license.replace('(', " ( ").replace(')', " ) ");
+  let tokens = license.split_whitespace().collect::<Vec<_>>();
+  parse_expression(&tokens, 0) == Some(tokens.len())
+}
+
+/// Gets the SPDX license reference a free text license is declared with,
+/// which may only contain letters, numbers, `.` and `-`.
+fn license_ref(license: &str) -> Option<String> {
+  let id = license
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() || c == '.' {
+        c
+      } else {
+        '-'
+      }
+    })
+    .collect::<String>();
+  let id = id.trim_matches('-');
+  if id.is_empty() {
+    None
+  } else {
+    Some(format!("LicenseRef-{id}"))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn test_components() -> Components {
+    Components {
+      root_name: "file:///project/main.ts".to_string(),
+      root_depends_on: vec![
+        "chalk@5.0.1".to_string(),
+        "https://deno.land/std@0.180.0/path/mod.ts".to_string(),
+      ],
+      components: vec![
+        Component {
+          id: "https://deno.land/std@0.180.0/path/mod.ts".to_string(),
+          kind: ComponentKind::RemoteModule,
+          name: "https://deno.land/std@0.180.0/path/mod.ts".to_string(),
+          version: Some("0.180.0".to_string()),
+          purl: None,
+          download_url: Some(
+            "https://deno.land/std@0.180.0/path/mod.ts".to_string(),
+          ),
+          hashes: vec![("SHA-256", "ab".repeat(32))],
+          license: None,
+          depends_on: Vec::new(),
+        },
+        Component {
+          id: "chalk@5.0.1".to_string(),
+          kind: ComponentKind::NpmPackage,
+          name: "chalk".to_string(),
+          version: Some("5.0.1".to_string()),
+          purl: Some("pkg:npm/chalk@5.0.1".to_string()),
+          download_url: Some(
+            "https://registry.npmjs.org/chalk/-/chalk-5.0.1.tgz".to_string(),
+          ),
+          hashes: vec![("SHA-512", "cd".repeat(64))],
+          license: Some("MIT".to_string()),
+          depends_on: Vec::new(),
+        },
+      ],
+    }
+  }
+
+  #[test]
+  fn test_is_spdx_expression() {
+    assert!(is_spdx_expression("MIT"));
+    assert!(is_spdx_expression("Apache-2.0"));
+    assert!(is_spdx_expression("GPL-2.0+"));
+    assert!(is_spdx_expression("(MIT OR Apache-2.0)"));
+    assert!(is_spdx_expression("MIT AND (BSD-2-Clause OR ISC)"));
+    assert!(is_spdx_expression("GPL-2.0 WITH Classpath-exception-2.0"));
+    assert!(!is_spdx_expression(""));
+    assert!(!is_spdx_expression("UNLICENSED"));
+    assert!(!is_spdx_expression("SEE LICENSE IN LICENSE.md"));
+    assert!(!is_spdx_expression("Apache 2.0"));
+    assert!(!is_spdx_expression("MIT/X11"));
+    assert!(!is_spdx_expression("(MIT OR"));
+    assert!(!is_spdx_expression("MIT OR"));
+  }
+
+  #[test]
+  fn test_license_ref() {
+    assert_eq!(
+      license_ref("SEE LICENSE IN LICENSE.md").as_deref(),
+      Some("LicenseRef-SEE-LICENSE-IN-LICENSE.md")
+    );
+    assert_eq!(license_ref("***"), None);
+  }
+
+  #[test]
+  fn test_non_spdx_license() {
+    let mut components = test_components();
+    components.components[1].license =
+      Some("SEE LICENSE IN LICENSE.md".to_string());
+
+    let sbom = to_cyclonedx(&components);
+    assert_eq!(
+      sbom["components"][1]["licenses"],
+      json!([{ "license": { "name": "SEE LICENSE IN LICENSE.md" } }])
+    );
+
+    let sbom = to_spdx(&components);
+    assert_eq!(
+      sbom["packages"][2]["licenseDeclared"],
+      "LicenseRef-SEE-LICENSE-IN-LICENSE.md"
+    );
+    assert_eq!(
+      sbom["hasExtractedLicensingInfos"],
+      json!([{
+        "licenseId": "LicenseRef-SEE-LICENSE-IN-LICENSE.md",
+        "name": "SEE LICENSE IN LICENSE.md",
+        "extractedText": "SEE LICENSE IN LICENSE.md",
+      }])
+    );
+  }
+
+  #[test]
+  fn test_npm_purl() {
+    assert_eq!(npm_purl("chalk", "5.0.1"), "pkg:npm/chalk@5.0.1");
+    assert_eq!(
+      npm_purl("@types/node", "18.0.0"),
+      "pkg:npm/%40types/node@18.0.0"
+    );
+  }
+
+  #[test]
+  fn test_integrity_to_hash() {
+    assert_eq!(
+      integrity_to_hash("sha512-AAEC/w=="),
+      Some(("SHA-512", "000102ff".to_string()))
+    );
+    assert_eq!(
+      integrity_to_hash("0123456789ABCDEF0123456789abcdef01234567"),
+      Some((
+        "SHA-1",
+        "0123456789abcdef0123456789abcdef01234567".to_string()
+      ))
+    );
+    assert_eq!(integrity_to_hash("md5-AAEC/w=="), None);
+    assert_eq!(integrity_to_hash("invalid"), None);
+  }
+
+  #[test]
+  fn test_url_version() {
+    let version = |url: &str| {
+      URL_VERSION_RE
+        .captures(url)
+        .map(|captures| captures[1].to_string())
+    };
+    assert_eq!(
+      version("https://deno.land/std@0.180.0/path/mod.ts"),
+      Some("0.180.0".to_string())
+    );
+    assert_eq!(
+      version("https://esm.sh/preact@v10.13.1"),
+      Some("10.13.1".to_string())
+    );
+    assert_eq!(version("https://example.com/mod.ts"), None);
+  }
+
+  #[test]
+  fn test_to_cyclonedx() {
+    let sbom = to_cyclonedx(&test_components());
+    assert_eq!(sbom["bomFormat"], "CycloneDX");
+    assert_eq!(
+      sbom["metadata"]["component"]["name"],
+      "file:///project/main.ts"
+    );
+    assert_eq!(
+      sbom["components"],
+      json!([{
+        "type": "file",
+        "bom-ref": "https://deno.land/std@0.180.0/path/mod.ts",
+        "name": "https://deno.land/std@0.180.0/path/mod.ts",
+        "version": "0.180.0",
+        "hashes": [{ "alg": "SHA-256", "content": "ab".repeat(32) }],
+        "externalReferences": [{
+          "type": "distribution",
+          "url": "https://deno.land/std@0.180.0/path/mod.ts",
+        }],
+      }, {
+        "type": "library",
+        "bom-ref": "chalk@5.0.1",
+        "name": "chalk",
+        "version": "5.0.1",
+        "purl": "pkg:npm/chalk@5.0.1",
+        "hashes": [{ "alg": "SHA-512", "content": "cd".repeat(64) }],
+        "licenses": [{ "expression": "MIT" }],
+        "externalReferences": [{
+          "type": "distribution",
+          "url": "https://registry.npmjs.org/chalk/-/chalk-5.0.1.tgz",
+        }],
+      }])
+    );
+    assert_eq!(
+      sbom["dependencies"][0],
+      json!({
+        "ref": "root",
+        "dependsOn": [
+          "chalk@5.0.1",
+          "https://deno.land/std@0.180.0/path/mod.ts",
+        ],
+      })
+    );
+  }
+
+  #[test]
+  fn test_to_spdx() {
+    let sbom = to_spdx(&test_components());
+    assert_eq!(sbom["spdxVersion"], "SPDX-2.3");
+    let packages = sbom["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 3);
+    assert_eq!(packages[1]["SPDXID"], "SPDXRef-Module-1");
+    assert_eq!(
+      packages[2]["checksums"],
+      json!([{ "algorithm": "SHA512", "checksumValue": "cd".repeat(64) }])
+    );
+    assert_eq!(packages[2]["licenseDeclared"], "MIT");
+    assert_eq!(sbom["hasExtractedLicensingInfos"], json!([]));
+    assert_eq!(
+      packages[2]["externalRefs"][0]["referenceLocator"],
+      "pkg:npm/chalk@5.0.1"
+    );
+    assert_eq!(
+      sbom["relationships"],
+      json!([{
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": "SPDXRef-Root",
+      }, {
+        "spdxElementId": "SPDXRef-Root",
+        "relationshipType": "DEPENDS_ON",
+        "relatedSpdxElement": "SPDXRef-Npm-2",
+      }, {
+        "spdxElementId": "SPDXRef-Root",
+        "relationshipType": "DEPENDS_ON",
+        "relatedSpdxElement": "SPDXRef-Module-1",
+      }])
+    );
+  }
+}
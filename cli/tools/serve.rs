@@ -0,0 +1,335 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::futures::future;
+use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::FutureExt;
+use deno_core::located_script_name;
+use deno_core::serde_json::json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::tokio_util::run_local;
+use deno_runtime::worker::MainWorker;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use crate::args::Flags;
+use crate::args::ServeFlags;
+use crate::colors;
+use crate::proc_state::ProcState;
+use crate::util;
+use crate::worker::create_main_worker;
+
+/// Starts serving the fetch handler of the module namespace passed to it,
+/// which is the `fetch` method of the default export. It returns a function
+/// that waits for the requests being handled to complete before closing the
+/// server.
+const START_SERVER_SOURCE: &str = r#"(namespace, options) => {
+  const handler = namespace.default;
+  if (typeof handler?.fetch !== "function") {
+    throw new TypeError(
+      "The default export of the module must be an object with a fetch method.",
+    );
+  }
+  const controller = new AbortController();
+  let pendingRequests = 0;
+  let onIdle = null;
+  const finished = Deno.serve({
+    hostname: options.hostname,
+    port: options.port,
+    reusePort: options.reusePort,
+    signal: controller.signal,
+    onListen: options.printListening ? undefined : () => {},
+  }, async (request, info) => {
+    pendingRequests++;
+    try {
+      return await handler.fetch(request, info);
+    } finally {
+      pendingRequests--;
+      if (pendingRequests === 0) {
+        onIdle?.();
+      }
+    }
+  });
+  return async () => {
+    if (pendingRequests > 0) {
+      await new Promise((resolve) => onIdle = resolve);
+    }
+    controller.abort();
+    await finished;
+  };
+}"#;
+
+/// How long the requests being handled by a worker that is stopped are
+/// waited for.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn serve(
+  mut flags: Flags,
+  serve_flags: ServeFlags,
+) -> Result<i32, AnyError> {
+  if serve_flags.parallel.get() > 1 && !cfg!(target_os = "linux") {
+    bail!("--parallel is only supported on Linux, where the workers can listen on the same port.");
+  }
+  // the server is started with `Deno.serve`, and the workers share the port
+  // with `reusePort`
+  let mut unstable_features = vec!["http"];
+  if serve_flags.parallel.get() > 1 {
+    unstable_features.push("net");
+  }
+  for feature in unstable_features {
+    if !flags.unstable_features.iter().any(|f| f == feature) {
+      flags.unstable_features.push(feature.to_string());
+    }
+  }
+
+  if flags.watch.is_some() {
+    return serve_with_watch(flags, serve_flags).await;
+  }
+
+  let ps = ProcState::from_flags(flags).await?;
+  let main_module = ps.options.resolve_main_module()?;
+  serve_with_workers(&ps, &main_module, &serve_flags).await
+}
+
+async fn serve_with_watch(
+  flags: Flags,
+  serve_flags: ServeFlags,
+) -> Result<i32, AnyError> {
+  let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+  let mut ps =
+    ProcState::from_flags_for_file_watcher(flags, sender.clone()).await?;
+  let clear_screen = !ps.options.no_clear_screen();
+  let filter = ps.options.watch_filter()?;
+  let debounce = ps.options.watch_debounce()?;
+  let before_restart = crate::tools::task::before_restart_hook(&ps.options)?;
+  let main_module = ps.options.resolve_main_module()?;
+
+  // the workers run on threads of their own, so that the requests they're
+  // handling can complete when the server is restarted
+  let running_workers: Rc<RefCell<Vec<WorkerHandle>>> = Default::default();
+  let operation = |main_module: ModuleSpecifier| {
+    ps.reset_for_file_watcher();
+    let ps = ps.clone();
+    let serve_flags = serve_flags.clone();
+    let running_workers = running_workers.clone();
+    Ok(async move {
+      // the workers of the previous run stop listening once their requests
+      // are handled, before the new workers listen on the port
+      let previous_workers = std::mem::take(&mut *running_workers.borrow_mut());
+      future::join_all(previous_workers.into_iter().map(WorkerHandle::drain))
+        .await;
+
+      let mut join_handles = Vec::new();
+      for index in 0..serve_flags.parallel.get() {
+        let (worker_handle, join_handle) =
+          spawn_worker(&ps, &main_module, &serve_flags, index);
+        running_workers.borrow_mut().push(worker_handle);
+        join_handles.push(join_handle);
+      }
+      let (result, _, _) = future::select_all(join_handles).await;
+      let workers = std::mem::take(&mut *running_workers.borrow_mut());
+      future::join_all(workers.into_iter().map(WorkerHandle::drain)).await;
+      result??;
+      Ok(())
+    })
+  };
+
+  util::file_watcher::watch_func2(
+    receiver,
+    operation,
+    main_module,
+    util::file_watcher::PrintConfig {
+      job_name: "Server".to_string(),
+      clear_screen,
+      filter,
+      debounce,
+      before_restart,
+    },
+  )
+  .await?;
+
+  Ok(0)
+}
+
+/// A worker serving requests on a thread of its own.
+struct WorkerHandle {
+  shutdown_sender: oneshot::Sender<()>,
+  stopped_receiver: oneshot::Receiver<()>,
+}
+
+impl WorkerHandle {
+  /// Stops the worker once the requests it's handling are completed.
+  async fn drain(self) {
+    let _ = self.shutdown_sender.send(());
+    let _ = self.stopped_receiver.await;
+  }
+}
+
+fn spawn_worker(
+  ps: &ProcState,
+  main_module: &ModuleSpecifier,
+  serve_flags: &ServeFlags,
+  index: usize,
+) -> (WorkerHandle, JoinHandle<Result<i32, AnyError>>) {
+  let (shutdown_sender, shutdown_receiver) = oneshot::channel();
+  let (stopped_sender, stopped_receiver) = oneshot::channel();
+  let ps = ps.clone();
+  let main_module = main_module.clone();
+  let serve_flags = serve_flags.clone();
+  let join_handle = tokio::task::spawn_blocking(move || {
+    let result = run_local(run_worker(
+      &ps,
+      &main_module,
+      &serve_flags,
+      index,
+      Some(shutdown_receiver),
+    ));
+    drop(stopped_sender);
+    result
+  });
+  let worker_handle = WorkerHandle {
+    shutdown_sender,
+    stopped_receiver,
+  };
+  (worker_handle, join_handle)
+}
+
+/// Runs the first worker on the current thread and the others on threads of
+/// their own, until any of them stops.
+async fn serve_with_workers(
+  ps: &ProcState,
+  main_module: &ModuleSpecifier,
+  serve_flags: &ServeFlags,
+) -> Result<i32, AnyError> {
+  let mut workers: Vec<LocalBoxFuture<Result<i32, AnyError>>> =
+    vec![run_worker(ps, main_module, serve_flags, 0, None).boxed_local()];
+  let mut worker_handles = Vec::new();
+  for index in 1..serve_flags.parallel.get() {
+    let (worker_handle, join_handle) =
+      spawn_worker(ps, main_module, serve_flags, index);
+    worker_handles.push(worker_handle);
+    workers.push(async move { join_handle.await? }.boxed_local());
+  }
+
+  let (result, _, _) = future::select_all(workers).await;
+  future::join_all(worker_handles.into_iter().map(WorkerHandle::drain)).await;
+  result
+}
+
+async fn run_worker(
+  ps: &ProcState,
+  main_module: &ModuleSpecifier,
+  serve_flags: &ServeFlags,
+  index: usize,
+  maybe_shutdown_receiver: Option<oneshot::Receiver<()>>,
+) -> Result<i32, AnyError> {
+  let permissions = PermissionsContainer::new(Permissions::from_options(
+    &ps.options.permissions_options(),
+  )?);
+  let mut worker =
+    create_main_worker(ps, main_module.clone(), permissions).await?;
+  let namespace = worker.execute_main_module_for_namespace().await?;
+  let mut worker = worker.into_main_worker();
+
+  let start_server = worker.js_runtime.execute_script(
+    located_script_name!(),
+    START_SERVER_SOURCE.to_string().into(),
+  )?;
+  let drain = {
+    let scope = &mut worker.js_runtime.handle_scope();
+    let namespace = v8::Local::new(scope, namespace);
+    let start_server = v8::Local::new(scope, start_server);
+    let start_server = v8::Local::<v8::Function>::try_from(start_server)?;
+    let options = serde_v8::to_v8(
+      scope,
+      json!({
+        "hostname": serve_flags.host,
+        "port": serve_flags.port,
+        "reusePort": serve_flags.parallel.get() > 1,
+        // only the first worker prints where the server is listening
+        "printListening": index == 0,
+      }),
+    )?;
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let recv = v8::undefined(tc_scope).into();
+    match start_server.call(tc_scope, recv, &[namespace.into(), options]) {
+      Some(drain) => {
+        let drain = v8::Local::<v8::Function>::try_from(drain)?;
+        v8::Global::new(tc_scope, drain)
+      }
+      None => {
+        let exception = tc_scope.exception().unwrap();
+        return Err(JsError::from_v8_exception(tc_scope, exception).into());
+      }
+    }
+  };
+  worker.dispatch_load_event(located_script_name!())?;
+
+  let is_shutdown = match maybe_shutdown_receiver {
+    Some(shutdown_receiver) => {
+      tokio::select! {
+        _ = shutdown_receiver => true,
+        result = worker.run_event_loop(false) => {
+          result?;
+          false
+        }
+      }
+    }
+    None => {
+      worker.run_event_loop(false).await?;
+      false
+    }
+  };
+  if is_shutdown {
+    drain_requests(&mut worker, drain).await?;
+    return Ok(0);
+  }
+  worker.dispatch_unload_event(located_script_name!())?;
+  Ok(worker.exit_code())
+}
+
+/// Waits for the requests the worker is handling to complete and closes its
+/// server, or gives up after `DRAIN_TIMEOUT`.
+async fn drain_requests(
+  worker: &mut MainWorker,
+  drain: v8::Global<v8::Function>,
+) -> Result<(), AnyError> {
+  let promise = {
+    let scope = &mut worker.js_runtime.handle_scope();
+    let drain = v8::Local::new(scope, drain);
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let recv = v8::undefined(tc_scope).into();
+    match drain.call(tc_scope, recv, &[]) {
+      Some(promise) => v8::Global::new(tc_scope, promise),
+      None => {
+        let exception = tc_scope.exception().unwrap();
+        return Err(JsError::from_v8_exception(tc_scope, exception).into());
+      }
+    }
+  };
+  match tokio::time::timeout(
+    DRAIN_TIMEOUT,
+    worker.js_runtime.resolve_value(promise),
+  )
+  .await
+  {
+    Ok(result) => {
+      result?;
+    }
+    Err(_) => log::warn!(
+      "{} The requests being handled by the server didn't complete within {} seconds.",
+      colors::yellow("Warning"),
+      DRAIN_TIMEOUT.as_secs(),
+    ),
+  }
+  Ok(())
+}
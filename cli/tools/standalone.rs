@@ -3,19 +3,27 @@
 use crate::args::CaData;
 use crate::args::CompileFlags;
 use crate::args::Flags;
+use crate::args::COMPILE_TARGETS;
 use crate::cache::DenoDir;
 use crate::graph_util::error_for_any_npm_specifier;
 use crate::http_util::HttpClient;
+use crate::standalone::find_trailer_pos;
+use crate::standalone::self_update::SelfUpdateConfig;
+use crate::standalone::virtual_fs::collect_embedded_dirs;
 use crate::standalone::Metadata;
+use crate::standalone::APP_BUNDLE_PAYLOAD_FILE_NAME;
 use crate::standalone::MAGIC_TRAILER;
+use crate::util::checksum;
 use crate::util::path::path_has_trailing_slash;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
 use crate::ProcState;
+use deno_ast::EmitOptions;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
+use deno_core::futures;
 use deno_core::resolve_url_or_path;
 use deno_core::serde_json;
 use deno_graph::ModuleSpecifier;
@@ -23,9 +31,6 @@ use deno_runtime::colors;
 use std::env;
 use std::fs;
 use std::fs::File;
-use std::io::Read;
-use std::io::Seek;
-use std::io::SeekFrom;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
@@ -39,21 +44,45 @@ pub async fn compile(
 ) -> Result<(), AnyError> {
   let ps = ProcState::from_flags(flags).await?;
   let module_specifier = ps.options.resolve_main_module()?;
+  let startup_modules = compile_flags
+    .snapshot
+    .iter()
+    .map(|specifier| resolve_url_or_path(specifier, ps.options.initial_cwd()))
+    .collect::<Result<Vec<_>, _>>()?;
+  if !startup_modules.is_empty() && compile_flags.target.is_some() {
+    // the snapshot is created by this binary, so it only works with binaries
+    // of the same version and target
+    bail!("The --snapshot flag is not supported when cross compiling with --target.");
+  }
   let module_roots = {
-    let mut vec = Vec::with_capacity(compile_flags.include.len() + 1);
+    let mut vec = Vec::with_capacity(
+      compile_flags.include.len() + startup_modules.len() + 1,
+    );
     vec.push(module_specifier.clone());
     for side_module in &compile_flags.include {
       vec.push(resolve_url_or_path(side_module, ps.options.initial_cwd())?);
     }
+    vec.extend(startup_modules.iter().cloned());
     vec
   };
-  let deno_dir = &ps.dir;
+  if ps.options.profile().map(|p| p.minify).unwrap_or(false) {
+    log::warn!(
+      "{} The \"minify\" option of the profile is not supported by \"deno compile\" and will be ignored.",
+      colors::yellow("Warning"),
+    );
+  }
 
-  let output_path = resolve_compile_executable_output_path(
-    &compile_flags,
-    ps.options.initial_cwd(),
-  )
-  .await?;
+  let output_paths = if compile_flags.all_targets {
+    resolve_all_targets_output_paths(&compile_flags, ps.options.initial_cwd())
+      .await?
+  } else {
+    let output_path = resolve_compile_executable_output_path(
+      &compile_flags,
+      ps.options.initial_cwd(),
+    )
+    .await?;
+    vec![(compile_flags.target.clone(), output_path)]
+  };
 
   let graph = Arc::try_unwrap(
     ps.module_graph_builder
@@ -66,7 +95,15 @@ pub async fn compile(
   error_for_any_npm_specifier(&graph)?;
 
   let parser = ps.parsed_source_cache.as_capturing_parser();
-  let eszip = eszip::EszipV2::from_graph(graph, &parser, Default::default())?;
+  // the source maps are inlined with the original sources, which the binary
+  // uses to point the stack traces of errors at the original lines
+  let emit_options = EmitOptions {
+    inline_source_map: true,
+    inline_sources: true,
+    source_map: false,
+    ..Default::default()
+  };
+  let eszip = eszip::EszipV2::from_graph(graph, &parser, emit_options)?;
 
   log::info!(
     "{} {}",
@@ -74,37 +111,361 @@ pub async fn compile(
     module_specifier.to_string()
   );
 
+  let eszip_archive = eszip.into_bytes();
+  for (target, output_path) in output_paths {
+    compile_for_target(
+      &ps,
+      &compile_flags,
+      target,
+      output_path,
+      eszip_archive.clone(),
+      &module_specifier,
+      &startup_modules,
+    )
+    .await?;
+  }
+  Ok(())
+}
+
+async fn compile_for_target(
+  ps: &ProcState,
+  compile_flags: &CompileFlags,
+  target: Option<String>,
+  output_path: PathBuf,
+  eszip_archive: Vec<u8>,
+  module_specifier: &ModuleSpecifier,
+  startup_modules: &[ModuleSpecifier],
+) -> Result<(), AnyError> {
   // Select base binary based on target
-  let original_binary =
-    get_base_binary(&ps.http_client, deno_dir, compile_flags.target.clone())
-      .await?;
+  let mut original_binary =
+    get_base_binary(&ps.http_client, &ps.dir, target.as_deref()).await?;
+
+  let target_os = TargetOs::resolve(&target);
+  let is_customized = compile_flags.icon.is_some()
+    || !compile_flags.metadata.is_empty()
+    || compile_flags.sign.is_some();
+  if is_customized && target_os == TargetOs::Linux {
+    bail!("The --icon, --metadata and --sign flags are only supported when compiling for Windows or macOS.");
+  }
+
+  if target_os == TargetOs::MacOs && is_customized {
+    // the executable of the app bundle is the unmodified base binary, which
+    // keeps it signable
+    let payload = create_standalone_binary(
+      Vec::new(),
+      eszip_archive,
+      module_specifier.clone(),
+      startup_modules,
+      compile_flags,
+      ps,
+    )
+    .await?;
+    let bundle_path = output_path.with_file_name(format!(
+      "{}.app",
+      output_path.file_name().unwrap().to_string_lossy()
+    ));
+    log::info!("{} {}", colors::green("Emit"), bundle_path.display());
+    write_app_bundle(
+      &bundle_path,
+      &output_path,
+      original_binary,
+      payload,
+      compile_flags,
+    )?;
+    if let Some(identity) = &compile_flags.sign {
+      codesign(&bundle_path, identity)?;
+    }
+    return Ok(());
+  }
+
+  if target_os == TargetOs::Windows
+    && (compile_flags.icon.is_some() || !compile_flags.metadata.is_empty())
+  {
+    original_binary = set_windows_resources(original_binary, compile_flags)?;
+  }
 
   let final_bin = create_standalone_binary(
     original_binary,
-    eszip,
-    module_specifier,
-    &compile_flags,
+    eszip_archive,
+    module_specifier.clone(),
+    startup_modules,
+    compile_flags,
     ps,
   )
   .await?;
 
   log::info!("{} {}", colors::green("Emit"), output_path.display());
 
-  write_standalone_binary(output_path, final_bin).await?;
+  write_standalone_binary(output_path.clone(), final_bin).await?;
+  if let Some(identity) = &compile_flags.sign {
+    signtool(&output_path, identity)?;
+  }
+  Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetOs {
+  Linux,
+  MacOs,
+  Windows,
+}
+
+impl TargetOs {
+  fn resolve(target: &Option<String>) -> Self {
+    let (is_windows, is_macos) = match target {
+      Some(target) => (target.contains("windows"), target.contains("apple")),
+      None => (cfg!(windows), cfg!(target_os = "macos")),
+    };
+    if is_windows {
+      TargetOs::Windows
+    } else if is_macos {
+      TargetOs::MacOs
+    } else {
+      TargetOs::Linux
+    }
+  }
+}
+
+fn metadata_value<'a>(
+  compile_flags: &'a CompileFlags,
+  key: &str,
+) -> Option<&'a str> {
+  compile_flags
+    .metadata
+    .iter()
+    .rev()
+    .find(|(k, _)| k == key)
+    .map(|(_, value)| value.as_str())
+}
+
+/// Runs a signing or resource tool, erroring with its output when it fails.
+fn run_tool(
+  mut command: std::process::Command,
+  name: &str,
+  hint: &str,
+) -> Result<(), AnyError> {
+  let output = match command.output() {
+    Ok(output) => output,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+      bail!("`{name}` was not found in your PATH. {hint}")
+    }
+    Err(err) => return Err(err.into()),
+  };
+  if !output.status.success() {
+    bail!(
+      "`{name}` failed: {}{}",
+      String::from_utf8_lossy(&output.stdout).trim(),
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+  }
   Ok(())
 }
 
+/// Sets the icon and version information of a Windows executable with
+/// rcedit, before the bundle is appended to it.
+fn set_windows_resources(
+  original_bin: Vec<u8>,
+  compile_flags: &CompileFlags,
+) -> Result<Vec<u8>, AnyError> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let exe_path = temp_dir.path().join("deno.exe");
+  fs::write(&exe_path, original_bin)?;
+
+  let mut command = std::process::Command::new("rcedit");
+  command.arg(&exe_path);
+  if let Some(icon) = &compile_flags.icon {
+    command.arg("--set-icon").arg(icon);
+  }
+  if let Some(version) = metadata_value(compile_flags, "version") {
+    command.args(["--set-file-version", version]);
+    command.args(["--set-product-version", version]);
+  }
+  for (key, resource_name) in [
+    ("name", "ProductName"),
+    ("description", "FileDescription"),
+    ("company", "CompanyName"),
+    ("copyright", "LegalCopyright"),
+  ] {
+    if let Some(value) = metadata_value(compile_flags, key) {
+      command.args(["--set-version-string", resource_name, value]);
+    }
+  }
+  run_tool(
+    command,
+    "rcedit",
+    "It sets the icon and metadata of Windows executables, see https://github.com/electron/rcedit",
+  )?;
+  Ok(fs::read(&exe_path)?)
+}
+
+fn signtool(output_path: &Path, identity: &str) -> Result<(), AnyError> {
+  let mut command = std::process::Command::new("signtool");
+  command.args(["sign", "/fd", "SHA256"]);
+  if identity.is_empty() {
+    command.arg("/a");
+  } else {
+    command.args(["/n", identity]);
+  }
+  command.arg(output_path);
+  run_tool(
+    command,
+    "signtool",
+    "It is part of the Windows SDK, and Windows executables must be signed on Windows.",
+  )?;
+  log::info!("{} {}", colors::green("Signed"), output_path.display());
+  Ok(())
+}
+
+/// The entitlements that V8 needs under the hardened runtime of macOS, which
+/// is required for notarization.
+const MACOS_ENTITLEMENTS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>com.apple.security.cs.allow-jit</key>
+  <true/>
+  <key>com.apple.security.cs.allow-unsigned-executable-memory</key>
+  <true/>
+  <key>com.apple.security.cs.disable-library-validation</key>
+  <true/>
+</dict>
+</plist>
+"#;
+
+fn codesign(bundle_path: &Path, identity: &str) -> Result<(), AnyError> {
+  let temp_dir = tempfile::TempDir::new()?;
+  let entitlements_path = temp_dir.path().join("entitlements.plist");
+  fs::write(&entitlements_path, MACOS_ENTITLEMENTS)?;
+  let mut command = std::process::Command::new("codesign");
+  command
+    .args(["--force", "--options", "runtime", "--entitlements"])
+    .arg(&entitlements_path)
+    .arg("--sign")
+    .arg(if identity.is_empty() { "-" } else { identity })
+    .arg(bundle_path);
+  run_tool(
+    command,
+    "codesign",
+    "It is part of the Xcode command line tools, and macOS executables must be signed on macOS.",
+  )?;
+  log::info!("{} {}", colors::green("Signed"), bundle_path.display());
+  Ok(())
+}
+
+/// Writes a macOS app bundle with the base binary as its executable and the
+/// bundle, snapshot and metadata in its resources.
+fn write_app_bundle(
+  bundle_path: &Path,
+  output_path: &Path,
+  original_bin: Vec<u8>,
+  payload: Vec<u8>,
+  compile_flags: &CompileFlags,
+) -> Result<(), AnyError> {
+  let contents_dir = bundle_path.join("Contents");
+  let resources_dir = contents_dir.join("Resources");
+  if bundle_path.exists() {
+    // make sure we don't overwrite an app bundle that deno didn't create
+    if !resources_dir.join(APP_BUNDLE_PAYLOAD_FILE_NAME).exists() {
+      bail!(
+        "Could not compile to '{}' because it already exists and was not created by deno compile. Please delete it or use the `--output <file-path>` flag to provide an alternative name.",
+        bundle_path.display()
+      );
+    }
+    fs::remove_dir_all(bundle_path)?;
+  }
+  let executable_dir = contents_dir.join("MacOS");
+  fs::create_dir_all(&executable_dir)?;
+  fs::create_dir_all(&resources_dir)?;
+
+  let executable_name = output_path
+    .file_name()
+    .unwrap()
+    .to_string_lossy()
+    .to_string();
+  let executable_path = executable_dir.join(&executable_name);
+  fs::write(&executable_path, original_bin)?;
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(&executable_path, fs::Permissions::from_mode(0o777))?;
+  }
+  fs::write(resources_dir.join(APP_BUNDLE_PAYLOAD_FILE_NAME), payload)?;
+
+  let mut entries = vec![
+    ("CFBundleExecutable", executable_name.clone()),
+    (
+      "CFBundleIdentifier",
+      metadata_value(compile_flags, "identifier")
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("land.deno.compiled.{executable_name}")),
+    ),
+    (
+      "CFBundleName",
+      metadata_value(compile_flags, "name")
+        .unwrap_or(&executable_name)
+        .to_string(),
+    ),
+    ("CFBundlePackageType", "APPL".to_string()),
+  ];
+  if let Some(version) = metadata_value(compile_flags, "version") {
+    entries.push(("CFBundleShortVersionString", version.to_string()));
+    entries.push(("CFBundleVersion", version.to_string()));
+  }
+  if let Some(description) = metadata_value(compile_flags, "description") {
+    entries.push(("CFBundleGetInfoString", description.to_string()));
+  }
+  if let Some(copyright) = metadata_value(compile_flags, "copyright") {
+    entries.push(("NSHumanReadableCopyright", copyright.to_string()));
+  }
+  if let Some(icon) = &compile_flags.icon {
+    let icon_file_name = format!("{executable_name}.icns");
+    fs::copy(icon, resources_dir.join(&icon_file_name))
+      .with_context(|| format!("Failed copying the icon {}", icon.display()))?;
+    entries.push(("CFBundleIconFile", icon_file_name));
+  }
+  fs::write(contents_dir.join("Info.plist"), info_plist(&entries))?;
+  Ok(())
+}
+
+fn info_plist(entries: &[(&str, String)]) -> String {
+  fn escape(text: &str) -> String {
+    text
+      .replace('&', "&amp;")
+      .replace('<', "&lt;")
+      .replace('>', "&gt;")
+  }
+
+  let mut text = String::from(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+"#,
+  );
+  for (key, value) in entries {
+    text.push_str(&format!(
+      "  <key>{}</key>\n  <string>{}</string>\n",
+      key,
+      escape(value)
+    ));
+  }
+  text.push_str("</dict>\n</plist>\n");
+  text
+}
+
 async fn get_base_binary(
   client: &HttpClient,
   deno_dir: &DenoDir,
-  target: Option<String>,
+  target: Option<&str>,
 ) -> Result<Vec<u8>, AnyError> {
-  if target.is_none() {
-    let path = std::env::current_exe()?;
-    return Ok(tokio::fs::read(path).await?);
-  }
-
-  let target = target.unwrap_or_else(|| env!("TARGET").to_string());
+  let target = match target {
+    Some(target) if target != env!("TARGET") => target,
+    // the current executable is the base binary of its own target
+    _ => {
+      let path = std::env::current_exe()?;
+      return Ok(tokio::fs::read(path).await?);
+    }
+  };
   let binary_name = format!("deno-{target}.zip");
 
   let binary_path_suffix = if crate::version::is_canary() {
@@ -115,13 +476,42 @@ async fn get_base_binary(
 
   let download_directory = deno_dir.dl_folder_path();
   let binary_path = download_directory.join(&binary_path_suffix);
+  let checksum_path =
+    download_directory.join(format!("{binary_path_suffix}.sha256sum"));
 
-  if !binary_path.exists() {
-    download_base_binary(client, &download_directory, &binary_path_suffix)
-      .await?;
+  let mut maybe_archive_data = match tokio::fs::read(&binary_path).await {
+    Ok(archive_data) => Some(archive_data),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+    Err(err) => return Err(err.into()),
+  };
+  if let Some(archive_data) = &maybe_archive_data {
+    // an archive without its checksum, like one of an interrupted download,
+    // is downloaded again as well
+    let result = match tokio::fs::read_to_string(&checksum_path).await {
+      Ok(checksum_text) => {
+        checksum::verify(archive_data, &checksum_text, &binary_name)
+      }
+      Err(err) => Err(AnyError::from(err).context(format!(
+        "Failed reading the checksum of the cached {binary_name}"
+      ))),
+    };
+    if let Err(err) = result {
+      log::warn!(
+        "{} {:#}, downloading it again.",
+        colors::yellow("Warning"),
+        err
+      );
+      maybe_archive_data = None;
+    }
   }
+  let archive_data = match maybe_archive_data {
+    Some(archive_data) => archive_data,
+    None => {
+      download_base_binary(client, &download_directory, &binary_path_suffix)
+        .await?
+    }
+  };
 
-  let archive_data = tokio::fs::read(binary_path).await?;
   let temp_dir = tempfile::TempDir::new()?;
   let base_binary_path = crate::tools::upgrade::unpack_into_dir(
     archive_data,
@@ -137,15 +527,20 @@ async fn download_base_binary(
   client: &HttpClient,
   output_directory: &Path,
   binary_path_suffix: &str,
-) -> Result<(), AnyError> {
+) -> Result<Vec<u8>, AnyError> {
   let download_url = format!("https://dl.deno.land/{binary_path_suffix}");
-  let maybe_bytes = {
+  let checksum_url = format!("{download_url}.sha256sum");
+  let (maybe_bytes, maybe_checksum) = {
     let progress_bars = ProgressBar::new(ProgressBarStyle::DownloadBars);
     let progress = progress_bars.update(&download_url);
-
-    client
+    let maybe_bytes = client
       .download_with_progress(download_url, &progress)
-      .await?
+      .await?;
+    let progress = progress_bars.update(&checksum_url);
+    let maybe_checksum = client
+      .download_with_progress(checksum_url, &progress)
+      .await?;
+    (maybe_bytes, maybe_checksum)
   };
   let bytes = match maybe_bytes {
     Some(bytes) => bytes,
@@ -155,24 +550,37 @@ async fn download_base_binary(
     }
   };
 
+  let binary_name = binary_path_suffix.rsplit('/').next().unwrap();
+  let checksum_text = match maybe_checksum {
+    Some(checksum) => String::from_utf8(checksum)?,
+    None => bail!(
+      "No checksum was published for {}, so it can't be verified.",
+      binary_name
+    ),
+  };
+  checksum::verify(&bytes, &checksum_text, binary_name)?;
+
   std::fs::create_dir_all(output_directory)?;
   let output_path = output_directory.join(binary_path_suffix);
   std::fs::create_dir_all(output_path.parent().unwrap())?;
-  tokio::fs::write(output_path, bytes).await?;
-  Ok(())
+  tokio::fs::write(&output_path, &bytes).await?;
+  let checksum_path =
+    output_directory.join(format!("{binary_path_suffix}.sha256sum"));
+  tokio::fs::write(checksum_path, checksum_text).await?;
+  Ok(bytes)
 }
 
 /// This functions creates a standalone deno binary by appending a bundle
-/// and magic trailer to the currently executing binary.
+/// and magic trailer to the currently executing binary. When startup modules
+/// are provided, a V8 startup snapshot of them is appended after the bundle.
 async fn create_standalone_binary(
   mut original_bin: Vec<u8>,
-  eszip: eszip::EszipV2,
+  mut eszip_archive: Vec<u8>,
   entrypoint: ModuleSpecifier,
+  startup_modules: &[ModuleSpecifier],
   compile_flags: &CompileFlags,
-  ps: ProcState,
+  ps: &ProcState,
 ) -> Result<Vec<u8>, AnyError> {
-  let mut eszip_archive = eszip.into_bytes();
-
   let ca_data = match ps.options.ca_data() {
     Some(CaData::File(ca_file)) => {
       Some(fs::read(ca_file).with_context(|| format!("Reading: {ca_file}"))?)
@@ -185,9 +593,17 @@ async fn create_standalone_binary(
     .resolve_import_map(&ps.file_fetcher)
     .await?
     .map(|import_map| (import_map.base_url().clone(), import_map.to_json()));
-  let metadata = Metadata {
+  let include_dirs = compile_flags
+    .include_dirs
+    .iter()
+    .map(|dir| ps.options.initial_cwd().join(dir))
+    .collect::<Vec<_>>();
+  let (embedded_dirs, mut embedded_data) =
+    collect_embedded_dirs(&include_dirs)?;
+  let mut metadata = Metadata {
     argv: compile_flags.args.clone(),
     unstable: ps.options.unstable(),
+    unstable_features: ps.options.unstable_features(),
     seed: ps.options.seed(),
     location: ps.options.location_flag().clone(),
     permissions: ps.options.permissions_options(),
@@ -201,11 +617,42 @@ async fn create_standalone_binary(
     ca_data,
     entrypoint,
     maybe_import_map,
+    startup_snapshot_len: None,
+    embedded_dirs,
+    self_update: compile_flags
+      .self_update
+      .as_ref()
+      .map(SelfUpdateConfig::from_flags)
+      .transpose()?,
   };
+  let mut startup_snapshot = if startup_modules.is_empty() {
+    Vec::new()
+  } else {
+    let reader = futures::io::BufReader::new(futures::io::Cursor::new(
+      eszip_archive.clone(),
+    ));
+    let (eszip, loader) = eszip::EszipV2::parse(reader)
+      .await
+      .context("Failed to parse eszip header")?;
+    loader.await.context("Failed to parse eszip archive")?;
+    crate::standalone::create_startup_snapshot(
+      ps,
+      eszip,
+      &metadata,
+      startup_modules,
+    )
+    .await?
+  };
+  if !startup_snapshot.is_empty() {
+    metadata.startup_snapshot_len = Some(startup_snapshot.len() as u64);
+  }
   let mut metadata = serde_json::to_string(&metadata)?.as_bytes().to_vec();
 
   let eszip_pos = original_bin.len();
-  let metadata_pos = eszip_pos + eszip_archive.len();
+  let metadata_pos = eszip_pos
+    + eszip_archive.len()
+    + embedded_data.len()
+    + startup_snapshot.len();
   let mut trailer = MAGIC_TRAILER.to_vec();
   trailer.write_all(&eszip_pos.to_be_bytes())?;
   trailer.write_all(&metadata_pos.to_be_bytes())?;
@@ -215,6 +662,8 @@ async fn create_standalone_binary(
   );
   final_bin.append(&mut original_bin);
   final_bin.append(&mut eszip_archive);
+  final_bin.append(&mut embedded_data);
+  final_bin.append(&mut startup_snapshot);
   final_bin.append(&mut metadata);
   final_bin.append(&mut trailer);
 
@@ -241,17 +690,9 @@ async fn write_standalone_binary(
     }
 
     // Make sure we don't overwrite any file not created by Deno compiler.
-    // Check for magic trailer in last 24 bytes.
-    let mut has_trailer = false;
+    // Check for the magic trailer, which may be followed by a signature.
     let mut output_file = File::open(&output_path)?;
-    // This seek may fail because the file is too small to possibly be
-    // `deno compile` output.
-    if output_file.seek(SeekFrom::End(-24)).is_ok() {
-      let mut trailer = [0; 24];
-      output_file.read_exact(&mut trailer)?;
-      let (magic_trailer, _) = trailer.split_at(8);
-      has_trailer = magic_trailer == MAGIC_TRAILER;
-    }
+    let has_trailer = find_trailer_pos(&mut output_file)?.is_some();
     if !has_trailer {
       bail!(
         concat!(
@@ -327,6 +768,40 @@ async fn resolve_compile_executable_output_path(
   })
 }
 
+/// Resolves an output path for every target, which is the inferred name
+/// suffixed with the target in the output directory.
+async fn resolve_all_targets_output_paths(
+  compile_flags: &CompileFlags,
+  current_dir: &Path,
+) -> Result<Vec<(Option<String>, PathBuf)>, AnyError> {
+  let module_specifier =
+    resolve_url_or_path(&compile_flags.source_file, current_dir)?;
+  let name = match infer_name_from_url(&module_specifier).await {
+    Some(name) => name,
+    None => {
+      bail!("An executable name could not be inferred from the URL. Aborting.")
+    }
+  };
+  let output_dir = match &compile_flags.output {
+    Some(output) => current_dir.join(output),
+    None => current_dir.to_path_buf(),
+  };
+  Ok(
+    COMPILE_TARGETS
+      .iter()
+      .map(|target| {
+        let target = target.to_string();
+        let output_path = output_dir.join(format!("{name}-{target}"));
+        let target = Some(target);
+        (
+          target.clone(),
+          get_os_specific_filepath(output_path, &target),
+        )
+      })
+      .collect(),
+  )
+}
+
 fn get_os_specific_filepath(
   output: PathBuf,
   target: &Option<String>,
@@ -360,6 +835,13 @@ mod test {
         args: Vec::new(),
         target: Some("x86_64-unknown-linux-gnu".to_string()),
         include: vec![],
+        snapshot: vec![],
+        icon: None,
+        metadata: vec![],
+        sign: None,
+        all_targets: false,
+        include_dirs: vec![],
+        self_update: None,
       },
       &std::env::current_dir().unwrap(),
     )
@@ -381,6 +863,13 @@ mod test {
         args: Vec::new(),
         target: Some("x86_64-pc-windows-msvc".to_string()),
         include: vec![],
+        snapshot: vec![],
+        icon: None,
+        metadata: vec![],
+        sign: None,
+        all_targets: false,
+        include_dirs: vec![],
+        self_update: None,
       },
       &std::env::current_dir().unwrap(),
     )
@@ -415,4 +904,75 @@ mod test {
     run_test("C:\\my-exe.0.1.2", Some("windows"), "C:\\my-exe.0.1.2.exe");
     run_test("my-exe-0.1.2", Some("linux"), "my-exe-0.1.2");
   }
+
+  #[tokio::test]
+  async fn resolve_all_targets_output_paths_suffixes_target() {
+    let paths = resolve_all_targets_output_paths(
+      &CompileFlags {
+        source_file: "mod.ts".to_string(),
+        output: Some(PathBuf::from("dist")),
+        args: Vec::new(),
+        target: None,
+        include: vec![],
+        snapshot: vec![],
+        icon: None,
+        metadata: vec![],
+        sign: None,
+        all_targets: true,
+        include_dirs: vec![],
+        self_update: None,
+      },
+      &std::env::current_dir().unwrap().join("my-app"),
+    )
+    .await
+    .unwrap();
+    let file_names = paths
+      .iter()
+      .map(|(_, path)| path.file_name().unwrap().to_string_lossy().to_string())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      file_names,
+      vec![
+        "my-app-x86_64-unknown-linux-gnu",
+        "my-app-x86_64-pc-windows-msvc.exe",
+        "my-app-x86_64-apple-darwin",
+        "my-app-aarch64-apple-darwin",
+      ]
+    );
+    assert!(paths
+      .iter()
+      .all(|(_, path)| path.parent().unwrap().ends_with("dist")));
+  }
+
+  #[test]
+  fn test_info_plist_escapes_values() {
+    let text = info_plist(&[
+      ("CFBundleExecutable", "my-app".to_string()),
+      (
+        "NSHumanReadableCopyright",
+        "Copyright <Me> & Co".to_string(),
+      ),
+    ]);
+    assert!(text.contains(
+      "  <key>CFBundleExecutable</key>\n  <string>my-app</string>\n"
+    ));
+    assert!(text.contains("<string>Copyright &lt;Me&gt; &amp; Co</string>"));
+    assert!(text.ends_with("</dict>\n</plist>\n"));
+  }
+
+  #[test]
+  fn test_target_os_resolve() {
+    assert_eq!(
+      TargetOs::resolve(&Some("x86_64-pc-windows-msvc".to_string())),
+      TargetOs::Windows
+    );
+    assert_eq!(
+      TargetOs::resolve(&Some("aarch64-apple-darwin".to_string())),
+      TargetOs::MacOs
+    );
+    assert_eq!(
+      TargetOs::resolve(&Some("x86_64-unknown-linux-gnu".to_string())),
+      TargetOs::Linux
+    );
+  }
 }
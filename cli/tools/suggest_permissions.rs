@@ -0,0 +1,317 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use deno_runtime::colors;
+use serde::Deserialize;
+
+use crate::args::ConfigFile;
+use crate::args::Flags;
+use crate::args::PermissionSetConfig;
+use crate::args::PermissionSetValue;
+use crate::args::SuggestPermissionsFlags;
+use crate::tools::deps::format_config_text;
+use crate::tools::deps::set_entry_in_config_text;
+use crate::util::path::relative_specifier;
+use crate::util::path::specifier_to_file_path;
+
+/// The order the permissions are printed in.
+const PERMISSION_NAMES: [&str; 8] =
+  ["read", "write", "net", "env", "sys", "run", "ffi", "hrtime"];
+
+/// A permission check from the trace of `--trace-permissions`.
+#[derive(Deserialize)]
+struct TracedCheck {
+  permission: String,
+  value: Option<String>,
+}
+
+/// The permissions a program used, where a permission without values was
+/// checked entirely (ex. `Deno.env.toObject()`).
+#[derive(Debug, Default, PartialEq)]
+struct SuggestedPermissions(BTreeMap<String, Option<BTreeSet<String>>>);
+
+impl SuggestedPermissions {
+  /// Aggregates the checks, where the paths in the current working directory
+  /// are made relative to it.
+  fn from_checks(
+    checks: impl IntoIterator<Item = TracedCheck>,
+    cwd: &Path,
+  ) -> Self {
+    let mut permissions: BTreeMap<String, Option<BTreeSet<String>>> =
+      BTreeMap::new();
+    for check in checks {
+      let value = match check.value {
+        Some(value) if is_path_permission(&check.permission) => {
+          Some(match Path::new(&value).strip_prefix(cwd) {
+            Ok(path) if path.as_os_str().is_empty() => ".".to_string(),
+            Ok(path) => path.display().to_string(),
+            Err(_) => value,
+          })
+        }
+        value => value,
+      };
+      let entry = permissions
+        .entry(check.permission)
+        .or_insert_with(|| Some(BTreeSet::new()));
+      match (entry, value) {
+        (Some(values), Some(value)) => {
+          values.insert(value);
+        }
+        (entry, None) => *entry = None,
+        (None, Some(_)) => {}
+      }
+    }
+    Self(permissions)
+  }
+
+  fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  fn sorted(&self) -> impl Iterator<Item = (&str, &Option<BTreeSet<String>>)> {
+    PERMISSION_NAMES
+      .iter()
+      .filter_map(|name| self.0.get(*name).map(|values| (*name, values)))
+  }
+
+  /// The minimal `--allow-*` flags that grant the permissions.
+  fn to_flags(&self) -> Vec<String> {
+    self
+      .sorted()
+      .map(|(name, values)| match values {
+        Some(values) if name != "hrtime" => format!(
+          "--allow-{name}={}",
+          values.iter().cloned().collect::<Vec<_>>().join(",")
+        ),
+        _ => format!("--allow-{name}"),
+      })
+      .collect()
+  }
+
+  /// Converts the permissions to a permission set of the configuration
+  /// file, with the paths made relative to it.
+  fn to_permission_set(
+    &self,
+    cwd: &Path,
+    config_specifier: &ModuleSpecifier,
+  ) -> PermissionSetConfig {
+    let mut permission_set = PermissionSetConfig::default();
+    for (name, values) in self.sorted() {
+      let is_path = is_path_permission(name);
+      let value = match values {
+        Some(values) => PermissionSetValue::List(
+          values
+            .iter()
+            .map(|value| {
+              if is_path {
+                relative_path(cwd, config_specifier, value)
+              } else {
+                value.clone()
+              }
+            })
+            .collect(),
+        ),
+        None => PermissionSetValue::All(true),
+      };
+      match name {
+        "read" => permission_set.read = Some(value),
+        "write" => permission_set.write = Some(value),
+        "net" => permission_set.net = Some(value),
+        "env" => permission_set.env = Some(value),
+        "sys" => permission_set.sys = Some(value),
+        "run" => permission_set.run = Some(value),
+        "ffi" => permission_set.ffi = Some(value),
+        "hrtime" => permission_set.hrtime = true,
+        _ => unreachable!(),
+      }
+    }
+    permission_set
+  }
+}
+
+fn is_path_permission(name: &str) -> bool {
+  matches!(name, "read" | "write" | "ffi")
+}
+
+/// Makes a path that the program used relative to the configuration file.
+fn relative_path(
+  cwd: &Path,
+  config_specifier: &ModuleSpecifier,
+  path: &str,
+) -> String {
+  ModuleSpecifier::from_file_path(cwd.join(path))
+    .ok()
+    .and_then(|specifier| relative_specifier(config_specifier, &specifier))
+    .unwrap_or_else(|| path.to_string())
+}
+
+/// Runs the program with its permission checks traced, then prints the
+/// permission flags it used and saves them as a permission set of the
+/// configuration file when `--suggest-permissions=<NAME>` is specified.
+pub async fn run_and_suggest_permissions(
+  mut flags: Flags,
+  suggest_flags: SuggestPermissionsFlags,
+) -> Result<i32, AnyError> {
+  let cwd = std::env::current_dir().context("Failed getting cwd.")?;
+  let maybe_config_file = ConfigFile::discover(&flags, &cwd)?;
+  let temp_dir = tempfile::TempDir::new()?;
+  let trace_path = temp_dir.path().join("permissions.ndjson");
+  flags.trace_permissions = Some(trace_path.clone());
+
+  // the permissions are suggested even when the program fails, as it might
+  // have failed because of a missing permission
+  let result = super::run::run_script(flags).await;
+
+  let text = match std::fs::read_to_string(&trace_path) {
+    Ok(text) => text,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+    Err(err) => return Err(err.into()),
+  };
+  let checks = text
+    .lines()
+    .map(serde_json::from_str::<TracedCheck>)
+    .collect::<Result<Vec<_>, _>>()
+    .context("Failed reading the permission trace")?;
+  let permissions = SuggestedPermissions::from_checks(checks, &cwd);
+  if permissions.is_empty() {
+    log::info!(
+      "{} the program didn't use any permissions",
+      colors::green("Suggested permissions:")
+    );
+  } else {
+    log::info!("{}", colors::green("Suggested permissions:"));
+    for flag in permissions.to_flags() {
+      log::info!("  {flag}");
+    }
+  }
+
+  if let Some(name) = suggest_flags.permission_set {
+    save_permission_set(&name, &permissions, &cwd, maybe_config_file.as_ref())?;
+  }
+  result
+}
+
+fn save_permission_set(
+  name: &str,
+  permissions: &SuggestedPermissions,
+  cwd: &Path,
+  maybe_config_file: Option<&ConfigFile>,
+) -> Result<(), AnyError> {
+  let config_path = match maybe_config_file {
+    Some(config_file) => specifier_to_file_path(&config_file.specifier)?,
+    None => cwd.join("deno.json"),
+  };
+  let config_specifier = ModuleSpecifier::from_file_path(&config_path)
+    .map_err(|_| {
+      deno_core::anyhow::anyhow!(
+        "Invalid config path {}",
+        config_path.display()
+      )
+    })?;
+  let text = match std::fs::read_to_string(&config_path) {
+    Ok(text) => text,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => "{}".to_string(),
+    Err(err) => {
+      return Err(err).with_context(|| {
+        format!("Unable to read config file: {}", config_path.display())
+      })
+    }
+  };
+  let fmt_options = maybe_config_file
+    .and_then(|config_file| config_file.to_fmt_config().ok())
+    .unwrap_or_default()
+    .unwrap_or_default()
+    .options;
+  let permission_set = permissions.to_permission_set(cwd, &config_specifier);
+  let text = set_entry_in_config_text(
+    &text,
+    "permissions",
+    name,
+    &serde_json::to_string(&permission_set)?,
+  )?;
+  std::fs::write(&config_path, format_config_text(text, &fmt_options))
+    .with_context(|| {
+      format!("Failed writing config file: {}", config_path.display())
+    })?;
+  log::info!(
+    "Saved the permissions as the permission set \"{}\" of {}. Grant them with --permission-set={}",
+    name,
+    config_path.display(),
+    name,
+  );
+  Ok(())
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  fn check(permission: &str, value: Option<&str>) -> TracedCheck {
+    TracedCheck {
+      permission: permission.to_string(),
+      value: value.map(|value| value.to_string()),
+    }
+  }
+
+  #[test]
+  fn suggests_minimal_flags() {
+    let cwd = Path::new("/project");
+    let permissions = SuggestedPermissions::from_checks(
+      [
+        check("net", Some("deno.land")),
+        check("read", Some("/project/b.txt")),
+        check("read", Some("/project/a.txt")),
+        check("read", Some("/project/a.txt")),
+        check("read", Some("/etc/hosts")),
+        check("env", Some("HOME")),
+        check("env", None),
+        check("env", Some("PATH")),
+        check("hrtime", None),
+      ],
+      cwd,
+    );
+    assert_eq!(
+      permissions.to_flags(),
+      vec![
+        "--allow-read=/etc/hosts,a.txt,b.txt",
+        "--allow-net=deno.land",
+        "--allow-env",
+        "--allow-hrtime",
+      ]
+    );
+  }
+
+  #[test]
+  fn converts_to_permission_set() {
+    let cwd = test_util::TempDir::new();
+    let config_specifier =
+      ModuleSpecifier::from_file_path(cwd.path().join("deno.json")).unwrap();
+    let permissions = SuggestedPermissions::from_checks(
+      [
+        check("read", Some("data.txt")),
+        check("net", Some("localhost:8000")),
+        check("run", None),
+      ],
+      cwd.path(),
+    );
+    assert_eq!(
+      serde_json::to_value(
+        permissions.to_permission_set(cwd.path(), &config_specifier)
+      )
+      .unwrap(),
+      serde_json::json!({
+        "read": ["./data.txt"],
+        "net": ["localhost:8000"],
+        "run": true,
+      })
+    );
+  }
+}
@@ -1,26 +1,42 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::args::CliOptions;
+use crate::args::ConfigFile;
 use crate::args::Flags;
 use crate::args::TaskFlags;
+use crate::args::TaskRecursiveFlags;
 use crate::colors;
 use crate::node::CliNodeResolver;
 use crate::npm::NpmPackageResolver;
 use crate::proc_state::ProcState;
+use crate::util::file_watcher::RestartHook;
 use crate::util::fs::canonicalize_path;
+use crate::util::path::specifier_parent;
+use crate::util::path::specifier_to_file_path;
+use crate::util::telemetry;
 use deno_core::anyhow::bail;
 use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures;
 use deno_core::futures::future::LocalBoxFuture;
+use deno_core::futures::FutureExt;
+use deno_core::serde_json;
 use deno_semver::npm::NpmPackageNv;
 use deno_task_shell::ExecuteResult;
 use deno_task_shell::ShellCommand;
 use deno_task_shell::ShellCommandContext;
+use deno_task_shell::ShellPipeReader;
+use deno_task_shell::ShellState;
 use indexmap::IndexMap;
+use regex::Regex;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
 
 pub async fn execute_script(
   flags: Flags,
@@ -42,6 +58,10 @@ pub async fn execute_script(
     }
   };
 
+  if let Some(recursive_flags) = &task_flags.recursive {
+    return execute_recursive(&ps, task_name, recursive_flags).await;
+  }
+
   if let Some(script) = tasks_config.get(task_name) {
     let config_file_url = ps.options.maybe_config_file_specifier().unwrap();
     let config_file_path = if config_file_url.scheme() == "file" {
@@ -58,9 +78,11 @@ pub async fn execute_script(
     let seq_list = deno_task_shell::parser::parse(&script)
       .with_context(|| format!("Error parsing script '{task_name}'."))?;
     let env_vars = collect_env_vars();
+    let mut span = task_span(task_name);
     let exit_code =
       deno_task_shell::execute(seq_list, env_vars, &cwd, Default::default())
         .await;
+    span.set_exit_code(exit_code);
     Ok(exit_code)
   } else if let Some(script) = package_json_scripts.get(task_name) {
     if let Some(package_deps) = ps.package_json_deps_installer.package_deps() {
@@ -102,8 +124,10 @@ pub async fn execute_script(
     let npx_commands =
       resolve_npm_commands(&ps.npm_resolver, &ps.node_resolver)?;
     let env_vars = collect_env_vars();
+    let mut span = task_span(task_name);
     let exit_code =
       deno_task_shell::execute(seq_list, env_vars, &cwd, npx_commands).await;
+    span.set_exit_code(exit_code);
     Ok(exit_code)
   } else {
     eprintln!("Task not found: {task_name}");
@@ -112,6 +136,412 @@ pub async fn execute_script(
   }
 }
 
+/// A member of the workspace of the configuration file.
+struct WorkspaceMember {
+  /// The directory of the member relative to the workspace root.
+  name: String,
+  dir: PathBuf,
+  config_file: ConfigFile,
+}
+
+/// Runs the task in the selected workspace members that define it, with
+/// the members that others depend on running first.
+async fn execute_recursive(
+  ps: &ProcState,
+  task_name: &str,
+  recursive_flags: &TaskRecursiveFlags,
+) -> Result<i32, AnyError> {
+  let config_file = match ps.options.maybe_config_file() {
+    Some(config_file) => config_file,
+    None => bail!("No config file found"),
+  };
+  let root_dir =
+    specifier_to_file_path(&specifier_parent(&config_file.specifier))?;
+  let member_dirs = config_file.to_workspace_members()?;
+  if member_dirs.is_empty() {
+    bail!("No \"workspace\" members found in the configuration file.");
+  }
+  let members = member_dirs
+    .into_iter()
+    .map(|dir| read_workspace_member(&root_dir, dir))
+    .collect::<Result<Vec<_>, _>>()?;
+  let dependencies = resolve_member_dependencies(&members);
+  let order = sort_members(&members, &dependencies)?;
+
+  let mut selected = (0..members.len()).collect::<HashSet<_>>();
+  if let Some(filter) = &recursive_flags.filter {
+    let filter = filter_to_regex(filter)?;
+    selected.retain(|index| {
+      let name = &members[*index].name;
+      filter.is_match(name)
+        || filter.is_match(name.rsplit('/').next().unwrap_or(name))
+    });
+  }
+  if let Some(git_ref) = &recursive_flags.changed {
+    let changed = changed_members(&root_dir, &members, &dependencies, git_ref)?;
+    selected.retain(|index| changed.contains(index));
+  }
+
+  // the tasks of the members can use the same commands as the tasks of the
+  // root, like the binaries of the npm packages
+  ps.package_json_deps_installer
+    .ensure_top_level_install()
+    .await?;
+  ps.npm_resolver.resolve_pending().await?;
+  let npm_commands = resolve_npm_commands(&ps.npm_resolver, &ps.node_resolver)?;
+  let env_vars = collect_env_vars();
+  let mut had_task = false;
+  for index in order {
+    if !selected.contains(&index) {
+      continue;
+    }
+    let member = &members[index];
+    let tasks_config = member.config_file.resolve_tasks_config()?;
+    let script = match tasks_config.get(task_name) {
+      Some(script) => script,
+      None => continue,
+    };
+    had_task = true;
+    let script = get_script_with_args(script, &ps.options);
+    log::info!(
+      "{} {} {} {}",
+      colors::green("Task"),
+      colors::gray(format!("[{}]", member.name)),
+      colors::cyan(&task_name),
+      script,
+    );
+    let seq_list =
+      deno_task_shell::parser::parse(&script).with_context(|| {
+        format!("Error parsing script '{task_name}' of '{}'.", member.name)
+      })?;
+    let mut span = task_span(task_name);
+    span.set_attribute("task.member", &member.name);
+    let exit_code = execute_with_prefix(
+      seq_list,
+      env_vars.clone(),
+      npm_commands.clone(),
+      &member.dir,
+      &member.name,
+    )
+    .await;
+    span.set_exit_code(exit_code);
+    drop(span);
+    if exit_code != 0 {
+      return Ok(exit_code);
+    }
+  }
+  if !had_task {
+    eprintln!("Task not found in the selected workspace members: {task_name}");
+    return Ok(1);
+  }
+  Ok(0)
+}
+
+fn read_workspace_member(
+  root_dir: &Path,
+  dir: PathBuf,
+) -> Result<WorkspaceMember, AnyError> {
+  let name = match dir.strip_prefix(root_dir) {
+    Ok(path) if path.as_os_str().is_empty() => ".".to_string(),
+    Ok(path) => path.to_string_lossy().replace('\\', "/"),
+    Err(_) => dir.to_string_lossy().to_string(),
+  };
+  let config_path = ["deno.json", "deno.jsonc"]
+    .iter()
+    .map(|file_name| dir.join(file_name))
+    .find(|path| path.is_file());
+  let config_file = match config_path {
+    Some(config_path) => ConfigFile::read(&config_path)?,
+    None => bail!(
+      "Could not find a deno.json or deno.jsonc file in the workspace member '{}'.",
+      name
+    ),
+  };
+  Ok(WorkspaceMember {
+    name,
+    dir,
+    config_file,
+  })
+}
+
+/// Gets the indexes of the members each member depends on, which are the
+/// members whose directory is mapped in its "imports".
+fn resolve_member_dependencies(members: &[WorkspaceMember]) -> Vec<Vec<usize>> {
+  members
+    .iter()
+    .enumerate()
+    .map(|(index, member)| {
+      let imports = match &member.config_file.json.imports {
+        Some(serde_json::Value::Object(imports)) => imports,
+        _ => return Vec::new(),
+      };
+      let config_dir = specifier_parent(&member.config_file.specifier);
+      let paths = imports
+        .values()
+        .filter_map(|value| value.as_str())
+        .filter_map(|value| config_dir.join(value).ok())
+        .filter(|specifier| specifier.scheme() == "file")
+        .filter_map(|specifier| specifier_to_file_path(&specifier).ok())
+        .collect::<Vec<_>>();
+      members
+        .iter()
+        .enumerate()
+        .filter(|(other_index, other)| {
+          *other_index != index
+            && paths.iter().any(|path| path.starts_with(&other.dir))
+        })
+        .map(|(other_index, _)| other_index)
+        .collect()
+    })
+    .collect()
+}
+
+/// Sorts the members so that they come after the members they depend on,
+/// otherwise keeping the order of the "workspace".
+fn sort_members(
+  members: &[WorkspaceMember],
+  dependencies: &[Vec<usize>],
+) -> Result<Vec<usize>, AnyError> {
+  fn visit(
+    index: usize,
+    members: &[WorkspaceMember],
+    dependencies: &[Vec<usize>],
+    visiting: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+  ) -> Result<(), AnyError> {
+    if order.contains(&index) {
+      return Ok(());
+    }
+    if let Some(position) = visiting.iter().position(|i| *i == index) {
+      let cycle = visiting[position..]
+        .iter()
+        .chain(std::iter::once(&index))
+        .map(|i| members[*i].name.as_str())
+        .collect::<Vec<_>>();
+      bail!(
+        "The workspace members depend on each other in a cycle: {}",
+        cycle.join(" -> ")
+      );
+    }
+    visiting.push(index);
+    for dependency in &dependencies[index] {
+      visit(*dependency, members, dependencies, visiting, order)?;
+    }
+    visiting.pop();
+    order.push(index);
+    Ok(())
+  }
+
+  let mut order = Vec::with_capacity(members.len());
+  for index in 0..members.len() {
+    visit(index, members, dependencies, &mut Vec::new(), &mut order)?;
+  }
+  Ok(order)
+}
+
+/// Converts a `--filter` pattern, where `*` matches any text, to a regex.
+fn filter_to_regex(filter: &str) -> Result<Regex, AnyError> {
+  let pattern = regex::escape(filter).replace("\\*", ".*");
+  Ok(Regex::new(&format!("^{pattern}$"))?)
+}
+
+/// Gets the members with files changed since the git ref, including the
+/// uncommitted and untracked ones, along with the members depending on them.
+fn changed_members(
+  root_dir: &Path,
+  members: &[WorkspaceMember],
+  dependencies: &[Vec<usize>],
+  git_ref: &str,
+) -> Result<HashSet<usize>, AnyError> {
+  let mut changed_files = Vec::new();
+  for args in [
+    vec!["diff", "--name-only", "--relative", git_ref],
+    vec!["ls-files", "--others", "--exclude-standard"],
+  ] {
+    let output = std::process::Command::new("git")
+      .args(&args)
+      .current_dir(root_dir)
+      .output()
+      .context("Failed running git to find the changed workspace members")?;
+    if !output.status.success() {
+      bail!(
+        "Failed getting the changed files from git: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+      );
+    }
+    changed_files.extend(
+      String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| root_dir.join(line)),
+    );
+  }
+
+  let mut changed = members
+    .iter()
+    .enumerate()
+    .filter(|(_, member)| {
+      changed_files
+        .iter()
+        .any(|file| file.starts_with(&member.dir))
+    })
+    .map(|(index, _)| index)
+    .collect::<HashSet<_>>();
+  loop {
+    let changed_count = changed.len();
+    for (index, member_dependencies) in dependencies.iter().enumerate() {
+      if member_dependencies.iter().any(|d| changed.contains(d)) {
+        changed.insert(index);
+      }
+    }
+    if changed.len() == changed_count {
+      return Ok(changed);
+    }
+  }
+}
+
+/// Starts the telemetry span of running the script of a task.
+fn task_span(task_name: &str) -> telemetry::Span {
+  let mut span = telemetry::span("subprocess");
+  span.set_attribute("task.name", task_name);
+  span
+}
+
+/// Executes the script, prefixing each line of its output with the name
+/// of the workspace member.
+async fn execute_with_prefix(
+  seq_list: deno_task_shell::parser::SequentialList,
+  env_vars: HashMap<String, String>,
+  custom_commands: HashMap<String, Rc<dyn ShellCommand>>,
+  cwd: &Path,
+  name: &str,
+) -> i32 {
+  let prefix = format!("{} ", colors::gray(format!("[{name}]")));
+  let (stdout_reader, stdout_writer) = deno_task_shell::pipe();
+  let (stderr_reader, stderr_writer) = deno_task_shell::pipe();
+  let stdout_handle =
+    spawn_prefixed_output(stdout_reader, prefix.clone(), std::io::stdout());
+  let stderr_handle =
+    spawn_prefixed_output(stderr_reader, prefix, std::io::stderr());
+  let state = ShellState::new(env_vars, cwd, custom_commands);
+  let exit_code = deno_task_shell::execute_with_pipes(
+    seq_list,
+    state,
+    ShellPipeReader::stdin(),
+    stdout_writer,
+    stderr_writer,
+  )
+  .await;
+  // the writers were dropped, so the output threads finish after
+  // reading what's left in the pipes
+  let _ = stdout_handle.join();
+  let _ = stderr_handle.join();
+  exit_code
+}
+
+fn spawn_prefixed_output<W: Write + Send + 'static>(
+  reader: ShellPipeReader,
+  prefix: String,
+  writer: W,
+) -> JoinHandle<()> {
+  std::thread::spawn(move || {
+    let mut writer = PrefixedWriter::new(prefix, writer);
+    let _ = reader.pipe_to(&mut writer);
+  })
+}
+
+/// Writes a prefix at the start of every line.
+struct PrefixedWriter<W: Write> {
+  prefix: String,
+  inner: W,
+  at_line_start: bool,
+}
+
+impl<W: Write> PrefixedWriter<W> {
+  pub fn new(prefix: String, inner: W) -> Self {
+    Self {
+      prefix,
+      inner,
+      at_line_start: true,
+    }
+  }
+}
+
+impl<W: Write> Write for PrefixedWriter<W> {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    for line in buf.split_inclusive(|b| *b == b'\n') {
+      if self.at_line_start {
+        self.inner.write_all(self.prefix.as_bytes())?;
+      }
+      self.inner.write_all(line)?;
+      self.at_line_start = line.ends_with(b"\n");
+    }
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    self.inner.flush()
+  }
+}
+
+/// Creates the hook that runs the `"beforeRestart"` task of the `"watch"`
+/// configuration before a subcommand restarts in watch mode. The task can use
+/// the binaries of the npm packages like the tasks run by `deno task`.
+pub fn before_restart_hook(
+  cli_options: &Arc<CliOptions>,
+) -> Result<Option<RestartHook>, AnyError> {
+  let config_file = match cli_options.maybe_config_file() {
+    Some(config_file) => config_file,
+    None => return Ok(None),
+  };
+  let task_name = match config_file.to_watch_config()?.before_restart {
+    Some(task_name) => task_name,
+    None => return Ok(None),
+  };
+  let script = match config_file.resolve_tasks_config()?.get(&task_name) {
+    Some(script) => script.clone(),
+    None => bail!(
+      "The \"beforeRestart\" task '{}' of the \"watch\" configuration was not found in the \"tasks\".\n  Config: {}",
+      task_name,
+      config_file.specifier,
+    ),
+  };
+  let cwd = specifier_to_file_path(&specifier_parent(&config_file.specifier))?;
+  let cli_options = cli_options.clone();
+  Ok(Some(Rc::new(move || {
+    let task_name = task_name.clone();
+    let script = script.clone();
+    let cwd = cwd.clone();
+    let cli_options = cli_options.clone();
+    async move {
+      output_task(&task_name, &script);
+      let seq_list = deno_task_shell::parser::parse(&script)
+        .with_context(|| format!("Error parsing script '{task_name}'."))?;
+      let ps = ProcState::from_cli_options(cli_options).await?;
+      ps.package_json_deps_installer
+        .ensure_top_level_install()
+        .await?;
+      ps.npm_resolver.resolve_pending().await?;
+      let npm_commands =
+        resolve_npm_commands(&ps.npm_resolver, &ps.node_resolver)?;
+      let mut span = task_span(&task_name);
+      let exit_code = deno_task_shell::execute(
+        seq_list,
+        collect_env_vars(),
+        &cwd,
+        npm_commands,
+      )
+      .await;
+      span.set_exit_code(exit_code);
+      drop(span);
+      if exit_code != 0 {
+        bail!("Task '{}' failed with exit code {}.", task_name, exit_code);
+      }
+      Ok(())
+    }
+    .boxed_local()
+  })))
+}
+
 fn get_script_with_args(script: &str, options: &CliOptions) -> String {
   let additional_args = options
     .argv()
@@ -256,3 +686,29 @@ fn resolve_npm_commands(
   }
   Ok(result)
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn prefixed_writer() {
+    let mut writer = PrefixedWriter::new("[a] ".to_string(), Vec::new());
+    writer.write_all(b"one\ntw").unwrap();
+    writer.write_all(b"o\n\nthree").unwrap();
+    assert_eq!(
+      String::from_utf8(writer.inner).unwrap(),
+      "[a] one\n[a] two\n[a] \n[a] three"
+    );
+  }
+
+  #[test]
+  fn filter_patterns() {
+    let filter = filter_to_regex("packages/*").unwrap();
+    assert!(filter.is_match("packages/app"));
+    assert!(!filter.is_match("tools/app"));
+    let filter = filter_to_regex("app").unwrap();
+    assert!(filter.is_match("app"));
+    assert!(!filter.is_match("apps"));
+  }
+}
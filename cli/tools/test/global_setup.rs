@@ -0,0 +1,178 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::proc_state::ProcState;
+use crate::worker::create_main_worker;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::ModuleSpecifier;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+use deno_runtime::tokio_util::run_local;
+use deno_runtime::worker::MainWorker;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// The global setup and teardown modules of a test run, which are run once in
+/// a worker of their own that is kept alive between them, so that the servers
+/// they start keep serving the test modules.
+///
+/// The default export of the setup module is called before the test modules
+/// and the JSON serializable value it returns is passed to the test workers.
+/// The default export of the teardown module is called with that value after
+/// the test modules completed, or when the run is interrupted with Ctrl+C.
+pub struct GlobalSetup {
+  context: Arc<serde_json::Value>,
+  teardown_sender: oneshot::Sender<()>,
+  join_handle: JoinHandle<Result<(), AnyError>>,
+}
+
+impl GlobalSetup {
+  pub async fn start(
+    ps: &ProcState,
+    permissions: &Permissions,
+    maybe_setup: Option<ModuleSpecifier>,
+    maybe_teardown: Option<ModuleSpecifier>,
+  ) -> Result<Self, AnyError> {
+    let (context_sender, context_receiver) = oneshot::channel();
+    let (teardown_sender, teardown_receiver) = oneshot::channel();
+    let ps = ps.clone();
+    let permissions = permissions.clone();
+    let join_handle = tokio::task::spawn_blocking(move || {
+      run_local(run(
+        ps,
+        permissions,
+        maybe_setup,
+        maybe_teardown,
+        context_sender,
+        teardown_receiver,
+      ))
+    });
+    match context_receiver.await {
+      Ok(context) => Ok(Self {
+        context: Arc::new(context),
+        teardown_sender,
+        join_handle,
+      }),
+      // the setup failed, so the worker already returned its error
+      Err(_) => {
+        join_handle.await??;
+        bail!("The global setup stopped without returning a context.")
+      }
+    }
+  }
+
+  /// The value returned by the global setup, which is `null` when there is
+  /// no global setup module or it returned nothing.
+  pub fn context(&self) -> Arc<serde_json::Value> {
+    self.context.clone()
+  }
+
+  /// Runs the global teardown and stops the worker.
+  pub async fn teardown(self) -> Result<(), AnyError> {
+    // the worker may have already stopped with an error, which is returned
+    // below
+    let _ = self.teardown_sender.send(());
+    self.join_handle.await?
+  }
+}
+
+async fn run(
+  ps: ProcState,
+  permissions: Permissions,
+  maybe_setup: Option<ModuleSpecifier>,
+  maybe_teardown: Option<ModuleSpecifier>,
+  context_sender: oneshot::Sender<serde_json::Value>,
+  mut teardown_receiver: oneshot::Receiver<()>,
+) -> Result<(), AnyError> {
+  let main_module = maybe_setup
+    .clone()
+    .or_else(|| maybe_teardown.clone())
+    .unwrap();
+  let worker = create_main_worker(
+    &ps,
+    main_module,
+    PermissionsContainer::new(permissions),
+  )
+  .await?;
+  let mut worker = worker.into_main_worker();
+
+  let context = match &maybe_setup {
+    Some(setup) => call_default_export(&mut worker, setup, None)
+      .await
+      .with_context(|| format!("Failed running global setup {setup}"))?,
+    None => serde_json::Value::Null,
+  };
+  if context_sender.send(context.clone()).is_err() {
+    return Ok(());
+  }
+
+  // keep the servers and other resources of the setup alive until the test
+  // modules completed
+  let maybe_event_loop_result = tokio::select! {
+    _ = &mut teardown_receiver => None,
+    result = worker.run_event_loop(false) => Some(result),
+  };
+  let event_loop_result = match maybe_event_loop_result {
+    Some(result) => {
+      let _ = teardown_receiver.await;
+      result.context("Uncaught error in global setup")
+    }
+    None => Ok(()),
+  };
+
+  // the teardown is run even when the setup had an uncaught error, so that
+  // it can release what the setup acquired
+  if let Some(teardown) = &maybe_teardown {
+    call_default_export(&mut worker, teardown, Some(&context))
+      .await
+      .with_context(|| format!("Failed running global teardown {teardown}"))?;
+  }
+  event_loop_result
+}
+
+/// Calls the default export of the module with the argument and returns the
+/// value it resolves to.
+async fn call_default_export(
+  worker: &mut MainWorker,
+  specifier: &ModuleSpecifier,
+  maybe_arg: Option<&serde_json::Value>,
+) -> Result<serde_json::Value, AnyError> {
+  let id = worker.preload_side_module(specifier).await?;
+  worker.evaluate_module(id).await?;
+  let namespace = worker.js_runtime.get_module_namespace(id)?;
+  let promise = {
+    let scope = &mut worker.js_runtime.handle_scope();
+    let namespace = v8::Local::new(scope, namespace);
+    let key = v8::String::new(scope, "default").unwrap();
+    let default_export = namespace.get(scope, key.into()).unwrap();
+    let func = match v8::Local::<v8::Function>::try_from(default_export) {
+      Ok(func) => func,
+      Err(_) => bail!("The module must default export a function."),
+    };
+    let args = match maybe_arg {
+      Some(arg) => vec![serde_v8::to_v8(scope, arg)?],
+      None => vec![],
+    };
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let recv = v8::undefined(tc_scope).into();
+    match func.call(tc_scope, recv, &args) {
+      Some(value) => v8::Global::new(tc_scope, value),
+      None => {
+        let exception = tc_scope.exception().unwrap();
+        return Err(JsError::from_v8_exception(tc_scope, exception).into());
+      }
+    }
+  };
+  let value = worker.js_runtime.resolve_value(promise).await?;
+  let scope = &mut worker.js_runtime.handle_scope();
+  let value = v8::Local::new(scope, value);
+  serde_v8::from_v8(scope, value)
+    .context("The returned value must be JSON serializable.")
+}
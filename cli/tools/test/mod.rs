@@ -3,9 +3,10 @@
 use crate::args::CliOptions;
 use crate::args::FilesConfig;
 use crate::args::TestOptions;
+use crate::args::TestReporterKind;
+use crate::args::TestShard;
 use crate::args::TypeCheckMode;
 use crate::colors;
-use crate::display;
 use crate::file_fetcher::File;
 use crate::graph_util::graph_valid_with_cli_options;
 use crate::ops;
@@ -18,6 +19,7 @@ use crate::util::path::get_extension;
 use crate::util::path::is_supported_ext;
 use crate::util::path::mapped_specifier_for_tsc;
 use crate::worker::create_custom_worker;
+use crate::worker::CliMainWorker;
 
 use deno_ast::swc::common::comments::CommentKind;
 use deno_ast::MediaType;
@@ -31,8 +33,8 @@ use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::located_script_name;
 use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
 use deno_core::serde_v8;
-use deno_core::url::Url;
 use deno_core::v8;
 use deno_core::ModuleSpecifier;
 use deno_runtime::deno_io::Stdio;
@@ -41,6 +43,7 @@ use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::permissions::Permissions;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::tokio_util::run_local;
+use deno_runtime::worker::MainWorker;
 use indexmap::IndexMap;
 use indexmap::IndexSet;
 use log::Level;
@@ -50,11 +53,8 @@ use rand::SeedableRng;
 use regex::Regex;
 use serde::Deserialize;
 use std::cell::RefCell;
-use std::collections::BTreeMap;
-use std::collections::BTreeSet;
-use std::collections::HashMap;
 use std::collections::HashSet;
-use std::fmt::Write as _;
+use std::collections::VecDeque;
 use std::io::Read;
 use std::io::Write;
 use std::num::NonZeroUsize;
@@ -64,13 +64,22 @@ use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::time::Duration;
 use std::time::Instant;
 use std::time::SystemTime;
 use tokio::signal;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::WeakUnboundedSender;
+use tokio::sync::oneshot;
+
+mod global_setup;
+mod reporters;
+mod snapshot;
+
+use global_setup::GlobalSetup;
+use reporters::create_reporter;
+use reporters::format_test_step_ancestry;
+pub use snapshot::SnapshotStore;
 
 /// The test mode is used to determine how a specifier is to be tested.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -335,11 +344,42 @@ pub struct TestSummary {
   pub uncaught_errors: Vec<(String, Box<JsError>)>,
 }
 
+/// The outcome of a test run. Failed tests are only turned into an error by
+/// [`TestReport::into_result`], so callers can inspect the failures.
+#[derive(Debug)]
+pub struct TestReport {
+  pub summary: TestSummary,
+  /// If a test or test step used the "only" option.
+  pub used_only: bool,
+}
+
+impl TestReport {
+  pub fn into_result(self) -> Result<(), AnyError> {
+    if self.used_only {
+      return Err(generic_error(
+        "Test failed because the \"only\" option was used",
+      ));
+    }
+
+    if self.summary.failed > 0 {
+      return Err(generic_error("Test failed"));
+    }
+
+    Ok(())
+  }
+}
+
 #[derive(Debug, Clone)]
 struct TestSpecifierOptions {
   concurrent_jobs: NonZeroUsize,
   fail_fast: Option<NonZeroUsize>,
   filter: TestFilter,
+  isolate_pool: bool,
+  reporter: TestReporterKind,
+  reporter_output: Option<PathBuf>,
+  shard: Option<TestShard>,
+  global_setup: Option<ModuleSpecifier>,
+  global_teardown: Option<ModuleSpecifier>,
 }
 
 impl TestSummary {
@@ -364,488 +404,6 @@ impl TestSummary {
   }
 }
 
-struct PrettyTestReporter {
-  parallel: bool,
-  echo_output: bool,
-  in_new_line: bool,
-  scope_test_id: Option<usize>,
-  cwd: Url,
-  did_have_user_output: bool,
-  started_tests: bool,
-  child_results_buffer:
-    HashMap<usize, IndexMap<usize, (TestStepDescription, TestStepResult, u64)>>,
-}
-
-impl PrettyTestReporter {
-  fn new(parallel: bool, echo_output: bool) -> PrettyTestReporter {
-    PrettyTestReporter {
-      parallel,
-      echo_output,
-      in_new_line: true,
-      scope_test_id: None,
-      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
-      did_have_user_output: false,
-      started_tests: false,
-      child_results_buffer: Default::default(),
-    }
-  }
-
-  fn force_report_wait(&mut self, description: &TestDescription) {
-    if !self.in_new_line {
-      println!();
-    }
-    if self.parallel {
-      print!(
-        "{}",
-        colors::gray(format!(
-          "{} => ",
-          self.to_relative_path_or_remote_url(&description.origin)
-        ))
-      );
-    }
-    print!("{} ...", description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.scope_test_id = Some(description.id);
-  }
-
-  fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
-    let url = Url::parse(path_or_url).unwrap();
-    if url.scheme() == "file" {
-      if let Some(mut r) = self.cwd.make_relative(&url) {
-        if !r.starts_with("../") {
-          r = format!("./{r}");
-        }
-        return r;
-      }
-    }
-    path_or_url.to_string()
-  }
-
-  fn force_report_step_wait(&mut self, description: &TestStepDescription) {
-    self.write_output_end();
-    if !self.in_new_line {
-      println!();
-    }
-    print!("{}{} ...", "  ".repeat(description.level), description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.scope_test_id = Some(description.id);
-  }
-
-  fn force_report_step_result(
-    &mut self,
-    description: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-  ) {
-    self.write_output_end();
-    if self.in_new_line || self.scope_test_id != Some(description.id) {
-      self.force_report_step_wait(description);
-    }
-
-    if !self.parallel {
-      let child_results = self
-        .child_results_buffer
-        .remove(&description.id)
-        .unwrap_or_default();
-      for (desc, result, elapsed) in child_results.values() {
-        self.force_report_step_result(desc, result, *elapsed);
-      }
-      if !child_results.is_empty() {
-        self.force_report_step_wait(description);
-      }
-    }
-
-    let status = match &result {
-      TestStepResult::Ok => colors::green("ok").to_string(),
-      TestStepResult::Ignored => colors::yellow("ignored").to_string(),
-      TestStepResult::Failed(failure) => failure.format_label(),
-    };
-    print!(" {}", status);
-    if let TestStepResult::Failed(failure) = result {
-      if let Some(inline_summary) = failure.format_inline_summary() {
-        print!(" ({})", inline_summary)
-      }
-    }
-    if !matches!(result, TestStepResult::Failed(TestFailure::Incomplete)) {
-      print!(
-        " {}",
-        colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-      );
-    }
-    println!();
-    self.in_new_line = true;
-    if self.parallel {
-      self.scope_test_id = None;
-    } else {
-      self.scope_test_id = Some(description.parent_id);
-    }
-    self
-      .child_results_buffer
-      .entry(description.parent_id)
-      .or_default()
-      .remove(&description.id);
-  }
-
-  fn write_output_end(&mut self) {
-    if self.did_have_user_output {
-      println!("{}", colors::gray("----- output end -----"));
-      self.in_new_line = true;
-      self.did_have_user_output = false;
-    }
-  }
-
-  fn report_register(&mut self, _description: &TestDescription) {}
-
-  fn report_plan(&mut self, plan: &TestPlan) {
-    if self.parallel {
-      return;
-    }
-    let inflection = if plan.total == 1 { "test" } else { "tests" };
-    println!(
-      "{}",
-      colors::gray(format!(
-        "running {} {} from {}",
-        plan.total,
-        inflection,
-        self.to_relative_path_or_remote_url(&plan.origin)
-      ))
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_wait(&mut self, description: &TestDescription) {
-    if !self.parallel {
-      self.force_report_wait(description);
-    }
-    self.started_tests = true;
-  }
-
-  fn report_output(&mut self, output: &[u8]) {
-    if !self.echo_output {
-      return;
-    }
-
-    if !self.did_have_user_output && self.started_tests {
-      self.did_have_user_output = true;
-      if !self.in_new_line {
-        println!();
-      }
-      println!("{}", colors::gray("------- output -------"));
-      self.in_new_line = true;
-    }
-
-    // output everything to stdout in order to prevent
-    // stdout and stderr racing
-    std::io::stdout().write_all(output).unwrap();
-  }
-
-  fn report_result(
-    &mut self,
-    description: &TestDescription,
-    result: &TestResult,
-    elapsed: u64,
-  ) {
-    if self.parallel {
-      self.force_report_wait(description);
-    }
-
-    self.write_output_end();
-    if self.in_new_line || self.scope_test_id != Some(description.id) {
-      self.force_report_wait(description);
-    }
-
-    let status = match result {
-      TestResult::Ok => colors::green("ok").to_string(),
-      TestResult::Ignored => colors::yellow("ignored").to_string(),
-      TestResult::Failed(failure) => failure.format_label(),
-      TestResult::Cancelled => colors::gray("cancelled").to_string(),
-    };
-    print!(" {}", status);
-    if let TestResult::Failed(failure) = result {
-      if let Some(inline_summary) = failure.format_inline_summary() {
-        print!(" ({})", inline_summary)
-      }
-    }
-    println!(
-      " {}",
-      colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-    );
-    self.in_new_line = true;
-    self.scope_test_id = None;
-  }
-
-  fn report_uncaught_error(&mut self, origin: &str, _error: &JsError) {
-    if !self.in_new_line {
-      println!();
-    }
-    println!(
-      "Uncaught error from {} {}",
-      self.to_relative_path_or_remote_url(origin),
-      colors::red("FAILED")
-    );
-    self.in_new_line = true;
-    self.did_have_user_output = false;
-  }
-
-  fn report_step_register(&mut self, _description: &TestStepDescription) {}
-
-  fn report_step_wait(&mut self, description: &TestStepDescription) {
-    if !self.parallel && self.scope_test_id == Some(description.parent_id) {
-      self.force_report_step_wait(description);
-    }
-  }
-
-  fn report_step_result(
-    &mut self,
-    desc: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if self.parallel {
-      self.write_output_end();
-      print!(
-        "{} {} ...",
-        colors::gray(format!(
-          "{} =>",
-          self.to_relative_path_or_remote_url(&desc.origin)
-        )),
-        self.format_test_step_ancestry(desc, tests, test_steps)
-      );
-      self.in_new_line = false;
-      self.scope_test_id = Some(desc.id);
-      self.force_report_step_result(desc, result, elapsed);
-    } else {
-      let sibling_results =
-        self.child_results_buffer.entry(desc.parent_id).or_default();
-      if self.scope_test_id == Some(desc.id)
-        || self.scope_test_id == Some(desc.parent_id)
-      {
-        let sibling_results = std::mem::take(sibling_results);
-        self.force_report_step_result(desc, result, elapsed);
-        // Flush buffered sibling results.
-        for (desc, result, elapsed) in sibling_results.values() {
-          self.force_report_step_result(desc, result, *elapsed);
-        }
-      } else {
-        sibling_results
-          .insert(desc.id, (desc.clone(), result.clone(), elapsed));
-      }
-    }
-  }
-
-  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
-    if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
-      #[allow(clippy::type_complexity)] // Type alias doesn't look better here
-      let mut failures_by_origin: BTreeMap<
-        String,
-        (Vec<(&TestDescription, &TestFailure)>, Option<&JsError>),
-      > = BTreeMap::default();
-      let mut failure_titles = vec![];
-      for (description, failure) in &summary.failures {
-        let (failures, _) = failures_by_origin
-          .entry(description.origin.clone())
-          .or_default();
-        failures.push((description, failure));
-      }
-      for (origin, js_error) in &summary.uncaught_errors {
-        let (_, uncaught_error) =
-          failures_by_origin.entry(origin.clone()).or_default();
-        let _ = uncaught_error.insert(js_error.as_ref());
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
-      for (origin, (failures, uncaught_error)) in failures_by_origin {
-        for (description, failure) in failures {
-          if !failure.hide_in_summary() {
-            let failure_title = self.format_test_for_summary(description);
-            println!("{}", &failure_title);
-            println!("{}: {}", colors::red_bold("error"), failure.to_string());
-            println!();
-            failure_titles.push(failure_title);
-          }
-        }
-        if let Some(js_error) = uncaught_error {
-          let failure_title = format!(
-            "{} (uncaught error)",
-            self.to_relative_path_or_remote_url(&origin)
-          );
-          println!("{}", &failure_title);
-          println!(
-            "{}: {}",
-            colors::red_bold("error"),
-            format_test_error(js_error)
-          );
-          println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
-          println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
-          println!();
-          failure_titles.push(failure_title);
-        }
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("{}\n", colors::white_bold_on_red(" FAILURES "));
-      for failure_title in failure_titles {
-        println!("{failure_title}");
-      }
-    }
-
-    let status = if summary.has_failed() {
-      colors::red("FAILED").to_string()
-    } else {
-      colors::green("ok").to_string()
-    };
-
-    let get_steps_text = |count: usize| -> String {
-      if count == 0 {
-        String::new()
-      } else if count == 1 {
-        " (1 step)".to_string()
-      } else {
-        format!(" ({count} steps)")
-      }
-    };
-
-    let mut summary_result = String::new();
-
-    write!(
-      summary_result,
-      "{} passed{} | {} failed{}",
-      summary.passed,
-      get_steps_text(summary.passed_steps),
-      summary.failed,
-      get_steps_text(summary.failed_steps),
-    )
-    .unwrap();
-
-    let ignored_steps = get_steps_text(summary.ignored_steps);
-    if summary.ignored > 0 || !ignored_steps.is_empty() {
-      write!(
-        summary_result,
-        " | {} ignored{}",
-        summary.ignored, ignored_steps
-      )
-      .unwrap()
-    }
-
-    if summary.measured > 0 {
-      write!(summary_result, " | {} measured", summary.measured,).unwrap();
-    }
-
-    if summary.filtered_out > 0 {
-      write!(summary_result, " | {} filtered out", summary.filtered_out)
-        .unwrap()
-    };
-
-    println!(
-      "\n{} | {} {}\n",
-      status,
-      summary_result,
-      colors::gray(format!(
-        "({})",
-        display::human_elapsed(elapsed.as_millis())
-      )),
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_sigint(
-    &mut self,
-    tests_pending: &HashSet<usize>,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if tests_pending.is_empty() {
-      return;
-    }
-    let mut formatted_pending = BTreeSet::new();
-    for id in tests_pending {
-      if let Some(desc) = tests.get(id) {
-        formatted_pending.insert(self.format_test_for_summary(desc));
-      }
-      if let Some(desc) = test_steps.get(id) {
-        formatted_pending
-          .insert(self.format_test_step_for_summary(desc, tests, test_steps));
-      }
-    }
-    println!(
-      "\n{} The following tests were pending:\n",
-      colors::intense_blue("SIGINT")
-    );
-    for entry in formatted_pending {
-      println!("{}", entry);
-    }
-    println!();
-    self.in_new_line = true;
-  }
-
-  fn format_test_step_ancestry(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let root;
-    let mut ancestor_names = vec![];
-    let mut current_desc = desc;
-    loop {
-      if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
-        ancestor_names.push(&step_desc.name);
-        current_desc = step_desc;
-      } else {
-        root = tests.get(&current_desc.parent_id).unwrap();
-        break;
-      }
-    }
-    ancestor_names.reverse();
-    let mut result = String::new();
-    result.push_str(&root.name);
-    result.push_str(" ... ");
-    for name in ancestor_names {
-      result.push_str(name);
-      result.push_str(" ... ");
-    }
-    result.push_str(&desc.name);
-    result
-  }
-
-  fn format_test_for_summary(&self, desc: &TestDescription) -> String {
-    format!(
-      "{} {}",
-      &desc.name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
-  }
-
-  fn format_test_step_for_summary(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let long_name = self.format_test_step_ancestry(desc, tests, test_steps);
-    format!(
-      "{} {}",
-      long_name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
-  }
-}
-
 fn abbreviate_test_error(js_error: &JsError) -> JsError {
   let mut js_error = js_error.clone();
   let frames = std::mem::take(&mut js_error.frames);
@@ -913,44 +471,106 @@ pub async fn test_specifier(
   mut sender: TestEventSender,
   fail_fast_tracker: FailFastTracker,
   filter: TestFilter,
+  global_context: Arc<serde_json::Value>,
 ) -> Result<(), AnyError> {
   if fail_fast_tracker.should_stop() {
     return Ok(());
   }
+  let mut worker = create_test_worker(
+    ps,
+    permissions,
+    specifier.clone(),
+    &sender,
+    global_context,
+  )
+  .await?;
+
+  let mut coverage_collector = worker.maybe_setup_coverage_collector().await?;
+
+  // We execute the main module as a side module so that import.meta.main is not set.
+  let result = worker.execute_side_module_possibly_with_npm().await;
+  if !handle_test_module_result(result, &specifier, &mut sender)? {
+    return Ok(());
+  }
+
+  let mut worker = worker.into_main_worker();
+  run_tests_for_worker(
+    ps,
+    &mut worker,
+    &specifier,
+    &mut sender,
+    &fail_fast_tracker,
+    &filter,
+  )
+  .await?;
+
+  if let Some(coverage_collector) = coverage_collector.as_mut() {
+    worker
+      .with_event_loop(coverage_collector.stop_collecting().boxed_local())
+      .await?;
+  }
+  Ok(())
+}
+
+async fn create_test_worker(
+  ps: &ProcState,
+  permissions: Permissions,
+  specifier: ModuleSpecifier,
+  sender: &TestEventSender,
+  global_context: Arc<serde_json::Value>,
+) -> Result<CliMainWorker, AnyError> {
   let stdout = StdioPipe::File(sender.stdout());
   let stderr = StdioPipe::File(sender.stderr());
-  let mut worker = create_custom_worker(
+  create_custom_worker(
     ps,
-    specifier.clone(),
+    specifier,
     PermissionsContainer::new(permissions),
-    vec![ops::testing::deno_test::init_ops(sender.clone())],
+    vec![ops::testing::deno_test::init_ops(
+      sender.clone(),
+      ps.options.update_snapshots(),
+      global_context,
+    )],
     Stdio {
       stdin: StdioPipe::Inherit,
       stdout,
       stderr,
     },
   )
-  .await?;
-
-  let mut coverage_collector = worker.maybe_setup_coverage_collector().await?;
+  .await
+}
 
-  // We execute the main module as a side module so that import.meta.main is not set.
-  match worker.execute_side_module_possibly_with_npm().await {
-    Ok(()) => {}
+/// Reports the uncaught error of executing a test module, returning if the
+/// module was executed successfully.
+fn handle_test_module_result(
+  result: Result<(), AnyError>,
+  specifier: &ModuleSpecifier,
+  sender: &mut TestEventSender,
+) -> Result<bool, AnyError> {
+  match result {
+    Ok(()) => Ok(true),
     Err(error) => {
       if error.is::<JsError>() {
         sender.send(TestEvent::UncaughtError(
           specifier.to_string(),
           Box::new(error.downcast::<JsError>().unwrap()),
         ))?;
-        return Ok(());
+        Ok(false)
       } else {
-        return Err(error);
+        Err(error)
       }
     }
   }
+}
 
-  let mut worker = worker.into_main_worker();
+/// Runs the tests registered by the test module executed in the worker.
+async fn run_tests_for_worker(
+  ps: &ProcState,
+  worker: &mut MainWorker,
+  specifier: &ModuleSpecifier,
+  sender: &mut TestEventSender,
+  fail_fast_tracker: &FailFastTracker,
+  filter: &TestFilter,
+) -> Result<(), AnyError> {
   if ps.options.trace_ops() {
     worker.js_runtime.execute_script_static(
       located_script_name!(),
@@ -976,6 +596,9 @@ pub async fn test_specifier(
   if let Some(seed) = ps.options.shuffle_tests() {
     tests.shuffle(&mut SmallRng::seed_from_u64(seed));
   }
+  // whether every test of the module ran and passed, so that the snapshots
+  // that weren't asserted are no longer needed
+  let mut all_passed = tests.len() == unfiltered;
   sender.send(TestEvent::Plan(TestPlan {
     origin: specifier.to_string(),
     total: tests.len(),
@@ -985,13 +608,16 @@ pub async fn test_specifier(
   let mut had_uncaught_error = false;
   for (desc, function) in tests {
     if fail_fast_tracker.should_stop() {
+      all_passed = false;
       break;
     }
     if desc.ignore {
+      all_passed = false;
       sender.send(TestEvent::Result(desc.id, TestResult::Ignored, 0))?;
       continue;
     }
     if had_uncaught_error {
+      all_passed = false;
       sender.send(TestEvent::Result(desc.id, TestResult::Cancelled, 0))?;
       continue;
     }
@@ -1015,6 +641,7 @@ pub async fn test_specifier(
           fail_fast_tracker.add_failure();
           sender.send(TestEvent::Result(desc.id, TestResult::Cancelled, 0))?;
           had_uncaught_error = true;
+          all_passed = false;
           continue;
         } else {
           return Err(error);
@@ -1026,11 +653,45 @@ pub async fn test_specifier(
     let result = serde_v8::from_v8::<TestResult>(scope, result)?;
     if matches!(result, TestResult::Failed(_)) {
       fail_fast_tracker.add_failure();
+      all_passed = false;
     }
     let elapsed = SystemTime::now().duration_since(earlier)?.as_millis();
     sender.send(TestEvent::Result(desc.id, result, elapsed as u64))?;
   }
 
+  let maybe_snapshots = worker
+    .js_runtime
+    .op_state()
+    .borrow_mut()
+    .try_take::<SnapshotStore>();
+  let maybe_snapshots = match maybe_snapshots {
+    Some(snapshots) => Some(snapshots),
+    // the snapshot file of a module that no longer asserts any snapshot is
+    // removed as well
+    None
+      if all_passed
+        && ps.options.update_snapshots()
+        && specifier.scheme() == "file" =>
+    {
+      Some(SnapshotStore::load(specifier, true)?)
+    }
+    None => None,
+  };
+  if let Some(snapshots) = maybe_snapshots {
+    let path = snapshots.path().to_path_buf();
+    let obsolete = snapshots.finish(all_passed)?;
+    if all_passed && obsolete > 0 {
+      log::warn!(
+        "{} {} obsolete snapshot{} in {}. Run with --update-snapshots to remove {}.",
+        colors::yellow("Warning"),
+        obsolete,
+        if obsolete == 1 { "" } else { "s" },
+        path.display(),
+        if obsolete == 1 { "it" } else { "them" },
+      );
+    }
+  }
+
   loop {
     if !worker.dispatch_beforeunload_event(located_script_name!())? {
       break;
@@ -1038,11 +699,203 @@ pub async fn test_specifier(
     worker.run_event_loop(false).await?;
   }
   worker.dispatch_unload_event(located_script_name!())?;
+  Ok(())
+}
 
-  if let Some(coverage_collector) = coverage_collector.as_mut() {
-    worker
-      .with_event_loop(coverage_collector.stop_collecting().boxed_local())
-      .await?;
+/// Installs the function resetting the global scope of a pooled worker to its
+/// state before any test module was executed. It removes the globals and the
+/// event listeners added by a test module, restores the globals it replaced,
+/// clears its timers and closes its resources, returning `false` when some of
+/// the globals can't be restored.
+const ISOLATE_POOL_SETUP_SCRIPT: &str = r#"(() => {
+  const ownKeys = Reflect.ownKeys;
+  const getDescriptor = Reflect.getOwnPropertyDescriptor;
+  const defineProperty = Reflect.defineProperty;
+  const deleteProperty = Reflect.deleteProperty;
+  const is = Object.is;
+  const addEventListener = globalThis.addEventListener;
+  const removeEventListener = globalThis.removeEventListener;
+  const setTimeout = globalThis.setTimeout;
+  const setInterval = globalThis.setInterval;
+  const clearTimeout = globalThis.clearTimeout;
+  const { resources, close } = globalThis.Deno;
+  const eventHandlerNames = [
+    "onbeforeunload",
+    "onerror",
+    "onload",
+    "onunhandledrejection",
+    "onunload",
+  ];
+  const listeners = [];
+  const timers = new Set();
+
+  defineProperty(globalThis, Symbol.for("Deno.testIsolatePoolReset"), {
+    value: () => {
+      for (const [type, listener, options] of listeners.splice(0)) {
+        removeEventListener.call(globalThis, type, listener, options);
+      }
+      for (const name of eventHandlerNames) {
+        if (name in globalThis) {
+          globalThis[name] = null;
+        }
+      }
+      // the ids of timeouts and intervals are shared
+      for (const id of timers) {
+        clearTimeout(id);
+      }
+      timers.clear();
+      for (const rid of Object.keys(resources())) {
+        if (!baselineResources.has(rid)) {
+          try {
+            close(Number(rid));
+          } catch {
+            // it was closed while closing another one
+          }
+        }
+      }
+      let restored = true;
+      for (const key of ownKeys(globalThis)) {
+        if (!baseline.has(key) && !deleteProperty(globalThis, key)) {
+          restored = false;
+        }
+      }
+      for (const [key, descriptor] of baseline) {
+        const current = getDescriptor(globalThis, key);
+        if (
+          current === undefined || !is(current.value, descriptor.value) ||
+          current.get !== descriptor.get || current.set !== descriptor.set
+        ) {
+          if (!defineProperty(globalThis, key, descriptor)) {
+            restored = false;
+          }
+        }
+      }
+      return restored;
+    },
+  });
+  defineProperty(globalThis, "addEventListener", {
+    ...getDescriptor(globalThis, "addEventListener"),
+    value: function (type, listener, options) {
+      const target = this ?? globalThis;
+      if (target === globalThis) {
+        listeners.push([type, listener, options]);
+      }
+      return addEventListener.call(target, type, listener, options);
+    },
+  });
+  const timerFunctions = [
+    ["setTimeout", setTimeout],
+    ["setInterval", setInterval],
+  ];
+  for (const [name, set] of timerFunctions) {
+    defineProperty(globalThis, name, {
+      ...getDescriptor(globalThis, name),
+      value: {
+        [name](...args) {
+          const id = set(...args);
+          timers.add(id);
+          return id;
+        },
+      }[name],
+    });
+  }
+
+  const baseline = new Map();
+  for (const key of ownKeys(globalThis)) {
+    baseline.set(key, getDescriptor(globalThis, key));
+  }
+  const baselineResources = new Set(Object.keys(resources()));
+})();"#;
+
+/// Test specifiers one after the other reusing the worker as long as its
+/// global scope can be reset after each test module, which avoids the
+/// startup cost of a new isolate for each of them. The globals, event
+/// listeners, timers, resources and permissions of a test module are reset
+/// before the next one runs, while the modules imported by several test
+/// modules are only evaluated once per isolate.
+async fn test_specifiers_with_isolate_pool(
+  ps: &ProcState,
+  permissions: Permissions,
+  specifiers: Arc<Mutex<VecDeque<ModuleSpecifier>>>,
+  mut sender: TestEventSender,
+  fail_fast_tracker: FailFastTracker,
+  filter: TestFilter,
+  global_context: Arc<serde_json::Value>,
+) -> Result<(), AnyError> {
+  let mut maybe_worker: Option<MainWorker> = None;
+  loop {
+    let maybe_specifier = specifiers.lock().pop_front();
+    let specifier = match maybe_specifier {
+      Some(specifier) => specifier,
+      None => break,
+    };
+    if fail_fast_tracker.should_stop() {
+      break;
+    }
+
+    let (mut worker, result) = match maybe_worker.take() {
+      Some(mut worker) => {
+        // the origin of the registered tests and `Deno.mainModule`
+        worker
+          .js_runtime
+          .op_state()
+          .borrow_mut()
+          .put(specifier.clone());
+        let result = async {
+          let id = worker.preload_side_module(&specifier).await?;
+          worker.evaluate_module(id).await
+        }
+        .await;
+        (worker, result)
+      }
+      None => {
+        let mut worker = create_test_worker(
+          ps,
+          permissions.clone(),
+          specifier.clone(),
+          &sender,
+          global_context.clone(),
+        )
+        .await?;
+        worker.execute_script_static(
+          located_script_name!(),
+          ISOLATE_POOL_SETUP_SCRIPT,
+        )?;
+        let result = worker.execute_side_module_possibly_with_npm().await;
+        (worker.into_main_worker(), result)
+      }
+    };
+    if !handle_test_module_result(result, &specifier, &mut sender)? {
+      // the worker may be left in an unknown state
+      continue;
+    }
+
+    run_tests_for_worker(
+      ps,
+      &mut worker,
+      &specifier,
+      &mut sender,
+      &fail_fast_tracker,
+      &filter,
+    )
+    .await?;
+
+    let restored = worker.js_runtime.execute_script_static(
+      located_script_name!(),
+      "globalThis[Symbol.for(\"Deno.testIsolatePoolReset\")]()",
+    )?;
+    let restored = {
+      let scope = &mut worker.js_runtime.handle_scope();
+      v8::Local::new(scope, restored).is_true()
+    };
+    if restored {
+      // the permissions revoked or requested by the test module
+      let op_state = worker.js_runtime.op_state();
+      let permissions_container =
+        op_state.borrow().borrow::<PermissionsContainer>().clone();
+      *permissions_container.0.lock() = permissions.clone();
+      maybe_worker = Some(worker);
+    }
   }
   Ok(())
 }
@@ -1263,7 +1116,7 @@ pub async fn check_specifiers(
       .prepare_module_load(
         specifiers,
         false,
-        lib,
+        lib.clone(),
         PermissionsContainer::new(Permissions::allow_all()),
         PermissionsContainer::new(permissions.clone()),
       )
@@ -1302,7 +1155,7 @@ async fn test_specifiers(
   permissions: &Permissions,
   specifiers: Vec<ModuleSpecifier>,
   options: TestSpecifierOptions,
-) -> Result<(), AnyError> {
+) -> Result<TestReport, AnyError> {
   let log_level = ps.options.log_level();
   let specifiers = if let Some(seed) = ps.options.shuffle_tests() {
     let mut rng = SmallRng::seed_from_u64(seed);
@@ -1317,6 +1170,34 @@ async fn test_specifiers(
   let (sender, mut receiver) = unbounded_channel::<TestEvent>();
   let sender = TestEventSender::new(sender);
   let concurrent_jobs = options.concurrent_jobs;
+  let mut reporter = create_reporter(
+    options.reporter,
+    options.reporter_output.as_deref(),
+    concurrent_jobs.get() > 1,
+    log_level != Some(Level::Error),
+  )?;
+  if let Some(shard) = &options.shard {
+    reporter.report_shard(shard, &specifiers);
+  }
+
+  let maybe_global_setup =
+    if options.global_setup.is_some() || options.global_teardown.is_some() {
+      Some(
+        GlobalSetup::start(
+          ps,
+          permissions,
+          options.global_setup.clone(),
+          options.global_teardown.clone(),
+        )
+        .await?,
+      )
+    } else {
+      None
+    };
+  let global_context = maybe_global_setup
+    .as_ref()
+    .map(|global_setup| global_setup.context())
+    .unwrap_or_default();
 
   let sender_ = sender.downgrade();
   let sigint_handler_handle = tokio::task::spawn(async move {
@@ -1325,35 +1206,64 @@ async fn test_specifiers(
   });
   HAS_TEST_RUN_SIGINT_HANDLER.store(true, Ordering::Relaxed);
 
-  let join_handles = specifiers.into_iter().map(move |specifier| {
-    let ps = ps.clone();
-    let permissions = permissions.clone();
-    let sender = sender.clone();
-    let options = options.clone();
+  // coverage and debugging are setup for each worker
+  let use_isolate_pool = options.isolate_pool
+    && ps.options.coverage_dir().is_none()
+    && ps.maybe_inspector_server.is_none();
+  let join_handles: Box<dyn Iterator<Item = _> + '_> = if use_isolate_pool {
+    let jobs_count = concurrent_jobs.get().min(specifiers.len());
+    let specifiers = Arc::new(Mutex::new(VecDeque::from(specifiers)));
     let fail_fast_tracker = FailFastTracker::new(options.fail_fast);
-    tokio::task::spawn_blocking(move || {
-      run_local(test_specifier(
-        &ps,
-        permissions,
-        specifier,
-        sender.clone(),
-        fail_fast_tracker,
-        options.filter,
-      ))
-    })
-  });
+    Box::new((0..jobs_count).map(move |_| {
+      let ps = ps.clone();
+      let permissions = permissions.clone();
+      let specifiers = specifiers.clone();
+      let sender = sender.clone();
+      let fail_fast_tracker = fail_fast_tracker.clone();
+      let filter = options.filter.clone();
+      let global_context = global_context.clone();
+      tokio::task::spawn_blocking(move || {
+        run_local(test_specifiers_with_isolate_pool(
+          &ps,
+          permissions,
+          specifiers,
+          sender,
+          fail_fast_tracker,
+          filter,
+          global_context,
+        ))
+      })
+    }))
+  } else {
+    Box::new(specifiers.into_iter().map(move |specifier| {
+      let ps = ps.clone();
+      let permissions = permissions.clone();
+      let sender = sender.clone();
+      let options = options.clone();
+      let fail_fast_tracker = FailFastTracker::new(options.fail_fast);
+      let global_context = global_context.clone();
+      tokio::task::spawn_blocking(move || {
+        run_local(test_specifier(
+          &ps,
+          permissions,
+          specifier,
+          sender.clone(),
+          fail_fast_tracker,
+          options.filter,
+          global_context,
+        ))
+      })
+    }))
+  };
 
   let join_stream = stream::iter(join_handles)
     .buffer_unordered(concurrent_jobs.get())
     .collect::<Vec<Result<Result<(), AnyError>, tokio::task::JoinError>>>();
 
-  let mut reporter = Box::new(PrettyTestReporter::new(
-    concurrent_jobs.get() > 1,
-    log_level != Some(Level::Error),
-  ));
-
+  let (sigint_sender, sigint_receiver) = oneshot::channel::<()>();
   let handler = {
     tokio::task::spawn(async move {
+      let mut sigint_sender = Some(sigint_sender);
       let earlier = Instant::now();
       let mut tests = IndexMap::new();
       let mut test_steps = IndexMap::new();
@@ -1446,7 +1356,7 @@ async fn test_specifiers(
                   summary.failures.push((
                     TestDescription {
                       id: description.id,
-                      name: reporter.format_test_step_ancestry(
+                      name: format_test_step_ancestry(
                         description,
                         &tests,
                         &test_steps,
@@ -1480,7 +1390,10 @@ async fn test_specifiers(
               &tests,
               &test_steps,
             );
-            std::process::exit(130);
+            // the process exits once the global teardown ran
+            if let Some(sender) = sigint_sender.take() {
+              let _ = sender.send(());
+            }
           }
         }
       }
@@ -1491,30 +1404,37 @@ async fn test_specifiers(
       let elapsed = Instant::now().duration_since(earlier);
       reporter.report_summary(&summary, &elapsed);
 
-      if used_only {
-        return Err(generic_error(
-          "Test failed because the \"only\" option was used",
-        ));
-      }
+      Ok::<_, AnyError>(TestReport { summary, used_only })
+    })
+  };
 
-      if summary.failed > 0 {
-        return Err(generic_error("Test failed"));
+  let (join_results, result) = tokio::select! {
+    results = future::join(join_stream, handler) => results,
+    Ok(()) = sigint_receiver => {
+      // release what the global setup acquired before exiting, since the
+      // test modules that are still running are not awaited
+      if let Some(global_setup) = maybe_global_setup {
+        if let Err(err) = global_setup.teardown().await {
+          log::error!("{}: {:#}", colors::red_bold("error"), err);
+        }
       }
-
-      Ok(())
-    })
+      std::process::exit(130);
+    }
   };
 
-  let (join_results, result) = future::join(join_stream, handler).await;
+  // the teardown runs even when the test modules failed
+  let teardown_result = match maybe_global_setup {
+    Some(global_setup) => global_setup.teardown().await,
+    None => Ok(()),
+  };
 
   // propagate any errors
   for join_result in join_results {
     join_result??;
   }
+  teardown_result?;
 
-  result??;
-
-  Ok(())
+  result?
 }
 
 /// Checks if the path has a basename and extension Deno supports for tests.
@@ -1626,10 +1546,30 @@ async fn fetch_specifiers_with_test_mode(
   Ok(specifiers_with_mode)
 }
 
+/// Keeps the modules of the shard, which are assigned to the shards in turn
+/// after ordering them like `--shuffle` does, so that every job of a CI
+/// matrix that uses the same seed runs a different part of the modules.
+fn shard_specifiers_with_test_mode(
+  mut specifiers_with_mode: Vec<(ModuleSpecifier, TestMode)>,
+  shard: &TestShard,
+  maybe_seed: Option<u64>,
+) -> Vec<(ModuleSpecifier, TestMode)> {
+  specifiers_with_mode.sort_by(|a, b| a.0.cmp(&b.0));
+  if let Some(seed) = maybe_seed {
+    specifiers_with_mode.shuffle(&mut SmallRng::seed_from_u64(seed));
+  }
+  specifiers_with_mode
+    .into_iter()
+    .enumerate()
+    .filter(|(i, _)| i % shard.count == shard.index - 1)
+    .map(|(_, specifier_with_mode)| specifier_with_mode)
+    .collect()
+}
+
 pub async fn run_tests(
   cli_options: CliOptions,
   test_options: TestOptions,
-) -> Result<(), AnyError> {
+) -> Result<TestReport, AnyError> {
   let ps = ProcState::from_cli_options(Arc::new(cli_options)).await?;
   // Various test files should not share the same permissions in terms of
   // `PermissionsContainer` - otherwise granting/revoking permissions in one
@@ -1648,11 +1588,23 @@ pub async fn run_tests(
     return Err(generic_error("No test modules found"));
   }
 
+  let specifiers_with_mode = match &test_options.shard {
+    Some(shard) => shard_specifiers_with_test_mode(
+      specifiers_with_mode,
+      shard,
+      ps.options.shuffle_tests(),
+    ),
+    None => specifiers_with_mode,
+  };
+
   check_specifiers(&ps, permissions.clone(), specifiers_with_mode.clone())
     .await?;
 
   if test_options.no_run {
-    return Ok(());
+    return Ok(TestReport {
+      summary: TestSummary::new(),
+      used_only: false,
+    });
   }
 
   test_specifiers(
@@ -1669,11 +1621,15 @@ pub async fn run_tests(
       concurrent_jobs: test_options.concurrent_jobs,
       fail_fast: test_options.fail_fast,
       filter: TestFilter::from_flag(&test_options.filter),
+      isolate_pool: test_options.isolate_pool,
+      reporter: test_options.reporter,
+      reporter_output: test_options.reporter_output.clone(),
+      shard: test_options.shard,
+      global_setup: test_options.global_setup.clone(),
+      global_teardown: test_options.global_teardown.clone(),
     },
   )
-  .await?;
-
-  Ok(())
+  .await
 }
 
 pub async fn run_tests_with_watch(
@@ -1816,7 +1772,15 @@ pub async fn run_tests_with_watch(
         &test_options.files,
         &test_options.doc,
       )
-      .await?
+      .await?;
+      let specifiers_with_mode = match &test_options.shard {
+        Some(shard) => shard_specifiers_with_test_mode(
+          specifiers_with_mode,
+          shard,
+          ps.options.shuffle_tests(),
+        ),
+        None => specifiers_with_mode,
+      }
       .into_iter()
       .filter(|(specifier, _)| modules_to_reload.contains(specifier))
       .collect::<Vec<(ModuleSpecifier, TestMode)>>();
@@ -1842,11 +1806,16 @@ pub async fn run_tests_with_watch(
           concurrent_jobs: test_options.concurrent_jobs,
           fail_fast: test_options.fail_fast,
           filter: TestFilter::from_flag(&test_options.filter),
+          isolate_pool: test_options.isolate_pool,
+          reporter: test_options.reporter,
+          reporter_output: test_options.reporter_output.clone(),
+          shard: test_options.shard,
+          global_setup: test_options.global_setup.clone(),
+          global_teardown: test_options.global_teardown.clone(),
         },
       )
-      .await?;
-
-      Ok(())
+      .await?
+      .into_result()
     }
   };
 
@@ -1864,12 +1833,19 @@ pub async fn run_tests_with_watch(
   });
 
   let clear_screen = !ps.borrow().options.no_clear_screen();
+  let filter = ps.borrow().options.watch_filter()?;
+  let debounce = ps.borrow().options.watch_debounce()?;
+  let before_restart =
+    crate::tools::task::before_restart_hook(&ps.borrow().options)?;
   file_watcher::watch_func(
     resolver,
     operation,
     file_watcher::PrintConfig {
       job_name: "Test".to_string(),
       clear_screen,
+      filter,
+      debounce,
+      before_restart,
     },
   )
   .await?;
@@ -0,0 +1,332 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::failure_js_error;
+use super::failure_message;
+use super::write_report;
+use super::TestReporter;
+use super::TestRunRecord;
+use super::TestStepRecord;
+use crate::args::TestShard;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestLocation;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTestReport {
+  /// Set when only a shard of the test modules ran.
+  shard: Option<JsonTestShard>,
+  tests: Vec<JsonTest>,
+  uncaught_errors: Vec<JsonUncaughtError>,
+  /// The output that isn't attributed to a test.
+  output: String,
+  /// Not set when the run was interrupted.
+  summary: Option<JsonTestSummary>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTestShard {
+  index: usize,
+  count: usize,
+  files: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTest {
+  name: String,
+  origin: String,
+  location: JsonTestLocation,
+  status: &'static str,
+  /// In milliseconds.
+  duration: u64,
+  error: Option<JsonTestError>,
+  output: String,
+  steps: Vec<JsonTestStep>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTestStep {
+  name: String,
+  location: JsonTestLocation,
+  status: &'static str,
+  duration: u64,
+  error: Option<JsonTestError>,
+  steps: Vec<JsonTestStep>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTestLocation {
+  file_name: String,
+  line_number: u32,
+  column_number: u32,
+}
+
+impl From<&TestLocation> for JsonTestLocation {
+  fn from(location: &TestLocation) -> Self {
+    Self {
+      file_name: location.file_name.clone(),
+      line_number: location.line_number,
+      column_number: location.column_number,
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTestError {
+  name: Option<String>,
+  message: String,
+  frames: Vec<JsonStackFrame>,
+}
+
+impl JsonTestError {
+  fn from_failure(failure: &TestFailure) -> Self {
+    match failure_js_error(failure) {
+      Some(js_error) => Self::from_js_error(&js_error),
+      None => Self {
+        name: None,
+        message: failure_message(failure),
+        frames: Vec::new(),
+      },
+    }
+  }
+
+  fn from_js_error(js_error: &JsError) -> Self {
+    Self {
+      name: js_error.name.clone(),
+      message: js_error.exception_message.clone(),
+      frames: js_error
+        .frames
+        .iter()
+        .map(|frame| JsonStackFrame {
+          function_name: frame.function_name.clone(),
+          file_name: frame.file_name.clone(),
+          line_number: frame.line_number,
+          column_number: frame.column_number,
+        })
+        .collect(),
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonStackFrame {
+  function_name: Option<String>,
+  file_name: Option<String>,
+  line_number: Option<i64>,
+  column_number: Option<i64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonUncaughtError {
+  origin: String,
+  error: JsonTestError,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonTestSummary {
+  passed: usize,
+  failed: usize,
+  ignored: usize,
+  passed_steps: usize,
+  failed_steps: usize,
+  ignored_steps: usize,
+  filtered_out: usize,
+  measured: usize,
+  /// In milliseconds.
+  duration: u128,
+}
+
+/// Writes the results of the run as a single JSON document once it
+/// completes.
+pub struct JsonTestReporter {
+  writer: Box<dyn Write + Send>,
+  record: TestRunRecord,
+}
+
+impl JsonTestReporter {
+  pub fn new(writer: Box<dyn Write + Send>, parallel: bool) -> Self {
+    Self {
+      writer,
+      record: TestRunRecord::new(parallel),
+    }
+  }
+
+  fn write(&mut self, summary: Option<JsonTestSummary>) {
+    let report = JsonTestReport {
+      shard: self
+        .record
+        .shard
+        .as_ref()
+        .map(|(shard, files)| JsonTestShard {
+          index: shard.index,
+          count: shard.count,
+          files: files.clone(),
+        }),
+      tests: self
+        .record
+        .tests
+        .values()
+        .map(|test| {
+          let (status, error) = match &test.result {
+            Some(TestResult::Ok) => ("passed", None),
+            Some(TestResult::Ignored) => ("ignored", None),
+            Some(TestResult::Failed(failure)) => {
+              ("failed", Some(JsonTestError::from_failure(failure)))
+            }
+            Some(TestResult::Cancelled) => ("cancelled", None),
+            None => ("pending", None),
+          };
+          JsonTest {
+            name: test.description.name.clone(),
+            origin: test.description.origin.clone(),
+            location: (&test.description.location).into(),
+            status,
+            duration: test.elapsed,
+            error,
+            output: String::from_utf8_lossy(&test.output).into_owned(),
+            steps: self.steps(test.description.id),
+          }
+        })
+        .collect(),
+      uncaught_errors: self
+        .record
+        .uncaught_errors
+        .iter()
+        .map(|(origin, js_error)| JsonUncaughtError {
+          origin: origin.clone(),
+          error: JsonTestError::from_js_error(js_error),
+        })
+        .collect(),
+      output: String::from_utf8_lossy(&self.record.output).into_owned(),
+      summary,
+    };
+    let mut json = serde_json::to_vec_pretty(&report).unwrap();
+    json.push(b'\n');
+    write_report(&mut self.writer, &json);
+  }
+
+  fn steps(&self, parent_id: usize) -> Vec<JsonTestStep> {
+    self
+      .record
+      .child_steps(parent_id)
+      .into_iter()
+      .map(|step: &TestStepRecord| {
+        let (status, error) = match &step.result {
+          Some(TestStepResult::Ok) => ("passed", None),
+          Some(TestStepResult::Ignored) => ("ignored", None),
+          Some(TestStepResult::Failed(failure)) => {
+            ("failed", Some(JsonTestError::from_failure(failure)))
+          }
+          None => ("pending", None),
+        };
+        JsonTestStep {
+          name: step.description.name.clone(),
+          location: (&step.description.location).into(),
+          status,
+          duration: step.elapsed,
+          error,
+          steps: self.steps(step.description.id),
+        }
+      })
+      .collect()
+  }
+}
+
+impl TestReporter for JsonTestReporter {
+  fn report_shard(
+    &mut self,
+    shard: &TestShard,
+    specifiers: &[ModuleSpecifier],
+  ) {
+    self.record.shard(shard, specifiers);
+  }
+
+  fn report_register(&mut self, description: &TestDescription) {
+    self.record.register(description);
+  }
+
+  fn report_plan(&mut self, _plan: &TestPlan) {}
+
+  fn report_wait(&mut self, description: &TestDescription) {
+    self.record.wait(description);
+  }
+
+  fn report_output(&mut self, output: &[u8]) {
+    self.record.output(output);
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    self.record.result(description, result, elapsed);
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self.record.uncaught_error(origin, error);
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    self.record.step_register(description);
+  }
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.record.step_result(desc, result, elapsed);
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    self.write(Some(JsonTestSummary {
+      passed: summary.passed,
+      failed: summary.failed,
+      ignored: summary.ignored,
+      passed_steps: summary.passed_steps,
+      failed_steps: summary.failed_steps,
+      ignored_steps: summary.ignored_steps,
+      filtered_out: summary.filtered_out,
+      measured: summary.measured,
+      duration: elapsed.as_millis(),
+    }));
+  }
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.write(None);
+  }
+}
@@ -0,0 +1,326 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::failure_js_error;
+use super::failure_message;
+use super::strip_ansi_codes;
+use super::write_report;
+use super::TestReporter;
+use super::TestRunRecord;
+use crate::args::TestShard;
+use crate::tools::test::format_test_error;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::JsError;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::time::Duration;
+
+enum JunitOutcome {
+  Passed,
+  Skipped,
+  Failed { message: String, details: String },
+  Error { message: String, details: String },
+}
+
+struct JunitTestCase {
+  name: String,
+  /// In milliseconds.
+  elapsed: u64,
+  outcome: JunitOutcome,
+  output: String,
+}
+
+/// Writes the results of the run as a JUnit XML report once it completes,
+/// with a test suite per module. Test steps are reported as test cases named
+/// after their ancestry.
+pub struct JunitTestReporter {
+  writer: Box<dyn Write + Send>,
+  record: TestRunRecord,
+}
+
+impl JunitTestReporter {
+  pub fn new(writer: Box<dyn Write + Send>, parallel: bool) -> Self {
+    Self {
+      writer,
+      record: TestRunRecord::new(parallel),
+    }
+  }
+
+  fn failure_outcome(failure: &TestFailure) -> JunitOutcome {
+    let details = match failure_js_error(failure) {
+      Some(js_error) => strip_ansi_codes(&format_test_error(&js_error)),
+      None => failure_message(failure),
+    };
+    JunitOutcome::Failed {
+      message: failure_message(failure),
+      details,
+    }
+  }
+
+  fn write(&mut self, maybe_elapsed: Option<&Duration>) {
+    let mut suites: IndexMap<String, Vec<JunitTestCase>> = IndexMap::new();
+    for test in self.record.tests.values() {
+      let outcome = match &test.result {
+        Some(TestResult::Ok) => JunitOutcome::Passed,
+        Some(TestResult::Ignored) => JunitOutcome::Skipped,
+        Some(TestResult::Failed(failure)) => Self::failure_outcome(failure),
+        Some(TestResult::Cancelled) => JunitOutcome::Error {
+          message: "Cancelled".to_string(),
+          details: String::new(),
+        },
+        None => JunitOutcome::Error {
+          message: "Interrupted".to_string(),
+          details: String::new(),
+        },
+      };
+      suites
+        .entry(test.description.origin.clone())
+        .or_default()
+        .push(JunitTestCase {
+          name: test.description.name.clone(),
+          elapsed: test.elapsed,
+          outcome,
+          output: String::from_utf8_lossy(&test.output).into_owned(),
+        });
+    }
+    for step in self.record.steps.values() {
+      let outcome = match &step.result {
+        Some(TestStepResult::Ok) => JunitOutcome::Passed,
+        Some(TestStepResult::Ignored) => JunitOutcome::Skipped,
+        Some(TestStepResult::Failed(failure)) => Self::failure_outcome(failure),
+        None => JunitOutcome::Error {
+          message: "Interrupted".to_string(),
+          details: String::new(),
+        },
+      };
+      suites
+        .entry(step.description.origin.clone())
+        .or_default()
+        .push(JunitTestCase {
+          name: self.record.step_ancestry(&step.description),
+          elapsed: step.elapsed,
+          outcome,
+          output: String::new(),
+        });
+    }
+    for (origin, js_error) in &self.record.uncaught_errors {
+      suites
+        .entry(origin.clone())
+        .or_default()
+        .push(JunitTestCase {
+          name: "(uncaught error)".to_string(),
+          elapsed: 0,
+          outcome: JunitOutcome::Error {
+            message: js_error.exception_message.clone(),
+            details: strip_ansi_codes(&format_test_error(js_error)),
+          },
+          output: String::new(),
+        });
+    }
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let all_cases = suites.values().flatten().collect::<Vec<_>>();
+    let total_elapsed = match maybe_elapsed {
+      Some(elapsed) => elapsed.as_millis() as u64,
+      None => all_cases.iter().map(|case| case.elapsed).sum(),
+    };
+    writeln!(
+      xml,
+      "<testsuites name=\"deno test\" {}>",
+      format_counts(&all_cases, total_elapsed)
+    )
+    .unwrap();
+    for (origin, cases) in &suites {
+      let cases = cases.iter().collect::<Vec<_>>();
+      let elapsed = cases.iter().map(|case| case.elapsed).sum();
+      let name =
+        escape_xml(&self.record.to_relative_path_or_remote_url(origin));
+      writeln!(
+        xml,
+        "  <testsuite name=\"{}\" {}>",
+        name,
+        format_counts(&cases, elapsed)
+      )
+      .unwrap();
+      if let Some((shard, _)) = &self.record.shard {
+        writeln!(
+          xml,
+          "    <properties>\n      <property name=\"shard\" value=\"{shard}\"/>\n    </properties>"
+        )
+        .unwrap();
+      }
+      for case in cases {
+        write!(
+          xml,
+          "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+          escape_xml(&case.name),
+          name,
+          format_seconds(case.elapsed)
+        )
+        .unwrap();
+        if matches!(case.outcome, JunitOutcome::Passed)
+          && case.output.is_empty()
+        {
+          xml.push_str("/>\n");
+          continue;
+        }
+        xml.push_str(">\n");
+        match &case.outcome {
+          JunitOutcome::Passed => {}
+          JunitOutcome::Skipped => xml.push_str("      <skipped/>\n"),
+          JunitOutcome::Failed { message, details } => writeln!(
+            xml,
+            "      <failure message=\"{}\">{}</failure>",
+            escape_xml(message),
+            escape_xml(details)
+          )
+          .unwrap(),
+          JunitOutcome::Error { message, details } => writeln!(
+            xml,
+            "      <error message=\"{}\">{}</error>",
+            escape_xml(message),
+            escape_xml(details)
+          )
+          .unwrap(),
+        }
+        if !case.output.is_empty() {
+          writeln!(
+            xml,
+            "      <system-out>{}</system-out>",
+            escape_xml(&strip_ansi_codes(&case.output))
+          )
+          .unwrap();
+        }
+        xml.push_str("    </testcase>\n");
+      }
+      xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    write_report(&mut self.writer, xml.as_bytes());
+  }
+}
+
+fn format_counts(cases: &[&JunitTestCase], elapsed: u64) -> String {
+  let count = |f: fn(&JunitOutcome) -> bool| {
+    cases.iter().filter(|case| f(&case.outcome)).count()
+  };
+  format!(
+    "tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\"",
+    cases.len(),
+    count(|outcome| matches!(outcome, JunitOutcome::Failed { .. })),
+    count(|outcome| matches!(outcome, JunitOutcome::Error { .. })),
+    count(|outcome| matches!(outcome, JunitOutcome::Skipped)),
+    format_seconds(elapsed)
+  )
+}
+
+fn format_seconds(millis: u64) -> String {
+  format!("{:.3}", millis as f64 / 1000.0)
+}
+
+fn escape_xml(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      // control characters other than whitespace are not allowed in XML
+      c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+impl TestReporter for JunitTestReporter {
+  fn report_shard(
+    &mut self,
+    shard: &TestShard,
+    specifiers: &[ModuleSpecifier],
+  ) {
+    self.record.shard(shard, specifiers);
+  }
+
+  fn report_register(&mut self, description: &TestDescription) {
+    self.record.register(description);
+  }
+
+  fn report_plan(&mut self, _plan: &TestPlan) {}
+
+  fn report_wait(&mut self, description: &TestDescription) {
+    self.record.wait(description);
+  }
+
+  fn report_output(&mut self, output: &[u8]) {
+    self.record.output(output);
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    self.record.result(description, result, elapsed);
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self.record.uncaught_error(origin, error);
+  }
+
+  fn report_step_register(&mut self, description: &TestStepDescription) {
+    self.record.step_register(description);
+  }
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.record.step_result(desc, result, elapsed);
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, elapsed: &Duration) {
+    self.write(Some(elapsed));
+  }
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.write(None);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_escape_xml() {
+    assert_eq!(
+      escape_xml("<a href=\"x\">'&'</a>\u{1b}[31m"),
+      "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;[31m"
+    );
+  }
+}
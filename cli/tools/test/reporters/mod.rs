@@ -0,0 +1,326 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::args::TestReporterKind;
+use crate::args::TestShard;
+use crate::tools::test::abbreviate_test_error;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::error::JsError;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+mod compound;
+mod json;
+mod junit;
+mod pretty;
+mod tap;
+
+pub use compound::CompoundTestReporter;
+pub use json::JsonTestReporter;
+pub use junit::JunitTestReporter;
+pub use pretty::PrettyTestReporter;
+pub use tap::TapTestReporter;
+
+pub trait TestReporter {
+  fn report_shard(&mut self, shard: &TestShard, specifiers: &[ModuleSpecifier]);
+  fn report_register(&mut self, description: &TestDescription);
+  fn report_plan(&mut self, plan: &TestPlan);
+  fn report_wait(&mut self, description: &TestDescription);
+  fn report_output(&mut self, output: &[u8]);
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  );
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError);
+  fn report_step_register(&mut self, description: &TestStepDescription);
+  fn report_step_wait(&mut self, description: &TestStepDescription);
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  );
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration);
+  fn report_sigint(
+    &mut self,
+    tests_pending: &HashSet<usize>,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  );
+}
+
+/// Creates the reporter for `--reporter`. When the report is written to a
+/// file with `--reporter-output`, the pretty reporter still prints to the
+/// console.
+pub fn create_reporter(
+  kind: TestReporterKind,
+  maybe_output_path: Option<&Path>,
+  parallel: bool,
+  echo_output: bool,
+) -> Result<Box<dyn TestReporter + Send>, AnyError> {
+  if kind == TestReporterKind::Pretty {
+    return Ok(Box::new(PrettyTestReporter::new(parallel, echo_output)));
+  }
+  let writer: Box<dyn Write + Send> = match maybe_output_path {
+    Some(path) => Box::new(std::io::BufWriter::new(
+      std::fs::File::create(path).with_context(|| {
+        format!("Failed creating test report file {}", path.display())
+      })?,
+    )),
+    None => Box::new(std::io::stdout()),
+  };
+  let reporter: Box<dyn TestReporter + Send> = match kind {
+    TestReporterKind::Pretty => unreachable!(),
+    TestReporterKind::Json => Box::new(JsonTestReporter::new(writer, parallel)),
+    TestReporterKind::Junit => {
+      Box::new(JunitTestReporter::new(writer, parallel))
+    }
+    TestReporterKind::Tap => Box::new(TapTestReporter::new(writer)),
+  };
+  if maybe_output_path.is_some() {
+    Ok(Box::new(CompoundTestReporter::new(vec![
+      Box::new(PrettyTestReporter::new(parallel, echo_output)),
+      reporter,
+    ])))
+  } else {
+    Ok(reporter)
+  }
+}
+
+pub fn format_test_step_ancestry(
+  desc: &TestStepDescription,
+  tests: &IndexMap<usize, TestDescription>,
+  test_steps: &IndexMap<usize, TestStepDescription>,
+) -> String {
+  let root;
+  let mut ancestor_names = vec![];
+  let mut current_desc = desc;
+  loop {
+    if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
+      ancestor_names.push(&step_desc.name);
+      current_desc = step_desc;
+    } else {
+      root = tests.get(&current_desc.parent_id).unwrap();
+      break;
+    }
+  }
+  ancestor_names.reverse();
+  let mut result = String::new();
+  result.push_str(&root.name);
+  result.push_str(" ... ");
+  for name in ancestor_names {
+    result.push_str(name);
+    result.push_str(" ... ");
+  }
+  result.push_str(&desc.name);
+  result
+}
+
+fn to_relative_path_or_remote_url(cwd: &Url, path_or_url: &str) -> String {
+  let url = Url::parse(path_or_url).unwrap();
+  if url.scheme() == "file" {
+    if let Some(mut r) = cwd.make_relative(&url) {
+      if !r.starts_with("../") {
+        r = format!("./{r}");
+      }
+      return r;
+    }
+  }
+  path_or_url.to_string()
+}
+
+/// Machine readable reports don't contain colors, which are only added to
+/// messages for the console.
+fn strip_ansi_codes(text: &str) -> String {
+  console_static_text::ansi::strip_ansi_codes(text).to_string()
+}
+
+fn failure_message(failure: &TestFailure) -> String {
+  match failure {
+    TestFailure::JsError(js_error) => js_error.exception_message.clone(),
+    failure => strip_ansi_codes(&failure.to_string()),
+  }
+}
+
+/// The JS error of a failure without the stack frames of Deno's internal
+/// code. The frames are already mapped to the original sources.
+fn failure_js_error(failure: &TestFailure) -> Option<JsError> {
+  match failure {
+    TestFailure::JsError(js_error) => Some(abbreviate_test_error(js_error)),
+    _ => None,
+  }
+}
+
+fn write_report(writer: &mut dyn Write, report: &[u8]) {
+  if let Err(err) = writer.write_all(report).and_then(|_| writer.flush()) {
+    log::error!("Failed writing the test report: {:#}", err);
+  }
+}
+
+struct TestRecord {
+  description: TestDescription,
+  result: Option<TestResult>,
+  elapsed: u64,
+  output: Vec<u8>,
+}
+
+struct TestStepRecord {
+  description: TestStepDescription,
+  result: Option<TestStepResult>,
+  elapsed: u64,
+}
+
+/// The events of a test run, collected by the reporters that write their
+/// report once the run completes.
+struct TestRunRecord {
+  parallel: bool,
+  cwd: Url,
+  tests: IndexMap<usize, TestRecord>,
+  steps: IndexMap<usize, TestStepRecord>,
+  uncaught_errors: Vec<(String, JsError)>,
+  /// The shard and the modules it ran, relative to the cwd.
+  shard: Option<(TestShard, Vec<String>)>,
+  /// The output that couldn't be attributed to a test, because it was written
+  /// outside of a test or tests ran in parallel.
+  output: Vec<u8>,
+  running_test_id: Option<usize>,
+}
+
+impl TestRunRecord {
+  fn new(parallel: bool) -> Self {
+    Self {
+      parallel,
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      tests: Default::default(),
+      steps: Default::default(),
+      uncaught_errors: Default::default(),
+      shard: None,
+      output: Default::default(),
+      running_test_id: None,
+    }
+  }
+
+  fn shard(&mut self, shard: &TestShard, specifiers: &[ModuleSpecifier]) {
+    let files = specifiers
+      .iter()
+      .map(|specifier| self.to_relative_path_or_remote_url(specifier.as_str()))
+      .collect();
+    self.shard = Some((*shard, files));
+  }
+
+  fn register(&mut self, description: &TestDescription) {
+    self.tests.insert(
+      description.id,
+      TestRecord {
+        description: description.clone(),
+        result: None,
+        elapsed: 0,
+        output: Vec::new(),
+      },
+    );
+  }
+
+  fn wait(&mut self, description: &TestDescription) {
+    if !self.parallel {
+      self.running_test_id = Some(description.id);
+    }
+  }
+
+  fn output(&mut self, output: &[u8]) {
+    let maybe_test =
+      self.running_test_id.and_then(|id| self.tests.get_mut(&id));
+    match maybe_test {
+      Some(test) => test.output.extend_from_slice(output),
+      None => self.output.extend_from_slice(output),
+    }
+  }
+
+  fn result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    if let Some(test) = self.tests.get_mut(&description.id) {
+      test.result = Some(result.clone());
+      test.elapsed = elapsed;
+    }
+    self.running_test_id = None;
+  }
+
+  fn uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self
+      .uncaught_errors
+      .push((origin.to_string(), abbreviate_test_error(error)));
+  }
+
+  fn step_register(&mut self, description: &TestStepDescription) {
+    self.steps.insert(
+      description.id,
+      TestStepRecord {
+        description: description.clone(),
+        result: None,
+        elapsed: 0,
+      },
+    );
+  }
+
+  fn step_result(
+    &mut self,
+    description: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+  ) {
+    if let Some(step) = self.steps.get_mut(&description.id) {
+      step.result = Some(result.clone());
+      step.elapsed = elapsed;
+    }
+  }
+
+  /// The steps whose parent is the test or step with the id, in the order
+  /// they were registered.
+  fn child_steps(&self, parent_id: usize) -> Vec<&TestStepRecord> {
+    self
+      .steps
+      .values()
+      .filter(|step| step.description.parent_id == parent_id)
+      .collect()
+  }
+
+  /// The name of the step prefixed with the names of its test and parent
+  /// steps, like [`format_test_step_ancestry`].
+  fn step_ancestry(&self, description: &TestStepDescription) -> String {
+    let mut names = vec![description.name.as_str()];
+    let mut parent_id = description.parent_id;
+    while let Some(step) = self.steps.get(&parent_id) {
+      names.push(&step.description.name);
+      parent_id = step.description.parent_id;
+    }
+    if let Some(test) = self.tests.get(&parent_id) {
+      names.push(&test.description.name);
+    }
+    names.reverse();
+    names.join(" ... ")
+  }
+
+  fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
+    to_relative_path_or_remote_url(&self.cwd, path_or_url)
+  }
+}
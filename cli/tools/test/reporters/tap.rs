@@ -0,0 +1,272 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::failure_js_error;
+use super::failure_message;
+use super::format_test_step_ancestry;
+use super::strip_ansi_codes;
+use super::to_relative_path_or_remote_url;
+use super::write_report;
+use super::TestReporter;
+use crate::args::TestShard;
+use crate::tools::test::format_test_error;
+use crate::tools::test::TestDescription;
+use crate::tools::test::TestFailure;
+use crate::tools::test::TestLocation;
+use crate::tools::test::TestPlan;
+use crate::tools::test::TestResult;
+use crate::tools::test::TestStepDescription;
+use crate::tools::test::TestStepResult;
+use crate::tools::test::TestSummary;
+
+use deno_core::error::JsError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::time::Duration;
+
+/// Streams the results in the Test Anything Protocol (version 13) as they
+/// come in. Test steps are reported as test points named after their
+/// ancestry, and test output as diagnostic lines.
+pub struct TapTestReporter {
+  writer: Box<dyn Write + Send>,
+  count: usize,
+  started: bool,
+}
+
+impl TapTestReporter {
+  pub fn new(writer: Box<dyn Write + Send>) -> Self {
+    Self {
+      writer,
+      count: 0,
+      started: false,
+    }
+  }
+
+  fn write(&mut self, text: &str) {
+    if !self.started {
+      self.started = true;
+      write_report(&mut self.writer, b"TAP version 13\n");
+    }
+    write_report(&mut self.writer, text.as_bytes());
+  }
+
+  fn write_test_point(
+    &mut self,
+    name: &str,
+    location: &TestLocation,
+    elapsed: u64,
+    ok: bool,
+    maybe_failure: Option<&TestFailure>,
+    directive: Option<&str>,
+  ) {
+    self.count += 1;
+    let mut text = String::new();
+    let status = if ok { "ok" } else { "not ok" };
+    write!(
+      text,
+      "{} {} - {}",
+      status,
+      self.count,
+      escape_description(name)
+    )
+    .unwrap();
+    if let Some(directive) = directive {
+      write!(text, " # {directive}").unwrap();
+    }
+    text.push('\n');
+    if let Some(failure) = maybe_failure {
+      text.push_str("  ---\n");
+      writeln!(
+        text,
+        "  message: {}",
+        yaml_string(&failure_message(failure))
+      )
+      .unwrap();
+      writeln!(text, "  severity: fail").unwrap();
+      writeln!(
+        text,
+        "  at: {}",
+        yaml_string(&format!(
+          "{}:{}:{}",
+          location.file_name, location.line_number, location.column_number
+        ))
+      )
+      .unwrap();
+      writeln!(text, "  duration_ms: {elapsed}").unwrap();
+      if let Some(js_error) = failure_js_error(failure) {
+        write_yaml_block(
+          &mut text,
+          "stack",
+          &strip_ansi_codes(&format_test_error(&js_error)),
+        );
+      }
+      text.push_str("  ...\n");
+    }
+    self.write(&text);
+  }
+}
+
+/// A `#` in a test point description starts a directive.
+fn escape_description(name: &str) -> String {
+  name.replace('\\', "\\\\").replace('#', "\\#")
+}
+
+/// JSON strings are valid YAML strings.
+fn yaml_string(text: &str) -> String {
+  serde_json::to_string(text).unwrap()
+}
+
+fn write_yaml_block(text: &mut String, key: &str, value: &str) {
+  writeln!(text, "  {key}: |-").unwrap();
+  for line in value.lines() {
+    writeln!(text, "    {line}").unwrap();
+  }
+}
+
+impl TestReporter for TapTestReporter {
+  fn report_shard(
+    &mut self,
+    shard: &TestShard,
+    specifiers: &[ModuleSpecifier],
+  ) {
+    let cwd =
+      Url::from_directory_path(std::env::current_dir().unwrap()).unwrap();
+    let mut text = format!("# shard {shard}\n");
+    for specifier in specifiers {
+      writeln!(
+        text,
+        "#   {}",
+        to_relative_path_or_remote_url(&cwd, specifier.as_str())
+      )
+      .unwrap();
+    }
+    self.write(&text);
+  }
+
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    self.write(&format!("# {}\n", plan.origin));
+  }
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, output: &[u8]) {
+    let output = strip_ansi_codes(&String::from_utf8_lossy(output));
+    let mut text = String::new();
+    for line in output.lines() {
+      writeln!(text, "# {line}").unwrap();
+    }
+    self.write(&text);
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    let (ok, maybe_failure, directive) = match result {
+      TestResult::Ok => (true, None, None),
+      TestResult::Ignored => (true, None, Some("SKIP")),
+      TestResult::Failed(failure) => (false, Some(failure), None),
+      TestResult::Cancelled => (false, None, Some("cancelled")),
+    };
+    self.write_test_point(
+      &description.name,
+      &description.location,
+      elapsed,
+      ok,
+      maybe_failure,
+      directive,
+    );
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self.count += 1;
+    let mut text = String::new();
+    writeln!(
+      text,
+      "not ok {} - {} (uncaught error)",
+      self.count,
+      escape_description(origin)
+    )
+    .unwrap();
+    text.push_str("  ---\n");
+    writeln!(text, "  message: {}", yaml_string(&error.exception_message))
+      .unwrap();
+    writeln!(text, "  severity: fail").unwrap();
+    write_yaml_block(
+      &mut text,
+      "stack",
+      &strip_ansi_codes(&format_test_error(error)),
+    );
+    text.push_str("  ...\n");
+    self.write(&text);
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let (ok, maybe_failure, directive) = match result {
+      TestStepResult::Ok => (true, None, None),
+      TestStepResult::Ignored => (true, None, Some("SKIP")),
+      TestStepResult::Failed(failure) => (false, Some(failure), None),
+    };
+    self.write_test_point(
+      &format_test_step_ancestry(desc, tests, test_steps),
+      &desc.location,
+      elapsed,
+      ok,
+      maybe_failure,
+      directive,
+    );
+  }
+
+  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
+    let mut text = format!("1..{}\n", self.count);
+    writeln!(
+      text,
+      "# passed {} | failed {} | ignored {} | filtered out {} ({}ms)",
+      summary.passed,
+      summary.failed,
+      summary.ignored,
+      summary.filtered_out,
+      elapsed.as_millis()
+    )
+    .unwrap();
+    self.write(&text);
+  }
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.write("Bail out! Interrupted\n");
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_escape_description() {
+    assert_eq!(escape_description("a # b \\ c"), "a \\# b \\\\ c");
+  }
+}
@@ -0,0 +1,191 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::colors;
+use crate::util::diff::diff;
+use crate::util::fs::atomic_write_file;
+use crate::util::path::specifier_to_file_path;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::ModuleSpecifier;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+const SNAPSHOTS_DIR_NAME: &str = "__snapshots__";
+
+/// The snapshots asserted by the tests of a test module, which are stored as
+/// JSON in `__snapshots__/<file name>.snap` next to the module.
+///
+/// The test runner reads and writes the file itself, so that the tests don't
+/// need to be granted any permission for it.
+pub struct SnapshotStore {
+  path: PathBuf,
+  update: bool,
+  snapshots: BTreeMap<String, String>,
+  asserted: HashSet<String>,
+  changed: bool,
+}
+
+impl SnapshotStore {
+  pub fn load(
+    specifier: &ModuleSpecifier,
+    update: bool,
+  ) -> Result<Self, AnyError> {
+    if specifier.scheme() != "file" {
+      bail!("Snapshots are only supported in local test modules.");
+    }
+    let path = snapshot_file_path(&specifier_to_file_path(specifier)?);
+    let snapshots = match std::fs::read_to_string(&path) {
+      Ok(text) => serde_json::from_str(&text).with_context(|| {
+        format!("Failed parsing snapshot file {}", path.display())
+      })?,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+      Err(err) => {
+        return Err(err).with_context(|| {
+          format!("Failed reading snapshot file {}", path.display())
+        })
+      }
+    };
+    Ok(Self {
+      path,
+      update,
+      snapshots,
+      asserted: HashSet::new(),
+      changed: false,
+    })
+  }
+
+  /// Checks the serialized value against the stored snapshot, or stores it
+  /// when updating the snapshots.
+  pub fn assert(&mut self, name: &str, actual: &str) -> Result<(), AnyError> {
+    if !self.asserted.insert(name.to_string()) {
+      bail!(
+        "Snapshot \"{}\" was asserted more than once. Use a unique name for it.",
+        name
+      );
+    }
+    if self.update {
+      if self.snapshots.get(name).map(|s| s.as_str()) != Some(actual) {
+        self.snapshots.insert(name.to_string(), actual.to_string());
+        self.changed = true;
+      }
+      return Ok(());
+    }
+    match self.snapshots.get(name) {
+      Some(expected) if expected == actual => Ok(()),
+      Some(expected) => Err(generic_error(format!(
+        "Snapshot \"{}\" does not match:\n\n{}\n{}",
+        name,
+        diff(expected, actual),
+        colors::gray("Run with --update-snapshots to update it."),
+      ))),
+      None => Err(generic_error(format!(
+        "Missing snapshot \"{name}\". Run with --update-snapshots to create it."
+      ))),
+    }
+  }
+
+  /// Writes the changed snapshots. The snapshots that weren't asserted are
+  /// removed when `prune` is set, which should only be done when every test
+  /// of the module ran. Returns the number of them that were kept.
+  pub fn finish(mut self, prune: bool) -> Result<usize, AnyError> {
+    let obsolete = self
+      .snapshots
+      .keys()
+      .filter(|name| !self.asserted.contains(*name))
+      .cloned()
+      .collect::<Vec<_>>();
+    if self.update && prune && !obsolete.is_empty() {
+      for name in &obsolete {
+        self.snapshots.remove(name);
+      }
+      self.changed = true;
+    }
+    if self.changed {
+      if self.snapshots.is_empty() {
+        std::fs::remove_file(&self.path).ok();
+      } else {
+        std::fs::create_dir_all(self.path.parent().unwrap())?;
+        let mut text = serde_json::to_string_pretty(&self.snapshots)?;
+        text.push('\n');
+        atomic_write_file(&self.path, text, 0o644).with_context(|| {
+          format!("Failed writing snapshot file {}", self.path.display())
+        })?;
+      }
+      return Ok(0);
+    }
+    Ok(obsolete.len())
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+}
+
+fn snapshot_file_path(module_path: &Path) -> PathBuf {
+  let file_name = module_path.file_name().unwrap().to_string_lossy();
+  module_path
+    .with_file_name(SNAPSHOTS_DIR_NAME)
+    .join(format!("{file_name}.snap"))
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use test_util::TempDir;
+
+  #[test]
+  fn test_snapshot_store() {
+    let temp_dir = TempDir::new();
+    let module_path = temp_dir.path().join("mod_test.ts");
+    let specifier = ModuleSpecifier::from_file_path(&module_path).unwrap();
+    let snapshot_path = temp_dir.path().join("__snapshots__/mod_test.ts.snap");
+
+    let mut store = SnapshotStore::load(&specifier, false).unwrap();
+    let err = store.assert("a 1", "{ a: 1 }").unwrap_err();
+    assert!(err.to_string().starts_with("Missing snapshot \"a 1\""));
+    assert_eq!(store.finish(true).unwrap(), 0);
+    assert!(!snapshot_path.exists());
+
+    let mut store = SnapshotStore::load(&specifier, true).unwrap();
+    store.assert("a 1", "{ a: 1 }").unwrap();
+    store.assert("b 1", "[ 1 ]").unwrap();
+    assert!(store.assert("b 1", "[ 1 ]").is_err());
+    assert_eq!(store.path(), snapshot_path);
+    store.finish(true).unwrap();
+    assert!(snapshot_path.exists());
+
+    let mut store = SnapshotStore::load(&specifier, false).unwrap();
+    store.assert("a 1", "{ a: 1 }").unwrap();
+    let err = store.assert("b 1", "[ 2 ]").unwrap_err();
+    assert!(err.to_string().contains("Snapshot \"b 1\" does not match"));
+    store.finish(true).unwrap();
+
+    // not pruned unless every test ran
+    let mut store = SnapshotStore::load(&specifier, true).unwrap();
+    store.assert("a 1", "{ a: 2 }").unwrap();
+    store.finish(false).unwrap();
+    let mut store = SnapshotStore::load(&specifier, false).unwrap();
+    store.assert("a 1", "{ a: 2 }").unwrap();
+    assert_eq!(store.finish(true).unwrap(), 1);
+
+    let mut store = SnapshotStore::load(&specifier, true).unwrap();
+    store.assert("a 1", "{ a: 2 }").unwrap();
+    store.finish(true).unwrap();
+    let store = SnapshotStore::load(&specifier, false).unwrap();
+    assert_eq!(store.snapshots.len(), 1);
+
+    // removed once none are left
+    let store = SnapshotStore::load(&specifier, true).unwrap();
+    store.finish(true).unwrap();
+    assert!(!snapshot_path.exists());
+
+    let remote = ModuleSpecifier::parse("https://deno.land/x/mod.ts").unwrap();
+    assert!(SnapshotStore::load(&remote, false).is_err());
+  }
+}
@@ -3,12 +3,16 @@
 //! This module provides feature to upgrade deno executable
 
 use crate::args::Flags;
+use crate::args::UpgradeChannel;
 use crate::args::UpgradeFlags;
 use crate::colors;
 use crate::http_util::HttpClient;
 use crate::proc_state::ProcState;
+use crate::util::checksum;
 use crate::util::progress_bar::ProgressBar;
 use crate::util::progress_bar::ProgressBarStyle;
+use crate::util::signature::parse_ed25519_public_key;
+use crate::util::signature::verify_ed25519;
 use crate::util::time;
 use crate::version;
 
@@ -17,6 +21,8 @@ use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures::future::BoxFuture;
 use deno_core::futures::FutureExt;
+use deno_core::serde_json;
+use deno_core::url::Url;
 use deno_semver::Version;
 use once_cell::sync::Lazy;
 use std::borrow::Cow;
@@ -33,6 +39,12 @@ static ARCHIVE_NAME: Lazy<String> =
 
 const RELEASE_URL: &str = "https://github.com/denoland/deno/releases";
 
+const GITHUB_API_URL: &str = "https://api.github.com/repos/denoland/deno";
+
+/// The base64 encoded Ed25519 public key that the archives of the stable and
+/// canary channels are signed with, which release builds set at build time.
+const RELEASE_VERIFY_KEY: Option<&str> = option_env!("DENO_RELEASE_VERIFY_KEY");
+
 // How often query server for new version. In hours.
 const UPGRADE_CHECK_INTERVAL: i64 = 24;
 
@@ -265,51 +277,58 @@ pub async fn upgrade(
 ) -> Result<(), AnyError> {
   let ps = ProcState::from_flags(flags).await?;
   let current_exe_path = std::env::current_exe()?;
-  let metadata = fs::metadata(&current_exe_path)?;
-  let permissions = metadata.permissions();
-
-  if permissions.readonly() {
-    bail!(
-      "You do not have write permission to {}",
-      current_exe_path.display()
-    );
-  }
-  #[cfg(unix)]
-  if std::os::unix::fs::MetadataExt::uid(&metadata) == 0
-    && !nix::unistd::Uid::effective().is_root()
-  {
-    bail!(concat!(
-      "You don't have write permission to {} because it's owned by root.\n",
+  let permissions = check_exe_writable(
+    &current_exe_path,
+    concat!(
       "Consider updating deno through your package manager if its installed from it.\n",
       "Otherwise run `deno upgrade` as root.",
-    ), current_exe_path.display());
-  }
+    ),
+  )?;
+  // the signature of a key passed with --verify-key is required, while the
+  // one of the release key is only checked when it was published
+  let require_signature = upgrade_flags.verify_key.is_some();
+  let verify_key = match &upgrade_flags.verify_key {
+    Some(verify_key) => Some(
+      parse_ed25519_public_key(verify_key)
+        .context("Invalid --verify-key flag.")?,
+    ),
+    None => match upgrade_flags.channel {
+      UpgradeChannel::Stable | UpgradeChannel::Canary => RELEASE_VERIFY_KEY
+        .map(parse_ed25519_public_key)
+        .transpose()
+        .context("Invalid release verification key.")?,
+      UpgradeChannel::Custom(_) => bail!("Upgrading from a custom channel requires the --verify-key flag, which its archives are verified with."),
+    },
+  };
 
   let client = &ps.http_client;
+  let channel = &upgrade_flags.channel;
+  let is_canary = *channel == UpgradeChannel::Canary;
 
   let install_version = match upgrade_flags.version {
     Some(passed_version) => {
       let re_hash = lazy_regex::regex!("^[0-9a-f]{40}$");
-
-      if upgrade_flags.canary && !re_hash.is_match(&passed_version) {
-        bail!("Invalid commit hash passed");
-      } else if !upgrade_flags.canary
-        && Version::parse_standard(&passed_version).is_err()
-      {
-        bail!("Invalid version passed");
-      }
-
-      let current_is_passed = if upgrade_flags.canary {
-        crate::version::GIT_COMMIT_HASH == passed_version
-      } else if !crate::version::is_canary() {
-        crate::version::deno() == passed_version
-      } else {
-        false
+      let is_valid = match channel {
+        UpgradeChannel::Stable => {
+          Version::parse_standard(&passed_version).is_ok()
+        }
+        UpgradeChannel::Canary => re_hash.is_match(&passed_version),
+        UpgradeChannel::Custom(_) => {
+          Version::parse_standard(&passed_version).is_ok()
+            || re_hash.is_match(&passed_version)
+        }
       };
+      if !is_valid {
+        if is_canary {
+          bail!("Invalid commit hash passed");
+        } else {
+          bail!("Invalid version passed");
+        }
+      }
 
       if !upgrade_flags.force
         && upgrade_flags.output.is_none()
-        && current_is_passed
+        && is_installed_version(channel, &passed_version)
       {
         log::info!("Version {} is already installed", crate::version::deno());
         return Ok(());
@@ -318,32 +337,28 @@ pub async fn upgrade(
       }
     }
     None => {
-      let latest_version = if upgrade_flags.canary {
-        log::info!("Looking up latest canary version");
-        get_latest_canary_version(client).await?
-      } else {
-        log::info!("Looking up latest version");
-        get_latest_release_version(client).await?
-      };
-
-      let current_is_most_recent = if upgrade_flags.canary {
-        let latest_hash = latest_version.clone();
-        crate::version::GIT_COMMIT_HASH == latest_hash
-      } else if !crate::version::is_canary() {
-        let current = Version::parse_standard(crate::version::deno()).unwrap();
-        let latest = Version::parse_standard(&latest_version).unwrap();
-        current >= latest
-      } else {
-        false
+      let latest_version = match channel {
+        UpgradeChannel::Stable => {
+          log::info!("Looking up latest version");
+          get_latest_release_version(client).await?
+        }
+        UpgradeChannel::Canary => {
+          log::info!("Looking up latest canary version");
+          get_latest_canary_version(client).await?
+        }
+        UpgradeChannel::Custom(url) => {
+          log::info!("Looking up latest version in {}", url);
+          get_latest_channel_version(client, url).await?
+        }
       };
 
       if !upgrade_flags.force
         && upgrade_flags.output.is_none()
-        && current_is_most_recent
+        && is_most_recent_version(channel, &latest_version)
       {
         log::info!(
           "Local deno version {} is the most recent release",
-          if upgrade_flags.canary {
+          if is_canary {
             crate::version::GIT_COMMIT_HASH
           } else {
             crate::version::deno()
@@ -357,25 +372,37 @@ pub async fn upgrade(
     }
   };
 
-  let download_url = if upgrade_flags.canary {
-    if env!("TARGET") == "aarch64-apple-darwin" {
-      bail!("Canary builds are not available for M1");
-    }
-
-    format!(
-      "https://dl.deno.land/canary/{}/{}",
-      install_version, *ARCHIVE_NAME
-    )
-  } else {
-    format!(
+  let download_url = match channel {
+    UpgradeChannel::Stable => format!(
       "{}/download/v{}/{}",
       RELEASE_URL, install_version, *ARCHIVE_NAME
-    )
+    ),
+    UpgradeChannel::Canary => {
+      if env!("TARGET") == "aarch64-apple-darwin" {
+        bail!("Canary builds are not available for M1");
+      }
+
+      format!(
+        "https://dl.deno.land/canary/{}/{}",
+        install_version, *ARCHIVE_NAME
+      )
+    }
+    UpgradeChannel::Custom(url) => channel_base_url(url)
+      .join(&format!("{}/{}", install_version, *ARCHIVE_NAME))?
+      .to_string(),
   };
 
   let archive_data = download_package(client, &download_url)
     .await
     .with_context(|| format!("Failed downloading {download_url}"))?;
+  let verified = verify_package(
+    client,
+    &download_url,
+    &archive_data,
+    verify_key.as_deref(),
+    require_signature,
+  )
+  .await?;
 
   log::info!("Deno is upgrading to version {}", &install_version);
 
@@ -384,15 +411,36 @@ pub async fn upgrade(
   fs::set_permissions(&new_exe_path, permissions)?;
   check_exe(&new_exe_path)?;
 
+  let output_exe_path =
+    upgrade_flags.output.as_ref().unwrap_or(&current_exe_path);
   if upgrade_flags.dry_run {
     fs::remove_file(&new_exe_path)?;
     log::info!("Upgraded successfully (dry run)");
-    if !upgrade_flags.canary {
-      print_release_notes(version::deno(), &install_version);
+    log::info!("  Channel: {}", channel_name(channel));
+    log::info!(
+      "  Version: {} → {}",
+      version::release_version_or_canary_commit_hash(),
+      install_version
+    );
+    log::info!("  Download: {}", download_url);
+    log::info!(
+      "  Verified: {}",
+      if verified.is_empty() {
+        "nothing".to_string()
+      } else {
+        verified.join(", ")
+      }
+    );
+    log::info!("  Replaces: {}", output_exe_path.display());
+    match fetch_changelog(client, channel, &install_version).await {
+      Some(changelog) => log::info!("\nChangelog:\n{}", changelog.trim_end()),
+      None => {
+        if !is_canary {
+          print_release_notes(version::deno(), &install_version);
+        }
+      }
     }
   } else {
-    let output_exe_path =
-      upgrade_flags.output.as_ref().unwrap_or(&current_exe_path);
     let output_result = if *output_exe_path == current_exe_path {
       replace_exe(&new_exe_path, output_exe_path)
     } else {
@@ -421,7 +469,7 @@ pub async fn upgrade(
       }
     }
     log::info!("Upgraded successfully");
-    if !upgrade_flags.canary {
+    if !is_canary {
       print_release_notes(version::deno(), &install_version);
     }
   }
@@ -430,6 +478,49 @@ pub async fn upgrade(
   Ok(())
 }
 
+fn channel_name(channel: &UpgradeChannel) -> Cow<str> {
+  match channel {
+    UpgradeChannel::Stable => Cow::Borrowed("stable"),
+    UpgradeChannel::Canary => Cow::Borrowed("canary"),
+    UpgradeChannel::Custom(url) => Cow::Owned(url.to_string()),
+  }
+}
+
+/// The URL of a custom channel as a directory, so that the files of the
+/// channel are resolved inside of it.
+fn channel_base_url(url: &Url) -> Url {
+  let mut url = url.clone();
+  if !url.path().ends_with('/') {
+    url.set_path(&format!("{}/", url.path()));
+  }
+  url
+}
+
+/// Whether the version of the channel is the one of this executable.
+fn is_installed_version(channel: &UpgradeChannel, version: &str) -> bool {
+  match channel {
+    UpgradeChannel::Canary => crate::version::GIT_COMMIT_HASH == version,
+    _ if crate::version::is_canary() => false,
+    _ => crate::version::deno() == version,
+  }
+}
+
+/// Whether this executable is as recent as the latest version of the
+/// channel.
+fn is_most_recent_version(channel: &UpgradeChannel, latest: &str) -> bool {
+  match channel {
+    UpgradeChannel::Canary => crate::version::GIT_COMMIT_HASH == latest,
+    _ if crate::version::is_canary() => false,
+    _ => match (
+      Version::parse_standard(crate::version::deno()),
+      Version::parse_standard(latest),
+    ) {
+      (Ok(current), Ok(latest)) => current >= latest,
+      _ => crate::version::deno() == latest,
+    },
+  }
+}
+
 async fn get_latest_release_version(
   client: &HttpClient,
 ) -> Result<String, AnyError> {
@@ -450,21 +541,24 @@ async fn get_latest_canary_version(
   Ok(version)
 }
 
+async fn get_latest_channel_version(
+  client: &HttpClient,
+  url: &Url,
+) -> Result<String, AnyError> {
+  let latest_url = channel_base_url(url).join("latest.txt")?;
+  let text = client
+    .download_text(latest_url.clone())
+    .await
+    .with_context(|| format!("Failed downloading {latest_url}"))?;
+  let version = text.trim();
+  Ok(version.strip_prefix('v').unwrap_or(version).to_string())
+}
+
 async fn download_package(
   client: &HttpClient,
   download_url: &str,
 ) -> Result<Vec<u8>, AnyError> {
-  log::info!("Downloading {}", &download_url);
-  let maybe_bytes = {
-    let progress_bar = ProgressBar::new(ProgressBarStyle::DownloadBars);
-    // provide an empty string here in order to prefer the downloading
-    // text above which will stay alive after the progress bars are complete
-    let progress = progress_bar.update("");
-    client
-      .download_with_progress(download_url, &progress)
-      .await?
-  };
-  match maybe_bytes {
+  match download_with_progress_bar(client, download_url).await? {
     Some(bytes) => Ok(bytes),
     None => {
       log::info!("Download could not be found, aborting");
@@ -473,6 +567,131 @@ async fn download_package(
   }
 }
 
+async fn download_with_progress_bar(
+  client: &HttpClient,
+  download_url: &str,
+) -> Result<Option<Vec<u8>>, AnyError> {
+  log::info!("Downloading {}", &download_url);
+  let progress_bar = ProgressBar::new(ProgressBarStyle::DownloadBars);
+  // provide an empty string here in order to prefer the downloading
+  // text above which will stay alive after the progress bars are complete
+  let progress = progress_bar.update("");
+  client.download_with_progress(download_url, &progress).await
+}
+
+/// Verifies the archive against its published `.sha256sum` checksum and,
+/// with a public key, its `.sig` signature, returning what was verified.
+/// Older releases were published without them, so they're skipped with a
+/// warning when missing, unless the signature is required.
+async fn verify_package(
+  client: &HttpClient,
+  download_url: &str,
+  archive_data: &[u8],
+  verify_key: Option<&[u8]>,
+  require_signature: bool,
+) -> Result<Vec<&'static str>, AnyError> {
+  let mut verified = Vec::new();
+  let checksum_url = format!("{download_url}.sha256sum");
+  match download_with_progress_bar(client, &checksum_url).await? {
+    Some(checksum) => {
+      checksum::verify(
+        archive_data,
+        &String::from_utf8(checksum)?,
+        &ARCHIVE_NAME,
+      )?;
+      verified.push("SHA-256 checksum");
+    }
+    None => log::warn!(
+      "{} No checksum was published for {}, so it was not verified.",
+      colors::yellow("Warning"),
+      *ARCHIVE_NAME
+    ),
+  }
+  if let Some(verify_key) = verify_key {
+    let signature_url = format!("{download_url}.sig");
+    match download_with_progress_bar(client, &signature_url).await? {
+      Some(signature) => {
+        verify_ed25519(
+          verify_key,
+          archive_data,
+          &String::from_utf8(signature)?,
+          &ARCHIVE_NAME,
+        )?;
+        verified.push("Ed25519 signature");
+      }
+      None if require_signature => {
+        bail!("No signature was published for {}.", *ARCHIVE_NAME)
+      }
+      None => log::warn!(
+        "{} No signature was published for {}, so it was not verified.",
+        colors::yellow("Warning"),
+        *ARCHIVE_NAME
+      ),
+    }
+  }
+  Ok(verified)
+}
+
+/// Fetches the changelog of a version, which is the release notes of stable
+/// releases, the commits since this executable of canary builds and the
+/// `<version>/CHANGELOG.md` file of custom channels.
+async fn fetch_changelog(
+  client: &HttpClient,
+  channel: &UpgradeChannel,
+  version: &str,
+) -> Option<String> {
+  match channel {
+    UpgradeChannel::Stable => {
+      let text = client
+        .download_text(format!("{GITHUB_API_URL}/releases/tags/v{version}"))
+        .await
+        .ok()?;
+      let release: serde_json::Value = serde_json::from_str(&text).ok()?;
+      release["body"]
+        .as_str()
+        .map(|body| body.replace("\r\n", "\n"))
+    }
+    UpgradeChannel::Canary => {
+      let text = client
+        .download_text(format!(
+          "{}/compare/{}...{}",
+          GITHUB_API_URL,
+          crate::version::GIT_COMMIT_HASH,
+          version
+        ))
+        .await
+        .ok()?;
+      let comparison: serde_json::Value = serde_json::from_str(&text).ok()?;
+      let commits = comparison["commits"].as_array()?;
+      Some(format_canary_changelog(commits))
+    }
+    UpgradeChannel::Custom(url) => {
+      let changelog_url = channel_base_url(url)
+        .join(&format!("{version}/CHANGELOG.md"))
+        .ok()?;
+      client.download_text(changelog_url).await.ok()
+    }
+  }
+}
+
+/// Lists the commits of a GitHub comparison with their short hash and the
+/// first line of their message.
+fn format_canary_changelog(commits: &[serde_json::Value]) -> String {
+  commits
+    .iter()
+    .filter_map(|commit| {
+      let sha = commit["sha"].as_str()?;
+      let message = commit["commit"]["message"].as_str()?;
+      Some(format!(
+        "- {} {}",
+        &sha[..sha.len().min(7)],
+        message.lines().next().unwrap_or_default()
+      ))
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
 pub fn unpack_into_dir(
   archive_data: Vec<u8>,
   is_windows: bool,
@@ -551,11 +770,45 @@ pub fn unpack_into_dir(
   Ok(exe_path)
 }
 
-fn replace_exe(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+/// Checks that the current user can replace the executable, returning its
+/// permissions for the new executable. The hint is shown when the executable
+/// is owned by root.
+pub(crate) fn check_exe_writable(
+  exe_path: &Path,
+  root_hint: &str,
+) -> Result<fs::Permissions, AnyError> {
+  let metadata = fs::metadata(exe_path)?;
+  let permissions = metadata.permissions();
+  if permissions.readonly() {
+    bail!("You do not have write permission to {}", exe_path.display());
+  }
+  #[cfg(unix)]
+  if std::os::unix::fs::MetadataExt::uid(&metadata) == 0
+    && !nix::unistd::Uid::effective().is_root()
+  {
+    bail!(
+      "You don't have write permission to {} because it's owned by root.\n{}",
+      exe_path.display(),
+      root_hint
+    );
+  }
+  #[cfg(not(unix))]
+  let _ = root_hint;
+  Ok(permissions)
+}
+
+/// Replaces the running executable. Renaming over it is atomic on Unix when
+/// both files are on the same file system, otherwise it is copied.
+pub(crate) fn replace_exe(
+  from: &Path,
+  to: &Path,
+) -> Result<(), std::io::Error> {
   if cfg!(windows) {
     // On windows you cannot replace the currently running executable.
     // so first we rename it to deno.old.exe
     fs::rename(to, to.with_extension("old.exe"))?;
+  } else if fs::rename(from, to).is_ok() {
+    return Ok(());
   } else {
     fs::remove_file(to)?;
   }
@@ -867,4 +1120,33 @@ mod test {
     let checker = UpdateChecker::new(env);
     assert_eq!(checker.should_prompt(), None);
   }
+
+  #[test]
+  fn test_channel_base_url() {
+    let url = Url::parse("https://deno.example.com/releases").unwrap();
+    assert_eq!(
+      channel_base_url(&url).join("latest.txt").unwrap().as_str(),
+      "https://deno.example.com/releases/latest.txt"
+    );
+    let url = Url::parse("https://deno.example.com/releases/").unwrap();
+    assert_eq!(channel_base_url(&url), url);
+  }
+
+  #[test]
+  fn test_format_canary_changelog() {
+    let commits = serde_json::json!([
+      {
+        "sha": "61fbfabe440f1cfffa7b8d17426ffdece4d430d0",
+        "commit": { "message": "fix(cli): something\n\nDetails" }
+      },
+      {
+        "sha": "1c0f778a1b2c3d4e5f60718293a4b5c6d7e8f901",
+        "commit": { "message": "feat: other" }
+      }
+    ]);
+    assert_eq!(
+      format_canary_changelog(commits.as_array().unwrap()),
+      "- 61fbfab fix(cli): something\n- 1c0f778 feat: other"
+    );
+  }
 }
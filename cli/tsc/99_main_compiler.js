@@ -763,6 +763,8 @@ delete Object.prototype.__proto__;
    * @property {boolean} debug
    * @property {string[]} rootNames
    * @property {boolean} localOnly
+   * @property {boolean} emitDeclarations
+   * @property {boolean} inferRemoteDeclarations
    */
 
   /**
@@ -789,7 +791,16 @@ delete Object.prototype.__proto__;
   /** The API that is called by Rust when executing a request.
    * @param {Request} request
    */
-  function exec({ config, debug: debugFlag, rootNames, localOnly }) {
+  function exec(
+    {
+      config,
+      debug: debugFlag,
+      rootNames,
+      localOnly,
+      emitDeclarations,
+      inferRemoteDeclarations,
+    },
+  ) {
     setLogDebug(debugFlag, "TS");
     performanceStart();
     if (logDebug) {
@@ -861,6 +872,44 @@ delete Object.prototype.__proto__;
     // @ts-ignore: emitBuildInfo is not exposed (https://github.com/microsoft/TypeScript/issues/49871)
     program.emitBuildInfo(host.writeFile);
 
+    // emit the declaration files of the local modules, or infer the ones of
+    // the remote JavaScript roots, which are written out by the caller
+    if (emitDeclarations || inferRemoteDeclarations) {
+      const roots = new Set(rootNames);
+      for (const sourceFile of program.getProgram().getSourceFiles()) {
+        if (sourceFile.isDeclarationFile) {
+          continue;
+        }
+        const shouldEmit = sourceFile.fileName.startsWith("file:///")
+          ? emitDeclarations
+          : inferRemoteDeclarations && roots.has(sourceFile.fileName);
+        if (!shouldEmit) {
+          continue;
+        }
+        const { diagnostics: emitDiagnostics } = program.emit(
+          sourceFile,
+          (fileName, data) =>
+            ops.op_emit({
+              fileName,
+              data,
+              maybeSpecifier: sourceFile.fileName,
+            }),
+          undefined,
+          /* emitOnlyDtsFiles */ true,
+        );
+        // the declarations inferred for remote modules are best effort, but
+        // the ones of the local modules must be reported like the other
+        // diagnostics, as the emitted files may be incomplete
+        if (sourceFile.fileName.startsWith("file:///")) {
+          diagnostics.push(
+            ...emitDiagnostics.filter((diagnostic) =>
+              !IGNORED_DIAGNOSTICS.includes(diagnostic.code)
+            ),
+          );
+        }
+      }
+    }
+
     performanceProgram({ program });
 
     ops.op_respond({
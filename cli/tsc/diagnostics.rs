@@ -35,6 +35,12 @@ const UNSTABLE_DENO_PROPS: &[&str] = &[
   "ServeTlsInit",
   "Handler",
   "osUptime",
+  "fingerprintAssets",
+  "FingerprintAssetsOptions",
+  "preloadModule",
+  "refreshModule",
+  "setDynamicImportRetryPolicy",
+  "DynamicImportRetryPolicy",
 ];
 
 static MSG_MISSING_PROPERTY_DENO: Lazy<Regex> =
@@ -637,6 +637,50 @@ declare namespace Deno {
     /** If the current test is a step of another test, the parent test context
      * will be set here. */
     parent?: TestContext;
+    /** The value returned by the default export of the global setup module,
+     * which is set with `deno test --global-setup` or `test.globalSetup` in
+     * the configuration file, or `null` when there is none.
+     *
+     * The setup module is run once before all test modules, so it can start a
+     * database or a server that they share. Its value is JSON serialized to be
+     * passed to the test modules, so each access returns a copy of it.
+     *
+     * ```ts
+     * // setup.ts
+     * export default function () {
+     *   const listener = Deno.listen({ port: 0 });
+     *   handleConnections(listener);
+     *   return { port: (listener.addr as Deno.NetAddr).port };
+     * }
+     *
+     * // server_test.ts
+     * Deno.test("server", async (t) => {
+     *   const { port } = t.globalContext as { port: number };
+     *   const conn = await Deno.connect({ port });
+     *   conn.close();
+     * });
+     * ```
+     */
+    readonly globalContext: unknown;
+
+    /** Asserts that the value matches the snapshot stored for it in
+     * `__snapshots__/<file name>.snap` next to the test module. Run
+     * `deno test --update-snapshots` to create or update the snapshots.
+     *
+     * Values other than strings are serialized like `Deno.inspect()` with
+     * their properties sorted. The snapshots are named after the test and a
+     * counter of the snapshots asserted in it, unless a name is given.
+     *
+     * The snapshot file is read and written by the test runner, so no
+     * permissions are needed for it.
+     *
+     * ```ts
+     * Deno.test("a snapshot", (t) => {
+     *   t.assertSnapshot({ hello: "world" });
+     * });
+     * ```
+     */
+    assertSnapshot(actual: unknown, options?: { name?: string }): void;
 
     /** Run a sub step of the parent test or step. Returns a promise
      * that resolves to a boolean signifying if the step completed successfully.
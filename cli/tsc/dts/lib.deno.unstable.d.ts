@@ -6,6 +6,183 @@
 declare namespace Deno {
   export {}; // stop default export type behavior
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Removes a module and every module that depends on it from Deno's
+   * in-memory module graph and caches, so that the source code is read again
+   * the next time the module is loaded. Resolves with the specifiers of the
+   * removed modules.
+   *
+   * Modules that were already evaluated in an isolate are not replaced, so
+   * this is typically used by long running processes that load changed
+   * plugins in new workers:
+   *
+   * ```ts
+   * const url = new URL("./plugin.ts", import.meta.url);
+   * await Deno.refreshModule(url);
+   * new Worker(url, { type: "module" });
+   * ```
+   *
+   * Relative paths are resolved against the current working directory.
+   *
+   * @category Runtime Environment
+   */
+  export function refreshModule(specifier: string | URL): Promise<string[]>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Loads and type checks a module and its dependencies up-front, so that
+   * the workers using it as their entrypoint start without preparing it
+   * again. This is useful before spawning many workers from the same module:
+   *
+   * ```ts
+   * const url = new URL("./worker.ts", import.meta.url);
+   * await Deno.preloadModule(url);
+   * for (let i = 0; i < 8; i++) {
+   *   new Worker(url, { type: "module" });
+   * }
+   * ```
+   *
+   * Relative paths are resolved against the current working directory. The
+   * command line equivalent is the `--prepare-worker` flag of `deno run`.
+   *
+   * @category Runtime Environment
+   */
+  export function preloadModule(specifier: string | URL): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for {@linkcode Deno.fingerprintAssets}.
+   *
+   * @category File System
+   */
+  export interface FingerprintAssetsOptions {
+    /** The directory the fingerprinted assets and `manifest.json` are
+     * written to. */
+    outDir: string | URL;
+    /** The directory the paths in the manifest are relative to, which must
+     * contain every asset. Defaults to the current working directory. */
+    baseDir?: string | URL;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Copies local assets to `outDir` with a hash of their content added to
+   * their file name (ex. `img/logo.png` to `img/logo.1a2b3c4d.png`), so they
+   * can be served with long-lived cache headers and busted whenever they
+   * change. Resolves with the manifest mapping the path of every asset
+   * relative to `baseDir` to its fingerprinted path, which is also written
+   * to `manifest.json` in `outDir`.
+   *
+   * ```ts
+   * const manifest = await Deno.fingerprintAssets(
+   *   [new URL("./static/logo.png", import.meta.url)],
+   *   {
+   *     baseDir: new URL("./static/", import.meta.url),
+   *     outDir: new URL("./dist/", import.meta.url),
+   *   },
+   * );
+   * console.log(manifest["logo.png"]); // "logo.1a2b3c4d.png"
+   * ```
+   *
+   * Requires `allow-read` permission for the assets and `allow-write`
+   * permission for `outDir`.
+   *
+   * @tags allow-read, allow-write
+   * @category File System
+   */
+  export function fingerprintAssets(
+    assets: (string | URL)[],
+    options: FingerprintAssetsOptions,
+  ): Promise<Record<string, string>>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for {@linkcode Deno.setDynamicImportRetryPolicy}.
+   *
+   * @category Runtime Environment
+   */
+  export interface DynamicImportRetryPolicy {
+    /** The total number of attempts to load a module, including the first
+     * one. Defaults to `3`. */
+    maxAttempts?: number;
+    /** The delay in milliseconds before the first retry, which doubles for
+     * every following retry. Defaults to `100`. */
+    initialDelay?: number;
+    /** The maximum delay in milliseconds between retries. Defaults to
+     * `10000`. */
+    maxDelay?: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Retries the dynamic imports of the current worker that fail because of
+   * a network error (ex. a connection reset while downloading a remote
+   * module) with an exponential backoff, before rejecting. Modules that are
+   * not found or fail to parse are never retried. Pass `null` to disable
+   * retrying again.
+   *
+   * ```ts
+   * Deno.setDynamicImportRetryPolicy({ maxAttempts: 5, initialDelay: 200 });
+   * const mod = await import("https://example.com/handler.ts");
+   * ```
+   *
+   * @category Runtime Environment
+   */
+  export function setDynamicImportRetryPolicy(
+    policy: DynamicImportRetryPolicy | null,
+  ): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The result of {@linkcode Deno.checkForSelfUpdate} and
+   * {@linkcode Deno.selfUpdate}.
+   *
+   * @category Runtime Environment
+   */
+  export interface SelfUpdateInfo {
+    /** The version of the executable, from `--app-version`. */
+    currentVersion: string;
+    /** The version in the update manifest. */
+    latestVersion: string;
+    /** Whether the version in the update manifest is newer. */
+    available: boolean;
+    /** Whether the executable was replaced with the newer version. */
+    updated: boolean;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Checks the update manifest of an executable compiled with
+   * `deno compile --update-url` for a newer version, without downloading
+   * it. Requires net permission for the manifest URL, and throws
+   * `Deno.errors.NotSupported` in other programs.
+   *
+   * @category Runtime Environment
+   */
+  export function checkForSelfUpdate(): Promise<SelfUpdateInfo>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Replaces an executable compiled with `deno compile --update-url` with
+   * the newer version in its update manifest, if any. The executable is
+   * downloaded, verified against the `--update-key` signature and renamed
+   * over the current one, so the new version runs on the next start:
+   *
+   * ```ts
+   * const { updated, latestVersion } = await Deno.selfUpdate();
+   * if (updated) {
+   *   console.log(`Updated to ${latestVersion}, restart to use it.`);
+   * }
+   * ```
+   *
+   * Requires net permission for the manifest and executable URLs, and throws
+   * `Deno.errors.NotSupported` in other programs.
+   *
+   * @category Runtime Environment
+   */
+  export function selfUpdate(): Promise<SelfUpdateInfo>;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Retrieve the process umask.  If `mask` is provided, sets the process umask.
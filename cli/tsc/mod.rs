@@ -311,6 +311,12 @@ pub struct Request {
   /// program.
   pub root_names: Vec<(ModuleSpecifier, MediaType)>,
   pub check_mode: TypeCheckMode,
+  /// Whether the declarations of the local modules should be emitted, which
+  /// requires the compiler options to enable them.
+  pub emit_declarations: bool,
+  /// Whether the declarations of the remote JavaScript roots should be
+  /// inferred, which requires the compiler options to enable them.
+  pub infer_remote_declarations: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -321,6 +327,17 @@ pub struct Response {
   pub maybe_tsbuildinfo: Option<String>,
   /// Statistics from the check.
   pub stats: Stats,
+  /// The declarations emitted for the local modules or inferred for the
+  /// remote roots when requested.
+  pub emitted_declarations: Vec<EmittedDeclaration>,
+}
+
+/// The declaration file and declaration map emitted for a module.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EmittedDeclaration {
+  pub specifier: ModuleSpecifier,
+  pub text: String,
+  pub maybe_map: Option<String>,
 }
 
 #[derive(Debug, Default)]
@@ -333,6 +350,7 @@ struct State {
   remapped_specifiers: HashMap<String, ModuleSpecifier>,
   root_map: HashMap<String, ModuleSpecifier>,
   current_dir: PathBuf,
+  emitted_declarations: Vec<EmittedDeclaration>,
 }
 
 impl State {
@@ -354,8 +372,19 @@ impl State {
       remapped_specifiers,
       root_map,
       current_dir,
+      emitted_declarations: Vec::new(),
     }
   }
+
+  /// Gets the original specifier of a module that was remapped for tsc.
+  fn original_specifier(&self, specifier: &str) -> Option<ModuleSpecifier> {
+    self
+      .remapped_specifiers
+      .get(specifier)
+      .or_else(|| self.root_map.get(specifier))
+      .cloned()
+      .or_else(|| ModuleSpecifier::parse(specifier).ok())
+  }
 }
 
 fn normalize_specifier(
@@ -379,6 +408,9 @@ struct EmitArgs {
   /// The _internal_ filename for the file.  This will be used to determine how
   /// the file is cached and stored.
   file_name: String,
+  /// The module a declaration file or declaration map was emitted for.
+  #[serde(default)]
+  maybe_specifier: Option<String>,
 }
 
 #[op]
@@ -387,8 +419,39 @@ fn op_emit(state: &mut OpState, args: EmitArgs) -> bool {
   match args.file_name.as_ref() {
     "internal:///.tsbuildinfo" => state.maybe_tsbuildinfo = Some(args.data),
     _ => {
-      if cfg!(debug_assertions) {
-        panic!("Unhandled emit write: {}", args.file_name);
+      let maybe_specifier = args
+        .maybe_specifier
+        .as_deref()
+        .and_then(|specifier| state.original_specifier(specifier));
+      match maybe_specifier {
+        Some(specifier) => {
+          let index = match state
+            .emitted_declarations
+            .iter()
+            .position(|d| d.specifier == specifier)
+          {
+            Some(index) => index,
+            None => {
+              state.emitted_declarations.push(EmittedDeclaration {
+                specifier,
+                text: String::new(),
+                maybe_map: None,
+              });
+              state.emitted_declarations.len() - 1
+            }
+          };
+          let declaration = &mut state.emitted_declarations[index];
+          if args.file_name.ends_with(".map") {
+            declaration.maybe_map = Some(args.data);
+          } else {
+            declaration.text = args.data;
+          }
+        }
+        None => {
+          if cfg!(debug_assertions) {
+            panic!("Unhandled emit write: {}", args.file_name);
+          }
+        }
       }
     }
   }
@@ -790,6 +853,8 @@ pub fn exec(request: Request) -> Result<Response, AnyError> {
     "debug": request.debug,
     "rootNames": root_names,
     "localOnly": request.check_mode == TypeCheckMode::Local,
+    "emitDeclarations": request.emit_declarations,
+    "inferRemoteDeclarations": request.infer_remote_declarations,
   });
   let exec_source = format!("globalThis.exec({request_value})").into();
 
@@ -821,6 +886,7 @@ pub fn exec(request: Request) -> Result<Response, AnyError> {
       diagnostics,
       maybe_tsbuildinfo,
       stats,
+      emitted_declarations: state.emitted_declarations,
     })
   } else {
     Err(anyhow!("The response for the exec request was not set."))
@@ -946,6 +1012,8 @@ mod tests {
       maybe_tsbuildinfo: None,
       root_names: vec![(specifier.clone(), MediaType::TypeScript)],
       check_mode: TypeCheckMode::All,
+      emit_declarations: false,
+      infer_remote_declarations: false,
     };
     exec(request)
   }
@@ -1001,6 +1069,7 @@ mod tests {
       EmitArgs {
         data: "some file content".to_string(),
         file_name: "internal:///.tsbuildinfo".to_string(),
+        maybe_specifier: None,
       },
     );
     assert!(actual);
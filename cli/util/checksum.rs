@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
 use ring::digest::Context;
 use ring::digest::SHA256;
 
@@ -17,6 +19,30 @@ pub fn gen(v: &[impl AsRef<[u8]>]) -> String {
   out.join("")
 }
 
+/// Verifies the data against the contents of a `.sha256sum` file, which is
+/// the hex digest optionally followed by the file name.
+pub fn verify(
+  data: &[u8],
+  checksum_text: &str,
+  name: &str,
+) -> Result<(), AnyError> {
+  let expected = checksum_text
+    .split_whitespace()
+    .next()
+    .unwrap_or_default()
+    .to_lowercase();
+  let actual = gen(&[data]);
+  if expected != actual {
+    bail!(
+      "The checksum of {} did not match (expected {}, got {})",
+      name,
+      expected,
+      actual
+    );
+  }
+  Ok(())
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -29,4 +55,18 @@ mod tests {
       "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
     );
   }
+
+  #[test]
+  fn test_verify() {
+    let checksum =
+      "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+    assert!(verify(b"hello world", checksum, "test.zip").is_ok());
+    assert!(verify(
+      b"hello world",
+      &format!("{}  test.zip\n", checksum.to_uppercase()),
+      "test.zip"
+    )
+    .is_ok());
+    assert!(verify(b"hello", checksum, "test.zip").is_err());
+  }
 }
@@ -0,0 +1,150 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+
+use deno_ast::swc::ast::Expr;
+use deno_ast::swc::ast::MemberProp;
+use deno_ast::swc::common::Spanned;
+use deno_ast::swc::visit::Visit;
+use deno_ast::swc::visit::VisitWith;
+use deno_ast::MediaType;
+use deno_ast::ModuleSpecifier;
+use deno_ast::SourcePos;
+use deno_ast::SourceTextInfo;
+
+/// Replaces the references to global identifiers or member expressions
+/// (ex. `process.env.NODE_ENV`) found in `defines` with their JavaScript
+/// expression. Returns `None` when the source has nothing to replace or
+/// couldn't be parsed, in which case the original source should be used.
+///
+/// Note that local bindings shadowing a defined name are replaced as well.
+pub fn apply_defines(
+  specifier: &ModuleSpecifier,
+  media_type: MediaType,
+  source: &str,
+  defines: &BTreeMap<String, String>,
+) -> Option<String> {
+  let is_script = matches!(
+    media_type,
+    MediaType::JavaScript
+      | MediaType::Jsx
+      | MediaType::Mjs
+      | MediaType::Cjs
+      | MediaType::TypeScript
+      | MediaType::Mts
+      | MediaType::Cts
+      | MediaType::Tsx
+  );
+  // avoid parsing when none of the defines could possibly be referenced
+  let might_reference = defines.keys().any(|name| {
+    let root = name.split('.').next().unwrap();
+    source.contains(root)
+  });
+  if !is_script || !might_reference {
+    return None;
+  }
+
+  let parsed_source = deno_ast::parse_module(deno_ast::ParseParams {
+    specifier: specifier.to_string(),
+    text_info: SourceTextInfo::from_string(source.to_string()),
+    media_type,
+    capture_tokens: false,
+    scope_analysis: false,
+    maybe_syntax: None,
+  })
+  .ok()?;
+  let mut collector = DefineCollector {
+    defines,
+    replacements: Vec::new(),
+  };
+  parsed_source.module().visit_with(&mut collector);
+  if collector.replacements.is_empty() {
+    return None;
+  }
+
+  let text_info = parsed_source.text_info();
+  let start_pos = text_info.range().start;
+  let mut replacements = collector.replacements;
+  replacements.sort_by_key(|(span, _)| span.lo);
+  let mut output = String::with_capacity(source.len());
+  let mut last_end = 0;
+  for (span, value) in replacements {
+    let start = SourcePos::unsafely_from_byte_pos(span.lo) - start_pos;
+    let end = SourcePos::unsafely_from_byte_pos(span.hi) - start_pos;
+    output.push_str(&source[last_end..start]);
+    output.push('(');
+    output.push_str(value);
+    output.push(')');
+    last_end = end;
+  }
+  output.push_str(&source[last_end..]);
+  Some(output)
+}
+
+struct DefineCollector<'a> {
+  defines: &'a BTreeMap<String, String>,
+  replacements: Vec<(deno_ast::swc::common::Span, &'a str)>,
+}
+
+impl<'a> Visit for DefineCollector<'a> {
+  fn visit_expr(&mut self, expr: &Expr) {
+    if let Some(name) = expr_to_name(expr) {
+      if let Some(value) = self.defines.get(&name) {
+        self.replacements.push((expr.span(), value));
+        return;
+      }
+    }
+    expr.visit_children_with(self);
+  }
+}
+
+/// Gets the dotted name of an identifier or a chain of non-computed
+/// member expressions (ex. `process.env.NODE_ENV`).
+fn expr_to_name(expr: &Expr) -> Option<String> {
+  match expr {
+    Expr::Ident(ident) => Some(ident.sym.to_string()),
+    Expr::Member(member) => match &member.prop {
+      MemberProp::Ident(prop) => {
+        Some(format!("{}.{}", expr_to_name(&member.obj)?, prop.sym))
+      }
+      _ => None,
+    },
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn apply(source: &str) -> Option<String> {
+    let defines = BTreeMap::from([
+      ("DEBUG".to_string(), "false".to_string()),
+      (
+        "process.env.NODE_ENV".to_string(),
+        "\"production\"".to_string(),
+      ),
+    ]);
+    apply_defines(
+      &ModuleSpecifier::parse("file:///mod.ts").unwrap(),
+      MediaType::TypeScript,
+      source,
+      &defines,
+    )
+  }
+
+  #[test]
+  fn replaces_defines() {
+    assert_eq!(
+      apply("if (DEBUG) console.log(process.env.NODE_ENV, process.env.HOME);")
+        .unwrap(),
+      "if ((false)) console.log((\"production\"), process.env.HOME);",
+    );
+    // properties and declarations are left alone
+    assert_eq!(
+      apply("const o = { DEBUG: 1 }; o.DEBUG; type T = typeof DEBUG;"),
+      None,
+    );
+    assert_eq!(apply("console.log(1);"), None);
+  }
+}
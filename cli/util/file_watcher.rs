@@ -2,10 +2,13 @@
 
 use crate::colors;
 use crate::util::fs::canonicalize_path;
+use crate::util::glob::GlobPattern;
 
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
+use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::Future;
+use deno_core::parking_lot::Mutex;
 use deno_runtime::fmt_errors::format_js_error;
 use log::info;
 use notify::event::Event as NotifyEvent;
@@ -15,7 +18,9 @@ use notify::RecommendedWatcher;
 use notify::RecursiveMode;
 use notify::Watcher;
 use std::collections::HashSet;
+use std::path::Path;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::select;
@@ -32,20 +37,30 @@ struct DebouncedReceiver {
   // lose items if a `recv()` never completes
   received_items: HashSet<PathBuf>,
   receiver: UnboundedReceiver<Vec<PathBuf>>,
+  debounce: Duration,
 }
 
 impl DebouncedReceiver {
-  fn new_with_sender() -> (Arc<mpsc::UnboundedSender<Vec<PathBuf>>>, Self) {
+  fn new_with_sender(
+    debounce: Duration,
+  ) -> (Arc<mpsc::UnboundedSender<Vec<PathBuf>>>, Self) {
     let (sender, receiver) = mpsc::unbounded_channel();
     (
       Arc::new(sender),
       Self {
         receiver,
         received_items: HashSet::new(),
+        debounce,
       },
     )
   }
 
+  /// Discards the changes that were received so far.
+  fn discard(&mut self) {
+    self.received_items.clear();
+    while self.receiver.try_recv().is_ok() {}
+  }
+
   async fn recv(&mut self) -> Option<Vec<PathBuf>> {
     if self.received_items.is_empty() {
       self
@@ -58,7 +73,7 @@ impl DebouncedReceiver {
         items = self.receiver.recv() => {
           self.received_items.extend(items?);
         }
-        _ = sleep(DEBOUNCE_INTERVAL) => {
+        _ = sleep(self.debounce) => {
           return Some(self.received_items.drain().collect());
         }
       }
@@ -95,14 +110,16 @@ pub enum ResolutionResult<T> {
 async fn next_restart<R, T, F>(
   resolver: &mut R,
   debounced_receiver: &mut DebouncedReceiver,
-) -> (Vec<PathBuf>, Result<T, AnyError>)
+  before_restart: &Option<RestartHook>,
+) -> (Vec<PathBuf>, Vec<PathBuf>, Result<T, AnyError>)
 where
   R: FnMut(Option<Vec<PathBuf>>) -> F,
   F: Future<Output = ResolutionResult<T>>,
 {
   loop {
     let changed = debounced_receiver.recv().await;
-    match resolver(changed).await {
+    run_before_restart(before_restart, debounced_receiver).await;
+    match resolver(changed.clone()).await {
       ResolutionResult::Ignore => {
         log::debug!("File change ignored")
       }
@@ -114,31 +131,127 @@ where
         if paths_to_watch.is_empty() {
           paths_to_watch.push(PathBuf::from("."));
         }
-        return (paths_to_watch, result);
+        return (changed.unwrap_or_default(), paths_to_watch, result);
       }
     }
   }
 }
 
+/// Runs the hook before the modules are prepared again, discarding the changes
+/// it made, like the ones of generated files, so they don't cause another
+/// restart.
+async fn run_before_restart(
+  before_restart: &Option<RestartHook>,
+  debounced_receiver: &mut DebouncedReceiver,
+) {
+  if let Some(before_restart) = before_restart {
+    if let Err(err) = before_restart().await {
+      eprintln!("{}: {:#}", colors::red_bold("error"), err);
+    }
+    debounced_receiver.discard();
+  }
+}
+
+/// A hook that runs when files changed, before the subcommand restarts, like
+/// a task that generates code.
+pub type RestartHook =
+  Rc<dyn Fn() -> LocalBoxFuture<'static, Result<(), AnyError>>>;
+
 pub struct PrintConfig {
   /// printing watcher status to terminal.
   pub job_name: String,
   /// determine whether to clear the terminal screen; applicable to TTY environments only.
   pub clear_screen: bool,
+  /// the paths to watch in addition to the ones of the module graph, and the
+  /// ones whose changes are ignored.
+  pub filter: WatchFilter,
+  /// how long to wait for more changes before restarting, which batches the
+  /// changes of a save of many files.
+  pub debounce: Option<Duration>,
+  /// runs before every restart.
+  pub before_restart: Option<RestartHook>,
+}
+
+/// The paths that are watched in addition to the ones reported by the
+/// subcommand (`--watch-include`), and the ones whose changes never cause a
+/// restart (`--watch-exclude`).
+#[derive(Clone, Debug, Default)]
+pub struct WatchFilter {
+  pub include: Vec<GlobPattern>,
+  pub exclude: Vec<GlobPattern>,
+}
+
+impl WatchFilter {
+  fn include_dirs(&self) -> Vec<PathBuf> {
+    self
+      .include
+      .iter()
+      .map(|pattern| pattern.base().to_path_buf())
+      .collect()
+  }
+
+  /// Whether a change of the path may cause a restart, where the watched
+  /// paths are the ones reported by the subcommand.
+  fn is_relevant(&self, path: &Path, watched_paths: &[PathBuf]) -> bool {
+    if self.exclude.iter().any(|pattern| pattern.matches(path)) {
+      return false;
+    }
+    // the directories of the include patterns are watched in their entirety,
+    // so only the paths they match or that are watched anyway are relevant
+    self.include.iter().any(|pattern| pattern.matches(path))
+      || watched_paths
+        .iter()
+        .any(|watched_path| path.starts_with(watched_path))
+  }
 }
 
-fn create_print_after_restart_fn(clear_screen: bool) -> impl Fn() {
-  move || {
+fn create_print_after_restart_fn(clear_screen: bool) -> impl Fn(&[PathBuf]) {
+  move |changed| {
     if clear_screen && atty::is(atty::Stream::Stderr) {
       eprint!("{CLEAR_SCREEN}");
     }
     info!(
-      "{} File change detected! Restarting!",
+      "{} File change detected! Restarting!{}",
       colors::intense_blue("Watcher"),
+      format_changed_paths(changed),
     );
   }
 }
 
+/// Formats the reason of a restart, which are the first few changed paths
+/// relative to the current directory.
+fn format_changed_paths(changed: &[PathBuf]) -> String {
+  const MAX_DISPLAYED_PATHS: usize = 3;
+  if changed.is_empty() {
+    return String::new();
+  }
+  let cwd = std::env::current_dir()
+    .ok()
+    .and_then(|cwd| canonicalize_path(&cwd).ok());
+  let mut paths = changed
+    .iter()
+    .map(|path| {
+      cwd
+        .as_ref()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+    })
+    .collect::<Vec<_>>();
+  paths.sort();
+  let mut text = paths
+    .iter()
+    .take(MAX_DISPLAYED_PATHS)
+    .cloned()
+    .collect::<Vec<_>>()
+    .join(", ");
+  if paths.len() > MAX_DISPLAYED_PATHS {
+    text.push_str(&format!(" and {} more", paths.len() - MAX_DISPLAYED_PATHS));
+  }
+  format!(" ({})", colors::gray(text))
+}
+
 /// Creates a file watcher, which will call `resolver` with every file change.
 ///
 /// - `resolver` is used for resolving file paths to be watched at every restarting
@@ -160,13 +273,17 @@ where
   F1: Future<Output = ResolutionResult<T>>,
   F2: Future<Output = Result<(), AnyError>>,
 {
-  let (sender, mut receiver) = DebouncedReceiver::new_with_sender();
-
   let PrintConfig {
     job_name,
     clear_screen,
+    filter,
+    debounce,
+    before_restart,
   } = print_config;
 
+  let (sender, mut receiver) =
+    DebouncedReceiver::new_with_sender(debounce.unwrap_or(DEBOUNCE_INTERVAL));
+
   // Store previous data. If module resolution fails at some point, the watcher will try to
   // continue watching files using these data.
   let mut paths_to_watch;
@@ -187,11 +304,12 @@ where
         colors::intense_blue("Watcher"),
       );
 
-      let (paths, result) = next_restart(&mut resolver, &mut receiver).await;
+      let (changed, paths, result) =
+        next_restart(&mut resolver, &mut receiver, &before_restart).await;
       paths_to_watch = paths;
       resolution_result = result;
 
-      print_after_restart();
+      print_after_restart(&changed);
     }
     ResolutionResult::Restart {
       paths_to_watch: mut paths,
@@ -209,20 +327,20 @@ where
   info!("{} {} started.", colors::intense_blue("Watcher"), job_name,);
 
   loop {
-    let mut watcher = new_watcher(sender.clone())?;
-    add_paths_to_watcher(&mut watcher, &paths_to_watch);
+    let mut watcher = FileWatcher::new(sender.clone(), filter.clone())?;
+    watcher.watch(&paths_to_watch);
 
     match resolution_result {
       Ok(operation_arg) => {
         let fut = error_handler(operation(operation_arg));
         select! {
-          (paths, result) = next_restart(&mut resolver, &mut receiver) => {
+          (changed, paths, result) = next_restart(&mut resolver, &mut receiver, &before_restart) => {
             if result.is_ok() {
               paths_to_watch = paths;
             }
             resolution_result = result;
 
-            print_after_restart();
+            print_after_restart(&changed);
             continue;
           },
           _ = fut => {},
@@ -244,13 +362,14 @@ where
       }
     }
 
-    let (paths, result) = next_restart(&mut resolver, &mut receiver).await;
+    let (changed, paths, result) =
+      next_restart(&mut resolver, &mut receiver, &before_restart).await;
     if result.is_ok() {
       paths_to_watch = paths;
     }
     resolution_result = result;
 
-    print_after_restart();
+    print_after_restart(&changed);
 
     drop(watcher);
   }
@@ -271,26 +390,29 @@ where
   O: FnMut(T) -> Result<F, AnyError>,
   F: Future<Output = Result<(), AnyError>>,
 {
-  let (watcher_sender, mut watcher_receiver) =
-    DebouncedReceiver::new_with_sender();
-
   let PrintConfig {
     job_name,
     clear_screen,
+    filter,
+    debounce,
+    before_restart,
   } = print_config;
 
+  let (watcher_sender, mut watcher_receiver) =
+    DebouncedReceiver::new_with_sender(debounce.unwrap_or(DEBOUNCE_INTERVAL));
+
   let print_after_restart = create_print_after_restart_fn(clear_screen);
 
   info!("{} {} started.", colors::intense_blue("Watcher"), job_name,);
 
   fn consume_paths_to_watch(
-    watcher: &mut RecommendedWatcher,
+    watcher: &mut FileWatcher,
     receiver: &mut UnboundedReceiver<Vec<PathBuf>>,
   ) {
     loop {
       match receiver.try_recv() {
         Ok(paths) => {
-          add_paths_to_watcher(watcher, &paths);
+          watcher.watch(&paths);
         }
         Err(e) => match e {
           mpsc::error::TryRecvError::Empty => {
@@ -304,21 +426,22 @@ where
   }
 
   loop {
-    let mut watcher = new_watcher(watcher_sender.clone())?;
+    let mut watcher = FileWatcher::new(watcher_sender.clone(), filter.clone())?;
     consume_paths_to_watch(&mut watcher, &mut paths_to_watch_receiver);
 
     let receiver_future = async {
       loop {
         let maybe_paths = paths_to_watch_receiver.recv().await;
-        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap());
+        watcher.watch(&maybe_paths.unwrap());
       }
     };
     let operation_future = error_handler(operation(operation_args.clone())?);
 
     select! {
       _ = receiver_future => {},
-      _ = watcher_receiver.recv() => {
-        print_after_restart();
+      changed = watcher_receiver.recv() => {
+        run_before_restart(&before_restart, &mut watcher_receiver).await;
+        print_after_restart(&changed.unwrap_or_default());
         continue;
       },
       _ = operation_future => {
@@ -335,48 +458,131 @@ where
     let receiver_future = async {
       loop {
         let maybe_paths = paths_to_watch_receiver.recv().await;
-        add_paths_to_watcher(&mut watcher, &maybe_paths.unwrap());
+        watcher.watch(&maybe_paths.unwrap());
       }
     };
     select! {
       _ = receiver_future => {},
-      _ = watcher_receiver.recv() => {
-        print_after_restart();
+      changed = watcher_receiver.recv() => {
+        run_before_restart(&before_restart, &mut watcher_receiver).await;
+        print_after_restart(&changed.unwrap_or_default());
         continue;
       },
     };
   }
 }
 
-fn new_watcher(
-  sender: Arc<mpsc::UnboundedSender<Vec<PathBuf>>>,
-) -> Result<RecommendedWatcher, AnyError> {
-  let watcher = Watcher::new(
-    move |res: Result<NotifyEvent, NotifyError>| {
-      if let Ok(event) = res {
-        if matches!(
-          event.kind,
-          EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
-        ) {
-          let paths = event
-            .paths
-            .iter()
-            .filter_map(|path| canonicalize_path(path).ok())
-            .collect();
-          sender.send(paths).unwrap();
+/// Watches the paths reported by the subcommand and the directories of the
+/// include patterns, sending the changed paths that pass the filter.
+struct FileWatcher {
+  watcher: RecommendedWatcher,
+  watched_paths: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl FileWatcher {
+  fn new(
+    sender: Arc<mpsc::UnboundedSender<Vec<PathBuf>>>,
+    filter: WatchFilter,
+  ) -> Result<Self, AnyError> {
+    let watched_paths: Arc<Mutex<Vec<PathBuf>>> = Default::default();
+    let watcher = Watcher::new(
+      {
+        let watched_paths = watched_paths.clone();
+        move |res: Result<NotifyEvent, NotifyError>| {
+          if let Ok(event) = res {
+            if matches!(
+              event.kind,
+              EventKind::Create(_)
+                | EventKind::Modify(_)
+                | EventKind::Remove(_)
+            ) {
+              let watched_paths = watched_paths.lock();
+              let paths = event
+                .paths
+                .iter()
+                .filter_map(|path| canonicalize_path(path).ok())
+                .filter(|path| filter.is_relevant(path, &watched_paths))
+                .collect::<Vec<_>>();
+              if !paths.is_empty() {
+                sender.send(paths).unwrap();
+              }
+            }
+          }
         }
-      }
-    },
-    Default::default(),
-  )?;
+      },
+      Default::default(),
+    )?;
+    let mut watcher = Self {
+      watcher,
+      watched_paths,
+    };
+    let include_dirs = filter.include_dirs();
+    if !include_dirs.is_empty() {
+      watcher.add_paths(&include_dirs);
+    }
+    Ok(watcher)
+  }
+
+  fn watch(&mut self, paths: &[PathBuf]) {
+    self.watched_paths.lock().extend(
+      paths
+        .iter()
+        .map(|path| canonicalize_path(path).unwrap_or_else(|_| path.clone())),
+    );
+    self.add_paths(paths);
+  }
 
-  Ok(watcher)
+  fn add_paths(&mut self, paths: &[PathBuf]) {
+    // Ignore any error e.g. `PathNotFound`
+    for path in paths {
+      let _ = self.watcher.watch(path, RecursiveMode::Recursive);
+    }
+    log::debug!("Watching paths: {:?}", paths);
+  }
 }
 
-fn add_paths_to_watcher(watcher: &mut RecommendedWatcher, paths: &[PathBuf]) {
-  // Ignore any error e.g. `PathNotFound`
-  for path in paths {
-    let _ = watcher.watch(path, RecursiveMode::Recursive);
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn formats_changed_paths() {
+    let cwd = canonicalize_path(&std::env::current_dir().unwrap()).unwrap();
+    assert_eq!(format_changed_paths(&[]), "");
+    let text = test_util::strip_ansi_codes(&format_changed_paths(&[
+      cwd.join("b.ts"),
+      cwd.join("a.ts"),
+    ]))
+    .to_string();
+    assert_eq!(text, " (a.ts, b.ts)");
+    let changed = ["a.ts", "b.ts", "c.ts", "d.ts", "e.ts"]
+      .iter()
+      .map(|name| cwd.join(name))
+      .collect::<Vec<_>>();
+    let text =
+      test_util::strip_ansi_codes(&format_changed_paths(&changed)).to_string();
+    assert_eq!(text, " (a.ts, b.ts, c.ts and 2 more)");
+  }
+
+  #[test]
+  fn filters_changed_paths() {
+    let base = Path::new("/project");
+    let filter = WatchFilter {
+      include: vec![GlobPattern::new(base, "templates/**/*.html").unwrap()],
+      exclude: vec![
+        GlobPattern::new(base, "node_modules").unwrap(),
+        GlobPattern::new(base, "src/generated").unwrap(),
+      ],
+    };
+    assert_eq!(filter.include_dirs(), vec![base.join("templates")]);
+    let watched_paths = vec![base.join("src")];
+    let is_relevant =
+      |path: &str| filter.is_relevant(Path::new(path), &watched_paths);
+    assert!(is_relevant("/project/src/main.ts"));
+    assert!(is_relevant("/project/templates/index.html"));
+    assert!(!is_relevant("/project/templates/index.css"));
+    assert!(!is_relevant("/project/src/generated/routes.ts"));
+    assert!(!is_relevant("/project/node_modules/chalk/index.js"));
+    assert!(!is_relevant("/project/README.md"));
   }
-  log::debug!("Watching paths: {:?}", paths);
 }
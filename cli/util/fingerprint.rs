@@ -0,0 +1,144 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+
+use super::checksum;
+use super::fs::atomic_write_file;
+
+/// The number of characters of the content hash added to file names.
+const HASH_LEN: usize = 8;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Copies the assets to the output directory with the hash of their content
+/// added to their file name (ex. `img/logo.png` to `img/logo.1a2b3c4d.png`),
+/// so they can be served with long-lived cache headers. The directory
+/// structure relative to the base directory is kept.
+///
+/// Returns the manifest that maps the relative path of every asset to its
+/// fingerprinted path, which is also written to `manifest.json` in the
+/// output directory.
+pub fn fingerprint_assets(
+  assets: &[PathBuf],
+  base_dir: &Path,
+  out_dir: &Path,
+) -> Result<BTreeMap<String, String>, AnyError> {
+  let mut manifest = BTreeMap::new();
+  for asset in assets {
+    let relative_path = match asset.strip_prefix(base_dir) {
+      Ok(path) => path,
+      Err(_) => bail!(
+        "Asset \"{}\" is not in the base directory \"{}\".",
+        asset.display(),
+        base_dir.display()
+      ),
+    };
+    let bytes = std::fs::read(asset).with_context(|| {
+      format!("Failed reading asset \"{}\".", asset.display())
+    })?;
+    let fingerprinted_path = fingerprinted_path(relative_path, &bytes);
+    let out_path = out_dir.join(&fingerprinted_path);
+    if let Some(parent) = out_path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&out_path, &bytes).with_context(|| {
+      format!("Failed writing asset \"{}\".", out_path.display())
+    })?;
+    manifest.insert(
+      to_manifest_path(relative_path),
+      to_manifest_path(&fingerprinted_path),
+    );
+  }
+  std::fs::create_dir_all(out_dir)?;
+  atomic_write_file(
+    &out_dir.join(MANIFEST_FILE_NAME),
+    serde_json::to_string_pretty(&manifest)?,
+    0o644,
+  )?;
+  Ok(manifest)
+}
+
+fn fingerprinted_path(path: &Path, bytes: &[u8]) -> PathBuf {
+  let hash = checksum::gen(&[bytes]);
+  let hash = &hash[..HASH_LEN];
+  let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+  let file_name = match path.extension() {
+    Some(ext) => format!("{}.{}.{}", stem, hash, ext.to_string_lossy()),
+    None => format!("{stem}.{hash}"),
+  };
+  path.with_file_name(file_name)
+}
+
+/// Manifests use forward slashes on every platform, so they can be used to
+/// build URLs.
+fn to_manifest_path(path: &Path) -> String {
+  path
+    .components()
+    .map(|c| c.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_util::TempDir;
+
+  #[test]
+  fn test_fingerprinted_path() {
+    assert_eq!(
+      fingerprinted_path(Path::new("img/logo.png"), b"hello world"),
+      PathBuf::from("img/logo.b94d27b9.png")
+    );
+    assert_eq!(
+      fingerprinted_path(Path::new("LICENSE"), b"hello world"),
+      PathBuf::from("LICENSE.b94d27b9")
+    );
+  }
+
+  #[test]
+  fn test_fingerprint_assets() {
+    let temp_dir = TempDir::new();
+    temp_dir.create_dir_all("static/img");
+    temp_dir.write("static/img/logo.svg", "<svg></svg>");
+    temp_dir.write("static/style.css", "body {}");
+    temp_dir.write("other.css", "");
+    let base_dir = temp_dir.path().join("static");
+    let out_dir = temp_dir.path().join("dist");
+
+    let manifest = fingerprint_assets(
+      &[base_dir.join("img/logo.svg"), base_dir.join("style.css")],
+      &base_dir,
+      &out_dir,
+    )
+    .unwrap();
+    let logo = manifest.get("img/logo.svg").unwrap();
+    assert!(logo.starts_with("img/logo."));
+    assert!(logo.ends_with(".svg"));
+    assert_eq!(
+      std::fs::read_to_string(out_dir.join(logo)).unwrap(),
+      "<svg></svg>"
+    );
+    assert!(manifest.contains_key("style.css"));
+    let written: BTreeMap<String, String> = serde_json::from_str(
+      &std::fs::read_to_string(out_dir.join(MANIFEST_FILE_NAME)).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(written, manifest);
+
+    let err = fingerprint_assets(
+      &[temp_dir.path().join("other.css")],
+      &base_dir,
+      &out_dir,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("is not in the base directory"));
+  }
+}
@@ -34,10 +34,15 @@ pub fn atomic_write_file<T: AsRef<[u8]>>(
     .map(|_| format!("{:02x}", rand::random::<u8>()))
     .collect();
   let extension = format!("{rand}.tmp");
+  let filename = to_extended_length_path(filename);
   let tmp_file = filename.with_extension(extension);
-  write_file(&tmp_file, data, mode)?;
-  std::fs::rename(tmp_file, filename)?;
-  Ok(())
+  let result = write_file(&tmp_file, data, mode)
+    .and_then(|_| std::fs::rename(&tmp_file, filename));
+  if result.is_err() {
+    // don't leave the partially written file behind
+    let _ignore = std::fs::remove_file(&tmp_file);
+  }
+  result
 }
 
 pub fn write_file<T: AsRef<[u8]>>(
@@ -117,6 +122,72 @@ pub fn canonicalize_path_maybe_not_exists(
   }
 }
 
+/// Resolves any symlinks in a `file:` specifier so that a module reached
+/// through different symlinked paths shares a single identity. Non-file
+/// specifiers and paths that can't be canonicalized are returned as is.
+pub fn canonicalize_file_specifier(
+  specifier: ModuleSpecifier,
+) -> ModuleSpecifier {
+  if specifier.scheme() != "file" {
+    return specifier;
+  }
+  specifier_to_file_path(&specifier)
+    .ok()
+    .and_then(|path| canonicalize_path_maybe_not_exists(&path).ok())
+    .and_then(|path| ModuleSpecifier::from_file_path(path).ok())
+    .unwrap_or(specifier)
+}
+
+/// Windows limits paths to `MAX_PATH` (260) characters unless they use the
+/// extended-length `\\?\` prefix, which deeply nested npm packages can
+/// easily exceed. This converts long absolute paths (including UNC network
+/// share paths) to that form. On other platforms the path is returned as is.
+pub fn to_extended_length_path(path: &Path) -> Cow<Path> {
+  #[cfg(windows)]
+  {
+    use std::path::Component;
+    use std::path::Prefix;
+
+    // leave room for the file names that might get joined to a directory
+    const MAX_DIR_PATH_LEN: usize = 248;
+
+    if path.as_os_str().len() < MAX_DIR_PATH_LEN || !path.is_absolute() {
+      return Cow::Borrowed(path);
+    }
+    // verbatim paths are not normalized by Windows, so do it here
+    let path = path.to_path_buf().clean();
+    let mut components = path.components();
+    match components.next() {
+      Some(Component::Prefix(prefix)) => match prefix.kind() {
+        // c:\path -> \\?\c:\path
+        Prefix::Disk(_) => {
+          let mut new_path = PathBuf::from(format!(
+            r"\\?\{}",
+            prefix.as_os_str().to_string_lossy()
+          ));
+          new_path.extend(components);
+          Cow::Owned(new_path)
+        }
+        // \\hostname\share_name\path -> \\?\UNC\hostname\share_name\path
+        Prefix::UNC(hostname, share_name) => {
+          let mut new_path = PathBuf::from(format!(
+            r"\\?\UNC\{}\{}\",
+            hostname.to_string_lossy(),
+            share_name.to_string_lossy()
+          ));
+          new_path
+            .extend(components.filter(|c| !matches!(c, Component::RootDir)));
+          Cow::Owned(new_path)
+        }
+        _ => Cow::Owned(path),
+      },
+      _ => Cow::Owned(path),
+    }
+  }
+  #[cfg(not(windows))]
+  Cow::Borrowed(path)
+}
+
 #[cfg(windows)]
 fn strip_unc_prefix(path: PathBuf) -> PathBuf {
   use std::path::Component;
@@ -333,6 +404,9 @@ pub async fn remove_dir_all_if_exists(path: &Path) -> std::io::Result<()> {
 ///
 /// Note: Does not handle symlinks.
 pub fn copy_dir_recursive(from: &Path, to: &Path) -> Result<(), AnyError> {
+  let from = to_extended_length_path(from);
+  let to = to_extended_length_path(to);
+  let (from, to) = (from.as_ref(), to.as_ref());
   std::fs::create_dir_all(to)
     .with_context(|| format!("Creating {}", to.display()))?;
   let read_dir = std::fs::read_dir(from)
@@ -362,6 +436,9 @@ pub fn copy_dir_recursive(from: &Path, to: &Path) -> Result<(), AnyError> {
 ///
 /// Note: Does not handle symlinks.
 pub fn hard_link_dir_recursive(from: &Path, to: &Path) -> Result<(), AnyError> {
+  let from = to_extended_length_path(from);
+  let to = to_extended_length_path(to);
+  let (from, to) = (from.as_ref(), to.as_ref());
   std::fs::create_dir_all(to)
     .with_context(|| format!("Creating {}", to.display()))?;
   let read_dir = std::fs::read_dir(from)
@@ -921,6 +998,31 @@ mod tests {
     assert_eq!(result, expected);
   }
 
+  #[cfg(windows)]
+  #[test]
+  fn test_to_extended_length_path() {
+    let long_name = "a".repeat(250);
+    run_test(r"C:\test\file.txt", r"C:\test\file.txt");
+    run_test(r"\\server\share\file.txt", r"\\server\share\file.txt");
+    run_test(
+      &format!(r"C:\test\{long_name}\..\{long_name}\file.txt"),
+      &format!(r"\\?\C:\test\{long_name}\file.txt"),
+    );
+    run_test(
+      &format!(r"\\server\share\{long_name}\file.txt"),
+      &format!(r"\\?\UNC\server\share\{long_name}\file.txt"),
+    );
+    let verbatim = format!(r"\\?\C:\{long_name}\file.txt");
+    run_test(&verbatim, &verbatim);
+
+    fn run_test(input: &str, expected: &str) {
+      assert_eq!(
+        to_extended_length_path(Path::new(input)),
+        Path::new(expected)
+      );
+    }
+  }
+
   #[cfg(windows)]
   #[test]
   fn test_strip_unc_prefix() {
@@ -0,0 +1,105 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use regex::Regex;
+
+/// A glob pattern of paths relative to a base directory, where `*` and `?`
+/// match within a path component and `**` matches any number of components.
+///
+/// A pattern also matches everything inside of the directories it matches, so
+/// `node_modules` matches `node_modules/chalk/index.js`.
+#[derive(Clone, Debug)]
+pub struct GlobPattern {
+  /// The directory to watch for the pattern, which is the part of the pattern
+  /// before its first component with a wildcard.
+  base: PathBuf,
+  regex: Regex,
+}
+
+impl GlobPattern {
+  pub fn new(base: &Path, pattern: &str) -> Result<Self, AnyError> {
+    let pattern = pattern.replace('\\', "/");
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let path = base.join(pattern);
+    let path_text = path.to_string_lossy().replace('\\', "/");
+    let mut regex_text = String::from("^");
+    let mut chars = path_text.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '*' if chars.peek() == Some(&'*') => {
+          chars.next();
+          if chars.peek() == Some(&'/') {
+            // `**/` matches zero or more components
+            chars.next();
+            regex_text.push_str("(?:[^/]*/)*");
+          } else {
+            regex_text.push_str(".*");
+          }
+        }
+        '*' => regex_text.push_str("[^/]*"),
+        '?' => regex_text.push_str("[^/]"),
+        c => regex_text.push_str(&regex::escape(&c.to_string())),
+      }
+    }
+    regex_text.push_str("(?:/.*)?$");
+    let regex = Regex::new(&regex_text)
+      .with_context(|| format!("Invalid glob pattern \"{pattern}\""))?;
+    let base = path
+      .ancestors()
+      .find(|ancestor| {
+        !ancestor
+          .to_string_lossy()
+          .contains(|c| matches!(c, '*' | '?'))
+      })
+      .unwrap_or(base)
+      .to_path_buf();
+    Ok(Self { base, regex })
+  }
+
+  /// The directory that contains every path the pattern matches.
+  pub fn base(&self) -> &Path {
+    &self.base
+  }
+
+  pub fn matches(&self, path: &Path) -> bool {
+    self
+      .regex
+      .is_match(&path.to_string_lossy().replace('\\', "/"))
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn matches_paths() {
+    let base = Path::new("/project");
+    let pattern = GlobPattern::new(base, "templates/**/*.html").unwrap();
+    assert_eq!(pattern.base(), Path::new("/project/templates"));
+    assert!(pattern.matches(Path::new("/project/templates/index.html")));
+    assert!(pattern.matches(Path::new("/project/templates/a/b/page.html")));
+    assert!(!pattern.matches(Path::new("/project/templates/index.css")));
+    assert!(!pattern.matches(Path::new("/project/index.html")));
+
+    let pattern = GlobPattern::new(base, "./node_modules/").unwrap();
+    assert_eq!(pattern.base(), Path::new("/project/node_modules"));
+    assert!(pattern.matches(Path::new("/project/node_modules")));
+    assert!(pattern.matches(Path::new("/project/node_modules/chalk/a.js")));
+    assert!(!pattern.matches(Path::new("/project/node_modules_old/a.js")));
+
+    let pattern = GlobPattern::new(base, "**/.git").unwrap();
+    assert_eq!(pattern.base(), Path::new("/project"));
+    assert!(pattern.matches(Path::new("/project/.git/HEAD")));
+    assert!(pattern.matches(Path::new("/project/vendor/lib/.git/HEAD")));
+    assert!(!pattern.matches(Path::new("/project/.github/workflows")));
+
+    let pattern = GlobPattern::new(base, "data/?.json").unwrap();
+    assert!(pattern.matches(Path::new("/project/data/a.json")));
+    assert!(!pattern.matches(Path::new("/project/data/ab.json")));
+  }
+}
@@ -2,6 +2,28 @@
 
 use std::io::Write;
 
+use deno_core::serde_json;
+
+/// The format of the log output, selected with `--log-format`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LogFormat {
+  #[default]
+  Text,
+  /// One JSON object per record, with its timestamp, level, target and
+  /// message.
+  Json,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LoggerOptions {
+  pub level: Option<log::Level>,
+  /// The level overrides of targets, like `module_loader=debug`.
+  pub filters: Vec<(String, log::LevelFilter)>,
+  pub format: LogFormat,
+  /// Prefix the records of the text format with their timestamp.
+  pub timestamps: bool,
+}
+
 struct CliLogger(env_logger::Logger);
 
 impl CliLogger {
@@ -30,44 +52,83 @@ impl log::Log for CliLogger {
   }
 }
 
-pub fn init(maybe_level: Option<log::Level>) {
-  let log_level = maybe_level.unwrap_or(log::Level::Info);
-  let logger = env_logger::Builder::from_env(
+/// The module paths a target of `--log-level` applies to, where the modules
+/// of the CLI can be written without the `deno::` prefix.
+fn log_target_modules(target: &str) -> Vec<String> {
+  if target.contains("::") {
+    vec![target.to_string()]
+  } else {
+    vec![target.to_string(), format!("deno::{target}")]
+  }
+}
+
+fn timestamp() -> String {
+  super::time::utc_now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+}
+
+fn json_record(record: &log::Record) -> serde_json::Value {
+  serde_json::json!({
+    "timestamp": timestamp(),
+    "level": record.level().as_str(),
+    "target": record.target(),
+    "line": record.line(),
+    "message": record.args().to_string(),
+  })
+}
+
+pub fn init(options: LoggerOptions) {
+  let log_level = options.level.unwrap_or(log::Level::Info);
+  let mut builder = env_logger::Builder::from_env(
     env_logger::Env::default()
       .default_filter_or(log_level.to_level_filter().to_string()),
-  )
-  // https://github.com/denoland/deno/issues/6641
-  .filter_module("rustyline", log::LevelFilter::Off)
-  // wgpu crates (gfx_backend), have a lot of useless INFO and WARN logs
-  .filter_module("wgpu", log::LevelFilter::Error)
-  .filter_module("gfx", log::LevelFilter::Error)
-  // used to make available the lsp_debug which is then filtered out at runtime
-  // in the cli logger
-  .filter_module("deno::lsp::performance", log::LevelFilter::Debug)
-  .format(|buf, record| {
-    let mut target = record.target().to_string();
-    if let Some(line_no) = record.line() {
-      target.push(':');
-      target.push_str(&line_no.to_string());
-    }
-    if record.level() <= log::Level::Info
-      || (record.target() == "deno::lsp::performance"
-        && record.level() == log::Level::Debug)
-    {
-      // Print ERROR, WARN, INFO and lsp_debug logs as they are
-      writeln!(buf, "{}", record.args())
-    } else {
-      // Add prefix to DEBUG or TRACE logs
-      writeln!(
-        buf,
-        "{} RS - {} - {}",
-        record.level(),
-        target,
-        record.args()
-      )
+  );
+  builder
+    // https://github.com/denoland/deno/issues/6641
+    .filter_module("rustyline", log::LevelFilter::Off)
+    // wgpu crates (gfx_backend), have a lot of useless INFO and WARN logs
+    .filter_module("wgpu", log::LevelFilter::Error)
+    .filter_module("gfx", log::LevelFilter::Error)
+    // used to make available the lsp_debug which is then filtered out at runtime
+    // in the cli logger
+    .filter_module("deno::lsp::performance", log::LevelFilter::Debug);
+  for (target, level) in &options.filters {
+    for module in log_target_modules(target) {
+      builder.filter_module(&module, *level);
     }
-  })
-  .build();
+  }
+  let format = options.format;
+  let timestamps = options.timestamps;
+  let logger = builder
+    .format(move |buf, record| {
+      if format == LogFormat::Json {
+        return writeln!(buf, "{}", json_record(record));
+      }
+      if timestamps {
+        write!(buf, "{} ", timestamp())?;
+      }
+      let mut target = record.target().to_string();
+      if let Some(line_no) = record.line() {
+        target.push(':');
+        target.push_str(&line_no.to_string());
+      }
+      if record.level() <= log::Level::Info
+        || (record.target() == "deno::lsp::performance"
+          && record.level() == log::Level::Debug)
+      {
+        // Print ERROR, WARN, INFO and lsp_debug logs as they are
+        writeln!(buf, "{}", record.args())
+      } else {
+        // Add prefix to DEBUG or TRACE logs
+        writeln!(
+          buf,
+          "{} RS - {} - {}",
+          record.level(),
+          target,
+          record.args()
+        )
+      }
+    })
+    .build();
 
   let cli_logger = CliLogger::new(logger);
   let max_level = cli_logger.filter();
@@ -77,3 +138,37 @@ pub fn init(maybe_level: Option<log::Level>) {
   }
   r.expect("Could not install logger.");
 }
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn resolves_log_target_modules() {
+    assert_eq!(
+      log_target_modules("module_loader"),
+      vec!["module_loader", "deno::module_loader"]
+    );
+    assert_eq!(
+      log_target_modules("deno_runtime::permissions"),
+      vec!["deno_runtime::permissions"]
+    );
+  }
+
+  #[test]
+  fn formats_json_records() {
+    let value = json_record(
+      &log::Record::builder()
+        .args(format_args!("Resolved {}", "./mod.ts"))
+        .level(log::Level::Debug)
+        .target("deno::module_loader")
+        .line(Some(42))
+        .build(),
+    );
+    assert_eq!(value["level"], "DEBUG");
+    assert_eq!(value["target"], "deno::module_loader");
+    assert_eq!(value["line"], 42);
+    assert_eq!(value["message"], "Resolved ./mod.ts");
+    assert!(value["timestamp"].as_str().unwrap().ends_with('Z'));
+  }
+}
@@ -3,15 +3,21 @@
 // Note: Only add code in this folder that has no application specific logic
 pub mod checksum;
 pub mod console;
+pub mod define;
 pub mod diff;
 pub mod display;
 pub mod draw_thread;
 pub mod file_watcher;
+pub mod fingerprint;
 pub mod fs;
+pub mod glob;
 pub mod logger;
 pub mod path;
 pub mod progress_bar;
+pub mod rate_limit;
+pub mod signature;
 pub mod sync;
+pub mod telemetry;
 pub mod text_encoding;
 pub mod time;
 pub mod unix;
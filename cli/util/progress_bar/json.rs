@@ -0,0 +1,125 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Newline delimited JSON progress events for `--progress=json`, which allows
+//! editors and other wrappers to render their own progress UI.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
+
+use crate::args::ProgressFlag;
+
+static EVENT_WRITER: OnceCell<Mutex<Box<dyn Write + Send>>> = OnceCell::new();
+static START_TIME: Lazy<Instant> = Lazy::new(Instant::now);
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts writing progress events to the provided output.
+pub fn init(flag: &ProgressFlag) -> Result<(), AnyError> {
+  let writer: Box<dyn Write + Send> = match flag {
+    ProgressFlag::JsonStderr => Box::new(std::io::stderr()),
+    ProgressFlag::JsonFile(path) => Box::new(
+      OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| {
+          format!("Failed opening progress output '{}'", path.display())
+        })?,
+    ),
+  };
+  Lazy::force(&START_TIME);
+  let _ = EVENT_WRITER.set(Mutex::new(writer));
+  Ok(())
+}
+
+pub fn is_enabled() -> bool {
+  EVENT_WRITER.get().is_some()
+}
+
+/// Writes a single event, which is a no-op when progress events are
+/// not enabled.
+pub fn write_event(kind: &str, mut data: serde_json::Value) {
+  let writer = match EVENT_WRITER.get() {
+    Some(writer) => writer,
+    None => return,
+  };
+  if let serde_json::Value::Object(map) = &mut data {
+    map.insert("event".to_string(), json!(kind));
+    map.insert(
+      "elapsedMs".to_string(),
+      json!(START_TIME.elapsed().as_millis() as u64),
+    );
+  }
+  let mut writer = writer.lock();
+  // errors are ignored because a consumer going away shouldn't stop deno
+  let _ = writeln!(writer, "{data}");
+  let _ = writer.flush();
+}
+
+/// A task that reports a "start" event on creation and a "finish" event
+/// when dropped.
+#[derive(Debug)]
+pub struct JsonProgressEntry {
+  id: usize,
+  total_size: AtomicU64,
+  last_percent: AtomicU64,
+}
+
+impl JsonProgressEntry {
+  pub fn start(kind: &str, message: &str) -> Self {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    write_event(
+      "start",
+      json!({
+        "id": id,
+        "kind": kind,
+        "message": message,
+      }),
+    );
+    Self {
+      id,
+      total_size: AtomicU64::new(0),
+      last_percent: AtomicU64::new(0),
+    }
+  }
+
+  pub fn set_total_size(&self, value: u64) {
+    self.total_size.store(value, Ordering::Relaxed);
+  }
+
+  pub fn set_position(&self, value: u64) {
+    let total_size = self.total_size.load(Ordering::Relaxed);
+    if total_size == 0 {
+      return;
+    }
+    // only report whole percentage changes to keep the stream small
+    let percent = value.min(total_size) * 100 / total_size;
+    if self.last_percent.swap(percent, Ordering::Relaxed) != percent {
+      write_event(
+        "progress",
+        json!({
+          "id": self.id,
+          "position": value,
+          "totalSize": total_size,
+        }),
+      );
+    }
+  }
+}
+
+impl Drop for JsonProgressEntry {
+  fn drop(&mut self) {
+    write_event("finish", json!({ "id": self.id }));
+  }
+}
@@ -10,6 +10,7 @@ use deno_runtime::ops::tty::ConsoleSize;
 
 use crate::colors;
 
+use self::json::JsonProgressEntry;
 use self::renderer::ProgressBarRenderer;
 use self::renderer::ProgressData;
 use self::renderer::ProgressDataDisplayEntry;
@@ -18,6 +19,7 @@ use super::draw_thread::DrawThread;
 use super::draw_thread::DrawThreadGuard;
 use super::draw_thread::DrawThreadRenderer;
 
+pub mod json;
 mod renderer;
 
 // Inspired by Indicatif, but this custom implementation allows
@@ -40,11 +42,21 @@ impl ProgressMessagePrompt {
       }
     }
   }
+
+  /// The kind reported in JSON progress events.
+  pub fn as_event_kind(&self) -> &'static str {
+    match self {
+      ProgressMessagePrompt::Download => "download",
+      ProgressMessagePrompt::Blocking => "blocking",
+      ProgressMessagePrompt::Initialize => "initialize",
+    }
+  }
 }
 
 #[derive(Debug)]
 pub struct UpdateGuard {
   maybe_entry: Option<ProgressBarEntry>,
+  maybe_json_entry: Option<JsonProgressEntry>,
 }
 
 impl Drop for UpdateGuard {
@@ -60,12 +72,29 @@ impl UpdateGuard {
     if let Some(entry) = &self.maybe_entry {
       entry.set_position(value);
     }
+    if let Some(entry) = &self.maybe_json_entry {
+      entry.set_position(value);
+    }
   }
 
   pub fn set_total_size(&self, value: u64) {
     if let Some(entry) = &self.maybe_entry {
       entry.set_total_size(value);
     }
+    if let Some(entry) = &self.maybe_json_entry {
+      entry.set_total_size(value);
+    }
+  }
+}
+
+/// Reports a phase of work that isn't tracked by a progress bar (ex. type
+/// checking) when JSON progress events are enabled. The phase finishes
+/// when the returned guard is dropped.
+pub fn report_phase(kind: &str, message: &str) -> UpdateGuard {
+  UpdateGuard {
+    maybe_entry: None,
+    maybe_json_entry: json::is_enabled()
+      .then(|| JsonProgressEntry::start(kind, message)),
   }
 }
 
@@ -286,19 +315,34 @@ impl ProgressBar {
     kind: ProgressMessagePrompt,
     msg: &str,
   ) -> UpdateGuard {
+    if json::is_enabled() {
+      // the consumer of the events renders the progress instead
+      return UpdateGuard {
+        maybe_entry: None,
+        maybe_json_entry: Some(JsonProgressEntry::start(
+          kind.as_event_kind(),
+          msg,
+        )),
+      };
+    }
+
     // only check if progress bars are supported once we go
     // to update so that we lazily initialize the progress bar
     if ProgressBar::are_supported() {
       let entry = self.inner.add_entry(kind, msg.to_string());
       UpdateGuard {
         maybe_entry: Some(entry),
+        maybe_json_entry: None,
       }
     } else {
       // if we're not running in TTY, fallback to using logger crate
       if !msg.is_empty() {
         log::log!(log::Level::Info, "{} {}", kind.as_text(), msg);
       }
-      UpdateGuard { maybe_entry: None }
+      UpdateGuard {
+        maybe_entry: None,
+        maybe_json_entry: None,
+      }
     }
   }
 
@@ -0,0 +1,137 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use deno_core::parking_lot::Mutex;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+/// Limits how many tasks may run at the same time and how often they
+/// may start, separately for each key.
+#[derive(Debug, Default)]
+pub struct KeyedLimiter {
+  max_concurrent: Option<usize>,
+  min_interval: Option<Duration>,
+  keys: Mutex<HashMap<String, Arc<KeyState>>>,
+}
+
+#[derive(Debug)]
+struct KeyState {
+  semaphore: Option<Arc<Semaphore>>,
+  next_start: Mutex<Instant>,
+}
+
+/// Held while a limited task is running.
+#[derive(Debug)]
+pub struct KeyedLimiterPermit {
+  _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl KeyedLimiter {
+  pub fn new(
+    max_concurrent: Option<usize>,
+    max_per_second: Option<f64>,
+  ) -> Self {
+    Self {
+      max_concurrent: max_concurrent.map(|max| max.max(1)),
+      min_interval: max_per_second
+        .filter(|max| *max > 0.0)
+        .map(|max| Duration::from_secs_f64(1.0 / max)),
+      keys: Default::default(),
+    }
+  }
+
+  pub fn is_unlimited(&self) -> bool {
+    self.max_concurrent.is_none() && self.min_interval.is_none()
+  }
+
+  /// Waits until a task for the provided key is allowed to start.
+  pub async fn acquire(&self, key: &str) -> KeyedLimiterPermit {
+    if self.is_unlimited() {
+      return KeyedLimiterPermit { _permit: None };
+    }
+    let state = self
+      .keys
+      .lock()
+      .entry(key.to_string())
+      .or_insert_with(|| {
+        Arc::new(KeyState {
+          semaphore: self
+            .max_concurrent
+            .map(|max| Arc::new(Semaphore::new(max))),
+          next_start: Mutex::new(Instant::now()),
+        })
+      })
+      .clone();
+
+    let permit = match &state.semaphore {
+      // the semaphore is never closed
+      Some(semaphore) => Some(semaphore.clone().acquire_owned().await.unwrap()),
+      None => None,
+    };
+    if let Some(min_interval) = self.min_interval {
+      let start = {
+        let mut next_start = state.next_start.lock();
+        let start = (*next_start).max(Instant::now());
+        *next_start = start + min_interval;
+        start
+      };
+      tokio::time::sleep_until(start).await;
+    }
+    KeyedLimiterPermit { _permit: permit }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::atomic::Ordering;
+
+  #[tokio::test]
+  async fn limits_concurrency_per_key() {
+    let limiter = Arc::new(KeyedLimiter::new(Some(2), None));
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let mut handles = Vec::new();
+    for _ in 0..6 {
+      let limiter = limiter.clone();
+      let running = running.clone();
+      let max_running = max_running.clone();
+      handles.push(tokio::spawn(async move {
+        let _permit = limiter.acquire("https://deno.land").await;
+        let count = running.fetch_add(1, Ordering::SeqCst) + 1;
+        max_running.fetch_max(count, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        running.fetch_sub(1, Ordering::SeqCst);
+      }));
+    }
+    // other keys are not limited by the first one
+    let _permits = (
+      limiter.acquire("https://esm.sh").await,
+      limiter.acquire("https://esm.sh").await,
+    );
+    for handle in handles {
+      handle.await.unwrap();
+    }
+    assert_eq!(max_running.load(Ordering::SeqCst), 2);
+  }
+
+  #[tokio::test]
+  async fn limits_rate_per_key() {
+    let limiter = KeyedLimiter::new(None, Some(20.0));
+    let start = Instant::now();
+    for _ in 0..3 {
+      limiter.acquire("https://deno.land").await;
+    }
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // the first request for another key starts right away
+    let start = Instant::now();
+    limiter.acquire("https://esm.sh").await;
+    assert!(start.elapsed() < Duration::from_millis(50));
+  }
+}
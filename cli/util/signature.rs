@@ -0,0 +1,79 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use ring::signature::UnparsedPublicKey;
+use ring::signature::ED25519;
+
+/// The DER prefix of an Ed25519 public key in the SubjectPublicKeyInfo
+/// format, like the one written by `openssl pkey -pubout -outform DER`.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+  0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Parses a base64 encoded Ed25519 public key, either raw or in the DER
+/// SubjectPublicKeyInfo format.
+pub fn parse_ed25519_public_key(public_key: &str) -> Result<Vec<u8>, AnyError> {
+  let bytes = base64::decode(public_key.trim())
+    .context("The public key must be base64 encoded.")?;
+  match bytes.len() {
+    32 => Ok(bytes),
+    44 if bytes.starts_with(&ED25519_SPKI_PREFIX) => Ok(bytes[12..].to_vec()),
+    _ => bail!("The public key must be an Ed25519 public key."),
+  }
+}
+
+/// Verifies the base64 encoded Ed25519 signature of the data.
+pub fn verify_ed25519(
+  public_key: &[u8],
+  data: &[u8],
+  signature: &str,
+  name: &str,
+) -> Result<(), AnyError> {
+  let signature = base64::decode(signature.trim()).with_context(|| {
+    format!("The signature of {name} must be base64 encoded.")
+  })?;
+  if UnparsedPublicKey::new(&ED25519, public_key)
+    .verify(data, &signature)
+    .is_err()
+  {
+    bail!("The signature of {} is invalid.", name);
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use ring::rand::SystemRandom;
+  use ring::signature::Ed25519KeyPair;
+  use ring::signature::KeyPair;
+
+  #[test]
+  fn test_parse_ed25519_public_key() {
+    let key = [7; 32];
+    assert_eq!(parse_ed25519_public_key(&base64::encode(key)).unwrap(), key);
+    let spki = [&ED25519_SPKI_PREFIX[..], &key[..]].concat();
+    assert_eq!(
+      parse_ed25519_public_key(&base64::encode(spki)).unwrap(),
+      key
+    );
+    assert!(parse_ed25519_public_key(&base64::encode([7; 16])).is_err());
+    assert!(parse_ed25519_public_key("not base64!").is_err());
+  }
+
+  #[test]
+  fn test_verify_ed25519() {
+    let rng = SystemRandom::new();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = key_pair.public_key().as_ref();
+    let signature = base64::encode(key_pair.sign(b"data"));
+    assert!(verify_ed25519(public_key, b"data", &signature, "data").is_ok());
+    assert!(
+      verify_ed25519(public_key, b"tampered", &signature, "data").is_err()
+    );
+    assert!(verify_ed25519(&[0; 32], b"data", &signature, "data").is_err());
+  }
+}
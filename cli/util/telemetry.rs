@@ -0,0 +1,795 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Optional OpenTelemetry export of the phases of the CLI as spans and of its
+//! cache usage as counters, which is configured with the standard `OTEL_*`
+//! environment variables and sent with OTLP/HTTP JSON periodically and when
+//! the process exits.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::url::Url;
+use deno_runtime::deno_tls::rustls::RootCertStore;
+use once_cell::sync::OnceCell;
+
+use crate::http_util::HttpClient;
+
+static TELEMETRY: OnceCell<Telemetry> = OnceCell::new();
+
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_EXPORT_INTERVAL_MS: u64 = 5_000;
+/// The spans that wait for the next export at most, like the default
+/// `OTEL_BSP_MAX_QUEUE_SIZE`. The spans after it are dropped.
+const MAX_QUEUED_SPANS: usize = 2048;
+
+/// Where and how the telemetry is exported.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TelemetryConfig {
+  pub traces_url: Option<Url>,
+  pub metrics_url: Option<Url>,
+  pub headers: Vec<(String, String)>,
+  pub resource_attributes: Vec<(String, String)>,
+  pub timeout: Duration,
+  /// How often the spans and counters are exported while the process runs.
+  pub export_interval: Duration,
+}
+
+impl TelemetryConfig {
+  /// Reads the configuration from the environment, which is `None` when no
+  /// OTLP endpoint is set or the export is disabled.
+  pub fn from_env() -> Result<Option<Self>, AnyError> {
+    Self::from_vars(|name| {
+      std::env::var(name).ok().filter(|value| !value.is_empty())
+    })
+  }
+
+  fn from_vars(
+    var: impl Fn(&str) -> Option<String>,
+  ) -> Result<Option<Self>, AnyError> {
+    if var("OTEL_SDK_DISABLED").map(|v| v.eq_ignore_ascii_case("true"))
+      == Some(true)
+    {
+      return Ok(None);
+    }
+    if let Some(protocol) = var("OTEL_EXPORTER_OTLP_PROTOCOL") {
+      if protocol != "http/json" {
+        log::warn!(
+          "Telemetry is not exported because OTEL_EXPORTER_OTLP_PROTOCOL is \"{}\", only \"http/json\" is supported.",
+          protocol
+        );
+        return Ok(None);
+      }
+    }
+    let endpoint = var("OTEL_EXPORTER_OTLP_ENDPOINT")
+      .map(|endpoint| {
+        let endpoint = if endpoint.ends_with('/') {
+          endpoint
+        } else {
+          format!("{endpoint}/")
+        };
+        Url::parse(&endpoint).with_context(|| {
+          format!("Invalid OTEL_EXPORTER_OTLP_ENDPOINT \"{endpoint}\".")
+        })
+      })
+      .transpose()?;
+    let signal_url = |exporter_var: &str,
+                      endpoint_var: &str,
+                      path: &str|
+     -> Result<Option<Url>, AnyError> {
+      if var(exporter_var).as_deref() == Some("none") {
+        return Ok(None);
+      }
+      match var(endpoint_var) {
+        Some(url) => {
+          Ok(Some(Url::parse(&url).with_context(|| {
+            format!("Invalid {endpoint_var} \"{url}\".")
+          })?))
+        }
+        None => match &endpoint {
+          Some(endpoint) => Ok(Some(endpoint.join(path)?)),
+          None => Ok(None),
+        },
+      }
+    };
+    let traces_url = signal_url(
+      "OTEL_TRACES_EXPORTER",
+      "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+      "v1/traces",
+    )?;
+    let metrics_url = signal_url(
+      "OTEL_METRICS_EXPORTER",
+      "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+      "v1/metrics",
+    )?;
+    if traces_url.is_none() && metrics_url.is_none() {
+      return Ok(None);
+    }
+
+    let mut resource_attributes = vec![(
+      "service.name".to_string(),
+      var("OTEL_SERVICE_NAME").unwrap_or_else(|| "deno".to_string()),
+    )];
+    for (key, value) in parse_key_values(
+      var("OTEL_RESOURCE_ATTRIBUTES")
+        .as_deref()
+        .unwrap_or_default(),
+    ) {
+      // OTEL_SERVICE_NAME takes precedence over the resource attribute
+      if key == "service.name" {
+        if var("OTEL_SERVICE_NAME").is_none() {
+          resource_attributes[0].1 = value;
+        }
+      } else {
+        resource_attributes.push((key, value));
+      }
+    }
+    let timeout = match var("OTEL_EXPORTER_OTLP_TIMEOUT") {
+      Some(timeout) => timeout.parse::<u64>().with_context(|| {
+        format!("Invalid OTEL_EXPORTER_OTLP_TIMEOUT \"{timeout}\".")
+      })?,
+      None => DEFAULT_TIMEOUT_MS,
+    };
+    let export_interval = match var("OTEL_BSP_SCHEDULE_DELAY") {
+      Some(delay) => delay.parse::<u64>().with_context(|| {
+        format!("Invalid OTEL_BSP_SCHEDULE_DELAY \"{delay}\".")
+      })?,
+      None => DEFAULT_EXPORT_INTERVAL_MS,
+    };
+
+    Ok(Some(Self {
+      traces_url,
+      metrics_url,
+      headers: parse_key_values(
+        var("OTEL_EXPORTER_OTLP_HEADERS")
+          .as_deref()
+          .unwrap_or_default(),
+      ),
+      resource_attributes,
+      timeout: Duration::from_millis(timeout),
+      export_interval: Duration::from_millis(export_interval),
+    }))
+  }
+}
+
+/// Parses the `key1=value1,key2=value2` lists of the OpenTelemetry
+/// environment variables, whose values are percent encoded.
+fn parse_key_values(text: &str) -> Vec<(String, String)> {
+  text
+    .split(',')
+    .filter_map(|pair| {
+      let (key, value) = pair.split_once('=')?;
+      let value = percent_encoding::percent_decode_str(value.trim())
+        .decode_utf8_lossy()
+        .to_string();
+      Some((key.trim().to_string(), value))
+    })
+    .filter(|(key, _)| !key.is_empty())
+    .collect()
+}
+
+#[derive(Debug, Clone)]
+struct SpanData {
+  span_id: [u8; 8],
+  parent_span_id: Option<[u8; 8]>,
+  name: String,
+  start_time: u64,
+  end_time: u64,
+  attributes: Vec<(String, String)>,
+  is_error: bool,
+}
+
+type CounterKey = (&'static str, Vec<(&'static str, String)>);
+
+/// The TLS options of the client that the telemetry is exported with.
+struct HttpClientOptions {
+  root_cert_store: Option<RootCertStore>,
+  unsafely_ignore_certificate_errors: Option<Vec<String>>,
+}
+
+/// Asks the exporter to export what is left, including the root span, and
+/// to stop.
+struct FlushRequest {
+  is_error: bool,
+  done_sender: mpsc::Sender<()>,
+}
+
+struct Telemetry {
+  config: TelemetryConfig,
+  trace_id: [u8; 16],
+  root_span: Mutex<SpanData>,
+  spans: Mutex<Vec<SpanData>>,
+  dropped_spans: AtomicUsize,
+  counters: Mutex<BTreeMap<CounterKey, u64>>,
+  client_options: Mutex<Option<HttpClientOptions>>,
+  flush_sender: Mutex<Option<mpsc::Sender<FlushRequest>>>,
+}
+
+impl Telemetry {
+  fn new(config: TelemetryConfig, root_name: &str) -> Self {
+    Self {
+      config,
+      trace_id: rand::random(),
+      root_span: Mutex::new(SpanData {
+        span_id: rand::random(),
+        parent_span_id: None,
+        name: root_name.to_string(),
+        start_time: unix_nanos(),
+        end_time: 0,
+        attributes: Vec::new(),
+        is_error: false,
+      }),
+      spans: Mutex::new(Vec::new()),
+      dropped_spans: AtomicUsize::new(0),
+      counters: Mutex::new(BTreeMap::new()),
+      client_options: Mutex::new(None),
+      flush_sender: Mutex::new(None),
+    }
+  }
+
+  /// Exports the spans that ended since the last export and the counters,
+  /// along with the root span when the process exits.
+  async fn export(
+    &self,
+    client: &mut Option<HttpClient>,
+    maybe_exit_is_error: Option<bool>,
+  ) {
+    let end_time = unix_nanos();
+    let mut spans = std::mem::take(&mut *self.spans.lock());
+    let start_time = {
+      let mut root_span = self.root_span.lock();
+      if let Some(is_error) = maybe_exit_is_error {
+        root_span.end_time = end_time;
+        root_span.is_error = is_error;
+        spans.push(root_span.clone());
+      }
+      root_span.start_time
+    };
+    // the counters are cumulative, so they are exported in full every time
+    let counters = self.counters.lock().clone();
+    let dropped_spans = self.dropped_spans.swap(0, Ordering::Relaxed);
+    if dropped_spans > 0 {
+      log::warn!(
+        "Dropped {} telemetry spans, because more than {} were waiting to be exported.",
+        dropped_spans,
+        MAX_QUEUED_SPANS
+      );
+    }
+
+    let mut requests = Vec::with_capacity(2);
+    if let Some(url) = &self.config.traces_url {
+      if !spans.is_empty() {
+        requests.push((url, self.traces_request(&spans)));
+      }
+    }
+    if let Some(url) = &self.config.metrics_url {
+      if !counters.is_empty() {
+        requests
+          .push((url, self.metrics_request(&counters, start_time, end_time)));
+      }
+    }
+    if requests.is_empty() {
+      return;
+    }
+
+    // the options are set when the command line is resolved, and commands
+    // that don't resolve it, like `deno completions`, use the defaults
+    let maybe_options = self.client_options.lock().take();
+    if maybe_options.is_some() || client.is_none() {
+      let options = maybe_options.unwrap_or(HttpClientOptions {
+        root_cert_store: None,
+        unsafely_ignore_certificate_errors: None,
+      });
+      match HttpClient::new(
+        options.root_cert_store,
+        options.unsafely_ignore_certificate_errors,
+      ) {
+        Ok(new_client) => *client = Some(new_client),
+        Err(err) => {
+          log::warn!("Failed exporting telemetry: {:#}", err);
+          return;
+        }
+      }
+    }
+    let client = client.as_ref().unwrap();
+    for (url, body) in requests {
+      let mut request = client
+        .post(url.clone())
+        .header("content-type", "application/json")
+        .body(body.to_string());
+      for (key, value) in &self.config.headers {
+        request = request.header(key, value);
+      }
+      let result =
+        tokio::time::timeout(self.config.timeout, request.send()).await;
+      let error = match result {
+        Ok(Ok(response)) => {
+          response.error_for_status().err().map(AnyError::from)
+        }
+        Ok(Err(err)) => Some(AnyError::from(err)),
+        Err(_) => Some(AnyError::msg("timed out")),
+      };
+      if let Some(err) = error {
+        log::warn!("Failed exporting telemetry to {}: {:#}", url, err);
+      }
+    }
+  }
+
+  fn resource(&self) -> serde_json::Value {
+    json!({
+      "attributes": attributes_json(
+        self
+          .config
+          .resource_attributes
+          .iter()
+          .map(|(k, v)| (k.as_str(), v.as_str()))
+          .chain([
+            ("process.runtime.name", "deno"),
+            ("process.runtime.version", crate::version::deno()),
+          ]),
+      ),
+    })
+  }
+
+  fn traces_request(&self, spans: &[SpanData]) -> serde_json::Value {
+    let spans = spans
+      .iter()
+      .map(|span| {
+        // STATUS_CODE_ERROR or STATUS_CODE_UNSET
+        let status_code = if span.is_error { 2 } else { 0 };
+        let mut value = json!({
+          "traceId": hex(&self.trace_id),
+          "spanId": hex(&span.span_id),
+          "name": span.name,
+          // SPAN_KIND_INTERNAL
+          "kind": 1,
+          "startTimeUnixNano": span.start_time.to_string(),
+          "endTimeUnixNano": span.end_time.to_string(),
+          "attributes": attributes_json(
+            span.attributes.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+          ),
+          "status": { "code": status_code },
+        });
+        if let Some(parent_span_id) = &span.parent_span_id {
+          value["parentSpanId"] = json!(hex(parent_span_id));
+        }
+        value
+      })
+      .collect::<Vec<_>>();
+    json!({
+      "resourceSpans": [{
+        "resource": self.resource(),
+        "scopeSpans": [{
+          "scope": { "name": "deno", "version": crate::version::deno() },
+          "spans": spans,
+        }],
+      }],
+    })
+  }
+
+  fn metrics_request(
+    &self,
+    counters: &BTreeMap<CounterKey, u64>,
+    start_time: u64,
+    end_time: u64,
+  ) -> serde_json::Value {
+    let mut data_points: BTreeMap<&str, Vec<serde_json::Value>> =
+      BTreeMap::new();
+    for ((name, attributes), value) in counters {
+      data_points.entry(*name).or_default().push(json!({
+        "attributes": attributes_json(
+          attributes.iter().map(|(k, v)| (*k, v.as_str())),
+        ),
+        "startTimeUnixNano": start_time.to_string(),
+        "timeUnixNano": end_time.to_string(),
+        "asInt": value.to_string(),
+      }));
+    }
+    let metrics = data_points
+      .into_iter()
+      .map(|(name, data_points)| {
+        json!({
+          "name": name,
+          "sum": {
+            "dataPoints": data_points,
+            // AGGREGATION_TEMPORALITY_CUMULATIVE
+            "aggregationTemporality": 2,
+            "isMonotonic": true,
+          },
+        })
+      })
+      .collect::<Vec<_>>();
+    json!({
+      "resourceMetrics": [{
+        "resource": self.resource(),
+        "scopeMetrics": [{
+          "scope": { "name": "deno", "version": crate::version::deno() },
+          "metrics": metrics,
+        }],
+      }],
+    })
+  }
+}
+
+fn attributes_json<'a>(
+  attributes: impl Iterator<Item = (&'a str, &'a str)>,
+) -> serde_json::Value {
+  attributes
+    .map(
+      |(key, value)| json!({ "key": key, "value": { "stringValue": value } }),
+    )
+    .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unix_nanos() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos() as u64)
+    .unwrap_or(0)
+}
+
+/// Starts collecting telemetry when it is configured in the environment,
+/// with a root span of the provided name that the phases are children of.
+/// It is exported on a thread of its own, so that it is exported while the
+/// process runs, like the language server or a server, even when its event
+/// loop is busy.
+pub fn init(root_name: &str) -> Result<(), AnyError> {
+  let config = match TelemetryConfig::from_env()? {
+    Some(config) => config,
+    None => return Ok(()),
+  };
+  if TELEMETRY.set(Telemetry::new(config, root_name)).is_err() {
+    return Ok(());
+  }
+  let (flush_sender, flush_receiver) = mpsc::channel();
+  *TELEMETRY.get().unwrap().flush_sender.lock() = Some(flush_sender);
+  std::thread::Builder::new()
+    .name("telemetry".to_string())
+    .spawn(move || run_exporter(flush_receiver))?;
+  // `Deno.exit()` exits the process without returning to the CLI
+  deno_runtime::ops::os::set_exit_hook(|exit_code| flush(exit_code != 0));
+  Ok(())
+}
+
+fn run_exporter(flush_receiver: mpsc::Receiver<FlushRequest>) {
+  let telemetry = TELEMETRY.get().unwrap();
+  let runtime = deno_runtime::tokio_util::create_basic_runtime();
+  let mut client = None;
+  loop {
+    match flush_receiver.recv_timeout(telemetry.config.export_interval) {
+      Ok(request) => {
+        runtime.block_on(telemetry.export(&mut client, Some(request.is_error)));
+        let _ = request.done_sender.send(());
+        return;
+      }
+      Err(mpsc::RecvTimeoutError::Timeout) => {
+        runtime.block_on(telemetry.export(&mut client, None));
+      }
+      Err(mpsc::RecvTimeoutError::Disconnected) => return,
+    }
+  }
+}
+
+/// Sets the TLS options of the client that the telemetry is exported with,
+/// which are the ones of the command line, like `--cert`.
+pub fn set_http_client_options(
+  root_cert_store: Option<RootCertStore>,
+  unsafely_ignore_certificate_errors: Option<Vec<String>>,
+) {
+  if let Some(telemetry) = TELEMETRY.get() {
+    *telemetry.client_options.lock() = Some(HttpClientOptions {
+      root_cert_store,
+      unsafely_ignore_certificate_errors,
+    });
+  }
+}
+
+pub fn is_enabled() -> bool {
+  TELEMETRY.get().is_some()
+}
+
+/// The name of the root span of a command line, which is its subcommand.
+pub fn root_span_name(args: &[String]) -> String {
+  match args.iter().skip(1).find(|arg| !arg.starts_with('-')) {
+    Some(subcommand) => format!("deno {subcommand}"),
+    None => "deno".to_string(),
+  }
+}
+
+/// A phase of the CLI that is recorded when dropped.
+pub struct Span {
+  data: Option<SpanData>,
+}
+
+impl Span {
+  pub fn set_attribute(&mut self, key: &str, value: impl ToString) {
+    if let Some(data) = &mut self.data {
+      data.attributes.push((key.to_string(), value.to_string()));
+    }
+  }
+
+  /// Marks the phase as failed.
+  pub fn set_error(&mut self) {
+    if let Some(data) = &mut self.data {
+      data.is_error = true;
+    }
+  }
+
+  /// Records the exit code of a subprocess, which fails the phase when it
+  /// isn't zero.
+  pub fn set_exit_code(&mut self, exit_code: i32) {
+    self.set_attribute("process.exit_code", exit_code);
+    if exit_code != 0 {
+      self.set_error();
+    }
+  }
+}
+
+impl Drop for Span {
+  fn drop(&mut self) {
+    if let (Some(mut data), Some(telemetry)) =
+      (self.data.take(), TELEMETRY.get())
+    {
+      data.end_time = unix_nanos();
+      let mut spans = telemetry.spans.lock();
+      if spans.len() < MAX_QUEUED_SPANS {
+        spans.push(data);
+      } else {
+        telemetry.dropped_spans.fetch_add(1, Ordering::Relaxed);
+      }
+    }
+  }
+}
+
+/// Starts a span, which is a no-op when telemetry is not enabled.
+pub fn span(name: &str) -> Span {
+  let data = TELEMETRY.get().map(|telemetry| SpanData {
+    span_id: rand::random(),
+    parent_span_id: Some(telemetry.root_span.lock().span_id),
+    name: name.to_string(),
+    start_time: unix_nanos(),
+    end_time: 0,
+    attributes: Vec::new(),
+    is_error: false,
+  });
+  Span { data }
+}
+
+/// Adds to a monotonic counter, which is a no-op when telemetry is not
+/// enabled.
+pub fn add_to_counter(
+  name: &'static str,
+  attributes: &[(&'static str, &str)],
+  value: u64,
+) {
+  if let Some(telemetry) = TELEMETRY.get() {
+    let attributes = attributes
+      .iter()
+      .map(|(key, value)| (*key, value.to_string()))
+      .collect();
+    *telemetry
+      .counters
+      .lock()
+      .entry((name, attributes))
+      .or_default() += value;
+  }
+}
+
+/// Counts a hit or a miss of one of the caches of the CLI.
+pub fn record_cache(cache: &str, hit: bool) {
+  let name = if hit {
+    "deno.cache.hits"
+  } else {
+    "deno.cache.misses"
+  };
+  add_to_counter(name, &[("cache", cache)], 1);
+}
+
+/// Ends the root span and exports what wasn't exported yet, waiting for the
+/// export. Failures are only logged because they shouldn't change the
+/// outcome of the command.
+pub fn flush(is_error: bool) {
+  let telemetry = match TELEMETRY.get() {
+    Some(telemetry) => telemetry,
+    None => return,
+  };
+  // it was flushed already, like by `Deno.exit()`
+  let flush_sender = match telemetry.flush_sender.lock().take() {
+    Some(flush_sender) => flush_sender,
+    None => return,
+  };
+  let (done_sender, done_receiver) = mpsc::channel();
+  let request = FlushRequest {
+    is_error,
+    done_sender,
+  };
+  if flush_sender.send(request).is_ok() {
+    // the requests time out on their own, this only bounds the wait for an
+    // export that is in progress
+    let _ = done_receiver.recv_timeout(telemetry.config.timeout * 3);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::collections::HashMap;
+
+  fn config_from(
+    vars: &[(&str, &str)],
+  ) -> Result<Option<TelemetryConfig>, AnyError> {
+    let vars = vars
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect::<HashMap<_, _>>();
+    TelemetryConfig::from_vars(|name| vars.get(name).cloned())
+  }
+
+  #[test]
+  fn config_from_env() {
+    assert_eq!(config_from(&[]).unwrap(), None);
+    assert_eq!(
+      config_from(&[
+        ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318"),
+        ("OTEL_SDK_DISABLED", "true"),
+      ])
+      .unwrap(),
+      None
+    );
+
+    let config = config_from(&[
+      ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318/otlp"),
+      ("OTEL_EXPORTER_OTLP_HEADERS", "api-key=a%20b, x-team=build"),
+      (
+        "OTEL_RESOURCE_ATTRIBUTES",
+        "service.name=farm,host.name=ci-1",
+      ),
+      ("OTEL_METRICS_EXPORTER", "none"),
+    ])
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+      config,
+      TelemetryConfig {
+        traces_url: Some(
+          Url::parse("http://localhost:4318/otlp/v1/traces").unwrap()
+        ),
+        metrics_url: None,
+        headers: vec![
+          ("api-key".to_string(), "a b".to_string()),
+          ("x-team".to_string(), "build".to_string()),
+        ],
+        resource_attributes: vec![
+          ("service.name".to_string(), "farm".to_string()),
+          ("host.name".to_string(), "ci-1".to_string()),
+        ],
+        timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+        export_interval: Duration::from_millis(DEFAULT_EXPORT_INTERVAL_MS),
+      }
+    );
+
+    let config = config_from(&[
+      (
+        "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+        "https://metrics.example.com/ingest",
+      ),
+      ("OTEL_SERVICE_NAME", "builder"),
+      ("OTEL_RESOURCE_ATTRIBUTES", "service.name=farm"),
+    ])
+    .unwrap()
+    .unwrap();
+    assert_eq!(config.traces_url, None);
+    assert_eq!(
+      config.metrics_url.unwrap().as_str(),
+      "https://metrics.example.com/ingest"
+    );
+    assert_eq!(
+      config.resource_attributes,
+      vec![("service.name".to_string(), "builder".to_string())]
+    );
+
+    assert!(config_from(&[
+      ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318"),
+      ("OTEL_EXPORTER_OTLP_TIMEOUT", "soon"),
+    ])
+    .is_err());
+    let config = config_from(&[
+      ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318"),
+      ("OTEL_BSP_SCHEDULE_DELAY", "1000"),
+    ])
+    .unwrap()
+    .unwrap();
+    assert_eq!(config.export_interval, Duration::from_millis(1000));
+  }
+
+  #[test]
+  fn export_requests() {
+    let telemetry = Telemetry::new(
+      TelemetryConfig {
+        traces_url: None,
+        metrics_url: None,
+        headers: Vec::new(),
+        resource_attributes: vec![(
+          "service.name".to_string(),
+          "deno".to_string(),
+        )],
+        timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+        export_interval: Duration::from_millis(DEFAULT_EXPORT_INTERVAL_MS),
+      },
+      "deno run",
+    );
+    let root_span = telemetry.root_span.lock().clone();
+    let span = SpanData {
+      span_id: [1; 8],
+      parent_span_id: Some(root_span.span_id),
+      name: "type_check".to_string(),
+      start_time: 10,
+      end_time: 20,
+      attributes: vec![("roots".to_string(), "file:///main.ts".to_string())],
+      is_error: false,
+    };
+    let request = telemetry.traces_request(&[span]);
+    let spans = &request["resourceSpans"][0]["scopeSpans"][0]["spans"];
+    assert_eq!(spans[0]["traceId"], json!(hex(&telemetry.trace_id)));
+    assert_eq!(spans[0]["spanId"], json!("0101010101010101"));
+    assert_eq!(spans[0]["parentSpanId"], json!(hex(&root_span.span_id)));
+    assert_eq!(spans[0]["startTimeUnixNano"], json!("10"));
+    assert_eq!(
+      spans[0]["attributes"],
+      json!([{ "key": "roots", "value": { "stringValue": "file:///main.ts" } }])
+    );
+    assert_eq!(
+      request["resourceSpans"][0]["resource"]["attributes"][0],
+      json!({ "key": "service.name", "value": { "stringValue": "deno" } })
+    );
+
+    let mut counters = BTreeMap::new();
+    counters
+      .insert(("deno.cache.hits", vec![("cache", "emit".to_string())]), 3);
+    counters
+      .insert(("deno.cache.hits", vec![("cache", "http".to_string())]), 1);
+    counters.insert(
+      ("deno.cache.misses", vec![("cache", "emit".to_string())]),
+      2,
+    );
+    let request = telemetry.metrics_request(&counters, 10, 20);
+    let metrics = &request["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+    assert_eq!(metrics.as_array().unwrap().len(), 2);
+    assert_eq!(metrics[0]["name"], json!("deno.cache.hits"));
+    assert_eq!(metrics[0]["sum"]["isMonotonic"], json!(true));
+    assert_eq!(
+      metrics[0]["sum"]["dataPoints"][0],
+      json!({
+        "attributes": [{ "key": "cache", "value": { "stringValue": "emit" } }],
+        "startTimeUnixNano": "10",
+        "timeUnixNano": "20",
+        "asInt": "3",
+      })
+    );
+    assert_eq!(metrics[1]["sum"]["dataPoints"][0]["asInt"], json!("2"));
+  }
+
+  #[test]
+  fn root_span_names() {
+    let args =
+      |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    assert_eq!(root_span_name(&args(&["deno"])), "deno");
+    assert_eq!(
+      root_span_name(&args(&["deno", "--unstable", "run", "main.ts"])),
+      "deno run"
+    );
+  }
+}
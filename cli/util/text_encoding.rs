@@ -62,9 +62,9 @@ pub fn source_map_from_code(code: &ModuleCode) -> Option<Vec<u8>> {
   let last_line = bytes.rsplit(|u| *u == b'\n').next()?;
   if last_line.starts_with(SOURCE_MAP_PREFIX) {
     let input = last_line.split_at(SOURCE_MAP_PREFIX.len()).1;
-    let decoded_map = base64::decode(input)
-      .expect("Unable to decode source map from emitted file.");
-    Some(decoded_map)
+    // inline source maps of `data:` and `blob:` modules are provided by user
+    // code rather than emitted by us, so they might be invalid
+    base64::decode(input).ok()
   } else {
     None
   }
@@ -167,4 +167,22 @@ mod tests {
       );
     }
   }
+
+  #[test]
+  fn test_source_map_from_code() {
+    assert_eq!(
+      source_map_from_code(&ModuleCode::from_static(
+        "test\n//# sourceMappingURL=data:application/json;base64,e30="
+      )),
+      Some(b"{}".to_vec())
+    );
+    assert_eq!(source_map_from_code(&ModuleCode::from_static("test")), None);
+    // user provided inline source maps might not be valid base64
+    assert_eq!(
+      source_map_from_code(&ModuleCode::from_static(
+        "test\n//# sourceMappingURL=data:application/json;base64,%%%"
+      )),
+      None
+    );
+  }
 }
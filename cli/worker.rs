@@ -5,12 +5,15 @@ use std::rc::Rc;
 use std::sync::Arc;
 
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures::task::LocalFutureObj;
 use deno_core::futures::FutureExt;
 use deno_core::located_script_name;
+use deno_core::v8;
 use deno_core::Extension;
 use deno_core::ModuleId;
+use deno_graph::source::Resolver;
 use deno_runtime::colors;
 use deno_runtime::deno_node;
 use deno_runtime::fmt_errors::format_js_error;
@@ -32,6 +35,7 @@ use crate::ops;
 use crate::proc_state::ProcState;
 use crate::tools;
 use crate::tools::coverage::CoverageCollector;
+use crate::tools::profiler::Profiler;
 use crate::util::checksum;
 use crate::version;
 
@@ -40,6 +44,9 @@ pub struct CliMainWorker {
   is_main_cjs: bool,
   worker: MainWorker,
   ps: ProcState,
+  module_loader: Rc<CliModuleLoader>,
+  /// The modules to evaluate before the main module.
+  preload_imports: Vec<ModuleSpecifier>,
 }
 
 impl CliMainWorker {
@@ -47,6 +54,18 @@ impl CliMainWorker {
     self.worker
   }
 
+  pub fn execute_script_static(
+    &mut self,
+    script_name: &'static str,
+    source_code: &'static str,
+  ) -> Result<(), AnyError> {
+    self
+      .worker
+      .js_runtime
+      .execute_script_static(script_name, source_code)?;
+    Ok(())
+  }
+
   pub async fn setup_repl(&mut self) -> Result<(), AnyError> {
     self.worker.run_event_loop(false).await?;
     Ok(())
@@ -55,10 +74,41 @@ impl CliMainWorker {
   pub async fn run(&mut self) -> Result<i32, AnyError> {
     let mut maybe_coverage_collector =
       self.maybe_setup_coverage_collector().await?;
+    let mut maybe_profiler = self.maybe_setup_profiler().await?;
     log::debug!("main_module {}", self.main_module);
 
+    let result = self
+      .run_main_module(&mut maybe_coverage_collector, maybe_profiler.as_mut())
+      .await;
+    // the profiles are also written when the program failed, which is when
+    // they're needed the most
+    if let Some(profiler) = maybe_profiler.as_mut() {
+      let stop_result = self
+        .worker
+        .with_event_loop(profiler.stop().boxed_local())
+        .await;
+      match (&result, stop_result) {
+        (Ok(()), stop_result) => stop_result?,
+        (Err(_), Err(err)) => {
+          log::warn!("Failed writing the profiles: {:#}", err);
+        }
+        (Err(_), Ok(())) => {}
+      }
+    }
+    result?;
+
+    Ok(self.worker.exit_code())
+  }
+
+  async fn run_main_module(
+    &mut self,
+    maybe_coverage_collector: &mut Option<CoverageCollector>,
+    mut maybe_profiler: Option<&mut Profiler>,
+  ) -> Result<(), AnyError> {
     if self.is_main_cjs {
+      let preload_ids = self.load_preload_imports().await?;
       self.initialize_main_module_for_node()?;
+      self.evaluate_preload_imports(preload_ids).await?;
       deno_node::load_cjs_module(
         &mut self.worker.js_runtime,
         &self.main_module.to_file_path().unwrap().to_string_lossy(),
@@ -72,10 +122,28 @@ impl CliMainWorker {
     self.worker.dispatch_load_event(located_script_name!())?;
 
     loop {
-      self
-        .worker
-        .run_event_loop(maybe_coverage_collector.is_none())
-        .await?;
+      if let Some(profiler) = maybe_profiler.as_mut() {
+        // the profiles are also captured on SIGUSR2 while the program runs
+        loop {
+          tokio::select! {
+            result = self.worker.run_event_loop(false) => {
+              result?;
+              break;
+            }
+            _ = profiler.signal() => {
+              self
+                .worker
+                .with_event_loop(profiler.capture().boxed_local())
+                .await?;
+            }
+          }
+        }
+      } else {
+        self
+          .worker
+          .run_event_loop(maybe_coverage_collector.is_none())
+          .await?;
+      }
       if !self
         .worker
         .dispatch_beforeunload_event(located_script_name!())?
@@ -85,6 +153,8 @@ impl CliMainWorker {
     }
 
     self.worker.dispatch_unload_event(located_script_name!())?;
+    // also covers the modules that were imported dynamically
+    self.worker.js_runtime.create_code_caches();
 
     if let Some(coverage_collector) = maybe_coverage_collector.as_mut() {
       self
@@ -92,8 +162,7 @@ impl CliMainWorker {
         .with_event_loop(coverage_collector.stop_collecting().boxed_local())
         .await?;
     }
-
-    Ok(self.worker.exit_code())
+    Ok(())
   }
 
   pub async fn run_for_watcher(self) -> Result<(), AnyError> {
@@ -169,19 +238,60 @@ impl CliMainWorker {
   pub async fn execute_main_module_possibly_with_npm(
     &mut self,
   ) -> Result<(), AnyError> {
+    let preload_ids = self.load_preload_imports().await?;
+    let id = self.worker.preload_main_module(&self.main_module).await?;
+    self
+      .evaluate_module_possibly_with_npm(preload_ids, id)
+      .await
+  }
+
+  /// Executes the main module and returns its namespace object, which lets
+  /// the caller use what the module exports.
+  pub async fn execute_main_module_for_namespace(
+    &mut self,
+  ) -> Result<v8::Global<v8::Object>, AnyError> {
+    let preload_ids = self.load_preload_imports().await?;
     let id = self.worker.preload_main_module(&self.main_module).await?;
-    self.evaluate_module_possibly_with_npm(id).await
+    self
+      .evaluate_module_possibly_with_npm(preload_ids, id)
+      .await?;
+    self.worker.js_runtime.get_module_namespace(id)
   }
 
   pub async fn execute_side_module_possibly_with_npm(
     &mut self,
   ) -> Result<(), AnyError> {
+    let preload_ids = self.load_preload_imports().await?;
     let id = self.worker.preload_side_module(&self.main_module).await?;
-    self.evaluate_module_possibly_with_npm(id).await
+    self
+      .evaluate_module_possibly_with_npm(preload_ids, id)
+      .await
+  }
+
+  /// Loads the modules to evaluate before the main module, which is only
+  /// done for the first module the worker executes.
+  async fn load_preload_imports(&mut self) -> Result<Vec<ModuleId>, AnyError> {
+    let preload_imports = std::mem::take(&mut self.preload_imports);
+    let mut ids = Vec::with_capacity(preload_imports.len());
+    for specifier in &preload_imports {
+      ids.push(self.worker.preload_side_module(specifier).await?);
+    }
+    Ok(ids)
+  }
+
+  async fn evaluate_preload_imports(
+    &mut self,
+    preload_ids: Vec<ModuleId>,
+  ) -> Result<(), AnyError> {
+    for id in preload_ids {
+      self.worker.evaluate_module(id).await?;
+    }
+    Ok(())
   }
 
   async fn evaluate_module_possibly_with_npm(
     &mut self,
+    preload_ids: Vec<ModuleId>,
     id: ModuleId,
   ) -> Result<(), AnyError> {
     if self.ps.npm_resolver.has_packages()
@@ -189,7 +299,10 @@ impl CliMainWorker {
     {
       self.initialize_main_module_for_node()?;
     }
-    self.worker.evaluate_module(id).await
+    self.evaluate_preload_imports(preload_ids).await?;
+    self.worker.evaluate_module(id).await?;
+    self.worker.js_runtime.create_code_caches();
+    Ok(())
   }
 
   fn initialize_main_module_for_node(&mut self) -> Result<(), AnyError> {
@@ -234,6 +347,24 @@ impl CliMainWorker {
       Ok(None)
     }
   }
+
+  pub async fn maybe_setup_profiler(
+    &mut self,
+  ) -> Result<Option<Profiler>, AnyError> {
+    if let Some(options) = self.ps.options.profiler_options() {
+      let session = self.worker.create_inspector_session().await;
+
+      let mut profiler =
+        Profiler::new(options, session, self.module_loader.clone());
+      self
+        .worker
+        .with_event_loop(profiler.start().boxed_local())
+        .await?;
+      Ok(Some(profiler))
+    } else {
+      Ok(None)
+    }
+  }
 }
 
 pub async fn create_main_worker(
@@ -279,6 +410,25 @@ pub async fn create_custom_worker(
     permissions.clone(),
   );
 
+  let worker_modules = ps.options.prepare_worker_modules()?;
+  if !worker_modules.is_empty() {
+    ps.module_load_preparer
+      .prepare_worker_modules(worker_modules, permissions.clone())
+      .await?;
+  }
+  let preload_imports = resolve_preload_imports(ps)?;
+  if !preload_imports.is_empty() {
+    ps.module_load_preparer
+      .prepare_module_load(
+        preload_imports.clone(),
+        false,
+        ps.options.ts_type_lib_window(),
+        PermissionsContainer::allow_all(),
+        permissions.clone(),
+      )
+      .await?;
+  }
+
   let maybe_inspector_server = ps.maybe_inspector_server.clone();
 
   let create_web_worker_cb =
@@ -302,7 +452,13 @@ pub async fn create_custom_worker(
       .join(checksum::gen(&[key.as_bytes()]))
   });
 
-  let mut extensions = ops::cli_exts(ps.npm_resolver.clone());
+  let mut extensions = ops::cli_exts(
+    ps.options.clone(),
+    ps.npm_resolver.clone(),
+    ps.module_load_preparer.clone(),
+    ps.options.worker_permissions_policy(),
+    None,
+  );
   extensions.append(&mut custom_extensions);
 
   let options = WorkerOptions {
@@ -324,15 +480,19 @@ pub async fn create_custom_worker(
       runtime_version: version::deno().to_string(),
       ts_version: version::TYPESCRIPT.to_string(),
       unstable: ps.options.unstable(),
+      unstable_features: ps.options.unstable_features(),
       user_agent: version::get_user_agent().to_string(),
       inspect: ps.options.is_inspecting(),
     },
     extensions,
     startup_snapshot: Some(crate::js::deno_isolate_init()),
+    will_snapshot: false,
     unsafely_ignore_certificate_errors: ps
       .options
       .unsafely_ignore_certificate_errors()
       .clone(),
+    pinned_hosts: ps.pinned_hosts.clone(),
+    fs_overlay: None,
     root_cert_store: Some(ps.root_cert_store.clone()),
     seed: ps.options.seed(),
     source_map_getter: Some(Box::new(module_loader.clone())),
@@ -343,7 +503,7 @@ pub async fn create_custom_worker(
     maybe_inspector_server,
     should_break_on_first_statement: ps.options.inspect_brk().is_some(),
     should_wait_for_inspector_session: ps.options.inspect_wait().is_some(),
-    module_loader,
+    module_loader: module_loader.clone(),
     npm_resolver: Some(Rc::new(ps.npm_resolver.as_require_npm_resolver())),
     get_error_class_fn: Some(&errors::get_error_class_name),
     cache_storage_dir,
@@ -366,16 +526,43 @@ pub async fn create_custom_worker(
     is_main_cjs,
     worker,
     ps: ps.clone(),
+    module_loader,
+    preload_imports,
   })
 }
 
-// TODO(bartlomieju): this callback could have default value
-// and not be required
+/// Resolves the modules to evaluate before the main module of the workers.
+fn resolve_preload_imports(
+  ps: &ProcState,
+) -> Result<Vec<ModuleSpecifier>, AnyError> {
+  let mut specifiers = Vec::new();
+  for referrer_imports in ps.options.preload_imports()? {
+    for import in &referrer_imports.imports {
+      let specifier = ps
+        .resolver
+        .resolve(import, &referrer_imports.referrer)
+        .with_context(|| {
+        format!("Failed resolving the preloaded module \"{import}\"")
+      })?;
+      specifiers.push(specifier);
+    }
+  }
+  Ok(specifiers)
+}
+
 fn create_web_worker_preload_module_callback(
-  _ps: ProcState,
+  ps: ProcState,
 ) -> Arc<WorkerEventCb> {
-  Arc::new(move |worker| {
-    let fut = async move { Ok(worker) };
+  Arc::new(move |mut worker| {
+    let ps = ps.clone();
+    let fut = async move {
+      // web workers evaluate the preloaded modules before their entrypoint
+      // like the main worker
+      for specifier in resolve_preload_imports(&ps)? {
+        worker.execute_side_module(&specifier).await?;
+      }
+      Ok(worker)
+    };
     LocalFutureObj::new(Box::new(fut))
   })
 }
@@ -420,7 +607,13 @@ fn create_web_worker_callback(
     let pre_execute_module_cb =
       create_web_worker_pre_execute_module_callback(ps.clone());
 
-    let extensions = ops::cli_exts(ps.npm_resolver.clone());
+    let extensions = ops::cli_exts(
+      ps.options.clone(),
+      ps.npm_resolver.clone(),
+      ps.module_load_preparer.clone(),
+      ps.options.worker_permissions_policy(),
+      None,
+    );
 
     let maybe_storage_key = ps.options.resolve_storage_key(&args.main_module);
     let cache_storage_dir = maybe_storage_key.map(|key| {
@@ -450,6 +643,7 @@ fn create_web_worker_callback(
         runtime_version: version::deno().to_string(),
         ts_version: version::TYPESCRIPT.to_string(),
         unstable: ps.options.unstable(),
+        unstable_features: ps.options.unstable_features(),
         user_agent: version::get_user_agent().to_string(),
         inspect: ps.options.is_inspecting(),
       },
@@ -459,6 +653,8 @@ fn create_web_worker_callback(
         .options
         .unsafely_ignore_certificate_errors()
         .clone(),
+      pinned_hosts: ps.pinned_hosts.clone(),
+      fs_overlay: None,
       root_cert_store: Some(ps.root_cert_store.clone()),
       seed: ps.options.seed(),
       create_web_worker_cb,
@@ -470,6 +666,10 @@ fn create_web_worker_callback(
       npm_resolver: Some(Rc::new(ps.npm_resolver.as_require_npm_resolver())),
       worker_type: args.worker_type,
       maybe_inspector_server,
+      should_break_on_first_statement: ps.options.inspect_children()
+        && ps.options.inspect_brk().is_some(),
+      should_wait_for_inspector_session: ps.options.inspect_children()
+        && ps.options.inspect_wait().is_some(),
       get_error_class_fn: Some(&errors::get_error_class_name),
       blob_store: ps.blob_store.clone(),
       broadcast_channel: ps.broadcast_channel.clone(),
@@ -507,7 +707,10 @@ mod tests {
       bootstrap: BootstrapOptions::default(),
       extensions: vec![],
       startup_snapshot: Some(crate::js::deno_isolate_init()),
+      will_snapshot: false,
       unsafely_ignore_certificate_errors: None,
+      pinned_hosts: Default::default(),
+      fs_overlay: None,
       root_cert_store: None,
       seed: None,
       format_js_error_fn: None,
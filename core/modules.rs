@@ -202,6 +202,8 @@ impl std::fmt::Display for ModuleType {
 pub struct ModuleSource {
   pub code: ModuleCode,
   pub module_type: ModuleType,
+  /// The V8 code cache of the module, see [`ModuleCodeCache`].
+  pub code_cache: Option<ModuleCodeCache>,
   module_url_specified: ModuleName,
   /// If the module was found somewhere other than the specified address, this will be [`Some`].
   module_url_found: Option<ModuleName>,
@@ -218,6 +220,7 @@ impl ModuleSource {
     Self {
       code,
       module_type: module_type.into(),
+      code_cache: None,
       module_url_specified,
       module_url_found: None,
     }
@@ -240,16 +243,24 @@ impl ModuleSource {
     Self {
       code,
       module_type: module_type.into(),
+      code_cache: None,
       module_url_specified,
       module_url_found,
     }
   }
 
+  /// Attach a V8 code cache to the module, or request one to be created.
+  pub fn with_code_cache(mut self, code_cache: ModuleCodeCache) -> Self {
+    self.code_cache = Some(code_cache);
+    self
+  }
+
   #[cfg(test)]
   pub fn for_test(code: &'static str, file: impl AsRef<str>) -> Self {
     Self {
       code: ModuleCode::from_static(code),
       module_type: ModuleType::JavaScript,
+      code_cache: None,
       module_url_specified: file.as_ref().to_owned().into(),
       module_url_found: None,
     }
@@ -272,12 +283,27 @@ impl ModuleSource {
     Self {
       code: ModuleCode::from_static(code),
       module_type: ModuleType::JavaScript,
+      code_cache: None,
       module_url_specified: specified.into(),
       module_url_found: found,
     }
   }
 }
 
+/// The V8 code cache of an ES module, which lets V8 skip parsing and
+/// compiling the code when it was cached for the same code and V8 version.
+#[derive(Debug)]
+pub enum ModuleCodeCache {
+  /// Code cache data previously handed to
+  /// [`ModuleLoader::code_cache_ready`] for the same code. When V8 rejects
+  /// it, a new one is created like for [`ModuleCodeCache::Requested`].
+  Data(Vec<u8>),
+  /// There is no code cache for the module yet. One is created by
+  /// [`crate::JsRuntime::create_code_caches`] and handed to
+  /// [`ModuleLoader::code_cache_ready`].
+  Requested,
+}
+
 pub(crate) type PrepareLoadFuture =
   dyn Future<Output = (ModuleLoadId, Result<RecursiveModuleLoad, Error>)>;
 pub type ModuleSourceFuture = dyn Future<Output = Result<ModuleSource, Error>>;
@@ -346,6 +372,18 @@ pub trait ModuleLoader {
   ) -> Pin<Box<dyn Future<Output = Result<(), Error>>>> {
     async { Ok(()) }.boxed_local()
   }
+
+  /// Called with the V8 code cache created for a module whose
+  /// [`ModuleSource`] requested one with [`ModuleCodeCache::Requested`] or
+  /// whose [`ModuleCodeCache::Data`] was rejected by V8.
+  ///
+  /// It's not required to implement this method.
+  fn code_cache_ready(
+    &self,
+    _module_specifier: ModuleSpecifier,
+    _code_cache: Vec<u8>,
+  ) {
+  }
 }
 
 /// Placeholder structure used when creating
@@ -866,6 +904,7 @@ impl RecursiveModuleLoad {
             module_url_found,
             module_source.code,
             self.is_dynamic_import(),
+            module_source.code_cache,
           )?
         }
         ModuleType::Json => self.module_map_rc.borrow_mut().new_json_module(
@@ -1106,6 +1145,11 @@ pub(crate) struct ModuleMap {
   // value from `new_json_module` to `json_module_evaluation_steps`
   json_value_store: HashMap<v8::Global<v8::Module>, v8::Global<v8::Value>>,
 
+  // The compiled modules that requested a code cache, which is created once
+  // they were evaluated so it includes the lazily compiled functions.
+  pub(crate) pending_code_caches:
+    Vec<(ModuleId, v8::Global<v8::UnboundModuleScript>)>,
+
   pub(crate) snapshot_loaded_and_not_snapshotting: bool,
 }
 
@@ -1397,6 +1441,7 @@ impl ModuleMap {
       preparing_dynamic_imports: FuturesUnordered::new(),
       pending_dynamic_imports: FuturesUnordered::new(),
       json_value_store: HashMap::new(),
+      pending_code_caches: vec![],
       snapshot_loaded_and_not_snapshotting,
     }
   }
@@ -1478,16 +1523,42 @@ impl ModuleMap {
     name: ModuleName,
     source: ModuleCode,
     is_dynamic_import: bool,
+    code_cache: Option<ModuleCodeCache>,
   ) -> Result<ModuleId, ModuleError> {
     let name_str = name.v8(scope);
     let source_str = source.v8(scope);
 
     let origin = bindings::module_origin(scope, name_str);
-    let source = v8::script_compiler::Source::new(source_str, Some(&origin));
 
     let tc_scope = &mut v8::TryCatch::new(scope);
 
-    let maybe_module = v8::script_compiler::compile_module(tc_scope, source);
+    let (maybe_module, is_code_cache_rejected) = match &code_cache {
+      Some(ModuleCodeCache::Data(data)) => {
+        let mut source = v8::script_compiler::Source::new_with_cached_data(
+          source_str,
+          Some(&origin),
+          v8::script_compiler::CachedData::new(data),
+        );
+        let maybe_module = v8::script_compiler::compile_module2(
+          tc_scope,
+          &mut source,
+          v8::script_compiler::CompileOptions::ConsumeCodeCache,
+          v8::script_compiler::NoCacheReason::NoReason,
+        );
+        // V8 compiles the code instead when it rejects the code cache (ex.
+        // it was created with other flags), so a new one should be created
+        let is_rejected = source
+          .get_cached_data()
+          .map(|cached_data| cached_data.rejected())
+          .unwrap_or(false);
+        (maybe_module, is_rejected)
+      }
+      _ => {
+        let source =
+          v8::script_compiler::Source::new(source_str, Some(&origin));
+        (v8::script_compiler::compile_module(tc_scope, source), false)
+      }
+    };
 
     if tc_scope.has_caught() {
       assert!(maybe_module.is_none());
@@ -1569,6 +1640,15 @@ impl ModuleMap {
       requests,
     );
 
+    if matches!(code_cache, Some(ModuleCodeCache::Requested))
+      || is_code_cache_rejected
+    {
+      let unbound_module_script = module.get_unbound_module_script(tc_scope);
+      self
+        .pending_code_caches
+        .push((id, v8::Global::new(tc_scope, unbound_module_script)));
+    }
+
     Ok(id)
   }
 
@@ -2172,6 +2252,7 @@ import "/a.js";
         "#
           ),
           false,
+          None,
         )
         .unwrap();
 
@@ -2192,6 +2273,7 @@ import "/a.js";
           ascii_str!("file:///b.js"),
           ascii_str!("export function b() { return 'b' }"),
           false,
+          None,
         )
         .unwrap();
       let imports = module_map.get_requested_modules(mod_b).unwrap();
@@ -2283,6 +2365,7 @@ import "/a.js";
           "#
           ),
           false,
+          None,
         )
         .unwrap();
 
@@ -2953,6 +3036,93 @@ if (import.meta.url != 'file:///main_with_code.js') throw Error();
     futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
   }
 
+  #[test]
+  fn module_code_cache() {
+    #[derive(Default)]
+    struct CodeCacheLoader {
+      code_cache: Rc<RefCell<Option<Vec<u8>>>>,
+    }
+
+    impl ModuleLoader for CodeCacheLoader {
+      fn resolve(
+        &self,
+        specifier: &str,
+        referrer: &str,
+        _kind: ResolutionKind,
+      ) -> Result<ModuleSpecifier, Error> {
+        let s = resolve_import(specifier, referrer).unwrap();
+        Ok(s)
+      }
+
+      fn load(
+        &self,
+        module_specifier: &ModuleSpecifier,
+        _maybe_referrer: Option<&ModuleSpecifier>,
+        _is_dyn_import: bool,
+      ) -> Pin<Box<ModuleSourceFuture>> {
+        let code_cache = match self.code_cache.borrow_mut().take() {
+          Some(data) => ModuleCodeCache::Data(data),
+          None => ModuleCodeCache::Requested,
+        };
+        let module_source = ModuleSource::for_test(
+          "globalThis.value = [1, 2, 3].map((n) => n * 2);",
+          module_specifier,
+        )
+        .with_code_cache(code_cache);
+        async move { Ok(module_source) }.boxed()
+      }
+
+      fn code_cache_ready(
+        &self,
+        module_specifier: ModuleSpecifier,
+        code_cache: Vec<u8>,
+      ) {
+        assert_eq!(module_specifier.as_str(), "file:///main_module.js");
+        *self.code_cache.borrow_mut() = Some(code_cache);
+      }
+    }
+
+    let main_specifier = resolve_url("file:///main_module.js").unwrap();
+    let code_cache = Rc::new(RefCell::new(None));
+    let run = || {
+      let loader = Rc::new(CodeCacheLoader {
+        code_cache: code_cache.clone(),
+      });
+      let mut runtime = JsRuntime::new(RuntimeOptions {
+        module_loader: Some(loader),
+        ..Default::default()
+      });
+      let main_id_fut = runtime
+        .load_main_module(&main_specifier, None)
+        .boxed_local();
+      let main_id = futures::executor::block_on(main_id_fut).unwrap();
+      #[allow(clippy::let_underscore_future)]
+      let _ = runtime.mod_evaluate(main_id);
+      futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+      runtime.create_code_caches();
+      runtime
+        .execute_script_static(
+          "check.js",
+          "if (globalThis.value.join() !== '2,4,6') throw Error();",
+        )
+        .unwrap();
+    };
+
+    // The first run requests a code cache, which is created after evaluation.
+    run();
+    assert!(code_cache.borrow().is_some());
+    // The second run consumes it and doesn't request a new one.
+    run();
+    assert!(code_cache.borrow().is_none());
+    // A code cache that V8 rejects is replaced by a new one.
+    *code_cache.borrow_mut() = Some(vec![0; 32]);
+    run();
+    assert!(matches!(
+      code_cache.borrow().as_ref(),
+      Some(data) if *data != vec![0; 32]
+    ));
+  }
+
   #[test]
   fn dynamic_imports_snapshot() {
     //TODO: Once the issue with the ModuleNamespaceEntryGetter is fixed, we can maintain a reference to the module
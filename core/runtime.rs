@@ -2097,7 +2097,7 @@ impl JsRuntime {
       // true for main module
       module_map_rc
         .borrow_mut()
-        .new_es_module(scope, true, specifier, code, false)
+        .new_es_module(scope, true, specifier, code, false, None)
         .map_err(|e| match e {
           ModuleError::Exception(exception) => {
             let exception = v8::Local::new(scope, exception);
@@ -2152,7 +2152,7 @@ impl JsRuntime {
       // false for side module (not main module)
       module_map_rc
         .borrow_mut()
-        .new_es_module(scope, false, specifier, code, false)
+        .new_es_module(scope, false, specifier, code, false, None)
         .map_err(|e| match e {
           ModuleError::Exception(exception) => {
             let exception = v8::Local::new(scope, exception);
@@ -2188,6 +2188,38 @@ impl JsRuntime {
     Ok(root_id)
   }
 
+  /// Creates the V8 code caches of the modules that requested one with
+  /// [`crate::ModuleCodeCache::Requested`] and hands them to
+  /// [`crate::ModuleLoader::code_cache_ready`].
+  ///
+  /// This should be called after the modules were evaluated, so the code
+  /// caches include the functions that V8 compiled lazily.
+  pub fn create_code_caches(&mut self) {
+    let module_map_rc = Self::module_map(self.v8_isolate());
+    let pending_code_caches =
+      std::mem::take(&mut module_map_rc.borrow_mut().pending_code_caches);
+    if pending_code_caches.is_empty() {
+      return;
+    }
+    let scope = &mut self.handle_scope();
+    let module_map = module_map_rc.borrow();
+    for (id, unbound_module_script) in pending_code_caches {
+      let unbound_module_script = v8::Local::new(scope, unbound_module_script);
+      let code_cache = match unbound_module_script.create_code_cache() {
+        Some(code_cache) => code_cache,
+        None => continue,
+      };
+      let specifier =
+        match ModuleSpecifier::parse(module_map.info[id].name.as_ref()) {
+          Ok(specifier) => specifier,
+          Err(_) => continue,
+        };
+      module_map
+        .loader
+        .code_cache_ready(specifier, code_cache.to_vec());
+    }
+  }
+
   fn check_promise_rejections(&mut self) -> Result<(), Error> {
     let state = self.state.clone();
     let scope = &mut self.handle_scope();
@@ -49,7 +49,10 @@ use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::convert::From;
+use std::net::IpAddr;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -74,6 +77,8 @@ pub struct Options {
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub client_cert_chain_and_key: Option<(String, String)>,
   pub file_fetch_handler: Rc<dyn FetchHandler>,
+  /// The addresses that hostnames are resolved to instead of with DNS.
+  pub pinned_hosts: HashMap<String, Vec<IpAddr>>,
 }
 
 impl Default for Options {
@@ -86,6 +91,7 @@ impl Default for Options {
       unsafely_ignore_certificate_errors: None,
       client_cert_chain_and_key: None,
       file_fetch_handler: Rc::new(DefaultFileFetchHandler),
+      pinned_hosts: HashMap::new(),
     }
   }
 }
@@ -113,7 +119,7 @@ deno_core::extension!(deno_fetch,
   state = |state, options| {
     state.put::<Options>(options.options.clone());
     state.put::<reqwest::Client>({
-      create_http_client(
+      let builder = create_http_client_builder(
         &options.options.user_agent,
         options.options.root_cert_store,
         vec![],
@@ -121,7 +127,8 @@ deno_core::extension!(deno_fetch,
         options.options.unsafely_ignore_certificate_errors,
         options.options.client_cert_chain_and_key
       )
-      .unwrap()
+      .unwrap();
+      pin_hosts(builder, &options.options.pinned_hosts).build().unwrap()
     });
   },
 );
@@ -630,7 +637,7 @@ where
     .map(|cert| cert.into_bytes())
     .collect::<Vec<_>>();
 
-  let client = create_http_client(
+  let builder = create_http_client_builder(
     &options.user_agent,
     options.root_cert_store.clone(),
     ca_certs,
@@ -638,6 +645,8 @@ where
     options.unsafely_ignore_certificate_errors.clone(),
     client_cert_chain_and_key,
   )?;
+  // unwrap here because it can only fail when native TLS is used.
+  let client = pin_hosts(builder, &options.pinned_hosts).build().unwrap();
 
   let rid = state.resource_table.add(HttpClientResource::new(client));
   Ok(rid)
@@ -653,6 +662,29 @@ pub fn create_http_client(
   unsafely_ignore_certificate_errors: Option<Vec<String>>,
   client_cert_chain_and_key: Option<(String, String)>,
 ) -> Result<Client, AnyError> {
+  let builder = create_http_client_builder(
+    user_agent,
+    root_cert_store,
+    ca_certs,
+    proxy,
+    unsafely_ignore_certificate_errors,
+    client_cert_chain_and_key,
+  )?;
+  // unwrap here because it can only fail when native TLS is used.
+  Ok(builder.build().unwrap())
+}
+
+/// Create the builder of a reqwest::Client configured like the ones of
+/// [`create_http_client`], for embedders that configure it further (ex. with
+/// proxies that are resolved for each request).
+pub fn create_http_client_builder(
+  user_agent: &str,
+  root_cert_store: Option<RootCertStore>,
+  ca_certs: Vec<Vec<u8>>,
+  proxy: Option<Proxy>,
+  unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  client_cert_chain_and_key: Option<(String, String)>,
+) -> Result<reqwest::ClientBuilder, AnyError> {
   let mut tls_config = deno_tls::create_client_config(
     root_cert_store,
     ca_certs,
@@ -678,6 +710,22 @@ pub fn create_http_client(
     builder = builder.proxy(reqwest_proxy);
   }
 
-  // unwrap here because it can only fail when native TLS is used.
-  Ok(builder.build().unwrap())
+  Ok(builder)
+}
+
+/// Makes the client connect to the pinned addresses of the hostnames instead
+/// of resolving them with DNS, so they can't change while the program runs.
+pub fn pin_hosts(
+  mut builder: reqwest::ClientBuilder,
+  pinned_hosts: &HashMap<String, Vec<IpAddr>>,
+) -> reqwest::ClientBuilder {
+  for (host, ips) in pinned_hosts {
+    // the port of the addresses is ignored in favor of the one of the URL
+    let addrs = ips
+      .iter()
+      .map(|ip| SocketAddr::new(*ip, 0))
+      .collect::<Vec<_>>();
+    builder = builder.resolve_to_addrs(host, &addrs);
+  }
+  builder
 }
@@ -2,6 +2,7 @@
 
 mod interface;
 mod ops;
+mod overlay_fs;
 mod std_fs;
 
 pub use crate::interface::File;
@@ -14,6 +15,8 @@ pub use crate::interface::FsStat;
 pub use crate::interface::OpenOptions;
 use crate::ops::*;
 
+pub use crate::overlay_fs::FsOverlay;
+pub use crate::overlay_fs::OverlayFs;
 pub use crate::std_fs::StdFs;
 
 use deno_core::error::AnyError;
@@ -0,0 +1,434 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_io::StdFileResource;
+
+use crate::interface::FsDirEntry;
+use crate::interface::FsError;
+use crate::interface::FsFileType;
+use crate::interface::FsResult;
+use crate::interface::FsStat;
+use crate::FileSystem;
+use crate::OpenOptions;
+use crate::StdFs;
+
+/// Read-only files that are overlaid on the file system, like the
+/// directories embedded in an executable by `deno compile --include-dir`.
+pub trait FsOverlay: Send + Sync {
+  /// Whether the absolute path is in the overlay, in which case the overlay
+  /// is used for it instead of the file system.
+  fn contains(&self, path: &Path) -> bool;
+  /// Opens a file of the overlay as a real file, which may be an unnamed
+  /// temporary copy of it.
+  fn open(&self, path: &Path) -> io::Result<std::fs::File>;
+  fn read_file(&self, path: &Path) -> io::Result<Vec<u8>>;
+  fn stat(&self, path: &Path) -> io::Result<FsStat>;
+  fn read_dir(&self, path: &Path) -> io::Result<Vec<FsDirEntry>>;
+}
+
+/// The file system of the standard library with an optional read-only
+/// overlay, which makes writing to the paths of the overlay fail.
+#[derive(Clone, Default)]
+pub struct OverlayFs {
+  overlay: Option<Arc<dyn FsOverlay>>,
+}
+
+impl OverlayFs {
+  pub fn new(overlay: Option<Arc<dyn FsOverlay>>) -> Self {
+    Self { overlay }
+  }
+
+  /// Returns the overlay and the absolute path when the path is in it.
+  fn overlay_path(
+    &self,
+    path: impl AsRef<Path>,
+  ) -> Option<(Arc<dyn FsOverlay>, PathBuf)> {
+    let overlay = self.overlay.as_ref()?;
+    let path = path.as_ref();
+    let path = if path.is_absolute() {
+      path.to_path_buf()
+    } else {
+      std::env::current_dir().ok()?.join(path)
+    };
+    if overlay.contains(&path) {
+      Some((overlay.clone(), path))
+    } else {
+      None
+    }
+  }
+
+  fn check_writable(&self, path: impl AsRef<Path>) -> FsResult<()> {
+    match self.overlay_path(path) {
+      Some(_) => Err(read_only_error()),
+      None => Ok(()),
+    }
+  }
+}
+
+fn read_only_error() -> FsError {
+  FsError::Io(io::Error::new(
+    io::ErrorKind::PermissionDenied,
+    "Embedded files are read-only",
+  ))
+}
+
+fn is_write(options: &OpenOptions) -> bool {
+  options.write
+    || options.append
+    || options.create
+    || options.create_new
+    || options.truncate
+}
+
+#[async_trait::async_trait(?Send)]
+impl FileSystem for OverlayFs {
+  type File = StdFileResource;
+
+  fn cwd(&self) -> FsResult<PathBuf> {
+    StdFs.cwd()
+  }
+
+  fn tmp_dir(&self) -> FsResult<PathBuf> {
+    StdFs.tmp_dir()
+  }
+
+  fn chdir(&self, path: impl AsRef<Path>) -> FsResult<()> {
+    StdFs.chdir(path)
+  }
+
+  fn umask(&self, mask: Option<u32>) -> FsResult<u32> {
+    StdFs.umask(mask)
+  }
+
+  fn open_sync(
+    &self,
+    path: impl AsRef<Path>,
+    options: OpenOptions,
+  ) -> FsResult<Self::File> {
+    match self.overlay_path(&path) {
+      Some(_) if is_write(&options) => Err(read_only_error()),
+      Some((overlay, path)) => {
+        Ok(StdFileResource::fs_file(overlay.open(&path)?))
+      }
+      None => StdFs.open_sync(path, options),
+    }
+  }
+  async fn open_async(
+    &self,
+    path: PathBuf,
+    options: OpenOptions,
+  ) -> FsResult<Self::File> {
+    match self.overlay_path(&path) {
+      Some(_) if is_write(&options) => Err(read_only_error()),
+      Some((overlay, path)) => {
+        let file =
+          tokio::task::spawn_blocking(move || overlay.open(&path)).await??;
+        Ok(StdFileResource::fs_file(file))
+      }
+      None => StdFs.open_async(path, options).await,
+    }
+  }
+
+  fn mkdir_sync(
+    &self,
+    path: impl AsRef<Path>,
+    recursive: bool,
+    mode: u32,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.mkdir_sync(path, recursive, mode)
+  }
+  async fn mkdir_async(
+    &self,
+    path: PathBuf,
+    recursive: bool,
+    mode: u32,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.mkdir_async(path, recursive, mode).await
+  }
+
+  fn chmod_sync(&self, path: impl AsRef<Path>, mode: u32) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.chmod_sync(path, mode)
+  }
+  async fn chmod_async(&self, path: PathBuf, mode: u32) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.chmod_async(path, mode).await
+  }
+
+  fn chown_sync(
+    &self,
+    path: impl AsRef<Path>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.chown_sync(path, uid, gid)
+  }
+  async fn chown_async(
+    &self,
+    path: PathBuf,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.chown_async(path, uid, gid).await
+  }
+
+  fn remove_sync(
+    &self,
+    path: impl AsRef<Path>,
+    recursive: bool,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.remove_sync(path, recursive)
+  }
+  async fn remove_async(&self, path: PathBuf, recursive: bool) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.remove_async(path, recursive).await
+  }
+
+  fn copy_file_sync(
+    &self,
+    from: impl AsRef<Path>,
+    to: impl AsRef<Path>,
+  ) -> FsResult<()> {
+    self.check_writable(&to)?;
+    match self.overlay_path(&from) {
+      Some((overlay, from)) => {
+        let data = overlay.read_file(&from)?;
+        StdFs.write_file_sync(
+          to,
+          OpenOptions::write(true, false, false, None),
+          &data,
+        )
+      }
+      None => StdFs.copy_file_sync(from, to),
+    }
+  }
+  async fn copy_file_async(&self, from: PathBuf, to: PathBuf) -> FsResult<()> {
+    self.check_writable(&to)?;
+    match self.overlay_path(&from) {
+      Some((overlay, from)) => {
+        let data =
+          tokio::task::spawn_blocking(move || overlay.read_file(&from))
+            .await??;
+        StdFs
+          .write_file_async(
+            to,
+            OpenOptions::write(true, false, false, None),
+            data,
+          )
+          .await
+      }
+      None => StdFs.copy_file_async(from, to).await,
+    }
+  }
+
+  fn stat_sync(&self, path: impl AsRef<Path>) -> FsResult<FsStat> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.stat(&path).map_err(Into::into),
+      None => StdFs.stat_sync(path),
+    }
+  }
+  async fn stat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.stat(&path).map_err(Into::into),
+      None => StdFs.stat_async(path).await,
+    }
+  }
+
+  fn lstat_sync(&self, path: impl AsRef<Path>) -> FsResult<FsStat> {
+    // the overlay has no symlinks
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.stat(&path).map_err(Into::into),
+      None => StdFs.lstat_sync(path),
+    }
+  }
+  async fn lstat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.stat(&path).map_err(Into::into),
+      None => StdFs.lstat_async(path).await,
+    }
+  }
+
+  fn realpath_sync(&self, path: impl AsRef<Path>) -> FsResult<PathBuf> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => {
+        overlay.stat(&path)?;
+        Ok(path)
+      }
+      None => StdFs.realpath_sync(path),
+    }
+  }
+  async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => {
+        overlay.stat(&path)?;
+        Ok(path)
+      }
+      None => StdFs.realpath_async(path).await,
+    }
+  }
+
+  fn read_dir_sync(&self, path: impl AsRef<Path>) -> FsResult<Vec<FsDirEntry>> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.read_dir(&path).map_err(Into::into),
+      None => StdFs.read_dir_sync(path),
+    }
+  }
+  async fn read_dir_async(&self, path: PathBuf) -> FsResult<Vec<FsDirEntry>> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.read_dir(&path).map_err(Into::into),
+      None => StdFs.read_dir_async(path).await,
+    }
+  }
+
+  fn rename_sync(
+    &self,
+    oldpath: impl AsRef<Path>,
+    newpath: impl AsRef<Path>,
+  ) -> FsResult<()> {
+    self.check_writable(&oldpath)?;
+    self.check_writable(&newpath)?;
+    StdFs.rename_sync(oldpath, newpath)
+  }
+  async fn rename_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.check_writable(&oldpath)?;
+    self.check_writable(&newpath)?;
+    StdFs.rename_async(oldpath, newpath).await
+  }
+
+  fn link_sync(
+    &self,
+    oldpath: impl AsRef<Path>,
+    newpath: impl AsRef<Path>,
+  ) -> FsResult<()> {
+    self.check_writable(&oldpath)?;
+    self.check_writable(&newpath)?;
+    StdFs.link_sync(oldpath, newpath)
+  }
+  async fn link_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    self.check_writable(&oldpath)?;
+    self.check_writable(&newpath)?;
+    StdFs.link_async(oldpath, newpath).await
+  }
+
+  fn symlink_sync(
+    &self,
+    oldpath: impl AsRef<Path>,
+    newpath: impl AsRef<Path>,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.check_writable(&newpath)?;
+    StdFs.symlink_sync(oldpath, newpath, file_type)
+  }
+  async fn symlink_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.check_writable(&newpath)?;
+    StdFs.symlink_async(oldpath, newpath, file_type).await
+  }
+
+  fn read_link_sync(&self, path: impl AsRef<Path>) -> FsResult<PathBuf> {
+    match self.overlay_path(&path) {
+      Some(_) => Err(FsError::Io(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "Embedded files are not symlinks",
+      ))),
+      None => StdFs.read_link_sync(path),
+    }
+  }
+  async fn read_link_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    match self.overlay_path(&path) {
+      Some(_) => self.read_link_sync(path),
+      None => StdFs.read_link_async(path).await,
+    }
+  }
+
+  fn truncate_sync(&self, path: impl AsRef<Path>, len: u64) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.truncate_sync(path, len)
+  }
+  async fn truncate_async(&self, path: PathBuf, len: u64) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.truncate_async(path, len).await
+  }
+
+  fn utime_sync(
+    &self,
+    path: impl AsRef<Path>,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.utime_sync(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+  }
+  async fn utime_async(
+    &self,
+    path: PathBuf,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs
+      .utime_async(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+      .await
+  }
+
+  fn write_file_sync(
+    &self,
+    path: impl AsRef<Path>,
+    options: OpenOptions,
+    data: &[u8],
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.write_file_sync(path, options, data)
+  }
+  async fn write_file_async(
+    &self,
+    path: PathBuf,
+    options: OpenOptions,
+    data: Vec<u8>,
+  ) -> FsResult<()> {
+    self.check_writable(&path)?;
+    StdFs.write_file_async(path, options, data).await
+  }
+
+  fn read_file_sync(&self, path: impl AsRef<Path>) -> FsResult<Vec<u8>> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => overlay.read_file(&path).map_err(Into::into),
+      None => StdFs.read_file_sync(path),
+    }
+  }
+  async fn read_file_async(&self, path: PathBuf) -> FsResult<Vec<u8>> {
+    match self.overlay_path(&path) {
+      Some((overlay, path)) => {
+        tokio::task::spawn_blocking(move || overlay.read_file(&path))
+          .await?
+          .map_err(Into::into)
+      }
+      None => StdFs.read_file_async(path).await,
+    }
+  }
+}
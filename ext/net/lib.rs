@@ -11,6 +11,8 @@ use deno_core::error::AnyError;
 use deno_core::OpState;
 use deno_tls::rustls::RootCertStore;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
@@ -75,6 +77,11 @@ pub struct DefaultTlsOptions {
 /// would override previously used alias.
 pub struct UnsafelyIgnoreCertificateErrors(pub Option<Vec<String>>);
 
+/// The addresses that the connections to hosts are pinned to, rather than
+/// resolving the hosts again when connecting (`--pin-dns`).
+#[derive(Clone, Default)]
+pub struct PinnedHosts(pub HashMap<String, Vec<IpAddr>>);
+
 deno_core::extension!(deno_net,
   deps = [ deno_web ],
   parameters = [ P: NetPermissions ],
@@ -115,8 +122,10 @@ deno_core::extension!(deno_net,
     root_cert_store: Option<RootCertStore>,
     unstable: bool,
     unsafely_ignore_certificate_errors: Option<Vec<String>>,
+    pinned_hosts: HashMap<String, Vec<IpAddr>>,
   },
   state = |state, options| {
+    state.put(PinnedHosts(options.pinned_hosts));
     state.put(DefaultTlsOptions {
       root_cert_store: options.root_cert_store,
     });
@@ -1,9 +1,11 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::io::TcpStreamResource;
+use crate::resolve_addr::pinned_addr;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
 use crate::NetPermissions;
+use crate::PinnedHosts;
 use deno_core::error::bad_resource;
 use deno_core::error::custom_error;
 use deno_core::error::generic_error;
@@ -318,10 +320,18 @@ where
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connect()")?;
   }
 
-  let addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
+  let pinned_addr = pinned_addr(
+    state.borrow().borrow::<PinnedHosts>(),
+    &addr.hostname,
+    addr.port,
+  );
+  let addr = match pinned_addr {
+    Some(addr) => addr,
+    None => resolve_addr(&addr.hostname, addr.port)
+      .await?
+      .next()
+      .ok_or_else(|| generic_error("No resolved address found"))?,
+  };
   let tcp_stream = TcpStream::connect(&addr).await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
@@ -3,10 +3,12 @@
 use crate::io::TcpStreamResource;
 use crate::ops::IpAddr;
 use crate::ops::TlsHandshakeInfo;
+use crate::resolve_addr::pinned_addr;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
 use crate::DefaultTlsOptions;
 use crate::NetPermissions;
+use crate::PinnedHosts;
 use crate::UnsafelyIgnoreCertificateErrors;
 use deno_core::error::bad_resource;
 use deno_core::error::custom_error;
@@ -891,10 +893,18 @@ where
     .clone();
   let hostname_dns = ServerName::try_from(&*addr.hostname)
     .map_err(|_| invalid_hostname(&addr.hostname))?;
-  let connect_addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
+  let pinned_addr = pinned_addr(
+    state.borrow().borrow::<PinnedHosts>(),
+    &addr.hostname,
+    addr.port,
+  );
+  let connect_addr = match pinned_addr {
+    Some(connect_addr) => connect_addr,
+    None => resolve_addr(&addr.hostname, addr.port)
+      .await?
+      .next()
+      .ok_or_else(|| generic_error("No resolved address found"))?,
+  };
   let tcp_stream = TcpStream::connect(connect_addr).await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
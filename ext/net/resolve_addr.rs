@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::PinnedHosts;
 use deno_core::error::AnyError;
 use std::net::SocketAddr;
 use std::net::ToSocketAddrs;
@@ -25,6 +26,20 @@ pub fn resolve_addr_sync(
   Ok(result)
 }
 
+/// Gets the address that the connections to `hostname` are pinned to, if
+/// any, in which case it isn't resolved again.
+pub fn pinned_addr(
+  pinned_hosts: &PinnedHosts,
+  hostname: &str,
+  port: u16,
+) -> Option<SocketAddr> {
+  pinned_hosts
+    .0
+    .get(&hostname.to_ascii_lowercase())
+    .and_then(|addrs| addrs.first())
+    .map(|addr| SocketAddr::new(*addr, port))
+}
+
 fn make_addr_port_pair(hostname: &str, port: u16) -> (&str, u16) {
   // Default to localhost if given just the port. Example: ":80"
   if hostname.is_empty() {
@@ -96,6 +111,26 @@ mod tests {
     assert_eq!(actual, expected);
   }
 
+  #[test]
+  fn pinned_addr_of_host() {
+    let pinned_hosts = PinnedHosts(
+      [(
+        "example.com".to_string(),
+        vec![std::net::IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))],
+      )]
+      .into_iter()
+      .collect(),
+    );
+    assert_eq!(
+      pinned_addr(&pinned_hosts, "Example.com", 443),
+      Some(SocketAddr::V4(SocketAddrV4::new(
+        Ipv4Addr::new(192, 0, 2, 1),
+        443
+      )))
+    );
+    assert_eq!(pinned_addr(&pinned_hosts, "example.org", 443), None);
+  }
+
   #[tokio::test]
   async fn resolve_addr_err() {
     assert!(resolve_addr("INVALID ADDR", 1234).await.is_err());
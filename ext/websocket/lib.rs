@@ -30,8 +30,11 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt;
+use std::net::IpAddr;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -76,6 +79,10 @@ pub trait WebSocketPermissions {
 /// would override previously used alias.
 pub struct UnsafelyIgnoreCertificateErrors(Option<Vec<String>>);
 
+/// The addresses that the connections to hosts are pinned to, rather than
+/// resolving the hosts again when connecting (`--pin-dns`).
+pub struct PinnedHosts(HashMap<String, Vec<IpAddr>>);
+
 type ClientWsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
 type ServerWsStream = WebSocketStream<Pin<Box<dyn Upgraded>>>;
 
@@ -294,8 +301,17 @@ where
     Some("ws") => 80,
     _ => unreachable!(),
   });
-  let addr = format!("{domain}:{port}");
-  let tcp_socket = TcpStream::connect(addr).await?;
+  let pinned_addr = state
+    .borrow()
+    .borrow::<PinnedHosts>()
+    .0
+    .get(&domain.to_ascii_lowercase())
+    .and_then(|addrs| addrs.first())
+    .map(|addr| SocketAddr::new(*addr, *port));
+  let tcp_socket = match pinned_addr {
+    Some(addr) => TcpStream::connect(addr).await?,
+    None => TcpStream::connect(format!("{domain}:{port}")).await?,
+  };
 
   let socket: MaybeTlsStream<TcpStream> = match uri.scheme_str() {
     Some("ws") => MaybeTlsStream::Plain(tcp_socket),
@@ -533,10 +549,12 @@ deno_core::extension!(deno_websocket,
   options = {
     user_agent: String,
     root_cert_store: Option<RootCertStore>,
-    unsafely_ignore_certificate_errors: Option<Vec<String>>
+    unsafely_ignore_certificate_errors: Option<Vec<String>>,
+    pinned_hosts: HashMap<String, Vec<IpAddr>>,
   },
   state = |state, options| {
     state.put::<WsUserAgent>(WsUserAgent(options.user_agent));
+    state.put(PinnedHosts(options.pinned_hosts));
     state.put(UnsafelyIgnoreCertificateErrors(
       options.unsafely_ignore_certificate_errors,
     ));
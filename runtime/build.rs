@@ -300,6 +300,7 @@ mod startup_snapshot {
         "".to_owned(),
         None,
         None,
+        Default::default(),
       ),
       deno_webstorage::deno_webstorage::init_ops_and_esm(None),
       deno_crypto::deno_crypto::init_ops_and_esm(None),
@@ -309,8 +310,10 @@ mod startup_snapshot {
       ),
       deno_ffi::deno_ffi::init_ops_and_esm::<Permissions>(false),
       deno_net::deno_net::init_ops_and_esm::<Permissions>(
-        None, false, // No --unstable.
         None,
+        false, // No --unstable.
+        None,
+        Default::default(),
       ),
       deno_tls::deno_tls::init_ops_and_esm(),
       deno_kv::deno_kv::init_ops_and_esm(
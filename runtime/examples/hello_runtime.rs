@@ -31,7 +31,10 @@ async fn main() -> Result<(), AnyError> {
     bootstrap: BootstrapOptions::default(),
     extensions: vec![],
     startup_snapshot: None,
+    will_snapshot: false,
     unsafely_ignore_certificate_errors: None,
+    pinned_hosts: Default::default(),
+    fs_overlay: None,
     root_cert_store: None,
     seed: None,
     source_map_getter: None,
@@ -0,0 +1,67 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::futures::FutureExt;
+use deno_core::url::Url;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::OpState;
+use deno_fetch::reqwest;
+use deno_fetch::CancelableResponseFuture;
+use deno_fetch::FetchHandler;
+use deno_fetch::FetchRequestBodyResource;
+use deno_fetch::FsFetchHandler;
+use deno_fs::FsOverlay;
+
+/// Creates the handler of `file:` URLs for `fetch()`, which reads the files
+/// of the overlay from it.
+pub fn create_file_fetch_handler(
+  maybe_overlay: Option<Arc<dyn FsOverlay>>,
+) -> Rc<dyn FetchHandler> {
+  match maybe_overlay {
+    Some(overlay) => Rc::new(OverlayFetchHandler(overlay)),
+    None => Rc::new(FsFetchHandler),
+  }
+}
+
+#[derive(Clone)]
+struct OverlayFetchHandler(Arc<dyn FsOverlay>);
+
+impl FetchHandler for OverlayFetchHandler {
+  fn fetch_file(
+    &self,
+    state: &mut OpState,
+    url: Url,
+  ) -> (
+    CancelableResponseFuture,
+    Option<FetchRequestBodyResource>,
+    Option<Rc<CancelHandle>>,
+  ) {
+    let path = match url.to_file_path() {
+      Ok(path) if self.0.contains(&path) => path,
+      _ => return FsFetchHandler.fetch_file(state, url),
+    };
+    let overlay = self.0.clone();
+    let cancel_handle = CancelHandle::new_rc();
+    let response_fut = async move {
+      let data = tokio::task::spawn_blocking(move || overlay.read_file(&path))
+        .await?
+        .map_err(|_| {
+          type_error("NetworkError when attempting to fetch resource.")
+        })?;
+      let response: reqwest::Response = http::Response::builder()
+        .status(http::StatusCode::OK)
+        .body(reqwest::Body::from(data))?
+        .into();
+      Ok::<_, AnyError>(response)
+    }
+    .or_cancel(&cancel_handle)
+    .boxed_local();
+
+    (response_fut, None, Some(cancel_handle))
+  }
+}
@@ -18,18 +18,27 @@ use deno_core::serde_json::Value;
 use deno_core::InspectorMsg;
 use deno_core::InspectorSessionProxy;
 use deno_core::JsRuntime;
+use deno_websocket::tokio_tungstenite;
 use deno_websocket::tokio_tungstenite::tungstenite;
 use deno_websocket::tokio_tungstenite::WebSocketStream;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::net::TcpListener;
 use std::pin::pin;
 use std::process;
 use std::rc::Rc;
 use std::thread;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
+/// The inspectors of child processes, which are listed and proxied by the
+/// server they were registered with.
+type ChildInspectorMap = Rc<RefCell<HashMap<Uuid, ChildInspector>>>;
+
 /// Websocket server that is used to proxy connections from
 /// devtools to the inspector.
 pub struct InspectorServer {
@@ -41,17 +50,46 @@ pub struct InspectorServer {
 
 impl InspectorServer {
   pub fn new(host: SocketAddr, name: &'static str) -> Self {
+    Self::new_with_parent(host, name, None, false)
+  }
+
+  /// Creates a server whose inspectors are also registered with the server
+  /// of a parent process, which lists them along with its own and proxies
+  /// the connections to them. A port of 0 binds an available port. The
+  /// inspectors of child processes are only registered with the server when
+  /// it accepts children, and only from this machine.
+  pub fn new_with_parent(
+    host: SocketAddr,
+    name: &'static str,
+    parent: Option<SocketAddr>,
+    accept_children: bool,
+  ) -> Self {
     let (register_inspector_tx, register_inspector_rx) =
       mpsc::unbounded::<InspectorInfo>();
 
     let (shutdown_server_tx, shutdown_server_rx) = oneshot::channel();
 
+    // bound here, so that the address of an available port is known before
+    // the inspectors are registered
+    let listener = bind_listener(host).unwrap_or_else(|e| {
+      eprintln!("Cannot start inspector server: {e}.");
+      process::exit(1);
+    });
+    let host = listener.local_addr().unwrap_or(host);
+
     let thread_handle = thread::spawn(move || {
       let rt = crate::tokio_util::create_basic_runtime();
       let local = tokio::task::LocalSet::new();
       local.block_on(
         &rt,
-        server(host, register_inspector_rx, shutdown_server_rx, name),
+        server(
+          listener,
+          parent,
+          accept_children,
+          register_inspector_rx,
+          shutdown_server_rx,
+          name,
+        ),
       )
     });
 
@@ -98,6 +136,22 @@ impl Drop for InspectorServer {
   }
 }
 
+fn bind_listener(host: SocketAddr) -> std::io::Result<TcpListener> {
+  let socket = socket2::Socket::new(
+    socket2::Domain::for_address(host),
+    socket2::Type::STREAM,
+    None,
+  )?;
+  // like the listeners of tokio, so that the port can be reused right after
+  // a previous process exited
+  #[cfg(not(windows))]
+  socket.set_reuse_address(true)?;
+  socket.bind(&host.into())?;
+  socket.listen(1024)?;
+  socket.set_nonblocking(true)?;
+  Ok(socket.into())
+}
+
 // Needed so hyper can use non Send futures
 #[derive(Clone)]
 struct LocalExecutor;
@@ -115,6 +169,7 @@ where
 fn handle_ws_request(
   req: http::Request<hyper::Body>,
   inspector_map_rc: Rc<RefCell<HashMap<Uuid, InspectorInfo>>>,
+  child_map: ChildInspectorMap,
 ) -> http::Result<http::Response<hyper::Body>> {
   let (parts, body) = req.into_parts();
   let req = http::Request::from_parts(parts, ());
@@ -125,27 +180,27 @@ fn handle_ws_request(
     .strip_prefix("/ws/")
     .and_then(|s| Uuid::parse_str(s).ok());
 
-  if maybe_uuid.is_none() {
-    return http::Response::builder()
-      .status(http::StatusCode::BAD_REQUEST)
-      .body("Malformed inspector UUID".into());
-  }
-
-  // run in a block to not hold borrow to `inspector_map` for too long
-  let new_session_tx = {
-    let inspector_map = inspector_map_rc.borrow();
-    let maybe_inspector_info = inspector_map.get(&maybe_uuid.unwrap());
-
-    if maybe_inspector_info.is_none() {
+  let uuid = match maybe_uuid {
+    Some(uuid) => uuid,
+    None => {
       return http::Response::builder()
-        .status(http::StatusCode::NOT_FOUND)
-        .body("Invalid inspector UUID".into());
+        .status(http::StatusCode::BAD_REQUEST)
+        .body("Malformed inspector UUID".into());
     }
-
-    let info = maybe_inspector_info.unwrap();
-    info.new_session_tx.clone()
   };
 
+  // the inspector is either one of this process or one of a child process
+  let maybe_new_session_tx = inspector_map_rc
+    .borrow()
+    .get(&uuid)
+    .map(|info| info.new_session_tx.clone());
+  let maybe_child = child_map.borrow().get(&uuid).cloned();
+  if maybe_new_session_tx.is_none() && maybe_child.is_none() {
+    return http::Response::builder()
+      .status(http::StatusCode::NOT_FOUND)
+      .body("Invalid inspector UUID".into());
+  }
+
   let resp = tungstenite::handshake::server::create_response(&req)
     .map(|resp| resp.map(|_| hyper::Body::empty()))
     .or_else(|e| match e {
@@ -158,6 +213,17 @@ fn handle_ws_request(
   let (parts, _) = req.into_parts();
   let req = http::Request::from_parts(parts, body);
 
+  let new_session_tx = match (maybe_new_session_tx, maybe_child) {
+    (Some(new_session_tx), _) => new_session_tx,
+    (None, Some(child)) => {
+      tokio::task::spawn_local(proxy_child_inspector(
+        req, uuid, child, child_map,
+      ));
+      return Ok(resp);
+    }
+    (None, None) => unreachable!(),
+  };
+
   // spawn a task that will wait for websocket connection and then pump messages between
   // the socket and inspector proxy
   tokio::task::spawn_local(async move {
@@ -195,11 +261,19 @@ fn handle_ws_request(
 
 fn handle_json_request(
   inspector_map: Rc<RefCell<HashMap<Uuid, InspectorInfo>>>,
+  child_map: ChildInspectorMap,
+  host: SocketAddr,
 ) -> http::Result<http::Response<hyper::Body>> {
   let data = inspector_map
     .borrow()
     .values()
     .map(|info| info.get_json_metadata())
+    .chain(
+      child_map
+        .borrow()
+        .iter()
+        .map(|(uuid, child)| child.get_json_metadata(host, uuid)),
+    )
     .collect::<Vec<_>>();
   http::Response::builder()
     .status(http::StatusCode::OK)
@@ -217,13 +291,17 @@ fn handle_json_version_request(
 }
 
 async fn server(
-  host: SocketAddr,
+  listener: TcpListener,
+  parent: Option<SocketAddr>,
+  accept_children: bool,
   register_inspector_rx: UnboundedReceiver<InspectorInfo>,
   shutdown_server_rx: oneshot::Receiver<()>,
   name: &str,
 ) {
+  let host = listener.local_addr().unwrap();
   let inspector_map_ =
     Rc::new(RefCell::new(HashMap::<Uuid, InspectorInfo>::new()));
+  let child_map_: ChildInspectorMap = Rc::new(RefCell::new(HashMap::new()));
 
   let inspector_map = Rc::clone(&inspector_map_);
   let mut register_inspector_handler = pin!(register_inspector_rx
@@ -236,6 +314,12 @@ async fn server(
       if info.wait_for_session {
         eprintln!("Deno is waiting for debugger to connect.");
       }
+      if let Some(parent) = parent {
+        let body = info.get_json_metadata().to_string();
+        tokio::task::spawn_local(async move {
+          send_to_parent(parent, "PUT", "/json/children", body).await;
+        });
+      }
       if inspector_map.borrow_mut().insert(info.uuid, info).is_some() {
         panic!("Inspector UUID already in map");
       }
@@ -244,9 +328,18 @@ async fn server(
 
   let inspector_map = Rc::clone(&inspector_map_);
   let mut deregister_inspector_handler = pin!(future::poll_fn(|cx| {
-    inspector_map
-      .borrow_mut()
-      .retain(|_, info| info.deregister_rx.poll_unpin(cx) == Poll::Pending);
+    inspector_map.borrow_mut().retain(|uuid, info| {
+      let is_registered = info.deregister_rx.poll_unpin(cx) == Poll::Pending;
+      if !is_registered {
+        if let Some(parent) = parent {
+          let path = format!("/json/children/{uuid}");
+          tokio::task::spawn_local(async move {
+            send_to_parent(parent, "DELETE", &path, String::new()).await;
+          });
+        }
+      }
+      is_registered
+    });
     Poll::<Never>::Pending
   })
   .fuse());
@@ -257,41 +350,65 @@ async fn server(
     "V8-Version": deno_core::v8_version(),
   });
 
-  let make_svc = hyper::service::make_service_fn(|_| {
-    let inspector_map = Rc::clone(&inspector_map_);
-    let json_version_response = json_version_response.clone();
-
-    future::ok::<_, Infallible>(hyper::service::service_fn(
-      move |req: http::Request<hyper::Body>| {
-        future::ready({
-          match (req.method(), req.uri().path()) {
-            (&http::Method::GET, path) if path.starts_with("/ws/") => {
-              handle_ws_request(req, Rc::clone(&inspector_map))
-            }
-            (&http::Method::GET, "/json/version") => {
-              handle_json_version_request(json_version_response.clone())
+  let make_svc = hyper::service::make_service_fn(
+    |conn: &hyper::server::conn::AddrStream| {
+      let inspector_map = Rc::clone(&inspector_map_);
+      let child_map = Rc::clone(&child_map_);
+      let json_version_response = json_version_response.clone();
+      // the child processes are on this machine, so the inspectors that other
+      // machines register aren't accepted
+      let accepts_children =
+        accept_children && conn.remote_addr().ip().is_loopback();
+
+      future::ok::<_, Infallible>(hyper::service::service_fn(
+        move |req: http::Request<hyper::Body>| {
+          let inspector_map = Rc::clone(&inspector_map);
+          let child_map = Rc::clone(&child_map);
+          let json_version_response = json_version_response.clone();
+          async move {
+            match (req.method(), req.uri().path()) {
+              (&http::Method::GET, path) if path.starts_with("/ws/") => {
+                handle_ws_request(req, inspector_map, child_map)
+              }
+              (&http::Method::GET, "/json/version") => {
+                handle_json_version_request(json_version_response)
+              }
+              (&http::Method::GET, "/json") => {
+                handle_json_request(inspector_map, child_map, host)
+              }
+              (&http::Method::GET, "/json/list") => {
+                handle_json_request(inspector_map, child_map, host)
+              }
+              (&http::Method::PUT, "/json/children") if accepts_children => {
+                handle_child_register_request(req, child_map, host).await
+              }
+              (&http::Method::DELETE, path)
+                if accepts_children && path.starts_with("/json/children/") =>
+              {
+                handle_child_deregister_request(path, child_map)
+              }
+              _ => http::Response::builder()
+                .status(http::StatusCode::NOT_FOUND)
+                .body("Not Found".into()),
             }
-            (&http::Method::GET, "/json") => {
-              handle_json_request(Rc::clone(&inspector_map))
-            }
-            (&http::Method::GET, "/json/list") => {
-              handle_json_request(Rc::clone(&inspector_map))
-            }
-            _ => http::Response::builder()
-              .status(http::StatusCode::NOT_FOUND)
-              .body("Not Found".into()),
           }
-        })
-      },
-    ))
-  });
+        },
+      ))
+    },
+  );
 
   // Create the server manually so it can use the Local Executor
-  let mut server_handler = pin!(hyper::server::Builder::new(
-    hyper::server::conn::AddrIncoming::bind(&host).unwrap_or_else(|e| {
+  let incoming = tokio::net::TcpListener::from_std(listener)
+    .map_err(AnyError::from)
+    .and_then(|listener| {
+      Ok(hyper::server::conn::AddrIncoming::from_listener(listener)?)
+    })
+    .unwrap_or_else(|e| {
       eprintln!("Cannot start inspector server: {e}.");
       process::exit(1);
-    }),
+    });
+  let mut server_handler = pin!(hyper::server::Builder::new(
+    incoming,
     hyper::server::conn::Http::new().with_executor(LocalExecutor),
   )
   .serve(make_svc)
@@ -309,6 +426,146 @@ async fn server(
     _ = deregister_inspector_handler => unreachable!(),
     _ = server_handler => {},
   }
+
+  // the inspectors that are still registered go away with the process
+  if let Some(parent) = parent {
+    let uuids = inspector_map_.borrow().keys().copied().collect::<Vec<_>>();
+    for uuid in uuids {
+      let path = format!("/json/children/{uuid}");
+      send_to_parent(parent, "DELETE", &path, String::new()).await;
+    }
+  }
+}
+
+/// Registers the inspector of a child process, whose metadata is the body of
+/// the request.
+async fn handle_child_register_request(
+  req: http::Request<hyper::Body>,
+  child_map: ChildInspectorMap,
+  host: SocketAddr,
+) -> http::Result<http::Response<hyper::Body>> {
+  let maybe_child = hyper::body::to_bytes(req.into_body())
+    .await
+    .ok()
+    .and_then(|body| serde_json::from_slice::<Value>(&body).ok())
+    .and_then(ChildInspector::from_json_metadata);
+  let (uuid, child) = match maybe_child {
+    Some(child) => child,
+    None => {
+      return http::Response::builder()
+        .status(http::StatusCode::BAD_REQUEST)
+        .body("Invalid child inspector".into());
+    }
+  };
+  eprintln!(
+    "Debugger of a child process listening on {}",
+    websocket_debugger_url(host, &uuid)
+  );
+  child_map.borrow_mut().insert(uuid, child);
+  http::Response::builder()
+    .status(http::StatusCode::OK)
+    .body(hyper::Body::empty())
+}
+
+fn handle_child_deregister_request(
+  path: &str,
+  child_map: ChildInspectorMap,
+) -> http::Result<http::Response<hyper::Body>> {
+  if let Some(uuid) = path
+    .strip_prefix("/json/children/")
+    .and_then(|s| Uuid::parse_str(s).ok())
+  {
+    child_map.borrow_mut().remove(&uuid);
+  }
+  http::Response::builder()
+    .status(http::StatusCode::OK)
+    .body(hyper::Body::empty())
+}
+
+/// Forwards the messages of a debugger connected to this server to the
+/// inspector of a child process.
+async fn proxy_child_inspector(
+  req: http::Request<hyper::Body>,
+  uuid: Uuid,
+  child: ChildInspector,
+  child_map: ChildInspectorMap,
+) {
+  let upgraded = match hyper::upgrade::on(req).await {
+    Ok(upgraded) => upgraded,
+    Err(_) => {
+      eprintln!("Inspector server failed to upgrade to WS connection");
+      return;
+    }
+  };
+  let websocket = WebSocketStream::from_raw_socket(
+    upgraded,
+    tungstenite::protocol::Role::Server,
+    None,
+  )
+  .await;
+  let child_websocket = match tokio::net::TcpStream::connect(child.host).await {
+    Ok(stream) => {
+      tokio_tungstenite::client_async(child.websocket_url.as_str(), stream)
+        .await
+        .map(|(websocket, _)| websocket)
+        .map_err(AnyError::from)
+    }
+    Err(err) => Err(err.into()),
+  };
+  let child_websocket = match child_websocket {
+    Ok(child_websocket) => child_websocket,
+    Err(err) => {
+      // the child process most likely exited without deregistering
+      eprintln!("Failed connecting to the debugger of a child process: {err}");
+      child_map.borrow_mut().remove(&uuid);
+      return;
+    }
+  };
+  let (websocket_tx, websocket_rx) = websocket.split();
+  let (child_websocket_tx, child_websocket_rx) = child_websocket.split();
+  let _ = future::select(
+    pin!(websocket_rx.forward(child_websocket_tx)),
+    pin!(child_websocket_rx.forward(websocket_tx)),
+  )
+  .await;
+}
+
+/// Sends a request about the inspectors of this process to the server of
+/// the parent process. Failures are only reported because the process can
+/// still be debugged with its own server.
+async fn send_to_parent(
+  parent: SocketAddr,
+  method: &str,
+  path: &str,
+  body: String,
+) {
+  let request = format!(
+    "{method} {path} HTTP/1.1\r\nHost: {parent}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+    body.len()
+  );
+  let result = tokio::time::timeout(Duration::from_secs(2), async {
+    let mut stream = tokio::net::TcpStream::connect(parent).await?;
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    if !response.starts_with(b"HTTP/1.1 200") {
+      return Err(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        String::from_utf8_lossy(
+          response.split(|b| *b == b'\r').next().unwrap_or_default(),
+        )
+        .to_string(),
+      ));
+    }
+    Ok(())
+  })
+  .await;
+  let error = match result {
+    Ok(Ok(())) => return,
+    Ok(Err(err)) => err.to_string(),
+    Err(_) => "timed out".to_string(),
+  };
+  eprintln!("Failed registering the debugger with {parent}: {error}");
 }
 
 /// The pump future takes care of forwarding messages between the websocket
@@ -402,14 +659,11 @@ impl InspectorInfo {
   }
 
   pub fn get_websocket_debugger_url(&self) -> String {
-    format!("ws://{}/ws/{}", &self.host, &self.uuid)
+    websocket_debugger_url(self.host, &self.uuid)
   }
 
   fn get_frontend_url(&self) -> String {
-    format!(
-        "devtools://devtools/bundled/js_app.html?ws={}/ws/{}&experiments=true&v8only=true",
-        &self.host, &self.uuid
-      )
+    frontend_url(self.host, &self.uuid)
   }
 
   fn get_title(&self) -> String {
@@ -424,3 +678,56 @@ impl InspectorInfo {
     )
   }
 }
+
+/// The inspector of a child process that was registered with this server.
+#[derive(Clone)]
+struct ChildInspector {
+  host: SocketAddr,
+  websocket_url: String,
+  metadata: Value,
+}
+
+impl ChildInspector {
+  fn from_json_metadata(metadata: Value) -> Option<(Uuid, Self)> {
+    let uuid = Uuid::parse_str(metadata.get("id")?.as_str()?).ok()?;
+    let websocket_url =
+      metadata.get("webSocketDebuggerUrl")?.as_str()?.to_string();
+    let host = websocket_url
+      .strip_prefix("ws://")?
+      .split('/')
+      .next()?
+      .parse::<SocketAddr>()
+      .ok()?;
+    // only the inspectors of processes on this machine are proxied
+    if !host.ip().is_loopback() {
+      return None;
+    }
+    Some((
+      uuid,
+      Self {
+        host,
+        websocket_url,
+        metadata,
+      },
+    ))
+  }
+
+  /// The metadata of the child inspector with the URLs of this server.
+  fn get_json_metadata(&self, host: SocketAddr, uuid: &Uuid) -> Value {
+    let mut metadata = self.metadata.clone();
+    metadata["devtoolsFrontendUrl"] = json!(frontend_url(host, uuid));
+    metadata["webSocketDebuggerUrl"] =
+      json!(websocket_debugger_url(host, uuid));
+    metadata
+  }
+}
+
+fn websocket_debugger_url(host: SocketAddr, uuid: &Uuid) -> String {
+  format!("ws://{host}/ws/{uuid}")
+}
+
+fn frontend_url(host: SocketAddr, uuid: &Uuid) -> String {
+  format!(
+    "devtools://devtools/bundled/js_app.html?ws={host}/ws/{uuid}&experiments=true&v8only=true"
+  )
+}
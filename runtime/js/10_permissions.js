@@ -9,16 +9,21 @@ const {
   ArrayIsArray,
   ArrayPrototypeIncludes,
   ArrayPrototypeMap,
+  ArrayPrototypePush,
   ArrayPrototypeSlice,
+  ErrorCaptureStackTrace,
   MapPrototypeGet,
   MapPrototypeHas,
   MapPrototypeSet,
   FunctionPrototypeCall,
+  ObjectKeys,
   PromiseResolve,
   PromiseReject,
+  ReflectApply,
   ReflectHas,
   SafeArrayIterator,
   SafeMap,
+  StringPrototypeStartsWith,
   Symbol,
   SymbolFor,
   TypeError,
@@ -279,4 +284,74 @@ function serializePermissions(permissions) {
   return permissions;
 }
 
-export { Permissions, permissions, PermissionStatus, serializePermissions };
+/**
+ * Returns the frames of the user code in a captured stack, after the source
+ * maps were applied to them.
+ * @param {{ stack?: string, __callSiteEvals?: object[] }} capture
+ * @returns {string[]}
+ */
+function userStackFrames(capture) {
+  // accessing the stack calls `Error.prepareStackTrace`, which applies the
+  // source maps and stores the evaluated call sites
+  if (capture.stack === undefined || !capture.__callSiteEvals) {
+    return [];
+  }
+  const frames = [];
+  const callSiteEvals = capture.__callSiteEvals;
+  for (let i = 0; i < callSiteEvals.length; ++i) {
+    const { fileName, lineNumber, columnNumber, functionName } =
+      callSiteEvals[i];
+    if (
+      !fileName || StringPrototypeStartsWith(fileName, "ext:") ||
+      StringPrototypeStartsWith(fileName, "internal:")
+    ) {
+      continue;
+    }
+    const location = `${fileName}:${lineNumber}:${columnNumber}`;
+    ArrayPrototypePush(
+      frames,
+      functionName ? `${functionName} (${location})` : location,
+    );
+  }
+  return frames;
+}
+
+/**
+ * Wraps the ops so that the permission checks they make are written to the
+ * trace of `--trace-permissions` with the stack of the code that called them.
+ */
+function wrapOpsForPermissionTrace() {
+  if (!ops.op_permission_trace_enabled()) {
+    return;
+  }
+  const opNames = ObjectKeys(ops);
+  for (let i = 0; i < opNames.length; ++i) {
+    const name = opNames[i];
+    const op = ops[name];
+    if (
+      typeof op !== "function" ||
+      StringPrototypeStartsWith(name, "op_permission_trace_")
+    ) {
+      continue;
+    }
+    ops[name] = function (...args) {
+      const capture = {};
+      ErrorCaptureStackTrace(capture);
+      try {
+        return ReflectApply(op, this, args);
+      } finally {
+        if (ops.op_permission_trace_pending()) {
+          ops.op_permission_trace_flush(userStackFrames(capture));
+        }
+      }
+    };
+  }
+}
+
+export {
+  Permissions,
+  permissions,
+  PermissionStatus,
+  serializePermissions,
+  wrapOpsForPermissionTrace,
+};
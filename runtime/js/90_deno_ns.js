@@ -151,31 +151,50 @@ const denoNs = {
   ChildProcess: process.ChildProcess,
 };
 
+// The unstable APIs keyed by the `--unstable-<name>` flag that enables them.
+const denoNsUnstableByFeature = {
+  "net": {
+    listenDatagram: net.createListenDatagram(
+      ops.op_net_listen_udp,
+      ops.op_net_listen_unixpacket,
+    ),
+  },
+  "fs": {
+    umask: fs.umask,
+    flock: fs.flock,
+    flockSync: fs.flockSync,
+    funlock: fs.funlock,
+    funlockSync: fs.funlockSync,
+  },
+  "http": {
+    HttpClient: httpClient.HttpClient,
+    createHttpClient: httpClient.createHttpClient,
+    // TODO(bartlomieju): why is it needed?
+    http,
+    upgradeHttp: http.upgradeHttp,
+    serve: http.serve,
+  },
+  "ffi": {
+    dlopen: ffi.dlopen,
+    UnsafeCallback: ffi.UnsafeCallback,
+    UnsafePointer: ffi.UnsafePointer,
+    UnsafePointerView: ffi.UnsafePointerView,
+    UnsafeFnPointer: ffi.UnsafeFnPointer,
+  },
+  "kv": {
+    openKv: kv.openKv,
+    Kv: kv.Kv,
+    KvU64: kv.KvU64,
+    KvListIterator: kv.KvListIterator,
+  },
+};
+
 const denoNsUnstable = {
-  listenDatagram: net.createListenDatagram(
-    ops.op_net_listen_udp,
-    ops.op_net_listen_unixpacket,
-  ),
-  umask: fs.umask,
-  HttpClient: httpClient.HttpClient,
-  createHttpClient: httpClient.createHttpClient,
-  // TODO(bartlomieju): why is it needed?
-  http,
-  dlopen: ffi.dlopen,
-  UnsafeCallback: ffi.UnsafeCallback,
-  UnsafePointer: ffi.UnsafePointer,
-  UnsafePointerView: ffi.UnsafePointerView,
-  UnsafeFnPointer: ffi.UnsafeFnPointer,
-  flock: fs.flock,
-  flockSync: fs.flockSync,
-  funlock: fs.funlock,
-  funlockSync: fs.funlockSync,
-  upgradeHttp: http.upgradeHttp,
-  serve: http.serve,
-  openKv: kv.openKv,
-  Kv: kv.Kv,
-  KvU64: kv.KvU64,
-  KvListIterator: kv.KvListIterator,
+  ...denoNsUnstableByFeature.net,
+  ...denoNsUnstableByFeature.fs,
+  ...denoNsUnstableByFeature.http,
+  ...denoNsUnstableByFeature.ffi,
+  ...denoNsUnstableByFeature.kv,
 };
 
-export { denoNs, denoNsUnstable };
+export { denoNs, denoNsUnstable, denoNsUnstableByFeature };
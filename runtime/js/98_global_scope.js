@@ -138,9 +138,19 @@ const windowOrWorkerGlobalScope = {
   [webidl.brand]: util.nonEnumerable(webidl.brand),
 };
 
+// The unstable globals keyed by the `--unstable-<name>` flag that enables them.
+const unstableWindowOrWorkerGlobalScopeByFeature = {
+  "broadcast-channel": {
+    BroadcastChannel: util.nonEnumerable(broadcastChannel.BroadcastChannel),
+  },
+  "net": {
+    WebSocketStream: util.nonEnumerable(webSocketStream.WebSocketStream),
+  },
+};
+
 const unstableWindowOrWorkerGlobalScope = {
-  BroadcastChannel: util.nonEnumerable(broadcastChannel.BroadcastChannel),
-  WebSocketStream: util.nonEnumerable(webSocketStream.WebSocketStream),
+  ...unstableWindowOrWorkerGlobalScopeByFeature["broadcast-channel"],
+  ...unstableWindowOrWorkerGlobalScopeByFeature.net,
 };
 
 class Navigator {
@@ -278,6 +288,7 @@ export {
   setNumCpus,
   setUserAgent,
   unstableWindowOrWorkerGlobalScope,
+  unstableWindowOrWorkerGlobalScopeByFeature,
   windowOrWorkerGlobalScope,
   workerRuntimeGlobalProperties,
 };
@@ -54,16 +54,22 @@ import * as performance from "ext:deno_web/15_performance.js";
 import * as url from "ext:deno_url/00_url.js";
 import * as fetch from "ext:deno_fetch/26_fetch.js";
 import * as messagePort from "ext:deno_web/13_message_port.js";
-import { denoNs, denoNsUnstable } from "ext:runtime/90_deno_ns.js";
+import {
+  denoNs,
+  denoNsUnstable,
+  denoNsUnstableByFeature,
+} from "ext:runtime/90_deno_ns.js";
 import { errors } from "ext:runtime/01_errors.js";
 import * as webidl from "ext:deno_webidl/00_webidl.js";
 import DOMException from "ext:deno_web/01_dom_exception.js";
+import { wrapOpsForPermissionTrace } from "ext:runtime/10_permissions.js";
 import {
   mainRuntimeGlobalProperties,
   setLanguage,
   setNumCpus,
   setUserAgent,
   unstableWindowOrWorkerGlobalScope,
+  unstableWindowOrWorkerGlobalScopeByFeature,
   windowOrWorkerGlobalScope,
   workerRuntimeGlobalProperties,
 } from "ext:runtime/98_global_scope.js";
@@ -406,6 +412,38 @@ const finalDenoNs = {
   ...denoNs,
 };
 
+// Defines the unstable globals of the features enabled with
+// `--unstable-<name>`, or all of them with `--unstable`.
+function defineUnstableGlobals(unstableFlag, unstableFeatures) {
+  if (unstableFlag) {
+    ObjectDefineProperties(globalThis, unstableWindowOrWorkerGlobalScope);
+    return;
+  }
+  for (let i = 0; i < unstableFeatures.length; ++i) {
+    const globals = unstableWindowOrWorkerGlobalScopeByFeature[
+      unstableFeatures[i]
+    ];
+    if (globals) {
+      ObjectDefineProperties(globalThis, globals);
+    }
+  }
+}
+
+// Adds the unstable `Deno` APIs of the features enabled with
+// `--unstable-<name>`, or all of them with `--unstable`.
+function assignUnstableDenoNs(unstableFlag, unstableFeatures) {
+  if (unstableFlag) {
+    ObjectAssign(finalDenoNs, denoNsUnstable);
+    return;
+  }
+  for (let i = 0; i < unstableFeatures.length; ++i) {
+    const ns = denoNsUnstableByFeature[unstableFeatures[i]];
+    if (ns) {
+      ObjectAssign(finalDenoNs, ns);
+    }
+  }
+}
+
 function bootstrapMainRuntime(runtimeOptions) {
   if (hasBootstrapped) {
     throw new Error("Worker runtime already bootstrapped");
@@ -429,6 +467,7 @@ function bootstrapMainRuntime(runtimeOptions) {
     14: userAgent,
     15: inspectFlag,
     // 16: enableTestingFeaturesFlag
+    17: unstableFeatures,
   } = runtimeOptions;
 
   performance.setTimeOrigin(DateNow());
@@ -450,9 +489,7 @@ function bootstrapMainRuntime(runtimeOptions) {
     location.setLocationHref(location_);
   }
 
-  if (unstableFlag) {
-    ObjectDefineProperties(globalThis, unstableWindowOrWorkerGlobalScope);
-  }
+  defineUnstableGlobals(unstableFlag, unstableFeatures);
   ObjectDefineProperties(globalThis, mainRuntimeGlobalProperties);
   ObjectDefineProperties(globalThis, {
     close: util.writable(windowClose),
@@ -486,6 +523,7 @@ function bootstrapMainRuntime(runtimeOptions) {
     noColor,
     isTty,
   );
+  wrapOpsForPermissionTrace();
 
   setNumCpus(cpuCount);
   setUserAgent(userAgent);
@@ -499,9 +537,7 @@ function bootstrapMainRuntime(runtimeOptions) {
     mainModule: util.getterOnly(opMainModule),
   });
 
-  if (unstableFlag) {
-    ObjectAssign(finalDenoNs, denoNsUnstable);
-  }
+  assignUnstableDenoNs(unstableFlag, unstableFeatures);
 
   // Setup `Deno` global - we're actually overriding already existing global
   // `Deno` with `Deno` namespace from "./deno.ts".
@@ -537,6 +573,7 @@ function bootstrapWorkerRuntime(
     // 14: userAgent,
     // 15: inspectFlag,
     16: enableTestingFeaturesFlag,
+    17: unstableFeatures,
   } = runtimeOptions;
 
   performance.setTimeOrigin(DateNow());
@@ -549,9 +586,7 @@ function bootstrapWorkerRuntime(
   delete globalThis.bootstrap;
   hasBootstrapped = true;
 
-  if (unstableFlag) {
-    ObjectDefineProperties(globalThis, unstableWindowOrWorkerGlobalScope);
-  }
+  defineUnstableGlobals(unstableFlag, unstableFeatures);
   ObjectDefineProperties(globalThis, workerRuntimeGlobalProperties);
   ObjectDefineProperties(globalThis, {
     name: util.writable(name),
@@ -596,6 +631,7 @@ function bootstrapWorkerRuntime(
     isTty,
     internalName ?? name,
   );
+  wrapOpsForPermissionTrace();
 
   location.setLocationHref(location_);
 
@@ -604,9 +640,7 @@ function bootstrapWorkerRuntime(
 
   globalThis.pollForMessages = pollForMessages;
 
-  if (unstableFlag) {
-    ObjectAssign(finalDenoNs, denoNsUnstable);
-  }
+  assignUnstableDenoNs(unstableFlag, unstableFeatures);
   ObjectDefineProperties(finalDenoNs, {
     pid: util.readOnly(pid),
     noColor: util.readOnly(noColor),
@@ -33,11 +33,29 @@ pub mod tokio_util;
 pub mod web_worker;
 pub mod worker;
 
+mod fs_fetch_handler;
+mod node_fs;
 mod worker_bootstrap;
 pub use worker_bootstrap::BootstrapOptions;
 
+/// The unstable features that can be enabled individually with an
+/// `--unstable-<name>` flag, instead of enabling all of them with
+/// `--unstable`, along with the help text of the flag.
+pub static UNSTABLE_GRANULAR_FLAGS: &[(&str, &str)] = &[
+  (
+    "broadcast-channel",
+    "Enable unstable `BroadcastChannel` API",
+  ),
+  ("ffi", "Enable unstable FFI APIs"),
+  ("fs", "Enable unstable file system APIs"),
+  ("http", "Enable unstable HTTP APIs"),
+  ("kv", "Enable unstable Key-Value store APIs"),
+  ("net", "Enable unstable net APIs"),
+  ("worker-options", "Enable unstable Web Worker APIs"),
+];
+
 pub struct RuntimeNodeEnv;
 impl deno_node::NodeEnv for RuntimeNodeEnv {
   type P = permissions::PermissionsContainer;
-  type Fs = deno_node::RealFs;
+  type Fs = node_fs::OverlayNodeFs;
 }
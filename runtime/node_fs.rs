@@ -0,0 +1,97 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::normalize_path;
+use deno_fs::FsOverlay;
+use deno_node::NodeFs;
+use deno_node::NodeFsMetadata;
+use deno_node::RealFs;
+use once_cell::sync::OnceCell;
+
+/// The overlay of the process, which `require()` reads from. The methods of
+/// `NodeFs` have no receiver, so it can't be kept by the workers.
+static OVERLAY: OnceCell<Arc<dyn FsOverlay>> = OnceCell::new();
+
+/// Sets the overlay that `require()` reads from, which is the first one of
+/// the process since all of its workers use the same one.
+pub fn set_overlay(overlay: Arc<dyn FsOverlay>) {
+  let _ = OVERLAY.set(overlay);
+}
+
+/// Returns the overlay and the absolute path when the path is in it.
+fn overlay_path(path: &Path) -> Option<(&'static dyn FsOverlay, PathBuf)> {
+  let overlay = OVERLAY.get()?;
+  let path = if path.is_absolute() {
+    normalize_path(path)
+  } else {
+    #[allow(clippy::disallowed_methods)]
+    normalize_path(std::env::current_dir().ok()?.join(path))
+  };
+  if overlay.contains(&path) {
+    Some((overlay.as_ref(), path))
+  } else {
+    None
+  }
+}
+
+/// The file system of `require()`, which reads the files of the overlay from
+/// it, like the file system of the `Deno` and `node:fs` APIs.
+pub struct OverlayNodeFs;
+
+impl NodeFs for OverlayNodeFs {
+  fn current_dir() -> io::Result<PathBuf> {
+    RealFs::current_dir()
+  }
+
+  fn metadata<P: AsRef<Path>>(path: P) -> io::Result<NodeFsMetadata> {
+    match overlay_path(path.as_ref()) {
+      Some((overlay, path)) => {
+        let stat = overlay.stat(&path)?;
+        Ok(NodeFsMetadata {
+          is_file: stat.is_file,
+          is_dir: stat.is_directory,
+        })
+      }
+      None => RealFs::metadata(path),
+    }
+  }
+
+  fn is_file<P: AsRef<Path>>(path: P) -> bool {
+    Self::metadata(path)
+      .map(|metadata| metadata.is_file)
+      .unwrap_or(false)
+  }
+
+  fn is_dir<P: AsRef<Path>>(path: P) -> bool {
+    Self::metadata(path)
+      .map(|metadata| metadata.is_dir)
+      .unwrap_or(false)
+  }
+
+  fn exists<P: AsRef<Path>>(path: P) -> bool {
+    Self::metadata(path).is_ok()
+  }
+
+  fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    match overlay_path(path.as_ref()) {
+      Some((overlay, path)) => String::from_utf8(overlay.read_file(&path)?)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+      None => RealFs::read_to_string(path),
+    }
+  }
+
+  fn canonicalize<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+    // the overlay has no symlinks
+    match overlay_path(path.as_ref()) {
+      Some((overlay, path)) => {
+        overlay.stat(&path)?;
+        Ok(path)
+      }
+      None => RealFs::canonicalize(path),
+    }
+  }
+}
@@ -22,6 +22,8 @@ use std::rc::Rc;
 /// would override previously used alias.
 pub struct UnstableChecker {
   pub unstable: bool,
+  /// The unstable features enabled with their `--unstable-<name>` flag.
+  pub features: Vec<String>,
 }
 
 impl UnstableChecker {
@@ -38,12 +40,30 @@ impl UnstableChecker {
       std::process::exit(70);
     }
   }
+
+  /// Quits the process if neither the --unstable flag nor the flag of the
+  /// unstable feature was provided.
+  pub fn check_unstable_feature(&self, feature: &str, api_name: &str) {
+    if !self.unstable && !self.features.iter().any(|f| f == feature) {
+      eprintln!(
+        "Unstable API '{api_name}'. The --unstable or --unstable-{feature} flag must be provided."
+      );
+      std::process::exit(70);
+    }
+  }
 }
 /// Helper for checking unstable features. Used for sync ops.
 pub fn check_unstable(state: &OpState, api_name: &str) {
   state.borrow::<UnstableChecker>().check_unstable(api_name)
 }
 
+/// Helper for checking an unstable feature that can be enabled individually.
+pub fn check_unstable_feature(state: &OpState, feature: &str, api_name: &str) {
+  state
+    .borrow::<UnstableChecker>()
+    .check_unstable_feature(feature, api_name)
+}
+
 /// Helper for checking unstable features. Used for async ops.
 pub fn check_unstable2(state: &Rc<RefCell<OpState>>, api_name: &str) {
   let state = state.borrow();
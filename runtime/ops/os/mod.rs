@@ -10,12 +10,21 @@ use deno_core::url::Url;
 use deno_core::v8;
 use deno_core::OpState;
 use deno_node::NODE_ENV_VAR_ALLOWLIST;
+use once_cell::sync::OnceCell;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::env;
 
 mod sys_info;
 
+static EXIT_HOOK: OnceCell<fn(i32)> = OnceCell::new();
+
+/// Sets the function that `Deno.exit()` calls with the exit code before it
+/// exits the process, like to flush what the embedder buffered.
+pub fn set_exit_hook(hook: fn(i32)) {
+  let _ = EXIT_HOOK.set(hook);
+}
+
 deno_core::ops!(
   deno_ops,
   [
@@ -155,6 +164,9 @@ fn op_set_exit_code(state: &mut OpState, code: i32) {
 #[op]
 fn op_exit(state: &mut OpState) {
   let code = state.borrow::<ExitCode>().get();
+  if let Some(hook) = EXIT_HOOK.get() {
+    hook(code);
+  }
   std::process::exit(code)
 }
 
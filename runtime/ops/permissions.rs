@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::permissions::parse_sys_kind;
+use crate::permissions::PermissionState;
 use crate::permissions::PermissionsContainer;
 use deno_core::error::custom_error;
 use deno_core::error::uri_error;
@@ -17,6 +18,9 @@ deno_core::extension!(
     op_query_permission,
     op_revoke_permission,
     op_request_permission,
+    op_permission_trace_enabled,
+    op_permission_trace_pending,
+    op_permission_trace_flush,
   ],
   customizer = |ext: &mut deno_core::ExtensionBuilder| {
     ext.force_op_registration();
@@ -33,12 +37,31 @@ pub struct PermissionArgs {
   command: Option<String>,
 }
 
+impl PermissionArgs {
+  /// The value that the deny rules of the permission are checked against.
+  fn deny_rules_value(&self) -> Option<&str> {
+    match self.name.as_str() {
+      "net" => self.host.as_deref(),
+      "env" => self.variable.as_deref(),
+      "sys" => self.kind.as_deref(),
+      "run" => self.command.as_deref(),
+      _ => self.path.as_deref(),
+    }
+  }
+}
+
 #[op]
 pub fn op_query_permission(
   state: &mut OpState,
   args: PermissionArgs,
 ) -> Result<String, AnyError> {
   let permissions = state.borrow::<PermissionsContainer>().0.lock();
+  if permissions
+    .deny
+    .is_denied(&args.name, args.deny_rules_value())
+  {
+    return Ok(PermissionState::Denied.to_string());
+  }
   let path = args.path.as_deref();
   let perm = match args.name.as_ref() {
     "read" => permissions.read.query(path.map(Path::new)),
@@ -107,6 +130,13 @@ pub fn op_request_permission(
   args: PermissionArgs,
 ) -> Result<String, AnyError> {
   let mut permissions = state.borrow_mut::<PermissionsContainer>().0.lock();
+  // a denied value isn't prompted for, since granting it wouldn't allow it
+  if permissions
+    .deny
+    .is_denied(&args.name, args.deny_rules_value())
+  {
+    return Ok(PermissionState::Denied.to_string());
+  }
   let path = args.path.as_deref();
   let perm = match args.name.as_ref() {
     "read" => permissions.read.request(path.map(Path::new)),
@@ -135,6 +165,42 @@ pub fn op_request_permission(
   Ok(perm.to_string())
 }
 
+/// Whether the permission checks are traced (`--trace-permissions`), in which
+/// case the ops are wrapped to flush the trace with their stack.
+#[op]
+pub fn op_permission_trace_enabled(state: &mut OpState) -> bool {
+  state
+    .borrow::<PermissionsContainer>()
+    .0
+    .lock()
+    .trace
+    .is_some()
+}
+
+#[op]
+pub fn op_permission_trace_pending(state: &mut OpState) -> bool {
+  let trace = state
+    .borrow::<PermissionsContainer>()
+    .0
+    .lock()
+    .trace
+    .clone();
+  trace.map(|trace| trace.has_pending()).unwrap_or(false)
+}
+
+#[op]
+pub fn op_permission_trace_flush(state: &mut OpState, stack: Vec<String>) {
+  let trace = state
+    .borrow::<PermissionsContainer>()
+    .0
+    .lock()
+    .trace
+    .clone();
+  if let Some(trace) = trace {
+    trace.flush(&stack);
+  }
+}
+
 fn parse_host(host_str: &str) -> Result<(String, Option<u16>), AnyError> {
   let url = url::Url::parse(&format!("http://{host_str}/"))
     .map_err(|_| uri_error("Invalid host"))?;
@@ -12,6 +12,7 @@ use crate::web_worker::WebWorkerType;
 use crate::web_worker::WorkerControlEvent;
 use crate::web_worker::WorkerId;
 use crate::worker::FormatJsErrorFn;
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::futures::future::LocalFutureObj;
 use deno_core::op;
@@ -44,6 +45,16 @@ pub type WorkerEventCb = dyn Fn(WebWorker) -> LocalFutureObj<'static, Result<Web
   + Sync
   + Send;
 
+/// Returns the permissions that a worker with the main module is restricted
+/// to, on top of the permissions it was created with.
+pub type WorkerPermissionsPolicyCb =
+  dyn Fn(&ModuleSpecifier) -> Option<ChildPermissionsArg> + Sync + Send;
+
+/// Restricts the permissions of workers based on their main module, which
+/// embedders put in the `OpState` of the workers that may create workers.
+#[derive(Clone)]
+pub struct WorkerPermissionsPolicy(pub Arc<WorkerPermissionsPolicyCb>);
+
 /// A holder for callback that is used to create a new
 /// WebWorker. It's a struct instead of a type alias
 /// because `GothamState` used in `OpState` overrides
@@ -161,7 +172,11 @@ fn op_create_worker(
   }
 
   if args.permissions.is_some() {
-    super::check_unstable(state, "Worker.deno.permissions");
+    super::check_unstable_feature(
+      state,
+      "worker-options",
+      "Worker.deno.permissions",
+    );
   }
   let parent_permissions = state.borrow_mut::<PermissionsContainer>();
   let worker_permissions = if let Some(child_permissions_arg) = args.permissions
@@ -188,6 +203,24 @@ fn op_create_worker(
   let module_specifier = deno_core::resolve_url(&specifier)?;
   let worker_name = args_name.unwrap_or_default();
 
+  let maybe_policy_permissions_arg = state
+    .try_borrow::<WorkerPermissionsPolicy>()
+    .and_then(|policy| (policy.0)(&module_specifier));
+  let worker_permissions = match maybe_policy_permissions_arg {
+    Some(policy_permissions_arg) => {
+      let mut permissions = worker_permissions.0.lock();
+      let perms =
+        create_child_permissions(&mut permissions, policy_permissions_arg)
+          .with_context(|| {
+            format!(
+          "Applying the permissions policy of worker \"{module_specifier}\""
+        )
+          })?;
+      PermissionsContainer::new(perms)
+    }
+    None => worker_permissions,
+  };
+
   let (handle_sender, handle_receiver) = std::sync::mpsc::sync_channel::<
     Result<SendableWebWorkerHandle, AnyError>,
   >(1);
@@ -0,0 +1,132 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use std::fmt;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// A network access that was allowed or denied, which is written to the
+/// network audit log.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetAuditEntry<'a> {
+  /// What made the request, like `import` for the downloads of remote
+  /// modules or the name of the runtime API (ex. `fetch()`).
+  pub api: &'a str,
+  pub host: &'a str,
+  pub port: Option<u16>,
+  pub url: Option<&'a str>,
+  pub allowed: bool,
+}
+
+/// Appends the network accesses to a file as newline delimited JSON, for
+/// reviewing what a program connected to (`--net-audit-log`).
+///
+/// Each entry is written with a single write, so multiple logs can append
+/// to the same file.
+#[derive(Clone)]
+pub struct NetAuditLog {
+  path: PathBuf,
+  file: Arc<Mutex<File>>,
+}
+
+impl NetAuditLog {
+  pub fn open(path: &Path) -> Result<Self, AnyError> {
+    let file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(path)
+      .with_context(|| {
+        format!("Failed opening the network audit log {}", path.display())
+      })?;
+    Ok(Self {
+      path: path.to_path_buf(),
+      file: Arc::new(Mutex::new(file)),
+    })
+  }
+
+  pub fn record(&self, entry: &NetAuditEntry) {
+    let time = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_millis() as u64)
+      .unwrap_or(0);
+    let mut value = serde_json::to_value(entry).unwrap();
+    value["time"] = time.into();
+    let mut line = value.to_string();
+    line.push('\n');
+    if let Err(err) = self.file.lock().write_all(line.as_bytes()) {
+      log::warn!(
+        "Failed writing to the network audit log {}: {}",
+        self.path.display(),
+        err
+      );
+    }
+  }
+}
+
+impl fmt::Debug for NetAuditLog {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("NetAuditLog")
+      .field("path", &self.path)
+      .finish()
+  }
+}
+
+// the permissions are compared by their state, which the log is not part of
+impl PartialEq for NetAuditLog {
+  fn eq(&self, other: &Self) -> bool {
+    self.path == other.path
+  }
+}
+
+impl Eq for NetAuditLog {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn appends_entries() {
+    let temp_dir = test_util::TempDir::new();
+    let path = temp_dir.path().join("audit.ndjson");
+    let log = NetAuditLog::open(&path).unwrap();
+    log.record(&NetAuditEntry {
+      api: "fetch()",
+      host: "deno.land",
+      port: Some(443),
+      url: Some("https://deno.land/"),
+      allowed: true,
+    });
+    // a second log of the same file appends to it
+    NetAuditLog::open(&path).unwrap().record(&NetAuditEntry {
+      api: "Deno.connect()",
+      host: "example.com",
+      port: Some(80),
+      url: None,
+      allowed: false,
+    });
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let entries = text
+      .lines()
+      .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["api"], "fetch()");
+    assert_eq!(entries[0]["url"], "https://deno.land/");
+    assert_eq!(entries[0]["allowed"], true);
+    assert!(entries[0]["time"].is_u64());
+    assert_eq!(entries[1]["host"], "example.com");
+    assert_eq!(entries[1]["allowed"], false);
+  }
+}
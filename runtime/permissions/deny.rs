@@ -0,0 +1,248 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::RunDescriptor;
+use crate::fs_util::canonicalize_path;
+use crate::fs_util::resolve_from_cwd;
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use deno_core::serde::Deserialize;
+use deno_core::serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Parts of the permissions that are denied even when they're granted, like a
+/// directory of secrets in a project that may otherwise be read. They come
+/// from the deny rules of a permission set of the configuration file.
+///
+/// A check of an entire permission (ex. `Deno.env.toObject()`) is denied when
+/// the permission has any rules.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PermissionDenyRules {
+  pub read: Vec<PathBuf>,
+  pub write: Vec<PathBuf>,
+  /// Hosts with an optional port, where a host without a port denies all of
+  /// its ports.
+  pub net: Vec<String>,
+  pub env: Vec<String>,
+  pub sys: Vec<String>,
+  pub run: Vec<String>,
+  pub ffi: Vec<PathBuf>,
+}
+
+impl PermissionDenyRules {
+  pub fn is_empty(&self) -> bool {
+    self.read.is_empty()
+      && self.write.is_empty()
+      && self.net.is_empty()
+      && self.env.is_empty()
+      && self.sys.is_empty()
+      && self.run.is_empty()
+      && self.ffi.is_empty()
+  }
+
+  /// Resolves the paths of the rules from the current working directory and
+  /// canonicalizes them, and resolves the `run` rules that are paths like the
+  /// `--allow-run` entries.
+  pub(crate) fn resolve(&self) -> Result<Self, AnyError> {
+    let resolve_paths = |paths: &Vec<PathBuf>| {
+      paths
+        .iter()
+        .map(|path| resolve_from_cwd(path).map(|path| canonicalize(&path)))
+        .collect::<Result<Vec<_>, _>>()
+    };
+    Ok(Self {
+      read: resolve_paths(&self.read)?,
+      write: resolve_paths(&self.write)?,
+      ffi: resolve_paths(&self.ffi)?,
+      run: self
+        .run
+        .iter()
+        .map(|cmd| RunDescriptor::from_str(cmd).unwrap().to_string())
+        .collect(),
+      ..self.clone()
+    })
+  }
+
+  /// Gets if the value of a permission check is denied by the rules, where
+  /// `None` is a check of the entire permission.
+  pub fn is_denied(&self, permission: &str, value: Option<&str>) -> bool {
+    match permission {
+      "read" => is_path_denied(&self.read, value),
+      "write" => is_path_denied(&self.write, value),
+      "ffi" => is_path_denied(&self.ffi, value),
+      "net" => is_denied(&self.net, value, |rule, value| {
+        value == rule
+          || value
+            .strip_prefix(rule)
+            .map(|port| port.starts_with(':'))
+            .unwrap_or(false)
+      }),
+      "env" => is_denied(&self.env, value, |rule, value| {
+        if cfg!(windows) {
+          rule.eq_ignore_ascii_case(value)
+        } else {
+          rule == value
+        }
+      }),
+      "sys" => is_denied(&self.sys, value, |rule, value| rule == value),
+      "run" => is_denied(&self.run, value, |rule, value| {
+        RunDescriptor::from_str(value).unwrap().to_string() == *rule
+      }),
+      _ => false,
+    }
+  }
+
+  /// Checks the value of a permission check against the rules, where `None`
+  /// is a check of the entire permission.
+  pub fn check(
+    &self,
+    permission: &str,
+    value: Option<&str>,
+  ) -> Result<(), AnyError> {
+    if !self.is_denied(permission, value) {
+      return Ok(());
+    }
+    Err(custom_error(
+      "PermissionDenied",
+      match value {
+        Some(value) => format!(
+          "Requires {permission} access to \"{value}\", which is denied by the permission set"
+        ),
+        None => format!(
+          "Requires all {permission} access, which is partially denied by the permission set"
+        ),
+      },
+    ))
+  }
+}
+
+fn is_denied<T>(
+  rules: &[T],
+  value: Option<&str>,
+  matches: impl Fn(&T, &str) -> bool,
+) -> bool {
+  match value {
+    Some(value) => rules.iter().any(|rule| matches(rule, value)),
+    None => !rules.is_empty(),
+  }
+}
+
+/// Checks the path against the canonicalized rules, so a symlink into a denied
+/// directory is denied as well.
+fn is_path_denied(rules: &[PathBuf], value: Option<&str>) -> bool {
+  is_denied(rules, value, |rule, value| {
+    resolve_from_cwd(Path::new(value))
+      .map(|path| canonicalize(&path).starts_with(rule))
+      .unwrap_or(false)
+  })
+}
+
+/// Canonicalizes the closest ancestor of the path that exists, since the
+/// checked paths may not exist yet (ex. a file to write).
+fn canonicalize(path: &Path) -> PathBuf {
+  for ancestor in path.ancestors() {
+    if let Ok(canonicalized) = canonicalize_path(ancestor) {
+      return match path.strip_prefix(ancestor) {
+        Ok(rest) if !rest.as_os_str().is_empty() => canonicalized.join(rest),
+        _ => canonicalized,
+      };
+    }
+  }
+  path.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn denies_matching_values() {
+    let cwd = std::env::current_dir().unwrap();
+    let rules = PermissionDenyRules {
+      read: vec![PathBuf::from("secrets")],
+      net: vec![
+        "internal.example.com".to_string(),
+        "localhost:5432".to_string(),
+      ],
+      env: vec!["AWS_SECRET_ACCESS_KEY".to_string()],
+      ..Default::default()
+    }
+    .resolve()
+    .unwrap();
+    assert_eq!(rules.read, vec![cwd.join("secrets")]);
+
+    assert!(rules.check("read", Some("secrets/key.pem")).is_err());
+    assert!(rules
+      .check("read", Some(&cwd.join("secrets").display().to_string()))
+      .is_err());
+    assert!(rules.check("read", Some("secrets.txt")).is_ok());
+    assert!(rules.check("read", Some("src/main.ts")).is_ok());
+    // reading everything includes the denied paths
+    assert!(rules.check("read", None).is_err());
+    assert!(rules.check("write", None).is_ok());
+
+    assert!(rules.check("net", Some("internal.example.com")).is_err());
+    assert!(rules
+      .check("net", Some("internal.example.com:443"))
+      .is_err());
+    assert!(rules.check("net", Some("localhost:5432")).is_err());
+    assert!(rules.check("net", Some("localhost:8000")).is_ok());
+    assert!(rules.check("net", Some("example.com")).is_ok());
+
+    let err = rules
+      .check("env", Some("AWS_SECRET_ACCESS_KEY"))
+      .unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Requires env access to \"AWS_SECRET_ACCESS_KEY\", which is denied by the permission set"
+    );
+    assert!(rules.check("env", Some("HOME")).is_ok());
+    assert!(rules.check("hrtime", None).is_ok());
+  }
+
+  #[test]
+  fn denies_run_entries_like_allow_run() {
+    let cwd = std::env::current_dir().unwrap();
+    let rules = PermissionDenyRules {
+      run: vec!["curl".to_string(), "./scripts/deploy.sh".to_string()],
+      ..Default::default()
+    }
+    .resolve()
+    .unwrap();
+    assert!(rules.is_denied("run", Some("curl")));
+    assert!(!rules.is_denied("run", Some("git")));
+    assert!(rules.is_denied("run", Some("scripts/deploy.sh")));
+    assert!(rules.is_denied(
+      "run",
+      Some(&cwd.join("scripts/deploy.sh").display().to_string())
+    ));
+    assert!(!rules.is_denied("run", Some("scripts/build.sh")));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn denies_symlinked_paths() {
+    let temp_dir = test_util::TempDir::new();
+    temp_dir.create_dir_all("secrets");
+    temp_dir.write("secrets/key.pem", "key");
+    std::os::unix::fs::symlink(
+      temp_dir.path().join("secrets"),
+      temp_dir.path().join("link"),
+    )
+    .unwrap();
+    let rules = PermissionDenyRules {
+      read: vec![temp_dir.path().join("secrets")],
+      write: vec![temp_dir.path().join("link")],
+      ..Default::default()
+    }
+    .resolve()
+    .unwrap();
+    let path = |path: &str| temp_dir.path().join(path).display().to_string();
+    assert!(rules.is_denied("read", Some(&path("link/key.pem"))));
+    assert!(rules.is_denied("read", Some(&path("link/new.pem"))));
+    assert!(rules.is_denied("write", Some(&path("secrets/new.pem"))));
+    assert!(!rules.is_denied("read", Some(&path("other.txt"))));
+  }
+}
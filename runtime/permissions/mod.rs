@@ -26,13 +26,21 @@ use std::str::FromStr;
 use std::string::ToString;
 use std::sync::Arc;
 
+mod audit;
+mod deny;
 mod prompter;
-use prompter::permission_prompt;
-use prompter::PromptResponse;
+mod trace;
 use prompter::PERMISSION_EMOJI;
 
+pub use audit::NetAuditEntry;
+pub use audit::NetAuditLog;
+pub use deny::PermissionDenyRules;
+pub use prompter::permission_prompt;
 pub use prompter::set_prompt_callbacks;
 pub use prompter::PromptCallback;
+pub use prompter::PromptResponse;
+pub use trace::PermissionTrace;
+pub use trace::TracedPermissionCheck;
 
 static DEBUG_LOG_ENABLED: Lazy<bool> =
   Lazy::new(|| log::log_enabled!(log::Level::Debug));
@@ -1546,6 +1554,15 @@ pub struct Permissions {
   pub run: UnaryPermission<RunDescriptor>,
   pub ffi: UnaryPermission<FfiDescriptor>,
   pub hrtime: UnitPermission,
+  /// Where the decisions of the network permission checks of the runtime
+  /// APIs are recorded.
+  pub net_audit_log: Option<NetAuditLog>,
+  /// Where every permission check is recorded with the stack of the code that
+  /// made it.
+  pub trace: Option<PermissionTrace>,
+  /// The parts of the granted permissions that are denied by a permission
+  /// set.
+  pub deny: PermissionDenyRules,
 }
 
 impl Default for Permissions {
@@ -1559,6 +1576,9 @@ impl Default for Permissions {
       run: Permissions::new_run(&None, false).unwrap(),
       ffi: Permissions::new_ffi(&None, false).unwrap(),
       hrtime: Permissions::new_hrtime(false),
+      net_audit_log: None,
+      trace: None,
+      deny: Default::default(),
     }
   }
 }
@@ -1574,6 +1594,13 @@ pub struct PermissionsOptions {
   pub allow_sys: Option<Vec<String>>,
   pub allow_write: Option<Vec<PathBuf>>,
   pub prompt: bool,
+  /// The file to append the network audit log to.
+  pub net_audit_log: Option<PathBuf>,
+  /// The file to append the permission trace to.
+  pub trace_permissions: Option<PathBuf>,
+  /// The parts of the granted permissions that are denied.
+  #[serde(default)]
+  pub deny: PermissionDenyRules,
 }
 
 impl Permissions {
@@ -1726,6 +1753,17 @@ impl Permissions {
       run: Permissions::new_run(&opts.allow_run, opts.prompt)?,
       ffi: Permissions::new_ffi(&opts.allow_ffi, opts.prompt)?,
       hrtime: Permissions::new_hrtime(opts.allow_hrtime),
+      net_audit_log: opts
+        .net_audit_log
+        .as_deref()
+        .map(NetAuditLog::open)
+        .transpose()?,
+      trace: opts
+        .trace_permissions
+        .as_deref()
+        .map(PermissionTrace::open)
+        .transpose()?,
+      deny: opts.deny.resolve()?,
     })
   }
 
@@ -1739,9 +1777,52 @@ impl Permissions {
       run: Permissions::new_run(&Some(vec![]), false).unwrap(),
       ffi: Permissions::new_ffi(&Some(vec![]), false).unwrap(),
       hrtime: Permissions::new_hrtime(true),
+      net_audit_log: None,
+      trace: None,
+      deny: Default::default(),
     }
   }
 
+  /// Checks the network permission like `net.check`, recording the decision
+  /// in the network audit log.
+  pub fn check_net<T: AsRef<str>>(
+    &mut self,
+    host: &(T, Option<u16>),
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    let result = self.net.check(host, Some(api_name));
+    if let Some(net_audit_log) = &self.net_audit_log {
+      net_audit_log.record(&NetAuditEntry {
+        api: api_name,
+        host: host.0.as_ref(),
+        port: host.1,
+        url: None,
+        allowed: result.is_ok(),
+      });
+    }
+    result
+  }
+
+  /// Checks the network permission like `net.check_url`, recording the
+  /// decision in the network audit log.
+  pub fn check_net_url(
+    &mut self,
+    url: &url::Url,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    let result = self.net.check_url(url, Some(api_name));
+    if let Some(net_audit_log) = &self.net_audit_log {
+      net_audit_log.record(&NetAuditEntry {
+        api: api_name,
+        host: url.host_str().unwrap_or_default(),
+        port: url.port_or_known_default(),
+        url: Some(url.as_str()),
+        allowed: result.is_ok(),
+      });
+    }
+    result
+  }
+
   /// A helper function that determines if the module specifier is a local or
   /// remote, and performs a read or net check for the specifier.
   pub fn check_specifier(
@@ -1780,12 +1861,114 @@ impl PermissionsContainer {
     Self::new(Permissions::allow_all())
   }
 
+  /// Runs a permission check after the deny rules, recording it to the trace
+  /// of `--trace-permissions` until the op call that made it flushes the
+  /// trace.
+  fn traced_check(
+    &self,
+    permission: &'static str,
+    value: impl FnOnce() -> Option<String>,
+    api_name: Option<&str>,
+    check: impl FnOnce(&mut Permissions) -> Result<(), AnyError>,
+  ) -> Result<(), AnyError> {
+    self.run_check(permission, value, api_name, true, check)
+  }
+
+  /// As `traced_check()`, but for the checks whose value is anonymized, which
+  /// are of paths resolved by the runtime (ex. `Deno.execPath()`) rather than
+  /// the program, so the deny rules don't apply to them.
+  fn traced_blind_check(
+    &self,
+    permission: &'static str,
+    display: &str,
+    api_name: &str,
+    check: impl FnOnce(&mut Permissions) -> Result<(), AnyError>,
+  ) -> Result<(), AnyError> {
+    self.run_check(
+      permission,
+      || Some(display.to_string()),
+      Some(api_name),
+      false,
+      check,
+    )
+  }
+
+  fn run_check(
+    &self,
+    permission: &'static str,
+    value: impl FnOnce() -> Option<String>,
+    api_name: Option<&str>,
+    apply_deny_rules: bool,
+    check: impl FnOnce(&mut Permissions) -> Result<(), AnyError>,
+  ) -> Result<(), AnyError> {
+    let mut permissions = self.0.lock();
+    let apply_deny_rules = apply_deny_rules && !permissions.deny.is_empty();
+    let value = if apply_deny_rules || permissions.trace.is_some() {
+      value()
+    } else {
+      None
+    };
+    let result = if apply_deny_rules {
+      permissions.deny.check(permission, value.as_deref())
+    } else {
+      Ok(())
+    }
+    .and_then(|()| check(&mut permissions));
+    if let Some(trace) = &permissions.trace {
+      trace.record(TracedPermissionCheck {
+        permission,
+        value,
+        api: api_name.map(|api_name| api_name.to_string()),
+        granted: result.is_ok(),
+      });
+    }
+    result
+  }
+
   #[inline(always)]
   pub fn check_specifier(
     &self,
     specifier: &ModuleSpecifier,
   ) -> Result<(), AnyError> {
-    self.0.lock().check_specifier(specifier)
+    let mut permissions = self.0.lock();
+    if permissions.trace.is_none() && permissions.deny.is_empty() {
+      return permissions.check_specifier(specifier);
+    }
+    let check = match specifier.scheme() {
+      "data" | "blob" => None,
+      "file" => Some((
+        "read",
+        specifier
+          .to_file_path()
+          .ok()
+          .map(|path| path.display().to_string()),
+      )),
+      _ => Some((
+        "net",
+        specifier
+          .host_str()
+          .map(|host| net_trace_value(host, specifier.port())),
+      )),
+    };
+    let result = match &check {
+      Some((permission, value)) => {
+        permissions.deny.check(permission, value.as_deref())
+      }
+      None => Ok(()),
+    }
+    .and_then(|()| permissions.check_specifier(specifier));
+    if let Some(trace) = &permissions.trace {
+      // imports aren't made by ops, so there is no stack to wait for
+      if let Some((permission, value)) = check {
+        trace.record_now(TracedPermissionCheck {
+          permission,
+          value,
+          api: Some("import()".to_string()),
+          granted: result.is_ok(),
+        });
+      }
+    }
+    result
   }
 
   #[inline(always)]
@@ -1794,7 +1977,12 @@ impl PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.traced_check(
+      "read",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.read.check(path, Some(api_name)),
+    )
   }
 
   #[inline(always)]
@@ -1804,12 +1992,19 @@ impl PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check_blind(path, display, api_name)
+    self.traced_blind_check("read", display, api_name, |perms| {
+      perms.read.check_blind(path, display, api_name)
+    })
   }
 
   #[inline(always)]
   pub fn check_read_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().read.check_all(Some(api_name))
+    self.traced_check(
+      "read",
+      || None,
+      Some(api_name),
+      |perms| perms.read.check_all(Some(api_name)),
+    )
   }
 
   #[inline(always)]
@@ -1818,12 +2013,22 @@ impl PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check(path, Some(api_name))
+    self.traced_check(
+      "write",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.write.check(path, Some(api_name)),
+    )
   }
 
   #[inline(always)]
   pub fn check_write_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().write.check_all(Some(api_name))
+    self.traced_check(
+      "write",
+      || None,
+      Some(api_name),
+      |perms| perms.write.check_all(Some(api_name)),
+    )
   }
 
   #[inline(always)]
@@ -1833,7 +2038,9 @@ impl PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check_blind(path, display, api_name)
+    self.traced_blind_check("write", display, api_name, |perms| {
+      perms.write.check_blind(path, display, api_name)
+    })
   }
 
   #[inline(always)]
@@ -1842,12 +2049,22 @@ impl PermissionsContainer {
     cmd: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().run.check(cmd, Some(api_name))
+    self.traced_check(
+      "run",
+      || Some(cmd.to_string()),
+      Some(api_name),
+      |perms| perms.run.check(cmd, Some(api_name)),
+    )
   }
 
   #[inline(always)]
   pub fn check_run_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().run.check_all(Some(api_name))
+    self.traced_check(
+      "run",
+      || None,
+      Some(api_name),
+      |perms| perms.run.check_all(Some(api_name)),
+    )
   }
 
   #[inline(always)]
@@ -1856,24 +2073,39 @@ impl PermissionsContainer {
     kind: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().sys.check(kind, Some(api_name))
+    self.traced_check(
+      "sys",
+      || Some(kind.to_string()),
+      Some(api_name),
+      |perms| perms.sys.check(kind, Some(api_name)),
+    )
   }
 
   #[inline(always)]
   pub fn check_env(&mut self, var: &str) -> Result<(), AnyError> {
-    self.0.lock().env.check(var)
+    self.traced_check(
+      "env",
+      || Some(var.to_string()),
+      None,
+      |perms| perms.env.check(var),
+    )
   }
 
   #[inline(always)]
   pub fn check_env_all(&mut self) -> Result<(), AnyError> {
-    self.0.lock().env.check_all()
+    self.traced_check("env", || None, None, |perms| perms.env.check_all())
   }
 }
 
 impl deno_node::NodePermissions for PermissionsContainer {
   #[inline(always)]
   fn check_read(&mut self, path: &Path) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, None)
+    self.traced_check(
+      "read",
+      || Some(path.display().to_string()),
+      None,
+      |perms| perms.read.check(path, None),
+    )
   }
 }
 
@@ -1884,7 +2116,12 @@ impl deno_net::NetPermissions for PermissionsContainer {
     host: &(T, Option<u16>),
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check(host, Some(api_name))
+    self.traced_check(
+      "net",
+      || Some(net_trace_value(host.0.as_ref(), host.1)),
+      Some(api_name),
+      |perms| perms.check_net(host, api_name),
+    )
   }
 
   #[inline(always)]
@@ -1893,7 +2130,12 @@ impl deno_net::NetPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.traced_check(
+      "read",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.read.check(path, Some(api_name)),
+    )
   }
 
   #[inline(always)]
@@ -1902,7 +2144,12 @@ impl deno_net::NetPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check(path, Some(api_name))
+    self.traced_check(
+      "write",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.write.check(path, Some(api_name)),
+    )
   }
 }
 
@@ -1913,7 +2160,12 @@ impl deno_fetch::FetchPermissions for PermissionsContainer {
     url: &url::Url,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check_url(url, Some(api_name))
+    self.traced_check(
+      "net",
+      || url.host_str().map(|host| net_trace_value(host, url.port())),
+      Some(api_name),
+      |perms| perms.check_net_url(url, api_name),
+    )
   }
 
   #[inline(always)]
@@ -1922,14 +2174,21 @@ impl deno_fetch::FetchPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.traced_check(
+      "read",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.read.check(path, Some(api_name)),
+    )
   }
 }
 
 impl deno_web::TimersPermission for PermissionsContainer {
   #[inline(always)]
   fn allow_hrtime(&mut self) -> bool {
-    self.0.lock().hrtime.check().is_ok()
+    self
+      .traced_check("hrtime", || None, None, |perms| perms.hrtime.check())
+      .is_ok()
   }
 
   #[inline(always)]
@@ -1945,7 +2204,12 @@ impl deno_websocket::WebSocketPermissions for PermissionsContainer {
     url: &url::Url,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check_url(url, Some(api_name))
+    self.traced_check(
+      "net",
+      || url.host_str().map(|host| net_trace_value(host, url.port())),
+      Some(api_name),
+      |perms| perms.check_net_url(url, api_name),
+    )
   }
 }
 
@@ -1955,7 +2219,12 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.traced_check(
+      "read",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.read.check(path, Some(api_name)),
+    )
   }
 
   fn check_read_blind(
@@ -1964,7 +2233,9 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check_blind(path, display, api_name)
+    self.traced_blind_check("read", display, api_name, |perms| {
+      perms.read.check_blind(path, display, api_name)
+    })
   }
 
   fn check_write(
@@ -1972,7 +2243,12 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check(path, Some(api_name))
+    self.traced_check(
+      "write",
+      || Some(path.display().to_string()),
+      Some(api_name),
+      |perms| perms.write.check(path, Some(api_name)),
+    )
   }
 
   fn check_write_blind(
@@ -1981,15 +2257,27 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check_blind(p, display, api_name)
+    self.traced_blind_check("write", display, api_name, |perms| {
+      perms.write.check_blind(p, display, api_name)
+    })
   }
 
   fn check_read_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().read.check_all(Some(api_name))
+    self.traced_check(
+      "read",
+      || None,
+      Some(api_name),
+      |perms| perms.read.check_all(Some(api_name)),
+    )
   }
 
   fn check_write_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().write.check_all(Some(api_name))
+    self.traced_check(
+      "write",
+      || None,
+      Some(api_name),
+      |perms| perms.write.check_all(Some(api_name)),
+    )
   }
 }
 
@@ -1998,26 +2286,54 @@ impl deno_fs::FsPermissions for PermissionsContainer {
 impl deno_napi::NapiPermissions for PermissionsContainer {
   #[inline(always)]
   fn check(&mut self, path: Option<&Path>) -> Result<(), AnyError> {
-    self.0.lock().ffi.check(path)
+    self.traced_check(
+      "ffi",
+      || path.map(|path| path.display().to_string()),
+      None,
+      |perms| perms.ffi.check(path),
+    )
   }
 }
 
 impl deno_ffi::FfiPermissions for PermissionsContainer {
   #[inline(always)]
   fn check(&mut self, path: Option<&Path>) -> Result<(), AnyError> {
-    self.0.lock().ffi.check(path)
+    self.traced_check(
+      "ffi",
+      || path.map(|path| path.display().to_string()),
+      None,
+      |perms| perms.ffi.check(path),
+    )
   }
 }
 
 impl deno_kv::sqlite::SqliteDbHandlerPermissions for PermissionsContainer {
   #[inline(always)]
   fn check_read(&mut self, p: &Path, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().read.check(p, Some(api_name))
+    self.traced_check(
+      "read",
+      || Some(p.display().to_string()),
+      Some(api_name),
+      |perms| perms.read.check(p, Some(api_name)),
+    )
   }
 
   #[inline(always)]
   fn check_write(&mut self, p: &Path, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().write.check(p, Some(api_name))
+    self.traced_check(
+      "write",
+      || Some(p.display().to_string()),
+      Some(api_name),
+      |perms| perms.write.check(p, Some(api_name)),
+    )
+  }
+}
+
+/// Formats a host like the entries of `--allow-net`.
+fn net_trace_value(host: &str, port: Option<u16>) -> String {
+  match port {
+    Some(port) => format!("{host}:{port}"),
+    None => host.to_string(),
   }
 }
 
@@ -2117,7 +2433,7 @@ fn escalation_error() -> AnyError {
   )
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ChildUnitPermissionArg {
   Inherit,
   Granted,
@@ -2169,7 +2485,7 @@ impl<'de> Deserialize<'de> for ChildUnitPermissionArg {
   }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ChildUnaryPermissionArg {
   Inherit,
   Granted,
@@ -2237,7 +2553,7 @@ impl<'de> Deserialize<'de> for ChildUnaryPermissionArg {
 }
 
 /// Directly deserializable from JS worker and test permission options.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ChildPermissionsArg {
   env: ChildUnaryPermissionArg,
   hrtime: ChildUnitPermissionArg,
@@ -2602,6 +2918,10 @@ pub fn create_child_permissions(
     worker_perms.write.global_state = PermissionState::Denied;
   }
   worker_perms.write.prompt = main_perms.write.prompt;
+  worker_perms.net_audit_log = main_perms.net_audit_log.clone();
+  worker_perms.trace =
+    main_perms.trace.as_ref().map(|trace| trace.for_worker());
+  worker_perms.deny = main_perms.deny.clone();
   Ok(worker_perms)
 }
 
@@ -3005,6 +3325,9 @@ mod tests {
         state: PermissionState::Prompt,
         ..Permissions::new_hrtime(false)
       },
+      net_audit_log: None,
+      trace: None,
+      deny: Default::default(),
     };
     #[rustfmt::skip]
     {
@@ -3144,6 +3467,9 @@ mod tests {
         state: PermissionState::Denied,
         ..Permissions::new_hrtime(false)
       },
+      net_audit_log: None,
+      trace: None,
+      deny: Default::default(),
     };
     #[rustfmt::skip]
     {
@@ -3178,6 +3504,9 @@ mod tests {
       run: Permissions::new_run(&None, true).unwrap(),
       ffi: Permissions::new_ffi(&None, true).unwrap(),
       hrtime: Permissions::new_hrtime(false),
+      net_audit_log: None,
+      trace: None,
+      deny: Default::default(),
     };
 
     let prompt_value = PERMISSION_PROMPT_STUB_VALUE_SETTER.lock();
@@ -3242,6 +3571,9 @@ mod tests {
       run: Permissions::new_run(&None, true).unwrap(),
       ffi: Permissions::new_ffi(&None, true).unwrap(),
       hrtime: Permissions::new_hrtime(false),
+      net_audit_log: None,
+      trace: None,
+      deny: Default::default(),
     };
 
     let prompt_value = PERMISSION_PROMPT_STUB_VALUE_SETTER.lock();
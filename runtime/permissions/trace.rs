@@ -0,0 +1,198 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde::Serialize;
+use deno_core::serde_json;
+use std::fmt;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// A permission check that was recorded by the trace.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracedPermissionCheck {
+  pub permission: &'static str,
+  pub value: Option<String>,
+  pub api: Option<String>,
+  pub granted: bool,
+}
+
+impl TracedPermissionCheck {
+  /// The flag that grants the checked permission, ex. `--allow-read=./data`.
+  pub fn flag(&self) -> String {
+    match &self.value {
+      Some(value) => format!("--allow-{}={}", self.permission, value),
+      None => format!("--allow-{}", self.permission),
+    }
+  }
+}
+
+/// Records every permission check, granted or denied, to a file as newline
+/// delimited JSON with the stack of the JavaScript code that made it
+/// (`--trace-permissions`).
+///
+/// The checks happen inside of ops, where the stack isn't available, so
+/// they're kept pending until the JavaScript side of the op call flushes
+/// them with its (source mapped) stack.
+#[derive(Clone)]
+pub struct PermissionTrace(Arc<PermissionTraceInner>);
+
+struct PermissionTraceInner {
+  path: PathBuf,
+  file: Arc<Mutex<File>>,
+  pending: Mutex<Vec<TracedPermissionCheck>>,
+}
+
+impl PermissionTrace {
+  pub fn open(path: &Path) -> Result<Self, AnyError> {
+    let file = OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(path)
+      .with_context(|| {
+        format!("Failed opening the permission trace {}", path.display())
+      })?;
+    Ok(Self(Arc::new(PermissionTraceInner {
+      path: path.to_path_buf(),
+      file: Arc::new(Mutex::new(file)),
+      pending: Default::default(),
+    })))
+  }
+
+  /// Creates a trace for the permissions of a worker, which writes to the same
+  /// file but flushes its checks with the stacks of its own thread.
+  pub fn for_worker(&self) -> Self {
+    Self(Arc::new(PermissionTraceInner {
+      path: self.0.path.clone(),
+      file: self.0.file.clone(),
+      pending: Default::default(),
+    }))
+  }
+
+  pub fn record(&self, check: TracedPermissionCheck) {
+    self.0.pending.lock().push(check);
+  }
+
+  /// Writes a check that isn't made by an op, like the one of an import,
+  /// without waiting for a stack.
+  pub fn record_now(&self, check: TracedPermissionCheck) {
+    self.0.write(&[check], &[]);
+  }
+
+  pub fn has_pending(&self) -> bool {
+    !self.0.pending.lock().is_empty()
+  }
+
+  /// Writes the pending checks with the stack of the op call that made them.
+  pub fn flush(&self, stack: &[String]) {
+    let pending = std::mem::take(&mut *self.0.pending.lock());
+    self.0.write(&pending, stack);
+  }
+}
+
+impl PermissionTraceInner {
+  fn write(&self, checks: &[TracedPermissionCheck], stack: &[String]) {
+    if checks.is_empty() {
+      return;
+    }
+    let mut text = String::new();
+    for check in checks {
+      let mut value = serde_json::to_value(check).unwrap();
+      value["flag"] = check.flag().into();
+      value["stack"] = stack.into();
+      text.push_str(&value.to_string());
+      text.push('\n');
+    }
+    if let Err(err) = self.file.lock().write_all(text.as_bytes()) {
+      log::warn!(
+        "Failed writing to the permission trace {}: {}",
+        self.path.display(),
+        err
+      );
+    }
+  }
+}
+
+impl Drop for PermissionTraceInner {
+  fn drop(&mut self) {
+    // the checks that were never flushed, ex. the ones of an async op that
+    // were made after the op call returned
+    let pending = std::mem::take(self.pending.get_mut());
+    self.write(&pending, &[]);
+  }
+}
+
+impl fmt::Debug for PermissionTrace {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("PermissionTrace")
+      .field("path", &self.0.path)
+      .finish()
+  }
+}
+
+// the permissions are compared by their state, which the trace is not part of
+impl PartialEq for PermissionTrace {
+  fn eq(&self, other: &Self) -> bool {
+    self.0.path == other.0.path
+  }
+}
+
+impl Eq for PermissionTrace {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn read_check(value: &str, granted: bool) -> TracedPermissionCheck {
+    TracedPermissionCheck {
+      permission: "read",
+      value: Some(value.to_string()),
+      api: Some("Deno.readFile()".to_string()),
+      granted,
+    }
+  }
+
+  #[test]
+  fn flushes_pending_checks_with_stack() {
+    let temp_dir = test_util::TempDir::new();
+    let path = temp_dir.path().join("trace.ndjson");
+    let trace = PermissionTrace::open(&path).unwrap();
+    trace.record(read_check("./a.txt", true));
+    trace.record(read_check("./b.txt", false));
+    assert!(trace.has_pending());
+    trace.flush(&["file:///main.ts:1:7".to_string()]);
+    assert!(!trace.has_pending());
+    // a worker writes to the same file
+    let worker_trace = trace.for_worker();
+    worker_trace.record(TracedPermissionCheck {
+      permission: "hrtime",
+      value: None,
+      api: None,
+      granted: true,
+    });
+    assert!(!trace.has_pending());
+    drop(worker_trace);
+    drop(trace);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let entries = text
+      .lines()
+      .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["permission"], "read");
+    assert_eq!(entries[0]["flag"], "--allow-read=./a.txt");
+    assert_eq!(entries[0]["granted"], true);
+    assert_eq!(entries[0]["stack"][0], "file:///main.ts:1:7");
+    assert_eq!(entries[1]["granted"], false);
+    // unflushed checks are written when the trace is dropped
+    assert_eq!(entries[2]["flag"], "--allow-hrtime");
+    assert_eq!(entries[2]["stack"].as_array().unwrap().len(), 0);
+  }
+}
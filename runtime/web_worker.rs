@@ -34,7 +34,7 @@ use deno_core::RuntimeOptions;
 use deno_core::SharedArrayBufferStore;
 use deno_core::Snapshot;
 use deno_core::SourceMapGetter;
-use deno_fs::StdFs;
+use deno_fs::OverlayFs;
 use deno_io::Stdio;
 use deno_kv::sqlite::SqliteDbHandler;
 use deno_node::RequireNpmResolver;
@@ -44,7 +44,9 @@ use deno_web::BlobStore;
 use deno_web::MessagePort;
 use log::debug;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
+use std::net::IpAddr;
 use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
@@ -323,6 +325,8 @@ pub struct WebWorker {
   pub main_module: ModuleSpecifier,
   poll_for_messages_fn: Option<v8::Global<v8::Value>>,
   bootstrap_fn_global: Option<v8::Global<v8::Function>>,
+  should_break_on_first_statement: bool,
+  should_wait_for_inspector_session: bool,
 }
 
 pub struct WebWorkerOptions {
@@ -330,6 +334,12 @@ pub struct WebWorkerOptions {
   pub extensions: Vec<Extension>,
   pub startup_snapshot: Option<Snapshot>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  /// The addresses that hostnames are resolved to by `fetch()` instead of
+  /// with DNS.
+  pub pinned_hosts: HashMap<String, Vec<IpAddr>>,
+  /// Read-only files overlaid on the file system, which are also read by
+  /// `fetch()` for `file:` URLs.
+  pub fs_overlay: Option<Arc<dyn deno_fs::FsOverlay>>,
   pub root_cert_store: Option<RootCertStore>,
   pub seed: Option<u64>,
   pub module_loader: Rc<dyn ModuleLoader>,
@@ -341,6 +351,13 @@ pub struct WebWorkerOptions {
   pub source_map_getter: Option<Box<dyn SourceMapGetter>>,
   pub worker_type: WebWorkerType,
   pub maybe_inspector_server: Option<Arc<InspectorServer>>,
+  // If true, the worker will wait for inspector session and break on first
+  // statement of user code. Takes higher precedence than
+  // `should_wait_for_inspector_session`.
+  pub should_break_on_first_statement: bool,
+  // If true, the worker will wait for inspector session before executing
+  // user code.
+  pub should_wait_for_inspector_session: bool,
   pub get_error_class_fn: Option<GetErrorClassFn>,
   pub blob_store: BlobStore,
   pub broadcast_channel: InMemoryBroadcastChannel,
@@ -376,22 +393,31 @@ impl WebWorker {
       options = {
         permissions: PermissionsContainer,
         unstable: bool,
+        unstable_features: Vec<String>,
         enable_testing_features: bool,
       },
       state = |state, options| {
         state.put::<PermissionsContainer>(options.permissions);
-        state.put(ops::UnstableChecker { unstable: options.unstable });
+        state.put(ops::UnstableChecker {
+          unstable: options.unstable,
+          features: options.unstable_features,
+        });
         state.put(ops::TestingFeaturesEnabled(options.enable_testing_features));
       },
     );
 
     // Permissions: many ops depend on this
     let unstable = options.bootstrap.unstable;
+    let unstable_features = options.bootstrap.unstable_features.clone();
     let enable_testing_features = options.bootstrap.enable_testing_features;
     let create_cache = options.cache_storage_dir.map(|storage_dir| {
       let create_cache_fn = move || SqliteBackedCache::new(storage_dir.clone());
       CreateCache(Arc::new(create_cache_fn))
     });
+    // `require()` reads the files of the overlay as well
+    if let Some(overlay) = &options.fs_overlay {
+      crate::node_fs::set_overlay(overlay.clone());
+    }
 
     // NOTE(bartlomieju): ordering is important here, keep it in sync with
     // `runtime/build.rs`, `runtime/worker.rs` and `cli/build.rs`!
@@ -411,7 +437,11 @@ impl WebWorker {
           unsafely_ignore_certificate_errors: options
             .unsafely_ignore_certificate_errors
             .clone(),
-          file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          file_fetch_handler:
+            crate::fs_fetch_handler::create_file_fetch_handler(
+              options.fs_overlay.clone(),
+            ),
+          pinned_hosts: options.pinned_hosts.clone(),
           ..Default::default()
         },
       ),
@@ -420,28 +450,35 @@ impl WebWorker {
         options.bootstrap.user_agent.clone(),
         options.root_cert_store.clone(),
         options.unsafely_ignore_certificate_errors.clone(),
+        options.pinned_hosts.clone(),
       ),
       deno_webstorage::deno_webstorage::init_ops(None).disable(),
       deno_crypto::deno_crypto::init_ops(options.seed),
       deno_broadcast_channel::deno_broadcast_channel::init_ops(
         options.broadcast_channel.clone(),
-        unstable,
+        options.bootstrap.has_unstable_feature("broadcast-channel"),
+      ),
+      deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(
+        options.bootstrap.has_unstable_feature("ffi"),
       ),
-      deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(unstable),
       deno_net::deno_net::init_ops::<PermissionsContainer>(
         options.root_cert_store.clone(),
-        unstable,
+        options.bootstrap.has_unstable_feature("net"),
         options.unsafely_ignore_certificate_errors.clone(),
+        options.pinned_hosts.clone(),
       ),
       deno_tls::deno_tls::init_ops(),
       deno_kv::deno_kv::init_ops(
         SqliteDbHandler::<PermissionsContainer>::new(None),
-        unstable,
+        options.bootstrap.has_unstable_feature("kv"),
       ),
       deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
       deno_http::deno_http::init_ops(),
       deno_io::deno_io::init_ops(Some(options.stdio)),
-      deno_fs::deno_fs::init_ops::<_, PermissionsContainer>(unstable, StdFs),
+      deno_fs::deno_fs::init_ops::<_, PermissionsContainer>(
+        options.bootstrap.has_unstable_feature("fs"),
+        OverlayFs::new(options.fs_overlay.clone()),
+      ),
       deno_node::deno_node::init_ops::<crate::RuntimeNodeEnv>(
         options.npm_resolver,
       ),
@@ -464,6 +501,7 @@ impl WebWorker {
       deno_permissions_web_worker::init_ops(
         permissions,
         unstable,
+        unstable_features,
         enable_testing_features,
       ),
     ];
@@ -495,7 +533,8 @@ impl WebWorker {
       server.register_inspector(
         main_module.to_string(),
         &mut js_runtime,
-        false,
+        options.should_break_on_first_statement
+          || options.should_wait_for_inspector_session,
       );
 
       // Put inspector handle into the op state so we can put a breakpoint when
@@ -547,6 +586,10 @@ impl WebWorker {
         main_module,
         poll_for_messages_fn: None,
         bootstrap_fn_global: Some(bootstrap_fn_global),
+        should_break_on_first_statement: options
+          .should_break_on_first_statement,
+        should_wait_for_inspector_session: options
+          .should_wait_for_inspector_session,
       },
       external_handle,
     )
@@ -653,6 +696,7 @@ impl WebWorker {
     &mut self,
     id: ModuleId,
   ) -> Result<(), AnyError> {
+    self.wait_for_inspector_session();
     let mut receiver = self.js_runtime.mod_evaluate(id);
     tokio::select! {
       biased;
@@ -714,6 +758,18 @@ impl WebWorker {
     poll_fn(|cx| self.poll_event_loop(cx, wait_for_inspector)).await
   }
 
+  fn wait_for_inspector_session(&mut self) {
+    if self.should_break_on_first_statement {
+      self
+        .js_runtime
+        .inspector()
+        .borrow_mut()
+        .wait_for_session_and_break_on_next_statement();
+    } else if self.should_wait_for_inspector_session {
+      self.js_runtime.inspector().borrow_mut().wait_for_session();
+    }
+  }
+
   // Starts polling for messages from worker host from JavaScript.
   fn start_polling_for_messages(&mut self) {
     let poll_for_messages_fn = self.poll_for_messages_fn.take().unwrap();
@@ -781,6 +837,7 @@ pub fn run_web_worker(
 
     // Execute provided source code immediately
     let result = if let Some(source_code) = maybe_source_code.take() {
+      worker.wait_for_inspector_session();
       let r = worker.execute_script(located_script_name!(), source_code.into());
       worker.start_polling_for_messages();
       r
@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::atomic::AtomicI32;
@@ -30,7 +32,7 @@ use deno_core::RuntimeOptions;
 use deno_core::SharedArrayBufferStore;
 use deno_core::Snapshot;
 use deno_core::SourceMapGetter;
-use deno_fs::StdFs;
+use deno_fs::OverlayFs;
 use deno_io::Stdio;
 use deno_kv::sqlite::SqliteDbHandler;
 use deno_node::RequireNpmResolver;
@@ -84,7 +86,17 @@ pub struct WorkerOptions {
 
   /// V8 snapshot that should be loaded on startup.
   pub startup_snapshot: Option<Snapshot>,
+
+  /// Creates the isolate so that a snapshot of it can be taken with
+  /// [MainWorker::snapshot]. The worker should not be bootstrapped then.
+  pub will_snapshot: bool,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  /// The addresses that hostnames are resolved to by `fetch()` instead of
+  /// with DNS.
+  pub pinned_hosts: HashMap<String, Vec<IpAddr>>,
+  /// Read-only files overlaid on the file system, which are also read by
+  /// `fetch()` for `file:` URLs.
+  pub fs_overlay: Option<Arc<dyn deno_fs::FsOverlay>>,
   pub root_cert_store: Option<RootCertStore>,
   pub seed: Option<u64>,
 
@@ -152,6 +164,8 @@ impl Default for WorkerOptions {
       module_loader: Rc::new(FsModuleLoader),
       seed: None,
       unsafely_ignore_certificate_errors: Default::default(),
+      pinned_hosts: Default::default(),
+      fs_overlay: None,
       should_break_on_first_statement: Default::default(),
       should_wait_for_inspector_session: Default::default(),
       compiled_wasm_module_store: Default::default(),
@@ -168,6 +182,7 @@ impl Default for WorkerOptions {
       blob_store: Default::default(),
       extensions: Default::default(),
       startup_snapshot: Default::default(),
+      will_snapshot: false,
       bootstrap: Default::default(),
       stdio: Default::default(),
     }
@@ -195,23 +210,32 @@ impl MainWorker {
       options = {
         permissions: PermissionsContainer,
         unstable: bool,
+        unstable_features: Vec<String>,
         enable_testing_features: bool,
       },
       state = |state, options| {
         state.put::<PermissionsContainer>(options.permissions);
-        state.put(ops::UnstableChecker { unstable: options.unstable });
+        state.put(ops::UnstableChecker {
+          unstable: options.unstable,
+          features: options.unstable_features,
+        });
         state.put(ops::TestingFeaturesEnabled(options.enable_testing_features));
       },
     );
 
     // Permissions: many ops depend on this
     let unstable = options.bootstrap.unstable;
+    let unstable_features = options.bootstrap.unstable_features.clone();
     let enable_testing_features = options.bootstrap.enable_testing_features;
     let exit_code = ExitCode(Arc::new(AtomicI32::new(0)));
     let create_cache = options.cache_storage_dir.map(|storage_dir| {
       let create_cache_fn = move || SqliteBackedCache::new(storage_dir.clone());
       CreateCache(Arc::new(create_cache_fn))
     });
+    // `require()` reads the files of the overlay as well
+    if let Some(overlay) = &options.fs_overlay {
+      crate::node_fs::set_overlay(overlay.clone());
+    }
 
     // NOTE(bartlomieju): ordering is important here, keep it in sync with
     // `runtime/build.rs`, `runtime/web_worker.rs` and `cli/build.rs`!
@@ -231,7 +255,11 @@ impl MainWorker {
           unsafely_ignore_certificate_errors: options
             .unsafely_ignore_certificate_errors
             .clone(),
-          file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          file_fetch_handler:
+            crate::fs_fetch_handler::create_file_fetch_handler(
+              options.fs_overlay.clone(),
+            ),
+          pinned_hosts: options.pinned_hosts.clone(),
           ..Default::default()
         },
       ),
@@ -240,6 +268,7 @@ impl MainWorker {
         options.bootstrap.user_agent.clone(),
         options.root_cert_store.clone(),
         options.unsafely_ignore_certificate_errors.clone(),
+        options.pinned_hosts.clone(),
       ),
       deno_webstorage::deno_webstorage::init_ops(
         options.origin_storage_dir.clone(),
@@ -247,25 +276,31 @@ impl MainWorker {
       deno_crypto::deno_crypto::init_ops(options.seed),
       deno_broadcast_channel::deno_broadcast_channel::init_ops(
         options.broadcast_channel.clone(),
-        unstable,
+        options.bootstrap.has_unstable_feature("broadcast-channel"),
+      ),
+      deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(
+        options.bootstrap.has_unstable_feature("ffi"),
       ),
-      deno_ffi::deno_ffi::init_ops::<PermissionsContainer>(unstable),
       deno_net::deno_net::init_ops::<PermissionsContainer>(
         options.root_cert_store.clone(),
-        unstable,
+        options.bootstrap.has_unstable_feature("net"),
         options.unsafely_ignore_certificate_errors.clone(),
+        options.pinned_hosts.clone(),
       ),
       deno_tls::deno_tls::init_ops(),
       deno_kv::deno_kv::init_ops(
         SqliteDbHandler::<PermissionsContainer>::new(
           options.origin_storage_dir.clone(),
         ),
-        unstable,
+        options.bootstrap.has_unstable_feature("kv"),
       ),
       deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
       deno_http::deno_http::init_ops(),
       deno_io::deno_io::init_ops(Some(options.stdio)),
-      deno_fs::deno_fs::init_ops::<_, PermissionsContainer>(unstable, StdFs),
+      deno_fs::deno_fs::init_ops::<_, PermissionsContainer>(
+        options.bootstrap.has_unstable_feature("fs"),
+        OverlayFs::new(options.fs_overlay.clone()),
+      ),
       deno_node::deno_node::init_ops::<crate::RuntimeNodeEnv>(
         options.npm_resolver,
       ),
@@ -287,6 +322,7 @@ impl MainWorker {
       deno_permissions_worker::init_ops(
         permissions,
         unstable,
+        unstable_features,
         enable_testing_features,
       ),
     ];
@@ -304,6 +340,7 @@ impl MainWorker {
     let mut js_runtime = JsRuntime::new(RuntimeOptions {
       module_loader: Some(options.module_loader.clone()),
       startup_snapshot: Some(startup_snapshot),
+      will_snapshot: options.will_snapshot,
       source_map_getter: options.source_map_getter,
       get_error_class_fn: options.get_error_class_fn,
       shared_array_buffer_store: options.shared_array_buffer_store.clone(),
@@ -371,6 +408,14 @@ impl MainWorker {
       .unwrap();
   }
 
+  /// Takes a snapshot of the worker, which includes the modules it
+  /// evaluated. See [JsRuntime::snapshot](deno_core::JsRuntime::snapshot)
+  pub fn snapshot(mut self) -> v8::StartupData {
+    // the bootstrap function handle must be dropped before snapshotting
+    self.bootstrap_fn_global.take();
+    self.js_runtime.snapshot()
+  }
+
   /// See [JsRuntime::execute_script](deno_core::JsRuntime::execute_script)
   pub fn execute_script(
     &mut self,
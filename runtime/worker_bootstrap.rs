@@ -25,6 +25,8 @@ pub struct BootstrapOptions {
   /// Sets `Deno.version.typescript` in JS runtime.
   pub ts_version: String,
   pub unstable: bool,
+  /// The unstable features enabled with their `--unstable-<name>` flag.
+  pub unstable_features: Vec<String>,
   pub user_agent: String,
   pub inspect: bool,
 }
@@ -50,6 +52,7 @@ impl Default for BootstrapOptions {
       locale: "en".to_string(),
       location: Default::default(),
       unstable: Default::default(),
+      unstable_features: Default::default(),
       inspect: Default::default(),
       args: Default::default(),
     }
@@ -57,11 +60,17 @@ impl Default for BootstrapOptions {
 }
 
 impl BootstrapOptions {
+  /// Gets if an unstable feature is enabled, either with `--unstable` or
+  /// with its own `--unstable-<name>` flag.
+  pub fn has_unstable_feature(&self, name: &str) -> bool {
+    self.unstable || self.unstable_features.iter().any(|f| f == name)
+  }
+
   pub fn as_v8<'s>(
     &self,
     scope: &mut v8::HandleScope<'s>,
   ) -> v8::Local<'s, v8::Array> {
-    let array = v8::Array::new(scope, 17);
+    let array = v8::Array::new(scope, 18);
 
     {
       let args = v8::Array::new(scope, self.args.len() as i32);
@@ -186,6 +195,15 @@ impl BootstrapOptions {
       array.set_index(scope, 16, val.into());
     }
 
+    {
+      let features = v8::Array::new(scope, self.unstable_features.len() as i32);
+      for (idx, feature) in self.unstable_features.iter().enumerate() {
+        let feature_str = v8::String::new(scope, feature).unwrap();
+        features.set_index(scope, idx as u32, feature_str.into());
+      }
+      array.set_index(scope, 17, features.into());
+    }
+
     array
   }
 }
@@ -15,11 +15,12 @@ const [libPrefix, libSuffix] = {
   windows: ["", "dll"],
 }[Deno.build.os];
 
-export function loadTestLibrary() {
-  const specifier = `${targetDir}/${libPrefix}test_napi.${libSuffix}`;
+export const testLibraryPath =
+  `${targetDir}/${libPrefix}test_napi.${libSuffix}`;
 
+export function loadTestLibrary() {
   // Internal, used in ext/node
-  return Deno[Deno.internal].core.ops.op_napi_open(specifier, {
+  return Deno[Deno.internal].core.ops.op_napi_open(testLibraryPath, {
     Buffer: {},
   });
 }
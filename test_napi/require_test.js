@@ -0,0 +1,22 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+import { assertEquals, testLibraryPath } from "./common.js";
+import { createRequire } from "node:module";
+
+const require = createRequire(import.meta.url);
+
+Deno.test("napi require .node addon", function () {
+  const dir = Deno.makeTempDirSync();
+  const addonPath = `${dir}/test_napi.node`;
+  Deno.copyFileSync(testLibraryPath, addonPath);
+
+  const addon = require(addonPath);
+  assertEquals(addon.test_int32(69), 69);
+  // the module is cached like any other required module
+  assertEquals(require(addonPath), addon);
+
+  // a loaded library can't be removed on Windows
+  if (Deno.build.os !== "windows") {
+    Deno.removeSync(dir, { recursive: true });
+  }
+});
@@ -29,6 +29,7 @@ fn napi_tests() {
     .arg("test")
     .arg("--allow-read")
     .arg("--allow-env")
+    .arg("--allow-write")
     .arg("--allow-ffi")
     .arg("--allow-run")
     .spawn()
@@ -44,3 +45,56 @@ fn napi_tests() {
   }
   assert!(output.status.success());
 }
+
+#[test]
+fn napi_import_native_addon() {
+  build();
+
+  let library_name = if cfg!(windows) {
+    "test_napi.dll"
+  } else if cfg!(target_os = "macos") {
+    "libtest_napi.dylib"
+  } else {
+    "libtest_napi.so"
+  };
+  let library_path = test_util::deno_exe_path()
+    .parent()
+    .unwrap()
+    .join(library_name);
+  let temp_dir = test_util::TempDir::new();
+  temp_dir.create_dir_all("node_modules/addon");
+  std::fs::copy(
+    library_path,
+    temp_dir.path().join("node_modules/addon/test_napi.node"),
+  )
+  .unwrap();
+  // Node-API addons imported from npm packages are loaded with `require`
+  temp_dir.write(
+    "main.js",
+    "import addon from './node_modules/addon/test_napi.node';\nconsole.log(addon.test_int32(69));\n",
+  );
+
+  let output = deno_cmd()
+    .current_dir(temp_dir.path())
+    .arg("run")
+    .arg("--node-modules-dir")
+    .arg("--allow-read")
+    .arg("--allow-env")
+    .arg("--allow-ffi")
+    .arg("main.js")
+    .stdout(std::process::Stdio::piped())
+    .stderr(std::process::Stdio::piped())
+    .spawn()
+    .unwrap()
+    .wait_with_output()
+    .unwrap();
+  let stdout = std::str::from_utf8(&output.stdout).unwrap();
+  let stderr = std::str::from_utf8(&output.stderr).unwrap();
+
+  if !output.status.success() {
+    println!("stdout {stdout}");
+    println!("stderr {stderr}");
+  }
+  assert!(output.status.success());
+  assert_eq!(stdout, "69\n");
+}
@@ -1045,13 +1045,110 @@ async fn main_server(
         return Ok(file_resp);
       }
 
+      // store the artifacts of the remote build cache in memory, for the
+      // requests that have the token of the tests
+      if let Some(key) = req.uri().path().strip_prefix("/remote_cache/") {
+        static REMOTE_CACHE_ARTIFACTS: Lazy<Mutex<HashMap<String, Vec<u8>>>> =
+          Lazy::new(Default::default);
+        let is_authorized = req
+          .headers()
+          .get("authorization")
+          .map(|value| value == "Bearer remote-cache-token")
+          .unwrap_or(false);
+        if !is_authorized {
+          return Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty());
+        }
+        let key = key.to_string();
+        if req.method() == hyper::Method::PUT {
+          let bytes = hyper::body::to_bytes(req.into_body()).await.unwrap();
+          REMOTE_CACHE_ARTIFACTS
+            .lock()
+            .unwrap()
+            .insert(key, bytes.to_vec());
+          return Response::builder()
+            .status(StatusCode::CREATED)
+            .body(Body::empty());
+        }
+        let maybe_bytes =
+          REMOTE_CACHE_ARTIFACTS.lock().unwrap().get(&key).cloned();
+        return match maybe_bytes {
+          Some(bytes) => Response::builder().body(bytes.into()),
+          None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+        };
+      }
+
+      // fail the first two requests for each path with a transient error
+      // before serving a module, which is used to test retries
+      if let Some(name) = req.uri().path().strip_prefix("/flaky/") {
+        static FLAKY_REQUEST_COUNTS: Lazy<Mutex<HashMap<String, usize>>> =
+          Lazy::new(Default::default);
+        let count = {
+          let mut counts = FLAKY_REQUEST_COUNTS.lock().unwrap();
+          let count = counts.entry(name.to_string()).or_default();
+          *count += 1;
+          *count
+        };
+        if count <= 2 {
+          return Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Body::empty());
+        }
+        return Response::builder()
+          .header("content-type", "application/javascript")
+          .body(format!("export default {count};").into());
+      }
+
+      // serve the @denotest packages from a registry that requires
+      // authentication like a private registry
+      if let Some(suffix) = req
+        .uri()
+        .path()
+        .strip_prefix("/npm/private_registry/@denotest/")
+      {
+        let expected_auth = format!("Bearer {TEST_AUTH_TOKEN}");
+        let is_authorized = req
+          .headers()
+          .get("authorization")
+          .and_then(|v| v.to_str().ok())
+          == Some(expected_auth.as_str());
+        if !is_authorized {
+          return Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .body(Body::empty());
+        }
+        if let Ok(Some(response)) =
+          handle_custom_npm_registry_path("@denotest/", suffix)
+        {
+          return Ok(response);
+        }
+      }
+
       // serve npm registry files
       if let Some(suffix) =
         req.uri().path().strip_prefix("/npm/registry/@denotest/")
       {
         // serve all requests to /npm/registry/@deno using the file system
         // at that path
-        match handle_custom_npm_registry_path(suffix) {
+        match handle_custom_npm_registry_path("@denotest/", suffix) {
+          Ok(Some(response)) => return Ok(response),
+          Ok(None) => {} // ignore, not found
+          Err(err) => {
+            return Response::builder()
+              .status(StatusCode::INTERNAL_SERVER_ERROR)
+              .body(format!("{err:#}").into());
+          }
+        }
+      } else if let Some(suffix) = req
+        .uri()
+        .path()
+        .strip_prefix("/npm/registry/@types/denotest__")
+      {
+        // serve the types packages of the @denotest packages the same way
+        match handle_custom_npm_registry_path("@types/denotest__", suffix) {
           Ok(Some(response)) => return Ok(response),
           Ok(None) => {} // ignore, not found
           Err(err) => {
@@ -1108,6 +1205,7 @@ async fn main_server(
 }
 
 fn handle_custom_npm_registry_path(
+  name_prefix: &str,
   path: &str,
 ) -> Result<Option<Response<Body>>, anyhow::Error> {
   let parts = path
@@ -1115,7 +1213,7 @@ fn handle_custom_npm_registry_path(
     .filter(|p| !p.is_empty())
     .collect::<Vec<_>>();
   let cache = &CUSTOM_NPM_PACKAGE_CACHE;
-  let package_name = format!("@denotest/{}", parts[0]);
+  let package_name = format!("{}{}", name_prefix, parts[0]);
   if parts.len() == 2 {
     if let Some(file_bytes) =
       cache.tarball_bytes(&package_name, parts[1].trim_end_matches(".tgz"))?
@@ -1607,6 +1705,15 @@ fn custom_headers(p: &str, body: Vec<u8>) -> Response<Body> {
     return response;
   }
 
+  // advertise a pre-bundle of the module graph, which can't be parsed so
+  // that the modules are fetched one by one
+  if p.ends_with("/run/eszip_prebundle/mod.ts") {
+    response.headers_mut().insert(
+      "x-deno-eszip",
+      HeaderValue::from_static("./prebundle.eszip"),
+    );
+  }
+
   let content_type = if p.contains(".t1.") {
     Some("text/typescript")
   } else if p.contains(".t2.") {
@@ -178,12 +178,14 @@ impl LspStdoutReader {
 
 pub struct InitializeParamsBuilder {
   params: InitializeParams,
+  pull_diagnostics: bool,
 }
 
 impl InitializeParamsBuilder {
   #[allow(clippy::new_without_default)]
   pub fn new() -> Self {
     Self {
+      pull_diagnostics: false,
       params: InitializeParams {
         process_id: None,
         client_info: Some(ClientInfo {
@@ -430,8 +432,23 @@ impl InitializeParamsBuilder {
     options.as_object_mut().unwrap()
   }
 
-  pub fn build(&self) -> InitializeParams {
-    self.params.clone()
+  /// Adds the `textDocument.diagnostic` capability, so that the client
+  /// pulls diagnostics instead of the server publishing them.
+  pub fn enable_pull_diagnostics(&mut self) -> &mut Self {
+    self.pull_diagnostics = true;
+    self
+  }
+
+  pub fn build(&self) -> Value {
+    let mut params = to_value(&self.params).unwrap();
+    if self.pull_diagnostics {
+      // lsp_types 0.93 does not have this capability
+      params["capabilities"]["textDocument"]["diagnostic"] = json!({
+        "dynamicRegistration": false,
+        "relatedDocumentSupport": false,
+      });
+    }
+    params
   }
 }
 
@@ -634,6 +651,17 @@ impl LspClient {
     CollectedDiagnostics(all_diagnostics)
   }
 
+  /// Reads the requests to pull diagnostics again, which a client pulling
+  /// diagnostics is sent instead of the published diagnostics.
+  pub fn read_diagnostics_refresh(&mut self) {
+    // they come in batches of three like the published diagnostics
+    for _ in 0..3 {
+      let (id, method, _) = self.read_request::<Value>();
+      assert_eq!(method, "workspace/diagnostic/refresh");
+      self.write_response(id, json!(null));
+    }
+  }
+
   pub fn shutdown(&mut self) {
     self.write_request("shutdown", json!(null));
     self.write_notification("exit", json!(null));
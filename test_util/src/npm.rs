@@ -22,7 +22,8 @@ struct CustomNpmPackage {
 }
 
 /// Creates tarballs and a registry json file for npm packages
-/// in the `testdata/npm/registry/@denotest` directory.
+/// in the `testdata/npm/registry/@denotest` directory and for their
+/// `@types/denotest__*` packages.
 #[derive(Default)]
 pub struct CustomNpmPackageCache(Mutex<HashMap<String, CustomNpmPackage>>);
 